@@ -1,5 +1,8 @@
 use std::env;
+use std::fmt::Display;
+use std::path::PathBuf;
 use std::process;
+use std::str::FromStr;
 
 use hive_router_plan_executor::introspection::schema::SchemaWithMetadata;
 use hive_router_plan_executor::projection::plan::FieldProjectionPlan;
@@ -9,19 +12,23 @@ use hive_router_query_planner::consumer_schema::ConsumerSchema;
 use hive_router_query_planner::graph::Graph;
 use hive_router_query_planner::graph::PlannerOverrideContext;
 use hive_router_query_planner::planner::best::find_best_combination;
+use hive_router_query_planner::planner::contextual_arguments::apply_contextual_arguments;
 use hive_router_query_planner::planner::fetch::fetch_graph::build_fetch_graph_from_query_tree;
 use hive_router_query_planner::planner::fetch::fetch_graph::FetchGraph;
 use hive_router_query_planner::planner::fetch::state::MultiTypeFetchStep;
-use hive_router_query_planner::planner::plan_nodes::QueryPlan;
+use hive_router_query_planner::planner::plan_format::{DiagramFormat, QueryPlanFormat};
+use hive_router_query_planner::planner::plan_nodes::{ContextArgument, QueryPlan};
 use hive_router_query_planner::planner::query_plan::build_query_plan_from_fetch_graph;
 use hive_router_query_planner::planner::tree::query_tree::QueryTree;
 use hive_router_query_planner::planner::walker::walk_operation;
+use hive_router_query_planner::planner::Planner;
 use hive_router_query_planner::planner::QueryPlannerOptions;
 use hive_router_query_planner::state::supergraph_state::OperationKind;
 use hive_router_query_planner::state::supergraph_state::SupergraphState;
 use hive_router_query_planner::utils::cancellation::CancellationToken;
 use hive_router_query_planner::utils::parsing::parse_operation;
 use hive_router_query_planner::utils::parsing::parse_schema;
+use hive_router_query_planner::utils::parsing::safe_parse_operation;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 fn main() {
@@ -60,7 +67,7 @@ fn main() {
             println!("{}", graph);
         }
         "paths" => {
-            let (graph, operation, supergraph_state) = load_graph_operation(&args[2], &args[3]);
+            let (graph, operation, _, supergraph_state) = load_graph_operation(&args[2], &args[3]);
             let override_context = PlannerOverrideContext::default();
             let cancellation_token = CancellationToken::new();
             let best_paths_per_leaf = walk_operation(
@@ -95,10 +102,58 @@ fn main() {
         }
         "plan" => {
             let plan = process_plan(&args[2], &args[3]);
-            if args.contains(&"--json".into()) {
-                println!("{}", serde_json::to_string_pretty(&plan).unwrap());
-            } else {
-                println!("{}", plan);
+            let format = match parse_flag::<QueryPlanFormat>(&args, "--format") {
+                Some(format) => format,
+                // `--json` is kept as a shorthand of `--format json`
+                None if args.contains(&"--json".into()) => QueryPlanFormat::Json,
+                None => QueryPlanFormat::Text,
+            };
+
+            match format {
+                QueryPlanFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&plan).unwrap())
+                }
+                QueryPlanFormat::Apollo => {
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&plan.to_apollo()).unwrap()
+                    )
+                }
+                QueryPlanFormat::Text => println!("{}", plan),
+                QueryPlanFormat::Mermaid => println!("{}", plan.to_mermaid()),
+            }
+        }
+        "viz" => {
+            let format =
+                parse_flag::<DiagramFormat>(&args, "--format").unwrap_or(DiagramFormat::Mermaid);
+
+            match parse_flag::<String>(&args, "--target").as_deref() {
+                None | Some("fetch_graph") => {
+                    let fetch_graph = process_fetch_graph(&args[2], &args[3]);
+                    print!("{}", fetch_graph.to_diagram(format));
+                }
+                Some("plan") => {
+                    let plan = process_plan(&args[2], &args[3]);
+                    print!("{}", plan.to_diagram(format));
+                }
+                Some(other) => {
+                    eprintln!(
+                        "unknown viz target '{}', expected one of: fetch_graph, plan",
+                        other
+                    );
+                    process::exit(1);
+                }
+            }
+        }
+        "plan-diff" => {
+            if args.len() < 5 {
+                eprintln!("Usage: query-planner plan-diff <old_supergraph_path> <new_supergraph_path> <operations_dir>");
+                process::exit(1);
+            }
+
+            let changed = process_plan_diff(&args[2], &args[3], &args[4]);
+            if changed > 0 {
+                process::exit(1);
             }
         }
         "normalize" => {
@@ -135,12 +190,111 @@ fn main() {
             }
         }
         _ => {
-            eprintln!("Unknown command. Available commands: consumer_graph, graph, paths, tree, fetch_graph, plan");
+            eprintln!("Unknown command. Available commands: consumer_graph, graph, paths, tree, fetch_graph, plan, viz, plan-diff");
             process::exit(1);
         }
     };
 }
 
+/// Parses the value following `flag` in the arguments, exiting when it is missing or invalid.
+fn parse_flag<T: FromStr>(args: &[String], flag: &str) -> Option<T>
+where
+    T::Err: Display,
+{
+    let index = args.iter().position(|arg| arg == flag)?;
+    let value = args.get(index + 1).unwrap_or_else(|| {
+        eprintln!("missing value of {}", flag);
+        process::exit(1);
+    });
+
+    Some(value.parse::<T>().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    }))
+}
+
+/// Plans every operation of the directory against both supergraphs,
+/// and prints the operations with a different plan. Returns the number of them.
+fn process_plan_diff(old_supergraph_path: &str, new_supergraph_path: &str, dir: &str) -> usize {
+    let old_planner = load_planner(old_supergraph_path);
+    let new_planner = load_planner(new_supergraph_path);
+
+    let mut operation_paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .expect("Unable to read operations directory")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "graphql" || extension == "gql")
+        })
+        .collect();
+    operation_paths.sort();
+
+    let mut changed = 0;
+    for operation_path in &operation_paths {
+        let document_text =
+            std::fs::read_to_string(operation_path).expect("Unable to read input file");
+        let old_plan = plan_with_planner(&old_planner, &document_text);
+        let new_plan = plan_with_planner(&new_planner, &document_text);
+        let name = operation_path.display();
+
+        match (old_plan, new_plan) {
+            (Ok(old_plan), Ok(new_plan)) => {
+                if old_plan.to_string() == new_plan.to_string() {
+                    continue;
+                }
+
+                let (old_stats, new_stats) = (old_plan.stats(), new_plan.stats());
+                if old_stats == new_stats {
+                    println!("{name}: plan changed, same shape ({new_stats})");
+                } else {
+                    println!("{name}: plan changed");
+                    println!("  - {old_stats}");
+                    println!("  + {new_stats}");
+                }
+            }
+            (Ok(_), Err(err)) => {
+                println!("{name}: fails to plan against the new supergraph: {err}")
+            }
+            (Err(err), Ok(_)) => {
+                println!("{name}: fails to plan against the old supergraph: {err}")
+            }
+            // an operation both supergraphs can't plan is not a change
+            (Err(_), Err(_)) => continue,
+        }
+        changed += 1;
+    }
+
+    println!(
+        "{} of {} operation(s) have a different plan",
+        changed,
+        operation_paths.len()
+    );
+
+    changed
+}
+
+fn load_planner(supergraph_path: &str) -> Planner {
+    let supergraph_sdl =
+        std::fs::read_to_string(supergraph_path).expect("Unable to read input file");
+    let parsed_schema = parse_schema(&supergraph_sdl);
+    Planner::new_from_supergraph(&parsed_schema, QueryPlannerOptions::default())
+        .expect("failed to create planner")
+}
+
+fn plan_with_planner(planner: &Planner, document_text: &str) -> Result<QueryPlan, String> {
+    let parsed_document = safe_parse_operation(document_text).map_err(|e| e.to_string())?;
+    let document = normalize_operation(&planner.supergraph, &parsed_document, None)
+        .map_err(|e| e.to_string())?;
+
+    planner
+        .plan_from_normalized_operation(
+            document.executable_operation(),
+            PlannerOverrideContext::default(),
+            &CancellationToken::new(),
+        )
+        .map_err(|e| e.to_string())
+}
+
 fn process_consumer_schema(path: &str) {
     let supergraph_sdl = std::fs::read_to_string(path).expect("Unable to read input file");
     let parsed_schema = parse_schema(&supergraph_sdl);
@@ -153,7 +307,7 @@ fn process_fetch_graph(
     supergraph_path: &str,
     operation_path: &str,
 ) -> FetchGraph<MultiTypeFetchStep> {
-    let (graph, query_tree, supergraph_state, operation_kind) =
+    let (graph, query_tree, supergraph_state, operation_kind, context_arguments) =
         process_merged_tree(supergraph_path, operation_path);
 
     let override_context = PlannerOverrideContext::default();
@@ -164,6 +318,7 @@ fn process_fetch_graph(
         &override_context,
         query_tree,
         operation_kind,
+        &context_arguments,
         &QueryPlannerOptions::default(),
         &cancellation_token,
     )
@@ -171,7 +326,8 @@ fn process_fetch_graph(
 }
 
 fn process_plan(supergraph_path: &str, operation_path: &str) -> QueryPlan {
-    let (graph, operation, supergraph) = load_graph_operation(supergraph_path, operation_path);
+    let (graph, operation, context_arguments, supergraph) =
+        load_graph_operation(supergraph_path, operation_path);
     let override_context = PlannerOverrideContext::default();
     let cancellation_token = CancellationToken::new();
 
@@ -194,6 +350,7 @@ fn process_plan(supergraph_path: &str, operation_path: &str) -> QueryPlan {
             .operation_kind
             .clone()
             .unwrap_or(OperationKind::Query),
+        &context_arguments,
         &QueryPlannerOptions::default(),
         &cancellation_token,
     )
@@ -206,8 +363,14 @@ fn process_plan(supergraph_path: &str, operation_path: &str) -> QueryPlan {
 fn process_merged_tree(
     supergraph_path: &str,
     operation_path: &str,
-) -> (Graph, QueryTree, SupergraphState, OperationKind) {
-    let (graph, operation, supergraph_state) =
+) -> (
+    Graph,
+    QueryTree,
+    SupergraphState,
+    OperationKind,
+    Vec<ContextArgument>,
+) {
+    let (graph, operation, context_arguments, supergraph_state) =
         load_graph_operation(supergraph_path, operation_path);
     let override_context = PlannerOverrideContext::default();
     let cancellation_token = CancellationToken::new();
@@ -230,28 +393,38 @@ fn process_merged_tree(
             .operation_kind
             .clone()
             .unwrap_or(OperationKind::Query),
+        context_arguments,
     )
 }
 
-fn get_operation(operation_path: &str, supergraph: &SupergraphState) -> OperationDefinition {
+fn get_operation(
+    operation_path: &str,
+    supergraph: &SupergraphState,
+) -> (OperationDefinition, Vec<ContextArgument>) {
     let document_text = std::fs::read_to_string(operation_path).expect("Unable to read input file");
     let parsed_document = parse_operation(&document_text);
     let document = normalize_operation(supergraph, &parsed_document, None).unwrap();
     let operation = document.executable_operation();
 
-    operation.clone()
+    apply_contextual_arguments(supergraph, operation)
+        .unwrap_or_else(|| (operation.clone(), Vec::new()))
 }
 
 fn load_graph_operation(
     supergraph_path: &str,
     operation_path: &str,
-) -> (Graph, OperationDefinition, SupergraphState) {
+) -> (
+    Graph,
+    OperationDefinition,
+    Vec<ContextArgument>,
+    SupergraphState,
+) {
     let supergraph_sdl =
         std::fs::read_to_string(supergraph_path).expect("Unable to read input file");
     let parsed_schema = parse_schema(&supergraph_sdl);
     let supergraph = SupergraphState::new(&parsed_schema);
     let graph = Graph::graph_from_supergraph_state(&supergraph).expect("failed to create graph");
-    let operation = get_operation(operation_path, &supergraph);
+    let (operation, context_arguments) = get_operation(operation_path, &supergraph);
 
-    (graph, operation, supergraph)
+    (graph, operation, context_arguments, supergraph)
 }
@@ -59,6 +59,7 @@ fn authorization_benchmark(c: &mut Criterion) {
         let hashes = hash_normalized_operation(
             &partitioned_operation.downstream_operation,
             partitioned_operation.introspection_operation.as_ref(),
+            None,
         );
 
         GraphQLNormalizationPayload {
@@ -77,6 +78,9 @@ fn authorization_benchmark(c: &mut Criterion) {
             operation_for_plan_hash: hashes.operation_for_plan_hash,
             operation_for_introspection_hash: hashes.operation_for_introspection_hash,
             normalized_operation_hash: hashes.combined_operation_hash,
+            deferred_operation: None,
+            deferred_projection_plan: None,
+            streamed_fields: Default::default(),
         }
     }
 
@@ -85,9 +89,7 @@ fn authorization_benchmark(c: &mut Criterion) {
         &metadata,
         "query { topProducts(first: 1) { name price } }",
     );
-    let bubble_up_variable_payload = CoerceVariablesPayload {
-        variables_map: None,
-    };
+    let bubble_up_variable_payload = CoerceVariablesPayload::default();
     let bubble_up = BenchEnv {
         normalized_payload: &bubble_up_payload,
         auth_metadata: &authorization,
@@ -118,9 +120,7 @@ fn authorization_benchmark(c: &mut Criterion) {
         &metadata,
         "query { topProducts { name shippingEstimate reviews { body } } me { name birthday } }",
     );
-    let complex_variable_payload = CoerceVariablesPayload {
-        variables_map: None,
-    };
+    let complex_variable_payload = CoerceVariablesPayload::default();
     let complex = BenchEnv {
         normalized_payload: &complex_payload,
         auth_metadata: &authorization,
@@ -151,9 +151,7 @@ fn authorization_benchmark(c: &mut Criterion) {
         &metadata,
         "query { topProducts { name shippingEstimate reviews { body } } me { name birthday } }",
     );
-    let complex_partially_variable_payload = CoerceVariablesPayload {
-        variables_map: None,
-    };
+    let complex_partially_variable_payload = CoerceVariablesPayload::default();
     let complex_partially = BenchEnv {
         normalized_payload: &complex_partially_payload,
         auth_metadata: &authorization,
@@ -231,9 +229,7 @@ fn authorization_benchmark(c: &mut Criterion) {
             }
         }"#,
     );
-    let large_mostly_auth_var_payload = CoerceVariablesPayload {
-        variables_map: None,
-    };
+    let large_mostly_auth_var_payload = CoerceVariablesPayload::default();
     let large_mostly_auth = BenchEnv {
         normalized_payload: &large_mostly_auth_payload,
         auth_metadata: &authorization,
@@ -305,9 +301,7 @@ fn authorization_benchmark(c: &mut Criterion) {
             }
         }"#,
     );
-    let large_partially_denied_var_payload = CoerceVariablesPayload {
-        variables_map: None,
-    };
+    let large_partially_denied_var_payload = CoerceVariablesPayload::default();
     let large_partially_denied = BenchEnv {
         normalized_payload: &large_partially_denied_payload,
         auth_metadata: &authorization,
@@ -388,9 +382,7 @@ fn authorization_benchmark(c: &mut Criterion) {
             }
         }"#,
     );
-    let deep_nested_var_payload = CoerceVariablesPayload {
-        variables_map: None,
-    };
+    let deep_nested_var_payload = CoerceVariablesPayload::default();
     let deep_nested = BenchEnv {
         normalized_payload: &deep_nested_payload,
         auth_metadata: &authorization,
@@ -451,9 +443,7 @@ fn authorization_benchmark(c: &mut Criterion) {
             }
         }"#,
     );
-    let large_unauth_var_payload = CoerceVariablesPayload {
-        variables_map: None,
-    };
+    let large_unauth_var_payload = CoerceVariablesPayload::default();
     let large_unauth = BenchEnv {
         normalized_payload: &large_unauth_payload,
         auth_metadata: &authorization,
@@ -498,9 +488,7 @@ fn authorization_benchmark(c: &mut Criterion) {
             }
         }"#,
     );
-    let interface_auth_inline_unauth_var_payload = CoerceVariablesPayload {
-        variables_map: None,
-    };
+    let interface_auth_inline_unauth_var_payload = CoerceVariablesPayload::default();
     let interface_auth_inline_unauth = BenchEnv {
         normalized_payload: &interface_auth_inline_unauth_payload,
         auth_metadata: &authorization,
@@ -546,9 +534,7 @@ fn authorization_benchmark(c: &mut Criterion) {
             }
         }"#,
     );
-    let interface_auth_inline_auth_var_payload = CoerceVariablesPayload {
-        variables_map: None,
-    };
+    let interface_auth_inline_auth_var_payload = CoerceVariablesPayload::default();
     let interface_auth_inline_auth = BenchEnv {
         normalized_payload: &interface_auth_inline_auth_payload,
         auth_metadata: &authorization,
@@ -86,9 +86,20 @@ pub fn register_cache_size_observers(
         total
     });
 
+    let plan_schema_state = Arc::clone(&schema_state);
     metrics.plan.observe_size_with(move || {
         let mut total = 0;
-        schema_state.for_each_runtime(|runtime| total += runtime.plan_cache.entry_count());
+        plan_schema_state.for_each_runtime(|runtime| total += runtime.plan_cache.entry_count());
+        total
+    });
+
+    metrics.entity.observe_size_with(move || {
+        let mut total = 0;
+        schema_state.for_each_runtime(|runtime| {
+            if let Some(entity_cache) = &runtime.entity_cache {
+                total += entity_cache.entry_count();
+            }
+        });
         total
     });
 }
@@ -0,0 +1,268 @@
+use std::{path::PathBuf, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use hive_router_config::{
+    hot_reload::{changed_fields, is_reloadable_field},
+    load_config, HiveRouterConfig, RouterConfigError,
+};
+use hive_router_internal::background_tasks::{BackgroundTask, CancellationToken};
+use hive_router_plan_executor::hooks::on_config_reload::OnConfigReloadHookPayload;
+use notify::EventKind;
+use tracing::{error, info, warn};
+use tracing_subscriber::EnvFilter;
+
+use crate::{
+    file_watcher::DebouncedFileWatcher,
+    schema_state::{
+        RouterSupergraphRuntimeError, SchemaState, SupergraphPushError, SupergraphRefreshOutcome,
+    },
+    shared_state::{ReloadableState, RouterSharedState, SharedStateError},
+    telemetry::reload_log_filter,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigReloadError {
+    #[error(transparent)]
+    Load(#[from] RouterConfigError),
+    #[error("failed to compare the configs: {0}")]
+    Compare(#[from] serde_json::Error),
+    #[error(
+        "the changed fields can't be applied without restarting the router: {}",
+        .0.join(", ")
+    )]
+    NotReloadable(Vec<String>),
+    #[error("invalid log filter: {0}")]
+    LogFilter(#[from] tracing_subscriber::filter::ParseError),
+    #[error(transparent)]
+    SharedState(#[from] SharedStateError),
+    #[error("failed to rebuild the subgraph executors: {0}")]
+    SupergraphRuntime(#[from] RouterSupergraphRuntimeError),
+}
+
+/// Applies the reloadable fields of the given config, as listed in [`hive_router_config::hot_reload`],
+/// and returns the fields it changed.
+/// The config is rejected as a whole when it changes any other field,
+/// the router keeps running with its current config.
+pub async fn reload_router_config(
+    shared_state: &RouterSharedState,
+    schema_state: &SchemaState,
+    new_config: HiveRouterConfig,
+) -> Result<Vec<String>, ConfigReloadError> {
+    let current = shared_state.reloadable.load_full();
+    let changed = changed_fields(&current.router_config, &new_config)?;
+    if changed.is_empty() {
+        return Ok(changed);
+    }
+
+    let not_reloadable: Vec<String> = changed
+        .iter()
+        .filter(|field| !is_reloadable_field(field))
+        .cloned()
+        .collect();
+    if !not_reloadable.is_empty() {
+        return Err(ConfigReloadError::NotReloadable(not_reloadable));
+    }
+
+    let changes = |section: &str| {
+        changed
+            .iter()
+            .any(|field| field.split('.').next() == Some(section))
+    };
+
+    // everything is built before anything is applied, for an invalid config to change nothing
+    let log_filter = changes("log")
+        .then(|| EnvFilter::from_str(new_config.log.env_filter_str()))
+        .transpose()?;
+    let new_config = Arc::new(new_config);
+    let reloadable = ReloadableState::from_config(new_config.clone())?;
+
+    // the subgraph executors are built with the traffic shaping of the subgraphs
+    if changed.iter().any(|field| {
+        field.starts_with("traffic_shaping.") && !field.starts_with("traffic_shaping.router.")
+    }) {
+        schema_state.apply_router_config(new_config.clone()).await?;
+    }
+
+    shared_state.reloadable.store(Arc::new(reloadable));
+    if changes("limits") {
+        // the parse and validation caches are keyed by the operation, not by the limits
+        // checked when their entries were computed
+        shared_state.parse_cache.invalidate_all();
+        schema_state.for_each_runtime(|runtime| runtime.validate_cache.invalidate_all());
+    }
+    if let Some(log_filter) = log_filter {
+        if let Err(err) = reload_log_filter(log_filter) {
+            warn!(error = %err, "failed to apply the reloaded log filter");
+        }
+    }
+
+    if let Some(plugins) = shared_state.plugins.as_ref() {
+        let payload = OnConfigReloadHookPayload {
+            router_config: &new_config,
+            changed_fields: &changed,
+        };
+        for plugin in plugins.iter() {
+            plugin.on_config_reload(&payload);
+        }
+    }
+
+    info!(changed_fields = ?changed, "reloaded the router config");
+    Ok(changed)
+}
+
+/// Reloads the router config when its file changes, when `hot_reload.watch` is enabled.
+pub struct ConfigWatcherTask {
+    config_path: PathBuf,
+    shared_state: Arc<RouterSharedState>,
+    schema_state: Arc<SchemaState>,
+    watcher: DebouncedFileWatcher,
+}
+
+impl ConfigWatcherTask {
+    pub fn new(
+        config_path: PathBuf,
+        shared_state: Arc<RouterSharedState>,
+        schema_state: Arc<SchemaState>,
+    ) -> Result<Self, notify::Error> {
+        // the watcher reports the absolute paths of the changed files
+        let config_path = std::path::absolute(&config_path).unwrap_or(config_path);
+        // Watch the parent directory so replace/rename save patterns are observed.
+        let watch_target = config_path
+            .parent()
+            .map(|parent| parent.to_path_buf())
+            .unwrap_or_else(|| config_path.clone());
+        let watched_path = config_path.clone();
+        let watcher = DebouncedFileWatcher::new([watch_target], move |result| match result {
+            Ok(event) => {
+                matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    && event.paths.iter().any(|path| path == &watched_path)
+            }
+            Err(err) => {
+                warn!("config file watcher event failed: {err}");
+                false
+            }
+        })?;
+
+        Ok(Self {
+            config_path,
+            shared_state,
+            schema_state,
+            watcher,
+        })
+    }
+
+    async fn reload(&self) {
+        if !self.watcher.take_change() {
+            return;
+        }
+
+        let new_config = match load_config(Some(self.config_path.to_string_lossy().into_owned())) {
+            Ok(new_config) => new_config,
+            Err(err) => {
+                error!(error = %err, "failed to load the changed config, keeping the current config");
+                return;
+            }
+        };
+
+        if let Err(err) =
+            reload_router_config(&self.shared_state, &self.schema_state, new_config).await
+        {
+            error!(error = %err, "failed to reload the config, keeping the current config");
+        }
+    }
+}
+
+#[async_trait]
+impl BackgroundTask for ConfigWatcherTask {
+    fn id(&self) -> &str {
+        "config-file-watcher"
+    }
+
+    async fn run(&self, token: CancellationToken) {
+        while self.watcher.changed(&token).await.is_some() {
+            self.reload().await;
+        }
+    }
+}
+
+/// Reloads the router config and refreshes the configured supergraph right away,
+/// regardless of its poll interval, when the router receives `SIGHUP`.
+#[cfg(unix)]
+pub struct SighupReloadTask {
+    pub config_path: Option<String>,
+    pub shared_state: Arc<RouterSharedState>,
+    pub schema_state: Arc<SchemaState>,
+}
+
+#[cfg(unix)]
+impl SighupReloadTask {
+    async fn reload(&self) {
+        info!("received SIGHUP, reloading the config and the supergraph");
+
+        let config = match load_config(self.config_path.clone()) {
+            Ok(new_config) => {
+                reload_router_config(&self.shared_state, &self.schema_state, new_config).await
+            }
+            Err(err) => Err(err.into()),
+        };
+        let supergraph = self.schema_state.refresh_supergraph().await;
+
+        let supergraph_failed = matches!(
+            supergraph,
+            Ok(SupergraphRefreshOutcome::Rejected(_) | SupergraphRefreshOutcome::LoadFailed(_))
+                | Err(SupergraphPushError::ReloadTaskUnavailable)
+        );
+        let supergraph = match supergraph {
+            Ok(outcome) => outcome.to_string(),
+            Err(err) => err.to_string(),
+        };
+        let (changed_fields, config_error) = match config {
+            Ok(changed_fields) => (changed_fields, None),
+            Err(err) => (Vec::new(), Some(err.to_string())),
+        };
+
+        if config_error.is_some() || supergraph_failed {
+            warn!(
+                config.changed_fields = ?changed_fields,
+                config.error = config_error.as_deref(),
+                supergraph = %supergraph,
+                "reloaded on SIGHUP, with errors"
+            );
+        } else {
+            info!(
+                config.changed_fields = ?changed_fields,
+                supergraph = %supergraph,
+                "reloaded on SIGHUP"
+            );
+        }
+    }
+}
+
+#[cfg(unix)]
+#[async_trait]
+impl BackgroundTask for SighupReloadTask {
+    fn id(&self) -> &str {
+        "sighup-reloader"
+    }
+
+    async fn run(&self, token: CancellationToken) {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(err) => {
+                warn!(error = %err, "failed to listen for SIGHUP, the router can't be reloaded with it");
+                return;
+            }
+        };
+
+        while token
+            .run_until_cancelled(hangups.recv())
+            .await
+            .flatten()
+            .is_some()
+        {
+            self.reload().await;
+        }
+    }
+}
@@ -1,10 +1,17 @@
 use hive_router_config::RouterConfigError;
 use hive_router_plan_executor::executors::error::TlsCertificatesError;
+use hive_router_plan_executor::response::error_status_codes::InvalidErrorStatusCode;
 
 use crate::{
-    jwt::jwks_manager::JwksSourceError, pipeline::usage_reporting::UsageReportingError,
-    plugins::registry::PluginRegistryError, schema_state::SupergraphManagerError,
-    shared_state::SharedStateError, storage::error::StorageError, telemetry::TelemetryInitError,
+    jwt::jwks_manager::JwksSourceError,
+    pipeline::{
+        apollo_usage_reporting::ApolloUsageReportingError, usage_reporting::UsageReportingError,
+    },
+    plugins::{plugin_storage_backend::PluginStorageConfigError, registry::PluginRegistryError},
+    schema_state::SupergraphManagerError,
+    shared_state::SharedStateError,
+    storage::error::StorageError,
+    telemetry::TelemetryInitError,
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -17,6 +24,12 @@ pub enum RouterInitError {
     HttpServerBindError(String, std::io::Error),
     #[error("Failed to bind HTTP callback server to address: {0}. Error: {1}")]
     HttpCallbackServerBindError(String, std::io::Error),
+    #[error("Failed to bind MCP server to address: {0}. Error: {1}")]
+    McpServerBindError(String, std::io::Error),
+    #[error("Failed to bind admin server to address: {0}. Error: {1}")]
+    AdminServerBindError(String, std::io::Error),
+    #[error("Failed to bind health probes server to address: {0}. Error: {1}")]
+    ProbesServerBindError(String, std::io::Error),
     #[error("Failed to start HTTP server: {0}")]
     HttpServerStartError(std::io::Error),
     #[error(transparent)]
@@ -24,11 +37,17 @@ pub enum RouterInitError {
     #[error("Usage Reporting - {0}")]
     UsageReportingError(#[from] UsageReportingError),
     #[error(transparent)]
+    ApolloUsageReportingError(#[from] ApolloUsageReportingError),
+    #[error(transparent)]
     SharedStateError(#[from] SharedStateError),
     #[error(transparent)]
     TelemetryInitError(#[from] TelemetryInitError),
     #[error(transparent)]
     PluginRegistryError(#[from] PluginRegistryError),
+    #[error(transparent)]
+    PluginStorageConfigError(#[from] PluginStorageConfigError),
+    #[error(transparent)]
+    InvalidErrorStatusCode(#[from] InvalidErrorStatusCode),
     #[error("Persisted documents endpoint incompatible: {0}")]
     PersistedDocumentsEndpointIncompatible(String),
     #[error("Endpoints of '{endpoint_name_one}' and '{endpoint_name_two}' cannot both use the same endpoint: {endpoint}")]
@@ -41,4 +60,6 @@ pub enum RouterInitError {
     TlsCertificatesError(#[from] TlsCertificatesError),
     #[error(transparent)]
     StorageError(#[from] StorageError),
+    #[error("the supergraph is not available: {0}")]
+    SupergraphUnavailable(String),
 }
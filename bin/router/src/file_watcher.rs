@@ -0,0 +1,81 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use hive_router_internal::background_tasks::CancellationToken;
+use notify::{Config as NotifyConfig, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::Notify;
+
+const CHANGE_EVENT_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Watches directories for changes of the files a background task reloads.
+///
+/// The events are debounced to reduce noisy save/update actions,
+/// like a file replaced by a rename, or a certificate and its key updated together.
+pub struct DebouncedFileWatcher {
+    // Signals a potential file change
+    dirty: Arc<AtomicBool>,
+    // Notification channel from watcher callback to background reload task.
+    change_signal: Arc<Notify>,
+    _watcher: RecommendedWatcher,
+}
+
+impl DebouncedFileWatcher {
+    /// Watches the given directories, non recursively,
+    /// and reports the events for which `is_change` returns true as changes.
+    pub fn new(
+        watch_targets: impl IntoIterator<Item = PathBuf>,
+        is_change: impl Fn(notify::Result<notify::Event>) -> bool + Send + 'static,
+    ) -> Result<Self, notify::Error> {
+        let dirty = Arc::new(AtomicBool::new(false));
+        let change_signal = Arc::new(Notify::new());
+
+        let mut watcher = RecommendedWatcher::new(
+            {
+                let dirty = dirty.clone();
+                let change_signal = change_signal.clone();
+                move |result: notify::Result<notify::Event>| {
+                    if is_change(result) {
+                        dirty.store(true, Ordering::Relaxed);
+                        change_signal.notify_one();
+                    }
+                }
+            },
+            NotifyConfig::default(),
+        )?;
+
+        let mut watch_targets: Vec<PathBuf> = watch_targets.into_iter().collect();
+        watch_targets.sort();
+        watch_targets.dedup();
+        for watch_target in watch_targets {
+            watcher.watch(&watch_target, RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(Self {
+            dirty,
+            change_signal,
+            _watcher: watcher,
+        })
+    }
+
+    /// Waits for a change, and for the events following it closely.
+    /// Returns `None` once the token is cancelled.
+    pub async fn changed(&self, token: &CancellationToken) -> Option<()> {
+        token
+            .run_until_cancelled(async {
+                self.change_signal.notified().await;
+                tokio::time::sleep(CHANGE_EVENT_DEBOUNCE).await;
+            })
+            .await
+    }
+
+    /// Returns true when the files changed since the last call.
+    pub fn take_change(&self) -> bool {
+        self.dirty.swap(false, Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use hive_router_config::primitives::value_or_expression::ValueOrExpression;
+use hive_router_internal::{http::read_body_stream, telemetry::utils::resolve_value_or_expression};
+use http::{header::CONTENT_TYPE, StatusCode};
+use ntex::{
+    http::ResponseBuilder,
+    web::{self, HttpRequest},
+};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::{
+    schema_state::{SchemaState, SupergraphPushError},
+    shared_state::RouterSharedState,
+    utils::secrets_match,
+};
+
+/// Supergraphs are usually much larger than GraphQL requests,
+/// so the endpoint does not share the limit of the GraphQL endpoint.
+const MAX_SUPERGRAPH_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AdminEndpointError {
+    #[error("invalid admin {0} config: {1}")]
+    Configuration(&'static str, String),
+}
+
+/// Authenticates the requests sent to an admin endpoint.
+pub struct AdminEndpointRuntime {
+    token_digest: [u8; 32],
+}
+
+impl AdminEndpointRuntime {
+    pub fn from_config(
+        endpoint: &'static str,
+        enabled: bool,
+        path: &str,
+        token: Option<&ValueOrExpression<String>>,
+    ) -> Result<Option<Self>, AdminEndpointError> {
+        if !enabled {
+            return Ok(None);
+        }
+
+        let token = token.ok_or_else(|| {
+            AdminEndpointError::Configuration(
+                endpoint,
+                "'token' is required when the endpoint is enabled".to_string(),
+            )
+        })?;
+        let token = resolve_value_or_expression(token, &format!("admin {endpoint} token"))
+            .map_err(|err| AdminEndpointError::Configuration(endpoint, err.to_string()))?;
+        if token.is_empty() {
+            return Err(AdminEndpointError::Configuration(
+                endpoint,
+                "'token' can't be empty".to_string(),
+            ));
+        }
+
+        info!("admin {} endpoint enabled on {}", endpoint, path);
+
+        Ok(Some(Self {
+            token_digest: Sha256::digest(token.as_bytes()).into(),
+        }))
+    }
+
+    fn is_authorized(&self, req: &HttpRequest) -> bool {
+        let Some(token) = req
+            .headers()
+            .get(http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return false;
+        };
+
+        // the digests are compared, for the length of the expected token not to be revealed either
+        let token_digest: [u8; 32] = Sha256::digest(token.trim().as_bytes()).into();
+        secrets_match(&self.token_digest, &token_digest)
+    }
+}
+
+pub fn json_response(status: StatusCode, field: &str, message: &str) -> web::HttpResponse {
+    ResponseBuilder::new(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(
+            serde_json::Value::Object(serde_json::Map::from_iter([(
+                field.to_string(),
+                message.into(),
+            )]))
+            .to_string(),
+        )
+}
+
+fn push_error_response(err: SupergraphPushError) -> web::HttpResponse {
+    let status = match err {
+        SupergraphPushError::UnsupportedSource | SupergraphPushError::NoPreviousSupergraph => {
+            StatusCode::CONFLICT
+        }
+        SupergraphPushError::Rejected(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        SupergraphPushError::ReloadTaskUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    warn!(error = %err, "failed to replace the supergraph");
+    json_response(status, "error", &err.to_string())
+}
+
+/// Returns the response to send when the request is not allowed to use the admin endpoint.
+pub fn check_authorization(
+    req: &HttpRequest,
+    runtime: Option<&AdminEndpointRuntime>,
+) -> Option<web::HttpResponse> {
+    match runtime {
+        Some(runtime) if runtime.is_authorized(req) => None,
+        Some(_) => Some(json_response(
+            StatusCode::UNAUTHORIZED,
+            "error",
+            "missing or invalid bearer token",
+        )),
+        None => Some(web::HttpResponse::NotFound().finish()),
+    }
+}
+
+pub async fn supergraph_push_handler(
+    req: HttpRequest,
+    body_stream: web::types::Payload,
+    schema_state: web::types::State<Arc<SchemaState>>,
+    app_state: web::types::State<Arc<RouterSharedState>>,
+) -> web::HttpResponse {
+    if let Some(response) = check_authorization(&req, app_state.supergraph_admin.as_ref()) {
+        return response;
+    }
+
+    let body = match read_body_stream(&req, body_stream, MAX_SUPERGRAPH_SIZE).await {
+        Ok(body) => body,
+        Err(err) => return json_response(err.status_code(), "error", &err.to_string()),
+    };
+    let sdl = match String::from_utf8(body.to_vec()) {
+        Ok(sdl) if !sdl.trim().is_empty() => sdl,
+        Ok(_) => return json_response(StatusCode::BAD_REQUEST, "error", "the supergraph is empty"),
+        Err(_) => {
+            return json_response(
+                StatusCode::BAD_REQUEST,
+                "error",
+                "the supergraph is not valid UTF-8",
+            )
+        }
+    };
+
+    match schema_state.push_supergraph(sdl).await {
+        Ok(()) => {
+            info!("supergraph replaced through the admin endpoint");
+            json_response(StatusCode::OK, "status", "updated")
+        }
+        Err(err) => push_error_response(err),
+    }
+}
+
+pub async fn supergraph_rollback_handler(
+    req: HttpRequest,
+    schema_state: web::types::State<Arc<SchemaState>>,
+    app_state: web::types::State<Arc<RouterSharedState>>,
+) -> web::HttpResponse {
+    if let Some(response) = check_authorization(&req, app_state.supergraph_admin.as_ref()) {
+        return response;
+    }
+
+    match schema_state.rollback_supergraph().await {
+        Ok(()) => {
+            info!("supergraph rolled back through the admin endpoint");
+            json_response(StatusCode::OK, "status", "rolled_back")
+        }
+        Err(err) => push_error_response(err),
+    }
+}
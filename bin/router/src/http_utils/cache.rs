@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use hive_router_internal::http::read_body_stream;
+use http::StatusCode;
+use ntex::web::{self, HttpRequest};
+use serde::{de::DeserializeOwned, Deserialize};
+use sonic_rs::JsonValueTrait;
+use tracing::info;
+
+use crate::{
+    http_utils::admin::{check_authorization, json_response},
+    schema_state::SchemaState,
+    shared_state::RouterSharedState,
+};
+
+/// The purge requests only carry a few type names and keys.
+const MAX_PURGE_REQUEST_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ResponseCachePurgeRequest {
+    /// Purges the responses of the operations selecting any of these types.
+    #[serde(default)]
+    types: Vec<String>,
+    /// Purges the responses containing any of these entities, as in `Product:upc=1`.
+    #[serde(default)]
+    surrogate_keys: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EntityCachePurgeRequest {
+    typename: String,
+    /// The key fields of the entity to purge, all the entities of the type are purged when missing.
+    #[serde(default)]
+    key: Option<sonic_rs::Value>,
+}
+
+/// Reads the JSON body of a purge request, an empty body being the default request.
+async fn read_purge_request<T: DeserializeOwned + Default>(
+    req: &HttpRequest,
+    body_stream: web::types::Payload,
+) -> Result<T, web::HttpResponse> {
+    let body = read_body_stream(req, body_stream, MAX_PURGE_REQUEST_SIZE)
+        .await
+        .map_err(|err| json_response(err.status_code(), "error", &err.to_string()))?;
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(T::default());
+    }
+    sonic_rs::from_slice(&body).map_err(|err| {
+        json_response(
+            StatusCode::BAD_REQUEST,
+            "error",
+            &format!("invalid purge request: {err}"),
+        )
+    })
+}
+
+pub async fn response_cache_purge_handler(
+    req: HttpRequest,
+    body_stream: web::types::Payload,
+    app_state: web::types::State<Arc<RouterSharedState>>,
+) -> web::HttpResponse {
+    if let Some(response) = check_authorization(&req, app_state.cache_admin.as_ref()) {
+        return response;
+    }
+
+    let Some(response_cache) = app_state.response_cache.as_ref() else {
+        return json_response(
+            StatusCode::CONFLICT,
+            "error",
+            "the response cache is disabled",
+        );
+    };
+    let request: ResponseCachePurgeRequest = match read_purge_request(&req, body_stream).await {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+
+    // all the responses are purged when neither types nor surrogate keys are given
+    let mut tags = request.types;
+    tags.extend(request.surrogate_keys);
+    response_cache.purge(&tags).await;
+    json_response(StatusCode::OK, "status", "purged")
+}
+
+pub async fn entity_cache_purge_handler(
+    req: HttpRequest,
+    body_stream: web::types::Payload,
+    schema_state: web::types::State<Arc<SchemaState>>,
+    app_state: web::types::State<Arc<RouterSharedState>>,
+) -> web::HttpResponse {
+    if let Some(response) = check_authorization(&req, app_state.cache_admin.as_ref()) {
+        return response;
+    }
+
+    if !app_state.router_config.entity_cache.enabled {
+        return json_response(
+            StatusCode::CONFLICT,
+            "error",
+            "the entity cache is disabled",
+        );
+    }
+    let request: Option<EntityCachePurgeRequest> = match read_purge_request(&req, body_stream).await
+    {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let Some(request) = request else {
+        return json_response(
+            StatusCode::BAD_REQUEST,
+            "error",
+            "the purge request must specify the 'typename' of the entities",
+        );
+    };
+    if request.key.as_ref().is_some_and(|key| !key.is_object()) {
+        return json_response(
+            StatusCode::BAD_REQUEST,
+            "error",
+            "the 'key' of the entity must be an object of its key fields",
+        );
+    }
+
+    info!(typename = %request.typename, key = ?request.key, "purging the entity cache");
+    schema_state.for_each_runtime(|runtime| {
+        if let Some(entity_cache) = &runtime.entity_cache {
+            entity_cache.purge(&request.typename, request.key.clone());
+        }
+    });
+    json_response(StatusCode::OK, "status", "purged")
+}
+
+pub async fn plan_cache_clear_handler(
+    req: HttpRequest,
+    schema_state: web::types::State<Arc<SchemaState>>,
+    app_state: web::types::State<Arc<RouterSharedState>>,
+) -> web::HttpResponse {
+    if let Some(response) = check_authorization(&req, app_state.cache_admin.as_ref()) {
+        return response;
+    }
+
+    info!("clearing the validation, normalization and query plan caches");
+    schema_state.for_each_runtime(|runtime| {
+        runtime.validate_cache.invalidate_all();
+        runtime.normalize_cache.invalidate_all();
+        runtime.plan_cache.invalidate_all();
+    });
+    json_response(StatusCode::OK, "status", "cleared")
+}
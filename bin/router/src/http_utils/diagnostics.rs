@@ -0,0 +1,116 @@
+use std::{collections::HashMap, sync::Arc};
+
+use hive_router_plan_executor::executors::map::CircuitBreakerState;
+use http::header::CONTENT_TYPE;
+use ntex::web::{self, HttpRequest};
+use serde_json::json;
+
+use crate::{
+    consts::ROUTER_VERSION,
+    http_utils::{admin::check_authorization, probes::reload_status_json},
+    schema_state::SchemaState,
+    shared_state::RouterSharedState,
+};
+
+/// Responds with the runtime state of the router, for dashboards and ops tooling.
+pub async fn diagnostics_handler(
+    req: HttpRequest,
+    schema_state: web::types::State<Arc<SchemaState>>,
+    app_state: web::types::State<Arc<RouterSharedState>>,
+) -> web::HttpResponse {
+    if let Some(response) = check_authorization(&req, app_state.diagnostics_admin.as_ref()) {
+        return response;
+    }
+
+    let supergraph = schema_state.published_supergraph().map(|published| {
+        let circuit_breakers: HashMap<String, CircuitBreakerState> = schema_state
+            .configured_runtime()
+            .map(|runtime| {
+                runtime
+                    .subgraph_executor_map
+                    .circuit_breaker_states()
+                    .into_iter()
+                    .collect()
+            })
+            .unwrap_or_default();
+        let subgraphs = schema_state
+            .configured_snapshot()
+            .map(|snapshot| {
+                snapshot
+                    .planner
+                    .supergraph
+                    .subgraph_endpoint_map
+                    .iter()
+                    .map(|(name, endpoint)| {
+                        let subgraph = json!({
+                            "endpoint": endpoint,
+                            // `null` when the circuit breaker is disabled for the subgraph
+                            "circuit_breaker": circuit_breakers.get(name).map(CircuitBreakerState::as_str),
+                        });
+                        (name.clone(), subgraph)
+                    })
+                    .collect::<serde_json::Map<_, _>>()
+            })
+            .unwrap_or_default();
+        json!({
+            "hash": published.hash,
+            "published_at": published.published_at,
+            "subgraphs": subgraphs,
+        })
+    });
+
+    let recent_reload_errors = schema_state
+        .recent_reload_errors()
+        .into_iter()
+        .map(reload_status_json)
+        .collect::<Vec<_>>();
+
+    let plugins = app_state
+        .plugins
+        .as_ref()
+        .map(|plugins| {
+            plugins
+                .iter()
+                .map(|plugin| plugin.name())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    web::HttpResponse::Ok()
+        .header(CONTENT_TYPE, "application/json")
+        .body(
+            json!({
+                "version": ROUTER_VERSION,
+                "supergraph": supergraph,
+                "last_supergraph_reload": schema_state.last_reload_status().map(reload_status_json),
+                "recent_supergraph_reload_errors": recent_reload_errors,
+                "caches": cache_entries(&schema_state, &app_state),
+                "plugins": plugins,
+            })
+            .to_string(),
+        )
+}
+
+/// The number of entries of the caches, summed across every supergraph runtime alive,
+/// like the `hive.router.*_cache.size` metrics.
+fn cache_entries(schema_state: &SchemaState, app_state: &RouterSharedState) -> serde_json::Value {
+    let (mut validate, mut normalize, mut plan, mut entity) = (0, 0, 0, 0);
+    schema_state.for_each_runtime(|runtime| {
+        validate += runtime.validate_cache.entry_count();
+        normalize += runtime.normalize_cache.entry_count();
+        plan += runtime.plan_cache.entry_count();
+        if let Some(entity_cache) = &runtime.entity_cache {
+            entity += entity_cache.entry_count();
+        }
+    });
+    let entity_cache_enabled = app_state.router_config.entity_cache.enabled;
+
+    json!({
+        "parse": { "entries": app_state.parse_cache.entry_count() },
+        "validate": { "entries": validate },
+        "normalize": { "entries": normalize },
+        "plan": { "entries": plan },
+        // `null` when the entity cache is disabled
+        "entity": entity_cache_enabled.then(|| json!({ "entries": entity })),
+    })
+}
@@ -1,2 +1,6 @@
+pub mod admin;
+pub mod cache;
+pub mod diagnostics;
 pub mod landing_page;
 pub mod probes;
+pub mod readiness;
@@ -1,20 +1,63 @@
 use std::sync::Arc;
 
+use http::header::CONTENT_TYPE;
 use ntex::web::{self, HttpRequest, Responder};
+use serde_json::json;
 
-use crate::schema_state::SchemaState;
+use crate::{
+    schema_state::{SchemaState, SupergraphReloadStatus},
+    shared_state::RouterSharedState,
+};
 
 pub async fn health_check_handler() -> impl Responder {
     web::HttpResponse::Ok()
 }
 
+/// Responds with `200 OK` when the router can serve requests, `503 Service Unavailable` otherwise.
+///
+/// The body reports the outcome of the last supergraph update, so a rejected supergraph
+/// is visible even though the router keeps serving the previous one,
+/// and the outcome of the dependency checks of `http.readiness`, when it lists some.
 pub async fn readiness_check_handler(
     req: HttpRequest,
     schema_state: web::types::State<Arc<SchemaState>>,
+    app_state: web::types::State<Arc<RouterSharedState>>,
 ) -> impl Responder {
-    if schema_state.is_ready(&req) {
+    let readiness_checks = app_state.readiness_checks.as_ref();
+    let ready = schema_state.is_ready(&req)
+        && readiness_checks.is_none_or(|readiness_checks| readiness_checks.passed());
+    let last_reload = schema_state.last_reload_status().map(reload_status_json);
+
+    let mut body = json!({
+        "ready": ready,
+        "last_supergraph_reload": last_reload,
+    });
+    if let Some(readiness_checks) = readiness_checks {
+        // `null` until the checks ran once
+        body["checks"] = json!(readiness_checks.results());
+    }
+
+    let mut response = if ready {
         web::HttpResponse::Ok()
     } else {
         web::HttpResponse::ServiceUnavailable()
+    };
+    response
+        .header(CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+}
+
+/// The outcome of a supergraph update, as reported by `/readiness` and the diagnostics endpoint.
+pub fn reload_status_json(status: SupergraphReloadStatus) -> serde_json::Value {
+    match status.error {
+        Some(error) => json!({
+            "status": "failed",
+            "error": error,
+            "timestamp": status.timestamp,
+        }),
+        None => json!({
+            "status": "succeeded",
+            "timestamp": status.timestamp,
+        }),
     }
 }
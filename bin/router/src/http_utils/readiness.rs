@@ -0,0 +1,240 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use arc_swap::ArcSwapOption;
+use async_trait::async_trait;
+use futures::future::join_all;
+use hive_router_config::{
+    http_server::ReadinessConfig, override_subgraph_urls::UrlOrExpression, HiveRouterConfig,
+};
+use hive_router_internal::background_tasks::{BackgroundTask, CancellationToken};
+use serde::Serialize;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::{
+    schema_state::SchemaState,
+    shared_state::RouterSharedState,
+    storage::redis_connections::{RedisConnection, RedisConnections},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReadinessChecksError {
+    #[error("invalid http.readiness config: {0}")]
+    Configuration(String),
+}
+
+/// The outcome of a check of a dependency of the router.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessCheckResult {
+    /// `jwks`, `redis:<component>` or `subgraph:<name>`
+    pub name: String,
+    pub status: ReadinessCheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessCheckStatus {
+    Passed,
+    Failed,
+}
+
+impl ReadinessCheckResult {
+    fn new(name: String, result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self {
+                name,
+                status: ReadinessCheckStatus::Passed,
+                error: None,
+            },
+            Err(error) => Self {
+                name,
+                status: ReadinessCheckStatus::Failed,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+/// Checks the dependencies of the router listed in `http.readiness`, for `/readiness` to report them.
+pub struct ReadinessChecksRuntime {
+    config: ReadinessConfig,
+    redis: Vec<(&'static str, RedisConnection)>,
+    http_client: reqwest::Client,
+    // `None` until the checks ran once
+    results: ArcSwapOption<Vec<ReadinessCheckResult>>,
+}
+
+impl ReadinessChecksRuntime {
+    /// The Redis connections are the ones of the components connected so far,
+    /// so the checks are created once the components keeping their state in Redis are.
+    pub fn from_config(
+        router_config: &HiveRouterConfig,
+        redis_connections: &RedisConnections,
+    ) -> Result<Option<Self>, ReadinessChecksError> {
+        let config = &router_config.http.readiness;
+        if !config.has_checks() {
+            return Ok(None);
+        }
+
+        let redis = match config.redis {
+            true => redis_connections.by_component(),
+            false => Vec::new(),
+        };
+        let http_client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|err| ReadinessChecksError::Configuration(err.to_string()))?;
+
+        info!("readiness checks enabled");
+
+        Ok(Some(Self {
+            config: config.clone(),
+            redis,
+            http_client,
+            results: ArcSwapOption::empty(),
+        }))
+    }
+
+    /// The outcome of the last run of the checks, `None` until they ran once.
+    pub fn results(&self) -> Option<Arc<Vec<ReadinessCheckResult>>> {
+        self.results.load_full()
+    }
+
+    /// Returns true when the checks ran, and they all passed.
+    pub fn passed(&self) -> bool {
+        self.results().is_some_and(|results| {
+            results
+                .iter()
+                .all(|result| result.status == ReadinessCheckStatus::Passed)
+        })
+    }
+
+    async fn run(&self, shared_state: &RouterSharedState, schema_state: &SchemaState) {
+        let mut results = Vec::new();
+
+        if self.config.jwks {
+            if let Some(jwt_auth_runtime) = shared_state.jwt_auth_runtime.as_ref() {
+                let unloaded = jwt_auth_runtime.unloaded_jwks_providers();
+                results.push(ReadinessCheckResult::new(
+                    "jwks".to_string(),
+                    match unloaded.is_empty() {
+                        true => Ok(()),
+                        false => Err(format!(
+                            "the keys of {} are not loaded",
+                            unloaded.join(", ")
+                        )),
+                    },
+                ));
+            }
+        }
+
+        let redis_checks = self.redis.iter().map(|(component, connection)| async move {
+            let ping = async { connection.ping().await.map_err(|err| err.to_string()) };
+            ReadinessCheckResult::new(
+                format!("redis:{component}"),
+                with_timeout(self.config.timeout, ping).await,
+            )
+        });
+        results.extend(join_all(redis_checks).await);
+
+        let snapshot = schema_state.configured_snapshot();
+        let subgraph_checks = self.config.subgraphs.iter().map(|(name, subgraph)| {
+            let url = match &subgraph.url {
+                Some(url) => Some(url.clone()),
+                None => match shared_state
+                    .router_config
+                    .override_subgraph_urls
+                    .get_subgraph_url(name)
+                {
+                    Some(UrlOrExpression::Url(url)) => Some(url.clone()),
+                    _ => snapshot.as_ref().and_then(|snapshot| {
+                        snapshot
+                            .planner
+                            .supergraph
+                            .subgraph_endpoint_map
+                            .get(name)
+                            .cloned()
+                    }),
+                },
+            };
+            let health_endpoint = subgraph.url.is_some();
+            async move {
+                let result = match url {
+                    Some(url) => self.ping_subgraph(&url, health_endpoint).await,
+                    None => Err("the subgraph is not part of the supergraph".to_string()),
+                };
+                ReadinessCheckResult::new(format!("subgraph:{name}"), result)
+            }
+        });
+        results.extend(join_all(subgraph_checks).await);
+
+        let passed = results
+            .iter()
+            .all(|result| result.status == ReadinessCheckStatus::Passed);
+        let previous = self.results.swap(Some(Arc::new(results)));
+        let previously_passed = previous.is_some_and(|previous| {
+            previous
+                .iter()
+                .all(|result| result.status == ReadinessCheckStatus::Passed)
+        });
+        if passed && !previously_passed {
+            info!("the readiness checks passed");
+        } else if !passed && previously_passed {
+            warn!("a readiness check failed, the router is not ready");
+        }
+    }
+
+    /// Requests the health endpoint of a subgraph,
+    /// or sends it a `{ __typename }` query when it has no health endpoint.
+    async fn ping_subgraph(&self, url: &str, health_endpoint: bool) -> Result<(), String> {
+        let request = match health_endpoint {
+            true => self.http_client.get(url),
+            false => self
+                .http_client
+                .post(url)
+                .json(&json!({ "query": "{ __typename }" })),
+        };
+        let response = request.send().await.map_err(|err| err.to_string())?;
+        match response.status().is_success() {
+            true => Ok(()),
+            false => Err(format!("responded with a {} status", response.status())),
+        }
+    }
+}
+
+async fn with_timeout(
+    timeout: Duration,
+    check: impl Future<Output = Result<(), String>>,
+) -> Result<(), String> {
+    tokio::time::timeout(timeout, check)
+        .await
+        .unwrap_or_else(|_| Err(format!("timed out after {timeout:?}")))
+}
+
+/// Runs the readiness checks every `http.readiness.interval`.
+pub struct ReadinessChecksTask {
+    pub shared_state: Arc<RouterSharedState>,
+    pub schema_state: Arc<SchemaState>,
+}
+
+#[async_trait]
+impl BackgroundTask for ReadinessChecksTask {
+    fn id(&self) -> &str {
+        "readiness-checks"
+    }
+
+    async fn run(&self, token: CancellationToken) {
+        let Some(runtime) = self.shared_state.readiness_checks.as_ref() else {
+            return;
+        };
+        let mut interval = tokio::time::interval(runtime.config.interval);
+
+        while token.run_until_cancelled(interval.tick()).await.is_some() {
+            token
+                .run_until_cancelled(runtime.run(&self.shared_state, &self.schema_state))
+                .await;
+        }
+    }
+}
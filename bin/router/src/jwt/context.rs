@@ -18,6 +18,14 @@ impl JwtRequestContext {
         Ok(sonic_rs::to_value(&self.token_payload.claims)?)
     }
 
+    /// Whether the `exp` claim of the token has passed. Tokens without it never expire.
+    pub fn is_expired(&self) -> bool {
+        self.token_payload
+            .claims
+            .exp
+            .is_some_and(|exp| exp <= jsonwebtoken::get_current_timestamp())
+    }
+
     /// Extracts an optional "scope"/"scopes" field form the token's payload.
     /// Supports both space-delimited and array formats.
     pub fn extract_scopes(&self) -> Option<Vec<String>> {
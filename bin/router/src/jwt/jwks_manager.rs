@@ -1,12 +1,17 @@
-use hive_router_config::jwt_auth::{JwksProviderSourceConfig, JwtAuthConfig};
+use hive_router_config::jwt_auth::{JwksProviderConfig, JwksProviderSourceConfig, JwtAuthConfig};
 use hive_router_internal::background_tasks::{BackgroundTask, BackgroundTasksManager};
+use serde::Deserialize;
 use sonic_rs::from_str;
-use std::sync::{Arc, RwLock};
+use std::{
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 use tokio::fs::read_to_string;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info};
 
-use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{jwk::JwkSet, Algorithm};
 
 pub struct JwksManager {
     sources: Vec<Arc<JwksSource>>,
@@ -23,10 +28,10 @@ impl JwksManager {
         JwksManager { sources }
     }
 
-    pub fn all(&self) -> Vec<Arc<JwkSet>> {
+    pub fn all(&self) -> Vec<Arc<JwksProvider>> {
         self.sources
             .iter()
-            .filter_map(|v| match v.get_jwk_set() {
+            .filter_map(|v| match v.get_provider() {
                 Ok(set) => Some(set),
                 Err(err) => {
                     error!("Failed to use JWK set: {}, ignoring", err);
@@ -50,6 +55,24 @@ impl JwksManager {
         Ok(())
     }
 
+    /// Loads every source, including the ones loaded in the background, to check they are reachable.
+    pub async fn load_sources(&self) -> Result<(), JwksSourceError> {
+        for source in &self.sources {
+            source.load_and_store_jwks().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the locations of the sources whose keys are not loaded yet.
+    pub fn unloaded_sources(&self) -> Vec<String> {
+        self.sources
+            .iter()
+            .filter(|source| source.get_provider().is_err())
+            .map(|source| source.location().to_string())
+            .collect()
+    }
+
     pub fn register_background_tasks(&self, background_tasks_mgr: &mut BackgroundTasksManager) {
         for source in &self.sources {
             if source.should_poll_in_background() {
@@ -59,10 +82,29 @@ impl JwksManager {
     }
 }
 
+/// The keys of a JWKS provider, with the constraints of the tokens they verify.
+#[derive(Debug)]
+pub struct JwksProvider {
+    pub jwk_set: JwkSet,
+    pub issuers: Option<Vec<String>>,
+    pub audiences: Option<Vec<String>>,
+    pub allowed_algorithms: Option<Vec<Algorithm>>,
+}
+
 #[derive(Debug)]
 pub struct JwksSource {
-    config: JwksProviderSourceConfig,
-    jwk: RwLock<Option<Arc<JwkSet>>>,
+    config: JwksProviderConfig,
+    provider: RwLock<Option<Arc<JwksProvider>>>,
+}
+
+/// The fields of the OpenID Connect discovery document used by the router.
+/// See https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata
+#[derive(Deserialize)]
+struct OidcDiscoveryDocument {
+    issuer: String,
+    jwks_uri: String,
+    #[serde(default)]
+    id_token_signing_alg_values_supported: Vec<String>,
 }
 
 struct JwksSourceTask(Arc<JwksSource>);
@@ -74,16 +116,12 @@ impl BackgroundTask for JwksSourceTask {
     }
 
     async fn run(&self, token: CancellationToken) {
-        if let JwksProviderSourceConfig::Remote {
-            polling_interval: Some(interval),
-            ..
-        } = &self.0.config
-        {
+        if let Some(interval) = self.0.polling_interval() {
             debug!(
                 "Starting remote jwks polling for source: {:?}",
-                self.0.config
+                self.0.config.source
             );
-            let mut tokio_interval = tokio::time::interval(*interval);
+            let mut tokio_interval = tokio::time::interval(interval);
 
             loop {
                 tokio::select! {
@@ -110,25 +148,52 @@ pub enum JwksSourceError {
     JwksContentInvalidStructure(sonic_rs::Error),
     #[error("failed to acquire jwks handle")]
     FailedToAcquireJwk,
+    #[error("failed to load the OpenID Connect discovery document: {0}")]
+    OidcDiscoveryNetworkError(reqwest::Error),
+    #[error("failed to parse the OpenID Connect discovery document: {0}")]
+    OidcDiscoveryInvalidStructure(sonic_rs::Error),
+    #[error("the issuer of the OpenID Connect discovery document '{found}' does not match the configured issuer '{expected}'")]
+    OidcIssuerMismatch { expected: String, found: String },
+}
+
+async fn fetch_remote(url: &str) -> Result<String, reqwest::Error> {
+    reqwest::Client::new().get(url).send().await?.text().await
+}
+
+async fn discover_oidc_provider(issuer: &str) -> Result<OidcDiscoveryDocument, JwksSourceError> {
+    let issuer = issuer.trim_end_matches('/');
+    let discovery_url = format!("{}/.well-known/openid-configuration", issuer);
+    debug!(
+        "loading OpenID Connect discovery document: {}",
+        discovery_url
+    );
+
+    let document_str = fetch_remote(&discovery_url)
+        .await
+        .map_err(JwksSourceError::OidcDiscoveryNetworkError)?;
+    let document = from_str::<OidcDiscoveryDocument>(&document_str)
+        .map_err(JwksSourceError::OidcDiscoveryInvalidStructure)?;
+
+    if document.issuer.trim_end_matches('/') != issuer {
+        return Err(JwksSourceError::OidcIssuerMismatch {
+            expected: issuer.to_string(),
+            found: document.issuer,
+        });
+    }
+
+    Ok(document)
 }
 
 impl JwksSource {
     async fn load_and_store_jwks(&self) -> Result<&Self, JwksSourceError> {
-        let jwks_str = match &self.config {
+        let mut discovery = None;
+        let jwks_str = match &self.config.source {
             JwksProviderSourceConfig::Remote { url, .. } => {
-                let client = reqwest::Client::new();
                 debug!("loading jwks from a remote source: {}", url);
 
-                let response_text = client
-                    .get(url)
-                    .send()
+                fetch_remote(url)
                     .await
                     .map_err(JwksSourceError::RemoteJwksNetworkError)?
-                    .text()
-                    .await
-                    .map_err(JwksSourceError::RemoteJwksNetworkError)?;
-
-                response_text
             }
             JwksProviderSourceConfig::File { file, .. } => {
                 debug!("loading jwks from a file source: {}", file.absolute);
@@ -139,36 +204,84 @@ impl JwksSource {
 
                 file_contents
             }
+            JwksProviderSourceConfig::Oidc { issuer, .. } => {
+                let document = discover_oidc_provider(issuer).await?;
+                debug!(
+                    "loading jwks from an OpenID Connect provider: {}",
+                    document.jwks_uri
+                );
+
+                let jwks_str = fetch_remote(&document.jwks_uri)
+                    .await
+                    .map_err(JwksSourceError::RemoteJwksNetworkError)?;
+                discovery = Some(document);
+
+                jwks_str
+            }
         };
 
-        let new_jwk = Arc::new(
-            from_str::<JwkSet>(&jwks_str).map_err(JwksSourceError::JwksContentInvalidStructure)?,
-        );
+        let jwk_set =
+            from_str::<JwkSet>(&jwks_str).map_err(JwksSourceError::JwksContentInvalidStructure)?;
 
-        if let Ok(mut w_jwk) = self.jwk.write() {
-            *w_jwk = Some(new_jwk);
+        // The constraints of the configuration win over the ones discovered from the provider.
+        let issuers = self.config.issuers.clone().or_else(|| {
+            discovery
+                .as_ref()
+                .map(|document| vec![document.issuer.clone()])
+        });
+        let allowed_algorithms = self.config.allowed_algorithms.clone().or_else(|| {
+            discovery.as_ref().and_then(|document| {
+                let algorithms: Vec<Algorithm> = document
+                    .id_token_signing_alg_values_supported
+                    .iter()
+                    // Algorithms unsupported by the router, like `none`, are ignored.
+                    .filter_map(|alg| Algorithm::from_str(alg).ok())
+                    .collect();
+                (!algorithms.is_empty()).then_some(algorithms)
+            })
+        });
+
+        let new_provider = Arc::new(JwksProvider {
+            jwk_set,
+            issuers,
+            audiences: self.config.audiences.clone(),
+            allowed_algorithms,
+        });
+
+        if let Ok(mut w_provider) = self.provider.write() {
+            *w_provider = Some(new_provider);
         }
 
         Ok(self)
     }
 
-    pub fn new(config: JwksProviderSourceConfig) -> Self {
+    pub fn new(config: JwksProviderConfig) -> Self {
         Self {
             config,
-            jwk: RwLock::new(None),
+            provider: RwLock::new(None),
         }
     }
 
     pub fn should_poll_in_background(&self) -> bool {
-        match &self.config {
-            JwksProviderSourceConfig::Remote { .. } => true,
+        match &self.config.source {
+            JwksProviderSourceConfig::Remote { .. } | JwksProviderSourceConfig::Oidc { .. } => true,
             JwksProviderSourceConfig::File { .. } => false,
         }
     }
 
+    /// The URL, the file or the issuer the keys are loaded from.
+    fn location(&self) -> &str {
+        match &self.config.source {
+            JwksProviderSourceConfig::Remote { url, .. } => url,
+            JwksProviderSourceConfig::File { file, .. } => &file.relative,
+            JwksProviderSourceConfig::Oidc { issuer, .. } => issuer,
+        }
+    }
+
     pub fn should_prefetch(&self) -> bool {
-        match &self.config {
-            JwksProviderSourceConfig::Remote { prefetch, .. } => match prefetch {
+        match &self.config.source {
+            JwksProviderSourceConfig::Remote { prefetch, .. }
+            | JwksProviderSourceConfig::Oidc { prefetch, .. } => match prefetch {
                 Some(prefetch) => *prefetch,
                 None => false,
             },
@@ -176,10 +289,22 @@ impl JwksSource {
         }
     }
 
-    pub fn get_jwk_set(&self) -> Result<Arc<JwkSet>, JwksSourceError> {
-        if let Ok(jwk) = self.jwk.try_read() {
-            if let Some(jwk) = jwk.as_ref() {
-                return Ok(jwk.clone());
+    fn polling_interval(&self) -> Option<Duration> {
+        match &self.config.source {
+            JwksProviderSourceConfig::Remote {
+                polling_interval, ..
+            }
+            | JwksProviderSourceConfig::Oidc {
+                polling_interval, ..
+            } => *polling_interval,
+            JwksProviderSourceConfig::File { .. } => None,
+        }
+    }
+
+    pub fn get_provider(&self) -> Result<Arc<JwksProvider>, JwksSourceError> {
+        if let Ok(provider) = self.provider.try_read() {
+            if let Some(provider) = provider.as_ref() {
+                return Ok(provider.clone());
             }
         }
 
@@ -8,11 +8,7 @@ use cookie::Cookie;
 use hive_router_config::jwt_auth::{JwtAuthConfig, JwtAuthPluginLookupLocation};
 use hive_router_internal::background_tasks::BackgroundTasksManager;
 use http::header::COOKIE;
-use jsonwebtoken::{
-    decode, decode_header,
-    jwk::{Jwk, JwkSet},
-    Algorithm, DecodingKey, Header, Validation,
-};
+use jsonwebtoken::{decode, decode_header, jwk::Jwk, Algorithm, DecodingKey, Header, Validation};
 use ntex::{http::header::HeaderValue, http::HeaderMap};
 use tracing::warn;
 
@@ -20,7 +16,7 @@ use crate::{
     jwt::{
         context::{Audience, JwtClaims, JwtRequestContext, JwtTokenPayload},
         errors::{JwtError, LookupError},
-        jwks_manager::{JwksManager, JwksSourceError},
+        jwks_manager::{JwksManager, JwksProvider, JwksSourceError},
     },
     shared_state::JwtClaimsCache,
 };
@@ -52,6 +48,16 @@ impl JwtAuthRuntime {
         Ok(instance)
     }
 
+    /// Loads every JWKS provider, to check they are reachable.
+    pub async fn load_jwks_providers(&self) -> Result<(), JwksSourceError> {
+        self.jwks.load_sources().await
+    }
+
+    /// Returns the locations of the JWKS providers whose keys are not loaded yet.
+    pub fn unloaded_jwks_providers(&self) -> Vec<String> {
+        self.jwks.unloaded_sources()
+    }
+
     fn lookup(&self, headers: &HeaderMap) -> Result<(Option<String>, String), LookupError> {
         for lookup_config in &self.config.lookup_locations {
             match lookup_config {
@@ -126,50 +132,66 @@ impl JwtAuthRuntime {
         Err(LookupError::LookupFailed)
     }
 
-    pub(crate) fn find_matching_jwks<'a>(
+    /// The providers whose keys may verify the token.
+    /// With several providers, e.g. one per issuer, more than one of them can match the `alg` of a token without `kid`.
+    pub(crate) fn find_matching_providers<'a>(
         &'a self,
         jwt_header: &Header,
-        jwks: &'a Vec<Arc<JwkSet>>,
-    ) -> Result<&'a JwkSet, JwtError> {
+        providers: &'a [Arc<JwksProvider>],
+    ) -> Result<Vec<&'a JwksProvider>, JwtError> {
         // If `kid` is vailable on the header, we can try to match it to the `kid` on the available JWKs.
         if let Some(jwt_kid) = &jwt_header.kid {
-            for jwk in jwks {
-                for key in &jwk.keys {
-                    if key.common.key_id.as_ref().is_some_and(|v| v == jwt_kid) {
-                        return Ok(jwk);
-                    }
-                }
+            let matching: Vec<&JwksProvider> = providers
+                .iter()
+                .filter(|provider| {
+                    provider
+                        .jwk_set
+                        .keys
+                        .iter()
+                        .any(|key| key.common.key_id.as_ref().is_some_and(|v| v == jwt_kid))
+                })
+                .map(|provider| provider.as_ref())
+                .collect();
+
+            if !matching.is_empty() {
+                return Ok(matching);
             }
         }
 
         // If we don't have `kid` on the token, we should try to match the `alg` field.
-        for jwk in jwks {
-            for key in &jwk.keys {
+        let mut matching = Vec::new();
+        for provider in providers {
+            for key in &provider.jwk_set.keys {
                 if let Some(key_alg) = key.common.key_algorithm {
                     let key_alg_cmp = Algorithm::from_str(&key_alg.to_string())
                         .map_err(JwtError::JwkAlgorithmNotSupported)?;
                     if key_alg_cmp == jwt_header.alg {
-                        return Ok(jwk);
+                        matching.push(provider.as_ref());
+                        break;
                     }
                 }
             }
         }
 
-        Err(JwtError::FailedToLocateProvider)
+        if matching.is_empty() {
+            return Err(JwtError::FailedToLocateProvider);
+        }
+
+        Ok(matching)
     }
 
     fn authenticate(
         &self,
-        jwks: &Vec<Arc<JwkSet>>,
+        providers: &[Arc<JwksProvider>],
         headers: &HeaderMap,
     ) -> Result<(JwtTokenPayload, Option<String>, String), JwtError> {
         match self.lookup(headers) {
             Ok((maybe_prefix, token)) => {
                 // First, we need to decode the header to determine which provider to use.
                 let header = decode_header(&token).map_err(JwtError::InvalidJwtHeader)?;
-                let jwk = self.find_matching_jwks(&header, jwks)?;
+                let providers = self.find_matching_providers(&header, providers)?;
 
-                self.decode_and_validate_token(&header, &token, &jwk.keys)
+                self.decode_and_validate_token(&header, &token, &providers)
                     .map(|token_data| (token_data, maybe_prefix, token))
             }
             Err(e) => {
@@ -184,28 +206,27 @@ impl JwtAuthRuntime {
         &self,
         header: &Header,
         token: &str,
-        jwks: &[Jwk],
+        providers: &[&JwksProvider],
     ) -> Result<JwtTokenPayload, JwtError> {
-        let decode_attempts = jwks
-            .iter()
-            .map(|jwk| self.try_decode_from_jwk(header, token, jwk));
+        let mut errors = Vec::new();
 
-        if let Some(success) = decode_attempts.clone().find(|result| result.is_ok()) {
-            return success;
+        for provider in providers {
+            for jwk in &provider.jwk_set.keys {
+                match self.try_decode_from_jwk(header, token, provider, jwk) {
+                    Ok(token_data) => return Ok(token_data),
+                    Err(err) => errors.push(err),
+                }
+            }
         }
 
-        Err(JwtError::AllProvidersFailedToDecode(
-            decode_attempts
-                .into_iter()
-                .map(|result: Result<JwtTokenPayload, JwtError>| result.unwrap_err())
-                .collect::<Vec<_>>(),
-        ))
+        Err(JwtError::AllProvidersFailedToDecode(errors))
     }
 
     fn try_decode_from_jwk(
         &self,
         header: &Header,
         token: &str,
+        provider: &JwksProvider,
         jwk: &Jwk,
     ) -> Result<JwtTokenPayload, JwtError> {
         let decoding_key = DecodingKey::from_jwk(jwk).map_err(JwtError::InvalidDecodingKey)?;
@@ -216,8 +237,14 @@ impl JwtAuthRuntime {
             None => header.alg,
         };
 
-        // Make sure the algorithm is in the allowed algorithms before proceeding
-        if let Some(allowed) = &self.config.allowed_algorithms {
+        // Make sure the algorithm is in the allowed algorithms, of the router and of the provider, before proceeding
+        for allowed in [
+            &self.config.allowed_algorithms,
+            &provider.allowed_algorithms,
+        ]
+        .into_iter()
+        .flatten()
+        {
             if !allowed.contains(&alg) {
                 return Err(JwtError::JwkAlgorithmNotSupported(
                     jsonwebtoken::errors::ErrorKind::InvalidAlgorithm.into(),
@@ -225,15 +252,22 @@ impl JwtAuthRuntime {
             }
         }
 
+        // The issuers and the audiences of a provider replace the ones of the router.
+        let issuers = provider.issuers.as_ref().or(self.config.issuers.as_ref());
+        let audiences = provider
+            .audiences
+            .as_ref()
+            .or(self.config.audiences.as_ref());
+
         let mut validation = Validation::new(alg);
 
         // This only validates the existence of the claim, it does not validate the values, we'll do it after decoding.
-        if let Some(iss) = &self.config.issuers {
+        if let Some(iss) = issuers {
             validation.set_issuer(iss);
         }
 
         // This only validates the existence of the claim, it does not validate the values, we'll do it after decoding.
-        if let Some(aud) = &self.config.audiences {
+        if let Some(aud) = audiences {
             validation.set_audience(aud);
         }
 
@@ -242,7 +276,7 @@ impl JwtAuthRuntime {
             Err(e) => return Err(JwtError::FailedToDecodeToken(e)),
         };
 
-        match (&self.config.issuers, &token_data.claims.iss) {
+        match (issuers, &token_data.claims.iss) {
             (Some(issuers), Some(token_iss)) if !issuers.contains(token_iss) => {
                 return Err(JwtError::FailedToDecodeToken(
                     jsonwebtoken::errors::ErrorKind::InvalidIssuer.into(),
@@ -256,7 +290,7 @@ impl JwtAuthRuntime {
             _ => {}
         };
 
-        match (&self.config.audiences, &token_data.claims.aud) {
+        match (audiences, &token_data.claims.aud) {
             (Some(audiences), Some(token_aud)) => {
                 let all_valid = match token_aud {
                     Audience::Single(s) => audiences.contains(s),
@@ -1,69 +1,94 @@
 mod cache_state;
+mod config_reload;
 mod consts;
 pub mod error;
+mod file_watcher;
 mod http_utils;
 mod jwt;
 pub mod pipeline;
+mod plan_cache;
 pub mod plugins;
 mod schema_state;
 mod shared_state;
+mod shutdown;
 mod storage;
 mod supergraph;
 pub mod telemetry;
+pub mod testing;
 mod utils;
+mod validate_config;
 
 use std::ops::ControlFlow;
 use std::sync::Arc;
-use tracing::error;
+use std::time::{Instant, SystemTime};
+use tracing::{error, Span};
 
 use crate::{
     consts::ROUTER_VERSION,
     error::RouterInitError,
     http_utils::{
+        admin::{supergraph_push_handler, supergraph_rollback_handler},
+        cache::{
+            entity_cache_purge_handler, plan_cache_clear_handler, response_cache_purge_handler,
+        },
+        diagnostics::diagnostics_handler,
         landing_page::landing_page_handler,
         probes::{health_check_handler, readiness_check_handler},
+        readiness::ReadinessChecksTask,
     },
     jwt::JwtAuthRuntime,
     pipeline::{
         active_subscriptions::ActiveSubscriptions,
+        apollo_usage_reporting::ApolloUsageAgent,
         error::handle_pipeline_error,
         graphql_request_handler,
         header::ResponseMode,
         http_callback::handler,
         long_lived_client_limit::LongLivedClientLimitService,
+        mcp::mcp_handler,
         persisted_documents::PersistedDocumentsRuntime,
+        rate_limit::RateLimitHeaders,
         request_extensions::{
             read_graphql_operation_metric_identity, read_graphql_response_metric_status,
             write_graphql_response_metric_status,
         },
+        request_id::{read_request_id, RequestId},
         timeout::handle_timeout,
         usage_reporting::init_hive_usage_agent,
-        validation::{
-            max_aliases_rule::MaxAliasesRule, max_depth_rule::MaxDepthRule,
-            max_directives_rule::MaxDirectivesRule,
-        },
         websocket_server::ws_index,
     },
-    plugins::plugins_service::PluginService,
-    storage::StorageManager,
+    plugins::{plugin_storage_backend::plugin_storage_from_config, plugins_service::PluginService},
+    storage::{redis_connections::RedisConnections, StorageManager},
     telemetry::{HeaderExtractor, PrometheusAttached},
 };
 
 use crate::cache_state::register_cache_size_observers;
+use crate::config_reload::ConfigWatcherTask;
+#[cfg(unix)]
+use crate::config_reload::SighupReloadTask;
+pub use crate::config_reload::{reload_router_config, ConfigReloadError};
 pub use crate::plugins::registry::PluginRegistry;
-pub use crate::{schema_state::SchemaState, shared_state::RouterSharedState};
+use crate::shutdown::{server_shutdown_timeout, ShutdownSignalTask};
+pub use crate::validate_config::validate_router_config;
+pub use crate::{
+    schema_state::{SchemaState, SupergraphRefreshOutcome},
+    shared_state::RouterSharedState,
+};
 pub use arc_swap::ArcSwap;
 pub use async_trait::async_trait;
 pub use dashmap::DashMap;
 pub use graphql_tools;
-use graphql_tools::validation::rules::default_rules_validation_plan;
 pub use hive_router_config::humantime_serde;
-use hive_router_config::{load_config, subscriptions::CallbackConfig, HiveRouterConfig};
+use hive_router_config::{
+    config_file_path, load_config, subscriptions::CallbackConfig, HiveRouterConfig,
+};
 pub use hive_router_internal::background_tasks;
 use hive_router_internal::background_tasks::{BackgroundTask, CancellationToken};
 use hive_router_internal::telemetry::{
+    metrics::{plugin_metrics::PluginMetrics, plugin_storage_metrics::PluginStorageMetrics},
     otel::tracing_opentelemetry::OpenTelemetrySpanExt,
-    traces::spans::http_request::HttpServerRequestSpan, TelemetryContext,
+    traces::spans::http_request::HttpServerRequestSpan,
+    TelemetryContext,
 };
 pub use hive_router_internal::BoxError;
 use hive_router_internal::{
@@ -72,6 +97,11 @@ use hive_router_internal::{
 pub use hive_router_plan_executor::execution::plan::PlanExecutionOutput;
 pub use hive_router_plan_executor::executors::http::SubgraphHttpResponse;
 use hive_router_plan_executor::headers::response::ResponseHeaderSink;
+use hive_router_plan_executor::hooks::on_http_response::handle_http_response_with_plugins;
+use hive_router_plan_executor::plugin_context::PluginContext;
+use hive_router_plan_executor::plugin_routes::PluginRoute;
+use hive_router_plan_executor::request_context::RequestContextExt;
+use hive_router_plan_executor::response::error_status_codes::ErrorStatusCodes;
 pub use hive_router_plan_executor::response::graphql_error::GraphQLError;
 pub use hive_router_query_planner as query_planner;
 pub use http;
@@ -95,31 +125,104 @@ static LABORATORY_HTML: &str = include_str!(concat!(env!("OUT_DIR"), "/laborator
 #[cfg(feature = "graphiql")]
 static LABORATORY_HTML: &str = include_str!("../static/graphiql.html");
 
-struct CallbackServer(std::sync::Mutex<Option<ntex::server::Server>>);
+/// A server running next to the main server, like the one of the HTTP callbacks,
+/// stopped with the background tasks.
+struct DedicatedServer {
+    id: &'static str,
+    server: std::sync::Mutex<Option<ntex::server::Server>>,
+}
 
-impl From<ntex::server::Server> for CallbackServer {
-    fn from(server: ntex::server::Server) -> Self {
-        Self(std::sync::Mutex::new(Some(server)))
+impl DedicatedServer {
+    fn new(id: &'static str, server: ntex::server::Server) -> Self {
+        Self {
+            id,
+            server: std::sync::Mutex::new(Some(server)),
+        }
     }
 }
 
 #[async_trait]
-impl BackgroundTask for CallbackServer {
+impl BackgroundTask for DedicatedServer {
     fn id(&self) -> &str {
-        "callback_server"
+        self.id
     }
 
     async fn run(&self, token: CancellationToken) {
         token.cancelled().await;
         // only poisoned if a thread panicked while holding the lock; since the only
         // operation inside is .take(), that can't happen
-        let server = self.0.lock().unwrap().take();
+        let server = self.server.lock().unwrap().take();
         if let Some(server) = server {
             server.stop(true).await;
         }
     }
 }
 
+/// Starts the dedicated servers of the administration endpoints and of the health probes,
+/// when `admin.listen` and `http.probes_listen` are set,
+/// and returns the paths left to the main server.
+pub fn start_dedicated_admin_servers(
+    router_config: &HiveRouterConfig,
+    paths: RouterPaths,
+    shared_state: &Arc<RouterSharedState>,
+    schema_state: &Arc<SchemaState>,
+    bg_tasks_manager: &mut background_tasks::BackgroundTasksManager,
+) -> Result<RouterPaths, RouterInitError> {
+    let mut main_paths = paths.clone();
+
+    if let Some(listen) = router_config.admin.listen {
+        let admin_addr = listen.to_string();
+        let admin_paths = paths.clone();
+        let admin_shared_state = shared_state.clone();
+        let admin_schema_state = schema_state.clone();
+        let admin_server = web::HttpServer::new(async move || {
+            let admin_paths = admin_paths.clone();
+            web::App::new()
+                .middleware(PluginService::new(admin_paths.clone(), None))
+                .state(admin_shared_state.clone())
+                .state(admin_schema_state.clone())
+                .state(admin_shared_state.telemetry_context.clone())
+                .configure(move |m| add_admin_handlers(m, &admin_paths))
+        })
+        .workers(1)
+        // stopped with the background tasks, once the main server is drained
+        .disable_signals()
+        .bind(&admin_addr)
+        .map_err(|err| RouterInitError::AdminServerBindError(admin_addr.clone(), err))?
+        .run();
+
+        info!("admin endpoints served on {}", admin_addr);
+        bg_tasks_manager.register_task(DedicatedServer::new("admin_server", admin_server));
+        main_paths = main_paths.without_admin();
+    }
+
+    if let Some(listen) = router_config.http.probes_listen {
+        let probes_addr = listen.to_string();
+        let probes_paths = paths.clone();
+        let probes_shared_state = shared_state.clone();
+        let probes_schema_state = schema_state.clone();
+        let probes_server = web::HttpServer::new(async move || {
+            let probes_paths = probes_paths.clone();
+            web::App::new()
+                .state(probes_shared_state.clone())
+                .state(probes_schema_state.clone())
+                .configure(move |m| add_probe_handlers(m, &probes_paths))
+        })
+        .workers(1)
+        // stopped with the background tasks, for `/readiness` to report the drain of the main server
+        .disable_signals()
+        .bind(&probes_addr)
+        .map_err(|err| RouterInitError::ProbesServerBindError(probes_addr.clone(), err))?
+        .run();
+
+        info!("health probes served on {}", probes_addr);
+        bg_tasks_manager.register_task(DedicatedServer::new("probes_server", probes_server));
+        main_paths = main_paths.without_probes();
+    }
+
+    Ok(main_paths)
+}
+
 async fn graphql_endpoint_handler(
     mut request: HttpRequest,
     body_stream: web::types::Payload,
@@ -131,9 +234,46 @@ async fn graphql_endpoint_handler(
         .metrics
         .http_server
         .capture_request(&request);
+    let access_log_sink = app_state
+        .access_log
+        .as_ref()
+        .and_then(|access_log| access_log.sample());
+    let received_at = SystemTime::now();
+    let started_at = Instant::now();
+    if let Some(access_log_sink) = access_log_sink.as_ref() {
+        request.extensions_mut().insert(access_log_sink.clone());
+    }
+    let reloadable = app_state.reloadable.load_full();
+    let request_id_config = &reloadable.router_config.request_id;
+    let request_id = RequestId::resolve(&request, request_id_config);
+    let log_span = match request_id.as_ref() {
+        Some(request_id) => {
+            request.extensions_mut().insert(request_id.clone());
+            telemetry::request_log_span(request_id.as_str())
+        }
+        None => Span::none(),
+    };
 
-    let response =
-        graphql_endpoint_dispatch(&mut request, body_stream, schema_state, app_state.clone()).await;
+    let mut response =
+        graphql_endpoint_dispatch(&mut request, body_stream, schema_state, app_state.clone())
+            .instrument(log_span)
+            .await;
+
+    if let Some(request_id) = request_id.as_ref() {
+        request_id.write_to(response.headers_mut(), request_id_config);
+    }
+
+    if let (Some(access_log), Some(access_log_sink)) =
+        (app_state.access_log.as_ref(), access_log_sink.as_ref())
+    {
+        access_log.write(
+            &request,
+            &response,
+            received_at,
+            started_at.elapsed(),
+            access_log_sink,
+        );
+    }
 
     let graphql_operation = read_graphql_operation_metric_identity(&request);
     let graphql_operation_name = graphql_operation
@@ -174,6 +314,9 @@ async fn graphql_endpoint_dispatch(
             .ip_header,
     );
     let _ = root_http_request_span.set_parent(parent_ctx);
+    if let Some(request_id) = read_request_id(request) {
+        root_http_request_span.record_request_id(request_id.as_str());
+    }
 
     let response_header_sink = ResponseHeaderSink::default();
 
@@ -212,8 +355,12 @@ async fn graphql_endpoint_dispatch(
             error!(error = %err, "Failed to apply response header rules to the outgoing client response");
         }
 
+        if let Some(rate_limit_headers) = request.extensions().get::<RateLimitHeaders>() {
+            rate_limit_headers.write_to(response.headers_mut());
+        }
+
         // Apply CORS headers to the final response if CORS is configured.
-        if let Some(cors) = app_state.cors_runtime.as_ref() {
+        if let Some(cors) = app_state.reloadable.load().cors_runtime.as_ref() {
             cors.set_headers(request, response.headers_mut());
         }
 
@@ -241,6 +388,26 @@ async fn graphql_endpoint_dispatch(
             };
         }
 
+        // Plugins see the final response, once nothing else changes it
+        let plugin_context = request.extensions().get::<Arc<PluginContext>>().cloned();
+        if let (Some(plugins), Some(plugin_context), Ok(request_context)) = (
+            app_state.plugins.as_ref(),
+            plugin_context.as_ref(),
+            request.read_request_context(),
+        ) {
+            response = handle_http_response_with_plugins(
+                plugins,
+                plugin_context,
+                &request_context,
+                response,
+            )
+            .await;
+        }
+
+        if let Some(plugin_context) = plugin_context {
+            root_http_request_span.record_plugin_attributes(plugin_context.take_span_attributes());
+        }
+
         root_http_request_span.record_response(&response);
 
         response
@@ -259,7 +426,26 @@ pub async fn router_entrypoint(plugin_registry: PluginRegistry) -> Result<(), Ro
     }
 
     let config_path = std::env::var("ROUTER_CONFIG_FILE_PATH").ok();
-    let router_config = load_config(config_path)?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--validate-config") {
+        let check_reachability = args.iter().any(|arg| arg == "--check-reachability");
+        let result = match load_config(config_path) {
+            Ok(router_config) => {
+                validate_router_config(router_config, plugin_registry, check_reachability).await
+            }
+            Err(err) => Err(err.into()),
+        };
+        match result {
+            Ok(()) => println!("config is valid"),
+            Err(err) => {
+                eprintln!("config is invalid: {err}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    let router_config = load_config(config_path.clone())?;
     let telemetry = telemetry::Telemetry::init_global(&router_config)?;
     let prometheus = telemetry
         .prometheus
@@ -270,6 +456,13 @@ pub async fn router_entrypoint(plugin_registry: PluginRegistry) -> Result<(), Ro
     let graphql_path = router_config.graphql_path().to_string();
     let websocket_path = router_config.websocket_path().map(|p| p.to_string());
     let callback_conf = router_config.callback_conf().cloned();
+    let admin_supergraph_path = router_config.admin_supergraph_path().map(|p| p.to_string());
+    let admin_diagnostics_path = router_config
+        .admin_diagnostics_path()
+        .map(|p| p.to_string());
+    let admin_cache_path = router_config.admin_cache_path().map(|p| p.to_string());
+    let mcp_path = router_config.mcp_path().map(|p| p.to_string());
+    let mcp_conf = router_config.mcp.clone();
     let workers = router_config.workers();
     let mut bg_tasks_manager = background_tasks::BackgroundTasksManager::new();
     let (shared_state, schema_state) = configure_app_from_config(
@@ -280,7 +473,29 @@ pub async fn router_entrypoint(plugin_registry: PluginRegistry) -> Result<(), Ro
     )
     .await?;
 
+    if shared_state.router_config.hot_reload.watch {
+        match config_file_path(config_path.as_deref()) {
+            Some(config_file_path) => match ConfigWatcherTask::new(
+                config_file_path,
+                shared_state.clone(),
+                schema_state.clone(),
+            ) {
+                Ok(config_watcher) => bg_tasks_manager.register_task(config_watcher),
+                Err(err) => warn!(error = %err, "failed to watch the config file"),
+            },
+            None => warn!("'hot_reload.watch' is enabled, but the router has no config file"),
+        }
+    }
+
+    #[cfg(unix)]
+    bg_tasks_manager.register_task(SighupReloadTask {
+        config_path,
+        shared_state: shared_state.clone(),
+        schema_state: schema_state.clone(),
+    });
+
     let shared_state_clone = shared_state.clone();
+    let schema_state_clone = schema_state.clone();
     let callback_subscriptions_for_handler = schema_state.callback_subscriptions.clone();
 
     // when `listen` is set, the callback route lives on a dedicated server bound to that address
@@ -311,12 +526,14 @@ pub async fn router_entrypoint(plugin_registry: PluginRegistry) -> Result<(), Ro
                 );
                 cb_server_builder = cb_server_builder.workers(workers.get());
             }
+            // stopped with the background tasks, for the subscriptions to be drained with their callbacks
             let cb_server = cb_server_builder
+                .disable_signals()
                 .bind(&cb_addr)
                 .map_err(|err| RouterInitError::HttpCallbackServerBindError(cb_addr, err))?
                 .run();
 
-            bg_tasks_manager.register_task(CallbackServer::from(cb_server));
+            bg_tasks_manager.register_task(DedicatedServer::new("callback_server", cb_server));
 
             None
         }
@@ -325,8 +542,53 @@ pub async fn router_entrypoint(plugin_registry: PluginRegistry) -> Result<(), Ro
     };
 
     // after callback config check because there we decide if callback_path should be set
-    let paths = RouterPaths::new(graphql_path.clone(), websocket_path, callback_path);
+    let paths = RouterPaths::new(
+        graphql_path.clone(),
+        websocket_path,
+        callback_path,
+        admin_supergraph_path,
+        admin_diagnostics_path,
+        admin_cache_path,
+        mcp_path,
+    )
+    .with_plugin_routes(shared_state.plugin_routes.clone());
     paths.detect_conflicts(&prometheus)?;
+    let paths = start_dedicated_admin_servers(
+        &shared_state.router_config,
+        paths,
+        &shared_state,
+        &schema_state,
+        &mut bg_tasks_manager,
+    )?;
+
+    // when `listen` is set, the MCP endpoint lives on a dedicated server bound to that address
+    if let (true, Some(listen)) = (mcp_conf.enabled, mcp_conf.listen) {
+        let mcp_addr = listen.to_string();
+        let mcp_path = mcp_conf.path.to_string();
+        let mcp_paths = paths.clone();
+        let mcp_shared_state = shared_state.clone();
+        let mcp_schema_state = schema_state.clone();
+        let mut mcp_server_builder = web::HttpServer::new(async move || {
+            let mcp_path = mcp_path.clone();
+            web::App::new()
+                .middleware(PluginService::new(mcp_paths.clone(), None))
+                .state(mcp_shared_state.clone())
+                .state(mcp_schema_state.clone())
+                .state(mcp_shared_state.telemetry_context.clone())
+                .configure(move |m| add_mcp_handler(m, &mcp_path))
+        });
+        if let Some(workers) = workers {
+            info!("configuring MCP server with {} worker(s)", workers);
+            mcp_server_builder = mcp_server_builder.workers(workers.get());
+        }
+        let mcp_server = mcp_server_builder
+            .disable_signals()
+            .bind(&mcp_addr)
+            .map_err(|err| RouterInitError::McpServerBindError(mcp_addr, err))?
+            .run();
+
+        bg_tasks_manager.register_task(DedicatedServer::new("mcp_server", mcp_server));
+    }
 
     let graphql_path = graphql_path.to_string();
     let long_lived_client_limit_service =
@@ -389,22 +651,47 @@ pub async fn router_entrypoint(plugin_registry: PluginRegistry) -> Result<(), Ro
         .tls
         .as_ref();
 
-    let maybe_error = if let Some(tls_config) = tls_config {
-        let rustls_config = tls::build_rustls_config(tls_config)?;
-        server.bind_rustls(&addr, &rustls_config)
-    } else {
-        server.bind(&addr)
+    let rustls_config = tls_config
+        .map(|tls_config| tls::build_server_rustls_config(tls_config, &mut bg_tasks_manager))
+        .transpose()?;
+    let addresses = std::iter::once(addr).chain(
+        shared_state_clone
+            .router_config
+            .http
+            .additional_listen
+            .iter()
+            .map(|listen| listen.to_string()),
+    );
+    for addr in addresses {
+        server = if let Some(rustls_config) = rustls_config.as_ref() {
+            server.bind_rustls(&addr, rustls_config)
+        } else {
+            server.bind(&addr)
+        }
+        .map_err(|err| RouterInitError::HttpServerBindError(addr.clone(), err))?;
     }
-    .map_err(|err| RouterInitError::HttpServerBindError(addr.to_string(), err))?
-    .run()
-    .await
-    .map_err(RouterInitError::HttpServerStartError);
+
+    let drain_timeout = shared_state_clone.router_config.http.drain_timeout;
+    // the shutdown signals are handled by the router, to drain the requests before the server stops
+    let server = server
+        .disable_signals()
+        .shutdown_timeout(server_shutdown_timeout(drain_timeout))
+        .run();
+    bg_tasks_manager.register_task(ShutdownSignalTask::new(
+        server.clone(),
+        schema_state_clone.clone(),
+        shared_state_clone.active_subscriptions.clone(),
+        drain_timeout,
+    ));
+
+    let maybe_error = server.await.map_err(RouterInitError::HttpServerStartError);
 
     info!("server stopped, clearing background tasks");
     bg_tasks_manager.shutdown();
-    telemetry.graceful_shutdown().await;
-
+    schema_state_clone.persist_plan_cache();
     invoke_shutdown_hooks(&shared_state_clone).await;
+    // flushed last, for the spans and metrics of the shutdown hooks to be exported
+    telemetry.graceful_shutdown().await;
 
     maybe_error
 }
@@ -435,41 +722,39 @@ pub async fn configure_app_from_config(
         }
         _ => None,
     };
-    let plugins_arc = plugin_registry.initialize_plugins(&router_config, bg_tasks_manager)?;
-
-    let active_subscriptions =
-        ActiveSubscriptions::new(router_config.subscriptions.broadcast_capacity);
+    let apollo_usage_agent = match router_config.telemetry.apollo.enabled {
+        true => Some(ApolloUsageAgent::init(
+            bg_tasks_manager,
+            &router_config.telemetry.apollo,
+        )?),
+        false => None,
+    };
+    let mut error_status_codes = ErrorStatusCodes::from_config(&router_config.errors)?;
+    let mut plugin_routes = Vec::new();
+    let plugin_metrics = PluginMetrics::new(telemetry_context.meter());
+    let redis_connections = Arc::new(RedisConnections::default());
+    let plugin_storage = plugin_storage_from_config(
+        &router_config.plugin_storage,
+        &redis_connections,
+        PluginStorageMetrics::new(telemetry_context.meter()),
+    )?;
+    let plugins_arc = plugin_registry.initialize_plugins(
+        &router_config,
+        bg_tasks_manager,
+        &mut error_status_codes,
+        &mut plugin_routes,
+        &plugin_metrics,
+        &plugin_storage,
+    )?;
+
+    let active_subscriptions = ActiveSubscriptions::new(
+        router_config.subscriptions.broadcast_capacity,
+        router_config.subscriptions.max_active_subscriptions,
+    );
     let storage_manager = Arc::new(StorageManager::new(&router_config.storages)?);
     let router_config_arc = Arc::new(router_config);
     let telemetry_context_arc = Arc::new(telemetry_context);
 
-    let schema_state = SchemaState::new_from_config(
-        bg_tasks_manager,
-        telemetry_context_arc.clone(),
-        router_config_arc.clone(),
-        plugins_arc.clone(),
-        active_subscriptions.clone(),
-        storage_manager.clone(),
-    )
-    .await?;
-    let schema_state_arc = Arc::new(schema_state);
-
-    let mut validation_plan = default_rules_validation_plan();
-    if let Some(max_depth_config) = &router_config_arc.limits.max_depth {
-        validation_plan.add_rule(Box::new(MaxDepthRule {
-            config: max_depth_config.clone(),
-        }));
-    }
-    if let Some(max_directives_config) = &router_config_arc.limits.max_directives {
-        validation_plan.add_rule(Box::new(MaxDirectivesRule {
-            config: max_directives_config.clone(),
-        }));
-    }
-    if let Some(max_aliases_config) = &router_config_arc.limits.max_aliases {
-        validation_plan.add_rule(Box::new(MaxAliasesRule {
-            config: max_aliases_config.clone(),
-        }));
-    }
     let persisted_documents_runtime = PersistedDocumentsRuntime::init(
         &router_config_arc.persisted_documents,
         &router_config_arc.http.graphql_endpoint,
@@ -490,19 +775,51 @@ pub async fn configure_app_from_config(
         ));
     }
 
+    let schema_state = SchemaState::new_from_config(
+        bg_tasks_manager,
+        telemetry_context_arc.clone(),
+        router_config_arc.clone(),
+        plugins_arc.clone(),
+        active_subscriptions.clone(),
+        storage_manager.clone(),
+        persisted_documents_runtime
+            .persisted_document_resolver
+            .clone(),
+    )
+    .await?;
+    let schema_state_arc = Arc::new(schema_state);
+
     let metrics_enabled = router_config_arc.telemetry.metrics.is_enabled();
     let shared_state = Arc::new(RouterSharedState::new(
         router_config_arc,
         persisted_documents_runtime,
         jwt_runtime,
         hive_usage_agent,
-        validation_plan,
+        apollo_usage_agent,
         telemetry_context_arc.clone(),
         plugins_arc,
+        error_status_codes,
+        plugin_routes,
         active_subscriptions.clone(),
         storage_manager,
+        redis_connections,
     )?);
 
+    if shared_state.readiness_checks.is_some() {
+        bg_tasks_manager.register_task(ReadinessChecksTask {
+            shared_state: shared_state.clone(),
+            schema_state: schema_state_arc.clone(),
+        });
+    }
+
+    if let Some(writer_task) = shared_state
+        .access_log
+        .as_ref()
+        .and_then(|access_log| access_log.take_writer_task())
+    {
+        bg_tasks_manager.register_task(writer_task);
+    }
+
     if metrics_enabled {
         register_cache_size_observers(
             telemetry_context_arc,
@@ -519,21 +836,61 @@ pub struct RouterPaths {
     pub graphql: String,
     websocket: Option<String>,
     callback: Option<String>,
+    admin_supergraph: Option<String>,
+    admin_diagnostics: Option<String>,
+    admin_cache: Option<String>,
+    mcp: Option<String>,
     pub health: String,
     pub readiness: String,
+    probes: bool,
+    plugin_routes: Vec<PluginRoute>,
 }
 
 impl RouterPaths {
-    pub fn new(graphql: String, websocket: Option<String>, callback: Option<String>) -> Self {
+    pub fn new(
+        graphql: String,
+        websocket: Option<String>,
+        callback: Option<String>,
+        admin_supergraph: Option<String>,
+        admin_diagnostics: Option<String>,
+        admin_cache: Option<String>,
+        mcp: Option<String>,
+    ) -> Self {
         RouterPaths {
             graphql,
             websocket,
             callback,
+            admin_supergraph,
+            admin_diagnostics,
+            admin_cache,
+            mcp,
             health: "/health".to_string(),
             readiness: "/readiness".to_string(),
+            probes: true,
+            plugin_routes: Vec::new(),
         }
     }
 
+    /// The paths served by the main server when the health probes have a dedicated server.
+    pub fn without_probes(mut self) -> Self {
+        self.probes = false;
+        self
+    }
+
+    /// The paths served by the main server when the admin endpoints have a dedicated server.
+    pub fn without_admin(mut self) -> Self {
+        self.admin_supergraph = None;
+        self.admin_diagnostics = None;
+        self.admin_cache = None;
+        self
+    }
+
+    /// The routes registered by the plugins, served next to the endpoints of the router.
+    pub fn with_plugin_routes(mut self, plugin_routes: Vec<PluginRoute>) -> Self {
+        self.plugin_routes = plugin_routes;
+        self
+    }
+
     pub fn detect_conflicts(
         &self,
         prometheus: &Option<PrometheusAttached>,
@@ -556,10 +913,31 @@ impl RouterPaths {
             paths.push(("callback", cb));
         }
 
+        if let Some(admin_supergraph) = self.admin_supergraph.as_deref() {
+            paths.push(("admin supergraph", admin_supergraph));
+        }
+
+        if let Some(admin_diagnostics) = self.admin_diagnostics.as_deref() {
+            paths.push(("admin diagnostics", admin_diagnostics));
+        }
+
+        if let Some(admin_cache) = self.admin_cache.as_deref() {
+            paths.push(("admin cache", admin_cache));
+        }
+
+        if let Some(mcp) = self.mcp.as_deref() {
+            paths.push(("mcp", mcp));
+        }
+
         if let Some(prom) = prometheus {
             paths.push(("prometheus", prom.endpoint.as_str()));
         }
 
+        // plugins can register several methods on the same path
+        for route in &self.plugin_routes {
+            paths.push(("plugin route", route.path()));
+        }
+
         for (name_a, path_a) in &paths {
             let conflict = paths
                 .iter()
@@ -586,6 +964,53 @@ pub fn add_callback_handler(cfg: &mut web::ServiceConfig, callback_path: &str) {
     cfg.route(&callback_route, web::post().to(handler));
 }
 
+pub fn add_mcp_handler(cfg: &mut web::ServiceConfig, mcp_path: &str) {
+    cfg.route(mcp_path, web::to(mcp_handler));
+}
+
+pub fn add_probe_handlers(cfg: &mut web::ServiceConfig, paths: &RouterPaths) {
+    cfg.route(paths.health.as_str(), web::to(health_check_handler))
+        .route(paths.readiness.as_str(), web::to(readiness_check_handler));
+}
+
+/// Registers the enabled administration endpoints.
+pub fn add_admin_handlers(cfg: &mut web::ServiceConfig, paths: &RouterPaths) {
+    if let Some(admin_supergraph) = &paths.admin_supergraph {
+        let rollback_path = format!("{}/rollback", admin_supergraph.trim_end_matches('/'));
+        cfg.route(
+            admin_supergraph.as_str(),
+            web::post().to(supergraph_push_handler),
+        )
+        .route(
+            rollback_path.as_str(),
+            web::post().to(supergraph_rollback_handler),
+        );
+    }
+
+    if let Some(admin_diagnostics) = &paths.admin_diagnostics {
+        cfg.route(
+            admin_diagnostics.as_str(),
+            web::get().to(diagnostics_handler),
+        );
+    }
+
+    if let Some(admin_cache) = &paths.admin_cache {
+        let admin_cache = admin_cache.trim_end_matches('/');
+        cfg.route(
+            format!("{admin_cache}/response").as_str(),
+            web::post().to(response_cache_purge_handler),
+        )
+        .route(
+            format!("{admin_cache}/entity").as_str(),
+            web::post().to(entity_cache_purge_handler),
+        )
+        .route(
+            format!("{admin_cache}/plan").as_str(),
+            web::post().to(plan_cache_clear_handler),
+        );
+    }
+}
+
 pub fn configure_ntex_app(
     cfg: &mut web::ServiceConfig,
     paths: &RouterPaths,
@@ -607,9 +1032,30 @@ pub fn configure_ntex_app(
         );
     }
 
-    cfg.route(paths.graphql.as_str(), web::to(graphql_endpoint_handler))
-        .route(paths.health.as_str(), web::to(health_check_handler))
-        .route(paths.readiness.as_str(), web::to(readiness_check_handler));
+    cfg.route(paths.graphql.as_str(), web::to(graphql_endpoint_handler));
+
+    if paths.probes {
+        add_probe_handlers(cfg, paths);
+    }
+
+    for route in &paths.plugin_routes {
+        let handler_route = route.clone();
+        cfg.route(
+            route.path(),
+            web::method(route.method().clone()).to(
+                move |request: HttpRequest, body: ntex::util::Bytes| {
+                    let route = handler_route.clone();
+                    async move { route.handle(request, body).await }
+                },
+            ),
+        );
+    }
+
+    add_admin_handlers(cfg, paths);
+
+    if let Some(mcp) = &paths.mcp {
+        add_mcp_handler(cfg, mcp);
+    }
 
     if let Some(prom) = prometheus {
         let registry = prom.registry;
@@ -0,0 +1,437 @@
+use std::{
+    collections::BTreeSet,
+    io::{LineWriter, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use hive_router_config::access_log::{
+    AccessLogConfig, AccessLogField, AccessLogFormat, AccessLogOutput,
+};
+use hive_router_internal::background_tasks::{BackgroundTask, CancellationToken};
+use hive_router_query_planner::planner::plan_nodes::{PlanNode, QueryPlan};
+use ntex::web::{HttpRequest, HttpResponse};
+use rand::Rng;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::pipeline::{
+    request_extensions::read_graphql_operation_metric_identity, request_id::read_request_id,
+};
+
+/// The number of lines waiting to be written, before new lines are dropped.
+const ACCESS_LOG_CHANNEL_CAPACITY: usize = 10_000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AccessLogError {
+    #[error("failed to open the access log file '{0}': {1}")]
+    OpenFile(String, std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    Hit,
+    Miss,
+}
+
+impl CacheStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CacheStatus::Hit => "hit",
+            CacheStatus::Miss => "miss",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct AccessLogDetails {
+    client_name: Option<String>,
+    client_version: Option<String>,
+    subgraphs: Vec<String>,
+    cache_status: Option<CacheStatus>,
+}
+
+/// Collects the details of a request sampled by the access log, while it is handled.
+/// Stored in the extensions of the request.
+#[derive(Debug, Clone, Default)]
+pub struct AccessLogSink(Arc<Mutex<AccessLogDetails>>);
+
+impl AccessLogSink {
+    pub fn record_client(&self, name: Option<&str>, version: Option<&str>) {
+        let mut details = self.lock();
+        details.client_name = name.map(str::to_string);
+        details.client_version = version.map(str::to_string);
+    }
+
+    /// Records the subgraphs fetched by the query plan, once it is executed.
+    pub fn record_subgraphs(&self, query_plan: &QueryPlan) {
+        let mut subgraphs = BTreeSet::new();
+        if let Some(node) = query_plan.node.as_ref() {
+            collect_subgraphs(node, &mut subgraphs);
+        }
+        self.lock().subgraphs = subgraphs.into_iter().map(str::to_string).collect();
+    }
+
+    pub fn record_cache_status(&self, cache_status: CacheStatus) {
+        self.lock().cache_status = Some(cache_status);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, AccessLogDetails> {
+        match self.0.lock() {
+            Ok(details) => details,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+fn collect_subgraphs<'a>(node: &'a PlanNode, subgraphs: &mut BTreeSet<&'a str>) {
+    match node {
+        PlanNode::Fetch(fetch) => {
+            subgraphs.insert(&fetch.service_name);
+        }
+        PlanNode::BatchFetch(batch_fetch) => {
+            subgraphs.insert(&batch_fetch.service_name);
+        }
+        PlanNode::Subscription(subscription) => {
+            subgraphs.insert(&subscription.primary.service_name);
+        }
+        PlanNode::Flatten(flatten) => collect_subgraphs(&flatten.node, subgraphs),
+        PlanNode::Sequence(sequence) => {
+            for node in sequence.nodes.iter() {
+                collect_subgraphs(node, subgraphs);
+            }
+        }
+        PlanNode::Parallel(parallel) => {
+            for node in parallel.nodes.iter() {
+                collect_subgraphs(node, subgraphs);
+            }
+        }
+        PlanNode::Condition(condition) => {
+            for node in [&condition.if_clause, &condition.else_clause]
+                .into_iter()
+                .flatten()
+            {
+                collect_subgraphs(node, subgraphs);
+            }
+        }
+        PlanNode::Defer(defer) => {
+            let deferred = defer.deferred.iter().map(|deferred| &deferred.node);
+            for node in std::iter::once(&defer.primary.node)
+                .chain(deferred)
+                .flatten()
+            {
+                collect_subgraphs(node, subgraphs);
+            }
+        }
+    }
+}
+
+/// A request handled by the router, as written to the access log.
+struct AccessLogEntry<'a> {
+    received_at: SystemTime,
+    method: &'a str,
+    path: &'a str,
+    status: u16,
+    duration: Duration,
+    operation_name: Option<&'a str>,
+    operation_type: Option<&'a str>,
+    request_id: Option<&'a str>,
+    details: &'a AccessLogDetails,
+}
+
+impl AccessLogEntry<'_> {
+    fn duration_ms(&self) -> f64 {
+        (self.duration.as_secs_f64() * 1_000_000.0).round() / 1000.0
+    }
+
+    fn json_value(&self, field: AccessLogField) -> Value {
+        let optional = |value: Option<&str>| value.map_or(Value::Null, Value::from);
+        match field {
+            AccessLogField::Timestamp => {
+                Value::from(humantime::format_rfc3339_millis(self.received_at).to_string())
+            }
+            AccessLogField::Method => Value::from(self.method),
+            AccessLogField::Path => Value::from(self.path),
+            AccessLogField::Status => Value::from(self.status),
+            AccessLogField::Duration => Value::from(self.duration_ms()),
+            AccessLogField::OperationName => optional(self.operation_name),
+            AccessLogField::OperationType => optional(self.operation_type),
+            AccessLogField::ClientName => optional(self.details.client_name.as_deref()),
+            AccessLogField::ClientVersion => optional(self.details.client_version.as_deref()),
+            AccessLogField::Subgraphs => Value::from(self.details.subgraphs.clone()),
+            AccessLogField::CacheStatus => {
+                optional(self.details.cache_status.map(|status| status.as_str()))
+            }
+            AccessLogField::RequestId => optional(self.request_id),
+        }
+    }
+
+    fn apache_value(&self, field: AccessLogField) -> String {
+        // the values chosen by the clients are quoted, for a space not to shift the other fields
+        let quoted = |value: Option<&str>| {
+            value.map_or("-".to_string(), |value| {
+                format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+            })
+        };
+        let plain = |value: Option<&str>| value.unwrap_or("-").to_string();
+        match field {
+            AccessLogField::Timestamp => {
+                format!("[{}]", humantime::format_rfc3339_millis(self.received_at))
+            }
+            AccessLogField::Method => self.method.to_string(),
+            AccessLogField::Path => self.path.to_string(),
+            AccessLogField::Status => self.status.to_string(),
+            AccessLogField::Duration => format!("{:.3}", self.duration_ms()),
+            AccessLogField::OperationName => quoted(self.operation_name),
+            AccessLogField::OperationType => plain(self.operation_type),
+            AccessLogField::ClientName => quoted(self.details.client_name.as_deref()),
+            AccessLogField::ClientVersion => quoted(self.details.client_version.as_deref()),
+            AccessLogField::Subgraphs => match self.details.subgraphs.is_empty() {
+                true => "-".to_string(),
+                false => self.details.subgraphs.join(","),
+            },
+            AccessLogField::CacheStatus => {
+                plain(self.details.cache_status.map(|status| status.as_str()))
+            }
+            AccessLogField::RequestId => quoted(self.request_id),
+        }
+    }
+
+    fn format(&self, format: AccessLogFormat, fields: &[AccessLogField]) -> String {
+        match format {
+            AccessLogFormat::Json => {
+                // written field by field, for the keys to keep the configured order
+                let entries: Vec<String> = fields
+                    .iter()
+                    .map(|field| {
+                        format!(
+                            "{}:{}",
+                            Value::from(field.as_str()),
+                            self.json_value(*field)
+                        )
+                    })
+                    .collect();
+                format!("{{{}}}", entries.join(","))
+            }
+            AccessLogFormat::Apache => fields
+                .iter()
+                .map(|field| self.apache_value(*field))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// Writes a line per request to the GraphQL endpoint, when `access_log` is enabled.
+/// The lines are written by an [`AccessLogWriterTask`], off the requests.
+pub struct AccessLogRuntime {
+    format: AccessLogFormat,
+    fields: Vec<AccessLogField>,
+    sampling: f64,
+    sender: mpsc::Sender<String>,
+    dropped_lines: Arc<AtomicU64>,
+    writer_task: Mutex<Option<AccessLogWriterTask>>,
+}
+
+impl AccessLogRuntime {
+    pub fn from_config(config: &AccessLogConfig) -> Result<Option<Self>, AccessLogError> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let output: Box<dyn Write + Send> = match &config.output {
+            AccessLogOutput::Stdout => Box::new(std::io::stdout()),
+            AccessLogOutput::Stderr => Box::new(std::io::stderr()),
+            AccessLogOutput::File(path) => Box::new(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path.absolute)
+                    .map_err(|err| AccessLogError::OpenFile(path.relative.clone(), err))?,
+            ),
+        };
+        let (sender, receiver) = mpsc::channel(ACCESS_LOG_CHANNEL_CAPACITY);
+        let dropped_lines = Arc::new(AtomicU64::new(0));
+
+        info!("access log enabled");
+
+        Ok(Some(Self {
+            format: config.format,
+            fields: config.fields.clone(),
+            sampling: config.sampling.as_f64(),
+            sender,
+            dropped_lines: dropped_lines.clone(),
+            writer_task: Mutex::new(Some(AccessLogWriterTask {
+                writer: tokio::sync::Mutex::new(AccessLogWriter {
+                    receiver,
+                    output: LineWriter::new(output),
+                }),
+                dropped_lines,
+            })),
+        }))
+    }
+
+    /// Returns the sink of a request, when it is sampled.
+    pub fn sample(&self) -> Option<AccessLogSink> {
+        rand::rng()
+            .random_bool(self.sampling)
+            .then(AccessLogSink::default)
+    }
+
+    /// Writes the line of a handled request.
+    pub fn write(
+        &self,
+        request: &HttpRequest,
+        response: &HttpResponse,
+        received_at: SystemTime,
+        duration: Duration,
+        sink: &AccessLogSink,
+    ) {
+        let operation = read_graphql_operation_metric_identity(request);
+        let request_id = read_request_id(request);
+        let details = sink.lock();
+        let entry = AccessLogEntry {
+            received_at,
+            method: request.method().as_str(),
+            path: request.path(),
+            status: response.status().as_u16(),
+            duration,
+            operation_name: operation
+                .as_ref()
+                .and_then(|operation| operation.operation_name.as_deref()),
+            operation_type: operation
+                .as_ref()
+                .and_then(|operation| operation.operation_type),
+            request_id: request_id.as_ref().map(|request_id| request_id.as_str()),
+            details: &*details,
+        };
+
+        let line = entry.format(self.format, &self.fields);
+        if self.sender.try_send(line).is_err() {
+            self.dropped_lines.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The task writing the lines, taken once when the router starts.
+    pub fn take_writer_task(&self) -> Option<AccessLogWriterTask> {
+        match self.writer_task.lock() {
+            Ok(mut writer_task) => writer_task.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        }
+    }
+}
+
+struct AccessLogWriter {
+    receiver: mpsc::Receiver<String>,
+    // flushed line by line, for the lines not to interleave with the logs of the router
+    output: LineWriter<Box<dyn Write + Send>>,
+}
+
+impl AccessLogWriter {
+    fn write(&mut self, line: String) {
+        if let Err(err) = self
+            .output
+            .write_all(line.as_bytes())
+            .and_then(|_| self.output.write_all(b"\n"))
+        {
+            warn!(error = %err, "failed to write to the access log");
+        }
+    }
+}
+
+/// Writes the lines of the access log, until the router stops.
+pub struct AccessLogWriterTask {
+    writer: tokio::sync::Mutex<AccessLogWriter>,
+    dropped_lines: Arc<AtomicU64>,
+}
+
+#[async_trait]
+impl BackgroundTask for AccessLogWriterTask {
+    fn id(&self) -> &str {
+        "access-log-writer"
+    }
+
+    async fn run(&self, token: CancellationToken) {
+        let mut writer = self.writer.lock().await;
+        let writer = &mut *writer;
+
+        while let Some(Some(line)) = token.run_until_cancelled(writer.receiver.recv()).await {
+            writer.write(line);
+
+            let dropped_lines = self.dropped_lines.swap(0, Ordering::Relaxed);
+            if dropped_lines > 0 {
+                warn!(
+                    dropped_lines,
+                    "the access log output is too slow, lines were dropped"
+                );
+            }
+        }
+
+        // the lines of the requests handled before the router stopped
+        writer.receiver.close();
+        while let Ok(line) = writer.receiver.try_recv() {
+            writer.write(line);
+        }
+        if let Err(err) = writer.output.flush() {
+            warn!(error = %err, "failed to flush the access log");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use hive_router_config::access_log::{AccessLogField, AccessLogFormat};
+
+    use super::{AccessLogDetails, AccessLogEntry, CacheStatus};
+
+    fn entry(details: &AccessLogDetails) -> AccessLogEntry<'_> {
+        AccessLogEntry {
+            received_at: UNIX_EPOCH + Duration::from_secs(1_800_000_000),
+            method: "POST",
+            path: "/graphql",
+            status: 200,
+            duration: Duration::from_micros(12_345),
+            operation_name: Some("GetUser"),
+            operation_type: Some("query"),
+            request_id: Some("01JAR7M1X4Q7ZC6C6QK6B6W2D0"),
+            details,
+        }
+    }
+
+    #[test]
+    fn formats_the_fields_in_order() {
+        let details = AccessLogDetails {
+            client_name: Some("web \"app\"".to_string()),
+            client_version: None,
+            subgraphs: vec!["accounts".to_string(), "products".to_string()],
+            cache_status: Some(CacheStatus::Miss),
+        };
+        let fields = [
+            AccessLogField::Timestamp,
+            AccessLogField::Status,
+            AccessLogField::Duration,
+            AccessLogField::OperationName,
+            AccessLogField::ClientName,
+            AccessLogField::ClientVersion,
+            AccessLogField::Subgraphs,
+            AccessLogField::CacheStatus,
+            AccessLogField::RequestId,
+        ];
+
+        assert_eq!(
+            entry(&details).format(AccessLogFormat::Json, &fields),
+            r#"{"timestamp":"2027-01-15T08:00:00.000Z","status":200,"duration":12.345,"operation_name":"GetUser","client_name":"web \"app\"","client_version":null,"subgraphs":["accounts","products"],"cache_status":"miss","request_id":"01JAR7M1X4Q7ZC6C6QK6B6W2D0"}"#
+        );
+        assert_eq!(
+            entry(&details).format(AccessLogFormat::Apache, &fields),
+            r#"[2027-01-15T08:00:00.000Z] 200 12.345 "GetUser" "web \"app\"" - accounts,products miss "01JAR7M1X4Q7ZC6C6QK6B6W2D0""#
+        );
+    }
+}
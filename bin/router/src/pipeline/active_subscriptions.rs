@@ -1,4 +1,10 @@
-use std::sync::Arc;
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use bytes::Bytes;
 use dashmap::DashMap;
@@ -24,17 +30,40 @@ pub enum SubscriptionEvent {
 #[derive(Clone)]
 pub struct ActiveSubscriptions {
     map: Arc<DashMap<SubscriptionId, broadcast::Sender<SubscriptionEvent>>>,
-    broadcast_capacity: usize,
+    broadcast_capacity: NonZeroUsize,
+    // the number of subscriptions served to clients, deduplicated ones included
+    client_count: Arc<AtomicUsize>,
+    max_client_subscriptions: Option<NonZeroUsize>,
 }
 
 impl ActiveSubscriptions {
-    pub fn new(broadcast_capacity: usize) -> Self {
+    pub fn new(
+        broadcast_capacity: NonZeroUsize,
+        max_client_subscriptions: Option<NonZeroUsize>,
+    ) -> Self {
         Self {
             map: Arc::new(DashMap::new()),
             broadcast_capacity,
+            client_count: Arc::new(AtomicUsize::new(0)),
+            max_client_subscriptions,
         }
     }
 
+    /// Reserve a slot for a client subscription, to be held for as long as the client is
+    /// subscribed. Returns `None` when the `subscriptions.max_active_subscriptions` limit is reached.
+    pub fn acquire_client_slot(&self) -> Option<ClientSubscriptionSlot> {
+        let limit = self
+            .max_client_subscriptions
+            .map_or(usize::MAX, NonZeroUsize::get);
+        self.client_count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                (current < limit).then_some(current + 1)
+            })
+            .ok()?;
+
+        Some(ClientSubscriptionSlot(self.client_count.clone()))
+    }
+
     /// Register a new active subscription. Returns a producer handle for the upstream pump
     /// and a pre-subscribed receiver for the leader consumer. The pump task owns the handle
     /// for the full lifetime of the upstream stream - when the handle drops (pump done or all
@@ -43,7 +72,7 @@ impl ActiveSubscriptions {
         &self,
         guard: Option<SharedRouterResponseGuard>,
     ) -> (ProducerHandle, broadcast::Receiver<SubscriptionEvent>) {
-        let (sender, receiver) = broadcast::channel(self.broadcast_capacity);
+        let (sender, receiver) = broadcast::channel(self.broadcast_capacity.get());
         let id = Ulid::gen().to_string();
         self.map.insert(id.clone(), sender.clone());
 
@@ -96,3 +125,44 @@ impl Drop for ProducerHandle {
         trace!(subscription_id = %self.id, "producer dropped, upstream closed");
     }
 }
+
+/// A client subscription counted toward the `subscriptions.max_active_subscriptions` limit.
+/// Dropping it frees the slot.
+pub struct ClientSubscriptionSlot(Arc<AtomicUsize>);
+
+impl Drop for ClientSubscriptionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::ActiveSubscriptions;
+
+    #[test]
+    fn client_slots_are_limited_and_freed_on_drop() {
+        let active_subscriptions =
+            ActiveSubscriptions::new(NonZeroUsize::new(1).unwrap(), NonZeroUsize::new(2));
+
+        let first = active_subscriptions.acquire_client_slot();
+        let second = active_subscriptions.acquire_client_slot();
+        assert!(first.is_some() && second.is_some());
+        assert!(active_subscriptions.acquire_client_slot().is_none());
+
+        drop(first);
+        assert!(active_subscriptions.acquire_client_slot().is_some());
+    }
+
+    #[test]
+    fn client_slots_are_unlimited_by_default() {
+        let active_subscriptions = ActiveSubscriptions::new(NonZeroUsize::new(1).unwrap(), None);
+
+        let slots: Vec<_> = (0..100)
+            .map(|_| active_subscriptions.acquire_client_slot())
+            .collect();
+        assert!(slots.iter().all(Option::is_some));
+    }
+}
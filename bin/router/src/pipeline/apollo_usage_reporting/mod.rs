@@ -0,0 +1,302 @@
+//! Reports usage and federated traces (ftv1) to Apollo GraphOS.
+//!
+//! Every sampled operation is reported as a trace, keyed by its operation signature,
+//! with the query plan annotated with the timings of the subgraph fetches
+//! and, when enabled, the federated traces returned by the subgraphs.
+
+use std::{
+    collections::HashMap,
+    io::Write,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use flate2::{write::GzEncoder, Compression};
+use hive_router_config::telemetry::apollo::ApolloTelemetryConfig;
+use hive_router_internal::background_tasks::{BackgroundTask, BackgroundTasksManager};
+use hive_router_internal::telemetry::utils::resolve_value_or_expression;
+use hive_router_plan_executor::execution::fetch_traces::FetchTraceSink;
+use hive_router_query_planner::planner::plan_nodes::QueryPlan;
+use prost::Message;
+use rand::prelude::*;
+use sha2::{Digest, Sha256};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error};
+
+use crate::consts::ROUTER_VERSION;
+
+pub mod proto;
+pub mod signature;
+pub mod trace;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApolloUsageReportingError {
+    #[error(
+        "Apollo usage reporting - API key is missing. Please provide it via 'APOLLO_KEY' environment variable or under 'telemetry.apollo.key' in the configuration."
+    )]
+    MissingKey,
+    #[error(
+        "Apollo usage reporting - graph ref is missing. Please provide it via 'APOLLO_GRAPH_REF' environment variable or under 'telemetry.apollo.graph_ref' in the configuration."
+    )]
+    MissingGraphRef,
+    #[error("Apollo usage reporting - invalid graph ref '{0}', expected 'graph-id@variant'")]
+    InvalidGraphRef(String),
+    #[error("Apollo usage reporting - configuration error: {0}")]
+    Configuration(String),
+    #[error("Apollo usage reporting - failed to create the http client: {0}")]
+    HttpClient(#[from] reqwest::Error),
+}
+
+/// Collects the details of a sampled operation during its execution.
+pub struct ApolloTraceContext {
+    started_at: SystemTime,
+    query_plan: OnceLock<Arc<QueryPlan>>,
+    pub fetch_traces: FetchTraceSink,
+}
+
+impl ApolloTraceContext {
+    pub fn set_query_plan(&self, query_plan: Arc<QueryPlan>) {
+        let _ = self.query_plan.set(query_plan);
+    }
+}
+
+/// The details of an executed operation, reported as a trace.
+pub struct ApolloOperationReport<'a> {
+    pub stats_report_key: String,
+    pub schema_cache_id: u64,
+    pub schema_sdl: &'a str,
+    pub client_name: Option<&'a str>,
+    pub client_version: Option<&'a str>,
+    pub duration: Duration,
+    pub trace_context: &'a ApolloTraceContext,
+}
+
+#[derive(Default)]
+struct ReportBuffer {
+    /// Traces by executable schema id and stats report key.
+    traces: HashMap<Arc<str>, HashMap<String, proto::TracesAndStats>>,
+    operation_count: usize,
+}
+
+pub struct ApolloUsageAgent {
+    inner: Arc<ApolloUsageAgentInner>,
+}
+
+struct ApolloUsageAgentInner {
+    client: reqwest::Client,
+    endpoint: String,
+    key: String,
+    graph_ref: String,
+    hostname: String,
+    sample_rate: f64,
+    federated_traces: bool,
+    buffer_size: usize,
+    flush_interval: Duration,
+    buffer: Mutex<ReportBuffer>,
+    /// The executable schema id of the last reported supergraph, by cache id.
+    schema_id: Mutex<Option<(u64, Arc<str>)>>,
+    buffer_full: Notify,
+}
+
+impl ApolloUsageAgent {
+    pub fn init(
+        bg_tasks_manager: &mut BackgroundTasksManager,
+        config: &ApolloTelemetryConfig,
+    ) -> Result<Self, ApolloUsageReportingError> {
+        let key = match &config.key {
+            Some(key) => resolve_value_or_expression(key, "Apollo key")
+                .map_err(|e| ApolloUsageReportingError::Configuration(e.to_string()))?,
+            None => return Err(ApolloUsageReportingError::MissingKey),
+        };
+        let graph_ref = match &config.graph_ref {
+            Some(graph_ref) => resolve_value_or_expression(graph_ref, "Apollo graph ref")
+                .map_err(|e| ApolloUsageReportingError::Configuration(e.to_string()))?,
+            None => return Err(ApolloUsageReportingError::MissingGraphRef),
+        };
+        if !is_valid_graph_ref(&graph_ref) {
+            return Err(ApolloUsageReportingError::InvalidGraphRef(graph_ref));
+        }
+
+        let client = reqwest::Client::builder()
+            .user_agent(format!("hive-router/{}", ROUTER_VERSION))
+            .timeout(config.request_timeout)
+            .build()?;
+
+        let inner = Arc::new(ApolloUsageAgentInner {
+            client,
+            endpoint: config.endpoint.clone(),
+            key,
+            graph_ref,
+            hostname: std::env::var("HOSTNAME").unwrap_or_default(),
+            sample_rate: config.sampling.as_f64(),
+            federated_traces: config.federated_traces,
+            buffer_size: config.buffer_size,
+            flush_interval: config.flush_interval,
+            buffer: Default::default(),
+            schema_id: Default::default(),
+            buffer_full: Notify::new(),
+        });
+
+        bg_tasks_manager.register_task(ApolloUsageAgentTask(inner.clone()));
+
+        Ok(Self { inner })
+    }
+
+    /// Decides whether an operation is reported, and if so,
+    /// returns the context collecting its details during the execution.
+    pub fn sample(&self) -> Option<ApolloTraceContext> {
+        if !rand::rng().random_bool(self.inner.sample_rate) {
+            return None;
+        }
+        Some(ApolloTraceContext {
+            started_at: SystemTime::now(),
+            query_plan: OnceLock::new(),
+            fetch_traces: FetchTraceSink::new(self.inner.federated_traces),
+        })
+    }
+
+    pub fn add_report(&self, report: ApolloOperationReport<'_>) {
+        let schema_id = self
+            .inner
+            .executable_schema_id(report.schema_cache_id, report.schema_sdl);
+        let stats_report_key = report.stats_report_key.clone();
+        let trace = trace::build_trace(report);
+
+        let is_full = {
+            let mut buffer = match self.inner.buffer.lock() {
+                Ok(buffer) => buffer,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            buffer
+                .traces
+                .entry(schema_id)
+                .or_default()
+                .entry(stats_report_key)
+                .or_default()
+                .trace
+                .push(trace);
+            buffer.operation_count += 1;
+            buffer.operation_count >= self.inner.buffer_size
+        };
+
+        if is_full {
+            self.inner.buffer_full.notify_one();
+        }
+    }
+}
+
+impl ApolloUsageAgentInner {
+    fn executable_schema_id(&self, cache_id: u64, sdl: &str) -> Arc<str> {
+        let mut schema_id = match self.schema_id.lock() {
+            Ok(schema_id) => schema_id,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        match schema_id.as_ref() {
+            Some((id, hash)) if *id == cache_id => hash.clone(),
+            _ => {
+                let hash: Arc<str> = Arc::from(format!("{:x}", Sha256::digest(sdl.as_bytes())));
+                *schema_id = Some((cache_id, hash.clone()));
+                hash
+            }
+        }
+    }
+
+    async fn flush(&self) {
+        let buffer = {
+            let mut buffer = match self.buffer.lock() {
+                Ok(buffer) => buffer,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            std::mem::take(&mut *buffer)
+        };
+
+        for (schema_id, traces_per_query) in buffer.traces {
+            let operation_count = traces_per_query
+                .values()
+                .map(|traces| traces.trace.len() as u64)
+                .sum();
+            let report = proto::Report {
+                header: Some(proto::ReportHeader {
+                    hostname: self.hostname.clone(),
+                    agent_version: format!("hive-router@{}", ROUTER_VERSION),
+                    runtime_version: "rust".to_string(),
+                    uname: std::env::consts::OS.to_string(),
+                    executable_schema_id: schema_id.to_string(),
+                    graph_ref: self.graph_ref.clone(),
+                }),
+                end_time: Some(SystemTime::now().into()),
+                traces_per_query,
+                operation_count,
+            };
+
+            if let Err(err) = self.send(&report).await {
+                error!("Failed to send Apollo usage report: {}", err);
+            }
+        }
+    }
+
+    async fn send(&self, report: &proto::Report) -> Result<(), String> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&report.encode_to_vec())
+            .map_err(|err| err.to_string())?;
+        let body = encoder.finish().map_err(|err| err.to_string())?;
+
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .header("X-Api-Key", &self.key)
+            .header(reqwest::header::CONTENT_TYPE, "application/protobuf")
+            .header(reqwest::header::CONTENT_ENCODING, "gzip")
+            .header(reqwest::header::ACCEPT, "application/json")
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("unexpected status {status}: {body}"));
+        }
+
+        debug!(
+            operation_count = report.operation_count,
+            "sent Apollo usage report"
+        );
+        Ok(())
+    }
+}
+
+fn is_valid_graph_ref(graph_ref: &str) -> bool {
+    match graph_ref.split_once('@') {
+        Some((graph_id, variant)) => !graph_id.is_empty() && !variant.is_empty(),
+        None => false,
+    }
+}
+
+struct ApolloUsageAgentTask(Arc<ApolloUsageAgentInner>);
+
+#[async_trait]
+impl BackgroundTask for ApolloUsageAgentTask {
+    fn id(&self) -> &str {
+        "apollo_usage_report_task"
+    }
+
+    async fn run(&self, token: CancellationToken) {
+        let mut interval = tokio::time::interval(self.0.flush_interval);
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => {
+                    debug!("flushing Apollo usage reports before shutdown");
+                    self.0.flush().await;
+                    return;
+                }
+                _ = interval.tick() => self.0.flush().await,
+                _ = self.0.buffer_full.notified() => self.0.flush().await,
+            }
+        }
+    }
+}
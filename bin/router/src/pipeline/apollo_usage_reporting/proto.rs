@@ -0,0 +1,197 @@
+//! The subset of the Apollo usage reporting protocol (`reports.proto`) written by the router.
+//!
+//! Field tags follow the upstream definitions, unused fields are omitted.
+
+use std::collections::HashMap;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Timestamp {
+    #[prost(int64, tag = "1")]
+    pub seconds: i64,
+    #[prost(int32, tag = "2")]
+    pub nanos: i32,
+}
+
+impl From<std::time::SystemTime> for Timestamp {
+    fn from(time: std::time::SystemTime) -> Self {
+        let since_epoch = time
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            seconds: since_epoch.as_secs() as i64,
+            nanos: since_epoch.subsec_nanos() as i32,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Report {
+    #[prost(message, optional, tag = "1")]
+    pub header: Option<ReportHeader>,
+    #[prost(message, optional, tag = "2")]
+    pub end_time: Option<Timestamp>,
+    #[prost(map = "string, message", tag = "5")]
+    pub traces_per_query: HashMap<String, TracesAndStats>,
+    #[prost(uint64, tag = "6")]
+    pub operation_count: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ReportHeader {
+    #[prost(string, tag = "5")]
+    pub hostname: String,
+    #[prost(string, tag = "6")]
+    pub agent_version: String,
+    #[prost(string, tag = "8")]
+    pub runtime_version: String,
+    #[prost(string, tag = "9")]
+    pub uname: String,
+    #[prost(string, tag = "11")]
+    pub executable_schema_id: String,
+    #[prost(string, tag = "12")]
+    pub graph_ref: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TracesAndStats {
+    #[prost(message, repeated, tag = "1")]
+    pub trace: Vec<Trace>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Trace {
+    #[prost(message, optional, tag = "3")]
+    pub end_time: Option<Timestamp>,
+    #[prost(message, optional, tag = "4")]
+    pub start_time: Option<Timestamp>,
+    #[prost(string, tag = "7")]
+    pub client_name: String,
+    #[prost(string, tag = "8")]
+    pub client_version: String,
+    #[prost(uint64, tag = "11")]
+    pub duration_ns: u64,
+    #[prost(message, optional, tag = "14")]
+    pub root: Option<TraceNode>,
+    #[prost(message, optional, tag = "26")]
+    pub query_plan: Option<QueryPlanNode>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TraceNode {
+    #[prost(oneof = "TraceNodeId", tags = "1, 2")]
+    pub id: Option<TraceNodeId>,
+    #[prost(string, tag = "3")]
+    pub r#type: String,
+    #[prost(uint64, tag = "8")]
+    pub start_time: u64,
+    #[prost(uint64, tag = "9")]
+    pub end_time: u64,
+    #[prost(message, repeated, tag = "11")]
+    pub error: Vec<TraceError>,
+    #[prost(message, repeated, tag = "12")]
+    pub child: Vec<TraceNode>,
+    #[prost(string, tag = "13")]
+    pub parent_type: String,
+    #[prost(string, tag = "14")]
+    pub original_field_name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum TraceNodeId {
+    #[prost(string, tag = "1")]
+    ResponseName(String),
+    #[prost(uint32, tag = "2")]
+    Index(u32),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TraceError {
+    #[prost(string, tag = "1")]
+    pub message: String,
+    #[prost(string, tag = "4")]
+    pub json: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryPlanNode {
+    #[prost(oneof = "query_plan_node::Node", tags = "1, 2, 3, 4, 6")]
+    pub node: Option<query_plan_node::Node>,
+}
+
+pub mod query_plan_node {
+    use super::{QueryPlanNode, Timestamp, Trace};
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Node {
+        #[prost(message, tag = "1")]
+        Sequence(SequenceNode),
+        #[prost(message, tag = "2")]
+        Parallel(ParallelNode),
+        #[prost(message, boxed, tag = "3")]
+        Fetch(Box<FetchNode>),
+        #[prost(message, boxed, tag = "4")]
+        Flatten(Box<FlattenNode>),
+        #[prost(message, boxed, tag = "6")]
+        Condition(Box<ConditionNode>),
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SequenceNode {
+        #[prost(message, repeated, tag = "1")]
+        pub nodes: Vec<QueryPlanNode>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ParallelNode {
+        #[prost(message, repeated, tag = "1")]
+        pub nodes: Vec<QueryPlanNode>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FetchNode {
+        #[prost(string, tag = "1")]
+        pub service_name: String,
+        #[prost(bool, tag = "2")]
+        pub trace_parsing_failed: bool,
+        #[prost(message, optional, tag = "3")]
+        pub trace: Option<Trace>,
+        #[prost(uint64, tag = "4")]
+        pub sent_time_offset: u64,
+        #[prost(message, optional, tag = "5")]
+        pub sent_time: Option<Timestamp>,
+        #[prost(message, optional, tag = "6")]
+        pub received_time: Option<Timestamp>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FlattenNode {
+        #[prost(message, repeated, tag = "1")]
+        pub response_path: Vec<ResponsePathElement>,
+        #[prost(message, optional, boxed, tag = "2")]
+        pub node: Option<Box<QueryPlanNode>>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ConditionNode {
+        #[prost(string, tag = "1")]
+        pub condition: String,
+        #[prost(message, optional, boxed, tag = "2")]
+        pub if_clause: Option<Box<QueryPlanNode>>,
+        #[prost(message, optional, boxed, tag = "3")]
+        pub else_clause: Option<Box<QueryPlanNode>>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ResponsePathElement {
+        #[prost(oneof = "ResponsePathElementId", tags = "1, 2")]
+        pub id: Option<ResponsePathElementId>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum ResponsePathElementId {
+        #[prost(string, tag = "1")]
+        FieldName(String),
+        #[prost(uint32, tag = "2")]
+        Index(u32),
+    }
+}
@@ -0,0 +1,378 @@
+//! Computes the usage reporting signature of an operation, the way Apollo tooling does,
+//! so operations reported by the router are grouped with those reported by Apollo Router.
+//!
+//! The signature is the executed operation and the fragments it uses, with:
+//! - literals hidden (numbers become `0`, strings `""`, lists `[]` and objects `{}`)
+//! - aliases removed
+//! - definitions, selections, arguments, directives and variables sorted
+//! - whitespace only kept between two names
+
+use std::collections::{BTreeSet, HashMap};
+
+use graphql_tools::parser::query::{
+    Definition, Directive, Document, FragmentDefinition, OperationDefinition, Selection,
+    SelectionSet, Type, TypeCondition, Value, VariableDefinition,
+};
+
+type Doc = Document<'static, String>;
+
+/// Returns the stats report key of an operation, `# <operation name>\n<signature>`,
+/// or `None` when the document has no such operation.
+pub fn stats_report_key(document: &Doc, operation_name: Option<&str>) -> Option<String> {
+    let operation = find_operation(document, operation_name)?;
+    let signature = signature(document, operation);
+    Some(format!(
+        "# {}\n{}",
+        operation_name
+            .or(operation_name_of(operation))
+            .unwrap_or("-"),
+        signature
+    ))
+}
+
+fn find_operation<'d>(
+    document: &'d Doc,
+    operation_name: Option<&str>,
+) -> Option<&'d OperationDefinition<'static, String>> {
+    let mut operations = document.definitions.iter().filter_map(|def| match def {
+        Definition::Operation(op) => Some(op),
+        Definition::Fragment(_) => None,
+    });
+    match operation_name {
+        Some(name) => operations.find(|op| operation_name_of(op) == Some(name)),
+        None => operations.next(),
+    }
+}
+
+fn operation_name_of<'d>(operation: &'d OperationDefinition<'static, String>) -> Option<&'d str> {
+    match operation {
+        OperationDefinition::SelectionSet(_) => None,
+        OperationDefinition::Query(q) => q.name.as_deref(),
+        OperationDefinition::Mutation(m) => m.name.as_deref(),
+        OperationDefinition::Subscription(s) => s.name.as_deref(),
+    }
+}
+
+fn signature(document: &Doc, operation: &OperationDefinition<'static, String>) -> String {
+    let fragments: HashMap<&str, &FragmentDefinition<'static, String>> = document
+        .definitions
+        .iter()
+        .filter_map(|def| match def {
+            Definition::Fragment(fragment) => Some((fragment.name.as_str(), fragment)),
+            Definition::Operation(_) => None,
+        })
+        .collect();
+
+    let mut used_fragments = BTreeSet::new();
+    collect_fragment_spreads(
+        operation_selection_set(operation),
+        &fragments,
+        &mut used_fragments,
+    );
+
+    let mut writer = SignatureWriter::default();
+    // Fragment definitions sort before operation definitions, by name.
+    for name in used_fragments {
+        let fragment = fragments[name];
+        writer.token("fragment");
+        writer.token(&fragment.name);
+        writer.token("on");
+        let TypeCondition::On(type_name) = &fragment.type_condition;
+        writer.token(type_name);
+        writer.directives(&fragment.directives);
+        writer.selection_set(&fragment.selection_set);
+    }
+    writer.operation(operation);
+    writer.out
+}
+
+fn operation_selection_set<'d>(
+    operation: &'d OperationDefinition<'static, String>,
+) -> &'d SelectionSet<'static, String> {
+    match operation {
+        OperationDefinition::SelectionSet(s) => s,
+        OperationDefinition::Query(q) => &q.selection_set,
+        OperationDefinition::Mutation(m) => &m.selection_set,
+        OperationDefinition::Subscription(s) => &s.selection_set,
+    }
+}
+
+fn collect_fragment_spreads<'d>(
+    selection_set: &'d SelectionSet<'static, String>,
+    fragments: &HashMap<&'d str, &'d FragmentDefinition<'static, String>>,
+    used: &mut BTreeSet<&'d str>,
+) {
+    for selection in &selection_set.items {
+        match selection {
+            Selection::Field(field) => {
+                collect_fragment_spreads(&field.selection_set, fragments, used)
+            }
+            Selection::InlineFragment(fragment) => {
+                collect_fragment_spreads(&fragment.selection_set, fragments, used)
+            }
+            Selection::FragmentSpread(spread) => {
+                if let Some((name, fragment)) =
+                    fragments.get_key_value(spread.fragment_name.as_str())
+                {
+                    if used.insert(name) {
+                        collect_fragment_spreads(&fragment.selection_set, fragments, used);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct SignatureWriter {
+    out: String,
+}
+
+impl SignatureWriter {
+    /// Writes a token, separated by a space only when both sides are names.
+    fn token(&mut self, token: &str) {
+        let is_name_char = |c: char| c == '_' || c.is_ascii_alphanumeric();
+        if self.out.ends_with(is_name_char) && token.starts_with(is_name_char) {
+            self.out.push(' ');
+        }
+        self.out.push_str(token);
+    }
+
+    fn operation(&mut self, operation: &OperationDefinition<'static, String>) {
+        let (keyword, name, variables, directives, selection_set) = match operation {
+            OperationDefinition::SelectionSet(s) => {
+                self.selection_set(s);
+                return;
+            }
+            OperationDefinition::Query(q) => (
+                "query",
+                &q.name,
+                &q.variable_definitions,
+                &q.directives,
+                &q.selection_set,
+            ),
+            OperationDefinition::Mutation(m) => (
+                "mutation",
+                &m.name,
+                &m.variable_definitions,
+                &m.directives,
+                &m.selection_set,
+            ),
+            OperationDefinition::Subscription(s) => (
+                "subscription",
+                &s.name,
+                &s.variable_definitions,
+                &s.directives,
+                &s.selection_set,
+            ),
+        };
+
+        // An anonymous query without variables and directives is printed as its selection set.
+        if keyword == "query" && name.is_none() && variables.is_empty() && directives.is_empty() {
+            self.selection_set(selection_set);
+            return;
+        }
+
+        self.token(keyword);
+        if let Some(name) = name {
+            self.token(name);
+        }
+        self.variable_definitions(variables);
+        self.directives(directives);
+        self.selection_set(selection_set);
+    }
+
+    fn variable_definitions(&mut self, variables: &[VariableDefinition<'static, String>]) {
+        if variables.is_empty() {
+            return;
+        }
+        let mut variables: Vec<_> = variables.iter().collect();
+        variables.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.token("(");
+        for (i, variable) in variables.into_iter().enumerate() {
+            if i > 0 {
+                self.token(",");
+            }
+            self.token("$");
+            self.token(&variable.name);
+            self.token(":");
+            self.var_type(&variable.var_type);
+            if let Some(default_value) = &variable.default_value {
+                self.token("=");
+                self.value(default_value);
+            }
+        }
+        self.token(")");
+    }
+
+    fn var_type(&mut self, var_type: &Type<'static, String>) {
+        match var_type {
+            Type::NamedType(name) => self.token(name),
+            Type::ListType(inner) => {
+                self.token("[");
+                self.var_type(inner);
+                self.token("]");
+            }
+            Type::NonNullType(inner) => {
+                self.var_type(inner);
+                self.token("!");
+            }
+        }
+    }
+
+    fn directives(&mut self, directives: &[Directive<'static, String>]) {
+        let mut directives: Vec<_> = directives.iter().collect();
+        directives.sort_by(|a, b| a.name.cmp(&b.name));
+        for directive in directives {
+            self.token("@");
+            self.token(&directive.name);
+            self.arguments(&directive.arguments);
+        }
+    }
+
+    fn arguments(&mut self, arguments: &[(String, Value<'static, String>)]) {
+        if arguments.is_empty() {
+            return;
+        }
+        let mut arguments: Vec<_> = arguments.iter().collect();
+        arguments.sort_by(|a, b| a.0.cmp(&b.0));
+
+        self.token("(");
+        for (i, (name, value)) in arguments.into_iter().enumerate() {
+            if i > 0 {
+                self.token(",");
+            }
+            self.token(name);
+            self.token(":");
+            self.value(value);
+        }
+        self.token(")");
+    }
+
+    /// Writes a value with its literals hidden.
+    fn value(&mut self, value: &Value<'static, String>) {
+        match value {
+            Value::Variable(name) => {
+                self.token("$");
+                self.token(name);
+            }
+            Value::Int(_) | Value::Float(_) => self.token("0"),
+            Value::String(_) => self.token("\"\""),
+            Value::Boolean(true) => self.token("true"),
+            Value::Boolean(false) => self.token("false"),
+            Value::Null => self.token("null"),
+            Value::Enum(name) => self.token(name),
+            Value::List(_) => self.token("[]"),
+            Value::Object(_) => self.token("{}"),
+        }
+    }
+
+    fn selection_set(&mut self, selection_set: &SelectionSet<'static, String>) {
+        if selection_set.items.is_empty() {
+            return;
+        }
+
+        // Fields sort before fragment spreads and inline fragments,
+        // fields and spreads by name, inline fragments keep their order.
+        let mut selections: Vec<_> = selection_set.items.iter().collect();
+        selections.sort_by_key(|selection| match selection {
+            Selection::Field(field) => (0, Some(field.name.as_str())),
+            Selection::FragmentSpread(spread) => (1, Some(spread.fragment_name.as_str())),
+            Selection::InlineFragment(_) => (2, None),
+        });
+
+        self.token("{");
+        for selection in selections {
+            match selection {
+                Selection::Field(field) => {
+                    self.token(&field.name);
+                    self.arguments(&field.arguments);
+                    self.directives(&field.directives);
+                    self.selection_set(&field.selection_set);
+                }
+                Selection::FragmentSpread(spread) => {
+                    self.token("...");
+                    self.token(&spread.fragment_name);
+                    self.directives(&spread.directives);
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.token("...");
+                    if let Some(TypeCondition::On(type_name)) = &fragment.type_condition {
+                        self.token("on");
+                        self.token(type_name);
+                    }
+                    self.directives(&fragment.directives);
+                    self.selection_set(&fragment.selection_set);
+                }
+            }
+        }
+        self.token("}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graphql_tools::parser::query::parse_query;
+
+    use super::stats_report_key;
+
+    fn key(query: &str, operation_name: Option<&str>) -> String {
+        let document = parse_query::<String>(query).unwrap().into_static();
+        stats_report_key(&document, operation_name).unwrap()
+    }
+
+    #[test]
+    fn anonymous_query() {
+        assert_eq!(key("{ me { name id } }", None), "# -\n{me{id name}}");
+    }
+
+    #[test]
+    fn hides_literals_and_removes_aliases() {
+        assert_eq!(
+            key(
+                r#"query Users($limit: Int = 10, $after: String!) {
+                    first: users(limit: $limit, filter: { name: "john" }, after: $after, order: ASC, tags: ["a"]) {
+                        fullName: name
+                        age(unit: "years", rounded: true)
+                    }
+                }"#,
+                Some("Users"),
+            ),
+            "# Users\nquery Users($after:String!,$limit:Int=0){users(after:$after,filter:{},limit:$limit,order:ASC,tags:[]){age(rounded:true,unit:\"\")name}}"
+        );
+    }
+
+    #[test]
+    fn keeps_used_fragments_sorted_before_the_operation() {
+        assert_eq!(
+            key(
+                r#"
+                query Other { other }
+                query Me {
+                    me {
+                        ... on User @include(if: true) { id }
+                        ...UserFields
+                        name
+                        ...AccountFields
+                    }
+                }
+                fragment UserFields on User { name }
+                fragment Unused on User { id }
+                fragment AccountFields on User { account { ...NestedFields } }
+                fragment NestedFields on Account { id }
+                "#,
+                Some("Me"),
+            ),
+            "# Me\nfragment AccountFields on User{account{...NestedFields}}fragment NestedFields on Account{id}fragment UserFields on User{name}query Me{me{name...AccountFields...UserFields...on User@include(if:true){id}}}"
+        );
+    }
+
+    #[test]
+    fn unknown_operation() {
+        let document = parse_query::<String>("query A { a }")
+            .unwrap()
+            .into_static();
+        assert_eq!(stats_report_key(&document, Some("B")), None);
+    }
+}
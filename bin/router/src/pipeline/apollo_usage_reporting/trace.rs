@@ -0,0 +1,213 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hive_router_plan_executor::execution::fetch_traces::FetchTrace;
+use hive_router_query_planner::planner::plan_nodes::{FlattenNodePathSegment, PlanNode};
+use prost::Message;
+
+use super::{
+    proto::{
+        query_plan_node::{
+            ConditionNode, FetchNode, FlattenNode, Node, ParallelNode, ResponsePathElement,
+            ResponsePathElementId, SequenceNode,
+        },
+        QueryPlanNode, Trace, TraceNode,
+    },
+    ApolloOperationReport,
+};
+
+pub fn build_trace(report: ApolloOperationReport<'_>) -> Trace {
+    let context = report.trace_context;
+    let fetch_traces: HashMap<i64, FetchTrace> = context
+        .fetch_traces
+        .take()
+        .into_iter()
+        .map(|trace| (trace.node_id, trace))
+        .collect();
+
+    Trace {
+        start_time: Some(context.started_at.into()),
+        end_time: Some((context.started_at + report.duration).into()),
+        duration_ns: report.duration.as_nanos() as u64,
+        client_name: report.client_name.unwrap_or_default().to_string(),
+        client_version: report.client_version.unwrap_or_default().to_string(),
+        root: Some(TraceNode::default()),
+        query_plan: context
+            .query_plan
+            .get()
+            .and_then(|plan| plan.node.as_ref())
+            .map(|node| query_plan_node(node, &fetch_traces, context.started_at)),
+    }
+}
+
+fn query_plan_node(
+    node: &PlanNode,
+    fetch_traces: &HashMap<i64, FetchTrace>,
+    started_at: SystemTime,
+) -> QueryPlanNode {
+    let nodes = |nodes: &[PlanNode]| -> Vec<QueryPlanNode> {
+        nodes
+            .iter()
+            .map(|node| query_plan_node(node, fetch_traces, started_at))
+            .collect()
+    };
+    let boxed = |node: &PlanNode| Box::new(query_plan_node(node, fetch_traces, started_at));
+
+    let node = match node {
+        PlanNode::Fetch(fetch) => {
+            fetch_node(fetch.id, &fetch.service_name, fetch_traces, started_at)
+        }
+        PlanNode::BatchFetch(fetch) => {
+            fetch_node(fetch.id, &fetch.service_name, fetch_traces, started_at)
+        }
+        PlanNode::Subscription(subscription) => fetch_node(
+            subscription.primary.id,
+            &subscription.primary.service_name,
+            fetch_traces,
+            started_at,
+        ),
+        PlanNode::Sequence(sequence) => Node::Sequence(SequenceNode {
+            nodes: nodes(&sequence.nodes),
+        }),
+        PlanNode::Parallel(parallel) => Node::Parallel(ParallelNode {
+            nodes: nodes(&parallel.nodes),
+        }),
+        PlanNode::Flatten(flatten) => Node::Flatten(Box::new(FlattenNode {
+            response_path: flatten
+                .path
+                .as_slice()
+                .iter()
+                .filter_map(|segment| match segment {
+                    FlattenNodePathSegment::Field(name) => Some(name.clone()),
+                    FlattenNodePathSegment::List => Some("@".to_string()),
+                    FlattenNodePathSegment::TypeCondition(_) => None,
+                })
+                .map(|field_name| ResponsePathElement {
+                    id: Some(ResponsePathElementId::FieldName(field_name)),
+                })
+                .collect(),
+            node: Some(boxed(&flatten.node)),
+        })),
+        PlanNode::Condition(condition) => Node::Condition(Box::new(ConditionNode {
+            condition: condition.condition.clone(),
+            if_clause: condition.if_clause.as_deref().map(boxed),
+            else_clause: condition.else_clause.as_deref().map(boxed),
+        })),
+        // Deferred nodes are reported in the order they are executed without incremental delivery.
+        PlanNode::Defer(defer) => Node::Sequence(SequenceNode {
+            nodes: defer
+                .primary
+                .node
+                .iter()
+                .chain(
+                    defer
+                        .deferred
+                        .iter()
+                        .filter_map(|deferred| deferred.node.as_ref()),
+                )
+                .map(|node| query_plan_node(node, fetch_traces, started_at))
+                .collect(),
+        }),
+    };
+
+    QueryPlanNode { node: Some(node) }
+}
+
+fn fetch_node(
+    id: i64,
+    service_name: &str,
+    fetch_traces: &HashMap<i64, FetchTrace>,
+    started_at: SystemTime,
+) -> Node {
+    let mut node = FetchNode {
+        service_name: service_name.to_string(),
+        ..Default::default()
+    };
+
+    if let Some(fetch_trace) = fetch_traces.get(&id) {
+        let sent_time = started_at + fetch_trace.sent_offset;
+        node.sent_time_offset = fetch_trace.sent_offset.as_nanos() as u64;
+        node.sent_time = Some(sent_time.into());
+        node.received_time = Some((sent_time + fetch_trace.duration).into());
+
+        if let Some(ftv1) = &fetch_trace.ftv1 {
+            match STANDARD
+                .decode(ftv1)
+                .ok()
+                .and_then(|bytes| Trace::decode(bytes.as_slice()).ok())
+            {
+                Some(trace) => node.trace = Some(trace),
+                None => node.trace_parsing_failed = true,
+            }
+        }
+    }
+
+    Node::Fetch(Box::new(node))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        time::{Duration, SystemTime},
+    };
+
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use hive_router_plan_executor::execution::fetch_traces::FetchTrace;
+    use prost::Message;
+
+    use super::{fetch_node, FetchNode, Node, Trace};
+
+    fn fetch(node: Node) -> FetchNode {
+        match node {
+            Node::Fetch(fetch) => *fetch,
+            _ => panic!("expected a fetch node"),
+        }
+    }
+
+    #[test]
+    fn annotates_fetches_with_their_timings_and_subgraph_traces() {
+        let subgraph_trace = Trace {
+            duration_ns: 42,
+            ..Default::default()
+        };
+        let fetch_traces: HashMap<i64, FetchTrace> = [
+            FetchTrace {
+                node_id: 1,
+                subgraph_name: "accounts".to_string(),
+                sent_offset: Duration::from_millis(1),
+                duration: Duration::from_millis(5),
+                ftv1: Some(STANDARD.encode(subgraph_trace.encode_to_vec())),
+            },
+            FetchTrace {
+                node_id: 2,
+                subgraph_name: "reviews".to_string(),
+                sent_offset: Duration::from_millis(6),
+                duration: Duration::from_millis(2),
+                ftv1: Some("not a trace".to_string()),
+            },
+        ]
+        .into_iter()
+        .map(|trace| (trace.node_id, trace))
+        .collect();
+        let started_at = SystemTime::now();
+
+        let accounts = fetch(fetch_node(1, "accounts", &fetch_traces, started_at));
+        assert_eq!(accounts.service_name, "accounts");
+        assert_eq!(accounts.sent_time_offset, 1_000_000);
+        assert_eq!(
+            accounts.received_time,
+            Some((started_at + Duration::from_millis(6)).into())
+        );
+        assert_eq!(accounts.trace, Some(subgraph_trace));
+        assert!(!accounts.trace_parsing_failed);
+
+        let reviews = fetch(fetch_node(2, "reviews", &fetch_traces, started_at));
+        assert_eq!(reviews.trace, None);
+        assert!(reviews.trace_parsing_failed);
+
+        let products = fetch(fetch_node(3, "products", &fetch_traces, started_at));
+        assert_eq!(products.service_name, "products");
+        assert_eq!(products.sent_time, None);
+    }
+}
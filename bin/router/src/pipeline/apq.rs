@@ -0,0 +1,278 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hive_router_config::apq::{ApqBackendConfig, ApqConfig};
+use hive_router_plan_executor::hooks::on_graphql_params::GraphQLParams;
+use moka::future::Cache;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use sha2::{Digest, Sha256};
+use sonic_rs::JsonValueTrait;
+use tracing::{debug, info, trace, warn};
+
+use crate::pipeline::error::PipelineError;
+use crate::storage::{
+    error::StorageError,
+    redis_connections::{RedisConnection, RedisConnections},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApqError {
+    #[error("invalid apq redis config: {0}")]
+    Redis(#[from] StorageError),
+}
+
+/// Implements Automatic Persisted Queries (APQ).
+///
+/// Requests carrying only the hash of an operation in `extensions.persistedQuery.sha256Hash`
+/// are resolved from the registered operations, before the persisted documents are resolved,
+/// and the resolved operation then goes through the regular parse, validation and plan caches.
+pub struct ApqRuntime {
+    store: Box<dyn ApqStore>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ApqOutcome {
+    /// The request is not an APQ request.
+    Skipped,
+    /// The operation sent along with its hash has been registered.
+    Registered,
+    /// The operation has been resolved from its hash.
+    Resolved,
+    /// The hash is unknown.
+    NotFound,
+}
+
+impl ApqRuntime {
+    pub fn from_config(
+        config: &ApqConfig,
+        redis_connections: &RedisConnections,
+    ) -> Result<Option<Self>, ApqError> {
+        if !config.enabled {
+            debug!("apq is disabled");
+            return Ok(None);
+        }
+
+        let store: Box<dyn ApqStore> = match &config.backend {
+            ApqBackendConfig::Memory { max_entries } => Box::new(MemoryApqStore {
+                cache: Cache::new(*max_entries),
+            }),
+            ApqBackendConfig::Redis {
+                url,
+                key_prefix,
+                ttl,
+            } => Box::new(RedisApqStore {
+                connection: redis_connections.connect("apq", url)?,
+                key_prefix: key_prefix.clone(),
+                ttl: *ttl,
+            }),
+        };
+
+        info!("apq enabled");
+
+        Ok(Some(Self { store }))
+    }
+
+    /// Resolves the operation of a request carrying only a hash,
+    /// or registers the operation of a request carrying both the operation and its hash.
+    pub async fn process(
+        &self,
+        graphql_params: &mut GraphQLParams,
+    ) -> Result<ApqOutcome, PipelineError> {
+        let Some(hash) = persisted_query_hash(graphql_params) else {
+            return Ok(ApqOutcome::Skipped);
+        };
+
+        match graphql_params.query.as_deref() {
+            Some(query) => {
+                if !hash.eq_ignore_ascii_case(&sha256_hex(query)) {
+                    return Err(PipelineError::PersistedQueryHashMismatch);
+                }
+                trace!(hash = %hash, "registering apq operation");
+                self.store
+                    .set(hash.to_ascii_lowercase(), Arc::from(query))
+                    .await;
+                Ok(ApqOutcome::Registered)
+            }
+            None => match self.store.get(&hash.to_ascii_lowercase()).await {
+                Some(query) => {
+                    graphql_params.query = Some(query.to_string());
+                    Ok(ApqOutcome::Resolved)
+                }
+                None => Ok(ApqOutcome::NotFound),
+            },
+        }
+    }
+}
+
+pub(crate) fn persisted_query_hash(graphql_params: &GraphQLParams) -> Option<String> {
+    graphql_params
+        .extensions
+        .as_ref()?
+        .get("persistedQuery")?
+        .get("sha256Hash")?
+        .as_str()
+        .map(ToString::to_string)
+}
+
+fn sha256_hex(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+#[async_trait]
+trait ApqStore: Send + Sync {
+    async fn get(&self, hash: &str) -> Option<Arc<str>>;
+    async fn set(&self, hash: String, query: Arc<str>);
+}
+
+struct MemoryApqStore {
+    cache: Cache<String, Arc<str>>,
+}
+
+#[async_trait]
+impl ApqStore for MemoryApqStore {
+    async fn get(&self, hash: &str) -> Option<Arc<str>> {
+        self.cache.get(hash).await
+    }
+
+    async fn set(&self, hash: String, query: Arc<str>) {
+        self.cache.insert(hash, query).await;
+    }
+}
+
+/// Stores the operations in Redis, every operation expiring `ttl` after it was registered,
+/// for the operations registered by the clients not to grow the store without bound.
+///
+/// Redis errors are logged and treated as unknown hashes.
+struct RedisApqStore {
+    connection: RedisConnection,
+    key_prefix: String,
+    ttl: Duration,
+}
+
+impl RedisApqStore {
+    async fn connection(&self) -> Option<ConnectionManager> {
+        match self.connection.get().await {
+            Ok(connection) => Some(connection),
+            Err(err) => {
+                warn!(error = %err, "failed to connect to the apq redis");
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ApqStore for RedisApqStore {
+    async fn get(&self, hash: &str) -> Option<Arc<str>> {
+        let mut connection = self.connection().await?;
+        let key = format!("{}{}", self.key_prefix, hash);
+        match connection.get::<_, Option<String>>(&key).await {
+            Ok(value) => value.map(Arc::from),
+            Err(err) => {
+                warn!(error = %err, "failed to read from the apq redis");
+                None
+            }
+        }
+    }
+
+    async fn set(&self, hash: String, query: Arc<str>) {
+        let Some(mut connection) = self.connection().await else {
+            return;
+        };
+        let key = format!("{}{}", self.key_prefix, hash);
+        // redis rejects a zero expiration, so sub-second TTLs are rounded up
+        let ttl_secs = self.ttl.as_secs_f64().ceil().max(1.0) as u64;
+        if let Err(err) = connection
+            .set_ex::<_, _, ()>(&key, query.as_ref(), ttl_secs)
+            .await
+        {
+            warn!(error = %err, "failed to write to the apq redis");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    const QUERY: &str = "{ me { id } }";
+
+    fn runtime() -> ApqRuntime {
+        ApqRuntime::from_config(
+            &ApqConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            &RedisConnections::default(),
+        )
+        .unwrap()
+        .unwrap()
+    }
+
+    fn params(query: Option<&str>, hash: &str) -> GraphQLParams {
+        let extensions = sonic_rs::from_str(&format!(
+            r#"{{"persistedQuery":{{"version":1,"sha256Hash":"{hash}"}}}}"#
+        ))
+        .unwrap();
+        GraphQLParams {
+            query: query.map(ToString::to_string),
+            operation_name: None,
+            variables: HashMap::new(),
+            extensions: Some(extensions),
+        }
+    }
+
+    #[tokio::test]
+    async fn registers_and_resolves_operations() {
+        let runtime = runtime();
+        let hash = sha256_hex(QUERY);
+
+        let mut hash_only = params(None, &hash);
+        assert_eq!(
+            runtime.process(&mut hash_only).await.unwrap(),
+            ApqOutcome::NotFound
+        );
+        assert_eq!(hash_only.query, None);
+
+        let mut full = params(Some(QUERY), &hash.to_ascii_uppercase());
+        assert_eq!(
+            runtime.process(&mut full).await.unwrap(),
+            ApqOutcome::Registered
+        );
+
+        let mut hash_only = params(None, &hash);
+        assert_eq!(
+            runtime.process(&mut hash_only).await.unwrap(),
+            ApqOutcome::Resolved
+        );
+        assert_eq!(hash_only.query.as_deref(), Some(QUERY));
+    }
+
+    #[tokio::test]
+    async fn rejects_mismatching_hashes() {
+        let runtime = runtime();
+        let mut full = params(Some(QUERY), &sha256_hex("{ other }"));
+        assert!(matches!(
+            runtime.process(&mut full).await,
+            Err(PipelineError::PersistedQueryHashMismatch)
+        ));
+    }
+
+    #[tokio::test]
+    async fn skips_requests_without_hash() {
+        let runtime = runtime();
+        let mut request = GraphQLParams {
+            query: Some(QUERY.to_string()),
+            operation_name: None,
+            variables: HashMap::new(),
+            extensions: None,
+        };
+        assert_eq!(
+            runtime.process(&mut request).await.unwrap(),
+            ApqOutcome::Skipped
+        );
+    }
+}
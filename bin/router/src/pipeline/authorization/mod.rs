@@ -9,13 +9,15 @@
 mod tests;
 
 pub mod metadata;
+pub mod opa;
 
 use std::sync::Arc;
 
 use crate::pipeline::error::PipelineError;
 use crate::pipeline::normalize::GraphQLNormalizationPayload;
 use crate::pipeline::nullify::rebuilder::{
-    rebuild_nulled_operation, rebuild_nulled_projection_plan,
+    rebuild_nulled_deferred_projection_plan, rebuild_nulled_operation,
+    rebuild_nulled_projection_plan,
 };
 use crate::pipeline::trie::Trie;
 use crate::utils::StrByAddr;
@@ -25,6 +27,7 @@ use hive_router_config::authorization::UnauthorizedMode;
 use hive_router_config::HiveRouterConfig;
 use hive_router_internal::authorization::metadata::{AuthorizationMetadata, AuthorizationRule};
 use hive_router_plan_executor::execution::client_request_details::JwtRequestDetails;
+use hive_router_plan_executor::execution::incremental::DeferredProjectionPlan;
 use hive_router_plan_executor::execution::plan::CoerceVariablesPayload;
 use hive_router_plan_executor::introspection::schema::SchemaMetadata;
 use hive_router_plan_executor::operation_filter::{OperationFilter, Selection};
@@ -54,6 +57,7 @@ pub enum AuthorizationDecision {
     Modified {
         new_operation_definition: OperationDefinition,
         new_projection_plan: Vec<FieldProjectionPlan>,
+        new_deferred_projection_plan: Option<DeferredProjectionPlan>,
         errors: Vec<AuthorizationError>,
     },
     /// The operation should be aborted due to unauthorized access and reject mode being enabled.
@@ -188,9 +192,14 @@ pub fn enforce_operation_authorization(
         AuthorizationDecision::Modified {
             new_operation_definition,
             new_projection_plan,
+            new_deferred_projection_plan,
             errors,
         } => (
-            normalized_payload.with_operation(new_operation_definition, new_projection_plan),
+            normalized_payload.with_operation(
+                new_operation_definition,
+                new_projection_plan,
+                new_deferred_projection_plan,
+            ),
             errors,
         ),
         AuthorizationDecision::Reject { errors } => {
@@ -285,10 +294,15 @@ pub fn apply_authorization_to_operation(
         rebuild_nulled_operation(&normalized_payload.operation_for_plan, &nulled_field_trie);
     let new_projection_plan =
         rebuild_nulled_projection_plan(&normalized_payload.projection_plan, &nulled_field_trie);
+    let new_deferred_projection_plan = normalized_payload
+        .deferred_projection_plan
+        .as_deref()
+        .map(|plan| rebuild_nulled_deferred_projection_plan(plan, &nulled_field_trie));
 
     Ok(AuthorizationDecision::Modified {
         new_operation_definition: new_operation,
         new_projection_plan,
+        new_deferred_projection_plan,
         errors,
     })
 }
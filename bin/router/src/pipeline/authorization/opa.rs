@@ -0,0 +1,491 @@
+//! Authorization of the operations with an [Open Policy Agent](https://www.openpolicyagent.org) policy.
+//!
+//! The policy is evaluated by an OPA server through its Data API, or within the router
+//! with a policy compiled to WebAssembly, with an input document describing the request.
+//! Its decision either allows or denies the whole operation,
+//! or lists the fields and types removed from the operation, like the authorization directives in `filter` mode.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use hive_router_config::authorization::{
+    OpaAuthorizationConfig, OpaDecisionCacheConfig, OpaErrorMode, OpaPolicyConfig,
+};
+use hive_router_plan_executor::execution::client_request_details::{
+    JwtRequestDetails, MutableClientRequestDetails,
+};
+use hive_router_plan_executor::execution::plan::{CoerceVariablesPayload, VariablesMap};
+use hive_router_plan_executor::introspection::schema::SchemaMetadata;
+use hive_router_plan_executor::operation_filter::{OperationFilter, Selection};
+use moka::future::Cache;
+use opa_wasm::{wasmtime, Runtime};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, error};
+
+use crate::pipeline::authorization::{unauthorized_error, AuthorizationError};
+use crate::pipeline::error::PipelineError;
+use crate::pipeline::normalize::GraphQLNormalizationPayload;
+use crate::pipeline::nullify::rebuilder::{
+    rebuild_nulled_deferred_projection_plan, rebuild_nulled_operation,
+    rebuild_nulled_projection_plan,
+};
+use crate::pipeline::trie::Trie;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OpaError {
+    #[error("failed to create the http client of the opa policy: {0}")]
+    HttpClient(reqwest::Error),
+    #[error("failed to load the wasm module of the opa policy: {0}")]
+    WasmLoad(String),
+    #[error("failed to serialize the input document of the opa policy: {0}")]
+    InputSerialization(sonic_rs::Error),
+    #[error("failed to send the opa request: {0}")]
+    Request(reqwest::Error),
+    #[error("the opa server responded with status {0}")]
+    Status(http::StatusCode),
+    #[error("the opa server responded with an invalid decision: {0}")]
+    InvalidResponse(sonic_rs::Error),
+    #[error("failed to evaluate the wasm opa policy: {0}")]
+    WasmEvaluation(String),
+}
+
+enum OpaPolicy {
+    Http {
+        client: reqwest::Client,
+        url: String,
+    },
+    Wasm {
+        engine: wasmtime::Engine,
+        module: wasmtime::Module,
+        entrypoint: String,
+    },
+}
+
+/// The decision of the policy for an operation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct OpaDecision {
+    allow: bool,
+    /// Field (`Type.field`) and type (`Type`) coordinates removed from the operation.
+    denied_fields: Vec<String>,
+}
+
+/// The decision of the policy is either a boolean, or an object with the denied fields.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OpaDecisionResult {
+    Allow(bool),
+    Detailed(OpaDetailedDecision),
+}
+
+/// An object decision only allows the operation when its `allow` rule is defined and true,
+/// and any other key makes the response invalid, rather than being ignored.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OpaDetailedDecision {
+    #[serde(default)]
+    allow: bool,
+    #[serde(default)]
+    denied_fields: Vec<String>,
+}
+
+impl From<Option<OpaDecisionResult>> for OpaDecision {
+    fn from(result: Option<OpaDecisionResult>) -> Self {
+        match result {
+            Some(OpaDecisionResult::Allow(allow)) => OpaDecision {
+                allow,
+                denied_fields: vec![],
+            },
+            Some(OpaDecisionResult::Detailed(OpaDetailedDecision {
+                allow,
+                denied_fields,
+            })) => OpaDecision {
+                allow,
+                denied_fields,
+            },
+            // An undefined decision denies the operation
+            None => OpaDecision::default(),
+        }
+    }
+}
+
+/// The response of the Data API of OPA, and an item of the result set of a wasm policy.
+#[derive(Deserialize)]
+struct OpaResult {
+    result: Option<OpaDecisionResult>,
+}
+
+#[derive(Serialize)]
+struct OpaRequest<'a> {
+    input: &'a OpaInput<'a>,
+}
+
+#[derive(Serialize)]
+struct OpaInput<'a> {
+    operation: OpaInputOperation<'a>,
+    variables: Option<&'a VariablesMap>,
+    jwt: OpaInputJwt<'a>,
+    headers: BTreeMap<&'a str, &'a str>,
+}
+
+#[derive(Serialize)]
+struct OpaInputOperation<'a> {
+    name: Option<&'a str>,
+    #[serde(rename = "type")]
+    kind: &'a str,
+    query: &'a str,
+}
+
+#[derive(Serialize)]
+struct OpaInputJwt<'a> {
+    authenticated: bool,
+    claims: Option<&'a sonic_rs::Value>,
+    scopes: Option<&'a [String]>,
+}
+
+impl<'a> OpaInput<'a> {
+    /// Builds the input document, with all the request headers,
+    /// or only the listed ones when the decisions are cached.
+    fn new(
+        client_request_details: &'a MutableClientRequestDetails,
+        variable_payload: &'a CoerceVariablesPayload,
+        header_names: Option<&[String]>,
+    ) -> Self {
+        let mut headers = BTreeMap::new();
+        for (name, value) in client_request_details.headers.iter() {
+            if header_names.is_some_and(|names| !names.iter().any(|listed| listed == name.as_str()))
+            {
+                continue;
+            }
+            if let Ok(value) = value.to_str() {
+                // Only the first value of a header is passed to the policy
+                headers.entry(name.as_str()).or_insert(value);
+            }
+        }
+
+        let jwt = match client_request_details.jwt.as_ref() {
+            JwtRequestDetails::Authenticated { claims, scopes, .. } => OpaInputJwt {
+                authenticated: true,
+                claims: Some(claims),
+                scopes: scopes.as_deref(),
+            },
+            JwtRequestDetails::Unauthenticated => OpaInputJwt {
+                authenticated: false,
+                claims: None,
+                scopes: None,
+            },
+        };
+
+        OpaInput {
+            operation: OpaInputOperation {
+                name: client_request_details.operation.name,
+                kind: client_request_details.operation.kind,
+                query: client_request_details.operation.query,
+            },
+            variables: variable_payload.variables_map.as_ref(),
+            jwt,
+            headers,
+        }
+    }
+}
+
+pub struct OpaAuthorizationRuntime {
+    policy: OpaPolicy,
+    operation_types: Option<Vec<&'static str>>,
+    on_error: OpaErrorMode,
+    cache: Option<OpaDecisionCache>,
+}
+
+struct OpaDecisionCache {
+    /// Decisions by the SHA-256 digest of their input document.
+    decisions: Cache<[u8; 32], OpaDecision>,
+    /// Lowercased names of the headers passed to the policy.
+    headers: Vec<String>,
+}
+
+impl OpaAuthorizationRuntime {
+    pub fn from_config(config: Option<&OpaAuthorizationConfig>) -> Result<Option<Self>, OpaError> {
+        let Some(config) = config else {
+            return Ok(None);
+        };
+
+        let policy = match &config.policy {
+            OpaPolicyConfig::Http { url, timeout } => OpaPolicy::Http {
+                client: reqwest::Client::builder()
+                    .timeout(*timeout)
+                    .build()
+                    .map_err(OpaError::HttpClient)?,
+                url: url.clone(),
+            },
+            OpaPolicyConfig::Wasm { path, entrypoint } => {
+                let mut wasm_config = wasmtime::Config::new();
+                wasm_config.async_support(true);
+                let engine = wasmtime::Engine::new(&wasm_config)
+                    .map_err(|err| OpaError::WasmLoad(err.to_string()))?;
+                let module = wasmtime::Module::from_file(&engine, &path.absolute)
+                    .map_err(|err| OpaError::WasmLoad(err.to_string()))?;
+                OpaPolicy::Wasm {
+                    engine,
+                    module,
+                    entrypoint: entrypoint.clone(),
+                }
+            }
+        };
+
+        let cache = config.cache.as_ref().map(
+            |OpaDecisionCacheConfig {
+                 ttl,
+                 max_entries,
+                 headers,
+             }| OpaDecisionCache {
+                decisions: Cache::builder()
+                    .max_capacity(*max_entries)
+                    .time_to_live(*ttl)
+                    .build(),
+                headers: headers
+                    .iter()
+                    .map(|name| name.to_ascii_lowercase())
+                    .collect(),
+            },
+        );
+
+        Ok(Some(Self {
+            policy,
+            operation_types: config
+                .operation_types
+                .as_ref()
+                .map(|types| types.iter().map(|kind| kind.as_str()).collect()),
+            on_error: config.on_error,
+            cache,
+        }))
+    }
+
+    /// Evaluates the policy for the operation, and removes the fields denied by the decision.
+    pub async fn authorize(
+        &self,
+        normalized_payload: &Arc<GraphQLNormalizationPayload>,
+        schema_metadata: &SchemaMetadata,
+        variable_payload: &CoerceVariablesPayload,
+        client_request_details: &MutableClientRequestDetails<'_>,
+    ) -> Result<(Arc<GraphQLNormalizationPayload>, Vec<AuthorizationError>), PipelineError> {
+        if self
+            .operation_types
+            .as_ref()
+            .is_some_and(|types| !types.contains(&client_request_details.operation.kind))
+        {
+            return Ok((normalized_payload.clone(), vec![]));
+        }
+
+        let input = OpaInput::new(
+            client_request_details,
+            variable_payload,
+            self.cache.as_ref().map(|cache| cache.headers.as_slice()),
+        );
+        let decision = match self.decide(&input).await {
+            Ok(decision) => decision,
+            Err(err) => {
+                error!(error = %err, "failed to evaluate the opa policy");
+                match self.on_error {
+                    OpaErrorMode::Deny => return Err(PipelineError::OperationNotAuthorized),
+                    OpaErrorMode::Allow => return Ok((normalized_payload.clone(), vec![])),
+                }
+            }
+        };
+
+        if !decision.allow {
+            debug!("operation denied by the opa policy");
+            return Err(PipelineError::OperationNotAuthorized);
+        }
+
+        if decision.denied_fields.is_empty() {
+            return Ok((normalized_payload.clone(), vec![]));
+        }
+
+        remove_denied_fields(
+            normalized_payload,
+            schema_metadata,
+            variable_payload,
+            &decision.denied_fields,
+        )
+    }
+
+    async fn decide(&self, input: &OpaInput<'_>) -> Result<OpaDecision, OpaError> {
+        let body = sonic_rs::to_vec(&OpaRequest { input }).map_err(OpaError::InputSerialization)?;
+
+        let Some(cache) = &self.cache else {
+            return self.evaluate(input, body).await;
+        };
+
+        let key: [u8; 32] = Sha256::digest(&body).into();
+        if let Some(decision) = cache.decisions.get(&key).await {
+            return Ok(decision);
+        }
+
+        let decision = self.evaluate(input, body).await?;
+        cache.decisions.insert(key, decision.clone()).await;
+
+        Ok(decision)
+    }
+
+    async fn evaluate(&self, input: &OpaInput<'_>, body: Vec<u8>) -> Result<OpaDecision, OpaError> {
+        match &self.policy {
+            OpaPolicy::Http { client, url } => {
+                let response = client
+                    .post(url)
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(body)
+                    .send()
+                    .await
+                    .map_err(OpaError::Request)?;
+
+                if !response.status().is_success() {
+                    return Err(OpaError::Status(response.status()));
+                }
+
+                let bytes = response.bytes().await.map_err(OpaError::Request)?;
+                let response: OpaResult =
+                    sonic_rs::from_slice(&bytes).map_err(OpaError::InvalidResponse)?;
+
+                Ok(response.result.into())
+            }
+            OpaPolicy::Wasm {
+                engine,
+                module,
+                entrypoint,
+            } => {
+                // Every evaluation gets its own instance of the module, so they can run concurrently.
+                let mut store = wasmtime::Store::new(engine, ());
+                let policy = Runtime::new(&mut store, module)
+                    .await
+                    .map_err(wasm_error)?
+                    .without_data(&mut store)
+                    .await
+                    .map_err(wasm_error)?;
+                let results: Vec<OpaResult> = policy
+                    .evaluate(&mut store, entrypoint, input)
+                    .await
+                    .map_err(wasm_error)?;
+
+                Ok(results
+                    .into_iter()
+                    .next()
+                    .and_then(|item| item.result)
+                    .into())
+            }
+        }
+    }
+}
+
+fn wasm_error(err: impl std::fmt::Display) -> OpaError {
+    OpaError::WasmEvaluation(err.to_string())
+}
+
+/// Removes the fields and types denied by the policy, with the same null bubbling
+/// as the authorization directives.
+fn remove_denied_fields(
+    normalized_payload: &Arc<GraphQLNormalizationPayload>,
+    schema_metadata: &SchemaMetadata,
+    variable_payload: &CoerceVariablesPayload,
+    denied_fields: &[String],
+) -> Result<(Arc<GraphQLNormalizationPayload>, Vec<AuthorizationError>), PipelineError> {
+    let is_type_denied = |type_name: &str| denied_fields.iter().any(|denied| denied == type_name);
+    let is_field_denied = |type_name: &str, field_name: &str| {
+        denied_fields.iter().any(|denied| {
+            denied
+                .split_once('.')
+                .is_some_and(|(denied_type, denied_field)| {
+                    denied_type == type_name && denied_field == field_name
+                })
+        })
+    };
+
+    let operation_filter_output = OperationFilter::new(schema_metadata).filter(
+        &normalized_payload.root_type_name,
+        &normalized_payload.operation_for_plan.selection_set,
+        variable_payload,
+        |selection| match selection {
+            Selection::Field(field) => {
+                if is_field_denied(field.parent_type_name, field.field_name)
+                    || is_type_denied(field.output_type_name)
+                {
+                    selection.reject(unauthorized_error())
+                } else {
+                    selection.keep()
+                }
+            }
+            Selection::Fragment(fragment) => {
+                if is_type_denied(fragment.type_condition) {
+                    selection.reject(unauthorized_error())
+                } else {
+                    selection.keep()
+                }
+            }
+        },
+    )?;
+
+    if operation_filter_output.errors.is_empty() {
+        return Ok((normalized_payload.clone(), vec![]));
+    }
+
+    let errors = operation_filter_output
+        .errors
+        .iter()
+        .map(AuthorizationError::from)
+        .collect();
+
+    let nulled_field_trie = Trie::from_paths(&operation_filter_output.rejected_paths);
+    let new_operation =
+        rebuild_nulled_operation(&normalized_payload.operation_for_plan, &nulled_field_trie);
+    let new_projection_plan =
+        rebuild_nulled_projection_plan(&normalized_payload.projection_plan, &nulled_field_trie);
+    let new_deferred_projection_plan = normalized_payload
+        .deferred_projection_plan
+        .as_deref()
+        .map(|plan| rebuild_nulled_deferred_projection_plan(plan, &nulled_field_trie));
+
+    Ok((
+        normalized_payload.with_operation(
+            new_operation,
+            new_projection_plan,
+            new_deferred_projection_plan,
+        ),
+        errors,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OpaDecision, OpaResult};
+
+    fn decision(response: &str) -> OpaDecision {
+        sonic_rs::from_str::<OpaResult>(response)
+            .unwrap()
+            .result
+            .into()
+    }
+
+    #[test]
+    fn parses_the_decisions_of_the_policy() {
+        assert!(decision(r#"{ "result": true }"#).allow);
+        assert!(!decision(r#"{ "result": false }"#).allow);
+        // An undefined decision denies the operation
+        assert!(!decision(r#"{}"#).allow);
+
+        let detailed = decision(
+            r#"{ "result": { "allow": true, "denied_fields": ["User.email", "Payment"] } }"#,
+        );
+        assert!(detailed.allow);
+        assert_eq!(detailed.denied_fields, vec!["User.email", "Payment"]);
+
+        // An undefined `allow` rule denies the operation
+        let detailed = decision(r#"{ "result": { "denied_fields": ["User.email"] } }"#);
+        assert!(!detailed.allow);
+        assert!(!decision(r#"{ "result": {} }"#).allow);
+
+        assert!(!decision(r#"{ "result": { "allow": false } }"#).allow);
+    }
+
+    #[test]
+    fn rejects_unknown_fields_in_the_decision() {
+        assert!(sonic_rs::from_str::<OpaResult>(r#"{ "result": { "deny": true } }"#).is_err());
+    }
+}
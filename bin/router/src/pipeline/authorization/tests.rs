@@ -90,8 +90,11 @@ impl SupergraphTestData {
         let operation_for_introspection =
             partitioned_operation.introspection_operation.map(Arc::new);
 
-        let hashes =
-            hash_normalized_operation(&operation_for_plan, operation_for_introspection.as_deref());
+        let hashes = hash_normalized_operation(
+            &operation_for_plan,
+            operation_for_introspection.as_deref(),
+            None,
+        );
 
         let payload = GraphQLNormalizationPayload {
             root_type_name,
@@ -107,6 +110,9 @@ impl SupergraphTestData {
                 operation_type: OperationKind::Query,
                 client_document_hash: "".to_string(),
             },
+            deferred_operation: None,
+            deferred_projection_plan: None,
+            streamed_fields: Default::default(),
         };
 
         let jwt = if let Some(scopes) = scopes {
@@ -0,0 +1,34 @@
+use std::{future::Future, pin::pin};
+
+use ntex::{
+    util::{select, Either},
+    web::HttpRequest,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+/// Runs the execution of a request, cancelling the token
+/// when the client closes the connection before the execution completes.
+///
+/// The execution is still awaited once the token is cancelled,
+/// so it stops on its own, and reports the cancellation like any other error.
+/// Requests without a connection to watch are executed as they are.
+pub async fn cancel_on_client_disconnect<F: Future>(
+    req: &HttpRequest,
+    disconnect_token: &CancellationToken,
+    execution: F,
+) -> F::Output {
+    let Some(io) = req.head().io() else {
+        return execution.await;
+    };
+
+    let mut execution = pin!(execution);
+    match select(io.on_disconnect(), &mut execution).await {
+        Either::Left(()) => {
+            debug!("client closed the connection, cancelling the execution of the request");
+            disconnect_token.cancel();
+            execution.await
+        }
+        Either::Right(output) => output,
+    }
+}
@@ -2,6 +2,8 @@ use hive_router_config::telemetry::ClientIdentificationConfig;
 use hive_router_plan_executor::request_context::{RequestContextError, SharedRequestContext};
 use ntex::http::HeaderMap;
 
+use crate::pipeline::error::PipelineError;
+
 pub struct ClientIdentity {
     pub(crate) name: Option<String>,
     pub(crate) version: Option<String>,
@@ -56,3 +58,23 @@ pub fn identify_client(
         version: client_version,
     })
 }
+
+/// Rejects the anonymous clients, when `telemetry.client_identification` requires a name or a version.
+pub fn require_client_identity(
+    name: Option<&str>,
+    version: Option<&str>,
+    config: &ClientIdentificationConfig,
+) -> Result<(), PipelineError> {
+    if config.require_name && name.is_none_or(str::is_empty) {
+        return Err(PipelineError::ClientIdentificationRequired(
+            config.name_header.get_header_ref().clone(),
+        ));
+    }
+    if config.require_version && version.is_none_or(str::is_empty) {
+        return Err(PipelineError::ClientIdentificationRequired(
+            config.version_header.get_header_ref().clone(),
+        ));
+    }
+
+    Ok(())
+}
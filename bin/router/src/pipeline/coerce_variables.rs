@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use hive_router_internal::telemetry::traces::spans::graphql::GraphQLVariableCoercionSpan;
 use hive_router_plan_executor::execution::plan::CoerceVariablesPayload;
 use hive_router_plan_executor::hooks::on_supergraph_load::SupergraphSnapshot;
-use hive_router_plan_executor::variables::collect_variables;
+use hive_router_plan_executor::variables::{collect_variables, serialized::RawVariables};
 use sonic_rs::Value;
 use tracing::{trace, warn};
 
@@ -14,6 +14,7 @@ use crate::pipeline::normalize::GraphQLNormalizationPayload;
 pub fn coerce_request_variables(
     supergraph: &SupergraphSnapshot,
     variables: &mut HashMap<String, Value>,
+    mut raw_variables: RawVariables,
     normalized_operation: &GraphQLNormalizationPayload,
 ) -> Result<CoerceVariablesPayload, PipelineError> {
     let span = GraphQLVariableCoercionSpan::new();
@@ -21,6 +22,7 @@ pub fn coerce_request_variables(
     match collect_variables(
         &normalized_operation.operation_for_plan,
         variables,
+        &mut raw_variables,
         &supergraph.metadata,
     ) {
         Ok(values) => {
@@ -29,9 +31,7 @@ pub fn coerce_request_variables(
                 values
             );
 
-            Ok(CoerceVariablesPayload {
-                variables_map: values,
-            })
+            Ok(CoerceVariablesPayload::new(values, raw_variables))
         }
         Err(err_msg) => {
             warn!(
@@ -0,0 +1,551 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use hive_router_config::demand_control::{
+    ClientCostBudgetKeyConfig, ClientCostBudgetsBackendConfig, ClientCostBudgetsConfig,
+    DemandControlMode,
+};
+use hive_router_plan_executor::execution::client_request_details::{
+    JwtRequestDetails, MutableClientRequestDetails,
+};
+use http::{header::RETRY_AFTER, HeaderValue};
+use moka::sync::Cache;
+use sha2::{Digest, Sha256};
+use sonic_rs::{json, JsonValueTrait};
+use tracing::{debug, info, warn};
+
+use crate::pipeline::error::PipelineError;
+use crate::storage::{
+    error::StorageError,
+    redis_connections::{RedisConnection, RedisConnections},
+};
+
+/// The identity of the operations without the configured client identity.
+const ANONYMOUS_CLIENT: &str = "anonymous";
+
+/// Checks the budgets of the client, and charges the cost to all of them
+/// when the operation fits, or when the budgets are not enforced.
+///
+/// `ARGV` holds the cost, the enforcement flag (`1` or `0`),
+/// then the max and the TTL in seconds of every key.
+/// Returns `{allowed, spent...}`, the spent budgets after the charge.
+const REDIS_CHARGE_SCRIPT: &str = r#"
+local cost = tonumber(ARGV[1])
+local enforce = ARGV[2] == '1'
+local allowed = 1
+local spent = {}
+for i, key in ipairs(KEYS) do
+  spent[i] = tonumber(redis.call('GET', key) or '0')
+  if spent[i] + cost > tonumber(ARGV[1 + 2 * i]) then
+    allowed = 0
+  end
+end
+if allowed == 1 or not enforce then
+  for i, key in ipairs(KEYS) do
+    spent[i] = redis.call('INCRBY', key, cost)
+    redis.call('EXPIRE', key, ARGV[2 + 2 * i])
+  end
+end
+table.insert(spent, 1, allowed)
+return spent
+"#;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CostBudgetError {
+    #[error("invalid demand_control.client_budgets config: {0}")]
+    Configuration(String),
+    #[error("invalid demand_control.client_budgets redis config: {0}")]
+    Redis(#[from] StorageError),
+}
+
+/// Charges the estimated cost of the operations to the budgets of their client,
+/// identified by `demand_control.client_budgets.key`.
+///
+/// The budgets are charged once per execution,
+/// so the requests deduplicated into an in-flight request are not charged.
+pub struct CostBudgetRuntime {
+    mode: DemandControlMode,
+    key: ClientCostBudgetKeyConfig,
+    windows: Vec<BudgetWindow>,
+    store: Box<dyn CostBudgetStore>,
+    expose_extension: bool,
+}
+
+struct BudgetWindow {
+    max: u64,
+    length_secs: u64,
+}
+
+/// The budgets of a client, after one of its operations.
+#[derive(Debug)]
+pub struct CostBudgetDecision {
+    pub allowed: bool,
+    pub windows: Vec<CostBudgetWindowState>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CostBudgetWindowState {
+    pub window: Duration,
+    pub max: u64,
+    pub remaining: u64,
+    /// How long until the window is over, and the budget is full again.
+    pub reset_after: Duration,
+}
+
+impl CostBudgetDecision {
+    /// The value of the `costBudget` extension of the response.
+    pub fn to_extension(&self) -> sonic_rs::Value {
+        let windows: Vec<sonic_rs::Value> = self
+            .windows
+            .iter()
+            .map(|window| {
+                json!({
+                    "windowSeconds": window.window.as_secs(),
+                    "max": window.max,
+                    "remaining": window.remaining,
+                    "resetSeconds": ceil_secs(window.reset_after),
+                })
+            })
+            .collect();
+        json!({ "windows": windows })
+    }
+
+    /// How long the client has to wait before an operation of the given cost fits in its budgets.
+    fn retry_after(&self, cost: u64) -> Duration {
+        self.windows
+            .iter()
+            .filter(|window| window.remaining < cost)
+            .map(|window| window.reset_after)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// A window of a budget, at the time of the charge.
+struct WindowSlot {
+    /// The index of the window since the Unix epoch.
+    index: u64,
+    length_secs: u64,
+    max: u64,
+    reset_after: Duration,
+}
+
+impl CostBudgetRuntime {
+    pub fn from_config(
+        config: Option<&ClientCostBudgetsConfig>,
+        redis_connections: &RedisConnections,
+    ) -> Result<Option<Self>, CostBudgetError> {
+        let Some(config) = config else {
+            return Ok(None);
+        };
+
+        if config.windows.is_empty() {
+            return Err(CostBudgetError::Configuration(
+                "at least one window is required".to_string(),
+            ));
+        }
+
+        let windows = config
+            .windows
+            .iter()
+            .map(|window| {
+                if window.window.as_secs() == 0 || window.window.subsec_nanos() != 0 {
+                    return Err(CostBudgetError::Configuration(format!(
+                        "window '{:?}' must be a whole number of seconds",
+                        window.window
+                    )));
+                }
+                Ok(BudgetWindow {
+                    max: window.max,
+                    length_secs: window.window.as_secs(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let longest_window = windows
+            .iter()
+            .map(|window| window.length_secs)
+            .max()
+            .unwrap_or_default();
+
+        let store: Box<dyn CostBudgetStore> = match &config.backend {
+            ClientCostBudgetsBackendConfig::Memory { max_entries } => {
+                Box::new(MemoryCostBudgetStore {
+                    // an idle client has a full budget once the longest window is over
+                    cache: Cache::builder()
+                        .max_capacity(*max_entries)
+                        .time_to_idle(Duration::from_secs(longest_window))
+                        .build(),
+                })
+            }
+            ClientCostBudgetsBackendConfig::Redis { url, key_prefix } => {
+                Box::new(RedisCostBudgetStore {
+                    connection: redis_connections.connect("client_budgets", url)?,
+                    key_prefix: key_prefix.clone(),
+                })
+            }
+        };
+
+        info!(mode = ?config.mode, windows = windows.len(), "client cost budgets enabled");
+
+        Ok(Some(Self {
+            mode: config.mode,
+            key: config.key.clone(),
+            windows,
+            store,
+            expose_extension: config.expose_extension,
+        }))
+    }
+
+    /// Charges the estimated cost of the operation to the budgets of its client.
+    ///
+    /// Fails with `PipelineError::CostBudgetExceeded` in `enforce` mode,
+    /// when the operation does not fit in one of the budgets.
+    /// Returns the budgets to report in the response, when `expose_extension` is enabled.
+    ///
+    /// The operations are allowed when the backend fails, the errors are logged.
+    pub async fn charge(
+        &self,
+        client_request_details: &MutableClientRequestDetails<'_>,
+        estimated_cost: u64,
+    ) -> Result<Option<CostBudgetDecision>, PipelineError> {
+        let client = self.client_key(client_request_details);
+        let Some(decision) = self.charge_client(&client, estimated_cost).await else {
+            return Ok(None);
+        };
+
+        if !decision.allowed {
+            match self.mode {
+                DemandControlMode::Enforce => {
+                    debug!(client = %client, estimated_cost, "operation exceeds the cost budget of the client");
+                    return Err(PipelineError::CostBudgetExceeded {
+                        response_headers: vec![(
+                            RETRY_AFTER,
+                            HeaderValue::from(
+                                ceil_secs(decision.retry_after(estimated_cost)).max(1),
+                            ),
+                        )],
+                    });
+                }
+                DemandControlMode::Measure => {
+                    warn!(client = %client, estimated_cost, "operation exceeds the cost budget of the client, allowing it in measure mode");
+                }
+            }
+        }
+
+        Ok(self.expose_extension.then_some(decision))
+    }
+
+    async fn charge_client(&self, client: &str, cost: u64) -> Option<CostBudgetDecision> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let slots = self.slots_at(now);
+        let enforce = self.mode == DemandControlMode::Enforce;
+
+        let (allowed, spent) = match self.store.charge(client, cost, &slots, enforce).await {
+            Ok(charge) => charge,
+            Err(err) => {
+                warn!(error = %err, "failed to charge the cost budget of the client, allowing the operation");
+                return None;
+            }
+        };
+
+        Some(CostBudgetDecision {
+            allowed,
+            windows: slots
+                .iter()
+                .zip(spent)
+                .map(|(slot, spent)| CostBudgetWindowState {
+                    window: Duration::from_secs(slot.length_secs),
+                    max: slot.max,
+                    remaining: slot.max.saturating_sub(spent),
+                    reset_after: slot.reset_after,
+                })
+                .collect(),
+        })
+    }
+
+    fn slots_at(&self, since_epoch: Duration) -> Vec<WindowSlot> {
+        let now_millis = since_epoch.as_millis() as u64;
+        self.windows
+            .iter()
+            .map(|window| {
+                let length_millis = window.length_secs * 1000;
+                let index = now_millis / length_millis;
+                WindowSlot {
+                    index,
+                    length_secs: window.length_secs,
+                    max: window.max,
+                    reset_after: Duration::from_millis((index + 1) * length_millis - now_millis),
+                }
+            })
+            .collect()
+    }
+
+    /// The identity of the client.
+    /// Falls back to the anonymous client when the configured identity is missing.
+    fn client_key(&self, client_request_details: &MutableClientRequestDetails<'_>) -> String {
+        let identity = match &self.key {
+            ClientCostBudgetKeyConfig::Header { name } => client_request_details
+                .headers
+                .get(name.get_header_ref())
+                .and_then(|value| value.to_str().ok())
+                .filter(|value| !value.is_empty())
+                .map(|value| format!("header:{value}")),
+            ClientCostBudgetKeyConfig::ApiKey { header } => client_request_details
+                .headers
+                .get(header.get_header_ref())
+                .and_then(|value| value.to_str().ok())
+                .filter(|value| !value.is_empty())
+                .map(|value| format!("api_key:{:x}", Sha256::digest(value.as_bytes()))),
+            ClientCostBudgetKeyConfig::JwtClaim { claim } => {
+                match client_request_details.jwt.as_ref() {
+                    JwtRequestDetails::Authenticated { claims, .. } => claims
+                        .get(claim.as_str())
+                        .map(|value| match value.as_str() {
+                            Some(value) => format!("jwt:{value}"),
+                            None => format!("jwt:{value}"),
+                        }),
+                    JwtRequestDetails::Unauthenticated => None,
+                }
+            }
+        };
+
+        identity.unwrap_or_else(|| ANONYMOUS_CLIENT.to_string())
+    }
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_micros().div_ceil(1_000_000) as u64
+}
+
+#[async_trait]
+trait CostBudgetStore: Send + Sync {
+    /// Charges the cost to the windows of the client,
+    /// and returns whether the cost fits in all of them, with the spent budget of every window.
+    async fn charge(
+        &self,
+        client: &str,
+        cost: u64,
+        slots: &[WindowSlot],
+        enforce: bool,
+    ) -> Result<(bool, Vec<u64>), redis::RedisError>;
+}
+
+/// The spent budget of a client in a window.
+#[derive(Clone, Copy, Default)]
+struct WindowUsage {
+    index: u64,
+    spent: u64,
+}
+
+struct MemoryCostBudgetStore {
+    /// The spent budgets of every client, one per configured window.
+    cache: Cache<String, Arc<Mutex<Vec<WindowUsage>>>>,
+}
+
+#[async_trait]
+impl CostBudgetStore for MemoryCostBudgetStore {
+    async fn charge(
+        &self,
+        client: &str,
+        cost: u64,
+        slots: &[WindowSlot],
+        enforce: bool,
+    ) -> Result<(bool, Vec<u64>), redis::RedisError> {
+        let usage = self.cache.get_with_by_ref(client, || {
+            Arc::new(Mutex::new(vec![WindowUsage::default(); slots.len()]))
+        });
+        let mut usage = match usage.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        Ok(charge_usage(&mut usage, slots, cost, enforce))
+    }
+}
+
+fn charge_usage(
+    usage: &mut [WindowUsage],
+    slots: &[WindowSlot],
+    cost: u64,
+    enforce: bool,
+) -> (bool, Vec<u64>) {
+    for (usage, slot) in usage.iter_mut().zip(slots) {
+        if usage.index != slot.index {
+            *usage = WindowUsage {
+                index: slot.index,
+                spent: 0,
+            };
+        }
+    }
+
+    let allowed = usage
+        .iter()
+        .zip(slots)
+        .all(|(usage, slot)| usage.spent.saturating_add(cost) <= slot.max);
+
+    if allowed || !enforce {
+        for usage in usage.iter_mut() {
+            usage.spent = usage.spent.saturating_add(cost);
+        }
+    }
+
+    (allowed, usage.iter().map(|usage| usage.spent).collect())
+}
+
+/// Keeps the spent budgets in Redis, one key per client and window,
+/// expiring at the end of the window.
+struct RedisCostBudgetStore {
+    connection: RedisConnection,
+    key_prefix: String,
+}
+
+#[async_trait]
+impl CostBudgetStore for RedisCostBudgetStore {
+    async fn charge(
+        &self,
+        client: &str,
+        cost: u64,
+        slots: &[WindowSlot],
+        enforce: bool,
+    ) -> Result<(bool, Vec<u64>), redis::RedisError> {
+        let mut connection = self.connection.get().await?;
+
+        let mut command = redis::cmd("EVAL");
+        command.arg(REDIS_CHARGE_SCRIPT).arg(slots.len());
+        for slot in slots {
+            command.arg(format!(
+                "{}{}:{}:{}",
+                self.key_prefix, client, slot.length_secs, slot.index
+            ));
+        }
+        command.arg(cost).arg(u8::from(enforce));
+        for slot in slots {
+            command
+                .arg(slot.max)
+                .arg(ceil_secs(slot.reset_after).max(1));
+        }
+
+        let mut result: Vec<u64> = command.query_async(&mut connection).await?;
+        if result.is_empty() {
+            return Ok((true, vec![0; slots.len()]));
+        }
+        let allowed = result.remove(0) == 1;
+
+        Ok((allowed, result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hive_router_config::demand_control::ClientCostBudgetWindowConfig;
+
+    use super::*;
+
+    fn runtime(mode: DemandControlMode) -> CostBudgetRuntime {
+        CostBudgetRuntime::from_config(
+            Some(&ClientCostBudgetsConfig {
+                mode,
+                key: ClientCostBudgetKeyConfig::Header {
+                    name: "x-tenant-id".into(),
+                },
+                windows: vec![
+                    ClientCostBudgetWindowConfig {
+                        max: 10,
+                        window: Duration::from_secs(60),
+                    },
+                    ClientCostBudgetWindowConfig {
+                        max: 15,
+                        window: Duration::from_secs(3600),
+                    },
+                ],
+                backend: ClientCostBudgetsBackendConfig::default(),
+                expose_extension: true,
+            }),
+            &RedisConnections::default(),
+        )
+        .unwrap()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rejects_operations_exceeding_any_window_in_enforce_mode() {
+        let runtime = runtime(DemandControlMode::Enforce);
+
+        let first = runtime.charge_client("header:a", 6).await.unwrap();
+        assert!(first.allowed);
+        assert_eq!(first.windows[0].remaining, 4);
+        assert_eq!(first.windows[1].remaining, 9);
+
+        let second = runtime.charge_client("header:a", 6).await.unwrap();
+        assert!(!second.allowed);
+        assert_eq!(
+            second.windows[0].remaining, 4,
+            "rejected operations are not charged"
+        );
+        assert!(second.retry_after(6) <= Duration::from_secs(60));
+        assert!(second.retry_after(6) > Duration::ZERO);
+
+        let other_client = runtime.charge_client("header:b", 6).await.unwrap();
+        assert!(other_client.allowed);
+    }
+
+    #[tokio::test]
+    async fn charges_exceeding_operations_in_measure_mode() {
+        let runtime = runtime(DemandControlMode::Measure);
+
+        runtime.charge_client("header:a", 8).await.unwrap();
+        let second = runtime.charge_client("header:a", 8).await.unwrap();
+        assert!(!second.allowed);
+        assert_eq!(second.windows[0].remaining, 0);
+        assert_eq!(second.windows[1].remaining, 0);
+    }
+
+    #[test]
+    fn resets_the_budget_of_a_new_window() {
+        let slot = |index| WindowSlot {
+            index,
+            length_secs: 60,
+            max: 10,
+            reset_after: Duration::from_secs(60),
+        };
+        let mut usage = vec![WindowUsage::default()];
+
+        assert_eq!(
+            charge_usage(&mut usage, &[slot(1)], 10, true),
+            (true, vec![10])
+        );
+        assert_eq!(
+            charge_usage(&mut usage, &[slot(1)], 1, true),
+            (false, vec![10])
+        );
+        assert_eq!(
+            charge_usage(&mut usage, &[slot(2)], 1, true),
+            (true, vec![1])
+        );
+    }
+
+    #[test]
+    fn requires_whole_seconds_windows() {
+        let config = ClientCostBudgetsConfig {
+            mode: DemandControlMode::Enforce,
+            key: ClientCostBudgetKeyConfig::JwtClaim {
+                claim: "sub".to_string(),
+            },
+            windows: vec![ClientCostBudgetWindowConfig {
+                max: 10,
+                window: Duration::from_millis(1500),
+            }],
+            backend: ClientCostBudgetsBackendConfig::default(),
+            expose_extension: true,
+        };
+        assert!(
+            CostBudgetRuntime::from_config(Some(&config), &RedisConnections::default()).is_err()
+        );
+    }
+}
@@ -20,6 +20,7 @@ use hive_router_plan_executor::{
 };
 use hive_router_query_planner::{
     ast::normalization::error::NormalizationError, planner::PlannerError,
+    utils::cancellation::CancellationError,
 };
 use http::{header, HeaderValue};
 use http::{HeaderName, Method, StatusCode};
@@ -69,6 +70,12 @@ pub enum PipelineError {
     #[error("Cannot perform mutations over GET")]
     #[strum(serialize = "MUTATION_NOT_ALLOWED_OVER_HTTP_GET")]
     MutationNotAllowedOverHttpGet,
+    #[error("GraphQL requests over GET are disabled")]
+    #[strum(serialize = "METHOD_NOT_ALLOWED")]
+    GetRequestsDisabled,
+    #[error("Only persisted operations can be sent over GET")]
+    #[strum(serialize = "PERSISTED_OPERATION_REQUIRED_OVER_HTTP_GET")]
+    GetRequestWithoutPersistedOperation,
     #[error("Failed to parse query parameters")]
     #[strum(serialize = "UNPROCESSABLE_QUERY_PARAMS")]
     GetUnprocessableQueryParams(#[from] QueryPayloadError),
@@ -86,9 +93,18 @@ pub enum PipelineError {
     #[error("Failed to parse GraphQL operation: {0}")]
     #[strum(serialize = "GRAPHQL_PARSE_FAILED")]
     FailedToParseOperation(#[from] Arc<graphql_tools::parser::query::ParseError>),
+    #[error("Operation document exceeds the size limit of {0} bytes")]
+    #[strum(serialize = "DOCUMENT_SIZE_LIMIT_EXCEEDED")]
+    DocumentSizeLimitExceeded(usize),
     #[error("Persisted document not found: {0}")]
     #[strum(serialize = "PERSISTED_DOCUMENT_NOT_FOUND")]
     PersistedDocumentNotFound(String),
+    #[error("PersistedQueryNotFound")]
+    #[strum(serialize = "PERSISTED_QUERY_NOT_FOUND")]
+    PersistedQueryNotFound,
+    #[error("provided sha does not match query")]
+    #[strum(serialize = "PERSISTED_QUERY_HASH_MISMATCH")]
+    PersistedQueryHashMismatch,
     #[error("Persisted document id is required")]
     #[strum(serialize = "PERSISTED_DOCUMENT_ID_REQUIRED")]
     PersistedDocumentIdRequired,
@@ -107,7 +123,7 @@ pub enum PipelineError {
     #[error("Failed to normalize GraphQL operation")]
     #[strum(serialize = "OPERATION_RESOLUTION_FAILURE")]
     NormalizationError(#[from] Arc<NormalizationError>),
-    #[error("Failed to collect GraphQL variables: {0}")]
+    #[error("{0}")]
     #[strum(serialize = "BAD_USER_INPUT")]
     VariablesCoercionError(String),
     #[error("Validation errors")]
@@ -116,12 +132,18 @@ pub enum PipelineError {
     #[error("Authorization failed")]
     #[strum(serialize = "UNAUTHORIZED_OPERATION")]
     AuthorizationFailed(Vec<AuthorizationError>),
+    #[error("The operation is not authorized")]
+    #[strum(serialize = "UNAUTHORIZED_OPERATION")]
+    OperationNotAuthorized,
     #[error("Failed to execute a plan: {0}")]
     #[strum(serialize = "PLAN_EXECUTION_FAILED")]
     PlanExecutionError(#[from] PlanExecutionError),
     #[error("Failed to produce a plan: {0}")]
     #[strum(serialize = "QUERY_PLAN_BUILD_FAILED")]
-    PlannerError(#[from] Arc<PlannerError>),
+    PlannerError(Arc<PlannerError>),
+    #[error("Query planning exceeded the configured timeout")]
+    #[strum(serialize = "PLANNING_TIMEOUT")]
+    PlanningTimeout,
     #[error(transparent)]
     #[strum(serialize = "OVERRIDE_LABEL_EVALUATION_FAILED")]
     LabelEvaluationError(#[from] LabelEvaluationError),
@@ -130,6 +152,9 @@ pub enum PipelineError {
     #[error("Required CSRF header(s) not present")]
     #[strum(serialize = "CSRF_PREVENTION_FAILED")]
     CsrfPreventionFailed,
+    #[error("The client must identify itself with the '{0}' header")]
+    #[strum(serialize = "CLIENT_IDENTIFICATION_REQUIRED")]
+    ClientIdentificationRequired(HeaderName),
 
     // JWT-auth plugin errors
     #[error(transparent)]
@@ -143,6 +168,9 @@ pub enum PipelineError {
     #[error("Failed to evaluate introspection expression: {0}")]
     #[strum(serialize = "INTROSPECTION_PERMISSION_EVALUATION_ERROR")]
     IntrospectionPermissionEvaluationError(String),
+    #[error("Failed to evaluate query plan exposure expression: {0}")]
+    #[strum(serialize = "EXPOSE_QUERY_PLAN_PERMISSION_EVALUATION_ERROR")]
+    ExposeQueryPlanPermissionEvaluationError(String),
     #[error("Introspection queries are disabled")]
     #[strum(serialize = "INTROSPECTION_DISABLED")]
     IntrospectionDisabled,
@@ -154,6 +182,9 @@ pub enum PipelineError {
     #[error("Subscriptions are not supported over accepted transport(s)")]
     #[strum(serialize = "SUBSCRIPTIONS_TRANSPORT_NOT_SUPPORTED")]
     SubscriptionsTransportNotSupported,
+    #[error("Too many active subscriptions, try again later")]
+    #[strum(serialize = "TOO_MANY_SUBSCRIPTIONS")]
+    TooManySubscriptions,
 
     #[error(transparent)]
     #[strum(serialize = "READ_BODY_STREAM_ERROR")]
@@ -177,6 +208,12 @@ pub enum PipelineError {
         response_headers: PipelineErrorAdditionalHeaders,
     },
 
+    #[error("Too many requests, the rate limit of the client is exceeded")]
+    #[strum(serialize = "RATE_LIMITED")]
+    RateLimited {
+        response_headers: PipelineErrorAdditionalHeaders,
+    },
+
     // Demand Control
     #[error("Operation estimated cost exceeds max cost")]
     #[strum(serialize = "COST_ESTIMATED_TOO_EXPENSIVE")]
@@ -184,6 +221,12 @@ pub enum PipelineError {
         response_headers: PipelineErrorAdditionalHeaders,
     },
 
+    #[error("Operation estimated cost exceeds the cost budget of the client")]
+    #[strum(serialize = "COST_BUDGET_EXCEEDED")]
+    CostBudgetExceeded {
+        response_headers: PipelineErrorAdditionalHeaders,
+    },
+
     #[error(
         "Exactly one slicing argument is required for field '{field_name}', but found {found}"
     )]
@@ -228,11 +271,34 @@ impl From<Arc<ParserCacheError>> for PipelineError {
     }
 }
 
+impl From<Arc<PlannerError>> for PipelineError {
+    fn from(value: Arc<PlannerError>) -> Self {
+        match value.as_ref() {
+            PlannerError::CancellationError(CancellationError::TimedOut) => {
+                PipelineError::PlanningTimeout
+            }
+            _ => PipelineError::PlannerError(value),
+        }
+    }
+}
+
 impl PipelineError {
     pub fn additional_response_headers(&self) -> Option<&Vec<(HeaderName, HeaderValue)>> {
         match self {
             PipelineError::CostEstimatedTooExpensive { response_headers } => Some(response_headers),
             PipelineError::NoSupergraphAvailable { response_headers } => Some(response_headers),
+            PipelineError::RateLimited { response_headers } => Some(response_headers),
+            PipelineError::CostBudgetExceeded { response_headers } => Some(response_headers),
+            _ => None,
+        }
+    }
+
+    /// The methods the request can be retried with, for the `Allow` header of `405` responses.
+    pub fn allowed_methods(&self) -> Option<&'static str> {
+        match self {
+            PipelineError::MutationNotAllowedOverHttpGet
+            | PipelineError::GetRequestsDisabled
+            | PipelineError::GetRequestWithoutPersistedOperation => Some("POST"),
             _ => None,
         }
     }
@@ -258,6 +324,7 @@ impl PipelineError {
     pub fn default_status_code(&self, prefer_ok: bool) -> StatusCode {
         match (self, prefer_ok) {
             (Self::PlannerError(_), _) => StatusCode::INTERNAL_SERVER_ERROR,
+            (Self::PlanningTimeout, _) => StatusCode::GATEWAY_TIMEOUT,
             (Self::PlanExecutionError(_), _) => StatusCode::INTERNAL_SERVER_ERROR,
             (Self::LabelEvaluationError(_), _) => StatusCode::INTERNAL_SERVER_ERROR,
             (Self::JwtForwardingError(_), _) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -270,6 +337,10 @@ impl PipelineError {
             (Self::FailedToParseExtensions(_), _) => StatusCode::BAD_REQUEST,
             (Self::PersistedDocumentNotFound(_), false) => StatusCode::BAD_REQUEST,
             (Self::PersistedDocumentNotFound(_), true) => StatusCode::OK,
+            // APQ clients expect a GraphQL response to retry with the full operation
+            (Self::PersistedQueryNotFound, _) => StatusCode::OK,
+            (Self::PersistedQueryHashMismatch, false) => StatusCode::BAD_REQUEST,
+            (Self::PersistedQueryHashMismatch, true) => StatusCode::OK,
             (Self::PersistedDocumentIdRequired, false) => StatusCode::BAD_REQUEST,
             (Self::PersistedDocumentIdRequired, true) => StatusCode::OK,
             (Self::PersistedDocumentExtraction(_), false) => StatusCode::BAD_REQUEST,
@@ -278,6 +349,8 @@ impl PipelineError {
             (Self::PersistedDocumentIdExpressionEvaluationError(_), _) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
+            (Self::DocumentSizeLimitExceeded(_), false) => StatusCode::BAD_REQUEST,
+            (Self::DocumentSizeLimitExceeded(_), true) => StatusCode::OK,
             (Self::FailedToParseOperation(_), false) => StatusCode::BAD_REQUEST,
             (Self::FailedToParseOperation(_), true) => StatusCode::OK,
             (Self::FailedToMinifyParsedOperation(_), false) => StatusCode::BAD_REQUEST,
@@ -286,28 +359,38 @@ impl PipelineError {
             (Self::VariablesCoercionError(_), false) => StatusCode::BAD_REQUEST,
             (Self::VariablesCoercionError(_), true) => StatusCode::OK,
             (Self::MutationNotAllowedOverHttpGet, _) => StatusCode::METHOD_NOT_ALLOWED,
+            (Self::GetRequestsDisabled, _) => StatusCode::METHOD_NOT_ALLOWED,
+            (Self::GetRequestWithoutPersistedOperation, _) => StatusCode::METHOD_NOT_ALLOWED,
             (Self::ValidationErrors(_), true) => StatusCode::OK,
             (Self::ValidationErrors(_), false) => StatusCode::BAD_REQUEST,
             (Self::CostEstimatedTooExpensive { .. }, true) => StatusCode::OK,
             (Self::CostEstimatedTooExpensive { .. }, false) => StatusCode::BAD_REQUEST,
+            (Self::CostBudgetExceeded { .. }, _) => StatusCode::TOO_MANY_REQUESTS,
             (Self::CostInvalidSlicingArguments { .. }, true) => StatusCode::OK,
             (Self::CostInvalidSlicingArguments { .. }, false) => StatusCode::BAD_REQUEST,
             (Self::AuthorizationFailed(_), _) => StatusCode::FORBIDDEN,
+            (Self::OperationNotAuthorized, _) => StatusCode::FORBIDDEN,
             (Self::MissingContentTypeHeader, _) => StatusCode::NOT_ACCEPTABLE,
             (Self::UnsupportedContentType, _) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             (Self::CsrfPreventionFailed, _) => StatusCode::FORBIDDEN,
+            (Self::ClientIdentificationRequired(_), _) => StatusCode::BAD_REQUEST,
             (Self::JwtError(err), _) => err.status_code(),
             (Self::IntrospectionPermissionEvaluationError(_), _) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
             (Self::IntrospectionDisabled, _) => StatusCode::FORBIDDEN,
+            (Self::ExposeQueryPlanPermissionEvaluationError(_), _) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
             (Self::SubscriptionsNotSupported, _) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             (Self::SubscriptionsTransportNotSupported, _) => StatusCode::NOT_ACCEPTABLE,
+            (Self::TooManySubscriptions, _) => StatusCode::SERVICE_UNAVAILABLE,
             (Self::ReadBodyStreamError(err), _) => err.status_code(),
             (Self::TimeoutError, _) => StatusCode::GATEWAY_TIMEOUT,
             (Self::HeaderPropagation(_), _) => StatusCode::INTERNAL_SERVER_ERROR,
             (Self::QueryPlanSerializationFailed(_), _) => StatusCode::INTERNAL_SERVER_ERROR,
             (Self::NoSupergraphAvailable { .. }, _) => StatusCode::SERVICE_UNAVAILABLE,
+            (Self::RateLimited { .. }, _) => StatusCode::TOO_MANY_REQUESTS,
             (Self::CoprocessorError(err), _) => err.status_code(),
             (Self::RequestContextError(_), _) => StatusCode::INTERNAL_SERVER_ERROR,
 
@@ -325,7 +408,7 @@ pub fn handle_pipeline_error(
     shared_state: &RouterSharedState,
     response_mode: &ResponseMode,
 ) -> web::HttpResponse {
-    let status = if matches!(response_mode, ResponseMode::StreamOnly(_)) {
+    let mut status = if matches!(response_mode, ResponseMode::StreamOnly(_)) {
         // alwats status OK for streaming response modes, because we accept
         // the stream and then stream the error from within the stream by default
         StatusCode::OK
@@ -342,6 +425,10 @@ pub fn handle_pipeline_error(
         }
     }
 
+    if let Some(allowed_methods) = err.allowed_methods() {
+        res.header(header::ALLOW, allowed_methods);
+    }
+
     let mut errors = match err {
         PipelineError::ValidationErrors(ref validation_errors) => {
             validation_errors.iter().map(|error| error.into()).collect()
@@ -365,6 +452,14 @@ pub fn handle_pipeline_error(
         }
     };
 
+    // the mapped status codes of the errors, plugins can still change it in on_graphql_error
+    if !matches!(response_mode, ResponseMode::StreamOnly(_)) {
+        if let Some(mapped_status) = shared_state.error_status_codes.status_code_for(&errors) {
+            status = mapped_status;
+            res.status(status);
+        }
+    }
+
     if let Some(plugins) = &shared_state.plugins {
         let plugin_context = req.extensions().get::<Arc<PluginContext>>().cloned();
         let request_context = req.read_request_context().ok();
@@ -412,7 +507,10 @@ pub fn handle_pipeline_error(
             )
             .streaming(sse::create_stream(
                 Box::pin(stream::once(async move { data })),
-                std::time::Duration::from_secs(10),
+                shared_state
+                    .router_config
+                    .subscriptions
+                    .client_heartbeat_interval,
             )),
         ResponseMode::StreamOnly(StreamContentType::ApolloMultipartHTTP) => res
             .header(
@@ -421,7 +519,10 @@ pub fn handle_pipeline_error(
             )
             .streaming(multipart_subscribe::create_apollo_multipart_http_stream(
                 Box::pin(stream::once(async move { data })),
-                std::time::Duration::from_secs(10),
+                shared_state
+                    .router_config
+                    .subscriptions
+                    .client_heartbeat_interval,
             )),
         ResponseMode::Laboratory => {
             unreachable!(
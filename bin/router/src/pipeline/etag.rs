@@ -0,0 +1,165 @@
+use hive_router_plan_executor::hooks::on_graphql_params::GraphQLParams;
+use hive_router_query_planner::state::supergraph_state::OperationKind;
+use http::{
+    header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+    HeaderValue, Method, StatusCode,
+};
+use ntex::{http::HeaderMap, web};
+use xxhash_rust::xxh3::xxh3_128;
+
+use crate::{pipeline::apq::persisted_query_hash, shared_state::SharedRouterSingleResponse};
+
+/// Whether the response of the request can be validated with an `ETag`,
+/// only queries sent over `GET` or through Automatic Persisted Queries are.
+pub fn is_conditional_request(
+    method: &Method,
+    operation_kind: Option<&OperationKind>,
+    graphql_params: &GraphQLParams,
+) -> bool {
+    matches!(operation_kind, None | Some(OperationKind::Query))
+        && (method == Method::GET || persisted_query_hash(graphql_params).is_some())
+}
+
+/// Sets a strong `ETag` on a successful response, and replaces it with
+/// a `304 Not Modified` response when the client already holds it.
+pub fn into_response_with_etag(
+    shared_response: SharedRouterSingleResponse,
+    request_headers: &HeaderMap,
+) -> web::HttpResponse {
+    if shared_response.status != StatusCode::OK {
+        return shared_response.into();
+    }
+
+    let etag = strong_etag(&shared_response.body);
+    if !if_none_match(request_headers, &etag) {
+        let mut response: web::HttpResponse = shared_response.into();
+        response.headers_mut().insert(ETAG, etag);
+        return response;
+    }
+
+    // the headers describing the body are left out, as there is no body
+    let mut response = web::HttpResponse::build(StatusCode::NOT_MODIFIED);
+    for (header_name, header_value) in shared_response.headers.iter() {
+        if header_name != CONTENT_TYPE && header_name != CONTENT_LENGTH {
+            response.set_header(header_name, header_value);
+        }
+    }
+    response.set_header(ETAG, etag);
+    response.finish()
+}
+
+fn strong_etag(body: &[u8]) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{:032x}\"", xxh3_128(body)))
+        .expect("a quoted hex string is a valid header value")
+}
+
+/// `If-None-Match` holds a list of entity tags, or `*`, compared with the weak comparison.
+/// https://www.rfc-editor.org/rfc/rfc9110#name-if-none-match
+fn if_none_match(request_headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    let Ok(etag) = etag.to_str() else {
+        return false;
+    };
+    request_headers
+        .get_all(IF_NONE_MATCH)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn response(body: &'static str) -> SharedRouterSingleResponse {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        SharedRouterSingleResponse {
+            body: body.into(),
+            headers: Arc::new(headers),
+            status: StatusCode::OK,
+            error_count: 0,
+        }
+    }
+
+    fn request_headers(if_none_match: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, HeaderValue::from_static(if_none_match));
+        headers
+    }
+
+    #[test]
+    fn sets_the_same_etag_for_the_same_body() {
+        let first = into_response_with_etag(response(r#"{"data":{}}"#), &HeaderMap::new());
+        let second = into_response_with_etag(response(r#"{"data":{}}"#), &HeaderMap::new());
+        let other = into_response_with_etag(response(r#"{"data":null}"#), &HeaderMap::new());
+
+        assert_eq!(first.status(), StatusCode::OK);
+        assert!(first.headers().get(ETAG).is_some());
+        assert_eq!(first.headers().get(ETAG), second.headers().get(ETAG));
+        assert_ne!(first.headers().get(ETAG), other.headers().get(ETAG));
+    }
+
+    #[test]
+    fn answers_matching_etags_with_not_modified() {
+        let etag = strong_etag(br#"{"data":{}}"#);
+        let etag = etag.to_str().unwrap().to_string();
+
+        for if_none_match in [
+            etag.clone(),
+            format!("W/{etag}"),
+            format!("\"other\", {etag}"),
+            "*".to_string(),
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                IF_NONE_MATCH,
+                HeaderValue::from_str(&if_none_match).unwrap(),
+            );
+            let res = into_response_with_etag(response(r#"{"data":{}}"#), &headers);
+            assert_eq!(res.status(), StatusCode::NOT_MODIFIED, "{if_none_match}");
+            assert!(res.headers().get(CONTENT_TYPE).is_none());
+            assert_eq!(res.headers().get(ETAG).unwrap().to_str().unwrap(), etag);
+        }
+
+        let res =
+            into_response_with_etag(response(r#"{"data":{}}"#), &request_headers("\"other\""));
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn only_validates_queries_over_get_or_apq() {
+        let params = GraphQLParams::default();
+        let apq_params = GraphQLParams {
+            extensions: Some(
+                sonic_rs::from_str(r#"{"persistedQuery":{"version":1,"sha256Hash":"abc"}}"#)
+                    .unwrap(),
+            ),
+            ..Default::default()
+        };
+
+        assert!(is_conditional_request(
+            &Method::GET,
+            Some(&OperationKind::Query),
+            &params
+        ));
+        assert!(is_conditional_request(&Method::GET, None, &params));
+        assert!(is_conditional_request(
+            &Method::POST,
+            Some(&OperationKind::Query),
+            &apq_params
+        ));
+        assert!(!is_conditional_request(
+            &Method::POST,
+            Some(&OperationKind::Query),
+            &params
+        ));
+        assert!(!is_conditional_request(
+            &Method::POST,
+            Some(&OperationKind::Mutation),
+            &apq_params
+        ));
+    }
+}
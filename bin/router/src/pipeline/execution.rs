@@ -1,30 +1,47 @@
+use crate::pipeline::access_log::AccessLogSink;
+use crate::pipeline::demand_control::budget::CostBudgetDecision;
 use crate::pipeline::error::PipelineError;
 use crate::pipeline::normalize::GraphQLNormalizationPayload;
 use crate::schema_state::SelectedSupergraph;
 use crate::shared_state::RouterSharedState;
+use hive_router_config::query_planner::AllowExposeQueryPlanConfig;
+use hive_router_internal::expressions::{
+    BooleanOrProgram, CompileExpression, ExpressionCompileError, ProgramHints,
+};
+use hive_router_internal::telemetry::metrics::catalog::values::GraphQLResponseStatus;
 use hive_router_internal::telemetry::traces::spans::graphql::{
     GraphQLExecuteSpan, GraphQLOperationSpan,
 };
-use hive_router_plan_executor::execution::client_request_details::ClientRequestDetails;
+use hive_router_plan_executor::execution::client_request_details::{
+    ClientRequestDetails, ClientRequestDetailsView,
+};
 use hive_router_plan_executor::execution::demand_control::DemandControlExecutionContext;
+use hive_router_plan_executor::execution::fetch_traces::FetchTraceSink;
 use hive_router_plan_executor::execution::jwt_forward::JwtAuthForwardingPlan;
 use hive_router_plan_executor::execution::operation_name::OperationNameFactory;
 use hive_router_plan_executor::execution::plan::{
-    execute_query_plan, CoerceVariablesPayload, ExecutionResultExtensions, PlanExecutionOutput,
-    QueryPlanExecutionOpts, QueryPlanExecutionResult,
+    execute_query_plan, CoerceVariablesPayload, ExecutionLimits, ExecutionResultExtensions,
+    PlanExecutionOutput, QueryPlanExecutionOpts, QueryPlanExecutionResult,
 };
 use hive_router_plan_executor::headers::response::ResponseHeaderSink;
 
 use hive_router_plan_executor::introspection::resolve::IntrospectionContext;
 use hive_router_plan_executor::plugin_context::PluginRequestState;
 use hive_router_plan_executor::response::graphql_error::GraphQLError;
+use hive_router_query_planner::planner::plan_format::QueryPlanFormat;
 use hive_router_query_planner::planner::plan_nodes::QueryPlan;
 use http::HeaderName;
 use sonic_rs::json;
+use std::collections::BTreeMap;
 use std::sync::Arc;
-use tracing::Instrument;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, Instrument};
+use vrl::core::Value as VrlValue;
 
 pub static EXPOSE_QUERY_PLAN_HEADER: HeaderName = HeaderName::from_static("hive-expose-query-plan");
+pub static EXPOSE_QUERY_PLAN_FORMAT_HEADER: HeaderName =
+    HeaderName::from_static("hive-expose-query-plan-format");
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ExposeQueryPlanMode {
@@ -33,14 +50,87 @@ pub enum ExposeQueryPlanMode {
     DryRun,
 }
 
+/// How long it took to get the query plan of a request, exposed along the query plan.
+pub struct QueryPlanTiming {
+    /// The time spent getting the plan, from the cache or from the query planner
+    pub duration: Duration,
+    pub cache_hit: bool,
+}
+
 pub struct PlannedRequest<'req> {
     pub normalized_payload: Arc<GraphQLNormalizationPayload>,
     pub query_plan_payload: &'req QueryPlan,
+    pub query_plan_timing: QueryPlanTiming,
     pub variable_payload: Arc<CoerceVariablesPayload>,
     pub client_request_details: Arc<ClientRequestDetails<'req>>,
     pub initial_errors: Vec<GraphQLError>,
     pub demand_control_execution_context: Option<DemandControlExecutionContext>,
+    /// The budgets of the client after the charge of the operation, reported in the `costBudget` extension.
+    pub cost_budget: Option<CostBudgetDecision>,
     pub plugin_req_state: Option<PluginRequestState<'req>>,
+    pub incremental_delivery: bool,
+    /// Cancelled when the client closes the connection, see `traffic_shaping.router.cancel_on_client_disconnect`.
+    pub disconnect_token: Option<CancellationToken>,
+    /// Collects the subgraphs fetched for the access log, when the request is sampled by it.
+    pub access_log_sink: Option<AccessLogSink>,
+}
+
+pub fn compile_expose_query_plan_policy(
+    allow_expose: &AllowExposeQueryPlanConfig,
+) -> Result<BooleanOrProgram, ExpressionCompileError> {
+    match allow_expose {
+        AllowExposeQueryPlanConfig::Boolean(b) => Ok(BooleanOrProgram::Value(*b)),
+        AllowExposeQueryPlanConfig::Expression { expression } => {
+            expression.compile_expression(None).map(|program| {
+                let hints = ProgramHints::from_program(&program);
+                BooleanOrProgram::Program(Box::new(program), hints)
+            })
+        }
+    }
+}
+
+/// Reads the mode requested with the `hive-expose-query-plan` header,
+/// and checks that the request is allowed to see the query plan.
+fn resolve_expose_query_plan_mode(
+    expose_query_plan_policy: &BooleanOrProgram,
+    client_request_details: &ClientRequestDetails<'_>,
+) -> Result<ExposeQueryPlanMode, PipelineError> {
+    let requested_mode = match client_request_details
+        .headers
+        .get(&EXPOSE_QUERY_PLAN_HEADER)
+        .map(|value| value.to_str().unwrap_or_default().trim())
+    {
+        Some("true") => ExposeQueryPlanMode::Yes,
+        Some("dry-run") => ExposeQueryPlanMode::DryRun,
+        _ => return Ok(ExposeQueryPlanMode::No),
+    };
+
+    let is_allowed = expose_query_plan_policy
+        .resolve(|| {
+            let mut context_map = BTreeMap::new();
+            context_map.insert("request".into(), client_request_details.to_vrl_value());
+
+            VrlValue::Object(context_map)
+        })
+        .map_err(|e| PipelineError::ExposeQueryPlanPermissionEvaluationError(e.to_string()))?;
+
+    if !is_allowed {
+        debug!("query plan is not exposed because the request is not allowed to see it");
+        return Ok(ExposeQueryPlanMode::No);
+    }
+
+    Ok(requested_mode)
+}
+
+/// Reads the format requested with the `hive-expose-query-plan-format` header,
+/// falling back to the JSON serialization of the query plan.
+fn expose_query_plan_format(client_request_details: &ClientRequestDetails<'_>) -> QueryPlanFormat {
+    client_request_details
+        .headers
+        .get(&EXPOSE_QUERY_PLAN_FORMAT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(QueryPlanFormat::Json)
 }
 
 #[inline]
@@ -50,6 +140,7 @@ pub async fn execute_plan<'exec>(
     planned_request: PlannedRequest<'exec>,
     span: GraphQLOperationSpan,
     response_header_sink: ResponseHeaderSink,
+    fetch_trace_sink: Option<FetchTraceSink>,
 ) -> Result<QueryPlanExecutionResult, PipelineError> {
     let execute_span = GraphQLExecuteSpan::new();
 
@@ -65,27 +156,49 @@ pub async fn execute_plan<'exec>(
     async {
         let mut extensions = ExecutionResultExtensions::default();
 
-        let mut expose_query_plan = ExposeQueryPlanMode::No;
-        if app_state.router_config.query_planner.allow_expose {
-            if let Some(expose_qp_header) = planned_request
-                .client_request_details
-                .headers
-                .get(&EXPOSE_QUERY_PLAN_HEADER)
-            {
-                let str_value = expose_qp_header.to_str().unwrap_or_default().trim();
-                match str_value {
-                    "true" => expose_query_plan = ExposeQueryPlanMode::Yes,
-                    "dry-run" => expose_query_plan = ExposeQueryPlanMode::DryRun,
-                    _ => {}
-                }
-            }
+        if let Some(cost_budget) = &planned_request.cost_budget {
+            extensions
+                .extensions
+                .insert("costBudget".to_string(), cost_budget.to_extension());
         }
 
+        let expose_query_plan = resolve_expose_query_plan_mode(
+            &app_state.expose_query_plan_policy,
+            &planned_request.client_request_details,
+        )?;
+
         if matches!(
             expose_query_plan,
             ExposeQueryPlanMode::Yes | ExposeQueryPlanMode::DryRun
         ) {
-            extensions.query_plan = Some(planned_request.query_plan_payload);
+            let query_plan = planned_request.query_plan_payload;
+            match expose_query_plan_format(&planned_request.client_request_details) {
+                QueryPlanFormat::Json => extensions.query_plan = Some(query_plan),
+                QueryPlanFormat::Apollo => {
+                    extensions.extensions.insert(
+                        "queryPlan".to_string(),
+                        sonic_rs::to_value(&query_plan.to_apollo())
+                            .map_err(PipelineError::QueryPlanSerializationFailed)?,
+                    );
+                }
+                QueryPlanFormat::Text => {
+                    extensions
+                        .extensions
+                        .insert("queryPlan".to_string(), json!(query_plan.to_string()));
+                }
+                QueryPlanFormat::Mermaid => {
+                    extensions
+                        .extensions
+                        .insert("queryPlan".to_string(), json!(query_plan.to_mermaid()));
+                }
+            }
+            extensions.extensions.insert(
+                "queryPlanTiming".to_string(),
+                json!({
+                    "planningMs": planned_request.query_plan_timing.duration.as_secs_f64() * 1000.0,
+                    "cacheHit": planned_request.query_plan_timing.cache_hit,
+                }),
+            );
         }
 
         if matches!(expose_query_plan, ExposeQueryPlanMode::DryRun) {
@@ -100,6 +213,10 @@ pub async fn execute_plan<'exec>(
             }));
         }
 
+        if let Some(access_log_sink) = planned_request.access_log_sink.as_ref() {
+            access_log_sink.record_subgraphs(planned_request.query_plan_payload);
+        }
+
         let jwt_auth_forwarding: Option<JwtAuthForwardingPlan> = if app_state
             .router_config
             .jwt
@@ -120,6 +237,29 @@ pub async fn execute_plan<'exec>(
         };
 
         let operation_name = planned_request.client_request_details.operation.name;
+        let operation_type = planned_request
+            .normalized_payload
+            .operation_identity
+            .operation_type
+            .clone();
+        let telemetry_attributes = app_state
+            .telemetry_attributes
+            .as_ref()
+            .map(|runtime| {
+                runtime.evaluate(
+                    planned_request.client_request_details.as_ref(),
+                    &planned_request.variable_payload,
+                )
+            })
+            .unwrap_or_default();
+        span.record_custom_attributes(&telemetry_attributes.span);
+        let execution_capture = app_state
+            .telemetry_context
+            .metrics
+            .graphql
+            .capture_execution();
+        let reloadable = app_state.reloadable.load_full();
+        let limits = &reloadable.router_config.limits;
         let result = execute_query_plan(QueryPlanExecutionOpts {
             query_plan: planned_request.query_plan_payload,
             operation_for_plan: planned_request
@@ -127,8 +267,11 @@ pub async fn execute_plan<'exec>(
                 .operation_for_plan
                 .clone(),
             projection_plan: planned_request.normalized_payload.projection_plan.clone(),
-            headers_plan: app_state.headers_plan.clone(),
+            headers_plan: reloadable.headers_plan.clone(),
             extensions_plan: app_state.extensions_plan.clone(),
+            error_masking_plan: app_state.error_masking_plan.clone(),
+            error_status_codes: app_state.error_status_codes.clone(),
+            error_policy: app_state.router_config.errors.policy,
             variable_values: planned_request.variable_payload.clone(),
             extensions,
             client_request: planned_request.client_request_details,
@@ -149,10 +292,43 @@ pub async fn execute_plan<'exec>(
                 operation_name,
             ),
             response_header_sink,
+            incremental_delivery: planned_request.incremental_delivery,
+            deferred_projection_plan: planned_request
+                .normalized_payload
+                .deferred_projection_plan
+                .clone(),
+            streamed_fields: planned_request.normalized_payload.streamed_fields.clone(),
+            entity_cache: supergraph.runtime.entity_cache.clone(),
+            fetch_trace_sink,
+            cancellation_token: planned_request.disconnect_token,
+            limits: ExecutionLimits {
+                max_response_size: limits
+                    .max_response_size
+                    .as_ref()
+                    .map(|size| size.to_bytes() as usize),
+                max_entities_per_fetch: limits.max_entities_per_fetch,
+                max_representations_size: limits
+                    .max_representations_size
+                    .as_ref()
+                    .map(|size| size.to_bytes() as usize),
+            },
         })
-        .await?;
+        .await;
+
+        // Streams are only measured until their first response is ready.
+        execution_capture.finish(
+            operation_type.as_str(),
+            match &result {
+                Ok(QueryPlanExecutionResult::Single(output)) if output.error_count == 0 => {
+                    GraphQLResponseStatus::Ok
+                }
+                Ok(QueryPlanExecutionResult::Stream(_)) => GraphQLResponseStatus::Ok,
+                _ => GraphQLResponseStatus::Error,
+            },
+            &telemetry_attributes.metrics,
+        );
 
-        Ok(result)
+        result.map_err(PipelineError::from)
     }
     .instrument(execute_span.span)
     .await
@@ -2,6 +2,7 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
 
+use hive_router_config::http_server::GetRequestsConfig;
 use hive_router_internal::json::MapAccessSerdeExt;
 use hive_router_internal::telemetry::metrics::Metrics;
 use hive_router_plan_executor::hooks::on_graphql_params::{
@@ -11,14 +12,16 @@ use hive_router_plan_executor::plugin_context::PluginRequestState;
 use hive_router_plan_executor::plugin_trait::{EndControlFlow, StartControlFlow};
 use hive_router_plan_executor::plugins::hooks;
 use hive_router_plan_executor::request_context::RequestContextExt;
+use hive_router_plan_executor::variables::serialized::RawVariables;
 use http::{header::CONTENT_TYPE, Method};
 use ntex::util::Bytes;
 use ntex::web::types::Query;
 use ntex::web::HttpRequest;
-use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, Visitor};
+use serde::de::{DeserializeSeed, Error as _, IgnoredAny, MapAccess, Visitor};
 use std::sync::Arc;
 use tracing::{info, trace, warn};
 
+use crate::pipeline::apq::{ApqOutcome, ApqRuntime};
 use crate::pipeline::error::PipelineError;
 use crate::pipeline::header::SingleContentType;
 use crate::pipeline::persisted_documents::extract::{
@@ -57,6 +60,7 @@ struct GraphQLPostInput {
     query: Option<String>,
     operation_name: Option<String>,
     variables: HashMap<String, sonic_rs::Value>,
+    raw_variables: RawVariables,
     extensions: Option<HashMap<String, sonic_rs::Value>>,
     document_id: Option<String>,
     nonstandard_json_fields: Option<HashMap<String, sonic_rs::OwnedLazyValue>>,
@@ -85,6 +89,9 @@ pub struct PreparedOperation {
     /// Represents the resolved document ID, if one was found,
     /// according to the document ID resolver plan.
     pub resolved_document_id: Option<PersistedDocumentId>,
+    /// The JSON of the variables, as sent by the client,
+    /// forwarded as is to the subgraphs when the router doesn't change them.
+    pub raw_variables: RawVariables,
 }
 
 impl PreparedOperation {
@@ -95,8 +102,10 @@ impl PreparedOperation {
         request_context: HttpRequestContext<'_>,
     ) -> Result<Self, PipelineError> {
         let document_id = get_input.document_id.clone();
+        let (graphql_params, raw_variables) = get_input.try_into()?;
         Ok(Self::from_graphql_params(
-            get_input.try_into()?,
+            graphql_params,
+            raw_variables,
             document_id_resolver,
             request_context,
             document_id.as_deref(),
@@ -114,6 +123,7 @@ impl PreparedOperation {
             query,
             operation_name,
             variables,
+            raw_variables,
             extensions,
             document_id,
             nonstandard_json_fields,
@@ -126,6 +136,7 @@ impl PreparedOperation {
                 variables,
                 extensions,
             },
+            raw_variables,
             document_id_resolver,
             request_context,
             document_id.as_deref(),
@@ -136,6 +147,7 @@ impl PreparedOperation {
     #[inline]
     fn from_graphql_params(
         graphql_params: GraphQLParams,
+        raw_variables: RawVariables,
         document_id_resolver: &DocumentIdResolver,
         request_context: HttpRequestContext<'_>,
         document_id: Option<&str>,
@@ -155,24 +167,29 @@ impl PreparedOperation {
         Self {
             graphql_params,
             resolved_document_id: persisted_document_id,
+            raw_variables,
         }
     }
 }
 
 struct GraphQLPostBodySeed<'a> {
     document_id_resolver: &'a DocumentIdResolver,
+    body: &'a Bytes,
 }
 
 impl<'a> GraphQLPostBodySeed<'a> {
     #[inline]
-    fn new(document_id_resolver: &'a DocumentIdResolver) -> Self {
+    fn new(document_id_resolver: &'a DocumentIdResolver, body: &'a Bytes) -> Self {
         Self {
             document_id_resolver,
+            body,
         }
     }
 }
 
-struct GraphQLPostBodyVisitor {
+struct GraphQLPostBodyVisitor<'a> {
+    // the deserialized body, for the variables to keep slices of their JSON
+    body: &'a Bytes,
     // wether to capture extra fields from the POST body
     // besides the query, operation name, variables, extensions and documentId.
     // We only need it when the document ID resolver requires something else than:
@@ -189,6 +206,7 @@ impl<'de> DeserializeSeed<'de> for GraphQLPostBodySeed<'_> {
         D: serde::Deserializer<'de>,
     {
         deserializer.deserialize_map(GraphQLPostBodyVisitor {
+            body: self.body,
             capture_nonstandard_json_fields: self
                 .document_id_resolver
                 .requires_nonstandard_json_fields(),
@@ -196,7 +214,7 @@ impl<'de> DeserializeSeed<'de> for GraphQLPostBodySeed<'_> {
     }
 }
 
-impl<'de> Visitor<'de> for GraphQLPostBodyVisitor {
+impl<'de> Visitor<'de> for GraphQLPostBodyVisitor<'_> {
     type Value = GraphQLPostInput;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -209,7 +227,7 @@ impl<'de> Visitor<'de> for GraphQLPostBodyVisitor {
     {
         let mut query: Option<String> = None;
         let mut operation_name: Option<String> = None;
-        let mut variables: Option<HashMap<String, sonic_rs::Value>> = None;
+        let mut variables: Option<HashMap<String, sonic_rs::LazyValue<'de>>> = None;
         let mut extensions: Option<HashMap<String, sonic_rs::Value>> = None;
         let mut document_id: Option<GraphQLDocumentIdValue> = None;
         let mut nonstandard_json_fields: Option<HashMap<String, sonic_rs::OwnedLazyValue>> =
@@ -237,10 +255,16 @@ impl<'de> Visitor<'de> for GraphQLPostBodyVisitor {
             }
         }
 
+        let (variables, raw_variables) = match variables {
+            Some(variables) => parse_variables(self.body, variables).map_err(A::Error::custom)?,
+            None => Default::default(),
+        };
+
         Ok(GraphQLPostInput {
             query,
             operation_name,
-            variables: variables.unwrap_or_default(),
+            variables,
+            raw_variables,
             extensions,
             document_id: document_id.map(GraphQLDocumentIdValue::into_string),
             nonstandard_json_fields,
@@ -248,18 +272,38 @@ impl<'de> Visitor<'de> for GraphQLPostBodyVisitor {
     }
 }
 
-impl TryInto<GraphQLParams> for GraphQLGetInput {
+/// Parses the variables, keeping the JSON sent by the client for each of them,
+/// as slices of the source they are read from.
+fn parse_variables(
+    source: &Bytes,
+    values: HashMap<String, sonic_rs::LazyValue<'_>>,
+) -> Result<(HashMap<String, sonic_rs::Value>, RawVariables), sonic_rs::Error> {
+    let mut variables = HashMap::with_capacity(values.len());
+    let mut raw_variables = RawVariables::with_capacity(values.len());
+    for (name, value) in values {
+        let raw = value.as_raw_str();
+        variables.insert(name.clone(), sonic_rs::from_str(raw)?);
+        let raw = source
+            .slice_ref_checked(raw.as_bytes())
+            .unwrap_or_else(|| Bytes::copy_from_slice(raw.as_bytes()));
+        raw_variables.insert(name, raw);
+    }
+
+    Ok((variables, raw_variables))
+}
+
+impl TryInto<(GraphQLParams, RawVariables)> for GraphQLGetInput {
     type Error = PipelineError;
 
-    fn try_into(self) -> Result<GraphQLParams, Self::Error> {
-        let variables = match self.variables.as_deref() {
-            Some(v_str) if !v_str.is_empty() => match sonic_rs::from_str(v_str) {
-                Ok(vars) => vars,
-                Err(e) => {
-                    return Err(PipelineError::FailedToParseVariables(e));
-                }
-            },
-            _ => HashMap::new(),
+    fn try_into(self) -> Result<(GraphQLParams, RawVariables), Self::Error> {
+        let (variables, raw_variables) = match self.variables {
+            Some(v_str) if !v_str.is_empty() => {
+                let source = Bytes::from(v_str);
+                sonic_rs::from_slice(&source)
+                    .and_then(|values| parse_variables(&source, values))
+                    .map_err(PipelineError::FailedToParseVariables)?
+            }
+            _ => Default::default(),
         };
 
         let extensions = match self.extensions.as_deref() {
@@ -279,7 +323,7 @@ impl TryInto<GraphQLParams> for GraphQLGetInput {
             extensions,
         };
 
-        Ok(execution_request)
+        Ok((execution_request, raw_variables))
     }
 }
 
@@ -303,6 +347,8 @@ pub enum OperationPreparationResult {
 pub struct OperationPreparation<'a> {
     req: &'a HttpRequest,
     persisted_documents_runtime: &'a PersistedDocumentsRuntime,
+    apq_runtime: Option<&'a ApqRuntime>,
+    get_requests: &'a GetRequestsConfig,
     plugin_req_state: &'a Option<PluginRequestState<'a>>,
     body: Bytes,
     persisted_documents_enabled: bool,
@@ -324,6 +370,8 @@ impl<'a> OperationPreparation<'a> {
         Self {
             req,
             persisted_documents_runtime: &shared_state.persisted_documents_runtime,
+            apq_runtime: shared_state.apq.as_ref(),
+            get_requests: &shared_state.router_config.http.get_requests,
             plugin_req_state,
             body,
             persisted_documents_enabled: shared_state.router_config.persisted_documents.enabled,
@@ -377,6 +425,9 @@ impl<'a> OperationPreparation<'a> {
 
         let mut operation = self.decode_or_use_plugin_override(graphql_params_from_plugins)?;
 
+        self.resolve_automatic_persisted_query(&mut operation)
+            .await?;
+
         if self.persisted_documents_enabled && operation.resolved_document_id.is_none() {
             self.metrics.persisted_documents.record_missing_id();
         }
@@ -405,6 +456,11 @@ impl<'a> OperationPreparation<'a> {
             self.resolve_query_from_document_id(&mut operation).await?;
         }
 
+        if !graphql_params_end_callbacks.is_empty() {
+            // the variables may be replaced by the plugins
+            operation.raw_variables.clear();
+        }
+
         if let Some(plugin_req_state) = self.plugin_req_state.as_ref() {
             let mut payload = OnGraphQLParamsEndHookPayload {
                 graphql_params: operation.graphql_params,
@@ -439,6 +495,7 @@ impl<'a> OperationPreparation<'a> {
         if let Some(graphql_params) = graphql_params_override {
             return Ok(PreparedOperation::from_graphql_params(
                 graphql_params,
+                RawVariables::new(),
                 &self.persisted_documents_runtime.document_id_resolver,
                 self.req.into(),
                 None,
@@ -460,6 +517,10 @@ impl<'a> OperationPreparation<'a> {
 
     #[inline]
     fn decode_get(&self) -> Result<PreparedOperation, PipelineError> {
+        if !self.get_requests.enabled {
+            return Err(PipelineError::GetRequestsDisabled);
+        }
+
         let query_params_str = self.req.uri().query();
         let query_params = if let Some(q) = query_params_str {
             Query::<GraphQLGetInput>::from_query(q)?.0
@@ -468,6 +529,10 @@ impl<'a> OperationPreparation<'a> {
             GraphQLGetInput::empty()
         };
 
+        if self.get_requests.persisted_operations_only && query_params.query.is_some() {
+            return Err(PipelineError::GetRequestWithoutPersistedOperation);
+        }
+
         PreparedOperation::from_get(
             query_params,
             &self.persisted_documents_runtime.document_id_resolver,
@@ -498,10 +563,12 @@ impl<'a> OperationPreparation<'a> {
 
         let mut deserializer = sonic_rs::Deserializer::from_slice(&self.body);
 
-        let post_input =
-            GraphQLPostBodySeed::new(&self.persisted_documents_runtime.document_id_resolver)
-                .deserialize(&mut deserializer)
-                .map_err(PipelineError::FailedToParseBody)?;
+        let post_input = GraphQLPostBodySeed::new(
+            &self.persisted_documents_runtime.document_id_resolver,
+            &self.body,
+        )
+        .deserialize(&mut deserializer)
+        .map_err(PipelineError::FailedToParseBody)?;
 
         // Calling end() is important to ensure there is no trailing garbage after the JSON payload.
         // Without calling it, this might be accepted:
@@ -566,6 +633,29 @@ impl<'a> OperationPreparation<'a> {
         Ok(())
     }
 
+    /// Resolves or registers the operation of an APQ request.
+    ///
+    /// Runs before the require-id policy, so a resolved operation is still subject to it.
+    /// Unknown hashes are left to the persisted documents when enabled.
+    #[inline]
+    async fn resolve_automatic_persisted_query(
+        &self,
+        prepared_operation: &mut PreparedOperation,
+    ) -> Result<(), PipelineError> {
+        let Some(apq_runtime) = self.apq_runtime else {
+            return Ok(());
+        };
+
+        let outcome = apq_runtime
+            .process(&mut prepared_operation.graphql_params)
+            .await?;
+        if outcome == ApqOutcome::NotFound && !self.persisted_documents_enabled {
+            return Err(PipelineError::PersistedQueryNotFound);
+        }
+
+        Ok(())
+    }
+
     #[inline]
     async fn resolve_query_from_document_id(
         &self,
@@ -606,6 +696,7 @@ mod tests {
     use std::sync::Arc;
 
     use async_trait::async_trait;
+    use hive_router_config::http_server::GetRequestsConfig;
     use hive_router_config::persisted_documents::PersistedDocumentsConfig;
     use hive_router_internal::expressions::ValueOrProgram;
     use hive_router_internal::telemetry::metrics::Metrics;
@@ -616,7 +707,7 @@ mod tests {
     use ntex::web::test::TestRequest;
     use ntex::web::HttpRequest;
 
-    use super::{OperationPreparation, PreparedOperation};
+    use super::{parse_variables, OperationPreparation, PreparedOperation};
     use crate::pipeline::error::PipelineError;
     use crate::pipeline::persisted_documents::extract::DocumentIdResolver;
     use crate::pipeline::persisted_documents::resolve::{
@@ -660,9 +751,22 @@ mod tests {
                 extensions: None,
             },
             resolved_document_id: PersistedDocumentId::from_option(persisted_id),
+            raw_variables: Default::default(),
         }
     }
 
+    #[test]
+    fn keeps_the_json_of_the_variables_as_sent() {
+        let source = Bytes::from_static(br#"{"id": "1", "filter": { "first" : 2 }, "s": "a\"b"}"#);
+        let values = sonic_rs::from_slice(&source).unwrap();
+        let (variables, raw_variables) = parse_variables(&source, values).unwrap();
+
+        assert_eq!(variables["filter"], sonic_rs::json!({ "first": 2 }));
+        assert_eq!(&raw_variables["id"][..], br#""1""#);
+        assert_eq!(&raw_variables["filter"][..], br#"{ "first" : 2 }"#);
+        assert_eq!(&raw_variables["s"][..], br#""a\"b""#);
+    }
+
     #[ntex::test]
     async fn resolves_query_from_persisted_document_id() {
         let req = request();
@@ -679,6 +783,8 @@ mod tests {
         let prep = OperationPreparation {
             req: &req,
             persisted_documents_runtime: &persisted_documents_runtime,
+            apq_runtime: None,
+            get_requests: &GetRequestsConfig::default(),
             plugin_req_state: &plugin_req_state,
             body: Bytes::new(),
             persisted_documents_enabled: true,
@@ -694,6 +800,7 @@ mod tests {
                 extensions: None,
             },
             resolved_document_id: Some(PersistedDocumentId::try_from("sha256:abc").unwrap()),
+            raw_variables: Default::default(),
         };
 
         prep.resolve_query_from_document_id(&mut op)
@@ -719,6 +826,8 @@ mod tests {
         let prep = OperationPreparation {
             req: &req,
             persisted_documents_runtime: &persisted_documents_runtime,
+            apq_runtime: None,
+            get_requests: &GetRequestsConfig::default(),
             plugin_req_state: &plugin_req_state,
             body: Bytes::new(),
             persisted_documents_enabled: true,
@@ -748,6 +857,8 @@ mod tests {
         let prep = OperationPreparation {
             req: &req,
             persisted_documents_runtime: &persisted_documents_runtime,
+            apq_runtime: None,
+            get_requests: &GetRequestsConfig::default(),
             plugin_req_state: &plugin_req_state,
             body: Bytes::new(),
             persisted_documents_enabled: true,
@@ -777,6 +888,8 @@ mod tests {
         let prep = OperationPreparation {
             req: &req,
             persisted_documents_runtime: &persisted_documents_runtime,
+            apq_runtime: None,
+            get_requests: &GetRequestsConfig::default(),
             plugin_req_state: &plugin_req_state,
             body: Bytes::new(),
             persisted_documents_enabled: true,
@@ -806,6 +919,8 @@ mod tests {
         let prep = OperationPreparation {
             req: &req,
             persisted_documents_runtime: &persisted_documents_runtime,
+            apq_runtime: None,
+            get_requests: &GetRequestsConfig::default(),
             plugin_req_state: &plugin_req_state,
             body: Bytes::new(),
             persisted_documents_enabled: false,
@@ -835,6 +950,8 @@ mod tests {
         let prep = OperationPreparation {
             req: &req,
             persisted_documents_runtime: &persisted_documents_runtime,
+            apq_runtime: None,
+            get_requests: &GetRequestsConfig::default(),
             plugin_req_state: &plugin_req_state,
             body: Bytes::new(),
             persisted_documents_enabled: true,
@@ -877,6 +994,8 @@ mod tests {
         let prep = OperationPreparation {
             req: &req,
             persisted_documents_runtime: &persisted_documents_runtime,
+            apq_runtime: None,
+            get_requests: &GetRequestsConfig::default(),
             plugin_req_state: &plugin_req_state,
             body: Bytes::new(),
             persisted_documents_enabled: true,
@@ -916,6 +1035,8 @@ mod tests {
         let prep = OperationPreparation {
             req: &req,
             persisted_documents_runtime: &persisted_documents_runtime,
+            apq_runtime: None,
+            get_requests: &GetRequestsConfig::default(),
             plugin_req_state: &plugin_req_state,
             body: Bytes::new(),
             persisted_documents_enabled: true,
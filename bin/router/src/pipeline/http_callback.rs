@@ -16,6 +16,8 @@ use serde::Deserialize;
 use strum::EnumString;
 use tracing::{debug, error, trace, warn};
 
+use crate::utils::secrets_match;
+
 #[derive(Debug, Deserialize, EnumString)]
 #[serde(rename_all = "lowercase")]
 #[strum(serialize_all = "lowercase")]
@@ -220,7 +222,10 @@ pub async fn handler(
         }
     };
 
-    if subscription.verifier != payload.verifier {
+    if !secrets_match(
+        subscription.verifier.as_bytes(),
+        payload.verifier.as_bytes(),
+    ) {
         return Err(CallbackError::InvalidVerifier {
             subscription_id: payload.id.clone(),
         });
@@ -0,0 +1,678 @@
+//! A Model Context Protocol (MCP) server, exposing the operations configured in `mcp.tools`
+//! as tools, over the Streamable HTTP transport.
+//!
+//! Tools are executed through the same stages as GraphQL requests (parsing, validation,
+//! authorization, limits...), with the headers of the MCP request.
+//! Only the JSON responses of the transport are supported, without server-sent events.
+
+use std::{collections::HashMap, sync::Arc};
+
+use graphql_tools::{
+    parser::parse_query,
+    static_graphql::{
+        query::{Definition, OperationDefinition, Type, VariableDefinition},
+        schema::{Document as SchemaDocument, TypeDefinition},
+    },
+};
+use hive_router_config::mcp::{McpToolConfig, McpToolOperation};
+use hive_router_internal::{
+    http::read_body_stream, telemetry::traces::spans::graphql::GraphQLOperationSpan,
+};
+use hive_router_plan_executor::{
+    headers::response::ResponseHeaderSink,
+    hooks::on_graphql_params::GraphQLParams,
+    plugin_context::{PluginContext, PluginRequestState},
+    request_context::RequestContextExt,
+};
+use hive_router_query_planner::ast::value::Value as PlannerValue;
+use http::{header::CONTENT_TYPE, Method, StatusCode};
+use ntex::{
+    http::ResponseBuilder,
+    web::{self, HttpRequest},
+};
+use sonic_rs::{json, JsonContainerTrait, JsonValueMutTrait, JsonValueTrait, Value};
+use tracing::{debug, warn, Instrument};
+
+use crate::{
+    consts::ROUTER_VERSION,
+    pipeline::{
+        error::PipelineError,
+        execute_planned_request,
+        header::{ResponseMode, SingleContentType},
+        normalize::normalize_request_with_cache,
+        parser::{parse_operation_with_cache, ParseResult},
+        persisted_documents::{
+            resolve::PersistedDocumentResolveInput,
+            types::{ClientIdentity, PersistedDocumentId},
+        },
+        validation::validate_operation_with_cache,
+    },
+    schema_state::{SchemaState, SelectedSupergraph},
+    shared_state::{RouterSharedState, SharedRouterResponse},
+};
+
+/// The versions of the protocol the server speaks, the latest first.
+const SUPPORTED_PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const INTERNAL_ERROR: i32 = -32603;
+
+struct JsonRpcError {
+    code: i32,
+    message: String,
+}
+
+impl JsonRpcError {
+    fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// The operation of a tool, with its GraphQL document resolved.
+struct ResolvedTool {
+    query: String,
+    operation_name: Option<String>,
+}
+
+pub async fn mcp_handler(
+    req: HttpRequest,
+    body_stream: web::types::Payload,
+    schema_state: web::types::State<Arc<SchemaState>>,
+    app_state: web::types::State<Arc<RouterSharedState>>,
+) -> web::HttpResponse {
+    if req.method() != Method::POST {
+        // the server never opens a stream of server-sent events
+        return web::HttpResponse::MethodNotAllowed()
+            .header(http::header::ALLOW, "POST")
+            .finish();
+    }
+
+    let max_request_body_size = app_state
+        .reloadable
+        .load()
+        .router_config
+        .limits
+        .max_request_body_size
+        .to_bytes() as usize;
+    let body = match read_body_stream(&req, body_stream, max_request_body_size).await {
+        Ok(body) => body,
+        Err(err) => return ResponseBuilder::new(err.status_code()).body(err.to_string()),
+    };
+
+    let message: Value = match sonic_rs::from_slice(&body) {
+        Ok(message) => message,
+        Err(err) => {
+            return json_rpc_response(
+                &Value::new_null(),
+                Err(JsonRpcError::new(PARSE_ERROR, err.to_string())),
+            )
+        }
+    };
+
+    if !message.is_object() || message.get("jsonrpc").as_str() != Some("2.0") {
+        return json_rpc_response(
+            &Value::new_null(),
+            Err(JsonRpcError::new(
+                INVALID_REQUEST,
+                "expected a single JSON-RPC 2.0 message",
+            )),
+        );
+    }
+
+    let Some(method) = message.get("method").as_str() else {
+        // responses of the client to requests of the server, that are never sent
+        return web::HttpResponse::Accepted().finish();
+    };
+    let Some(id) = message.get("id") else {
+        debug!(method, "received MCP notification");
+        return web::HttpResponse::Accepted().finish();
+    };
+    let params = message.get("params");
+
+    let result = match method {
+        "initialize" => Ok(initialize(params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => list_tools(&req, &schema_state, &app_state).await,
+        "tools/call" => call_tool(&req, params, &schema_state, &app_state).await,
+        _ => Err(JsonRpcError::new(
+            METHOD_NOT_FOUND,
+            format!("method '{method}' not found"),
+        )),
+    };
+
+    json_rpc_response(id, result)
+}
+
+fn json_rpc_response(id: &Value, result: Result<Value, JsonRpcError>) -> web::HttpResponse {
+    let body = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id.clone(), "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id.clone(),
+            "error": { "code": error.code, "message": error.message }
+        }),
+    };
+
+    ResponseBuilder::new(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+}
+
+fn initialize(params: Option<&Value>) -> Value {
+    let requested_version = params.and_then(|params| params.get("protocolVersion").as_str());
+    let protocol_version = SUPPORTED_PROTOCOL_VERSIONS
+        .into_iter()
+        .find(|version| Some(*version) == requested_version)
+        .unwrap_or(SUPPORTED_PROTOCOL_VERSIONS[0]);
+
+    json!({
+        "protocolVersion": protocol_version,
+        "capabilities": { "tools": { "listChanged": false } },
+        "serverInfo": { "name": "hive-router", "version": ROUTER_VERSION }
+    })
+}
+
+fn select_supergraph(
+    req: &HttpRequest,
+    schema_state: &SchemaState,
+) -> Result<SelectedSupergraph, JsonRpcError> {
+    match schema_state.select_supergraph(req) {
+        Ok(Some(supergraph)) => Ok(supergraph),
+        Ok(None) => Err(JsonRpcError::new(
+            INTERNAL_ERROR,
+            "no supergraph available yet",
+        )),
+        Err(err) => Err(JsonRpcError::new(INTERNAL_ERROR, err.to_string())),
+    }
+}
+
+async fn list_tools(
+    req: &HttpRequest,
+    schema_state: &SchemaState,
+    app_state: &RouterSharedState,
+) -> Result<Value, JsonRpcError> {
+    let supergraph = select_supergraph(req, schema_state)?;
+    let schema = &supergraph.snapshot.public_schema.document;
+
+    let mut tools = Vec::with_capacity(app_state.router_config.mcp.tools.len());
+    for tool_config in &app_state.router_config.mcp.tools {
+        let tool = match resolve_tool(tool_config, app_state).await {
+            Ok(tool) => tool,
+            Err(err) => {
+                warn!(tool = %tool_config.name, error = %err, "Failed to resolve the operation of an MCP tool");
+                continue;
+            }
+        };
+
+        let input_schema = match tool_input_schema(&tool, schema) {
+            Ok(input_schema) => input_schema,
+            Err(err) => {
+                warn!(tool = %tool_config.name, error = %err, "Failed to describe the input of an MCP tool");
+                continue;
+            }
+        };
+
+        tools.push(json!({
+            "name": tool_config.name,
+            "description": tool_config.description.as_deref().unwrap_or(&tool.query),
+            "inputSchema": input_schema,
+        }));
+    }
+
+    Ok(json!({ "tools": tools }))
+}
+
+async fn call_tool(
+    req: &HttpRequest,
+    params: Option<&Value>,
+    schema_state: &Arc<SchemaState>,
+    app_state: &Arc<RouterSharedState>,
+) -> Result<Value, JsonRpcError> {
+    let name = params
+        .and_then(|params| params.get("name").as_str())
+        .ok_or_else(|| JsonRpcError::new(INVALID_PARAMS, "missing tool name"))?;
+    let tool_config = app_state
+        .router_config
+        .mcp
+        .tools
+        .iter()
+        .find(|tool| tool.name == name)
+        .ok_or_else(|| JsonRpcError::new(INVALID_PARAMS, format!("unknown tool '{name}'")))?;
+
+    let variables = match params.and_then(|params| params.get("arguments")) {
+        Some(arguments) if arguments.is_object() => arguments
+            .as_object()
+            .into_iter()
+            .flat_map(|arguments| arguments.iter())
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect(),
+        Some(arguments) if !arguments.is_null() => {
+            return Err(JsonRpcError::new(
+                INVALID_PARAMS,
+                "tool arguments must be an object",
+            ))
+        }
+        _ => HashMap::new(),
+    };
+
+    let tool = resolve_tool(tool_config, app_state)
+        .await
+        .map_err(|err| JsonRpcError::new(INTERNAL_ERROR, err))?;
+    let supergraph = select_supergraph(req, schema_state)?;
+
+    let graphql_params = GraphQLParams {
+        query: Some(tool.query),
+        operation_name: tool.operation_name,
+        variables,
+        extensions: None,
+    };
+
+    // failures of the operation are results of the tool, for the model to see them
+    let (text, is_error) =
+        match execute_tool(req, graphql_params, &supergraph, schema_state, app_state).await {
+            Ok(Some(SharedRouterResponse::Single(response))) => (
+                String::from_utf8_lossy(&response.body).into_owned(),
+                response.error_count > 0,
+            ),
+            Ok(Some(SharedRouterResponse::Stream(_))) => {
+                ("subscriptions can't be executed as tools".to_string(), true)
+            }
+            Ok(None) => ("the request was ended early by a plugin".to_string(), true),
+            Err(err) => {
+                let code = err.graphql_error_code();
+                app_state
+                    .telemetry_context
+                    .metrics
+                    .graphql
+                    .record_error(code);
+                (
+                    json!({
+                        "errors": [{
+                            "message": err.graphql_error_message(),
+                            "extensions": { "code": code }
+                        }]
+                    })
+                    .to_string(),
+                    true,
+                )
+            }
+        };
+
+    Ok(json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    }))
+}
+
+/// Executes the operation of a tool like a GraphQL request,
+/// resulting in `None` when a plugin ends the request early.
+async fn execute_tool(
+    req: &HttpRequest,
+    graphql_params: GraphQLParams,
+    supergraph: &SelectedSupergraph,
+    schema_state: &Arc<SchemaState>,
+    app_state: &Arc<RouterSharedState>,
+) -> Result<Option<SharedRouterResponse>, PipelineError> {
+    let request_context = req.read_request_context()?;
+    let plugin_req_state = match (
+        app_state.plugins.as_ref(),
+        req.extensions().get::<Arc<PluginContext>>(),
+    ) {
+        (Some(plugins), Some(plugin_context)) => Some(PluginRequestState {
+            plugins: plugins.clone(),
+            router_http_request: req.into(),
+            context: plugin_context.clone(),
+            request_context: request_context.clone(),
+        }),
+        _ => None,
+    };
+
+    let operation_span = GraphQLOperationSpan::new();
+    let span_clone = operation_span.clone();
+
+    async {
+        let parser_payload = match parse_operation_with_cache(
+            app_state,
+            &graphql_params,
+            &plugin_req_state,
+        )
+        .await?
+        {
+            ParseResult::Payload(payload) => payload,
+            ParseResult::EarlyResponse(_) => return Ok(None),
+        };
+
+        if validate_operation_with_cache(
+            supergraph,
+            schema_state,
+            app_state,
+            &parser_payload,
+            &plugin_req_state,
+        )
+        .await?
+        .is_some()
+        {
+            return Ok(None);
+        }
+
+        let normalize_payload = normalize_request_with_cache(
+            &supergraph.snapshot,
+            &supergraph.runtime,
+            schema_state,
+            &graphql_params,
+            &parser_payload,
+        )
+        .await?;
+
+        let response_mode = ResponseMode::SingleOnly(SingleContentType::default());
+        execute_planned_request(
+            req.method(),
+            req.uri(),
+            req.headers().clone(),
+            None,
+            Default::default(),
+            Default::default(),
+            graphql_params,
+            Default::default(),
+            &normalize_payload,
+            supergraph,
+            app_state,
+            schema_state,
+            operation_span,
+            plugin_req_state,
+            &request_context,
+            &response_mode,
+            None,
+            ResponseHeaderSink::default(),
+            None,
+            None,
+            None,
+        )
+        .await
+        .map(Some)
+    }
+    .instrument(span_clone)
+    .await
+}
+
+async fn resolve_tool(
+    config: &McpToolConfig,
+    app_state: &RouterSharedState,
+) -> Result<ResolvedTool, String> {
+    match &config.operation {
+        McpToolOperation::Document {
+            query,
+            operation_name,
+        } => Ok(ResolvedTool {
+            query: query.clone(),
+            operation_name: operation_name.clone(),
+        }),
+        McpToolOperation::PersistedDocument {
+            persisted_document_id,
+        } => {
+            let resolver = app_state
+                .persisted_documents_runtime
+                .persisted_document_resolver
+                .as_ref()
+                .ok_or_else(|| "persisted documents are not enabled".to_string())?;
+            let document = resolver
+                .resolve(PersistedDocumentResolveInput {
+                    persisted_document_id: &PersistedDocumentId::new(persisted_document_id.clone()),
+                    client_identity: ClientIdentity::default(),
+                })
+                .await
+                .map_err(|err| err.to_string())?;
+
+            Ok(ResolvedTool {
+                query: document.text.to_string(),
+                operation_name: None,
+            })
+        }
+    }
+}
+
+/// Describes the variables of the operation of the tool as a JSON schema.
+fn tool_input_schema(tool: &ResolvedTool, schema: &SchemaDocument) -> Result<Value, String> {
+    let document = parse_query::<String>(&tool.query)
+        .map_err(|err| err.to_string())?
+        .into_static();
+
+    let operations: Vec<&OperationDefinition> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            Definition::Fragment(_) => None,
+        })
+        .collect();
+    let operation = match tool.operation_name.as_deref() {
+        Some(operation_name) => operations
+            .into_iter()
+            .find(|operation| operation_name_of(operation) == Some(operation_name))
+            .ok_or_else(|| format!("operation '{operation_name}' not found"))?,
+        None if operations.len() == 1 => operations[0],
+        None => return Err("the document must contain a single operation".to_string()),
+    };
+
+    let variable_definitions: &[VariableDefinition] = match operation {
+        OperationDefinition::Query(query) => &query.variable_definitions,
+        OperationDefinition::Mutation(mutation) => &mutation.variable_definitions,
+        OperationDefinition::SelectionSet(_) => &[],
+        OperationDefinition::Subscription(_) => {
+            return Err("subscriptions can't be exposed as tools".to_string())
+        }
+    };
+
+    let types: HashMap<&str, &TypeDefinition> = schema
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            graphql_tools::static_graphql::schema::Definition::TypeDefinition(type_definition) => {
+                Some((type_definition.name(), type_definition))
+            }
+            _ => None,
+        })
+        .collect();
+
+    Ok(variables_json_schema(variable_definitions, &types))
+}
+
+fn operation_name_of(operation: &OperationDefinition) -> Option<&str> {
+    match operation {
+        OperationDefinition::Query(query) => query.name.as_deref(),
+        OperationDefinition::Mutation(mutation) => mutation.name.as_deref(),
+        OperationDefinition::Subscription(subscription) => subscription.name.as_deref(),
+        OperationDefinition::SelectionSet(_) => None,
+    }
+}
+
+fn variables_json_schema(
+    variable_definitions: &[VariableDefinition],
+    types: &HashMap<&str, &TypeDefinition>,
+) -> Value {
+    let mut properties = sonic_rs::Object::new();
+    let mut required = Value::new_array();
+
+    for variable in variable_definitions {
+        let mut property = type_json_schema(&variable.var_type, types, &mut Vec::new());
+        if let Some(default_value) = &variable.default_value {
+            if let Some(property) = property.as_object_mut() {
+                property.insert("default", Value::from(&PlannerValue::from(default_value)));
+            }
+        } else if matches!(variable.var_type, Type::NonNullType(_)) {
+            required.append_value(Value::from(variable.name.as_str()));
+        }
+        properties.insert(&variable.name, property);
+    }
+
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        if let Some(schema) = schema.as_object_mut() {
+            schema.insert("required", required);
+        }
+    }
+    schema
+}
+
+/// `visiting` holds the input types being described, to stop at recursive input types.
+fn type_json_schema<'a>(
+    graphql_type: &'a Type,
+    types: &HashMap<&str, &'a TypeDefinition>,
+    visiting: &mut Vec<&'a str>,
+) -> Value {
+    match graphql_type {
+        Type::NonNullType(inner) => type_json_schema(inner, types, visiting),
+        Type::ListType(inner) => json!({
+            "type": "array",
+            "items": type_json_schema(inner, types, visiting),
+        }),
+        Type::NamedType(name) => named_type_json_schema(name, types, visiting),
+    }
+}
+
+fn named_type_json_schema<'a>(
+    name: &'a str,
+    types: &HashMap<&str, &'a TypeDefinition>,
+    visiting: &mut Vec<&'a str>,
+) -> Value {
+    match name {
+        "Int" => return json!({ "type": "integer" }),
+        "Float" => return json!({ "type": "number" }),
+        "String" | "ID" => return json!({ "type": "string" }),
+        "Boolean" => return json!({ "type": "boolean" }),
+        _ => {}
+    }
+
+    match types.get(name) {
+        Some(TypeDefinition::Enum(enum_type)) => {
+            let mut values = Value::new_array_with(enum_type.values.len());
+            for value in &enum_type.values {
+                values.append_value(Value::from(value.name.as_str()));
+            }
+            with_description(
+                json!({ "type": "string", "enum": values }),
+                enum_type.description.as_deref(),
+            )
+        }
+        Some(TypeDefinition::InputObject(input_type)) => {
+            if visiting.contains(&name) {
+                return json!({ "type": "object" });
+            }
+            visiting.push(name);
+
+            let mut properties = sonic_rs::Object::new();
+            let mut required = Value::new_array();
+            for field in &input_type.fields {
+                let property = with_description(
+                    type_json_schema(&field.value_type, types, visiting),
+                    field.description.as_deref(),
+                );
+                if matches!(field.value_type, Type::NonNullType(_)) && field.default_value.is_none()
+                {
+                    required.append_value(Value::from(field.name.as_str()));
+                }
+                properties.insert(&field.name, property);
+            }
+            visiting.pop();
+
+            let mut schema = json!({ "type": "object", "properties": properties });
+            if !required.is_empty() {
+                if let Some(schema) = schema.as_object_mut() {
+                    schema.insert("required", required);
+                }
+            }
+            with_description(schema, input_type.description.as_deref())
+        }
+        // custom scalars accept any JSON value
+        Some(TypeDefinition::Scalar(scalar_type)) => {
+            with_description(json!({}), scalar_type.description.as_deref())
+        }
+        _ => json!({}),
+    }
+}
+
+fn with_description(mut schema: Value, description: Option<&str>) -> Value {
+    if let (Some(description), Some(object)) = (description, schema.as_object_mut()) {
+        object.insert("description", Value::from(description));
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use graphql_tools::{
+        parser::{parse_query, parse_schema},
+        static_graphql::{
+            query::{Definition, OperationDefinition},
+            schema::{Definition as SchemaDefinition, TypeDefinition},
+        },
+    };
+    use sonic_rs::json;
+
+    use super::variables_json_schema;
+
+    #[test]
+    fn derives_the_input_schema_from_variable_definitions() {
+        let schema = parse_schema::<String>(
+            r#"
+            type Query { products(filter: ProductFilter, first: Int): [String] }
+            enum Sort { ASC DESC }
+            "A filter of products"
+            input ProductFilter { name: String! tags: [String!] sort: Sort = ASC next: ProductFilter }
+            "#,
+        )
+        .expect("failed to parse schema")
+        .into_static();
+        let types: HashMap<&str, &TypeDefinition> = schema
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                SchemaDefinition::TypeDefinition(type_definition) => {
+                    Some((type_definition.name(), type_definition))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let document = parse_query::<String>(
+            "query($filter: ProductFilter!, $first: Int = 10) { products(filter: $filter, first: $first) }",
+        )
+        .expect("failed to parse query")
+        .into_static();
+        let Some(Definition::Operation(OperationDefinition::Query(query))) =
+            document.definitions.first()
+        else {
+            panic!("expected a query");
+        };
+
+        assert_eq!(
+            variables_json_schema(&query.variable_definitions, &types),
+            json!({
+                "type": "object",
+                "properties": {
+                    "filter": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "tags": { "type": "array", "items": { "type": "string" } },
+                            "sort": { "type": "string", "enum": ["ASC", "DESC"] },
+                            "next": { "type": "object" }
+                        },
+                        "required": ["name"],
+                        "description": "A filter of products"
+                    },
+                    "first": { "type": "integer", "default": 10 }
+                },
+                "required": ["filter"]
+            })
+        );
+    }
+}
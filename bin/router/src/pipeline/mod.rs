@@ -1,4 +1,5 @@
 use futures::StreamExt;
+use hive_router_config::subscriptions::SubscriptionSchemaReloadTermination;
 use hive_router_internal::{
     http::read_body_stream,
     telemetry::traces::spans::{
@@ -9,10 +10,12 @@ use hive_router_plan_executor::{
     coprocessor::runtime::MutableRequestState,
     execution::{
         client_request_details::{
-            JwtRequestDetails, MutableClientRequestDetails, OperationDetails, PathParams,
+            ClientIdentityDetails, JwtRequestDetails, MutableClientRequestDetails,
+            OperationDetails, PathParams,
         },
         plan::{CoerceVariablesPayload, PlanExecutionOutput, QueryPlanExecutionResult},
     },
+    executors::dedupe::RouterRequestDedupeHeaderPolicy,
     headers::response::{ResponseHeaderAggregator, ResponseHeaderSink},
     hooks::{
         on_graphql_analysis::{OnGraphqlAnalysisHookPayload, OnGraphqlAnalysisHookResult},
@@ -22,6 +25,7 @@ use hive_router_plan_executor::{
     plugins::hooks,
     request_context::{RequestContextExt, SharedRequestContext},
     response::graphql_error::GraphQLError,
+    variables::serialized::RawVariables,
 };
 use hive_router_query_planner::{
     state::supergraph_state::OperationKind, utils::cancellation::CancellationToken,
@@ -46,14 +50,18 @@ use std::{
     sync::Arc,
     time::Instant,
 };
-use tracing::{error, Instrument};
+use tracing::{debug, error, Instrument};
 use xxhash_rust::xxh3::Xxh3;
 
 use crate::{
+    jwt::context::JwtRequestContext,
     pipeline::{
+        access_log::{AccessLogSink, CacheStatus},
         active_subscriptions::SubscriptionEvent,
+        apollo_usage_reporting::ApolloTraceContext,
         authorization::enforce_operation_authorization,
-        client_identification::identify_client,
+        client_disconnect::cancel_on_client_disconnect,
+        client_identification::{identify_client, require_client_identity},
         coerce_variables::coerce_request_variables,
         csrf_prevention::perform_csrf_prevention,
         error::PipelineError,
@@ -68,32 +76,39 @@ use crate::{
         request_extensions::{
             write_graphql_operation_metric_identity, write_graphql_response_metric_status,
         },
-        validation::validate_operation_with_cache,
+        request_id::read_request_id,
+        validation::{self, validate_operation_with_cache},
     },
     schema_state::{SchemaState, SelectedSupergraph},
     shared_state::{
-        RouterRequestDedupeHeaderPolicy, RouterSharedState, SharedRouterResponse,
-        SharedRouterResponseGuard, SharedRouterSingleResponse, SharedRouterStreamResponse,
+        RouterSharedState, SharedRouterResponse, SharedRouterResponseGuard,
+        SharedRouterSingleResponse, SharedRouterStreamResponse,
     },
     LABORATORY_HTML,
 };
 
 use hive_router_internal::telemetry::metrics::catalog::values::GraphQLResponseStatus;
 
+pub mod access_log;
 pub mod active_subscriptions;
+pub mod apollo_usage_reporting;
+pub mod apq;
 pub mod authorization;
+pub mod client_disconnect;
 mod client_identification;
 pub mod coerce_variables;
 pub mod cors;
 pub mod csrf_prevention;
 pub mod demand_control;
 pub mod error;
+pub mod etag;
 pub mod execution;
 pub mod execution_request;
 pub mod header;
 pub mod http_callback;
 pub mod introspection_policy;
 pub mod long_lived_client_limit;
+pub mod mcp;
 pub mod multipart_subscribe;
 pub mod normalize;
 pub mod nullify;
@@ -101,8 +116,12 @@ pub mod parser;
 pub mod persisted_documents;
 pub mod progressive_override;
 pub mod query_plan;
+pub mod rate_limit;
 pub mod request_extensions;
+pub mod request_id;
+pub mod response_cache;
 pub mod sse;
+pub mod telemetry_attributes;
 pub mod timeout;
 pub(crate) mod trie;
 pub mod usage_reporting;
@@ -119,8 +138,11 @@ pub async fn graphql_request_handler(
     response_mode: &mut ResponseMode,
     response_header_sink: ResponseHeaderSink,
 ) -> Result<web::HttpResponse, PipelineError> {
+    // the config re-applied on reload, loaded once so that the request sees a single version of it
+    let reloadable = shared_state.reloadable.load_full();
+
     // If an early CORS response is needed, return it immediately.
-    if let Some(early_response) = shared_state
+    if let Some(early_response) = reloadable
         .cors_runtime
         .as_ref()
         .and_then(|cors| cors.get_early_response(req))
@@ -141,16 +163,24 @@ pub async fn graphql_request_handler(
     }
 
     let started_at = Instant::now();
+    let apollo_trace_context = shared_state
+        .apollo_usage_agent
+        .as_ref()
+        .and_then(|agent| agent.sample());
     let operation_span = GraphQLOperationSpan::new();
     let span_clone = operation_span.clone();
 
     async {
+        if let Some(rate_limit) = shared_state.rate_limit.as_ref() {
+            rate_limit.enforce(req, shared_state).await?;
+        }
+
         perform_csrf_prevention(req, &shared_state.router_config.csrf)?;
 
         let body_bytes = read_body_stream(
             req,
             body_stream,
-            shared_state
+            reloadable
                 .router_config
                 .limits
                 .max_request_body_size
@@ -171,6 +201,20 @@ pub async fn graphql_request_handler(
         let client_name = client.name.as_deref();
         let client_version = client.version.as_deref();
 
+        let access_log_sink = req.extensions().get::<AccessLogSink>().cloned();
+        if let Some(access_log_sink) = access_log_sink.as_ref() {
+            access_log_sink.record_client(client_name, client_version);
+        }
+        let client_identity = Arc::new(ClientIdentityDetails {
+            name: client.name.clone(),
+            version: client.version.clone(),
+        });
+        require_client_identity(
+            client_name,
+            client_version,
+            &shared_state.router_config.telemetry.client_identification,
+        )?;
+
         let mut plugin_req_state = None;
 
         if let (Some(plugins), Some(plugin_context)) = (
@@ -183,6 +227,19 @@ pub async fn graphql_request_handler(
                 context: plugin_context.clone(),
                 request_context: request_context.clone(),
             });
+        } else if shared_state
+            .router_config
+            .coprocessor
+            .as_ref()
+            .is_some_and(|coprocessor| coprocessor.stages.subgraph.is_enabled())
+        {
+            // The subgraph stages of the coprocessor read the request context from the plugin state
+            plugin_req_state = Some(PluginRequestState {
+                plugins: Arc::new(Vec::new()),
+                router_http_request: req.deref().into(),
+                context: Arc::new(PluginContext::default()),
+                request_context: request_context.clone(),
+            });
         }
 
         let operation_preparation_result = OperationPreparation::prepare(
@@ -203,6 +260,7 @@ pub async fn graphql_request_handler(
         };
 
         let mut graphql_params = prepared_operation.graphql_params;
+        let mut raw_variables = prepared_operation.raw_variables;
 
         write_graphql_operation_metric_identity(req, graphql_params.operation_name.clone(), None);
 
@@ -269,6 +327,8 @@ pub async fn graphql_request_handler(
             };
 
             if performed_mutations.body {
+                // the variables may have been replaced as well
+                raw_variables.clear();
                 let parser_result =
                     parse_operation_with_cache(shared_state, &graphql_params, &plugin_req_state)
                         .await?;
@@ -338,14 +398,30 @@ pub async fn graphql_request_handler(
             return Err(PipelineError::SubscriptionsNotSupported);
         }
 
-        let request_dedupe_enabled = shared_state
+        // held by the client stream for as long as the client is subscribed
+        let subscription_slot = if is_subscription {
+            Some(
+                shared_state
+                    .active_subscriptions
+                    .acquire_client_slot()
+                    .ok_or(PipelineError::TooManySubscriptions)?,
+            )
+        } else {
+            None
+        };
+
+        let request_dedupe_enabled = reloadable
             .router_config
             .traffic_shaping
             .router
             .dedupe
             .enabled;
 
+        // Incrementally delivered responses are not shared,
+        // a late subscriber would miss the payloads sent before it joined.
         let fingerprint = if request_dedupe_enabled
+            && normalize_payload.streamed_fields.is_empty()
+            && normalize_payload.deferred_operation.is_none()
             && matches!(
                 normalize_payload.operation_for_plan.operation_kind,
                 // same deduplication applies for queries and subscriptions
@@ -361,7 +437,7 @@ pub async fn graphql_request_handler(
                 req.method(),
                 req.path(),
                 &request_headers,
-                &shared_state.in_flight_requests_header_policy,
+                &reloadable.in_flight_requests_header_policy,
                 supergraph.snapshot.cache_id,
                 normalize_payload.normalized_operation_hash,
                 variables_hash,
@@ -371,16 +447,37 @@ pub async fn graphql_request_handler(
             None
         };
 
+        // Set once the fingerprint is computed, for a generated ID not to defeat the deduplication
+        if let Some(request_id) = read_request_id(req) {
+            request_id.write_to(&mut request_headers, &reloadable.router_config.request_id);
+        }
+
+        let etag_enabled = shared_state.router_config.http.etag
+            && etag::is_conditional_request(
+                req.method(),
+                normalize_payload.operation_for_plan.operation_kind.as_ref(),
+                &graphql_params,
+            );
+
         let request_context = req.read_request_context()?;
         let path_params = req.match_info().into();
+        let disconnect_token = reloadable
+            .router_config
+            .traffic_shaping
+            .router
+            .cancel_on_client_disconnect
+            .then(tokio_util::sync::CancellationToken::new);
 
         let exec = |guard| {
             execute_planned_request(
                 req.method(),
                 req.uri(),
                 request_headers,
+                None,
+                client_identity.clone(),
                 path_params,
                 graphql_params,
+                raw_variables,
                 &normalize_payload,
                 &supergraph,
                 shared_state,
@@ -391,26 +488,38 @@ pub async fn graphql_request_handler(
                 response_mode,
                 guard,
                 response_header_sink.clone(),
+                apollo_trace_context.as_ref(),
+                disconnect_token.clone(),
+                access_log_sink.clone(),
             )
         };
 
-        let shared_response = if let Some(fp) = fingerprint {
-            let (shared_response, _role) = if is_subscription {
-                shared_state
-                    .in_flight_requests
-                    .claim(fp)
-                    .get_or_try_init_with_guard(|guard| exec(Some(guard)))
-                    .await?
+        let execution = async {
+            if let Some(fp) = fingerprint {
+                let (shared_response, _role) = if is_subscription {
+                    shared_state
+                        .in_flight_requests
+                        .claim(fp)
+                        .get_or_try_init_with_guard(|guard| exec(Some(guard)))
+                        .await?
+                } else {
+                    shared_state
+                        .in_flight_requests
+                        .claim(fp)
+                        .get_or_try_init(|| exec(None))
+                        .await?
+                };
+                Ok::<_, PipelineError>(Arc::unwrap_or_clone(shared_response))
             } else {
-                shared_state
-                    .in_flight_requests
-                    .claim(fp)
-                    .get_or_try_init(|| exec(None))
-                    .await?
-            };
-            Arc::unwrap_or_clone(shared_response)
-        } else {
-            exec(None).await?
+                exec(None).await
+            }
+        };
+
+        let shared_response = match disconnect_token.as_ref() {
+            Some(disconnect_token) => {
+                cancel_on_client_disconnect(req, disconnect_token, execution).await?
+            }
+            None => execution.await?,
         };
 
         if let Some(hive_usage_agent) = &shared_state.hive_usage_agent {
@@ -429,6 +538,26 @@ pub async fn graphql_request_handler(
             .await;
         }
 
+        if let (Some(apollo_usage_agent), Some(trace_context)) = (
+            &shared_state.apollo_usage_agent,
+            apollo_trace_context.as_ref(),
+        ) {
+            if let Some(stats_report_key) = apollo_usage_reporting::signature::stats_report_key(
+                &parser_payload.parsed_operation,
+                normalize_payload.operation_for_plan.name.as_deref(),
+            ) {
+                apollo_usage_agent.add_report(apollo_usage_reporting::ApolloOperationReport {
+                    stats_report_key,
+                    schema_cache_id: supergraph.snapshot.cache_id,
+                    schema_sdl: &supergraph.snapshot.public_schema.sdl,
+                    client_name,
+                    client_version,
+                    duration: started_at.elapsed(),
+                    trace_context,
+                });
+            }
+        }
+
         write_graphql_response_metric_status(
             req,
             if shared_response.error_count() > 0 {
@@ -438,7 +567,20 @@ pub async fn graphql_request_handler(
             },
         );
 
-        shared_response.into_response(response_mode, &shared_state.telemetry_context.metrics)
+        match shared_response {
+            SharedRouterResponse::Single(single) if etag_enabled => {
+                Ok(etag::into_response_with_etag(single, req.headers()))
+            }
+            shared_response => shared_response.into_response(
+                response_mode,
+                &shared_state.telemetry_context.metrics,
+                shared_state
+                    .router_config
+                    .subscriptions
+                    .client_heartbeat_interval,
+                subscription_slot,
+            ),
+        }
     }
     .instrument(span_clone)
     .await
@@ -452,8 +594,11 @@ pub async fn execute_planned_request<'exec>(
     method: &'exec Method,
     url: &'exec http::Uri,
     headers: HeaderMap,
+    jwt_context: Option<JwtRequestContext>,
+    client: Arc<ClientIdentityDetails>,
     path_params: PathParams<'exec>,
     mut graphql_params: GraphQLParams,
+    raw_variables: RawVariables,
     normalize_payload: &Arc<GraphQLNormalizationPayload>,
     supergraph: &'exec SelectedSupergraph,
     shared_state: &'exec Arc<RouterSharedState>,
@@ -464,19 +609,26 @@ pub async fn execute_planned_request<'exec>(
     response_mode: &'exec ResponseMode,
     guard: Option<SharedRouterResponseGuard>,
     response_header_sink: ResponseHeaderSink,
+    apollo_trace_context: Option<&ApolloTraceContext>,
+    disconnect_token: Option<tokio_util::sync::CancellationToken>,
+    access_log_sink: Option<AccessLogSink>,
 ) -> Result<SharedRouterResponse, PipelineError> {
-    let jwt_request_details = match &shared_state.jwt_auth_runtime {
-        Some(jwt_auth_runtime) => match jwt_auth_runtime
-            .validate_headers(&headers, &shared_state.jwt_claims_cache)
-            .await?
-        {
-            Some(jwt_context) => JwtRequestDetails::Authenticated {
-                scopes: jwt_context.extract_scopes(),
-                claims: jwt_context.get_claims_value()?,
-                token: jwt_context.token_raw,
-                prefix: jwt_context.token_prefix,
-            },
-            None => JwtRequestDetails::Unauthenticated,
+    // an identity already validated by the caller, e.g. for the lifetime of a WebSocket connection
+    let jwt_context = match (jwt_context, &shared_state.jwt_auth_runtime) {
+        (Some(jwt_context), _) => Some(jwt_context),
+        (None, Some(jwt_auth_runtime)) => {
+            jwt_auth_runtime
+                .validate_headers(&headers, &shared_state.jwt_claims_cache)
+                .await?
+        }
+        (None, None) => None,
+    };
+    let jwt_request_details = match jwt_context {
+        Some(jwt_context) => JwtRequestDetails::Authenticated {
+            scopes: jwt_context.extract_scopes(),
+            claims: jwt_context.get_claims_value()?,
+            token: jwt_context.token_raw,
+            prefix: jwt_context.token_prefix,
         },
         None => JwtRequestDetails::Unauthenticated,
     };
@@ -485,6 +637,7 @@ pub async fn execute_planned_request<'exec>(
     let variable_payload = coerce_request_variables(
         &supergraph.snapshot,
         &mut graphql_params.variables,
+        raw_variables,
         normalize_payload,
     )?;
 
@@ -502,7 +655,11 @@ pub async fn execute_planned_request<'exec>(
             query: graphql_params.get_query()?,
         },
         jwt: jwt_request_details.into(),
+        client,
         path_params,
+        plugin_context: plugin_req_state
+            .as_ref()
+            .map(|plugin_req_state| plugin_req_state.context.clone()),
     };
 
     match execute_pipeline(
@@ -517,6 +674,10 @@ pub async fn execute_planned_request<'exec>(
         plugin_req_state,
         request_context,
         response_header_sink.clone(),
+        response_mode.can_stream(),
+        apollo_trace_context,
+        disconnect_token,
+        access_log_sink,
     )
     .await?
     {
@@ -536,9 +697,30 @@ pub async fn execute_planned_request<'exec>(
             // between spawn and the consumer calling subscribe()
             let sender = producer_handle.sender().clone();
 
+            // with `terminate_on_schema_reload: breaking_changes`, the operation is validated
+            // against the new supergraph on reload - only possible when it replaces the
+            // configured supergraph this subscription was started with
+            let revalidated_query = match shared_state
+                .router_config
+                .subscriptions
+                .terminate_on_schema_reload
+            {
+                SubscriptionSchemaReloadTermination::Always => None,
+                SubscriptionSchemaReloadTermination::BreakingChanges => schema_state
+                    .configured_snapshot()
+                    .is_some_and(|configured| configured.cache_id == supergraph.snapshot.cache_id)
+                    .then(|| graphql_params.query.clone())
+                    .flatten(),
+            };
+            let schema_state = schema_state.clone();
+            let shared_state = shared_state.clone();
+
             let mut body_stream = result.body;
             let supergraph = supergraph.clone();
             rt::spawn(async move {
+                // the supergraph whose retirement terminates the subscription, moved forward
+                // to the reloaded supergraph every time the subscription survives a reload
+                let mut watched_snapshot = supergraph.snapshot.clone();
                 loop {
                     tokio::select! {
                         chunk = body_stream.next() => {
@@ -552,7 +734,21 @@ pub async fn execute_planned_request<'exec>(
                                 None => break,
                             }
                         }
-                        _ = supergraph.snapshot.retired() => {
+                        _ = watched_snapshot.retired() => {
+                            let reloaded = revalidated_query.as_deref().and_then(|query| {
+                                validation::configured_supergraph_accepting(
+                                    query,
+                                    &schema_state,
+                                    &shared_state,
+                                )
+                            });
+                            if let Some(reloaded) = reloaded {
+                                // the operation is unaffected by the reload, keep draining
+                                // through the query plan of the retired supergraph
+                                debug!("subscription kept alive across a schema reload");
+                                watched_snapshot = reloaded;
+                                continue;
+                            }
                             // the supergraph this subscription was selected from has been
                             // retired (configured reload, or the owning plugin dropped/replaced
                             // its variant) - terminate with the same error a full reload used to
@@ -641,6 +837,10 @@ pub async fn execute_pipeline<'exec>(
     plugin_req_state: Option<PluginRequestState<'exec>>,
     request_context: &SharedRequestContext,
     response_header_sink: ResponseHeaderSink,
+    incremental_delivery: bool,
+    apollo_trace_context: Option<&ApolloTraceContext>,
+    disconnect_token: Option<tokio_util::sync::CancellationToken>,
+    access_log_sink: Option<AccessLogSink>,
 ) -> Result<QueryPlanExecutionResult, PipelineError> {
     if normalize_payload.operation_for_introspection.is_some() {
         handle_introspection_policy(&shared_state.introspection_policy, &client_request_details)?;
@@ -650,7 +850,7 @@ pub async fn execute_pipeline<'exec>(
     let cancellation_token =
         CancellationToken::with_timeout(shared_state.router_config.query_planner.timeout);
 
-    let (mut normalize_payload, authorization_errors) = enforce_operation_authorization(
+    let (mut normalize_payload, mut authorization_errors) = enforce_operation_authorization(
         &shared_state.router_config,
         &normalize_payload,
         &supergraph.runtime.authorization,
@@ -659,6 +859,19 @@ pub async fn execute_pipeline<'exec>(
         &client_request_details.jwt,
     )?;
 
+    if let Some(opa_authorization) = shared_state.opa_authorization.as_ref() {
+        let (opa_payload, opa_errors) = opa_authorization
+            .authorize(
+                &normalize_payload,
+                &supergraph.snapshot.metadata,
+                &variable_payload,
+                &client_request_details,
+            )
+            .await?;
+        normalize_payload = opa_payload;
+        authorization_errors.extend(opa_errors);
+    }
+
     let mut progressive_override_ctx = RequestOverrideContext::new(
         &shared_state.override_labels_evaluator,
         &client_request_details,
@@ -750,13 +963,18 @@ pub async fn execute_pipeline<'exec>(
     )
     .await?;
 
-    let query_plan_payload = match query_plan_result {
-        QueryPlanResult::QueryPlan(plan) => plan,
+    let (query_plan_payload, query_plan_timing) = match query_plan_result {
+        QueryPlanResult::QueryPlan(plan, timing) => (plan, timing),
         QueryPlanResult::EarlyResponse(response) => {
             return Ok(QueryPlanExecutionResult::Single(response));
         }
     };
 
+    if let Some(apollo_trace_context) = apollo_trace_context {
+        apollo_trace_context.set_query_plan(query_plan_payload.clone());
+    }
+    let fetch_trace_sink = apollo_trace_context.map(|context| context.fetch_traces.clone());
+
     let variable_payload = Arc::new(variable_payload);
 
     let demand_control_execution_context = match supergraph.runtime.demand_control_runtime.as_ref()
@@ -779,9 +997,25 @@ pub async fn execute_pipeline<'exec>(
         None => None,
     };
 
+    let cost_budget = match (
+        shared_state.cost_budgets.as_ref(),
+        demand_control_execution_context.as_ref(),
+    ) {
+        (Some(cost_budgets), Some(demand_control)) => {
+            cost_budgets
+                .charge(
+                    &client_request_details,
+                    demand_control.evaluation.estimated_cost,
+                )
+                .await?
+        }
+        _ => None,
+    };
+
     let planned_request = PlannedRequest {
         normalized_payload: normalize_payload,
         query_plan_payload: &query_plan_payload,
+        query_plan_timing,
         variable_payload: variable_payload.clone(),
         client_request_details: client_request_details.clone(),
         initial_errors: authorization_errors
@@ -790,17 +1024,129 @@ pub async fn execute_pipeline<'exec>(
             .chain(plugin_graphql_errors)
             .collect(),
         demand_control_execution_context,
+        cost_budget,
         plugin_req_state,
+        incremental_delivery,
+        disconnect_token,
+        access_log_sink: access_log_sink.clone(),
+    };
+
+    // the entities returned by a mutation are invalidated from the caches once it is executed
+    let mutation_payload = (supergraph.runtime.entity_keys.is_some()
+        && matches!(
+            planned_request.normalized_payload.operation_kind,
+            OperationKind::Mutation
+        ))
+    .then(|| planned_request.normalized_payload.clone());
+    if let Some(mutation_payload) = mutation_payload {
+        let result = execute_plan(
+            supergraph,
+            shared_state,
+            planned_request,
+            operation_span,
+            response_header_sink,
+            fetch_trace_sink,
+        )
+        .await?;
+        return Ok(match result {
+            QueryPlanExecutionResult::Single(output) => {
+                response_cache::invalidate_mutated_entities(
+                    supergraph,
+                    shared_state,
+                    &mutation_payload.operation_for_plan,
+                    &mutation_payload.root_type_name,
+                    &output,
+                )
+                .await;
+                QueryPlanExecutionResult::Single(output)
+            }
+            QueryPlanExecutionResult::Stream(output) => QueryPlanExecutionResult::Stream(
+                response_cache::invalidate_mutated_entities_after_stream(
+                    supergraph,
+                    shared_state,
+                    mutation_payload.operation_for_plan.clone(),
+                    mutation_payload.root_type_name.clone(),
+                    output,
+                ),
+            ),
+        });
+    }
+
+    let response_cache_request = match shared_state.response_cache.as_ref() {
+        Some(response_cache)
+            if planned_request.initial_errors.is_empty()
+                && planned_request
+                    .normalized_payload
+                    .streamed_fields
+                    .is_empty()
+                && planned_request
+                    .normalized_payload
+                    .deferred_operation
+                    .is_none()
+                && matches!(
+                    planned_request.normalized_payload.operation_kind,
+                    OperationKind::Query
+                ) =>
+        {
+            response_cache
+                .prepare(
+                    supergraph,
+                    &planned_request.normalized_payload,
+                    graphql_params,
+                    &client_request_details.headers,
+                )
+                .map(|request| (response_cache, request))
+        }
+        _ => None,
     };
 
-    execute_plan(
+    let Some((response_cache, response_cache_request)) = response_cache_request else {
+        return execute_plan(
+            supergraph,
+            shared_state,
+            planned_request,
+            operation_span,
+            response_header_sink,
+            fetch_trace_sink,
+        )
+        .await;
+    };
+
+    if let Some(cached) = response_cache
+        .lookup(&response_cache_request, &response_header_sink)
+        .await
+    {
+        if let Some(access_log_sink) = access_log_sink.as_ref() {
+            access_log_sink.record_cache_status(CacheStatus::Hit);
+        }
+        return Ok(QueryPlanExecutionResult::Single(cached));
+    }
+    if let Some(access_log_sink) = access_log_sink.as_ref() {
+        access_log_sink.record_cache_status(CacheStatus::Miss);
+    }
+
+    let result = execute_plan(
         supergraph,
         shared_state,
         planned_request,
         operation_span,
-        response_header_sink,
+        response_header_sink.clone(),
+        fetch_trace_sink,
     )
-    .await
+    .await?;
+
+    if let QueryPlanExecutionResult::Single(output) = &result {
+        response_cache
+            .store(
+                supergraph,
+                response_cache_request,
+                output,
+                &response_header_sink,
+            )
+            .await;
+    }
+
+    Ok(result)
 }
 
 #[allow(clippy::too_many_arguments)]
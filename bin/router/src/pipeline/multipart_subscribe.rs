@@ -82,7 +82,8 @@ pub const APOLLO_MULTIPART_HTTP_CONTENT_TYPE: &str = concat!(
 
 /// Create a multipart subscription stream following Apollo's Multipart HTTP spec.
 ///
-/// Will use `graphql` as boundary.
+/// Will use `graphql` as boundary. A heartbeat is sent after every `heartbeat_interval`
+/// of inactivity, a zero interval disables heartbeats.
 ///
 /// Read more: https://github.com/graphql/graphql-over-http/blob/d312e43384006fa323b918d49cfd9fbd76ac1257/rfcs/IncrementalDelivery.md
 pub fn create_apollo_multipart_http_stream(
@@ -123,7 +124,7 @@ pub fn create_apollo_multipart_http_stream(
                         },
                     }
                 }
-                _ = tokio::time::sleep(heartbeat_interval) => {
+                _ = tokio::time::sleep(heartbeat_interval), if !heartbeat_interval.is_zero() => {
                     yield Ok(Bytes::from(start_boundary));
                     yield Ok(Bytes::from(ping));
                 }
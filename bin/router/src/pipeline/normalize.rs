@@ -4,10 +4,14 @@ use std::sync::Arc;
 use hive_router_internal::telemetry::traces::spans::graphql::{
     GraphQLNormalizeSpan, GraphQLSpanOperationIdentity,
 };
+use hive_router_plan_executor::execution::incremental::{DeferredProjectionPlan, StreamedField};
 use hive_router_plan_executor::hooks::on_graphql_params::GraphQLParams;
 use hive_router_plan_executor::hooks::on_supergraph_load::SupergraphSnapshot;
 use hive_router_plan_executor::introspection::partition::partition_operation;
 use hive_router_plan_executor::projection::plan::FieldProjectionPlan;
+use hive_router_query_planner::ast::normalization::defer::{
+    normalize_deferred_operation, DeferredOperation,
+};
 use hive_router_query_planner::ast::normalization::error::NormalizationError;
 use hive_router_query_planner::ast::normalization::normalize_operation;
 use hive_router_query_planner::ast::operation::OperationDefinition;
@@ -17,7 +21,8 @@ use xxhash_rust::xxh3::Xxh3;
 use crate::cache_state::{CacheHitMiss, EntryResultHitMissExt};
 use crate::pipeline::error::PipelineError;
 use crate::pipeline::nullify::rebuilder::{
-    rebuild_nulled_operation, rebuild_nulled_projection_plan,
+    rebuild_nulled_deferred_projection_plan, rebuild_nulled_operation,
+    rebuild_nulled_projection_plan,
 };
 use crate::pipeline::parser::GraphQLParserPayload;
 use crate::pipeline::trie::Trie;
@@ -38,6 +43,11 @@ pub struct GraphQLNormalizationPayload {
     pub operation_kind: OperationKind,
     pub projection_plan: Arc<Vec<FieldProjectionPlan>>,
     pub operation_identity: OperationIdentity,
+    /// The primary operation and deferred fragments of a query with `@defer`.
+    pub deferred_operation: Option<Arc<DeferredOperation>>,
+    pub deferred_projection_plan: Option<Arc<DeferredProjectionPlan>>,
+    /// Root fields of the operation annotated with `@stream`.
+    pub streamed_fields: Arc<Vec<StreamedField>>,
 }
 
 #[derive(Debug, Clone)]
@@ -63,9 +73,13 @@ impl GraphQLNormalizationPayload {
         &self,
         new_operation: OperationDefinition,
         new_projection_plan: Vec<FieldProjectionPlan>,
+        new_deferred_projection_plan: Option<DeferredProjectionPlan>,
     ) -> Arc<GraphQLNormalizationPayload> {
-        let hashes =
-            hash_normalized_operation(&new_operation, self.operation_for_introspection.as_deref());
+        let hashes = hash_normalized_operation(
+            &new_operation,
+            self.operation_for_introspection.as_deref(),
+            self.deferred_operation.as_deref(),
+        );
 
         Arc::new(GraphQLNormalizationPayload {
             operation_for_plan: Arc::new(new_operation),
@@ -78,6 +92,9 @@ impl GraphQLNormalizationPayload {
             operation_kind: self.operation_kind.clone(),
             projection_plan: Arc::new(new_projection_plan),
             operation_identity: self.operation_identity.clone(),
+            deferred_operation: self.deferred_operation.clone(),
+            deferred_projection_plan: new_deferred_projection_plan.map(Arc::new),
+            streamed_fields: self.streamed_fields.clone(),
         })
     }
 }
@@ -100,15 +117,23 @@ impl<'exec> FilterOutputExt<'exec> for OperationFilterOutput<'exec> {
         let trie = Trie::from_paths(&self.rejected_paths);
         let new_op = rebuild_nulled_operation(&payload.operation_for_plan, &trie);
         let new_projection = rebuild_nulled_projection_plan(&payload.projection_plan, &trie);
-        (payload.with_operation(new_op, new_projection), self.errors)
+        let new_deferred_projection = payload
+            .deferred_projection_plan
+            .as_deref()
+            .map(|plan| rebuild_nulled_deferred_projection_plan(plan, &trie));
+        (
+            payload.with_operation(new_op, new_projection, new_deferred_projection),
+            self.errors,
+        )
     }
 }
 
 pub fn hash_normalized_operation(
     operation_for_plan: &OperationDefinition,
     operation_for_introspection: Option<&OperationDefinition>,
+    deferred_operation: Option<&DeferredOperation>,
 ) -> NormalizedOperationHashes {
-    let operation_for_plan_hash = operation_for_plan.hash();
+    let operation_for_plan_hash = hash_operation_for_plan(operation_for_plan, deferred_operation);
     let operation_for_introspection_hash =
         operation_for_introspection.map(OperationDefinition::hash);
 
@@ -126,6 +151,22 @@ pub fn hash_normalized_operation(
     }
 }
 
+/// Hashes the operation to plan, a query with `@defer` fragments is planned
+/// differently than the same query without them.
+pub fn hash_operation_for_plan(
+    operation_for_plan: &OperationDefinition,
+    deferred_operation: Option<&DeferredOperation>,
+) -> u64 {
+    let Some(deferred_operation) = deferred_operation else {
+        return operation_for_plan.hash();
+    };
+
+    let mut hasher = Xxh3::new();
+    operation_for_plan.hash().hash(&mut hasher);
+    deferred_operation.hash().hash(&mut hasher);
+    hasher.finish()
+}
+
 pub struct NormalizedOperationHashes {
     pub operation_for_plan_hash: u64,
     pub operation_for_introspection_hash: Option<u64>,
@@ -170,6 +211,15 @@ pub async fn normalize_request_with_cache(
                     doc.operation
                 );
 
+                let deferred_operation = normalize_deferred_operation(
+                    &supergraph.planner.supergraph,
+                    &parser_payload.parsed_operation,
+                    graphql_params.operation_name.as_deref(),
+                )?;
+                let deferred_projection_plan = deferred_operation.as_ref().map(|operation| {
+                    DeferredProjectionPlan::from_operation(operation, &supergraph.metadata)
+                });
+
                 let operation = doc.operation;
                 let operation_kind = operation
                     .operation_kind
@@ -187,6 +237,7 @@ pub async fn normalize_request_with_cache(
                 let hashes = hash_normalized_operation(
                     &operation_for_plan,
                     operation_for_introspection.as_deref(),
+                    deferred_operation.as_ref(),
                 );
 
                 let payload = GraphQLNormalizationPayload {
@@ -203,6 +254,12 @@ pub async fn normalize_request_with_cache(
                         operation_type: parser_payload.operation_type.clone(),
                         client_document_hash: parser_payload.cache_key_string.clone(),
                     },
+                    deferred_operation: deferred_operation.map(Arc::new),
+                    deferred_projection_plan: deferred_projection_plan.map(Arc::new),
+                    streamed_fields: Arc::new(StreamedField::collect_from_document(
+                        &parser_payload.parsed_operation,
+                        graphql_params.operation_name.as_deref(),
+                    )),
                 };
 
                 Ok(Arc::new(payload))
@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
 use ahash::HashSet;
+use hive_router_plan_executor::execution::incremental::{
+    DeferredFragmentProjectionPlan, DeferredProjectionPlan,
+};
 use hive_router_plan_executor::projection::plan::{FieldProjectionPlan, ProjectionValueSource};
 use hive_router_query_planner::ast::{
     operation::OperationDefinition,
@@ -128,6 +131,26 @@ pub(crate) fn rebuild_nulled_projection_plan(
         .unwrap_or_default()
 }
 
+/// Rebuilds the projection plans of the primary operation and deferred fragments
+/// to set nulled fields to null.
+pub(crate) fn rebuild_nulled_deferred_projection_plan(
+    original_plan: &DeferredProjectionPlan,
+    nulled_field_trie: &Trie,
+) -> DeferredProjectionPlan {
+    DeferredProjectionPlan {
+        primary: rebuild_nulled_projection_plan(&original_plan.primary, nulled_field_trie),
+        fragments: original_plan
+            .fragments
+            .iter()
+            .map(|fragment| DeferredFragmentProjectionPlan {
+                path: fragment.path.clone(),
+                if_variable: fragment.if_variable.clone(),
+                plans: rebuild_nulled_projection_plan(&fragment.plans, nulled_field_trie),
+            })
+            .collect(),
+    }
+}
+
 /// Recursively filters projection plans. Nulled fields become null.
 fn rebuild_nulled_projection_plan_recursive(
     original_plans: &Vec<FieldProjectionPlan>,
@@ -2,8 +2,8 @@ use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use combine::easy::Info;
-use graphql_tools::parser::minify_query;
 use graphql_tools::parser::query::{Definition, Document, OperationDefinition};
+use graphql_tools::parser::{minify_query, DEFAULT_RECURSION_LIMIT};
 use graphql_tools::validation::utils::ValidationError;
 use hive_console_sdk::agent::utils::normalize_operation as hive_sdk_normalize_operation;
 use hive_router_internal::telemetry::traces::spans::graphql::{
@@ -17,9 +17,7 @@ use hive_router_plan_executor::plugin_context::PluginRequestState;
 use hive_router_plan_executor::plugin_trait::{CacheHint, EndControlFlow, StartControlFlow};
 use hive_router_plan_executor::plugins::hooks;
 use hive_router_query_planner::state::supergraph_state::OperationKind;
-use hive_router_query_planner::utils::parsing::{
-    safe_parse_operation, safe_parse_operation_with_token_limit,
-};
+use hive_router_query_planner::utils::parsing::safe_parse_operation_with_limits;
 use xxhash_rust::xxh3::Xxh3;
 
 use crate::cache_state::{CacheHitMiss, EntryResultHitMissExt};
@@ -138,24 +136,50 @@ pub async fn parse_operation_with_cache(
 
         let query_str = graphql_params.get_query()?;
 
+        let reloadable = app_state.reloadable.load_full();
+        let limits = &reloadable.router_config.limits;
+        if let Some(max_document_size) = &limits.max_document_size {
+            let limit = max_document_size.to_bytes() as usize;
+            if query_str.len() > limit {
+                return Err(PipelineError::DocumentSizeLimitExceeded(limit));
+            }
+        }
+
         let parse_cache_item = app_state
             .parse_cache
             .entry(cache_key)
             .or_try_insert_with::<_, ParserCacheError>(async {
-                let parsed = match app_state.router_config.limits.max_tokens.as_ref() {
-                    Some(cfg) => safe_parse_operation_with_token_limit(query_str, cfg.n),
-                    _ => safe_parse_operation(query_str),
-                }
+                let parsed = safe_parse_operation_with_limits(
+                    query_str,
+                    limits.max_tokens.as_ref().map(|cfg| cfg.n),
+                    limits
+                        .max_recursion
+                        .as_ref()
+                        .map_or(DEFAULT_RECURSION_LIMIT, |cfg| cfg.n),
+                    limits.max_parse_duration,
+                )
                 .map_err(|err| {
                     if let Some(combine::stream::easy::Error::Message(Info::Static(msg))) =
                         err.0.errors.first()
                     {
-                        if *msg == "Token limit exceeded" {
+                        let limit_error = match *msg {
+                            "Token limit exceeded" => {
+                                Some(("Token limit exceeded.", "TOKEN_LIMIT_EXCEEDED"))
+                            }
+                            "Recursion limit exceeded" => {
+                                Some(("Recursion limit exceeded.", "RECURSION_LIMIT_EXCEEDED"))
+                            }
+                            "Parse timeout exceeded" => {
+                                Some(("Parse timeout exceeded.", "PARSE_TIMEOUT_EXCEEDED"))
+                            }
+                            _ => None,
+                        };
+                        if let Some((message, error_code)) = limit_error {
                             return ParserCacheError::ValidationErrors(
                                 vec![ValidationError {
                                     locations: vec![err.0.position],
-                                    message: "Token limit exceeded.".to_string(),
-                                    error_code: "TOKEN_LIMIT_EXCEEDED",
+                                    message: message.to_string(),
+                                    error_code,
                                 }]
                                 .into(),
                             );
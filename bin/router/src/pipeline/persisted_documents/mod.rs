@@ -48,6 +48,7 @@ impl PersistedDocumentsRuntime {
 
         let require_id = match &config.require_id {
             ValueOrExpression::Value(value) => ValueOrProgram::Value(*value),
+            ValueOrExpression::Secret(secret) => ValueOrProgram::Value(*secret.value()),
             ValueOrExpression::Expression { expression } => {
                 let program = expression.compile_expression(None).map_err(|err| {
                     PersistedDocumentResolverError::Configuration(format!(
@@ -1,18 +1,17 @@
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
-use notify::{Config as NotifyConfig, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::EventKind;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
 use thiserror::Error;
-use tokio::sync::{Mutex, Notify};
+use tokio::sync::Mutex;
 use tracing::{info, warn};
 
 use hive_router_config::persisted_documents::PersistedDocumentsFileStorageConfig;
 use hive_router_internal::background_tasks::{BackgroundTask, CancellationToken};
 
+use crate::file_watcher::DebouncedFileWatcher;
 use crate::pipeline::persisted_documents::resolve::shared_file_manifest::{
     parse_manifest, DocumentsById,
 };
@@ -22,8 +21,6 @@ use super::{
     ResolvedDocument,
 };
 
-const RELOAD_EVENT_DEBOUNCE: Duration = Duration::from_millis(150);
-
 #[async_trait]
 impl PersistedDocumentResolver for FileManifestResolver {
     async fn resolve(
@@ -49,14 +46,10 @@ pub struct FileManifestResolver {
     manifest_path: String,
     // Snapshot of currently active documents for lock-free reads.
     documents: ArcSwap<DocumentsById>,
-    // Signals a potential file change
-    dirty: Arc<AtomicBool>,
     // Ensures at-most-one reload in flight so watcher events do not race
     // and publish snapshots out of order.
     reload_guard: Mutex<()>,
-    // Notification channel from watcher callback to background reload task.
-    reload_signal: Arc<Notify>,
-    watcher: Option<RecommendedWatcher>,
+    watcher: Option<DebouncedFileWatcher>,
 }
 
 // Background task wrapper registered in the shared task manager.
@@ -74,10 +67,8 @@ impl Deref for FileManifestReloadTask {
 pub enum FileResolverError {
     #[error("failed to read persisted documents manifest at '{path}': {message}")]
     ReadManifest { path: String, message: String },
-    #[error("failed to initialize persisted documents file watcher for '{path}': {message}")]
-    WatcherInit { path: String, message: String },
-    #[error("failed to watch persisted documents path '{path}': {message}")]
-    WatcherWatchPath { path: String, message: String },
+    #[error("failed to watch the persisted documents manifest '{path}': {message}")]
+    Watcher { path: String, message: String },
 }
 
 impl FileManifestResolver {
@@ -86,14 +77,8 @@ impl FileManifestResolver {
     ) -> Result<Self, PersistedDocumentResolverError> {
         let manifest_path = config.path.absolute.clone();
         let documents = Self::read_manifest_documents(&manifest_path).await?;
-        let dirty = Arc::new(AtomicBool::new(false));
-        let reload_signal = Arc::new(Notify::new());
         let watcher = if config.watch {
-            Some(Self::create_watcher(
-                &manifest_path,
-                Arc::clone(&dirty),
-                Arc::clone(&reload_signal),
-            )?)
+            Some(Self::create_watcher(&manifest_path)?)
         } else {
             None
         };
@@ -101,9 +86,7 @@ impl FileManifestResolver {
         Ok(Self {
             manifest_path,
             documents: ArcSwap::from_pointee(documents),
-            dirty,
             reload_guard: Mutex::new(()),
-            reload_signal,
             watcher,
         })
     }
@@ -112,67 +95,41 @@ impl FileManifestResolver {
         self.watcher.is_some()
     }
 
-    fn create_watcher(
-        manifest_path: &str,
-        dirty: Arc<AtomicBool>,
-        reload_signal: Arc<Notify>,
-    ) -> Result<RecommendedWatcher, PersistedDocumentResolverError> {
+    fn create_watcher(manifest_path: &str) -> Result<DebouncedFileWatcher, FileResolverError> {
         let path = Path::new(manifest_path);
         let manifest_path_buf = PathBuf::from(manifest_path);
         // Watch the parent directory so replace/rename save patterns are observed.
-        let watch_target = path.parent().unwrap_or(path);
-
-        let mut watcher = match RecommendedWatcher::new(
-            move |result: notify::Result<notify::Event>| {
-                let should_signal_reload = match result {
-                    Ok(event) => {
-                        let is_relevant_kind = matches!(
-                            event.kind,
-                            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
-                        );
-                        let touches_manifest =
-                            event.paths.iter().any(|path| path == &manifest_path_buf);
-                        is_relevant_kind && touches_manifest
-                    }
-                    Err(err) => {
-                        warn!("persisted documents watcher event failed: {err}");
-                        true
-                    }
-                };
-
-                if should_signal_reload {
-                    dirty.store(true, Ordering::Relaxed);
-                    reload_signal.notify_one();
-                }
-            },
-            NotifyConfig::default(),
-        ) {
-            Ok(watcher) => watcher,
-            Err(err) => {
-                return Err(FileResolverError::WatcherInit {
-                    path: manifest_path.to_string(),
-                    message: err.to_string(),
-                }
-                .into());
+        let watch_target = path.parent().unwrap_or(path).to_path_buf();
+
+        DebouncedFileWatcher::new([watch_target], move |result| match result {
+            Ok(event) => {
+                let is_relevant_kind = matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                );
+                let touches_manifest = event.paths.iter().any(|path| path == &manifest_path_buf);
+                is_relevant_kind && touches_manifest
             }
-        };
-
-        if let Err(err) = watcher.watch(watch_target, RecursiveMode::NonRecursive) {
-            return Err(FileResolverError::WatcherWatchPath {
-                path: manifest_path.to_string(),
-                message: err.to_string(),
+            Err(err) => {
+                warn!("persisted documents watcher event failed: {err}");
+                true
             }
-            .into());
-        }
-
-        Ok(watcher)
+        })
+        .map_err(|err| FileResolverError::Watcher {
+            path: manifest_path.to_string(),
+            message: err.to_string(),
+        })
     }
 
     // Keeps last known good snapshot active when reload fails
     pub(crate) async fn reload_if_needed(&self) -> Result<(), PersistedDocumentResolverError> {
         let _reload_guard = self.reload_guard.lock().await;
 
-        if !self.dirty.swap(false, Ordering::Relaxed) {
+        if !self
+            .watcher
+            .as_ref()
+            .is_some_and(|watcher| watcher.take_change())
+        {
             return Ok(());
         }
 
@@ -210,15 +167,11 @@ impl BackgroundTask for FileManifestReloadTask {
     }
 
     async fn run(&self, token: CancellationToken) {
-        // Watcher events are debounced to reduce noisy save/update actions
-        while token
-            .run_until_cancelled(async {
-                self.reload_signal.notified().await;
-                tokio::time::sleep(RELOAD_EVENT_DEBOUNCE).await;
-            })
-            .await
-            .is_some()
-        {
+        let Some(watcher) = self.watcher.as_ref() else {
+            return;
+        };
+
+        while watcher.changed(&token).await.is_some() {
             if let Err(err) = self.reload_if_needed().await {
                 warn!("persisted documents background reload failed: {err}");
             }
@@ -1,12 +1,13 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 
 use hive_router_config::override_labels::{LabelOverrideValue, OverrideLabelsConfig};
+use hive_router_config::progressive_override::ProgressiveOverrideConfig;
 use hive_router_internal::expressions::CompileExpression;
 use hive_router_plan_executor::execution::client_request_details::ClientRequestDetailsView;
 use hive_router_plan_executor::request_context::RequestContextError;
 use hive_router_plan_executor::request_context::SharedRequestContext;
 use hive_router_query_planner::{
-    graph::{PlannerOverrideContext, PERCENTAGE_SCALE_FACTOR},
+    graph::{OverrideLabel, PlannerOverrideContext, PERCENTAGE_SCALE_FACTOR},
     state::supergraph_state::SupergraphState,
 };
 use rand::prelude::*;
@@ -22,10 +23,11 @@ use vrl::{
 };
 
 #[derive(thiserror::Error, Debug)]
-#[error("Failed to compile override label expression for label '{label}': {error}")]
-pub struct OverrideLabelsCompileError {
-    pub label: String,
-    pub error: String,
+pub enum OverrideLabelsCompileError {
+    #[error("Failed to compile override label expression for label '{label}': {error}")]
+    Label { label: String, error: String },
+    #[error("Failed to compile the percentage key expression of progressive overrides: {0}")]
+    PercentageKey(String),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -41,6 +43,10 @@ pub enum LabelEvaluationError {
         "VRL expression for override label '{label}' did not evaluate to a boolean. Got: {got}"
     )]
     ExpressionWrongType { label: String, got: String },
+    #[error("Failed to resolve VRL expression for the percentage key. Runtime error: {0}")]
+    PercentageKeyResolutionFailure(ExpressionError),
+    #[error("VRL expression for the percentage key did not evaluate to a string. Got: {0}")]
+    PercentageKeyWrongType(String),
     #[error(transparent)]
     RequestContext(#[from] RequestContextError),
 }
@@ -51,8 +57,14 @@ pub enum LabelEvaluationError {
 pub struct RequestOverrideContext {
     /// The set of override flags that are active for this request.
     pub active_flags: HashSet<String>,
-    /// The randomly generated percentage value for this request.
+    /// The set of override flags disabled by the configuration,
+    /// that can't be enabled by plugins or coprocessors.
+    pub disabled_flags: HashSet<String>,
+    /// The percentage value for this request,
+    /// derived from the percentage key, or randomly generated.
     pub percentage_value: u64,
+    /// Outcomes of the percentage labels (`percent(x)`) forced on or off.
+    pub forced_percentages: HashMap<u64, bool>,
 }
 
 impl RequestOverrideContext {
@@ -64,7 +76,10 @@ impl RequestOverrideContext {
     ) -> Result<Self, LabelEvaluationError> {
         let progressive_override_state = request_context.snapshot()?.progressive_override;
 
-        let active_flags = override_labels_evaluator.evaluate(
+        let EvaluatedOverrideLabels {
+            active_flags,
+            disabled_flags,
+        } = override_labels_evaluator.evaluate(
             progressive_override_state.labels_to_override.as_ref(),
             client_request_details,
         )?;
@@ -89,22 +104,55 @@ impl RequestOverrideContext {
             })?;
         }
 
-        // Generate the random percentage value for this request.
+        // Derive the percentage value from the key of the request,
+        // or generate a random one when there's no key.
         // Percentage is 0 - 100_000_000_000 (100*PERCENTAGE_SCALE_FACTOR)
         // 0 = 0%
         // 100_000_000_000 = 100%
         // 50_000_000_000 = 50%
         // 50_123_456_789 = 50.12345678%
-        let percentage_value: u64 = rand::rng().random_range(0..=(100 * PERCENTAGE_SCALE_FACTOR));
+        let percentage_value =
+            match override_labels_evaluator.evaluate_percentage(client_request_details)? {
+                Some(percentage_value) => percentage_value,
+                None => rand::rng().random_range(0..=(100 * PERCENTAGE_SCALE_FACTOR)),
+            };
 
         let override_context = RequestOverrideContext {
+            forced_percentages: forced_percentages(&active_flags, &disabled_flags),
             active_flags,
+            disabled_flags,
             percentage_value,
         };
 
         Ok(override_context)
     }
 
+    /// The override context of a request that doesn't resolve labels at runtime,
+    /// only the labels enabled or disabled statically in the configuration apply.
+    pub fn from_static_labels(override_labels_config: &OverrideLabelsConfig) -> Self {
+        let mut active_flags = HashSet::new();
+        let mut disabled_flags = HashSet::new();
+
+        for (label, value) in override_labels_config.iter() {
+            match value {
+                LabelOverrideValue::Boolean(true) => {
+                    active_flags.insert(label.clone());
+                }
+                LabelOverrideValue::Boolean(false) => {
+                    disabled_flags.insert(label.clone());
+                }
+                LabelOverrideValue::Expression { .. } => {}
+            }
+        }
+
+        RequestOverrideContext {
+            forced_percentages: forced_percentages(&active_flags, &disabled_flags),
+            active_flags,
+            disabled_flags,
+            percentage_value: 0,
+        }
+    }
+
     pub fn update_from(
         &mut self,
         request_context: &SharedRequestContext,
@@ -114,15 +162,47 @@ impl RequestOverrideContext {
             .progressive_override
             .labels_to_override;
 
-        self.active_flags = active_labels.unwrap_or_default();
+        self.active_flags = active_labels
+            .unwrap_or_default()
+            .difference(&self.disabled_flags)
+            .cloned()
+            .collect();
+        self.forced_percentages = forced_percentages(&self.active_flags, &self.disabled_flags);
 
         Ok(())
     }
+
+    /// Whether the request is in the range of a `percent(x)` label.
+    pub fn is_in_range(&self, threshold: u64) -> bool {
+        match self.forced_percentages.get(&threshold) {
+            Some(forced) => *forced,
+            None => self.percentage_value < threshold,
+        }
+    }
+}
+
+/// Collects the percentage labels (`percent(x)`) among the active and disabled flags.
+fn forced_percentages(
+    active_flags: &HashSet<String>,
+    disabled_flags: &HashSet<String>,
+) -> HashMap<u64, bool> {
+    let mut forced_percentages = HashMap::new();
+
+    for (flags, in_range) in [(active_flags, true), (disabled_flags, false)] {
+        for flag in flags {
+            if let Some(percentage) = OverrideLabel::percentage_from_label(flag) {
+                forced_percentages.insert(percentage, in_range);
+            }
+        }
+    }
+
+    forced_percentages
 }
 
 impl From<&RequestOverrideContext> for PlannerOverrideContext {
     fn from(value: &RequestOverrideContext) -> Self {
         Self::new(value.active_flags.clone(), value.percentage_value)
+            .with_forced_percentages(value.forced_percentages.clone())
     }
 }
 
@@ -148,7 +228,7 @@ impl StableOverrideContext {
 
         let mut percentage_outcomes = BTreeMap::new();
         for &threshold in &supergraph.progressive_overrides.percentages {
-            let in_range = request_override_context.percentage_value < threshold;
+            let in_range = request_override_context.is_in_range(threshold);
             percentage_outcomes.insert(threshold, in_range);
         }
 
@@ -164,14 +244,24 @@ impl StableOverrideContext {
 /// It's intended to be used as a shared state in the router.
 pub struct OverrideLabelsEvaluator {
     static_enabled_labels: HashSet<String>,
+    static_disabled_labels: HashSet<String>,
     expressions: HashMap<String, VrlProgram>,
+    percentage_key: Option<VrlProgram>,
+}
+
+/// The outcome of the override labels configuration for a request.
+pub(crate) struct EvaluatedOverrideLabels {
+    pub active_flags: HashSet<String>,
+    pub disabled_flags: HashSet<String>,
 }
 
 impl OverrideLabelsEvaluator {
     pub(crate) fn from_config(
         override_labels_config: &OverrideLabelsConfig,
+        progressive_override_config: &ProgressiveOverrideConfig,
     ) -> Result<Self, OverrideLabelsCompileError> {
         let mut static_enabled_labels = HashSet::new();
+        let mut static_disabled_labels = HashSet::new();
         let mut expressions = HashMap::new();
 
         for (label, value) in override_labels_config.iter() {
@@ -179,22 +269,37 @@ impl OverrideLabelsEvaluator {
                 LabelOverrideValue::Boolean(true) => {
                     static_enabled_labels.insert(label.clone());
                 }
+                LabelOverrideValue::Boolean(false) => {
+                    static_disabled_labels.insert(label.clone());
+                }
                 LabelOverrideValue::Expression { expression } => {
                     let program = expression.compile_expression(None).map_err(|err| {
-                        OverrideLabelsCompileError {
+                        OverrideLabelsCompileError::Label {
                             label: label.clone(),
                             error: err.to_string(),
                         }
                     })?;
                     expressions.insert(label.clone(), program);
                 }
-                _ => {} // Skip false booleans
             }
         }
 
+        let percentage_key = progressive_override_config
+            .percentage_key
+            .as_ref()
+            .map(|percentage_key| {
+                percentage_key
+                    .expression
+                    .compile_expression(None)
+                    .map_err(|err| OverrideLabelsCompileError::PercentageKey(err.to_string()))
+            })
+            .transpose()?;
+
         Ok(Self {
             static_enabled_labels,
+            static_disabled_labels,
             expressions,
+            percentage_key,
         })
     }
 
@@ -203,24 +308,23 @@ impl OverrideLabelsEvaluator {
         // Labels that have already been resolved either by plugins or coprocessors
         resolved_labels: Option<&HashSet<String>>,
         client_request: &impl ClientRequestDetailsView,
-    ) -> Result<HashSet<String>, LabelEvaluationError> {
-        let mut active_flags = match resolved_labels {
+    ) -> Result<EvaluatedOverrideLabels, LabelEvaluationError> {
+        let mut active_flags: HashSet<String> = match resolved_labels {
             Some(set) => set.union(&self.static_enabled_labels).cloned().collect(),
             None => self.static_enabled_labels.clone(),
         };
+        // Labels disabled in the configuration win over the ones resolved at runtime
+        active_flags.retain(|label| !self.static_disabled_labels.contains(label));
+        let mut disabled_flags = self.static_disabled_labels.clone();
 
         if self.expressions.is_empty() {
-            return Ok(active_flags);
+            return Ok(EvaluatedOverrideLabels {
+                active_flags,
+                disabled_flags,
+            });
         }
 
-        let mut target = VrlTargetValue {
-            value: VrlValue::Object(BTreeMap::from([(
-                "request".into(),
-                client_request.to_vrl_value(),
-            )])),
-            metadata: VrlValue::Object(BTreeMap::new()),
-            secrets: VrlSecrets::default(),
-        };
+        let mut target = vrl_target(client_request);
 
         let mut state = VrlState::default();
         let timezone = VrlTimeZone::default();
@@ -239,7 +343,11 @@ impl OverrideLabelsEvaluator {
                         active_flags.insert(label.clone());
                     }
                     VrlValue::Boolean(false) => {
-                        // Do nothing for false
+                        // A percentage label is forced off,
+                        // instead of falling back to the percentage of the request
+                        if OverrideLabel::percentage_from_label(label).is_some() {
+                            disabled_flags.insert(label.clone());
+                        }
                     }
                     invalid_value => {
                         return Err(LabelEvaluationError::ExpressionWrongType {
@@ -257,6 +365,48 @@ impl OverrideLabelsEvaluator {
             }
         }
 
-        Ok(active_flags)
+        Ok(EvaluatedOverrideLabels {
+            active_flags,
+            disabled_flags,
+        })
+    }
+
+    /// Evaluates the percentage key of the request, and maps it to a percentage value.
+    /// Returns `None` when there's no key, so the percentage should be random.
+    pub(crate) fn evaluate_percentage(
+        &self,
+        client_request: &impl ClientRequestDetailsView,
+    ) -> Result<Option<u64>, LabelEvaluationError> {
+        let Some(percentage_key) = &self.percentage_key else {
+            return Ok(None);
+        };
+
+        let mut target = vrl_target(client_request);
+        let mut state = VrlState::default();
+        let timezone = VrlTimeZone::default();
+        let mut ctx = VrlContext::new(&mut target, &mut state, &timezone);
+
+        match percentage_key.resolve(&mut ctx) {
+            Ok(VrlValue::Bytes(key)) => Ok(Some(
+                PlannerOverrideContext::percentage_from_request_key(&key),
+            )),
+            Ok(VrlValue::Null) => Ok(None),
+            Ok(invalid_value) => Err(LabelEvaluationError::PercentageKeyWrongType(format!(
+                "{:?}",
+                invalid_value
+            ))),
+            Err(err) => Err(LabelEvaluationError::PercentageKeyResolutionFailure(err)),
+        }
+    }
+}
+
+fn vrl_target(client_request: &impl ClientRequestDetailsView) -> VrlTargetValue {
+    VrlTargetValue {
+        value: VrlValue::Object(BTreeMap::from([(
+            "request".into(),
+            client_request.to_vrl_value(),
+        )])),
+        metadata: VrlValue::Object(BTreeMap::new()),
+        secrets: VrlSecrets::default(),
     }
 }
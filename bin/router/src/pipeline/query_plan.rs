@@ -1,11 +1,14 @@
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, LazyLock};
+use std::time::Instant;
 
 use crate::cache_state::{CacheHitMiss, EntryResultHitMissExt};
 use crate::pipeline::error::PipelineError;
-use crate::pipeline::normalize::GraphQLNormalizationPayload;
+use crate::pipeline::execution::QueryPlanTiming;
+use crate::pipeline::normalize::{hash_operation_for_plan, GraphQLNormalizationPayload};
 use crate::pipeline::progressive_override::{RequestOverrideContext, StableOverrideContext};
 use crate::schema_state::{SchemaState, SelectedSupergraph};
+use hive_router_internal::telemetry::metrics::catalog::values::PlanningStatus;
 use hive_router_internal::telemetry::traces::spans::graphql::GraphQLPlanSpan;
 use hive_router_plan_executor::execution::plan::PlanExecutionOutput;
 use hive_router_plan_executor::hooks::on_query_plan::{
@@ -16,12 +19,13 @@ use hive_router_plan_executor::plugin_trait::{CacheHint, EndControlFlow, StartCo
 use hive_router_plan_executor::plugins::hooks;
 use hive_router_query_planner::planner::plan_nodes::QueryPlan;
 use hive_router_query_planner::planner::query_plan::QUERY_PLAN_KIND;
-use hive_router_query_planner::utils::cancellation::CancellationToken;
+use hive_router_query_planner::planner::PlannerError;
+use hive_router_query_planner::utils::cancellation::{CancellationError, CancellationToken};
 use tracing::Instrument;
 use xxhash_rust::xxh3::Xxh3;
 
 pub enum QueryPlanResult {
-    QueryPlan(Arc<QueryPlan>),
+    QueryPlan(Arc<QueryPlan>, QueryPlanTiming),
     EarlyResponse(PlanExecutionOutput),
 }
 static EMPTY_QUERY_PLAN: LazyLock<Arc<QueryPlan>> = LazyLock::new(|| {
@@ -41,6 +45,7 @@ pub async fn plan_operation_with_cache(
     plugin_req_state: &Option<PluginRequestState<'_>>,
 ) -> Result<QueryPlanResult, PipelineError> {
     let plan_span = GraphQLPlanSpan::new();
+    let started_at = Instant::now();
 
     async {
         let mut on_end_callbacks = vec![];
@@ -89,7 +94,10 @@ pub async fn plan_operation_with_cache(
         ) {
             normalized_operation.operation_for_plan_hash
         } else {
-            filtered_operation_for_plan.hash()
+            hash_operation_for_plan(
+                filtered_operation_for_plan,
+                normalized_operation.deferred_operation.as_deref(),
+            )
         };
         let plan_cache_key = calculate_cache_key(operation_for_plan_hash, &stable_override_context);
         let is_plan_operation_empty = filtered_operation_for_plan.selection_set.is_empty();
@@ -124,15 +132,30 @@ pub async fn plan_operation_with_cache(
                     return Ok(EMPTY_QUERY_PLAN.clone());
                 }
 
-                supergraph
-                    .snapshot
-                    .planner
-                    .plan_from_normalized_operation(
+                let planning_capture = metrics.graphql.capture_planning();
+                let planner = &supergraph.snapshot.planner;
+                let plan = match &normalized_operation.deferred_operation {
+                    Some(deferred_operation) => planner.plan_deferred_operation(
+                        filtered_operation_for_plan,
+                        deferred_operation,
+                        (&request_override_context.clone()).into(),
+                        cancellation_token,
+                    ),
+                    None => planner.plan_from_normalized_operation(
                         filtered_operation_for_plan,
                         (&request_override_context.clone()).into(),
                         cancellation_token,
-                    )
-                    .map(Arc::new)
+                    ),
+                }
+                .map(Arc::new);
+                planning_capture.finish(match &plan {
+                    Ok(_) => PlanningStatus::Ok,
+                    Err(PlannerError::CancellationError(CancellationError::TimedOut)) => {
+                        PlanningStatus::Timeout
+                    }
+                    Err(_) => PlanningStatus::Error,
+                });
+                plan
             })
             .await
             .map_err(PipelineError::from)
@@ -149,6 +172,8 @@ pub async fn plan_operation_with_cache(
                 }
             })?;
 
+        let cache_hit = matches!(cache_hint, CacheHint::Hit);
+
         if !on_end_callbacks.is_empty() {
             let mut end_payload = OnQueryPlanEndHookPayload {
                 query_plan: plan,
@@ -174,14 +199,20 @@ pub async fn plan_operation_with_cache(
             plan = end_payload.query_plan;
         }
 
-        Ok(QueryPlanResult::QueryPlan(plan))
+        Ok(QueryPlanResult::QueryPlan(
+            plan,
+            QueryPlanTiming {
+                duration: started_at.elapsed(),
+                cache_hit,
+            },
+        ))
     }
     .instrument(plan_span.clone())
     .await
 }
 
 #[inline]
-fn calculate_cache_key(operation_hash: u64, context: &StableOverrideContext) -> u64 {
+pub(crate) fn calculate_cache_key(operation_hash: u64, context: &StableOverrideContext) -> u64 {
     let mut hasher = Xxh3::new();
     operation_hash.hash(&mut hasher);
     context.hash(&mut hasher);
@@ -0,0 +1,528 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use hive_router_config::rate_limit::{RateLimitBackendConfig, RateLimitConfig, RateLimitKeyConfig};
+use hive_router_internal::telemetry::{
+    metrics::catalog::values::RateLimitResult, traces::spans::http_request::resolve_client_ip,
+};
+use http::{header::RETRY_AFTER, HeaderName, HeaderValue};
+use moka::sync::Cache;
+use ntex::{http::HeaderMap, web::HttpRequest};
+use sha2::{Digest, Sha256};
+use sonic_rs::JsonValueTrait;
+use tracing::{debug, info, warn};
+
+use crate::{
+    pipeline::error::{PipelineError, PipelineErrorAdditionalHeaders},
+    storage::{
+        error::StorageError,
+        redis_connections::{RedisConnection, RedisConnections},
+    },
+    RouterSharedState,
+};
+
+const X_RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+const X_RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+const X_RATELIMIT_RESET: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// Takes a token from the bucket of the key, and returns the state of the bucket.
+///
+/// The bucket is a GCRA (Generic Cell Rate Algorithm), like the subgraph rate limiter:
+/// it only stores the theoretical arrival time of the next request.
+/// Returns `{allowed, retry_after, reset_after}`, in microseconds.
+const REDIS_ACQUIRE_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000000 + tonumber(time[2])
+local emission_interval = tonumber(ARGV[1])
+local burst_tolerance = tonumber(ARGV[2])
+local arrival = tonumber(redis.call('GET', KEYS[1]) or now)
+if arrival < now then
+  arrival = now
+end
+local next_arrival = arrival + emission_interval
+local wait = next_arrival - burst_tolerance - now
+if wait > 0 then
+  return {0, wait, arrival - now}
+end
+redis.call('SET', KEYS[1], next_arrival, 'PX', math.ceil((next_arrival - now) / 1000))
+return {1, 0, next_arrival - now}
+"#;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RateLimitError {
+    #[error("invalid rate limit config: {0}")]
+    Configuration(String),
+    #[error("invalid rate limit redis config: {0}")]
+    Redis(#[from] StorageError),
+}
+
+/// Limits the rate of the requests of every client, identified by `rate_limit.key`.
+///
+/// The requests are checked before their body is read,
+/// and the limited ones are rejected with a `429 Too Many Requests` response.
+pub struct RateLimitRuntime {
+    key: RateLimitKeyConfig,
+    store: Box<dyn RateLimitStore>,
+    /// The capacity of the bucket of a client.
+    limit: u32,
+    /// Time between two requests at the steady rate.
+    emission_interval: Duration,
+    /// How far ahead of the steady rate the requests can be sent.
+    burst_tolerance: Duration,
+    expose_headers: bool,
+}
+
+/// The state of the bucket of a client, after one of its requests.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// How long the client has to wait before its next request is allowed.
+    pub retry_after: Duration,
+    /// How long until the bucket of the client is full again.
+    pub reset_after: Duration,
+}
+
+impl RateLimitDecision {
+    /// The `x-ratelimit-*` headers describing the state of the bucket.
+    pub fn response_headers(&self) -> PipelineErrorAdditionalHeaders {
+        vec![
+            (X_RATELIMIT_LIMIT, HeaderValue::from(self.limit)),
+            (X_RATELIMIT_REMAINING, HeaderValue::from(self.remaining)),
+            (
+                X_RATELIMIT_RESET,
+                HeaderValue::from(ceil_secs(self.reset_after)),
+            ),
+        ]
+    }
+}
+
+/// The `x-ratelimit-*` headers of the request, written to the response sent to the client.
+pub struct RateLimitHeaders(PipelineErrorAdditionalHeaders);
+
+impl RateLimitHeaders {
+    pub fn write_to(&self, headers: &mut HeaderMap) {
+        for (name, value) in &self.0 {
+            headers.insert(name.clone(), value.clone());
+        }
+    }
+}
+
+impl RateLimitRuntime {
+    pub fn from_config(
+        config: &RateLimitConfig,
+        redis_connections: &RedisConnections,
+    ) -> Result<Option<Self>, RateLimitError> {
+        if !config.enabled {
+            debug!("rate limiting is disabled");
+            return Ok(None);
+        }
+
+        let requests_per_second = config.requests_per_second.ok_or_else(|| {
+            RateLimitError::Configuration(
+                "'requests_per_second' is required when the rate limiting is enabled".to_string(),
+            )
+        })?;
+
+        if requests_per_second == 0 {
+            return Err(RateLimitError::Configuration(
+                "'requests_per_second' must be greater than 0".to_string(),
+            ));
+        }
+
+        let burst = config.burst.unwrap_or(requests_per_second);
+
+        if burst == 0 {
+            return Err(RateLimitError::Configuration(
+                "'burst' must be greater than 0".to_string(),
+            ));
+        }
+
+        let emission_interval = Duration::from_secs(1) / requests_per_second;
+        let burst_tolerance = emission_interval * burst;
+
+        let store: Box<dyn RateLimitStore> = match &config.backend {
+            RateLimitBackendConfig::Memory { max_entries } => Box::new(MemoryRateLimitStore {
+                // an idle bucket is full once the burst tolerance has elapsed
+                cache: Cache::builder()
+                    .max_capacity(*max_entries)
+                    .time_to_idle(burst_tolerance)
+                    .build(),
+            }),
+            RateLimitBackendConfig::Redis { url, key_prefix } => Box::new(RedisRateLimitStore {
+                connection: redis_connections.connect("rate_limit", url)?,
+                key_prefix: key_prefix.clone(),
+            }),
+        };
+
+        info!(requests_per_second, burst, "rate limiting enabled");
+
+        Ok(Some(Self {
+            key: config.key.clone(),
+            store,
+            limit: burst,
+            emission_interval,
+            burst_tolerance,
+            expose_headers: config.expose_headers,
+        }))
+    }
+
+    /// Takes a token from the bucket of the client sending the request,
+    /// or fails with `PipelineError::RateLimited` when the bucket is empty.
+    ///
+    /// The requests are allowed when the backend fails, the errors are logged.
+    pub async fn enforce(
+        &self,
+        req: &HttpRequest,
+        shared_state: &RouterSharedState,
+    ) -> Result<(), PipelineError> {
+        let key = self.client_key(req, shared_state).await;
+        let Some(decision) = self.acquire(&key).await else {
+            return Ok(());
+        };
+
+        if self.expose_headers {
+            req.extensions_mut()
+                .insert(RateLimitHeaders(decision.response_headers()));
+        }
+
+        let metrics = &shared_state.telemetry_context.metrics.rate_limit;
+        if decision.allowed {
+            metrics.record_request(RateLimitResult::Allowed);
+            return Ok(());
+        }
+
+        metrics.record_request(RateLimitResult::Limited);
+        debug!(key = %key, "request exceeds the rate limit of the client");
+
+        Err(PipelineError::RateLimited {
+            response_headers: vec![(
+                RETRY_AFTER,
+                // a client retrying right away would be limited again
+                HeaderValue::from(ceil_secs(decision.retry_after).max(1)),
+            )],
+        })
+    }
+
+    async fn acquire(&self, key: &str) -> Option<RateLimitDecision> {
+        let bucket = match self
+            .store
+            .acquire(key, self.emission_interval, self.burst_tolerance)
+            .await
+        {
+            Ok(bucket) => bucket,
+            Err(err) => {
+                warn!(error = %err, "failed to check the rate limit of the client, allowing the request");
+                return None;
+            }
+        };
+
+        let remaining = if bucket.allowed {
+            let available = self.burst_tolerance.saturating_sub(bucket.reset_after);
+            (available.as_nanos() / self.emission_interval.as_nanos()) as u32
+        } else {
+            0
+        };
+
+        Some(RateLimitDecision {
+            allowed: bucket.allowed,
+            limit: self.limit,
+            remaining,
+            retry_after: bucket.retry_after,
+            reset_after: bucket.reset_after,
+        })
+    }
+
+    /// The key of the bucket of the client.
+    /// Falls back to the IP address of the client when the configured identity is missing.
+    async fn client_key(&self, req: &HttpRequest, shared_state: &RouterSharedState) -> String {
+        let identity = match &self.key {
+            RateLimitKeyConfig::Ip => None,
+            RateLimitKeyConfig::Header { name } => {
+                header_value(req, name.get_header_ref()).map(|value| format!("header:{value}"))
+            }
+            RateLimitKeyConfig::ApiKey { header } => header_value(req, header.get_header_ref())
+                .map(|value| format!("api_key:{}", sha256_hex(value))),
+            RateLimitKeyConfig::JwtClaim { claim } => jwt_claim(req, shared_state, claim)
+                .await
+                .map(|value| format!("jwt:{value}")),
+        };
+
+        identity.unwrap_or_else(|| {
+            let client_ip = resolve_client_ip(
+                req,
+                &shared_state
+                    .router_config
+                    .telemetry
+                    .client_identification
+                    .ip_header,
+            );
+            match client_ip {
+                Some(ip) => format!("ip:{ip}"),
+                None => "ip:unknown".to_string(),
+            }
+        })
+    }
+}
+
+fn header_value<'req>(req: &'req HttpRequest, name: &HeaderName) -> Option<&'req str> {
+    req.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+}
+
+/// The value of a claim of the JWT of the request, when the token is valid.
+/// Invalid tokens are rejected later on, by the JWT authentication.
+async fn jwt_claim(
+    req: &HttpRequest,
+    shared_state: &RouterSharedState,
+    claim: &str,
+) -> Option<String> {
+    let jwt_auth_runtime = shared_state.jwt_auth_runtime.as_ref()?;
+    let jwt_context = jwt_auth_runtime
+        .validate_headers(req.headers(), &shared_state.jwt_claims_cache)
+        .await
+        .ok()??;
+    let claims = jwt_context.get_claims_value().ok()?;
+    let value = claims.get(claim)?;
+
+    Some(match value.as_str() {
+        Some(value) => value.to_string(),
+        None => value.to_string(),
+    })
+}
+
+fn sha256_hex(value: &str) -> String {
+    format!("{:x}", Sha256::digest(value.as_bytes()))
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_micros().div_ceil(1_000_000) as u64
+}
+
+/// The state of a bucket, after taking a token from it.
+struct BucketState {
+    allowed: bool,
+    retry_after: Duration,
+    reset_after: Duration,
+}
+
+#[async_trait]
+trait RateLimitStore: Send + Sync {
+    async fn acquire(
+        &self,
+        key: &str,
+        emission_interval: Duration,
+        burst_tolerance: Duration,
+    ) -> Result<BucketState, redis::RedisError>;
+}
+
+struct MemoryRateLimitStore {
+    /// The theoretical arrival time of the next request of every client.
+    cache: Cache<String, Arc<Mutex<Instant>>>,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn acquire(
+        &self,
+        key: &str,
+        emission_interval: Duration,
+        burst_tolerance: Duration,
+    ) -> Result<BucketState, redis::RedisError> {
+        let now = Instant::now();
+        let bucket = self
+            .cache
+            .get_with_by_ref(key, || Arc::new(Mutex::new(now)));
+        let mut theoretical_arrival = match bucket.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        Ok(acquire_at(
+            &mut theoretical_arrival,
+            now,
+            emission_interval,
+            burst_tolerance,
+        ))
+    }
+}
+
+fn acquire_at(
+    theoretical_arrival: &mut Instant,
+    now: Instant,
+    emission_interval: Duration,
+    burst_tolerance: Duration,
+) -> BucketState {
+    let arrival = (*theoretical_arrival).max(now);
+    let next_arrival = arrival + emission_interval;
+    let wait = next_arrival
+        .saturating_duration_since(now)
+        .saturating_sub(burst_tolerance);
+
+    if !wait.is_zero() {
+        return BucketState {
+            allowed: false,
+            retry_after: wait,
+            reset_after: arrival - now,
+        };
+    }
+
+    *theoretical_arrival = next_arrival;
+    BucketState {
+        allowed: true,
+        retry_after: Duration::ZERO,
+        reset_after: next_arrival - now,
+    }
+}
+
+/// Keeps the buckets in Redis, updated atomically by a Lua script.
+struct RedisRateLimitStore {
+    connection: RedisConnection,
+    key_prefix: String,
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn acquire(
+        &self,
+        key: &str,
+        emission_interval: Duration,
+        burst_tolerance: Duration,
+    ) -> Result<BucketState, redis::RedisError> {
+        let mut connection = self.connection.get().await?;
+
+        let (allowed, retry_after, reset_after): (u8, u64, u64) = redis::cmd("EVAL")
+            .arg(REDIS_ACQUIRE_SCRIPT)
+            .arg(1)
+            .arg(format!("{}{}", self.key_prefix, key))
+            .arg(emission_interval.as_micros() as u64)
+            .arg(burst_tolerance.as_micros() as u64)
+            .query_async(&mut connection)
+            .await?;
+
+        Ok(BucketState {
+            allowed: allowed == 1,
+            retry_after: Duration::from_micros(retry_after),
+            reset_after: Duration::from_micros(reset_after),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runtime() -> RateLimitRuntime {
+        RateLimitRuntime::from_config(
+            &RateLimitConfig {
+                enabled: true,
+                requests_per_second: Some(10),
+                burst: Some(2),
+                ..Default::default()
+            },
+            &RedisConnections::default(),
+        )
+        .unwrap()
+        .unwrap()
+    }
+
+    #[test]
+    fn requires_requests_per_second() {
+        let config = RateLimitConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let redis_connections = RedisConnections::default();
+        assert!(RateLimitRuntime::from_config(&config, &redis_connections).is_err());
+        assert!(
+            RateLimitRuntime::from_config(&RateLimitConfig::default(), &redis_connections)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn limits_every_client_on_its_own() {
+        let runtime = runtime();
+
+        let first = runtime.acquire("ip:127.0.0.1").await.unwrap();
+        assert!(first.allowed);
+        assert_eq!(first.limit, 2);
+        assert_eq!(first.remaining, 1);
+
+        let second = runtime.acquire("ip:127.0.0.1").await.unwrap();
+        assert!(second.allowed);
+        assert_eq!(second.remaining, 0);
+
+        let third = runtime.acquire("ip:127.0.0.1").await.unwrap();
+        assert!(!third.allowed);
+        assert!(third.retry_after > Duration::ZERO);
+        assert!(third.retry_after <= Duration::from_millis(100));
+
+        let other_client = runtime.acquire("ip:127.0.0.2").await.unwrap();
+        assert!(other_client.allowed);
+        assert_eq!(other_client.remaining, 1);
+    }
+
+    #[test]
+    fn refills_the_bucket_at_the_steady_rate() {
+        let emission_interval = Duration::from_millis(100);
+        let burst_tolerance = Duration::from_millis(200);
+        let now = Instant::now();
+        let mut theoretical_arrival = now;
+
+        assert!(
+            acquire_at(
+                &mut theoretical_arrival,
+                now,
+                emission_interval,
+                burst_tolerance
+            )
+            .allowed
+        );
+        assert!(
+            acquire_at(
+                &mut theoretical_arrival,
+                now,
+                emission_interval,
+                burst_tolerance
+            )
+            .allowed
+        );
+        let limited = acquire_at(
+            &mut theoretical_arrival,
+            now,
+            emission_interval,
+            burst_tolerance,
+        );
+        assert!(!limited.allowed);
+        assert_eq!(limited.retry_after, Duration::from_millis(100));
+        assert_eq!(limited.reset_after, Duration::from_millis(200));
+
+        let later = now + Duration::from_millis(100);
+        assert!(
+            acquire_at(
+                &mut theoretical_arrival,
+                later,
+                emission_interval,
+                burst_tolerance
+            )
+            .allowed
+        );
+        assert!(
+            !acquire_at(
+                &mut theoretical_arrival,
+                later,
+                emission_interval,
+                burst_tolerance
+            )
+            .allowed
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use hive_router_config::request_id::RequestIdConfig;
+use http::HeaderValue;
+use ntex::{http::HeaderMap, web::HttpRequest};
+use ulid::Ulid;
+
+/// The longest ID accepted from a client, longer ones are replaced by a generated ID.
+const MAX_INCOMING_REQUEST_ID_LENGTH: usize = 128;
+
+/// The ID of a request to the GraphQL endpoint, stored in the extensions of the request
+/// when `request_id` is enabled.
+#[derive(Debug, Clone)]
+pub struct RequestId(HeaderValue);
+
+impl RequestId {
+    /// Reuses the ID sent by the client when it is accepted and valid, or generates one.
+    pub fn resolve(request: &HttpRequest, config: &RequestIdConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+
+        let incoming = config
+            .accept_incoming
+            .then(|| request.headers().get(config.header.get_header_ref()))
+            .flatten()
+            .filter(|value| is_valid_request_id(value.as_bytes()))
+            .and_then(|value| HeaderValue::from_bytes(value.as_bytes()).ok());
+
+        Some(match incoming {
+            Some(value) => Self(value),
+            None => Self(
+                HeaderValue::from_str(&Ulid::gen().to_string())
+                    .expect("a ULID is a valid header value"),
+            ),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only visible ASCII characters are accepted
+        self.0.to_str().unwrap_or_default()
+    }
+
+    /// Sets the ID in `request_id.header`, of the client request or of its response.
+    pub fn write_to(&self, headers: &mut HeaderMap, config: &RequestIdConfig) {
+        headers.insert(config.header.get_header_ref().clone(), self.0.clone());
+    }
+}
+
+#[inline]
+pub fn read_request_id(req: &HttpRequest) -> Option<RequestId> {
+    req.extensions().get::<RequestId>().cloned()
+}
+
+fn is_valid_request_id(value: &[u8]) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_INCOMING_REQUEST_ID_LENGTH
+        && value.iter().all(|byte| byte.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_valid_request_id;
+
+    #[test]
+    fn accepts_visible_ascii_ids() {
+        assert!(is_valid_request_id(b"01JAR7M1X4Q7ZC6C6QK6B6W2D0"));
+        assert!(is_valid_request_id(b"b3f1c6a2-8d2e-4c8e-9c1a-2f1d3e4a5b6c"));
+        assert!(!is_valid_request_id(b""));
+        assert!(!is_valid_request_id(b"an id with spaces"));
+        assert!(!is_valid_request_id("été".as_bytes()));
+        assert!(!is_valid_request_id(&[b'a'; 129]));
+    }
+}
@@ -0,0 +1,408 @@
+use std::collections::{BTreeSet, HashMap};
+
+use graphql_tools::parser::query::Value;
+use graphql_tools::parser::schema::{Definition, Directive, TypeDefinition};
+use graphql_tools::static_graphql::schema::Document;
+use hive_router_plan_executor::introspection::schema::SchemaMetadata;
+use hive_router_query_planner::ast::operation::OperationDefinition;
+use hive_router_query_planner::ast::selection_item::SelectionItem;
+use hive_router_query_planner::ast::selection_set::SelectionSet;
+use xxhash_rust::xxh3::xxh3_64;
+
+const CACHE_CONTROL_DIRECTIVE: &str = "cacheControl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheScope {
+    Public,
+    Private,
+}
+
+/// A `@cacheControl(maxAge: Int, scope: CacheControlScope, inheritMaxAge: Boolean)` hint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct CacheHint {
+    max_age: Option<u32>,
+    private: bool,
+    inherit_max_age: bool,
+}
+
+/// The caching policy of an operation: the lowest max-age of its fields
+/// and whether any of them is scoped to a single client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    pub max_age: u32,
+    pub scope: CacheScope,
+}
+
+/// The `@cacheControl` hints of a supergraph, on types and on fields.
+#[derive(Debug, Default)]
+pub struct CacheControlHints {
+    /// Hash of the supergraph, stable across router instances sharing a cache.
+    pub schema_hash: u64,
+    types: HashMap<String, CacheHint>,
+    fields: HashMap<String, HashMap<String, CacheHint>>,
+}
+
+impl CacheControlHints {
+    pub fn from_supergraph(document: &Document) -> Self {
+        let mut hints = Self {
+            schema_hash: xxh3_64(document.to_string().as_bytes()),
+            ..Default::default()
+        };
+
+        for definition in &document.definitions {
+            let Definition::TypeDefinition(type_definition) = definition else {
+                continue;
+            };
+
+            let (type_name, directives, fields) = match type_definition {
+                TypeDefinition::Object(object) => {
+                    (&object.name, &object.directives, object.fields.as_slice())
+                }
+                TypeDefinition::Interface(interface) => (
+                    &interface.name,
+                    &interface.directives,
+                    interface.fields.as_slice(),
+                ),
+                TypeDefinition::Union(union) => (&union.name, &union.directives, &[][..]),
+                _ => continue,
+            };
+
+            if let Some(hint) = CacheHint::from_directives(directives) {
+                hints.types.insert(type_name.clone(), hint);
+            }
+
+            for field in fields {
+                if let Some(hint) = CacheHint::from_directives(&field.directives) {
+                    hints
+                        .fields
+                        .entry(type_name.clone())
+                        .or_default()
+                        .insert(field.name.clone(), hint);
+                }
+            }
+        }
+
+        hints
+    }
+
+    /// Computes the caching policy of an operation.
+    ///
+    /// Every field contributes a max-age: its own hint, or the hint of the composite type it returns.
+    /// Root fields and fields returning composite types without a hint fall back to `default_max_age`,
+    /// other fields inherit the max-age of their parent.
+    ///
+    /// Returns `None` when the operation must not be cached.
+    pub fn policy_for_operation(
+        &self,
+        operation: &OperationDefinition,
+        root_type_name: &str,
+        schema_metadata: &SchemaMetadata,
+        default_max_age: u32,
+    ) -> Option<CachePolicy> {
+        let mut walker = PolicyWalker {
+            hints: self,
+            schema_metadata,
+            default_max_age,
+            max_age: None,
+            private: false,
+        };
+        walker.visit(&operation.selection_set, root_type_name, true);
+
+        match walker.max_age {
+            Some(max_age) if max_age > 0 => Some(CachePolicy {
+                max_age,
+                scope: if walker.private {
+                    CacheScope::Private
+                } else {
+                    CacheScope::Public
+                },
+            }),
+            _ => None,
+        }
+    }
+
+    fn field_hint(&self, type_name: &str, field_name: &str) -> Option<&CacheHint> {
+        self.fields.get(type_name)?.get(field_name)
+    }
+}
+
+struct PolicyWalker<'a> {
+    hints: &'a CacheControlHints,
+    schema_metadata: &'a SchemaMetadata,
+    default_max_age: u32,
+    max_age: Option<u32>,
+    private: bool,
+}
+
+impl PolicyWalker<'_> {
+    fn visit(&mut self, selection_set: &SelectionSet, parent_type_name: &str, is_root: bool) {
+        for item in &selection_set.items {
+            match item {
+                SelectionItem::Field(field) => {
+                    if field.name == "__typename" {
+                        continue;
+                    }
+
+                    let Some(output_type_name) = self
+                        .schema_metadata
+                        .get_type_fields(parent_type_name)
+                        .and_then(|fields| fields.get(&field.name))
+                        .map(|field_info| field_info.output_type_name.as_str())
+                    else {
+                        continue;
+                    };
+
+                    let is_composite = !field.selections.is_empty();
+                    let field_hint = self.hints.field_hint(parent_type_name, &field.name);
+                    let type_hint = is_composite
+                        .then(|| self.hints.types.get(output_type_name))
+                        .flatten();
+
+                    if field_hint.is_some_and(|hint| hint.private)
+                        || type_hint.is_some_and(|hint| hint.private)
+                    {
+                        self.private = true;
+                    }
+
+                    let max_age = match (field_hint, type_hint) {
+                        (Some(hint), _) if hint.max_age.is_some() => hint.max_age,
+                        (Some(hint), _) if hint.inherit_max_age => None,
+                        (_, Some(hint)) if hint.max_age.is_some() => hint.max_age,
+                        (_, Some(hint)) if hint.inherit_max_age => None,
+                        _ if is_root || is_composite => Some(self.default_max_age),
+                        _ => None,
+                    };
+
+                    if let Some(max_age) = max_age {
+                        self.max_age = Some(self.max_age.map_or(max_age, |acc| acc.min(max_age)));
+                    }
+
+                    if is_composite {
+                        self.visit(&field.selections, output_type_name, false);
+                    }
+                }
+                SelectionItem::InlineFragment(fragment) => {
+                    self.visit(&fragment.selections, &fragment.type_condition, is_root);
+                }
+                // normalized operations have their fragment spreads inlined
+                SelectionItem::FragmentSpread(_) => {}
+            }
+        }
+    }
+}
+
+/// The types selected by an operation: its root type, the composite types returned by its fields
+/// and the type conditions of its inline fragments.
+///
+/// The cached responses are tagged with these types, so they can be purged by type.
+pub fn selected_types(
+    operation: &OperationDefinition,
+    root_type_name: &str,
+    schema_metadata: &SchemaMetadata,
+) -> BTreeSet<String> {
+    fn visit(
+        selection_set: &SelectionSet,
+        parent_type_name: &str,
+        schema_metadata: &SchemaMetadata,
+        types: &mut BTreeSet<String>,
+    ) {
+        for item in &selection_set.items {
+            match item {
+                SelectionItem::Field(field) => {
+                    if field.selections.is_empty() {
+                        continue;
+                    }
+                    let Some(output_type_name) = schema_metadata
+                        .get_type_fields(parent_type_name)
+                        .and_then(|fields| fields.get(&field.name))
+                        .map(|field_info| field_info.output_type_name.as_str())
+                    else {
+                        continue;
+                    };
+                    if !types.contains(output_type_name) {
+                        types.insert(output_type_name.to_string());
+                    }
+                    visit(&field.selections, output_type_name, schema_metadata, types);
+                }
+                SelectionItem::InlineFragment(fragment) => {
+                    if !types.contains(&fragment.type_condition) {
+                        types.insert(fragment.type_condition.clone());
+                    }
+                    visit(
+                        &fragment.selections,
+                        &fragment.type_condition,
+                        schema_metadata,
+                        types,
+                    );
+                }
+                SelectionItem::FragmentSpread(_) => {}
+            }
+        }
+    }
+
+    let mut types = BTreeSet::from([root_type_name.to_string()]);
+    visit(
+        &operation.selection_set,
+        root_type_name,
+        schema_metadata,
+        &mut types,
+    );
+    types
+}
+
+impl CacheHint {
+    fn from_directives(directives: &[Directive<'static, String>]) -> Option<Self> {
+        let directive = directives
+            .iter()
+            .find(|directive| directive.name == CACHE_CONTROL_DIRECTIVE)?;
+
+        let mut hint = CacheHint::default();
+        for (name, value) in &directive.arguments {
+            match (name.as_str(), value) {
+                ("maxAge", Value::Int(max_age)) => {
+                    hint.max_age = max_age
+                        .as_i64()
+                        .map(|max_age| max_age.clamp(0, u32::MAX as i64) as u32);
+                }
+                ("scope", Value::Enum(scope)) => hint.private = scope == "PRIVATE",
+                ("inheritMaxAge", Value::Boolean(inherit)) => hint.inherit_max_age = *inherit,
+                _ => {}
+            }
+        }
+
+        Some(hint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hive_router_plan_executor::introspection::schema::SchemaWithMetadata;
+    use hive_router_query_planner::ast::normalization::normalize_operation;
+    use hive_router_query_planner::consumer_schema::ConsumerSchema;
+    use hive_router_query_planner::state::supergraph_state::SupergraphState;
+    use hive_router_query_planner::utils::parsing::{parse_operation, parse_schema};
+
+    use super::*;
+
+    const SCHEMA: &str = r#"
+        schema
+            @link(url: "https://specs.apollo.dev/link/v1.0")
+            @link(url: "https://specs.apollo.dev/join/v0.3", for: EXECUTION)
+        {
+            query: Query
+        }
+
+        directive @link(url: String, as: String, for: link__Purpose, import: [link__Import]) repeatable on SCHEMA
+        scalar link__Import
+        enum link__Purpose { SECURITY EXECUTION }
+
+        enum CacheControlScope { PUBLIC PRIVATE }
+        directive @cacheControl(maxAge: Int, scope: CacheControlScope, inheritMaxAge: Boolean) on FIELD_DEFINITION | OBJECT | INTERFACE | UNION
+
+        type Query {
+            me: User @cacheControl(maxAge: 10, scope: PRIVATE)
+            products: [Product]
+            topProduct: Product @cacheControl(maxAge: 20)
+            version: String
+            inherited: Product @cacheControl(inheritMaxAge: true)
+        }
+
+        type User {
+            id: ID!
+            name: String
+        }
+
+        type Product @cacheControl(maxAge: 60) {
+            id: ID!
+            price: Int @cacheControl(maxAge: 30)
+            reviews: [Review]
+        }
+
+        type Review {
+            id: ID!
+        }
+    "#;
+
+    fn types(query: &str) -> Vec<String> {
+        let document = parse_schema(SCHEMA);
+        let supergraph_state = SupergraphState::new(&document);
+        let metadata = ConsumerSchema::new_from_supergraph(&document).schema_metadata();
+        let normalized =
+            normalize_operation(&supergraph_state, &parse_operation(query), None).unwrap();
+        selected_types(&normalized.operation, "Query", &metadata)
+            .into_iter()
+            .collect()
+    }
+
+    fn policy(query: &str, default_max_age: u32) -> Option<CachePolicy> {
+        let document = parse_schema(SCHEMA);
+        let hints = CacheControlHints::from_supergraph(&document);
+        let supergraph_state = SupergraphState::new(&document);
+        let metadata = ConsumerSchema::new_from_supergraph(&document).schema_metadata();
+        let normalized =
+            normalize_operation(&supergraph_state, &parse_operation(query), None).unwrap();
+        hints.policy_for_operation(&normalized.operation, "Query", &metadata, default_max_age)
+    }
+
+    #[test]
+    fn type_hints_apply_to_fields_returning_the_type() {
+        assert_eq!(
+            policy("{ products { id } }", 0),
+            Some(CachePolicy {
+                max_age: 60,
+                scope: CacheScope::Public
+            })
+        );
+    }
+
+    #[test]
+    fn lowest_max_age_wins() {
+        assert_eq!(
+            policy("{ products { id price } topProduct { id } }", 0).map(|p| p.max_age),
+            Some(20)
+        );
+        assert_eq!(
+            policy("{ topProduct { price } }", 0).map(|p| p.max_age),
+            Some(20)
+        );
+    }
+
+    #[test]
+    fn private_scope_is_propagated() {
+        assert_eq!(
+            policy("{ products { id } me { id } }", 0),
+            Some(CachePolicy {
+                max_age: 10,
+                scope: CacheScope::Private
+            })
+        );
+    }
+
+    #[test]
+    fn unhinted_fields_use_the_default_max_age() {
+        assert_eq!(policy("{ version }", 0), None);
+        assert_eq!(policy("{ version }", 5).map(|p| p.max_age), Some(5));
+        assert_eq!(policy("{ products { reviews { id } } }", 0), None);
+        assert_eq!(
+            policy("{ products { reviews { id } } }", 100).map(|p| p.max_age),
+            Some(60)
+        );
+    }
+
+    #[test]
+    fn inherit_max_age_skips_the_field() {
+        assert_eq!(
+            policy("{ inherited { id } products { id } }", 0).map(|p| p.max_age),
+            Some(60)
+        );
+    }
+
+    #[test]
+    fn selected_types_include_nested_composite_types() {
+        assert_eq!(types("{ version }"), vec!["Query"]);
+        assert_eq!(
+            types("{ products { id reviews { id } } }"),
+            vec!["Product", "Query", "Review"]
+        );
+    }
+}
@@ -0,0 +1,517 @@
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use hive_router_config::response_cache::{ResponseCacheBackendConfig, ResponseCacheConfig};
+use hive_router_plan_executor::execution::plan::{PlanExecutionOutput, PlanSubscriptionOutput};
+use hive_router_plan_executor::headers::cache_control::cacheable_max_age;
+use hive_router_plan_executor::headers::plan::HeaderAggregationStrategy;
+use hive_router_plan_executor::headers::response::{ResponseHeaderAggregator, ResponseHeaderSink};
+use hive_router_plan_executor::hooks::on_graphql_params::GraphQLParams;
+use hive_router_query_planner::ast::operation::OperationDefinition;
+use http::header::CACHE_CONTROL;
+use http::{HeaderName, HeaderValue, StatusCode};
+use ntex::http::HeaderMap;
+use ntex::util::Bytes;
+use sonic_rs::{JsonValueMutTrait, JsonValueTrait};
+use tracing::{debug, info, trace, warn};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::pipeline::normalize::GraphQLNormalizationPayload;
+use crate::pipeline::{hash_graphql_extensions, hash_graphql_variables};
+use crate::schema_state::SelectedSupergraph;
+use crate::shared_state::RouterSharedState;
+use crate::storage::{error::StorageError, redis_connections::RedisConnections};
+
+use self::hints::{selected_types, CachePolicy, CacheScope};
+use self::store::{
+    CachedResponse, MemoryResponseCacheStore, RedisResponseCacheStore, ResponseCacheStore,
+};
+use self::surrogate_keys::EntityKey;
+
+pub mod hints;
+pub mod store;
+pub mod surrogate_keys;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ResponseCacheError {
+    #[error("invalid response cache config: {0}")]
+    Configuration(String),
+    #[error("invalid response cache redis config: {0}")]
+    Redis(#[from] StorageError),
+}
+
+/// Caches the responses of queries, for the lowest max-age of their fields,
+/// as set by the `@cacheControl` hints of the supergraph.
+///
+/// The response cache is looked up once the operation is authorized and planned,
+/// a cached response skips the execution of the query plan entirely,
+/// including the execution and subgraph plugin hooks and the response header propagation.
+pub struct ResponseCacheRuntime {
+    store: Box<dyn ResponseCacheStore>,
+    default_max_age: u32,
+    private_id_header: HeaderName,
+    invalidate_on_mutation: bool,
+}
+
+/// A query eligible for caching: its cache key, caching policy and the types it selects.
+pub struct ResponseCacheRequest {
+    key: String,
+    policy: CachePolicy,
+    types: Vec<String>,
+    operation: Arc<OperationDefinition>,
+    root_type_name: String,
+}
+
+impl ResponseCacheRuntime {
+    pub fn from_config(
+        config: &ResponseCacheConfig,
+        redis_connections: &RedisConnections,
+    ) -> Result<Option<Self>, ResponseCacheError> {
+        if !config.enabled {
+            debug!("response cache is disabled");
+            return Ok(None);
+        }
+
+        if config.invalidate_on_mutation && !config.surrogate_keys {
+            return Err(ResponseCacheError::Configuration(
+                "'invalidate_on_mutation' requires 'surrogate_keys' to be enabled".to_string(),
+            ));
+        }
+
+        let store: Box<dyn ResponseCacheStore> = match &config.backend {
+            ResponseCacheBackendConfig::Memory { max_entries } => {
+                Box::new(MemoryResponseCacheStore::new(*max_entries))
+            }
+            ResponseCacheBackendConfig::Redis { url, key_prefix } => {
+                Box::new(RedisResponseCacheStore::new(
+                    redis_connections.connect("response_cache", url)?,
+                    key_prefix.clone(),
+                ))
+            }
+        };
+
+        info!(
+            default_max_age = ?config.default_max_age,
+            private_id_header = %config.private_id_header.get_header_ref(),
+            "response cache enabled"
+        );
+
+        Ok(Some(Self {
+            store,
+            default_max_age: config.default_max_age.as_secs().min(u32::MAX as u64) as u32,
+            private_id_header: config.private_id_header.get_header_ref().clone(),
+            invalidate_on_mutation: config.invalidate_on_mutation,
+        }))
+    }
+
+    /// Computes the caching policy and cache key of a query.
+    ///
+    /// Returns `None` when the response of the query must not be cached:
+    /// a field has a max-age of 0, or it has a `PRIVATE` scope and the request does not identify the client.
+    pub fn prepare(
+        &self,
+        supergraph: &SelectedSupergraph,
+        normalize_payload: &GraphQLNormalizationPayload,
+        graphql_params: &GraphQLParams,
+        headers: &HeaderMap,
+    ) -> Option<ResponseCacheRequest> {
+        let hints = supergraph.runtime.cache_control_hints.as_ref()?;
+        let policy = hints.policy_for_operation(
+            &normalize_payload.operation_for_plan,
+            &normalize_payload.root_type_name,
+            &supergraph.snapshot.metadata,
+            self.default_max_age,
+        )?;
+
+        let mut hasher = Xxh3::new();
+        hints.schema_hash.hash(&mut hasher);
+        normalize_payload
+            .normalized_operation_hash
+            .hash(&mut hasher);
+        hash_graphql_variables(&graphql_params.variables).hash(&mut hasher);
+        graphql_params
+            .extensions
+            .as_ref()
+            .map_or(0, hash_graphql_extensions)
+            .hash(&mut hasher);
+        if policy.scope == CacheScope::Private {
+            let Some(client_id) = headers.get(&self.private_id_header) else {
+                trace!("private response is not cached, client is not identified");
+                return None;
+            };
+            client_id.as_bytes().hash(&mut hasher);
+        }
+
+        Some(ResponseCacheRequest {
+            key: format!("{:032x}", hasher.digest128()),
+            policy,
+            types: selected_types(
+                &normalize_payload.operation_for_plan,
+                &normalize_payload.root_type_name,
+                &supergraph.snapshot.metadata,
+            )
+            .into_iter()
+            .collect(),
+            operation: normalize_payload.operation_for_plan.clone(),
+            root_type_name: normalize_payload.root_type_name.clone(),
+        })
+    }
+
+    /// Returns the cached response of the query, and sets its `Cache-Control` header.
+    pub async fn lookup(
+        &self,
+        request: &ResponseCacheRequest,
+        response_header_sink: &ResponseHeaderSink,
+    ) -> Option<PlanExecutionOutput> {
+        let cached = self.store.get(&request.key).await?;
+        let max_age = cached.remaining_ttl();
+        if max_age == 0 {
+            return None;
+        }
+        trace!(key = request.key, max_age, "response cache hit");
+
+        let mut aggregator = ResponseHeaderAggregator::default();
+        set_cache_control(&mut aggregator, max_age, request.policy.scope);
+        response_header_sink.store(aggregator);
+
+        Some(PlanExecutionOutput {
+            body: cached.body.to_vec(),
+            error_count: 0,
+            status_code: StatusCode::OK,
+        })
+    }
+
+    /// Caches a successful response of the query, and sets its `Cache-Control` header.
+    ///
+    /// The max-age of the response is further lowered by the `Cache-Control` headers of the subgraphs,
+    /// when propagated to the client, and a subgraph forbidding caching prevents the response from being cached.
+    ///
+    /// The response is tagged with the types selected by the query, and with the surrogate keys
+    /// of its entities when `surrogate_keys` is enabled.
+    pub async fn store(
+        &self,
+        supergraph: &SelectedSupergraph,
+        request: ResponseCacheRequest,
+        output: &PlanExecutionOutput,
+        response_header_sink: &ResponseHeaderSink,
+    ) {
+        if output.status_code != StatusCode::OK || output.error_count > 0 {
+            return;
+        }
+
+        let mut aggregator = response_header_sink.take();
+        let subgraphs_max_age = match aggregator.entries.get(&CACHE_CONTROL) {
+            Some((_, values)) => {
+                match values
+                    .first()
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(cacheable_max_age)
+                {
+                    Some(max_age) => max_age,
+                    None => {
+                        trace!(key = request.key, "subgraphs forbid caching the response");
+                        response_header_sink.store(aggregator);
+                        return;
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let max_age = subgraphs_max_age.map_or(request.policy.max_age, |subgraphs_max_age| {
+            request.policy.max_age.min(subgraphs_max_age)
+        });
+        if max_age > 0 {
+            set_cache_control(&mut aggregator, max_age as u64, request.policy.scope);
+        }
+        response_header_sink.store(aggregator);
+
+        if max_age == 0 {
+            return;
+        }
+
+        let mut tags = request.types;
+        if let Some(entity_keys) = &supergraph.runtime.entity_keys {
+            match sonic_rs::from_slice::<sonic_rs::Value>(&output.body) {
+                Ok(body) => tags.extend(entity_keys.surrogate_keys(
+                    &request.operation,
+                    &request.root_type_name,
+                    &supergraph.snapshot.metadata,
+                    &body["data"],
+                )),
+                Err(err) => warn!(error = %err, "failed to parse the response to cache"),
+            }
+        }
+
+        trace!(
+            key = request.key,
+            max_age,
+            tags = tags.len(),
+            "caching response"
+        );
+        self.store
+            .set(
+                request.key,
+                CachedResponse::new(
+                    Bytes::copy_from_slice(&output.body),
+                    Duration::from_secs(max_age as u64),
+                ),
+                tags,
+            )
+            .await;
+    }
+
+    /// Purges the cached responses tagged with any of the given types or surrogate keys,
+    /// or all the cached responses when none is given.
+    pub async fn purge(&self, tags: &[String]) {
+        if tags.is_empty() {
+            info!("purging the response cache");
+            self.store.purge_all().await;
+        } else {
+            info!(tags = ?tags, "purging the response cache by tag");
+            self.store.purge_tags(tags).await;
+        }
+    }
+}
+
+/// Purges the cached responses and entities of the entities returned by a successful mutation,
+/// for the caches with `invalidate_on_mutation` enabled.
+pub async fn invalidate_mutated_entities(
+    supergraph: &SelectedSupergraph,
+    shared_state: &RouterSharedState,
+    operation: &OperationDefinition,
+    root_type_name: &str,
+    output: &PlanExecutionOutput,
+) {
+    if !invalidates_on_mutation(supergraph, shared_state)
+        || output.status_code != StatusCode::OK
+        || output.error_count > 0
+    {
+        return;
+    }
+
+    let body = match sonic_rs::from_slice::<sonic_rs::Value>(&output.body) {
+        Ok(body) => body,
+        Err(err) => {
+            warn!(error = %err, "failed to parse the response of the mutation");
+            return;
+        }
+    };
+    invalidate_entities(
+        supergraph,
+        shared_state,
+        operation,
+        root_type_name,
+        &body["data"],
+    )
+    .await;
+}
+
+/// Purges the cached responses and entities of the entities returned by a successful mutation
+/// delivered incrementally, once its last payload is sent.
+///
+/// The payloads are merged back into the data of the whole response,
+/// nothing is purged when any of them has errors.
+pub fn invalidate_mutated_entities_after_stream(
+    supergraph: &SelectedSupergraph,
+    shared_state: &Arc<RouterSharedState>,
+    operation: Arc<OperationDefinition>,
+    root_type_name: String,
+    output: PlanSubscriptionOutput,
+) -> PlanSubscriptionOutput {
+    if !invalidates_on_mutation(supergraph, shared_state) {
+        return output;
+    }
+
+    let supergraph = supergraph.clone();
+    let shared_state = shared_state.clone();
+    let mut payloads = output.body;
+    let body = Box::pin(async_stream::stream! {
+        let mut response = IncrementalResponse::default();
+        while let Some(payload) = payloads.next().await {
+            response.merge(&payload);
+            yield payload;
+        }
+
+        if !response.failed {
+            invalidate_entities(
+                &supergraph,
+                &shared_state,
+                &operation,
+                &root_type_name,
+                &response.data,
+            )
+            .await;
+        }
+    });
+
+    PlanSubscriptionOutput {
+        body,
+        error_count: output.error_count,
+    }
+}
+
+fn invalidates_on_mutation(
+    supergraph: &SelectedSupergraph,
+    shared_state: &RouterSharedState,
+) -> bool {
+    supergraph.runtime.entity_keys.is_some()
+        && (shared_state
+            .response_cache
+            .as_ref()
+            .is_some_and(|response_cache| response_cache.invalidate_on_mutation)
+            || (supergraph.runtime.entity_cache.is_some()
+                && shared_state
+                    .router_config
+                    .entity_cache
+                    .invalidate_on_mutation))
+}
+
+async fn invalidate_entities(
+    supergraph: &SelectedSupergraph,
+    shared_state: &RouterSharedState,
+    operation: &OperationDefinition,
+    root_type_name: &str,
+    data: &sonic_rs::Value,
+) {
+    let Some(entity_keys) = &supergraph.runtime.entity_keys else {
+        return;
+    };
+    let response_cache = shared_state
+        .response_cache
+        .as_ref()
+        .filter(|response_cache| response_cache.invalidate_on_mutation);
+    let entity_cache = supergraph.runtime.entity_cache.as_ref().filter(|_| {
+        shared_state
+            .router_config
+            .entity_cache
+            .invalidate_on_mutation
+    });
+
+    let entities = entity_keys.entities(
+        operation,
+        root_type_name,
+        &supergraph.snapshot.metadata,
+        data,
+    );
+    if entities.is_empty() {
+        return;
+    }
+    debug!(
+        entities = entities.len(),
+        "invalidating the entities returned by the mutation"
+    );
+
+    if let Some(entity_cache) = entity_cache {
+        for entity in &entities {
+            entity_cache.purge(&entity.type_name, Some(entity.fields.clone()));
+        }
+    }
+    if let Some(response_cache) = response_cache {
+        let surrogate_keys = entities
+            .iter()
+            .map(EntityKey::surrogate_key)
+            .collect::<Vec<_>>();
+        response_cache.store.purge_tags(&surrogate_keys).await;
+    }
+}
+
+/// The data of a response delivered incrementally, merged back from its payloads.
+#[derive(Default)]
+struct IncrementalResponse {
+    data: sonic_rs::Value,
+    /// Whether a payload had errors, or couldn't be parsed.
+    failed: bool,
+}
+
+impl IncrementalResponse {
+    /// Merges the `data` of the initial payload, the fields of the deferred fragments
+    /// and the items of the streamed lists, at their `path`.
+    fn merge(&mut self, payload: &[u8]) {
+        if self.failed {
+            return;
+        }
+        let mut payload = match sonic_rs::from_slice::<sonic_rs::Value>(payload) {
+            Ok(payload) if payload.get("errors").is_none() => payload,
+            _ => {
+                self.failed = true;
+                return;
+            }
+        };
+
+        if let Some(data) = payload.get_mut("data") {
+            self.data = data.take();
+        }
+        let Some(entries) = payload
+            .get_mut("incremental")
+            .and_then(|entries| entries.as_array_mut())
+        else {
+            return;
+        };
+        for entry in entries.iter_mut() {
+            if entry.get("errors").is_some() {
+                self.failed = true;
+                return;
+            }
+            let Some(mut path) = entry.get_mut("path").map(sonic_rs::Value::take) else {
+                continue;
+            };
+            let Some(path) = path.as_array_mut() else {
+                continue;
+            };
+
+            if let Some(items) = entry
+                .get_mut("items")
+                .and_then(|items| items.as_array_mut())
+            {
+                // the last segment is the index of the first item in the list
+                path.pop();
+                let Some(list) =
+                    value_at_path(&mut self.data, path).and_then(|list| list.as_array_mut())
+                else {
+                    continue;
+                };
+                for item in items.iter_mut() {
+                    list.push(item.take());
+                }
+            } else if let Some(fields) = entry.get_mut("data").and_then(|data| data.as_object_mut())
+            {
+                let Some(object) =
+                    value_at_path(&mut self.data, path).and_then(|object| object.as_object_mut())
+                else {
+                    continue;
+                };
+                for (key, value) in fields.iter_mut() {
+                    object.insert(key, value.take());
+                }
+            }
+        }
+    }
+}
+
+fn value_at_path<'a>(
+    data: &'a mut sonic_rs::Value,
+    path: &[sonic_rs::Value],
+) -> Option<&'a mut sonic_rs::Value> {
+    path.iter()
+        .try_fold(data, |value, segment| match segment.as_str() {
+            Some(key) => value.get_mut(key),
+            None => value.get_mut(segment.as_u64()? as usize),
+        })
+}
+
+fn set_cache_control(aggregator: &mut ResponseHeaderAggregator, max_age: u64, scope: CacheScope) {
+    let scope = match scope {
+        CacheScope::Public => "public",
+        CacheScope::Private => "private",
+    };
+    // safety: the value only contains ASCII
+    let value = HeaderValue::from_str(&format!("max-age={max_age}, {scope}"))
+        .expect("cache-control value is ASCII");
+    aggregator.entries.insert(
+        CACHE_CONTROL,
+        (HeaderAggregationStrategy::Last, vec![value]),
+    );
+}
@@ -0,0 +1,368 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use moka::{future::Cache, Expiry};
+use ntex::util::Bytes;
+use redis::{aio::ConnectionManager, AsyncCommands};
+use tracing::warn;
+
+use crate::storage::redis_connections::RedisConnection;
+
+/// A response stored in the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedResponse {
+    pub body: Bytes,
+    /// When the response expires, in seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+impl CachedResponse {
+    pub fn new(body: Bytes, ttl: Duration) -> Self {
+        Self {
+            body,
+            expires_at: unix_now().saturating_add(ttl.as_secs()),
+        }
+    }
+
+    /// The number of seconds left before the response expires.
+    pub fn remaining_ttl(&self) -> u64 {
+        self.expires_at.saturating_sub(unix_now())
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let expires_at = self.expires_at.to_string();
+        let mut encoded = Vec::with_capacity(expires_at.len() + 1 + self.body.len());
+        encoded.extend_from_slice(expires_at.as_bytes());
+        encoded.push(b'\n');
+        encoded.extend_from_slice(&self.body);
+        encoded
+    }
+
+    fn decode(encoded: &[u8]) -> Option<Self> {
+        let separator = encoded.iter().position(|byte| *byte == b'\n')?;
+        let expires_at = std::str::from_utf8(&encoded[..separator])
+            .ok()?
+            .parse()
+            .ok()?;
+        Some(Self {
+            body: Bytes::copy_from_slice(&encoded[separator + 1..]),
+            expires_at,
+        })
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default()
+}
+
+#[async_trait]
+pub trait ResponseCacheStore: Send + Sync {
+    /// Returns the cached response of the given key, if it has not expired.
+    async fn get(&self, key: &str) -> Option<CachedResponse>;
+    /// Stores the response under the given key, until it expires.
+    /// The tags are the types selected by the operation, the response is purged with any of them.
+    async fn set(&self, key: String, response: CachedResponse, tags: Vec<String>);
+    /// Removes all the cached responses.
+    async fn purge_all(&self);
+    /// Removes the cached responses tagged with any of the given tags.
+    async fn purge_tags(&self, tags: &[String]);
+}
+
+pub struct MemoryResponseCacheStore {
+    cache: Cache<String, TaggedResponse>,
+}
+
+#[derive(Clone)]
+struct TaggedResponse {
+    response: CachedResponse,
+    tags: Box<[String]>,
+}
+
+struct CachedResponseExpiry;
+
+impl Expiry<String, TaggedResponse> for CachedResponseExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        value: &TaggedResponse,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(Duration::from_secs(value.response.remaining_ttl()))
+    }
+}
+
+impl MemoryResponseCacheStore {
+    pub fn new(max_entries: u64) -> Self {
+        Self {
+            cache: Cache::builder()
+                .max_capacity(max_entries)
+                .expire_after(CachedResponseExpiry)
+                .support_invalidation_closures()
+                .build(),
+        }
+    }
+}
+
+#[async_trait]
+impl ResponseCacheStore for MemoryResponseCacheStore {
+    async fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.cache.get(key).await.map(|tagged| tagged.response)
+    }
+
+    async fn set(&self, key: String, response: CachedResponse, tags: Vec<String>) {
+        self.cache
+            .insert(
+                key,
+                TaggedResponse {
+                    response,
+                    tags: tags.into_boxed_slice(),
+                },
+            )
+            .await;
+    }
+
+    async fn purge_all(&self) {
+        self.cache.invalidate_all();
+    }
+
+    async fn purge_tags(&self, tags: &[String]) {
+        let tags = tags.to_vec();
+        if let Err(err) = self.cache.invalidate_entries_if(move |_, tagged| {
+            tagged.tags.iter().any(|tag| tags.contains(tag))
+        }) {
+            warn!(error = %err, "failed to purge the response cache");
+        }
+    }
+}
+
+/// Stores the responses in Redis, with the expiration of every key set to the TTL of the response.
+///
+/// Every tag is a Redis set of the keys of the responses tagged with it,
+/// expiring with the last of these responses.
+///
+/// Redis errors are logged and treated as cache misses.
+pub struct RedisResponseCacheStore {
+    connection: RedisConnection,
+    key_prefix: String,
+}
+
+/// The number of keys requested by every `SCAN` call when purging all the responses.
+const PURGE_SCAN_COUNT: usize = 1000;
+
+impl RedisResponseCacheStore {
+    pub fn new(connection: RedisConnection, key_prefix: String) -> Self {
+        Self {
+            connection,
+            key_prefix,
+        }
+    }
+
+    async fn connection(&self) -> Option<ConnectionManager> {
+        match self.connection.get().await {
+            Ok(connection) => Some(connection),
+            Err(err) => {
+                warn!(error = %err, "failed to connect to the response cache redis");
+                None
+            }
+        }
+    }
+
+    fn tag_key(&self, tag: &str) -> String {
+        format!("{}tag:{}", self.key_prefix, tag)
+    }
+
+    async fn delete_keys_of_tag(
+        &self,
+        connection: &mut ConnectionManager,
+        tag: &str,
+    ) -> Result<(), redis::RedisError> {
+        let tag_key = self.tag_key(tag);
+        let keys: Vec<String> = connection.smembers(&tag_key).await?;
+        let mut keys = keys
+            .into_iter()
+            .map(|key| format!("{}{}", self.key_prefix, key))
+            .collect::<Vec<_>>();
+        keys.push(tag_key);
+        connection.del::<_, ()>(keys).await
+    }
+
+    async fn delete_all_keys(
+        &self,
+        connection: &mut ConnectionManager,
+    ) -> Result<(), redis::RedisError> {
+        let pattern = format!("{}*", escape_glob(&self.key_prefix));
+        let mut cursor: u64 = 0;
+        loop {
+            let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(PURGE_SCAN_COUNT)
+                .query_async(connection)
+                .await?;
+            if !keys.is_empty() {
+                connection.del::<_, ()>(keys).await?;
+            }
+            if next_cursor == 0 {
+                return Ok(());
+            }
+            cursor = next_cursor;
+        }
+    }
+}
+
+/// Escapes the special characters of the glob-style patterns of Redis.
+fn escape_glob(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        if matches!(char, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
+#[async_trait]
+impl ResponseCacheStore for RedisResponseCacheStore {
+    async fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut connection = self.connection().await?;
+        let key = format!("{}{}", self.key_prefix, key);
+        match connection.get::<_, Option<Vec<u8>>>(&key).await {
+            Ok(value) => value.and_then(|value| CachedResponse::decode(&value)),
+            Err(err) => {
+                warn!(error = %err, "failed to read from the response cache redis");
+                None
+            }
+        }
+    }
+
+    async fn set(&self, key: String, response: CachedResponse, tags: Vec<String>) {
+        let ttl = response.remaining_ttl();
+        if ttl == 0 {
+            return;
+        }
+        let Some(mut connection) = self.connection().await else {
+            return;
+        };
+
+        let mut pipeline = redis::pipe();
+        pipeline
+            .set_ex(
+                format!("{}{}", self.key_prefix, key),
+                response.encode(),
+                ttl,
+            )
+            .ignore();
+        for tag in &tags {
+            let tag_key = self.tag_key(tag);
+            pipeline.sadd(&tag_key, &key).ignore();
+            // the set expires with the last response tagged with it
+            pipeline
+                .cmd("EXPIRE")
+                .arg(&tag_key)
+                .arg(ttl)
+                .arg("NX")
+                .ignore();
+            pipeline
+                .cmd("EXPIRE")
+                .arg(&tag_key)
+                .arg(ttl)
+                .arg("GT")
+                .ignore();
+        }
+        if let Err(err) = pipeline.query_async::<()>(&mut connection).await {
+            warn!(error = %err, "failed to write to the response cache redis");
+        }
+    }
+
+    async fn purge_all(&self) {
+        let Some(mut connection) = self.connection().await else {
+            return;
+        };
+        if let Err(err) = self.delete_all_keys(&mut connection).await {
+            warn!(error = %err, "failed to purge the response cache redis");
+        }
+    }
+
+    async fn purge_tags(&self, tags: &[String]) {
+        let Some(mut connection) = self.connection().await else {
+            return;
+        };
+        for tag in tags {
+            if let Err(err) = self.delete_keys_of_tag(&mut connection, tag).await {
+                warn!(error = %err, tag, "failed to purge the response cache redis");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cached_response_roundtrip() {
+        let response = CachedResponse::new(
+            Bytes::from_static(b"{\"data\":{\n}}"),
+            Duration::from_secs(30),
+        );
+        assert_eq!(CachedResponse::decode(&response.encode()), Some(response));
+    }
+
+    #[tokio::test]
+    async fn memory_store_returns_stored_responses() {
+        let store = MemoryResponseCacheStore::new(10);
+        let response = CachedResponse::new(Bytes::from_static(b"{}"), Duration::from_secs(30));
+        store.set("key".to_string(), response.clone(), vec![]).await;
+        assert_eq!(store.get("key").await, Some(response));
+        assert_eq!(store.get("other").await, None);
+    }
+
+    #[tokio::test]
+    async fn memory_store_evicts_expired_responses() {
+        let store = MemoryResponseCacheStore::new(10);
+        let expired = CachedResponse {
+            body: Bytes::from_static(b"{}"),
+            expires_at: unix_now().saturating_sub(1),
+        };
+        store.set("key".to_string(), expired, vec![]).await;
+        assert_eq!(store.get("key").await, None);
+    }
+
+    #[tokio::test]
+    async fn memory_store_purges_tagged_responses() {
+        let store = MemoryResponseCacheStore::new(10);
+        let response = CachedResponse::new(Bytes::from_static(b"{}"), Duration::from_secs(30));
+        store
+            .set(
+                "products".to_string(),
+                response.clone(),
+                vec!["Query".to_string(), "Product".to_string()],
+            )
+            .await;
+        store
+            .set(
+                "users".to_string(),
+                response.clone(),
+                vec!["Query".to_string(), "User".to_string()],
+            )
+            .await;
+
+        store.purge_tags(&["Product".to_string()]).await;
+        assert_eq!(store.get("products").await, None);
+        assert_eq!(store.get("users").await, Some(response));
+
+        store.purge_all().await;
+        assert_eq!(store.get("users").await, None);
+    }
+
+    #[test]
+    fn escapes_glob_patterns() {
+        assert_eq!(escape_glob("router:*[a]?"), "router:\\*\\[a\\]\\?");
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use graphql_tools::parser::parse_query;
+use graphql_tools::parser::query::{Definition, OperationDefinition as ParsedOperation, Selection};
+use hive_router_plan_executor::introspection::schema::SchemaMetadata;
+use hive_router_query_planner::ast::operation::OperationDefinition;
+use hive_router_query_planner::ast::selection_item::SelectionItem;
+use hive_router_query_planner::ast::selection_set::{FieldSelection, SelectionSet};
+use hive_router_query_planner::state::supergraph_state::SupergraphState;
+use sonic_rs::{JsonContainerTrait, JsonType, JsonValueTrait, Object, Value};
+use tracing::warn;
+
+/// A field of a `@key`, and the fields selected below it for composite fields.
+#[derive(Debug, PartialEq, Eq)]
+struct KeyField {
+    name: String,
+    selections: Vec<KeyField>,
+}
+
+/// The `@key`s of the entity types of a supergraph, identifying the entities of the responses.
+///
+/// The surrogate key of an entity is its typename and the values of the fields of one of its keys,
+/// as in `Product:upc=1` or `Review:id=1,product.upc=1` for nested key fields.
+#[derive(Debug, Default)]
+pub struct EntityKeys {
+    keys: HashMap<String, Vec<Vec<KeyField>>>,
+}
+
+/// An entity of a response: its type and the values of the fields of one of its keys.
+#[derive(Debug, PartialEq)]
+pub struct EntityKey {
+    pub type_name: String,
+    /// The key fields and their values, nested like in the representations of the entity.
+    pub fields: Value,
+}
+
+impl EntityKey {
+    pub fn surrogate_key(&self) -> String {
+        let mut pairs = Vec::new();
+        push_key_pairs(&self.fields, "", &mut pairs);
+        format!("{}:{}", self.type_name, pairs.join(","))
+    }
+}
+
+impl EntityKeys {
+    pub fn from_supergraph(supergraph: &SupergraphState) -> Self {
+        let mut keys: HashMap<String, Vec<Vec<KeyField>>> = HashMap::new();
+        for (type_name, definition) in &supergraph.definitions {
+            // the same key is usually declared by several subgraphs
+            let type_keys = definition
+                .join_types()
+                .iter()
+                .filter_map(|join_type| join_type.key.as_deref())
+                .collect::<BTreeSet<_>>();
+            for key in type_keys {
+                match parse_key(key) {
+                    Some(fields) => keys.entry(type_name.clone()).or_default().push(fields),
+                    None => warn!(type_name, key, "failed to parse the key of the entity type"),
+                }
+            }
+        }
+        Self { keys }
+    }
+
+    /// Returns the keys of the entities in the data of a response,
+    /// walking the data along the selections of the operation.
+    pub fn entities(
+        &self,
+        operation: &OperationDefinition,
+        root_type_name: &str,
+        schema_metadata: &SchemaMetadata,
+        data: &Value,
+    ) -> Vec<EntityKey> {
+        let mut entities = Vec::new();
+        self.visit(
+            &operation.selection_set,
+            root_type_name,
+            schema_metadata,
+            data,
+            &mut entities,
+        );
+        // the same entity is often returned several times
+        let mut seen = HashSet::with_capacity(entities.len());
+        entities.retain(|entity| seen.insert(entity.surrogate_key()));
+        entities
+    }
+
+    /// Returns the surrogate keys of the entities in the data of a response.
+    pub fn surrogate_keys(
+        &self,
+        operation: &OperationDefinition,
+        root_type_name: &str,
+        schema_metadata: &SchemaMetadata,
+        data: &Value,
+    ) -> BTreeSet<String> {
+        self.entities(operation, root_type_name, schema_metadata, data)
+            .iter()
+            .map(EntityKey::surrogate_key)
+            .collect()
+    }
+
+    fn visit(
+        &self,
+        selection_set: &SelectionSet,
+        type_name: &str,
+        schema_metadata: &SchemaMetadata,
+        value: &Value,
+        entities: &mut Vec<EntityKey>,
+    ) {
+        if let Some(items) = value.as_array() {
+            for item in items.iter() {
+                self.visit(selection_set, type_name, schema_metadata, item, entities);
+            }
+            return;
+        }
+        if !value.is_object() {
+            return;
+        }
+
+        // the concrete type of abstract types is only known when `__typename` is selected
+        let type_name = value
+            .get("__typename")
+            .and_then(|typename| typename.as_str())
+            .unwrap_or(type_name);
+
+        if let Some(type_keys) = self.keys.get(type_name) {
+            for key in type_keys {
+                if let Some(fields) =
+                    key_values(selection_set, type_name, schema_metadata, value, key)
+                {
+                    entities.push(EntityKey {
+                        type_name: type_name.to_string(),
+                        fields,
+                    });
+                }
+            }
+        }
+
+        self.visit_fields(selection_set, type_name, schema_metadata, value, entities);
+    }
+
+    fn visit_fields(
+        &self,
+        selection_set: &SelectionSet,
+        type_name: &str,
+        schema_metadata: &SchemaMetadata,
+        value: &Value,
+        entities: &mut Vec<EntityKey>,
+    ) {
+        for item in &selection_set.items {
+            match item {
+                SelectionItem::Field(field) => {
+                    if field.selections.is_empty() {
+                        continue;
+                    }
+                    let Some(output_type_name) = schema_metadata
+                        .get_type_fields(type_name)
+                        .and_then(|fields| fields.get(&field.name))
+                        .map(|field_info| field_info.output_type_name.as_str())
+                    else {
+                        continue;
+                    };
+                    if let Some(field_value) = value.get(field.selection_identifier()) {
+                        self.visit(
+                            &field.selections,
+                            output_type_name,
+                            schema_metadata,
+                            field_value,
+                            entities,
+                        );
+                    }
+                }
+                SelectionItem::InlineFragment(fragment) => {
+                    if schema_metadata
+                        .possible_types
+                        .entity_satisfies_type_condition(type_name, &fragment.type_condition)
+                    {
+                        self.visit_fields(
+                            &fragment.selections,
+                            type_name,
+                            schema_metadata,
+                            value,
+                            entities,
+                        );
+                    }
+                }
+                // normalized operations have their fragment spreads inlined
+                SelectionItem::FragmentSpread(_) => {}
+            }
+        }
+    }
+}
+
+/// Collects the values of the fields of a key, from the fields selected by the operation.
+///
+/// Returns `None` when a field of the key is not selected or is `null`.
+fn key_values(
+    selection_set: &SelectionSet,
+    type_name: &str,
+    schema_metadata: &SchemaMetadata,
+    value: &Value,
+    key: &[KeyField],
+) -> Option<Value> {
+    let mut object = Object::new();
+    for key_field in key {
+        let (field, field_value) = find_selected_field(
+            selection_set,
+            type_name,
+            schema_metadata,
+            value,
+            &key_field.name,
+        )?;
+
+        if key_field.selections.is_empty() {
+            if !matches!(
+                field_value.get_type(),
+                JsonType::String | JsonType::Number | JsonType::Boolean
+            ) {
+                return None;
+            }
+            object.insert(&key_field.name, field_value.clone());
+            continue;
+        }
+
+        let output_type_name = schema_metadata
+            .get_type_fields(type_name)
+            .and_then(|fields| fields.get(&field.name))
+            .map(|field_info| field_info.output_type_name.as_str())?;
+        if !field_value.is_object() {
+            return None;
+        }
+        let nested = key_values(
+            &field.selections,
+            output_type_name,
+            schema_metadata,
+            field_value,
+            &key_field.selections,
+        )?;
+        object.insert(&key_field.name, nested);
+    }
+    Some(Value::from(object))
+}
+
+/// Flattens the values of the fields of a key into `path=value` pairs.
+fn push_key_pairs(fields: &Value, path_prefix: &str, pairs: &mut Vec<String>) {
+    let Some(object) = fields.as_object() else {
+        return;
+    };
+    for (name, value) in object.iter() {
+        let path = format!("{path_prefix}{name}");
+        match value.as_str() {
+            Some(value) => pairs.push(format!("{path}={value}")),
+            None if value.is_object() => push_key_pairs(value, &format!("{path}."), pairs),
+            None => pairs.push(format!("{path}={value}")),
+        }
+    }
+}
+
+/// Finds the selection of a field, and its value in the response.
+fn find_selected_field<'a>(
+    selection_set: &'a SelectionSet,
+    type_name: &str,
+    schema_metadata: &SchemaMetadata,
+    value: &'a Value,
+    field_name: &str,
+) -> Option<(&'a FieldSelection, &'a Value)> {
+    selection_set.items.iter().find_map(|item| match item {
+        SelectionItem::Field(field) if field.name == field_name => value
+            .get(field.selection_identifier())
+            .map(|field_value| (field, field_value)),
+        SelectionItem::InlineFragment(fragment)
+            if schema_metadata
+                .possible_types
+                .entity_satisfies_type_condition(type_name, &fragment.type_condition) =>
+        {
+            find_selected_field(
+                &fragment.selections,
+                type_name,
+                schema_metadata,
+                value,
+                field_name,
+            )
+        }
+        _ => None,
+    })
+}
+
+/// Parses the fields of a `@key`, as in `id` or `id product { upc }`.
+fn parse_key(key: &str) -> Option<Vec<KeyField>> {
+    let selection_set = format!("{{{key}}}");
+    let document = parse_query::<String>(&selection_set).ok()?;
+    match document.definitions.first()? {
+        Definition::Operation(ParsedOperation::SelectionSet(selection_set)) => {
+            Some(key_fields(&selection_set.items))
+        }
+        _ => None,
+    }
+}
+
+fn key_fields(items: &[Selection<'_, String>]) -> Vec<KeyField> {
+    items
+        .iter()
+        .filter_map(|selection| match selection {
+            Selection::Field(field) => Some(KeyField {
+                name: field.name.clone(),
+                selections: key_fields(&field.selection_set.items),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use hive_router_plan_executor::introspection::schema::SchemaWithMetadata;
+    use hive_router_query_planner::ast::normalization::normalize_operation;
+    use hive_router_query_planner::consumer_schema::ConsumerSchema;
+    use hive_router_query_planner::utils::parsing::{parse_operation, parse_schema};
+
+    use super::*;
+
+    const SCHEMA: &str = r#"
+        schema
+            @link(url: "https://specs.apollo.dev/link/v1.0")
+            @link(url: "https://specs.apollo.dev/join/v0.3", for: EXECUTION)
+        {
+            query: Query
+        }
+
+        directive @join__type(graph: join__Graph!, key: join__FieldSet, extension: Boolean! = false, resolvable: Boolean! = true, isInterfaceObject: Boolean! = false) repeatable on OBJECT | INTERFACE | UNION | ENUM | INPUT_OBJECT | SCALAR
+        directive @join__graph(name: String!, url: String!) on ENUM_VALUE
+        directive @link(url: String, as: String, for: link__Purpose, import: [link__Import]) repeatable on SCHEMA
+        scalar join__FieldSet
+        scalar link__Import
+        enum link__Purpose { SECURITY EXECUTION }
+
+        enum join__Graph {
+            PRODUCTS @join__graph(name: "products", url: "http://products")
+            REVIEWS @join__graph(name: "reviews", url: "http://reviews")
+        }
+
+        type Query @join__type(graph: PRODUCTS) @join__type(graph: REVIEWS) {
+            products: [Product]
+        }
+
+        type Product @join__type(graph: PRODUCTS, key: "upc") @join__type(graph: REVIEWS, key: "upc") {
+            upc: String!
+            name: String
+            reviews: [Review]
+        }
+
+        type Review @join__type(graph: REVIEWS, key: "id product { upc }") {
+            id: ID!
+            product: Product
+        }
+    "#;
+
+    fn surrogate_keys(query: &str, data: &str) -> Vec<String> {
+        let document = parse_schema(SCHEMA);
+        let supergraph_state = SupergraphState::new(&document);
+        let metadata = ConsumerSchema::new_from_supergraph(&document).schema_metadata();
+        let normalized =
+            normalize_operation(&supergraph_state, &parse_operation(query), None).unwrap();
+        EntityKeys::from_supergraph(&supergraph_state)
+            .surrogate_keys(
+                &normalized.operation,
+                "Query",
+                &metadata,
+                &sonic_rs::from_str(data).unwrap(),
+            )
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn derives_surrogate_keys_of_selected_entities() {
+        assert_eq!(
+            surrogate_keys(
+                "{ products { upc name } }",
+                r#"{ "products": [{ "upc": "1", "name": "Table" }, { "upc": "2", "name": "Chair" }, null] }"#
+            ),
+            vec!["Product:upc=1", "Product:upc=2"]
+        );
+    }
+
+    #[test]
+    fn derives_surrogate_keys_of_nested_and_aliased_keys() {
+        assert_eq!(
+            surrogate_keys(
+                "{ products { sku: upc reviews { id product { upc } } } }",
+                r#"{ "products": [{ "sku": "1", "reviews": [{ "id": "10", "product": { "upc": "1" } }] }] }"#
+            ),
+            vec!["Product:upc=1", "Review:id=10,product.upc=1"]
+        );
+    }
+
+    #[test]
+    fn skips_entities_without_their_key_fields() {
+        assert!(surrogate_keys(
+            "{ products { name reviews { id } } }",
+            r#"{ "products": [{ "name": "Table", "reviews": [{ "id": "10" }] }] }"#
+        )
+        .is_empty());
+    }
+}
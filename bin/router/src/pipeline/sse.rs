@@ -1,5 +1,3 @@
-// TODO: test thoroughly
-
 use futures_util::{Stream, StreamExt};
 use ntex::util::Bytes;
 use std::time::Duration;
@@ -7,6 +5,12 @@ use tokio_util::bytes::BufMut;
 
 pub const SSE_HEADER: &str = "text/event-stream";
 
+/// Create a subscription stream following the "distinct connections mode" of the GraphQL over SSE spec.
+///
+/// Every execution result is sent as a `next` event, and a `complete` event is sent once the input ends.
+/// A heartbeat comment is sent after every `heartbeat_interval` of inactivity, a zero interval disables heartbeats.
+///
+/// Read more: https://github.com/graphql/graphql-over-http/blob/main/rfcs/GraphQLOverSSE.md#distinct-connections-mode
 pub fn create_stream(
     input: impl Stream<Item = Vec<u8>> + Send + Unpin + 'static,
     heartbeat_interval: Duration,
@@ -38,7 +42,7 @@ pub fn create_stream(
                         },
                     }
                 }
-                _ = tokio::time::sleep(heartbeat_interval) => {
+                _ = tokio::time::sleep(heartbeat_interval), if !heartbeat_interval.is_zero() => {
                     yield Ok(Bytes::from(":\n\n"));
                 }
             }
@@ -46,3 +50,79 @@ pub fn create_stream(
     }
     .boxed()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::{stream, StreamExt};
+
+    use super::create_stream;
+
+    async fn collect(
+        stream: impl futures_util::Stream<Item = Result<ntex::util::Bytes, std::io::Error>>,
+    ) -> String {
+        stream
+            .map(|chunk| String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+            .collect::<Vec<_>>()
+            .await
+            .concat()
+    }
+
+    #[tokio::test]
+    async fn sends_next_events_and_completes() {
+        let input = stream::iter(vec![
+            br#"{"data":{"a":1}}"#.to_vec(),
+            br#"{"data":{"a":2}}"#.to_vec(),
+        ]);
+
+        let output = collect(create_stream(input, Duration::from_secs(10))).await;
+
+        assert_eq!(
+            output,
+            "event: next\ndata: {\"data\":{\"a\":1}}\n\nevent: next\ndata: {\"data\":{\"a\":2}}\n\nevent: complete\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_heartbeats_while_idle() {
+        let input = stream::once(async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            br#"{"data":{"a":1}}"#.to_vec()
+        })
+        .boxed();
+
+        let output = collect(create_stream(input, Duration::from_millis(10))).await;
+
+        assert!(output.starts_with(":\n\n"), "unexpected output: {output}");
+        assert!(
+            output.ends_with("event: next\ndata: {\"data\":{\"a\":1}}\n\nevent: complete\n\n"),
+            "unexpected output: {output}"
+        );
+    }
+
+    #[tokio::test]
+    async fn does_not_send_heartbeats_when_disabled() {
+        let input = stream::once(async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            br#"{"data":{"a":1}}"#.to_vec()
+        })
+        .boxed();
+
+        let output = collect(create_stream(input, Duration::ZERO)).await;
+
+        assert_eq!(
+            output,
+            "event: next\ndata: {\"data\":{\"a\":1}}\n\nevent: complete\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_on_invalid_utf8() {
+        let input = stream::iter(vec![vec![0xff, 0xfe]]);
+        let mut output = create_stream(input, Duration::from_secs(10));
+
+        assert!(output.next().await.unwrap().is_err());
+        assert!(output.next().await.is_none());
+    }
+}
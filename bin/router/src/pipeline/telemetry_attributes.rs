@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+use hive_router_config::telemetry::instrumentation::{
+    CustomAttributeMetricsConfig, InstrumentationConfig,
+};
+use hive_router_internal::expressions::{CompileExpression, ToVrlValue};
+use hive_router_internal::telemetry::metrics::catalog::{labels_for, names};
+use hive_router_internal::telemetry::otel::opentelemetry::{Key, KeyValue, Value as OtelValue};
+use hive_router_plan_executor::execution::client_request_details::ClientRequestDetailsView;
+use hive_router_plan_executor::execution::plan::CoerceVariablesPayload;
+use tracing::warn;
+use vrl::{
+    compiler::Program as VrlProgram,
+    compiler::TargetValue as VrlTargetValue,
+    core::Value as VrlValue,
+    prelude::{state::RuntimeState as VrlState, Context as VrlContext, TimeZone as VrlTimeZone},
+    value::Secrets as VrlSecrets,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TelemetryAttributesError {
+    #[error("invalid telemetry.instrumentation config: {0}")]
+    Configuration(String),
+}
+
+/// Computes the attributes of `telemetry.instrumentation` from every GraphQL operation.
+pub struct TelemetryAttributesRuntime {
+    attributes: Vec<CustomAttribute>,
+}
+
+struct CustomAttribute {
+    key: Key,
+    program: VrlProgram,
+    spans: bool,
+    // `None` when the attribute is not added to the metrics
+    metrics: Option<CustomAttributeMetricsConfig>,
+}
+
+/// The attributes computed from an operation, for its span and for its metrics.
+#[derive(Default)]
+pub struct TelemetryAttributes {
+    pub span: Vec<KeyValue>,
+    /// With the values not allowed replaced by `other_value`.
+    pub metrics: Vec<KeyValue>,
+}
+
+impl TelemetryAttributesRuntime {
+    pub fn from_config(
+        config: &InstrumentationConfig,
+    ) -> Result<Option<Self>, TelemetryAttributesError> {
+        if config.attributes.is_empty() {
+            return Ok(None);
+        }
+
+        let catalog_labels = labels_for(names::GRAPHQL_EXECUTION_DURATION).unwrap_or_default();
+        let attributes = config
+            .attributes
+            .iter()
+            .map(|(name, attribute)| {
+                if attribute.metrics.enabled && catalog_labels.contains(&name.as_str()) {
+                    return Err(TelemetryAttributesError::Configuration(format!(
+                        "the attribute '{name}' is already recorded by {}",
+                        names::GRAPHQL_EXECUTION_DURATION
+                    )));
+                }
+
+                let program = attribute
+                    .expression
+                    .compile_expression(None)
+                    .map_err(|err| {
+                        TelemetryAttributesError::Configuration(format!(
+                            "failed to compile the expression of the attribute '{name}': {err}"
+                        ))
+                    })?;
+
+                Ok(CustomAttribute {
+                    key: Key::new(name.clone()),
+                    program,
+                    spans: attribute.spans,
+                    metrics: attribute.metrics.enabled.then(|| attribute.metrics.clone()),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(Self { attributes }))
+    }
+
+    /// Evaluates the expressions with the request, in `.request`, and the variables, in `.variables`.
+    /// An attribute is skipped when its expression returns `null` or fails.
+    pub fn evaluate(
+        &self,
+        client_request: &impl ClientRequestDetailsView,
+        variables: &CoerceVariablesPayload,
+    ) -> TelemetryAttributes {
+        let variables_value = VrlValue::Object(
+            variables
+                .variables_map
+                .iter()
+                .flatten()
+                .map(|(name, value)| (name.as_str().into(), value.to_vrl_value()))
+                .collect(),
+        );
+        let mut target = VrlTargetValue {
+            value: VrlValue::Object(BTreeMap::from([
+                ("request".into(), client_request.to_vrl_value()),
+                ("variables".into(), variables_value),
+            ])),
+            metadata: VrlValue::Object(BTreeMap::new()),
+            secrets: VrlSecrets::default(),
+        };
+        let mut state = VrlState::default();
+        let timezone = VrlTimeZone::default();
+        let mut ctx = VrlContext::new(&mut target, &mut state, &timezone);
+
+        let mut attributes = TelemetryAttributes::default();
+        for attribute in &self.attributes {
+            let value = match attribute.program.resolve(&mut ctx) {
+                Ok(value) => value,
+                Err(err) => {
+                    warn!(
+                        attribute = attribute.key.as_str(),
+                        "failed to evaluate the telemetry attribute: {err}"
+                    );
+                    continue;
+                }
+            };
+            let Some(value) = vrl_to_otel_value(value) else {
+                continue;
+            };
+
+            if let Some(metrics) = &attribute.metrics {
+                let value = match metrics.is_allowed(&value.as_str()) {
+                    true => value.clone(),
+                    false => OtelValue::from(metrics.other_value.clone()),
+                };
+                attributes
+                    .metrics
+                    .push(KeyValue::new(attribute.key.clone(), value));
+            }
+            if attribute.spans {
+                attributes
+                    .span
+                    .push(KeyValue::new(attribute.key.clone(), value));
+            }
+        }
+
+        attributes
+    }
+}
+
+fn vrl_to_otel_value(value: VrlValue) -> Option<OtelValue> {
+    match value {
+        VrlValue::Null => None,
+        VrlValue::Bytes(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned().into()),
+        VrlValue::Integer(integer) => Some(integer.into()),
+        VrlValue::Float(float) => Some(float.into_inner().into()),
+        VrlValue::Boolean(boolean) => Some(boolean.into()),
+        other => serde_json::to_string(&other).ok().map(Into::into),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use hive_router_config::telemetry::instrumentation::InstrumentationConfig;
+    use hive_router_internal::telemetry::otel::opentelemetry::{KeyValue, Value};
+    use hive_router_plan_executor::execution::client_request_details::{
+        ClientRequestDetails, JwtRequestDetails, OperationDetails,
+    };
+    use hive_router_plan_executor::execution::plan::CoerceVariablesPayload;
+    use ntex::http::HeaderMap;
+    use sonic_rs::json;
+
+    use super::TelemetryAttributesRuntime;
+
+    #[test]
+    fn computes_span_and_metric_attributes() {
+        let config: InstrumentationConfig = serde_json::from_value(serde_json::json!({
+            "attributes": {
+                "cart.size": {
+                    "expression": "length(array(.variables.items) ?? [])",
+                },
+                "tenant.id": {
+                    "expression": "downcase(string!(.variables.tenant))",
+                    "spans": false,
+                    "metrics": { "enabled": true, "allow": ["acme"] },
+                },
+                "missing": {
+                    "expression": ".variables.missing",
+                },
+            }
+        }))
+        .unwrap();
+        let runtime = TelemetryAttributesRuntime::from_config(&config)
+            .unwrap()
+            .unwrap();
+
+        let url = http::Uri::from_static("http://localhost/graphql");
+        let client_request = ClientRequestDetails {
+            method: &http::Method::POST,
+            url: &url,
+            headers: HeaderMap::new().into(),
+            operation: OperationDetails {
+                name: None,
+                query: "{ __typename }",
+                kind: "query",
+            },
+            jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
+            path_params: Default::default(),
+            plugin_context: None,
+        };
+        let evaluate = |variables: Vec<(&str, sonic_rs::Value)>| {
+            let variables = CoerceVariablesPayload::new(
+                Some(HashMap::from_iter(
+                    variables
+                        .into_iter()
+                        .map(|(name, value)| (name.to_string(), value)),
+                )),
+                Default::default(),
+            );
+            runtime.evaluate(&client_request, &variables)
+        };
+
+        let attributes = evaluate(vec![("tenant", json!("ACME")), ("items", json!([1, 2, 3]))]);
+        assert_eq!(
+            attributes.span,
+            vec![KeyValue::new("cart.size", Value::I64(3))]
+        );
+        assert_eq!(attributes.metrics, vec![KeyValue::new("tenant.id", "acme")]);
+
+        let attributes = evaluate(vec![("tenant", json!("Globex"))]);
+        assert_eq!(
+            attributes.span,
+            vec![KeyValue::new("cart.size", Value::I64(0))]
+        );
+        assert_eq!(
+            attributes.metrics,
+            vec![KeyValue::new("tenant.id", "other")]
+        );
+    }
+}
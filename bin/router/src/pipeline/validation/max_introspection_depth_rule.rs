@@ -0,0 +1,252 @@
+use std::{cmp, collections::HashMap};
+
+use graphql_tools::{
+    ast::{OperationVisitor, OperationVisitorContext},
+    static_graphql::query::{Definition, Document, FragmentDefinition},
+    validation::{
+        rules::{ValidationRule, ValidationVisitor},
+        utils::{ValidationError, ValidationErrorContext},
+    },
+};
+use hive_router_config::limits::MaxIntrospectionDepthRuleConfig;
+
+use crate::pipeline::validation::shared::{CountableNode, VisitedFragment};
+
+/// Introspection fields listing the members of a type,
+/// each of them resolving other types that can be introspected further.
+const NESTED_INTROSPECTION_FIELDS: [&str; 4] =
+    ["fields", "interfaces", "possibleTypes", "inputFields"];
+
+pub struct MaxIntrospectionDepthRule {
+    pub config: MaxIntrospectionDepthRuleConfig,
+}
+
+impl ValidationRule for MaxIntrospectionDepthRule {
+    fn error_code(&self) -> &'static str {
+        "MAX_INTROSPECTION_DEPTH_EXCEEDED"
+    }
+
+    fn visitor<'doc>(&self) -> ValidationVisitor<'doc> {
+        Box::new(MaxIntrospectionDepthVisitor {
+            config: self.config.clone(),
+            visited_fragments: HashMap::new(),
+        })
+    }
+}
+
+struct MaxIntrospectionDepthVisitor<'doc> {
+    config: MaxIntrospectionDepthRuleConfig,
+    /// The same fragment is counted differently within and outside of introspection fields.
+    visited_fragments: HashMap<(&'doc str, bool), VisitedFragment>,
+}
+
+impl<'doc> MaxIntrospectionDepthVisitor<'doc> {
+    fn check_limit(&self, count: usize) -> Result<usize, ValidationError> {
+        if count > self.config.n {
+            Err(ValidationError {
+                locations: vec![],
+                message: "Introspection depth limit exceeded.".to_string(),
+                error_code: "MAX_INTROSPECTION_DEPTH_EXCEEDED",
+            })
+        } else {
+            Ok(count)
+        }
+    }
+
+    /// Counts the nesting of the type members selected below `__schema` and `__type`,
+    /// looking through inline fragments and fragment spreads.
+    fn count_depth(
+        &mut self,
+        known_fragments: &HashMap<&'doc str, &'doc FragmentDefinition>,
+        node: CountableNode<'doc>,
+        in_introspection: bool,
+    ) -> Result<usize, ValidationError> {
+        let mut in_introspection = in_introspection;
+        let mut own_depth = 0;
+
+        if let CountableNode::Field(field) = node {
+            let field_name = field.name.as_str();
+            if field_name == "__schema" || field_name == "__type" {
+                in_introspection = true;
+            } else if in_introspection && NESTED_INTROSPECTION_FIELDS.contains(&field_name) {
+                own_depth = 1;
+            }
+        }
+
+        let mut depth = 0;
+        if let Some(selection_set) = node.selection_set() {
+            for child in &selection_set.items {
+                depth = cmp::max(
+                    depth,
+                    self.count_depth(known_fragments, child.into(), in_introspection)?,
+                );
+            }
+        }
+
+        if let CountableNode::FragmentSpread(node) = node {
+            let visited_key = (node.fragment_name.as_str(), in_introspection);
+            match self.visited_fragments.get(&visited_key) {
+                Some(VisitedFragment::Counted(fragment_depth)) => return Ok(*fragment_depth),
+                // Recursive fragments are rejected by the other validation rules
+                Some(VisitedFragment::Visiting) => return Ok(0),
+                None => {}
+            }
+
+            self.visited_fragments
+                .insert(visited_key, VisitedFragment::Visiting);
+
+            if let Some(fragment) = known_fragments.get(visited_key.0) {
+                let fragment_depth =
+                    self.count_depth(known_fragments, fragment.into(), in_introspection)?;
+                self.visited_fragments
+                    .insert(visited_key, VisitedFragment::Counted(fragment_depth));
+                depth = fragment_depth;
+            }
+        }
+
+        self.check_limit(own_depth + depth)
+    }
+}
+
+impl<'doc> OperationVisitor<'doc, ValidationErrorContext> for MaxIntrospectionDepthVisitor<'doc> {
+    fn enter_document(
+        &mut self,
+        context: &mut OperationVisitorContext<'doc>,
+        user_context: &mut ValidationErrorContext,
+        document: &'doc Document,
+    ) {
+        self.visited_fragments = HashMap::with_capacity(context.known_fragments.len());
+
+        for definition in &document.definitions {
+            let Definition::Operation(op) = definition else {
+                continue;
+            };
+            if let Err(err) = self.count_depth(&context.known_fragments, op.into(), false) {
+                user_context.report_error(err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graphql_tools::parser::{parse_query, parse_schema};
+    use graphql_tools::validation::validate::ValidationPlan;
+    use hive_router_config::limits::MaxIntrospectionDepthRuleConfig;
+
+    use crate::pipeline::validation::max_introspection_depth_rule::MaxIntrospectionDepthRule;
+
+    const TYPE_DEFS: &'static str = r#"
+        type Book {
+            title: String
+            fields: [String]
+        }
+
+        type Query {
+            books: [Book]
+        }
+    "#;
+
+    const INTROSPECTION_QUERY: &'static str =
+        include_str!("test_fixtures/introspection_query.fixture.graphql");
+
+    fn validate(query: &str, n: usize) -> Vec<graphql_tools::validation::utils::ValidationError> {
+        let validation_plan = ValidationPlan::from(vec![Box::new(MaxIntrospectionDepthRule {
+            config: MaxIntrospectionDepthRuleConfig { n },
+        })]);
+
+        let schema: graphql_tools::static_graphql::schema::Document =
+            parse_schema(TYPE_DEFS).expect("Failed to parse schema");
+
+        let doc: graphql_tools::static_graphql::query::Document =
+            parse_query(query).expect("Failed to parse query");
+
+        graphql_tools::validation::validate::validate(&schema, &doc, &validation_plan)
+    }
+
+    #[test]
+    fn allows_the_introspection_query() {
+        let errors = validate(INTROSPECTION_QUERY, 1);
+
+        assert!(
+            errors.is_empty(),
+            "Expected no validation errors but found some"
+        );
+    }
+
+    #[test]
+    fn ignores_fields_outside_of_introspection() {
+        let errors = validate("query { books { title fields } }", 0);
+
+        assert!(
+            errors.is_empty(),
+            "Expected no validation errors but found some"
+        );
+    }
+
+    #[test]
+    fn rejects_nested_introspection_exceeding_max_depth() {
+        let errors = validate(
+            r#"
+            query {
+                __type(name: "Query") {
+                    fields {
+                        type {
+                            ofType {
+                                fields {
+                                    name
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            "#,
+            1,
+        );
+
+        assert!(
+            !errors.is_empty(),
+            "Expected validation errors but found none"
+        );
+
+        let error = &errors[0];
+        assert_eq!(error.message, "Introspection depth limit exceeded.");
+    }
+
+    #[test]
+    fn rejects_nested_introspection_through_fragments() {
+        let errors = validate(
+            r#"
+            query {
+                __schema {
+                    types {
+                        ...Members
+                    }
+                }
+            }
+
+            fragment Members on __Type {
+                possibleTypes {
+                    ...Interfaces
+                }
+            }
+
+            fragment Interfaces on __Type {
+                interfaces {
+                    name
+                }
+            }
+            "#,
+            1,
+        );
+
+        assert!(
+            !errors.is_empty(),
+            "Expected validation errors but found none"
+        );
+
+        let error = &errors[0];
+        assert_eq!(error.message, "Introspection depth limit exceeded.");
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use graphql_tools::{
+    ast::{OperationVisitor, OperationVisitorContext},
+    static_graphql::query::{Definition, Document, FragmentDefinition},
+    validation::{
+        rules::{ValidationRule, ValidationVisitor},
+        utils::{ValidationError, ValidationErrorContext},
+    },
+};
+use hive_router_config::limits::MaxRootFieldsRuleConfig;
+
+use crate::pipeline::validation::shared::{CountableNode, VisitedFragment};
+
+pub struct MaxRootFieldsRule {
+    pub config: MaxRootFieldsRuleConfig,
+}
+
+impl ValidationRule for MaxRootFieldsRule {
+    fn error_code(&self) -> &'static str {
+        "MAX_ROOT_FIELDS_EXCEEDED"
+    }
+
+    fn visitor<'doc>(&self) -> ValidationVisitor<'doc> {
+        Box::new(MaxRootFieldsVisitor {
+            config: self.config.clone(),
+            visited_fragments: HashMap::new(),
+        })
+    }
+}
+
+struct MaxRootFieldsVisitor<'doc> {
+    config: MaxRootFieldsRuleConfig,
+    visited_fragments: HashMap<&'doc str, VisitedFragment>,
+}
+
+impl<'doc> MaxRootFieldsVisitor<'doc> {
+    fn check_limit(&self, count: usize) -> Result<usize, ValidationError> {
+        if count > self.config.n {
+            Err(ValidationError {
+                locations: vec![],
+                message: "Root fields limit exceeded.".to_string(),
+                error_code: "MAX_ROOT_FIELDS_EXCEEDED",
+            })
+        } else {
+            Ok(count)
+        }
+    }
+
+    /// Counts the fields selected on the root type, looking through
+    /// the inline fragments and fragment spreads of the root selection set,
+    /// but not into the selection sets of the root fields themselves.
+    fn count_root_fields(
+        &mut self,
+        known_fragments: &HashMap<&'doc str, &'doc FragmentDefinition>,
+        countable_node: CountableNode<'doc>,
+    ) -> Result<usize, ValidationError> {
+        let mut field_count: usize = 0;
+
+        if let Some(selection_set) = countable_node.selection_set() {
+            for selection in &selection_set.items {
+                field_count = match selection.into() {
+                    CountableNode::Field(_) => self.check_limit(field_count + 1)?,
+                    child => {
+                        let child_fields = self.count_root_fields(known_fragments, child)?;
+                        self.check_limit(field_count + child_fields)?
+                    }
+                };
+            }
+        }
+
+        if let CountableNode::FragmentSpread(node) = countable_node {
+            let fragment_name = node.fragment_name.as_str();
+
+            match self.visited_fragments.get(fragment_name) {
+                Some(VisitedFragment::Counted(num)) => {
+                    return self.check_limit(field_count + num);
+                }
+                Some(VisitedFragment::Visiting) => return Ok(field_count),
+                None => {}
+            }
+
+            self.visited_fragments
+                .insert(fragment_name, VisitedFragment::Visiting);
+
+            if let Some(fragment_def) = known_fragments.get(fragment_name).copied() {
+                let fragment_field_count = self.count_root_fields(
+                    known_fragments,
+                    CountableNode::FragmentDefinition(fragment_def),
+                )?;
+
+                self.visited_fragments.insert(
+                    fragment_name,
+                    VisitedFragment::Counted(fragment_field_count),
+                );
+                field_count = self.check_limit(field_count + fragment_field_count)?;
+            }
+        }
+
+        Ok(field_count)
+    }
+}
+
+impl<'doc> OperationVisitor<'doc, ValidationErrorContext> for MaxRootFieldsVisitor<'doc> {
+    fn enter_document(
+        &mut self,
+        context: &mut OperationVisitorContext<'doc>,
+        user_context: &mut ValidationErrorContext,
+        document: &'doc Document,
+    ) {
+        self.visited_fragments = HashMap::with_capacity(context.known_fragments.len());
+
+        for definition in &document.definitions {
+            let Definition::Operation(op) = definition else {
+                continue;
+            };
+
+            if let Err(err) = self.count_root_fields(&context.known_fragments, op.into()) {
+                user_context.report_error(err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graphql_tools::{
+        parser::parse_schema,
+        validation::validate::{validate, ValidationPlan},
+    };
+    use hive_router_config::limits::MaxRootFieldsRuleConfig;
+
+    use crate::pipeline::validation::max_root_fields_rule::MaxRootFieldsRule;
+
+    const TYPE_DEFS: &str = r#"
+        type Book {
+            title: String
+            author: String
+        }
+
+        type Query {
+            books: [Book]
+            getBook(title: String): Book
+        }
+    "#;
+
+    fn validate_with_limit(query: &str, n: usize) -> Vec<&'static str> {
+        let schema = parse_schema(TYPE_DEFS)
+            .expect("Failed to parse schema")
+            .into_static();
+        let query = graphql_tools::parser::parse_query(query)
+            .expect("Failed to parse query")
+            .into_static();
+        let validation_plan = ValidationPlan::from(vec![Box::new(MaxRootFieldsRule {
+            config: MaxRootFieldsRuleConfig { n },
+        })]);
+
+        validate(&schema, &query, &validation_plan)
+            .into_iter()
+            .map(|error| error.error_code)
+            .collect()
+    }
+
+    #[test]
+    fn does_not_count_nested_fields() {
+        let errors = validate_with_limit(
+            r#"
+            query {
+                books {
+                    title
+                    author
+                }
+                getBook(title: "null") {
+                    title
+                }
+            }
+        "#,
+            2,
+        );
+
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn rejects_query_exceeding_max_root_fields() {
+        let errors = validate_with_limit(
+            r#"
+            query {
+                first: getBook(title: "a") { title }
+                second: getBook(title: "b") { title }
+                third: getBook(title: "c") { title }
+            }
+        "#,
+            2,
+        );
+
+        assert_eq!(errors, vec!["MAX_ROOT_FIELDS_EXCEEDED"]);
+    }
+
+    #[test]
+    fn counts_root_fields_of_fragments() {
+        let errors = validate_with_limit(
+            r#"
+            query {
+                books { title }
+                ... on Query {
+                    getBook(title: "a") { title }
+                }
+                ...RootFields
+            }
+
+            fragment RootFields on Query {
+                __typename
+            }
+        "#,
+            2,
+        );
+
+        assert_eq!(errors, vec!["MAX_ROOT_FIELDS_EXCEEDED"]);
+    }
+
+    #[test]
+    fn do_not_crash_on_recursive_fragment() {
+        let errors = validate_with_limit(
+            r#"
+            query {
+                ...A
+            }
+
+            fragment A on Query {
+                books { title }
+                ...B
+            }
+
+            fragment B on Query {
+                ...A
+            }
+        "#,
+            10,
+        );
+
+        assert!(errors.is_empty());
+    }
+}
@@ -7,20 +7,66 @@ use crate::pipeline::parser::GraphQLParserPayload;
 use crate::schema_state::SelectedSupergraph;
 use crate::shared_state::RouterSharedState;
 use crate::SchemaState;
-use graphql_tools::validation::validate::validate;
+use graphql_tools::validation::rules::default_rules_validation_plan;
+use graphql_tools::validation::validate::{validate, ValidationPlan};
+use hive_router_config::limits::LimitsConfig;
 use hive_router_internal::telemetry::traces::spans::graphql::GraphQLValidateSpan;
 use hive_router_plan_executor::hooks::on_graphql_validation::{
     OnGraphQLValidationEndHookPayload, OnGraphQLValidationStartHookPayload,
 };
+use hive_router_plan_executor::hooks::on_supergraph_load::SupergraphSnapshot;
 use hive_router_plan_executor::plugin_context::PluginRequestState;
 use hive_router_plan_executor::plugin_trait::{CacheHint, EndControlFlow, StartControlFlow};
 use hive_router_plan_executor::plugins::hooks;
+use hive_router_query_planner::utils::parsing::safe_parse_operation;
 use tracing::{error, trace, Instrument};
 use xxhash_rust::xxh3::Xxh3;
 pub mod max_aliases_rule;
 pub mod max_depth_rule;
 pub mod max_directives_rule;
+pub mod max_introspection_depth_rule;
+pub mod max_root_fields_rule;
 mod shared;
+pub mod stream_placement_rule;
+
+use max_aliases_rule::MaxAliasesRule;
+use max_depth_rule::MaxDepthRule;
+use max_directives_rule::MaxDirectivesRule;
+use max_introspection_depth_rule::MaxIntrospectionDepthRule;
+use max_root_fields_rule::MaxRootFieldsRule;
+use stream_placement_rule::StreamPlacementRule;
+
+/// The default validation rules, along with the rules of the configured limits.
+pub fn validation_plan_from_config(limits: &LimitsConfig) -> ValidationPlan {
+    let mut validation_plan = default_rules_validation_plan();
+    validation_plan.add_rule(Box::new(StreamPlacementRule));
+    if let Some(max_depth_config) = &limits.max_depth {
+        validation_plan.add_rule(Box::new(MaxDepthRule {
+            config: max_depth_config.clone(),
+        }));
+    }
+    if let Some(max_directives_config) = &limits.max_directives {
+        validation_plan.add_rule(Box::new(MaxDirectivesRule {
+            config: max_directives_config.clone(),
+        }));
+    }
+    if let Some(max_aliases_config) = &limits.max_aliases {
+        validation_plan.add_rule(Box::new(MaxAliasesRule {
+            config: max_aliases_config.clone(),
+        }));
+    }
+    if let Some(max_root_fields_config) = &limits.max_root_fields {
+        validation_plan.add_rule(Box::new(MaxRootFieldsRule {
+            config: max_root_fields_config.clone(),
+        }));
+    }
+    if let Some(max_introspection_depth_config) = &limits.max_introspection_depth {
+        validation_plan.add_rule(Box::new(MaxIntrospectionDepthRule {
+            config: max_introspection_depth_config.clone(),
+        }));
+    }
+    validation_plan
+}
 
 #[inline]
 pub async fn validate_operation_with_cache(
@@ -37,7 +83,7 @@ pub async fn validate_operation_with_cache(
         let mut on_end_callbacks = vec![];
         let mut validation_schema = supergraph.snapshot.planner.consumer_schema.clone();
         let mut validation_operation = parser_payload.parsed_operation.clone();
-        let mut validation_plan = app_state.validation_plan.clone();
+        let mut validation_plan = app_state.reloadable.load().validation_plan.clone();
 
         if let Some(plugin_req_state) = plugin_req_state {
             let mut start_payload = OnGraphQLValidationStartHookPayload {
@@ -160,3 +206,22 @@ pub async fn validate_operation_with_cache(
     .instrument(validate_span.clone())
     .await
 }
+
+/// Returns the router's currently configured supergraph when the operation is valid against it.
+/// Used to decide if a subscription survives the reload of the supergraph it was planned for.
+pub fn configured_supergraph_accepting(
+    query: &str,
+    schema_state: &SchemaState,
+    app_state: &RouterSharedState,
+) -> Option<SupergraphSnapshot> {
+    let snapshot = schema_state.configured_snapshot()?;
+    let operation = safe_parse_operation(query).ok()?;
+
+    validate(
+        &snapshot.planner.consumer_schema.document,
+        &operation,
+        &app_state.reloadable.load().validation_plan,
+    )
+    .is_empty()
+    .then_some(snapshot)
+}
@@ -0,0 +1,218 @@
+use graphql_tools::{
+    ast::{OperationVisitor, OperationVisitorContext},
+    static_graphql::query::{
+        Definition, Directive, Document, Field, OperationDefinition, Selection, SelectionSet,
+    },
+    validation::{
+        rules::{ValidationRule, ValidationVisitor},
+        utils::{ValidationError, ValidationErrorContext},
+    },
+};
+
+const ERROR_CODE: &str = "STREAM_NOT_SUPPORTED";
+
+/// Rejects the placements of `@stream` the router can't deliver incrementally.
+///
+/// Only the lists of fields selected directly on the root type of a query or mutation
+/// are streamed, in operations without `@defer` fragments.
+pub struct StreamPlacementRule;
+
+impl ValidationRule for StreamPlacementRule {
+    fn error_code(&self) -> &'static str {
+        ERROR_CODE
+    }
+
+    fn visitor<'doc>(&self) -> ValidationVisitor<'doc> {
+        Box::new(StreamPlacementVisitor)
+    }
+}
+
+struct StreamPlacementVisitor;
+
+impl<'doc> OperationVisitor<'doc, ValidationErrorContext> for StreamPlacementVisitor {
+    fn enter_document(
+        &mut self,
+        _context: &mut OperationVisitorContext<'doc>,
+        user_context: &mut ValidationErrorContext,
+        document: &'doc Document,
+    ) {
+        let mut placements = StreamPlacements::default();
+        for definition in &document.definitions {
+            match definition {
+                Definition::Operation(operation) => {
+                    let streams_root_fields =
+                        !matches!(operation, OperationDefinition::Subscription(_));
+                    placements.visit(operation.selection_set(), streams_root_fields);
+                }
+                Definition::Fragment(fragment) => {
+                    placements.visit(&fragment.selection_set, false);
+                }
+            }
+        }
+
+        for directive in placements.unsupported {
+            user_context.report_error(ValidationError {
+                locations: vec![directive.position],
+                message: "`@stream` is only supported on the fields of the root type of a query or mutation.".to_string(),
+                error_code: ERROR_CODE,
+            });
+        }
+
+        if placements.has_defer {
+            for directive in placements.supported {
+                user_context.report_error(ValidationError {
+                    locations: vec![directive.position],
+                    message: "`@stream` is not supported in operations with `@defer`.".to_string(),
+                    error_code: ERROR_CODE,
+                });
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct StreamPlacements<'doc> {
+    supported: Vec<&'doc Directive>,
+    unsupported: Vec<&'doc Directive>,
+    has_defer: bool,
+}
+
+impl<'doc> StreamPlacements<'doc> {
+    /// Collects the `@stream` directives of the selection set, `root` being whether
+    /// its fields are the streamed root fields of an operation.
+    fn visit(&mut self, selection_set: &'doc SelectionSet, root: bool) {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    if let Some(directive) = stream_directive(field) {
+                        if root {
+                            self.supported.push(directive);
+                        } else {
+                            self.unsupported.push(directive);
+                        }
+                    }
+                    self.visit(&field.selection_set, false);
+                }
+                Selection::InlineFragment(inline_fragment) => {
+                    self.has_defer |= has_defer(&inline_fragment.directives);
+                    self.visit(&inline_fragment.selection_set, false);
+                }
+                Selection::FragmentSpread(fragment_spread) => {
+                    self.has_defer |= has_defer(&fragment_spread.directives);
+                }
+            }
+        }
+    }
+}
+
+fn stream_directive(field: &Field) -> Option<&Directive> {
+    field
+        .directives
+        .iter()
+        .find(|directive| directive.name == "stream")
+}
+
+fn has_defer(directives: &[Directive]) -> bool {
+    directives.iter().any(|directive| directive.name == "defer")
+}
+
+#[cfg(test)]
+mod tests {
+    use graphql_tools::{
+        parser::parse_schema,
+        validation::validate::{validate, ValidationPlan},
+    };
+
+    use crate::pipeline::validation::stream_placement_rule::StreamPlacementRule;
+
+    const TYPE_DEFS: &str = r#"
+        directive @stream(if: Boolean! = true, label: String, initialCount: Int = 0) on FIELD
+        directive @defer(if: Boolean! = true, label: String) on FRAGMENT_SPREAD | INLINE_FRAGMENT
+
+        type Book {
+            title: String
+            reviews: [String]
+        }
+
+        type Query {
+            books: [Book]
+        }
+    "#;
+
+    fn validate_query(query: &str) -> Vec<String> {
+        let schema = parse_schema(TYPE_DEFS)
+            .expect("Failed to parse schema")
+            .into_static();
+        let query = graphql_tools::parser::parse_query(query)
+            .expect("Failed to parse query")
+            .into_static();
+        let validation_plan = ValidationPlan::from(vec![Box::new(StreamPlacementRule)]);
+
+        validate(&schema, &query, &validation_plan)
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    #[test]
+    fn accepts_stream_on_root_fields() {
+        let errors = validate_query(
+            r#"
+            query {
+                books @stream(initialCount: 1) {
+                    title
+                }
+            }
+        "#,
+        );
+
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn rejects_stream_on_nested_fields() {
+        let errors = validate_query(
+            r#"
+            query {
+                books {
+                    reviews @stream
+                    ...Reviews
+                }
+            }
+
+            fragment Reviews on Book {
+                reviews @stream
+            }
+        "#,
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "`@stream` is only supported on the fields of the root type of a query or mutation.";
+                2
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_stream_in_operations_with_defer() {
+        let errors = validate_query(
+            r#"
+            query {
+                books @stream {
+                    title
+                    ... @defer {
+                        reviews
+                    }
+                }
+            }
+        "#,
+        );
+
+        assert_eq!(
+            errors,
+            vec!["`@stream` is not supported in operations with `@defer`."]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use hive_console_sdk::agent::usage_agent::RequestDetails;
+use hive_router_config::websocket::WebSocketProtocolsConfig;
 use hive_router_plan_executor::headers::response::ResponseHeaderSink;
 use http::Method;
 use ntex::channel::oneshot;
@@ -13,7 +14,7 @@ use std::collections::HashMap;
 use std::io;
 use std::rc::Rc;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::{debug, error, trace, warn, Instrument};
 
@@ -22,9 +23,11 @@ use hive_router_internal::telemetry::metrics::subscription_metrics::{
     ActiveClientConnectionGuard, SubscriptionTransport,
 };
 use hive_router_internal::telemetry::traces::spans::graphql::GraphQLOperationSpan;
+use hive_router_plan_executor::execution::client_request_details::ClientIdentityDetails;
 use hive_router_plan_executor::executors::graphql_transport_ws::{
     ClientMessage, CloseCode, ConnectionInitPayload, ServerMessage, WS_SUBPROTOCOL,
 };
+use hive_router_plan_executor::executors::graphql_ws::{self, WS_LEGACY_SUBPROTOCOL};
 use hive_router_plan_executor::executors::websocket_common::{
     handshake_timeout, heartbeat, parse_frame_to_text, FrameNotParsedToText, WsState,
 };
@@ -36,8 +39,10 @@ use hive_router_plan_executor::request_context::{RequestContextExt, SharedReques
 use hive_router_plan_executor::response::graphql_error::{GraphQLError, GraphQLErrorExtensions};
 use hive_router_query_planner::state::supergraph_state::OperationKind;
 
+use crate::jwt::context::JwtRequestContext;
 use crate::jwt::errors::JwtError;
 use crate::pipeline::active_subscriptions::SubscriptionEvent;
+use crate::pipeline::client_identification::require_client_identity;
 use crate::pipeline::error::PipelineError;
 use crate::pipeline::execute_planned_request;
 use crate::pipeline::header::{ResponseMode, SingleContentType, StreamContentType};
@@ -50,6 +55,8 @@ use crate::schema_state::SchemaState;
 use crate::shared_state::{RouterSharedState, SharedRouterResponse};
 
 type WsStateRef = Rc<RefCell<WsState<tokio::sync::mpsc::Sender<()>>>>;
+/// The JWT identity validated from the connection init payload, shared by the operations of the connection.
+type ConnectionIdentityRef = Rc<RefCell<Option<JwtRequestContext>>>;
 
 pub async fn ws_index(
     req: HttpRequest,
@@ -59,9 +66,7 @@ pub async fn ws_index(
     let schema_state = schema_state.get_ref().clone();
     let shared_state = shared_state.get_ref().clone();
 
-    let accepted_subprotocol = ws::subprotocols(&req)
-        .find(|p| *p == WS_SUBPROTOCOL)
-        .map(|_| WS_SUBPROTOCOL);
+    let subprotocol = Subprotocol::negotiate(&req, &shared_state.router_config.websocket.protocols);
 
     let plugin_context = req.extensions().get::<Arc<PluginContext>>().cloned();
     let request_context = match req.read_request_context() {
@@ -71,7 +76,7 @@ pub async fn ws_index(
 
     ws::start(
         req.clone(),
-        accepted_subprotocol,
+        subprotocol.map(Subprotocol::name),
         fn_factory_with_config(move |sink: ws::WsSink| {
             let schema_state = schema_state.clone();
             let shared_state = shared_state.clone();
@@ -80,7 +85,7 @@ pub async fn ws_index(
             let req = req.clone();
             async move {
                 ws_service(
-                    accepted_subprotocol.is_some(),
+                    subprotocol,
                     sink,
                     schema_state,
                     shared_state,
@@ -95,9 +100,90 @@ pub async fn ws_index(
     .await
 }
 
+/// The GraphQL over WebSocket subprotocol negotiated with the client. Messages of the legacy
+/// `graphql-ws` subprotocol are mapped to their `graphql-transport-ws` equivalents, so both are
+/// handled the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Subprotocol {
+    GraphQLTransportWs,
+    GraphQLWs,
+}
+
+impl Subprotocol {
+    /// Picks the first subprotocol offered by the client that is enabled in the configuration.
+    fn negotiate(req: &HttpRequest, config: &WebSocketProtocolsConfig) -> Option<Self> {
+        ws::subprotocols(req).find_map(|protocol| match protocol {
+            WS_SUBPROTOCOL if config.graphql_transport_ws.enabled => {
+                Some(Subprotocol::GraphQLTransportWs)
+            }
+            WS_LEGACY_SUBPROTOCOL if config.graphql_ws.enabled => Some(Subprotocol::GraphQLWs),
+            _ => None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Subprotocol::GraphQLTransportWs => WS_SUBPROTOCOL,
+            Subprotocol::GraphQLWs => WS_LEGACY_SUBPROTOCOL,
+        }
+    }
+
+    /// Returns `None` when the client asks to terminate the connection.
+    fn parse_client_message(self, text: &str) -> Result<Option<ClientMessage>, sonic_rs::Error> {
+        match self {
+            Subprotocol::GraphQLTransportWs => sonic_rs::from_str(text).map(Some),
+            Subprotocol::GraphQLWs => sonic_rs::from_str::<graphql_ws::ClientMessage>(text)
+                .map(graphql_ws::ClientMessage::into_transport_ws),
+        }
+    }
+
+    fn ack(self) -> ws::Message {
+        match self {
+            Subprotocol::GraphQLTransportWs => ServerMessage::ack(),
+            Subprotocol::GraphQLWs => graphql_ws::ServerMessage::ack(),
+        }
+    }
+
+    fn next(self, id: &str, body: &[u8]) -> ws::Message {
+        match self {
+            Subprotocol::GraphQLTransportWs => ServerMessage::next(id, body),
+            Subprotocol::GraphQLWs => graphql_ws::ServerMessage::data(id, body),
+        }
+    }
+
+    fn error(self, id: &str, errors: &[GraphQLError]) -> ws::Message {
+        match self {
+            Subprotocol::GraphQLTransportWs => ServerMessage::error(id, errors),
+            Subprotocol::GraphQLWs => graphql_ws::ServerMessage::error(id, errors),
+        }
+    }
+
+    fn complete(self, id: &str) -> ws::Message {
+        match self {
+            Subprotocol::GraphQLTransportWs => ServerMessage::complete(id),
+            Subprotocol::GraphQLWs => graphql_ws::ServerMessage::complete(id),
+        }
+    }
+}
+
+/// Send `ka` messages of the legacy `graphql-ws` subprotocol, until the connection is closed.
+async fn legacy_keep_alive(sink: ws::WsSink, interval: Duration) {
+    loop {
+        if sink
+            .send(graphql_ws::ServerMessage::keep_alive())
+            .await
+            .is_err()
+        {
+            trace!("Failed to send keep alive message, stopping keep alive task");
+            return;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn ws_service(
-    has_accepted_subprotocol: bool,
+    subprotocol: Option<Subprotocol>,
     sink: ws::WsSink,
     schema_state: Arc<SchemaState>,
     shared_state: Arc<RouterSharedState>,
@@ -106,7 +192,7 @@ async fn ws_service(
     request_context: SharedRequestContext,
 ) -> Result<impl Service<ws::Frame, Response = Option<ws::Message>, Error = io::Error>, web::Error>
 {
-    if !has_accepted_subprotocol {
+    if subprotocol.is_none() {
         debug!("WebSocket connection rejecting due to unacceptable subprotocol");
         let _ = sink.send(CloseCode::SubprotocolNotAcceptable.into()).await;
         // we dont return an Err here because we want to gracefully close the
@@ -116,7 +202,7 @@ async fn ws_service(
         debug!("WebSocket connection accepted");
     }
 
-    let conn_guard: Option<ActiveClientConnectionGuard> = if has_accepted_subprotocol {
+    let conn_guard: Option<ActiveClientConnectionGuard> = if subprotocol.is_some() {
         Some(
             shared_state
                 .telemetry_context
@@ -137,13 +223,44 @@ async fn ws_service(
             .unwrap_or_else(|_| http::Uri::from_static("/graphql")),
     );
     let ws_path: Rc<Path<http::Uri>> = Rc::new(Path::new((*ws_uri).clone()));
+    // the connection is already being closed when no subprotocol was accepted
+    let subprotocol = subprotocol.unwrap_or(Subprotocol::GraphQLTransportWs);
 
     let (heartbeat_tx, heartbeat_rx) = oneshot::channel();
+    let (idle_timeout_tx, idle_timeout_rx) = oneshot::channel();
+    let (identity_expiry_tx, identity_expiry_rx) = oneshot::channel();
     let (acknowledged_tx, acknowledged_rx) = oneshot::channel();
 
     let state: WsStateRef = Rc::new(RefCell::new(WsState::new(acknowledged_tx)));
-
-    rt::spawn(heartbeat(state.clone(), sink.clone(), heartbeat_rx));
+    let identity: ConnectionIdentityRef = Rc::new(RefCell::new(None));
+
+    let config = &shared_state.router_config.websocket;
+    if !config.keep_alive_interval.is_zero() {
+        rt::spawn(heartbeat(
+            state.clone(),
+            sink.clone(),
+            heartbeat_rx,
+            config.keep_alive_interval,
+            config.keep_alive_timeout,
+        ));
+    }
+    if let Some(timeout) = config.idle_timeout {
+        rt::spawn(idle_timeout(
+            state.clone(),
+            sink.clone(),
+            idle_timeout_rx,
+            timeout,
+        ));
+    }
+    if shared_state.jwt_auth_runtime.is_some() && config.headers.accepts_connection_headers() {
+        rt::spawn(identity_expiry(
+            state.clone(),
+            identity.clone(),
+            sink.clone(),
+            shared_state.clone(),
+            identity_expiry_rx,
+        ));
+    }
     rt::spawn(handshake_timeout(
         state.clone(),
         sink.clone(),
@@ -155,6 +272,7 @@ async fn ws_service(
     let service = fn_service(move |frame| {
         let sink = sink.clone();
         let state = state_for_service.clone();
+        let identity = identity.clone();
         let schema_state = schema_state.clone();
         let shared_state = shared_state.clone();
         let plugin_context = plugin_context.clone();
@@ -166,8 +284,10 @@ async fn ws_service(
             match parse_frame_to_text(frame, &state) {
                 Ok(text) => Ok(handle_text_frame(
                     text,
+                    subprotocol,
                     sink,
                     state,
+                    &identity,
                     &schema_state,
                     &shared_state,
                     &req,
@@ -188,8 +308,10 @@ async fn ws_service(
     });
 
     let on_shutdown = fn_shutdown(async move || {
-        // stop heartbeat and handshake timeout tasks on shutdown
+        // stop heartbeat, idle timeout, identity expiry and handshake timeout tasks on shutdown
         let _ = heartbeat_tx.send(());
+        let _ = idle_timeout_tx.send(());
+        let _ = identity_expiry_tx.send(());
         if let Some(tx) = state.borrow_mut().acknowledged_tx.take() {
             let _ = tx.send(());
         }
@@ -204,6 +326,90 @@ async fn ws_service(
     Ok(chain(service).and_then(on_shutdown))
 }
 
+/// How often a connection is checked for active subscriptions when an idle timeout is configured.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Close the connection once it has had no active subscription for the idle timeout.
+async fn idle_timeout(
+    state: WsStateRef,
+    sink: ws::WsSink,
+    mut stop_rx: oneshot::Receiver<()>,
+    timeout: Duration,
+) {
+    let check_interval = timeout.min(IDLE_CHECK_INTERVAL);
+    let mut idle_since = Instant::now();
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(check_interval) => {
+                if !state.borrow().subscriptions.is_empty() {
+                    idle_since = Instant::now();
+                } else if idle_since.elapsed() >= timeout {
+                    debug!("WebSocket connection idle timeout, closing connection");
+                    let _ = sink.send(CloseCode::IdleTimeout.into()).await;
+                    return;
+                }
+            }
+            _ = &mut stop_rx => return,
+        }
+    }
+}
+
+/// How often the identity of a connection is checked for expiry when JWT authentication is enabled.
+const IDENTITY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Re-validate the credentials of the connection once its identity expires. The connection is
+/// closed unless its init payload carries a valid token by then, e.g. one persisted from the
+/// headers of a later operation.
+async fn identity_expiry(
+    state: WsStateRef,
+    identity: ConnectionIdentityRef,
+    sink: ws::WsSink,
+    shared_state: Arc<RouterSharedState>,
+    mut stop_rx: oneshot::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(IDENTITY_CHECK_INTERVAL) => {
+                if !identity.borrow().as_ref().is_some_and(JwtRequestContext::is_expired) {
+                    continue;
+                }
+
+                debug!("WebSocket connection identity expired, re-validating connection credentials");
+                let headers =
+                    parse_headers_from_connection_init_payload(state.borrow().init_payload.as_ref());
+                let err = match authenticate_connection(&shared_state, &headers).await {
+                    Ok(Some(jwt_context)) if !jwt_context.is_expired() => {
+                        *identity.borrow_mut() = Some(jwt_context);
+                        continue;
+                    }
+                    Ok(_) => JwtError::FailedToDecodeToken(
+                        jsonwebtoken::errors::ErrorKind::ExpiredSignature.into(),
+                    ),
+                    Err(err) => err,
+                };
+                let _ = sink.send(err.into_close_message()).await;
+                return;
+            }
+            _ = &mut stop_rx => return,
+        }
+    }
+}
+
+/// Validates the JWT found in the headers of the connection init payload.
+async fn authenticate_connection(
+    shared_state: &RouterSharedState,
+    headers: &HeaderMap,
+) -> Result<Option<JwtRequestContext>, JwtError> {
+    match &shared_state.jwt_auth_runtime {
+        Some(jwt_auth_runtime) => {
+            jwt_auth_runtime
+                .validate_headers(headers, &shared_state.jwt_claims_cache)
+                .await
+        }
+        None => Ok(None),
+    }
+}
+
 /// Ensure a subscription is removed from active subscriptions when dropped (server-side).
 struct SubscriptionGuard {
     state: WsStateRef,
@@ -224,8 +430,10 @@ lazy_static::lazy_static! {
 #[allow(clippy::too_many_arguments)]
 async fn handle_text_frame(
     text: String,
+    subprotocol: Subprotocol,
     sink: ws::WsSink,
     state: WsStateRef,
+    identity: &ConnectionIdentityRef,
     schema_state: &Arc<SchemaState>,
     shared_state: &Arc<RouterSharedState>,
     req: &HttpRequest,
@@ -251,8 +459,12 @@ async fn handle_text_frame(
 
     // TODO: cover response header aggregation for WS
     let response_header_sink = ResponseHeaderSink::default();
-    let client_msg: ClientMessage = match sonic_rs::from_str(&text) {
-        Ok(msg) => msg,
+    let client_msg = match subprotocol.parse_client_message(&text) {
+        Ok(Some(msg)) => msg,
+        Ok(None) => {
+            debug!("Client terminated the connection");
+            return Some(ws::Message::Close(Some(ws::CloseCode::Normal.into())));
+        }
         Err(e) => {
             error!("Failed to parse client message to JSON: {}", e);
             return Some(CloseCode::BadRequest("Invalid message received from client").into());
@@ -268,11 +480,6 @@ async fn handle_text_frame(
             }
             state.borrow_mut().handshake_received = true;
             state.borrow_mut().init_payload = payload;
-            state.borrow_mut().complete_handshake();
-
-            let _ = sink.send(ServerMessage::ack()).await;
-
-            debug!("Connection acknowledged");
 
             let header_map =
                 parse_headers_from_connection_init_payload(state.borrow().init_payload.as_ref());
@@ -282,6 +489,43 @@ async fn handle_text_frame(
                 trace!("Connection init message does not contain headers in the payload");
             }
 
+            // browsers cannot set headers on the upgrade request, so the credentials are read
+            // from the init payload and the identity is kept for the lifetime of the connection
+            if shared_state
+                .router_config
+                .websocket
+                .headers
+                .accepts_connection_headers()
+            {
+                match authenticate_connection(shared_state, &header_map).await {
+                    Ok(jwt_context) => *identity.borrow_mut() = jwt_context,
+                    // the token can still be provided with the headers of each operation
+                    Err(JwtError::LookupFailed(_)) => {}
+                    Err(err) => {
+                        debug!("Connection init payload failed authentication: {}", err);
+                        return Some(err.into_close_message());
+                    }
+                }
+            }
+
+            state.borrow_mut().complete_handshake();
+
+            let _ = sink.send(subprotocol.ack()).await;
+
+            if subprotocol == Subprotocol::GraphQLWs {
+                let interval = shared_state
+                    .router_config
+                    .websocket
+                    .protocols
+                    .graphql_ws
+                    .keep_alive_interval;
+                if !interval.is_zero() {
+                    rt::spawn(legacy_keep_alive(sink.clone(), interval));
+                }
+            }
+
+            debug!("Connection acknowledged");
+
             None
         }
         ClientMessage::Subscribe { id, payload } => {
@@ -293,6 +537,22 @@ async fn handle_text_frame(
                 return Some(CloseCode::SubscriberAlreadyExists(id).into());
             }
 
+            if let Some(max) = shared_state
+                .router_config
+                .websocket
+                .max_subscriptions_per_connection
+            {
+                if state.borrow().subscriptions.len() >= max.get() {
+                    return Some(subprotocol.error(
+                        &id,
+                        &[GraphQLError::from_message_and_extensions(
+                            "Too many active subscriptions on this connection".to_string(),
+                            GraphQLErrorExtensions::new_from_code("TOO_MANY_SUBSCRIPTIONS"),
+                        )],
+                    ));
+                }
+            }
+
             let started_at = Instant::now();
             let operation_span = GraphQLOperationSpan::new();
             let span_clone = operation_span.clone();
@@ -302,7 +562,7 @@ async fn handle_text_frame(
                     // IMPORTANT: we dont do this earlier because router might be loading the
                     // supergraph as we speak, so we simply reject the operation request instead
                     warn!("No supergraph available yet, unable to process client subscribe message");
-                    return Some(ServerMessage::error(
+                    return Some(subprotocol.error(
                         &id,
                         &[GraphQLError::from_message_and_extensions(
                             "No supergraph available yet".to_string(),
@@ -315,7 +575,7 @@ async fn handle_text_frame(
                     // the supergraph selected at upgrade time has since been retired (configured
                     // reload, or the owning plugin dropped/replaced its variant) - reject new
                     // operations on this connection rather than create work from a stale schema.
-                    return Some(ServerMessage::error(
+                    return Some(subprotocol.error(
                         &id,
                         &[GraphQLError::from_message_and_extensions(
                             "Supergraph used by this connection has been retired".to_string(),
@@ -339,6 +599,17 @@ async fn handle_text_frame(
                     HeaderMap::new()
                 };
 
+                // the identity of the connection is reused unless the operation brings its own headers,
+                // an expired one is validated again and rejected along with the operation
+                let jwt_context = if extensions_headers.is_empty() {
+                    identity
+                        .borrow()
+                        .clone()
+                        .filter(|jwt_context| !jwt_context.is_expired())
+                } else {
+                    None
+                };
+
                 // merge, extensions have precedence
                 let mut headers = connection_init_headers;
                 for (key, value) in extensions_headers.iter() {
@@ -410,17 +681,28 @@ async fn handle_text_frame(
                             .version_header.get_header_ref(),
                     )
                     .and_then(|v| v.to_str().ok());
+                let client_identity = Arc::new(ClientIdentityDetails {
+                    name: client_name.map(str::to_string),
+                    version: client_version.map(str::to_string),
+                });
+                if let Err(err) = require_client_identity(
+                    client_name,
+                    client_version,
+                    &shared_state.router_config.telemetry.client_identification,
+                ) {
+                    return Some(err.into_server_message(subprotocol, &id, shared_state));
+                }
 
                 let parser_result =
                     match parse_operation_with_cache(shared_state, &payload, &plugin_req_state).await {
                         Ok(result) => result,
-                        Err(err) => return Some(err.into_server_message(&id, shared_state)),
+                        Err(err) => return Some(err.into_server_message(subprotocol, &id, shared_state)),
                     };
 
                 let parser_payload = match parser_result {
                     crate::pipeline::parser::ParseResult::Payload(payload) => payload,
                     crate::pipeline::parser::ParseResult::EarlyResponse(_) => {
-                        return Some(ServerMessage::error(
+                        return Some(subprotocol.error(
                             &id,
                             &[GraphQLError::from_message_and_code(
                                 "Unexpected early response during parse",
@@ -448,7 +730,7 @@ async fn handle_text_frame(
                 .await
                 {
                     Ok(Some(_)) => {
-                        return Some(ServerMessage::error(
+                        return Some(subprotocol.error(
                             &id,
                             &[GraphQLError::from_message_and_code(
                                 "Unexpected early response during validation",
@@ -457,7 +739,7 @@ async fn handle_text_frame(
                         ));
                     }
                     Ok(None) => {}
-                    Err(err) => return Some(err.into_server_message(&id, shared_state)),
+                    Err(err) => return Some(err.into_server_message(subprotocol, &id, shared_state)),
                 }
 
                 let normalize_payload = match normalize_request_with_cache(
@@ -470,7 +752,7 @@ async fn handle_text_frame(
                 .await
                 {
                     Ok(payload) => payload,
-                    Err(err) => return Some(err.into_server_message(&id, shared_state)),
+                    Err(err) => return Some(err.into_server_message(subprotocol, &id, shared_state)),
                 };
 
                 let is_subscription = matches!(
@@ -479,11 +761,22 @@ async fn handle_text_frame(
                 );
 
                 if is_subscription && !shared_state.router_config.subscriptions.enabled {
-                    return Some(PipelineError::SubscriptionsNotSupported.into_server_message(&id, shared_state));
+                    return Some(PipelineError::SubscriptionsNotSupported.into_server_message(subprotocol, &id, shared_state));
                 }
 
+                // held by the subscription loop below for as long as the client is subscribed
+                let subscription_slot = if is_subscription {
+                    match shared_state.active_subscriptions.acquire_client_slot() {
+                        Some(slot) => Some(slot),
+                        None => return Some(PipelineError::TooManySubscriptions.into_server_message(subprotocol, &id, shared_state)),
+                    }
+                } else {
+                    None
+                };
+
+                let reloadable = shared_state.reloadable.load_full();
                 let request_dedupe_enabled =
-                    shared_state.router_config.traffic_shaping.router.dedupe.enabled;
+                    reloadable.router_config.traffic_shaping.router.dedupe.enabled;
 
                 let fingerprint = if request_dedupe_enabled
                     && matches!(
@@ -500,7 +793,7 @@ async fn handle_text_frame(
                         &Method::POST,
                         ws_uri.path(),
                         headers.as_ref(),
-                        &shared_state.in_flight_requests_header_policy,
+                        &reloadable.in_flight_requests_header_policy,
                         supergraph.snapshot.cache_id,
                         normalize_payload.normalized_operation_hash,
                         variables_hash,
@@ -520,9 +813,13 @@ async fn handle_text_frame(
                     &method,
                     ws_uri,
                     headers.as_ref().clone(),
+                    jwt_context.clone(),
+                    client_identity.clone(),
                     // TODO: WebSocket subscriptions do not yet expose route path params
                     Default::default(),
                     payload,
+                    // the variables are parsed from the message, without keeping their JSON
+                    Default::default(),
                     &normalize_payload,
                     supergraph,
                     shared_state,
@@ -532,7 +829,10 @@ async fn handle_text_frame(
                     request_context,
                     &response_mode,
                     guard,
-                    response_header_sink.clone()
+                    response_header_sink.clone(),
+                    None,
+                    None,
+                    None,
                 );
 
                 let shared_response = if let Some(fp) = fingerprint {
@@ -552,24 +852,24 @@ async fn handle_text_frame(
                     let (shared_response, _role) = match result {
                         Ok(result) => result,
                         Err(PipelineError::JwtError(err)) => {
-                            let _ = sink.send(err.clone().into_server_message(&id, shared_state)).await;
+                            let _ = sink.send(err.clone().into_server_message(subprotocol, &id, shared_state)).await;
                             // we report error as graphql error, but we also close the
                             // connection since we're dealing with auth so let's be safe
                             return Some(err.into_close_message());
                         },
-                        Err(err) => return Some(err.into_server_message(&id, shared_state)),
+                        Err(err) => return Some(err.into_server_message(subprotocol, &id, shared_state)),
                     };
                     Arc::unwrap_or_clone(shared_response)
                 } else {
                     match exec(None).await {
                         Ok(result) => result,
                         Err(PipelineError::JwtError(err)) => {
-                            let _ = sink.send(err.clone().into_server_message(&id, shared_state)).await;
+                            let _ = sink.send(err.clone().into_server_message(subprotocol, &id, shared_state)).await;
                             // we report error as graphql error, but we also close the
                             // connection since we're dealing with auth so let's be safe
                             return Some(err.into_close_message());
                         },
-                        Err(err) => return Some(err.into_server_message(&id, shared_state)),
+                        Err(err) => return Some(err.into_server_message(subprotocol, &id, shared_state)),
                     }
                 };
 
@@ -603,8 +903,8 @@ async fn handle_text_frame(
 
                 match shared_response {
                     SharedRouterResponse::Single(response) => {
-                        let _ = sink.send(ServerMessage::next(&id, &response.body)).await;
-                        Some(ServerMessage::complete(&id))
+                        let _ = sink.send(subprotocol.next(&id, &response.body)).await;
+                        Some(subprotocol.complete(&id))
                     }
                     SharedRouterResponse::Stream(response) => {
                         let (cancel_tx, mut cancel_rx) = mpsc::channel::<()>(1);
@@ -635,6 +935,7 @@ async fn handle_text_frame(
                         // making cancellation impossible
                         rt::spawn(async move {
                             let _guard = guard;
+                            let _subscription_slot = subscription_slot;
                             let mut client_op_guard = client_op_guard;
                             let mut cancelled = false;
 
@@ -643,12 +944,12 @@ async fn handle_text_frame(
                                     maybe_item = receiver.recv() => {
                                         match maybe_item {
                                             Ok(SubscriptionEvent::Raw(data)) => {
-                                                let _ = sink.send(ServerMessage::next(&id_for_loop, &data)).await;
+                                                let _ = sink.send(subprotocol.next(&id_for_loop, &data)).await;
                                                 metrics.subscriptions.record_client_sent(SubscriptionTransport::WebSocket);
                                             }
                                             Ok(SubscriptionEvent::Error(errors)) => {
                                                 client_op_guard.set_end_reason(SubscriptionEndReason::Error);
-                                                let _ = sink.send(ServerMessage::error(&id_for_loop, &errors)).await;
+                                                let _ = sink.send(subprotocol.error(&id_for_loop, &errors)).await;
                                                 break;
                                             }
                                             Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
@@ -676,7 +977,7 @@ async fn handle_text_frame(
                                 trace!(id = %id_for_loop, "Subscription cancelled");
                             } else {
                                 trace!(id = %id_for_loop, "Subscription completed");
-                                let _ = sink.send(ServerMessage::complete(&id_for_loop)).await;
+                                let _ = sink.send(subprotocol.complete(&id_for_loop)).await;
                             }
                         });
 
@@ -779,7 +1080,12 @@ fn parse_headers_from_extensions(extensions: Option<&HashMap<String, Value>>) ->
 
 // NOTE: no `From` trait because it can into ws message and ws closecode but both are ws::Message
 impl PipelineError {
-    fn into_server_message(self, id: &str, shared_state: &RouterSharedState) -> ws::Message {
+    fn into_server_message(
+        self,
+        subprotocol: Subprotocol,
+        id: &str,
+        shared_state: &RouterSharedState,
+    ) -> ws::Message {
         let code = self.graphql_error_code();
         let message = self.graphql_error_message();
 
@@ -794,13 +1100,18 @@ impl PipelineError {
             GraphQLErrorExtensions::new_from_code(code),
         );
 
-        ServerMessage::error(id, &[graphql_error])
+        subprotocol.error(id, &[graphql_error])
     }
 }
 
 // NOTE: no `From` trait because it can into ws message and ws closecode but both are ws::Message
 impl JwtError {
-    fn into_server_message(self, id: &str, shared_state: &RouterSharedState) -> ws::Message {
+    fn into_server_message(
+        self,
+        subprotocol: Subprotocol,
+        id: &str,
+        shared_state: &RouterSharedState,
+    ) -> ws::Message {
         let code = self.error_code();
 
         shared_state
@@ -809,7 +1120,7 @@ impl JwtError {
             .graphql
             .record_error(code);
 
-        ServerMessage::error(
+        subprotocol.error(
             id,
             &[GraphQLError::from_message_and_code(self.to_string(), code)],
         )
@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use hive_router_config::query_planner::{QueryPlanCacheConfig, QueryPlanWarmupOperation};
+use hive_router_plan_executor::hooks::on_supergraph_load::SupergraphSnapshot;
+use hive_router_plan_executor::introspection::partition::partition_operation;
+use hive_router_query_planner::ast::normalization::normalize_operation;
+use hive_router_query_planner::planner::plan_nodes::QueryPlan;
+use hive_router_query_planner::utils::cancellation::CancellationToken;
+use hive_router_query_planner::utils::parsing::safe_parse_operation;
+use moka::future::Cache;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::pipeline::persisted_documents::resolve::{
+    PersistedDocumentResolveInput, PersistedDocumentResolver,
+};
+use crate::pipeline::persisted_documents::types::{ClientIdentity, PersistedDocumentId};
+use crate::pipeline::progressive_override::{RequestOverrideContext, StableOverrideContext};
+use crate::pipeline::query_plan::calculate_cache_key;
+use crate::schema_state::RouterSupergraphRuntime;
+
+/// Builds the cache of query plans of a supergraph, bounded either by the number of plans,
+/// or by their estimated size when `max_size` is set.
+pub fn build_plan_cache(config: &QueryPlanCacheConfig) -> Cache<u64, Arc<QueryPlan>> {
+    let mut builder = Cache::builder();
+
+    builder = match config.max_size {
+        Some(max_size) => builder
+            .max_capacity(max_size)
+            .weigher(|_, plan: &Arc<QueryPlan>| {
+                sonic_rs::to_vec(plan.as_ref())
+                    .ok()
+                    .and_then(|serialized| u32::try_from(serialized.len()).ok())
+                    .unwrap_or(u32::MAX)
+            }),
+        None => builder.max_capacity(config.max_entries),
+    };
+
+    if let Some(ttl) = config.ttl {
+        builder = builder.time_to_live(ttl);
+    }
+
+    builder.build()
+}
+
+/// An operation planned before a supergraph serves requests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupOperation {
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<String>,
+}
+
+/// The content of the file of `query_planner.cache.persistence`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedPlanCache {
+    operations: Vec<WarmupOperation>,
+}
+
+/// Resolves the operations of `query_planner.warmup`,
+/// skipping the persisted documents that can't be resolved.
+pub async fn resolve_warmup_operations(
+    warmup: &[QueryPlanWarmupOperation],
+    persisted_document_resolver: Option<&Arc<dyn PersistedDocumentResolver>>,
+) -> Vec<WarmupOperation> {
+    let mut operations = Vec::with_capacity(warmup.len());
+
+    for warmup_operation in warmup {
+        match warmup_operation {
+            QueryPlanWarmupOperation::Document {
+                query,
+                operation_name,
+            } => operations.push(WarmupOperation {
+                query: query.clone(),
+                operation_name: operation_name.clone(),
+            }),
+            QueryPlanWarmupOperation::PersistedDocument {
+                persisted_document_id,
+            } => {
+                let Some(resolver) = persisted_document_resolver else {
+                    warn!(
+                        %persisted_document_id,
+                        "Can't warm up the query plan of a persisted document, persisted documents are not enabled"
+                    );
+                    continue;
+                };
+
+                let persisted_document_id = PersistedDocumentId::new(persisted_document_id.clone());
+                let resolved = resolver
+                    .resolve(PersistedDocumentResolveInput {
+                        persisted_document_id: &persisted_document_id,
+                        client_identity: ClientIdentity::default(),
+                    })
+                    .await;
+
+                match resolved {
+                    Ok(document) => operations.push(WarmupOperation {
+                        query: document.text.to_string(),
+                        operation_name: None,
+                    }),
+                    Err(err) => warn!(
+                        persisted_document_id = %persisted_document_id,
+                        error = %err,
+                        "Failed to resolve a persisted document to warm up"
+                    ),
+                }
+            }
+        }
+    }
+
+    operations
+}
+
+/// Plans the given operations and stores the plans in the cache of the runtime,
+/// the same way requests with the given override context would.
+pub async fn warm_up_plan_cache(
+    snapshot: &SupergraphSnapshot,
+    runtime: &RouterSupergraphRuntime,
+    operations: &[WarmupOperation],
+    override_context: &RequestOverrideContext,
+    timeout: Duration,
+) {
+    if operations.is_empty() {
+        return;
+    }
+
+    let stable_override_context =
+        StableOverrideContext::new(&snapshot.planner.supergraph, override_context);
+    let mut planned = 0;
+
+    for operation in operations {
+        match plan_operation(snapshot, operation, override_context, timeout) {
+            Ok(Some((operation_for_plan_hash, plan))) => {
+                runtime
+                    .plan_cache
+                    .insert(
+                        calculate_cache_key(operation_for_plan_hash, &stable_override_context),
+                        Arc::new(plan),
+                    )
+                    .await;
+                planned += 1;
+            }
+            // nothing to plan, like in a pure introspection query
+            Ok(None) => {}
+            Err(err) => warn!(
+                operation_name = operation.operation_name.as_deref(),
+                error = %err,
+                "Failed to warm up the query plan of an operation"
+            ),
+        }
+    }
+
+    info!(
+        "Warmed up the query plan cache with {} of {} operation(s)",
+        planned,
+        operations.len()
+    );
+}
+
+fn plan_operation(
+    snapshot: &SupergraphSnapshot,
+    operation: &WarmupOperation,
+    override_context: &RequestOverrideContext,
+    timeout: Duration,
+) -> Result<Option<(u64, QueryPlan)>, String> {
+    let parsed_operation = safe_parse_operation(&operation.query).map_err(|e| e.to_string())?;
+    let normalized = normalize_operation(
+        &snapshot.planner.supergraph,
+        &parsed_operation,
+        operation.operation_name.as_deref(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let operation_for_plan = partition_operation(normalized.operation).downstream_operation;
+    if operation_for_plan.selection_set.is_empty() {
+        return Ok(None);
+    }
+
+    let plan = snapshot
+        .planner
+        .plan_from_normalized_operation(
+            &operation_for_plan,
+            override_context.into(),
+            &CancellationToken::with_timeout(timeout),
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(Some((operation_for_plan.hash(), plan)))
+}
+
+/// Reads the operations persisted by a previous run of the router.
+/// A missing or unreadable file means there is nothing to warm up.
+pub fn read_persisted_operations(path: &str) -> Vec<WarmupOperation> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            debug!(path, "No persisted query plan cache to warm up from");
+            return Vec::new();
+        }
+        Err(err) => {
+            warn!(path, error = %err, "Failed to read the persisted query plan cache");
+            return Vec::new();
+        }
+    };
+
+    match sonic_rs::from_str::<PersistedPlanCache>(&content) {
+        Ok(persisted) => persisted.operations,
+        Err(err) => {
+            warn!(path, error = %err, "Failed to parse the persisted query plan cache");
+            Vec::new()
+        }
+    }
+}
+
+/// Writes the operations of the cached query plans of the runtime to a file,
+/// to plan them again when the router starts.
+pub fn persist_operations(runtime: &RouterSupergraphRuntime, path: &str) {
+    let mut seen = HashSet::new();
+    let operations: Vec<WarmupOperation> = runtime
+        .normalize_cache
+        .iter()
+        .filter(|(_, payload)| seen.insert(payload.operation_for_plan_hash))
+        .filter(|(_, payload)| !payload.operation_for_plan.selection_set.is_empty())
+        .map(|(_, payload)| WarmupOperation {
+            query: payload.operation_for_plan.to_string(),
+            operation_name: None,
+        })
+        .collect();
+
+    let persisted = PersistedPlanCache { operations };
+    let result = sonic_rs::to_string(&persisted)
+        .map_err(|e| e.to_string())
+        .and_then(|content| std::fs::write(path, content).map_err(|e| e.to_string()));
+
+    match result {
+        Ok(()) => info!(
+            path,
+            "Persisted {} operation(s) of the query plan cache",
+            persisted.operations.len()
+        ),
+        Err(err) => warn!(path, error = %err, "Failed to persist the query plan cache"),
+    }
+}
@@ -1,3 +1,8 @@
+pub mod plugin_storage_backend;
 pub mod plugins_service;
 pub mod registry;
+pub mod rhai_scripts;
+pub mod vrl_hooks;
+pub mod wasm;
+pub use hive_router_internal::telemetry::metrics::plugin_metrics;
 pub use hive_router_plan_executor::plugins::*;
@@ -0,0 +1,92 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use hive_router_config::plugin_storage::{PluginStorageBackendConfig, PluginStorageConfig};
+use hive_router_internal::telemetry::metrics::plugin_storage_metrics::PluginStorageMetrics;
+use hive_router_plan_executor::plugin_storage::{
+    MemoryPluginStorageBackend, PluginStorage, PluginStorageBackend, PluginStorageError,
+};
+use redis::AsyncCommands;
+
+use crate::storage::{
+    error::StorageError,
+    redis_connections::{RedisConnection, RedisConnections},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PluginStorageConfigError {
+    #[error("invalid plugin storage redis config: {0}")]
+    Redis(#[from] StorageError),
+}
+
+pub fn plugin_storage_from_config(
+    config: &PluginStorageConfig,
+    redis_connections: &RedisConnections,
+    metrics: PluginStorageMetrics,
+) -> Result<PluginStorage, PluginStorageConfigError> {
+    let backend: Arc<dyn PluginStorageBackend> = match &config.backend {
+        PluginStorageBackendConfig::Memory { max_entries } => {
+            Arc::new(MemoryPluginStorageBackend::new(*max_entries))
+        }
+        PluginStorageBackendConfig::Redis { url, key_prefix } => {
+            Arc::new(RedisPluginStorageBackend {
+                connection: redis_connections.connect("plugin_storage", url)?,
+                key_prefix: key_prefix.clone(),
+            })
+        }
+    };
+
+    Ok(PluginStorage::new(backend, metrics))
+}
+
+/// Stores the values of the plugins in Redis, shared between router instances,
+/// over the connection the router shares between its components.
+struct RedisPluginStorageBackend {
+    connection: RedisConnection,
+    key_prefix: String,
+}
+
+fn redis_error(err: redis::RedisError) -> PluginStorageError {
+    PluginStorageError(Box::new(err))
+}
+
+#[async_trait]
+impl PluginStorageBackend for RedisPluginStorageBackend {
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, PluginStorageError> {
+        let mut connection = self.connection.get().await.map_err(redis_error)?;
+        let key = format!("{}{}", self.key_prefix, key);
+        connection
+            .get::<_, Option<Vec<u8>>>(&key)
+            .await
+            .map(|value| value.map(Bytes::from))
+            .map_err(redis_error)
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        value: Bytes,
+        ttl: Option<Duration>,
+    ) -> Result<(), PluginStorageError> {
+        let mut connection = self.connection.get().await.map_err(redis_error)?;
+        let key = format!("{}{}", self.key_prefix, key);
+        match ttl {
+            // redis rejects a zero expiration, so sub-millisecond TTLs are rounded up
+            Some(ttl) => connection
+                .pset_ex::<_, _, ()>(&key, value.as_ref(), (ttl.as_millis() as u64).max(1))
+                .await
+                .map_err(redis_error),
+            None => connection
+                .set::<_, _, ()>(&key, value.as_ref())
+                .await
+                .map_err(redis_error),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), PluginStorageError> {
+        let mut connection = self.connection.get().await.map_err(redis_error)?;
+        let key = format!("{}{}", self.key_prefix, key);
+        connection.del::<_, ()>(&key).await.map_err(redis_error)
+    }
+}
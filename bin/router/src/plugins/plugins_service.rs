@@ -78,20 +78,36 @@ where
         let request_context = SharedRequestContext::default();
         req.write_request_context(request_context.clone());
 
-        let coprocessor_runtime = if is_probe {
+        let coprocessor = if is_probe {
             None
         } else {
-            shared_state
-                .as_ref()
-                .and_then(|shared_state| shared_state.coprocessor.as_ref())
+            shared_state.as_ref().and_then(|shared_state| {
+                shared_state
+                    .coprocessor
+                    .as_ref()
+                    .map(|coprocessor_runtime| {
+                        // the limit is reloadable, so it's read for every request
+                        let body_size_limit = shared_state
+                            .reloadable
+                            .load()
+                            .router_config
+                            .limits
+                            .max_request_body_size
+                            .to_bytes() as usize;
+                        (coprocessor_runtime, body_size_limit)
+                    })
+            })
         };
 
         let plugins = shared_state
             .as_ref()
             .and_then(|state| state.plugins.clone());
 
-        if let Some(coprocessor_runtime) = coprocessor_runtime {
-            match coprocessor_runtime.on_router_request(req).await {
+        if let Some((coprocessor_runtime, body_size_limit)) = coprocessor {
+            match coprocessor_runtime
+                .on_router_request(req, body_size_limit)
+                .await
+            {
                 ControlFlow::Break(response) => return Ok(response),
                 ControlFlow::Continue(new_req) => req = new_req,
             }
@@ -130,7 +146,7 @@ where
 
             let mut response = ctx.call(&self.service, req).await?;
 
-            if let Some(coprocessor_runtime) = coprocessor_runtime {
+            if let Some((coprocessor_runtime, _)) = coprocessor {
                 response = coprocessor_runtime.on_router_response(response).await;
             }
 
@@ -163,7 +179,7 @@ where
 
         let mut response = ctx.call(&self.service, req).await?;
 
-        if let Some(coprocessor_runtime) = coprocessor_runtime {
+        if let Some((coprocessor_runtime, _)) = coprocessor {
             response = coprocessor_runtime.on_router_response(response).await;
         }
 
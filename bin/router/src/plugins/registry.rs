@@ -1,17 +1,33 @@
 use std::sync::Arc;
 
 use hive_router_config::HiveRouterConfig;
-use hive_router_internal::{background_tasks::BackgroundTasksManager, BoxError};
+use hive_router_internal::{
+    background_tasks::BackgroundTasksManager, telemetry::metrics::plugin_metrics::PluginMetrics,
+    BoxError,
+};
 use hive_router_plan_executor::{
     hooks::on_plugin_init::OnPluginInitPayload,
+    plugin_routes::PluginRoute,
+    plugin_storage::PluginStorage,
     plugin_trait::{RouterPlugin, RouterPluginBoxed},
+    response::error_status_codes::ErrorStatusCodes,
 };
 use tracing::{info, warn};
 
+use crate::plugins::{
+    rhai_scripts::{RhaiPlugin, RhaiScriptError},
+    vrl_hooks::{VrlHookError, VrlHooksPlugin},
+    wasm::{WasmPlugin, WasmPluginError},
+};
+
 type PluginFactory = Box<
     dyn Fn(
         &serde_json::Value,
         &mut BackgroundTasksManager,
+        &mut ErrorStatusCodes,
+        &mut Vec<PluginRoute>,
+        &PluginMetrics,
+        &PluginStorage,
     ) -> Result<Option<RouterPluginBoxed>, PluginRegistryError>,
 >;
 
@@ -33,6 +49,12 @@ pub enum PluginRegistryError {
         "Plugin '{0}' is not registered in the registry but is specified in the configuration"
     )]
     MissingInRegistry(String),
+    #[error("Failed to load the WebAssembly plugin '{0}': {1}")]
+    Wasm(String, WasmPluginError),
+    #[error("Invalid hooks: {0}")]
+    VrlHooks(#[from] VrlHookError),
+    #[error("Failed to load the Rhai scripts: {0}")]
+    Rhai(#[from] RhaiScriptError),
 }
 
 impl PluginRegistry {
@@ -47,10 +69,26 @@ impl PluginRegistry {
             plugin_name,
             Box::new(
                 |plugin_config: &serde_json::Value,
-                 bg_tasks_manager: &mut BackgroundTasksManager| {
-                    let payload = OnPluginInitPayload::new(plugin_config, bg_tasks_manager);
+                 bg_tasks_manager: &mut BackgroundTasksManager,
+                 error_status_codes: &mut ErrorStatusCodes,
+                 routes: &mut Vec<PluginRoute>,
+                 metrics: &PluginMetrics,
+                 storage: &PluginStorage| {
+                    let mut plugin_routes = Vec::new();
+                    let payload = OnPluginInitPayload::new(
+                        plugin_config,
+                        bg_tasks_manager,
+                        error_status_codes,
+                        &mut plugin_routes,
+                        metrics,
+                        storage,
+                    );
                     let plugin = P::on_plugin_init(payload)
                         .map_err(|err| PluginRegistryError::Initialization(plugin_name, err))?;
+                    // the routes of a plugin disabled during its initialization are not served
+                    if plugin.is_some() {
+                        routes.extend(plugin_routes);
+                    }
                     Ok(Option::map(plugin, |p| Box::new(p) as RouterPluginBoxed))
                 },
             ),
@@ -61,6 +99,10 @@ impl PluginRegistry {
         &self,
         router_config: &HiveRouterConfig,
         bg_tasks_manager: &mut BackgroundTasksManager,
+        error_status_codes: &mut ErrorStatusCodes,
+        routes: &mut Vec<PluginRoute>,
+        metrics: &PluginMetrics,
+        storage: &PluginStorage,
     ) -> Result<Option<Arc<Vec<RouterPluginBoxed>>>, PluginRegistryError> {
         let mut plugins_unordered = Vec::with_capacity(router_config.plugins.len());
 
@@ -73,7 +115,14 @@ impl PluginRegistry {
                 .iter()
                 .find_map(|(name, factory)| (*name == plugin_name).then_some(factory))
             {
-                let plugin_init_result = factory(&plugin_config_value.config, bg_tasks_manager);
+                let plugin_init_result = factory(
+                    &plugin_config_value.config,
+                    bg_tasks_manager,
+                    error_status_codes,
+                    routes,
+                    metrics,
+                    storage,
+                );
                 match plugin_init_result {
                     Err(plugin_init_error) => {
                         if plugin_config_value.warn_on_error {
@@ -111,6 +160,43 @@ impl PluginRegistry {
             }
         }
 
+        // VRL hooks run after the registered plugins
+        if let Some(hooks) = VrlHooksPlugin::from_config(&router_config.hooks)? {
+            info!("VRL hooks successfully enabled");
+            plugins_ordered.push(Box::new(hooks) as RouterPluginBoxed);
+        }
+
+        // Rhai scripts run after the VRL hooks
+        if let Some(rhai_config) = &router_config.rhai {
+            let plugin = RhaiPlugin::load(rhai_config)?;
+            info!("Rhai scripts successfully enabled");
+            plugins_ordered.push(Box::new(plugin) as RouterPluginBoxed);
+        }
+
+        // WebAssembly plugins run after the registered ones, in the order of the configuration
+        for wasm_plugin_config in router_config.wasm_plugins.iter() {
+            if !wasm_plugin_config.enabled {
+                continue;
+            }
+            match WasmPlugin::load(wasm_plugin_config) {
+                Ok(plugin) => {
+                    info!(
+                        "WebAssembly plugin '{}' successfully enabled",
+                        wasm_plugin_config.name
+                    );
+                    plugins_ordered.push(Box::new(plugin) as RouterPluginBoxed);
+                }
+                Err(err) => {
+                    let err = PluginRegistryError::Wasm(wasm_plugin_config.name.clone(), err);
+                    if wasm_plugin_config.warn_on_error {
+                        warn!("Plugin initialization error: {}", err);
+                    } else {
+                        return Err(err);
+                    }
+                }
+            }
+        }
+
         if plugins_ordered.is_empty() {
             Ok(None)
         } else {
@@ -124,7 +210,9 @@ mod tests {
     use std::collections::HashMap;
 
     use hive_router_config::{HiveRouterConfig, PluginConfig};
-    use hive_router_internal::background_tasks::BackgroundTasksManager;
+    use hive_router_internal::{
+        background_tasks::BackgroundTasksManager, telemetry::metrics::plugin_metrics::PluginMetrics,
+    };
     use hive_router_plan_executor::{
         hooks::{
             on_graphql_params::{
@@ -133,9 +221,11 @@ mod tests {
             on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
         },
         plugin_context::{PluginContext, RouterHttpRequest},
+        plugin_storage::PluginStorage,
         plugin_trait::{RouterPlugin, StartHookPayload},
         plugins::hooks,
         request_context::SharedRequestContext,
+        response::error_status_codes::ErrorStatusCodes,
     };
     use ntex::router::Path;
 
@@ -247,7 +337,14 @@ mod tests {
             .into_iter(),
         );
         let plugins = registry
-            .initialize_plugins(&router_config, bg_tasks_manager)
+            .initialize_plugins(
+                &router_config,
+                bg_tasks_manager,
+                &mut ErrorStatusCodes::default(),
+                &mut Vec::new(),
+                &PluginMetrics::default(),
+                &PluginStorage::default(),
+            )
             .expect("Plugins should be initialized successfully")
             .expect("Plugins should exist");
         let uri: http::Uri = "http://example.com/graphql".parse().unwrap();
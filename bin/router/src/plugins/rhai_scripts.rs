@@ -0,0 +1,573 @@
+//! Rhai scripts, configured in the `rhai` section of the configuration.
+//!
+//! Scripts are written the same way as for Apollo Router, so existing scripts keep working.
+//! The main script defines the services it hooks into, and registers callbacks on them:
+//!
+//! ```rhai
+//! fn router_service(service) {
+//!     service.map_request(|request| {
+//!         if !request.headers.contains("x-api-key") {
+//!             throw #{ status: 401, message: "Missing API key" };
+//!         }
+//!         request.context["tenant"] = request.headers["x-tenant"];
+//!     });
+//! }
+//!
+//! fn subgraph_service(service, subgraph) {
+//!     service.map_request(|request| {
+//!         request.headers["x-tenant"] = request.context["tenant"];
+//!     });
+//! }
+//! ```
+//!
+//! - `router_service` callbacks receive the HTTP request received by the router,
+//!   and the HTTP response sent by it.
+//! - `subgraph_service` is called once per subgraph, and its callbacks receive the HTTP requests
+//!   sent to the subgraph, and their responses.
+//!
+//! Requests have `method`, `uri`, `headers` and `context`, responses have `status_code`,
+//! `headers` and `context`. A header with several values is an array of strings.
+//! Throwing ends the request with a GraphQL error, from a message
+//! or `#{ status: ..., message: ..., code: ... }` (`500` and `REQUEST_REJECTED` by default).
+
+use std::{
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use dashmap::DashMap;
+use hive_router_config::{headers::HOP_BY_HOP_HEADERS, rhai::RhaiConfig};
+use hive_router_plan_executor::{
+    hooks::{
+        on_http_request::{OnHttpRequestHookPayload, OnHttpRequestHookResult},
+        on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+        on_subgraph_http_request::{
+            OnSubgraphHttpRequestHookPayload, OnSubgraphHttpRequestHookResult,
+        },
+    },
+    plugin_context::PluginContext,
+    plugin_trait::{EndHookPayload, RouterPlugin, StartHookPayload},
+    response::graphql_error::GraphQLError,
+};
+use http::StatusCode;
+use rhai::{
+    module_resolvers::FileModuleResolver, Array, Dynamic, Engine, EvalAltResult, FnPtr, Map, Scope,
+    AST,
+};
+use tracing::error;
+
+const ROUTER_SERVICE: &str = "router_service";
+const SUBGRAPH_SERVICE: &str = "subgraph_service";
+
+#[derive(Debug, thiserror::Error)]
+pub enum RhaiScriptError {
+    #[error("failed to compile the script: {0}")]
+    Compile(Box<EvalAltResult>),
+    #[error("failed to run '{0}': {1}")]
+    Call(&'static str, Box<EvalAltResult>),
+    #[error("invalid header: {0}")]
+    InvalidHeader(String),
+}
+
+pub struct RhaiPlugin {
+    engine: Engine,
+    ast: AST,
+    router_service: RhaiService,
+    /// The callbacks of `subgraph_service`, registered the first time a subgraph is requested
+    subgraph_services: DashMap<String, RhaiService>,
+}
+
+/// The callbacks registered on a service by the script
+#[derive(Clone, Default)]
+struct RhaiService {
+    request: Arc<Mutex<Vec<FnPtr>>>,
+    response: Arc<Mutex<Vec<FnPtr>>>,
+}
+
+impl RhaiService {
+    fn map_request(&mut self, callback: FnPtr) {
+        lock(&self.request).push(callback);
+    }
+
+    fn map_response(&mut self, callback: FnPtr) {
+        lock(&self.response).push(callback);
+    }
+
+    fn request_callbacks(&self) -> Vec<FnPtr> {
+        lock(&self.request).clone()
+    }
+
+    fn response_callbacks(&self) -> Vec<FnPtr> {
+        lock(&self.response).clone()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The `context` of the scripts for a request
+#[derive(Clone, Default)]
+struct RhaiContext(Arc<Mutex<Map>>);
+
+/// A request or a response, shared between the callbacks and the router,
+/// so the changes made by a callback are seen once it returns.
+#[derive(Clone)]
+struct RhaiMessage(Arc<Mutex<RhaiMessageData>>);
+
+struct RhaiMessageData {
+    method: String,
+    uri: String,
+    status_code: i64,
+    headers: Map,
+    context: RhaiContext,
+}
+
+impl RhaiMessage {
+    fn new(data: RhaiMessageData) -> Self {
+        Self(Arc::new(Mutex::new(data)))
+    }
+
+    fn method(&mut self) -> String {
+        lock(&self.0).method.clone()
+    }
+
+    fn uri(&mut self) -> String {
+        lock(&self.0).uri.clone()
+    }
+
+    fn status_code(&mut self) -> i64 {
+        lock(&self.0).status_code
+    }
+
+    fn headers(&mut self) -> Map {
+        lock(&self.0).headers.clone()
+    }
+
+    fn set_headers(&mut self, headers: Map) {
+        lock(&self.0).headers = headers;
+    }
+
+    fn context(&mut self) -> Map {
+        lock(&lock(&self.0).context.0).clone()
+    }
+
+    fn set_context(&mut self, context: Map) {
+        *lock(&lock(&self.0).context.0) = context;
+    }
+
+    fn into_headers(self) -> Map {
+        std::mem::take(&mut lock(&self.0).headers)
+    }
+}
+
+fn headers_to_map<'a>(headers: impl Iterator<Item = (&'a str, &'a [u8])>) -> Map {
+    let mut map = Map::new();
+    for (name, value) in headers {
+        let Ok(value) = std::str::from_utf8(value) else {
+            continue;
+        };
+        match map.get_mut(name) {
+            None => {
+                map.insert(name.into(), value.into());
+            }
+            Some(existing) if existing.is_array() => {
+                if let Some(mut values) = existing.write_lock::<Array>() {
+                    values.push(value.into());
+                }
+            }
+            Some(existing) => {
+                let previous = std::mem::take(existing);
+                *existing = Dynamic::from_array(vec![previous, value.into()]);
+            }
+        }
+    }
+    map
+}
+
+/// The headers to set, from the headers of a message once the callbacks ran
+fn map_to_headers(map: Map) -> Result<Vec<(http::HeaderName, http::HeaderValue)>, RhaiScriptError> {
+    let mut headers = Vec::with_capacity(map.len());
+    for (name, values) in map {
+        let name = http::HeaderName::from_str(&name)
+            .map_err(|err| RhaiScriptError::InvalidHeader(err.to_string()))?;
+        if HOP_BY_HOP_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        let values = if values.is_array() {
+            values.cast::<Array>()
+        } else {
+            vec![values]
+        };
+        for value in values {
+            let value = http::HeaderValue::from_str(&value.to_string())
+                .map_err(|err| RhaiScriptError::InvalidHeader(err.to_string()))?;
+            headers.push((name.clone(), value));
+        }
+    }
+    Ok(headers)
+}
+
+fn replace_ntex_headers(
+    headers: &mut ntex::http::HeaderMap,
+    map: Map,
+) -> Result<(), RhaiScriptError> {
+    let parsed = map_to_headers(map)?;
+    let keys_to_remove: Vec<_> = headers
+        .keys()
+        .filter(|name| !HOP_BY_HOP_HEADERS.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    for key in keys_to_remove {
+        headers.remove(&key);
+    }
+    for (name, value) in parsed {
+        headers.append(name, value.into());
+    }
+    Ok(())
+}
+
+fn replace_http_headers(headers: &mut http::HeaderMap, map: Map) -> Result<(), RhaiScriptError> {
+    let parsed = map_to_headers(map)?;
+    let keys_to_remove: Vec<_> = headers
+        .keys()
+        .filter(|name| !HOP_BY_HOP_HEADERS.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    for key in keys_to_remove {
+        headers.remove(&key);
+    }
+    for (name, value) in parsed {
+        headers.append(name, value);
+    }
+    Ok(())
+}
+
+/// The GraphQL error ending the request, from the value thrown by a callback,
+/// or a generic error when the script failed on its own.
+fn error_from_script(mut err: &EvalAltResult) -> (GraphQLError, StatusCode) {
+    // Errors thrown by callbacks are wrapped in the error of the call
+    while let EvalAltResult::ErrorInFunctionCall(_, _, inner, _)
+    | EvalAltResult::ErrorInModule(_, inner, _) = err
+    {
+        err = inner.as_ref();
+    }
+
+    let EvalAltResult::ErrorRuntime(thrown, _) = err else {
+        error!(%err, "Rhai script failed");
+        return (failure_error(), StatusCode::INTERNAL_SERVER_ERROR);
+    };
+
+    let (message, status, code) = match thrown.read_lock::<Map>() {
+        Some(thrown) => (
+            thrown.get("message").map(|message| message.to_string()),
+            thrown
+                .get("status")
+                .and_then(|status| status.as_int().ok())
+                .and_then(|status| u16::try_from(status).ok())
+                .and_then(|status| StatusCode::from_u16(status).ok()),
+            thrown.get("code").map(|code| code.to_string()),
+        ),
+        None => (Some(thrown.to_string()), None, None),
+    };
+
+    (
+        GraphQLError::from_message_and_code(
+            message.unwrap_or_else(|| "Internal server error".to_string()),
+            code.unwrap_or_else(|| "REQUEST_REJECTED".to_string()),
+        ),
+        status.unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+    )
+}
+
+impl RhaiPlugin {
+    pub fn load(config: &RhaiConfig) -> Result<Self, RhaiScriptError> {
+        let mut engine = Engine::new();
+        if let Some(directory) = Path::new(&config.main.absolute).parent() {
+            engine.set_module_resolver(FileModuleResolver::new_with_path(directory));
+        }
+        engine
+            .register_type_with_name::<RhaiService>("Service")
+            .register_fn("map_request", RhaiService::map_request)
+            .register_fn("map_response", RhaiService::map_response)
+            .register_type_with_name::<RhaiMessage>("Message")
+            .register_get("method", RhaiMessage::method)
+            .register_get("uri", RhaiMessage::uri)
+            .register_get("status_code", RhaiMessage::status_code)
+            .register_get_set("headers", RhaiMessage::headers, RhaiMessage::set_headers)
+            .register_get_set("context", RhaiMessage::context, RhaiMessage::set_context);
+
+        let ast = engine
+            .compile_file(config.main.absolute.clone().into())
+            .map_err(RhaiScriptError::Compile)?;
+
+        let plugin = Self {
+            engine,
+            ast,
+            router_service: RhaiService::default(),
+            subgraph_services: DashMap::new(),
+        };
+        if plugin.has_function(ROUTER_SERVICE) {
+            plugin.call_service(ROUTER_SERVICE, (plugin.router_service.clone(),))?;
+        }
+
+        Ok(plugin)
+    }
+
+    fn has_function(&self, name: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|function| function.name == name)
+    }
+
+    fn call_service(
+        &self,
+        name: &'static str,
+        args: impl rhai::FuncArgs,
+    ) -> Result<(), RhaiScriptError> {
+        self.engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, name, args)
+            .map(|_| ())
+            .map_err(|err| RhaiScriptError::Call(name, err))
+    }
+
+    fn subgraph_service(&self, subgraph_name: &str) -> Result<RhaiService, RhaiScriptError> {
+        if let Some(service) = self.subgraph_services.get(subgraph_name) {
+            return Ok(service.clone());
+        }
+
+        let service = RhaiService::default();
+        if self.has_function(SUBGRAPH_SERVICE) {
+            self.call_service(
+                SUBGRAPH_SERVICE,
+                (service.clone(), subgraph_name.to_string()),
+            )?;
+        }
+        self.subgraph_services
+            .insert(subgraph_name.to_string(), service.clone());
+        Ok(service)
+    }
+
+    fn run_callbacks(
+        &self,
+        callbacks: &[FnPtr],
+        message: &RhaiMessage,
+    ) -> Result<(), Box<EvalAltResult>> {
+        for callback in callbacks {
+            callback.call::<Dynamic>(&self.engine, &self.ast, (message.clone(),))?;
+        }
+        Ok(())
+    }
+
+    fn context_of(context: &PluginContext) -> RhaiContext {
+        if let Some(rhai_context) = context.get_ref::<RhaiContext>() {
+            return rhai_context.clone();
+        }
+        let rhai_context = RhaiContext::default();
+        context.insert(rhai_context.clone());
+        rhai_context
+    }
+}
+
+fn failure_error() -> GraphQLError {
+    GraphQLError::from_message_and_code("Internal server error", "RHAI_SCRIPT_FAILURE")
+}
+
+#[async_trait::async_trait]
+impl RouterPlugin for RhaiPlugin {
+    type Config = ();
+
+    fn plugin_name() -> &'static str {
+        "rhai"
+    }
+
+    fn on_plugin_init(_payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+        // Rhai scripts are not part of the registry, they are loaded from `rhai` with `RhaiPlugin::load`.
+        Err("Rhai scripts are configured in `rhai`".into())
+    }
+
+    fn on_http_request<'req>(
+        &'req self,
+        mut payload: OnHttpRequestHookPayload<'req>,
+    ) -> OnHttpRequestHookResult<'req> {
+        let request_callbacks = self.router_service.request_callbacks();
+        if !request_callbacks.is_empty() {
+            let request = &payload.router_http_request;
+            let message = RhaiMessage::new(RhaiMessageData {
+                method: request.method().to_string(),
+                uri: request.uri().to_string(),
+                status_code: 0,
+                headers: headers_to_map(
+                    request
+                        .headers()
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.as_bytes())),
+                ),
+                context: Self::context_of(payload.context),
+            });
+
+            if let Err(err) = self.run_callbacks(&request_callbacks, &message) {
+                let (error, status) = error_from_script(&err);
+                return payload.end_with_graphql_error(error, status);
+            }
+            if let Err(err) = replace_ntex_headers(
+                payload.router_http_request.headers_mut(),
+                message.into_headers(),
+            ) {
+                error!(%err, "Rhai script failed");
+                return payload
+                    .end_with_graphql_error(failure_error(), StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+
+        let response_callbacks = self.router_service.response_callbacks();
+        if response_callbacks.is_empty() {
+            return payload.proceed();
+        }
+
+        payload.on_end(move |mut payload| {
+            let response = payload.response.response();
+            let message = RhaiMessage::new(RhaiMessageData {
+                method: String::new(),
+                uri: String::new(),
+                status_code: response.status().as_u16().into(),
+                headers: headers_to_map(
+                    response
+                        .headers()
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.as_bytes())),
+                ),
+                context: Self::context_of(payload.context),
+            });
+
+            if let Err(err) = self.run_callbacks(&response_callbacks, &message) {
+                let (error, status) = error_from_script(&err);
+                return payload.end_with_graphql_error(error, status);
+            }
+            if let Err(err) = replace_ntex_headers(
+                payload.response.response_mut().headers_mut(),
+                message.into_headers(),
+            ) {
+                error!(%err, "Rhai script failed");
+                return payload
+                    .end_with_graphql_error(failure_error(), StatusCode::INTERNAL_SERVER_ERROR);
+            }
+            payload.proceed()
+        })
+    }
+
+    async fn on_subgraph_http_request<'exec>(
+        &'exec self,
+        mut payload: OnSubgraphHttpRequestHookPayload<'exec>,
+    ) -> OnSubgraphHttpRequestHookResult<'exec> {
+        let service = match self.subgraph_service(payload.subgraph_name) {
+            Ok(service) => service,
+            Err(err) => {
+                error!(%err, subgraph_name = payload.subgraph_name, "Rhai script failed");
+                return payload
+                    .end_with_graphql_error(failure_error(), StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        let request_callbacks = service.request_callbacks();
+        if !request_callbacks.is_empty() {
+            let message = RhaiMessage::new(RhaiMessageData {
+                method: payload.method.to_string(),
+                uri: payload.endpoint.to_string(),
+                status_code: 0,
+                headers: headers_to_map(
+                    payload
+                        .execution_request
+                        .headers
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.as_bytes())),
+                ),
+                context: Self::context_of(payload.context),
+            });
+
+            if let Err(err) = self.run_callbacks(&request_callbacks, &message) {
+                let (error, status) = error_from_script(&err);
+                return payload.end_with_graphql_error(error, status);
+            }
+            if let Err(err) = replace_http_headers(
+                &mut payload.execution_request.headers,
+                message.into_headers(),
+            ) {
+                error!(%err, subgraph_name = payload.subgraph_name, "Rhai script failed");
+                return payload
+                    .end_with_graphql_error(failure_error(), StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+
+        let response_callbacks = service.response_callbacks();
+        if response_callbacks.is_empty() {
+            return payload.proceed();
+        }
+
+        payload.on_end(move |mut payload| {
+            let message = RhaiMessage::new(RhaiMessageData {
+                method: String::new(),
+                uri: String::new(),
+                status_code: payload.response.status.as_u16().into(),
+                headers: headers_to_map(
+                    payload
+                        .response
+                        .headers
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.as_bytes())),
+                ),
+                context: Self::context_of(payload.context),
+            });
+
+            if let Err(err) = self.run_callbacks(&response_callbacks, &message) {
+                let (error, status) = error_from_script(&err);
+                return payload.end_with_graphql_error(error, status);
+            }
+
+            // The response may be shared with other deduplicated requests,
+            // so the headers are replaced on a copy.
+            let mut headers = payload.response.headers.as_ref().clone();
+            if let Err(err) = replace_http_headers(&mut headers, message.into_headers()) {
+                error!(%err, "Rhai script failed");
+                return payload
+                    .end_with_graphql_error(failure_error(), StatusCode::INTERNAL_SERVER_ERROR);
+            }
+            payload.response.headers = Arc::new(headers);
+            payload.proceed()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{headers_to_map, map_to_headers};
+
+    #[test]
+    fn keeps_every_value_of_a_header() {
+        let headers = [
+            ("x-single", "1".as_bytes()),
+            ("x-multi", "a".as_bytes()),
+            ("x-multi", "b".as_bytes()),
+        ];
+        let map = headers_to_map(headers.into_iter());
+
+        assert!(map["x-single"].is_string());
+        assert_eq!(map["x-multi"].clone().cast::<rhai::Array>().len(), 2);
+
+        let parsed = map_to_headers(map)
+            .expect("failed to parse headers")
+            .into_iter()
+            .fold(HashMap::<_, Vec<_>>::new(), |mut parsed, (name, value)| {
+                parsed
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(value.to_str().unwrap().to_string());
+                parsed
+            });
+        assert_eq!(parsed["x-single"], vec!["1"]);
+        assert_eq!(parsed["x-multi"], vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,410 @@
+//! Hooks written as VRL programs, in the `hooks` section of the configuration.
+//!
+//! A program runs against a target describing the request, and assigns fields of it:
+//! - `.request.headers`: the headers set, changed or removed (or set to `null`) are applied to the request.
+//! - `.context`: kept for the following hooks of the same request.
+//! - `.reject`: ends the request with a GraphQL error, from a message
+//!   or `{ "message": ..., "status": ..., "code": ... }` (`403` and `REQUEST_REJECTED` by default).
+
+use std::{collections::BTreeMap, str::FromStr};
+
+use hive_router_config::{headers::HOP_BY_HOP_HEADERS, hooks::HooksConfig};
+use hive_router_internal::expressions::{
+    vrl::{compiler::Program as VrlProgram, core::Value as VrlValue, value::KeyString},
+    CompileExpression, ExecutableProgram, ExpressionCompileError, ExpressionExecutionError,
+    FromVrlValue, ToVrlValue,
+};
+use hive_router_plan_executor::{
+    execution::client_request_details::ntex_header_map_to_vrl_value,
+    hooks::{
+        on_http_request::{OnHttpRequestHookPayload, OnHttpRequestHookResult},
+        on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+        on_subgraph_http_request::{
+            OnSubgraphHttpRequestHookPayload, OnSubgraphHttpRequestHookResult,
+        },
+    },
+    plugin_context::PluginContext,
+    plugin_trait::{RouterPlugin, StartHookPayload},
+    response::graphql_error::GraphQLError,
+};
+use http::{HeaderName, HeaderValue, StatusCode};
+use tracing::error;
+
+const ON_HTTP_REQUEST: &str = "on_http_request";
+const ON_SUBGRAPH_REQUEST: &str = "on_subgraph_request";
+
+#[derive(Debug, thiserror::Error)]
+pub enum VrlHookError {
+    #[error("failed to compile the '{0}' hook: {1}")]
+    Compile(&'static str, ExpressionCompileError),
+    #[error("the '{0}' hook failed: {1}")]
+    Execution(&'static str, ExpressionExecutionError),
+    #[error("the '{0}' hook set an invalid header: {1}")]
+    InvalidHeader(&'static str, String),
+}
+
+pub struct VrlHooksPlugin {
+    on_http_request: Option<VrlProgram>,
+    on_subgraph_request: Option<VrlProgram>,
+}
+
+/// The `.context` of the hooks for a request
+struct VrlHooksContext(VrlValue);
+
+/// What a hook asked for, read from the target once the program ran
+struct HookOutcome {
+    header_changes: Vec<(HeaderName, Vec<HeaderValue>)>,
+    rejection: Option<(GraphQLError, StatusCode)>,
+}
+
+impl VrlHooksPlugin {
+    /// Returns `None` when no hook is configured.
+    pub fn from_config(config: &HooksConfig) -> Result<Option<Self>, VrlHookError> {
+        if config.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            on_http_request: compile(ON_HTTP_REQUEST, config.on_http_request.as_deref())?,
+            on_subgraph_request: compile(
+                ON_SUBGRAPH_REQUEST,
+                config.on_subgraph_request.as_deref(),
+            )?,
+        }))
+    }
+}
+
+fn compile(hook: &'static str, source: Option<&str>) -> Result<Option<VrlProgram>, VrlHookError> {
+    source
+        .map(|source| {
+            source
+                .compile_expression(None)
+                .map_err(|err| VrlHookError::Compile(hook, err))
+        })
+        .transpose()
+}
+
+/// Runs the program of a hook against `target`, with `.context` added to it.
+fn run_hook(
+    hook: &'static str,
+    program: &VrlProgram,
+    context: &PluginContext,
+    mut target: BTreeMap<KeyString, VrlValue>,
+) -> Result<HookOutcome, VrlHookError> {
+    let original_headers = request_headers(&target).cloned();
+    let hook_context = context
+        .get_ref::<VrlHooksContext>()
+        .map(|hook_context| hook_context.0.clone())
+        .unwrap_or_else(|| VrlValue::Object(BTreeMap::new()));
+    target.insert("context".into(), hook_context);
+
+    let mut target = VrlValue::Object(target);
+    program
+        .execute_in_place(&mut target)
+        .map_err(|err| VrlHookError::Execution(hook, err))?;
+
+    let VrlValue::Object(mut target) = target else {
+        return Ok(HookOutcome {
+            header_changes: Vec::new(),
+            rejection: None,
+        });
+    };
+
+    if let Some(hook_context) = target.remove("context") {
+        if let Some(mut current) = context.get_mut::<VrlHooksContext>() {
+            current.0 = hook_context;
+        } else {
+            context.insert(VrlHooksContext(hook_context));
+        }
+    }
+
+    Ok(HookOutcome {
+        header_changes: header_changes(hook, original_headers.as_ref(), request_headers(&target))?,
+        rejection: target.remove("reject").and_then(rejection),
+    })
+}
+
+fn request_headers(target: &BTreeMap<KeyString, VrlValue>) -> Option<&VrlValue> {
+    match target.get("request") {
+        Some(VrlValue::Object(request)) => request.get("headers"),
+        _ => None,
+    }
+}
+
+/// The headers to replace, compared to the ones the program started with.
+/// An empty list of values removes the header.
+fn header_changes(
+    hook: &'static str,
+    original: Option<&VrlValue>,
+    updated: Option<&VrlValue>,
+) -> Result<Vec<(HeaderName, Vec<HeaderValue>)>, VrlHookError> {
+    let (Some(VrlValue::Object(original)), Some(VrlValue::Object(updated))) = (original, updated)
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut changes = Vec::new();
+    for (name, value) in updated {
+        if original.get(name) == Some(value) {
+            continue;
+        }
+        changes.push((
+            parse_header_name(hook, name.as_str())?,
+            parse_header_values(hook, value)?,
+        ));
+    }
+    for name in original.keys() {
+        if !updated.contains_key(name) {
+            changes.push((parse_header_name(hook, name.as_str())?, Vec::new()));
+        }
+    }
+
+    changes.retain(|(name, _)| !HOP_BY_HOP_HEADERS.contains(&name.as_str()));
+    Ok(changes)
+}
+
+fn parse_header_name(hook: &'static str, name: &str) -> Result<HeaderName, VrlHookError> {
+    HeaderName::from_str(name).map_err(|err| VrlHookError::InvalidHeader(hook, err.to_string()))
+}
+
+fn parse_header_values(
+    hook: &'static str,
+    value: &VrlValue,
+) -> Result<Vec<HeaderValue>, VrlHookError> {
+    let values = match value {
+        VrlValue::Null => return Ok(Vec::new()),
+        VrlValue::Array(values) => values.clone(),
+        value => vec![value.clone()],
+    };
+
+    values
+        .into_iter()
+        .map(|value| {
+            HeaderValue::from_vrl_value(value)
+                .map_err(|err| VrlHookError::InvalidHeader(hook, err.to_string()))
+        })
+        .collect()
+}
+
+fn rejection(value: VrlValue) -> Option<(GraphQLError, StatusCode)> {
+    let (message, status, code) = match value {
+        VrlValue::Null | VrlValue::Boolean(false) => return None,
+        VrlValue::Object(mut object) => (
+            object.remove("message").and_then(vrl_string),
+            match object.remove("status") {
+                Some(VrlValue::Integer(status)) => u16::try_from(status)
+                    .ok()
+                    .and_then(|status| StatusCode::from_u16(status).ok()),
+                _ => None,
+            },
+            object.remove("code").and_then(vrl_string),
+        ),
+        value => (vrl_string(value), None, None),
+    };
+
+    Some((
+        GraphQLError::from_message_and_code(
+            message.unwrap_or_else(|| "Forbidden".to_string()),
+            code.unwrap_or_else(|| "REQUEST_REJECTED".to_string()),
+        ),
+        status.unwrap_or(StatusCode::FORBIDDEN),
+    ))
+}
+
+fn vrl_string(value: VrlValue) -> Option<String> {
+    match value {
+        VrlValue::Bytes(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+        _ => None,
+    }
+}
+
+fn failure_error() -> GraphQLError {
+    GraphQLError::from_message_and_code("Internal server error", "VRL_HOOK_FAILURE")
+}
+
+#[async_trait::async_trait]
+impl RouterPlugin for VrlHooksPlugin {
+    type Config = ();
+
+    fn plugin_name() -> &'static str {
+        "hooks"
+    }
+
+    fn on_plugin_init(_payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+        // The hooks are not part of the registry,
+        // they are compiled from `hooks` with `VrlHooksPlugin::from_config`.
+        Err("VRL hooks are configured in `hooks`".into())
+    }
+
+    fn on_http_request<'req>(
+        &'req self,
+        mut payload: OnHttpRequestHookPayload<'req>,
+    ) -> OnHttpRequestHookResult<'req> {
+        let Some(program) = &self.on_http_request else {
+            return payload.proceed();
+        };
+
+        let request = &payload.router_http_request;
+        let target = BTreeMap::from([(
+            "request".into(),
+            VrlValue::Object(BTreeMap::from([
+                ("method".into(), request.method().as_str().into()),
+                ("url".into(), request.uri().to_vrl_value()),
+                (
+                    "headers".into(),
+                    ntex_header_map_to_vrl_value(request.headers()),
+                ),
+            ])),
+        )]);
+
+        let outcome = match run_hook(ON_HTTP_REQUEST, program, payload.context, target) {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                error!(%err, "VRL hook failed");
+                return payload
+                    .end_with_graphql_error(failure_error(), StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        if let Some((error, status)) = outcome.rejection {
+            return payload.end_with_graphql_error(error, status);
+        }
+
+        let headers = payload.router_http_request.headers_mut();
+        for (name, values) in outcome.header_changes {
+            headers.remove(&name);
+            for value in values {
+                headers.append(name.clone(), value.into());
+            }
+        }
+
+        payload.proceed()
+    }
+
+    async fn on_subgraph_http_request<'exec>(
+        &'exec self,
+        mut payload: OnSubgraphHttpRequestHookPayload<'exec>,
+    ) -> OnSubgraphHttpRequestHookResult<'exec> {
+        let Some(program) = &self.on_subgraph_request else {
+            return payload.proceed();
+        };
+
+        let target = BTreeMap::from([
+            (
+                "subgraph".into(),
+                VrlValue::Object(BTreeMap::from([(
+                    "name".into(),
+                    payload.subgraph_name.into(),
+                )])),
+            ),
+            (
+                "request".into(),
+                VrlValue::Object(BTreeMap::from([
+                    ("method".into(), payload.method.as_str().into()),
+                    ("url".into(), payload.endpoint.to_vrl_value()),
+                    (
+                        "headers".into(),
+                        payload.execution_request.headers.to_vrl_value(),
+                    ),
+                ])),
+            ),
+        ]);
+
+        let outcome = match run_hook(ON_SUBGRAPH_REQUEST, program, payload.context, target) {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                error!(%err, subgraph_name = payload.subgraph_name, "VRL hook failed");
+                return payload
+                    .end_with_graphql_error(failure_error(), StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        if let Some((error, status)) = outcome.rejection {
+            return payload.end_with_graphql_error(error, status);
+        }
+
+        let headers = &mut payload.execution_request.headers;
+        for (name, values) in outcome.header_changes {
+            headers.remove(&name);
+            for value in values {
+                headers.append(name.clone(), value);
+            }
+        }
+
+        payload.proceed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use hive_router_internal::expressions::{
+        vrl::{core::Value as VrlValue, value::KeyString},
+        CompileExpression, ToVrlValue,
+    };
+    use hive_router_plan_executor::plugin_context::PluginContext;
+    use http::{HeaderMap, StatusCode};
+
+    use super::{run_hook, VrlHooksContext};
+
+    fn target_with_headers(headers: &HeaderMap) -> BTreeMap<KeyString, VrlValue> {
+        BTreeMap::from([(
+            "request".into(),
+            VrlValue::Object(BTreeMap::from([("headers".into(), headers.to_vrl_value())])),
+        )])
+    }
+
+    #[test]
+    fn applies_header_changes_and_keeps_the_context() {
+        let program = r#"
+            .request.headers."x-added" = "1"
+            del(.request.headers."x-removed")
+            .context.tenant = .request.headers."x-tenant"
+        "#
+        .compile_expression(None)
+        .expect("failed to compile program");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-removed", "1".parse().unwrap());
+        headers.insert("x-tenant", "acme".parse().unwrap());
+
+        let context = PluginContext::default();
+        let outcome = run_hook("test", &program, &context, target_with_headers(&headers))
+            .expect("failed to run hook");
+
+        let changes = outcome
+            .header_changes
+            .iter()
+            .map(|(name, values)| (name.as_str(), values.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(changes, vec![("x-added", 1), ("x-removed", 0)]);
+        assert!(outcome.rejection.is_none());
+
+        let hook_context = context.get_ref::<VrlHooksContext>().unwrap();
+        let VrlValue::Object(hook_context) = &hook_context.0 else {
+            panic!("expected the context to be an object");
+        };
+        assert_eq!(hook_context.get("tenant"), Some(&VrlValue::from("acme")));
+    }
+
+    #[test]
+    fn rejects_the_request() {
+        let program = r#".reject = { "status": 401, "message": "Missing API key" }"#
+            .compile_expression(None)
+            .expect("failed to compile program");
+
+        let context = PluginContext::default();
+        let outcome = run_hook(
+            "test",
+            &program,
+            &context,
+            target_with_headers(&HeaderMap::new()),
+        )
+        .expect("failed to run hook");
+
+        let (error, status) = outcome.rejection.expect("expected a rejection");
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(error.message, "Missing API key");
+    }
+}
@@ -0,0 +1,720 @@
+//! A host for plugins compiled to WebAssembly, loaded from the `.wasm` modules listed in `wasm_plugins`.
+//!
+//! Modules are run with [Extism](https://extism.org), so they can be written in any language
+//! with an Extism PDK. Every hook is an optional export of the module, called with a JSON input
+//! and returning a JSON output (an empty output proceeds without changes):
+//!
+//! | Export                     | Input                                                         |
+//! |----------------------------|---------------------------------------------------------------|
+//! | `on_plugin_init`           | the `config` of the plugin                                    |
+//! | `on_http_request`          | `method`, `path`, `query`, `headers`, `context`               |
+//! | `on_http_response`         | `status`, `headers`, `context`                                |
+//! | `on_graphql_params`        | `params` (`query`, `operationName`, `variables`, `extensions`), `headers`, `context` |
+//! | `on_execute`               | `operationName`, `variables`, `headers`, `context`            |
+//! | `on_subgraph_http_request` | `subgraph`, `method`, `url`, `headers`, `body`, `context`     |
+//!
+//! The output of a hook may contain:
+//! - `control`: `"continue"` (the default) or `{ "break": <status code> }` to end the request
+//!   with `errors` as GraphQL errors, or with `body` as JSON.
+//! - `headers`: replaces the headers of the request (or of the response in `on_http_response`),
+//!   as `{ "name": "value" }` or `{ "name": ["value1", "value2"] }`.
+//! - `context`: the state of the plugin for the request, passed to its following hooks.
+//! - `params`: replaces the GraphQL parameters, in `on_graphql_params`.
+//! - `errors` and `extensions`: added to the GraphQL response, in `on_execute`.
+//! - `body`: replaces the body of the subgraph request, in `on_subgraph_http_request`.
+
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+use extism::{Manifest, Plugin, Wasm};
+use hive_router_config::{headers::HOP_BY_HOP_HEADERS, wasm_plugins::WasmPluginConfig};
+use hive_router_plan_executor::{
+    coprocessor::protocol::CoprocessorControl,
+    executors::http::SubgraphHttpResponse,
+    hooks::{
+        on_execute::{OnExecuteStartHookPayload, OnExecuteStartHookResult},
+        on_graphql_params::{
+            GraphQLParams, OnGraphQLParamsStartHookPayload, OnGraphQLParamsStartHookResult,
+        },
+        on_http_request::{OnHttpRequestHookPayload, OnHttpRequestHookResult},
+        on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+        on_subgraph_http_request::{
+            OnSubgraphHttpRequestHookPayload, OnSubgraphHttpRequestHookResult,
+        },
+    },
+    plugin_context::PluginContext,
+    plugin_trait::{
+        from_graphql_errors_to_bytes, EarlyHTTPResponse, EndHookPayload, RouterPlugin,
+        StartHookPayload,
+    },
+    response::graphql_error::GraphQLError,
+};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+const ON_PLUGIN_INIT: &str = "on_plugin_init";
+const ON_HTTP_REQUEST: &str = "on_http_request";
+const ON_HTTP_RESPONSE: &str = "on_http_response";
+const ON_GRAPHQL_PARAMS: &str = "on_graphql_params";
+const ON_EXECUTE: &str = "on_execute";
+const ON_SUBGRAPH_HTTP_REQUEST: &str = "on_subgraph_http_request";
+
+#[derive(Debug, thiserror::Error)]
+pub enum WasmPluginError {
+    #[error("failed to load the module: {0}")]
+    Load(extism::Error),
+    #[error("'{0}' failed: {1}")]
+    Call(&'static str, extism::Error),
+    #[error("failed to serialize the input of '{0}': {1}")]
+    InvalidInput(&'static str, sonic_rs::Error),
+    #[error("'{0}' returned an invalid output: {1}")]
+    InvalidOutput(&'static str, sonic_rs::Error),
+    #[error("'{0}' returned an invalid header: {1}")]
+    InvalidHeader(&'static str, String),
+}
+
+pub struct WasmPlugin {
+    name: String,
+    instances: WasmInstances,
+    exports: WasmExports,
+}
+
+/// The hooks exported by the module, the others are not called at all.
+struct WasmExports {
+    on_http_request: bool,
+    on_http_response: bool,
+    on_graphql_params: bool,
+    on_execute: bool,
+    on_subgraph_http_request: bool,
+}
+
+/// Instances of a module. An instance runs one call at a time,
+/// so calls go to the first idle instance, or wait for one of them.
+struct WasmInstances {
+    instances: Vec<Mutex<Plugin>>,
+    next: AtomicUsize,
+}
+
+impl WasmInstances {
+    fn call(&self, export: &'static str, input: &[u8]) -> Result<Vec<u8>, WasmPluginError> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let len = self.instances.len();
+
+        let mut instance = (0..len)
+            .find_map(|offset| self.instances[(start + offset) % len].try_lock().ok())
+            .unwrap_or_else(|| {
+                self.instances[start % len]
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+            });
+
+        instance
+            .call::<&[u8], Vec<u8>>(export, input)
+            .map_err(|err| WasmPluginError::Call(export, err))
+    }
+}
+
+/// The state of the WebAssembly plugins for a request, by plugin name
+#[derive(Default)]
+struct WasmPluginsContext(HashMap<String, sonic_rs::Value>);
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct WasmHookOutput {
+    control: Option<CoprocessorControl>,
+    headers: Option<HashMap<String, WasmHeaderValues>>,
+    context: Option<sonic_rs::Value>,
+    params: Option<GraphQLParams>,
+    errors: Vec<GraphQLError>,
+    extensions: HashMap<String, sonic_rs::Value>,
+    body: Option<sonic_rs::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WasmHeaderValues {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl WasmHeaderValues {
+    fn iter(&self) -> impl Iterator<Item = &str> {
+        let values = match self {
+            WasmHeaderValues::One(value) => std::slice::from_ref(value),
+            WasmHeaderValues::Many(values) => values.as_slice(),
+        };
+        values.iter().map(String::as_str)
+    }
+}
+
+impl WasmHookOutput {
+    fn break_status(&self) -> Option<StatusCode> {
+        self.control
+            .as_ref()
+            .and_then(CoprocessorControl::break_status)
+    }
+
+    /// The body of the response ending the request, from `errors` or `body`
+    fn break_body(&mut self) -> Vec<u8> {
+        if !self.errors.is_empty() {
+            return from_graphql_errors_to_bytes(std::mem::take(&mut self.errors));
+        }
+        self.body
+            .as_ref()
+            .and_then(|body| sonic_rs::to_vec(body).ok())
+            .unwrap_or_default()
+    }
+
+    fn break_response(&mut self, status: StatusCode) -> ntex::http::Response {
+        let body = self.break_body();
+        if body.is_empty() {
+            return ntex::http::Response::build(status).finish();
+        }
+        ntex::http::Response::build(status)
+            .content_type("application/json")
+            .body(body)
+    }
+}
+
+type WasmHeadersJson<'a> = HashMap<&'a str, Vec<&'a str>>;
+
+fn headers_to_json<'a>(headers: impl Iterator<Item = (&'a str, &'a [u8])>) -> WasmHeadersJson<'a> {
+    let mut json: WasmHeadersJson = HashMap::new();
+    for (name, value) in headers {
+        if let Ok(value) = std::str::from_utf8(value) {
+            json.entry(name).or_default().push(value);
+        }
+    }
+    json
+}
+
+fn ntex_headers_to_json(headers: &ntex::http::HeaderMap) -> WasmHeadersJson<'_> {
+    headers_to_json(
+        headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes())),
+    )
+}
+
+fn http_headers_to_json(headers: &http::HeaderMap) -> WasmHeadersJson<'_> {
+    headers_to_json(
+        headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes())),
+    )
+}
+
+fn replace_ntex_headers(
+    export: &'static str,
+    headers: &mut ntex::http::HeaderMap,
+    values: &HashMap<String, WasmHeaderValues>,
+) -> Result<(), WasmPluginError> {
+    use ntex::http::header::{HeaderName, HeaderValue};
+
+    let mut parsed = Vec::with_capacity(values.len());
+    for (name, values) in values {
+        let name = HeaderName::from_str(name)
+            .map_err(|err| WasmPluginError::InvalidHeader(export, err.to_string()))?;
+        if HOP_BY_HOP_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        for value in values.iter() {
+            let value = HeaderValue::from_str(value)
+                .map_err(|err| WasmPluginError::InvalidHeader(export, err.to_string()))?;
+            parsed.push((name.clone(), value));
+        }
+    }
+
+    let keys_to_remove: Vec<_> = headers
+        .keys()
+        .filter(|name| !HOP_BY_HOP_HEADERS.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    for key in keys_to_remove {
+        headers.remove(&key);
+    }
+    for (name, value) in parsed {
+        headers.append(name, value);
+    }
+    Ok(())
+}
+
+fn replace_http_headers(
+    export: &'static str,
+    headers: &mut http::HeaderMap,
+    values: &HashMap<String, WasmHeaderValues>,
+) -> Result<(), WasmPluginError> {
+    use http::header::{HeaderName, HeaderValue};
+
+    let mut parsed = Vec::with_capacity(values.len());
+    for (name, values) in values {
+        let name = HeaderName::from_str(name)
+            .map_err(|err| WasmPluginError::InvalidHeader(export, err.to_string()))?;
+        if HOP_BY_HOP_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        for value in values.iter() {
+            let value = HeaderValue::from_str(value)
+                .map_err(|err| WasmPluginError::InvalidHeader(export, err.to_string()))?;
+            parsed.push((name.clone(), value));
+        }
+    }
+
+    let keys_to_remove: Vec<_> = headers
+        .keys()
+        .filter(|name| !HOP_BY_HOP_HEADERS.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    for key in keys_to_remove {
+        headers.remove(&key);
+    }
+    for (name, value) in parsed {
+        headers.append(name, value);
+    }
+    Ok(())
+}
+
+fn failure_error() -> GraphQLError {
+    GraphQLError::from_message_and_code("Internal server error", "WASM_PLUGIN_FAILURE")
+}
+
+#[derive(Serialize)]
+struct HttpRequestInput<'a> {
+    method: &'a str,
+    path: &'a str,
+    query: &'a str,
+    headers: WasmHeadersJson<'a>,
+    context: &'a sonic_rs::Value,
+}
+
+#[derive(Serialize)]
+struct HttpResponseInput<'a> {
+    status: u16,
+    headers: WasmHeadersJson<'a>,
+    context: &'a sonic_rs::Value,
+}
+
+#[derive(Serialize)]
+struct GraphQLParamsInput<'a> {
+    params: &'a GraphQLParams,
+    headers: WasmHeadersJson<'a>,
+    context: &'a sonic_rs::Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecuteInput<'a> {
+    operation_name: Option<&'a str>,
+    variables: Option<&'a HashMap<String, sonic_rs::Value>>,
+    headers: WasmHeadersJson<'a>,
+    context: &'a sonic_rs::Value,
+}
+
+#[derive(Serialize)]
+struct SubgraphHttpRequestInput<'a> {
+    subgraph: &'a str,
+    method: &'a str,
+    url: String,
+    headers: WasmHeadersJson<'a>,
+    body: sonic_rs::Value,
+    context: &'a sonic_rs::Value,
+}
+
+impl WasmPlugin {
+    pub fn load(config: &WasmPluginConfig) -> Result<Self, WasmPluginError> {
+        let manifest = Manifest::new([Wasm::file(&config.path.absolute)])
+            .with_timeout(config.timeout)
+            .with_allowed_hosts(config.allowed_hosts.iter().cloned());
+        let plugin_config = sonic_rs::to_vec(&config.config)
+            .map_err(|err| WasmPluginError::InvalidInput(ON_PLUGIN_INIT, err))?;
+
+        let mut instances = Vec::with_capacity(config.instances.max(1));
+        for _ in 0..config.instances.max(1) {
+            let mut instance = Plugin::new(&manifest, [], true).map_err(WasmPluginError::Load)?;
+            if instance.function_exists(ON_PLUGIN_INIT) {
+                instance
+                    .call::<&[u8], Vec<u8>>(ON_PLUGIN_INIT, &plugin_config)
+                    .map_err(|err| WasmPluginError::Call(ON_PLUGIN_INIT, err))?;
+            }
+            instances.push(Mutex::new(instance));
+        }
+
+        let exports = {
+            let instance = instances[0].lock().unwrap_or_else(PoisonError::into_inner);
+            WasmExports {
+                on_http_request: instance.function_exists(ON_HTTP_REQUEST),
+                on_http_response: instance.function_exists(ON_HTTP_RESPONSE),
+                on_graphql_params: instance.function_exists(ON_GRAPHQL_PARAMS),
+                on_execute: instance.function_exists(ON_EXECUTE),
+                on_subgraph_http_request: instance.function_exists(ON_SUBGRAPH_HTTP_REQUEST),
+            }
+        };
+
+        Ok(Self {
+            name: config.name.clone(),
+            instances: WasmInstances {
+                instances,
+                next: AtomicUsize::new(0),
+            },
+            exports,
+        })
+    }
+
+    fn call<I: Serialize>(
+        &self,
+        export: &'static str,
+        input: &I,
+    ) -> Result<WasmHookOutput, WasmPluginError> {
+        let input =
+            sonic_rs::to_vec(input).map_err(|err| WasmPluginError::InvalidInput(export, err))?;
+        let output = self.instances.call(export, &input)?;
+        if output.is_empty() {
+            return Ok(WasmHookOutput::default());
+        }
+        sonic_rs::from_slice(&output).map_err(|err| WasmPluginError::InvalidOutput(export, err))
+    }
+
+    fn context_of(&self, context: &PluginContext) -> sonic_rs::Value {
+        context
+            .get_ref::<WasmPluginsContext>()
+            .and_then(|plugins_context| plugins_context.0.get(&self.name).cloned())
+            .unwrap_or_default()
+    }
+
+    fn store_context(&self, context: &PluginContext, output: &mut WasmHookOutput) {
+        let Some(value) = output.context.take() else {
+            return;
+        };
+        if let Some(mut plugins_context) = context.get_mut::<WasmPluginsContext>() {
+            plugins_context.0.insert(self.name.clone(), value);
+            return;
+        }
+        let mut plugins_context = WasmPluginsContext::default();
+        plugins_context.0.insert(self.name.clone(), value);
+        context.insert(plugins_context);
+    }
+
+    fn log_failure(&self, err: &WasmPluginError) {
+        error!(%err, plugin = %self.name, "WebAssembly plugin failed");
+    }
+}
+
+#[async_trait::async_trait]
+impl RouterPlugin for WasmPlugin {
+    type Config = ();
+
+    fn plugin_name() -> &'static str {
+        "wasm"
+    }
+
+    fn on_plugin_init(_payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+        // WebAssembly plugins are not part of the registry,
+        // they are loaded from `wasm_plugins` with `WasmPlugin::load`.
+        Err("WebAssembly plugins are configured in `wasm_plugins`".into())
+    }
+
+    fn on_http_request<'req>(
+        &'req self,
+        mut payload: OnHttpRequestHookPayload<'req>,
+    ) -> OnHttpRequestHookResult<'req> {
+        if self.exports.on_http_request {
+            let output = {
+                let context = self.context_of(payload.context);
+                let request = &payload.router_http_request;
+                let input = HttpRequestInput {
+                    method: request.method().as_str(),
+                    path: request.path(),
+                    query: request.query_string(),
+                    headers: ntex_headers_to_json(request.headers()),
+                    context: &context,
+                };
+                self.call(ON_HTTP_REQUEST, &input)
+            };
+
+            let output = output.and_then(|mut output| {
+                if let Some(headers) = output.headers.take() {
+                    replace_ntex_headers(
+                        ON_HTTP_REQUEST,
+                        payload.router_http_request.headers_mut(),
+                        &headers,
+                    )?;
+                }
+                Ok(output)
+            });
+            let mut output = match output {
+                Ok(output) => output,
+                Err(err) => {
+                    self.log_failure(&err);
+                    return payload.end_with_graphql_error(
+                        failure_error(),
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    );
+                }
+            };
+
+            self.store_context(payload.context, &mut output);
+            if let Some(status) = output.break_status() {
+                let response = output.break_response(status);
+                return payload.end_with_response(response);
+            }
+        }
+
+        if !self.exports.on_http_response {
+            return payload.proceed();
+        }
+
+        payload.on_end(move |mut payload| {
+            let output = {
+                let context = self.context_of(payload.context);
+                let input = HttpResponseInput {
+                    status: payload.response.status().as_u16(),
+                    headers: ntex_headers_to_json(payload.response.headers()),
+                    context: &context,
+                };
+                self.call(ON_HTTP_RESPONSE, &input)
+            };
+
+            let output = output.and_then(|mut output| {
+                if let Some(headers) = output.headers.take() {
+                    replace_ntex_headers(
+                        ON_HTTP_RESPONSE,
+                        payload.response.response_mut().headers_mut(),
+                        &headers,
+                    )?;
+                }
+                Ok(output)
+            });
+            match output {
+                Ok(mut output) => {
+                    self.store_context(payload.context, &mut output);
+                    payload.proceed()
+                }
+                Err(err) => {
+                    self.log_failure(&err);
+                    payload
+                        .end_with_graphql_error(failure_error(), StatusCode::INTERNAL_SERVER_ERROR)
+                }
+            }
+        })
+    }
+
+    async fn on_graphql_params<'exec>(
+        &'exec self,
+        payload: OnGraphQLParamsStartHookPayload<'exec>,
+    ) -> OnGraphQLParamsStartHookResult<'exec> {
+        if !self.exports.on_graphql_params {
+            return payload.proceed();
+        }
+
+        // The parameters are parsed after the start of the hook
+        let request_headers = payload.router_http_request.headers;
+        payload.on_end(move |mut payload| {
+            let output = {
+                let context = self.context_of(payload.context);
+                let input = GraphQLParamsInput {
+                    params: &payload.graphql_params,
+                    headers: ntex_headers_to_json(request_headers),
+                    context: &context,
+                };
+                self.call(ON_GRAPHQL_PARAMS, &input)
+            };
+            let mut output = match output {
+                Ok(output) => output,
+                Err(err) => {
+                    self.log_failure(&err);
+                    return payload.end_with_graphql_error(
+                        failure_error(),
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    );
+                }
+            };
+
+            self.store_context(payload.context, &mut output);
+            if let Some(status) = output.break_status() {
+                let response = output.break_response(status);
+                return payload.end_with_response(response);
+            }
+            if let Some(params) = output.params.take() {
+                payload.graphql_params = params;
+            }
+            payload.proceed()
+        })
+    }
+
+    async fn on_execute<'exec>(
+        &'exec self,
+        mut payload: OnExecuteStartHookPayload<'exec>,
+    ) -> OnExecuteStartHookResult<'exec> {
+        if !self.exports.on_execute {
+            return payload.proceed();
+        }
+
+        let output = {
+            let context = self.context_of(payload.context);
+            let input = ExecuteInput {
+                operation_name: payload.operation_for_plan.name.as_deref(),
+                variables: payload.variable_values.as_ref(),
+                headers: ntex_headers_to_json(payload.router_http_request.headers),
+                context: &context,
+            };
+            self.call(ON_EXECUTE, &input)
+        };
+        let mut output = match output {
+            Ok(output) => output,
+            Err(err) => {
+                self.log_failure(&err);
+                return payload
+                    .end_with_graphql_error(failure_error(), StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        self.store_context(payload.context, &mut output);
+        if let Some(status_code) = output.break_status() {
+            return payload.end_with_response(EarlyHTTPResponse {
+                body: output.break_body(),
+                headers: Default::default(),
+                status_code,
+            });
+        }
+        for error in output.errors.drain(..) {
+            payload.add_error(error);
+        }
+        for (key, value) in output.extensions.drain() {
+            payload.extensions.insert(key, value);
+        }
+        payload.proceed()
+    }
+
+    async fn on_subgraph_http_request<'exec>(
+        &'exec self,
+        mut payload: OnSubgraphHttpRequestHookPayload<'exec>,
+    ) -> OnSubgraphHttpRequestHookResult<'exec> {
+        if !self.exports.on_subgraph_http_request {
+            return payload.proceed();
+        }
+
+        let output = {
+            let context = self.context_of(payload.context);
+            let input = SubgraphHttpRequestInput {
+                subgraph: payload.subgraph_name,
+                method: payload.method.as_str(),
+                url: payload.endpoint.to_string(),
+                headers: http_headers_to_json(&payload.execution_request.headers),
+                body: sonic_rs::from_slice(&payload.body).unwrap_or_default(),
+                context: &context,
+            };
+            self.call(ON_SUBGRAPH_HTTP_REQUEST, &input)
+        };
+
+        let output = output.and_then(|mut output| {
+            if let Some(headers) = output.headers.take() {
+                replace_http_headers(
+                    ON_SUBGRAPH_HTTP_REQUEST,
+                    &mut payload.execution_request.headers,
+                    &headers,
+                )?;
+            }
+            Ok(output)
+        });
+        let mut output = match output {
+            Ok(output) => output,
+            Err(err) => {
+                self.log_failure(&err);
+                return payload
+                    .end_with_graphql_error(failure_error(), StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        self.store_context(payload.context, &mut output);
+        if let Some(status) = output.break_status() {
+            return payload.end_with_response(SubgraphHttpResponse {
+                status,
+                body: output.break_body().into(),
+                ..Default::default()
+            });
+        }
+        if let Some(body) = output.body.as_ref() {
+            match sonic_rs::to_vec(body) {
+                Ok(body) => payload.body = body,
+                Err(err) => {
+                    self.log_failure(&WasmPluginError::InvalidOutput(
+                        ON_SUBGRAPH_HTTP_REQUEST,
+                        err,
+                    ));
+                    return payload.end_with_graphql_error(
+                        failure_error(),
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    );
+                }
+            }
+        }
+        payload.proceed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use http::{HeaderMap, StatusCode};
+
+    use super::{replace_http_headers, WasmHeaderValues, WasmHookOutput};
+
+    #[test]
+    fn parses_the_output_of_a_hook() {
+        let mut output: WasmHookOutput = sonic_rs::from_str(
+            r#"{
+                "control": { "break": 403 },
+                "errors": [{ "message": "Forbidden", "extensions": { "code": "FORBIDDEN" } }],
+                "context": { "user": "1" }
+            }"#,
+        )
+        .expect("failed to parse output");
+
+        assert_eq!(output.break_status(), Some(StatusCode::FORBIDDEN));
+        assert_eq!(
+            String::from_utf8(output.break_body()).unwrap(),
+            r#"{"errors":[{"message":"Forbidden","extensions":{"code":"FORBIDDEN"}}]}"#
+        );
+
+        let output: WasmHookOutput = sonic_rs::from_str("{}").expect("failed to parse output");
+        assert_eq!(output.break_status(), None);
+    }
+
+    #[test]
+    fn replaces_headers_except_hop_by_hop_ones() {
+        let mut headers = HeaderMap::new();
+        headers.insert("connection", "keep-alive".parse().unwrap());
+        headers.insert("x-removed", "1".parse().unwrap());
+        headers.insert("x-kept", "1".parse().unwrap());
+
+        let values = HashMap::from([
+            ("x-kept".to_string(), WasmHeaderValues::One("2".into())),
+            (
+                "x-added".to_string(),
+                WasmHeaderValues::Many(vec!["a".into(), "b".into()]),
+            ),
+        ]);
+        replace_http_headers("on_subgraph_http_request", &mut headers, &values)
+            .expect("failed to replace headers");
+
+        assert_eq!(headers.get("connection").unwrap(), "keep-alive");
+        assert_eq!(headers.get("x-removed"), None);
+        assert_eq!(headers.get("x-kept").unwrap(), "2");
+        assert_eq!(
+            headers
+                .get_all("x-added")
+                .iter()
+                .map(|value| value.to_str().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+    }
+}
@@ -10,7 +10,9 @@ use hive_router_config::{supergraph::SupergraphSource, HiveRouterConfig};
 use hive_router_internal::authorization::metadata::AuthorizationMetadata;
 use hive_router_internal::background_tasks::{BackgroundTask, BackgroundTasksManager};
 use hive_router_internal::telemetry::{metrics::Metrics, TelemetryContext};
+use hive_router_plan_executor::execution::entity_cache::EntityCache;
 use hive_router_plan_executor::execution::operation_name::OperationNameForwardConfig;
+use hive_router_plan_executor::executors::connector::SupergraphConnectors;
 use hive_router_plan_executor::executors::http_callback::{
     CallbackMessage, CallbackSubscriptionsMap,
 };
@@ -21,6 +23,7 @@ use hive_router_plan_executor::{
         OnSupergraphLoadEndHookPayload, OnSupergraphLoadStartHookPayload, Supergraph,
         SupergraphBuildError, SupergraphSnapshot,
     },
+    plugin_context::PluginContext,
     plugin_trait::{EndControlFlow, RouterPluginBoxed, StartControlFlow},
     response::graphql_error::GraphQLError,
     SubgraphExecutorMap,
@@ -30,11 +33,13 @@ use hive_router_query_planner::{
 };
 use moka::future::Cache;
 use ntex::web::HttpRequest;
+use sha2::{Digest, Sha256};
 use std::collections::hash_map;
 use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use tokio::sync::mpsc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, trace};
 
@@ -43,6 +48,13 @@ use crate::{
     pipeline::authorization::AuthorizationMetadataExt,
     pipeline::demand_control::runtime::DemandControlRuntime,
     pipeline::normalize::GraphQLNormalizationPayload,
+    pipeline::persisted_documents::resolve::PersistedDocumentResolver,
+    pipeline::progressive_override::RequestOverrideContext,
+    pipeline::response_cache::{hints::CacheControlHints, surrogate_keys::EntityKeys},
+    plan_cache::{
+        build_plan_cache, persist_operations, read_persisted_operations, resolve_warmup_operations,
+        warm_up_plan_cache,
+    },
     supergraph::{
         base::{LoadSupergraphError, ReloadSupergraphResult, SupergraphLoader},
         resolve_from_config,
@@ -70,7 +82,7 @@ pub enum RouterSupergraphRuntimeError {
 /// router runtime rather than in the executor crate.
 ///
 /// The runtime also holds the schema-dependent caches for validation, normalization, planning,
-/// and demand-control formulas. Evicting the runtime drops those caches once active users release it.
+/// demand-control formulas and entities. Evicting the runtime drops those caches once active users release it.
 pub struct RouterSupergraphRuntime {
     pub subgraph_executor_map: Arc<SubgraphExecutorMap>,
     pub operation_name_forward_config: Arc<OperationNameForwardConfig>,
@@ -79,6 +91,13 @@ pub struct RouterSupergraphRuntime {
     pub normalize_cache: Cache<u64, Arc<GraphQLNormalizationPayload>>,
     pub plan_cache: Cache<u64, Arc<QueryPlan>>,
     pub demand_control_runtime: Option<DemandControlRuntime>,
+    /// The `@cacheControl` hints of the supergraph, when the response cache is enabled.
+    pub cache_control_hints: Option<CacheControlHints>,
+    /// The keys of the entity types, when the response cache tags responses with surrogate keys,
+    /// or when mutations invalidate the entity cache.
+    pub entity_keys: Option<EntityKeys>,
+    /// The cache of the entities resolved by subgraphs, when the entity cache is enabled.
+    pub entity_cache: Option<Arc<EntityCache>>,
 }
 
 impl RouterSupergraphRuntime {
@@ -88,12 +107,17 @@ impl RouterSupergraphRuntime {
         telemetry_context: &Arc<TelemetryContext>,
         callback_subscriptions: &CallbackSubscriptionsMap,
     ) -> Result<Self, RouterSupergraphRuntimeError> {
-        let subgraph_executor_map = Arc::new(SubgraphExecutorMap::from_http_endpoint_map(
+        let subgraph_executor_map = SubgraphExecutorMap::from_http_endpoint_map(
             &snapshot.planner.supergraph.subgraph_endpoint_map,
             router_config.clone(),
             telemetry_context.clone(),
             callback_subscriptions.clone(),
-        )?);
+        )?;
+        subgraph_executor_map.register_connectors(SupergraphConnectors::from_supergraph(
+            &snapshot.supergraph_schema,
+            &snapshot.planner.supergraph,
+        )?)?;
+        let subgraph_executor_map = Arc::new(subgraph_executor_map);
         let operation_name_forward_config = Arc::new(OperationNameForwardConfig::new(
             &router_config.traffic_shaping,
             snapshot.planner.supergraph.known_subgraphs.values(),
@@ -104,14 +128,32 @@ impl RouterSupergraphRuntime {
             router_config.demand_control.as_ref(),
             telemetry_context.metrics.clone(),
         );
+        let cache_control_hints = router_config
+            .response_cache
+            .enabled
+            .then(|| CacheControlHints::from_supergraph(&snapshot.supergraph_schema));
+        let entity_keys = ((router_config.response_cache.enabled
+            && router_config.response_cache.surrogate_keys)
+            || (router_config.entity_cache.enabled
+                && router_config.entity_cache.invalidate_on_mutation))
+            .then(|| EntityKeys::from_supergraph(&snapshot.planner.supergraph));
+        let entity_cache = router_config.entity_cache.enabled.then(|| {
+            Arc::new(EntityCache::new(
+                &router_config.entity_cache,
+                telemetry_context.metrics.cache.entity.clone(),
+            ))
+        });
         Ok(Self {
             subgraph_executor_map,
             operation_name_forward_config,
             authorization,
             validate_cache: Cache::new(1000),
             normalize_cache: Cache::new(1000),
-            plan_cache: Cache::new(1000),
+            plan_cache: build_plan_cache(&router_config.query_planner.cache),
             demand_control_runtime,
+            cache_control_hints,
+            entity_keys,
+            entity_cache,
         })
     }
 }
@@ -149,10 +191,135 @@ impl From<&ConfiguredSupergraph> for SelectedSupergraph {
 
 const RUNTIME_CACHE_MAX_SIZE: usize = 10;
 
+/// A supergraph SDL to build and publish as the configured supergraph,
+/// either loaded from the configured source, or pushed to the admin endpoint.
+pub struct SupergraphUpdate {
+    pub sdl: String,
+    /// Notified once the supergraph is published or rejected, when the sender awaits the outcome.
+    pub outcome: Option<oneshot::Sender<Result<(), String>>>,
+}
+
+impl SupergraphUpdate {
+    fn notify(self, outcome: Result<(), String>) {
+        if let Some(sender) = self.outcome {
+            // the receiver is gone when the client disconnected before the end of the update
+            sender.send(outcome).ok();
+        }
+    }
+}
+
+/// The SDL of the currently published configured supergraph, and of the one before it.
+#[derive(Default)]
+struct SupergraphHistory {
+    current: Option<String>,
+    previous: Option<String>,
+    published: Option<PublishedSupergraph>,
+}
+
+/// The currently published configured supergraph.
+#[derive(Debug, Clone)]
+pub struct PublishedSupergraph {
+    /// The SHA-256 digest of the supergraph SDL, hex encoded.
+    pub hash: String,
+    /// When the supergraph was published, in seconds since the Unix epoch.
+    pub published_at: u64,
+}
+
+/// How many rejected updates of the configured supergraph are kept for the diagnostics endpoint.
+const RECENT_RELOAD_ERRORS_MAX_SIZE: usize = 10;
+
+/// The outcomes of the updates of the configured supergraph.
+#[derive(Default)]
+struct SupergraphReloadLog {
+    // `None` until the first update of the configured supergraph is processed
+    last: ArcSwap<Option<SupergraphReloadStatus>>,
+    // the most recent rejected updates, oldest first
+    recent_errors: Mutex<VecDeque<SupergraphReloadStatus>>,
+}
+
+impl SupergraphReloadLog {
+    fn record(&self, status: SupergraphReloadStatus) {
+        if status.error.is_some() {
+            let mut recent_errors = self.recent_errors.lock().unwrap();
+            if recent_errors.len() == RECENT_RELOAD_ERRORS_MAX_SIZE {
+                recent_errors.pop_front();
+            }
+            recent_errors.push_back(status.clone());
+        }
+        self.last.store(Arc::new(Some(status)));
+    }
+}
+
+/// The outcome of the last update of the configured supergraph,
+/// either loaded from the supergraph source or pushed through the admin endpoint.
+#[derive(Debug, Clone)]
+pub struct SupergraphReloadStatus {
+    /// Why the supergraph was rejected, `None` when it was published.
+    /// A rejected supergraph leaves the previous one in place.
+    pub error: Option<String>,
+    /// When the update was processed, in seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl SupergraphReloadStatus {
+    fn now(error: Option<String>) -> Self {
+        Self {
+            error,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SupergraphPushError {
+    #[error("the supergraph can't be replaced when 'supergraph.source' is 'plugin'")]
+    UnsupportedSource,
+    #[error("no previous supergraph to roll back to")]
+    NoPreviousSupergraph,
+    #[error("the supergraph was rejected: {0}")]
+    Rejected(String),
+    #[error("the supergraph reload task is not running")]
+    ReloadTaskUnavailable,
+}
+
+/// The outcome of a refresh of the configured supergraph, requested with
+/// [`SchemaState::refresh_supergraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupergraphRefreshOutcome {
+    /// The supergraph source holds the published supergraph.
+    Unchanged,
+    /// The supergraph source held a new supergraph, now published.
+    Updated,
+    /// The supergraph source held a new supergraph, rejected.
+    Rejected(String),
+    /// The supergraph source could not be reached.
+    LoadFailed(String),
+}
+
+impl std::fmt::Display for SupergraphRefreshOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unchanged => write!(f, "unchanged"),
+            Self::Updated => write!(f, "updated"),
+            Self::Rejected(error) => write!(f, "rejected: {error}"),
+            Self::LoadFailed(error) => write!(f, "failed to load: {error}"),
+        }
+    }
+}
+
+type SupergraphRefreshRequest = oneshot::Sender<SupergraphRefreshOutcome>;
+
 type RouterSupergraphRuntimeCache = Mutex<VecDeque<(u64, Arc<RouterSupergraphRuntime>)>>;
 
 pub struct SchemaState {
-    router_config: Arc<HiveRouterConfig>,
+    // swapped when the config is reloaded, read by the reload task for every update
+    router_config: Arc<ArcSwap<HiveRouterConfig>>,
+    // held while the configured supergraph is replaced, for a reload of the config
+    // and an update of the supergraph to never build the configured runtime concurrently
+    configured_update_lock: Arc<tokio::sync::Mutex<()>>,
     /// The supergraph configured through the router config that can be loaded (and polled)
     ///   - `Some` when the router's configured supergraph is available and has been loaded
     ///   - sometimes `None` when the supergraph is being fetched and built
@@ -166,8 +333,18 @@ pub struct SchemaState {
     // when the runtime cache cleanup task hasn't been registered (e.g. in tests constructing
     // `SchemaState` directly).
     runtime_cache_cleanup: Option<mpsc::UnboundedSender<RuntimeCacheCleanupMessage>>,
+    // sender half of the reload task, `None` when the router is configured with
+    // `supergraph.source: plugin`, as there is no configured supergraph to replace
+    supergraph_updates: Option<mpsc::Sender<SupergraphUpdate>>,
+    // sender half of the requests to poll the supergraph source right away, `None` when the
+    // router is configured with `supergraph.source: plugin`
+    supergraph_refresh: Option<mpsc::Sender<SupergraphRefreshRequest>>,
+    supergraph_history: Arc<Mutex<SupergraphHistory>>,
+    reload_log: Arc<SupergraphReloadLog>,
     pub telemetry_context: Arc<TelemetryContext>,
     pub callback_subscriptions: CallbackSubscriptionsMap,
+    // set once the router received a shutdown signal, for the readiness probe to fail while it drains
+    shutting_down: AtomicBool,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -216,6 +393,7 @@ impl SchemaState {
             };
 
             req.extensions_mut().insert(selected.clone());
+            link_supergraph_context(req, &selected);
 
             return Ok(Some(selected));
         }
@@ -231,6 +409,7 @@ impl SchemaState {
 
         if let Some(selected) = &selected {
             req.extensions_mut().insert(selected.clone());
+            link_supergraph_context(req, selected);
         }
 
         Ok(selected)
@@ -246,6 +425,16 @@ impl SchemaState {
             .map(|configured| configured.runtime.clone())
     }
 
+    /// Returns the snapshot of the router's currently configured supergraph, if any (`None` for
+    /// `supergraph.source: plugin`, or before the first supergraph has loaded).
+    pub fn configured_snapshot(&self) -> Option<SupergraphSnapshot> {
+        self.configured
+            .load()
+            .as_ref()
+            .as_ref()
+            .map(|configured| configured.snapshot.clone())
+    }
+
     /// Calls `f` for every runtime currently alive: every plugin-selected runtime still sitting
     /// in the bounded FIFO cache, plus the configured default (if any).
     pub fn for_each_runtime(&self, mut f: impl FnMut(&RouterSupergraphRuntime)) {
@@ -274,7 +463,7 @@ impl SchemaState {
 
         let runtime = Arc::new(RouterSupergraphRuntime::build(
             snapshot,
-            &self.router_config,
+            &self.router_config.load_full(),
             &self.telemetry_context,
             &self.callback_subscriptions,
         )?);
@@ -315,10 +504,142 @@ impl SchemaState {
         Ok(runtime)
     }
 
+    /// Builds the given supergraph and publishes it as the configured supergraph,
+    /// once every in-progress update is done.
+    pub async fn push_supergraph(&self, sdl: String) -> Result<(), SupergraphPushError> {
+        let sender = self
+            .supergraph_updates
+            .as_ref()
+            .ok_or(SupergraphPushError::UnsupportedSource)?;
+
+        let (outcome_tx, outcome_rx) = oneshot::channel();
+        sender
+            .send(SupergraphUpdate {
+                sdl,
+                outcome: Some(outcome_tx),
+            })
+            .await
+            .map_err(|_| SupergraphPushError::ReloadTaskUnavailable)?;
+
+        outcome_rx
+            .await
+            .map_err(|_| SupergraphPushError::ReloadTaskUnavailable)?
+            .map_err(SupergraphPushError::Rejected)
+    }
+
+    /// Publishes again the configured supergraph published before the current one.
+    /// Rolling back twice restores the current supergraph.
+    pub async fn rollback_supergraph(&self) -> Result<(), SupergraphPushError> {
+        if self.supergraph_updates.is_none() {
+            return Err(SupergraphPushError::UnsupportedSource);
+        }
+
+        let previous_sdl = self
+            .supergraph_history
+            .lock()
+            .unwrap()
+            .previous
+            .clone()
+            .ok_or(SupergraphPushError::NoPreviousSupergraph)?;
+
+        self.push_supergraph(previous_sdl).await
+    }
+
+    /// Polls the supergraph source right away, regardless of its poll interval,
+    /// and publishes the supergraph it holds when it changed.
+    pub async fn refresh_supergraph(
+        &self,
+    ) -> Result<SupergraphRefreshOutcome, SupergraphPushError> {
+        let sender = self
+            .supergraph_refresh
+            .as_ref()
+            .ok_or(SupergraphPushError::UnsupportedSource)?;
+
+        let (outcome_tx, outcome_rx) = oneshot::channel();
+        sender
+            .send(outcome_tx)
+            .await
+            .map_err(|_| SupergraphPushError::ReloadTaskUnavailable)?;
+
+        outcome_rx
+            .await
+            .map_err(|_| SupergraphPushError::ReloadTaskUnavailable)
+    }
+
+    /// Returns the outcome of the last update of the configured supergraph, if any.
+    pub fn last_reload_status(&self) -> Option<SupergraphReloadStatus> {
+        self.reload_log.last.load().as_ref().clone()
+    }
+
+    /// Returns the most recent rejected updates of the configured supergraph, oldest first.
+    pub fn recent_reload_errors(&self) -> Vec<SupergraphReloadStatus> {
+        self.reload_log
+            .recent_errors
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the currently published configured supergraph, if any (`None` for
+    /// `supergraph.source: plugin`, or before the first supergraph has loaded).
+    pub fn published_supergraph(&self) -> Option<PublishedSupergraph> {
+        self.supergraph_history.lock().unwrap().published.clone()
+    }
+
+    /// Writes the operations of the cached query plans of the configured supergraph to a file,
+    /// when `query_planner.cache.persistence` is set.
+    pub fn persist_plan_cache(&self) {
+        let router_config = self.router_config.load();
+        let Some(persistence) = &router_config.query_planner.cache.persistence else {
+            return;
+        };
+
+        if let Some(runtime) = self.configured_runtime() {
+            persist_operations(&runtime, &persistence.path);
+        }
+    }
+
+    /// Rebuilds the runtime of the configured supergraph with the given config, for its subgraph
+    /// executors to apply it, and drops the cached runtimes of the plugin-selected supergraphs.
+    /// The configured supergraph itself is kept, along with the subscriptions selected from it.
+    pub async fn apply_router_config(
+        &self,
+        router_config: Arc<HiveRouterConfig>,
+    ) -> Result<(), RouterSupergraphRuntimeError> {
+        let _update_guard = self.configured_update_lock.lock().await;
+
+        let configured = self.configured.load_full();
+        if let Some(current) = configured.as_ref() {
+            let runtime = RouterSupergraphRuntime::build(
+                &current.snapshot,
+                &router_config,
+                &self.telemetry_context,
+                &self.callback_subscriptions,
+            )?;
+            self.configured.store(Arc::new(Some(ConfiguredSupergraph {
+                _owner: current._owner.clone(),
+                snapshot: current.snapshot.clone(),
+                runtime: Arc::new(runtime),
+            })));
+        }
+
+        self.router_config.store(router_config);
+        self.runtime_cache.lock().unwrap().clear();
+        Ok(())
+    }
+
     /// Returns true if the router is ready to serve requests, i.e. if a supergraph is available for
-    /// the request (either plugin-selected or configured default).
+    /// the request (either plugin-selected or configured default), and the router is not shutting down.
     pub fn is_ready(&self, req: &HttpRequest) -> bool {
-        matches!(self.select_supergraph(req), Ok(Some(selected)) if !selected.snapshot.is_retired())
+        !self.shutting_down.load(Ordering::Relaxed)
+            && matches!(self.select_supergraph(req), Ok(Some(selected)) if !selected.snapshot.is_retired())
+    }
+
+    /// Marks the router as shutting down, for the readiness probe to take it out of the load balancer.
+    pub fn begin_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::Relaxed);
     }
 
     pub async fn new_from_config(
@@ -328,6 +649,7 @@ impl SchemaState {
         plugins: Option<Arc<Vec<RouterPluginBoxed>>>,
         active_subscriptions: ActiveSubscriptions,
         storage_manager: Arc<StorageManager>,
+        persisted_document_resolver: Option<Arc<dyn PersistedDocumentResolver>>,
     ) -> Result<Self, SupergraphManagerError> {
         let configured: Arc<ArcSwap<Option<ConfiguredSupergraph>>> =
             Arc::new(ArcSwap::from(Arc::new(None)));
@@ -337,15 +659,25 @@ impl SchemaState {
         // the heartbeat enforcer below watches it too. building a runtime with a *different* map
         // would silently break callback routing and heartbeat enforcement for it
         let callback_subscriptions: CallbackSubscriptionsMap = Arc::new(DashMap::new());
+        let supergraph_history: Arc<Mutex<SupergraphHistory>> = Default::default();
+        let mut supergraph_updates = None;
+        let mut supergraph_refresh = None;
+        let reload_log: Arc<SupergraphReloadLog> = Default::default();
+        let router_config_swap = Arc::new(ArcSwap::new(router_config.clone()));
+        let configured_update_lock: Arc<tokio::sync::Mutex<()>> = Default::default();
 
         // `supergraph.source: plugin` has no configured source at all... no loader, no polling
         // task, no configured-default value. a plugin must select a supergraph for every request
         // that needs one and the plugin author is responsible for maintaining the supergraphs
         if !matches!(router_config.supergraph, SupergraphSource::Plugin) {
-            let (tx, mut rx) = mpsc::channel::<String>(1);
+            let (tx, mut rx) = mpsc::channel::<SupergraphUpdate>(1);
+            supergraph_updates = Some(tx.clone());
+            let (refresh_tx, refresh_rx) = mpsc::channel::<SupergraphRefreshRequest>(1);
+            supergraph_refresh = Some(refresh_tx);
             let background_loader = SupergraphBackgroundLoader::new(
                 &router_config.supergraph,
                 tx,
+                refresh_rx,
                 telemetry_context.metrics.clone(),
                 storage_manager.clone(),
             )?;
@@ -353,21 +685,40 @@ impl SchemaState {
                 .register_task(SupergraphBackgroundLoaderTask(Arc::new(background_loader)));
 
             let configured_spawn_clone = configured.clone();
-            let router_config_for_task = router_config.clone();
+            let router_config_swap_for_task = router_config_swap.clone();
+            let configured_update_lock_for_task = configured_update_lock.clone();
             let task_telemetry = telemetry_context.clone();
             let callback_subscriptions_for_reload = callback_subscriptions.clone();
+            let supergraph_history_for_reload = supergraph_history.clone();
+            let reload_log_for_task = reload_log.clone();
+            // operations of the plans cached before the last shutdown, planned for the first supergraph only
+            let mut persisted_operations = router_config
+                .query_planner
+                .cache
+                .persistence
+                .as_ref()
+                .map(|persistence| read_persisted_operations(&persistence.path))
+                .unwrap_or_default();
+            let warmup_override_context =
+                RequestOverrideContext::from_static_labels(&router_config.override_labels);
 
             bg_tasks_manager.register_handle(async move {
                 let supergraph_metrics = &task_telemetry.metrics.supergraph;
-                while let Some(new_sdl) = rx.recv().await {
+                while let Some(mut update) = rx.recv().await {
+                    let _update_guard = configured_update_lock_for_task.lock().await;
+                    let router_config_for_task = router_config_swap_for_task.load_full();
                     let process_capture = supergraph_metrics.capture_process();
                     debug!("Received new supergraph SDL, building new supergraph state...");
 
-                    let mut new_ast = match safe_parse_schema(&new_sdl) {
+                    let mut new_ast = match safe_parse_schema(&update.sdl) {
                         Ok(ast) => ast,
                         Err(e) => {
                             process_capture.finish_error();
-                            error!(error = %e, "Failed to parse supergraph during update");
+                            error!(error = %e, "Failed to parse supergraph during update, keeping the current supergraph");
+                            let error = format!("failed to parse supergraph: {e}");
+                            reload_log_for_task
+                                .record(SupergraphReloadStatus::now(Some(error.clone())));
+                            update.notify(Err(error));
                             continue;
                         }
                     };
@@ -409,6 +760,12 @@ impl SchemaState {
                             experimental_abstract_type_folding: router_config_for_task
                                 .query_planner
                                 .experimental_abstract_type_folding,
+                            contract: router_config_for_task.contract.as_ref().map(|contract| {
+                                hive_router_query_planner::consumer_schema::contract::Contract {
+                                    include_tags: contract.include_tags.clone(),
+                                    exclude_tags: contract.exclude_tags.clone(),
+                                }
+                            }),
                         };
 
                     let built = new_supergraph
@@ -460,6 +817,22 @@ impl SchemaState {
 
                     match built {
                         Ok(new_configured) => {
+                            // plan the warmup operations before the supergraph serves requests
+                            let mut warmup_operations = resolve_warmup_operations(
+                                &router_config_for_task.query_planner.warmup,
+                                persisted_document_resolver.as_ref(),
+                            )
+                            .await;
+                            warmup_operations.append(&mut persisted_operations);
+                            warm_up_plan_cache(
+                                &new_configured.snapshot,
+                                &new_configured.runtime,
+                                &warmup_operations,
+                                &warmup_override_context,
+                                router_config_for_task.query_planner.timeout,
+                            )
+                            .await;
+
                             // swapping in the new value here is enough: the previous
                             // `ConfiguredSupergraph`'s owner `Arc<Supergraph>` is only kept alive
                             // by this slot (ordinary requests only ever hold a snapshot),
@@ -467,12 +840,31 @@ impl SchemaState {
                             // subscription producer selected from it terminates on its own -
                             // no global subscription closure needed here
                             configured_spawn_clone.store(Arc::new(Some(new_configured)));
+                            let status = SupergraphReloadStatus::now(None);
+                            {
+                                let published = PublishedSupergraph {
+                                    hash: format!("{:x}", Sha256::digest(update.sdl.as_bytes())),
+                                    published_at: status.timestamp,
+                                };
+                                let mut history = supergraph_history_for_reload.lock().unwrap();
+                                history.previous =
+                                    history.current.replace(std::mem::take(&mut update.sdl));
+                                history.published = Some(published);
+                            }
                             debug!("Supergraph updated successfully");
                             process_capture.finish_ok();
+                            reload_log_for_task.record(status);
+                            update.notify(Ok(()));
                         }
                         Err(e) => {
                             process_capture.finish_error();
-                            error!("Failed to build new supergraph data: {}", e);
+                            error!(
+                                "Failed to build new supergraph data, keeping the current supergraph: {}",
+                                e
+                            );
+                            reload_log_for_task
+                                .record(SupergraphReloadStatus::now(Some(e.to_string())));
+                            update.notify(Err(e.to_string()));
                         }
                     }
                 }
@@ -508,23 +900,39 @@ impl SchemaState {
             configured,
             runtime_cache,
             runtime_cache_cleanup: Some(cleanup_tx),
-            router_config,
+            supergraph_updates,
+            supergraph_refresh,
+            supergraph_history,
+            reload_log,
+            router_config: router_config_swap,
+            configured_update_lock,
             telemetry_context: telemetry_context.clone(),
             callback_subscriptions,
+            shutting_down: AtomicBool::new(false),
         })
     }
 }
 
+/// Links the context of the request to the context of its selected supergraph,
+/// for the plugins to reach it with `supergraph_context()`.
+fn link_supergraph_context(req: &HttpRequest, selected: &SelectedSupergraph) {
+    if let Some(plugin_context) = req.extensions().get::<Arc<PluginContext>>() {
+        plugin_context.set_supergraph(selected.snapshot.clone());
+    }
+}
+
 pub struct SupergraphBackgroundLoader {
     loader: Box<dyn SupergraphLoader + Send + Sync>,
-    sender: Arc<mpsc::Sender<String>>,
+    sender: Arc<mpsc::Sender<SupergraphUpdate>>,
+    refresh_requests: tokio::sync::Mutex<mpsc::Receiver<SupergraphRefreshRequest>>,
     metrics: Arc<Metrics>,
 }
 
 impl SupergraphBackgroundLoader {
     pub fn new(
         config: &SupergraphSource,
-        sender: mpsc::Sender<String>,
+        sender: mpsc::Sender<SupergraphUpdate>,
+        refresh_requests: mpsc::Receiver<SupergraphRefreshRequest>,
         metrics: Arc<Metrics>,
         storage_manager: Arc<StorageManager>,
     ) -> Result<Self, LoadSupergraphError> {
@@ -533,6 +941,7 @@ impl SupergraphBackgroundLoader {
         Ok(Self {
             loader,
             sender: Arc::new(sender),
+            refresh_requests: tokio::sync::Mutex::new(refresh_requests),
             metrics,
         })
     }
@@ -548,6 +957,8 @@ impl BackgroundTask for SupergraphBackgroundLoaderTask {
 
     async fn run(&self, token: CancellationToken) {
         let supergraph_metrics = &self.0.metrics.supergraph;
+        let mut refresh_requests = self.0.refresh_requests.lock().await;
+        let mut refresh_request: Option<SupergraphRefreshRequest> = None;
         loop {
             if token.is_cancelled() {
                 trace!("Background task cancelled");
@@ -556,39 +967,86 @@ impl BackgroundTask for SupergraphBackgroundLoaderTask {
             }
 
             let poll_capture = supergraph_metrics.capture_poll();
-            match self.0.loader.load().await {
+            let outcome = match self.0.loader.load().await {
                 Ok(ReloadSupergraphResult::Unchanged) => {
                     debug!("Supergraph fetched successfully with no changes");
                     poll_capture.finish_not_modified();
+                    SupergraphRefreshOutcome::Unchanged
                 }
                 Ok(ReloadSupergraphResult::Changed { new_sdl }) => {
                     debug!("Supergraph loaded successfully with changes, updating...");
 
-                    if self.0.sender.clone().send(new_sdl).await.is_err() {
+                    // a requested refresh waits for the supergraph to be published
+                    let (outcome_tx, outcome_rx) = match refresh_request {
+                        Some(_) => {
+                            let (outcome_tx, outcome_rx) = oneshot::channel();
+                            (Some(outcome_tx), Some(outcome_rx))
+                        }
+                        None => (None, None),
+                    };
+                    let update = SupergraphUpdate {
+                        sdl: new_sdl,
+                        outcome: outcome_tx,
+                    };
+                    if self.0.sender.clone().send(update).await.is_err() {
                         error!("Failed to send new supergraph SDL: receiver dropped.");
                         poll_capture.finish_error();
                         break;
                     }
 
                     poll_capture.finish_updated();
+                    match outcome_rx {
+                        Some(outcome_rx) => match outcome_rx.await {
+                            Ok(Ok(())) => SupergraphRefreshOutcome::Updated,
+                            Ok(Err(error)) => SupergraphRefreshOutcome::Rejected(error),
+                            Err(_) => SupergraphRefreshOutcome::Rejected(
+                                "the supergraph reload task stopped".to_string(),
+                            ),
+                        },
+                        None => SupergraphRefreshOutcome::Updated,
+                    }
                 }
                 Err(err) => {
                     error!("Failed to load supergraph: {}", err);
                     poll_capture.finish_error();
+                    SupergraphRefreshOutcome::LoadFailed(err.to_string())
                 }
+            };
+
+            if let Some(refresh_request) = refresh_request.take() {
+                // the requester is gone when it stopped waiting for the outcome
+                refresh_request.send(outcome).ok();
             }
 
-            if let Some(interval) = self.0.loader.reload_interval() {
-                debug!(
-                    "waiting for {:?}ms before checking again for supergraph changes",
-                    interval.as_millis()
-                );
+            // the source is polled again once its poll interval elapsed, or once a refresh is requested
+            let next_poll = async {
+                match self.0.loader.reload_interval() {
+                    Some(interval) => {
+                        debug!(
+                            "waiting for {:?}ms before checking again for supergraph changes",
+                            interval.as_millis()
+                        );
+
+                        tokio::select! {
+                            _ = ntex::time::sleep(*interval) => Some(None),
+                            Some(request) = refresh_requests.recv() => Some(Some(request)),
+                        }
+                    }
+                    None => {
+                        debug!("poll interval not configured for supergraph changes, waiting for a refresh");
+
+                        refresh_requests.recv().await.map(Some)
+                    }
+                }
+            };
 
-                ntex::time::sleep(*interval).await;
-            } else {
-                debug!("poll interval not configured for supergraph changes, breaking");
+            match token.run_until_cancelled(next_poll).await.flatten() {
+                Some(next_refresh_request) => refresh_request = next_refresh_request,
+                None => {
+                    trace!("Background task cancelled");
 
-                break;
+                    break;
+                }
             }
         }
     }
@@ -770,11 +1228,17 @@ mod plugin_runtime_cache_tests {
             configured: Arc::new(ArcSwap::from(Arc::new(None))),
             runtime_cache: Arc::new(Mutex::new(VecDeque::with_capacity(RUNTIME_CACHE_MAX_SIZE))),
             runtime_cache_cleanup: None,
-            router_config: Arc::new(HiveRouterConfig::default()),
+            supergraph_updates: None,
+            supergraph_refresh: None,
+            supergraph_history: Default::default(),
+            reload_log: Default::default(),
+            router_config: Arc::new(ArcSwap::from_pointee(HiveRouterConfig::default())),
+            configured_update_lock: Default::default(),
             telemetry_context: Arc::new(TelemetryContext::from_propagation_config(
                 &Default::default(),
             )),
             callback_subscriptions: Arc::new(DashMap::new()),
+            shutting_down: AtomicBool::new(false),
         }
     }
 
@@ -866,7 +1330,7 @@ mod plugin_runtime_cache_tests {
         let first_runtime = Arc::new(
             RouterSupergraphRuntime::build(
                 &first_snapshot,
-                &state.router_config,
+                &state.router_config.load_full(),
                 &state.telemetry_context,
                 &state.callback_subscriptions,
             )
@@ -894,7 +1358,7 @@ mod plugin_runtime_cache_tests {
         let second_runtime = Arc::new(
             RouterSupergraphRuntime::build(
                 &second_snapshot,
-                &state.router_config,
+                &state.router_config.load_full(),
                 &state.telemetry_context,
                 &state.callback_subscriptions,
             )
@@ -911,6 +1375,33 @@ mod plugin_runtime_cache_tests {
         assert!(selected.snapshot.is_retired());
     }
 
+    #[test]
+    fn is_not_ready_once_shutting_down() {
+        let state = test_schema_state();
+        let owner = test_owner();
+        let snapshot = owner.snapshot();
+        let runtime = Arc::new(
+            RouterSupergraphRuntime::build(
+                &snapshot,
+                &state.router_config.load_full(),
+                &state.telemetry_context,
+                &state.callback_subscriptions,
+            )
+            .unwrap(),
+        );
+        state.configured.store(Arc::new(Some(ConfiguredSupergraph {
+            _owner: owner,
+            snapshot,
+            runtime,
+        })));
+
+        let req = ntex::web::test::TestRequest::default().to_http_request();
+        assert!(state.is_ready(&req));
+
+        state.begin_shutdown();
+        assert!(!state.is_ready(&req));
+    }
+
     #[test]
     fn eleventh_unique_supergraph_evicts_the_first() {
         let state = test_schema_state();
@@ -946,6 +1437,67 @@ mod plugin_runtime_cache_tests {
         assert_eq!(entries.front().unwrap().0, first.cache_id);
     }
 
+    #[test]
+    fn reload_log_keeps_the_most_recent_errors() {
+        let state = test_schema_state();
+
+        for index in 0..RECENT_RELOAD_ERRORS_MAX_SIZE + 2 {
+            state
+                .reload_log
+                .record(SupergraphReloadStatus::now(Some(format!("error {index}"))));
+        }
+        state.reload_log.record(SupergraphReloadStatus::now(None));
+
+        assert!(state.last_reload_status().unwrap().error.is_none());
+        let errors = state.recent_reload_errors();
+        assert_eq!(errors.len(), RECENT_RELOAD_ERRORS_MAX_SIZE);
+        assert_eq!(errors.first().unwrap().error.as_deref(), Some("error 2"));
+        assert_eq!(
+            errors.last().unwrap().error.as_deref(),
+            Some(format!("error {}", RECENT_RELOAD_ERRORS_MAX_SIZE + 1).as_str())
+        );
+    }
+
+    #[ntex::test]
+    async fn applying_a_config_rebuilds_the_runtimes_but_keeps_the_supergraph() {
+        let state = test_schema_state();
+        let owner = test_owner();
+        let snapshot = owner.snapshot();
+        let runtime = Arc::new(
+            RouterSupergraphRuntime::build(
+                &snapshot,
+                &state.router_config.load_full(),
+                &state.telemetry_context,
+                &state.callback_subscriptions,
+            )
+            .unwrap(),
+        );
+        state.configured.store(Arc::new(Some(ConfiguredSupergraph {
+            _owner: owner,
+            snapshot: snapshot.clone(),
+            runtime: runtime.clone(),
+        })));
+        state.resolve_runtime(&test_owner().snapshot()).unwrap();
+
+        let router_config = Arc::new(HiveRouterConfig::default());
+        state
+            .apply_router_config(router_config.clone())
+            .await
+            .unwrap();
+
+        assert!(Arc::ptr_eq(
+            &state.router_config.load_full(),
+            &router_config
+        ));
+        assert!(!Arc::ptr_eq(&state.configured_runtime().unwrap(), &runtime));
+        assert_eq!(
+            state.configured_snapshot().unwrap().cache_id,
+            snapshot.cache_id
+        );
+        assert!(!snapshot.is_retired());
+        assert!(state.runtime_cache.lock().unwrap().is_empty());
+    }
+
     #[test]
     fn dropping_owner_marks_snapshot_retired_without_a_cleanup_task() {
         let state = test_schema_state();
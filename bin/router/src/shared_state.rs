@@ -1,9 +1,7 @@
+use arc_swap::ArcSwap;
 use futures::Stream;
 use graphql_tools::validation::validate::ValidationPlan;
 use hive_console_sdk::agent::usage_agent::{AgentError, UsageAgent};
-use hive_router_config::traffic_shaping::{
-    TrafficShapingRouterDedupeHeadersConfig, TrafficShapingRouterDedupeHeadersKeyword,
-};
 use hive_router_config::HiveRouterConfig;
 use hive_router_internal::expressions::{BooleanOrProgram, ExpressionCompileError};
 use hive_router_internal::inflight::{InFlightCleanupGuard, InFlightMap};
@@ -13,28 +11,44 @@ use hive_router_internal::telemetry::metrics::Metrics;
 use hive_router_internal::telemetry::TelemetryContext;
 use hive_router_plan_executor::coprocessor::{CoprocessorError, CoprocessorRuntime};
 use hive_router_plan_executor::execution::plan::FailedExecutionResult;
+use hive_router_plan_executor::executors::dedupe::RouterRequestDedupeHeaderPolicy;
 use hive_router_plan_executor::extensions::{
     compile::compile_extensions_plan, plan::ExtensionsPlan,
 };
 use hive_router_plan_executor::headers::{
-    compile::compile_headers_plan, errors::HeaderRuleCompileError, plan::HeaderRulesPlan,
+    compile::{compile_headers_plan, compile_jwt_claim_headers},
+    errors::HeaderRuleCompileError,
+    plan::{HeaderRulesPlan, RequestIdHeader},
 };
+use hive_router_plan_executor::plugin_routes::PluginRoute;
 use hive_router_plan_executor::plugin_trait::RouterPluginBoxed;
+use hive_router_plan_executor::response::error_masking::ErrorMaskingPlan;
+use hive_router_plan_executor::response::error_status_codes::ErrorStatusCodes;
 use http::StatusCode;
 use moka::future::Cache;
 use moka::Expiry;
 use ntex::web;
 use ntex::{http::HeaderMap, util::Bytes};
 use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use std::{collections::HashSet, sync::Arc};
 use tracing::debug;
 
+use crate::http_utils::admin::{AdminEndpointError, AdminEndpointRuntime};
+use crate::http_utils::readiness::{ReadinessChecksError, ReadinessChecksRuntime};
 use crate::jwt::context::JwtTokenPayload;
 use crate::jwt::JwtAuthRuntime;
-use crate::pipeline::active_subscriptions::{ActiveSubscriptions, SubscriptionEvent};
+use crate::pipeline::access_log::{AccessLogError, AccessLogRuntime};
+use crate::pipeline::active_subscriptions::{
+    ActiveSubscriptions, ClientSubscriptionSlot, SubscriptionEvent,
+};
+use crate::pipeline::apollo_usage_reporting::ApolloUsageAgent;
+use crate::pipeline::apq::{ApqError, ApqRuntime};
+use crate::pipeline::authorization::opa::{OpaAuthorizationRuntime, OpaError};
 use crate::pipeline::cors::{CORSConfigError, Cors};
+use crate::pipeline::demand_control::budget::{CostBudgetError, CostBudgetRuntime};
 use crate::pipeline::error::PipelineError;
+use crate::pipeline::execution::compile_expose_query_plan_policy;
 use crate::pipeline::header::{ResponseMode, StreamContentType};
 use crate::pipeline::introspection_policy::compile_introspection_policy;
 use crate::pipeline::multipart_subscribe::{
@@ -44,55 +58,16 @@ use crate::pipeline::parser::ParseCacheEntry;
 use crate::pipeline::persisted_documents::resolve::PersistedDocumentResolverError;
 use crate::pipeline::persisted_documents::PersistedDocumentsRuntime;
 use crate::pipeline::progressive_override::{OverrideLabelsCompileError, OverrideLabelsEvaluator};
+use crate::pipeline::rate_limit::{RateLimitError, RateLimitRuntime};
+use crate::pipeline::response_cache::{ResponseCacheError, ResponseCacheRuntime};
 use crate::pipeline::sse;
-use crate::storage::StorageManager;
+use crate::pipeline::telemetry_attributes::{TelemetryAttributesError, TelemetryAttributesRuntime};
+use crate::pipeline::validation::validation_plan_from_config;
+use crate::storage::{redis_connections::RedisConnections, StorageManager};
 
 pub type JwtClaimsCache = Cache<String, Arc<JwtTokenPayload>>;
 pub type RouterInflightRequestsMap = InFlightMap<u64, SharedRouterResponse>;
 
-#[derive(Clone)]
-pub enum RouterRequestDedupeHeaderPolicy {
-    All,
-    None,
-    Include(HashSet<String>),
-}
-
-impl RouterRequestDedupeHeaderPolicy {
-    #[inline]
-    pub fn should_include(&self, header_name: &str) -> bool {
-        match self {
-            Self::All => true,
-            Self::None => false,
-            Self::Include(allowed_headers) => allowed_headers.contains(header_name),
-        }
-    }
-}
-
-impl From<&TrafficShapingRouterDedupeHeadersConfig> for RouterRequestDedupeHeaderPolicy {
-    fn from(headers: &TrafficShapingRouterDedupeHeadersConfig) -> Self {
-        match headers {
-            TrafficShapingRouterDedupeHeadersConfig::Keyword(
-                TrafficShapingRouterDedupeHeadersKeyword::All,
-            ) => Self::All,
-            TrafficShapingRouterDedupeHeadersConfig::Keyword(
-                TrafficShapingRouterDedupeHeadersKeyword::None,
-            ) => Self::None,
-            TrafficShapingRouterDedupeHeadersConfig::Include { include } => {
-                if include.is_empty() {
-                    return Self::None;
-                }
-
-                let mut dedupe_headers = HashSet::with_capacity(include.len());
-                for header in include {
-                    dedupe_headers.insert(header.get_header_ref().as_str().to_owned());
-                }
-
-                Self::Include(dedupe_headers)
-            }
-        }
-    }
-}
-
 pub type SharedRouterResponseGuard = InFlightCleanupGuard<u64, SharedRouterResponse>;
 
 #[derive(Clone)]
@@ -112,6 +87,8 @@ impl SharedRouterResponse {
         self,
         response_mode: &ResponseMode,
         metrics: &Arc<Metrics>,
+        heartbeat_interval: Duration,
+        subscription_slot: Option<ClientSubscriptionSlot>,
     ) -> Result<web::HttpResponse, PipelineError> {
         match self {
             SharedRouterResponse::Single(single) => Ok(single.into()),
@@ -119,7 +96,12 @@ impl SharedRouterResponse {
                 let stream_content_type = response_mode
                     .stream_content_type()
                     .ok_or(PipelineError::SubscriptionsTransportNotSupported)?;
-                Ok(stream.into_response(stream_content_type, metrics))
+                Ok(stream.into_response(
+                    stream_content_type,
+                    metrics,
+                    heartbeat_interval,
+                    subscription_slot,
+                ))
             }
         }
     }
@@ -173,6 +155,8 @@ impl SharedRouterStreamResponse {
         self,
         stream_content_type: &StreamContentType,
         metrics: &Arc<Metrics>,
+        heartbeat_interval: Duration,
+        subscription_slot: Option<ClientSubscriptionSlot>,
     ) -> web::HttpResponse {
         // leader already has a pre-subscribed receiver to avoid missing
         // any potential events emitted. joiners, on the other hand, subscribe
@@ -192,6 +176,7 @@ impl SharedRouterStreamResponse {
 
         let stream = Box::pin(async_stream::stream! {
             let _client_conn_guard = client_conn_guard;
+            let _subscription_slot = subscription_slot;
             loop {
                 match receiver.recv().await {
                     Ok(SubscriptionEvent::Raw(data)) => {
@@ -236,14 +221,11 @@ impl SharedRouterStreamResponse {
             StreamContentType::IncrementalDelivery => Box::pin(
                 multipart_subscribe::create_incremental_delivery_stream(stream),
             ),
-            StreamContentType::SSE => Box::pin(sse::create_stream(
-                stream,
-                std::time::Duration::from_secs(10),
-            )),
+            StreamContentType::SSE => Box::pin(sse::create_stream(stream, heartbeat_interval)),
             StreamContentType::ApolloMultipartHTTP => {
                 Box::pin(multipart_subscribe::create_apollo_multipart_http_stream(
                     stream,
-                    std::time::Duration::from_secs(10),
+                    heartbeat_interval,
                 ))
             }
         };
@@ -300,15 +282,61 @@ impl Expiry<String, Arc<JwtTokenPayload>> for JwtClaimsExpiry {
         Some(DEFAULT_TTL.min(time_until_exp))
     }
 }
-pub struct RouterSharedState {
+/// The state built from the sections of the config re-applied when the config is reloaded,
+/// swapped as a whole so that a request never sees a partially applied config.
+pub struct ReloadableState {
+    /// The config currently applied, only differing from the one the router started with
+    /// in the fields re-applied on reload.
+    pub router_config: Arc<HiveRouterConfig>,
     pub validation_plan: Arc<ValidationPlan>,
+    pub headers_plan: Arc<HeaderRulesPlan>,
+    pub cors_runtime: Option<Cors>,
+    pub in_flight_requests_header_policy: RouterRequestDedupeHeaderPolicy,
+}
+
+impl ReloadableState {
+    pub fn from_config(router_config: Arc<HiveRouterConfig>) -> Result<Self, SharedStateError> {
+        let mut headers_plan = compile_headers_plan(&router_config.headers).map_err(Box::new)?;
+        if router_config.jwt.is_jwt_auth_enabled() {
+            headers_plan.request.jwt_claims =
+                compile_jwt_claim_headers(&router_config.jwt.forward_claims_to_upstream_headers)
+                    .map_err(Box::new)?;
+        }
+        if let Some(subgraph_header) = router_config.request_id.subgraph_header() {
+            headers_plan.request.request_id = Some(RequestIdHeader {
+                source: router_config.request_id.header.get_header_ref().clone(),
+                name: subgraph_header.get_header_ref().clone(),
+            });
+        }
+
+        Ok(Self {
+            validation_plan: Arc::new(validation_plan_from_config(&router_config.limits)),
+            headers_plan: Arc::new(headers_plan),
+            cors_runtime: Cors::from_config(&router_config.cors).map_err(Box::new)?,
+            in_flight_requests_header_policy: (&router_config
+                .traffic_shaping
+                .router
+                .dedupe
+                .headers)
+                .into(),
+            router_config,
+        })
+    }
+}
+
+pub struct RouterSharedState {
     pub parse_cache: Cache<u64, ParseCacheEntry>,
     pub persisted_documents_runtime: PersistedDocumentsRuntime,
+    /// The config the router started with.
+    /// The fields re-applied on reload are read from [`ReloadableState::router_config`].
     pub router_config: Arc<HiveRouterConfig>,
-    pub headers_plan: Arc<HeaderRulesPlan>,
+    /// The state of the `headers`, `cors`, `limits` and `traffic_shaping.router` sections.
+    pub reloadable: ArcSwap<ReloadableState>,
     pub extensions_plan: Arc<ExtensionsPlan>,
+    pub error_masking_plan: Arc<ErrorMaskingPlan>,
+    /// HTTP status codes of the responses by error code, from the config and the plugins.
+    pub error_status_codes: Arc<ErrorStatusCodes>,
     pub override_labels_evaluator: OverrideLabelsEvaluator,
-    pub cors_runtime: Option<Cors>,
     /// Cache for validated JWT claims to avoid re-parsing on every request.
     /// The cache key is the raw JWT token string.
     /// Stores the parsed claims payload for 5s,
@@ -316,18 +344,44 @@ pub struct RouterSharedState {
     pub jwt_claims_cache: JwtClaimsCache,
     pub jwt_auth_runtime: Option<JwtAuthRuntime>,
     pub hive_usage_agent: Option<UsageAgent>,
+    pub apollo_usage_agent: Option<ApolloUsageAgent>,
     pub introspection_policy: BooleanOrProgram,
+    pub expose_query_plan_policy: BooleanOrProgram,
     pub telemetry_context: Arc<TelemetryContext>,
+    /// Computes the span and metric attributes of `telemetry.instrumentation`, when it defines some.
+    pub telemetry_attributes: Option<TelemetryAttributesRuntime>,
     pub coprocessor: Option<CoprocessorRuntime>,
+    /// Authorizes the operations with an Open Policy Agent policy, when `authorization.opa` is set.
+    pub opa_authorization: Option<OpaAuthorizationRuntime>,
     pub plugins: Option<Arc<Vec<RouterPluginBoxed>>>,
+    /// The HTTP routes registered by the plugins.
+    pub plugin_routes: Vec<PluginRoute>,
     pub in_flight_requests: RouterInflightRequestsMap,
-    pub in_flight_requests_header_policy: RouterRequestDedupeHeaderPolicy,
     /// Tracks the number of active long-lived clients (websockets + http streams)
     pub long_lived_client_count: Arc<AtomicUsize>,
     /// Tracks all active subscriptions from clients to the router.
     pub active_subscriptions: ActiveSubscriptions,
     /// The storage manager for the router.
     pub storage_manager: Arc<StorageManager>,
+    /// The Redis connections shared by the components keeping their state in Redis.
+    pub redis_connections: Arc<RedisConnections>,
+    pub response_cache: Option<ResponseCacheRuntime>,
+    pub apq: Option<ApqRuntime>,
+    /// Limits the rate of the requests of every client, when `rate_limit` is enabled.
+    pub rate_limit: Option<RateLimitRuntime>,
+    /// Charges the estimated cost of the operations to the budgets of their client,
+    /// when `demand_control.client_budgets` is set.
+    pub cost_budgets: Option<CostBudgetRuntime>,
+    /// Authenticates the requests replacing the supergraph, when the admin endpoint is enabled.
+    pub supergraph_admin: Option<AdminEndpointRuntime>,
+    /// Authenticates the requests reading the runtime state, when the diagnostics endpoint is enabled.
+    pub diagnostics_admin: Option<AdminEndpointRuntime>,
+    /// Authenticates the requests purging the caches, when the cache endpoints are enabled.
+    pub cache_admin: Option<AdminEndpointRuntime>,
+    /// Checks the dependencies reported by `/readiness`, when `http.readiness` lists some.
+    pub readiness_checks: Option<ReadinessChecksRuntime>,
+    /// Writes a line per request to the GraphQL endpoint, when `access_log` is enabled.
+    pub access_log: Option<AccessLogRuntime>,
 }
 
 impl RouterSharedState {
@@ -337,33 +391,32 @@ impl RouterSharedState {
         persisted_documents_runtime: PersistedDocumentsRuntime,
         jwt_auth_runtime: Option<JwtAuthRuntime>,
         hive_usage_agent: Option<UsageAgent>,
-        validation_plan: ValidationPlan,
+        apollo_usage_agent: Option<ApolloUsageAgent>,
         telemetry_context: Arc<TelemetryContext>,
         plugins: Option<Arc<Vec<RouterPluginBoxed>>>,
+        error_status_codes: ErrorStatusCodes,
+        plugin_routes: Vec<PluginRoute>,
         active_subscriptions: ActiveSubscriptions,
         storage_manager: Arc<StorageManager>,
+        redis_connections: Arc<RedisConnections>,
     ) -> Result<Self, SharedStateError> {
         let parse_cache = Cache::new(1000);
         let coprocessor = router_config
             .coprocessor
             .as_ref()
             .map(|coprocessor_config| {
-                CoprocessorRuntime::from_config(
-                    coprocessor_config,
-                    telemetry_context.clone(),
-                    router_config.limits.max_request_body_size.to_bytes() as usize,
-                )
-                .map_err(Box::new)
+                CoprocessorRuntime::from_config(coprocessor_config, telemetry_context.clone())
+                    .map_err(Box::new)
             })
             .transpose()?;
 
         Ok(Self {
-            validation_plan: Arc::new(validation_plan),
-            headers_plan: Arc::new(compile_headers_plan(&router_config.headers).map_err(Box::new)?),
+            reloadable: ArcSwap::from_pointee(ReloadableState::from_config(router_config.clone())?),
             extensions_plan: Arc::new(compile_extensions_plan(&router_config.response_extensions)),
+            error_masking_plan: Arc::new(ErrorMaskingPlan::from_config(&router_config.errors)),
+            error_status_codes: Arc::new(error_status_codes),
             parse_cache,
             persisted_documents_runtime,
-            cors_runtime: Cors::from_config(&router_config.cors).map_err(Box::new)?,
             jwt_claims_cache: Cache::builder()
                 // High capacity due to potentially high token diversity.
                 // Capping prevents unbounded memory usage.
@@ -373,25 +426,85 @@ impl RouterSharedState {
             router_config: router_config.clone(),
             override_labels_evaluator: OverrideLabelsEvaluator::from_config(
                 &router_config.override_labels,
+                &router_config.progressive_override,
             )
             .map_err(Box::new)?,
             jwt_auth_runtime,
             hive_usage_agent,
+            apollo_usage_agent,
             introspection_policy: compile_introspection_policy(&router_config.introspection)
                 .map_err(Box::new)?,
+            expose_query_plan_policy: compile_expose_query_plan_policy(
+                &router_config.query_planner.allow_expose,
+            )
+            .map_err(|err| SharedStateError::ExposeQueryPlanPolicyCompile(Box::new(err)))?,
             telemetry_context,
+            telemetry_attributes: TelemetryAttributesRuntime::from_config(
+                &router_config.telemetry.instrumentation,
+            )
+            .map_err(Box::new)?,
             coprocessor,
+            opa_authorization: OpaAuthorizationRuntime::from_config(
+                router_config.authorization.opa.as_ref(),
+            )
+            .map_err(Box::new)?,
             plugins,
+            plugin_routes,
             in_flight_requests: InFlightMap::default(),
-            in_flight_requests_header_policy: (&router_config
-                .traffic_shaping
-                .router
-                .dedupe
-                .headers)
-                .into(),
             long_lived_client_count: Arc::new(AtomicUsize::new(0)),
             active_subscriptions,
             storage_manager,
+            response_cache: ResponseCacheRuntime::from_config(
+                &router_config.response_cache,
+                &redis_connections,
+            )
+            .map_err(Box::new)?,
+            apq: ApqRuntime::from_config(&router_config.apq, &redis_connections)
+                .map_err(Box::new)?,
+            rate_limit: RateLimitRuntime::from_config(
+                &router_config.rate_limit,
+                &redis_connections,
+            )
+            .map_err(Box::new)?,
+            cost_budgets: CostBudgetRuntime::from_config(
+                router_config
+                    .demand_control
+                    .as_ref()
+                    .filter(|demand_control| demand_control.enabled)
+                    .and_then(|demand_control| demand_control.client_budgets.as_ref()),
+                &redis_connections,
+            )
+            .map_err(Box::new)?,
+            supergraph_admin: AdminEndpointRuntime::from_config(
+                "supergraph",
+                router_config.admin.supergraph.enabled,
+                &router_config.admin.supergraph.path,
+                router_config.admin.supergraph.token.as_ref(),
+            )
+            .map_err(Box::new)?,
+            diagnostics_admin: AdminEndpointRuntime::from_config(
+                "diagnostics",
+                router_config.admin.diagnostics.enabled,
+                &router_config.admin.diagnostics.path,
+                router_config.admin.diagnostics.token.as_ref(),
+            )
+            .map_err(Box::new)?,
+            cache_admin: AdminEndpointRuntime::from_config(
+                "cache",
+                router_config.admin.cache.enabled,
+                &router_config.admin.cache.path,
+                router_config.admin.cache.token.as_ref(),
+            )
+            .map_err(Box::new)?,
+            // created after the components connecting to Redis, for their connections to be checked
+            readiness_checks: ReadinessChecksRuntime::from_config(
+                &router_config,
+                &redis_connections,
+            )
+            .map_err(Box::new)?,
+            access_log: AccessLogRuntime::from_config(&router_config.access_log)
+                .map_err(Box::new)?,
+            redis_connections,
         })
     }
 }
@@ -410,43 +523,26 @@ pub enum SharedStateError {
     PersistedDocuments(#[from] Box<PersistedDocumentResolverError>),
     #[error("invalid introspection config: {0}")]
     IntrospectionPolicyCompile(#[from] Box<ExpressionCompileError>),
+    #[error("invalid query_planner.allow_expose config: {0}")]
+    ExposeQueryPlanPolicyCompile(Box<ExpressionCompileError>),
     #[error("invalid coprocessor config: {0}")]
     CoprocessorRuntime(#[from] Box<CoprocessorError>),
-}
-
-#[cfg(test)]
-mod tests {
-    use super::RouterRequestDedupeHeaderPolicy;
-    use hive_router_config::traffic_shaping::{
-        TrafficShapingRouterDedupeHeadersConfig, TrafficShapingRouterDedupeHeadersKeyword,
-    };
-
-    #[test]
-    fn should_map_header_variants_to_policy() {
-        let all = TrafficShapingRouterDedupeHeadersConfig::Keyword(
-            TrafficShapingRouterDedupeHeadersKeyword::All,
-        );
-        assert!(matches!(
-            RouterRequestDedupeHeaderPolicy::from(&all),
-            RouterRequestDedupeHeaderPolicy::All
-        ));
-
-        let none = TrafficShapingRouterDedupeHeadersConfig::Keyword(
-            TrafficShapingRouterDedupeHeadersKeyword::None,
-        );
-        assert!(matches!(
-            RouterRequestDedupeHeaderPolicy::from(&none),
-            RouterRequestDedupeHeaderPolicy::None
-        ));
-
-        let include = TrafficShapingRouterDedupeHeadersConfig::Include {
-            include: vec!["Authorization".into()],
-        };
-        let include_policy = RouterRequestDedupeHeaderPolicy::from(&include);
-        assert!(matches!(
-            include_policy,
-            RouterRequestDedupeHeaderPolicy::Include(_)
-        ));
-        assert!(include_policy.should_include("authorization"));
-    }
+    #[error("invalid authorization.opa config: {0}")]
+    OpaAuthorization(#[from] Box<OpaError>),
+    #[error(transparent)]
+    ResponseCache(#[from] Box<ResponseCacheError>),
+    #[error(transparent)]
+    Apq(#[from] Box<ApqError>),
+    #[error(transparent)]
+    RateLimit(#[from] Box<RateLimitError>),
+    #[error(transparent)]
+    CostBudget(#[from] Box<CostBudgetError>),
+    #[error(transparent)]
+    AdminEndpoint(#[from] Box<AdminEndpointError>),
+    #[error(transparent)]
+    ReadinessChecks(#[from] Box<ReadinessChecksError>),
+    #[error(transparent)]
+    AccessLog(#[from] Box<AccessLogError>),
+    #[error(transparent)]
+    TelemetryAttributes(#[from] Box<TelemetryAttributesError>),
 }
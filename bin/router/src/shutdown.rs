@@ -0,0 +1,165 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use hive_router_internal::background_tasks::{BackgroundTask, CancellationToken};
+use hive_router_plan_executor::response::graphql_error::GraphQLError;
+use ntex::time::Seconds;
+use tracing::{info, warn};
+
+use crate::{pipeline::active_subscriptions::ActiveSubscriptions, schema_state::SchemaState};
+
+/// The time the HTTP server keeps waiting for the subscriptions closed once the drain timeout expired,
+/// for their clients to receive the error closing them.
+const SUBSCRIPTIONS_CLOSE_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+/// The shutdown timeout of the HTTP server, in whole seconds:
+/// the drain timeout rounded up, and the grace period of the closed subscriptions.
+pub fn server_shutdown_timeout(drain_timeout: Duration) -> Seconds {
+    let drain_timeout = drain_timeout.saturating_add(SUBSCRIPTIONS_CLOSE_GRACE_PERIOD);
+    let seconds = drain_timeout.as_secs() + u64::from(drain_timeout.subsec_nanos() > 0);
+    Seconds(u16::try_from(seconds).unwrap_or(u16::MAX))
+}
+
+/// Drains the router when it receives `SIGTERM` or `SIGINT`:
+/// the readiness probe fails, and the HTTP server stops accepting connections
+/// and waits for the in-flight requests and subscriptions, up to `http.drain_timeout`.
+/// The subscriptions still active once it expired are completed with an error.
+/// A second signal stops the server right away.
+pub struct ShutdownSignalTask {
+    // only poisoned if a thread panicked while holding the lock; since the only
+    // operation inside is .clone(), that can't happen
+    server: std::sync::Mutex<ntex::server::Server>,
+    schema_state: Arc<SchemaState>,
+    active_subscriptions: ActiveSubscriptions,
+    drain_timeout: Duration,
+}
+
+impl ShutdownSignalTask {
+    pub fn new(
+        server: ntex::server::Server,
+        schema_state: Arc<SchemaState>,
+        active_subscriptions: ActiveSubscriptions,
+        drain_timeout: Duration,
+    ) -> Self {
+        Self {
+            server: std::sync::Mutex::new(server),
+            schema_state,
+            active_subscriptions,
+            drain_timeout,
+        }
+    }
+
+    async fn drain(&self, signal: &'static str, signals: &mut ShutdownSignals) {
+        info!(
+            drain_timeout = ?self.drain_timeout,
+            "received {signal}, draining the in-flight requests"
+        );
+        self.schema_state.begin_shutdown();
+
+        let server = self.server.lock().unwrap().clone();
+        let stopped = server.stop(true);
+        tokio::pin!(stopped);
+
+        tokio::select! {
+            _ = &mut stopped => return,
+            _ = tokio::time::sleep(self.drain_timeout) => {
+                self.active_subscriptions
+                    .close_all_with_error(vec![GraphQLError::from_message_and_code(
+                        "The router is shutting down",
+                        "SHUTTING_DOWN",
+                    )]);
+            }
+            signal = signals.recv() => {
+                warn!("received {signal} while draining, stopping the router right away");
+                server.stop(false).await;
+                return;
+            }
+        }
+
+        tokio::select! {
+            _ = &mut stopped => {}
+            signal = signals.recv() => {
+                warn!("received {signal} while draining, stopping the router right away");
+                server.stop(false).await;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl BackgroundTask for ShutdownSignalTask {
+    fn id(&self) -> &str {
+        "shutdown-signal"
+    }
+
+    async fn run(&self, token: CancellationToken) {
+        let mut signals = match ShutdownSignals::new() {
+            Ok(signals) => signals,
+            Err(err) => {
+                warn!(error = %err, "failed to listen for the shutdown signals, the router won't drain its requests on shutdown");
+                return;
+            }
+        };
+
+        if let Some(signal) = token.run_until_cancelled(signals.recv()).await {
+            token
+                .run_until_cancelled(self.drain(signal, &mut signals))
+                .await;
+        }
+    }
+}
+
+#[cfg(unix)]
+struct ShutdownSignals {
+    terminate: tokio::signal::unix::Signal,
+    interrupt: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl ShutdownSignals {
+    fn new() -> std::io::Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        Ok(Self {
+            terminate: signal(SignalKind::terminate())?,
+            interrupt: signal(SignalKind::interrupt())?,
+        })
+    }
+
+    async fn recv(&mut self) -> &'static str {
+        tokio::select! {
+            _ = self.terminate.recv() => "SIGTERM",
+            _ = self.interrupt.recv() => "SIGINT",
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct ShutdownSignals;
+
+#[cfg(not(unix))]
+impl ShutdownSignals {
+    fn new() -> std::io::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn recv(&mut self) -> &'static str {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl-C"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::server_shutdown_timeout;
+
+    #[test]
+    fn rounds_the_shutdown_timeout_up_to_whole_seconds() {
+        assert_eq!(server_shutdown_timeout(Duration::ZERO).0, 1);
+        assert_eq!(server_shutdown_timeout(Duration::from_millis(500)).0, 2);
+        assert_eq!(server_shutdown_timeout(Duration::from_secs(30)).0, 31);
+        assert_eq!(server_shutdown_timeout(Duration::MAX).0, u16::MAX);
+    }
+}
@@ -6,4 +6,6 @@ pub enum StorageError {
     Store(#[from] object_store::Error),
     #[error("failed to format contents: {0}")]
     Format(#[from] std::string::FromUtf8Error),
+    #[error("invalid redis url: {0}")]
+    RedisUrl(#[from] redis::RedisError),
 }
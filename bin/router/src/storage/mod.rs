@@ -6,6 +6,7 @@ use std::{collections::HashMap, sync::Arc};
 use tracing::debug;
 
 pub mod error;
+pub mod redis_connections;
 pub mod s3_runtime;
 pub mod utils;
 
@@ -18,7 +19,8 @@ impl StorageManager {
         let mut storage_runtimes: HashMap<String, Arc<Box<dyn StorageRuntime>>> = HashMap::new();
 
         for (id, config) in config_map {
-            debug!(storage_id = id, config = ?config, "creating storage runtime");
+            // the config is not logged, as it holds the credentials of the storage
+            debug!(storage_id = id, "creating storage runtime");
 
             storage_runtimes.insert(
                 id.to_string(),
@@ -0,0 +1,126 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use hive_router_config::primitives::value_or_expression::ValueOrExpression;
+use hive_router_internal::telemetry::utils::resolve_value_or_expression;
+use redis::{aio::ConnectionManager, Client};
+use tokio::sync::OnceCell;
+
+use crate::storage::error::StorageError;
+
+/// The Redis servers the router keeps its state in,
+/// shared by the APQ, the response cache, the rate limits, the client budgets and the plugin storage.
+///
+/// The components configured with the same URL share a single connection,
+/// so the router opens one connection per Redis server, whatever the number of components using it.
+#[derive(Default)]
+pub struct RedisConnections {
+    inner: Mutex<RedisConnectionsInner>,
+}
+
+#[derive(Default)]
+struct RedisConnectionsInner {
+    by_url: HashMap<String, RedisConnection>,
+    by_component: Vec<(&'static str, RedisConnection)>,
+}
+
+/// A connection to a Redis server, established on first use,
+/// multiplexed between the requests, and re-established by the connection manager when lost.
+#[derive(Clone)]
+pub struct RedisConnection {
+    client: Client,
+    connection: Arc<OnceCell<ConnectionManager>>,
+}
+
+impl RedisConnection {
+    pub async fn get(&self) -> Result<ConnectionManager, redis::RedisError> {
+        self.connection
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await
+            .cloned()
+    }
+
+    /// Sends a `PING` over the connection used by the components.
+    pub async fn ping(&self) -> Result<(), redis::RedisError> {
+        let mut connection = self.get().await?;
+        let _: String = redis::cmd("PING").query_async(&mut connection).await?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn shares_connection_with(&self, other: &RedisConnection) -> bool {
+        Arc::ptr_eq(&self.connection, &other.connection)
+    }
+}
+
+impl RedisConnections {
+    /// Returns the connection to the Redis server of `url`, for `component`,
+    /// reusing the connection of another component configured with the same URL.
+    pub fn connect(
+        &self,
+        component: &'static str,
+        url: &ValueOrExpression<String>,
+    ) -> Result<RedisConnection, StorageError> {
+        let url = resolve_value_or_expression(url, &format!("{component} redis url"))
+            .map_err(|err| StorageError::Configuration(err.to_string()))?;
+
+        let mut inner = self.inner.lock().unwrap();
+        let connection = match inner.by_url.get(&url) {
+            Some(connection) => connection.clone(),
+            None => {
+                let connection = RedisConnection {
+                    client: Client::open(url.as_str())?,
+                    connection: Arc::new(OnceCell::new()),
+                };
+                inner.by_url.insert(url, connection.clone());
+                connection
+            }
+        };
+        inner.by_component.push((component, connection.clone()));
+
+        Ok(connection)
+    }
+
+    /// The connections of the components connected so far.
+    pub fn by_component(&self) -> Vec<(&'static str, RedisConnection)> {
+        self.inner.lock().unwrap().by_component.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hive_router_config::primitives::value_or_expression::ValueOrExpression;
+
+    use super::RedisConnections;
+
+    #[test]
+    fn shares_the_connections_to_the_same_server() {
+        let connections = RedisConnections::default();
+        let url = |url: &str| ValueOrExpression::Value(url.to_string());
+
+        let apq = connections
+            .connect("apq", &url("redis://localhost:6379"))
+            .unwrap();
+        let rate_limit = connections
+            .connect("rate_limit", &url("redis://localhost:6379"))
+            .unwrap();
+        let response_cache = connections
+            .connect("response_cache", &url("redis://cache:6379"))
+            .unwrap();
+
+        assert!(apq.shares_connection_with(&rate_limit));
+        assert!(!apq.shares_connection_with(&response_cache));
+        assert_eq!(
+            connections
+                .by_component()
+                .iter()
+                .map(|(component, _)| *component)
+                .collect::<Vec<_>>(),
+            vec!["apq", "rate_limit", "response_cache"]
+        );
+
+        assert!(connections.connect("apq", &url("not a url")).is_err());
+    }
+}
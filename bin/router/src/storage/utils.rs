@@ -53,6 +53,7 @@ where
 {
     match value_or_expr {
         ValueOrExpression::Value(v) => Ok(v.clone()),
+        ValueOrExpression::Secret(secret) => Ok(secret.value().clone()),
         ValueOrExpression::Expression { expression } => {
             evaluate_expression::<T>(expression, context)
         }
@@ -1,5 +1,7 @@
 use async_trait::async_trait;
 
+use hive_router_query_planner::composition::CompositionError;
+
 use crate::storage::error::StorageError;
 
 #[derive(Debug, thiserror::Error)]
@@ -27,6 +29,10 @@ pub enum LoadSupergraphError {
     StorageIdNotFound(String),
     #[error("Storage error: {0}")]
     StorageError(#[from] StorageError),
+    #[error("Failed to fetch the schema of subgraph '{0}': {1}")]
+    SubgraphSchemaError(String, String),
+    #[error("Failed to compose supergraph: {0}")]
+    CompositionError(#[from] CompositionError),
     #[error("'supergraph.source: plugin' has no loader - a plugin must select a supergraph for every request")]
     NoLoaderForPluginSource,
 }
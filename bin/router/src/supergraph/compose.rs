@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hive_router_config::supergraph::ComposeSubgraphConfig;
+use hive_router_query_planner::composition::{compose, SubgraphSchema};
+use tokio::{fs, sync::Mutex};
+use tracing::{debug, trace};
+
+use crate::supergraph::base::{LoadSupergraphError, ReloadSupergraphResult, SupergraphLoader};
+
+const SERVICE_SDL_QUERY: &str = r#"{"query":"{ _service { sdl } }"}"#;
+
+pub struct SupergraphComposeLoader {
+    subgraphs: Vec<ComposeSubgraphConfig>,
+    poll_interval: Option<Duration>,
+    http_client: reqwest::Client,
+    /// The subgraph schemas the last supergraph was composed from.
+    last_schemas: Mutex<Option<Vec<String>>>,
+}
+
+impl SupergraphComposeLoader {
+    async fn fetch_schema(
+        &self,
+        subgraph: &ComposeSubgraphConfig,
+    ) -> Result<String, LoadSupergraphError> {
+        if let Some(schema_file) = &subgraph.schema_file {
+            debug!(
+                "Reading schema of subgraph '{}' from file path: '{}'",
+                subgraph.name, schema_file.absolute
+            );
+
+            return Ok(fs::read_to_string(&schema_file.absolute).await?);
+        }
+
+        debug!(
+            "Fetching schema of subgraph '{}' from '{}'",
+            subgraph.name, subgraph.routing_url
+        );
+        let body = self
+            .http_client
+            .post(&subgraph.routing_url)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(SERVICE_SDL_QUERY)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        let response: serde_json::Value = serde_json::from_slice(&body).map_err(|err| {
+            LoadSupergraphError::SubgraphSchemaError(subgraph.name.clone(), err.to_string())
+        })?;
+        response
+            .pointer("/data/_service/sdl")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                LoadSupergraphError::SubgraphSchemaError(
+                    subgraph.name.clone(),
+                    "the response is missing 'data._service.sdl'".to_string(),
+                )
+            })
+    }
+}
+
+#[async_trait]
+impl SupergraphLoader for SupergraphComposeLoader {
+    async fn load(&self) -> Result<ReloadSupergraphResult, LoadSupergraphError> {
+        let mut schemas = Vec::with_capacity(self.subgraphs.len());
+        for subgraph in &self.subgraphs {
+            schemas.push(SubgraphSchema {
+                name: subgraph.name.clone(),
+                url: subgraph.routing_url.clone(),
+                sdl: self.fetch_schema(subgraph).await?,
+            });
+        }
+
+        let sdls: Vec<String> = schemas.iter().map(|schema| schema.sdl.clone()).collect();
+        let mut last_schemas = self.last_schemas.lock().await;
+        if last_schemas.as_ref() == Some(&sdls) {
+            return Ok(ReloadSupergraphResult::Unchanged);
+        }
+        // stored before composing, so unchanged schemas that fail to compose are reported once
+        *last_schemas = Some(sdls);
+
+        let supergraph = compose(&schemas)?;
+        trace!("Supergraph composed from {} subgraphs", schemas.len());
+
+        Ok(ReloadSupergraphResult::Changed {
+            new_sdl: supergraph,
+        })
+    }
+
+    fn reload_interval(&self) -> Option<&std::time::Duration> {
+        self.poll_interval.as_ref()
+    }
+}
+
+impl SupergraphComposeLoader {
+    pub fn try_new(
+        subgraphs: &[ComposeSubgraphConfig],
+        poll_interval: Option<Duration>,
+    ) -> Result<Box<Self>, LoadSupergraphError> {
+        if subgraphs.is_empty() {
+            return Err(LoadSupergraphError::InvalidConfiguration(
+                "'supergraph.subgraphs' must list at least one subgraph".to_string(),
+            ));
+        }
+        debug!(
+            "Creating supergraph source composing {} subgraphs",
+            subgraphs.len()
+        );
+
+        let http_client = reqwest::Client::builder()
+            .build()
+            .map_err(|err| LoadSupergraphError::InitializationError(err.to_string()))?;
+
+        Ok(Box::new(Self {
+            subgraphs: subgraphs.to_vec(),
+            poll_interval,
+            http_client,
+            last_schemas: Mutex::new(None),
+        }))
+    }
+}
@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
-use hive_router_config::supergraph::SupergraphSource;
+use hive_router_config::supergraph::{ComposeSubgraphConfig, SupergraphSource};
 
 use crate::{
     storage::{utils::resolve_value_or_expression, StorageManager},
     supergraph::{
         base::{LoadSupergraphError, SupergraphLoader},
+        compose::SupergraphComposeLoader,
         file::SupergraphFileLoader,
         hive::SupergraphHiveConsoleLoader,
         storage::SupergraphStorageLoader,
@@ -14,6 +15,7 @@ use crate::{
 use tracing::debug;
 
 pub mod base;
+pub mod compose;
 pub mod file;
 pub mod hive;
 pub mod storage;
@@ -91,6 +93,24 @@ pub fn resolve_from_config(
             //     retry_policy.max_retries,
             // )?)
         }
+        SupergraphSource::Compose {
+            subgraphs,
+            poll_interval,
+        } => Ok(SupergraphComposeLoader::try_new(subgraphs, *poll_interval)?),
+        // a single API is composed as the only subgraph of the supergraph
+        SupergraphSource::Schema {
+            endpoint,
+            path,
+            subgraph_name,
+            poll_interval,
+        } => Ok(SupergraphComposeLoader::try_new(
+            &[ComposeSubgraphConfig {
+                name: subgraph_name.clone(),
+                routing_url: endpoint.clone(),
+                schema_file: Some(path.clone()),
+            }],
+            *poll_interval,
+        )?),
         // there is no loader for a source that's entirely plugin-provided, this should never
         // be called in when the `supergraph.source = plugin`
         SupergraphSource::Plugin => Err(LoadSupergraphError::NoLoaderForPluginSource),
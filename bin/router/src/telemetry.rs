@@ -1,4 +1,8 @@
-use std::{io::IsTerminal, str::FromStr, sync::Mutex};
+use std::{
+    io::IsTerminal,
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+};
 
 use hive_router_config::{log::LogFormat, HiveRouterConfig};
 use hive_router_internal::{
@@ -18,14 +22,14 @@ use hive_router_internal::{
                 trace::{RandomIdGenerator, SdkTracerProvider},
             },
         },
-        traces::set_tracing_enabled,
+        traces::{set_tracing_enabled, spans::LOG_CONTEXT_TARGET_NAME},
         TelemetryContext,
     },
 };
 use ntex::web::{self};
 use ntex::web::{App, HttpResponse, HttpServer};
 use prometheus::{Encoder, TextEncoder};
-use tracing_subscriber::{filter::filter_fn, util::SubscriberInitExt, Layer};
+use tracing_subscriber::{filter::filter_fn, reload, util::SubscriberInitExt, Layer};
 use tracing_subscriber::{fmt::time::UtcTime, EnvFilter};
 use tracing_subscriber::{
     fmt::{self},
@@ -301,7 +305,13 @@ fn create_prometheus_runtime(
     let registry_for_result = registry.clone();
     let path_for_result = path.clone();
 
-    let listen_address = (config.host(), port);
+    let listen_address = (
+        prometheus_config
+            .host
+            .clone()
+            .unwrap_or_else(|| config.host()),
+        port,
+    );
     let server = HttpServer::new(move || {
         let registry = registry.clone();
         let path = path.clone();
@@ -368,21 +378,50 @@ async fn metrics_handler(registry: web::types::State<prometheus::Registry>) -> H
     build_metrics_response(&registry)
 }
 
+type LogFilterReload = Box<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>;
+
+/// Replaces the filter of the global logger, set by [`init_logging`].
+static LOG_FILTER_RELOAD: OnceLock<LogFilterReload> = OnceLock::new();
+
+fn set_log_filter_handle<S: tracing::Subscriber + 'static>(handle: reload::Handle<EnvFilter, S>) {
+    let _ = LOG_FILTER_RELOAD.set(Box::new(move |filter| handle.reload(filter)));
+}
+
+/// Replaces the filter of the global logger, when the config is reloaded.
+/// Does nothing when the global logger is not set, as in tests.
+pub fn reload_log_filter(filter: EnvFilter) -> Result<(), reload::Error> {
+    match LOG_FILTER_RELOAD.get() {
+        Some(reload) => reload(filter),
+        None => Ok(()),
+    }
+}
+
+/// The span of a request to the GraphQL endpoint, attaching its ID to the log lines written while it is handled.
+///
+/// It is an error span, for the ID to be attached whatever the `log` level is.
+pub fn request_log_span(request_id: &str) -> tracing::Span {
+    tracing::error_span!(target: LOG_CONTEXT_TARGET_NAME, "request", request_id)
+}
+
 pub fn init_logging<S>(config: &HiveRouterConfig, registry: S) -> Result<(), TelemetryInitError>
 where
     S: tracing::Subscriber
         + for<'span> tracing_subscriber::registry::LookupSpan<'span>
         + Send
-        + Sync,
+        + Sync
+        + 'static,
 {
     let timer = UtcTime::rfc_3339();
     let filter = EnvFilter::from_str(config.log.env_filter_str())?;
     let is_terminal = std::io::stdout().is_terminal();
 
-    let events_only = filter_fn(|m| !m.is_span());
+    // The spans meant for the log lines are kept, for their fields to be written with the events
+    let events_and_log_context =
+        filter_fn(|m| !m.is_span() || m.target() == LOG_CONTEXT_TARGET_NAME);
 
     match config.log.format {
         LogFormat::PrettyTree => {
+            let (filter, handle) = reload::Layer::new(filter);
             registry
                 .with(
                     tracing_tree::HierarchicalLayer::new(2)
@@ -395,33 +434,38 @@ where
                         .with_thread_names(false)
                         .with_thread_ids(false)
                         .with_targets(false)
-                        .with_filter(events_only),
+                        .with_filter(events_and_log_context),
                 )
                 .with(filter)
                 .init();
+            set_log_filter_handle(handle);
         }
         LogFormat::Json => {
+            let (filter, handle) = reload::Layer::new(filter);
             registry
                 .with(
                     fmt::layer()
                         .json()
                         .with_timer(timer)
-                        .with_filter(events_only),
+                        .with_filter(events_and_log_context),
                 )
                 .with(filter)
                 .init();
+            set_log_filter_handle(handle);
         }
         LogFormat::PrettyCompact => {
+            let (filter, handle) = reload::Layer::new(filter);
             registry
                 .with(
                     fmt::layer()
                         .compact()
                         .with_ansi(is_terminal)
                         .with_timer(timer)
-                        .with_filter(events_only),
+                        .with_filter(events_and_log_context),
                 )
                 .with(filter)
                 .init();
+            set_log_filter_handle(handle);
         }
     };
 
@@ -0,0 +1,372 @@
+//! Helpers to unit-test the hooks of plugins, without running the router and the subgraphs.
+//!
+//! A [`TestRequest`] holds everything a request owns during its execution:
+//! the incoming HTTP request, the plugin context and the request context.
+//! Its [`TestHooks`] build the payloads of the hooks, with sensible defaults,
+//! and the fields of the payloads are public, so a test can tweak them before calling the hook.
+//!
+//! ```ignore
+//! use hive_router::testing::TestRequest;
+//!
+//! #[ntex::test]
+//! async fn rejects_anonymous_requests() {
+//!     let plugin = AuthPlugin::default();
+//!     let request = TestRequest::post("/graphql").header("x-user-id", "1");
+//!     let hooks = request.hooks();
+//!
+//!     let result = plugin.on_execute(hooks.on_execute_payload()).await;
+//!
+//!     assert!(matches!(result.control_flow, StartControlFlow::Proceed));
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use hive_router_plan_executor::{
+    executors::common::SubgraphExecutionRequest,
+    hooks::{
+        self, on_execute::OnExecuteStartHookPayload,
+        on_graphql_params::OnGraphQLParamsStartHookPayload,
+        on_subgraph_http_request::OnSubgraphHttpRequestHookPayload,
+    },
+    plugin_context::{PluginContext, RouterHttpRequest},
+    request_context::SharedRequestContext,
+    response::value::Value,
+};
+use hive_router_query_planner::{
+    ast::{operation::OperationDefinition, selection_set::SelectionSet},
+    planner::plan_nodes::QueryPlan,
+    state::supergraph_state::OperationKind,
+};
+use ntex::{
+    http::{
+        header::{HeaderName, HeaderValue},
+        HeaderMap,
+    },
+    router::Path,
+    util::Bytes,
+};
+
+/// A request of a client, with the state the router keeps for it while it is executed.
+///
+/// Panics on invalid URIs and headers, as it is meant to be used in tests only.
+pub struct TestRequest {
+    uri: http::Uri,
+    method: http::Method,
+    headers: HeaderMap,
+    match_info: Path<http::Uri>,
+    body: Bytes,
+    context: PluginContext,
+    request_context: SharedRequestContext,
+    query_plan: QueryPlan,
+    operation: OperationDefinition,
+    variable_values: Option<HashMap<String, sonic_rs::Value>>,
+    subgraph: TestSubgraphRequest,
+}
+
+/// The request sent to a subgraph, used by the payloads of the subgraph hooks.
+pub struct TestSubgraphRequest {
+    pub name: String,
+    pub endpoint: http::Uri,
+    pub query: String,
+}
+
+impl Default for TestSubgraphRequest {
+    fn default() -> Self {
+        Self {
+            name: "subgraph".to_string(),
+            endpoint: http::Uri::from_static("http://localhost:4001/graphql"),
+            query: "{__typename}".to_string(),
+        }
+    }
+}
+
+impl Default for TestRequest {
+    fn default() -> Self {
+        Self::post("/graphql")
+    }
+}
+
+impl TestRequest {
+    /// A `POST` request to the given URI, e.g. `/graphql` or `http://localhost:4000/graphql`.
+    pub fn post(uri: &str) -> Self {
+        Self::new(http::Method::POST, uri)
+    }
+
+    /// A `GET` request to the given URI, e.g. `/graphql?query={__typename}`.
+    pub fn get(uri: &str) -> Self {
+        Self::new(http::Method::GET, uri)
+    }
+
+    pub fn new(method: http::Method, uri: &str) -> Self {
+        let uri: http::Uri = uri.parse().expect("the URI of the test request is invalid");
+        Self {
+            match_info: Path::new(uri.clone()),
+            uri,
+            method,
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+            context: PluginContext::default(),
+            request_context: SharedRequestContext::default(),
+            query_plan: QueryPlan {
+                kind: "QueryPlan",
+                node: None,
+            },
+            operation: OperationDefinition {
+                name: None,
+                operation_kind: Some(OperationKind::Query),
+                selection_set: SelectionSet::default(),
+                variable_definitions: None,
+            },
+            variable_values: None,
+            subgraph: TestSubgraphRequest::default(),
+        }
+    }
+
+    /// Appends a header to the incoming HTTP request.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = HeaderName::try_from(name).expect("the header name is invalid");
+        let value = HeaderValue::try_from(value).expect("the header value is invalid");
+        self.headers.append(name, value);
+        self
+    }
+
+    /// The raw body of the incoming HTTP request, passed to the `on_graphql_params` hook.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// The query plan passed to the `on_execute` hook. Defaults to an empty plan.
+    pub fn query_plan(mut self, query_plan: QueryPlan) -> Self {
+        self.query_plan = query_plan;
+        self
+    }
+
+    /// The operation passed to the `on_execute` hook. Defaults to an anonymous query.
+    pub fn operation(mut self, operation: OperationDefinition) -> Self {
+        self.operation = operation;
+        self
+    }
+
+    /// The coerced variables passed to the `on_execute` hook.
+    pub fn variables(mut self, variables: HashMap<String, sonic_rs::Value>) -> Self {
+        self.variable_values = Some(variables);
+        self
+    }
+
+    /// The request sent to a subgraph, passed to the `on_subgraph_http_request` hook.
+    pub fn subgraph_request(mut self, subgraph: TestSubgraphRequest) -> Self {
+        self.subgraph = subgraph;
+        self
+    }
+
+    /// The context shared by the hooks of the request,
+    /// to seed it before calling a hook or to inspect it afterwards.
+    pub fn context(&self) -> &PluginContext {
+        &self.context
+    }
+
+    pub fn request_context(&self) -> &SharedRequestContext {
+        &self.request_context
+    }
+
+    /// The builders of the payloads of the hooks, borrowing the request.
+    pub fn hooks(&self) -> TestHooks<'_> {
+        TestHooks {
+            request: self,
+            router_http_request: RouterHttpRequest {
+                uri: &self.uri,
+                method: &self.method,
+                version: http::Version::HTTP_11,
+                headers: &self.headers,
+                path: self.uri.path(),
+                query_string: self.uri.query().unwrap_or(""),
+                match_info: &self.match_info,
+            },
+        }
+    }
+}
+
+/// Builds the payloads of the hooks for a [`TestRequest`].
+pub struct TestHooks<'req> {
+    request: &'req TestRequest,
+    router_http_request: RouterHttpRequest<'req>,
+}
+
+impl<'req> TestHooks<'req> {
+    pub fn router_http_request(&self) -> &RouterHttpRequest<'req> {
+        &self.router_http_request
+    }
+
+    /// The payload of `on_graphql_params`, with the body of the request and no parsed params yet.
+    pub fn on_graphql_params_payload(&self) -> OnGraphQLParamsStartHookPayload<'_> {
+        OnGraphQLParamsStartHookPayload {
+            router_http_request: &self.router_http_request,
+            context: &self.request.context,
+            request_context: self
+                .request
+                .request_context
+                .for_plugin::<hooks::OnGraphqlParams>(),
+            body: self.request.body.clone(),
+            graphql_params: None,
+        }
+    }
+
+    /// The payload of `on_execute`, with the query plan, the operation and the variables of the request.
+    pub fn on_execute_payload(&self) -> OnExecuteStartHookPayload<'_> {
+        OnExecuteStartHookPayload {
+            router_http_request: &self.router_http_request,
+            context: &self.request.context,
+            request_context: self
+                .request
+                .request_context
+                .for_plugin::<hooks::OnExecute>(),
+            query_plan: &self.request.query_plan,
+            operation_for_plan: &self.request.operation,
+            data: Value::Null,
+            errors: Vec::new(),
+            extensions: HashMap::new(),
+            variable_values: &self.request.variable_values,
+            dedupe_subgraph_requests: false,
+            demand_control_estimate: None,
+        }
+    }
+
+    /// The payload of `on_subgraph_http_request`, for the subgraph request of the test request.
+    pub fn on_subgraph_http_request_payload(&self) -> OnSubgraphHttpRequestHookPayload<'_> {
+        let subgraph = &self.request.subgraph;
+        OnSubgraphHttpRequestHookPayload {
+            subgraph_name: &subgraph.name,
+            endpoint: &subgraph.endpoint,
+            method: http::Method::POST,
+            body: sonic_rs::json!({ "query": subgraph.query })
+                .to_string()
+                .into_bytes(),
+            execution_request: SubgraphExecutionRequest {
+                query: &subgraph.query,
+                document_name_write_pos: 0,
+                dedupe: false,
+                idempotent: true,
+                operation_name: None,
+                variables: None,
+                serialized_variables: None,
+                overridden_variables: Default::default(),
+                headers: http::HeaderMap::new(),
+                raw_variable_values: None,
+                extensions: None,
+                custom_scalar_paths: None,
+            },
+            deduplicate_request: false,
+            context: &self.request.context,
+            request_context: self
+                .request
+                .request_context
+                .for_plugin::<hooks::OnSubgraphHttp>(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hive_router_plan_executor::{
+        hooks::{
+            on_execute::{OnExecuteStartHookPayload, OnExecuteStartHookResult},
+            on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+            on_subgraph_http_request::{
+                OnSubgraphHttpRequestHookPayload, OnSubgraphHttpRequestHookResult,
+            },
+        },
+        plugin_trait::{RouterPlugin, StartControlFlow, StartHookPayload},
+        response::graphql_error::GraphQLError,
+    };
+
+    use super::{TestRequest, TestSubgraphRequest};
+
+    struct UserId(String);
+
+    #[derive(Default)]
+    struct TestPlugin;
+
+    #[async_trait::async_trait]
+    impl RouterPlugin for TestPlugin {
+        type Config = ();
+
+        fn plugin_name() -> &'static str {
+            "test_plugin"
+        }
+
+        fn on_plugin_init(payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+            payload.initialize_plugin_with_defaults()
+        }
+
+        async fn on_execute<'exec>(
+            &'exec self,
+            payload: OnExecuteStartHookPayload<'exec>,
+        ) -> OnExecuteStartHookResult<'exec> {
+            let Some(user_id) = payload.router_http_request.headers.get("x-user-id") else {
+                return payload.end_with_graphql_error(
+                    GraphQLError::from_message_and_code("Unauthorized", "UNAUTHORIZED"),
+                    http::StatusCode::UNAUTHORIZED,
+                );
+            };
+            payload
+                .context
+                .insert(UserId(user_id.to_str().unwrap().to_string()));
+            payload.proceed()
+        }
+
+        async fn on_subgraph_http_request<'exec>(
+            &'exec self,
+            mut payload: OnSubgraphHttpRequestHookPayload<'exec>,
+        ) -> OnSubgraphHttpRequestHookResult<'exec> {
+            if let Some(user_id) = payload.context.get_ref::<UserId>() {
+                payload
+                    .execution_request
+                    .headers
+                    .insert("x-user-id", user_id.0.parse().unwrap());
+            }
+            payload.proceed()
+        }
+    }
+
+    #[ntex::test]
+    async fn builds_the_payloads_of_the_hooks() {
+        let plugin = TestPlugin;
+
+        let anonymous = TestRequest::post("/graphql");
+        let result = plugin
+            .on_execute(anonymous.hooks().on_execute_payload())
+            .await;
+        assert!(matches!(
+            result.control_flow,
+            StartControlFlow::EndWithResponse(_)
+        ));
+
+        let request = TestRequest::post("/graphql")
+            .header("x-user-id", "42")
+            .subgraph_request(TestSubgraphRequest {
+                name: "accounts".to_string(),
+                ..Default::default()
+            });
+        let hooks = request.hooks();
+
+        let result = plugin.on_execute(hooks.on_execute_payload()).await;
+        assert!(matches!(result.control_flow, StartControlFlow::Proceed));
+        assert_eq!(request.context().get_ref::<UserId>().unwrap().0, "42");
+
+        let result = plugin
+            .on_subgraph_http_request(hooks.on_subgraph_http_request_payload())
+            .await;
+        assert_eq!(result.payload.subgraph_name, "accounts");
+        assert_eq!(
+            result
+                .payload
+                .execution_request
+                .headers
+                .get("x-user-id")
+                .unwrap(),
+            "42"
+        );
+    }
+}
@@ -1,36 +1,195 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
 use hive_router_config::traffic_shaping::ServerTLSConfig;
+use hive_router_internal::background_tasks::{
+    BackgroundTask, BackgroundTasksManager, CancellationToken,
+};
 use hive_router_plan_executor::executors::{
     error::TlsCertificatesError, tls::from_cert_file_config_to_certificate_der,
 };
+use notify::EventKind;
 use rustls::{
+    crypto::CryptoProvider,
     pki_types::{pem::PemObject, PrivateKeyDer},
-    server::{NoClientAuth, WebPkiClientVerifier},
+    server::{ClientHello, NoClientAuth, ResolvesServerCert, WebPkiClientVerifier},
+    sign::CertifiedKey,
     RootCertStore, ServerConfig,
 };
+use tracing::{info, warn};
+
+use crate::file_watcher::DebouncedFileWatcher;
 
 pub fn build_rustls_config(
     tls_config: &ServerTLSConfig,
 ) -> Result<ServerConfig, TlsCertificatesError> {
-    let client_auth = if let Some(client_auth_config) = tls_config.client_auth.as_ref() {
-        let certs = from_cert_file_config_to_certificate_der(&client_auth_config.cert_file)?;
-        let mut roots = RootCertStore::empty();
-        roots.add_parsable_certificates(certs);
-        let builder = WebPkiClientVerifier::builder(roots.into());
-        let required = client_auth_config.required.unwrap_or(true);
-        if required {
-            builder.build()?
-        } else {
-            builder.allow_unauthenticated().build()?
-        }
-    } else {
-        Arc::new(NoClientAuth)
-    };
     let certs = from_cert_file_config_to_certificate_der(&tls_config.cert_file)?;
     let key = PrivateKeyDer::from_pem_file(&tls_config.key_file.absolute)
         .map_err(|err| TlsCertificatesError::CustomTlsCertificatesError("key_file", err))?;
     Ok(ServerConfig::builder()
-        .with_client_cert_verifier(client_auth)
+        .with_client_cert_verifier(build_client_verifier(tls_config)?)
         .with_single_cert(certs, key)?)
 }
+
+/// Builds the rustls config of the router's HTTP server.
+/// When `watch` is enabled, the certificate is served by a [`ReloadableCertResolver`],
+/// and a [`TlsCertificatesWatcherTask`] reloads it when its files change.
+pub fn build_server_rustls_config(
+    tls_config: &ServerTLSConfig,
+    bg_tasks_manager: &mut BackgroundTasksManager,
+) -> Result<ServerConfig, TlsCertificatesError> {
+    if !tls_config.watch {
+        return build_rustls_config(tls_config);
+    }
+
+    let (rustls_config, resolver) = build_reloadable_rustls_config(tls_config)?;
+    match TlsCertificatesWatcherTask::new(tls_config.clone(), resolver) {
+        Ok(watcher) => bg_tasks_manager.register_task(watcher),
+        Err(err) => warn!(error = %err, "failed to watch the TLS certificate files"),
+    }
+
+    Ok(rustls_config)
+}
+
+pub fn build_reloadable_rustls_config(
+    tls_config: &ServerTLSConfig,
+) -> Result<(ServerConfig, Arc<ReloadableCertResolver>), TlsCertificatesError> {
+    let resolver = Arc::new(ReloadableCertResolver::new(load_certified_key(tls_config)?));
+    let rustls_config = ServerConfig::builder()
+        .with_client_cert_verifier(build_client_verifier(tls_config)?)
+        .with_cert_resolver(resolver.clone());
+
+    Ok((rustls_config, resolver))
+}
+
+fn build_client_verifier(
+    tls_config: &ServerTLSConfig,
+) -> Result<Arc<dyn rustls::server::danger::ClientCertVerifier>, TlsCertificatesError> {
+    let Some(client_auth_config) = tls_config.client_auth.as_ref() else {
+        return Ok(Arc::new(NoClientAuth));
+    };
+
+    let certs = from_cert_file_config_to_certificate_der(&client_auth_config.cert_file)?;
+    let mut roots = RootCertStore::empty();
+    roots.add_parsable_certificates(certs);
+    let builder = WebPkiClientVerifier::builder(roots.into());
+    let required = client_auth_config.required.unwrap_or(true);
+    Ok(if required {
+        builder.build()?
+    } else {
+        builder.allow_unauthenticated().build()?
+    })
+}
+
+/// Reads the certificate chain and the private key, and checks that they belong together.
+fn load_certified_key(tls_config: &ServerTLSConfig) -> Result<CertifiedKey, TlsCertificatesError> {
+    let certs = from_cert_file_config_to_certificate_der(&tls_config.cert_file)?;
+    let key = PrivateKeyDer::from_pem_file(&tls_config.key_file.absolute)
+        .map_err(|err| TlsCertificatesError::CustomTlsCertificatesError("key_file", err))?;
+    let provider = match CryptoProvider::get_default() {
+        Some(provider) => provider.clone(),
+        None => Arc::new(rustls::crypto::aws_lc_rs::default_provider()),
+    };
+
+    Ok(CertifiedKey::from_der(certs, key, &provider)?)
+}
+
+/// Serves the current certificate of the router, which can be swapped while the server runs.
+/// Established connections keep the certificate they were handshaked with.
+#[derive(Debug)]
+pub struct ReloadableCertResolver {
+    certified_key: ArcSwap<CertifiedKey>,
+}
+
+impl ReloadableCertResolver {
+    fn new(certified_key: CertifiedKey) -> Self {
+        Self {
+            certified_key: ArcSwap::from_pointee(certified_key),
+        }
+    }
+
+    /// Reads the certificate files again, and serves them to the new connections.
+    /// The current certificate is kept when the files are invalid.
+    pub fn reload(&self, tls_config: &ServerTLSConfig) -> Result<(), TlsCertificatesError> {
+        let certified_key = load_certified_key(tls_config)?;
+        self.certified_key.store(Arc::new(certified_key));
+        Ok(())
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.certified_key.load_full())
+    }
+}
+
+/// Reloads the certificate of the router when its files change, when `traffic_shaping.router.tls.watch` is enabled.
+pub struct TlsCertificatesWatcherTask {
+    tls_config: ServerTLSConfig,
+    resolver: Arc<ReloadableCertResolver>,
+    watcher: DebouncedFileWatcher,
+}
+
+impl TlsCertificatesWatcherTask {
+    pub fn new(
+        tls_config: ServerTLSConfig,
+        resolver: Arc<ReloadableCertResolver>,
+    ) -> Result<Self, notify::Error> {
+        let watch_targets = tls_config
+            .cert_file
+            .values()
+            .iter()
+            .chain(std::iter::once(&tls_config.key_file))
+            .map(|file| {
+                let path = PathBuf::from(&file.absolute);
+                path.parent()
+                    .map(|parent| parent.to_path_buf())
+                    .unwrap_or(path)
+            })
+            .collect::<Vec<_>>();
+        let watcher = DebouncedFileWatcher::new(watch_targets, |result| match result {
+            // Any change of the directories is a potential change of the files:
+            // mounted secrets are updated by swapping a symlink, not by writing the files.
+            // Reading the files is not a change, and the reload reads them.
+            Ok(event) => !matches!(event.kind, EventKind::Access(_)),
+            Err(err) => {
+                warn!("TLS certificate watcher event failed: {err}");
+                false
+            }
+        })?;
+
+        Ok(Self {
+            tls_config,
+            resolver,
+            watcher,
+        })
+    }
+
+    fn reload(&self) {
+        if !self.watcher.take_change() {
+            return;
+        }
+
+        match self.resolver.reload(&self.tls_config) {
+            Ok(()) => info!("reloaded the TLS certificate"),
+            Err(err) => {
+                warn!(error = %err, "failed to reload the TLS certificate, keeping the current certificate")
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl BackgroundTask for TlsCertificatesWatcherTask {
+    fn id(&self) -> &str {
+        "tls-certificates-watcher"
+    }
+
+    async fn run(&self, token: CancellationToken) {
+        // the certificate and its key are updated together within the debounce period
+        while self.watcher.changed(&token).await.is_some() {
+            self.reload();
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::hash::{Hash, Hasher};
 
+use subtle::ConstantTimeEq;
+
 /// A wrapper for a string slice (`&'a str`) that implements `Hash`, `PartialEq`,
 /// and `Eq` based on the pointer address of the slice, not its content.
 ///
@@ -42,3 +44,33 @@ impl<'a> PartialEq for StrByAddr<'a> {
 }
 
 impl<'a> Eq for StrByAddr<'a> {}
+
+/// Compares a secret provided by a client with the expected one in constant time,
+/// so the time it takes to reject it does not reveal how much of it was guessed correctly.
+/// Only a difference in length is rejected early.
+pub fn secrets_match(expected: &[u8], provided: &[u8]) -> bool {
+    expected.ct_eq(provided).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::secrets_match;
+
+    #[test]
+    fn matches_identical_secrets() {
+        assert!(secrets_match(
+            b"01J0000000000000000000000",
+            b"01J0000000000000000000000"
+        ));
+    }
+
+    #[test]
+    fn rejects_different_secrets() {
+        assert!(!secrets_match(
+            b"01J0000000000000000000000",
+            b"01J0000000000000000000001"
+        ));
+        assert!(!secrets_match(b"01J0000000000000000000000", b"01J"));
+        assert!(!secrets_match(b"01J0000000000000000000000", b""));
+    }
+}
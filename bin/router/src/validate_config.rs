@@ -0,0 +1,86 @@
+use hive_router_config::{subscriptions::CallbackConfig, HiveRouterConfig};
+use hive_router_internal::{background_tasks::BackgroundTasksManager, telemetry::TelemetryContext};
+
+use crate::{
+    configure_app_from_config,
+    error::RouterInitError,
+    plugins::registry::PluginRegistry,
+    schema_state::{SupergraphPushError, SupergraphRefreshOutcome},
+    tls, RouterPaths,
+};
+
+/// Validates the config the way the router does when it starts, without starting it:
+/// the expressions are compiled, the files it points to are read, the plugins are initialized
+/// with their config, and the paths of the endpoints are checked for conflicts.
+///
+/// When `check_reachability` is set, the supergraph is loaded and built,
+/// and the remote JWKS providers are fetched, even the ones not prefetched on start.
+pub async fn validate_router_config(
+    router_config: HiveRouterConfig,
+    plugin_registry: PluginRegistry,
+    check_reachability: bool,
+) -> Result<(), RouterInitError> {
+    if let Some(tls_config) = router_config.traffic_shaping.router.tls.as_ref() {
+        tls::build_rustls_config(tls_config)?;
+    }
+
+    let callback_path = match router_config.callback_conf() {
+        // the callback route is served by a dedicated server
+        Some(CallbackConfig {
+            listen: Some(_), ..
+        }) => None,
+        Some(callback) => Some(callback.path.to_string()),
+        None => None,
+    };
+    let paths = RouterPaths::new(
+        router_config.graphql_path().to_string(),
+        router_config.websocket_path().map(|p| p.to_string()),
+        callback_path,
+        router_config.admin_supergraph_path().map(|p| p.to_string()),
+        router_config
+            .admin_diagnostics_path()
+            .map(|p| p.to_string()),
+        router_config.admin_cache_path().map(|p| p.to_string()),
+        router_config.mcp_path().map(|p| p.to_string()),
+    );
+
+    let telemetry_context =
+        TelemetryContext::from_propagation_config(&router_config.telemetry.tracing.propagation);
+    let mut bg_tasks_manager = BackgroundTasksManager::new();
+    let result: Result<(), RouterInitError> = async {
+        let (shared_state, schema_state) = configure_app_from_config(
+            router_config,
+            telemetry_context,
+            &mut bg_tasks_manager,
+            plugin_registry,
+        )
+        .await?;
+
+        paths
+            .with_plugin_routes(shared_state.plugin_routes.clone())
+            .detect_conflicts(&None)?;
+
+        if check_reachability {
+            if let Some(jwt_auth_runtime) = shared_state.jwt_auth_runtime.as_ref() {
+                jwt_auth_runtime.load_jwks_providers().await?;
+            }
+
+            let supergraph_error = match schema_state.refresh_supergraph().await {
+                Ok(SupergraphRefreshOutcome::Unchanged | SupergraphRefreshOutcome::Updated) => None,
+                // a plugin provides the supergraphs
+                Err(SupergraphPushError::UnsupportedSource) => None,
+                Ok(outcome) => Some(outcome.to_string()),
+                Err(err) => Some(err.to_string()),
+            };
+            if let Some(err) = supergraph_error {
+                return Err(RouterInitError::SupergraphUnavailable(err));
+            }
+        }
+
+        Ok(())
+    }
+    .await;
+
+    bg_tasks_manager.shutdown();
+    result
+}
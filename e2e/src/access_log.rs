@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod access_log_e2e_tests {
+    use std::time::Duration;
+
+    use ntex::http;
+    use sonic_rs::{json, JsonValueTrait};
+
+    use crate::testkit::{some_header_map, TestRouter, TestSubgraphs};
+
+    async fn wait_for_lines(path: &std::path::Path, count: usize) -> Vec<String> {
+        for _ in 0..50 {
+            let lines: Vec<String> = std::fs::read_to_string(path)
+                .unwrap_or_default()
+                .lines()
+                .map(str::to_string)
+                .collect();
+            if lines.len() >= count {
+                return lines;
+            }
+            ntex::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("expected {count} lines in the access log");
+    }
+
+    #[ntex::test]
+    async fn writes_a_line_per_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("access.log");
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                response_cache:
+                    enabled: true
+                    default_max_age: 60s
+                access_log:
+                    enabled: true
+                    fields: [status, operation_name, operation_type, client_name, client_version, subgraphs, cache_status, duration]
+                    output:
+                        file: {}
+                "#,
+                path.display()
+            ))
+            .build()
+            .start()
+            .await;
+
+        let query = "query TopProducts { topProducts(first: 1) { upc name } }";
+        for _ in 0..2 {
+            let res = router
+                .send_graphql_request(
+                    query,
+                    None,
+                    some_header_map!(
+                        http::header::HeaderName::from_static("graphql-client-name") => "e2e",
+                        http::header::HeaderName::from_static("graphql-client-version") => "tests"
+                    ),
+                )
+                .await;
+            assert_eq!(res.status(), 200);
+        }
+
+        let lines = wait_for_lines(&path, 2).await;
+        let first: sonic_rs::Value = sonic_rs::from_str(&lines[0]).unwrap();
+        assert_eq!(first["status"].as_u64(), Some(200));
+        assert_eq!(first["operation_name"].as_str(), Some("TopProducts"));
+        assert_eq!(first["operation_type"].as_str(), Some("query"));
+        assert_eq!(first["client_name"].as_str(), Some("e2e"));
+        assert_eq!(first["client_version"].as_str(), Some("tests"));
+        assert_eq!(first["subgraphs"], json!(["products"]));
+        assert_eq!(first["cache_status"].as_str(), Some("miss"));
+        assert!(first["duration"].as_f64().is_some());
+
+        let second: sonic_rs::Value = sonic_rs::from_str(&lines[1]).unwrap();
+        assert_eq!(second["cache_status"].as_str(), Some("hit"));
+        assert_eq!(second["subgraphs"], json!([]));
+    }
+
+    #[ntex::test]
+    async fn writes_the_apache_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("access.log");
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                access_log:
+                    enabled: true
+                    format: apache
+                    fields: [method, path, status, operation_name, client_name, subgraphs, cache_status]
+                    output:
+                        file: {}
+                "#,
+                path.display()
+            ))
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ topProducts(first: 1) { upc } }", None, None)
+            .await;
+        assert_eq!(res.status(), 200);
+
+        let lines = wait_for_lines(&path, 1).await;
+        assert_eq!(lines[0], "POST /graphql 200 - - products -");
+    }
+}
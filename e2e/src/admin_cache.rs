@@ -0,0 +1,263 @@
+#[cfg(test)]
+mod admin_cache_e2e_tests {
+    use http::header::AUTHORIZATION;
+    use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
+
+    use crate::testkit::{ClientResponseExt, Started, TestRouter, TestSubgraphs};
+
+    fn requests(subgraphs: &TestSubgraphs<Started>, subgraph: &str) -> usize {
+        subgraphs
+            .get_requests_log(subgraph)
+            .map(|r| r.len())
+            .unwrap_or(0)
+    }
+
+    /// The representations sent by every `_entities` request to the subgraph.
+    fn representations(subgraphs: &TestSubgraphs<Started>, subgraph: &str) -> Vec<usize> {
+        subgraphs
+            .get_requests_log(subgraph)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|request| {
+                let body: Value = sonic_rs::from_slice(request.body.as_ref()?).ok()?;
+                Some(body["variables"]["representations"].as_array()?.len())
+            })
+            .collect()
+    }
+
+    #[ntex::test]
+    async fn should_purge_responses_by_type() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                response_cache:
+                    enabled: true
+                    default_max_age: 60s
+                admin:
+                    cache:
+                        enabled: true
+                        token: secret
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let query = r#"{ topProducts(first: 1) { upc name } }"#;
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(requests(&subgraphs, "products"), 1);
+
+        // the operation does not select users
+        let res = router
+            .serv()
+            .post("/admin/cache/response")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send_body(r#"{"types": ["User"]}"#)
+            .await
+            .expect("failed to purge the response cache");
+        assert_eq!(res.status(), 200);
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            requests(&subgraphs, "products"),
+            1,
+            "expected the response to be served from the cache"
+        );
+
+        let res = router
+            .serv()
+            .post("/admin/cache/response")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send_body(r#"{"types": ["Product"]}"#)
+            .await
+            .expect("failed to purge the response cache");
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json_body().await["status"].as_str(), Some("purged"));
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            requests(&subgraphs, "products"),
+            2,
+            "expected the purged response to be executed again"
+        );
+    }
+
+    #[ntex::test]
+    async fn should_purge_responses_by_surrogate_key() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                response_cache:
+                    enabled: true
+                    default_max_age: 60s
+                    surrogate_keys: true
+                admin:
+                    cache:
+                        enabled: true
+                        token: secret
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let first_product = r#"{ topProducts(first: 1) { upc name } }"#;
+        let two_products = r#"{ topProducts(first: 2) { upc name } }"#;
+        let res = router.send_graphql_request(first_product, None, None).await;
+        assert_eq!(res.status(), 200);
+        let res = router.send_graphql_request(two_products, None, None).await;
+        assert_eq!(res.status(), 200);
+        let body = res.json_body().await;
+        let second_upc = body["data"]["topProducts"][1]["upc"]
+            .as_str()
+            .expect("expected the upc of the second product")
+            .to_string();
+        assert_eq!(requests(&subgraphs, "products"), 2);
+
+        let res = router
+            .serv()
+            .post("/admin/cache/response")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send_body(format!(
+                r#"{{"surrogate_keys": ["Product:upc={second_upc}"]}}"#
+            ))
+            .await
+            .expect("failed to purge the response cache");
+        assert_eq!(res.status(), 200);
+
+        let res = router.send_graphql_request(first_product, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            requests(&subgraphs, "products"),
+            2,
+            "expected the response without the purged entity to be served from the cache"
+        );
+        let res = router.send_graphql_request(two_products, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            requests(&subgraphs, "products"),
+            3,
+            "expected the response with the purged entity to be executed again"
+        );
+    }
+
+    #[ntex::test]
+    async fn should_purge_entities_by_key() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                entity_cache:
+                    enabled: true
+                    types:
+                        Product:
+                            ttl: 60s
+                admin:
+                    cache:
+                        enabled: true
+                        token: secret
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let query = r#"{ topProducts(first: 2) { upc reviews { id body } } }"#;
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+        let body = res.json_body().await;
+        let upc = body["data"]["topProducts"][0]["upc"]
+            .as_str()
+            .expect("expected the upc of the first product")
+            .to_string();
+
+        let res = router
+            .serv()
+            .post("/admin/cache/entity")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send_body(format!(
+                r#"{{"typename": "Product", "key": {{"upc": "{upc}"}}}}"#
+            ))
+            .await
+            .expect("failed to purge the entity cache");
+        assert_eq!(res.status(), 200);
+
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            representations(&subgraphs, "reviews"),
+            vec![2, 1],
+            "expected only the purged entity to be fetched again"
+        );
+    }
+
+    #[ntex::test]
+    async fn should_clear_plan_caches_and_reject_invalid_requests() {
+        let router = TestRouter::builder()
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                admin:
+                    cache:
+                        enabled: true
+                        token: secret
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .serv()
+            .post("/admin/cache/plan")
+            .send()
+            .await
+            .expect("failed to clear the plan caches");
+        assert_eq!(res.status(), 401);
+
+        let res = router
+            .serv()
+            .post("/admin/cache/plan")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send()
+            .await
+            .expect("failed to clear the plan caches");
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json_body().await["status"].as_str(), Some("cleared"));
+
+        // neither the response cache nor the entity cache is enabled
+        let res = router
+            .serv()
+            .post("/admin/cache/response")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send()
+            .await
+            .expect("failed to purge the response cache");
+        assert_eq!(res.status(), 409);
+        let res = router
+            .serv()
+            .post("/admin/cache/entity")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send_body(r#"{"typename": "Product"}"#)
+            .await
+            .expect("failed to purge the entity cache");
+        assert_eq!(res.status(), 409);
+    }
+}
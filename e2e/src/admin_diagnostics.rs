@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod admin_diagnostics_e2e_tests {
+    use http::header::AUTHORIZATION;
+    use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    #[ntex::test]
+    async fn should_expose_the_runtime_state() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                traffic_shaping:
+                    subgraphs:
+                        accounts:
+                            circuit_breaker:
+                                enabled: true
+                admin:
+                    diagnostics:
+                        enabled: true
+                        token: secret
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        let res = router
+            .serv()
+            .get("/admin/diagnostics")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send()
+            .await
+            .expect("failed to read diagnostics");
+        assert_eq!(res.status(), 200);
+        let body = res.json_body().await;
+
+        let supergraph = &body["supergraph"];
+        assert_eq!(supergraph["hash"].as_str().map(str::len), Some(64));
+        assert!(supergraph["published_at"].as_u64().is_some());
+        assert_eq!(
+            supergraph["subgraphs"]["accounts"]["circuit_breaker"].as_str(),
+            Some("closed")
+        );
+        assert!(supergraph["subgraphs"]["products"]["circuit_breaker"].is_null());
+
+        assert_eq!(
+            body["last_supergraph_reload"]["status"].as_str(),
+            Some("succeeded")
+        );
+        assert!(body["recent_supergraph_reload_errors"]
+            .as_array()
+            .is_some_and(|errors| errors.is_empty()));
+        assert!(body["caches"]["plan"]["entries"].as_u64().is_some());
+        assert!(body["caches"]["entity"].is_null());
+        assert!(body["plugins"]
+            .as_array()
+            .is_some_and(|plugins| plugins.is_empty()));
+    }
+
+    #[ntex::test]
+    async fn should_reject_requests_without_valid_token() {
+        let router = TestRouter::builder()
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                admin:
+                    diagnostics:
+                        enabled: true
+                        token: secret
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .serv()
+            .get("/admin/diagnostics")
+            .header(AUTHORIZATION, "Bearer wrong")
+            .send()
+            .await
+            .expect("failed to read diagnostics");
+        assert_eq!(res.status(), 401);
+    }
+
+    #[ntex::test]
+    async fn should_not_expose_endpoint_when_disabled() {
+        let router = TestRouter::builder().build().start().await;
+
+        let res = router
+            .serv()
+            .get("/admin/diagnostics")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send()
+            .await
+            .expect("failed to read diagnostics");
+        assert_eq!(res.status(), 404);
+    }
+}
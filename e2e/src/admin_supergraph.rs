@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod admin_supergraph_e2e_tests {
+    use std::fs;
+
+    use http::header::AUTHORIZATION;
+    use tempfile::NamedTempFile;
+
+    use crate::testkit::{ClientResponseExt, Started, TestRouter};
+
+    const QUERY_FIELDS: &str = r#"{ __type(name: "Query") { fields { name } } }"#;
+
+    async fn start_router(file: &NamedTempFile) -> TestRouter<Started> {
+        let supergraph_file_path = file
+            .path()
+            .to_str()
+            .expect("failed to convert path to string")
+            .to_string();
+        fs::write(&supergraph_file_path, "type Query { initial: String }")
+            .expect("failed to write supergraph");
+
+        TestRouter::builder()
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: {supergraph_file_path}
+                admin:
+                    supergraph:
+                        enabled: true
+                        token: secret
+                "#,
+            ))
+            .build()
+            .start()
+            .await
+    }
+
+    async fn query_fields(router: &TestRouter<Started>) -> String {
+        let res = router.send_graphql_request(QUERY_FIELDS, None, None).await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+        res.json_body_string_pretty().await
+    }
+
+    #[ntex::test]
+    async fn should_replace_supergraph_and_roll_back() {
+        let file = NamedTempFile::new().expect("failed to create temp file");
+        let router = start_router(&file).await;
+
+        let res = router
+            .serv()
+            .post("/admin/supergraph")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send_body("type Query { pushed: String }")
+            .await
+            .expect("failed to push supergraph");
+        assert_eq!(res.status(), 200);
+        let body = res.body().await.unwrap();
+        insta::assert_snapshot!(String::from_utf8_lossy(&body), @r#"{"status":"updated"}"#);
+
+        insta::assert_snapshot!(query_fields(&router).await, @r#"
+        {
+          "data": {
+            "__type": {
+              "fields": [
+                {
+                  "name": "pushed"
+                }
+              ]
+            }
+          }
+        }
+        "#);
+
+        let res = router
+            .serv()
+            .post("/admin/supergraph/rollback")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send()
+            .await
+            .expect("failed to roll back supergraph");
+        assert_eq!(res.status(), 200);
+        let body = res.body().await.unwrap();
+        insta::assert_snapshot!(String::from_utf8_lossy(&body), @r#"{"status":"rolled_back"}"#);
+
+        insta::assert_snapshot!(query_fields(&router).await, @r#"
+        {
+          "data": {
+            "__type": {
+              "fields": [
+                {
+                  "name": "initial"
+                }
+              ]
+            }
+          }
+        }
+        "#);
+    }
+
+    // the requests are sent without a body, as the router responds before reading it
+    #[ntex::test]
+    async fn should_reject_requests_without_valid_token() {
+        let file = NamedTempFile::new().expect("failed to create temp file");
+        let router = start_router(&file).await;
+
+        let res = router
+            .serv()
+            .post("/admin/supergraph")
+            .send()
+            .await
+            .expect("failed to push supergraph");
+        assert_eq!(res.status(), 401);
+
+        let res = router
+            .serv()
+            .post("/admin/supergraph")
+            .header(AUTHORIZATION, "Bearer wrong")
+            .send()
+            .await
+            .expect("failed to push supergraph");
+        assert_eq!(res.status(), 401);
+
+        insta::assert_snapshot!(query_fields(&router).await, @r#"
+        {
+          "data": {
+            "__type": {
+              "fields": [
+                {
+                  "name": "initial"
+                }
+              ]
+            }
+          }
+        }
+        "#);
+    }
+
+    #[ntex::test]
+    async fn should_keep_current_supergraph_when_pushed_one_is_invalid() {
+        let file = NamedTempFile::new().expect("failed to create temp file");
+        let router = start_router(&file).await;
+
+        let res = router
+            .serv()
+            .post("/admin/supergraph")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send_body("type Query {")
+            .await
+            .expect("failed to push supergraph");
+        assert_eq!(res.status(), 422);
+
+        insta::assert_snapshot!(query_fields(&router).await, @r#"
+        {
+          "data": {
+            "__type": {
+              "fields": [
+                {
+                  "name": "initial"
+                }
+              ]
+            }
+          }
+        }
+        "#);
+    }
+
+    #[ntex::test]
+    async fn should_not_expose_endpoint_when_disabled() {
+        let router = TestRouter::builder().build().start().await;
+
+        let res = router
+            .serv()
+            .post("/admin/supergraph")
+            .header(AUTHORIZATION, "Bearer secret")
+            .send()
+            .await
+            .expect("failed to push supergraph");
+        assert_eq!(res.status(), 404);
+    }
+}
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod apq_e2e_tests {
+    use sonic_rs::{json, JsonValueTrait, Value};
+
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    const QUERY: &str = "{ topProducts(first: 1) { upc } }";
+    /// The SHA-256 hash of `QUERY`.
+    const QUERY_HASH: &str = "c44f46cd9d58485f07372bff76fffe63ab3cfa1429963a6fecca4cfeb1cf08ac";
+
+    const CONFIG: &str = r#"
+        supergraph:
+            source: file
+            path: supergraph.graphql
+        apq:
+            enabled: true
+        "#;
+
+    fn hash_only() -> Value {
+        json!({
+            "extensions": {
+                "persistedQuery": { "version": 1, "sha256Hash": QUERY_HASH }
+            }
+        })
+    }
+
+    fn with_query(query: &str) -> Value {
+        json!({
+            "query": query,
+            "extensions": {
+                "persistedQuery": { "version": 1, "sha256Hash": QUERY_HASH }
+            }
+        })
+    }
+
+    #[ntex::test]
+    async fn registers_and_resolves_operations() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_post_request(router.graphql_path(), hash_only(), None)
+            .await;
+        assert_eq!(res.status(), 200);
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("PERSISTED_QUERY_NOT_FOUND")
+        );
+        assert_eq!(
+            body["errors"][0]["message"].as_str(),
+            Some("PersistedQueryNotFound")
+        );
+
+        let res = router
+            .send_post_request(router.graphql_path(), with_query(QUERY), None)
+            .await;
+        assert_eq!(res.status(), 200);
+        let registered_body = res.json_body_string_pretty().await;
+        insta::assert_snapshot!(registered_body, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "upc": "1"
+              }
+            ]
+          }
+        }
+        "#);
+
+        let res = router
+            .send_post_request(router.graphql_path(), hash_only(), None)
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json_body_string_pretty().await, registered_body);
+    }
+
+    #[ntex::test]
+    async fn resolves_operations_over_get() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_post_request(router.graphql_path(), with_query(QUERY), None)
+            .await;
+        assert_eq!(res.status(), 200);
+
+        let extensions = format!(
+            "%7B%22persistedQuery%22%3A%7B%22version%22%3A1%2C%22sha256Hash%22%3A%22{QUERY_HASH}%22%7D%7D"
+        );
+        let url = format!("{}?extensions={extensions}", router.graphql_path());
+        let res = router
+            .serv()
+            .get(url)
+            .header("accept", "application/graphql-response+json")
+            .send()
+            .await
+            .expect("failed to send graphql request");
+        assert_eq!(res.status(), 200);
+        let body = res.json_body().await;
+        assert_eq!(body["data"]["topProducts"][0]["upc"].as_str(), Some("1"));
+    }
+
+    #[ntex::test]
+    async fn rejects_mismatching_hashes() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_post_request(
+                router.graphql_path(),
+                with_query("{ topProducts(first: 2) { upc } }"),
+                None,
+            )
+            .await;
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("PERSISTED_QUERY_HASH_MISMATCH")
+        );
+
+        // the mismatching operation is not registered
+        let res = router
+            .send_post_request(router.graphql_path(), hash_only(), None)
+            .await;
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("PERSISTED_QUERY_NOT_FOUND")
+        );
+    }
+
+    #[ntex::test]
+    async fn ignores_hashes_when_disabled() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_post_request(
+                router.graphql_path(),
+                with_query("{ topProducts(first: 2) { upc } }"),
+                None,
+            )
+            .await;
+        assert_eq!(res.status(), 200);
+        let body = res.json_body().await;
+        assert!(body["errors"].is_null());
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod aws_sigv4_e2e_tests {
+    use crate::testkit::{EnvVarsGuard, TestRouter, TestSubgraphs};
+
+    #[ntex::test]
+    async fn should_sign_subgraph_requests_with_credentials_from_env() {
+        let _env_guard = EnvVarsGuard::new()
+            .set("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE")
+            .set(
+                "AWS_SECRET_ACCESS_KEY",
+                "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            )
+            .set("AWS_SESSION_TOKEN", "session-token")
+            .apply()
+            .await;
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                traffic_shaping:
+                  all:
+                    aws_sig_v4:
+                      region: eu-west-1
+                      service: appsync
+                  subgraphs:
+                    accounts:
+                      aws_sig_v4:
+                        enabled: true
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ users { id } topProducts { upc } }", None, None)
+            .await;
+        assert!(res.status().is_success());
+
+        let accounts_requests = subgraphs
+            .get_requests_log("accounts")
+            .expect("expected requests sent to accounts subgraph");
+        assert_eq!(accounts_requests.len(), 1);
+        let headers = &accounts_requests[0].headers;
+        let authorization = headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .expect("expected the request to be signed");
+        assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(authorization.contains("/eu-west-1/appsync/aws4_request"));
+        assert_eq!(
+            headers
+                .get("x-amz-security-token")
+                .and_then(|v| v.to_str().ok()),
+            Some("session-token")
+        );
+        assert!(headers.contains_key("x-amz-date"));
+        assert!(headers.contains_key("x-amz-content-sha256"));
+
+        let products_requests = subgraphs
+            .get_requests_log("products")
+            .expect("expected requests sent to products subgraph");
+        assert_eq!(products_requests.len(), 1);
+        assert!(!products_requests[0].headers.contains_key("authorization"));
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod client_disconnect_e2e_tests {
+    use std::time::Duration;
+
+    use crate::testkit::{TestRouter, TestSubgraphs};
+
+    const QUERY: &str = "{ topProducts(first: 1) { upc reviews { body } } }";
+
+    #[ntex::test]
+    async fn cancels_the_remaining_plan_nodes_when_the_client_disconnects() {
+        let subgraphs = TestSubgraphs::builder()
+            .with_delay(Duration::from_millis(500))
+            .build()
+            .start()
+            .await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        // the client gives up while the products subgraph is still responding
+        let result = tokio::time::timeout(
+            Duration::from_millis(200),
+            router.send_graphql_request(QUERY, None, None),
+        )
+        .await;
+        assert!(result.is_err(), "expected the client to give up");
+
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+
+        assert!(
+            subgraphs
+                .get_requests_log("reviews")
+                .unwrap_or_default()
+                .is_empty(),
+            "the reviews of the products should not be fetched for a disconnected client"
+        );
+    }
+
+    #[ntex::test]
+    async fn completes_the_execution_when_cancellation_is_disabled() {
+        let subgraphs = TestSubgraphs::builder()
+            .with_delay(Duration::from_millis(500))
+            .build()
+            .start()
+            .await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                traffic_shaping:
+                    router:
+                        cancel_on_client_disconnect: false
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let result = tokio::time::timeout(
+            Duration::from_millis(200),
+            router.send_graphql_request(QUERY, None, None),
+        )
+        .await;
+        assert!(result.is_err(), "expected the client to give up");
+
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+
+        assert_eq!(
+            subgraphs
+                .get_requests_log("reviews")
+                .unwrap_or_default()
+                .len(),
+            1,
+            "the reviews of the products should be fetched"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod client_identification_e2e_tests {
+    use ntex::http;
+    use sonic_rs::JsonValueTrait;
+
+    use crate::testkit::{some_header_map, ClientResponseExt, TestRouter, TestSubgraphs};
+
+    const QUERY: &str = "{ topProducts(first: 1) { upc } }";
+
+    #[ntex::test]
+    async fn rejects_anonymous_clients_when_required() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                telemetry:
+                    client_identification:
+                        require_name: true
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.status(), 400);
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("CLIENT_IDENTIFICATION_REQUIRED")
+        );
+        assert_eq!(
+            body["errors"][0]["message"].as_str(),
+            Some("The client must identify itself with the 'graphql-client-name' header")
+        );
+        assert!(subgraphs.get_requests_log("products").is_none());
+
+        let res = router
+            .send_graphql_request(
+                QUERY,
+                None,
+                some_header_map!(
+                    http::header::HeaderName::from_static("graphql-client-name") => "web"
+                ),
+            )
+            .await;
+        assert_eq!(res.status(), 200);
+    }
+
+    #[ntex::test]
+    async fn exposes_the_client_to_header_rules() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                telemetry:
+                    client_identification:
+                        name_header: x-app-name
+                        version_header: x-app-version
+                headers:
+                    all:
+                        request:
+                            - when:
+                                client_name: "^web$"
+                                rules:
+                                    - insert:
+                                        name: x-web
+                                        value: "true"
+                            - insert:
+                                name: x-client
+                                expression: '(string(.request.client.name) ?? "anonymous") + "@" + (string(.request.client.version) ?? "unknown")'
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                QUERY,
+                None,
+                some_header_map!(
+                    http::header::HeaderName::from_static("x-app-name") => "web",
+                    http::header::HeaderName::from_static("x-app-version") => "1.2.0"
+                ),
+            )
+            .await;
+        assert_eq!(res.status(), 200);
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.status(), 200);
+
+        let subgraph_requests = subgraphs
+            .get_requests_log("products")
+            .expect("expected requests to the products subgraph");
+        assert_eq!(subgraph_requests.len(), 2);
+        assert_eq!(
+            subgraph_requests[0]
+                .headers
+                .get("x-client")
+                .map(|v| v.to_str().unwrap()),
+            Some("web@1.2.0")
+        );
+        assert_eq!(
+            subgraph_requests[0]
+                .headers
+                .get("x-web")
+                .map(|v| v.to_str().unwrap()),
+            Some("true")
+        );
+        assert_eq!(
+            subgraph_requests[1]
+                .headers
+                .get("x-client")
+                .map(|v| v.to_str().unwrap()),
+            Some("anonymous@unknown")
+        );
+        assert!(subgraph_requests[1].headers.get("x-web").is_none());
+    }
+}
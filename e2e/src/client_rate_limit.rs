@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod client_rate_limit_e2e_tests {
+    use http::{HeaderMap, StatusCode};
+    use sonic_rs::JsonValueTrait;
+
+    use crate::testkit::{ClientResponseExt, Started, TestRouter, TestSubgraphs};
+
+    const QUERY: &str = "{ topProducts(first: 1) { upc } }";
+
+    async fn router_with_rate_limit(
+        subgraphs: &TestSubgraphs<Started>,
+        key_config: &str,
+    ) -> TestRouter<Started> {
+        TestRouter::builder()
+            .with_subgraphs(subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                rate_limit:
+                  enabled: true
+                  requests_per_second: 1
+                  burst: 2
+                  key: {key_config}
+                "#
+            ))
+            .build()
+            .start()
+            .await
+    }
+
+    fn tenant(id: &str) -> Option<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-tenant-id", id.parse().unwrap());
+        Some(headers)
+    }
+
+    #[ntex::test]
+    async fn rejects_requests_exceeding_the_limit_of_the_client() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = router_with_rate_limit(&subgraphs, "{ type: ip }").await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert!(res.status().is_success());
+        assert_eq!(res.headers().get("x-ratelimit-limit").unwrap(), "2");
+        assert_eq!(res.headers().get("x-ratelimit-remaining").unwrap(), "1");
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert!(res.status().is_success());
+        assert_eq!(res.headers().get("x-ratelimit-remaining").unwrap(), "0");
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers().get("retry-after").unwrap(), "1");
+        assert_eq!(res.headers().get("x-ratelimit-remaining").unwrap(), "0");
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("RATE_LIMITED")
+        );
+
+        assert_eq!(
+            subgraphs
+                .get_requests_log("products")
+                .expect("expected requests sent to products subgraph")
+                .len(),
+            2,
+            "the limited request should not reach the subgraphs"
+        );
+    }
+
+    #[ntex::test]
+    async fn limits_every_client_on_its_own() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router =
+            router_with_rate_limit(&subgraphs, "{ type: header, name: x-tenant-id }").await;
+
+        for _ in 0..2 {
+            let res = router.send_graphql_request(QUERY, None, tenant("a")).await;
+            assert!(res.status().is_success());
+        }
+        let res = router.send_graphql_request(QUERY, None, tenant("a")).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let res = router.send_graphql_request(QUERY, None, tenant("b")).await;
+        assert!(res.status().is_success());
+        assert_eq!(res.headers().get("x-ratelimit-remaining").unwrap(), "1");
+    }
+}
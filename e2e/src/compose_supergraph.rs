@@ -0,0 +1,216 @@
+#[cfg(test)]
+mod compose_supergraph_e2e_tests {
+    use std::{fs, time::Duration};
+
+    use sonic_rs::JsonValueTrait;
+    use tempfile::NamedTempFile;
+
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    const INVENTORY_SCHEMA: &str =
+        include_str!("../../lib/query-planner/fixture/composition/inventory.graphql");
+
+    fn write_subgraph_schema(file: &NamedTempFile, schema: &str) -> String {
+        let path = file
+            .path()
+            .to_str()
+            .expect("failed to convert path to string")
+            .to_string();
+        fs::write(&path, schema).expect("failed to write subgraph schema");
+        path
+    }
+
+    #[ntex::test]
+    async fn should_compose_supergraph_from_subgraphs() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let file = NamedTempFile::new().expect("failed to create temp file");
+        let inventory_schema_path = write_subgraph_schema(&file, INVENTORY_SCHEMA);
+        let url = subgraphs.url();
+
+        let router = TestRouter::builder()
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: compose
+                    subgraphs:
+                        - name: accounts
+                          routing_url: {url}/accounts
+                        - name: inventory
+                          routing_url: {url}/inventory
+                          schema_file: {inventory_schema_path}
+                        - name: products
+                          routing_url: {url}/products
+                        - name: reviews
+                          routing_url: {url}/reviews
+                "#,
+            ))
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                "{ topProducts(first: 2) { name shippingEstimate reviews { author { username } } } }",
+                None,
+                None,
+            )
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "name": "Table",
+                "shippingEstimate": 50,
+                "reviews": [
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  },
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  },
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  },
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  }
+                ]
+              },
+              {
+                "name": "Couch",
+                "shippingEstimate": 0,
+                "reviews": [
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  },
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  },
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  },
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  }
+                ]
+              }
+            ]
+          }
+        }
+        "#);
+    }
+
+    #[ntex::test]
+    async fn should_recompose_supergraph_when_subgraph_schema_changes() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let file = NamedTempFile::new().expect("failed to create temp file");
+        let inventory_schema_path = write_subgraph_schema(&file, INVENTORY_SCHEMA);
+        let url = subgraphs.url();
+
+        let router = TestRouter::builder()
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: compose
+                    poll_interval: 100ms
+                    subgraphs:
+                        - name: inventory
+                          routing_url: {url}/inventory
+                          schema_file: {inventory_schema_path}
+                        - name: products
+                          routing_url: {url}/products
+                "#,
+            ))
+            .build()
+            .start()
+            .await;
+
+        let query = r#"{ __type(name: "Product") { fields { name } } }"#;
+        let res = router.send_graphql_request(query, None, None).await;
+        let body = String::from_utf8_lossy(&res.body().await.unwrap()).to_string();
+        assert!(body.contains("inStock"), "Expected 'inStock' field: {body}");
+
+        write_subgraph_schema(&file, &INVENTORY_SCHEMA.replace("inStock", "isAvailable"));
+
+        // Poll for the supergraph to be recomposed
+        let mut attempts = 0;
+        loop {
+            let res = router.send_graphql_request(query, None, None).await;
+            let body = String::from_utf8_lossy(&res.body().await.unwrap()).to_string();
+            if body.contains("isAvailable") {
+                assert!(
+                    !body.contains("inStock"),
+                    "Expected 'inStock' to be removed"
+                );
+                break;
+            }
+
+            attempts += 1;
+            if attempts >= 20 {
+                panic!("Supergraph was not recomposed within timeout");
+            }
+            ntex::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    #[ntex::test]
+    async fn should_serve_a_single_non_federated_api() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let file = NamedTempFile::new().expect("failed to create temp file");
+        let schema_path = write_subgraph_schema(
+            &file,
+            "type Query { users: [User] } type User { id: ID! username: String }",
+        );
+        let url = subgraphs.url();
+
+        let router = TestRouter::builder()
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: schema
+                    endpoint: {url}/accounts
+                    path: {schema_path}
+                "#,
+            ))
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ users { id username } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        let json_body = res.json_body().await;
+        assert!(json_body.get("errors").is_none(), "Expected no errors");
+        assert_eq!(json_body["data"]["users"][0]["id"], "1");
+
+        let subgraph_requests = subgraphs
+            .get_requests_log("accounts")
+            .expect("expected requests to the api");
+        assert_eq!(
+            subgraph_requests.len(),
+            1,
+            "expected the operation to be sent in a single request"
+        );
+    }
+}
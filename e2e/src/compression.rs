@@ -0,0 +1,206 @@
+#[cfg(test)]
+mod compression_e2e_tests {
+    use std::io::{Read, Write};
+
+    use bytes::Bytes;
+
+    use crate::testkit::{ClientResponseExt, ResponseLike, TestRouter, TestSubgraphs};
+
+    const TOP_PRODUCTS_RESPONSE: &str = r#"{"data":{"topProducts":[{"upc":"1"},{"upc":"2"}]}}"#;
+
+    fn gzip(body: &[u8]) -> Bytes {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(body).unwrap();
+        Bytes::from(encoder.finish().unwrap())
+    }
+
+    /// The `products` subgraph responds with a gzip-compressed body,
+    /// when the router accepts it.
+    fn gzip_products_response(req: crate::testkit::RequestLike) -> Option<ResponseLike> {
+        if req.path != "/products" {
+            return None;
+        }
+        let accepts_gzip = req
+            .headers
+            .get(http::header::ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("gzip"));
+        if !accepts_gzip {
+            return None;
+        }
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::from_static("application/json"),
+        );
+        headers.insert(
+            http::header::CONTENT_ENCODING,
+            http::HeaderValue::from_static("gzip"),
+        );
+        Some(ResponseLike {
+            status: axum::http::StatusCode::OK,
+            headers,
+            body: Some(gzip(TOP_PRODUCTS_RESPONSE.as_bytes())),
+        })
+    }
+
+    #[ntex::test]
+    async fn should_decompress_subgraph_responses() {
+        let subgraphs = TestSubgraphs::builder()
+            .with_on_request(gzip_products_response)
+            .build()
+            .start()
+            .await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ topProducts(first: 2) { upc } }", None, None)
+            .await;
+
+        assert!(res.status().is_success());
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "upc": "1"
+              },
+              {
+                "upc": "2"
+              }
+            ]
+          }
+        }
+        "#);
+
+        let requests = subgraphs
+            .get_requests_log("products")
+            .expect("expected requests sent to products subgraph");
+        assert_eq!(
+            requests[0].headers[http::header::ACCEPT_ENCODING],
+            "gzip, br, zstd"
+        );
+    }
+
+    #[ntex::test]
+    async fn should_not_accept_compressed_responses_when_disabled() {
+        let subgraphs = TestSubgraphs::builder()
+            .with_on_request(gzip_products_response)
+            .build()
+            .start()
+            .await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                traffic_shaping:
+                  subgraphs:
+                    products:
+                      compression:
+                        accept_encoding: false
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ topProducts(first: 1) { upc } }", None, None)
+            .await;
+
+        assert!(res.status().is_success());
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "upc": "1"
+              }
+            ]
+          }
+        }
+        "#);
+
+        let requests = subgraphs
+            .get_requests_log("products")
+            .expect("expected requests sent to products subgraph");
+        assert!(!requests[0]
+            .headers
+            .contains_key(http::header::ACCEPT_ENCODING));
+    }
+
+    #[ntex::test]
+    async fn should_compress_large_request_bodies() {
+        let subgraphs = TestSubgraphs::builder()
+            .with_on_request(|req| {
+                if req.path != "/products" {
+                    return None;
+                }
+                // The subgraph can't decompress the request, respond on its behalf
+                Some(ResponseLike::new(
+                    axum::http::StatusCode::OK,
+                    Some(TOP_PRODUCTS_RESPONSE.to_string()),
+                    Some(http::HeaderMap::from_iter([(
+                        http::header::CONTENT_TYPE,
+                        http::HeaderValue::from_static("application/json"),
+                    )])),
+                ))
+            })
+            .build()
+            .start()
+            .await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                traffic_shaping:
+                  all:
+                    compression:
+                      request_encoding: gzip
+                      request_min_size: 10
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                "{ topProducts(first: 2) { upc upc1: upc upc2: upc upc3: upc upc4: upc } }",
+                None,
+                None,
+            )
+            .await;
+        assert!(res.status().is_success());
+
+        let requests = subgraphs
+            .get_requests_log("products")
+            .expect("expected requests sent to products subgraph");
+        assert_eq!(requests[0].headers[http::header::CONTENT_ENCODING], "gzip");
+
+        let body = requests[0].body.as_ref().expect("expected a request body");
+        let mut decompressed = String::new();
+        flate2::read::GzDecoder::new(body.as_ref())
+            .read_to_string(&mut decompressed)
+            .expect("request body should be gzip-compressed");
+        assert!(decompressed.starts_with(r#"{"query":"#));
+    }
+}
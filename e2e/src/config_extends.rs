@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod config_extends_e2e_tests {
+    use std::path::PathBuf;
+
+    use hive_router_config::{load_config, supergraph::SupergraphSource, RouterConfigError};
+    use sonic_rs::JsonValueTrait;
+
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    fn config_path(name: &str) -> String {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("configs/extends")
+            .join(name)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[ntex::test]
+    /// Test that a config is overlaid on the config it extends:
+    /// the headers rules of the overlay replace the ones of the base config,
+    /// while the supergraph and the limits come from the base config.
+    async fn should_overlay_the_extended_config() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .file_config("configs/extends/prod.router.yaml")
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+        assert_eq!(
+            res.headers().get("x-env").map(|v| v.to_str().unwrap()),
+            Some("prod")
+        );
+        assert!(res.headers().get("x-base").is_none());
+
+        let res = router
+            .send_graphql_request("{ a: users { id } b: users { id } }", None, None)
+            .await;
+        assert_eq!(
+            res.json_body().await["errors"][0]["extensions"]["code"].as_str(),
+            Some("MAX_ALIASES_EXCEEDED")
+        );
+    }
+
+    #[ntex::test]
+    async fn should_reject_configs_extending_each_other() {
+        let err = load_config(Some(config_path("cycle_a.router.yaml")))
+            .expect_err("expected the cycle to be rejected");
+
+        assert!(
+            matches!(&err, RouterConfigError::ConfigExtendsCycle(cycle) if cycle.ends_with("cycle_a.router.yaml")),
+            "{err}"
+        );
+    }
+
+    #[ntex::test]
+    /// Test that the relative paths of an extended file are relative to the directory of that file,
+    /// and not to the directory of the extending file.
+    async fn should_resolve_the_paths_of_an_extended_file_against_its_directory() {
+        let config = load_config(Some(config_path("nested/prod.router.yaml")))
+            .expect("expected the config to load");
+
+        let SupergraphSource::File {
+            path: Some(path), ..
+        } = &config.supergraph
+        else {
+            panic!("expected a file supergraph source");
+        };
+        let expected = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("supergraph.graphql")
+            .canonicalize()
+            .unwrap();
+        assert_eq!(PathBuf::from(&path.absolute), expected);
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .file_config("configs/extends/nested/prod.router.yaml")
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+        assert_eq!(
+            res.headers().get("x-env").map(|v| v.to_str().unwrap()),
+            Some("prod")
+        );
+    }
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod config_reload_e2e_tests {
+    use hive_router::{reload_router_config, ConfigReloadError};
+    use hive_router_config::{parse_yaml_config, HiveRouterConfig};
+    use sonic_rs::JsonValueTrait;
+
+    use crate::testkit::{ClientResponseExt, Started, TestRouter};
+
+    const CONFIG: &str = r#"
+        supergraph:
+            source: file
+            path: supergraph.graphql
+        "#;
+
+    const QUERY: &str = "{ a: __typename b: __typename }";
+
+    /// Parses the config, on the port the router listens on, as the router was started with it.
+    fn config(router: &TestRouter<Started>, config_yaml: &str) -> HiveRouterConfig {
+        let mut config =
+            parse_yaml_config(config_yaml.to_string()).expect("failed to parse the config");
+        config.http.port = router.shared_state().router_config.http.port;
+        config
+    }
+
+    #[ntex::test]
+    async fn applies_the_reloadable_fields() {
+        let router = TestRouter::builder()
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json_body().await["data"]["a"].as_str(), Some("Query"));
+
+        let changed = reload_router_config(
+            router.shared_state(),
+            router.schema_state(),
+            config(
+                &router,
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                limits:
+                    max_aliases:
+                        n: 1
+                "#,
+            ),
+        )
+        .await
+        .expect("failed to reload the config");
+        assert_eq!(changed, vec!["limits.max_aliases"]);
+
+        // the operation was validated, and cached, before the reload
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("MAX_ALIASES_EXCEEDED")
+        );
+
+        let changed = reload_router_config(
+            router.shared_state(),
+            router.schema_state(),
+            config(&router, CONFIG),
+        )
+        .await
+        .expect("failed to reload the config");
+        assert_eq!(changed, vec!["limits.max_aliases"]);
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.json_body().await["data"]["b"].as_str(), Some("Query"));
+    }
+
+    #[ntex::test]
+    async fn applies_the_reloaded_parser_limits_to_parsed_operations() {
+        let router = TestRouter::builder()
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.json_body().await["data"]["a"].as_str(), Some("Query"));
+
+        let changed = reload_router_config(
+            router.shared_state(),
+            router.schema_state(),
+            config(
+                &router,
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                limits:
+                    max_tokens:
+                        n: 3
+                "#,
+            ),
+        )
+        .await
+        .expect("failed to reload the config");
+        assert_eq!(changed, vec!["limits.max_tokens"]);
+
+        // the operation was parsed, and cached, before the reload
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(
+            res.json_body().await["errors"][0]["extensions"]["code"].as_str(),
+            Some("TOKEN_LIMIT_EXCEEDED")
+        );
+    }
+
+    #[ntex::test]
+    async fn rejects_configs_changing_fields_applied_on_start_only() {
+        let router = TestRouter::builder()
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let err = reload_router_config(
+            router.shared_state(),
+            router.schema_state(),
+            config(
+                &router,
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                limits:
+                    max_aliases:
+                        n: 1
+                query_planner:
+                    timeout: 1s
+                "#,
+            ),
+        )
+        .await
+        .expect_err("expected the config to be rejected");
+        assert!(
+            matches!(&err, ConfigReloadError::NotReloadable(fields) if fields == &["query_planner.timeout"]),
+            "{err}"
+        );
+
+        // nothing was applied
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.json_body().await["data"]["a"].as_str(), Some("Query"));
+        assert!(reload_router_config(
+            router.shared_state(),
+            router.schema_state(),
+            config(&router, CONFIG),
+        )
+        .await
+        .expect("failed to reload the config")
+        .is_empty());
+    }
+}
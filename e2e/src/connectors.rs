@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod connectors_e2e_tests {
+    use sonic_rs::{json, JsonValueTrait};
+
+    use crate::testkit::{some_header_map, ClientResponseExt, TestRouter};
+
+    fn router_config(host: &str) -> String {
+        format!(
+            r#"
+            supergraph:
+                source: file
+                path: supergraph-connectors.graphql
+            override_subgraph_urls:
+                subgraphs:
+                    users:
+                        url: "http://{host}"
+            headers:
+                all:
+                    request:
+                        - propagate:
+                            named: x-api-key
+            "#
+        )
+    }
+
+    #[ntex::test]
+    async fn should_resolve_fields_with_rest_connectors() {
+        let mut server = mockito::Server::new_async().await;
+        let host = server.host_with_port();
+
+        let user_mock = server
+            .mock("GET", "/users/1")
+            .match_header("x-api-key", "secret")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"id":"1","name":"Ada","contact":{"email":"ada@example.com"},"internal":true}"#,
+            )
+            .create_async()
+            .await;
+
+        let router = TestRouter::builder()
+            .inline_config(router_config(&host))
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                r#"{ me: user(id: "1") { __typename id name email } }"#,
+                None,
+                some_header_map! {
+                    http::header::HeaderName::from_static("x-api-key") => "secret"
+                },
+            )
+            .await;
+
+        assert_eq!(res.status(), 200);
+        let body = res.json_body().await;
+        assert!(body["errors"].is_null(), "unexpected errors: {body}");
+        assert_eq!(
+            body["data"],
+            json!({
+                "me": {
+                    "__typename": "User",
+                    "id": "1",
+                    "name": "Ada",
+                    "email": "ada@example.com"
+                }
+            })
+        );
+
+        user_mock.assert_async().await;
+    }
+
+    #[ntex::test]
+    async fn should_map_lists_and_report_failed_calls() {
+        let mut server = mockito::Server::new_async().await;
+        let host = server.host_with_port();
+
+        server
+            .mock("GET", "/users")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"results":[{"id":"1","name":"Ada"},{"id":"2","name":"Alan"}]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/users/3")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let router = TestRouter::builder()
+            .inline_config(router_config(&host))
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(r#"{ users { name } user(id: "3") { id } }"#, None, None)
+            .await;
+
+        assert_eq!(res.status(), 200);
+        let body = res.json_body().await;
+        assert_eq!(
+            body["data"]["users"],
+            json!([{ "name": "Ada" }, { "name": "Alan" }])
+        );
+        assert!(body["data"]["user"].is_null());
+        assert_eq!(
+            body["errors"][0]["message"].as_str(),
+            Some("the REST API responded with status 404 Not Found")
+        );
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("CONNECTOR_FETCH_ERROR")
+        );
+    }
+}
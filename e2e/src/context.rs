@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod context_e2e_tests {
+    use serde_json::json;
+
+    use crate::testkit::{
+        mock_subgraphs::mock_subgraphs, ClientResponseExt, Started, TestRouter, TestSubgraphs,
+        TestSubgraphsBuilder,
+    };
+
+    // `User` sets the `userContext` in the `payments` subgraph,
+    // and `Transaction.amount` receives the `currency` of the user with @fromContext.
+    const CONFIG: &str = r#"
+        supergraph:
+            source: file
+            path: ../lib/query-planner/fixture/tests/context.supergraph.graphql
+    "#;
+
+    fn subgraphs() -> TestSubgraphsBuilder {
+        let alice = json!({ "__typename": "User", "id": "1", "name": "Alice" });
+        let bob = json!({ "__typename": "User", "id": "2", "name": "Bob" });
+
+        TestSubgraphs::builder().with_on_request(mock_subgraphs(json!({
+            "accounts": {
+                "query": {
+                    "me": alice,
+                    "users": [alice, bob],
+                },
+                "entities": [alice, bob],
+            },
+            "payments": {
+                "entities": [
+                    {
+                        "__typename": "User",
+                        "id": "1",
+                        "currency": "EUR",
+                        "transactions": [{ "__typename": "Transaction", "id": "t1" }],
+                    },
+                    {
+                        "__typename": "User",
+                        "id": "2",
+                        "currency": "USD",
+                        "transactions": [{ "__typename": "Transaction", "id": "t2" }],
+                    },
+                    { "__typename": "Transaction", "id": "t1", "amount": "10.00" },
+                    { "__typename": "Transaction", "id": "t2", "amount": "25.00" },
+                ],
+            },
+        })))
+    }
+
+    /// Request bodies sent to the `payments` subgraph, that pass a contextual argument.
+    fn contextual_requests(subgraphs: &TestSubgraphs<Started>) -> Vec<String> {
+        subgraphs
+            .get_requests_log("payments")
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|req| req.body)
+            .map(|body| String::from_utf8_lossy(&body).to_string())
+            .filter(|body| body.contains("\"contextualArgument_0\":"))
+            .collect()
+    }
+
+    #[ntex::test]
+    async fn should_pass_value_from_context_to_field() {
+        let subgraphs = subgraphs().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ me { name transactions { id amount } } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        let requests = contextual_requests(&subgraphs);
+        assert_eq!(requests.len(), 1, "expected a single request: {requests:?}");
+        assert!(
+            requests[0].contains("\"contextualArgument_0\":\"EUR\""),
+            "expected the currency of the user: {}",
+            requests[0]
+        );
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "me": {
+              "name": "Alice",
+              "transactions": [
+                {
+                  "id": "t1",
+                  "amount": "10.00"
+                }
+              ]
+            }
+          }
+        }
+        "#);
+    }
+
+    /// Entities receiving different values are resolved in separate requests.
+    #[ntex::test]
+    async fn should_split_entity_calls_by_value_from_context() {
+        let subgraphs = subgraphs().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ users { name transactions { id amount } } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        let requests = contextual_requests(&subgraphs);
+        assert_eq!(
+            requests.len(),
+            2,
+            "expected a request per currency: {requests:?}"
+        );
+        assert!(requests
+            .iter()
+            .any(|body| body.contains("\"contextualArgument_0\":\"EUR\"")));
+        assert!(requests
+            .iter()
+            .any(|body| body.contains("\"contextualArgument_0\":\"USD\"")));
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "users": [
+              {
+                "name": "Alice",
+                "transactions": [
+                  {
+                    "id": "t1",
+                    "amount": "10.00"
+                  }
+                ]
+              },
+              {
+                "name": "Bob",
+                "transactions": [
+                  {
+                    "id": "t2",
+                    "amount": "25.00"
+                  }
+                ]
+              }
+            ]
+          }
+        }
+        "#);
+    }
+}
@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod contract_e2e_tests {
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    const CONTRACT_CONFIG: &str = r#"
+        supergraph:
+            source: file
+            path: supergraph-contract.graphql
+        contract:
+            exclude_tags: [internal]
+    "#;
+
+    #[ntex::test]
+    async fn should_reject_excluded_fields() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONTRACT_CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ me { id birthday } users { id } }", None, None)
+            .await;
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "errors": [
+            {
+              "message": "Cannot query field \"birthday\" on type \"User\".",
+              "locations": [
+                {
+                  "line": 1,
+                  "column": 11
+                }
+              ],
+              "extensions": {
+                "code": "FieldsOnCorrectType"
+              }
+            },
+            {
+              "message": "Cannot query field \"users\" on type \"Query\".",
+              "locations": [
+                {
+                  "line": 1,
+                  "column": 22
+                }
+              ],
+              "extensions": {
+                "code": "FieldsOnCorrectType"
+              }
+            }
+          ]
+        }
+        "#);
+        assert_eq!(
+            subgraphs
+                .get_requests_log("accounts")
+                .map(|r| r.len())
+                .unwrap_or(0),
+            0,
+            "expected 0 requests to accounts subgraph"
+        );
+    }
+
+    #[ntex::test]
+    async fn should_hide_excluded_fields_from_introspection() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONTRACT_CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                r#"{
+                  query: __type(name: "Query") { fields { name } }
+                  user: __type(name: "User") { fields { name } }
+                }"#,
+                None,
+                None,
+            )
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "query": {
+              "fields": [
+                {
+                  "name": "me"
+                },
+                {
+                  "name": "user"
+                },
+                {
+                  "name": "topProducts"
+                }
+              ]
+            },
+            "user": {
+              "fields": [
+                {
+                  "name": "id"
+                },
+                {
+                  "name": "name"
+                },
+                {
+                  "name": "username"
+                },
+                {
+                  "name": "reviews"
+                }
+              ]
+            }
+          }
+        }
+        "#);
+    }
+
+    #[ntex::test]
+    async fn should_plan_against_the_full_supergraph() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONTRACT_CONFIG)
+            .build()
+            .start()
+            .await;
+
+        // `shippingEstimate` requires `weight`, hidden from the clients but still fetched
+        let res = router
+            .send_graphql_request(
+                "{ topProducts(first: 1) { name shippingEstimate reviews { author { username } } } }",
+                None,
+                None,
+            )
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "name": "Table",
+                "shippingEstimate": 50,
+                "reviews": [
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  },
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  },
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  },
+                  {
+                    "author": {
+                      "username": "urigo"
+                    }
+                  }
+                ]
+              }
+            ]
+          }
+        }
+        "#);
+    }
+}
@@ -13,4 +13,6 @@ mod router_request;
 #[cfg(test)]
 mod router_response;
 #[cfg(test)]
+mod subgraph;
+#[cfg(test)]
 mod unix_domain_socket;
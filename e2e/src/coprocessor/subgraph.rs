@@ -0,0 +1,236 @@
+use sonic_rs::json;
+use sonic_rs::JsonValueTrait;
+
+use crate::testkit::{coprocessor::TestCoprocessor, ClientResponseExt, TestRouter, TestSubgraphs};
+
+#[ntex::test]
+/// This test checks that subgraph.request receives the subgraph name and request headers,
+/// and that the headers returned by the coprocessor are sent to the subgraph.
+async fn replaces_subgraph_request_headers() {
+    let subgraphs = TestSubgraphs::builder().build().start().await;
+    let mut coprocessor = TestCoprocessor::new().await;
+    let host = coprocessor.host_with_port();
+
+    let request_stage_mock = coprocessor
+        .mock_stage_with_matcher("subgraph.request", |payload| {
+            payload.get("subgraph_name").and_then(|name| name.as_str()) == Some("products")
+                && payload.pointer(&["headers", "content-type"]).is_some()
+                && payload.get("body").is_none()
+        })
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+              "version": 1,
+              "control": "continue",
+              "headers": {
+                "content-type": "application/json",
+                "x-from-coprocessor": "yes"
+              }
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+
+    let router = TestRouter::builder()
+        .with_subgraphs(&subgraphs)
+        .inline_config(format!(
+            r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                coprocessor:
+                  url: http://{host}/coprocessor
+                  protocol: http1
+                  stages:
+                    subgraph:
+                      request:
+                        condition:
+                          expression: .subgraph.name == "products"
+                        include:
+                          headers: true
+                "#
+        ))
+        .build()
+        .start()
+        .await;
+
+    let response = router
+        .send_graphql_request("{ topProducts(first:1) { name } }", None, None)
+        .await;
+
+    insta::assert_snapshot!(response.json_body_string_pretty_stable().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "name": "Table"
+              }
+            ]
+          }
+        }
+        "#);
+
+    let requests = subgraphs
+        .get_requests_log("products")
+        .expect("expected requests to the products subgraph");
+    assert_eq!(requests.len(), 1, "expected 1 request to products subgraph");
+    assert_eq!(
+        requests[0]
+            .headers
+            .get("x-from-coprocessor")
+            .and_then(|value| value.to_str().ok()),
+        Some("yes"),
+        "subgraph should receive the headers returned by the coprocessor"
+    );
+
+    request_stage_mock.assert_async().await;
+}
+
+#[ntex::test]
+/// This test checks that breaking subgraph.request skips the subgraph,
+/// the response of the coprocessor being used as the response of the subgraph.
+async fn short_circuits_subgraph_request() {
+    let subgraphs = TestSubgraphs::builder().build().start().await;
+    let mut coprocessor = TestCoprocessor::new().await;
+    let host = coprocessor.host_with_port();
+
+    let request_stage_mock = coprocessor
+        .mock_stage("subgraph.request")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+              "version": 1,
+              "control": {
+                "break": 200
+              },
+              "headers": {
+                "content-type": "application/json"
+              },
+              "body": {
+                "data": {
+                  "topProducts": [{ "name": "From coprocessor" }]
+                }
+              }
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+
+    let router = TestRouter::builder()
+        .with_subgraphs(&subgraphs)
+        .inline_config(format!(
+            r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                coprocessor:
+                  url: http://{host}/coprocessor
+                  protocol: http1
+                  stages:
+                    subgraph:
+                      request: {{}}
+                "#
+        ))
+        .build()
+        .start()
+        .await;
+
+    let response = router
+        .send_graphql_request("{ topProducts(first:1) { name } }", None, None)
+        .await;
+
+    insta::assert_snapshot!(response.json_body_string_pretty_stable().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "name": "From coprocessor"
+              }
+            ]
+          }
+        }
+        "#);
+    assert!(
+        subgraphs.get_requests_log("products").is_none(),
+        "expected no request to the products subgraph"
+    );
+
+    request_stage_mock.assert_async().await;
+}
+
+#[ntex::test]
+/// This test checks that subgraph.response receives the status code and body of the subgraph response,
+/// and that the body returned by the coprocessor replaces it.
+async fn replaces_subgraph_response_body() {
+    let subgraphs = TestSubgraphs::builder().build().start().await;
+    let mut coprocessor = TestCoprocessor::new().await;
+    let host = coprocessor.host_with_port();
+
+    let response_stage_mock = coprocessor
+        .mock_stage_with_matcher("subgraph.response", |payload| {
+            payload
+                .get("status_code")
+                .and_then(|status| status.as_u64())
+                == Some(200)
+                && payload
+                    .get("body")
+                    .and_then(|body| body.as_str())
+                    .is_some_and(|body| body.contains("Table"))
+        })
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+              "version": 1,
+              "control": "continue",
+              "body": "{\"data\":{\"topProducts\":[{\"name\":\"Chair\"}]}}"
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+
+    let router = TestRouter::builder()
+        .with_subgraphs(&subgraphs)
+        .inline_config(format!(
+            r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                coprocessor:
+                  url: http://{host}/coprocessor
+                  protocol: http1
+                  stages:
+                    subgraph:
+                      response:
+                        include:
+                          body: true
+                          status_code: true
+                "#
+        ))
+        .build()
+        .start()
+        .await;
+
+    let response = router
+        .send_graphql_request("{ topProducts(first:1) { name } }", None, None)
+        .await;
+
+    insta::assert_snapshot!(response.json_body_string_pretty_stable().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "name": "Chair"
+              }
+            ]
+          }
+        }
+        "#);
+
+    response_stage_mock.assert_async().await;
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod dedicated_listeners_e2e_tests {
+    use std::time::Duration;
+
+    use http::header::AUTHORIZATION;
+
+    use crate::testkit::{TestRouter, TestSubgraphs};
+
+    // fixed high ports, as the admin and probes servers bind to the configured addresses,
+    // see the comment of the http callback tests
+    const ADMIN_PORT: u16 = 61020;
+    const PROBES_PORT: u16 = 61021;
+
+    async fn wait_for_ok(url: &str) -> reqwest::Response {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match reqwest::get(url).await {
+                    Ok(res) if res.status() == 200 => return res,
+                    _ => tokio::time::sleep(Duration::from_millis(100)).await,
+                }
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("{url} did not respond with 200 OK"))
+    }
+
+    #[ntex::test]
+    async fn should_serve_the_admin_endpoints_and_the_probes_on_their_own_servers() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                http:
+                    probes_listen: 127.0.0.1:{PROBES_PORT}
+                admin:
+                    listen: 127.0.0.1:{ADMIN_PORT}
+                    diagnostics:
+                        enabled: true
+                        token: secret
+                "#
+            ))
+            // the probes are not served by the main server
+            .skip_wait_for_healthy_on_start()
+            .skip_wait_for_ready_on_start()
+            .build()
+            .start()
+            .await;
+
+        wait_for_ok(&format!("http://127.0.0.1:{PROBES_PORT}/health")).await;
+        let res = wait_for_ok(&format!("http://127.0.0.1:{PROBES_PORT}/readiness")).await;
+        let body: serde_json::Value = res.json().await.expect("failed to read readiness");
+        assert_eq!(body["ready"], true);
+
+        let res = router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        let res = reqwest::Client::new()
+            .get(format!("http://127.0.0.1:{ADMIN_PORT}/admin/diagnostics"))
+            .header(AUTHORIZATION, "Bearer secret")
+            .send()
+            .await
+            .expect("failed to read diagnostics");
+        assert_eq!(res.status(), 200);
+
+        for path in ["/admin/diagnostics", "/health", "/readiness"] {
+            let res = router
+                .serv()
+                .get(path)
+                .header(AUTHORIZATION, "Bearer secret")
+                .send()
+                .await
+                .expect("failed to send request");
+            assert_eq!(res.status(), 404, "{path} is served by the main server");
+        }
+    }
+}
@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod defer_e2e_tests {
+    use hive_router::{
+        async_trait,
+        plugins::{
+            hooks::{
+                on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+                on_subgraph_execute::{
+                    OnSubgraphExecuteStartHookPayload, OnSubgraphExecuteStartHookResult,
+                },
+            },
+            plugin_trait::{RouterPlugin, StartHookPayload},
+        },
+    };
+    use insta::assert_snapshot;
+
+    use crate::testkit::{
+        some_header_map, ClientResponseExt, ResponseLike, TestRouter, TestSubgraphs,
+    };
+
+    const QUERY: &str = r#"
+        query {
+            topProducts(first: 2) {
+                name
+                ... @defer(label: "stock") {
+                    inStock
+                }
+            }
+        }
+        "#;
+
+    const CONFIG: &str = r#"
+        supergraph:
+            source: file
+            path: supergraph.graphql
+        "#;
+
+    #[ntex::test]
+    async fn delivers_deferred_fragments_at_their_path() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                QUERY,
+                None,
+                some_header_map! {
+                    http::header::ACCEPT => "multipart/mixed"
+                },
+            )
+            .await;
+
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        let content_type_header = res
+            .header("content-type")
+            .expect("must have content-type header");
+
+        let body = res.body().await.unwrap();
+        let body_str = std::str::from_utf8(&body).unwrap();
+
+        assert_snapshot!(body_str, @r#"
+        ---
+        Content-Type: application/json
+
+        {"data":{"topProducts":[{"name":"Table"},{"name":"Couch"}]},"hasNext":true}
+        ---
+        Content-Type: application/json
+
+        {"incremental":[{"data":{"inStock":true},"path":["topProducts",0],"label":"stock"},{"data":{"inStock":false},"path":["topProducts",1],"label":"stock"}],"hasNext":false}
+        -----
+        "#);
+
+        assert_eq!(content_type_header, "multipart/mixed;boundary=\"-\"");
+    }
+
+    #[ntex::test]
+    async fn resolves_deferred_fragments_in_a_single_response() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+
+        assert!(res.status().is_success(), "Expected 200 OK");
+        assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "name": "Table",
+                "inStock": true
+              },
+              {
+                "name": "Couch",
+                "inStock": false
+              }
+            ]
+          }
+        }
+        "#);
+    }
+
+    #[ntex::test]
+    async fn runs_the_subgraph_hooks_of_the_plugins_for_deferred_fetches() {
+        struct TagSubgraphRequestsPlugin;
+
+        #[async_trait]
+        impl RouterPlugin for TagSubgraphRequestsPlugin {
+            type Config = ();
+
+            fn plugin_name() -> &'static str {
+                "tag_subgraph_requests"
+            }
+
+            fn on_plugin_init(payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+                payload.initialize_plugin(Self)
+            }
+
+            async fn on_subgraph_execute<'exec>(
+                &'exec self,
+                mut payload: OnSubgraphExecuteStartHookPayload<'exec>,
+            ) -> OnSubgraphExecuteStartHookResult<'exec> {
+                payload
+                    .execution_request
+                    .headers
+                    .insert("x-tagged-by", http::HeaderValue::from_static("plugin"));
+                payload.proceed()
+            }
+        }
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                plugins:
+                    tag_subgraph_requests:
+                        enabled: true
+                "#,
+            )
+            .register_plugin::<TagSubgraphRequestsPlugin>()
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                QUERY,
+                None,
+                some_header_map! {
+                    http::header::ACCEPT => "multipart/mixed"
+                },
+            )
+            .await;
+
+        assert!(res.status().is_success(), "Expected 200 OK");
+        let body = res.body().await.unwrap();
+        assert!(
+            std::str::from_utf8(&body)
+                .unwrap()
+                .contains(r#""hasNext":false"#),
+            "Expected the deferred payload to be delivered"
+        );
+
+        // `inStock` is only resolved by the deferred fetch
+        let inventory_requests = subgraphs
+            .get_requests_log("inventory")
+            .expect("expected requests sent to inventory subgraph");
+        assert_eq!(inventory_requests.len(), 1);
+        assert_eq!(
+            inventory_requests[0]
+                .headers
+                .get("x-tagged-by")
+                .map(|value| value.to_str().unwrap()),
+            Some("plugin")
+        );
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod client_budgets_tests {
+    use http::{HeaderMap, StatusCode};
+
+    use super::super::common::*;
+    use crate::testkit::Started;
+
+    const QUERY: &str = r#"query { me { name } }"#;
+
+    async fn router_with_budgets(
+        subgraphs: &TestSubgraphs<Started>,
+        mode: &str,
+    ) -> TestRouter<Started> {
+        TestRouter::builder()
+            .with_subgraphs(subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                demand_control:
+                    enabled: true
+                    operation_cost:
+                      max: 100
+                      mode: enforce
+                    subgraphs_budget:
+                      mode: measure
+                    client_budgets:
+                      mode: {mode}
+                      key:
+                        type: header
+                        name: x-tenant-id
+                      windows:
+                        - max: 2
+                          window: 1h
+                "#
+            ))
+            .build()
+            .start()
+            .await
+    }
+
+    fn tenant(id: &str) -> Option<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-tenant-id", id.parse().unwrap());
+        Some(headers)
+    }
+
+    #[ntex::test]
+    async fn rejects_operations_exceeding_the_budget_of_the_client() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = router_with_budgets(&subgraphs, "enforce").await;
+
+        for expected_remaining in [1, 0] {
+            let res = router.send_graphql_request(QUERY, None, tenant("a")).await;
+            assert!(res.status().is_success());
+            let json = res.json_body().await;
+            assert_eq!(json["data"]["me"]["name"].as_str(), Some("Uri Goldshtein"));
+            let window = &json["extensions"]["costBudget"]["windows"][0];
+            assert_eq!(window["windowSeconds"].as_u64(), Some(3600));
+            assert_eq!(window["max"].as_u64(), Some(2));
+            assert_eq!(window["remaining"].as_u64(), Some(expected_remaining));
+        }
+
+        let res = router.send_graphql_request(QUERY, None, tenant("a")).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(res.headers().get("retry-after").is_some());
+        let json = res.json_body().await;
+        assert_eq!(
+            json["errors"][0]["extensions"]["code"].as_str(),
+            Some("COST_BUDGET_EXCEEDED")
+        );
+
+        let res = router.send_graphql_request(QUERY, None, tenant("b")).await;
+        assert!(res.status().is_success());
+        let json = res.json_body().await;
+        assert_eq!(
+            json["extensions"]["costBudget"]["windows"][0]["remaining"].as_u64(),
+            Some(1)
+        );
+    }
+
+    #[ntex::test]
+    async fn mode_measure_allows_operations_exceeding_the_budget() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = router_with_budgets(&subgraphs, "measure").await;
+
+        for _ in 0..3 {
+            let res = router.send_graphql_request(QUERY, None, tenant("a")).await;
+            assert!(res.status().is_success());
+            let json = res.json_body().await;
+            assert_eq!(json["data"]["me"]["name"].as_str(), Some("Uri Goldshtein"));
+        }
+    }
+}
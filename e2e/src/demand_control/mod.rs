@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod actual_cost;
 #[cfg(test)]
+mod client_budgets;
+#[cfg(test)]
 mod common;
 #[cfg(test)]
 mod enforcement;
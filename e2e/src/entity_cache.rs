@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod entity_cache_e2e_tests {
+    use sonic_rs::{JsonContainerTrait, Value};
+
+    use crate::testkit::{ClientResponseExt, Started, TestRouter, TestSubgraphs};
+
+    const CONFIG: &str = r#"
+        supergraph:
+            source: file
+            path: supergraph.graphql
+        entity_cache:
+            enabled: true
+            types:
+                Product:
+                    ttl: 60s
+        "#;
+
+    /// The representations sent by every `_entities` request to the subgraph.
+    fn representations(subgraphs: &TestSubgraphs<Started>, subgraph: &str) -> Vec<usize> {
+        subgraphs
+            .get_requests_log(subgraph)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|request| {
+                let body: Value = sonic_rs::from_slice(request.body.as_ref()?).ok()?;
+                Some(body["variables"]["representations"].as_array()?.len())
+            })
+            .collect()
+    }
+
+    #[ntex::test]
+    async fn serves_cached_entities_without_fetching_them() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let query = r#"{ topProducts(first: 2) { upc reviews { id body } } }"#;
+
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+        let first_body = res.json_body_string_pretty().await;
+        assert_eq!(representations(&subgraphs, "reviews"), vec![2]);
+
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json_body_string_pretty().await, first_body);
+        assert_eq!(
+            representations(&subgraphs, "reviews"),
+            vec![2],
+            "expected the entities to be served from the cache"
+        );
+    }
+
+    #[ntex::test]
+    async fn only_fetches_uncached_representations() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                r#"{ topProducts(first: 1) { upc reviews { id body } } }"#,
+                None,
+                None,
+            )
+            .await;
+        assert_eq!(res.status(), 200);
+
+        let res = router
+            .send_graphql_request(
+                r#"{ topProducts(first: 3) { upc reviews { id body } } }"#,
+                None,
+                None,
+            )
+            .await;
+        assert_eq!(res.status(), 200);
+        let body = res.json_body_string_pretty().await;
+
+        assert_eq!(representations(&subgraphs, "reviews"), vec![1, 2]);
+
+        // the same response as without the entity cache
+        let uncached_subgraphs = TestSubgraphs::builder().build().start().await;
+        let uncached_router = TestRouter::builder()
+            .with_subgraphs(&uncached_subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+        let res = uncached_router
+            .send_graphql_request(
+                r#"{ topProducts(first: 3) { upc reviews { id body } } }"#,
+                None,
+                None,
+            )
+            .await;
+        assert_eq!(res.json_body_string_pretty().await, body);
+    }
+
+    #[ntex::test]
+    async fn does_not_cache_unlisted_types() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let query = r#"{ topProducts(first: 1) { reviews { author { name } } } }"#;
+        for _ in 0..2 {
+            let res = router.send_graphql_request(query, None, None).await;
+            assert_eq!(res.status(), 200);
+        }
+
+        assert_eq!(representations(&subgraphs, "reviews"), vec![1]);
+        assert_eq!(
+            representations(&subgraphs, "accounts").len(),
+            2,
+            "expected User entities to be fetched on every request"
+        );
+    }
+}
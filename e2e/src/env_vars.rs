@@ -1,5 +1,7 @@
 #[cfg(test)]
 mod env_vars_e2e_tests {
+    use hive_router_config::parse_yaml_config;
+
     use crate::testkit::{ClientResponseExt, EnvVarsGuard, TestRouter, TestSubgraphs};
 
     #[ntex::test]
@@ -146,4 +148,80 @@ mod env_vars_e2e_tests {
             );
         }
     }
+
+    #[ntex::test]
+    /// Test that the `${ENV_VAR}` placeholders of the config values are expanded on load,
+    /// with a fallback to their default, and that a missing variable without a default
+    /// fails the load.
+    async fn should_interpolate_env_vars_in_config_values() {
+        const CONFIG: &str = r#"
+            supergraph:
+                source: file
+                path: supergraph.graphql
+            headers:
+                all:
+                    response:
+                        - insert:
+                            name: x-router-env
+                            value: ${E2E_ROUTER_ENV:-default}
+            "#;
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+
+        {
+            let router = TestRouter::builder()
+                .with_subgraphs(&subgraphs)
+                .inline_config(CONFIG)
+                .build()
+                .start()
+                .await;
+
+            let res = router
+                .send_graphql_request("{ users { id } }", None, None)
+                .await;
+
+            assert_eq!(
+                res.headers()
+                    .get("x-router-env")
+                    .map(|v| v.to_str().unwrap()),
+                Some("default")
+            );
+
+            drop(router); // Ensure router is dropped before subgraphs
+        }
+
+        {
+            let _env_guard = EnvVarsGuard::new()
+                .set("E2E_ROUTER_ENV", "e2e")
+                .apply()
+                .await;
+
+            let router = TestRouter::builder()
+                .with_subgraphs(&subgraphs)
+                .inline_config(CONFIG)
+                .build()
+                .start()
+                .await;
+
+            let res = router
+                .send_graphql_request("{ users { id } }", None, None)
+                .await;
+
+            assert_eq!(
+                res.headers()
+                    .get("x-router-env")
+                    .map(|v| v.to_str().unwrap()),
+                Some("e2e")
+            );
+
+            drop(router); // Ensure router is dropped before subgraphs
+        }
+
+        let err = parse_yaml_config(CONFIG.replace(":-default", ""))
+            .expect_err("expected the config to reference a variable that is not set");
+        assert_eq!(
+            err.to_string(),
+            "Failed to interpolate the environment variables: 'headers.all.response[0].insert.value' references the environment variable 'E2E_ROUTER_ENV', which is not set. Set it, or give it a default with '${E2E_ROUTER_ENV:-default}'"
+        );
+    }
 }
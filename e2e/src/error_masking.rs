@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod error_masking_e2e_tests {
+    use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+
+    use crate::testkit::{ClientResponseExt, ResponseLike, Started, TestRouter, TestSubgraphs};
+
+    async fn subgraphs_with_errors() -> TestSubgraphs<Started> {
+        TestSubgraphs::builder()
+            .with_on_request(|req| {
+                if req.path != "/accounts" {
+                    return None;
+                }
+                let mut headers = http::HeaderMap::new();
+                headers.insert(
+                    http::header::CONTENT_TYPE,
+                    http::HeaderValue::from_static("application/json"),
+                );
+                Some(ResponseLike::new(
+                    axum::http::StatusCode::OK,
+                    Some(
+                        r#"{
+                          "data": { "me": null },
+                          "errors": [
+                            {
+                              "message": "connection to db.internal:5432 refused",
+                              "path": ["me"],
+                              "extensions": { "code": "INTERNAL_SERVER_ERROR", "stacktrace": ["at me"] }
+                            },
+                            {
+                              "message": "You must be logged in",
+                              "path": ["me"],
+                              "extensions": { "code": "UNAUTHENTICATED", "stacktrace": ["at me"] }
+                            }
+                          ]
+                        }"#
+                        .to_string(),
+                    ),
+                    Some(headers),
+                ))
+            })
+            .build()
+            .start()
+            .await
+    }
+
+    #[ntex::test]
+    async fn masks_subgraph_errors_and_strips_extensions() {
+        let subgraphs = subgraphs_with_errors().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                errors:
+                  masking:
+                    enabled: true
+                    allowed_codes: [UNAUTHENTICATED]
+                  strip_extensions: [stacktrace]
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ me { id } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "me": null
+          },
+          "errors": [
+            {
+              "message": "Unexpected error",
+              "path": [
+                "me"
+              ]
+            },
+            {
+              "message": "You must be logged in",
+              "path": [
+                "me"
+              ],
+              "extensions": {
+                "code": "UNAUTHENTICATED",
+                "service": "accounts"
+              }
+            }
+          ]
+        }
+        "#);
+    }
+
+    #[ntex::test]
+    async fn adds_correlation_id_to_masked_errors() {
+        let subgraphs = subgraphs_with_errors().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                errors:
+                  masking:
+                    enabled: true
+                    message: Something went wrong
+                    correlation_id: true
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ me { id } }", None, None)
+            .await;
+        let body = res.json_body().await;
+        let errors = body["errors"].as_array().expect("errors array");
+
+        assert_eq!(errors.len(), 2);
+        for error in errors {
+            assert_eq!(error["message"].as_str(), Some("Something went wrong"));
+            assert!(error["extensions"]["code"].is_null());
+            assert_eq!(
+                error["extensions"]["correlationId"]
+                    .as_str()
+                    .map(|id| id.len()),
+                Some(36)
+            );
+        }
+        assert_ne!(
+            errors[0]["extensions"]["correlationId"],
+            errors[1]["extensions"]["correlationId"]
+        );
+    }
+    #[ntex::test]
+    async fn removes_subgraph_attribution_when_disabled() {
+        let subgraphs = subgraphs_with_errors().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                errors:
+                  subgraph_attribution: false
+                  strip_extensions: [stacktrace]
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ me { id } }", None, None)
+            .await;
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "me": null
+          },
+          "errors": [
+            {
+              "message": "connection to db.internal:5432 refused",
+              "path": [
+                "me"
+              ],
+              "extensions": {
+                "code": "INTERNAL_SERVER_ERROR"
+              }
+            },
+            {
+              "message": "You must be logged in",
+              "path": [
+                "me"
+              ],
+              "extensions": {
+                "code": "UNAUTHENTICATED"
+              }
+            }
+          ]
+        }
+        "#);
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod error_policy_e2e_tests {
+    use crate::testkit::{ClientResponseExt, ResponseLike, Started, TestRouter, TestSubgraphs};
+
+    const QUERY: &str = "{ me { reviews { id product { upc name } } } }";
+
+    /// The `reviews` subgraph responds with its data and an error,
+    /// the `products` subgraph is queried after it for the names of the products.
+    async fn subgraphs_with_failing_reviews() -> TestSubgraphs<Started> {
+        TestSubgraphs::builder()
+            .with_on_request(|req| {
+                if req.path != "/reviews" {
+                    return None;
+                }
+                let mut headers = http::HeaderMap::new();
+                headers.insert(
+                    http::header::CONTENT_TYPE,
+                    http::HeaderValue::from_static("application/json"),
+                );
+                Some(ResponseLike::new(
+                    axum::http::StatusCode::OK,
+                    Some(
+                        r#"{
+                          "data": {
+                            "_entities": [
+                              { "reviews": [{ "id": "1", "product": { "__typename": "Product", "upc": "1" } }] }
+                            ]
+                          },
+                          "errors": [{ "message": "Some reviews are not available" }]
+                        }"#
+                        .to_string(),
+                    ),
+                    Some(headers),
+                ))
+            })
+            .build()
+            .start()
+            .await
+    }
+
+    async fn router_with_policy(
+        subgraphs: &TestSubgraphs<Started>,
+        policy: &str,
+    ) -> TestRouter<Started> {
+        TestRouter::builder()
+            .with_subgraphs(subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                errors:
+                  policy: {policy}
+                "#
+            ))
+            .build()
+            .start()
+            .await
+    }
+
+    #[ntex::test]
+    async fn best_effort_responds_with_partial_data() {
+        let subgraphs = subgraphs_with_failing_reviews().await;
+        let router = router_with_policy(&subgraphs, "best_effort").await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "me": {
+              "reviews": [
+                {
+                  "id": "1",
+                  "product": {
+                    "upc": "1",
+                    "name": "Table"
+                  }
+                }
+              ]
+            }
+          },
+          "errors": [
+            {
+              "message": "Some reviews are not available",
+              "extensions": {
+                "code": "DOWNSTREAM_SERVICE_ERROR",
+                "service": "reviews",
+                "affectedPath": "me"
+              }
+            }
+          ]
+        }
+        "#);
+        assert_eq!(
+            subgraphs
+                .get_requests_log("products")
+                .map(|requests| requests.len()),
+            Some(1)
+        );
+    }
+
+    #[ntex::test]
+    async fn fail_fast_skips_the_remaining_plan_nodes() {
+        let subgraphs = subgraphs_with_failing_reviews().await;
+        let router = router_with_policy(&subgraphs, "fail_fast").await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "me": {
+              "reviews": [
+                {
+                  "id": "1",
+                  "product": {
+                    "upc": "1",
+                    "name": null
+                  }
+                }
+              ]
+            }
+          },
+          "errors": [
+            {
+              "message": "Some reviews are not available",
+              "extensions": {
+                "code": "DOWNSTREAM_SERVICE_ERROR",
+                "service": "reviews",
+                "affectedPath": "me"
+              }
+            }
+          ]
+        }
+        "#);
+        assert!(
+            subgraphs.get_requests_log("products").is_none(),
+            "products subgraph should not be requested after the reviews error"
+        );
+    }
+
+    #[ntex::test]
+    async fn propagate_responds_with_null_data() {
+        let subgraphs = subgraphs_with_failing_reviews().await;
+        let router = router_with_policy(&subgraphs, "propagate").await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": null,
+          "errors": [
+            {
+              "message": "Some reviews are not available",
+              "extensions": {
+                "code": "DOWNSTREAM_SERVICE_ERROR",
+                "service": "reviews",
+                "affectedPath": "me"
+              }
+            }
+          ]
+        }
+        "#);
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod error_status_codes_e2e_tests {
+    use hive_router::{
+        async_trait,
+        plugins::{
+            hooks::on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+            plugin_trait::RouterPlugin,
+        },
+    };
+    use sonic_rs::JsonValueTrait;
+
+    use crate::testkit::{ClientResponseExt, ResponseLike, Started, TestRouter, TestSubgraphs};
+
+    async fn subgraphs_with_unauthenticated_error() -> TestSubgraphs<Started> {
+        TestSubgraphs::builder()
+            .with_on_request(|req| {
+                if req.path != "/accounts" {
+                    return None;
+                }
+                let mut headers = http::HeaderMap::new();
+                headers.insert(
+                    http::header::CONTENT_TYPE,
+                    http::HeaderValue::from_static("application/json"),
+                );
+                Some(ResponseLike::new(
+                    axum::http::StatusCode::OK,
+                    Some(
+                        r#"{
+                          "data": { "me": null },
+                          "errors": [
+                            {
+                              "message": "You must be logged in",
+                              "path": ["me"],
+                              "extensions": { "code": "UNAUTHENTICATED" }
+                            }
+                          ]
+                        }"#
+                        .to_string(),
+                    ),
+                    Some(headers),
+                ))
+            })
+            .build()
+            .start()
+            .await
+    }
+
+    #[ntex::test]
+    async fn maps_error_codes_of_the_config_to_status_codes() {
+        let subgraphs = subgraphs_with_unauthenticated_error().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                errors:
+                  status_codes:
+                    UNAUTHENTICATED: 401
+                    GRAPHQL_PARSE_FAILED: 422
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ me { id } }", None, None)
+            .await;
+        assert_eq!(res.status(), 401, "subgraph errors should be mapped");
+
+        let res = router.send_graphql_request("{ me {", None, None).await;
+        assert_eq!(res.status(), 422, "errors of the router should be mapped");
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("GRAPHQL_PARSE_FAILED")
+        );
+
+        let res = router
+            .send_graphql_request("{ topProducts { name } }", None, None)
+            .await;
+        assert_eq!(res.status(), 200, "responses without errors are untouched");
+    }
+
+    #[ntex::test]
+    async fn plugins_register_status_codes_of_error_codes() {
+        #[derive(Default)]
+        struct AuthStatusPlugin;
+
+        #[async_trait]
+        impl RouterPlugin for AuthStatusPlugin {
+            type Config = ();
+
+            fn plugin_name() -> &'static str {
+                "auth_status"
+            }
+
+            fn on_plugin_init(mut payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+                payload.register_error_status_code("UNAUTHENTICATED", http::StatusCode::FORBIDDEN);
+                payload.initialize_plugin_with_defaults()
+            }
+        }
+
+        let subgraphs = subgraphs_with_unauthenticated_error().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                plugins:
+                  auth_status:
+                    enabled: true
+                "#,
+            )
+            .register_plugin::<AuthStatusPlugin>()
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ me { id } }", None, None)
+            .await;
+        assert_eq!(res.status(), 403);
+    }
+}
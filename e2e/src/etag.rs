@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod etag_e2e_tests {
+    use http::header::{ETAG, IF_NONE_MATCH};
+    use sonic_rs::{json, JsonValueTrait};
+
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    const CONFIG: &str = r#"
+        supergraph:
+            source: file
+            path: supergraph.graphql
+        http:
+            etag: true
+        apq:
+            enabled: true
+        "#;
+
+    /// The url of `{ topProducts(first: 1) { upc } }` sent over `GET`.
+    const GET_URL: &str = "/graphql?query=%7B%20topProducts(first%3A%201)%20%7B%20upc%20%7D%20%7D";
+
+    #[ntex::test]
+    async fn answers_get_requests_holding_the_etag_with_not_modified() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .serv()
+            .get(GET_URL)
+            .header("accept", "application/graphql-response+json")
+            .send()
+            .await
+            .expect("failed to send graphql request");
+        assert_eq!(res.status(), 200);
+        let etag = res
+            .headers()
+            .get(ETAG)
+            .expect("expected an etag")
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(etag.starts_with('"') && etag.ends_with('"'), "{etag}");
+        let body = res.json_body().await;
+        assert_eq!(body["data"]["topProducts"][0]["upc"].as_str(), Some("1"));
+
+        let res = router
+            .serv()
+            .get(GET_URL)
+            .header("accept", "application/graphql-response+json")
+            .header(IF_NONE_MATCH, etag.as_str())
+            .send()
+            .await
+            .expect("failed to send graphql request");
+        assert_eq!(res.status(), 304);
+        assert_eq!(
+            res.headers().get(ETAG).and_then(|v| v.to_str().ok()),
+            Some(etag.as_str())
+        );
+        assert!(res.body().await.unwrap().is_empty());
+
+        let res = router
+            .serv()
+            .get(GET_URL)
+            .header("accept", "application/graphql-response+json")
+            .header(IF_NONE_MATCH, "\"outdated\"")
+            .send()
+            .await
+            .expect("failed to send graphql request");
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            res.headers().get(ETAG).and_then(|v| v.to_str().ok()),
+            Some(etag.as_str())
+        );
+    }
+
+    #[ntex::test]
+    async fn sets_etags_on_apq_requests_only() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ topProducts(first: 1) { upc } }", None, None)
+            .await;
+        assert_eq!(res.status(), 200);
+        assert!(res.headers().get(ETAG).is_none());
+
+        let apq_request = json!({
+            "query": "{ topProducts(first: 1) { upc } }",
+            "extensions": {
+                "persistedQuery": {
+                    "version": 1,
+                    "sha256Hash": "c44f46cd9d58485f07372bff76fffe63ab3cfa1429963a6fecca4cfeb1cf08ac"
+                }
+            }
+        });
+        let res = router
+            .send_post_request(router.graphql_path(), apq_request.clone(), None)
+            .await;
+        assert_eq!(res.status(), 200);
+        let etag = res
+            .headers()
+            .get(ETAG)
+            .expect("expected an etag")
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, etag.parse().unwrap());
+        let res = router
+            .send_post_request(router.graphql_path(), apq_request, Some(headers))
+            .await;
+        assert_eq!(res.status(), 304);
+    }
+
+    #[ntex::test]
+    async fn does_not_set_etags_when_disabled() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .serv()
+            .get(GET_URL)
+            .header("accept", "application/graphql-response+json")
+            .send()
+            .await
+            .expect("failed to send graphql request");
+        assert_eq!(res.status(), 200);
+        assert!(res.headers().get(ETAG).is_none());
+    }
+}
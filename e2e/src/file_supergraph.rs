@@ -4,6 +4,8 @@ mod file_supergraph_e2e_tests {
     use std::{fs, time::Duration};
     use tempfile::NamedTempFile;
 
+    use hive_router::SupergraphRefreshOutcome;
+
     use crate::testkit::{ClientResponseExt, TestRouter};
 
     #[ntex::test]
@@ -181,4 +183,63 @@ mod file_supergraph_e2e_tests {
             "Expected types to contain 'NewType'"
         );
     }
+
+    #[ntex::test]
+    async fn should_refresh_supergraph_on_demand() {
+        let file = NamedTempFile::new().expect("failed to create temp file");
+        let supergraph_file_path = file
+            .path()
+            .to_str()
+            .expect("failed to convert path to string")
+            .to_string();
+
+        fs::write(&supergraph_file_path, "type Query { f: String }")
+            .expect("failed to write supergraph");
+
+        let router = TestRouter::builder()
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: {supergraph_file_path}
+                    poll_interval: 1h
+                "#,
+            ))
+            .build()
+            .start()
+            .await;
+
+        // the file has not changed since the router loaded it
+        assert_eq!(
+            router.schema_state().refresh_supergraph().await.unwrap(),
+            SupergraphRefreshOutcome::Unchanged
+        );
+
+        // the modification time of the file must move forward
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        fs::write(
+            &supergraph_file_path,
+            "type Query { dummyNew: NewType } type NewType { id: ID! }",
+        )
+        .expect("failed to write supergraph");
+
+        // the poll interval is not elapsed, the supergraph is published right away
+        assert_eq!(
+            router.schema_state().refresh_supergraph().await.unwrap(),
+            SupergraphRefreshOutcome::Updated
+        );
+        let res = router
+            .send_graphql_request("{ __schema { types { name } } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+        let body = res.body().await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("NewType"));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        fs::write(&supergraph_file_path, "type Query {").expect("failed to write supergraph");
+        assert!(matches!(
+            router.schema_state().refresh_supergraph().await.unwrap(),
+            SupergraphRefreshOutcome::Rejected(_)
+        ));
+    }
 }
@@ -3,6 +3,20 @@
 mod header_propagation_e2e_tests {
     use crate::testkit::{some_header_map, TestRouter, TestSubgraphs};
     use futures::join;
+    use hive_router::{
+        async_trait,
+        plugins::{
+            hooks::{
+                on_execute::{OnExecuteStartHookPayload, OnExecuteStartHookResult},
+                on_http_request::{OnHttpRequestHookPayload, OnHttpRequestHookResult},
+                on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+                on_supergraph_load::{
+                    OnSupergraphLoadStartHookPayload, OnSupergraphLoadStartHookResult,
+                },
+            },
+            plugin_trait::{EndHookPayload, RouterPlugin, StartHookPayload},
+        },
+    };
 
     #[ntex::test]
     async fn should_propagate_headers_to_subgraphs() {
@@ -352,4 +366,119 @@ mod header_propagation_e2e_tests {
             "expected content-type to win over propagated content-type"
         );
     }
+
+    #[ntex::test]
+    async fn should_evaluate_expressions_with_the_values_exposed_by_plugins() {
+        struct SchemaVersion(&'static str);
+
+        #[derive(Default)]
+        struct TenantPlugin;
+
+        #[async_trait]
+        impl RouterPlugin for TenantPlugin {
+            type Config = ();
+
+            fn plugin_name() -> &'static str {
+                "tenant"
+            }
+
+            fn on_plugin_init(payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+                payload.initialize_plugin_with_defaults()
+            }
+
+            fn on_supergraph_reload<'exec>(
+                &'exec self,
+                payload: OnSupergraphLoadStartHookPayload,
+            ) -> OnSupergraphLoadStartHookResult<'exec> {
+                payload.on_end(|payload| {
+                    payload.new_supergraph.context.insert(SchemaVersion("v1"));
+                    payload.proceed()
+                })
+            }
+
+            fn on_http_request<'req>(
+                &'req self,
+                payload: OnHttpRequestHookPayload<'req>,
+            ) -> OnHttpRequestHookResult<'req> {
+                if let Some(api_key) = payload
+                    .router_http_request
+                    .headers()
+                    .get("x-api-key")
+                    .and_then(|value| value.to_str().ok())
+                {
+                    payload
+                        .context
+                        .expose("tenant", api_key.trim_start_matches("key-").to_string());
+                }
+                payload.proceed()
+            }
+
+            async fn on_execute<'exec>(
+                &'exec self,
+                payload: OnExecuteStartHookPayload<'exec>,
+            ) -> OnExecuteStartHookResult<'exec> {
+                let schema_version = payload.context.supergraph_context().and_then(|context| {
+                    context.get_ref::<SchemaVersion>().map(|version| version.0)
+                });
+                if let Some(schema_version) = schema_version {
+                    payload.context.expose("schema_version", schema_version);
+                }
+                payload.proceed()
+            }
+        }
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                headers:
+                  all:
+                    request:
+                      - insert:
+                          name: x-tenant
+                          expression: .request.context.tenant
+                      - insert:
+                          name: x-schema-version
+                          expression: .request.context.schema_version
+                plugins:
+                  tenant:
+                    enabled: true
+                "#,
+            )
+            .register_plugin::<TenantPlugin>()
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                "{ users { id } }",
+                None,
+                some_header_map! {
+                    http::header::HeaderName::from_static("x-api-key") => "key-acme"
+                },
+            )
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        let subgraph_requests = subgraphs
+            .get_requests_log("accounts")
+            .expect("expected requests sent to accounts subgraph");
+        assert_eq!(subgraph_requests.len(), 1);
+        assert_eq!(
+            subgraph_requests[0].headers.get("x-tenant").unwrap(),
+            "acme"
+        );
+        assert_eq!(
+            subgraph_requests[0]
+                .headers
+                .get("x-schema-version")
+                .unwrap(),
+            "v1"
+        );
+    }
 }
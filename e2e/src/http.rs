@@ -1,14 +1,35 @@
 #[cfg(test)]
 mod http_tests {
-    use std::time::{Duration, Instant};
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
 
     use futures::{stream::FuturesUnordered, StreamExt};
-    use hive_router::pipeline::execution::EXPOSE_QUERY_PLAN_HEADER;
+    use hive_router::pipeline::execution::{
+        EXPOSE_QUERY_PLAN_FORMAT_HEADER, EXPOSE_QUERY_PLAN_HEADER,
+    };
+    use hive_router::{
+        async_trait,
+        ntex::web,
+        plugins::{
+            hooks::{
+                on_http_request::{OnHttpRequestHookPayload, OnHttpRequestHookResult},
+                on_http_response::{OnHttpResponseBodyHookPayload, OnHttpResponseHookResult},
+                on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+            },
+            plugin_routes::PluginRoute,
+            plugin_trait::RouterPlugin,
+        },
+    };
     use ntex::time;
     use sonic_rs::JsonValueTrait;
 
+    use ntex::client::ClientResponse;
+
     use crate::testkit::{
-        some_header_map, wait_until_mock_matched, ClientResponseExt, TestRouter, TestSubgraphs,
+        some_header_map, wait_until_mock_matched, ClientResponseExt, Started, TestRouter,
+        TestSubgraphs,
     };
 
     #[ntex::test]
@@ -155,6 +176,126 @@ mod http_tests {
         assert!(json_body["errors"].is_null());
         assert!(json_body["extensions"].is_object());
         assert!(json_body["extensions"]["queryPlan"].is_object());
+        assert!(json_body["extensions"]["queryPlanTiming"]["planningMs"].is_number());
+        assert_eq!(
+            json_body["extensions"]["queryPlanTiming"]["cacheHit"].as_bool(),
+            Some(false)
+        );
+    }
+
+    #[ntex::test]
+    async fn should_expose_query_plan_only_when_the_expression_allows_it() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                query_planner:
+                    allow_expose:
+                        expression: '.request.headers."x-internal-token" == "secret"'
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let query = "{ topProducts { name } }";
+
+        let res = router
+            .send_graphql_request(
+                query,
+                None,
+                some_header_map! {
+                    http::header::HeaderName::from_static(EXPOSE_QUERY_PLAN_HEADER.as_str()) => "true"
+                },
+            )
+            .await;
+
+        assert!(res.status().is_success(), "Expected 200 OK");
+        let json_body = res.json_body().await;
+        assert!(json_body["data"].is_object());
+        assert!(json_body["extensions"].is_null());
+
+        let res = router
+            .send_graphql_request(
+                query,
+                None,
+                some_header_map! {
+                    http::header::HeaderName::from_static(EXPOSE_QUERY_PLAN_HEADER.as_str()) => "true",
+                    http::header::HeaderName::from_static("x-internal-token") => "secret"
+                },
+            )
+            .await;
+
+        assert!(res.status().is_success(), "Expected 200 OK");
+        let json_body = res.json_body().await;
+        assert!(json_body["data"].is_object());
+        assert!(json_body["extensions"]["queryPlan"].is_object());
+        assert_eq!(
+            json_body["extensions"]["queryPlanTiming"]["cacheHit"].as_bool(),
+            Some(true),
+            "the plan of the first request should be reused"
+        );
+    }
+
+    #[ntex::test]
+    async fn should_expose_query_plan_in_the_requested_format() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                query_planner:
+                    allow_expose: true
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let query = "{ topProducts { name } }";
+
+        let res = router
+            .send_graphql_request(
+                query,
+                None,
+                some_header_map! {
+                    http::header::HeaderName::from_static(EXPOSE_QUERY_PLAN_HEADER.as_str()) => "dry-run",
+                    http::header::HeaderName::from_static(EXPOSE_QUERY_PLAN_FORMAT_HEADER.as_str()) => "apollo"
+                },
+            )
+            .await;
+
+        assert!(res.status().is_success(), "Expected 200 OK");
+        let json_body = res.json_body().await;
+        let query_plan = &json_body["extensions"]["queryPlan"];
+        assert_eq!(query_plan["kind"].as_str(), Some("QueryPlan"));
+        assert_eq!(query_plan["node"]["kind"].as_str(), Some("Fetch"));
+        assert!(query_plan["node"]["requires"].is_array());
+        assert!(query_plan["node"]["variableUsages"].is_array());
+
+        let res = router
+            .send_graphql_request(
+                query,
+                None,
+                some_header_map! {
+                    http::header::HeaderName::from_static(EXPOSE_QUERY_PLAN_HEADER.as_str()) => "dry-run",
+                    http::header::HeaderName::from_static(EXPOSE_QUERY_PLAN_FORMAT_HEADER.as_str()) => "text"
+                },
+            )
+            .await;
+
+        assert!(res.status().is_success(), "Expected 200 OK");
+        let json_body = res.json_body().await;
+        assert!(json_body["extensions"]["queryPlan"]
+            .as_str()
+            .is_some_and(|plan| plan.starts_with("QueryPlan {")));
     }
 
     #[ntex::test]
@@ -212,6 +353,42 @@ mod http_tests {
         );
     }
 
+    #[ntex::test]
+    async fn should_abort_planning_after_the_timeout() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                query_planner:
+                    timeout: 0s
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ topProducts { name } }", None, None)
+            .await;
+
+        assert_eq!(res.status(), 504, "Expected 504 Gateway Timeout");
+
+        let json_body = res.json_body().await;
+
+        assert_eq!(
+            json_body["errors"][0]["extensions"]["code"].as_str(),
+            Some("PLANNING_TIMEOUT")
+        );
+        assert!(
+            subgraphs.get_requests_log("products").is_none(),
+            "expected no requests to products subgraph"
+        );
+    }
+
     #[ntex::test]
     async fn should_not_dedupe_inflight_router_requests_by_default() {
         let subgraphs = TestSubgraphs::builder()
@@ -726,4 +903,405 @@ mod http_tests {
             "expected exactly one products subgraph request when allowlisted header matches case-insensitively"
         );
     }
+
+    async fn subgraph_requests_with_request_ids(dedupe_headers: &str) -> usize {
+        let subgraphs = TestSubgraphs::builder()
+            .with_delay(Duration::from_millis(100))
+            .build()
+            .start()
+            .await;
+
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                headers:
+                    all:
+                        request:
+                            - propagate:
+                                named: x-request-id
+                traffic_shaping:
+                    all:
+                        dedupe_enabled: true
+                    subgraphs:
+                        products:
+                            {dedupe_headers}
+                "#
+            ))
+            .build()
+            .start()
+            .await;
+
+        let query = r#"
+            {
+                topProducts {
+                    name
+                    price
+                }
+            }
+        "#;
+
+        let (response_a, response_b) = futures::join!(
+            router.send_graphql_request(
+                query,
+                None,
+                some_header_map! {
+                    "x-request-id" => "a"
+                },
+            ),
+            router.send_graphql_request(
+                query,
+                None,
+                some_header_map! {
+                    "x-request-id" => "b"
+                },
+            )
+        );
+
+        assert!(
+            response_a.status().is_success(),
+            "Expected first request 200 OK"
+        );
+        assert!(
+            response_b.status().is_success(),
+            "Expected second request 200 OK"
+        );
+
+        subgraphs
+            .get_requests_log("products")
+            .unwrap_or_default()
+            .len()
+    }
+
+    #[ntex::test]
+    async fn should_use_all_headers_in_subgraph_dedupe_key_by_default() {
+        let products_requests = subgraph_requests_with_request_ids("dedupe_enabled: true").await;
+
+        assert_eq!(
+            products_requests, 2,
+            "expected a products subgraph request per distinct propagated header; got {products_requests}"
+        );
+    }
+
+    #[ntex::test]
+    async fn should_only_use_selected_headers_in_subgraph_dedupe_key() {
+        let products_requests =
+            subgraph_requests_with_request_ids(r#"dedupe_headers: { include: ["Authorization"] }"#)
+                .await;
+
+        assert_eq!(
+            products_requests, 1,
+            "expected the products subgraph requests to be deduplicated when the differing header is not part of the dedupe key; got {products_requests}"
+        );
+    }
+
+    async fn send_get_request(router: &TestRouter<Started>, params: &str) -> ClientResponse {
+        router
+            .serv()
+            .get(format!("{}?{params}", router.graphql_path()))
+            .header("accept", "application/graphql-response+json")
+            .send()
+            .await
+            .expect("failed to send graphql request")
+    }
+
+    #[ntex::test]
+    async fn should_execute_queries_over_get() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .build()
+            .start()
+            .await;
+
+        let res = send_get_request(
+            &router,
+            "query=query%20Q(%24first%3A%20Int)%20%7B%20topProducts(first%3A%20%24first)%20%7B%20upc%20%7D%20%7D&variables=%7B%22first%22%3A1%7D",
+        )
+        .await;
+        assert_eq!(res.status(), 200);
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "upc": "1"
+              }
+            ]
+          }
+        }
+        "#);
+
+        let res = send_get_request(&router, "query=mutation%20%7B%20__typename%20%7D").await;
+        assert_eq!(res.status(), 405);
+        assert_eq!(
+            res.headers().get("allow").and_then(|v| v.to_str().ok()),
+            Some("POST")
+        );
+    }
+
+    #[ntex::test]
+    async fn should_reject_get_requests_when_disabled() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                http:
+                    get_requests:
+                        enabled: false
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = send_get_request(&router, "query=%7B%20__typename%20%7D").await;
+        assert_eq!(res.status(), 405);
+        assert_eq!(
+            res.headers().get("allow").and_then(|v| v.to_str().ok()),
+            Some("POST")
+        );
+
+        let res = router
+            .send_graphql_request("{ __typename }", None, None)
+            .await;
+        assert_eq!(res.status(), 200);
+    }
+
+    #[ntex::test]
+    async fn should_only_accept_persisted_operations_over_get_when_configured() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                apq:
+                    enabled: true
+                http:
+                    get_requests:
+                        persisted_operations_only: true
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = send_get_request(&router, "query=%7B%20__typename%20%7D").await;
+        assert_eq!(res.status(), 405);
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("PERSISTED_OPERATION_REQUIRED_OVER_HTTP_GET")
+        );
+
+        // the SHA-256 hash of `{ __typename }`
+        let extensions = "extensions=%7B%22persistedQuery%22%3A%7B%22version%22%3A1%2C%22sha256Hash%22%3A%227f56e67dd21ab3f30d1ff8b7bed08893f0a0db86449836189b361dd1e56ddb4b%22%7D%7D";
+        let res = send_get_request(&router, extensions).await;
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("PERSISTED_QUERY_NOT_FOUND")
+        );
+
+        // registered over POST
+        let res = router
+            .send_post_request(
+                router.graphql_path(),
+                sonic_rs::json!({
+                    "query": "{ __typename }",
+                    "extensions": {
+                        "persistedQuery": {
+                            "version": 1,
+                            "sha256Hash": "7f56e67dd21ab3f30d1ff8b7bed08893f0a0db86449836189b361dd1e56ddb4b"
+                        }
+                    }
+                }),
+                None,
+            )
+            .await;
+        assert_eq!(res.status(), 200);
+
+        let res = send_get_request(&router, extensions).await;
+        assert_eq!(res.status(), 200);
+        let body = res.json_body().await;
+        assert_eq!(body["data"]["__typename"].as_str(), Some("Query"));
+    }
+
+    #[ntex::test]
+    async fn plugins_read_and_replace_the_final_response() {
+        #[derive(Default)]
+        struct ResponseChecksumPlugin;
+
+        #[async_trait]
+        impl RouterPlugin for ResponseChecksumPlugin {
+            type Config = ();
+
+            fn plugin_name() -> &'static str {
+                "response_checksum"
+            }
+
+            fn on_plugin_init(payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+                payload.initialize_plugin_with_defaults()
+            }
+
+            async fn on_http_response<'req>(
+                &'req self,
+                mut payload: OnHttpResponseBodyHookPayload<'req>,
+            ) -> OnHttpResponseHookResult<'req> {
+                let length = payload.body.len().to_string();
+                payload.headers.insert(
+                    http::HeaderName::from_static("x-body-length"),
+                    http::HeaderValue::from_str(&length).unwrap(),
+                );
+                payload.status_code = http::StatusCode::ACCEPTED;
+                payload.proceed()
+            }
+        }
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                plugins:
+                    response_checksum:
+                        enabled: true
+                "#,
+            )
+            .register_plugin::<ResponseChecksumPlugin>()
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ __typename }", None, None)
+            .await;
+
+        assert_eq!(res.status(), 202);
+        let body_length = res
+            .headers()
+            .get("x-body-length")
+            .and_then(|value| value.to_str().ok())
+            .expect("expected the header set by the plugin")
+            .to_string();
+        let body = res.string_body().await;
+        assert_eq!(body_length, body.len().to_string());
+        assert_eq!(body, r#"{"data":{"__typename":"Query"}}"#);
+    }
+
+    #[ntex::test]
+    async fn plugins_register_routes_sharing_their_state_and_the_context() {
+        struct Signature(String);
+
+        #[derive(Default)]
+        struct WebhooksPlugin {
+            received: Arc<Mutex<Vec<String>>>,
+        }
+
+        #[async_trait]
+        impl RouterPlugin for WebhooksPlugin {
+            type Config = ();
+
+            fn plugin_name() -> &'static str {
+                "webhooks"
+            }
+
+            fn on_plugin_init(mut payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+                let plugin = Self::default();
+                let received = plugin.received.clone();
+                payload.register_route(PluginRoute::post("/webhooks/stripe", move |request| {
+                    let received = received.clone();
+                    async move {
+                        let signature = request
+                            .context
+                            .get_ref::<Signature>()
+                            .map(|signature| signature.0.clone())
+                            .unwrap_or_default();
+                        let body = String::from_utf8_lossy(&request.body).to_string();
+                        received.lock().unwrap().push(body.clone());
+                        web::HttpResponse::Ok().body(format!("{signature}:{body}"))
+                    }
+                }));
+                let received = plugin.received.clone();
+                payload.register_route(PluginRoute::get("/webhooks/stripe", move |_request| {
+                    let count = received.lock().unwrap().len();
+                    async move { web::HttpResponse::Ok().body(count.to_string()) }
+                }));
+                payload.initialize_plugin(plugin)
+            }
+
+            fn on_http_request<'req>(
+                &'req self,
+                payload: OnHttpRequestHookPayload<'req>,
+            ) -> OnHttpRequestHookResult<'req> {
+                if let Some(signature) = payload
+                    .router_http_request
+                    .headers()
+                    .get("x-signature")
+                    .and_then(|value| value.to_str().ok())
+                {
+                    payload.context.insert(Signature(signature.to_string()));
+                }
+                payload.proceed()
+            }
+        }
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                plugins:
+                    webhooks:
+                        enabled: true
+                "#,
+            )
+            .register_plugin::<WebhooksPlugin>()
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .serv()
+            .post("/webhooks/stripe")
+            .header("x-signature", "t=1,v1=abc")
+            .send_body("payment_intent.succeeded")
+            .await
+            .expect("failed to send the webhook");
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            res.string_body().await,
+            "t=1,v1=abc:payment_intent.succeeded"
+        );
+
+        let res = router
+            .serv()
+            .get("/webhooks/stripe")
+            .send()
+            .await
+            .expect("failed to count the webhooks");
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.string_body().await, "1");
+
+        let res = router
+            .send_graphql_request("{ __typename }", None, None)
+            .await;
+        assert_eq!(res.status(), 200, "the GraphQL endpoint is still served");
+    }
 }
@@ -342,6 +342,67 @@ mod http2_tests {
         );
     }
 
+    /// Verify that the HTTP/2 connection pool settings keep the connection to the subgraph usable.
+    #[ntex::test]
+    async fn h2c_router_to_subgraph_with_pool_settings() {
+        let subgraphs = TestSubgraphs::builder()
+            .with_http2_only()
+            .build()
+            .start()
+            .await;
+
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+            supergraph:
+                source: file
+                path: supergraph.graphql
+            traffic_shaping:
+                all:
+                    pool_max_idle_per_host: 1
+                    http2:
+                        keep_alive_interval: 50ms
+                        keep_alive_timeout: 1s
+                subgraphs:
+                    accounts:
+                        allow_only_http2: true
+                        http2:
+                            keep_alive_while_idle: true
+                            max_concurrent_streams: 10
+                "#
+                .to_string(),
+            )
+            .build()
+            .start()
+            .await;
+
+        for _ in 0..2 {
+            let resp = router
+                .send_graphql_request("{ me { name } }", None, None)
+                .await;
+            assert!(resp.status().is_success());
+
+            let body = resp.string_body().await;
+            assert!(
+                body.contains("Uri Goldshtein"),
+                "Response should contain expected data, got: {}",
+                body
+            );
+
+            // let a few keep-alive pings go through the idle connection
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+
+        let subgraph_requests = subgraphs
+            .get_requests_log("accounts")
+            .expect("Expected requests sent to accounts subgraph");
+        assert_eq!(subgraph_requests.len(), 2);
+        assert!(subgraph_requests
+            .iter()
+            .all(|request| request.http_version == http::Version::HTTP_2));
+    }
+
     /// Verify that without allow_only_http2 flag, plain HTTP to an h2c subgraph fails
     /// (the router defaults to HTTP/1.1 and the h2c-only subgraph rejects it).
     #[ntex::test]
@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod interface_object_e2e_tests {
+    use serde_json::{json, Value};
+
+    use crate::testkit::{
+        mock_subgraphs::mock_subgraphs, ClientResponseExt, TestRouter, TestSubgraphs,
+        TestSubgraphsBuilder,
+    };
+
+    // `Media` is an entity interface of the `media` subgraph,
+    // that `reviews` and `inventory` subgraphs model as an object with @interfaceObject.
+    const CONFIG: &str = r#"
+        supergraph:
+            source: file
+            path: ../lib/query-planner/fixture/tests/interface-object-entities.supergraph.graphql
+    "#;
+
+    fn subgraphs() -> TestSubgraphsBuilder {
+        let book =
+            json!({ "__typename": "Book", "id": "1", "title": "Dune", "author": "Frank Herbert" });
+        let movie = json!({ "__typename": "Movie", "id": "2", "title": "Alien", "director": "Ridley Scott" });
+        // the @interfaceObject subgraphs only know about `Media`
+        let media = |id: &str, fields: Value| {
+            let mut entity = json!({ "__typename": "Media", "id": id });
+            entity
+                .as_object_mut()
+                .unwrap()
+                .extend(fields.as_object().unwrap().clone());
+            entity
+        };
+
+        TestSubgraphs::builder().with_on_request(mock_subgraphs(json!({
+            "media": {
+                "query": {
+                    "media": [book, movie],
+                },
+                "entities": [book, movie],
+                "interfaces": { "Media": ["Book", "Movie"] },
+            },
+            "reviews": {
+                "query": {
+                    "topRatedMedia": [
+                        media("2", json!({ "reviews": [{ "score": 5 }] })),
+                        media("1", json!({ "reviews": [{ "score": 4 }] })),
+                    ],
+                },
+                "entities": [
+                    media("1", json!({ "reviews": [{ "score": 4 }] })),
+                    media("2", json!({ "reviews": [{ "score": 5 }] })),
+                ],
+            },
+            "inventory": {
+                "entities": [
+                    media("1", json!({ "inStock": true })),
+                    media("2", json!({ "inStock": false })),
+                ],
+            },
+        })))
+    }
+
+    /// The representations sent to the @interfaceObject subgraphs use the interface `__typename`,
+    /// while the response keeps the `__typename` of the implementations.
+    #[ntex::test]
+    async fn should_resolve_interface_object_fields_of_implementations() {
+        let subgraphs = subgraphs().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                "{ media { __typename id title inStock reviews { score } } }",
+                None,
+                None,
+            )
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "media": [
+              {
+                "__typename": "Book",
+                "id": "1",
+                "title": "Dune",
+                "inStock": true,
+                "reviews": [
+                  {
+                    "score": 4
+                  }
+                ]
+              },
+              {
+                "__typename": "Movie",
+                "id": "2",
+                "title": "Alien",
+                "inStock": false,
+                "reviews": [
+                  {
+                    "score": 5
+                  }
+                ]
+              }
+            ]
+          }
+        }
+        "#);
+    }
+
+    /// The `__typename` and the fragments on implementations are resolved by the subgraph
+    /// defining the interface, for entities returned by an @interfaceObject subgraph.
+    #[ntex::test]
+    async fn should_resolve_implementations_of_interface_object_root_field() {
+        let subgraphs = subgraphs().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                r#"{
+                  topRatedMedia {
+                    __typename
+                    title
+                    inStock
+                    reviews { score }
+                    ... on Book { author }
+                    ... on Movie { director }
+                  }
+                }"#,
+                None,
+                None,
+            )
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topRatedMedia": [
+              {
+                "__typename": "Movie",
+                "title": "Alien",
+                "inStock": false,
+                "reviews": [
+                  {
+                    "score": 5
+                  }
+                ],
+                "director": "Ridley Scott"
+              },
+              {
+                "__typename": "Book",
+                "title": "Dune",
+                "inStock": true,
+                "reviews": [
+                  {
+                    "score": 4
+                  }
+                ],
+                "author": "Frank Herbert"
+              }
+            ]
+          }
+        }
+        "#);
+    }
+}
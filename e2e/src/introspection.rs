@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod introspection_e2e_tests {
-    use crate::testkit::{ClientResponseExt, TestRouter};
+    use sonic_rs::{json, JsonValueTrait};
+
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
 
     #[ntex::test]
     async fn should_work_correctly_for_repeatable_directives() {
@@ -59,6 +61,14 @@ mod introspection_e2e_tests {
                   "name": "deprecated",
                   "isRepeatable": false
                 },
+                {
+                  "name": "stream",
+                  "isRepeatable": false
+                },
+                {
+                  "name": "defer",
+                  "isRepeatable": false
+                },
                 {
                   "name": "specifiedBy",
                   "isRepeatable": false
@@ -111,7 +121,7 @@ mod introspection_e2e_tests {
                 __schema {
                     directives {
                         name
-                        args {
+                        args(includeDeprecated: true) {
                             name
                             isDeprecated
                             deprecationReason
@@ -225,6 +235,41 @@ mod introspection_e2e_tests {
                     }
                   ]
                 },
+                {
+                  "name": "stream",
+                  "args": [
+                    {
+                      "name": "if",
+                      "isDeprecated": false,
+                      "deprecationReason": null
+                    },
+                    {
+                      "name": "label",
+                      "isDeprecated": false,
+                      "deprecationReason": null
+                    },
+                    {
+                      "name": "initialCount",
+                      "isDeprecated": false,
+                      "deprecationReason": null
+                    }
+                  ]
+                },
+                {
+                  "name": "defer",
+                  "args": [
+                    {
+                      "name": "if",
+                      "isDeprecated": false,
+                      "deprecationReason": null
+                    },
+                    {
+                      "name": "label",
+                      "isDeprecated": false,
+                      "deprecationReason": null
+                    }
+                  ]
+                },
                 {
                   "name": "specifiedBy",
                   "args": [
@@ -375,4 +420,197 @@ mod introspection_e2e_tests {
         }
         "#);
     }
+
+    #[ntex::test]
+    async fn should_exclude_deprecated_input_values_by_default() {
+        let router = TestRouter::builder()
+            .inline_config(&format!(
+                r#"supergraph:
+                source: file
+                path: "./supergraph-introspection-extended.graphql"
+          "#,
+            ))
+            .build()
+            .start()
+            .await;
+
+        let resp = router
+            .send_graphql_request(
+                r#"
+            query ExcludeDeprecatedInputValues {
+                Query: __type(name: "Query") {
+                    fields {
+                        name
+                        args {
+                            name
+                        }
+                    }
+                }
+                TestInput: __type(name: "TestInput") {
+                    inputFields {
+                        name
+                    }
+                }
+                __schema {
+                    ... on __Schema {
+                        queryType {
+                            name
+                        }
+                    }
+                }
+            }
+        "#,
+                None,
+                None,
+            )
+            .await;
+
+        assert!(resp.status().is_success(), "Expected 200 OK");
+
+        insta::assert_snapshot!(resp.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "Query": {
+              "fields": [
+                {
+                  "name": "testField",
+                  "args": [
+                    {
+                      "name": "newArg"
+                    }
+                  ]
+                }
+              ]
+            },
+            "TestInput": {
+              "inputFields": [
+                {
+                  "name": "newField"
+                }
+              ]
+            },
+            "__schema": {
+              "queryType": {
+                "name": "Query"
+              }
+            }
+          }
+        }
+        "#);
+    }
+
+    #[ntex::test]
+    async fn should_merge_introspection_with_entity_fields() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let resp = router
+            .send_graphql_request(
+                r#"
+            query MixedIntrospection {
+                topProducts(first: 1) {
+                    upc
+                    reviews {
+                        id
+                    }
+                }
+                schema: __schema {
+                    queryType {
+                        name
+                    }
+                }
+                productType: __type(name: "Product") {
+                    name
+                    kind
+                }
+                me {
+                    name
+                }
+                ... on Query {
+                    userType: __type(name: "User") {
+                        name
+                    }
+                }
+            }
+        "#,
+                None,
+                None,
+            )
+            .await;
+
+        assert!(resp.status().is_success(), "Expected 200 OK");
+
+        let body = resp.json_body().await;
+        assert!(body["errors"].is_null());
+        let data = &body["data"];
+        assert_eq!(data["topProducts"][0]["upc"].as_str(), Some("1"));
+        assert_eq!(
+            data["topProducts"][0]["reviews"][0]["id"].as_str(),
+            Some("1")
+        );
+        assert_eq!(data["schema"]["queryType"]["name"].as_str(), Some("Query"));
+        assert_eq!(data["productType"]["name"].as_str(), Some("Product"));
+        assert_eq!(data["productType"]["kind"].as_str(), Some("OBJECT"));
+        assert_eq!(data["me"]["name"].as_str(), Some("Uri Goldshtein"));
+        assert_eq!(data["userType"]["name"].as_str(), Some("User"));
+    }
+
+    #[ntex::test]
+    async fn should_not_resolve_skipped_introspection_fields() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let resp = router
+            .send_graphql_request(
+                r#"
+            query SkippedIntrospection($withSchema: Boolean!) {
+                me {
+                    name
+                }
+                __schema @include(if: $withSchema) {
+                    queryType {
+                        name
+                    }
+                }
+            }
+        "#,
+                Some(json!({ "withSchema": false })),
+                None,
+            )
+            .await;
+
+        assert!(resp.status().is_success(), "Expected 200 OK");
+
+        insta::assert_snapshot!(resp.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "me": {
+              "name": "Uri Goldshtein"
+            }
+          }
+        }
+        "#);
+    }
 }
@@ -1,7 +1,13 @@
 #[cfg(test)]
 mod jwt_e2e_tests {
+    use futures::StreamExt;
+    use hive_router_plan_executor::executors::{
+        graphql_transport_ws::{ConnectionInitPayload, SubscribePayload},
+        websocket_client::WsClient,
+    };
     use jsonwebtoken::{encode, Algorithm, EncodingKey};
     use sonic_rs::{json, JsonValueTrait, Value};
+    use std::collections::HashMap;
     use std::time::{SystemTime, UNIX_EPOCH};
 
     use crate::testkit::{some_header_map, ClientResponseExt, TestRouter, TestSubgraphs};
@@ -577,4 +583,243 @@ mod jwt_e2e_tests {
             "Expected 403 for wrong algorithm"
         );
     }
+
+    #[ntex::test]
+    async fn validates_tokens_with_the_constraints_of_their_provider() {
+        let router = TestRouter::builder()
+            .file_config("configs/jwt_auth_multiple_issuers.router.yaml")
+            .build()
+            .start()
+            .await;
+
+        let exp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3600;
+
+        for (claims, expected_status) in [
+            (
+                json!({ "iss": "first-issuer", "aud": "first-app", "exp": exp }),
+                ntex::http::StatusCode::OK,
+            ),
+            // The audiences of the first provider don't apply to the second one
+            (
+                json!({ "iss": "second-issuer", "exp": exp }),
+                ntex::http::StatusCode::OK,
+            ),
+            (
+                json!({ "iss": "first-issuer", "aud": "other-app", "exp": exp }),
+                ntex::http::StatusCode::FORBIDDEN,
+            ),
+            (
+                json!({ "iss": "unknown-issuer", "exp": exp }),
+                ntex::http::StatusCode::FORBIDDEN,
+            ),
+        ] {
+            let token = generate_jwt(&claims);
+            let res = router
+                .send_graphql_request(
+                    "{ __typename }",
+                    None,
+                    some_header_map! {
+                        http::header::AUTHORIZATION => format!("Bearer {}", token)
+                    },
+                )
+                .await;
+
+            assert_eq!(res.status(), expected_status, "claims: {}", claims);
+        }
+    }
+
+    #[ntex::test]
+    async fn discovers_the_keys_of_an_oidc_provider() {
+        let mut server = mockito::Server::new_async().await;
+        let issuer = server.url();
+
+        let discovery_mock = server
+            .mock("GET", "/.well-known/openid-configuration")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "issuer": &issuer,
+                    "jwks_uri": format!("{}/jwks.json", issuer),
+                    "id_token_signing_alg_values_supported": ["RS512"],
+                })
+                .to_string(),
+            )
+            // Fetched on startup, and refreshed in the background
+            .expect_at_least(1)
+            .create();
+        let jwks_mock = server
+            .mock("GET", "/jwks.json")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(include_str!("../jwks.rsa512.json"))
+            .expect_at_least(1)
+            .create();
+
+        let router = TestRouter::builder()
+            .inline_config(format!(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                jwt:
+                  enabled: true
+                  require_authentication: true
+                  jwks_providers:
+                    - source: oidc
+                      issuer: {issuer}
+                      prefetch: true
+                "#,
+            ))
+            .build()
+            .start()
+            .await;
+
+        discovery_mock.assert_async().await;
+        jwks_mock.assert_async().await;
+
+        let exp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3600;
+
+        for (token, expected_status) in [
+            (
+                generate_jwt(&json!({ "iss": &issuer, "exp": exp })),
+                ntex::http::StatusCode::OK,
+            ),
+            // The issuer of the discovery document is required
+            (
+                generate_jwt(&json!({ "iss": "other-issuer", "exp": exp })),
+                ntex::http::StatusCode::FORBIDDEN,
+            ),
+            // Only the algorithms supported by the provider are accepted
+            (
+                generate_jwt_with_alg(
+                    &json!({ "iss": &issuer, "exp": exp }),
+                    jsonwebtoken::Algorithm::RS256,
+                ),
+                ntex::http::StatusCode::FORBIDDEN,
+            ),
+        ] {
+            let res = router
+                .send_graphql_request(
+                    "{ __typename }",
+                    None,
+                    some_header_map! {
+                        http::header::AUTHORIZATION => format!("Bearer {}", token)
+                    },
+                )
+                .await;
+
+            assert_eq!(res.status(), expected_status);
+        }
+    }
+
+    const WEBSOCKET_JWT_CONFIG: &str = r#"
+        supergraph:
+          source: file
+          path: supergraph.graphql
+        websocket:
+          enabled: true
+        jwt:
+          enabled: true
+          require_authentication: true
+          forward_claims_to_upstream_extensions:
+            enabled: true
+            field_name: jwt
+          jwks_providers:
+            - source: file
+              path: jwks.rsa512.json
+        "#;
+
+    fn authorization_init_payload(token: &str) -> ConnectionInitPayload {
+        ConnectionInitPayload::new(HashMap::from([(
+            "authorization".to_string(),
+            json!(format!("Bearer {}", token)),
+        )]))
+    }
+
+    #[ntex::test]
+    async fn authenticates_websocket_connections_from_the_connection_init_payload() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(WEBSOCKET_JWT_CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let exp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3600;
+        let claims = json!({
+            "sub": "user1",
+            "exp": exp,
+        });
+        let token = generate_jwt(&claims);
+
+        let mut client =
+            WsClient::init(router.ws().await, Some(authorization_init_payload(&token)))
+                .await
+                .expect("Failed to init WsClient");
+
+        // both operations are authenticated with the identity of the connection
+        for _ in 0..2 {
+            let mut stream = client
+                .subscribe(
+                    SubscribePayload {
+                        query: "{ users { id } }".into(),
+                        ..Default::default()
+                    },
+                    None,
+                )
+                .await;
+            let response = stream.next().await.expect("Expected a response");
+            assert!(response.errors.is_none(), "Expected no errors");
+        }
+
+        let subgraph_requests = subgraphs
+            .get_requests_log("accounts")
+            .expect("expected requests sent to accounts subgraph");
+        assert_eq!(subgraph_requests.len(), 2);
+        for request in subgraph_requests {
+            let body: Value =
+                sonic_rs::from_slice(request.body.as_ref().expect("expected request body"))
+                    .expect("expected valid JSON body");
+            assert_eq!(body.get("extensions").unwrap().get("jwt").unwrap(), &claims);
+        }
+    }
+
+    #[ntex::test]
+    async fn rejects_websocket_connections_with_an_invalid_token() {
+        let router = TestRouter::builder()
+            .inline_config(WEBSOCKET_JWT_CONFIG)
+            .build()
+            .start()
+            .await;
+
+        let exp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - 3600;
+        let expired_token = generate_jwt(&json!({ "sub": "user1", "exp": exp }));
+
+        for token in ["not-a-jwt", expired_token.as_str()] {
+            let result =
+                WsClient::init(router.ws().await, Some(authorization_init_payload(token))).await;
+            assert!(
+                result.is_err(),
+                "Expected the connection to be closed before ack"
+            );
+        }
+    }
 }
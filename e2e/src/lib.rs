@@ -1,18 +1,54 @@
 #[cfg(test)]
+mod access_log;
+#[cfg(test)]
+mod admin_cache;
+#[cfg(test)]
+mod admin_diagnostics;
+#[cfg(test)]
+mod admin_supergraph;
+#[cfg(test)]
+mod apq;
+#[cfg(test)]
 mod authorization_directives_filter;
 #[cfg(test)]
 mod authorization_directives_reject;
 #[cfg(test)]
+mod aws_sigv4;
+#[cfg(test)]
 mod body_limit;
 #[cfg(test)]
 mod cache_control;
 #[cfg(test)]
 mod circuit_breaker;
 #[cfg(test)]
+mod client_disconnect;
+#[cfg(test)]
+mod client_identification;
+#[cfg(test)]
+mod client_rate_limit;
+#[cfg(test)]
+mod compose_supergraph;
+#[cfg(test)]
+mod compression;
+#[cfg(test)]
 mod conditional_directives;
 #[cfg(test)]
+mod config_extends;
+#[cfg(test)]
+mod config_reload;
+#[cfg(test)]
+mod connectors;
+#[cfg(test)]
+mod context;
+#[cfg(test)]
+mod contract;
+#[cfg(test)]
 mod coprocessor;
 #[cfg(test)]
+mod dedicated_listeners;
+#[cfg(test)]
+mod defer;
+#[cfg(test)]
 mod demand_control;
 #[cfg(test)]
 mod demand_control_parity;
@@ -21,10 +57,20 @@ mod disable_introspection;
 #[cfg(test)]
 mod entity_batching;
 #[cfg(test)]
+mod entity_cache;
+#[cfg(test)]
 mod env_vars;
 #[cfg(test)]
 mod error_handling;
 #[cfg(test)]
+mod error_masking;
+#[cfg(test)]
+mod error_policy;
+#[cfg(test)]
+mod error_status_codes;
+#[cfg(test)]
+mod etag;
+#[cfg(test)]
 mod extensions_propagation;
 #[cfg(test)]
 mod file_supergraph;
@@ -39,6 +85,8 @@ mod http2;
 #[cfg(test)]
 mod http_callback;
 #[cfg(test)]
+mod interface_object;
+#[cfg(test)]
 mod introspection;
 #[cfg(test)]
 mod issues;
@@ -51,16 +99,44 @@ mod max_depth;
 #[cfg(test)]
 mod max_directives;
 #[cfg(test)]
+mod max_introspection_depth;
+#[cfg(test)]
+mod max_root_fields;
+#[cfg(test)]
 mod max_tokens;
 #[cfg(test)]
+mod mcp;
+#[cfg(test)]
+mod opa_authorization;
+#[cfg(test)]
 mod operation_name;
 #[cfg(test)]
 mod override_subgraph_urls;
 #[cfg(test)]
+mod parser_limits;
+#[cfg(test)]
 mod persisted_documents;
 #[cfg(test)]
+mod plugin_storage;
+#[cfg(test)]
 mod probes;
 #[cfg(test)]
+mod progressive_override;
+#[cfg(test)]
+mod proxy;
+#[cfg(test)]
+mod query_plan_cache;
+#[cfg(test)]
+mod rate_limit;
+#[cfg(test)]
+mod request_id;
+#[cfg(test)]
+mod response_cache;
+#[cfg(test)]
+mod response_limits;
+#[cfg(test)]
+mod retry;
+#[cfg(test)]
 mod router_timeout;
 #[cfg(test)]
 mod storage;
@@ -77,6 +153,12 @@ mod tls;
 #[cfg(test)]
 mod traffic_shaping;
 #[cfg(test)]
+mod unix_socket;
+#[cfg(test)]
+mod validate_config;
+#[cfg(test)]
+mod variable_coercion;
+#[cfg(test)]
 mod websocket;
 
 pub use insta;
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod max_introspection_depth_e2e_tests {
+    use sonic_rs::JsonValueTrait;
+
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    #[ntex::test]
+    async fn allows_introspection_within_max_introspection_depth() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+        supergraph:
+            source: file
+            path: supergraph.graphql
+        limits:
+            max_introspection_depth:
+                n: 1
+        "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                r#"query {
+                __type(name: "User") {
+                    fields {
+                        name
+                    }
+                }
+            }"#,
+                None,
+                None,
+            )
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        let body = res.json_body().await;
+        assert!(body["errors"].is_null());
+    }
+
+    #[ntex::test]
+    async fn rejects_introspection_exceeding_max_introspection_depth() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+        supergraph:
+            source: file
+            path: supergraph.graphql
+        limits:
+            max_introspection_depth:
+                n: 1
+        "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                r#"query {
+                __type(name: "User") {
+                    fields {
+                        type {
+                            fields {
+                                name
+                            }
+                        }
+                    }
+                }
+            }"#,
+                None,
+                None,
+            )
+            .await;
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r###"
+        {
+          "errors": [
+            {
+              "message": "Introspection depth limit exceeded.",
+              "extensions": {
+                "code": "MAX_INTROSPECTION_DEPTH_EXCEEDED"
+              }
+            }
+          ]
+        }
+        "###);
+    }
+}
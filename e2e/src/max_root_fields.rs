@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod max_root_fields_e2e_tests {
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    #[ntex::test]
+    async fn allows_query_within_max_root_fields() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+        supergraph:
+            source: file
+            path: supergraph.graphql
+        limits:
+            max_root_fields:
+                n: 2
+        "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                "query {
+                __typename
+                me {
+                    __typename
+                    name
+                }
+            }",
+                None,
+                None,
+            )
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r###"
+        {
+          "data": {
+            "__typename": "Query",
+            "me": {
+              "__typename": "User",
+              "name": "Uri Goldshtein"
+            }
+          }
+        }
+        "###);
+    }
+
+    #[ntex::test]
+    async fn rejects_query_exceeding_max_root_fields() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+        supergraph:
+            source: file
+            path: supergraph.graphql
+        limits:
+            max_root_fields:
+                n: 2
+        "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                "query {
+                __typename
+                first: me {
+                    name
+                }
+                second: me {
+                    name
+                }
+            }",
+                None,
+                None,
+            )
+            .await;
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r###"
+        {
+          "errors": [
+            {
+              "message": "Root fields limit exceeded.",
+              "extensions": {
+                "code": "MAX_ROOT_FIELDS_EXCEEDED"
+              }
+            }
+          ]
+        }
+        "###);
+    }
+}
@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod mcp_e2e_tests {
+    use http::header::CONTENT_TYPE;
+    use sonic_rs::{json, JsonValueTrait, Value};
+
+    use crate::testkit::{ClientResponseExt, Started, TestRouter, TestSubgraphs};
+
+    async fn send_mcp_message(router: &TestRouter<Started>, message: Value) -> Value {
+        let res = router
+            .serv()
+            .post("/mcp")
+            .header(CONTENT_TYPE, "application/json")
+            .send_body(message.to_string())
+            .await
+            .expect("failed to send MCP message");
+        assert_eq!(res.status(), 200);
+        res.json_body().await
+    }
+
+    #[ntex::test]
+    async fn should_list_and_call_tools() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                mcp:
+                    enabled: true
+                    tools:
+                        - name: get_user
+                          description: Returns a user by its id.
+                          query: "query GetUser($id: ID!, $withName: Boolean = true) { user(id: $id) { id name @include(if: $withName) } }"
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let initialize = send_mcp_message(
+            &router,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-03-26",
+                    "capabilities": {},
+                    "clientInfo": { "name": "test", "version": "1.0.0" }
+                }
+            }),
+        )
+        .await;
+        assert_eq!(
+            initialize["result"]["protocolVersion"].as_str(),
+            Some("2025-03-26")
+        );
+
+        let notification = router
+            .serv()
+            .post("/mcp")
+            .header(CONTENT_TYPE, "application/json")
+            .send_body(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .await
+            .expect("failed to send MCP notification");
+        assert_eq!(notification.status(), 202);
+
+        let list = send_mcp_message(
+            &router,
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+        )
+        .await;
+        assert_eq!(
+            list["result"]["tools"],
+            json!([{
+                "name": "get_user",
+                "description": "Returns a user by its id.",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "withName": { "type": "boolean", "default": true }
+                    },
+                    "required": ["id"]
+                }
+            }])
+        );
+
+        let call = send_mcp_message(
+            &router,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "tools/call",
+                "params": { "name": "get_user", "arguments": { "id": "1" } }
+            }),
+        )
+        .await;
+        assert_eq!(call["result"]["isError"].as_bool(), Some(false));
+        let text = call["result"]["content"][0]["text"]
+            .as_str()
+            .expect("expected a text content");
+        let data: Value = sonic_rs::from_str(text).expect("expected a GraphQL response");
+        assert_eq!(data["data"]["user"]["id"].as_str(), Some("1"));
+    }
+
+    #[ntex::test]
+    async fn should_only_call_configured_tools() {
+        let router = TestRouter::builder()
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                mcp:
+                    enabled: true
+                    tools:
+                        - name: get_user
+                          query: "query GetUser($id: ID!) { user(id: $id) { id } }"
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let call = send_mcp_message(
+            &router,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": { "name": "users", "arguments": {} }
+            }),
+        )
+        .await;
+        assert_eq!(call["error"]["code"].as_i64(), Some(-32602));
+        assert_eq!(
+            call["error"]["message"].as_str(),
+            Some("unknown tool 'users'")
+        );
+
+        let invalid_arguments = send_mcp_message(
+            &router,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": { "name": "get_user", "arguments": {} }
+            }),
+        )
+        .await;
+        assert_eq!(invalid_arguments["result"]["isError"].as_bool(), Some(true));
+
+        let res = router
+            .serv()
+            .get("/mcp")
+            .send()
+            .await
+            .expect("failed to send GET request");
+        assert_eq!(res.status(), 405);
+    }
+}
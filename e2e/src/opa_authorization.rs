@@ -0,0 +1,221 @@
+#[cfg(test)]
+mod opa_authorization_e2e_tests {
+    use mockito::Matcher;
+    use sonic_rs::JsonValueTrait;
+
+    use crate::testkit::{some_header_map, ClientResponseExt, TestRouter, TestSubgraphs};
+
+    fn opa_router_config(opa_url: &str, extra: &str) -> String {
+        format!(
+            r#"
+            supergraph:
+              source: file
+              path: supergraph.graphql
+            authorization:
+              opa:
+                policy:
+                  type: http
+                  url: {opa_url}/v1/data/graphql/authz
+                {extra}
+            "#,
+        )
+    }
+
+    #[ntex::test]
+    async fn denies_the_operation_rejected_by_the_policy() {
+        let mut opa = mockito::Server::new_async().await;
+        let decision_mock = opa
+            .mock("POST", "/v1/data/graphql/authz")
+            .match_body(Matcher::PartialJsonString(
+                r#"{ "input": { "operation": { "type": "query" }, "jwt": { "authenticated": false } } }"#
+                    .to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "result": false }"#)
+            .create();
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(opa_router_config(&opa.url(), ""))
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+
+        assert_eq!(res.status(), ntex::http::StatusCode::FORBIDDEN);
+        let json_body = res.json_body().await;
+        assert_eq!(
+            json_body["errors"][0]["extensions"]["code"],
+            "UNAUTHORIZED_OPERATION"
+        );
+        assert!(subgraphs.get_requests_log("accounts").is_none());
+        decision_mock.assert_async().await;
+    }
+
+    #[ntex::test]
+    async fn removes_the_fields_denied_by_the_policy() {
+        let mut opa = mockito::Server::new_async().await;
+        opa.mock("POST", "/v1/data/graphql/authz")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "result": { "allow": true, "denied_fields": ["User.name"] } }"#)
+            .create();
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(opa_router_config(&opa.url(), ""))
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ users { id name } }", None, None)
+            .await;
+
+        assert!(res.status().is_success(), "Expected 200 OK");
+        let json_body = res.json_body().await;
+        assert!(json_body["data"]["users"][0]["id"].is_str());
+        assert!(json_body["data"]["users"][0]["name"].is_null());
+        assert_eq!(
+            json_body["errors"][0]["extensions"]["code"],
+            "UNAUTHORIZED_FIELD_OR_TYPE"
+        );
+    }
+
+    #[ntex::test]
+    async fn caches_the_decisions_and_skips_other_operation_types() {
+        let mut opa = mockito::Server::new_async().await;
+        let decision_mock = opa
+            .mock("POST", "/v1/data/graphql/authz")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "result": true }"#)
+            .expect(1)
+            .create();
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(opa_router_config(
+                &opa.url(),
+                "operation_types: [query]
+                cache:
+                  ttl: 1m",
+            ))
+            .build()
+            .start()
+            .await;
+
+        // Headers not listed in the cache config are not part of the input, nor of the key
+        for request_id in ["1", "2"] {
+            let res = router
+                .send_graphql_request(
+                    "{ users { id } }",
+                    None,
+                    some_header_map!(
+                        http::header::HeaderName::from_static("x-request-id") => request_id
+                    ),
+                )
+                .await;
+            assert!(res.status().is_success(), "Expected 200 OK");
+        }
+
+        decision_mock.assert_async().await;
+    }
+
+    #[ntex::test]
+    async fn caches_the_decisions_by_the_listed_headers() {
+        let mut opa = mockito::Server::new_async().await;
+        let acme_mock = opa
+            .mock("POST", "/v1/data/graphql/authz")
+            .match_body(Matcher::PartialJsonString(
+                r#"{ "input": { "headers": { "x-tenant": "acme" } } }"#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "result": true }"#)
+            .expect(1)
+            .create();
+        let globex_mock = opa
+            .mock("POST", "/v1/data/graphql/authz")
+            .match_body(Matcher::PartialJsonString(
+                r#"{ "input": { "headers": { "x-tenant": "globex" } } }"#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{ "result": false }"#)
+            .expect(1)
+            .create();
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(opa_router_config(
+                &opa.url(),
+                "cache:
+                  ttl: 1m
+                  headers: [X-Tenant]",
+            ))
+            .build()
+            .start()
+            .await;
+
+        for (tenant, expected_status) in [
+            ("acme", ntex::http::StatusCode::OK),
+            ("globex", ntex::http::StatusCode::FORBIDDEN),
+            ("acme", ntex::http::StatusCode::OK),
+            ("globex", ntex::http::StatusCode::FORBIDDEN),
+        ] {
+            let res = router
+                .send_graphql_request(
+                    "{ users { id } }",
+                    None,
+                    some_header_map!(
+                        http::header::HeaderName::from_static("x-tenant") => tenant
+                    ),
+                )
+                .await;
+            assert_eq!(res.status(), expected_status);
+        }
+
+        acme_mock.assert_async().await;
+        globex_mock.assert_async().await;
+    }
+
+    #[ntex::test]
+    async fn applies_on_error_when_the_policy_can_not_be_evaluated() {
+        let mut opa = mockito::Server::new_async().await;
+        opa.mock("POST", "/v1/data/graphql/authz")
+            .with_status(500)
+            .create();
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let deny_router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(opa_router_config(&opa.url(), ""))
+            .build()
+            .start()
+            .await;
+        let res = deny_router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+        assert_eq!(res.status(), ntex::http::StatusCode::FORBIDDEN);
+
+        let allow_router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(opa_router_config(&opa.url(), "on_error: allow"))
+            .build()
+            .start()
+            .await;
+        let res = allow_router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+    }
+}
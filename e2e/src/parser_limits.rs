@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod parser_limits_e2e_tests {
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    #[ntex::test]
+    async fn rejects_an_operation_exceeding_recursion_limit() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+            supergraph:
+                source: file
+                path: ./supergraph.graphql
+            limits:
+                max_recursion:
+                    n: 2
+            "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ me { reviews { id } } }", None, None)
+            .await;
+        assert_eq!(res.status().as_u16(), 400);
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "errors": [
+            {
+              "message": "Recursion limit exceeded.",
+              "locations": [
+                {
+                  "line": 1,
+                  "column": 16
+                }
+              ],
+              "extensions": {
+                "code": "RECURSION_LIMIT_EXCEEDED"
+              }
+            }
+          ]
+        }
+        "#);
+
+        let res = router
+            .send_graphql_request("{ me { id } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+    }
+
+    #[ntex::test]
+    async fn rejects_an_operation_exceeding_document_size_limit() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+            supergraph:
+                source: file
+                path: ./supergraph.graphql
+            limits:
+                max_document_size: 16B
+            "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ me { id name } }", None, None)
+            .await;
+        assert_eq!(res.status().as_u16(), 400);
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "errors": [
+            {
+              "message": "Operation document exceeds the size limit of 16 bytes",
+              "extensions": {
+                "code": "DOCUMENT_SIZE_LIMIT_EXCEEDED"
+              }
+            }
+          ]
+        }
+        "#);
+
+        let res = router
+            .send_graphql_request("{ me { id } }", None, None)
+            .await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+    }
+
+    #[ntex::test]
+    async fn rejects_an_operation_exceeding_parse_duration_limit() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+            supergraph:
+                source: file
+                path: ./supergraph.graphql
+            limits:
+                max_parse_duration: 0s
+            "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ me { id } }", None, None)
+            .await;
+        assert_eq!(res.status().as_u16(), 400);
+
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "errors": [
+            {
+              "message": "Parse timeout exceeded.",
+              "locations": [
+                {
+                  "line": 1,
+                  "column": 1
+                }
+              ],
+              "extensions": {
+                "code": "PARSE_TIMEOUT_EXCEEDED"
+              }
+            }
+          ]
+        }
+        "#);
+    }
+}
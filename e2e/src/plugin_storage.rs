@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod plugin_storage_e2e_tests {
+    use hive_router::{
+        async_trait,
+        ntex::web,
+        plugins::{
+            hooks::on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+            plugin_routes::PluginRoute,
+            plugin_trait::RouterPlugin,
+        },
+    };
+
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    #[ntex::test]
+    async fn plugins_store_values_in_the_storage_of_the_router() {
+        struct VisitsPlugin;
+
+        #[async_trait]
+        impl RouterPlugin for VisitsPlugin {
+            type Config = ();
+
+            fn plugin_name() -> &'static str {
+                "visits"
+            }
+
+            fn on_plugin_init(mut payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+                let storage = payload.storage();
+                payload.register_route(PluginRoute::post("/visits", move |_request| {
+                    let storage = storage.clone();
+                    async move {
+                        let visits = storage
+                            .get("count")
+                            .await
+                            .expect("failed to read the count")
+                            .and_then(|value| String::from_utf8_lossy(&value).parse().ok())
+                            .unwrap_or(0u64)
+                            + 1;
+                        storage
+                            .set("count", visits.to_string(), None)
+                            .await
+                            .expect("failed to write the count");
+                        web::HttpResponse::Ok().body(visits.to_string())
+                    }
+                }));
+                payload.initialize_plugin(Self)
+            }
+        }
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                plugin_storage:
+                  backend:
+                    type: memory
+                    max_entries: 10
+                plugins:
+                  visits:
+                    enabled: true
+                "#,
+            )
+            .register_plugin::<VisitsPlugin>()
+            .build()
+            .start()
+            .await;
+
+        for expected in ["1", "2", "3"] {
+            let res = router
+                .serv()
+                .post("/visits")
+                .send()
+                .await
+                .expect("failed to send the request");
+            assert_eq!(res.status(), 200);
+            assert_eq!(res.string_body().await, expected);
+        }
+    }
+}
@@ -1,11 +1,15 @@
 #[cfg(test)]
 mod probes_e2e_tests {
     use std::{
+        fs,
         thread::{self},
         time::Duration,
     };
 
-    use crate::testkit::TestRouter;
+    use sonic_rs::JsonValueTrait;
+    use tempfile::NamedTempFile;
+
+    use crate::testkit::{ClientResponseExt, TestRouter};
 
     #[ntex::test]
     async fn should_respond_to_probes_correctly() {
@@ -115,4 +119,135 @@ mod probes_e2e_tests {
         let res = router.serv().post("/readiness").send().await.unwrap();
         assert!(res.status().is_success());
     }
+
+    #[ntex::test]
+    async fn should_report_rejected_supergraph_reload_on_readiness() {
+        let file = NamedTempFile::new().expect("failed to create temp file");
+        let supergraph_file_path = file
+            .path()
+            .to_str()
+            .expect("failed to convert path to string")
+            .to_string();
+
+        let supergraph = include_str!("../supergraph.graphql");
+        fs::write(&supergraph_file_path, supergraph).expect("failed to write supergraph");
+
+        let router = TestRouter::builder()
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: {supergraph_file_path}
+                    poll_interval: 100ms
+                "#,
+            ))
+            .build()
+            .start()
+            .await;
+
+        let res = router.serv().get("/readiness").send().await.unwrap();
+        assert!(res.status().is_success());
+        let body = res.json_body().await;
+        assert_eq!(body["ready"].as_bool(), Some(true));
+        assert_eq!(
+            body["last_supergraph_reload"]["status"].as_str(),
+            Some("succeeded")
+        );
+
+        // a subgraph URL without scheme is rejected when building the new supergraph
+        fs::write(
+            &supergraph_file_path,
+            supergraph.replace("http://0.0.0.0:4200/accounts", "/accounts"),
+        )
+        .expect("failed to write supergraph");
+        ntex::time::sleep(Duration::from_millis(500)).await;
+
+        // the router keeps serving the previous supergraph
+        let res = router.serv().get("/readiness").send().await.unwrap();
+        assert!(res.status().is_success());
+        let body = res.json_body().await;
+        assert_eq!(body["ready"].as_bool(), Some(true));
+        assert_eq!(
+            body["last_supergraph_reload"]["status"].as_str(),
+            Some("failed")
+        );
+        insta::assert_snapshot!(
+            body["last_supergraph_reload"]["error"].as_str().unwrap(),
+            @r#"Endpoint "/accounts" is not a valid subgraph URL: missing scheme"#
+        );
+
+        let res = router
+            .send_graphql_request("{ __typename }", None, None)
+            .await;
+        assert!(res.status().is_success());
+    }
+
+    #[ntex::test]
+    async fn should_report_failing_dependency_checks_on_readiness() {
+        let mut server = mockito::Server::new_async().await;
+        let host = server.host_with_port();
+        server
+            .mock("GET", "/accounts/health")
+            .with_status(200)
+            .create();
+        let unhealthy_products = server
+            .mock("GET", "/products/health")
+            .with_status(503)
+            .create();
+
+        let router = TestRouter::builder()
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                http:
+                    readiness:
+                        interval: 100ms
+                        subgraphs:
+                            accounts:
+                                url: http://{host}/accounts/health
+                            products:
+                                url: http://{host}/products/health
+                "#,
+            ))
+            .skip_wait_for_ready_on_start()
+            .build()
+            .start()
+            .await;
+
+        // the checks run in the background
+        ntex::time::sleep(Duration::from_millis(300)).await;
+
+        let res = router.serv().get("/readiness").send().await.unwrap();
+        assert_eq!(res.status(), 503);
+        let body = res.json_body().await;
+        assert_eq!(body["ready"].as_bool(), Some(false));
+        assert_eq!(
+            body["checks"][0]["name"].as_str(),
+            Some("subgraph:accounts")
+        );
+        assert_eq!(body["checks"][0]["status"].as_str(), Some("passed"));
+        assert_eq!(
+            body["checks"][1]["name"].as_str(),
+            Some("subgraph:products")
+        );
+        assert_eq!(body["checks"][1]["status"].as_str(), Some("failed"));
+        assert_eq!(
+            body["checks"][1]["error"].as_str(),
+            Some("responded with a 503 Service Unavailable status")
+        );
+
+        unhealthy_products.remove();
+        server
+            .mock("GET", "/products/health")
+            .with_status(200)
+            .create();
+
+        router.wait_for_ready(None).await;
+        let res = router.serv().get("/readiness").send().await.unwrap();
+        let body = res.json_body().await;
+        assert_eq!(body["ready"].as_bool(), Some(true));
+        assert_eq!(body["checks"][1]["status"].as_str(), Some("passed"));
+    }
 }
@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod progressive_override_e2e_tests {
+    use hive_router_query_planner::graph::{PlannerOverrideContext, PERCENTAGE_SCALE_FACTOR};
+    use serde_json::json;
+    use sonic_rs::JsonValueTrait;
+
+    use crate::testkit::{
+        mock_subgraphs::mock_subgraphs, some_header_map, ClientResponseExt, TestRouter,
+        TestSubgraphs, TestSubgraphsBuilder,
+    };
+
+    // `Post.createdAt` is moved from `a` to `b` with @override(label: "percent(75)")
+    const SUPERGRAPH: &str = "fixtures/progressive_override/supergraph.graphql";
+    const QUERY: &str = "{ aFeed { createdAt } }";
+
+    fn subgraphs() -> TestSubgraphsBuilder {
+        TestSubgraphs::builder().with_on_request(mock_subgraphs(json!({
+            "a": {
+                "query": {
+                    "aFeed": [{ "__typename": "Post", "id": "1", "createdAt": "from-a" }],
+                },
+                "entities": [{ "__typename": "Post", "id": "1", "createdAt": "from-a" }],
+            },
+            "b": {
+                "entities": [{ "__typename": "Post", "id": "1", "createdAt": "from-b" }],
+            },
+        })))
+    }
+
+    fn config(extra: &str) -> String {
+        format!(
+            r#"
+            supergraph:
+                source: file
+                path: {SUPERGRAPH}
+            {extra}
+            "#
+        )
+    }
+
+    #[ntex::test]
+    async fn should_resolve_percentage_labels_by_the_percentage_key() {
+        let subgraphs = subgraphs().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(config(
+                r#"
+            progressive_override:
+                percentage_key:
+                    expression: '.request.headers."x-user-id"'
+            "#,
+            ))
+            .build()
+            .start()
+            .await;
+
+        let is_overridden = PlannerOverrideContext::percentage_from_request_key(b"user-1")
+            < 75 * PERCENTAGE_SCALE_FACTOR;
+        let expected_created_at = if is_overridden { "from-b" } else { "from-a" };
+
+        for _ in 0..5 {
+            let res = router
+                .send_graphql_request(QUERY, None, some_header_map! { "x-user-id" => "user-1" })
+                .await;
+            assert!(res.status().is_success(), "Expected 200 OK");
+
+            let body = res.json_body().await;
+            assert_eq!(
+                body["data"]["aFeed"][0]["createdAt"].as_str(),
+                Some(expected_created_at),
+                "the same key should always be on the same side of the rollout"
+            );
+        }
+
+        let b_requests = subgraphs.get_requests_log("b").unwrap_or_default().len();
+        assert_eq!(b_requests, if is_overridden { 5 } else { 0 });
+    }
+
+    #[ntex::test]
+    async fn should_force_percentage_label_on() {
+        let subgraphs = subgraphs().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(config(
+                r#"
+            override_labels:
+                percent(75): true
+            "#,
+            ))
+            .build()
+            .start()
+            .await;
+
+        for _ in 0..5 {
+            let res = router.send_graphql_request(QUERY, None, None).await;
+            assert!(res.status().is_success(), "Expected 200 OK");
+            insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+            {
+              "data": {
+                "aFeed": [
+                  {
+                    "createdAt": "from-b"
+                  }
+                ]
+              }
+            }
+            "#);
+        }
+    }
+
+    #[ntex::test]
+    async fn should_force_percentage_label_off() {
+        let subgraphs = subgraphs().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(config(
+                r#"
+            override_labels:
+                percent(75): false
+            "#,
+            ))
+            .build()
+            .start()
+            .await;
+
+        for _ in 0..5 {
+            let res = router.send_graphql_request(QUERY, None, None).await;
+            assert!(res.status().is_success(), "Expected 200 OK");
+            insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+            {
+              "data": {
+                "aFeed": [
+                  {
+                    "createdAt": "from-a"
+                  }
+                ]
+              }
+            }
+            "#);
+        }
+
+        assert!(subgraphs
+            .get_requests_log("b")
+            .unwrap_or_default()
+            .is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod proxy_e2e_tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::{TcpListener, TcpStream},
+    };
+
+    use crate::testkit::{Started, TestRouter, TestSubgraphs};
+
+    /// Request line and `Proxy-Authorization` header of a received `CONNECT` request.
+    type ConnectRequest = (String, Option<String>);
+
+    /// Starts a forward proxy accepting `CONNECT` requests,
+    /// and returns its address with the log of the received requests.
+    async fn start_connect_proxy() -> (String, Arc<Mutex<Vec<ConnectRequest>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind tcp listener");
+        let addr = listener
+            .local_addr()
+            .expect("failed to get local address")
+            .to_string();
+        let requests_log = Arc::new(Mutex::new(Vec::new()));
+
+        let log = requests_log.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let log = log.clone();
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    let mut request_line = String::new();
+                    stream.read_line(&mut request_line).await.unwrap();
+                    let mut authorization = None;
+                    loop {
+                        let mut line = String::new();
+                        stream.read_line(&mut line).await.unwrap();
+                        if line == "\r\n" {
+                            break;
+                        }
+                        if let Some((name, value)) = line.split_once(':') {
+                            if name.eq_ignore_ascii_case("proxy-authorization") {
+                                authorization = Some(value.trim().to_string());
+                            }
+                        }
+                    }
+
+                    let target = request_line
+                        .split_whitespace()
+                        .nth(1)
+                        .expect("expected a CONNECT target")
+                        .to_string();
+                    log.lock()
+                        .unwrap()
+                        .push((request_line.trim().to_string(), authorization));
+
+                    let mut upstream = TcpStream::connect(target)
+                        .await
+                        .expect("failed to connect to subgraphs");
+                    let mut stream = stream.into_inner();
+                    stream
+                        .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                        .await
+                        .unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+                });
+            }
+        });
+
+        (addr, requests_log)
+    }
+
+    async fn router_with_proxy(
+        subgraphs: &TestSubgraphs<Started>,
+        proxy_config: &str,
+    ) -> TestRouter<Started> {
+        TestRouter::builder()
+            .with_subgraphs(subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                traffic_shaping:
+                  all:
+                    proxy:
+                      {proxy_config}
+                "#
+            ))
+            .build()
+            .start()
+            .await
+    }
+
+    #[ntex::test]
+    async fn should_tunnel_subgraph_requests_through_proxy() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let (proxy_addr, proxy_log) = start_connect_proxy().await;
+        let router = router_with_proxy(
+            &subgraphs,
+            &format!("{{ url: \"http://user:secret@{proxy_addr}\" }}"),
+        )
+        .await;
+
+        let res = router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+        assert!(res.status().is_success());
+
+        assert_eq!(
+            subgraphs
+                .get_requests_log("accounts")
+                .expect("expected requests sent to accounts subgraph")
+                .len(),
+            1,
+        );
+
+        let subgraphs_addr = subgraphs.url().trim_start_matches("http://").to_string();
+        let proxy_log = proxy_log.lock().unwrap();
+        assert_eq!(proxy_log.len(), 1);
+        assert_eq!(
+            proxy_log[0],
+            (
+                format!("CONNECT {subgraphs_addr} HTTP/1.1"),
+                // base64 of "user:secret"
+                Some("Basic dXNlcjpzZWNyZXQ=".to_string()),
+            )
+        );
+    }
+
+    #[ntex::test]
+    async fn should_bypass_proxy_for_no_proxy_hosts() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let (proxy_addr, proxy_log) = start_connect_proxy().await;
+        let router = router_with_proxy(
+            &subgraphs,
+            &format!("{{ url: \"http://{proxy_addr}\", no_proxy: [\"127.0.0.0/8\"] }}"),
+        )
+        .await;
+
+        let res = router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+        assert!(res.status().is_success());
+
+        assert_eq!(
+            subgraphs
+                .get_requests_log("accounts")
+                .expect("expected requests sent to accounts subgraph")
+                .len(),
+            1,
+        );
+        assert!(proxy_log.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod query_plan_cache_e2e_tests {
+    use serde_json::json;
+
+    use crate::testkit::{
+        mock_subgraphs::mock_subgraphs, ClientResponseExt, TestRouter, TestSubgraphs,
+        TestSubgraphsBuilder,
+    };
+
+    const SUPERGRAPH: &str = "fixtures/progressive_override/supergraph.graphql";
+    const QUERY: &str = "{ aFeed { id } }";
+
+    fn subgraphs() -> TestSubgraphsBuilder {
+        TestSubgraphs::builder().with_on_request(mock_subgraphs(json!({
+            "a": {
+                "query": {
+                    "aFeed": [{ "__typename": "Post", "id": "1" }],
+                },
+            },
+        })))
+    }
+
+    #[ntex::test]
+    async fn should_plan_warmup_operations_before_serving_requests() {
+        let subgraphs = subgraphs().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: {SUPERGRAPH}
+                query_planner:
+                    warmup:
+                        - query: "{QUERY}"
+                "#
+            ))
+            .build()
+            .start()
+            .await;
+
+        let runtime = router
+            .schema_state()
+            .configured_runtime()
+            .expect("configured runtime to exist");
+        runtime.plan_cache.run_pending_tasks().await;
+        assert_eq!(
+            runtime.plan_cache.entry_count(),
+            1,
+            "the warmup operation should be planned before the first request"
+        );
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "aFeed": [
+              {
+                "id": "1"
+              }
+            ]
+          }
+        }
+        "#);
+
+        runtime.plan_cache.run_pending_tasks().await;
+        assert_eq!(
+            runtime.plan_cache.entry_count(),
+            1,
+            "the request should reuse the plan of the warmup operation"
+        );
+    }
+
+    #[ntex::test]
+    async fn should_warm_up_from_the_persisted_plan_cache() {
+        let persistence_dir = tempfile::tempdir().expect("must create temporary directory");
+        let persistence_path = persistence_dir.path().join("plan-cache.json");
+        let config = format!(
+            r#"
+            supergraph:
+                source: file
+                path: {SUPERGRAPH}
+            query_planner:
+                cache:
+                    persistence:
+                        path: {}
+            "#,
+            persistence_path.display()
+        );
+
+        let subgraphs = subgraphs().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(config.clone())
+            .build()
+            .start()
+            .await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        let runtime = router
+            .schema_state()
+            .configured_runtime()
+            .expect("configured runtime to exist");
+        runtime.normalize_cache.run_pending_tasks().await;
+        router.schema_state().persist_plan_cache();
+        drop(router);
+
+        assert!(
+            persistence_path.exists(),
+            "the plan cache should be persisted"
+        );
+
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(config)
+            .build()
+            .start()
+            .await;
+
+        let runtime = router
+            .schema_state()
+            .configured_runtime()
+            .expect("configured runtime to exist");
+        runtime.plan_cache.run_pending_tasks().await;
+        assert_eq!(
+            runtime.plan_cache.entry_count(),
+            1,
+            "the persisted operation should be planned when the router starts"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod rate_limit_e2e_tests {
+    use std::time::{Duration, Instant};
+
+    use sonic_rs::JsonValueTrait;
+
+    use crate::testkit::{ClientResponseExt, Started, TestRouter, TestSubgraphs};
+
+    const QUERY: &str = "{ topProducts(first: 1) { upc } }";
+
+    async fn router_with_products_rate_limit(
+        subgraphs: &TestSubgraphs<Started>,
+        rate_limit_config: &str,
+    ) -> TestRouter<Started> {
+        TestRouter::builder()
+            .with_subgraphs(subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                traffic_shaping:
+                  subgraphs:
+                    products:
+                      rate_limit:
+                        {rate_limit_config}
+                "#
+            ))
+            .build()
+            .start()
+            .await
+    }
+
+    #[ntex::test]
+    async fn should_shed_requests_exceeding_the_limit() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = router_with_products_rate_limit(
+            &subgraphs,
+            "{ enabled: true, requests_per_second: 1, burst: 1 }",
+        )
+        .await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert!(res.status().is_success());
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "upc": "1"
+              }
+            ]
+          }
+        }
+        "#);
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert!(res.status().is_success());
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topProducts": null
+          },
+          "errors": [
+            {
+              "message": "Rejected by the rate limiter",
+              "extensions": {
+                "code": "SUBGRAPH_RATE_LIMITED",
+                "service": "products"
+              }
+            }
+          ]
+        }
+        "#);
+
+        assert_eq!(
+            subgraphs
+                .get_requests_log("products")
+                .expect("expected requests sent to products subgraph")
+                .len(),
+            1,
+        );
+    }
+
+    #[ntex::test]
+    async fn should_queue_requests_exceeding_the_limit() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = router_with_products_rate_limit(
+            &subgraphs,
+            "{ enabled: true, requests_per_second: 5, burst: 1, strategy: queue, max_queue_wait: 1s }",
+        )
+        .await;
+
+        let started_at = Instant::now();
+        for _ in 0..2 {
+            let res = router.send_graphql_request(QUERY, None, None).await;
+            assert!(res.status().is_success());
+            let body = res.json_body().await;
+            assert!(
+                body.get("errors").is_none(),
+                "expected no errors, got: {body}"
+            );
+        }
+
+        // The second request waits for the 200ms between two requests at 5 requests per second
+        assert!(started_at.elapsed() >= Duration::from_millis(150));
+        assert_eq!(
+            subgraphs
+                .get_requests_log("products")
+                .expect("expected requests sent to products subgraph")
+                .len(),
+            2,
+        );
+    }
+}
@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod request_id_e2e_tests {
+    use ntex::http;
+
+    use crate::testkit::{some_header_map, TestRouter, TestSubgraphs};
+
+    const QUERY: &str = "{ topProducts(first: 1) { upc } }";
+
+    #[ntex::test]
+    async fn generates_and_forwards_an_id() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                request_id:
+                    enabled: true
+                    subgraph_header: x-correlation-id
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.status(), 200);
+        let request_id = res
+            .headers()
+            .get("x-request-id")
+            .expect("the response should carry the request id")
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(request_id.len(), 26, "a ULID is expected");
+
+        let subgraph_requests = subgraphs
+            .get_requests_log("products")
+            .expect("expected requests to the products subgraph");
+        assert_eq!(
+            subgraph_requests[0]
+                .headers
+                .get("x-correlation-id")
+                .map(|v| v.to_str().unwrap()),
+            Some(request_id.as_str())
+        );
+        assert!(subgraph_requests[0].headers.get("x-request-id").is_none());
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_ne!(
+            res.headers().get("x-request-id").unwrap().to_str().unwrap(),
+            request_id,
+            "every request should get its own id"
+        );
+    }
+
+    #[ntex::test]
+    async fn reuses_a_valid_incoming_id() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                request_id:
+                    enabled: true
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                QUERY,
+                None,
+                some_header_map!(
+                    http::header::HeaderName::from_static("x-request-id") => "lb-1234"
+                ),
+            )
+            .await;
+        assert_eq!(
+            res.headers()
+                .get("x-request-id")
+                .map(|v| v.to_str().unwrap()),
+            Some("lb-1234")
+        );
+        let subgraph_requests = subgraphs.get_requests_log("products").unwrap();
+        assert_eq!(
+            subgraph_requests[0]
+                .headers
+                .get("x-request-id")
+                .map(|v| v.to_str().unwrap()),
+            Some("lb-1234")
+        );
+
+        let res = router
+            .send_graphql_request(
+                QUERY,
+                None,
+                some_header_map!(
+                    http::header::HeaderName::from_static("x-request-id") => "not a valid id"
+                ),
+            )
+            .await;
+        let request_id = res.headers().get("x-request-id").unwrap().to_str().unwrap();
+        assert_ne!(request_id, "not a valid id");
+        assert_eq!(request_id.len(), 26, "a ULID is expected");
+    }
+
+    #[ntex::test]
+    async fn is_disabled_by_default() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert!(res.headers().get("x-request-id").is_none());
+        let subgraph_requests = subgraphs.get_requests_log("products").unwrap();
+        assert!(subgraph_requests[0].headers.get("x-request-id").is_none());
+    }
+}
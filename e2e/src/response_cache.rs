@@ -0,0 +1,322 @@
+#[cfg(test)]
+mod response_cache_e2e_tests {
+    use mockito::Matcher;
+    use ntex::http;
+    use reqwest::StatusCode;
+    use sonic_rs::json;
+
+    use crate::testkit::{
+        some_header_map, ClientResponseExt, ResponseLike, Started, TestRouter, TestSubgraphs,
+    };
+
+    fn cache_control(res: &ntex::client::ClientResponse) -> Option<String> {
+        res.header("cache-control").map(|v| {
+            v.to_str()
+                .expect("cache-control is not valid ascii")
+                .to_string()
+        })
+    }
+
+    fn products_requests(subgraphs: &TestSubgraphs<Started>) -> usize {
+        subgraphs
+            .get_requests_log("products")
+            .map(|r| r.len())
+            .unwrap_or(0)
+    }
+
+    #[ntex::test]
+    async fn caches_query_responses() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                response_cache:
+                    enabled: true
+                    default_max_age: 60s
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let query = r#"{ topProducts(first: 1) { upc name } }"#;
+
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(cache_control(&res).as_deref(), Some("max-age=60, public"));
+        let first_body = res.json_body_string_pretty().await;
+        assert_eq!(products_requests(&subgraphs), 1);
+
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+        let cc = cache_control(&res).expect("expected cache-control header");
+        assert!(cc.ends_with(", public"), "unexpected cache-control: {cc}");
+        assert_eq!(res.json_body_string_pretty().await, first_body);
+        assert_eq!(
+            products_requests(&subgraphs),
+            1,
+            "expected the second response to be served from the cache"
+        );
+
+        let res = router
+            .send_graphql_request(
+                r#"query ($first: Int) { topProducts(first: $first) { upc name } }"#,
+                Some(json!({ "first": 2 })),
+                None,
+            )
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            products_requests(&subgraphs),
+            2,
+            "expected a different query to miss the cache"
+        );
+    }
+
+    #[ntex::test]
+    async fn does_not_cache_without_max_age() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                response_cache:
+                    enabled: true
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let query = r#"{ topProducts(first: 1) { upc name } }"#;
+        for _ in 0..2 {
+            let res = router.send_graphql_request(query, None, None).await;
+            assert_eq!(res.status(), 200);
+            assert_eq!(cache_control(&res), None);
+        }
+        assert_eq!(products_requests(&subgraphs), 2);
+    }
+
+    #[ntex::test]
+    async fn subgraph_cache_control_lowers_max_age() {
+        let subgraphs = TestSubgraphs::builder()
+            .with_on_request(|req| {
+                if req.path.contains("products") {
+                    Some(ResponseLike::new(
+                        StatusCode::OK,
+                        None,
+                        some_header_map! {
+                            http::header::CACHE_CONTROL => "public, max-age=30"
+                        },
+                    ))
+                } else {
+                    None
+                }
+            })
+            .build()
+            .start()
+            .await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                headers:
+                    all:
+                        response:
+                            - propagate:
+                                named: cache-control
+                                algorithm: append
+                response_cache:
+                    enabled: true
+                    default_max_age: 60s
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(r#"{ topProducts(first: 1) { upc name } }"#, None, None)
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(cache_control(&res).as_deref(), Some("max-age=30, public"));
+    }
+
+    #[ntex::test]
+    async fn subgraph_no_store_prevents_caching() {
+        let subgraphs = TestSubgraphs::builder()
+            .with_on_request(|req| {
+                if req.path.contains("products") {
+                    Some(ResponseLike::new(
+                        StatusCode::OK,
+                        None,
+                        some_header_map! {
+                            http::header::CACHE_CONTROL => "no-store"
+                        },
+                    ))
+                } else {
+                    None
+                }
+            })
+            .build()
+            .start()
+            .await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                headers:
+                    all:
+                        response:
+                            - propagate:
+                                named: cache-control
+                                algorithm: append
+                response_cache:
+                    enabled: true
+                    default_max_age: 60s
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let query = r#"{ topProducts(first: 1) { upc name } }"#;
+        for _ in 0..2 {
+            let res = router.send_graphql_request(query, None, None).await;
+            assert_eq!(res.status(), 200);
+            let cc = cache_control(&res).expect("expected cache-control header");
+            assert!(cc.contains("no-store"), "unexpected cache-control: {cc}");
+        }
+        assert_eq!(products_requests(&subgraphs), 2);
+    }
+
+    #[ntex::test]
+    async fn private_responses_are_scoped_to_the_client() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph-cache-control.graphql
+                response_cache:
+                    enabled: true
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let query = r#"{ me { id name } }"#;
+        let accounts_requests = || {
+            subgraphs
+                .get_requests_log("accounts")
+                .map(|r| r.len())
+                .unwrap_or(0)
+        };
+
+        // not cached without the client identifier
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(cache_control(&res), None);
+
+        for token in ["a", "a", "b"] {
+            let res = router
+                .send_graphql_request(
+                    query,
+                    None,
+                    some_header_map! {
+                        http::header::AUTHORIZATION => token
+                    },
+                )
+                .await;
+            assert_eq!(res.status(), 200);
+            let cc = cache_control(&res).expect("expected cache-control header");
+            assert!(cc.ends_with(", private"), "unexpected cache-control: {cc}");
+        }
+
+        assert_eq!(accounts_requests(), 3);
+    }
+
+    #[ntex::test]
+    async fn mutations_invalidate_the_responses_of_their_entities() {
+        let mut products_server = mockito::Server::new_async().await;
+        let host = products_server.host_with_port();
+
+        let query_mock = products_server
+            .mock("POST", "/products")
+            .match_body(Matcher::Regex(r"product\(".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data":{"product":{"upc":"1","name":"Table"}}}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let mutation_mock = products_server
+            .mock("POST", "/products")
+            .match_body(Matcher::Regex(r"renameProduct\(".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data":{"renameProduct":{"upc":"1","name":"Desk"}}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let router = TestRouter::builder()
+            .inline_config(format!(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph-mutations.graphql
+                response_cache:
+                    enabled: true
+                    default_max_age: 60s
+                    surrogate_keys: true
+                    invalidate_on_mutation: true
+                override_subgraph_urls:
+                    subgraphs:
+                        products:
+                            url: "http://{host}/products"
+                "#
+            ))
+            .build()
+            .start()
+            .await;
+
+        let query = r#"{ product(upc: "1") { upc name } }"#;
+        for _ in 0..2 {
+            let res = router.send_graphql_request(query, None, None).await;
+            assert_eq!(res.status(), 200);
+        }
+
+        let res = router
+            .send_graphql_request(
+                r#"mutation { renameProduct(upc: "1", name: "Desk") { upc name } }"#,
+                None,
+                None,
+            )
+            .await;
+        assert_eq!(res.status(), 200);
+
+        // the cached response contains the renamed product
+        let res = router.send_graphql_request(query, None, None).await;
+        assert_eq!(res.status(), 200);
+
+        query_mock.assert_async().await;
+        mutation_mock.assert_async().await;
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod response_limits_e2e_tests {
+    use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
+
+    use crate::testkit::{ClientResponseExt, Started, TestRouter, TestSubgraphs};
+
+    const QUERY: &str = r#"{ topProducts(first: 3) { upc reviews { id body } } }"#;
+
+    const SUPERGRAPH_CONFIG: &str = r#"
+        supergraph:
+          source: file
+          path: supergraph.graphql
+        "#;
+
+    async fn router_with_limits(
+        subgraphs: &TestSubgraphs<Started>,
+        limits: &str,
+    ) -> TestRouter<Started> {
+        TestRouter::builder()
+            .with_subgraphs(subgraphs)
+            .inline_config(format!(
+                r#"{SUPERGRAPH_CONFIG}
+        limits:
+          {limits}
+        "#
+            ))
+            .build()
+            .start()
+            .await
+    }
+
+    /// The representations sent by every `_entities` request to the subgraph.
+    fn representations(subgraphs: &TestSubgraphs<Started>, subgraph: &str) -> Vec<usize> {
+        subgraphs
+            .get_requests_log(subgraph)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|request| {
+                let body: Value = sonic_rs::from_slice(request.body.as_ref()?).ok()?;
+                Some(body["variables"]["representations"].as_array()?.len())
+            })
+            .collect()
+    }
+
+    async fn unlimited_response(subgraphs: &TestSubgraphs<Started>) -> String {
+        let router = TestRouter::builder()
+            .with_subgraphs(subgraphs)
+            .inline_config(SUPERGRAPH_CONFIG)
+            .build()
+            .start()
+            .await;
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.status(), 200);
+        res.json_body_string_pretty().await
+    }
+
+    #[ntex::test]
+    async fn splits_entities_exceeding_the_limit_per_fetch() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let expected = unlimited_response(&subgraphs).await;
+        assert_eq!(representations(&subgraphs, "reviews"), vec![3]);
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = router_with_limits(&subgraphs, "max_entities_per_fetch: 2").await;
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json_body_string_pretty().await, expected);
+        assert_eq!(representations(&subgraphs, "reviews"), vec![2, 1]);
+    }
+
+    #[ntex::test]
+    async fn splits_representations_exceeding_the_size_limit() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let expected = unlimited_response(&subgraphs).await;
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        // every representation exceeds the limit, so each one is sent on its own
+        let router = router_with_limits(&subgraphs, "max_representations_size: 1B").await;
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json_body_string_pretty().await, expected);
+        assert_eq!(representations(&subgraphs, "reviews"), vec![1, 1, 1]);
+    }
+
+    #[ntex::test]
+    async fn rejects_responses_exceeding_the_size_limit() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = router_with_limits(&subgraphs, "max_response_size: 64B").await;
+
+        let res = router
+            .send_graphql_request("{ topProducts(first: 1) { upc } }", None, None)
+            .await;
+        assert_eq!(res.status(), 200);
+
+        let res = router.send_graphql_request(QUERY, None, None).await;
+        assert_eq!(res.status(), 500);
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("RESPONSE_TOO_LARGE")
+        );
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod retry_e2e_tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use crate::testkit::{ClientResponseExt, ResponseLike, Started, TestRouter, TestSubgraphs};
+
+    /// The `products` subgraph responds with `503 Service Unavailable`
+    /// to its first `failures` requests, and with its data afterwards.
+    async fn subgraphs_with_unavailable_products(failures: usize) -> TestSubgraphs<Started> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        TestSubgraphs::builder()
+            .with_on_request(move |req| {
+                if req.path != "/products" {
+                    return None;
+                }
+                if calls.fetch_add(1, Ordering::SeqCst) < failures {
+                    return Some(ResponseLike::new(
+                        axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                        None,
+                        None,
+                    ));
+                }
+                None
+            })
+            .build()
+            .start()
+            .await
+    }
+
+    async fn router_with_retry_config(
+        subgraphs: &TestSubgraphs<Started>,
+        retry_config: &str,
+    ) -> TestRouter<Started> {
+        TestRouter::builder()
+            .with_subgraphs(subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                traffic_shaping:
+                  all:
+                    retry:
+                      {retry_config}
+                "#
+            ))
+            .build()
+            .start()
+            .await
+    }
+
+    #[ntex::test]
+    async fn should_retry_queries_on_5xx_responses() {
+        let subgraphs = subgraphs_with_unavailable_products(2).await;
+        let router = router_with_retry_config(
+            &subgraphs,
+            "{ enabled: true, max_attempts: 3, initial_backoff: 10ms }",
+        )
+        .await;
+
+        let res = router
+            .send_graphql_request("{ topProducts(first: 1) { upc } }", None, None)
+            .await;
+
+        assert!(res.status().is_success());
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topProducts": [
+              {
+                "upc": "1"
+              }
+            ]
+          }
+        }
+        "#);
+        assert_eq!(
+            subgraphs
+                .get_requests_log("products")
+                .expect("expected requests sent to products subgraph")
+                .len(),
+            3,
+        );
+    }
+
+    #[ntex::test]
+    async fn should_stop_retrying_after_max_attempts() {
+        let subgraphs = subgraphs_with_unavailable_products(usize::MAX).await;
+        let router = router_with_retry_config(
+            &subgraphs,
+            "{ enabled: true, max_attempts: 2, initial_backoff: 10ms }",
+        )
+        .await;
+
+        let res = router
+            .send_graphql_request("{ topProducts(first: 1) { upc } }", None, None)
+            .await;
+
+        assert!(res.status().is_success());
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "topProducts": null
+          },
+          "errors": [
+            {
+              "message": "Received empty response body from subgraph \"products\"",
+              "extensions": {
+                "code": "SUBGRAPH_RESPONSE_BODY_EMPTY",
+                "service": "products"
+              }
+            }
+          ]
+        }
+        "#);
+        assert_eq!(
+            subgraphs
+                .get_requests_log("products")
+                .expect("expected requests sent to products subgraph")
+                .len(),
+            2,
+        );
+    }
+
+    #[ntex::test]
+    async fn should_not_retry_when_disabled() {
+        let subgraphs = subgraphs_with_unavailable_products(1).await;
+        let router = router_with_retry_config(&subgraphs, "{ enabled: false }").await;
+
+        let res = router
+            .send_graphql_request("{ topProducts(first: 1) { upc } }", None, None)
+            .await;
+
+        assert!(res.status().is_success());
+        assert_eq!(
+            subgraphs
+                .get_requests_log("products")
+                .expect("expected requests sent to products subgraph")
+                .len(),
+            1,
+        );
+    }
+
+    #[ntex::test]
+    async fn should_not_retry_mutations() {
+        let subgraphs = subgraphs_with_unavailable_products(1).await;
+        let router = router_with_retry_config(
+            &subgraphs,
+            "{ enabled: true, max_attempts: 3, initial_backoff: 10ms }",
+        )
+        .await;
+
+        let res = router
+            .send_graphql_request("mutation { oneofTest(input: {}) { id } }", None, None)
+            .await;
+
+        assert!(res.status().is_success());
+        assert_eq!(
+            subgraphs
+                .get_requests_log("products")
+                .expect("expected requests sent to products subgraph")
+                .len(),
+            1,
+        );
+    }
+}
@@ -1,6 +1,23 @@
 #[cfg(test)]
 mod subscriptions_e2e_tests {
 
+    use std::sync::Mutex;
+
+    use hive_router::{
+        async_trait,
+        plugins::{
+            hooks::{
+                on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+                on_subscription::{
+                    OnSubscriptionEndHookPayload, OnSubscriptionEventHookPayload,
+                    OnSubscriptionEventHookResult, OnSubscriptionStartHookPayload,
+                    OnSubscriptionStartHookResult, SubscriptionEndReason,
+                },
+            },
+            plugin_trait::{FromGraphQLErrorToResponse, RouterPlugin},
+        },
+        GraphQLError, PlanExecutionOutput,
+    };
     use insta::assert_snapshot;
     use ntex::http;
     use reqwest::StatusCode;
@@ -1031,6 +1048,74 @@ mod subscriptions_e2e_tests {
         assert_snapshot!(accept_header, @r#"multipart/mixed;subscriptionSpec="1.0", text/event-stream"#);
     }
 
+    #[ntex::test]
+    async fn subscription_http_accept_only_sse_for_sse_subgraphs() {
+        let subgraphs = TestSubgraphs::builder()
+            .with_http_streaming_subscriptions_protocol(
+                subgraphs::HTTPStreamingSubscriptionProtocol::SseOnly,
+            )
+            .build()
+            .start()
+            .await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                subscriptions:
+                    enabled: true
+                    sse:
+                        subgraphs:
+                            - reviews
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                r#"
+                subscription ($upc: String!) {
+                    reviewAddedForProduct(productUpc: $upc, intervalInMs: 0) {
+                        product {
+                            upc
+                            name
+                        }
+                    }
+                }
+                "#,
+                Some(json!({
+                    "upc": "2"
+                })),
+                some_header_map! {
+                    http::header::ACCEPT => "text/event-stream"
+                },
+            )
+            .await;
+
+        assert!(res.status().is_success(), "Expected 200 OK");
+
+        let subgraph_request = subgraphs
+            .get_requests_log("reviews")
+            .expect("expected requests sent to reviews subgraph");
+
+        let Ok(accept_header) = subgraph_request
+            .first()
+            .expect("expected at least one request to reviews")
+            .headers
+            .get("accept")
+            .expect("expected accept header to be sent with the subgraph request")
+            .to_str()
+        else {
+            panic!("accept header could not be converted to string")
+        };
+
+        assert_snapshot!(accept_header, @"text/event-stream");
+    }
+
     #[ntex::test]
     async fn subscription_stream_failed_source_subgraph_requests() {
         let subgraphs = TestSubgraphs::builder()
@@ -1827,6 +1912,79 @@ mod subscriptions_e2e_tests {
         );
     }
 
+    #[ntex::test]
+    async fn max_active_subscriptions_rejects_over_limit_across_transports() {
+        use futures::StreamExt;
+        use hive_router_plan_executor::executors::{
+            graphql_transport_ws::SubscribePayload, websocket_client::WsClient,
+        };
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                websocket:
+                    enabled: true
+                subscriptions:
+                    enabled: true
+                    max_active_subscriptions: 1
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let query = r#"
+            subscription {
+                reviewAdded(intervalInMs: 200) {
+                    id
+                }
+            }
+        "#;
+        let headers = some_header_map! {
+            http::header::ACCEPT => "text/event-stream"
+        };
+
+        // keep the only slot busy by reading the first event
+        let mut sub1 = router.send_graphql_request(query, None, headers).await;
+        assert!(sub1.status().is_success(), "sub1 should be accepted");
+        let _ = sub1.next().await;
+
+        let wsconn = router.ws().await;
+        let mut ws_client = WsClient::init(wsconn, None)
+            .await
+            .expect("Failed to init WsClient");
+        let ws_payload = || SubscribePayload {
+            query: query.into(),
+            ..Default::default()
+        };
+
+        let mut ws_stream = ws_client.subscribe(ws_payload(), None).await;
+        let response = ws_stream.next().await.expect("Expected a response");
+        let errors = response
+            .errors
+            .expect("Expected the subscription to be rejected");
+        assert_eq!(
+            errors[0].extensions.code.as_deref(),
+            Some("TOO_MANY_SUBSCRIPTIONS")
+        );
+
+        // release the slot and wait briefly for it to be freed
+        drop(sub1);
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let mut ws_stream = ws_client.subscribe(ws_payload(), None).await;
+        let response = ws_stream.next().await.expect("Expected a response");
+        assert!(
+            response.errors.is_none(),
+            "Expected the subscription to be accepted after the slot was freed"
+        );
+    }
+
     #[ntex::test]
     async fn backpressure_http_subgraph_drops_messages_not_subscription() {
         let subgraphs = TestSubgraphs::builder()
@@ -1950,4 +2108,184 @@ mod subscriptions_e2e_tests {
 
         drop(res);
     }
+
+    #[ntex::test]
+    async fn subscription_start_hook_rejects_subscription() {
+        #[derive(Default)]
+        struct RequireTokenPlugin;
+
+        #[async_trait]
+        impl RouterPlugin for RequireTokenPlugin {
+            type Config = ();
+
+            fn plugin_name() -> &'static str {
+                "require_token"
+            }
+
+            fn on_plugin_init(payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+                payload.initialize_plugin_with_defaults()
+            }
+
+            async fn on_subscription_start<'exec>(
+                &'exec self,
+                payload: OnSubscriptionStartHookPayload<'exec>,
+            ) -> OnSubscriptionStartHookResult {
+                if payload.router_http_request.headers.get("x-token").is_none() {
+                    return OnSubscriptionStartHookResult::EndWithResponse(
+                        PlanExecutionOutput::from_graphql_error_to_response(
+                            GraphQLError::from_message_and_code("Missing token", "UNAUTHENTICATED"),
+                            StatusCode::UNAUTHORIZED,
+                        ),
+                    );
+                }
+                payload.proceed()
+            }
+        }
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                subscriptions:
+                    enabled: true
+                plugins:
+                    require_token:
+                        enabled: true
+                "#,
+            )
+            .register_plugin::<RequireTokenPlugin>()
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                r#"
+                subscription {
+                    reviewAdded(intervalInMs: 0) {
+                        product {
+                            upc
+                        }
+                    }
+                }
+                "#,
+                None,
+                some_header_map! {
+                    http::header::ACCEPT => "text/event-stream"
+                },
+            )
+            .await;
+
+        let body = res.body().await.unwrap();
+        let body_str = std::str::from_utf8(&body).unwrap();
+
+        assert!(
+            body_str.contains(r#""code":"UNAUTHENTICATED""#),
+            "expected the subscription to be rejected, got: {body_str}"
+        );
+        assert!(
+            subgraphs.get_requests_log("reviews").is_none(),
+            "expected no subscription to the reviews subgraph"
+        );
+    }
+
+    #[ntex::test]
+    async fn subscription_event_and_end_hooks() {
+        static ENDED: Mutex<Vec<(usize, SubscriptionEndReason)>> = Mutex::new(Vec::new());
+
+        #[derive(Default)]
+        struct EventIndexPlugin;
+
+        #[async_trait]
+        impl RouterPlugin for EventIndexPlugin {
+            type Config = ();
+
+            fn plugin_name() -> &'static str {
+                "event_index"
+            }
+
+            fn on_plugin_init(payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+                payload.initialize_plugin_with_defaults()
+            }
+
+            fn on_subscription_event<'a>(
+                &'a self,
+                mut payload: OnSubscriptionEventHookPayload<'a>,
+            ) -> OnSubscriptionEventHookResult<'a> {
+                payload.body =
+                    format!(r#"{{"data":{{"index":{}}}}}"#, payload.event_index).into_bytes();
+                payload.proceed()
+            }
+
+            fn on_subscription_end<'a>(&'a self, payload: &OnSubscriptionEndHookPayload<'a>) {
+                ENDED
+                    .lock()
+                    .unwrap()
+                    .push((payload.event_count, payload.reason));
+            }
+        }
+
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                subscriptions:
+                    enabled: true
+                plugins:
+                    event_index:
+                        enabled: true
+                "#,
+            )
+            .register_plugin::<EventIndexPlugin>()
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request(
+                r#"
+                subscription {
+                    reviewAdded(intervalInMs: 0) {
+                        product {
+                            upc
+                        }
+                    }
+                }
+                "#,
+                None,
+                some_header_map! {
+                    http::header::ACCEPT => "text/event-stream"
+                },
+            )
+            .await;
+
+        let body = res.body().await.unwrap();
+        let body_str = std::str::from_utf8(&body).unwrap();
+
+        assert!(
+            body_str.contains(r#"data: {"data":{"index":0}}"#)
+                && body_str.contains(r#"data: {"data":{"index":10}}"#),
+            "expected the events to be replaced, got: {body_str}"
+        );
+
+        // the end hook runs once the stream is dropped
+        for _ in 0..50 {
+            if !ENDED.lock().unwrap().is_empty() {
+                break;
+            }
+            ntex::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(
+            ENDED.lock().unwrap().as_slice(),
+            &[(11, SubscriptionEndReason::Completed)]
+        );
+    }
 }
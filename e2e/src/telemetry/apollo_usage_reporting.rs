@@ -0,0 +1,144 @@
+use std::io::Read;
+use std::sync::Arc;
+use std::time::Duration;
+
+use flate2::read::GzDecoder;
+use hive_router::pipeline::apollo_usage_reporting::proto::{query_plan_node::Node, Report};
+use prost::Message;
+use tokio::sync::Mutex;
+
+use crate::testkit::{TestRouter, TestSubgraphs};
+
+struct ReceivedReport {
+    api_key: Option<String>,
+    report: Report,
+}
+
+/// A mock of the GraphOS usage reporting ingress, decoding the received reports.
+struct MockApolloIngress {
+    address: String,
+    reports: Arc<Mutex<Vec<ReceivedReport>>>,
+    _handle: std::thread::JoinHandle<()>,
+}
+
+impl MockApolloIngress {
+    fn start() -> Self {
+        let server =
+            tiny_http::Server::http("127.0.0.1:0").expect("Failed to start mock Apollo ingress");
+        let address = format!("http://{}", server.server_addr());
+        let reports: Arc<Mutex<Vec<ReceivedReport>>> = Arc::new(Mutex::new(Vec::new()));
+        let reports_clone = reports.clone();
+
+        let handle = std::thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let api_key = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("x-api-key"))
+                    .map(|header| header.value.to_string());
+                let mut body = Vec::new();
+                let _ = GzDecoder::new(request.as_reader()).read_to_end(&mut body);
+
+                if let Ok(report) = Report::decode(body.as_slice()) {
+                    reports_clone
+                        .blocking_lock()
+                        .push(ReceivedReport { api_key, report });
+                }
+
+                let _ = request.respond(tiny_http::Response::from_string("{}"));
+            }
+        });
+
+        MockApolloIngress {
+            address,
+            reports,
+            _handle: handle,
+        }
+    }
+
+    async fn wait_for_report(&self) -> ReceivedReport {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let Some(report) = self.reports.lock().await.pop() {
+                    return report;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("Timed out waiting for an Apollo usage report")
+    }
+}
+
+#[ntex::test]
+async fn apollo_usage_reporting_sends_traces_with_query_plan() {
+    let ingress = MockApolloIngress::start();
+    let subgraphs = TestSubgraphs::builder().build().start().await;
+
+    let router = TestRouter::builder()
+        .inline_config(format!(
+            r#"
+            supergraph:
+              source: file
+              path: supergraph.graphql
+
+            telemetry:
+              apollo:
+                enabled: true
+                key: service:my-graph:secret
+                graph_ref: my-graph@current
+                endpoint: {}
+                buffer_size: 1
+                flush_interval: 100ms
+            "#,
+            ingress.address
+        ))
+        .with_subgraphs(&subgraphs)
+        .build()
+        .start()
+        .await;
+
+    let res = router
+        .send_graphql_request("query Users { all: users { id } }", None, None)
+        .await;
+    assert!(res.status().is_success());
+
+    let received = ingress.wait_for_report().await;
+    assert_eq!(received.api_key.as_deref(), Some("service:my-graph:secret"));
+
+    let header = received.report.header.expect("report header");
+    assert_eq!(header.graph_ref, "my-graph@current");
+    assert_eq!(header.executable_schema_id.len(), 64);
+
+    let traces = received
+        .report
+        .traces_per_query
+        .get("# Users\nquery Users{users{id}}")
+        .expect("traces of the operation signature");
+    assert_eq!(traces.trace.len(), 1);
+    let trace = &traces.trace[0];
+    assert!(trace.duration_ns > 0);
+
+    let Some(Node::Fetch(fetch)) = trace
+        .query_plan
+        .as_ref()
+        .and_then(|plan| plan.node.as_ref())
+    else {
+        panic!("expected the query plan to be a single fetch");
+    };
+    assert_eq!(fetch.service_name, "accounts");
+    assert!(fetch.sent_time.is_some());
+    assert!(fetch.received_time.is_some());
+
+    // federated traces are requested from the subgraphs
+    let requests = subgraphs
+        .get_requests_log("accounts")
+        .expect("requests to the accounts subgraph");
+    assert_eq!(
+        requests[0]
+            .headers
+            .get("apollo-federation-include-trace")
+            .and_then(|value| value.to_str().ok()),
+        Some("ftv1")
+    );
+}
@@ -3,13 +3,18 @@ use std::time::Duration;
 
 use crate::testkit::{
     otel::{CollectedMetrics, OtlpCollector},
-    ClientResponseExt, TestRouter, TestSubgraphs,
+    some_header_map, ClientResponseExt, TestRouter, TestSubgraphs,
 };
 use hive_router::{
-    async_trait, plugins::hooks::on_graphql_error::OnGraphQLErrorHookPayload,
+    async_trait,
+    plugins::hooks::on_graphql_error::OnGraphQLErrorHookPayload,
     plugins::hooks::on_graphql_error::OnGraphQLErrorHookResult,
+    plugins::hooks::on_http_request::OnHttpRequestHookPayload,
+    plugins::hooks::on_http_request::OnHttpRequestHookResult,
     plugins::hooks::on_plugin_init::OnPluginInitPayload,
-    plugins::hooks::on_plugin_init::OnPluginInitResult, plugins::plugin_trait::RouterPlugin,
+    plugins::hooks::on_plugin_init::OnPluginInitResult,
+    plugins::plugin_metrics::{KeyValue, PluginCounter},
+    plugins::plugin_trait::RouterPlugin,
 };
 use hive_router_internal::telemetry::metrics::catalog::{labels, labels_for, names, values};
 use tempfile::NamedTempFile;
@@ -135,6 +140,29 @@ async fn test_otlp_http_metrics_export_with_graphql_request() {
     assert_histogram_count(&metrics, names::VALIDATE_CACHE_DURATION, &no_attrs, 2);
     assert_histogram_count(&metrics, names::NORMALIZE_CACHE_DURATION, &no_attrs, 2);
     assert_histogram_count(&metrics, names::PLAN_CACHE_DURATION, &no_attrs, 2);
+
+    // planning only happens on plan cache misses
+    let planning_attrs = [(labels::STATUS, values::PlanningStatus::Ok.as_str())];
+    assert_histogram_count(
+        &metrics,
+        names::GRAPHQL_PLANNING_DURATION,
+        &planning_attrs,
+        1,
+    );
+
+    let execution_attrs = [
+        (labels::GRAPHQL_OPERATION_TYPE, "query"),
+        (
+            labels::GRAPHQL_RESPONSE_STATUS,
+            values::GraphQLResponseStatus::Ok.as_str(),
+        ),
+    ];
+    assert_histogram_count(
+        &metrics,
+        names::GRAPHQL_EXECUTION_DURATION,
+        &execution_attrs,
+        2,
+    );
 }
 
 #[ntex::test]
@@ -522,6 +550,8 @@ async fn test_otlp_all_metrics_path_attribute_names() {
         (names::PLAN_CACHE_REQUESTS_TOTAL, &[][..]),
         (names::PLAN_CACHE_DURATION, &[][..]),
         (names::PLAN_CACHE_SIZE, &[][..]),
+        (names::GRAPHQL_PLANNING_DURATION, &[][..]),
+        (names::GRAPHQL_EXECUTION_DURATION, &[][..]),
         (names::PERSISTED_DOCUMENTS_STORAGE_FAILURES_TOTAL, &[][..]),
         (names::PERSISTED_DOCUMENTS_EXTRACT_MISSING_ID_TOTAL, &[][..]),
     ] {
@@ -834,6 +864,82 @@ async fn test_otlp_metric_attribute_true_override_is_noop() {
     );
 }
 
+/// Ensures the attributes of `telemetry.instrumentation` are recorded on the execution duration,
+/// with the values outside of the allow list replaced by `other_value`.
+#[ntex::test]
+async fn test_otlp_custom_attributes_on_execution_duration() {
+    let supergraph_path =
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("supergraph.graphql");
+
+    let otlp_collector = OtlpCollector::start()
+        .await
+        .expect("Failed to start OTLP collector");
+    let otlp_endpoint = otlp_collector.http_metrics_endpoint();
+
+    let subgraphs = TestSubgraphs::builder().build().start().await;
+
+    let router = TestRouter::builder()
+        .inline_config(format!(
+            r#"
+          supergraph:
+            source: file
+            path: {}
+
+          telemetry:
+            instrumentation:
+              attributes:
+                tenant.id:
+                  expression: .request.headers."x-tenant-id"
+                  metrics:
+                    enabled: true
+                    allow: [acme]
+            metrics:
+              exporters:
+                - kind: otlp
+                  endpoint: {}
+                  protocol: http
+                  interval: 30ms
+                  max_export_timeout: 2s
+      "#,
+            supergraph_path.to_str().unwrap(),
+            otlp_endpoint
+        ))
+        .with_subgraphs(&subgraphs)
+        .build()
+        .start()
+        .await;
+
+    for tenant in ["acme", "globex", "initech"] {
+        router
+            .send_graphql_request(
+                "{ users { id } }",
+                None,
+                some_header_map!(
+                    http::header::HeaderName::from_static("x-tenant-id") => tenant
+                ),
+            )
+            .await;
+    }
+
+    wait_for_metrics_export().await;
+
+    let metrics = otlp_collector.metrics_view().await;
+
+    assert_histogram_count(
+        &metrics,
+        names::GRAPHQL_EXECUTION_DURATION,
+        &[("tenant.id", "acme")],
+        1,
+    );
+    assert_histogram_count(
+        &metrics,
+        names::GRAPHQL_EXECUTION_DURATION,
+        &[("tenant.id", "other")],
+        2,
+    );
+    assert_metric_has_attrs(&metrics, names::GRAPHQL_EXECUTION_DURATION, &[]);
+}
+
 /// Ensures parse failures increment GraphQL error counters and set server GraphQL error status.
 ///
 /// This test focuses on GraphQL-layer semantics (`graphql.response.status`) rather than HTTP error
@@ -1148,3 +1254,91 @@ async fn test_otlp_http_client_transport_failure_sets_graphql_status_and_error_t
         labels::HTTP_RESPONSE_STATUS_CODE
     );
 }
+
+/// Ensures the metrics created by plugins are exported with the metrics of the router.
+#[ntex::test]
+async fn test_otlp_plugin_metrics_are_exported() {
+    struct TestCountingPlugin {
+        requests: PluginCounter,
+    }
+
+    #[async_trait]
+    impl RouterPlugin for TestCountingPlugin {
+        type Config = ();
+
+        fn plugin_name() -> &'static str {
+            "test_counting"
+        }
+
+        fn on_plugin_init(payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+            let requests = payload
+                .metrics()
+                .counter("test_counting.requests", "Requests seen by the plugin");
+            payload.initialize_plugin(Self { requests })
+        }
+
+        fn on_http_request<'req>(
+            &'req self,
+            payload: OnHttpRequestHookPayload<'req>,
+        ) -> OnHttpRequestHookResult<'req> {
+            let method = payload.router_http_request.method().to_string();
+            self.requests.add(1, &[KeyValue::new("method", method)]);
+            payload.proceed()
+        }
+    }
+
+    let supergraph_path =
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("supergraph.graphql");
+
+    let otlp_collector = OtlpCollector::start()
+        .await
+        .expect("Failed to start OTLP collector");
+    let otlp_endpoint = otlp_collector.http_metrics_endpoint();
+
+    let subgraphs = TestSubgraphs::builder().build().start().await;
+
+    let router = TestRouter::builder()
+        .inline_config(format!(
+            r#"
+          supergraph:
+            source: file
+            path: {}
+
+          telemetry:
+            metrics:
+              exporters:
+                - kind: otlp
+                  endpoint: {}
+                  protocol: http
+                  interval: 30ms
+                  max_export_timeout: 2s
+
+          plugins:
+            test_counting:
+              enabled: true
+      "#,
+            supergraph_path.to_str().unwrap(),
+            otlp_endpoint
+        ))
+        .with_subgraphs(&subgraphs)
+        .register_plugin::<TestCountingPlugin>()
+        .build()
+        .start()
+        .await;
+
+    for _ in 0..2 {
+        router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+    }
+
+    wait_for_metrics_export().await;
+
+    let metrics = otlp_collector.metrics_view().await;
+    assert_counter_eq(
+        &metrics,
+        "test_counting.requests",
+        &[("method", "POST")],
+        2.0,
+    );
+}
@@ -1,3 +1,4 @@
+mod apollo_usage_reporting;
 mod metrics;
 mod subscription_metrics;
 mod tracing;
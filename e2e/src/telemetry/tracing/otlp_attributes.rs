@@ -1,3 +1,14 @@
+use hive_router::{
+    async_trait,
+    plugins::{
+        hooks::{
+            on_http_request::{OnHttpRequestHookPayload, OnHttpRequestHookResult},
+            on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
+        },
+        plugin_trait::RouterPlugin,
+    },
+};
+
 use crate::testkit::{otel::OtlpCollector, some_header_map, TestRouter, TestSubgraphs};
 
 /// Verify only deprecated attributes are emitted for deprecated mode
@@ -576,3 +587,94 @@ async fn test_custom_resource_attributes() {
         "Expected 'custom.foo' resource attribute to be 'bar'"
     );
 }
+
+/// Verify the attributes set by plugins are recorded on the http.server span
+#[ntex::test]
+async fn test_plugin_span_attributes() {
+    #[derive(Default)]
+    struct TenantPlugin;
+
+    #[async_trait]
+    impl RouterPlugin for TenantPlugin {
+        type Config = ();
+
+        fn plugin_name() -> &'static str {
+            "tenant"
+        }
+
+        fn on_plugin_init(payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+            payload.initialize_plugin_with_defaults()
+        }
+
+        fn on_http_request<'req>(
+            &'req self,
+            payload: OnHttpRequestHookPayload<'req>,
+        ) -> OnHttpRequestHookResult<'req> {
+            payload.context.set_span_attribute("tenant.id", "acme");
+            payload.context.set_span_attribute("tenant.premium", true);
+            payload.proceed()
+        }
+    }
+
+    let supergraph_path =
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("supergraph.graphql");
+    let supergraph_path = supergraph_path.to_str().unwrap();
+
+    let otlp_collector = OtlpCollector::start()
+        .await
+        .expect("Failed to start OTLP collector");
+    let otlp_endpoint = otlp_collector.grpc_endpoint();
+
+    let subgraphs = TestSubgraphs::builder().build().start().await;
+
+    let router = TestRouter::builder()
+        .inline_config(format!(
+            r#"
+          supergraph:
+            source: file
+            path: {supergraph_path}
+
+          telemetry:
+            tracing:
+              exporters:
+                - kind: otlp
+                  endpoint: {otlp_endpoint}
+                  protocol: grpc
+                  batch_processor:
+                    scheduled_delay: 50ms
+                    max_export_timeout: 2s
+
+          plugins:
+            tenant:
+              enabled: true
+      "#,
+        ))
+        .with_subgraphs(&subgraphs)
+        .register_plugin::<TenantPlugin>()
+        .build()
+        .start()
+        .await;
+
+    let res = router
+        .send_graphql_request("{ users { id } }", None, None)
+        .await;
+    assert!(res.status().is_success());
+
+    let http_server_span = otlp_collector
+        .wait_for_span_by_hive_kind_one("http.server")
+        .await;
+    assert_eq!(
+        http_server_span
+            .attributes
+            .get("tenant.id")
+            .map(String::as_str),
+        Some("acme")
+    );
+    assert_eq!(
+        http_server_span
+            .attributes
+            .get("tenant.premium")
+            .map(String::as_str),
+        Some("true")
+    );
+}
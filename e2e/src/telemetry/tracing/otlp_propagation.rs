@@ -438,3 +438,189 @@ async fn test_otlp_http_jaeger_propagation() {
     );
     assert_eq!(downstream_flags, "1");
 }
+
+#[ntex::test]
+async fn test_otlp_http_datadog_propagation() {
+    let supergraph_path =
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("supergraph.graphql");
+    let supergraph_path = supergraph_path.to_str().unwrap();
+
+    let otlp_collector = OtlpCollector::start()
+        .await
+        .expect("Failed to start OTLP collector");
+    let otlp_endpoint = otlp_collector.http_traces_endpoint();
+
+    let subgraphs = TestSubgraphs::builder().build().start().await;
+
+    let router = TestRouter::builder()
+        .inline_config(format!(
+            r#"
+          supergraph:
+            source: file
+            path: {supergraph_path}
+
+          telemetry:
+            tracing:
+              propagation:
+                datadog: true
+              exporters:
+                - kind: otlp
+                  endpoint: {otlp_endpoint}
+                  protocol: http
+                  batch_processor:
+                    scheduled_delay: 50ms
+                    max_export_timeout: 2s
+      "#,
+        ))
+        .with_subgraphs(&subgraphs)
+        .build()
+        .start()
+        .await;
+
+    // Datadog IDs are 64-bit decimal numbers
+    let upstream_trace_id: u64 = 6597393441853587715;
+    let upstream_span_id: u64 = 2315425134870293371;
+
+    let res = router
+        .send_graphql_request(
+            "{ users { id } }",
+            None,
+            some_header_map!(
+                "x-datadog-trace-id" => upstream_trace_id.to_string(),
+                "x-datadog-parent-id" => upstream_span_id.to_string(),
+                "x-datadog-sampling-priority" => "2",
+            ),
+        )
+        .await;
+
+    assert!(res.status().is_success());
+
+    let http_server_span = otlp_collector
+        .wait_for_span_by_hive_kind_one("http.server")
+        .await;
+    let http_client_span = otlp_collector
+        .wait_for_span_by_hive_kind_one("http.client")
+        .await;
+
+    assert_eq!(
+        http_server_span.parent_span_id,
+        format!("{upstream_span_id:016x}"),
+        "http.server span should have the upstream span as parent"
+    );
+    assert_eq!(
+        http_server_span.trace_id,
+        format!("{upstream_trace_id:032x}"),
+        "http.server span should continue the upstream trace"
+    );
+
+    let account_requests = subgraphs
+        .get_requests_log("accounts")
+        .expect("Expected at least one request to account subgraph");
+    let first_account_request = &account_requests[0];
+    let header = |name: &str| {
+        first_account_request
+            .headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_else(|| panic!("Subgraph request should have the {name} header"))
+            .to_string()
+    };
+
+    assert_eq!(header("x-datadog-trace-id"), upstream_trace_id.to_string());
+    // The subgraph receives the span id of the http.client span, in decimal
+    assert_eq!(
+        header("x-datadog-parent-id"),
+        u64::from_str_radix(&http_client_span.id, 16)
+            .unwrap()
+            .to_string()
+    );
+    // The priority of the upstream is kept
+    assert_eq!(header("x-datadog-sampling-priority"), "2");
+}
+
+#[ntex::test]
+async fn test_otlp_http_untrusted_incoming_trace_context() {
+    let supergraph_path =
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("supergraph.graphql");
+    let supergraph_path = supergraph_path.to_str().unwrap();
+
+    let otlp_collector = OtlpCollector::start()
+        .await
+        .expect("Failed to start OTLP collector");
+    let otlp_endpoint = otlp_collector.http_traces_endpoint();
+
+    let subgraphs = TestSubgraphs::builder().build().start().await;
+
+    let router = TestRouter::builder()
+        .inline_config(format!(
+            r#"
+          supergraph:
+            source: file
+            path: {supergraph_path}
+
+          telemetry:
+            tracing:
+              propagation:
+                trace_context: true
+                trust_incoming: false
+              exporters:
+                - kind: otlp
+                  endpoint: {otlp_endpoint}
+                  protocol: http
+                  batch_processor:
+                    scheduled_delay: 50ms
+                    max_export_timeout: 2s
+      "#,
+        ))
+        .with_subgraphs(&subgraphs)
+        .build()
+        .start()
+        .await;
+
+    let upstream_trace_id = TraceParent::random_trace_id();
+    let upstream_span_id = TraceParent::random_span_id();
+    let upstream_traceparent = TraceParent {
+        trace_id: &upstream_trace_id,
+        span_id: &upstream_span_id,
+        sampled: true,
+    };
+
+    let res = router
+        .send_graphql_request(
+            "{ users { id } }",
+            None,
+            some_header_map!("traceparent" => upstream_traceparent.to_string()),
+        )
+        .await;
+
+    assert!(res.status().is_success());
+
+    let http_server_span = otlp_collector
+        .wait_for_span_by_hive_kind_one("http.server")
+        .await;
+
+    // The incoming trace context is ignored, the router starts a new trace
+    assert_ne!(
+        http_server_span.trace_id, upstream_trace_id,
+        "http.server span should not continue the incoming trace"
+    );
+    assert_ne!(
+        http_server_span.parent_span_id, upstream_span_id,
+        "http.server span should not have the incoming span as parent"
+    );
+
+    // The router's own trace context is still propagated to subgraphs
+    let account_requests = subgraphs
+        .get_requests_log("accounts")
+        .expect("Expected at least one request to account subgraph");
+    let subgraph_traceparent = account_requests[0]
+        .headers
+        .get("traceparent")
+        .and_then(|v| v.to_str().ok())
+        .expect("Subgraph request should have traceparent header");
+    let downstream_traceparent = TraceParent::parse(subgraph_traceparent);
+    assert_eq!(
+        downstream_traceparent.trace_id, http_server_span.trace_id,
+        "Expected the router's trace_id to be propagated"
+    );
+}
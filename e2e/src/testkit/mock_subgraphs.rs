@@ -12,6 +12,8 @@
 //! * Handles federation's `_entities` resolver by matching each
 //!   `representations[i]` against the configured `entities: [..]` list using
 //!   the rule "entity has at least all of representation's fields".
+//!   A representation of an entity interface matches the entities of the object
+//!   types listed for that interface in `interfaces: {..}`.
 //!
 //! This is intentionally a small, schema-less executor: it knows nothing
 //! about the subgraph schema, just walks the query AST against pre-baked
@@ -38,7 +40,8 @@ use super::{RequestLike, ResponseLike};
 ///   "subgraphName": {
 ///     "query": { "rootField": ... },
 ///     "mutation": { "rootField": ... },
-///     "entities": [ { "__typename": "X", "id": "1", ... }, ... ]
+///     "entities": [ { "__typename": "X", "id": "1", ... }, ... ],
+///     "interfaces": { "I": ["X", ...] }
 ///   }
 /// }
 /// ```
@@ -77,6 +80,8 @@ struct SubgraphMock {
     query: JsonValue,
     mutation: Option<JsonValue>,
     entities: Vec<JsonValue>,
+    /// Object types implementing an entity interface, by interface name.
+    interfaces: BTreeMap<String, Vec<String>>,
 }
 
 impl SubgraphMock {
@@ -89,6 +94,10 @@ impl SubgraphMock {
                 .and_then(|v| v.as_array())
                 .cloned()
                 .unwrap_or_default(),
+            interfaces: value
+                .get("interfaces")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default(),
         }
     }
 
@@ -284,11 +293,24 @@ impl SubgraphMock {
                 Some(o) => o,
                 None => return false,
             };
-            repr_obj
-                .iter()
-                .all(|(k, v)| entity_obj.get(k).is_some_and(|ev| is_a_match(ev, v)))
+            repr_obj.iter().all(|(k, v)| {
+                entity_obj.get(k).is_some_and(|ev| {
+                    is_a_match(ev, v) || (k == "__typename" && self.implements(ev, v))
+                })
+            })
         })
     }
+
+    /// Whether the entity's type implements the entity interface of the representation.
+    fn implements(&self, entity_typename: &JsonValue, repr_typename: &JsonValue) -> bool {
+        match (entity_typename.as_str(), repr_typename.as_str()) {
+            (Some(object_type), Some(interface)) => self
+                .interfaces
+                .get(interface)
+                .is_some_and(|object_types| object_types.iter().any(|t| t == object_type)),
+            _ => false,
+        }
+    }
 }
 
 /// "Entity contains at least every field of the representation" check.
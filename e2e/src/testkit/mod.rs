@@ -40,8 +40,8 @@ use hive_router::{
     add_callback_handler, background_tasks::BackgroundTasksManager, configure_app_from_config,
     configure_ntex_app, init_rustls_crypto_provider, invoke_shutdown_hooks,
     pipeline::long_lived_client_limit::LongLivedClientLimitService,
-    plugins::plugins_service::PluginService, telemetry::Telemetry, PluginRegistry, RouterPaths,
-    RouterSharedState, SchemaState,
+    plugins::plugins_service::PluginService, start_dedicated_admin_servers, telemetry::Telemetry,
+    PluginRegistry, RouterPaths, RouterSharedState, SchemaState,
 };
 use hive_router_config::{
     load_config, parse_yaml_config, subscriptions::CallbackConfig, HiveRouterConfig,
@@ -856,10 +856,32 @@ impl TestRouter<Built> {
             serv_graphql_path,
             serv_websocket_path,
             serv_callback_path.clone(),
-        );
+            shared_state
+                .router_config
+                .admin_supergraph_path()
+                .map(str::to_string),
+            shared_state
+                .router_config
+                .admin_diagnostics_path()
+                .map(str::to_string),
+            shared_state
+                .router_config
+                .admin_cache_path()
+                .map(str::to_string),
+            shared_state.router_config.mcp_path().map(str::to_string),
+        )
+        .with_plugin_routes(shared_state.plugin_routes.clone());
         paths
             .detect_conflicts(&prometheus)
             .expect("failed to detect endpoint conflicts");
+        let paths = start_dedicated_admin_servers(
+            &shared_state.router_config,
+            paths,
+            &shared_state,
+            &schema_state,
+            &mut bg_tasks_manager,
+        )
+        .expect("failed to start the dedicated admin servers");
 
         let serv_listener = self.listener.unwrap_or(
             std::net::TcpListener::bind(format!("127.0.0.1:{}", self.port))
@@ -890,8 +912,9 @@ impl TestRouter<Built> {
             .tls
             .as_ref()
         {
-            let rustls_config = hive_router::tls::build_rustls_config(tls_config)
-                .expect("failed to build rustls config for test router");
+            let rustls_config =
+                hive_router::tls::build_server_rustls_config(tls_config, &mut bg_tasks_manager)
+                    .expect("failed to build rustls config for test router");
             serv_config = serv_config.rustls(rustls_config);
         }
 
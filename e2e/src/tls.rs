@@ -117,6 +117,91 @@ mod tls_tests {
             , @r#"{"data":{"me":{"name":"Uri Goldshtein"}}}"#);
     }
 
+    /// Setup TLS on the router, with `watch` enabled
+    /// Replace the certificate and its key on disk
+    /// Verify that new connections are served the new certificate, without restarting the router
+    #[ntex::test]
+    async fn reloads_the_router_certificate_when_its_files_change() {
+        init_rustls_crypto_provider();
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let generated_key_pair = generate_keypair().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(format!(
+                r#"
+            supergraph:
+                source: file
+                path: supergraph.graphql
+            traffic_shaping:
+                router:
+                    tls:
+                        key_file: "{}"
+                        cert_file: "{}"
+                        watch: true
+                "#,
+                generated_key_pair.key_file_path, generated_key_pair.cert_file_path
+            ))
+            .build()
+            .start_without_healthcheck()
+            .await;
+        let graphql_endpoint = router.serv().url(router.graphql_path());
+        let send_request = |cert_pem: String| {
+            let graphql_endpoint = graphql_endpoint.clone();
+            async move {
+                reqwest::Client::builder()
+                    .add_root_certificate(
+                        reqwest::Certificate::from_pem(cert_pem.as_bytes())
+                            .expect("Failed to create certificate from PEM"),
+                    )
+                    .use_rustls_tls()
+                    .build()
+                    .expect("Failed to build reqwest client with custom TLS configuration")
+                    .post(graphql_endpoint)
+                    .json(&json!({
+                        "query": "{ me { name } }"
+                    }))
+                    .send()
+                    .await
+            }
+        };
+
+        send_request(generated_key_pair.cert_pem.clone())
+            .await
+            .expect("Failed to send request to router with TLS");
+
+        let renewed_key_pair = generate_keypair().await;
+        std::fs::write(
+            &generated_key_pair.key_file_path,
+            renewed_key_pair.key_pem.as_bytes(),
+        )
+        .expect("Failed to write the renewed private key");
+        std::fs::write(
+            &generated_key_pair.cert_file_path,
+            renewed_key_pair.cert_pem.as_bytes(),
+        )
+        .expect("Failed to write the renewed certificate");
+
+        let mut resp = None;
+        for _ in 0..50 {
+            if let Ok(ok) = send_request(renewed_key_pair.cert_pem.clone()).await {
+                resp = Some(ok);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        let resp = resp.expect("the router was expected to serve the renewed certificate");
+        insta::assert_snapshot!(
+            resp.text().await.expect("Failed to parse text response from router with TLS")
+            , @r#"{"data":{"me":{"name":"Uri Goldshtein"}}}"#);
+
+        assert!(
+            send_request(generated_key_pair.cert_pem.clone())
+                .await
+                .is_err(),
+            "the previous certificate was expected to be replaced"
+        );
+    }
+
     /// Setup TLS on a subgraph
     /// Configure the router to trust the subgraph's certificate authority
     /// Send a request to the router that requires communication with the TLS-enabled subgraph and verify that the request succeeds,
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod unix_socket_e2e_tests {
+    use std::path::PathBuf;
+
+    use tempfile::Builder;
+    use tokio::net::{TcpStream, UnixListener};
+
+    use crate::testkit::{ClientResponseExt, TestRouter, TestSubgraphs};
+
+    /// Forwards the connections accepted on the Unix domain socket to the TCP address of the subgraphs.
+    fn spawn_unix_socket_proxy(socket_path: PathBuf, tcp_addr: String) {
+        let listener = UnixListener::bind(&socket_path).expect("failed to bind unix socket");
+
+        tokio::spawn(async move {
+            while let Ok((mut unix_stream, _)) = listener.accept().await {
+                let tcp_addr = tcp_addr.clone();
+                tokio::spawn(async move {
+                    let mut tcp_stream = TcpStream::connect(tcp_addr)
+                        .await
+                        .expect("failed to connect to subgraphs");
+                    let _ = tokio::io::copy_bidirectional(&mut unix_stream, &mut tcp_stream).await;
+                });
+            }
+        });
+    }
+
+    #[ntex::test]
+    async fn should_reach_subgraph_over_unix_domain_socket() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+
+        let socket_dir = Builder::new()
+            .prefix("hive-subgraph-")
+            .tempdir_in("/tmp")
+            .expect("failed to create temporary socket directory");
+        let socket_path = socket_dir.path().join("accounts.sock");
+        let tcp_addr = subgraphs.url().trim_start_matches("http://").to_string();
+        spawn_unix_socket_proxy(socket_path.clone(), tcp_addr);
+
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(format!(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                override_subgraph_urls:
+                  subgraphs:
+                    accounts:
+                      url: "unix://{}?path=/accounts"
+                "#,
+                socket_path.display()
+            ))
+            .build()
+            .start()
+            .await;
+
+        let res = router
+            .send_graphql_request("{ users { id } }", None, None)
+            .await;
+
+        assert!(res.status().is_success());
+        insta::assert_snapshot!(res.json_body_string_pretty().await, @r#"
+        {
+          "data": {
+            "users": [
+              {
+                "id": "1"
+              },
+              {
+                "id": "2"
+              },
+              {
+                "id": "3"
+              },
+              {
+                "id": "4"
+              },
+              {
+                "id": "5"
+              },
+              {
+                "id": "6"
+              }
+            ]
+          }
+        }
+        "#);
+
+        assert_eq!(
+            subgraphs
+                .get_requests_log("accounts")
+                .expect("expected requests sent to accounts subgraph")
+                .len(),
+            1,
+        );
+    }
+}
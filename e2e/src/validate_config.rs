@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod validate_config_e2e_tests {
+    use std::io::Write;
+
+    use hive_router::{error::RouterInitError, validate_router_config, PluginRegistry};
+    use hive_router_config::{parse_yaml_config, HiveRouterConfig};
+    use tempfile::NamedTempFile;
+
+    fn config(config_yaml: &str) -> HiveRouterConfig {
+        parse_yaml_config(config_yaml.to_string()).expect("failed to parse the config")
+    }
+
+    #[ntex::test]
+    async fn accepts_a_valid_config() {
+        let config = config(
+            r#"
+            supergraph:
+                source: file
+                path: supergraph.graphql
+            headers:
+                all:
+                    request:
+                        - insert:
+                            name: x-tenant
+                            expression: .request.headers."x-tenant"
+            "#,
+        );
+
+        validate_router_config(config, PluginRegistry::new(), true)
+            .await
+            .expect("expected the config to be valid");
+    }
+
+    #[ntex::test]
+    async fn rejects_invalid_expressions() {
+        let config = config(
+            r#"
+            supergraph:
+                source: file
+                path: supergraph.graphql
+            headers:
+                all:
+                    request:
+                        - insert:
+                            name: x-tenant
+                            expression: .request.headers.(
+            "#,
+        );
+
+        let err = validate_router_config(config, PluginRegistry::new(), false)
+            .await
+            .expect_err("expected the config to be invalid");
+        assert!(matches!(err, RouterInitError::SharedStateError(_)), "{err}");
+    }
+
+    #[ntex::test]
+    async fn checks_the_supergraph_only_when_asked_to() {
+        let mut supergraph = NamedTempFile::new().expect("failed to create a temp file");
+        supergraph
+            .write_all(b"type Query {")
+            .expect("failed to write the supergraph");
+        let config_yaml = format!(
+            r#"
+            supergraph:
+                source: file
+                path: {}
+            "#,
+            supergraph.path().display()
+        );
+
+        validate_router_config(config(&config_yaml), PluginRegistry::new(), false)
+            .await
+            .expect("expected the config to be valid");
+
+        let err = validate_router_config(config(&config_yaml), PluginRegistry::new(), true)
+            .await
+            .expect_err("expected the supergraph to be unavailable");
+        assert!(
+            matches!(err, RouterInitError::SupergraphUnavailable(_)),
+            "{err}"
+        );
+    }
+}
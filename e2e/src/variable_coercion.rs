@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod variable_coercion_e2e_tests {
+    use http::StatusCode;
+    use sonic_rs::{json, JsonValueTrait};
+
+    use crate::testkit::{ClientResponseExt, Started, TestRouter, TestSubgraphs};
+
+    async fn build_router() -> (TestSubgraphs<Started>, TestRouter<Started>) {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                  source: file
+                  path: supergraph.graphql
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+        (subgraphs, router)
+    }
+
+    async fn assert_rejected(query: &str, variables: sonic_rs::Value, expected_message: &str) {
+        let (subgraphs, router) = build_router().await;
+
+        let res = router
+            .send_graphql_request(query, Some(variables), None)
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let body = res.json_body().await;
+        assert_eq!(
+            body["errors"][0]["message"].as_str(),
+            Some(expected_message)
+        );
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"].as_str(),
+            Some("BAD_USER_INPUT")
+        );
+        assert!(
+            subgraphs.get_requests_log("products").is_none(),
+            "invalid variables should not reach the subgraphs"
+        );
+    }
+
+    #[ntex::test]
+    async fn rejects_variables_of_invalid_types() {
+        assert_rejected(
+            "query ($first: Int) { topProducts(first: $first) { upc } }",
+            json!({ "first": "5" }),
+            r#"Variable "$first" got invalid value "5"; Int cannot represent non-integer value: "5""#,
+        )
+        .await;
+    }
+
+    #[ntex::test]
+    async fn rejects_missing_required_variables() {
+        assert_rejected(
+            "query ($first: Int!) { topProducts(first: $first) { upc } }",
+            json!({}),
+            r#"Variable "$first" of required type "Int!" was not provided."#,
+        )
+        .await;
+    }
+
+    #[ntex::test]
+    async fn rejects_invalid_input_object_fields() {
+        assert_rejected(
+            "mutation ($input: OneOfTestInput!) { oneofTest(input: $input) { string } }",
+            json!({ "input": { "string": "a", "color": "red" } }),
+            r#"Variable "$input" got invalid value {"string":"a","color":"red"}; Field "color" is not defined by type "OneOfTestInput"."#,
+        )
+        .await;
+    }
+
+    #[ntex::test]
+    async fn uses_default_values_of_omitted_variables() {
+        let (_subgraphs, router) = build_router().await;
+
+        let res = router
+            .send_graphql_request(
+                "query ($first: Int = 1) { topProducts(first: $first) { upc } }",
+                Some(json!({})),
+                None,
+            )
+            .await;
+        assert!(res.status().is_success());
+
+        let body = res.json_body().await;
+        assert!(body["errors"].is_null(), "unexpected errors: {}", body);
+        assert_eq!(body["data"]["topProducts"][0]["upc"].as_str(), Some("1"));
+    }
+}
@@ -216,6 +216,64 @@ mod websocket_e2e_tests {
         );
     }
 
+    #[ntex::test]
+    async fn rejects_subscriptions_over_the_connection_limit() {
+        let subgraphs = TestSubgraphs::builder().build().start().await;
+        let router = TestRouter::builder()
+            .with_subgraphs(&subgraphs)
+            .inline_config(
+                r#"
+                supergraph:
+                    source: file
+                    path: supergraph.graphql
+                websocket:
+                    enabled: true
+                    max_subscriptions_per_connection: 1
+                subscriptions:
+                    enabled: true
+                "#,
+            )
+            .build()
+            .start()
+            .await;
+
+        let wsconn = router.ws().await;
+
+        let mut client = WsClient::init(wsconn, None)
+            .await
+            .expect("Failed to init WsClient");
+
+        let subscribe_payload = || SubscribePayload {
+            query: r#"
+                subscription {
+                    reviewAdded(intervalInMs: 200) {
+                        id
+                    }
+                }
+                "#
+            .into(),
+            ..Default::default()
+        };
+
+        let mut stream1 = client.subscribe(subscribe_payload(), None).await;
+        let response = stream1.next().await.expect("Expected a response");
+        assert!(response.errors.is_none(), "Expected no errors in stream1");
+
+        let mut stream2 = client.subscribe(subscribe_payload(), None).await;
+        let response = stream2.next().await.expect("Expected a response");
+        let errors = response
+            .errors
+            .expect("Expected the second subscription to be rejected");
+        assert_eq!(
+            errors[0].extensions.code.as_deref(),
+            Some("TOO_MANY_SUBSCRIPTIONS")
+        );
+
+        // the connection stays open for the first subscription
+        let response = stream1.next().await.expect("Expected a response");
+        assert!(response.errors.is_none(), "Expected no errors in stream1");
+    }
+
     #[ntex::test]
     async fn header_propagation_from_connection_init_payload() {
         let subgraphs = TestSubgraphs::builder().build().start().await;
@@ -0,0 +1,99 @@
+use criterion::{criterion_group, criterion_main};
+use criterion::{BenchmarkId, Criterion};
+use hive_router_plan_executor::response::merge::{deep_merge, deep_merge_batch};
+use hive_router_plan_executor::response::value::Value;
+use std::hint::black_box;
+
+const BATCH_SIZES: [usize; 4] = [100, 1_000, 10_000, 50_000];
+
+/// Products as resolved by the `products` subgraph of the benchmark,
+/// and the entities of the `reviews` subgraph merged into them.
+fn product_entities(count: usize) -> (Vec<sonic_rs::Value>, Vec<sonic_rs::Value>) {
+    let products = (0..count)
+        .map(|index| {
+            sonic_rs::json!({
+                "__typename": "Product",
+                "name": format!("Product {index}"),
+                "price": 899,
+                "upc": index.to_string(),
+                "weight": 100,
+            })
+        })
+        .collect();
+    let reviews = (0..count)
+        .map(|index| {
+            sonic_rs::json!({
+                "reviews": [
+                    {
+                        "author": { "__typename": "User", "id": "1" },
+                        "body": "Lorem ipsum dolor sit amet, consectetur adipiscing elit.",
+                        "id": format!("{index}-1"),
+                    },
+                    {
+                        "author": { "__typename": "User", "id": "2" },
+                        "body": "Sed ut perspiciatis unde omnis iste natus error sit voluptatem.",
+                        "id": format!("{index}-2"),
+                    },
+                ],
+            })
+        })
+        .collect();
+
+    (products, reviews)
+}
+
+fn to_values(values: &[sonic_rs::Value]) -> Vec<Value<'_>> {
+    values
+        .iter()
+        .map(|value| Value::from(value.as_ref()))
+        .collect()
+}
+
+fn merge_entities_test(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merge_entities");
+
+    for batch_size in BATCH_SIZES {
+        let (products, reviews) = product_entities(batch_size);
+
+        group.bench_with_input(
+            BenchmarkId::new("serial", batch_size),
+            &batch_size,
+            |b, _| {
+                b.iter_batched(
+                    || (to_values(&products), to_values(&reviews)),
+                    |(mut targets, sources)| {
+                        for (target, source) in targets.iter_mut().zip(sources) {
+                            deep_merge(target, source);
+                        }
+                        black_box(targets);
+                    },
+                    criterion::BatchSize::LargeInput,
+                );
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("batch", batch_size),
+            &batch_size,
+            |b, _| {
+                b.iter_batched(
+                    || (to_values(&products), to_values(&reviews)),
+                    |(mut targets, sources)| {
+                        deep_merge_batch(targets.iter_mut().zip(sources).collect());
+                        black_box(targets);
+                    },
+                    criterion::BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn all_benchmarks(c: &mut Criterion) {
+    merge_entities_test(c);
+}
+
+criterion_group!(benches, all_benchmarks);
+criterion_main!(benches);
@@ -8,4 +8,4 @@ pub mod stages;
 // TODO: Allow adding jwt claims via coprocessor.
 
 pub use error::CoprocessorError;
-pub use runtime::CoprocessorRuntime;
+pub use runtime::{CoprocessorRuntime, CoprocessorSubgraphRuntime};
@@ -19,7 +19,12 @@ use crate::coprocessor::stages::graphql::{
 use crate::coprocessor::stages::router::{
     RouterRequestInput, RouterRequestStage, RouterResponseInput, RouterResponseStage,
 };
+use crate::coprocessor::stages::subgraph::{
+    into_subgraph_http_response, SubgraphRequestInput, SubgraphRequestStage, SubgraphResponseInput,
+    SubgraphResponseStage,
+};
 use crate::execution::plan::FailedExecutionResult;
+use crate::executors::http::SubgraphHttpResponse;
 use crate::plugins::hooks::on_graphql_params::GraphQLParams;
 use crate::request_context::{
     RequestContextError, RequestContextExt, RequestContextPatch, SharedRequestContext,
@@ -32,7 +37,13 @@ pub struct CoprocessorRuntime {
     graphql_request: Option<StageRuntime<GraphqlRequestStage>>,
     graphql_analysis: Option<StageRuntime<GraphqlAnalysisStage>>,
     graphql_response: Option<StageRuntime<GraphqlResponseStage>>,
-    body_size_limit: usize,
+}
+
+/// Runs the subgraph stages of the coprocessor, around the HTTP requests sent to subgraphs.
+/// Kept apart from `CoprocessorRuntime`, as it's owned by the subgraph executors.
+pub struct CoprocessorSubgraphRuntime {
+    subgraph_request: Option<StageRuntime<SubgraphRequestStage>>,
+    subgraph_response: Option<StageRuntime<SubgraphResponseStage>>,
 }
 
 #[derive(Default)]
@@ -188,7 +199,6 @@ impl CoprocessorRuntime {
     pub fn from_config(
         config: &CoprocessorConfig,
         telemetry_context: Arc<TelemetryContext>,
-        body_size_limit: usize,
     ) -> Result<Self, CoprocessorError> {
         let client = Arc::new(CoprocessorClient::new(
             config.clone(),
@@ -246,13 +256,15 @@ impl CoprocessorRuntime {
             graphql_request,
             graphql_analysis,
             graphql_response,
-            body_size_limit,
         })
     }
 
+    /// Runs the router request stage, reading the request body up to `body_size_limit` bytes
+    /// when the stage includes it.
     pub async fn on_router_request(
         &self,
         mut req: web::WebRequest<DefaultError>,
+        body_size_limit: usize,
     ) -> ControlFlow<web::WebResponse, web::WebRequest<DefaultError>> {
         let Some(stage) = &self.router_request else {
             return ControlFlow::Continue(req);
@@ -261,7 +273,7 @@ impl CoprocessorRuntime {
         // We read the request body only when this stage needs to include body
         let request_body = if stage.stage.include_body() {
             let body_stream = web::types::Payload(req.take_payload());
-            let new_body = match read_body_stream(&req, body_stream, self.body_size_limit).await {
+            let new_body = match read_body_stream(&req, body_stream, body_size_limit).await {
                 Ok(body) => body,
                 // We deliberately do not map to CoprocessorError here,
                 // to follow the same logic for status codes
@@ -396,6 +408,87 @@ impl CoprocessorRuntime {
     }
 }
 
+impl CoprocessorSubgraphRuntime {
+    /// Returns `None` when no subgraph stage is configured.
+    pub fn from_config(
+        config: &CoprocessorConfig,
+        telemetry_context: Arc<TelemetryContext>,
+    ) -> Result<Option<Self>, CoprocessorError> {
+        if !config.stages.subgraph.is_enabled() {
+            return Ok(None);
+        }
+
+        let client = Arc::new(CoprocessorClient::new(
+            config.clone(),
+            telemetry_context.clone(),
+        )?);
+
+        let subgraph_request = config
+            .stages
+            .subgraph
+            .request
+            .as_ref()
+            .map(SubgraphRequestStage::from_config)
+            .transpose()?
+            .map(|adapter| StageRuntime::new(client.clone(), adapter, telemetry_context.clone()));
+
+        let subgraph_response = config
+            .stages
+            .subgraph
+            .response
+            .as_ref()
+            .map(SubgraphResponseStage::from_config)
+            .transpose()?
+            .map(|adapter| StageRuntime::new(client, adapter, telemetry_context));
+
+        Ok(Some(Self {
+            subgraph_request,
+            subgraph_response,
+        }))
+    }
+
+    /// Breaking the stage skips the subgraph request,
+    /// the response of the coprocessor is used as the response of the subgraph.
+    pub async fn on_subgraph_request(
+        &self,
+        subgraph_name: &str,
+        method: &HttpMethod,
+        uri: &Uri,
+        headers: &mut http::HeaderMap,
+        body: &mut Vec<u8>,
+        context: &SharedRequestContext,
+    ) -> Result<ControlFlow<SubgraphHttpResponse, PerformedMutations>, CoprocessorError> {
+        let Some(stage) = &self.subgraph_request else {
+            return Ok(ControlFlow::Continue(Default::default()));
+        };
+
+        let mut input = SubgraphRequestInput::new(subgraph_name, method, uri, headers, body);
+        Ok(match stage.execute(&mut input, context).await? {
+            ControlFlow::Continue(mutations) => ControlFlow::Continue(mutations),
+            ControlFlow::Break(response) => {
+                ControlFlow::Break(into_subgraph_http_response(response))
+            }
+        })
+    }
+
+    pub async fn on_subgraph_response(
+        &self,
+        subgraph_name: &str,
+        response: SubgraphHttpResponse,
+        context: &SharedRequestContext,
+    ) -> Result<SubgraphHttpResponse, CoprocessorError> {
+        let Some(stage) = &self.subgraph_response else {
+            return Ok(response);
+        };
+
+        let mut input = SubgraphResponseInput::new(subgraph_name, response);
+        Ok(match stage.execute(&mut input, context).await? {
+            ControlFlow::Continue(_) => input.response,
+            ControlFlow::Break(response) => into_subgraph_http_response(response),
+        })
+    }
+}
+
 fn error_to_break<A: Stage>(
     stage: &StageRuntime<A>,
     err: CoprocessorError,
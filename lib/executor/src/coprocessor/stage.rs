@@ -134,6 +134,44 @@ impl<'a> StageResponseHeaders<'a> {
         })
     }
 
+    /// Same as `replace_into`, for the `http` headers of subgraph requests and responses.
+    pub(crate) fn replace_into_http(
+        &self,
+        headers_mut: &mut http::HeaderMap,
+    ) -> Result<(), CoprocessorError> {
+        let keys_to_remove: Vec<_> = headers_mut
+            .keys()
+            .filter(|name| !HOP_BY_HOP_HEADERS.contains(&name.as_str()))
+            .cloned()
+            .collect();
+
+        for key in keys_to_remove {
+            headers_mut.remove(&key);
+        }
+
+        for (name, values) in &self.0 {
+            let header_name = http::HeaderName::from_str(name.as_ref())
+                .map_err(|error| CoprocessorError::InvalidHeaderName(error.to_string()))?;
+
+            if HOP_BY_HOP_HEADERS.contains(&header_name.as_str()) {
+                continue;
+            }
+
+            let values = match values {
+                OneOrMore::One(value) => std::slice::from_ref(value),
+                OneOrMore::More(values) => values.as_slice(),
+            };
+
+            for value in values {
+                let header_value = http::HeaderValue::from_str(value)
+                    .map_err(|error| CoprocessorError::InvalidHeaderValue(error.to_string()))?;
+                headers_mut.append(header_name.clone(), header_value);
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn apply_to_response_builder(
         &self,
         response: &mut web::HttpResponseBuilder,
@@ -192,6 +230,7 @@ pub fn compile_condition(
 ) -> Result<Option<BooleanOrProgram>, CoprocessorError> {
     match condition {
         Some(ValueOrExpression::Value(value)) => Ok(Some(ValueOrProgram::Value(*value))),
+        Some(ValueOrExpression::Secret(secret)) => Ok(Some(ValueOrProgram::Value(*secret.value()))),
         Some(ValueOrExpression::Expression { expression }) => {
             let program = expression.compile_expression(None)?;
             let hints = ProgramHints::from_program(&program);
@@ -273,47 +312,81 @@ impl Serialize for HeaderMapJsonRef<'_> {
     where
         S: Serializer,
     {
-        // Protocol requires header values as arrays: {"name": ["value1", ...]}
-        enum HeaderValues<'a> {
-            One(&'a str),
-            Many(Vec<&'a str>),
-        }
+        serialize_grouped_headers(
+            serializer,
+            self.0.len(),
+            self.0
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.to_str().ok())),
+        )
+    }
+}
 
-        // Group duplicate header names so multi-value headers are serialized once.
-        let mut grouped_headers: HashMap<&str, HeaderValues<'_>> =
-            HashMap::with_capacity(self.0.len());
-        for (name, value) in self.0.iter() {
-            let Ok(value) = value.to_str() else {
-                continue;
-            };
+/// Borrowed view used to serialize `http` headers, like the ones of subgraph requests, as protocol JSON.
+pub struct HttpHeaderMapJsonRef<'a>(pub &'a http::HeaderMap);
 
-            match grouped_headers.entry(name.as_str()) {
-                Entry::Vacant(entry) => {
-                    entry.insert(HeaderValues::One(value));
-                }
-                Entry::Occupied(mut entry) => match entry.get_mut() {
-                    HeaderValues::One(previous) => {
-                        let previous = *previous;
-                        entry.insert(HeaderValues::Many(vec![previous, value]));
-                    }
-                    HeaderValues::Many(values) => {
-                        values.push(value);
-                    }
-                },
-            }
-        }
+impl Serialize for HttpHeaderMapJsonRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_grouped_headers(
+            serializer,
+            self.0.len(),
+            self.0
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.to_str().ok())),
+        )
+    }
+}
 
-        let mut map = serializer.serialize_map(Some(grouped_headers.len()))?;
-        for (name, values) in grouped_headers {
-            match values {
-                HeaderValues::One(value) => {
-                    map.serialize_entry(name, &[value])?;
+fn serialize_grouped_headers<'a, S>(
+    serializer: S,
+    len: usize,
+    headers: impl Iterator<Item = (&'a str, Option<&'a str>)>,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    // Protocol requires header values as arrays: {"name": ["value1", ...]}
+    enum HeaderValues<'a> {
+        One(&'a str),
+        Many(Vec<&'a str>),
+    }
+
+    // Group duplicate header names so multi-value headers are serialized once.
+    let mut grouped_headers: HashMap<&str, HeaderValues<'_>> = HashMap::with_capacity(len);
+    for (name, value) in headers {
+        let Some(value) = value else {
+            continue;
+        };
+
+        match grouped_headers.entry(name) {
+            Entry::Vacant(entry) => {
+                entry.insert(HeaderValues::One(value));
+            }
+            Entry::Occupied(mut entry) => match entry.get_mut() {
+                HeaderValues::One(previous) => {
+                    let previous = *previous;
+                    entry.insert(HeaderValues::Many(vec![previous, value]));
                 }
                 HeaderValues::Many(values) => {
-                    map.serialize_entry(name, &values)?;
+                    values.push(value);
                 }
+            },
+        }
+    }
+
+    let mut map = serializer.serialize_map(Some(grouped_headers.len()))?;
+    for (name, values) in grouped_headers {
+        match values {
+            HeaderValues::One(value) => {
+                map.serialize_entry(name, &[value])?;
+            }
+            HeaderValues::Many(values) => {
+                map.serialize_entry(name, &values)?;
             }
         }
-        map.end()
     }
+    map.end()
 }
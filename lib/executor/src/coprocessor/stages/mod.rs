@@ -1,2 +1,3 @@
 pub mod graphql;
 pub mod router;
+pub mod subgraph;
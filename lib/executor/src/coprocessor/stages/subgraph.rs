@@ -0,0 +1,318 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use hive_router_config::coprocessor::{
+    CoprocessorHookConfig, CoprocessorSubgraphRequestIncludeConfig,
+    CoprocessorSubgraphResponseIncludeConfig,
+};
+use hive_router_internal::expressions::BooleanOrProgram;
+use hive_router_internal::expressions::ToVrlValue;
+use http::{HeaderMap, Method, Uri};
+use ntex::http::body::{Body, ResponseBody};
+use ntex::web;
+
+use crate::coprocessor::error::CoprocessorError;
+use crate::coprocessor::protocol::COPROCESSOR_VERSION;
+use crate::coprocessor::stage::{
+    compile_condition, evaluate_condition, CoprocessorRequest, CoprocessorRequestBody,
+    HttpHeaderMapJsonRef, Stage, StageResponsePayload,
+};
+use crate::executors::http::SubgraphHttpResponse;
+use crate::request_context::{SelectedRequestContext, SharedRequestContext};
+
+pub struct SubgraphRequestStage {
+    condition: Option<BooleanOrProgram>,
+    include: CoprocessorSubgraphRequestIncludeConfig,
+}
+
+pub struct SubgraphResponseStage {
+    condition: Option<BooleanOrProgram>,
+    include: CoprocessorSubgraphResponseIncludeConfig,
+}
+
+pub struct SubgraphRequestInput<'a> {
+    subgraph_name: &'a str,
+    method: &'a Method,
+    uri: &'a Uri,
+    headers: &'a mut HeaderMap,
+    body: &'a mut Vec<u8>,
+}
+
+pub struct SubgraphResponseInput<'a> {
+    subgraph_name: &'a str,
+    pub(crate) response: SubgraphHttpResponse,
+}
+
+impl SubgraphRequestStage {
+    pub fn from_config(
+        config: &CoprocessorHookConfig<CoprocessorSubgraphRequestIncludeConfig>,
+    ) -> Result<Self, CoprocessorError> {
+        Ok(Self {
+            condition: compile_condition(config.condition.as_ref())?,
+            include: config.include.clone(),
+        })
+    }
+}
+
+impl SubgraphResponseStage {
+    pub fn from_config(
+        config: &CoprocessorHookConfig<CoprocessorSubgraphResponseIncludeConfig>,
+    ) -> Result<Self, CoprocessorError> {
+        Ok(Self {
+            condition: compile_condition(config.condition.as_ref())?,
+            include: config.include.clone(),
+        })
+    }
+}
+
+impl<'a> SubgraphRequestInput<'a> {
+    pub fn new(
+        subgraph_name: &'a str,
+        method: &'a Method,
+        uri: &'a Uri,
+        headers: &'a mut HeaderMap,
+        body: &'a mut Vec<u8>,
+    ) -> Self {
+        Self {
+            subgraph_name,
+            method,
+            uri,
+            headers,
+            body,
+        }
+    }
+}
+
+impl<'a> SubgraphResponseInput<'a> {
+    pub fn new(subgraph_name: &'a str, response: SubgraphHttpResponse) -> Self {
+        Self {
+            subgraph_name,
+            response,
+        }
+    }
+}
+
+impl Stage for SubgraphRequestStage {
+    type Input<'a> = SubgraphRequestInput<'a>;
+
+    const STAGE_NAME: &'static str = "subgraph.request";
+
+    fn should_run(&self, input: &Self::Input<'_>) -> Result<bool, CoprocessorError> {
+        evaluate_condition(self.condition.as_ref(), |hints| {
+            hints.context_builder(|root| {
+                root.insert_object("subgraph", |subgraph| {
+                    subgraph.insert_lazy("name", || input.subgraph_name.into());
+                });
+                root.insert_object("request", |req| {
+                    req.insert_lazy("method", || input.method.as_str().into())
+                        .insert_lazy("headers", || input.headers.to_vrl_value())
+                        .insert_lazy("url", || input.uri.to_vrl_value());
+                });
+            })
+        })
+    }
+
+    fn build_request<'a>(
+        &self,
+        input: &Self::Input<'_>,
+        id: &'a str,
+        context: &SharedRequestContext,
+    ) -> Result<CoprocessorRequest<'a>, CoprocessorError> {
+        let context_snapshot = if self.include.context.is_none() {
+            None
+        } else {
+            Some(context.snapshot()?)
+        };
+        let body = if self.include.body {
+            Some(
+                CoprocessorRequestBody::from(input.body.as_slice())
+                    .try_to_utf8(Self::STAGE_NAME)?,
+            )
+        } else {
+            None
+        };
+
+        let payload = SubgraphRequestPayload {
+            version: COPROCESSOR_VERSION,
+            stage: Self::STAGE_NAME,
+            id,
+            subgraph_name: input.subgraph_name,
+            method: self.include.method.then(|| input.method.as_str()),
+            uri: self.include.uri.then(|| input.uri.to_string().into()),
+            headers: self
+                .include
+                .headers
+                .then_some(HttpHeaderMapJsonRef(input.headers)),
+            body,
+            context: context_snapshot
+                .as_ref()
+                .map(|ctx| ctx.as_selected(&self.include.context)),
+        };
+
+        Ok(CoprocessorRequest {
+            id,
+            body: sonic_rs::to_vec(&payload)?.into(),
+        })
+    }
+
+    fn apply_mutations<'b>(
+        &self,
+        parsed: StageResponsePayload<'b>,
+        input: &mut Self::Input<'_>,
+    ) -> Result<(), CoprocessorError> {
+        // Subgraph request stage mutates headers and body. Method and URL stay read-only.
+        if let Some(headers) = parsed.headers {
+            headers.replace_into_http(input.headers)?;
+        }
+
+        if let Some(body) = parsed.body {
+            *input.body = Self::parse_json_body(&body)?.into_owned().into_bytes();
+        }
+
+        Ok(())
+    }
+}
+
+impl Stage for SubgraphResponseStage {
+    type Input<'a> = SubgraphResponseInput<'a>;
+
+    const STAGE_NAME: &'static str = "subgraph.response";
+
+    fn should_run(&self, input: &Self::Input<'_>) -> Result<bool, CoprocessorError> {
+        evaluate_condition(self.condition.as_ref(), |hints| {
+            hints.context_builder(|root| {
+                root.insert_object("subgraph", |subgraph| {
+                    subgraph.insert_lazy("name", || input.subgraph_name.into());
+                });
+                root.insert_object("response", |res| {
+                    res.insert_lazy("headers", || input.response.headers.to_vrl_value())
+                        .insert_lazy("status_code", || input.response.status.as_u16().into());
+                });
+            })
+        })
+    }
+
+    fn build_request<'a>(
+        &self,
+        input: &Self::Input<'_>,
+        id: &'a str,
+        context: &SharedRequestContext,
+    ) -> Result<CoprocessorRequest<'a>, CoprocessorError> {
+        let context_snapshot = if self.include.context.is_none() {
+            None
+        } else {
+            Some(context.snapshot()?)
+        };
+        let body = if self.include.body {
+            Some(
+                CoprocessorRequestBody::from(input.response.body.as_ref())
+                    .try_to_utf8(Self::STAGE_NAME)?,
+            )
+        } else {
+            None
+        };
+
+        let payload = SubgraphResponsePayload {
+            version: COPROCESSOR_VERSION,
+            stage: Self::STAGE_NAME,
+            id,
+            subgraph_name: input.subgraph_name,
+            headers: self
+                .include
+                .headers
+                .then(|| HttpHeaderMapJsonRef(&input.response.headers)),
+            body,
+            context: context_snapshot
+                .as_ref()
+                .map(|ctx| ctx.as_selected(&self.include.context)),
+            status_code: self
+                .include
+                .status_code
+                .then_some(input.response.status.as_u16()),
+        };
+
+        Ok(CoprocessorRequest {
+            id,
+            body: sonic_rs::to_vec(&payload)?.into(),
+        })
+    }
+
+    fn apply_mutations<'b>(
+        &self,
+        parsed: StageResponsePayload<'b>,
+        input: &mut Self::Input<'_>,
+    ) -> Result<(), CoprocessorError> {
+        // Subgraph response stage mutates headers and body only. The status property is controlled by break.
+        if let Some(headers) = parsed.headers {
+            // The response may be shared with other deduplicated requests,
+            // so the headers are replaced on a copy.
+            let mut response_headers = input.response.headers.as_ref().clone();
+            headers.replace_into_http(&mut response_headers)?;
+            input.response.headers = Arc::new(response_headers);
+        }
+
+        if let Some(body) = parsed.body {
+            input.response.body =
+                Bytes::from(Self::parse_json_body(&body)?.into_owned().into_bytes());
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts the response of a short-circuited subgraph stage into a subgraph response,
+/// processed as if the subgraph responded with it.
+pub(crate) fn into_subgraph_http_response(response: web::HttpResponse) -> SubgraphHttpResponse {
+    let mut headers = HeaderMap::with_capacity(response.headers().len());
+    for (name, value) in response.headers().iter() {
+        if let Ok(value) = http::HeaderValue::from_bytes(value.as_bytes()) {
+            headers.append(name.clone(), value);
+        }
+    }
+
+    let body = match response.body() {
+        ResponseBody::Body(Body::Bytes(bytes)) => Bytes::copy_from_slice(bytes),
+        _ => Bytes::new(),
+    };
+
+    SubgraphHttpResponse {
+        status: response.status(),
+        headers: Arc::new(headers),
+        body,
+    }
+}
+
+#[derive(serde::Serialize)]
+struct SubgraphRequestPayload<'a> {
+    version: u8,
+    stage: &'static str,
+    id: &'a str,
+    subgraph_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    method: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers: Option<HttpHeaderMapJsonRef<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context: Option<SelectedRequestContext<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct SubgraphResponsePayload<'a> {
+    version: u8,
+    stage: &'static str,
+    id: &'a str,
+    subgraph_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers: Option<HttpHeaderMapJsonRef<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context: Option<SelectedRequestContext<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status_code: Option<u16>,
+}
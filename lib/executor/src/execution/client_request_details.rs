@@ -9,7 +9,11 @@ use hive_router_internal::expressions::{vrl::core::Value, ToVrlValue};
 use http::{Method, Uri};
 use ntex::{http::HeaderMap as NtexHeaderMap, router::Path};
 
-use crate::request_context::{RequestContextError, SharedRequestContext};
+use crate::{
+    headers::cookies::request_cookies,
+    plugin_context::PluginContext,
+    request_context::{RequestContextError, SharedRequestContext},
+};
 
 pub struct OperationDetails<'exec> {
     pub name: Option<&'exec str>,
@@ -51,13 +55,22 @@ impl<'exec> PathParams<'exec> {
     }
 }
 
+/// The name and version of the client, as identified with `telemetry.client_identification`.
+#[derive(Debug, Clone, Default)]
+pub struct ClientIdentityDetails {
+    pub name: Option<String>,
+    pub version: Option<String>,
+}
+
 pub struct MutableClientRequestDetails<'exec> {
     pub method: &'exec Method,
     pub url: &'exec http::Uri,
     pub headers: NtexHeaderMap,
     pub operation: OperationDetails<'exec>,
     pub jwt: Arc<JwtRequestDetails>,
+    pub client: Arc<ClientIdentityDetails>,
     pub path_params: PathParams<'exec>,
+    pub plugin_context: Option<Arc<PluginContext>>,
 }
 
 pub struct ClientRequestDetails<'exec> {
@@ -66,9 +79,14 @@ pub struct ClientRequestDetails<'exec> {
     pub headers: Arc<NtexHeaderMap>,
     pub operation: OperationDetails<'exec>,
     pub jwt: Arc<JwtRequestDetails>,
+    /// Exposed to VRL expressions as `.request.client`.
+    pub client: Arc<ClientIdentityDetails>,
     /// Path parameters captured from the GraphQL endpoint pattern (e.g. `/{tenant}/graphql`)
     /// during URL routing. Exposed to VRL expressions as `.request.path_params`.
     pub path_params: PathParams<'exec>,
+    /// The context of the plugins for the request, whose exposed values
+    /// are available to VRL expressions as `.request.context`.
+    pub plugin_context: Option<Arc<PluginContext>>,
 }
 
 // Trait for accessing read-only client request details.
@@ -79,7 +97,9 @@ pub trait ClientRequestDetailsView {
     fn headers(&self) -> &NtexHeaderMap;
     fn operation<'a>(&'a self) -> &'a OperationDetails<'a>;
     fn jwt(&self) -> &JwtRequestDetails;
+    fn client(&self) -> &ClientIdentityDetails;
     fn path_params<'a>(&'a self) -> &'a PathParams<'a>;
+    fn plugin_context(&self) -> Option<&PluginContext>;
 
     fn to_vrl_value(&self) -> Value {
         request_details_to_vrl_value(self)
@@ -107,9 +127,17 @@ impl ClientRequestDetailsView for MutableClientRequestDetails<'_> {
         &self.jwt
     }
 
+    fn client(&self) -> &ClientIdentityDetails {
+        &self.client
+    }
+
     fn path_params<'a>(&'a self) -> &'a PathParams<'a> {
         &self.path_params
     }
+
+    fn plugin_context(&self) -> Option<&PluginContext> {
+        self.plugin_context.as_deref()
+    }
 }
 
 impl ClientRequestDetailsView for ClientRequestDetails<'_> {
@@ -133,9 +161,17 @@ impl ClientRequestDetailsView for ClientRequestDetails<'_> {
         &self.jwt
     }
 
+    fn client(&self) -> &ClientIdentityDetails {
+        &self.client
+    }
+
     fn path_params<'a>(&'a self) -> &'a PathParams<'a> {
         &self.path_params
     }
+
+    fn plugin_context(&self) -> Option<&PluginContext> {
+        self.plugin_context.as_deref()
+    }
 }
 
 impl<'exec> MutableClientRequestDetails<'exec> {
@@ -146,7 +182,9 @@ impl<'exec> MutableClientRequestDetails<'exec> {
             headers: self.headers.into(),
             operation: self.operation,
             jwt: self.jwt,
+            client: self.client,
             path_params: self.path_params,
+            plugin_context: self.plugin_context,
         }
     }
 }
@@ -212,6 +250,17 @@ fn request_details_to_vrl_value(details: &(impl ClientRequestDetailsView + ?Size
     // .request.headers
     let headers_value = ntex_header_map_to_vrl_value(details.headers());
 
+    // .request.cookies - the first value wins when a cookie is sent several times,
+    // so the cookies are collected in reverse order
+    let cookies: Vec<_> = request_cookies(details.headers()).collect();
+    let cookies_value = Value::Object(
+        cookies
+            .into_iter()
+            .rev()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect(),
+    );
+
     // .request.url
     let url_value = details.url().to_vrl_value();
 
@@ -266,12 +315,35 @@ fn request_details_to_vrl_value(details: &(impl ClientRequestDetailsView + ?Size
         ])),
     };
 
+    // .request.client
+    let client_value = Value::Object(BTreeMap::from([
+        ("name".into(), details.client().name.as_deref().into()),
+        ("version".into(), details.client().version.as_deref().into()),
+    ]));
+
+    // .request.context - the values exposed by the plugins
+    let context_value = Value::Object(
+        details
+            .plugin_context()
+            .map(|plugin_context| {
+                plugin_context
+                    .exposed_values()
+                    .into_iter()
+                    .map(|(key, value)| (key.into(), value))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    );
+
     Value::Object(BTreeMap::from([
         ("method".into(), details.method().as_str().into()),
         ("headers".into(), headers_value),
+        ("cookies".into(), cookies_value),
         ("url".into(), url_value),
         ("path_params".into(), path_params_value),
         ("operation".into(), operation_value),
         ("jwt".into(), jwt_value),
+        ("client".into(), client_value),
+        ("context".into(), context_value),
     ]))
 }
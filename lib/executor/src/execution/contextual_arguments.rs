@@ -0,0 +1,191 @@
+use ahash::{HashMap as AHashMap, HashMapExt};
+use hive_router_query_planner::planner::plan_nodes::{ContextArgument, FlattenNodePathSegment};
+
+use crate::{
+    introspection::schema::PossibleTypes, response::value::Value,
+    utils::traverse::traverse_and_callback_with_ancestors,
+};
+
+const NULL_VALUE: &[u8] = b"null";
+
+/// Entities of a flatten fetch, grouped by the values of their contextual arguments.
+/// Every group is sent to the subgraph in a separate request,
+/// as a variable holds a single value per request.
+pub struct ContextValueGroups<'exec> {
+    /// The group of each entity, in the order of `traverse_and_callback`
+    pub entity_groups: Vec<usize>,
+    /// Serialized values of the contextual arguments, per group
+    pub raw_variable_values: Vec<Vec<(&'exec str, Vec<u8>)>>,
+}
+
+/// Selects the values of contextual arguments for each entity at the path.
+/// A value is taken from the closest ancestor of the entity exposing it,
+/// under a response key equal to the name of the variable.
+pub fn group_entities_by_context_values<'exec>(
+    data: &Value<'_>,
+    path: &[FlattenNodePathSegment],
+    possible_types: &PossibleTypes,
+    context_arguments: &'exec [ContextArgument],
+) -> ContextValueGroups<'exec> {
+    let mut entity_groups = Vec::new();
+    let mut group_by_values: AHashMap<Vec<Vec<u8>>, usize> = AHashMap::new();
+    let mut raw_variable_values = Vec::new();
+
+    traverse_and_callback_with_ancestors(
+        data,
+        path,
+        possible_types,
+        &mut Vec::new(),
+        &mut |_entity, ancestors| {
+            let values: Vec<Vec<u8>> = context_arguments
+                .iter()
+                .map(|context_argument| {
+                    find_context_value(ancestors, context_argument)
+                        .and_then(|value| sonic_rs::to_vec(value).ok())
+                        .unwrap_or_else(|| NULL_VALUE.to_vec())
+                })
+                .collect();
+
+            let next_group = group_by_values.len();
+            let group = *group_by_values.entry(values).or_insert_with_key(|values| {
+                raw_variable_values.push(
+                    context_arguments
+                        .iter()
+                        .map(|context_argument| context_argument.variable_name.as_str())
+                        .zip(values.iter().cloned())
+                        .collect(),
+                );
+                next_group
+            });
+            entity_groups.push(group);
+        },
+    );
+
+    ContextValueGroups {
+        entity_groups,
+        raw_variable_values,
+    }
+}
+
+fn find_context_value<'a>(
+    ancestors: &[&'a Value<'a>],
+    context_argument: &ContextArgument,
+) -> Option<&'a Value<'a>> {
+    let mut value = ancestors.iter().rev().copied().find_map(|ancestor| {
+        ancestor
+            .as_object()?
+            .iter()
+            .find(|(key, _)| *key == context_argument.variable_name)
+            .map(|(_, value)| value)
+    })?;
+
+    for key in &context_argument.path {
+        value = value
+            .as_object()?
+            .iter()
+            .find(|(field_name, _)| *field_name == key.as_str())
+            .map(|(_, value)| value)?;
+    }
+
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use hive_router_query_planner::planner::plan_nodes::{ContextArgument, FlattenNodePathSegment};
+
+    use crate::response::value::Value;
+
+    fn user(name: &'static str, posts: Vec<Value<'static>>) -> Value<'static> {
+        Value::Object(vec![
+            ("contextualArgument_0", Value::String(name.into())),
+            ("posts", Value::Array(posts)),
+        ])
+    }
+
+    fn post(id: &'static str) -> Value<'static> {
+        Value::Object(vec![("id", Value::String(id.into()))])
+    }
+
+    #[test]
+    fn groups_entities_by_the_closest_ancestor_value() {
+        let data = Value::Object(vec![(
+            "users",
+            Value::Array(vec![
+                user("a", vec![post("1"), post("2")]),
+                user("b", vec![post("3")]),
+                user("a", vec![post("4")]),
+            ]),
+        )]);
+        let path = vec![
+            FlattenNodePathSegment::Field("users".into()),
+            FlattenNodePathSegment::List,
+            FlattenNodePathSegment::Field("posts".into()),
+            FlattenNodePathSegment::List,
+        ];
+        let context_arguments = vec![ContextArgument {
+            variable_name: "contextualArgument_0".into(),
+            path: vec![],
+        }];
+
+        let groups = super::group_entities_by_context_values(
+            &data,
+            &path,
+            &Default::default(),
+            &context_arguments,
+        );
+
+        assert_eq!(groups.entity_groups, vec![0, 0, 1, 0]);
+        assert_eq!(
+            groups.raw_variable_values,
+            vec![
+                vec![("contextualArgument_0", b"\"a\"".to_vec())],
+                vec![("contextualArgument_0", b"\"b\"".to_vec())],
+            ]
+        );
+    }
+
+    #[test]
+    fn follows_the_path_of_the_value() {
+        let data = Value::Object(vec![(
+            "user",
+            Value::Object(vec![
+                (
+                    "contextualArgument_0",
+                    Value::Object(vec![("id", Value::I64(7))]),
+                ),
+                ("post", post("1")),
+            ]),
+        )]);
+        let path = vec![
+            FlattenNodePathSegment::Field("user".into()),
+            FlattenNodePathSegment::Field("post".into()),
+        ];
+        let context_arguments = vec![
+            ContextArgument {
+                variable_name: "contextualArgument_0".into(),
+                path: vec!["id".into()],
+            },
+            ContextArgument {
+                variable_name: "contextualArgument_1".into(),
+                path: vec![],
+            },
+        ];
+
+        let groups = super::group_entities_by_context_values(
+            &data,
+            &path,
+            &Default::default(),
+            &context_arguments,
+        );
+
+        assert_eq!(groups.entity_groups, vec![0]);
+        assert_eq!(
+            groups.raw_variable_values,
+            vec![vec![
+                ("contextualArgument_0", b"7".to_vec()),
+                ("contextualArgument_1", b"null".to_vec()),
+            ]]
+        );
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use ahash::{HashMap as AHashMap, HashMapExt};
+use bytes::{BufMut, Bytes};
+use hive_router_config::entity_cache::EntityCacheConfig;
+use hive_router_internal::telemetry::metrics::cache_metrics::CacheMetricSet;
+use hive_router_query_planner::planner::plan_nodes::CustomScalarPaths;
+use moka::{sync::Cache, Expiry};
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::{
+    execution::plan::VariablesMap,
+    response::{subgraph_response::SubgraphResponse, value::Value},
+    utils::consts::{CLOSE_BRACE, CLOSE_BRACKET, COMMA, OPEN_BRACKET, TYPENAME_FIELD_NAME},
+};
+
+/// Caches the entities resolved by subgraphs through `_entities` queries,
+/// keyed by the subgraph, the fetch operation and its variables, and the representation of the entity.
+///
+/// Entities are kept as serialized JSON, and are parsed back with the custom scalar paths
+/// of the fetch node when served from the cache.
+pub struct EntityCache {
+    cache: Cache<u128, CachedEntity>,
+    config: EntityCacheConfig,
+    metrics: CacheMetricSet,
+}
+
+#[derive(Clone)]
+struct CachedEntity {
+    bytes: Bytes,
+    ttl: Duration,
+    type_name: Box<str>,
+    /// The serialized representation of the entity, to purge it by its key fields.
+    representation: Bytes,
+}
+
+struct CachedEntityExpiry;
+
+impl Expiry<u128, CachedEntity> for CachedEntityExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &u128,
+        value: &CachedEntity,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.ttl)
+    }
+}
+
+pub enum EntityCacheLookup {
+    /// The serialized entity.
+    Hit(Bytes),
+    /// The entity is not cached, and should be cached once resolved by the subgraph.
+    Miss(EntityCacheWrite),
+    /// The type of the entity is not cached.
+    Skip,
+}
+
+pub struct EntityCacheWrite {
+    key: u128,
+    ttl: Duration,
+    type_name: Box<str>,
+    representation: Bytes,
+}
+
+/// The entities of a flatten fetch served from the cache.
+pub struct CachedEntities<'exec> {
+    /// The bytes the entities borrow from.
+    pub bytes: Option<Bytes>,
+    pub entities: Vec<Value<'exec>>,
+    pub representation_hash_to_index: AHashMap<u64, usize>,
+}
+
+impl EntityCache {
+    pub fn new(config: &EntityCacheConfig, metrics: CacheMetricSet) -> Self {
+        Self {
+            cache: Cache::builder()
+                .max_capacity(config.max_entries)
+                .expire_after(CachedEntityExpiry)
+                .support_invalidation_closures()
+                .build(),
+            config: config.clone(),
+            metrics,
+        }
+    }
+
+    pub fn entry_count(&self) -> u64 {
+        self.cache.entry_count()
+    }
+
+    /// Drops the cached entities of the given type, or only the ones whose representation
+    /// has the same values for all the fields of `key`, when given.
+    pub fn purge(&self, type_name: &str, key: Option<sonic_rs::Value>) {
+        let type_name = type_name.to_string();
+        let result = self.cache.invalidate_entries_if(move |_, cached| {
+            if *cached.type_name != *type_name {
+                return false;
+            }
+            let Some(key) = key.as_ref().and_then(|key| key.as_object()) else {
+                return true;
+            };
+            let Ok(representation) =
+                sonic_rs::from_slice::<sonic_rs::Value>(&cached.representation)
+            else {
+                return false;
+            };
+            key.iter()
+                .all(|(field, value)| representation.get(field) == Some(value))
+        });
+        if let Err(err) = result {
+            tracing::warn!(error = %err, "failed to purge the entity cache");
+        }
+    }
+
+    /// Drops all the cached entities.
+    pub fn clear(&self) {
+        self.cache.invalidate_all();
+    }
+
+    /// Hashes the parts of the cache key shared by all representations of a fetch:
+    /// the subgraph, the operation and the values of the variables it uses.
+    pub fn fetch_hash(
+        subgraph_name: &str,
+        operation_hash: u64,
+        variable_usages: Option<&BTreeSet<String>>,
+        variable_values: &Option<VariablesMap>,
+    ) -> u64 {
+        let mut hasher = Xxh3::new();
+        subgraph_name.hash(&mut hasher);
+        operation_hash.hash(&mut hasher);
+        if let (Some(variable_usages), Some(variable_values)) = (variable_usages, variable_values) {
+            for name in variable_usages {
+                name.hash(&mut hasher);
+                if let Some(value) = variable_values.get(name) {
+                    sonic_rs::to_string(value)
+                        .unwrap_or_default()
+                        .hash(&mut hasher);
+                }
+            }
+        }
+        hasher.finish()
+    }
+
+    pub fn lookup(
+        &self,
+        fetch_hash: u64,
+        representation_hash: u64,
+        representation: &Value,
+    ) -> EntityCacheLookup {
+        let Some(type_name) = representation_type_name(representation) else {
+            return EntityCacheLookup::Skip;
+        };
+        let Some(ttl) = self.config.ttl_for_type(type_name) else {
+            return EntityCacheLookup::Skip;
+        };
+
+        let mut hasher = Xxh3::new();
+        fetch_hash.hash(&mut hasher);
+        type_name.hash(&mut hasher);
+        representation_hash.hash(&mut hasher);
+        let key = hasher.digest128();
+
+        let started_at = Instant::now();
+        match self.cache.get(&key) {
+            Some(cached) => {
+                self.metrics.hit(started_at.elapsed());
+                EntityCacheLookup::Hit(cached.bytes)
+            }
+            None => {
+                self.metrics.miss(started_at.elapsed());
+                match sonic_rs::to_vec(representation) {
+                    Ok(serialized) => EntityCacheLookup::Miss(EntityCacheWrite {
+                        key,
+                        ttl,
+                        type_name: type_name.into(),
+                        representation: Bytes::from(serialized),
+                    }),
+                    Err(err) => {
+                        tracing::warn!(error = %err, "failed to serialize entity representation");
+                        EntityCacheLookup::Skip
+                    }
+                }
+            }
+        }
+    }
+
+    /// Caches the entities resolved by the subgraph, `writes` being aligned with `entities`.
+    pub fn store(&self, writes: &[Option<EntityCacheWrite>], entities: &[Value]) {
+        for (write, entity) in writes.iter().zip(entities) {
+            let Some(write) = write else {
+                continue;
+            };
+            if entity.is_null() {
+                continue;
+            }
+            match sonic_rs::to_vec(entity) {
+                Ok(bytes) => self.cache.insert(
+                    write.key,
+                    CachedEntity {
+                        bytes: Bytes::from(bytes),
+                        ttl: write.ttl,
+                        type_name: write.type_name.clone(),
+                        representation: write.representation.clone(),
+                    },
+                ),
+                Err(err) => tracing::warn!(error = %err, "failed to serialize entity to cache"),
+            }
+        }
+    }
+}
+
+impl CachedEntities<'_> {
+    /// Parses the cached entities the same way as the `_entities` of a subgraph response.
+    pub fn parse(
+        hits: Vec<(u64, Bytes)>,
+        custom_scalar_paths: Option<&CustomScalarPaths>,
+    ) -> Option<Self> {
+        if hits.is_empty() {
+            return None;
+        }
+
+        const PREFIX: &[u8] = b"{\"data\":{\"_entities\":";
+        let mut body = Vec::with_capacity(
+            PREFIX.len() + hits.iter().map(|(_, bytes)| bytes.len() + 1).sum::<usize>() + 3,
+        );
+        let mut representation_hash_to_index = AHashMap::with_capacity(hits.len());
+        body.put(PREFIX);
+        body.put(OPEN_BRACKET);
+        for (index, (hash, bytes)) in hits.iter().enumerate() {
+            if index > 0 {
+                body.put(COMMA);
+            }
+            body.put(bytes.as_ref());
+            representation_hash_to_index.insert(*hash, index);
+        }
+        body.put(CLOSE_BRACKET);
+        body.put(CLOSE_BRACE);
+        body.put(CLOSE_BRACE);
+
+        let mut response = match SubgraphResponse::deserialize_from_bytes(
+            Bytes::from(body),
+            custom_scalar_paths,
+        ) {
+            Ok(response) => response,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to parse cached entities");
+                return None;
+            }
+        };
+
+        Some(Self {
+            entities: response.data.take_entities()?,
+            bytes: response.bytes.take(),
+            representation_hash_to_index,
+        })
+    }
+}
+
+fn representation_type_name<'a>(representation: &'a Value) -> Option<&'a str> {
+    let object = representation.as_object()?;
+    object
+        .binary_search_by_key(&TYPENAME_FIELD_NAME, |(key, _)| key)
+        .ok()
+        .and_then(|idx| object[idx].1.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use hive_router_config::entity_cache::EntityCacheTypeConfig;
+    use hive_router_internal::telemetry::metrics::cache_metrics::CacheMetrics;
+
+    use super::*;
+
+    fn cache() -> EntityCache {
+        let config = EntityCacheConfig {
+            enabled: true,
+            types: HashMap::from([(
+                "Product".to_string(),
+                EntityCacheTypeConfig {
+                    ttl: Duration::from_secs(30),
+                },
+            )]),
+            ..Default::default()
+        };
+        EntityCache::new(&config, CacheMetrics::new(None).entity)
+    }
+
+    fn representation(json: &str) -> Value<'_> {
+        sonic_rs::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn caches_entities_of_configured_types() {
+        let cache = cache();
+        let product = representation(r#"{"__typename":"Product","upc":"1"}"#);
+
+        let EntityCacheLookup::Miss(write) = cache.lookup(1, 2, &product) else {
+            panic!("expected a miss");
+        };
+        let entity = representation(r#"{"name":"Table"}"#);
+        cache.store(&[Some(write)], &[entity]);
+
+        match cache.lookup(1, 2, &product) {
+            EntityCacheLookup::Hit(bytes) => assert_eq!(bytes.as_ref(), br#"{"name":"Table"}"#),
+            _ => panic!("expected a hit"),
+        }
+        // another fetch of the same representation
+        assert!(matches!(
+            cache.lookup(3, 2, &product),
+            EntityCacheLookup::Miss(_)
+        ));
+
+        let user = representation(r#"{"__typename":"User","id":"1"}"#);
+        assert!(matches!(cache.lookup(1, 2, &user), EntityCacheLookup::Skip));
+    }
+
+    #[test]
+    fn purges_entities_by_type_and_key() {
+        let cache = cache();
+        let table = representation(r#"{"__typename":"Product","upc":"1"}"#);
+        let chair = representation(r#"{"__typename":"Product","upc":"2"}"#);
+        for (hash, product) in [(1, &table), (2, &chair)] {
+            let EntityCacheLookup::Miss(write) = cache.lookup(1, hash, product) else {
+                panic!("expected a miss");
+            };
+            cache.store(&[Some(write)], &[representation(r#"{"name":"Table"}"#)]);
+        }
+
+        cache.purge("Product", Some(sonic_rs::json!({"upc": "1"})));
+        assert!(matches!(
+            cache.lookup(1, 1, &table),
+            EntityCacheLookup::Miss(_)
+        ));
+        assert!(matches!(
+            cache.lookup(1, 2, &chair),
+            EntityCacheLookup::Hit(_)
+        ));
+
+        cache.purge("Product", None);
+        assert!(matches!(
+            cache.lookup(1, 2, &chair),
+            EntityCacheLookup::Miss(_)
+        ));
+    }
+
+    #[test]
+    fn parses_cached_entities() {
+        let cached = CachedEntities::parse(
+            vec![
+                (10, Bytes::from_static(br#"{"name":"Table"}"#)),
+                (20, Bytes::from_static(br#"{"name":"Chair"}"#)),
+            ],
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(cached.entities.len(), 2);
+        assert_eq!(cached.representation_hash_to_index.get(&20), Some(&1));
+        assert_eq!(
+            sonic_rs::to_string(&cached.entities[1]).unwrap(),
+            r#"{"name":"Chair"}"#
+        );
+    }
+}
@@ -18,6 +18,22 @@ pub enum PlanExecutionErrorKind {
     #[error(transparent)]
     #[strum(serialize = "SUBGRAPH_EXECUTION_FAILURE")]
     SubgraphExecutor(#[from] SubgraphExecutorError),
+
+    #[error("Variable \"${0}\" used as the condition of @skip or @include must be a boolean")]
+    #[strum(serialize = "BAD_USER_INPUT")]
+    InvalidConditionVariable(String),
+
+    #[error("Argument \"initialCount\" of @stream on field \"{0}\" must not be negative")]
+    #[strum(serialize = "BAD_USER_INPUT")]
+    InvalidStreamInitialCount(String),
+
+    #[error("Execution cancelled, the client closed the connection")]
+    #[strum(serialize = "EXECUTION_CANCELLED")]
+    Cancelled,
+
+    #[error("Response of {size} bytes exceeds the maximum response size of {max} bytes")]
+    #[strum(serialize = "RESPONSE_TOO_LARGE")]
+    ResponseTooLarge { size: usize, max: usize },
 }
 
 /// The central error type for all query plan execution failures.
@@ -69,6 +85,11 @@ impl PlanExecutionError {
         (&self.kind).into()
     }
 
+    /// Whether the execution was cancelled, because the client closed the connection.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.kind, PlanExecutionErrorKind::Cancelled)
+    }
+
     pub fn subgraph_name(&self) -> &Option<String> {
         &self.context.subgraph_name
     }
@@ -0,0 +1,87 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use http::{HeaderName, HeaderValue};
+
+use crate::response::value::Value;
+
+/// Asks a subgraph to include its federated trace in `extensions.ftv1` of the response.
+pub static FTV1_REQUEST_HEADER: HeaderName =
+    HeaderName::from_static("apollo-federation-include-trace");
+pub static FTV1_REQUEST_HEADER_VALUE: HeaderValue = HeaderValue::from_static("ftv1");
+
+/// The timing of a subgraph fetch, identified by the id of its plan node.
+#[derive(Debug, Clone)]
+pub struct FetchTrace {
+    pub node_id: i64,
+    pub subgraph_name: String,
+    /// The time the request was sent, relative to the creation of the sink.
+    pub sent_offset: Duration,
+    pub duration: Duration,
+    /// The base64 encoded federated trace returned by the subgraph.
+    pub ftv1: Option<String>,
+}
+
+/// Collects the timings of the subgraph fetches of an execution.
+#[derive(Clone, Debug)]
+pub struct FetchTraceSink {
+    started_at: Instant,
+    include_ftv1: bool,
+    traces: Arc<Mutex<Vec<FetchTrace>>>,
+}
+
+impl FetchTraceSink {
+    pub fn new(include_ftv1: bool) -> Self {
+        Self {
+            started_at: Instant::now(),
+            include_ftv1,
+            traces: Default::default(),
+        }
+    }
+
+    pub fn started_at(&self) -> Instant {
+        self.started_at
+    }
+
+    pub fn include_ftv1(&self) -> bool {
+        self.include_ftv1
+    }
+
+    pub(crate) fn record(
+        &self,
+        node_id: i64,
+        subgraph_name: &str,
+        sent_at: Instant,
+        extensions: Option<&Value>,
+    ) {
+        let ftv1 = match extensions {
+            Some(Value::Object(fields)) if self.include_ftv1 => {
+                fields.iter().find_map(|(key, value)| match value {
+                    Value::String(ftv1) if *key == "ftv1" => Some(ftv1.to_string()),
+                    _ => None,
+                })
+            }
+            _ => None,
+        };
+        let trace = FetchTrace {
+            node_id,
+            subgraph_name: subgraph_name.to_string(),
+            sent_offset: sent_at.saturating_duration_since(self.started_at),
+            duration: sent_at.elapsed(),
+            ftv1,
+        };
+        match self.traces.lock() {
+            Ok(mut traces) => traces.push(trace),
+            Err(poisoned) => poisoned.into_inner().push(trace),
+        }
+    }
+
+    pub fn take(&self) -> Vec<FetchTrace> {
+        match self.traces.lock() {
+            Ok(mut traces) => std::mem::take(&mut *traces),
+            Err(poisoned) => std::mem::take(&mut *poisoned.into_inner()),
+        }
+    }
+}
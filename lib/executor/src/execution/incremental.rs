@@ -0,0 +1,1069 @@
+use std::sync::Arc;
+
+use graphql_tools::static_graphql::query::{
+    Definition, Document, OperationDefinition, Selection, Value as QueryValue,
+};
+use hive_router_config::errors::ErrorPolicy;
+use hive_router_internal::telemetry::traces::spans::graphql::GraphQLOperationSpan;
+use hive_router_query_planner::{
+    ast::normalization::defer::DeferredOperation,
+    planner::plan_nodes::{DeferNode, PlanNode},
+};
+use http::{Method, StatusCode, Uri};
+use ntex::{http::HeaderMap as NtexHeaderMap, router::Path};
+use sonic_rs::JsonValueTrait;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    execution::{
+        client_request_details::{
+            ClientIdentityDetails, ClientRequestDetails, JwtRequestDetails, OperationDetails,
+            PathParams,
+        },
+        error::{
+            IntoPlanExecutionError, LazyPlanContext, PlanExecutionError, PlanExecutionErrorKind,
+        },
+        plan::{
+            log_plan_execution_error, ExecutionResultExtensions, Executor, FailedExecutionResult,
+            PlanSubscriptionOutput, QueryPlanExecutionOpts, VariablesMap,
+        },
+    },
+    execution_context::ExecutionContext,
+    hooks::on_graphql_error::handle_graphql_errors_with_plugins,
+    introspection::{resolve::resolve_introspection, schema::SchemaMetadata},
+    json_writer::{write_and_escape_string, write_u64},
+    plugin_context::{PluginContext, PluginRequestState, RouterHttpRequest},
+    plugin_trait::RouterPluginBoxed,
+    projection::{
+        plan::FieldProjectionPlan,
+        response::{
+            project_by_operation, project_deferred_fragment, project_stream_items, DeferredPatch,
+        },
+    },
+    request_context::SharedRequestContext,
+    response::{error_masking::ErrorMaskingPlan, graphql_error::GraphQLError, value::Value},
+    utils::consts::{
+        CLOSE_BRACE, CLOSE_BRACKET, COLON, COMMA, FALSE, NULL, OPEN_BRACE, OPEN_BRACKET, QUOTE,
+        TRUE,
+    },
+};
+
+const HAS_NEXT: &[u8] = b"\"hasNext\":";
+const INCREMENTAL: &[u8] = b"{\"incremental\":[";
+const DATA: &[u8] = b"{\"data\":";
+const ITEMS: &[u8] = b"{\"items\":";
+const PATH: &[u8] = b"\"path\":";
+
+/// A root field of an operation, annotated with the `@stream` directive.
+///
+/// The arguments are kept as written in the document,
+/// and resolved against the variables of every request.
+#[derive(Debug, Clone)]
+pub struct StreamedField {
+    pub response_key: String,
+    pub label: Option<String>,
+    pub if_condition: Option<QueryValue>,
+    pub initial_count: Option<QueryValue>,
+}
+
+impl StreamedField {
+    /// Collects root fields annotated with `@stream` in the executed operation.
+    ///
+    /// Only fields selected directly on the root type of a query or mutation are considered,
+    /// the validation of the operation rejects `@stream` anywhere else.
+    pub fn collect_from_document(document: &Document, operation_name: Option<&str>) -> Vec<Self> {
+        let selection_set = document
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Operation(operation) => Some(operation),
+                Definition::Fragment(_) => None,
+            })
+            .find_map(|operation| {
+                let (name, selection_set) = match operation {
+                    OperationDefinition::SelectionSet(selection_set) => (None, selection_set),
+                    OperationDefinition::Query(query) => {
+                        (query.name.as_deref(), &query.selection_set)
+                    }
+                    OperationDefinition::Mutation(mutation) => {
+                        (mutation.name.as_deref(), &mutation.selection_set)
+                    }
+                    OperationDefinition::Subscription(subscription) => {
+                        (subscription.name.as_deref(), &subscription.selection_set)
+                    }
+                };
+
+                match operation_name {
+                    Some(operation_name) if name != Some(operation_name) => None,
+                    _ => Some((operation, selection_set)),
+                }
+            })
+            .and_then(|(operation, selection_set)| match operation {
+                OperationDefinition::Subscription(_) => None,
+                _ => Some(selection_set),
+            });
+
+        let Some(selection_set) = selection_set else {
+            return Vec::new();
+        };
+
+        selection_set
+            .items
+            .iter()
+            .filter_map(|selection| match selection {
+                Selection::Field(field) => Some(field),
+                _ => None,
+            })
+            .filter_map(|field| {
+                let directive = field.directives.iter().find(|d| d.name == "stream")?;
+                let argument = |name: &str| {
+                    directive
+                        .arguments
+                        .iter()
+                        .find(|(arg_name, _)| arg_name == name)
+                        .map(|(_, value)| value.clone())
+                };
+
+                Some(StreamedField {
+                    response_key: field.alias.as_ref().unwrap_or(&field.name).clone(),
+                    label: match argument("label") {
+                        Some(QueryValue::String(label)) => Some(label),
+                        _ => None,
+                    },
+                    if_condition: argument("if"),
+                    initial_count: argument("initialCount"),
+                })
+            })
+            .collect()
+    }
+
+    /// Resolves the number of items delivered in the initial payload,
+    /// or `None` when streaming is disabled by the `if` argument.
+    pub fn resolve_initial_count(
+        &self,
+        variables: &Option<VariablesMap>,
+    ) -> Result<Option<usize>, PlanExecutionError> {
+        let variable = |name: &str| variables.as_ref().and_then(|vars| vars.get(name));
+
+        let enabled = match &self.if_condition {
+            None => true,
+            Some(QueryValue::Boolean(value)) => *value,
+            Some(QueryValue::Variable(name)) => variable(name)
+                .and_then(|value| value.as_bool())
+                .unwrap_or(true),
+            Some(_) => true,
+        };
+
+        if !enabled {
+            return Ok(None);
+        }
+
+        let initial_count = match &self.initial_count {
+            Some(QueryValue::Int(value)) => value.as_i64(),
+            Some(QueryValue::Variable(name)) => variable(name).and_then(|value| value.as_i64()),
+            _ => None,
+        };
+
+        match initial_count {
+            Some(initial_count) if initial_count < 0 => Err(PlanExecutionError::new(
+                PlanExecutionErrorKind::InvalidStreamInitialCount(self.response_key.clone()),
+                LazyPlanContext {
+                    subgraph_name: || None,
+                    affected_path: || None,
+                },
+            )),
+            initial_count => Ok(Some(initial_count.unwrap_or(0) as usize)),
+        }
+    }
+}
+
+/// Projection plans of a query with `@defer` fragments, one for every part of the response.
+#[derive(Debug, Clone)]
+pub struct DeferredProjectionPlan {
+    /// Projects the fields of the primary operation, delivered in the initial payload.
+    pub primary: Vec<FieldProjectionPlan>,
+    /// Projects the fields of every deferred fragment, in the order of the deferred nodes.
+    pub fragments: Vec<DeferredFragmentProjectionPlan>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeferredFragmentProjectionPlan {
+    /// Response keys of the fields leading to the fragment.
+    pub path: Vec<String>,
+    /// The variable of the `if` argument, the fragment is deferred only when it's true.
+    pub if_variable: Option<String>,
+    /// Projects the fields leading to the fragment, and the fields of the fragment.
+    pub plans: Vec<FieldProjectionPlan>,
+}
+
+impl DeferredProjectionPlan {
+    pub fn from_operation(
+        deferred_operation: &DeferredOperation,
+        schema_metadata: &SchemaMetadata,
+    ) -> Self {
+        let (_, primary) =
+            FieldProjectionPlan::from_operation(&deferred_operation.primary, schema_metadata);
+
+        DeferredProjectionPlan {
+            primary,
+            fragments: deferred_operation
+                .fragments
+                .iter()
+                .map(|fragment| DeferredFragmentProjectionPlan {
+                    path: fragment.path.clone(),
+                    if_variable: fragment.if_variable.clone(),
+                    plans: FieldProjectionPlan::from_operation(
+                        &fragment.operation,
+                        schema_metadata,
+                    )
+                    .1,
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether the fragments are deferred with the variables of a request.
+    ///
+    /// The plan of a query is shared by all requests, so a fragment disabled with `@defer(if: $var)`
+    /// disables the incremental delivery of the whole response, the query plan then runs to completion.
+    pub fn is_enabled(&self, variables: &Option<VariablesMap>) -> bool {
+        self.fragments.iter().all(|fragment| {
+            fragment.if_variable.as_ref().is_none_or(|name| {
+                variables
+                    .as_ref()
+                    .and_then(|vars| vars.get(name))
+                    .and_then(|value| value.as_bool())
+                    .unwrap_or(true)
+            })
+        })
+    }
+}
+
+/// Client request details, owned by a stream of incrementally delivered payloads.
+struct OwnedClientRequest {
+    method: Method,
+    url: Uri,
+    headers: Arc<NtexHeaderMap>,
+    operation_name: Option<String>,
+    operation_query: String,
+    operation_kind: &'static str,
+    jwt: Arc<JwtRequestDetails>,
+    client: Arc<ClientIdentityDetails>,
+    path_params: PathParams<'static>,
+    plugin_context: Option<Arc<PluginContext>>,
+}
+
+impl OwnedClientRequest {
+    fn from_opts(opts: &QueryPlanExecutionOpts<'_>) -> Self {
+        OwnedClientRequest {
+            method: opts.client_request.method.clone(),
+            url: opts.client_request.url.clone(),
+            headers: opts.client_request.headers.clone(),
+            operation_name: opts.client_request.operation.name.map(|s| s.to_string()),
+            operation_query: opts.client_request.operation.query.to_string(),
+            operation_kind: opts.client_request.operation.kind,
+            jwt: opts.client_request.jwt.clone(),
+            client: opts.client_request.client.clone(),
+            path_params: opts.client_request.path_params.into_owned(),
+            plugin_context: opts.client_request.plugin_context.clone(),
+        }
+    }
+
+    fn details(&self) -> ClientRequestDetails<'_> {
+        ClientRequestDetails {
+            method: &self.method,
+            url: &self.url,
+            headers: self.headers.clone(),
+            operation: OperationDetails {
+                query: &self.operation_query,
+                name: self.operation_name.as_deref(),
+                kind: self.operation_kind,
+            },
+            jwt: self.jwt.clone(),
+            client: self.client.clone(),
+            path_params: self.path_params.clone(),
+            plugin_context: self.plugin_context.clone(),
+        }
+    }
+}
+
+/// Plugin state of the request, owned by a stream of incrementally delivered payloads,
+/// for the hooks of the subgraph requests and errors to run for every payload.
+struct OwnedPluginRequestState {
+    plugins: Arc<Vec<RouterPluginBoxed>>,
+    uri: Uri,
+    method: Method,
+    version: http::Version,
+    headers: NtexHeaderMap,
+    path: String,
+    query_string: String,
+    match_info: Path<Uri>,
+    context: Arc<PluginContext>,
+    request_context: SharedRequestContext,
+}
+
+impl OwnedPluginRequestState {
+    fn from_opts(opts: &QueryPlanExecutionOpts<'_>) -> Option<Self> {
+        let state = opts.plugin_req_state.as_ref()?;
+        Some(OwnedPluginRequestState {
+            plugins: state.plugins.clone(),
+            uri: state.router_http_request.uri.clone(),
+            method: state.router_http_request.method.clone(),
+            version: state.router_http_request.version,
+            headers: state.router_http_request.headers.clone(),
+            path: state.router_http_request.path.to_string(),
+            query_string: state.router_http_request.query_string.to_string(),
+            match_info: state.router_http_request.match_info.clone(),
+            context: state.context.clone(),
+            request_context: state.request_context.clone(),
+        })
+    }
+
+    fn state(&self) -> PluginRequestState<'_> {
+        PluginRequestState {
+            plugins: self.plugins.clone(),
+            router_http_request: RouterHttpRequest {
+                uri: &self.uri,
+                method: &self.method,
+                version: self.version,
+                headers: &self.headers,
+                path: &self.path,
+                query_string: &self.query_string,
+                match_info: &self.match_info,
+            },
+            context: self.context.clone(),
+            request_context: self.request_context.clone(),
+        }
+    }
+}
+
+/// Executes a plan node, returns `false` when the client went away before it completed.
+async fn execute_plan_node_until_cancelled<'exec>(
+    executor: &Executor<'exec>,
+    exec_ctx: &mut ExecutionContext<'exec>,
+    node: &'exec PlanNode,
+    cancellation_token: Option<&CancellationToken>,
+) -> bool {
+    match cancellation_token {
+        // dropping the execution of the plan aborts the pending subgraph fetches
+        Some(cancellation_token) => cancellation_token
+            .run_until_cancelled(executor.execute_plan_node(exec_ctx, node))
+            .await
+            .is_some(),
+        None => {
+            executor.execute_plan_node(exec_ctx, node).await;
+            true
+        }
+    }
+}
+
+/// Runs the error hooks of the plugins and masks the errors of a payload.
+fn finalize_errors(
+    mut errors: Vec<GraphQLError>,
+    plugin_req_state: Option<&PluginRequestState<'_>>,
+    error_masking_plan: &ErrorMaskingPlan,
+) -> Vec<GraphQLError> {
+    if let Some(plugin_req_state) = plugin_req_state.filter(|_| !errors.is_empty()) {
+        // streamed responses always use `200`, the status code set by the plugins is ignored
+        (errors, _) = handle_graphql_errors_with_plugins(
+            plugin_req_state.plugins.as_ref(),
+            plugin_req_state.context.as_ref(),
+            &plugin_req_state.request_context,
+            errors,
+            StatusCode::OK,
+        );
+    }
+    error_masking_plan.apply(&mut errors);
+    errors
+}
+
+/// Reports the actual cost of the operation, once all of its data is resolved.
+fn report_actual_cost(
+    executor: &Executor<'_>,
+    exec_ctx: &ExecutionContext<'_>,
+    operation_name: Option<&str>,
+    span: &GraphQLOperationSpan,
+) {
+    if let Some(demand_control) = executor.demand_control_context.as_ref() {
+        let actual = demand_control.calculate_actual_cost(
+            &exec_ctx.data,
+            executor.variable_values,
+            &exec_ctx.subgraph_response_cost_tracker,
+        );
+        demand_control.report_telemetry(actual, operation_name, span);
+    }
+}
+
+/// Executes a query plan with a `DeferNode` at its root, delivering the response incrementally.
+///
+/// The first payload holds the fields of the primary operation and `hasNext: true`.
+/// Every deferred node then produces a subsequent payload, with an `incremental` entry
+/// for every object at the path of its fragment, the last one of them carrying `hasNext: false`.
+///
+/// When the error policy stops the execution after an error, the payload holding the error
+/// is the last one. With the `propagate` policy its data is `null`, in the initial payload,
+/// or in every entry of a deferred fragment, as the data sent before can't be taken back.
+pub(crate) fn execute_query_plan_incrementally(
+    opts: QueryPlanExecutionOpts<'_>,
+    defer_node: &DeferNode,
+    deferred_projection_plan: Arc<DeferredProjectionPlan>,
+) -> PlanSubscriptionOutput {
+    // clone all necessary data from the context for usage in the stream.
+    // the stream will move all of these values inside its closure
+    let defer_node = defer_node.clone();
+    let owned_client_request = OwnedClientRequest::from_opts(&opts);
+    let owned_plugin_req_state = OwnedPluginRequestState::from_opts(&opts);
+    let extensions = opts.extensions.extensions;
+
+    let body = Box::pin(async_stream::stream! {
+        let client_request = owned_client_request.details();
+        let plugin_req_state = owned_plugin_req_state.as_ref().map(|state| state.state());
+
+        let data = if let Some(introspection_query) = &opts.introspection_context.query {
+            resolve_introspection(introspection_query, &opts.introspection_context)
+        } else if opts.projection_plan.is_empty() {
+            Value::Null
+        } else {
+            Value::Object(Vec::new())
+        };
+
+        let executor = Executor {
+            variable_values: &opts.variable_values.variables_map,
+            variable_definitions: opts.operation_for_plan.variable_definitions.as_deref(),
+            serialized_variables: &opts.variable_values.serialized_variables,
+            schema_metadata: &opts.introspection_context.metadata,
+            executors: &opts.executors,
+            client_request: &client_request,
+            headers_plan: &opts.headers_plan,
+            extensions_plan: &opts.extensions_plan,
+            jwt_forwarding_plan: opts.jwt_auth_forwarding.clone(),
+            dedupe_subgraph_requests: opts.operation_kind.is_query(),
+            demand_control_context: opts.demand_control_context.clone(),
+            plugin_req_state: plugin_req_state.as_ref(),
+            operation_name_factory: &opts.operation_name_factory,
+            entity_cache: opts
+                .entity_cache
+                .as_deref()
+                .filter(|_| opts.operation_kind.is_query()),
+            fetch_trace_sink: opts.fetch_trace_sink.as_ref(),
+            error_policy: opts.error_policy,
+            limits: opts.limits,
+        };
+
+        let mut exec_ctx = ExecutionContext::new(data, opts.initial_errors);
+
+        if let Some(primary) = &defer_node.primary.node {
+            if !execute_plan_node_until_cancelled(
+                &executor,
+                &mut exec_ctx,
+                primary,
+                opts.cancellation_token.as_ref(),
+            )
+            .await
+            {
+                return;
+            }
+        }
+
+        let stopped = executor.should_stop(&exec_ctx);
+        if stopped && opts.error_policy == ErrorPolicy::Propagate {
+            exec_ctx.data = Value::Null;
+        }
+
+        let errors = finalize_errors(
+            exec_ctx.take_errors(),
+            plugin_req_state.as_ref(),
+            &opts.error_masking_plan,
+        );
+        let initial_payload = project_by_operation(
+            &exec_ctx.data,
+            errors,
+            &ExecutionResultExtensions {
+                query_plan: None,
+                extensions,
+            },
+            opts.operation_type_name.as_str(),
+            &deferred_projection_plan.primary,
+            &opts.variable_values.variables_map,
+            exec_ctx.response_storage.estimate_final_response_size(),
+            &opts.introspection_context.metadata,
+        )
+        .with_plan_context(LazyPlanContext {
+            subgraph_name: || None,
+            affected_path: || None,
+        });
+
+        match initial_payload {
+            Ok(body) => {
+                yield write_initial_payload(body, !stopped && !defer_node.deferred.is_empty())
+            }
+            Err(ref err) => {
+                // fatal error, stream it and stop
+                log_plan_execution_error(err);
+                yield FailedExecutionResult {
+                    errors: vec![err.into()],
+                }.serialize();
+                return;
+            }
+        }
+
+        if stopped {
+            report_actual_cost(
+                &executor,
+                &exec_ctx,
+                opts.operation_for_plan.name.as_deref(),
+                &opts.span,
+            );
+            return;
+        }
+
+        let deferred_count = defer_node.deferred.len();
+        for (index, deferred) in defer_node.deferred.iter().enumerate() {
+            if let Some(node) = &deferred.node {
+                if !execute_plan_node_until_cancelled(
+                    &executor,
+                    &mut exec_ctx,
+                    node,
+                    opts.cancellation_token.as_ref(),
+                )
+                .await
+                {
+                    return;
+                }
+            }
+
+            let Some(fragment) = deferred_projection_plan.fragments.get(index) else {
+                continue;
+            };
+
+            let stopped = executor.should_stop(&exec_ctx);
+            let mut errors = exec_ctx.take_errors();
+            let patches = project_deferred_fragment(
+                &exec_ctx.data,
+                &mut errors,
+                &fragment.plans,
+                &fragment.path,
+                opts.operation_type_name.as_str(),
+                &opts.variable_values.variables_map,
+                &opts.introspection_context.metadata,
+            )
+            .with_plan_context(LazyPlanContext {
+                subgraph_name: || None,
+                affected_path: || None,
+            });
+            let errors = finalize_errors(errors, plugin_req_state.as_ref(), &opts.error_masking_plan);
+
+            match patches {
+                Ok(mut patches) => {
+                    if stopped && opts.error_policy == ErrorPolicy::Propagate {
+                        for patch in patches.iter_mut() {
+                            patch.data = NULL.to_vec();
+                        }
+                    }
+                    yield write_deferred_payload(
+                        &patches,
+                        &errors,
+                        deferred.label.as_deref(),
+                        !stopped && index + 1 < deferred_count,
+                    );
+                    if stopped {
+                        break;
+                    }
+                }
+                Err(ref err) => {
+                    log_plan_execution_error(err);
+                    yield FailedExecutionResult {
+                        errors: vec![err.into()],
+                    }.serialize();
+                    return;
+                }
+            }
+        }
+
+        report_actual_cost(
+                &executor,
+                &exec_ctx,
+                opts.operation_for_plan.name.as_deref(),
+                &opts.span,
+            );
+    });
+
+    PlanSubscriptionOutput {
+        body,
+        error_count: 0, // NOTE: errors are delivered within the payloads
+    }
+}
+
+/// Executes a query plan of an operation with `@stream` root fields, delivering the response incrementally.
+///
+/// The whole plan is executed before anything is sent, the streamed lists are then split
+/// at their `initialCount`. The first payload holds the initial items of every streamed list,
+/// and each streamed field produces a subsequent payload with the remaining `items`.
+///
+/// The query planner is not aware of `@stream`, so the remaining items are resolved together
+/// with the initial ones, only their delivery is split. When the error policy stops the execution
+/// after an error, the lists are not split, and the response is delivered in a single payload.
+///
+/// The initial counts are resolved before, in the order of the streamed fields,
+/// for a negative one to fail the request instead of its stream.
+pub(crate) fn execute_query_plan_with_streamed_fields(
+    opts: QueryPlanExecutionOpts<'_>,
+    initial_counts: Vec<Option<usize>>,
+) -> PlanSubscriptionOutput {
+    // clone all necessary data from the context for usage in the stream.
+    // the stream will move all of these values inside its closure
+    let root_node = opts.query_plan.node.clone();
+    let owned_client_request = OwnedClientRequest::from_opts(&opts);
+    let owned_plugin_req_state = OwnedPluginRequestState::from_opts(&opts);
+    let extensions = opts.extensions.extensions;
+
+    let body = Box::pin(async_stream::stream! {
+        let client_request = owned_client_request.details();
+        let plugin_req_state = owned_plugin_req_state.as_ref().map(|state| state.state());
+
+        let data = if let Some(introspection_query) = &opts.introspection_context.query {
+            resolve_introspection(introspection_query, &opts.introspection_context)
+        } else if opts.projection_plan.is_empty() {
+            Value::Null
+        } else {
+            Value::Object(Vec::new())
+        };
+
+        let executor = Executor {
+            variable_values: &opts.variable_values.variables_map,
+            variable_definitions: opts.operation_for_plan.variable_definitions.as_deref(),
+            serialized_variables: &opts.variable_values.serialized_variables,
+            schema_metadata: &opts.introspection_context.metadata,
+            executors: &opts.executors,
+            client_request: &client_request,
+            headers_plan: &opts.headers_plan,
+            extensions_plan: &opts.extensions_plan,
+            jwt_forwarding_plan: opts.jwt_auth_forwarding.clone(),
+            dedupe_subgraph_requests: opts.operation_kind.is_query(),
+            demand_control_context: opts.demand_control_context.clone(),
+            plugin_req_state: plugin_req_state.as_ref(),
+            operation_name_factory: &opts.operation_name_factory,
+            entity_cache: opts
+                .entity_cache
+                .as_deref()
+                .filter(|_| opts.operation_kind.is_query()),
+            fetch_trace_sink: opts.fetch_trace_sink.as_ref(),
+            error_policy: opts.error_policy,
+            limits: opts.limits,
+        };
+
+        let mut exec_ctx = ExecutionContext::new(data, opts.initial_errors);
+
+        if let Some(root_node) = &root_node {
+            if !execute_plan_node_until_cancelled(
+                &executor,
+                &mut exec_ctx,
+                root_node,
+                opts.cancellation_token.as_ref(),
+            )
+            .await
+            {
+                return;
+            }
+        }
+
+        report_actual_cost(
+                &executor,
+                &exec_ctx,
+                opts.operation_for_plan.name.as_deref(),
+                &opts.span,
+            );
+
+        let stopped = executor.should_stop(&exec_ctx);
+        if stopped && opts.error_policy == ErrorPolicy::Propagate {
+            exec_ctx.data = Value::Null;
+        }
+
+        // Items past the initial count are taken out of the response,
+        // together with the projection plan of the list they belong to.
+        let mut streamed_lists = Vec::new();
+        if let (false, Value::Object(fields)) = (stopped, &mut exec_ctx.data) {
+            for (streamed_field, initial_count) in opts.streamed_fields.iter().zip(initial_counts) {
+                let Some(initial_count) = initial_count else {
+                    continue;
+                };
+                let Some(plan) = opts
+                    .projection_plan
+                    .iter()
+                    .find(|plan| plan.response_key == streamed_field.response_key)
+                else {
+                    continue;
+                };
+                let Ok(index) = fields
+                    .binary_search_by_key(&streamed_field.response_key.as_str(), |(key, _)| *key)
+                else {
+                    continue;
+                };
+
+                if let Value::Array(items) = &mut fields[index].1 {
+                    if items.len() > initial_count {
+                        let remaining = items.split_off(initial_count);
+                        streamed_lists.push((streamed_field, plan, initial_count, remaining));
+                    }
+                }
+            }
+        }
+
+        let errors = finalize_errors(
+            exec_ctx.take_errors(),
+            plugin_req_state.as_ref(),
+            &opts.error_masking_plan,
+        );
+        let initial_payload = project_by_operation(
+            &exec_ctx.data,
+            errors,
+            &ExecutionResultExtensions {
+                query_plan: None,
+                extensions,
+            },
+            opts.operation_type_name.as_str(),
+            &opts.projection_plan,
+            &opts.variable_values.variables_map,
+            exec_ctx.response_storage.estimate_final_response_size(),
+            &opts.introspection_context.metadata,
+        )
+        .with_plan_context(LazyPlanContext {
+            subgraph_name: || None,
+            affected_path: || None,
+        });
+
+        match initial_payload {
+            Ok(body) => yield write_initial_payload(body, !streamed_lists.is_empty()),
+            Err(ref err) => {
+                // fatal error, stream it and stop
+                log_plan_execution_error(err);
+                yield FailedExecutionResult {
+                    errors: vec![err.into()],
+                }.serialize();
+                return;
+            }
+        }
+
+        let streamed_count = streamed_lists.len();
+        for (index, (streamed_field, plan, initial_count, remaining)) in
+            streamed_lists.into_iter().enumerate()
+        {
+            let mut errors = Vec::new();
+            let items = project_stream_items(
+                &Value::Array(remaining),
+                &mut errors,
+                plan,
+                opts.operation_type_name.as_str(),
+                &opts.variable_values.variables_map,
+                &opts.introspection_context.metadata,
+            )
+            .with_plan_context(LazyPlanContext {
+                subgraph_name: || None,
+                affected_path: || None,
+            });
+            let errors = finalize_errors(errors, plugin_req_state.as_ref(), &opts.error_masking_plan);
+
+            match items {
+                Ok(items) => yield write_stream_payload(
+                    items,
+                    &errors,
+                    &streamed_field.response_key,
+                    initial_count,
+                    streamed_field.label.as_deref(),
+                    index + 1 < streamed_count,
+                ),
+                Err(ref err) => {
+                    log_plan_execution_error(err);
+                    yield FailedExecutionResult {
+                        errors: vec![err.into()],
+                    }.serialize();
+                    return;
+                }
+            }
+        }
+    });
+
+    PlanSubscriptionOutput {
+        body,
+        error_count: 0, // NOTE: errors are delivered within the payloads
+    }
+}
+
+/// Appends `hasNext` to a projected response, `{"data":{..}}` becomes `{"data":{..},"hasNext":true}`.
+fn write_initial_payload(mut body: Vec<u8>, has_next: bool) -> Vec<u8> {
+    // projected responses always end with a closing brace
+    body.pop();
+    body.extend_from_slice(COMMA);
+    write_has_next(&mut body, has_next);
+    body.extend_from_slice(CLOSE_BRACE);
+    body
+}
+
+/// Writes the patches of a deferred fragment as `incremental` entries,
+/// `{"incremental":[{"data":{..},"path":["me"],"label":"slow"}],"hasNext":false}`.
+///
+/// The errors are part of the first entry. Without entries, the fragment was not reached
+/// (a `null` object on its path), so the errors raised by the subgraphs are not delivered,
+/// they belong to fields that are not part of the response.
+fn write_deferred_payload(
+    patches: &[DeferredPatch],
+    errors: &[GraphQLError],
+    label: Option<&str>,
+    has_next: bool,
+) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(
+        patches
+            .iter()
+            .map(|patch| patch.data.len() + 64)
+            .sum::<usize>()
+            + 32,
+    );
+    if patches.is_empty() {
+        payload.extend_from_slice(OPEN_BRACE);
+    } else {
+        payload.extend_from_slice(INCREMENTAL);
+        for (index, patch) in patches.iter().enumerate() {
+            if index > 0 {
+                payload.extend_from_slice(COMMA);
+            }
+            payload.extend_from_slice(DATA);
+            payload.extend_from_slice(&patch.data);
+            if index == 0 && !errors.is_empty() {
+                if let Ok(serialized_errors) = sonic_rs::to_vec(errors) {
+                    payload.extend_from_slice(COMMA);
+                    payload.extend_from_slice(QUOTE);
+                    payload.extend_from_slice(b"errors");
+                    payload.extend_from_slice(QUOTE);
+                    payload.extend_from_slice(COLON);
+                    payload.extend_from_slice(&serialized_errors);
+                }
+            }
+            payload.extend_from_slice(COMMA);
+            payload.extend_from_slice(PATH);
+            if let Ok(serialized_path) = sonic_rs::to_vec(&patch.path) {
+                payload.extend_from_slice(&serialized_path);
+            }
+            write_label(&mut payload, label);
+            payload.extend_from_slice(CLOSE_BRACE);
+        }
+        payload.extend_from_slice(CLOSE_BRACKET);
+        payload.extend_from_slice(COMMA);
+    }
+
+    write_has_next(&mut payload, has_next);
+    payload.extend_from_slice(CLOSE_BRACE);
+    payload
+}
+
+/// Writes the remaining items of a streamed list as an `incremental` entry,
+/// `[..]` becomes `{"incremental":[{"items":[..],"path":["field",2]}],"hasNext":false}`.
+fn write_stream_payload(
+    items: Vec<u8>,
+    errors: &[GraphQLError],
+    response_key: &str,
+    initial_count: usize,
+    label: Option<&str>,
+    has_next: bool,
+) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(items.len() + 64);
+    payload.extend_from_slice(INCREMENTAL);
+    payload.extend_from_slice(ITEMS);
+    payload.extend_from_slice(&items);
+    if !errors.is_empty() {
+        if let Ok(serialized_errors) = sonic_rs::to_vec(errors) {
+            payload.extend_from_slice(COMMA);
+            payload.extend_from_slice(QUOTE);
+            payload.extend_from_slice(b"errors");
+            payload.extend_from_slice(QUOTE);
+            payload.extend_from_slice(COLON);
+            payload.extend_from_slice(&serialized_errors);
+        }
+    }
+    payload.extend_from_slice(COMMA);
+    payload.extend_from_slice(PATH);
+    payload.extend_from_slice(OPEN_BRACKET);
+    write_and_escape_string(&mut payload, response_key);
+    payload.extend_from_slice(COMMA);
+    write_u64(&mut payload, initial_count as u64);
+    payload.extend_from_slice(CLOSE_BRACKET);
+    write_label(&mut payload, label);
+    payload.extend_from_slice(CLOSE_BRACE);
+    payload.extend_from_slice(CLOSE_BRACKET);
+    payload.extend_from_slice(COMMA);
+    write_has_next(&mut payload, has_next);
+    payload.extend_from_slice(CLOSE_BRACE);
+    payload
+}
+
+fn write_label(buffer: &mut Vec<u8>, label: Option<&str>) {
+    if let Some(label) = label {
+        buffer.extend_from_slice(COMMA);
+        buffer.extend_from_slice(QUOTE);
+        buffer.extend_from_slice(b"label");
+        buffer.extend_from_slice(QUOTE);
+        buffer.extend_from_slice(COLON);
+        write_and_escape_string(buffer, label);
+    }
+}
+
+fn write_has_next(buffer: &mut Vec<u8>, has_next: bool) {
+    buffer.extend_from_slice(HAS_NEXT);
+    buffer.extend_from_slice(if has_next { TRUE } else { FALSE });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use hive_router_query_planner::utils::parsing::safe_parse_operation;
+
+    use super::{
+        write_deferred_payload, write_initial_payload, write_stream_payload, StreamedField,
+    };
+    use crate::{
+        projection::response::DeferredPatch,
+        response::graphql_error::{GraphQLError, GraphQLErrorPath, GraphQLErrorPathSegment},
+    };
+
+    #[test]
+    fn writes_initial_payload_with_has_next() {
+        let payload = write_initial_payload(br#"{"data":{"a":1}}"#.to_vec(), true);
+        assert_eq!(
+            std::str::from_utf8(&payload).unwrap(),
+            r#"{"data":{"a":1},"hasNext":true}"#
+        );
+    }
+
+    #[test]
+    fn writes_deferred_payload_with_label() {
+        let patch = |index: usize, data: &[u8]| DeferredPatch {
+            path: GraphQLErrorPath {
+                segments: vec![
+                    GraphQLErrorPathSegment::String("users".to_string()),
+                    GraphQLErrorPathSegment::Index(index),
+                ],
+            },
+            data: data.to_vec(),
+        };
+        let payload = write_deferred_payload(
+            &[patch(0, br#"{"name":"a"}"#), patch(1, b"null")],
+            &[GraphQLError::from("oops")],
+            Some("slow"),
+            false,
+        );
+        assert_eq!(
+            std::str::from_utf8(&payload).unwrap(),
+            r#"{"incremental":[{"data":{"name":"a"},"errors":[{"message":"oops"}],"path":["users",0],"label":"slow"},{"data":null,"path":["users",1],"label":"slow"}],"hasNext":false}"#
+        );
+    }
+
+    #[test]
+    fn writes_deferred_payload_without_patches() {
+        let payload = write_deferred_payload(&[], &[GraphQLError::from("oops")], None, true);
+        assert_eq!(
+            std::str::from_utf8(&payload).unwrap(),
+            r#"{"hasNext":true}"#
+        );
+    }
+
+    #[test]
+    fn writes_stream_payload() {
+        let payload = write_stream_payload(
+            br#"[{"id":3},{"id":4}]"#.to_vec(),
+            &[],
+            "products",
+            2,
+            Some("rest"),
+            false,
+        );
+        assert_eq!(
+            std::str::from_utf8(&payload).unwrap(),
+            r#"{"incremental":[{"items":[{"id":3},{"id":4}],"path":["products",2],"label":"rest"}],"hasNext":false}"#
+        );
+    }
+
+    #[test]
+    fn writes_stream_payload_with_errors() {
+        let payload = write_stream_payload(
+            b"[null]".to_vec(),
+            &[GraphQLError::from("oops")],
+            "products",
+            0,
+            None,
+            true,
+        );
+        assert_eq!(
+            std::str::from_utf8(&payload).unwrap(),
+            r#"{"incremental":[{"items":[null],"errors":[{"message":"oops"}],"path":["products",0]}],"hasNext":true}"#
+        );
+    }
+
+    #[test]
+    fn collects_streamed_root_fields() {
+        let document = safe_parse_operation(
+            r#"
+            query A($count: Int, $enabled: Boolean!) {
+              all: products @stream(label: "all", initialCount: $count) { id }
+              some: products @stream(initialCount: 1, if: $enabled) { id }
+              users { friends @stream { id } }
+              me { id }
+            }
+            query B { products @stream(initialCount: 5) { id } }
+            "#,
+        )
+        .unwrap();
+
+        let fields = StreamedField::collect_from_document(&document, Some("A"));
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].response_key, "all");
+        assert_eq!(fields[0].label.as_deref(), Some("all"));
+        assert_eq!(fields[1].response_key, "some");
+
+        let variables = Some(HashMap::from([
+            ("count".to_string(), sonic_rs::json!(3)),
+            ("enabled".to_string(), sonic_rs::json!(false)),
+        ]));
+        assert_eq!(
+            fields[0].resolve_initial_count(&variables).unwrap(),
+            Some(3)
+        );
+        assert_eq!(fields[1].resolve_initial_count(&variables).unwrap(), None);
+        assert_eq!(fields[0].resolve_initial_count(&None).unwrap(), Some(0));
+        assert_eq!(fields[1].resolve_initial_count(&None).unwrap(), Some(1));
+
+        let fields = StreamedField::collect_from_document(&document, Some("B"));
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].resolve_initial_count(&None).unwrap(), Some(5));
+    }
+
+    #[test]
+    fn rejects_negative_initial_counts() {
+        let document = safe_parse_operation(
+            r#"
+            query ($count: Int) {
+              all: products @stream(initialCount: $count) { id }
+              some: products @stream(initialCount: -1) { id }
+            }
+            "#,
+        )
+        .unwrap();
+        let fields = StreamedField::collect_from_document(&document, None);
+
+        let variables = Some(HashMap::from([("count".to_string(), sonic_rs::json!(-2))]));
+        let err = fields[0].resolve_initial_count(&variables).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Argument \"initialCount\" of @stream on field \"all\" must not be negative"
+        );
+        assert_eq!(err.error_code(), "BAD_USER_INPUT");
+
+        let err = fields[1].resolve_initial_count(&None).unwrap_err();
+        assert_eq!(err.error_code(), "BAD_USER_INPUT");
+    }
+
+    #[test]
+    fn ignores_streamed_fields_of_subscriptions() {
+        let document = safe_parse_operation("subscription { products @stream { id } }").unwrap();
+        assert!(StreamedField::collect_from_document(&document, None).is_empty());
+    }
+}
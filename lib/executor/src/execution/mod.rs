@@ -1,6 +1,10 @@
 pub mod client_request_details;
+pub mod contextual_arguments;
 pub mod demand_control;
+pub mod entity_cache;
 pub mod error;
+pub mod fetch_traces;
+pub mod incremental;
 pub mod jwt_forward;
 pub mod operation_name;
 pub mod plan;
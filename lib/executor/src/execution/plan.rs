@@ -1,26 +1,31 @@
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeSet, HashMap};
+use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Instant;
 use std::vec;
 
-use ahash::{HashMap as AHashMap, HashMapExt};
-use bytes::BufMut;
+use ahash::{HashMap as AHashMap, HashMapExt, HashSet as AHashSet, HashSetExt};
+use bytes::{BufMut, Bytes};
 use futures::TryFutureExt;
 use futures::{
-    future::BoxFuture,
+    future::{self, BoxFuture},
     stream::{BoxStream, FuturesUnordered},
     FutureExt, StreamExt,
 };
+use hive_router_config::errors::ErrorPolicy;
 use hive_router_internal::graphql::ObservedError;
 use hive_router_internal::telemetry::metrics::graphql_metrics::GraphQLErrorMetricsRecorder;
 use hive_router_internal::telemetry::traces::spans::graphql::{
     GraphQLOperationSpan, GraphQLSpanOperationIdentity, GraphQLSubgraphOperationSpan,
 };
 use hive_router_query_planner::ast::operation::SubgraphFetchOperation;
+use hive_router_query_planner::ast::selection_set::SelectionSet;
 use hive_router_query_planner::planner::plan_nodes::{CustomScalarPaths, FetchNode, FlattenNode};
 use hive_router_query_planner::planner::query_plan::QUERY_PLAN_KIND;
 use hive_router_query_planner::{
-    ast::operation::OperationDefinition,
+    ast::operation::{OperationDefinition, VariableDefinition},
+    ast::value::Value as AstValue,
     planner::plan_nodes::{
         ConditionNode, EntityBatch, EntityBatchAlias, FetchRewrite, FlattenNodePath, PlanNode,
         QueryPlan, SequenceNode,
@@ -29,17 +34,34 @@ use hive_router_query_planner::{
 };
 use http::{HeaderMap, StatusCode};
 use serde::Serialize;
-use sonic_rs::{JsonValueTrait, ValueRef};
+use sonic_rs::JsonValueTrait;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
 use tracing::Instrument;
 
 use crate::execution::client_request_details::OperationDetails;
+use crate::execution::contextual_arguments::{
+    group_entities_by_context_values, ContextValueGroups,
+};
 use crate::execution::demand_control::DemandControlExecutionContext;
+use crate::execution::entity_cache::{
+    CachedEntities, EntityCache, EntityCacheLookup, EntityCacheWrite,
+};
+use crate::execution::fetch_traces::{
+    FetchTraceSink, FTV1_REQUEST_HEADER, FTV1_REQUEST_HEADER_VALUE,
+};
+use crate::execution::incremental::{
+    execute_query_plan_incrementally, execute_query_plan_with_streamed_fields,
+    DeferredProjectionPlan, StreamedField,
+};
 use crate::execution::operation_name::OperationNameFactory;
 use crate::headers::cache_control;
 use crate::{
     execution::{
         client_request_details::ClientRequestDetails,
-        error::{IntoPlanExecutionError, LazyPlanContext, PlanExecutionError},
+        error::{
+            IntoPlanExecutionError, LazyPlanContext, PlanExecutionError, PlanExecutionErrorKind,
+        },
         jwt_forward::JwtAuthForwardingPlan,
         rewrites::FetchRewriteExt,
     },
@@ -57,6 +79,9 @@ use crate::{
             OnExecuteResponse, OnExecuteStartHookPayload,
         },
         on_graphql_error::handle_graphql_errors_with_plugins,
+        on_subscription::{
+            OnSubscriptionStartHookPayload, OnSubscriptionStartHookResult, SubscriptionHooks,
+        },
     },
     introspection::{
         resolve::{resolve_introspection, IntrospectionContext},
@@ -69,8 +94,10 @@ use crate::{
         plan::FieldProjectionPlan, request::project_requires, response::project_by_operation,
     },
     response::{
+        error_masking::ErrorMaskingPlan,
+        error_status_codes::ErrorStatusCodes,
         graphql_error::{GraphQLError, GraphQLErrorPath, GraphQLErrorPathSegment},
-        merge::deep_merge,
+        merge::{deep_merge, deep_merge_batch},
         subgraph_response::SubgraphResponse,
         value::Value,
     },
@@ -78,6 +105,7 @@ use crate::{
         consts::{CLOSE_BRACKET, OPEN_BRACKET},
         traverse::{traverse_and_callback, traverse_and_callback_mut},
     },
+    variables::serialized::{RawVariables, SerializedVariables},
 };
 
 pub type VariablesMap = HashMap<String, sonic_rs::Value>;
@@ -101,9 +129,18 @@ impl ExecutionResultExtensions<'_> {
 #[derive(Clone, Debug, Default)]
 pub struct CoerceVariablesPayload {
     pub variables_map: Option<VariablesMap>,
+    /// The JSON of the variables, shared by the subgraph requests of the operation.
+    pub serialized_variables: SerializedVariables,
 }
 
 impl CoerceVariablesPayload {
+    pub fn new(variables_map: Option<VariablesMap>, raw_variables: RawVariables) -> Self {
+        Self {
+            serialized_variables: SerializedVariables::new(&variables_map, raw_variables),
+            variables_map,
+        }
+    }
+
     pub fn variable_equals_true(&self, name: &str) -> bool {
         self.variables_map
             .as_ref()
@@ -119,6 +156,12 @@ pub struct QueryPlanExecutionOpts<'exec> {
     pub projection_plan: Arc<Vec<FieldProjectionPlan>>,
     pub headers_plan: Arc<HeaderRulesPlan>,
     pub extensions_plan: Arc<ExtensionsPlan>,
+    /// Masks and redacts the errors of the final response.
+    pub error_masking_plan: Arc<ErrorMaskingPlan>,
+    /// HTTP status codes of the response by error code.
+    pub error_status_codes: Arc<ErrorStatusCodes>,
+    /// How the errors occurring during the execution affect the response.
+    pub error_policy: ErrorPolicy,
     pub variable_values: Arc<CoerceVariablesPayload>,
     pub extensions: ExecutionResultExtensions<'exec>,
     pub client_request: Arc<ClientRequestDetails<'exec>>,
@@ -134,6 +177,34 @@ pub struct QueryPlanExecutionOpts<'exec> {
     pub plugin_req_state: Option<PluginRequestState<'exec>>,
     pub operation_name_factory: OperationNameFactory,
     pub response_header_sink: ResponseHeaderSink,
+    /// Whether the client accepts incrementally delivered responses (`@defer`).
+    /// When disabled, deferred nodes are resolved as part of a single response.
+    pub incremental_delivery: bool,
+    /// Projection plans of the primary operation and deferred fragments of a query with `@defer`,
+    /// only used with incremental delivery.
+    pub deferred_projection_plan: Option<Arc<DeferredProjectionPlan>>,
+    /// Root fields annotated with `@stream`, only used with incremental delivery.
+    pub streamed_fields: Arc<Vec<StreamedField>>,
+    /// The cache of the entities resolved by subgraphs, only used for queries.
+    pub entity_cache: Option<Arc<EntityCache>>,
+    /// Collects the timings of the subgraph fetches, only set for operations reported with traces.
+    pub fetch_trace_sink: Option<FetchTraceSink>,
+    /// Cancelled when the client closes the connection.
+    /// The pending subgraph fetches are dropped, and the remaining plan nodes are not executed.
+    pub cancellation_token: Option<CancellationToken>,
+    /// Limits of the subgraph requests and of the final response.
+    pub limits: ExecutionLimits,
+}
+
+/// Limits applied while executing a query plan.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExecutionLimits {
+    /// The maximum size, in bytes, of the serialized response.
+    pub max_response_size: Option<usize>,
+    /// The maximum number of entity representations sent in a single subgraph request.
+    pub max_entities_per_fetch: Option<NonZeroUsize>,
+    /// The maximum size, in bytes, of the entity representations sent in a single subgraph request.
+    pub max_representations_size: Option<usize>,
 }
 
 pub struct PlanSubscriptionOutput {
@@ -191,7 +262,7 @@ fn early_http_response_into_execution_output(
     }
 }
 
-fn log_plan_execution_error(error: &PlanExecutionError) {
+pub(crate) fn log_plan_execution_error(error: &PlanExecutionError) {
     if let Some(subgraph_name) = error.subgraph_name() {
         tracing::error!(
             "Error executing plan with subgraph '{}': {}",
@@ -270,10 +341,13 @@ pub async fn execute_query_plan<'exec>(
             query: fetch_node.operation.document_str.as_str(),
             document_name_write_pos: fetch_node.operation.name_write_position,
             dedupe: false,
+            idempotent: false,
             operation_name: opts
                 .operation_name_factory
                 .generate(&fetch_node.service_name, fetch_node.id),
             variables: variable_refs,
+            serialized_variables: None,
+            overridden_variables: Default::default(),
             headers: headers_map,
             raw_variable_values: None,
             extensions: None,
@@ -302,6 +376,27 @@ pub async fn execute_query_plan<'exec>(
             );
         }
 
+        if let Some(plugin_req_state) = opts.plugin_req_state.as_ref() {
+            for plugin in plugin_req_state.plugins.as_ref() {
+                let start_payload = OnSubscriptionStartHookPayload {
+                    router_http_request: &plugin_req_state.router_http_request,
+                    context: &plugin_req_state.context,
+                    request_context: plugin_req_state
+                        .request_context
+                        .for_plugin::<hooks::OnSubscription>(),
+                    subgraph_name: &fetch_node.service_name,
+                    operation_for_plan: &opts.operation_for_plan,
+                    variable_values: &opts.variable_values.variables_map,
+                };
+                match plugin.on_subscription_start(start_payload).await {
+                    OnSubscriptionStartHookResult::Proceed => {}
+                    OnSubscriptionStartHookResult::EndWithResponse(response) => {
+                        return Ok(QueryPlanExecutionResult::Single(response));
+                    }
+                }
+            }
+        }
+
         let mut response_stream = opts
             .executors
             .subscribe(
@@ -324,10 +419,17 @@ pub async fn execute_query_plan<'exec>(
         let client_operation_query = opts.client_request.operation.query.to_string();
         let client_operation_kind = opts.client_request.operation.kind;
         let client_jwt = opts.client_request.jwt.clone();
+        let client_identity = opts.client_request.client.clone();
         let client_path_params = opts.client_request.path_params.into_owned();
+        let client_plugin_context = opts.client_request.plugin_context.clone();
         let response_header_sink = opts.response_header_sink.clone();
 
         let operation_name_factory = opts.operation_name_factory.clone();
+        // lives in the stream, so the end hooks run once it is done or dropped
+        let mut subscription_hooks = opts
+            .plugin_req_state
+            .as_ref()
+            .map(|plugin_req_state| SubscriptionHooks::new(plugin_req_state, &subgraph_name));
 
         let body_stream = Box::pin(async_stream::stream! {
             while let Some(stream_result) = response_stream.next().await {
@@ -350,6 +452,9 @@ pub async fn execute_query_plan<'exec>(
                         // we cannot guarantee that the subgraph will recover and clients might
                         // simply ignore errors wasting the router's resources
                         log_plan_execution_error(err);
+                        if let Some(subscription_hooks) = subscription_hooks.as_mut() {
+                            subscription_hooks.on_error();
+                        }
                         yield FailedExecutionResult {
                             errors: vec![err.into()],
                         }.serialize();
@@ -366,6 +471,9 @@ pub async fn execute_query_plan<'exec>(
                     projection_plan: opts.projection_plan.clone(),
                     headers_plan: opts.headers_plan.clone(),
                     extensions_plan: opts.extensions_plan.clone(),
+                    error_masking_plan: opts.error_masking_plan.clone(),
+                    error_status_codes: opts.error_status_codes.clone(),
+                    error_policy: opts.error_policy,
                     variable_values: opts.variable_values.clone(),
                     extensions: ExecutionResultExtensions::default(),
                     client_request: ClientRequestDetails {
@@ -378,7 +486,9 @@ pub async fn execute_query_plan<'exec>(
                             kind: client_operation_kind,
                         },
                         jwt: client_jwt.clone(),
+                        client: client_identity.clone(),
                         path_params: client_path_params.clone(),
+                        plugin_context: client_plugin_context.clone(),
                     }.into(),
                     introspection_context: opts.introspection_context.clone(),
                     operation_type_name: opts.operation_type_name.clone(),
@@ -393,12 +503,30 @@ pub async fn execute_query_plan<'exec>(
                     operation_name_factory: operation_name_factory.clone(),
                     demand_control_context: opts.demand_control_context.clone(),
                     response_header_sink: response_header_sink.clone(),
+                    incremental_delivery: false,
+                    deferred_projection_plan: None,
+                    streamed_fields: Default::default(),
+                    entity_cache: None,
+                    fetch_trace_sink: None,
+                    // the stream is dropped when the client goes away
+                    cancellation_token: None,
+                    // the events are not limited by the maximum response size
+                    limits: ExecutionLimits {
+                        max_response_size: None,
+                        ..opts.limits
+                    },
                 };
                 match execute_query_plan_with_data(response.data, opts).await {
-                    Ok(result) => yield result.body,
+                    Ok(result) => match subscription_hooks.as_mut() {
+                        Some(subscription_hooks) => yield subscription_hooks.on_event(result.body),
+                        None => yield result.body,
+                    },
                     Err(ref err) => {
                         // fatal error, stream it and stop
                         log_plan_execution_error(err);
+                        if let Some(subscription_hooks) = subscription_hooks.as_mut() {
+                            subscription_hooks.on_error();
+                        }
                         yield FailedExecutionResult {
                             errors: vec![err.into()],
                         }.serialize();
@@ -406,6 +534,9 @@ pub async fn execute_query_plan<'exec>(
                     }
                 }
             }
+            if let Some(subscription_hooks) = subscription_hooks.as_mut() {
+                subscription_hooks.on_complete();
+            }
         });
 
         return Ok(QueryPlanExecutionResult::Stream(PlanSubscriptionOutput {
@@ -416,6 +547,34 @@ pub async fn execute_query_plan<'exec>(
 
     // query or mutation
 
+    // the arguments of `@stream` are checked even when the lists are delivered in full
+    let initial_counts = opts
+        .streamed_fields
+        .iter()
+        .map(|streamed_field| {
+            streamed_field.resolve_initial_count(&opts.variable_values.variables_map)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if opts.incremental_delivery {
+        if let (Some(PlanNode::Defer(defer_node)), Some(deferred_projection_plan)) =
+            (&opts.query_plan.node, &opts.deferred_projection_plan)
+        {
+            if deferred_projection_plan.is_enabled(&opts.variable_values.variables_map) {
+                let deferred_projection_plan = deferred_projection_plan.clone();
+                return Ok(QueryPlanExecutionResult::Stream(
+                    execute_query_plan_incrementally(opts, defer_node, deferred_projection_plan),
+                ));
+            }
+        }
+
+        if !opts.streamed_fields.is_empty() {
+            return Ok(QueryPlanExecutionResult::Stream(
+                execute_query_plan_with_streamed_fields(opts, initial_counts),
+            ));
+        }
+    }
+
     let introspection_context_clone = Arc::clone(&opts.introspection_context);
     let data = if let Some(introspection_query) = &introspection_context_clone.query {
         resolve_introspection(introspection_query, &introspection_context_clone)
@@ -494,6 +653,8 @@ async fn execute_query_plan_with_data<'exec>(
     // We can directly create `Executor` instance here
     let executor = Executor {
         variable_values: &opts.variable_values.variables_map,
+        variable_definitions: opts.operation_for_plan.variable_definitions.as_deref(),
+        serialized_variables: &opts.variable_values.serialized_variables,
         schema_metadata: &opts.introspection_context.metadata,
         executors: &opts.executors,
         client_request: &opts.client_request,
@@ -504,10 +665,35 @@ async fn execute_query_plan_with_data<'exec>(
         demand_control_context: opts.demand_control_context.clone(),
         plugin_req_state: opts.plugin_req_state.as_ref(),
         operation_name_factory: &opts.operation_name_factory,
+        entity_cache: opts
+            .entity_cache
+            .as_deref()
+            .filter(|_| opts.operation_kind.is_query()),
+        fetch_trace_sink: opts.fetch_trace_sink.as_ref(),
+        error_policy: opts.error_policy,
+        limits: opts.limits,
     };
 
     if let Some(node) = &opts.query_plan.node {
-        executor.execute_plan_node(&mut exec_ctx, node).await;
+        match opts.cancellation_token.as_ref() {
+            Some(cancellation_token) => {
+                // dropping the execution of the plan aborts the pending subgraph fetches
+                if cancellation_token
+                    .run_until_cancelled(executor.execute_plan_node(&mut exec_ctx, node))
+                    .await
+                    .is_none()
+                {
+                    return Err(PlanExecutionError::new(
+                        PlanExecutionErrorKind::Cancelled,
+                        LazyPlanContext {
+                            subgraph_name: || None,
+                            affected_path: || None,
+                        },
+                    ));
+                }
+            }
+            None => executor.execute_plan_node(&mut exec_ctx, node).await,
+        }
     }
 
     let error_count = exec_ctx.errors.len(); // Added for usage reporting
@@ -527,6 +713,10 @@ async fn execute_query_plan_with_data<'exec>(
         }
     }
 
+    if opts.error_policy == ErrorPolicy::Propagate && exec_ctx.has_execution_errors() {
+        exec_ctx.data = Value::Null;
+    }
+
     let mut data = exec_ctx.data;
     let mut errors = exec_ctx.errors;
     let mut response_size_estimate = exec_ctx.response_storage.estimate_final_response_size();
@@ -625,6 +815,10 @@ async fn execute_query_plan_with_data<'exec>(
     let mut status_code = StatusCode::OK;
 
     if !errors.is_empty() {
+        if let Some(mapped_status_code) = opts.error_status_codes.status_code_for(&errors) {
+            status_code = mapped_status_code;
+        }
+
         if let Some(plugin_req_state) = opts.plugin_req_state.as_ref() {
             let (new_errors, new_status_code) = handle_graphql_errors_with_plugins(
                 plugin_req_state.plugins.as_ref(),
@@ -639,6 +833,9 @@ async fn execute_query_plan_with_data<'exec>(
         }
     }
 
+    // Applied last, so plugins see the original errors
+    opts.error_masking_plan.apply(&mut errors);
+
     let body = project_by_operation(
         &data,
         errors,
@@ -654,6 +851,21 @@ async fn execute_query_plan_with_data<'exec>(
         affected_path: || None,
     })?;
 
+    if let Some(max) = opts.limits.max_response_size {
+        if body.len() > max {
+            return Err(PlanExecutionError::new(
+                PlanExecutionErrorKind::ResponseTooLarge {
+                    size: body.len(),
+                    max,
+                },
+                LazyPlanContext {
+                    subgraph_name: || None,
+                    affected_path: || None,
+                },
+            ));
+        }
+    }
+
     Ok(PlanExecutionOutput {
         body,
         error_count,
@@ -663,6 +875,9 @@ async fn execute_query_plan_with_data<'exec>(
 
 pub struct Executor<'exec> {
     pub variable_values: &'exec Option<VariablesMap>,
+    /// Definitions of the operation's variables, their default values apply to omitted variables.
+    pub variable_definitions: Option<&'exec [VariableDefinition]>,
+    pub serialized_variables: &'exec SerializedVariables,
     pub schema_metadata: &'exec SchemaMetadata,
     pub executors: &'exec SubgraphExecutorMap,
     pub client_request: &'exec ClientRequestDetails<'exec>,
@@ -673,6 +888,10 @@ pub struct Executor<'exec> {
     pub demand_control_context: Option<Arc<DemandControlExecutionContext>>,
     pub plugin_req_state: Option<&'exec PluginRequestState<'exec>>,
     pub operation_name_factory: &'exec OperationNameFactory,
+    pub entity_cache: Option<&'exec EntityCache>,
+    pub fetch_trace_sink: Option<&'exec FetchTraceSink>,
+    pub error_policy: ErrorPolicy,
+    pub limits: ExecutionLimits,
 }
 
 pub enum ExecutionJob<'exec> {
@@ -693,6 +912,10 @@ pub enum ExecutionJob<'exec> {
         representation_hashes: Vec<Option<u64>>,
         representation_hash_to_index: AHashMap<u64, usize>,
         output_rewrites: Option<&'exec [FetchRewrite]>,
+        // Entities served from the entity cache, not sent to the subgraph
+        cached_entities: Option<Box<CachedEntities<'exec>>>,
+        // Entity cache writes, aligned with the entities of the response
+        entity_cache_writes: Vec<Option<EntityCacheWrite>>,
     },
     BatchFetch {
         subgraph_name: &'exec str,
@@ -767,6 +990,8 @@ impl<'exec> ExecutionJob<'exec> {
 }
 
 struct PrepareExecutionJobOpts<'exec> {
+    // The id of the fetch node
+    node_id: i64,
     // The name of the subgraph
     subgraph_name: &'exec str,
     // Variable usages
@@ -787,43 +1012,178 @@ struct PrepareExecutionJobOpts<'exec> {
     affected_path: Option<&'exec FlattenNodePath>,
 }
 
+/// Entities of a flatten fetch sharing the values of contextual arguments.
+struct ContextGroup<'a, 'exec> {
+    // The group of each entity, in the order of `traverse_and_callback`
+    entity_groups: &'a [usize],
+    group_index: usize,
+    raw_variable_values: Vec<(&'exec str, Vec<u8>)>,
+}
+
+impl ContextGroup<'_, '_> {
+    fn contains(&self, position: usize) -> bool {
+        self.entity_groups.get(position) == Some(&self.group_index)
+    }
+}
+
+/// The entity representations of a flatten fetch, split into multiple subgraph requests
+/// following `max_entities_per_fetch` and `max_representations_size`.
+struct RepresentationChunks {
+    limits: ExecutionLimits,
+    // The serialized representations of every request
+    chunks: Vec<Vec<u8>>,
+    // The index of the first representation of every request
+    offsets: Vec<usize>,
+}
+
+impl RepresentationChunks {
+    fn new(limits: ExecutionLimits) -> Self {
+        Self {
+            limits,
+            chunks: vec![OPEN_BRACKET.to_vec()],
+            offsets: vec![0],
+        }
+    }
+
+    /// Serializes the representation at `index` with `project`,
+    /// and starts a new request when the current one reaches a limit.
+    fn project(&mut self, index: usize, project: impl FnOnce(&mut Vec<u8>, bool) -> bool) -> bool {
+        let mut count = index - self.current_offset();
+        let is_new_chunk = count > 0
+            && self
+                .limits
+                .max_entities_per_fetch
+                .is_some_and(|max| count >= max.get());
+        if is_new_chunk {
+            self.start_chunk(index);
+            count = 0;
+        }
+
+        let max_size = self.limits.max_representations_size;
+        let chunk = self.current_chunk();
+        let len_before = chunk.len();
+        if !project(chunk, count == 0) {
+            // Drops what was written of the representation
+            chunk.truncate(len_before);
+            if is_new_chunk {
+                self.chunks.pop();
+                self.offsets.pop();
+            }
+            return false;
+        }
+
+        // The closing bracket is counted as well
+        if count > 0 && max_size.is_some_and(|max| chunk.len() + 1 > max) {
+            // Moves the representation to a new request, without its leading comma
+            let representation = chunk.split_off(len_before + 1);
+            chunk.truncate(len_before);
+            self.start_chunk(index);
+            self.current_chunk().extend_from_slice(&representation);
+        }
+
+        true
+    }
+
+    fn current_offset(&self) -> usize {
+        self.offsets.last().copied().unwrap_or_default()
+    }
+
+    fn current_chunk(&mut self) -> &mut Vec<u8> {
+        self.chunks
+            .last_mut()
+            .expect("representation chunks always contain a chunk")
+    }
+
+    fn start_chunk(&mut self, index: usize) {
+        self.chunks.push(OPEN_BRACKET.to_vec());
+        self.offsets.push(index);
+    }
+
+    /// The request containing the representation at `index`, and its index in that request.
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let chunk = self.offsets.partition_point(|offset| *offset <= index) - 1;
+        (chunk, index - self.offsets[chunk])
+    }
+}
+
 impl<'exec> Executor<'exec> {
-    async fn execute_plan_node(&self, ctx: &mut ExecutionContext<'exec>, node: &'exec PlanNode) {
+    /// Whether the remaining plan nodes should be skipped, as an error occurred
+    /// and the error policy does not continue after errors.
+    #[inline]
+    pub(crate) fn should_stop(&self, ctx: &ExecutionContext<'exec>) -> bool {
+        self.error_policy != ErrorPolicy::BestEffort && ctx.has_execution_errors()
+    }
+
+    pub(crate) async fn execute_plan_node(
+        &self,
+        ctx: &mut ExecutionContext<'exec>,
+        node: &'exec PlanNode,
+    ) {
         match node {
             PlanNode::Parallel(parallel_node) => {
                 let mut scope = FuturesUnordered::new();
 
                 for child in &parallel_node.nodes {
-                    // We borrow `ctx.data` only for sync preparation of the job future,
-                    // and the actual execution of the job future is done without the borrow of `ctx.data`
-                    if let Some(fut) = self.prepare_job_future(child, &ctx.data) {
-                        scope.push(fut);
-                    }
+                    // We borrow `ctx.data` only for sync preparation of the job futures,
+                    // and the actual execution of the job futures is done without the borrow of `ctx.data`
+                    scope.extend(self.prepare_job_futures(child, &ctx.data));
                 }
 
                 while let Some(job) = scope.next().await {
                     self.process_job_result(ctx, job);
+                    if self.should_stop(ctx) {
+                        // dropping the scope cancels the jobs still in flight
+                        return;
+                    }
                 }
             }
             PlanNode::Sequence(sequence_node) => {
                 for child in &sequence_node.nodes {
+                    if self.should_stop(ctx) {
+                        return;
+                    }
                     // We use `Box.pin` here to avoid the compiler error about recursive future,
                     // as `execute_plan_node` is calling itself recursively for sequence nodes
                     Box::pin(self.execute_plan_node(ctx, child)).await;
                 }
             }
             PlanNode::Condition(condition_node) => {
-                if let Some(next_node) =
-                    condition_node_by_variables(condition_node, self.variable_values)
-                {
-                    // We use `Box.pin` here to avoid the compiler error about recursive future,
-                    // as `execute_plan_node` is calling itself recursively for condition nodes
-                    Box::pin(self.execute_plan_node(ctx, next_node)).await;
+                match condition_node_by_variables(
+                    condition_node,
+                    self.variable_values,
+                    self.variable_definitions,
+                ) {
+                    Ok(Some(next_node)) => {
+                        // We use `Box.pin` here to avoid the compiler error about recursive future,
+                        // as `execute_plan_node` is calling itself recursively for condition nodes
+                        Box::pin(self.execute_plan_node(ctx, next_node)).await;
+                    }
+                    Ok(None) => {}
+                    Err(err) => self.process_job_result(ctx, Err(err)),
+                }
+            }
+            PlanNode::Defer(defer_node) => {
+                // Without incremental delivery, the deferred nodes are executed right after
+                // the primary node, and everything is delivered in a single response
+                if let Some(primary) = &defer_node.primary.node {
+                    Box::pin(self.execute_plan_node(ctx, primary)).await;
+                }
+                for deferred in &defer_node.deferred {
+                    if self.should_stop(ctx) {
+                        return;
+                    }
+                    if let Some(node) = &deferred.node {
+                        Box::pin(self.execute_plan_node(ctx, node)).await;
+                    }
                 }
             }
             node => {
-                if let Some(fut) = self.prepare_job_future(node, &ctx.data) {
-                    let job = fut.await;
+                let mut scope: FuturesUnordered<_> = self
+                    .prepare_job_futures(node, &ctx.data)
+                    .into_iter()
+                    .collect();
+
+                while let Some(job) = scope.next().await {
                     self.process_job_result(ctx, job);
                 }
             }
@@ -836,6 +1196,7 @@ impl<'exec> Executor<'exec> {
         fetch_node: &'exec FetchNode,
     ) -> BoxFuture<'wave, Result<ExecutionJob<'exec>, PlanExecutionError>> {
         self.prepare_execution_job(PrepareExecutionJobOpts {
+            node_id: fetch_node.id,
             subgraph_name: &fetch_node.service_name,
             variable_usages: fetch_node.variable_usages.as_ref(),
             operation_name: self
@@ -856,17 +1217,19 @@ impl<'exec> Executor<'exec> {
      * and the actual execution of the subgraph request is done in `prepare_fetch_job` which is async.
      * So we do everything in sync with `ctx.data` and return a future for the actual execution of the subgraph request.
      *
-     * The return type is not a future of `Option`, but `Option` of future because the only case when we don't have a future,
-     * and the result(`None`) is when the plan node is flatten node with no data.
+     * The return type is a list of futures, as a plan node results in zero or more subgraph requests.
+     * There are none when the plan node is flatten node with no data,
+     * and there are many when a flatten node passes different values of contextual arguments to its entities.
      */
-    fn prepare_job_future<'wave>(
+    fn prepare_job_futures<'wave>(
         &'wave self,
         node: &'exec PlanNode,
         data: &Value<'exec>,
-    ) -> Option<BoxFuture<'wave, Result<ExecutionJob<'exec>, PlanExecutionError>>> {
+    ) -> Vec<BoxFuture<'wave, Result<ExecutionJob<'exec>, PlanExecutionError>>> {
         match node {
-            PlanNode::Fetch(fetch_node) => Some(
-                self.prepare_execution_job(PrepareExecutionJobOpts {
+            PlanNode::Fetch(fetch_node) => vec![self
+                .prepare_execution_job(PrepareExecutionJobOpts {
+                    node_id: fetch_node.id,
                     subgraph_name: &fetch_node.service_name,
                     variable_usages: fetch_node.variable_usages.as_ref(),
                     operation_name: self
@@ -879,8 +1242,7 @@ impl<'exec> Executor<'exec> {
                     raw_variable_values: None,
                     affected_path: None,
                 })
-                .boxed(),
-            ),
+                .boxed()],
             PlanNode::BatchFetch(batch_fetch_node) => {
                 let (raw_variable_values, aliases) =
                     self.prepare_batch_fetch_job_state(&batch_fetch_node.entity_batch, data);
@@ -895,11 +1257,12 @@ impl<'exec> Executor<'exec> {
                         alias_count = aliases.len(),
                         "Skipping batched entity fetch with no representations"
                     );
-                    return None;
+                    return Vec::new();
                 }
 
-                Some(
-                    self.prepare_execution_job(PrepareExecutionJobOpts {
+                vec![self
+                    .prepare_execution_job(PrepareExecutionJobOpts {
+                        node_id: batch_fetch_node.id,
                         subgraph_name: &batch_fetch_node.service_name,
                         variable_usages: batch_fetch_node.variable_usages.as_ref(),
                         operation_name: self
@@ -918,117 +1281,291 @@ impl<'exec> Executor<'exec> {
                         response: fetch_job.response(),
                         aliases,
                     })
-                    .boxed(),
-                )
+                    .boxed()]
             }
             PlanNode::Flatten(flatten_node) => {
                 let fetch_node = match flatten_node.node.as_ref() {
                     PlanNode::Fetch(fetch_node) => fetch_node,
-                    _ => return None,
+                    _ => return Vec::new(),
                 };
 
                 // If there are no requirements in the node (no _entities call), then we only need to make
                 // a regular call. This happens when we perform subgraph re-entry.
                 // So we can just create the fetch step future with the info we have
                 let Some(requires_nodes) = fetch_node.requires.as_ref() else {
-                    return Some(self.prepare_root_reentry_fetch_job(flatten_node, fetch_node));
+                    return vec![self.prepare_root_reentry_fetch_job(flatten_node, fetch_node)];
                 };
 
-                let mut index = 0;
-                let normalized_path = flatten_node.path.as_slice();
-                let mut filtered_representations = Vec::new();
-                filtered_representations.put(OPEN_BRACKET);
-                let possible_types = &self.schema_metadata.possible_types;
-                let mut representation_hashes: Vec<Option<u64>> = Vec::new();
-                let mut representation_hash_to_index: AHashMap<u64, usize> = AHashMap::new();
-                let arena = bumpalo::Bump::new();
+                let Some(context_arguments) = fetch_node.context_arguments.as_deref() else {
+                    return self.prepare_flatten_fetch_job(
+                        flatten_node,
+                        fetch_node,
+                        requires_nodes,
+                        data,
+                        None,
+                    );
+                };
 
-                traverse_and_callback(
+                // Entities receiving different values of contextual arguments are fetched in separate requests
+                let ContextValueGroups {
+                    entity_groups,
+                    raw_variable_values,
+                } = group_entities_by_context_values(
                     data,
-                    normalized_path,
+                    flatten_node.path.as_slice(),
                     &self.schema_metadata.possible_types,
-                    &mut |entity| {
-                        if entity.is_null() {
-                            representation_hashes.push(None);
-                            return;
-                        }
+                    context_arguments,
+                );
 
-                        let hash = entity.to_hash(&requires_nodes.items, possible_types);
-                        representation_hashes.push(Some(hash));
-                        let is_first_representation = representation_hash_to_index.is_empty();
-                        let vacant_entry = match representation_hash_to_index.entry(hash) {
-                            Entry::Occupied(_) => return,
-                            Entry::Vacant(vacant_entry) => vacant_entry,
-                        };
-
-                        let entity = if let Some(input_rewrites) = &fetch_node.input_rewrites {
-                            let new_entity = arena.alloc(entity.clone());
-                            for input_rewrite in input_rewrites {
-                                input_rewrite
-                                    .rewrite(&self.schema_metadata.possible_types, new_entity);
-                            }
-                            new_entity
-                        } else {
-                            entity
-                        };
-
-                        let is_projected = project_requires(
-                            possible_types,
-                            &requires_nodes.items,
-                            entity,
-                            &mut filtered_representations,
-                            is_first_representation,
-                            None,
-                        );
+                raw_variable_values
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(group_index, raw_variable_values)| {
+                        self.prepare_flatten_fetch_job(
+                            flatten_node,
+                            fetch_node,
+                            requires_nodes,
+                            data,
+                            Some(ContextGroup {
+                                entity_groups: &entity_groups,
+                                group_index,
+                                raw_variable_values,
+                            }),
+                        )
+                    })
+                    .collect()
+            }
+            PlanNode::Condition(node) => match condition_node_by_variables(
+                node,
+                self.variable_values,
+                self.variable_definitions,
+            ) {
+                Ok(Some(node)) => self.prepare_job_futures(node, data),
+                Ok(None) => Vec::new(),
+                Err(err) => vec![future::ready(Err(err)).boxed()],
+            },
+            // Our Query Planner does not produce any other plan node types in ParallelNode
+            _ => Vec::new(),
+        }
+    }
 
-                        if is_projected {
-                            vacant_entry.insert(index);
-                            index += 1;
+    fn prepare_flatten_fetch_job<'wave>(
+        &'wave self,
+        flatten_node: &'exec FlattenNode,
+        fetch_node: &'exec FetchNode,
+        requires_nodes: &'exec SelectionSet,
+        data: &Value<'exec>,
+        context_group: Option<ContextGroup<'_, 'exec>>,
+    ) -> Vec<BoxFuture<'wave, Result<ExecutionJob<'exec>, PlanExecutionError>>> {
+        let mut index = 0;
+        let normalized_path = flatten_node.path.as_slice();
+        let mut representation_chunks = RepresentationChunks::new(self.limits);
+        let possible_types = &self.schema_metadata.possible_types;
+        let mut representation_hashes: Vec<Option<u64>> = Vec::new();
+        let mut representation_hash_to_index: AHashMap<u64, usize> = AHashMap::new();
+        let arena = bumpalo::Bump::new();
+        // The values of contextual arguments are not a part of the cache key
+        let entity_cache = self.entity_cache.filter(|_| context_group.is_none());
+        let mut position = 0;
+
+        let entity_cache_fetch_hash = entity_cache.map(|_| {
+            EntityCache::fetch_hash(
+                &fetch_node.service_name,
+                fetch_node.operation.hash,
+                fetch_node.variable_usages.as_ref(),
+                self.variable_values,
+            )
+        });
+        let mut entity_cache_hits: Vec<(u64, Bytes)> = Vec::new();
+        let mut entity_cache_hit_hashes: AHashSet<u64> = AHashSet::new();
+        let mut entity_cache_writes: Vec<Option<EntityCacheWrite>> = Vec::new();
+
+        traverse_and_callback(
+            data,
+            normalized_path,
+            &self.schema_metadata.possible_types,
+            &mut |entity| {
+                let is_in_context_group = context_group
+                    .as_ref()
+                    .is_none_or(|context_group| context_group.contains(position));
+                position += 1;
+
+                if entity.is_null() || !is_in_context_group {
+                    representation_hashes.push(None);
+                    return;
+                }
+
+                let hash = entity.to_hash(&requires_nodes.items, possible_types);
+                representation_hashes.push(Some(hash));
+                if entity_cache_hit_hashes.contains(&hash) {
+                    return;
+                }
+                let vacant_entry = match representation_hash_to_index.entry(hash) {
+                    Entry::Occupied(_) => return,
+                    Entry::Vacant(vacant_entry) => vacant_entry,
+                };
+
+                let entity_cache_write = match (entity_cache, entity_cache_fetch_hash) {
+                    (Some(entity_cache), Some(fetch_hash)) => {
+                        match entity_cache.lookup(fetch_hash, hash, entity) {
+                            EntityCacheLookup::Hit(bytes) => {
+                                entity_cache_hit_hashes.insert(hash);
+                                entity_cache_hits.push((hash, bytes));
+                                return;
+                            }
+                            EntityCacheLookup::Miss(write) => Some(write),
+                            EntityCacheLookup::Skip => None,
                         }
-                    },
-                );
+                    }
+                    _ => None,
+                };
 
-                filtered_representations.put(CLOSE_BRACKET);
+                let entity = if let Some(input_rewrites) = &fetch_node.input_rewrites {
+                    let new_entity = arena.alloc(entity.clone());
+                    for input_rewrite in input_rewrites {
+                        input_rewrite.rewrite(&self.schema_metadata.possible_types, new_entity);
+                    }
+                    new_entity
+                } else {
+                    entity
+                };
 
-                if representation_hash_to_index.is_empty() {
-                    return None;
+                let is_projected = representation_chunks.project(index, |buffer, is_first| {
+                    project_requires(
+                        possible_types,
+                        &requires_nodes.items,
+                        entity,
+                        buffer,
+                        is_first,
+                        None,
+                    )
+                });
+
+                if is_projected {
+                    vacant_entry.insert(index);
+                    entity_cache_writes.push(entity_cache_write);
+                    index += 1;
                 }
+            },
+        );
 
-                // This is the future for the actual fetch job
-                Some(
-                    self.prepare_execution_job(PrepareExecutionJobOpts {
-                        subgraph_name: &fetch_node.service_name,
-                        variable_usages: fetch_node.variable_usages.as_ref(),
-                        operation_name: self
-                            .operation_name_factory
-                            .generate(&fetch_node.service_name, fetch_node.id),
-                        operation_kind: fetch_node.operation_kind.as_ref(),
+        let mut cached_entities =
+            CachedEntities::parse(entity_cache_hits, fetch_node.custom_scalar_paths.as_ref())
+                .map(Box::new);
+
+        if representation_hash_to_index.is_empty() {
+            // Every representation is served from the entity cache,
+            // so we skip the network call.
+            return cached_entities
+                .map(|cached_entities| {
+                    future::ready(Ok(ExecutionJob::FlattenFetch {
                         operation: &fetch_node.operation,
-                        output_rewrites: fetch_node.output_rewrites.as_deref(),
-                        custom_scalar_paths: fetch_node.custom_scalar_paths.as_ref(),
-                        raw_variable_values: Some(vec![(
-                            "representations",
-                            filtered_representations,
-                        )]),
-                        affected_path: Some(&flatten_node.path),
-                    })
-                    .map_ok(|fetch_job| ExecutionJob::FlattenFetch {
-                        operation: fetch_job.operation(),
                         flatten_node_path: &flatten_node.path,
-                        response: fetch_job.response(),
+                        response: SubgraphResponse::default(),
                         subgraph_name: fetch_node.service_name.as_str(),
                         representation_hashes,
                         representation_hash_to_index,
                         output_rewrites: fetch_node.output_rewrites.as_deref(),
-                    })
-                    .boxed(),
-                )
+                        cached_entities: Some(cached_entities),
+                        entity_cache_writes,
+                    }))
+                    .boxed()
+                })
+                .into_iter()
+                .collect();
+        }
+
+        let chunk_count = representation_chunks.chunks.len();
+        // Every request receives the indexes of its own representations
+        let mut chunk_hash_to_index = if chunk_count == 1 {
+            vec![representation_hash_to_index]
+        } else {
+            let mut chunk_hash_to_index = vec![AHashMap::new(); chunk_count];
+            for (hash, index) in representation_hash_to_index {
+                let (chunk, index) = representation_chunks.locate(index);
+                chunk_hash_to_index[chunk].insert(hash, index);
             }
-            PlanNode::Condition(node) => condition_node_by_variables(node, self.variable_values)
-                .and_then(|node| self.prepare_job_future(node, data)),
-            // Our Query Planner does not produce any other plan node types in ParallelNode
-            _ => None,
+            chunk_hash_to_index
+        };
+        let mut chunk_cache_writes = Vec::with_capacity(chunk_count);
+        for offset in representation_chunks.offsets.iter().rev() {
+            chunk_cache_writes.push(entity_cache_writes.split_off(*offset));
         }
+        chunk_cache_writes.reverse();
+
+        // The requests of a split fetch are sent one after another
+        let sequential = (chunk_count > 1).then(|| Arc::new(Semaphore::new(1)));
+        let mut context_raw_variable_values =
+            context_group.map(|context_group| context_group.raw_variable_values);
+        let mut jobs = Vec::with_capacity(chunk_count);
+
+        for (chunk_index, mut representations) in
+            representation_chunks.chunks.into_iter().enumerate()
+        {
+            let is_last = chunk_index + 1 == chunk_count;
+            representations.put(CLOSE_BRACKET);
+
+            let mut raw_variable_values = vec![("representations", representations)];
+            if let Some(context_values) = &mut context_raw_variable_values {
+                if is_last {
+                    raw_variable_values.append(context_values);
+                } else {
+                    raw_variable_values.extend(context_values.iter().cloned());
+                }
+            }
+            let representation_hashes = if is_last {
+                std::mem::take(&mut representation_hashes)
+            } else {
+                representation_hashes.clone()
+            };
+            let representation_hash_to_index =
+                std::mem::take(&mut chunk_hash_to_index[chunk_index]);
+            let entity_cache_writes = std::mem::take(&mut chunk_cache_writes[chunk_index]);
+            // Cached entities are merged once, with the first request
+            let cached_entities = cached_entities.take();
+
+            // This is the future for the actual fetch job
+            let fetch_job = self
+                .prepare_execution_job(PrepareExecutionJobOpts {
+                    node_id: fetch_node.id,
+                    subgraph_name: &fetch_node.service_name,
+                    variable_usages: fetch_node.variable_usages.as_ref(),
+                    operation_name: self
+                        .operation_name_factory
+                        .generate(&fetch_node.service_name, fetch_node.id),
+                    operation_kind: fetch_node.operation_kind.as_ref(),
+                    operation: &fetch_node.operation,
+                    output_rewrites: fetch_node.output_rewrites.as_deref(),
+                    custom_scalar_paths: fetch_node.custom_scalar_paths.as_ref(),
+                    raw_variable_values: Some(raw_variable_values),
+                    affected_path: Some(&flatten_node.path),
+                })
+                .map_ok(|fetch_job| ExecutionJob::FlattenFetch {
+                    operation: fetch_job.operation(),
+                    flatten_node_path: &flatten_node.path,
+                    response: fetch_job.response(),
+                    subgraph_name: fetch_node.service_name.as_str(),
+                    representation_hashes,
+                    representation_hash_to_index,
+                    output_rewrites: fetch_node.output_rewrites.as_deref(),
+                    cached_entities,
+                    entity_cache_writes,
+                });
+
+            jobs.push(match sequential.clone() {
+                Some(sequential) => async move {
+                    let _permit = sequential
+                        .acquire_owned()
+                        .await
+                        .expect("the semaphore of a split fetch is never closed");
+                    fetch_job.await
+                }
+                .boxed(),
+                None => fetch_job.boxed(),
+            });
+        }
+
+        jobs
     }
 
     // We handle `Result` instead of passing `PlanExecutionError` directly
@@ -1149,12 +1686,23 @@ impl<'exec> Executor<'exec> {
                         representation_hashes,
                         ref representation_hash_to_index,
                         output_rewrites,
+                        cached_entities,
+                        entity_cache_writes,
                         ..
                     } => {
                         if let Some(response_bytes) = response.bytes {
                             ctx.response_storage.add_response(response_bytes);
                         }
-                        if let Some(mut entities) = response.data.take_entities() {
+                        let cached_entities = cached_entities.map(|mut cached_entities| {
+                            if let Some(bytes) = cached_entities.bytes.take() {
+                                ctx.response_storage.add_cached(bytes);
+                            }
+                            cached_entities
+                        });
+                        let entities = response.data.take_entities();
+                        if entities.is_some() || cached_entities.is_some() {
+                            let has_entities = entities.is_some();
+                            let mut entities = entities.unwrap_or_default();
                             if let Some(output_rewrites) = output_rewrites {
                                 for output_rewrite in output_rewrites {
                                     for entity in &mut entities {
@@ -1166,14 +1714,14 @@ impl<'exec> Executor<'exec> {
 
                             let mut index = 0;
                             let normalized_path = flatten_node_path.as_slice();
+                            let response_errors = response.errors.as_ref().filter(|_| has_entities);
                             // If there is an error in the response, then collect the paths for normalizing the error
-                            let initial_error_path = response.errors.as_ref().map(|_| {
+                            let initial_error_path = response_errors.map(|_| {
                                 GraphQLErrorPath::with_capacity(normalized_path.len() + 2)
                             });
-                            let mut entity_index_error_map = response
-                                .errors
-                                .as_ref()
-                                .map(|_| HashMap::with_capacity(entities.len()));
+                            let mut entity_index_error_map =
+                                response_errors.map(|_| HashMap::with_capacity(entities.len()));
+                            let mut merges = Vec::with_capacity(representation_hashes.len());
                             traverse_and_callback_mut(
                                 &mut ctx.data,
                                 normalized_path,
@@ -1204,11 +1752,34 @@ impl<'exec> Executor<'exec> {
                                             // is valid.
                                             let new_val: Value<'_> =
                                                 unsafe { std::mem::transmute(entity.clone()) };
-                                            deep_merge(target, new_val);
+                                            merges.push((target, new_val));
                                         }
+                                    } else if let Some(entity) =
+                                        cached_entities.as_ref().and_then(|cached_entities| {
+                                            cached_entities
+                                                .representation_hash_to_index
+                                                .get(&hash)
+                                                .and_then(|index| {
+                                                    cached_entities.entities.get(*index)
+                                                })
+                                        })
+                                    {
+                                        // SAFETY: the cached entity borrows from bytes kept alive
+                                        // by the response storage, for the lifetime of the execution.
+                                        let new_val: Value<'_> =
+                                            unsafe { std::mem::transmute(entity.clone()) };
+                                        merges.push((target, new_val));
                                     }
                                 },
                             );
+                            deep_merge_batch(merges);
+
+                            // Only entities of successful responses are cached
+                            if let (Some(entity_cache), None) =
+                                (self.entity_cache, response.errors.as_ref())
+                            {
+                                entity_cache.store(&entity_cache_writes, &entities);
+                            }
 
                             ctx.handle_errors(
                                 subgraph_name,
@@ -1402,6 +1973,7 @@ impl<'exec> Executor<'exec> {
             let initial_error_path = has_alias_errors
                 // Small extra capacity for path segments that will be appended later.
                 .then(|| GraphQLErrorPath::with_capacity(normalized_path.len() + 2));
+            let mut merges = Vec::with_capacity(path_state.representation_hashes.len());
 
             // For each visited target:
             traverse_and_callback_mut(
@@ -1434,11 +2006,12 @@ impl<'exec> Executor<'exec> {
                             // SAFETY: `new_val` is a clone of an entity that lives for `'a`.
                             // The transmute is to satisfy the compiler, but the lifetime is valid.
                             let new_val: Value<'_> = unsafe { std::mem::transmute(entity.clone()) };
-                            deep_merge(target_data, new_val);
+                            merges.push((target_data, new_val));
                         }
                     }
                 },
             );
+            deep_merge_batch(merges);
         }
 
         entity_index_error_map
@@ -1580,14 +2153,26 @@ impl<'exec> Executor<'exec> {
                 subgraph_name: subgraph_name_factory,
                 affected_path: affected_path_factory,
             })?;
+            if self
+                .fetch_trace_sink
+                .is_some_and(|sink| sink.include_ftv1())
+            {
+                headers_map.insert(
+                    FTV1_REQUEST_HEADER.clone(),
+                    FTV1_REQUEST_HEADER_VALUE.clone(),
+                );
+            }
             let variable_refs = select_fetch_variables(self.variable_values, opts.variable_usages);
 
             let mut subgraph_request = SubgraphExecutionRequest {
                 query: &opts.operation.document_str,
                 document_name_write_pos: opts.operation.name_write_position,
                 dedupe: self.dedupe_subgraph_requests,
+                idempotent: !matches!(opts.operation_kind, Some(OperationKind::Mutation)),
                 operation_name: opts.operation_name,
                 variables: variable_refs,
+                serialized_variables: Some(self.serialized_variables),
+                overridden_variables: Default::default(),
                 raw_variable_values: opts.raw_variable_values,
                 headers: headers_map,
                 extensions: None,
@@ -1612,6 +2197,7 @@ impl<'exec> Executor<'exec> {
                 );
             }
 
+            let sent_at = Instant::now();
             let mut response = self
                 .executors
                 .execute(
@@ -1627,6 +2213,15 @@ impl<'exec> Executor<'exec> {
                     affected_path: affected_path_factory,
                 })?;
 
+            if let Some(fetch_trace_sink) = self.fetch_trace_sink {
+                fetch_trace_sink.record(
+                    opts.node_id,
+                    opts.subgraph_name,
+                    sent_at,
+                    response.extensions.as_ref(),
+                );
+            }
+
             if let Some(errors) = &response.errors {
                 if !errors.is_empty() {
                     subgraph_operation_span.record_error_count(errors.len());
@@ -1662,18 +2257,45 @@ impl<'exec> Executor<'exec> {
     }
 }
 
+/// Picks the branch of a condition node, by the value of the variable of `@skip` or `@include`.
+/// When the variable is not provided, the default value of its definition is used.
 fn condition_node_by_variables<'a>(
     condition_node: &'a ConditionNode,
-    variable_values: &'a Option<VariablesMap>,
-) -> Option<&'a PlanNode> {
-    let vars = variable_values.as_ref()?;
-    let value = vars.get(&condition_node.condition)?;
-    let condition_met = matches!(value.as_ref(), ValueRef::Bool(true));
+    variable_values: &Option<VariablesMap>,
+    variable_definitions: Option<&[VariableDefinition]>,
+) -> Result<Option<&'a PlanNode>, PlanExecutionError> {
+    let variable_name = condition_node.condition.as_str();
+    let provided_value = variable_values
+        .as_ref()
+        .and_then(|values| values.get(variable_name));
+
+    let condition_met = match provided_value {
+        Some(value) => value.as_bool(),
+        None => variable_definitions
+            .and_then(|definitions| {
+                definitions
+                    .iter()
+                    .find(|definition| definition.name == variable_name)
+            })
+            .and_then(|definition| match definition.default_value {
+                Some(AstValue::Boolean(default_value)) => Some(default_value),
+                _ => None,
+            }),
+    }
+    .ok_or_else(|| {
+        PlanExecutionError::new(
+            PlanExecutionErrorKind::InvalidConditionVariable(variable_name.to_string()),
+            LazyPlanContext {
+                subgraph_name: || None,
+                affected_path: || None,
+            },
+        )
+    })?;
 
     if condition_met {
-        condition_node.if_clause.as_deref()
+        Ok(condition_node.if_clause.as_deref())
     } else {
-        condition_node.else_clause.as_deref()
+        Ok(condition_node.else_clause.as_deref())
     }
 }
 
@@ -1701,7 +2323,7 @@ mod tests {
         execution::{
             client_request_details::{ClientRequestDetails, JwtRequestDetails, OperationDetails},
             operation_name::OperationNameFactory,
-            plan::Executor,
+            plan::{ExecutionLimits, Executor, RepresentationChunks},
         },
         execution_context::ExecutionContext,
         extensions::plan::ExtensionsPlan,
@@ -1711,23 +2333,31 @@ mod tests {
             graphql_error::{GraphQLErrorExtensions, GraphQLErrorPath},
             value::Value as ResponseValue,
         },
+        variables::serialized::SerializedVariables,
         SubgraphExecutorMap,
     };
 
-    use super::select_fetch_variables;
+    use super::{condition_node_by_variables, select_fetch_variables};
     use dashmap::DashMap;
     use graphql_tools::parser::query::{self, Definition};
-    use hive_router_config::HiveRouterConfig;
+    use hive_router_config::{errors::ErrorPolicy, HiveRouterConfig};
     use hive_router_internal::telemetry::TelemetryContext;
     use hive_router_query_planner::{
-        ast::{document::Document, operation::SubgraphFetchOperation},
-        planner::plan_nodes::{EntityBatch, EntityBatchAlias, FetchNode, ParallelNode, PlanNode},
+        ast::{
+            document::Document,
+            operation::{SubgraphFetchOperation, VariableDefinition},
+        },
+        planner::plan_nodes::{
+            ConditionNode, DeferNode, DeferPrimary, DeferredNode, EntityBatch, EntityBatchAlias,
+            FetchNode, ParallelNode, PlanNode, SequenceNode,
+        },
         utils::parsing::parse_operation,
     };
     use ntex::http::HeaderMap;
     use sonic_rs::Value;
     use std::{
         collections::{BTreeSet, HashMap},
+        num::NonZeroUsize,
         sync::{mpsc::channel, Arc},
         time::Duration,
         vec,
@@ -1805,6 +2435,78 @@ mod tests {
 
         assert!(selected.is_none());
     }
+
+    fn include_condition_node() -> ConditionNode {
+        ConditionNode {
+            condition: "withReviews".to_string(),
+            if_clause: Some(Box::new(PlanNode::Parallel(ParallelNode { nodes: vec![] }))),
+            else_clause: Some(Box::new(PlanNode::Sequence(SequenceNode { nodes: vec![] }))),
+        }
+    }
+
+    fn variable_definitions(query: &str) -> Vec<VariableDefinition> {
+        parse_document(query)
+            .operation
+            .variable_definitions
+            .expect("variable definitions should exist")
+    }
+
+    #[test]
+    fn condition_node_uses_provided_variable_values() {
+        let condition_node = include_condition_node();
+        let definitions =
+            variable_definitions("query ($withReviews: Boolean = true) { me { id } }");
+        let variable_values = Some(HashMap::from([(
+            "withReviews".to_string(),
+            Value::from(false),
+        )]));
+
+        let node = condition_node_by_variables(
+            &condition_node,
+            &variable_values,
+            Some(definitions.as_slice()),
+        )
+        .unwrap();
+
+        assert!(matches!(node, Some(PlanNode::Sequence(_))));
+    }
+
+    #[test]
+    fn condition_node_falls_back_to_default_values() {
+        let condition_node = include_condition_node();
+
+        let definitions =
+            variable_definitions("query ($withReviews: Boolean = true) { me { id } }");
+        let node =
+            condition_node_by_variables(&condition_node, &None, Some(definitions.as_slice()))
+                .unwrap();
+        assert!(matches!(node, Some(PlanNode::Parallel(_))));
+
+        let definitions =
+            variable_definitions("query ($withReviews: Boolean = false) { me { id } }");
+        let node =
+            condition_node_by_variables(&condition_node, &None, Some(definitions.as_slice()))
+                .unwrap();
+        assert!(matches!(node, Some(PlanNode::Sequence(_))));
+    }
+
+    #[test]
+    fn condition_node_rejects_non_boolean_values() {
+        let condition_node = include_condition_node();
+
+        let variable_values = Some(HashMap::from([(
+            "withReviews".to_string(),
+            Value::from("yes"),
+        )]));
+        let err = condition_node_by_variables(&condition_node, &variable_values, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Variable \"$withReviews\" used as the condition of @skip or @include must be a boolean"
+        );
+
+        let err = condition_node_by_variables(&condition_node, &None, None).unwrap_err();
+        assert_eq!(err.error_code(), "BAD_USER_INPUT");
+    }
     #[test]
     /**
      * We have the same entity in two different paths ["a", 0] and ["b", 1],
@@ -1892,6 +2594,8 @@ mod tests {
 
         let executor = Executor {
             variable_values: &None,
+            variable_definitions: None,
+            serialized_variables: &SerializedVariables::default(),
             schema_metadata: &SchemaMetadata::default(),
             executors: &executors,
             client_request: &ClientRequestDetails {
@@ -1904,7 +2608,9 @@ mod tests {
                     kind: "query",
                 },
                 jwt: JwtRequestDetails::Unauthenticated.into(),
+                client: Default::default(),
                 path_params: Default::default(),
+                plugin_context: None,
             },
             headers_plan: &HeaderRulesPlan::default(),
             extensions_plan: &ExtensionsPlan::default(),
@@ -1913,6 +2619,10 @@ mod tests {
             demand_control_context: None,
             plugin_req_state: None,
             operation_name_factory: &OperationNameFactory::default(),
+            entity_cache: None,
+            fetch_trace_sink: None,
+            error_policy: ErrorPolicy::default(),
+            limits: ExecutionLimits::default(),
         };
 
         let data: ResponseValue = sonic_rs::from_str(
@@ -2000,6 +2710,8 @@ mod tests {
         ]);
         let executor = Executor {
             variable_values: &None,
+            variable_definitions: None,
+            serialized_variables: &SerializedVariables::default(),
             schema_metadata: &SchemaMetadata::default(),
             executors: &SubgraphExecutorMap::from_http_endpoint_map(
                 &subgraph_endpoint_map,
@@ -2020,7 +2732,9 @@ mod tests {
                     kind: "query",
                 },
                 jwt: JwtRequestDetails::Unauthenticated.into(),
+                client: Default::default(),
                 path_params: Default::default(),
+                plugin_context: None,
             },
             headers_plan: &HeaderRulesPlan::default(),
             extensions_plan: &ExtensionsPlan::default(),
@@ -2029,6 +2743,10 @@ mod tests {
             demand_control_context: None,
             plugin_req_state: None,
             operation_name_factory: &OperationNameFactory::default(),
+            entity_cache: None,
+            fetch_trace_sink: None,
+            error_policy: ErrorPolicy::default(),
+            limits: ExecutionLimits::default(),
         };
 
         let mock_a = subgraph_a
@@ -2089,6 +2807,7 @@ mod tests {
                             requires: None,
                             input_rewrites: None,
                             output_rewrites: None,
+                            context_arguments: None,
                             variable_usages: None,
                             operation_kind: None,
                         }),
@@ -2102,6 +2821,7 @@ mod tests {
                             requires: None,
                             input_rewrites: None,
                             output_rewrites: None,
+                            context_arguments: None,
                             variable_usages: None,
                             operation_kind: None,
                         }),
@@ -2117,4 +2837,160 @@ mod tests {
             .expect("Failed to receive from_a value through channel");
         assert_eq!(from_a_value, "value_a");
     }
+
+    #[tokio::test]
+    async fn executes_deferred_nodes_after_primary_node() {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+        let mut subgraph_a = mockito::Server::new_async().await;
+        let subgraph_endpoint_map = HashMap::from([(
+            "subgraph_a".to_string(),
+            format!("http://{}/graphql", subgraph_a.host_with_port())
+                .parse()
+                .unwrap(),
+        )]);
+        let executor = Executor {
+            variable_values: &None,
+            variable_definitions: None,
+            serialized_variables: &SerializedVariables::default(),
+            schema_metadata: &SchemaMetadata::default(),
+            executors: &SubgraphExecutorMap::from_http_endpoint_map(
+                &subgraph_endpoint_map,
+                HiveRouterConfig::default().into(),
+                Arc::new(TelemetryContext::from_propagation_config(
+                    &Default::default(),
+                )),
+                Arc::new(DashMap::new()),
+            )
+            .unwrap(),
+            client_request: &ClientRequestDetails {
+                method: &http::Method::POST,
+                url: &"http://example.com".parse().unwrap(),
+                headers: HeaderMap::new().into(),
+                operation: OperationDetails {
+                    name: None,
+                    query: "{ fast ... @defer { slow } }",
+                    kind: "query",
+                },
+                jwt: JwtRequestDetails::Unauthenticated.into(),
+                client: Default::default(),
+                path_params: Default::default(),
+                plugin_context: None,
+            },
+            headers_plan: &HeaderRulesPlan::default(),
+            extensions_plan: &ExtensionsPlan::default(),
+            jwt_forwarding_plan: None,
+            dedupe_subgraph_requests: false,
+            demand_control_context: None,
+            plugin_req_state: None,
+            operation_name_factory: &OperationNameFactory::default(),
+            entity_cache: None,
+            fetch_trace_sink: None,
+            error_policy: ErrorPolicy::default(),
+            limits: ExecutionLimits::default(),
+        };
+
+        let mock_fast = subgraph_a
+            .mock("POST", "/graphql")
+            .match_body(mockito::Matcher::Regex("fast".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data":{"fast":"value_fast"}}"#)
+            .create();
+        let mock_slow = subgraph_a
+            .mock("POST", "/graphql")
+            .match_body(mockito::Matcher::Regex("slow".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data":{"slow":"value_slow"}}"#)
+            .create();
+
+        let fetch_node = |id: i64, query: &str| {
+            Box::new(PlanNode::Fetch(FetchNode {
+                id,
+                service_name: "subgraph_a".to_string(),
+                operation: SubgraphFetchOperation::from_anonymous_operation(parse_document(query)),
+                custom_scalar_paths: None,
+                requires: None,
+                input_rewrites: None,
+                output_rewrites: None,
+                context_arguments: None,
+                variable_usages: None,
+                operation_kind: None,
+            }))
+        };
+
+        let plan_node = PlanNode::Defer(DeferNode {
+            primary: DeferPrimary {
+                subselection: Some("{ fast }".to_string()),
+                node: Some(fetch_node(1, "{ fast }")),
+            },
+            deferred: vec![DeferredNode {
+                depends: vec![],
+                label: Some("slow".to_string()),
+                query_path: vec![],
+                subselection: Some("{ slow }".to_string()),
+                node: Some(fetch_node(2, "{ slow }")),
+            }],
+        });
+
+        let mut exec_ctx = ExecutionContext {
+            data: ResponseValue::Object(Vec::new()),
+            ..Default::default()
+        };
+        executor.execute_plan_node(&mut exec_ctx, &plan_node).await;
+
+        mock_fast.assert();
+        mock_slow.assert();
+        let data = exec_ctx.data.as_object().expect("data should be an object");
+        assert!(data
+            .iter()
+            .any(|(k, v)| *k == "fast" && v.as_str() == Some("value_fast")));
+        assert!(data
+            .iter()
+            .any(|(k, v)| *k == "slow" && v.as_str() == Some("value_slow")));
+    }
+
+    fn project_representation(value: &'static str) -> impl FnOnce(&mut Vec<u8>, bool) -> bool {
+        move |buffer, is_first| {
+            if !is_first {
+                buffer.push(b',');
+            }
+            buffer.extend_from_slice(value.as_bytes());
+            true
+        }
+    }
+
+    #[test]
+    fn splits_representations_by_count() {
+        let mut chunks = RepresentationChunks::new(ExecutionLimits {
+            max_entities_per_fetch: NonZeroUsize::new(2),
+            ..Default::default()
+        });
+        for (index, value) in ["1", "2", "3"].into_iter().enumerate() {
+            assert!(chunks.project(index, project_representation(value)));
+        }
+
+        assert_eq!(chunks.chunks, vec![b"[1,2".to_vec(), b"[3".to_vec()]);
+        assert_eq!(chunks.offsets, vec![0, 2]);
+        assert_eq!(chunks.locate(1), (0, 1));
+        assert_eq!(chunks.locate(2), (1, 0));
+    }
+
+    #[test]
+    fn splits_representations_by_size() {
+        let mut chunks = RepresentationChunks::new(ExecutionLimits {
+            max_representations_size: Some(8),
+            ..Default::default()
+        });
+        for (index, value) in ["11", "22", "3333333333", "4"].into_iter().enumerate() {
+            assert!(chunks.project(index, project_representation(value)));
+        }
+
+        // a representation larger than the limit is sent on its own
+        assert_eq!(
+            chunks.chunks,
+            vec![b"[11,22".to_vec(), b"[3333333333".to_vec(), b"[4".to_vec()]
+        );
+        assert_eq!(chunks.offsets, vec![0, 2, 3]);
+    }
 }
@@ -20,6 +20,8 @@ pub struct ExecutionContext<'a> {
     pub response_headers_aggregator: ResponseHeaderAggregator,
     pub extensions_aggregator: ExtensionsAggregator<'a>,
     pub subgraph_response_cost_tracker: SubgraphResponseCostTracker<'a>,
+    /// Number of errors collected before the query plan started executing.
+    pub initial_error_count: usize,
 }
 
 impl<'a> Default for ExecutionContext<'a> {
@@ -31,6 +33,7 @@ impl<'a> Default for ExecutionContext<'a> {
             response_headers_aggregator: Default::default(),
             extensions_aggregator: Default::default(),
             subgraph_response_cost_tracker: SubgraphResponseCostTracker::new(),
+            initial_error_count: 0,
         }
     }
 }
@@ -39,11 +42,26 @@ impl<'a> ExecutionContext<'a> {
     pub fn new(data: Value<'a>, errors: Vec<GraphQLError>) -> Self {
         ExecutionContext {
             data,
+            initial_error_count: errors.len(),
             errors,
             ..Default::default()
         }
     }
 
+    /// Whether errors occurred while executing the query plan,
+    /// ignoring the errors collected before the execution started (e.g. authorization errors).
+    #[inline]
+    pub fn has_execution_errors(&self) -> bool {
+        self.errors.len() > self.initial_error_count
+    }
+
+    /// Takes the errors delivered in a payload of an incrementally delivered response,
+    /// the errors collected afterwards are all execution errors.
+    pub fn take_errors(&mut self) -> Vec<GraphQLError> {
+        self.initial_error_count = 0;
+        std::mem::take(&mut self.errors)
+    }
+
     pub fn handle_errors(
         &mut self,
         subgraph_name: &str,
@@ -0,0 +1,179 @@
+use bytes::Bytes;
+use hive_router_config::traffic_shaping::TrafficShapingAwsSigV4Config;
+use http::{header, HeaderMap, HeaderName, Method, Uri};
+use object_store::aws::{AmazonS3Builder, AmazonS3ConfigKey, AwsAuthorizer, AwsCredentialProvider};
+
+use crate::executors::error::SubgraphExecutorError;
+
+/// Headers set by the signing of a request.
+const SIGNATURE_HEADERS: [HeaderName; 5] = [
+    header::AUTHORIZATION,
+    header::HOST,
+    HeaderName::from_static("x-amz-date"),
+    HeaderName::from_static("x-amz-content-sha256"),
+    HeaderName::from_static("x-amz-security-token"),
+];
+
+/// The credential chain of `object_store` is only exposed through its S3 client,
+/// the bucket is never used.
+const UNUSED_BUCKET_NAME: &str = "hive-router-sigv4";
+
+/// Signs the requests sent to a subgraph with AWS Signature Version 4.
+///
+/// The credentials are resolved from the environment variables (`AWS_ACCESS_KEY_ID`,
+/// `AWS_SECRET_ACCESS_KEY`, `AWS_SESSION_TOKEN`, web identity or container credentials),
+/// and fall back to the EC2 Instance Metadata Service.
+#[derive(Debug)]
+pub struct SubgraphRequestSigner {
+    credentials: AwsCredentialProvider,
+    region: String,
+    service: String,
+}
+
+impl SubgraphRequestSigner {
+    /// Merges the subgraph-level signing configuration with the global (`all`) one.
+    /// Returns `None` when the signing is disabled for the subgraph.
+    pub fn from_config(
+        global_config: Option<&TrafficShapingAwsSigV4Config>,
+        subgraph_config: Option<&TrafficShapingAwsSigV4Config>,
+    ) -> Result<Option<Self>, String> {
+        let enabled = subgraph_config
+            .and_then(|c| c.enabled)
+            .or_else(|| global_config.and_then(|c| c.enabled))
+            .unwrap_or(false);
+
+        if !enabled {
+            return Ok(None);
+        }
+
+        let service = subgraph_config
+            .and_then(|c| c.service.clone())
+            .or_else(|| global_config.and_then(|c| c.service.clone()))
+            .ok_or("'service' is required when the signing is enabled")?;
+
+        let mut builder = AmazonS3Builder::from_env().with_bucket_name(UNUSED_BUCKET_NAME);
+
+        let region = subgraph_config
+            .and_then(|c| c.region.clone())
+            .or_else(|| global_config.and_then(|c| c.region.clone()))
+            .or_else(|| builder.get_config_value(&AmazonS3ConfigKey::Region))
+            .ok_or("'region' is required when the AWS_REGION environment variable is not set")?;
+        builder = builder.with_region(&region);
+
+        let credentials = builder
+            .build()
+            .map_err(|err| format!("failed to resolve the AWS credentials: {err}"))?
+            .credentials()
+            .clone();
+
+        Ok(Some(Self {
+            credentials,
+            region,
+            service,
+        }))
+    }
+
+    /// Adds the signature of the request to its headers.
+    pub async fn sign(
+        &self,
+        method: &Method,
+        uri: &Uri,
+        headers: &mut HeaderMap,
+        body: &Bytes,
+    ) -> Result<(), SubgraphExecutorError> {
+        let credential = self
+            .credentials
+            .get_credential()
+            .await
+            .map_err(|err| SubgraphExecutorError::AwsSigV4SigningFailure(err.to_string()))?;
+
+        let mut request = http::Request::builder()
+            .method(method.clone())
+            .uri(uri.clone())
+            .body(body.clone().into())?;
+        // Only the headers with a textual value can be signed,
+        // the other ones are still sent, without being part of the signature.
+        for (name, value) in headers.iter() {
+            if value.to_str().is_ok() {
+                request.headers_mut().append(name, value.clone());
+            }
+        }
+
+        AwsAuthorizer::new(&credential, &self.service, &self.region).authorize(&mut request, None);
+
+        for name in SIGNATURE_HEADERS {
+            if let Some(value) = request.headers_mut().remove(&name) {
+                headers.insert(name, value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use hive_router_config::traffic_shaping::TrafficShapingAwsSigV4Config;
+    use http::{HeaderMap, Method, Uri};
+
+    use super::SubgraphRequestSigner;
+
+    fn config(value: serde_json::Value) -> TrafficShapingAwsSigV4Config {
+        serde_json::from_value(value).expect("config should be valid")
+    }
+
+    #[test]
+    fn should_require_service() {
+        let global = config(serde_json::json!({ "enabled": true, "region": "eu-west-1" }));
+        assert!(SubgraphRequestSigner::from_config(Some(&global), None).is_err());
+
+        let disabled = config(serde_json::json!({ "enabled": false }));
+        assert!(
+            SubgraphRequestSigner::from_config(Some(&global), Some(&disabled))
+                .expect("disabled signing should be valid")
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_sign_request() {
+        std::env::set_var("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE");
+        std::env::set_var(
+            "AWS_SECRET_ACCESS_KEY",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+        );
+
+        let global = config(serde_json::json!({ "region": "eu-west-1", "service": "appsync" }));
+        let subgraph = config(serde_json::json!({ "enabled": true }));
+        let signer = SubgraphRequestSigner::from_config(Some(&global), Some(&subgraph))
+            .expect("config should be valid")
+            .expect("signing should be enabled");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            "application/json".parse().unwrap(),
+        );
+        signer
+            .sign(
+                &Method::POST,
+                &Uri::from_static("https://example.appsync-api.eu-west-1.amazonaws.com/graphql"),
+                &mut headers,
+                &Bytes::from_static(b"{\"query\":\"{ __typename }\"}"),
+            )
+            .await
+            .expect("request should be signed");
+
+        let authorization = headers[http::header::AUTHORIZATION].to_str().unwrap();
+        assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(authorization.contains("/eu-west-1/appsync/aws4_request"));
+        assert!(authorization
+            .contains("SignedHeaders=content-type;host;x-amz-content-sha256;x-amz-date"));
+        assert_eq!(
+            headers[http::header::HOST],
+            "example.appsync-api.eu-west-1.amazonaws.com"
+        );
+        assert!(headers.contains_key("x-amz-date"));
+    }
+}
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
 use async_trait::async_trait;
 use futures::stream::BoxStream;
@@ -8,7 +12,7 @@ use sonic_rs::Value;
 
 use crate::{
     executors::error::SubgraphExecutorError, plugin_context::PluginRequestState,
-    response::subgraph_response::SubgraphResponse,
+    response::subgraph_response::SubgraphResponse, variables::serialized::SerializedVariables,
 };
 
 #[async_trait]
@@ -52,16 +56,34 @@ pub struct SubgraphExecutionRequest<'a> {
     pub query: &'a str,
     pub document_name_write_pos: usize,
     pub dedupe: bool,
+    /// Whether the operation can safely be sent more than once (queries, not mutations).
+    /// Only idempotent requests are retried.
+    pub idempotent: bool,
     pub operation_name: Option<String>,
-    // TODO: variables could be stringified before even executing the request
+    /// The variables of the client request used by the subgraph request.
+    /// Use [`SubgraphExecutionRequest::set_variable`] to replace a value,
+    /// for its JSON to be serialized again.
     pub variables: Option<HashMap<&'a str, &'a sonic_rs::Value>>,
+    /// The JSON of the variables of the client request, shared by all the subgraph requests.
+    /// Variables missing from it are serialized when the request is sent.
+    pub serialized_variables: Option<&'a SerializedVariables>,
+    /// The variables replaced since the request was built, serialized when the request is sent.
+    pub overridden_variables: HashSet<&'a str>,
     pub headers: HeaderMap,
     pub raw_variable_values: Option<Vec<(&'a str, Vec<u8>)>>,
     pub extensions: Option<SubgraphRequestExtensions>,
     pub custom_scalar_paths: Option<&'a CustomScalarPaths>,
 }
 
-impl SubgraphExecutionRequest<'_> {
+impl<'a> SubgraphExecutionRequest<'a> {
+    /// Sets the value of a variable, replacing the one of the client request.
+    pub fn set_variable(&mut self, name: &'a str, value: &'a sonic_rs::Value) {
+        self.variables
+            .get_or_insert_with(HashMap::new)
+            .insert(name, value);
+        self.overridden_variables.insert(name);
+    }
+
     pub fn add_request_extensions_field(&mut self, key: String, value: Value) {
         self.extensions
             .get_or_insert_with(HashMap::new)
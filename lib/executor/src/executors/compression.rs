@@ -0,0 +1,361 @@
+use std::io::{Read, Write};
+
+use bytes::Bytes;
+use hive_router_config::traffic_shaping::{CompressionEncoding, TrafficShapingCompressionConfig};
+use http::{header, HeaderMap, HeaderValue};
+
+use crate::executors::error::SubgraphExecutorError;
+
+const ACCEPT_ENCODING_VALUE: HeaderValue = HeaderValue::from_static("gzip, br, zstd");
+const DEFAULT_REQUEST_MIN_SIZE: usize = 1024;
+
+const BROTLI_BUFFER_SIZE: usize = 4096;
+/// Favors the speed over the ratio, the requests are compressed on the hot path.
+const BROTLI_QUALITY: u32 = 4;
+const BROTLI_WINDOW_SIZE: u32 = 22;
+const ZSTD_LEVEL: i32 = 3;
+
+/// Content encodings understood by the router.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Gzip,
+    Brotli,
+    Zstd,
+    /// Never advertised, but still decoded, as some servers use it regardless.
+    Deflate,
+}
+
+impl Encoding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+            Encoding::Deflate => "deflate",
+        }
+    }
+
+    pub fn header_value(self) -> HeaderValue {
+        HeaderValue::from_static(self.as_str())
+    }
+
+    /// Reads the encoding of a body from its `Content-Encoding` header.
+    /// Returns `None` for the bodies sent as is.
+    fn from_headers(headers: &HeaderMap) -> Result<Option<Self>, String> {
+        let Some(content_encoding) = headers.get(header::CONTENT_ENCODING) else {
+            return Ok(None);
+        };
+
+        let content_encoding = content_encoding
+            .to_str()
+            .map_err(|err| format!("invalid content-encoding header: {err}"))?
+            .trim();
+
+        if content_encoding.is_empty() || content_encoding.eq_ignore_ascii_case("identity") {
+            return Ok(None);
+        }
+
+        if content_encoding.as_bytes().contains(&b',') {
+            // Same as for the coprocessor responses, the stacked encodings are not supported,
+            // as they are practically never used.
+            return Err(format!(
+                "stacked content-encoding '{content_encoding}' is not supported"
+            ));
+        }
+
+        [
+            Encoding::Gzip,
+            Encoding::Brotli,
+            Encoding::Zstd,
+            Encoding::Deflate,
+        ]
+        .into_iter()
+        .find(|encoding| content_encoding.eq_ignore_ascii_case(encoding.as_str()))
+        .map(Some)
+        .ok_or_else(|| format!("unsupported content-encoding '{content_encoding}'"))
+    }
+
+    fn compress(self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(body.len() / 2);
+        match self {
+            Encoding::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(&mut out, flate2::Compression::fast());
+                encoder.write_all(body)?;
+                encoder.finish()?;
+            }
+            Encoding::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(
+                    &mut out,
+                    BROTLI_BUFFER_SIZE,
+                    BROTLI_QUALITY,
+                    BROTLI_WINDOW_SIZE,
+                );
+                encoder.write_all(body)?;
+                // The stream is finished when the encoder is dropped
+            }
+            Encoding::Zstd => {
+                zstd::stream::copy_encode(body, &mut out, ZSTD_LEVEL)?;
+            }
+            Encoding::Deflate => {
+                let mut encoder =
+                    flate2::write::ZlibEncoder::new(&mut out, flate2::Compression::fast());
+                encoder.write_all(body)?;
+                encoder.finish()?;
+            }
+        }
+        Ok(out)
+    }
+
+    fn decompress(self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(body.len() * 4);
+        match self {
+            Encoding::Gzip => flate2::read::GzDecoder::new(body).read_to_end(&mut out)?,
+            Encoding::Brotli => {
+                brotli::Decompressor::new(body, BROTLI_BUFFER_SIZE).read_to_end(&mut out)?
+            }
+            Encoding::Zstd => zstd::stream::read::Decoder::new(body)?.read_to_end(&mut out)?,
+            Encoding::Deflate => flate2::read::ZlibDecoder::new(body).read_to_end(&mut out)?,
+        };
+        Ok(out)
+    }
+}
+
+/// A request body compressed before being sent to the subgraph.
+pub struct CompressedBody {
+    pub encoding: Encoding,
+    pub body: Bytes,
+}
+
+/// Compression settings of a subgraph, merged from the global (`all`) and the subgraph-level configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionOptions {
+    pub accept_encoding: bool,
+    pub request_encoding: Option<Encoding>,
+    pub request_min_size: usize,
+}
+
+impl CompressionOptions {
+    pub fn from_config(
+        global_config: Option<&TrafficShapingCompressionConfig>,
+        subgraph_config: Option<&TrafficShapingCompressionConfig>,
+    ) -> Self {
+        let accept_encoding = subgraph_config
+            .and_then(|c| c.accept_encoding)
+            .or_else(|| global_config.and_then(|c| c.accept_encoding))
+            .unwrap_or(true);
+
+        let request_encoding = subgraph_config
+            .and_then(|c| c.request_encoding)
+            .or_else(|| global_config.and_then(|c| c.request_encoding))
+            .and_then(|encoding| match encoding {
+                CompressionEncoding::None => None,
+                CompressionEncoding::Gzip => Some(Encoding::Gzip),
+                CompressionEncoding::Brotli => Some(Encoding::Brotli),
+                CompressionEncoding::Zstd => Some(Encoding::Zstd),
+            });
+
+        let request_min_size = subgraph_config
+            .and_then(|c| c.request_min_size)
+            .or_else(|| global_config.and_then(|c| c.request_min_size))
+            .unwrap_or(DEFAULT_REQUEST_MIN_SIZE);
+
+        Self {
+            accept_encoding,
+            request_encoding,
+            request_min_size,
+        }
+    }
+
+    /// Value of the `Accept-Encoding` header sent to the subgraph,
+    /// `None` when the subgraph should respond uncompressed.
+    pub fn accept_encoding_header(&self) -> Option<HeaderValue> {
+        self.accept_encoding.then_some(ACCEPT_ENCODING_VALUE)
+    }
+
+    /// Compresses the body of a request, when it is large enough to be worth it.
+    /// Returns `None` for the bodies sent as is.
+    pub fn compress_request_body(
+        &self,
+        body: &[u8],
+    ) -> Result<Option<CompressedBody>, SubgraphExecutorError> {
+        let Some(encoding) = self.request_encoding else {
+            return Ok(None);
+        };
+
+        if body.len() < self.request_min_size {
+            return Ok(None);
+        }
+
+        let compressed = encoding.compress(body).map_err(|err| {
+            SubgraphExecutorError::RequestBodyCompressionFailure(encoding.as_str(), err.to_string())
+        })?;
+
+        // A body that does not shrink is sent as is
+        if compressed.len() >= body.len() {
+            return Ok(None);
+        }
+
+        Ok(Some(CompressedBody {
+            encoding,
+            body: Bytes::from(compressed),
+        }))
+    }
+}
+
+/// Decompresses the body of a subgraph response, based on its `Content-Encoding` header.
+///
+/// The `Content-Encoding` and `Content-Length` headers of a decompressed response are removed,
+/// as they describe the body received from the subgraph, not the returned one.
+/// Returns the encoding of the received body, `None` when it was not compressed.
+pub fn decompress_response_body(
+    subgraph_name: &str,
+    headers: &mut HeaderMap,
+    body: Bytes,
+) -> Result<(Bytes, Option<Encoding>), SubgraphExecutorError> {
+    let failure = |reason: String, headers: &HeaderMap| {
+        SubgraphExecutorError::ResponseBodyDecompressionFailure(
+            subgraph_name.to_string(),
+            reason,
+            headers.clone().into(),
+        )
+    };
+
+    let encoding = match Encoding::from_headers(headers) {
+        Ok(Some(encoding)) => encoding,
+        Ok(None) => return Ok((body, None)),
+        Err(reason) => return Err(failure(reason, headers)),
+    };
+
+    // Responses without content, e.g. to HEAD requests, may still carry the header
+    if body.is_empty() {
+        return Ok((body, None));
+    }
+
+    let decompressed = encoding.decompress(&body).map_err(|err| {
+        failure(
+            format!("invalid {} body: {err}", encoding.as_str()),
+            headers,
+        )
+    })?;
+
+    headers.remove(header::CONTENT_ENCODING);
+    headers.remove(header::CONTENT_LENGTH);
+
+    Ok((Bytes::from(decompressed), Some(encoding)))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use hive_router_config::traffic_shaping::TrafficShapingCompressionConfig;
+    use http::{header, HeaderMap, HeaderValue};
+
+    use super::{decompress_response_body, CompressionOptions, Encoding};
+
+    fn config(value: serde_json::Value) -> TrafficShapingCompressionConfig {
+        serde_json::from_value(value).expect("config should be valid")
+    }
+
+    fn representations(count: usize) -> Vec<u8> {
+        let representations = (0..count)
+            .map(|id| format!(r#"{{"__typename":"Product","upc":"{id}"}}"#))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(r#"{{"query":"query($representations:[_Any!]!){{_entities(representations:$representations){{...on Product{{name}}}}}}","variables":{{"representations":[{representations}]}}}}"#)
+            .into_bytes()
+    }
+
+    #[test]
+    fn should_merge_compression_options() {
+        let options = CompressionOptions::from_config(None, None);
+        assert!(options.accept_encoding);
+        assert_eq!(options.request_encoding, None);
+        assert_eq!(options.request_min_size, 1024);
+
+        let global =
+            config(serde_json::json!({ "request_encoding": "zstd", "request_min_size": 10 }));
+        let subgraph =
+            config(serde_json::json!({ "accept_encoding": false, "request_encoding": "br" }));
+        let options = CompressionOptions::from_config(Some(&global), Some(&subgraph));
+        assert!(!options.accept_encoding);
+        assert!(options.accept_encoding_header().is_none());
+        assert_eq!(options.request_encoding, Some(Encoding::Brotli));
+        assert_eq!(options.request_min_size, 10);
+
+        let disabled = config(serde_json::json!({ "request_encoding": "none" }));
+        let options = CompressionOptions::from_config(Some(&global), Some(&disabled));
+        assert_eq!(options.request_encoding, None);
+    }
+
+    #[test]
+    fn should_only_compress_large_request_bodies() {
+        let global = config(serde_json::json!({ "request_encoding": "gzip" }));
+        let options = CompressionOptions::from_config(Some(&global), None);
+
+        let small = representations(2);
+        assert!(options
+            .compress_request_body(&small)
+            .expect("compression should succeed")
+            .is_none());
+
+        let large = representations(100);
+        let compressed = options
+            .compress_request_body(&large)
+            .expect("compression should succeed")
+            .expect("body should be compressed");
+        assert_eq!(compressed.encoding, Encoding::Gzip);
+        assert!(compressed.body.len() < large.len());
+    }
+
+    #[test]
+    fn should_decompress_response_bodies() {
+        let body = representations(50);
+
+        for encoding in [
+            Encoding::Gzip,
+            Encoding::Brotli,
+            Encoding::Zstd,
+            Encoding::Deflate,
+        ] {
+            let compressed = encoding
+                .compress(&body)
+                .expect("compression should succeed");
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::CONTENT_ENCODING,
+                HeaderValue::from_static(encoding.as_str()),
+            );
+            headers.insert(header::CONTENT_LENGTH, compressed.len().into());
+
+            let (decompressed, received_encoding) =
+                decompress_response_body("products", &mut headers, Bytes::from(compressed))
+                    .expect("decompression should succeed");
+            assert_eq!(decompressed.as_ref(), body.as_slice());
+            assert_eq!(received_encoding, Some(encoding));
+            assert!(!headers.contains_key(header::CONTENT_ENCODING));
+            assert!(!headers.contains_key(header::CONTENT_LENGTH));
+        }
+    }
+
+    #[test]
+    fn should_reject_unsupported_response_encodings() {
+        for content_encoding in ["compress", "gzip, br"] {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::CONTENT_ENCODING,
+                HeaderValue::from_static(content_encoding),
+            );
+            assert!(
+                decompress_response_body("products", &mut headers, Bytes::from_static(b"{}"))
+                    .is_err()
+            );
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        assert!(
+            decompress_response_body("products", &mut headers, Bytes::from_static(b"{}")).is_err()
+        );
+    }
+}
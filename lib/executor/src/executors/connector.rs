@@ -0,0 +1,848 @@
+//! Connectors resolve the root fields of a subgraph with calls to a REST API,
+//! instead of sending the subgraph request to a GraphQL server.
+//!
+//! A connector is declared on a field of the supergraph with `@join__directive`:
+//!
+//! ```graphql
+//! type Query @join__type(graph: USERS) {
+//!   user(id: ID!): User
+//!     @join__field(graph: USERS)
+//!     @join__directive(
+//!       graphs: [USERS]
+//!       name: "connect"
+//!       args: {
+//!         http: { GET: "/users/{$args.id}", headers: [{ name: "authorization", from: "authorization" }] }
+//!         selection: "id name email: contact.email"
+//!       }
+//!     )
+//! }
+//! ```
+//!
+//! Relative URLs are resolved against the URL of the subgraph.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{future::join_all, stream::BoxStream};
+use graphql_tools::static_graphql::{
+    query::{
+        Definition as QueryDefinition, Field as QueryField, FragmentDefinition,
+        OperationDefinition, Selection, SelectionSet, TypeCondition, Value as QueryValue,
+    },
+    schema::{Definition, Document, TypeDefinition, Value as SchemaValue},
+};
+use hive_router_query_planner::state::supergraph_state::SupergraphState;
+use http::{header, HeaderMap, HeaderName, HeaderValue, Method, Uri};
+use http_body_util::{BodyExt, Full};
+use sonic_rs::{json, Array, JsonContainerTrait, JsonValueMutTrait, JsonValueTrait, Object, Value};
+use tracing::debug;
+
+use crate::{
+    executors::{
+        common::{SubgraphExecutionRequest, SubgraphExecutor},
+        connector_selection::{follow_path, JsonSelection},
+        error::SubgraphExecutorError,
+        http::HttpClient,
+    },
+    plugin_context::PluginRequestState,
+    response::subgraph_response::SubgraphResponse,
+};
+
+const CONNECT_DIRECTIVE_NAME: &str = "connect";
+const CONNECTOR_ERROR_CODE: &str = "CONNECTOR_FETCH_ERROR";
+
+/// The connectors of a supergraph, by subgraph name.
+#[derive(Debug, Default)]
+pub struct SupergraphConnectors {
+    pub subgraphs: HashMap<String, SubgraphConnectors>,
+}
+
+/// The connectors of the root fields of a subgraph.
+#[derive(Debug)]
+pub struct SubgraphConnectors {
+    query_type: String,
+    mutation_type: Option<String>,
+    query: HashMap<String, Connector>,
+    mutation: HashMap<String, Connector>,
+    /// Named types of the fields of the object types, to know the `__typename` of the objects
+    field_types: Arc<HashMap<String, HashMap<String, String>>>,
+}
+
+#[derive(Debug, Clone)]
+struct Connector {
+    method: Method,
+    url: Template,
+    headers: Vec<ConnectorHeader>,
+    /// Path of the value sent as the JSON body, from the arguments of the field
+    body: Option<Vec<String>>,
+    selection: JsonSelection,
+}
+
+#[derive(Debug, Clone)]
+enum ConnectorHeader {
+    Value {
+        name: HeaderName,
+        value: Template,
+    },
+    /// A header of the subgraph request, after the header propagation rules
+    From {
+        name: HeaderName,
+        from: HeaderName,
+    },
+}
+
+/// A string with `{$args.path}` placeholders, replaced by the arguments of the field.
+#[derive(Debug, Clone)]
+struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone)]
+enum TemplatePart {
+    Text(String),
+    Argument(Vec<String>),
+}
+
+impl SupergraphConnectors {
+    /// Collects the `connect` directives of the root fields of the supergraph.
+    pub fn from_supergraph(
+        document: &Document,
+        supergraph: &SupergraphState,
+    ) -> Result<Self, SubgraphExecutorError> {
+        let mut connectors = Self::default();
+        let mut field_types: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let mut connected_fields = Vec::new();
+
+        for definition in &document.definitions {
+            let Definition::TypeDefinition(TypeDefinition::Object(object)) = definition else {
+                continue;
+            };
+
+            let types = field_types.entry(object.name.clone()).or_default();
+            for field in &object.fields {
+                types.insert(
+                    field.name.clone(),
+                    field.field_type.inner_type().to_string(),
+                );
+
+                for directive in &field.directives {
+                    if directive.name != "join__directive" {
+                        continue;
+                    }
+                    let Some(SchemaValue::String(name)) = argument(&directive.arguments, "name")
+                    else {
+                        continue;
+                    };
+                    if name != CONNECT_DIRECTIVE_NAME {
+                        continue;
+                    }
+
+                    let coordinate = format!("{}.{}", object.name, field.name);
+                    let is_mutation = supergraph.mutation_type.as_deref() == Some(&object.name);
+                    if object.name != supergraph.query_type && !is_mutation {
+                        return Err(SubgraphExecutorError::ConnectorDefinitionInvalid(
+                            coordinate,
+                            "only fields of the Query and Mutation types can be connected"
+                                .to_string(),
+                        ));
+                    }
+
+                    let connector = argument(&directive.arguments, "args")
+                        .ok_or_else(|| "missing 'args'".to_string())
+                        .and_then(Connector::parse)
+                        .map_err(|err| {
+                            SubgraphExecutorError::ConnectorDefinitionInvalid(
+                                coordinate.clone(),
+                                err,
+                            )
+                        })?;
+
+                    let graphs = match argument(&directive.arguments, "graphs") {
+                        Some(SchemaValue::List(graphs)) => graphs.as_slice(),
+                        _ => &[],
+                    };
+                    for graph in graphs {
+                        let (SchemaValue::Enum(graph_id) | SchemaValue::String(graph_id)) = graph
+                        else {
+                            continue;
+                        };
+                        let subgraph_name =
+                            supergraph.resolve_graph_id(graph_id).map_err(|_| {
+                                SubgraphExecutorError::ConnectorDefinitionInvalid(
+                                    coordinate.clone(),
+                                    format!("unknown graph '{graph_id}'"),
+                                )
+                            })?;
+                        connected_fields.push((
+                            subgraph_name.0,
+                            is_mutation,
+                            field.name.clone(),
+                            connector.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let field_types = Arc::new(field_types);
+        for (subgraph_name, is_mutation, field_name, connector) in connected_fields {
+            let subgraph_connectors =
+                connectors
+                    .subgraphs
+                    .entry(subgraph_name)
+                    .or_insert_with(|| SubgraphConnectors {
+                        query_type: supergraph.query_type.clone(),
+                        mutation_type: supergraph.mutation_type.clone(),
+                        query: HashMap::new(),
+                        mutation: HashMap::new(),
+                        field_types: field_types.clone(),
+                    });
+            let fields = if is_mutation {
+                &mut subgraph_connectors.mutation
+            } else {
+                &mut subgraph_connectors.query
+            };
+            fields.insert(field_name, connector);
+        }
+
+        Ok(connectors)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subgraphs.is_empty()
+    }
+}
+
+impl Connector {
+    fn parse(args: &SchemaValue) -> Result<Self, String> {
+        let SchemaValue::Object(args) = args else {
+            return Err("'args' must be an object".to_string());
+        };
+        let Some(SchemaValue::Object(http)) = argument(args, "http") else {
+            return Err("missing 'http' object".to_string());
+        };
+
+        let mut method_and_url = None;
+        for method in ["GET", "POST", "PUT", "PATCH", "DELETE"] {
+            if let Some(url) = argument(http, method) {
+                let SchemaValue::String(url) = url else {
+                    return Err(format!("'http.{method}' must be a string"));
+                };
+                if method_and_url.is_some() {
+                    return Err("'http' must have a single method".to_string());
+                }
+                method_and_url = Some((method, url));
+            }
+        }
+        let Some((method, url)) = method_and_url else {
+            return Err("'http' must have one of GET, POST, PUT, PATCH or DELETE".to_string());
+        };
+
+        let headers = match argument(http, "headers") {
+            Some(SchemaValue::List(headers)) => headers
+                .iter()
+                .map(ConnectorHeader::parse)
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err("'http.headers' must be a list".to_string()),
+            None => Vec::new(),
+        };
+
+        let body = match argument(http, "body") {
+            Some(SchemaValue::String(body)) => Some(parse_argument_path(body)?),
+            Some(_) => return Err("'http.body' must be a string".to_string()),
+            None => None,
+        };
+
+        let selection = match argument(args, "selection") {
+            Some(SchemaValue::String(selection)) => JsonSelection::parse(selection)?,
+            Some(_) => return Err("'selection' must be a string".to_string()),
+            None => JsonSelection::default(),
+        };
+
+        Ok(Self {
+            method: method
+                .parse()
+                .map_err(|_| format!("invalid method '{method}'"))?,
+            url: Template::parse(url)?,
+            headers,
+            body,
+            selection,
+        })
+    }
+}
+
+impl ConnectorHeader {
+    fn parse(header: &SchemaValue) -> Result<Self, String> {
+        let SchemaValue::Object(header) = header else {
+            return Err("a header must be an object".to_string());
+        };
+        let name = match argument(header, "name") {
+            Some(SchemaValue::String(name)) => parse_header_name(name)?,
+            _ => return Err("a header must have a 'name'".to_string()),
+        };
+
+        match (argument(header, "value"), argument(header, "from")) {
+            (Some(SchemaValue::String(value)), None) => Ok(ConnectorHeader::Value {
+                name,
+                value: Template::parse(value)?,
+            }),
+            (None, Some(SchemaValue::String(from))) => Ok(ConnectorHeader::From {
+                name,
+                from: parse_header_name(from)?,
+            }),
+            _ => Err(format!(
+                "header '{name}' must have either a 'value' or a 'from' string"
+            )),
+        }
+    }
+}
+
+impl Template {
+    fn parse(input: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut rest = input;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(TemplatePart::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("unclosed placeholder in '{input}'"))?;
+            parts.push(TemplatePart::Argument(parse_argument_path(
+                &rest[start + 1..start + end],
+            )?));
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Text(rest.to_string()));
+        }
+
+        Ok(Self { parts })
+    }
+
+    fn render(&self, arguments: &Value, url_encode: bool) -> String {
+        let mut output = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Text(text) => output.push_str(text),
+                TemplatePart::Argument(path) => {
+                    let value = follow_path(arguments, path);
+                    let text = match value.as_str() {
+                        Some(text) => text.to_string(),
+                        None if value.is_null() => String::new(),
+                        None => sonic_rs::to_string(&value).unwrap_or_default(),
+                    };
+                    if url_encode {
+                        percent_encode(&text, &mut output);
+                    } else {
+                        output.push_str(&text);
+                    }
+                }
+            }
+        }
+        output
+    }
+}
+
+/// Parses `$args.a.b` into the path `a.b` in the arguments of the field.
+fn parse_argument_path(input: &str) -> Result<Vec<String>, String> {
+    let input = input.trim();
+    let path = input
+        .strip_prefix("$args")
+        .ok_or_else(|| format!("'{input}' must start with '$args'"))?;
+
+    Ok(path
+        .split('.')
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn parse_header_name(name: &str) -> Result<HeaderName, String> {
+    HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("invalid header name '{name}'"))
+}
+
+fn percent_encode(text: &str, output: &mut String) {
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            output.push(byte as char);
+        } else {
+            output.push_str(&format!("%{:02X}", byte));
+        }
+    }
+}
+
+fn argument<'a>(arguments: &'a [(String, SchemaValue)], name: &str) -> Option<&'a SchemaValue> {
+    arguments
+        .iter()
+        .find(|(argument_name, _)| argument_name == name)
+        .map(|(_, value)| value)
+}
+
+/// Resolves the root fields of the subgraph requests with their connectors.
+pub struct ConnectorSubgraphExecutor {
+    subgraph_name: String,
+    endpoint: Uri,
+    client: Arc<HttpClient>,
+    connectors: Arc<SubgraphConnectors>,
+}
+
+impl ConnectorSubgraphExecutor {
+    pub fn new(
+        subgraph_name: String,
+        endpoint: Uri,
+        client: Arc<HttpClient>,
+        connectors: Arc<SubgraphConnectors>,
+    ) -> Self {
+        Self {
+            subgraph_name,
+            endpoint,
+            client,
+            connectors,
+        }
+    }
+
+    fn failure(&self, message: impl Into<String>) -> SubgraphExecutorError {
+        SubgraphExecutorError::ConnectorFailure(self.subgraph_name.clone(), message.into())
+    }
+
+    async fn resolve_field(
+        &self,
+        field: &QueryField,
+        root_type: &str,
+        connectors: &HashMap<String, Connector>,
+        context: &ResolveContext<'_>,
+        timeout: Option<Duration>,
+    ) -> Result<Value, String> {
+        if field.name == "__typename" {
+            return Ok(Value::from(root_type));
+        }
+
+        let connector = connectors
+            .get(&field.name)
+            .ok_or_else(|| format!("field '{}' has no connector", field.name))?;
+        let mut arguments = Object::new();
+        for (name, value) in &field.arguments {
+            arguments.insert(name, context.resolve_value(value));
+        }
+        let arguments = Value::from(arguments);
+
+        let value = self
+            .fetch(connector, &arguments, context.headers, timeout)
+            .await?;
+
+        let field_type = context.field_type(root_type, &field.name);
+        Ok(context.project(&value, &field.selection_set, field_type))
+    }
+
+    async fn fetch(
+        &self,
+        connector: &Connector,
+        arguments: &Value,
+        subgraph_headers: &HeaderMap,
+        timeout: Option<Duration>,
+    ) -> Result<Value, String> {
+        let url = connector.url.render(arguments, true);
+        let url = if url.starts_with('/') {
+            format!("{}{}", self.endpoint.to_string().trim_end_matches('/'), url)
+        } else {
+            url
+        };
+        let uri = url
+            .parse::<Uri>()
+            .map_err(|err| format!("invalid URL '{url}': {err}"))?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        for connector_header in &connector.headers {
+            match connector_header {
+                ConnectorHeader::Value { name, value } => {
+                    let value = HeaderValue::from_str(&value.render(arguments, false))
+                        .map_err(|err| format!("invalid value of header '{name}': {err}"))?;
+                    headers.insert(name.clone(), value);
+                }
+                ConnectorHeader::From { name, from } => {
+                    if let Some(value) = subgraph_headers.get(from) {
+                        headers.insert(name.clone(), value.clone());
+                    }
+                }
+            }
+        }
+
+        let body = match &connector.body {
+            Some(path) => {
+                headers.insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/json"),
+                );
+                Bytes::from(
+                    sonic_rs::to_vec(&follow_path(arguments, path))
+                        .map_err(|err| err.to_string())?,
+                )
+            }
+            None => Bytes::new(),
+        };
+
+        let mut request = hyper::Request::builder()
+            .method(connector.method.clone())
+            .uri(uri)
+            .body(Full::new(body))
+            .map_err(|err| err.to_string())?;
+        *request.headers_mut() = headers;
+
+        debug!(
+            subgraph_name = %self.subgraph_name,
+            "making connector request to {} {}",
+            connector.method,
+            url
+        );
+
+        let response_future = self.client.request(request);
+        let response = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, response_future)
+                .await
+                .map_err(|_| "the request timed out".to_string())?,
+            None => response_future.await,
+        }
+        .map_err(|err| err.to_string())?;
+
+        let status = response.status();
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(|err| err.to_string())?
+            .to_bytes();
+
+        if !status.is_success() {
+            return Err(format!("the REST API responded with status {status}"));
+        }
+
+        let value: Value = if body.is_empty() {
+            Value::new_null()
+        } else {
+            sonic_rs::from_slice(&body)
+                .map_err(|err| format!("the REST API responded with invalid JSON: {err}"))?
+        };
+
+        Ok(connector.selection.apply(&value))
+    }
+}
+
+/// What the fields of a subgraph request are resolved with.
+struct ResolveContext<'a> {
+    fragments: HashMap<&'a str, &'a FragmentDefinition>,
+    variables: HashMap<&'a str, &'a Value>,
+    headers: &'a HeaderMap,
+    field_types: &'a HashMap<String, HashMap<String, String>>,
+}
+
+impl ResolveContext<'_> {
+    fn field_type(&self, type_name: &str, field_name: &str) -> Option<&str> {
+        self.field_types
+            .get(type_name)
+            .and_then(|fields| fields.get(field_name))
+            .map(String::as_str)
+    }
+
+    fn resolve_value(&self, value: &QueryValue) -> Value {
+        match value {
+            QueryValue::Variable(name) => self
+                .variables
+                .get(name.as_str())
+                .map(|value| (*value).clone())
+                .unwrap_or_else(Value::new_null),
+            QueryValue::Int(number) => number
+                .as_i64()
+                .map(Value::from)
+                .unwrap_or_else(Value::new_null),
+            QueryValue::Float(number) => Value::new_f64(*number).unwrap_or_else(Value::new_null),
+            QueryValue::String(text) => Value::from(text.as_str()),
+            QueryValue::Boolean(boolean) => Value::from(*boolean),
+            QueryValue::Null => Value::new_null(),
+            QueryValue::Enum(name) => Value::from(name.as_str()),
+            QueryValue::List(items) => {
+                let mut array = Value::new_array_with(items.len());
+                for item in items {
+                    array.append_value(self.resolve_value(item));
+                }
+                array
+            }
+            QueryValue::Object(fields) => {
+                let mut object = Object::new();
+                for (name, value) in fields {
+                    object.insert(name, self.resolve_value(value));
+                }
+                Value::from(object)
+            }
+        }
+    }
+
+    /// Shapes the value mapped by the connector as the selection set of the subgraph request.
+    fn project(
+        &self,
+        value: &Value,
+        selection_set: &SelectionSet,
+        type_name: Option<&str>,
+    ) -> Value {
+        if selection_set.items.is_empty() {
+            return value.clone();
+        }
+
+        if let Some(items) = value.as_array() {
+            let mut array = Value::new_array_with(items.len());
+            for item in items.iter() {
+                array.append_value(self.project(item, selection_set, type_name));
+            }
+            return array;
+        }
+
+        match value.as_object() {
+            Some(object) => {
+                let type_name = object
+                    .get(&"__typename")
+                    .and_then(|type_name| type_name.as_str())
+                    .or(type_name);
+                let mut result = Object::new();
+                self.project_into(object, selection_set, type_name, &mut result);
+                Value::from(result)
+            }
+            None => Value::new_null(),
+        }
+    }
+
+    fn project_into(
+        &self,
+        object: &Object,
+        selection_set: &SelectionSet,
+        type_name: Option<&str>,
+        result: &mut Object,
+    ) {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    let response_key = field.alias.as_ref().unwrap_or(&field.name);
+                    let value = if field.name == "__typename" {
+                        type_name.map(Value::from).unwrap_or_else(Value::new_null)
+                    } else {
+                        let field_type =
+                            type_name.and_then(|type_name| self.field_type(type_name, &field.name));
+                        match object.get(&field.name) {
+                            Some(value) => self.project(value, &field.selection_set, field_type),
+                            None => {
+                                self.project(&Value::new_null(), &field.selection_set, field_type)
+                            }
+                        }
+                    };
+                    result.insert(response_key, value);
+                }
+                Selection::InlineFragment(fragment) => {
+                    if self.applies(fragment.type_condition.as_ref(), type_name) {
+                        self.project_into(object, &fragment.selection_set, type_name, result);
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    if let Some(fragment) = self.fragments.get(spread.fragment_name.as_str()) {
+                        if self.applies(Some(&fragment.type_condition), type_name) {
+                            self.project_into(object, &fragment.selection_set, type_name, result);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// A fragment applies to objects of its type, and to all objects for abstract types.
+    fn applies(&self, type_condition: Option<&TypeCondition>, type_name: Option<&str>) -> bool {
+        let Some(TypeCondition::On(condition)) = type_condition else {
+            return true;
+        };
+
+        match type_name {
+            Some(type_name) => {
+                condition == type_name || !self.field_types.contains_key(condition.as_str())
+            }
+            None => true,
+        }
+    }
+}
+
+/// Collects the root fields of the selection set, through its fragments.
+fn collect_root_fields<'a>(
+    selection_set: &'a SelectionSet,
+    fragments: &HashMap<&str, &'a FragmentDefinition>,
+    fields: &mut Vec<&'a QueryField>,
+) {
+    for selection in &selection_set.items {
+        match selection {
+            Selection::Field(field) => fields.push(field),
+            Selection::InlineFragment(fragment) => {
+                collect_root_fields(&fragment.selection_set, fragments, fields)
+            }
+            Selection::FragmentSpread(spread) => {
+                if let Some(fragment) = fragments.get(spread.fragment_name.as_str()) {
+                    collect_root_fields(&fragment.selection_set, fragments, fields)
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SubgraphExecutor for ConnectorSubgraphExecutor {
+    fn endpoint(&self) -> &Uri {
+        &self.endpoint
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, fields(subgraph_name = %self.subgraph_name))]
+    async fn execute<'a>(
+        &self,
+        execution_request: SubgraphExecutionRequest<'a>,
+        timeout: Option<Duration>,
+        _plugin_req_state: Option<&'a PluginRequestState<'a>>,
+    ) -> Result<SubgraphResponse<'static>, SubgraphExecutorError> {
+        let document = graphql_tools::parser::parse_query::<String>(execution_request.query)
+            .map_err(|err| self.failure(err.to_string()))?
+            .into_static();
+
+        let mut operation = None;
+        let mut fragments = HashMap::new();
+        for definition in &document.definitions {
+            match definition {
+                QueryDefinition::Operation(definition) => operation = Some(definition),
+                QueryDefinition::Fragment(fragment) => {
+                    fragments.insert(fragment.name.as_str(), fragment);
+                }
+            }
+        }
+
+        let connectors = &self.connectors;
+        let (selection_set, root_type, field_connectors, is_mutation) = match operation {
+            Some(OperationDefinition::Query(query)) => (
+                &query.selection_set,
+                connectors.query_type.as_str(),
+                &connectors.query,
+                false,
+            ),
+            Some(OperationDefinition::SelectionSet(selection_set)) => (
+                selection_set,
+                connectors.query_type.as_str(),
+                &connectors.query,
+                false,
+            ),
+            Some(OperationDefinition::Mutation(mutation)) => (
+                &mutation.selection_set,
+                connectors.mutation_type.as_deref().unwrap_or("Mutation"),
+                &connectors.mutation,
+                true,
+            ),
+            Some(OperationDefinition::Subscription(_)) => {
+                return Err(self.failure("subscriptions can't be resolved by connectors"))
+            }
+            None => return Err(self.failure("the request has no operation")),
+        };
+
+        let variables = execution_request
+            .variables
+            .iter()
+            .flatten()
+            .map(|(name, value)| (*name, *value))
+            .collect();
+        let context = ResolveContext {
+            fragments,
+            variables,
+            headers: &execution_request.headers,
+            field_types: &connectors.field_types,
+        };
+
+        let mut fields = Vec::new();
+        collect_root_fields(selection_set, &context.fragments, &mut fields);
+
+        // mutation fields are resolved one after the other, like in a GraphQL server
+        let results = if is_mutation {
+            let mut results = Vec::with_capacity(fields.len());
+            for field in &fields {
+                results.push(
+                    self.resolve_field(field, root_type, field_connectors, &context, timeout)
+                        .await,
+                );
+            }
+            results
+        } else {
+            join_all(fields.iter().map(|field| {
+                self.resolve_field(field, root_type, field_connectors, &context, timeout)
+            }))
+            .await
+        };
+
+        let mut data = Object::new();
+        let mut errors = Array::new();
+        for (field, result) in fields.iter().zip(results) {
+            let response_key = field.alias.as_ref().unwrap_or(&field.name);
+            match result {
+                Ok(value) => {
+                    data.insert(response_key, value);
+                }
+                Err(message) => {
+                    errors.push(json!({
+                        "message": message,
+                        "path": [response_key.as_str()],
+                        "extensions": { "code": CONNECTOR_ERROR_CODE }
+                    }));
+                    data.insert(response_key, Value::new_null());
+                }
+            }
+        }
+
+        let mut response = json!({ "data": data });
+        if !errors.is_empty() {
+            if let Some(response) = response.as_object_mut() {
+                response.insert(&"errors", errors);
+            }
+        }
+        let bytes = sonic_rs::to_vec(&response).map_err(|err| self.failure(err.to_string()))?;
+
+        SubgraphResponse::deserialize_from_bytes(
+            Bytes::from(bytes),
+            execution_request.custom_scalar_paths,
+        )
+    }
+
+    async fn subscribe<'a>(
+        &self,
+        _execution_request: SubgraphExecutionRequest<'a>,
+        _timeout: Option<Duration>,
+    ) -> Result<
+        BoxStream<'static, Result<SubgraphResponse<'static>, SubgraphExecutorError>>,
+        SubgraphExecutorError,
+    > {
+        Err(self.failure("subscriptions can't be resolved by connectors"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sonic_rs::json;
+
+    use super::Template;
+
+    #[test]
+    fn renders_url_templates_with_encoded_arguments() {
+        let template =
+            Template::parse("/users/{$args.id}?q={$args.filter.name}").expect("invalid template");
+
+        assert_eq!(
+            template.render(&json!({ "id": 1, "filter": { "name": "Ada L" } }), true),
+            "/users/1?q=Ada%20L"
+        );
+        assert_eq!(template.render(&json!({}), false), "/users/?q=");
+    }
+
+    #[test]
+    fn rejects_placeholders_outside_of_the_arguments() {
+        assert!(Template::parse("/users/{$config.id}").is_err());
+        assert!(Template::parse("/users/{$args.id").is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! The JSON selection of a connector, mapping the JSON response of a REST API
+//! to the fields of the GraphQL type the connector resolves.
+//!
+//! `id name email: contact.email address { city }` keeps `id` and `name` as they are,
+//! takes `email` from `contact.email` and only `city` from the `address` object.
+//! A leading `$.path` selects the value to map first, as in `$.results { id name }`.
+
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Object, Value};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JsonSelection {
+    /// Path to the value to map, from the value the selection is applied to
+    root: Vec<String>,
+    /// Fields of the mapped objects, all of them are kept when empty
+    items: Vec<JsonSelectionItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct JsonSelectionItem {
+    name: String,
+    path: Vec<String>,
+    selection: Option<JsonSelection>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Dollar,
+    Dot,
+    Colon,
+    OpenBrace,
+    CloseBrace,
+}
+
+impl JsonSelection {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let tokens = tokenize(input)?;
+        let mut position = 0;
+        let selection = parse_selection(&tokens, &mut position)?;
+
+        match tokens.get(position) {
+            None => Ok(selection),
+            Some(token) => Err(format!("unexpected {:?} in JSON selection", token)),
+        }
+    }
+
+    pub fn apply(&self, value: &Value) -> Value {
+        self.apply_items(&follow_path(value, &self.root))
+    }
+
+    fn apply_items(&self, value: &Value) -> Value {
+        if let Some(items) = value.as_array() {
+            let mut array = Value::new_array_with(items.len());
+            for item in items.iter() {
+                array.append_value(self.apply_items(item));
+            }
+            return array;
+        }
+
+        if !value.is_object() || self.items.is_empty() {
+            return value.clone();
+        }
+
+        let mut object = Object::new();
+        for item in &self.items {
+            let item_value = follow_path(value, &item.path);
+            let item_value = match &item.selection {
+                Some(selection) => selection.apply(&item_value),
+                None => item_value,
+            };
+            object.insert(&item.name, item_value);
+        }
+        Value::from(object)
+    }
+}
+
+/// Follows the keys of the path, through the items of the lists on the way.
+/// A missing key results in `null`.
+pub fn follow_path(value: &Value, path: &[String]) -> Value {
+    let Some((key, rest)) = path.split_first() else {
+        return value.clone();
+    };
+
+    if let Some(items) = value.as_array() {
+        let mut array = Value::new_array_with(items.len());
+        for item in items.iter() {
+            array.append_value(follow_path(item, path));
+        }
+        return array;
+    }
+
+    value
+        .as_object()
+        .and_then(|object| object.get(key))
+        .map(|child| follow_path(child, rest))
+        .unwrap_or_else(Value::new_null)
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '$' => tokens.push(Token::Dollar),
+            '.' => tokens.push(Token::Dot),
+            ':' => tokens.push(Token::Colon),
+            '{' => tokens.push(Token::OpenBrace),
+            '}' => tokens.push(Token::CloseBrace),
+            ',' => {}
+            char if char.is_whitespace() => {}
+            char if char.is_ascii_alphanumeric() || char == '_' => {
+                let mut name = String::from(char);
+                while let Some(next) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    name.push(next);
+                }
+                tokens.push(Token::Name(name));
+            }
+            other => return Err(format!("unexpected character '{other}' in JSON selection")),
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_selection(tokens: &[Token], position: &mut usize) -> Result<JsonSelection, String> {
+    let mut selection = JsonSelection::default();
+
+    if tokens.get(*position) == Some(&Token::Dollar) {
+        *position += 1;
+        while tokens.get(*position) == Some(&Token::Dot) {
+            *position += 1;
+            selection.root.push(expect_name(tokens, position)?);
+        }
+    }
+
+    while let Some(Token::Name(_)) = tokens.get(*position) {
+        let name = expect_name(tokens, position)?;
+        let path = if tokens.get(*position) == Some(&Token::Colon) {
+            *position += 1;
+            parse_path(tokens, position)?
+        } else {
+            vec![name.clone()]
+        };
+        let item_selection = if tokens.get(*position) == Some(&Token::OpenBrace) {
+            *position += 1;
+            let item_selection = parse_selection(tokens, position)?;
+            if tokens.get(*position) != Some(&Token::CloseBrace) {
+                return Err(format!("expected '}}' to close the selection of '{name}'"));
+            }
+            *position += 1;
+            Some(item_selection)
+        } else {
+            None
+        };
+
+        selection.items.push(JsonSelectionItem {
+            name,
+            path,
+            selection: item_selection,
+        });
+    }
+
+    Ok(selection)
+}
+
+fn parse_path(tokens: &[Token], position: &mut usize) -> Result<Vec<String>, String> {
+    let mut path = vec![expect_name(tokens, position)?];
+    while tokens.get(*position) == Some(&Token::Dot) {
+        *position += 1;
+        path.push(expect_name(tokens, position)?);
+    }
+    Ok(path)
+}
+
+fn expect_name(tokens: &[Token], position: &mut usize) -> Result<String, String> {
+    match tokens.get(*position) {
+        Some(Token::Name(name)) => {
+            *position += 1;
+            Ok(name.clone())
+        }
+        Some(token) => Err(format!(
+            "expected a name in JSON selection, found {:?}",
+            token
+        )),
+        None => Err("unexpected end of JSON selection".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sonic_rs::json;
+
+    use super::JsonSelection;
+
+    #[test]
+    fn maps_renamed_and_nested_fields() {
+        let selection = JsonSelection::parse("id name email: contact.email address { city }")
+            .expect("failed to parse selection");
+
+        let value = json!({
+            "id": 1,
+            "name": "Ada",
+            "contact": { "email": "ada@example.com", "phone": "123" },
+            "address": { "city": "London", "street": "Baker Street" },
+            "internal": true
+        });
+
+        assert_eq!(
+            selection.apply(&value),
+            json!({
+                "id": 1,
+                "name": "Ada",
+                "email": "ada@example.com",
+                "address": { "city": "London" }
+            })
+        );
+    }
+
+    #[test]
+    fn maps_lists_under_a_root_path() {
+        let selection =
+            JsonSelection::parse("$.data.results { id }").expect("failed to parse selection");
+
+        let value = json!({ "data": { "results": [{ "id": 1, "x": 1 }, { "id": 2 }] } });
+
+        assert_eq!(selection.apply(&value), json!([{ "id": 1 }, { "id": 2 }]));
+    }
+
+    #[test]
+    fn rejects_unclosed_selections() {
+        assert!(JsonSelection::parse("address { city").is_err());
+        assert!(JsonSelection::parse("email: ").is_err());
+    }
+}
@@ -1,5 +1,8 @@
 use ahash::RandomState;
-use std::collections::BTreeMap;
+use hive_router_config::traffic_shaping::{
+    TrafficShapingRouterDedupeHeadersConfig, TrafficShapingRouterDedupeHeadersKeyword,
+};
+use std::collections::{BTreeMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::OnceLock;
@@ -7,28 +10,71 @@ use xxhash_rust::xxh3::Xxh3;
 
 use crate::executors::http::SendRequestOpts;
 
-impl Hash for SendRequestOpts<'_> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+/// Headers participating in the deduplication key of the router requests and of the subgraph requests.
+#[derive(Clone)]
+pub enum RouterRequestDedupeHeaderPolicy {
+    All,
+    None,
+    Include(HashSet<String>),
+}
+
+impl RouterRequestDedupeHeaderPolicy {
+    #[inline]
+    pub fn should_include(&self, header_name: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::None => false,
+            Self::Include(allowed_headers) => allowed_headers.contains(header_name),
+        }
+    }
+}
+
+impl From<&TrafficShapingRouterDedupeHeadersConfig> for RouterRequestDedupeHeaderPolicy {
+    fn from(headers: &TrafficShapingRouterDedupeHeadersConfig) -> Self {
+        match headers {
+            TrafficShapingRouterDedupeHeadersConfig::Keyword(
+                TrafficShapingRouterDedupeHeadersKeyword::All,
+            ) => Self::All,
+            TrafficShapingRouterDedupeHeadersConfig::Keyword(
+                TrafficShapingRouterDedupeHeadersKeyword::None,
+            ) => Self::None,
+            TrafficShapingRouterDedupeHeadersConfig::Include { include } => {
+                if include.is_empty() {
+                    return Self::None;
+                }
+
+                let mut dedupe_headers = HashSet::with_capacity(include.len());
+                for header in include {
+                    dedupe_headers.insert(header.get_header_ref().as_str().to_owned());
+                }
+
+                Self::Include(dedupe_headers)
+            }
+        }
+    }
+}
+
+impl SendRequestOpts<'_> {
+    /// Generate a hash for the request options, used for deduplication.
+    ///
+    /// Only the headers selected by the policy are part of the hash.
+    pub fn fingerprint(&self, header_policy: &RouterRequestDedupeHeaderPolicy) -> u64 {
         // BTreeMap to ensure case-insensitivity and consistent order for hashing
         let mut headers = BTreeMap::new();
         for (header_name, header_value) in self.headers.iter() {
+            if !header_policy.should_include(header_name.as_str()) {
+                continue;
+            }
             if let Ok(value_str) = header_value.to_str() {
                 headers.insert(header_name.as_str(), value_str);
             }
         }
 
-        self.method.hash(state);
-        self.endpoint.hash(state);
-        headers.hash(state);
-        self.body.hash(state);
-    }
-}
-
-impl SendRequestOpts<'_> {
-    /// Generate a hash for the request options, used for deduplication.
-    pub fn fingerprint(&self) -> u64 {
         let mut hasher = Xxh3::new();
-        self.hash(&mut hasher);
+        self.method.hash(&mut hasher);
+        self.endpoint.hash(&mut hasher);
+        headers.hash(&mut hasher);
+        self.body.hash(&mut hasher);
         hasher.finish()
     }
 }
@@ -44,3 +90,47 @@ pub fn unique_leader_fingerprint() -> u64 {
         LEADER_SALT.get_or_init(|| RandomState::new().hash_one(b"unique-leader-fingerprint"));
     idx ^ salt
 }
+
+#[cfg(test)]
+mod tests {
+    use super::RouterRequestDedupeHeaderPolicy;
+    use hive_router_config::traffic_shaping::{
+        TrafficShapingRouterDedupeHeadersConfig, TrafficShapingRouterDedupeHeadersKeyword,
+    };
+
+    #[test]
+    fn should_map_header_variants_to_policy() {
+        let all = TrafficShapingRouterDedupeHeadersConfig::Keyword(
+            TrafficShapingRouterDedupeHeadersKeyword::All,
+        );
+        assert!(matches!(
+            RouterRequestDedupeHeaderPolicy::from(&all),
+            RouterRequestDedupeHeaderPolicy::All
+        ));
+
+        let none = TrafficShapingRouterDedupeHeadersConfig::Keyword(
+            TrafficShapingRouterDedupeHeadersKeyword::None,
+        );
+        assert!(matches!(
+            RouterRequestDedupeHeaderPolicy::from(&none),
+            RouterRequestDedupeHeaderPolicy::None
+        ));
+
+        let empty = TrafficShapingRouterDedupeHeadersConfig::Include { include: vec![] };
+        assert!(matches!(
+            RouterRequestDedupeHeaderPolicy::from(&empty),
+            RouterRequestDedupeHeaderPolicy::None
+        ));
+
+        let include = TrafficShapingRouterDedupeHeadersConfig::Include {
+            include: vec!["Authorization".into()],
+        };
+        let include_policy = RouterRequestDedupeHeaderPolicy::from(&include);
+        assert!(matches!(
+            include_policy,
+            RouterRequestDedupeHeaderPolicy::Include(_)
+        ));
+        assert!(include_policy.should_include("authorization"));
+        assert!(!include_policy.should_include("cookie"));
+    }
+}
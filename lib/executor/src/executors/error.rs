@@ -5,6 +5,7 @@ use http::{uri::InvalidUri, HeaderMap, StatusCode};
 use rustls::server::VerifierBuilderError;
 use strum::IntoStaticStr;
 
+use crate::coprocessor::CoprocessorError;
 use crate::response::subgraph_response::SubgraphResponse;
 
 #[derive(thiserror::Error, Debug, IntoStaticStr)]
@@ -12,6 +13,15 @@ pub enum SubgraphExecutorError {
     #[error("Failed to parse endpoint \"{0}\" as URI: {1}")]
     #[strum(serialize = "SUBGRAPH_ENDPOINT_PARSE_FAILURE")]
     EndpointParseFailure(String, InvalidUri),
+    #[error("Endpoint \"{0}\" is not a valid subgraph URL: {1}")]
+    #[strum(serialize = "SUBGRAPH_ENDPOINT_INVALID")]
+    EndpointInvalid(String, &'static str),
+    #[error("Failed to parse Unix socket endpoint \"{0}\": {1}")]
+    #[strum(serialize = "SUBGRAPH_UNIX_SOCKET_ENDPOINT_PARSE_FAILURE")]
+    UnixSocketEndpointParseFailure(String, String),
+    #[error("Subgraph \"{0}\" is reached over a Unix socket, which only supports the HTTP and SSE subscription protocols")]
+    #[strum(serialize = "SUBGRAPH_UNIX_SOCKET_UNSUPPORTED_PROTOCOL")]
+    UnixSocketUnsupportedProtocol(String),
     #[error("Failed to build WebSocket endpoint \"{0}\" as URI: {1}")]
     #[strum(serialize = "SUBGRAPH_WEBSOCKET_ENDPOINT_BUILD_FAILURE")]
     WebSocketEndpointBuildFailure(String, http::Error),
@@ -53,6 +63,12 @@ pub enum SubgraphExecutorError {
     #[error("Failed to read response body from subgraph \"{0}\": {1}")]
     #[strum(serialize = "SUBGRAPH_RESPONSE_BODY_READ_FAILURE")]
     ResponseBodyReadFailure(String, String, Arc<HeaderMap>),
+    #[error("Failed to decompress response body from subgraph \"{0}\": {1}")]
+    #[strum(serialize = "SUBGRAPH_RESPONSE_BODY_DECOMPRESSION_FAILURE")]
+    ResponseBodyDecompressionFailure(String, String, Arc<HeaderMap>),
+    #[error("Failed to compress the request body with {0}: {1}")]
+    #[strum(serialize = "SUBGRAPH_REQUEST_BODY_COMPRESSION_FAILURE")]
+    RequestBodyCompressionFailure(&'static str, String),
     #[error("Received empty response body from subgraph \"{0}\"")]
     #[strum(serialize = "SUBGRAPH_RESPONSE_BODY_EMPTY")]
     EmptyResponseBody(String, Arc<HeaderMap>),
@@ -74,6 +90,21 @@ pub enum SubgraphExecutorError {
     #[error("Rejected by the circuit breaker")]
     #[strum(serialize = "SUBGRAPH_CIRCUIT_BREAKER_REJECTED")]
     CircuitBreakerRejected,
+    #[error("Unable to create rate limiter for subgraph \"{0}\": {1}")]
+    #[strum(serialize = "SUBGRAPH_RATE_LIMITER_CREATION_FAILURE")]
+    RateLimiterCreationError(String, String),
+    #[error("Rejected by the rate limiter")]
+    #[strum(serialize = "SUBGRAPH_RATE_LIMITED")]
+    RateLimited,
+    #[error("Unable to create AWS SigV4 request signer for subgraph \"{0}\": {1}")]
+    #[strum(serialize = "SUBGRAPH_AWS_SIGV4_SIGNER_CREATION_FAILURE")]
+    AwsSigV4SignerCreationError(String, String),
+    #[error("Failed to sign the request with AWS SigV4: {0}")]
+    #[strum(serialize = "SUBGRAPH_AWS_SIGV4_SIGNING_FAILURE")]
+    AwsSigV4SigningFailure(String),
+    #[error("Invalid proxy configuration: {0}")]
+    #[strum(serialize = "SUBGRAPH_PROXY_CONFIGURATION_FAILURE")]
+    ProxyConfigurationError(String),
     #[error("Unsupported content-type '{0}': expected 'multipart/mixed' or 'text/event-stream' for HTTP subscriptions")]
     #[strum(serialize = "SUBGRAPH_SUBSCRIPTION_UNSUPPORTED_CONTENT_TYPE")]
     UnsupportedContentTypeError(String),
@@ -124,6 +155,18 @@ pub enum SubgraphExecutorError {
     )]
     #[strum(serialize = "SUBGRAPH_COST_ESTIMATED_TOO_EXPENSIVE")]
     CostEstimatedTooExpensive,
+    #[error("Invalid connector on field '{0}': {1}")]
+    #[strum(serialize = "SUBGRAPH_CONNECTOR_DEFINITION_INVALID")]
+    ConnectorDefinitionInvalid(String, String),
+    #[error("Failed to resolve the request to subgraph \"{0}\" with connectors: {1}")]
+    #[strum(serialize = "SUBGRAPH_CONNECTOR_FAILURE")]
+    ConnectorFailure(String, String),
+    #[error("Coprocessor failed around the request to subgraph \"{0}\": {1}")]
+    #[strum(serialize = "SUBGRAPH_COPROCESSOR_FAILURE")]
+    CoprocessorFailure(String, Box<CoprocessorError>),
+    #[error("Failed to initialize the coprocessor subgraph stages: {0}")]
+    #[strum(serialize = "SUBGRAPH_COPROCESSOR_CONFIGURATION_ERROR")]
+    CoprocessorConfigurationError(Box<CoprocessorError>),
 }
 
 impl SubgraphExecutorError {
@@ -136,6 +179,7 @@ impl SubgraphExecutorError {
             Self::InternalServerError(response) => response.headers.as_deref(),
             Self::EmptyResponseBody(_, headers) => Some(headers.as_ref()),
             Self::ResponseBodyReadFailure(_, _, headers) => Some(headers.as_ref()),
+            Self::ResponseBodyDecompressionFailure(_, _, headers) => Some(headers.as_ref()),
             Self::ResponseDeserializationFailure(_, headers) => headers.as_deref(),
             Self::MalformedResponse(headers) => headers.as_deref(),
             Self::InvalidContentType(_, _, headers) => Some(headers.as_ref()),
@@ -22,6 +22,7 @@ pub enum CloseCode {
     BadResponse(&'static str),
     SubscriberAlreadyExists(String),
     InternalServerError(Option<String>),
+    IdleTimeout,
 }
 
 impl From<CloseCode> for ws::Message {
@@ -69,6 +70,10 @@ impl From<CloseCode> for ws::Message {
                 code: ntex::ws::CloseCode::from(4500),
                 description: reason.or(Some("Internal Server Error".into())),
             })),
+            CloseCode::IdleTimeout => ws::Message::Close(Some(ws::CloseReason {
+                code: ws::CloseCode::Normal,
+                description: Some("Connection idle timeout".into()),
+            })),
         }
     }
 }
@@ -466,8 +471,11 @@ mod tests {
             query: anonymous_document_str,
             document_name_write_pos,
             dedupe: false,
+            idempotent: false,
             operation_name,
             variables: None,
+            serialized_variables: None,
+            overridden_variables: Default::default(),
             headers: HeaderMap::new(),
             raw_variable_values: None,
             extensions: None,
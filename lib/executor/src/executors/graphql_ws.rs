@@ -0,0 +1,241 @@
+/// Messages of the legacy GraphQL over WebSocket Protocol of the deprecated
+/// `subscriptions-transport-ws` library, as per the spec:
+/// https://github.com/apollographql/subscriptions-transport-ws/blob/master/PROTOCOL.md
+///
+/// Only the server side is implemented, the router never uses it towards subgraphs.
+use ntex::ws;
+use serde::{Deserialize, Serialize};
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
+use strum::AsRefStr;
+use tracing::error;
+
+use crate::{
+    executors::graphql_transport_ws::{self, CloseCode, ConnectionInitPayload, SubscribePayload},
+    response::graphql_error::GraphQLError,
+};
+
+pub const WS_LEGACY_SUBPROTOCOL: &str = "graphql-ws";
+
+#[derive(
+    Debug,
+    AsRefStr, // for logging the enum variant type as a string without the fields
+)]
+pub enum ClientMessage {
+    ConnectionInit {
+        payload: Option<ConnectionInitPayload>,
+    },
+    Start {
+        id: String,
+        payload: SubscribePayload,
+    },
+    Stop {
+        id: String,
+    },
+    ConnectionTerminate,
+}
+
+// using a custom deserializer due to compatibility issues
+// with internally-tagged enum deserialization #[serde(tag = "type")]
+impl<'de> Deserialize<'de> for ClientMessage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let obj = value
+            .as_object()
+            .ok_or_else(|| serde::de::Error::custom("expected object"))?;
+
+        let type_key = "type".to_string();
+        let msg_type = obj
+            .get(&type_key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| serde::de::Error::missing_field("type"))?;
+
+        let id_key = "id".to_string();
+        let payload_key = "payload".to_string();
+        let id = || -> Result<String, D::Error> {
+            obj.get(&id_key)
+                .and_then(|v| v.as_str())
+                .map(|id| id.to_string())
+                .ok_or_else(|| serde::de::Error::missing_field("id"))
+        };
+
+        match msg_type {
+            "connection_init" => {
+                let payload = obj
+                    .get(&payload_key)
+                    .filter(|v| !v.is_null())
+                    .map(|v| {
+                        sonic_rs::from_str(&v.to_string())
+                            .map_err(|e| serde::de::Error::custom(e.to_string()))
+                    })
+                    .transpose()?;
+                Ok(ClientMessage::ConnectionInit { payload })
+            }
+            "start" => {
+                let payload_value = obj
+                    .get(&payload_key)
+                    .ok_or_else(|| serde::de::Error::missing_field("payload"))?;
+                let payload: SubscribePayload = sonic_rs::from_str(&payload_value.to_string())
+                    .map_err(|e| serde::de::Error::custom(e.to_string()))?;
+                Ok(ClientMessage::Start { id: id()?, payload })
+            }
+            "stop" => Ok(ClientMessage::Stop { id: id()? }),
+            "connection_terminate" => Ok(ClientMessage::ConnectionTerminate),
+            other => Err(serde::de::Error::unknown_variant(
+                other,
+                &["connection_init", "start", "stop", "connection_terminate"],
+            )),
+        }
+    }
+}
+
+impl ClientMessage {
+    /// The equivalent message of the `graphql-transport-ws` protocol, so that both protocols
+    /// are handled the same way. `None` when the client terminates the connection.
+    pub fn into_transport_ws(self) -> Option<graphql_transport_ws::ClientMessage> {
+        match self {
+            ClientMessage::ConnectionInit { payload } => {
+                Some(graphql_transport_ws::ClientMessage::ConnectionInit { payload })
+            }
+            ClientMessage::Start { id, payload } => {
+                Some(graphql_transport_ws::ClientMessage::Subscribe { id, payload })
+            }
+            ClientMessage::Stop { id } => {
+                Some(graphql_transport_ws::ClientMessage::Complete { id })
+            }
+            ClientMessage::ConnectionTerminate => None,
+        }
+    }
+}
+
+#[derive(
+    Serialize,
+    Debug,
+    AsRefStr, // for logging the enum variant type as a string without the fields
+)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    ConnectionAck {},
+    #[serde(rename = "ka")]
+    KeepAlive {},
+    Data {
+        id: String,
+        payload: Value,
+    },
+    Error {
+        id: String,
+        payload: Vec<GraphQLError>,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+impl ServerMessage {
+    pub fn ack() -> ws::Message {
+        ServerMessage::ConnectionAck {}.into()
+    }
+
+    pub fn keep_alive() -> ws::Message {
+        ServerMessage::KeepAlive {}.into()
+    }
+
+    pub fn data(id: &str, body: &[u8]) -> ws::Message {
+        let payload = match sonic_rs::from_slice(body) {
+            Ok(value) => value,
+            Err(err) => {
+                error!("Failed to serialize plan execution output body: {}", err);
+                return CloseCode::InternalServerError(None).into();
+            }
+        };
+        ServerMessage::Data {
+            id: id.to_string(),
+            payload,
+        }
+        .into()
+    }
+
+    pub fn error(id: &str, errors: &[GraphQLError]) -> ws::Message {
+        ServerMessage::Error {
+            id: id.to_string(),
+            payload: errors.to_vec(),
+        }
+        .into()
+    }
+
+    pub fn complete(id: &str) -> ws::Message {
+        ServerMessage::Complete { id: id.to_string() }.into()
+    }
+}
+
+impl From<ServerMessage> for ws::Message {
+    fn from(msg: ServerMessage) -> Self {
+        match sonic_rs::to_string(&msg) {
+            Ok(text) => ws::Message::Text(text.into()),
+            Err(e) => {
+                error!("Failed to serialize server message to JSON: {}", e);
+                CloseCode::InternalServerError(None).into()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(msg: ws::Message) -> String {
+        match msg {
+            ws::Message::Text(text) => text.to_string(),
+            other => panic!("expected a text message, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn start_and_stop_map_to_subscribe_and_complete() {
+        let msg: ClientMessage = sonic_rs::from_str(
+            r#"{"type":"start","id":"1","payload":{"query":"subscription { a }"}}"#,
+        )
+        .unwrap();
+        let Some(graphql_transport_ws::ClientMessage::Subscribe { id, payload }) =
+            msg.into_transport_ws()
+        else {
+            panic!("expected a subscribe message");
+        };
+        assert_eq!(id, "1");
+        assert_eq!(payload.query, "subscription { a }");
+
+        let msg: ClientMessage = sonic_rs::from_str(r#"{"type":"stop","id":"1"}"#).unwrap();
+        assert!(matches!(
+            msg.into_transport_ws(),
+            Some(graphql_transport_ws::ClientMessage::Complete { id }) if id == "1"
+        ));
+
+        let msg: ClientMessage = sonic_rs::from_str(r#"{"type":"connection_terminate"}"#).unwrap();
+        assert!(msg.into_transport_ws().is_none());
+    }
+
+    #[test]
+    fn rejects_graphql_transport_ws_messages() {
+        let result = sonic_rs::from_str::<ClientMessage>(
+            r#"{"type":"subscribe","id":"1","payload":{"query":"subscription { a }"}}"#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn serializes_server_messages() {
+        assert_eq!(text(ServerMessage::ack()), r#"{"type":"connection_ack"}"#);
+        assert_eq!(text(ServerMessage::keep_alive()), r#"{"type":"ka"}"#);
+        assert_eq!(
+            text(ServerMessage::data("1", br#"{"data":{"a":1}}"#)),
+            r#"{"type":"data","id":"1","payload":{"data":{"a":1}}}"#
+        );
+        assert_eq!(
+            text(ServerMessage::complete("1")),
+            r#"{"type":"complete","id":"1"}"#
+        );
+    }
+}
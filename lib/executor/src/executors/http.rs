@@ -1,9 +1,15 @@
+use std::ops::ControlFlow;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use crate::coprocessor::CoprocessorSubgraphRuntime;
+use crate::executors::aws_sigv4::SubgraphRequestSigner;
+use crate::executors::compression::{decompress_response_body, CompressionOptions};
 use crate::executors::dedupe::unique_leader_fingerprint;
 use crate::executors::map::InflightRequestsMap;
 use crate::executors::multipart_subscribe;
+use crate::executors::proxy::ProxyConnector;
+use crate::executors::retry::RetryPolicy;
 use crate::executors::sse;
 use crate::executors::subscription_buffer;
 use crate::hooks::on_subgraph_http_request::{
@@ -15,9 +21,12 @@ use crate::plugin_trait::{EndControlFlow, StartControlFlow};
 use crate::plugins::hooks;
 use crate::response::subgraph_response::SubgraphResponse;
 use futures::stream::BoxStream;
+use hive_router_config::subscriptions::SubscriptionProtocol;
 use hive_router_config::HiveRouterConfig;
 use hive_router_internal::inflight::InFlightRole;
-use hive_router_internal::telemetry::metrics::catalog::values::GraphQLResponseStatus;
+use hive_router_internal::telemetry::metrics::catalog::values::{
+    CompressionDirection, GraphQLResponseStatus,
+};
 use hive_router_internal::telemetry::metrics::http_client_metrics::HttpClientRequestStateCapture;
 use hive_router_internal::telemetry::metrics::subscription_metrics::SubscriptionTransport;
 use hive_router_internal::telemetry::TelemetryContext;
@@ -33,11 +42,13 @@ use http_body_util::BodyExt;
 use http_body_util::Full;
 use hyper::Version;
 use hyper_rustls::HttpsConnector;
-use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::client::legacy::{Client, ResponseFuture};
+use hyperlocal::UnixConnector;
 use tokio::sync::Semaphore;
 use tracing::{debug, trace};
 
 use crate::executors::common::SubgraphExecutionRequest;
+use crate::executors::dedupe::RouterRequestDedupeHeaderPolicy;
 use crate::executors::error::SubgraphExecutorError;
 use crate::utils::consts::CLOSE_BRACE;
 use crate::utils::consts::COLON;
@@ -51,20 +62,42 @@ use tracing::Instrument;
 pub struct HTTPSubgraphExecutor {
     pub subgraph_name: String,
     pub endpoint: http::Uri,
-    pub http_client: Arc<HttpClient>,
+    pub http_client: SubgraphHttpClient,
     pub header_map: HeaderMap,
     pub semaphore: Arc<Semaphore>,
     pub dedupe_enabled: bool,
+    pub dedupe_headers: RouterRequestDedupeHeaderPolicy,
     pub in_flight_requests: InflightRequestsMap,
+    pub retry_policy: Option<RetryPolicy>,
+    pub request_signer: Option<Arc<SubgraphRequestSigner>>,
+    pub compression: CompressionOptions,
     pub telemetry_context: Arc<TelemetryContext>,
     pub config: Arc<HiveRouterConfig>,
+    pub coprocessor: Option<Arc<CoprocessorSubgraphRuntime>>,
 }
 
 const FIRST_VARIABLE_STR: &[u8] = b",\"variables\":{";
 const FIRST_QUOTE_STR: &[u8] = b"{\"query\":";
 const OPERATION_NAME_STR: &[u8] = b",\"operationName\":";
 
-pub type HttpClient = Client<HttpsConnector<HttpConnector>, Full<Bytes>>;
+pub type HttpClient = Client<HttpsConnector<ProxyConnector>, Full<Bytes>>;
+pub type UnixSocketHttpClient = Client<UnixConnector, Full<Bytes>>;
+
+/// HTTP client reaching a subgraph, either over TCP or over a Unix domain socket.
+#[derive(Clone)]
+pub enum SubgraphHttpClient {
+    Tcp(Arc<HttpClient>),
+    UnixSocket(Arc<UnixSocketHttpClient>),
+}
+
+impl SubgraphHttpClient {
+    pub fn request(&self, req: http::Request<Full<Bytes>>) -> ResponseFuture {
+        match self {
+            Self::Tcp(client) => client.request(req),
+            Self::UnixSocket(client) => client.request(req),
+        }
+    }
+}
 
 struct FetchedSubgraphResponse<'a> {
     response: SubgraphHttpResponse,
@@ -108,10 +141,26 @@ pub fn build_request_body(
             body.put(variable_name.as_bytes());
             body.put(QUOTE);
             body.put(COLON);
-            let value_str = sonic_rs::to_string(variable_value).map_err(|err| {
-                SubgraphExecutorError::VariablesSerializationFailure(variable_name.to_string(), err)
-            })?;
-            body.put(value_str.as_bytes());
+            let serialized_value = execution_request
+                .serialized_variables
+                .filter(|_| {
+                    !execution_request
+                        .overridden_variables
+                        .contains(variable_name)
+                })
+                .and_then(|serialized| serialized.get(variable_name, variable_value));
+            match serialized_value {
+                Some(serialized_value) => body.put_slice(serialized_value),
+                None => {
+                    let value_str = sonic_rs::to_string(variable_value).map_err(|err| {
+                        SubgraphExecutorError::VariablesSerializationFailure(
+                            variable_name.to_string(),
+                            err,
+                        )
+                    })?;
+                    body.put(value_str.as_bytes());
+                }
+            }
         }
     }
     if let Some(raw_variable_values) = &execution_request.raw_variable_values {
@@ -161,12 +210,17 @@ impl HTTPSubgraphExecutor {
     pub fn new(
         subgraph_name: String,
         endpoint: http::Uri,
-        http_client: Arc<HttpClient>,
+        http_client: SubgraphHttpClient,
         semaphore: Arc<Semaphore>,
         dedupe_enabled: bool,
+        dedupe_headers: RouterRequestDedupeHeaderPolicy,
         in_flight_requests: InflightRequestsMap,
+        retry_policy: Option<RetryPolicy>,
+        request_signer: Option<Arc<SubgraphRequestSigner>>,
+        compression: CompressionOptions,
         telemetry_context: Arc<TelemetryContext>,
         config: Arc<HiveRouterConfig>,
+        coprocessor: Option<Arc<CoprocessorSubgraphRuntime>>,
     ) -> Self {
         let mut header_map = HeaderMap::new();
         header_map.insert(
@@ -185,56 +239,93 @@ impl HTTPSubgraphExecutor {
             header_map,
             semaphore,
             dedupe_enabled,
+            dedupe_headers,
             in_flight_requests,
+            retry_policy,
+            request_signer,
+            compression,
             telemetry_context,
             config,
+            coprocessor,
         }
     }
 }
 
+#[derive(Clone)]
 pub struct SendRequestOpts<'a> {
-    pub http_client: &'a HttpClient,
+    pub http_client: &'a SubgraphHttpClient,
     pub endpoint: &'a http::Uri,
     pub subgraph_name: &'a str,
     pub method: http::Method,
-    pub body: Vec<u8>,
+    pub body: Bytes,
     pub headers: HeaderMap,
     pub timeout: Option<Duration>,
+    pub request_signer: Option<&'a SubgraphRequestSigner>,
     pub telemetry_context: &'a Arc<TelemetryContext>,
+    /// Number of times the request was already sent, for retried requests.
+    pub resend_count: u32,
+}
+
+/// A subgraph request that failed,
+/// with the status code of the response if the subgraph responded.
+struct FailedSubgraphRequest {
+    error: SubgraphExecutorError,
+    status: Option<StatusCode>,
 }
 
 async fn send_request<'a>(
     opts: SendRequestOpts<'a>,
-) -> Result<FetchedSubgraphResponse<'a>, SubgraphExecutorError> {
+) -> Result<FetchedSubgraphResponse<'a>, FailedSubgraphRequest> {
     let SendRequestOpts {
         http_client,
         endpoint,
         subgraph_name,
         method,
         body,
-        headers,
+        mut headers,
         timeout,
+        request_signer,
         telemetry_context,
+        resend_count,
     } = opts;
     let request_body_size = body.len() as u64;
 
+    // Every attempt is signed again, the signature is only valid for a limited time
+    if let Some(request_signer) = request_signer {
+        request_signer
+            .sign(&method, endpoint, &mut headers, &body)
+            .await
+            .map_err(|error| FailedSubgraphRequest {
+                error,
+                status: None,
+            })?;
+    }
+
     let mut req = hyper::Request::builder()
         .method(method)
         .uri(endpoint)
         .version(Version::HTTP_11)
-        .body(Full::new(Bytes::from(body)))?;
+        .body(Full::new(body))
+        .map_err(|err| FailedSubgraphRequest {
+            error: err.into(),
+            status: None,
+        })?;
 
     *req.headers_mut() = headers;
 
     debug!("making http request to {}", endpoint.to_string());
 
     let http_request_span = HttpClientRequestSpan::from_request(&req);
+    if resend_count > 0 {
+        http_request_span.record_resend_count(resend_count);
+    }
     let mut http_request_capture = telemetry_context.metrics.http_client.capture_request(
         &req,
         request_body_size,
         Some(subgraph_name),
     );
     let transport_started_at = Instant::now();
+    let mut response_status = None;
 
     let response: Result<SubgraphHttpResponse, SubgraphExecutorError> = async {
         // TODO: let's decide at some point if the tracing headers
@@ -251,6 +342,7 @@ async fn send_request<'a>(
 
         http_request_span.record_response(&res);
         http_request_capture.set_status_code(res.status().as_u16());
+        response_status = Some(res.status());
 
         debug!(
             "http request to {} completed, status: {}",
@@ -258,7 +350,7 @@ async fn send_request<'a>(
             res.status()
         );
 
-        let (parts, body) = res.into_parts();
+        let (mut parts, body) = res.into_parts();
 
         let body = match body.collect().await {
             Ok(body) => body.to_bytes(),
@@ -271,6 +363,21 @@ async fn send_request<'a>(
             }
         };
 
+        let received_body_size = body.len();
+        let (body, encoding) = decompress_response_body(subgraph_name, &mut parts.headers, body)?;
+        if let Some(encoding) = encoding {
+            telemetry_context
+                .metrics
+                .compression
+                .record_compressed_body(
+                    subgraph_name,
+                    CompressionDirection::Response,
+                    encoding.as_str(),
+                    body.len(),
+                    received_body_size,
+                );
+        }
+
         if body.is_empty() {
             return Err(SubgraphExecutorError::EmptyResponseBody(
                 subgraph_name.to_string(),
@@ -298,11 +405,90 @@ async fn send_request<'a>(
         Err(err) => {
             http_request_span.record_error(err.error_code());
             http_request_capture.finish_error(err.error_code(), transport_duration);
-            Err(err)
+            Err(FailedSubgraphRequest {
+                error: err,
+                status: response_status,
+            })
         }
     }
 }
 
+/// Sends the request, and sends it again as long as the retry policy allows it.
+/// Sends the request while holding a permit of the concurrency limit of the subgraph.
+async fn send_request_with_permit<'a>(
+    semaphore: &Semaphore,
+    opts: SendRequestOpts<'a>,
+) -> Result<FetchedSubgraphResponse<'a>, FailedSubgraphRequest> {
+    // This unwrap is safe because the semaphore is never closed during the application's lifecycle.
+    // `acquire()` only fails if the semaphore is closed, so this will always return `Ok`.
+    let _permit = semaphore.acquire().await.unwrap();
+    send_request(opts).await
+}
+
+/// Every attempt acquires its own permit, so a request waiting for its backoff
+/// does not hold back the other requests to the subgraph.
+async fn send_request_with_retries<'a>(
+    semaphore: &Semaphore,
+    mut opts: SendRequestOpts<'a>,
+    retry_policy: Option<&RetryPolicy>,
+) -> Result<FetchedSubgraphResponse<'a>, SubgraphExecutorError> {
+    let Some(retry_policy) = retry_policy else {
+        return send_request_with_permit(semaphore, opts)
+            .await
+            .map_err(|failed| failed.error);
+    };
+
+    loop {
+        if opts.resend_count + 1 >= retry_policy.max_attempts {
+            return send_request_with_permit(semaphore, opts)
+                .await
+                .map_err(|failed| failed.error);
+        }
+
+        let result = send_request_with_permit(semaphore, opts.clone()).await;
+        let should_retry = match &result {
+            Ok(fetched_response) => {
+                retry_policy.should_retry_status(fetched_response.response.status)
+            }
+            // The subgraph may respond with an error status and an empty body
+            Err(FailedSubgraphRequest {
+                status: Some(status),
+                ..
+            }) => retry_policy.should_retry_status(*status),
+            Err(FailedSubgraphRequest { error, .. }) => retry_policy.should_retry_error(error),
+        };
+
+        if !should_retry {
+            return result.map_err(|failed| failed.error);
+        }
+
+        if let Ok(mut fetched_response) = result {
+            // The response is discarded, but it still counts as a completed request
+            fetched_response.http_request_capture.finish(
+                fetched_response.response.body.len() as u64,
+                fetched_response.transport_duration,
+                GraphQLResponseStatus::Error,
+                None,
+            );
+        }
+
+        opts.resend_count += 1;
+        let backoff = retry_policy.backoff(opts.resend_count);
+        debug!(
+            subgraph_name = opts.subgraph_name,
+            resend_count = opts.resend_count,
+            backoff_ms = backoff.as_millis() as u64,
+            "retrying http request to {}",
+            opts.endpoint.to_string()
+        );
+        opts.telemetry_context
+            .metrics
+            .retry
+            .record_retry(opts.subgraph_name);
+        tokio::time::sleep(backoff).await;
+    }
+}
+
 pub enum DeduplicationHint {
     Deduped {
         fingerprint: u64,
@@ -329,6 +515,12 @@ impl SubgraphExecutor for HTTPSubgraphExecutor {
         self.header_map.iter().for_each(|(key, value)| {
             execution_request.headers.insert(key, value.clone());
         });
+        // Only set for the single responses, the subscription streams are never compressed
+        if let Some(accept_encoding) = self.compression.accept_encoding_header() {
+            execution_request
+                .headers
+                .insert(http::header::ACCEPT_ENCODING, accept_encoding);
+        }
 
         let mut method = http::Method::POST;
         let mut deduplicate_request = !self.dedupe_enabled || !execution_request.dedupe;
@@ -371,12 +563,64 @@ impl SubgraphExecutor for HTTPSubgraphExecutor {
             deduplicate_request = start_payload.deduplicate_request;
         }
 
+        // The coprocessor sees the request as modified by the plugins, right before it's sent
+        let coprocessor = self
+            .coprocessor
+            .as_ref()
+            .zip(plugin_req_state.map(|state| &state.request_context));
+        if let Some((coprocessor, request_context)) = coprocessor.filter(|_| response.is_none()) {
+            let control_flow = coprocessor
+                .on_subgraph_request(
+                    &self.subgraph_name,
+                    &method,
+                    &self.endpoint,
+                    &mut execution_request.headers,
+                    &mut body,
+                    request_context,
+                )
+                .await
+                .map_err(|err| {
+                    SubgraphExecutorError::CoprocessorFailure(
+                        self.subgraph_name.clone(),
+                        Box::new(err),
+                    )
+                })?;
+            if let ControlFlow::Break(early_response) = control_flow {
+                response = Some(early_response);
+            }
+        }
+        // Early responses are not sent to the subgraph, so the response stage is skipped for them
+        let coprocessor = coprocessor.filter(|_| response.is_none());
+
         let mut deduplication_hint = DeduplicationHint::NotDeduped;
         let mut http_request_capture = None;
 
         let mut response = match response {
             Some(resp) => resp,
             None => {
+                // Mutations are not idempotent, sending them again could apply them twice
+                let retry_policy = self
+                    .retry_policy
+                    .as_ref()
+                    .filter(|_| execution_request.idempotent);
+                let mut body = Bytes::from(body);
+                if let Some(compressed) = self.compression.compress_request_body(&body)? {
+                    self.telemetry_context
+                        .metrics
+                        .compression
+                        .record_compressed_body(
+                            &self.subgraph_name,
+                            CompressionDirection::Request,
+                            compressed.encoding.as_str(),
+                            body.len(),
+                            compressed.body.len(),
+                        );
+                    execution_request.headers.insert(
+                        http::header::CONTENT_ENCODING,
+                        compressed.encoding.header_value(),
+                    );
+                    body = compressed.body;
+                }
                 let send_request_opts = SendRequestOpts {
                     http_client: &self.http_client,
                     endpoint: &self.endpoint,
@@ -385,14 +629,15 @@ impl SubgraphExecutor for HTTPSubgraphExecutor {
                     body,
                     headers: execution_request.headers,
                     timeout,
+                    request_signer: self.request_signer.as_deref(),
                     telemetry_context: &self.telemetry_context,
+                    resend_count: 0,
                 };
 
                 if deduplicate_request {
-                    // This unwrap is safe because the semaphore is never closed during the application's lifecycle.
-                    // `acquire()` only fails if the semaphore is closed, so this will always return `Ok`.
-                    let _permit = self.semaphore.acquire().await.unwrap();
-                    let fetched_response = send_request(send_request_opts).await?;
+                    let fetched_response =
+                        send_request_with_retries(&self.semaphore, send_request_opts, retry_policy)
+                            .await?;
                     http_request_capture = Some(HttpRequestTelemetryCapture {
                         capture: fetched_response.http_request_capture,
                         response_body_size: fetched_response.response.body.len() as u64,
@@ -400,7 +645,7 @@ impl SubgraphExecutor for HTTPSubgraphExecutor {
                     });
                     fetched_response.response
                 } else {
-                    let fingerprint = send_request_opts.fingerprint();
+                    let fingerprint = send_request_opts.fingerprint(&self.dedupe_headers);
 
                     let inflight_span = HttpInflightRequestSpan::new(
                         &send_request_opts.method,
@@ -414,12 +659,12 @@ impl SubgraphExecutor for HTTPSubgraphExecutor {
                         let mut leader_http_request_capture = None;
                         let (shared_response, role) = claim
                             .get_or_try_init(|| async {
-                                let res = {
-                                    // This unwrap is safe because the semaphore is never closed during the application's lifecycle.
-                                    // `acquire()` only fails if the semaphore is closed, so this will always return `Ok`.
-                                    let _permit = self.semaphore.acquire().await.unwrap();
-                                    send_request(send_request_opts).await
-                                };
+                                let res = send_request_with_retries(
+                                    &self.semaphore,
+                                    send_request_opts,
+                                    retry_policy,
+                                )
+                                .await;
 
                                 res.map(|fetched_response| {
                                     leader_http_request_capture =
@@ -468,6 +713,18 @@ impl SubgraphExecutor for HTTPSubgraphExecutor {
             }
         };
 
+        if let Some((coprocessor, request_context)) = coprocessor {
+            response = coprocessor
+                .on_subgraph_response(&self.subgraph_name, response, request_context)
+                .await
+                .map_err(|err| {
+                    SubgraphExecutorError::CoprocessorFailure(
+                        self.subgraph_name.clone(),
+                        Box::new(err),
+                    )
+                })?;
+        }
+
         if !on_end_callbacks.is_empty() {
             let plugin_state_ref = plugin_req_state
                 .as_ref()
@@ -531,28 +788,39 @@ impl SubgraphExecutor for HTTPSubgraphExecutor {
         SubgraphExecutorError,
     > {
         let custom_scalar_paths = execution_request.custom_scalar_paths.cloned();
-        let buffer_capacity = self.config.subscriptions.subgraph_buffer_capacity;
-        let body = build_request_body(&execution_request)?;
-
-        let mut req = hyper::Request::builder()
-            .method(http::Method::POST)
-            .uri(&self.endpoint)
-            .version(Version::HTTP_11)
-            .body(Full::new(Bytes::from(body)))?;
+        let buffer_capacity = self.config.subscriptions.subgraph_buffer_capacity.get();
+        let body = Bytes::from(build_request_body(&execution_request)?);
 
         let mut headers = execution_request.headers;
         self.header_map.iter().for_each(|(key, value)| {
             headers.insert(key, value.clone());
         });
 
-        // Prefer multipart over SSE for subscriptions
-        // https://www.apollographql.com/docs/graphos/routing/operations/subscriptions/multipart-protocol
-        headers.insert(
-            http::header::ACCEPT,
-            HeaderValue::from_static(
+        let accept = match self
+            .config
+            .subscriptions
+            .get_protocol_for_subgraph(&self.subgraph_name)
+        {
+            SubscriptionProtocol::SSE => HeaderValue::from_static("text/event-stream"),
+            // Prefer multipart over SSE for subscriptions
+            // https://www.apollographql.com/docs/graphos/routing/operations/subscriptions/multipart-protocol
+            _ => HeaderValue::from_static(
                 r#"multipart/mixed;subscriptionSpec="1.0", text/event-stream"#,
             ),
-        );
+        };
+        headers.insert(http::header::ACCEPT, accept);
+
+        if let Some(request_signer) = &self.request_signer {
+            request_signer
+                .sign(&http::Method::POST, &self.endpoint, &mut headers, &body)
+                .await?;
+        }
+
+        let mut req = hyper::Request::builder()
+            .method(http::Method::POST)
+            .uri(&self.endpoint)
+            .version(Version::HTTP_11)
+            .body(Full::new(body))?;
         *req.headers_mut() = headers;
 
         debug!(
@@ -807,8 +1075,11 @@ mod tests {
             query: "query { me { id } }",
             document_name_write_pos: 5,
             dedupe: false,
+            idempotent: false,
             operation_name: Some("GetMe_accounts_0".to_string()),
             variables: None,
+            serialized_variables: None,
+            overridden_variables: Default::default(),
             headers: HeaderMap::new(),
             raw_variable_values: None,
             extensions: None,
@@ -1,6 +1,9 @@
 use std::{
     collections::{BTreeMap, HashMap},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
@@ -12,7 +15,9 @@ use hive_router_config::{
     override_subgraph_urls::UrlOrExpression,
     primitives::value_or_expression::ValueOrExpression,
     subscriptions::SubscriptionProtocol,
-    traffic_shaping::{DurationOrExpression, StatusCodeMatcher},
+    traffic_shaping::{
+        DurationOrExpression, StatusCodeMatcher, TrafficShapingRouterDedupeHeadersConfig,
+    },
     HiveRouterConfig,
 };
 use hive_router_internal::expressions::{
@@ -25,23 +30,29 @@ use hive_router_internal::{
     telemetry::TelemetryContext,
 };
 use http::{StatusCode, Uri};
-use hyper_util::{
-    client::legacy::Client,
-    rt::{TokioExecutor, TokioTimer},
-};
+use hyperlocal::UnixConnector;
 use recloser::AsyncRecloser;
 use tokio::sync::Semaphore;
 
 use crate::{
+    coprocessor::CoprocessorSubgraphRuntime,
     execution::{
         client_request_details::ClientRequestDetails, demand_control::DemandControlExecutionContext,
     },
     executors::{
+        aws_sigv4::SubgraphRequestSigner,
         common::{SubgraphExecutionRequest, SubgraphExecutor, SubgraphExecutorBoxedArc},
+        compression::CompressionOptions,
+        connector::{ConnectorSubgraphExecutor, SupergraphConnectors},
         error::SubgraphExecutorError,
-        http::{HTTPSubgraphExecutor, HttpClient, SubgraphHttpResponse},
+        http::{HTTPSubgraphExecutor, HttpClient, SubgraphHttpClient, SubgraphHttpResponse},
         http_callback::{CallbackSubscriptionsMap, HttpCallbackSubgraphExecutor},
+        pool::HttpClientPoolOptions,
+        proxy::ProxyOptions,
+        rate_limit::SubgraphRateLimiter,
+        retry::RetryPolicy,
         tls::{build_https_client_config, build_https_connector, get_merged_tls_config},
+        unix_socket::parse_unix_socket_endpoint,
         websocket::WsSubgraphExecutor,
     },
     hooks::on_subgraph_execute::{
@@ -105,8 +116,32 @@ struct SubgraphCircuitBreaker {
     /// matches any entry. Wrapped in `Arc` so the value is cheap to clone
     /// out of the `DashMap`.
     error_status_codes: Arc<Vec<StatusCodeMatcher>>,
+    /// Whether the breaker rejected the last request sent to the subgraph.
+    rejecting: Arc<AtomicBool>,
+}
+
+/// The state of the circuit breaker of a subgraph, as observed by the last request sent to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitBreakerState {
+    /// The last request was let through, either because the breaker is closed
+    /// or because it is probing the subgraph after its reset timeout.
+    Closed,
+    /// The last request was rejected without reaching the subgraph.
+    Open,
+}
+
+impl CircuitBreakerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitBreakerState::Closed => "closed",
+            CircuitBreakerState::Open => "open",
+        }
+    }
 }
+
 type CircuitBreakersBySubgraph = DashMap<SubgraphName, SubgraphCircuitBreaker>;
+type RateLimitersBySubgraph = DashMap<SubgraphName, Arc<SubgraphRateLimiter>>;
+type RequestSignersBySubgraph = DashMap<SubgraphName, Arc<SubgraphRequestSigner>>;
 
 lazy_static::lazy_static! {
     /// Default HTTP statuses tracked as failures by the circuit breaker when
@@ -131,8 +166,10 @@ lazy_static::lazy_static! {
 
 struct ResolvedSubgraphConfig<'a> {
     client: Arc<HttpClient>,
+    pool_options: HttpClientPoolOptions,
     timeout_config: &'a DurationOrExpression,
     dedupe_enabled: bool,
+    dedupe_headers: &'a TrafficShapingRouterDedupeHeadersConfig,
 }
 
 pub type InflightRequestsMap = InFlightMap<u64, (SubgraphHttpResponse, u64)>;
@@ -140,6 +177,8 @@ pub type InflightRequestsMap = InFlightMap<u64, (SubgraphHttpResponse, u64)>;
 pub struct SubgraphExecutorMap {
     http_executors_by_subgraph: ExecutorsBySubgraphMap,
     subscription_executors_by_subgraph: ExecutorsBySubgraphMap,
+    /// Subgraphs resolved by REST connectors instead of a GraphQL server
+    connector_executors_by_subgraph: DashMap<SubgraphName, SubgraphExecutorBoxedArc>,
     /// Mapping from subgraph name to static endpoint for quick lookup
     /// based on subgraph SDL and static overrides from router's config.
     static_endpoints_by_subgraph: StaticEndpointsBySubgraphMap,
@@ -149,6 +188,8 @@ pub struct SubgraphExecutorMap {
     all_endpoint_expression: GlobalSubgraphUrlOverride,
     timeouts_by_subgraph: TimeoutsBySubgraph,
     circuit_breakers_by_subgraph: CircuitBreakersBySubgraph,
+    rate_limiters_by_subgraph: RateLimitersBySubgraph,
+    request_signers_by_subgraph: RequestSignersBySubgraph,
     global_timeout: DurationOrProgram,
     config: Arc<HiveRouterConfig>,
     client: Arc<HttpClient>,
@@ -158,6 +199,8 @@ pub struct SubgraphExecutorMap {
     telemetry_context: Arc<TelemetryContext>,
     /// Shared map of active HTTP callback subscriptions
     callback_subscriptions: CallbackSubscriptionsMap,
+    /// Subgraph stages of the coprocessor, shared by the HTTP executors
+    coprocessor: Option<Arc<CoprocessorSubgraphRuntime>>,
 }
 impl SubgraphExecutorMap {
     pub fn new(
@@ -165,23 +208,35 @@ impl SubgraphExecutorMap {
         global_timeout: DurationOrProgram,
         telemetry_context: Arc<TelemetryContext>,
     ) -> Result<Self, SubgraphExecutorError> {
-        let mut client_builder = Client::builder(TokioExecutor::new());
-        client_builder
-            .pool_timer(TokioTimer::new())
-            .pool_idle_timeout(config.traffic_shaping.all.pool_idle_timeout)
-            .pool_max_idle_per_host(config.traffic_shaping.max_connections_per_host);
-        if config.traffic_shaping.all.allow_only_http2 {
-            client_builder.http2_only(true);
-        }
-        let client: HttpClient = client_builder.build(build_https_connector(
-            config.traffic_shaping.all.tls.as_ref(),
-        )?);
+        let proxy_options =
+            ProxyOptions::from_config(config.traffic_shaping.all.proxy.as_ref(), None)
+                .map_err(SubgraphExecutorError::ProxyConfigurationError)?;
+        let client: HttpClient = HttpClientPoolOptions::global(&config.traffic_shaping)
+            .build_client(build_https_connector(
+                config.traffic_shaping.all.tls.as_ref(),
+                proxy_options.as_ref(),
+            )?);
 
         let max_connections_per_host = config.traffic_shaping.max_connections_per_host;
 
+        let coprocessor = config
+            .coprocessor
+            .as_ref()
+            .map(|coprocessor_config| {
+                CoprocessorSubgraphRuntime::from_config(
+                    coprocessor_config,
+                    telemetry_context.clone(),
+                )
+            })
+            .transpose()
+            .map_err(|err| SubgraphExecutorError::CoprocessorConfigurationError(Box::new(err)))?
+            .flatten()
+            .map(Arc::new);
+
         Ok(SubgraphExecutorMap {
             http_executors_by_subgraph: Default::default(),
             subscription_executors_by_subgraph: Default::default(),
+            connector_executors_by_subgraph: Default::default(),
             static_endpoints_by_subgraph: Default::default(),
             expression_endpoints_by_subgraph: Default::default(),
             all_endpoint_expression: Default::default(),
@@ -192,9 +247,12 @@ impl SubgraphExecutorMap {
             in_flight_requests: InFlightMap::default(),
             timeouts_by_subgraph: Default::default(),
             circuit_breakers_by_subgraph: Default::default(),
+            rate_limiters_by_subgraph: Default::default(),
+            request_signers_by_subgraph: Default::default(),
             global_timeout,
             telemetry_context,
             callback_subscriptions: Arc::new(DashMap::new()),
+            coprocessor,
         })
     }
 
@@ -241,9 +299,11 @@ impl SubgraphExecutorMap {
             };
 
             subgraph_executor_map.register_static_endpoint(subgraph_name, endpoint_str);
+            subgraph_executor_map.register_request_signer(subgraph_name)?;
             subgraph_executor_map.register_executor(subgraph_name, endpoint_str, false)?;
             subgraph_executor_map.register_subgraph_timeout(subgraph_name)?;
             subgraph_executor_map.register_circuit_breaker(subgraph_name)?;
+            subgraph_executor_map.register_rate_limiter(subgraph_name)?;
         }
 
         subgraph_executor_map.all_endpoint_expression = global_url_override;
@@ -251,11 +311,55 @@ impl SubgraphExecutorMap {
         Ok(subgraph_executor_map)
     }
 
+    /// Resolves the requests to the subgraphs with connectors with their REST APIs,
+    /// relative URLs of the connectors being resolved against the endpoint of the subgraph.
+    pub fn register_connectors(
+        &self,
+        connectors: SupergraphConnectors,
+    ) -> Result<(), SubgraphExecutorError> {
+        for (subgraph_name, subgraph_connectors) in connectors.subgraphs {
+            let endpoint_str = self
+                .static_endpoints_by_subgraph
+                .get(&subgraph_name)
+                .map(|endpoint| endpoint.value().clone())
+                .ok_or(SubgraphExecutorError::StaticEndpointNotFound)?;
+            let endpoint_uri = endpoint_str.parse::<Uri>().map_err(|e| {
+                SubgraphExecutorError::EndpointParseFailure(endpoint_str.to_string(), e)
+            })?;
+
+            let executor = ConnectorSubgraphExecutor::new(
+                subgraph_name.clone(),
+                endpoint_uri,
+                self.client.clone(),
+                Arc::new(subgraph_connectors),
+            );
+            self.connector_executors_by_subgraph
+                .insert(subgraph_name, executor.to_boxed_arc());
+        }
+
+        Ok(())
+    }
+
     /// Returns the shared active callback subscriptions map for use by callback handlers.
     pub fn callback_subscriptions(&self) -> CallbackSubscriptionsMap {
         self.callback_subscriptions.clone()
     }
 
+    /// Returns the state of the circuit breaker of every subgraph it is enabled for.
+    pub fn circuit_breaker_states(&self) -> Vec<(String, CircuitBreakerState)> {
+        self.circuit_breakers_by_subgraph
+            .iter()
+            .map(|entry| {
+                let state = if entry.value().rejecting.load(Ordering::Relaxed) {
+                    CircuitBreakerState::Open
+                } else {
+                    CircuitBreakerState::Closed
+                };
+                (entry.key().clone(), state)
+            })
+            .collect()
+    }
+
     pub async fn execute<'exec>(
         &self,
         subgraph_name: &'exec str,
@@ -339,6 +443,7 @@ impl SubgraphExecutorMap {
         let mut execution_result = match execution_result {
             Some(execution_result) => execution_result,
             None => {
+                self.acquire_rate_limit(subgraph_name).await?;
                 let exec_fut = executor.execute(execution_request, timeout, plugin_req_state);
                 // Clone the circuit breaker out of the DashMap before awaiting to avoid
                 // holding the shard read-lock across an await point (potential deadlock).
@@ -351,6 +456,7 @@ impl SubgraphExecutorMap {
                         let SubgraphCircuitBreaker {
                             recloser,
                             error_status_codes,
+                            rejecting,
                         } = circuit_breaker;
                         // Treat configured status codes as errors so the
                         // circuit breaker can track them. Default: 500, 502,
@@ -372,6 +478,13 @@ impl SubgraphExecutorMap {
                             &self.telemetry_context.metrics.circuit_breaker;
                         recloser
                             .call(exec_fut)
+                            .map(|exec_res| {
+                                rejecting.store(
+                                    matches!(exec_res, Err(recloser::Error::Rejected)),
+                                    Ordering::Relaxed,
+                                );
+                                exec_res
+                            })
                             .map(|exec_res| match exec_res {
                                 Err(recloser::Error::Inner(e)) => {
                                     // The call was permitted by the breaker but the
@@ -449,6 +562,8 @@ impl SubgraphExecutorMap {
 
         let timeout = self.resolve_subgraph_timeout(subgraph_name, client_request)?;
 
+        self.acquire_rate_limit(subgraph_name).await?;
+
         let subscribe_fut = executor.subscribe(execution_request, timeout);
 
         // The circuit breaker only guards the establishment of the
@@ -487,6 +602,21 @@ impl SubgraphExecutorMap {
         }
     }
 
+    /// Waits for the rate limiter of the subgraph, if any, to allow a request to be sent.
+    async fn acquire_rate_limit(&self, subgraph_name: &str) -> Result<(), SubgraphExecutorError> {
+        // Clone the rate limiter out of the DashMap before awaiting,
+        // to avoid holding the shard read-lock across an await point.
+        let rate_limiter = self
+            .rate_limiters_by_subgraph
+            .get(subgraph_name)
+            .map(|r| r.value().clone());
+
+        match rate_limiter {
+            Some(rate_limiter) => rate_limiter.acquire().await,
+            None => Ok(()),
+        }
+    }
+
     fn resolve_subgraph_timeout(
         &self,
         subgraph_name: &str,
@@ -555,6 +685,10 @@ impl SubgraphExecutorMap {
         subgraph_name: &str,
         client_request: &ClientRequestDetails<'_>,
     ) -> Result<SubgraphExecutorBoxedArc, SubgraphExecutorError> {
+        if let Some(executor) = self.connector_executors_by_subgraph.get(subgraph_name) {
+            return Ok(executor.clone());
+        }
+
         let endpoint_str = self.resolve_endpoint(subgraph_name, client_request)?;
 
         if let Some(executor) = self
@@ -573,6 +707,10 @@ impl SubgraphExecutorMap {
         subgraph_name: &str,
         client_request: &ClientRequestDetails<'_>,
     ) -> Result<SubgraphExecutorBoxedArc, SubgraphExecutorError> {
+        if let Some(executor) = self.connector_executors_by_subgraph.get(subgraph_name) {
+            return Ok(executor.clone());
+        }
+
         let endpoint_str = self.resolve_endpoint(subgraph_name, client_request)?;
 
         if let Some(executor) = self
@@ -623,9 +761,18 @@ impl SubgraphExecutorMap {
         endpoint_str: &str,
         for_subscription: bool,
     ) -> Result<SubgraphExecutorBoxedArc, SubgraphExecutorError> {
-        let endpoint_uri = endpoint_str.parse::<Uri>().map_err(|e| {
-            SubgraphExecutorError::EndpointParseFailure(endpoint_str.to_string(), e)
-        })?;
+        let unix_socket_endpoint_uri = parse_unix_socket_endpoint(endpoint_str)?;
+        let is_unix_socket = unix_socket_endpoint_uri.is_some();
+        let endpoint_uri = match unix_socket_endpoint_uri {
+            Some(endpoint_uri) => endpoint_uri,
+            None => {
+                let endpoint_uri = endpoint_str.parse::<Uri>().map_err(|e| {
+                    SubgraphExecutorError::EndpointParseFailure(endpoint_str.to_string(), e)
+                })?;
+                check_endpoint_uri(endpoint_str, &endpoint_uri)?;
+                endpoint_uri
+            }
+        };
 
         let origin = format!(
             "{}://{}:{}",
@@ -653,19 +800,60 @@ impl SubgraphExecutorMap {
             SubscriptionProtocol::HTTP
         };
 
+        if is_unix_socket
+            && !matches!(
+                protocol,
+                SubscriptionProtocol::HTTP | SubscriptionProtocol::SSE
+            )
+        {
+            return Err(SubgraphExecutorError::UnixSocketUnsupportedProtocol(
+                subgraph_name.to_string(),
+            ));
+        }
+
         match protocol {
-            SubscriptionProtocol::HTTP => {
+            // the HTTP executor negotiates SSE by itself
+            SubscriptionProtocol::HTTP | SubscriptionProtocol::SSE => {
                 let subgraph_config = self.resolve_subgraph_config(subgraph_name)?;
 
+                let http_client = if is_unix_socket {
+                    SubgraphHttpClient::UnixSocket(Arc::new(
+                        subgraph_config.pool_options.build_client(UnixConnector),
+                    ))
+                } else {
+                    SubgraphHttpClient::Tcp(subgraph_config.client)
+                };
+
                 let http_executor = HTTPSubgraphExecutor::new(
                     subgraph_name.to_string(),
                     endpoint_uri,
-                    subgraph_config.client,
+                    http_client,
                     semaphore,
                     subgraph_config.dedupe_enabled,
+                    subgraph_config.dedupe_headers.into(),
                     self.in_flight_requests.clone(),
+                    RetryPolicy::from_config(
+                        self.config.traffic_shaping.all.retry.as_ref(),
+                        self.config
+                            .traffic_shaping
+                            .subgraphs
+                            .get(subgraph_name)
+                            .and_then(|c| c.retry.as_ref()),
+                    ),
+                    self.request_signers_by_subgraph
+                        .get(subgraph_name)
+                        .map(|signer| signer.value().clone()),
+                    CompressionOptions::from_config(
+                        self.config.traffic_shaping.all.compression.as_ref(),
+                        self.config
+                            .traffic_shaping
+                            .subgraphs
+                            .get(subgraph_name)
+                            .and_then(|c| c.compression.as_ref()),
+                    ),
                     self.telemetry_context.clone(),
                     self.config.clone(),
+                    self.coprocessor.clone(),
                 )
                 .to_boxed_arc();
 
@@ -736,7 +924,7 @@ impl SubgraphExecutorMap {
                     // we use the new constructed ws_endpoint_uri here
                     ws_endpoint_uri,
                     ws_tls_config,
-                    self.config.subscriptions.subgraph_buffer_capacity,
+                    self.config.subscriptions.subgraph_buffer_capacity.get(),
                     self.telemetry_context.clone(),
                 )
                 .to_boxed_arc();
@@ -791,6 +979,7 @@ impl SubgraphExecutorMap {
     ) -> Result<Uri, SubgraphExecutorError> {
         let raw = match public_url {
             ValueOrExpression::Value(url) => url.clone(),
+            ValueOrExpression::Secret(secret) => secret.value().clone(),
             ValueOrExpression::Expression { expression } => expression
                 .compile_expression(None)
                 .map_err(|err| {
@@ -829,46 +1018,53 @@ impl SubgraphExecutorMap {
     ) -> Result<ResolvedSubgraphConfig<'a>, SubgraphExecutorError> {
         let mut config = ResolvedSubgraphConfig {
             client: self.client.clone(),
+            pool_options: HttpClientPoolOptions::global(&self.config.traffic_shaping),
             timeout_config: &self.config.traffic_shaping.all.request_timeout,
             dedupe_enabled: self.config.traffic_shaping.all.dedupe_enabled,
+            dedupe_headers: &self.config.traffic_shaping.all.dedupe_headers,
         };
 
         let Some(subgraph_config) = self.config.traffic_shaping.subgraphs.get(subgraph_name) else {
             return Ok(config);
         };
 
-        let pool_idle_timeout = subgraph_config
-            .pool_idle_timeout
-            .unwrap_or(self.config.traffic_shaping.all.pool_idle_timeout);
-        // Override client only if pool idle timeout is customized, TLS config is provided, or allow_only_http2 differs
-        let subgraph_allow_only_http2 = subgraph_config
-            .allow_only_http2
-            .unwrap_or(self.config.traffic_shaping.all.allow_only_http2);
-        if pool_idle_timeout != self.config.traffic_shaping.all.pool_idle_timeout
+        // Override client only if the connection pool settings are customized,
+        // or TLS or proxy config is provided
+        let pool_options =
+            HttpClientPoolOptions::for_subgraph(&self.config.traffic_shaping, subgraph_config);
+        if pool_options != config.pool_options
             || subgraph_config.tls.is_some()
-            || subgraph_allow_only_http2 != self.config.traffic_shaping.all.allow_only_http2
+            || subgraph_config.proxy.is_some()
         {
             let tls_config = get_merged_tls_config(
                 self.config.traffic_shaping.all.tls.as_ref(),
                 subgraph_config.tls.as_ref(),
             );
-            let mut client_builder = Client::builder(TokioExecutor::new());
-            client_builder
-                .pool_timer(TokioTimer::new())
-                .pool_idle_timeout(pool_idle_timeout)
-                .pool_max_idle_per_host(self.max_connections_per_host);
-            if subgraph_allow_only_http2 {
-                client_builder.http2_only(true);
-            }
-            config.client =
-                Arc::new(client_builder.build(build_https_connector(tls_config.as_ref())?));
+            let proxy_options = ProxyOptions::from_config(
+                self.config.traffic_shaping.all.proxy.as_ref(),
+                subgraph_config.proxy.as_ref(),
+            )
+            .map_err(|e| {
+                SubgraphExecutorError::ProxyConfigurationError(format!(
+                    "subgraph \"{subgraph_name}\": {e}"
+                ))
+            })?;
+            config.client = Arc::new(pool_options.build_client(build_https_connector(
+                tls_config.as_ref(),
+                proxy_options.as_ref(),
+            )?));
         }
+        config.pool_options = pool_options;
 
         // Apply other subgraph-specific overrides
         if let Some(dedupe_enabled) = subgraph_config.dedupe_enabled {
             config.dedupe_enabled = dedupe_enabled;
         }
 
+        if let Some(dedupe_headers) = &subgraph_config.dedupe_headers {
+            config.dedupe_headers = dedupe_headers;
+        }
+
         if let Some(custom_timeout) = &subgraph_config.request_timeout {
             config.timeout_config = custom_timeout;
         }
@@ -909,8 +1105,60 @@ impl SubgraphExecutorMap {
         Ok(())
     }
 
-    /// Registers a circuit breaker for a specific subgraph.
-    /// If the subgraph already has a circuit breaker registered, it will do nothing.
+    /// Registers a rate limiter for a specific subgraph.
+    /// If the subgraph already has a rate limiter registered, it will do nothing.
+    fn register_rate_limiter(&self, subgraph_name: &str) -> Result<(), SubgraphExecutorError> {
+        if self.rate_limiters_by_subgraph.contains_key(subgraph_name) {
+            return Ok(());
+        }
+
+        let rate_limiter = SubgraphRateLimiter::from_config(
+            self.config.traffic_shaping.all.rate_limit.as_ref(),
+            self.config
+                .traffic_shaping
+                .subgraphs
+                .get(subgraph_name)
+                .and_then(|s| s.rate_limit.as_ref()),
+        )
+        .map_err(|e| {
+            SubgraphExecutorError::RateLimiterCreationError(subgraph_name.to_string(), e)
+        })?;
+
+        if let Some(rate_limiter) = rate_limiter {
+            self.rate_limiters_by_subgraph
+                .insert(subgraph_name.to_string(), Arc::new(rate_limiter));
+        }
+
+        Ok(())
+    }
+
+    /// Registers an AWS SigV4 request signer for a specific subgraph.
+    /// If the subgraph already has a request signer registered, it will do nothing.
+    fn register_request_signer(&self, subgraph_name: &str) -> Result<(), SubgraphExecutorError> {
+        if self.request_signers_by_subgraph.contains_key(subgraph_name) {
+            return Ok(());
+        }
+
+        let request_signer = SubgraphRequestSigner::from_config(
+            self.config.traffic_shaping.all.aws_sig_v4.as_ref(),
+            self.config
+                .traffic_shaping
+                .subgraphs
+                .get(subgraph_name)
+                .and_then(|s| s.aws_sig_v4.as_ref()),
+        )
+        .map_err(|e| {
+            SubgraphExecutorError::AwsSigV4SignerCreationError(subgraph_name.to_string(), e)
+        })?;
+
+        if let Some(request_signer) = request_signer {
+            self.request_signers_by_subgraph
+                .insert(subgraph_name.to_string(), Arc::new(request_signer));
+        }
+
+        Ok(())
+    }
+
     fn register_circuit_breaker(&self, subgraph_name: &str) -> Result<(), SubgraphExecutorError> {
         if self
             .circuit_breakers_by_subgraph
@@ -985,6 +1233,7 @@ impl SubgraphExecutorMap {
                 SubgraphCircuitBreaker {
                     recloser,
                     error_status_codes,
+                    rejecting: Default::default(),
                 },
             );
 
@@ -998,6 +1247,35 @@ impl SubgraphExecutorMap {
     }
 }
 
+/// Rejects the endpoints that parse as a URI but can't be sent requests to,
+/// like relative paths or URLs with an unknown scheme.
+fn check_endpoint_uri(endpoint_str: &str, endpoint_uri: &Uri) -> Result<(), SubgraphExecutorError> {
+    match endpoint_uri.scheme_str() {
+        Some("http" | "https" | "ws" | "wss") => {}
+        Some(_) => {
+            return Err(SubgraphExecutorError::EndpointInvalid(
+                endpoint_str.to_string(),
+                "unsupported scheme",
+            ))
+        }
+        None => {
+            return Err(SubgraphExecutorError::EndpointInvalid(
+                endpoint_str.to_string(),
+                "missing scheme",
+            ))
+        }
+    }
+
+    if endpoint_uri.host().is_none_or(str::is_empty) {
+        return Err(SubgraphExecutorError::EndpointInvalid(
+            endpoint_str.to_string(),
+            "missing host",
+        ));
+    }
+
+    Ok(())
+}
+
 /// Resolves a timeout DurationOrProgram to a concrete Duration.
 /// Optionally includes a default timeout value in the VRL context.
 fn resolve_timeout(
@@ -1035,3 +1313,38 @@ pub fn compile_duration_or_expression(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use http::Uri;
+
+    use super::check_endpoint_uri;
+
+    fn check(endpoint: &str) -> Result<(), String> {
+        let uri = endpoint.parse::<Uri>().expect("endpoint should parse");
+        check_endpoint_uri(endpoint, &uri).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn accepts_absolute_urls() {
+        assert!(check("http://accounts:4001/graphql").is_ok());
+        assert!(check("https://accounts.example.com/graphql").is_ok());
+        assert!(check("ws://accounts:4001/graphql").is_ok());
+    }
+
+    #[test]
+    fn rejects_urls_without_scheme_or_host() {
+        assert_eq!(
+            check("/graphql").unwrap_err(),
+            "Endpoint \"/graphql\" is not a valid subgraph URL: missing scheme"
+        );
+        assert_eq!(
+            check("accounts:4001").unwrap_err(),
+            "Endpoint \"accounts:4001\" is not a valid subgraph URL: missing scheme"
+        );
+        assert_eq!(
+            check("ftp://accounts/graphql").unwrap_err(),
+            "Endpoint \"ftp://accounts/graphql\" is not a valid subgraph URL: unsupported scheme"
+        );
+    }
+}
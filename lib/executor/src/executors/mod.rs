@@ -1,14 +1,24 @@
+pub mod aws_sigv4;
 pub mod common;
+pub mod compression;
+pub mod connector;
+pub mod connector_selection;
 pub mod dedupe;
 pub mod error;
 pub mod graphql_transport_ws;
+pub mod graphql_ws;
 pub mod http;
 pub mod http_callback;
 pub mod map;
 pub mod multipart_subscribe;
+pub mod pool;
+pub mod proxy;
+pub mod rate_limit;
+pub mod retry;
 pub mod sse;
 pub mod subscription_buffer;
 pub mod tls;
+pub mod unix_socket;
 pub mod websocket;
 pub mod websocket_client;
 pub mod websocket_common;
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use hive_router_config::traffic_shaping::{
+    TrafficShapingConfig, TrafficShapingExecutorSubgraphConfig,
+};
+use http_body_util::Full;
+use hyper_util::{
+    client::legacy::{connect::Connect, Client},
+    rt::{TokioExecutor, TokioTimer},
+};
+
+/// Connection pool settings of the HTTP client used to reach the subgraphs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpClientPoolOptions {
+    pub pool_idle_timeout: Duration,
+    pub pool_max_idle_per_host: usize,
+    pub http2_only: bool,
+    pub http2_keep_alive_interval: Option<Duration>,
+    pub http2_keep_alive_timeout: Option<Duration>,
+    pub http2_keep_alive_while_idle: Option<bool>,
+    pub http2_max_concurrent_streams: Option<usize>,
+}
+
+impl HttpClientPoolOptions {
+    /// Settings defined in the global (`all`) configuration.
+    pub fn global(config: &TrafficShapingConfig) -> Self {
+        let http2 = config.all.http2.as_ref();
+        Self {
+            pool_idle_timeout: config.all.pool_idle_timeout,
+            pool_max_idle_per_host: config
+                .all
+                .pool_max_idle_per_host
+                .unwrap_or(config.max_connections_per_host),
+            http2_only: config.all.allow_only_http2,
+            http2_keep_alive_interval: http2.and_then(|c| c.keep_alive_interval),
+            http2_keep_alive_timeout: http2.and_then(|c| c.keep_alive_timeout),
+            http2_keep_alive_while_idle: http2.and_then(|c| c.keep_alive_while_idle),
+            http2_max_concurrent_streams: http2.and_then(|c| c.max_concurrent_streams),
+        }
+    }
+
+    /// Settings of a subgraph, falling back to the global ones.
+    pub fn for_subgraph(
+        config: &TrafficShapingConfig,
+        subgraph_config: &TrafficShapingExecutorSubgraphConfig,
+    ) -> Self {
+        let global = Self::global(config);
+        let http2 = subgraph_config.http2.as_ref();
+
+        Self {
+            pool_idle_timeout: subgraph_config
+                .pool_idle_timeout
+                .unwrap_or(global.pool_idle_timeout),
+            pool_max_idle_per_host: subgraph_config
+                .pool_max_idle_per_host
+                .unwrap_or(global.pool_max_idle_per_host),
+            http2_only: subgraph_config
+                .allow_only_http2
+                .unwrap_or(global.http2_only),
+            http2_keep_alive_interval: http2
+                .and_then(|c| c.keep_alive_interval)
+                .or(global.http2_keep_alive_interval),
+            http2_keep_alive_timeout: http2
+                .and_then(|c| c.keep_alive_timeout)
+                .or(global.http2_keep_alive_timeout),
+            http2_keep_alive_while_idle: http2
+                .and_then(|c| c.keep_alive_while_idle)
+                .or(global.http2_keep_alive_while_idle),
+            http2_max_concurrent_streams: http2
+                .and_then(|c| c.max_concurrent_streams)
+                .or(global.http2_max_concurrent_streams),
+        }
+    }
+
+    pub fn build_client<C>(&self, connector: C) -> Client<C, Full<Bytes>>
+    where
+        C: Connect + Clone,
+    {
+        let mut client_builder = Client::builder(TokioExecutor::new());
+        client_builder
+            .pool_timer(TokioTimer::new())
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host);
+        if self.http2_only {
+            client_builder.http2_only(true);
+        }
+        if let Some(keep_alive_interval) = self.http2_keep_alive_interval {
+            // The keep-alive pings are scheduled with the HTTP/2 timer
+            client_builder
+                .timer(TokioTimer::new())
+                .http2_keep_alive_interval(keep_alive_interval);
+        }
+        if let Some(keep_alive_timeout) = self.http2_keep_alive_timeout {
+            client_builder.http2_keep_alive_timeout(keep_alive_timeout);
+        }
+        if let Some(keep_alive_while_idle) = self.http2_keep_alive_while_idle {
+            client_builder.http2_keep_alive_while_idle(keep_alive_while_idle);
+        }
+        if let Some(max_concurrent_streams) = self.http2_max_concurrent_streams {
+            client_builder.http2_initial_max_send_streams(max_concurrent_streams);
+        }
+        client_builder.build(connector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use hive_router_config::traffic_shaping::TrafficShapingConfig;
+
+    use super::HttpClientPoolOptions;
+
+    #[test]
+    fn should_fall_back_to_global_settings() {
+        let config: TrafficShapingConfig = serde_json::from_value(serde_json::json!({
+            "max_connections_per_host": 50,
+            "all": {
+                "http2": { "keep_alive_interval": "10s", "keep_alive_timeout": "5s" }
+            },
+            "subgraphs": {
+                "accounts": {
+                    "pool_max_idle_per_host": 5,
+                    "http2": { "keep_alive_interval": "30s" }
+                }
+            }
+        }))
+        .expect("config should be valid");
+
+        let global = HttpClientPoolOptions::global(&config);
+        assert_eq!(global.pool_max_idle_per_host, 50);
+        assert_eq!(
+            global.http2_keep_alive_interval,
+            Some(Duration::from_secs(10))
+        );
+
+        let accounts = HttpClientPoolOptions::for_subgraph(&config, &config.subgraphs["accounts"]);
+        assert_eq!(accounts.pool_max_idle_per_host, 5);
+        assert_eq!(
+            accounts.http2_keep_alive_interval,
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            accounts.http2_keep_alive_timeout,
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(accounts.pool_idle_timeout, global.pool_idle_timeout);
+        assert_ne!(accounts, global);
+    }
+}
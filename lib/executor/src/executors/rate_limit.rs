@@ -0,0 +1,193 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use hive_router_config::traffic_shaping::{
+    RateLimitStrategy, TrafficShapingSubgraphRateLimitConfig,
+};
+
+use crate::executors::error::SubgraphExecutorError;
+
+const DEFAULT_MAX_QUEUE_WAIT: Duration = Duration::from_secs(1);
+
+/// Limits the rate of the requests sent to a subgraph.
+///
+/// Implemented as a GCRA (Generic Cell Rate Algorithm), an equivalent of a token bucket
+/// that only keeps track of the theoretical arrival time of the next request.
+#[derive(Debug)]
+pub struct SubgraphRateLimiter {
+    /// Time between two requests at the steady rate.
+    emission_interval: Duration,
+    /// How far ahead of the steady rate the requests can be sent.
+    burst_tolerance: Duration,
+    strategy: RateLimitStrategy,
+    max_queue_wait: Duration,
+    theoretical_arrival: Mutex<Instant>,
+}
+
+impl SubgraphRateLimiter {
+    /// Merges the subgraph-level rate limit configuration with the global (`all`) one.
+    /// Returns `None` when the rate limiting is disabled for the subgraph.
+    pub fn from_config(
+        global_config: Option<&TrafficShapingSubgraphRateLimitConfig>,
+        subgraph_config: Option<&TrafficShapingSubgraphRateLimitConfig>,
+    ) -> Result<Option<Self>, String> {
+        let enabled = subgraph_config
+            .and_then(|c| c.enabled)
+            .or_else(|| global_config.and_then(|c| c.enabled))
+            .unwrap_or(false);
+
+        if !enabled {
+            return Ok(None);
+        }
+
+        let requests_per_second = subgraph_config
+            .and_then(|c| c.requests_per_second)
+            .or_else(|| global_config.and_then(|c| c.requests_per_second))
+            .ok_or("'requests_per_second' is required when the rate limiting is enabled")?;
+
+        if requests_per_second == 0 {
+            return Err("'requests_per_second' must be greater than 0".to_string());
+        }
+
+        let burst = subgraph_config
+            .and_then(|c| c.burst)
+            .or_else(|| global_config.and_then(|c| c.burst))
+            .unwrap_or(requests_per_second);
+
+        if burst == 0 {
+            return Err("'burst' must be greater than 0".to_string());
+        }
+
+        let strategy = subgraph_config
+            .and_then(|c| c.strategy)
+            .or_else(|| global_config.and_then(|c| c.strategy))
+            .unwrap_or(RateLimitStrategy::Shed);
+
+        let max_queue_wait = subgraph_config
+            .and_then(|c| c.max_queue_wait)
+            .or_else(|| global_config.and_then(|c| c.max_queue_wait))
+            .unwrap_or(DEFAULT_MAX_QUEUE_WAIT);
+
+        let emission_interval = Duration::from_secs(1) / requests_per_second;
+
+        Ok(Some(Self {
+            emission_interval,
+            burst_tolerance: emission_interval * burst,
+            strategy,
+            max_queue_wait,
+            theoretical_arrival: Mutex::new(Instant::now()),
+        }))
+    }
+
+    /// Waits until the request is allowed to be sent,
+    /// or fails if the request exceeds the limit.
+    pub async fn acquire(&self) -> Result<(), SubgraphExecutorError> {
+        let wait = self
+            .reserve(Instant::now())
+            .ok_or(SubgraphExecutorError::RateLimited)?;
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+
+        Ok(())
+    }
+
+    /// Reserves a slot for a request made at `now`.
+    /// Returns how long the request has to wait for its slot,
+    /// or `None` if it can't get one.
+    fn reserve(&self, now: Instant) -> Option<Duration> {
+        let mut theoretical_arrival = match self.theoretical_arrival.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let next_arrival = (*theoretical_arrival).max(now) + self.emission_interval;
+        let wait = next_arrival
+            .saturating_duration_since(now)
+            .saturating_sub(self.burst_tolerance);
+
+        if !wait.is_zero()
+            && (self.strategy == RateLimitStrategy::Shed || wait > self.max_queue_wait)
+        {
+            return None;
+        }
+
+        *theoretical_arrival = next_arrival;
+        Some(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use hive_router_config::traffic_shaping::{
+        RateLimitStrategy, TrafficShapingSubgraphRateLimitConfig,
+    };
+
+    use super::SubgraphRateLimiter;
+
+    fn rate_limiter(strategy: RateLimitStrategy) -> SubgraphRateLimiter {
+        let config = TrafficShapingSubgraphRateLimitConfig {
+            enabled: Some(true),
+            requests_per_second: Some(10),
+            burst: Some(2),
+            strategy: Some(strategy),
+            max_queue_wait: Some(Duration::from_millis(150)),
+        };
+        SubgraphRateLimiter::from_config(Some(&config), None)
+            .expect("config should be valid")
+            .expect("rate limiting should be enabled")
+    }
+
+    #[test]
+    fn should_require_requests_per_second() {
+        let config = TrafficShapingSubgraphRateLimitConfig {
+            enabled: Some(true),
+            requests_per_second: None,
+            burst: None,
+            strategy: None,
+            max_queue_wait: None,
+        };
+        assert!(SubgraphRateLimiter::from_config(Some(&config), None).is_err());
+
+        let disabled = TrafficShapingSubgraphRateLimitConfig {
+            enabled: Some(false),
+            ..config
+        };
+        assert!(matches!(
+            SubgraphRateLimiter::from_config(None, Some(&disabled)),
+            Ok(None)
+        ));
+    }
+
+    #[test]
+    fn should_shed_requests_above_the_burst() {
+        let limiter = rate_limiter(RateLimitStrategy::Shed);
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve(now), Some(Duration::ZERO));
+        assert_eq!(limiter.reserve(now), Some(Duration::ZERO));
+        assert_eq!(limiter.reserve(now), None);
+
+        // a new slot is available after the emission interval
+        let later = now + Duration::from_millis(100);
+        assert_eq!(limiter.reserve(later), Some(Duration::ZERO));
+        assert_eq!(limiter.reserve(later), None);
+    }
+
+    #[test]
+    fn should_queue_requests_above_the_burst() {
+        let limiter = rate_limiter(RateLimitStrategy::Queue);
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve(now), Some(Duration::ZERO));
+        assert_eq!(limiter.reserve(now), Some(Duration::ZERO));
+        assert_eq!(limiter.reserve(now), Some(Duration::from_millis(100)));
+        // the fourth request would wait 200ms, more than the allowed 150ms
+        assert_eq!(limiter.reserve(now), None);
+    }
+}
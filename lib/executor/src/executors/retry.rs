@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use hive_router_config::traffic_shaping::{StatusCodeMatcher, TrafficShapingSubgraphRetryConfig};
+use http::StatusCode;
+
+use crate::executors::error::SubgraphExecutorError;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Decides whether, and after which delay, a failed subgraph request is sent again.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first request.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// HTTP status codes returned by the subgraph that should be retried.
+    pub status_codes: Vec<StatusCodeMatcher>,
+}
+
+impl RetryPolicy {
+    /// Merges the subgraph-level retry configuration with the global (`all`) one.
+    /// Returns `None` when the retries are disabled for the subgraph.
+    pub fn from_config(
+        global_config: Option<&TrafficShapingSubgraphRetryConfig>,
+        subgraph_config: Option<&TrafficShapingSubgraphRetryConfig>,
+    ) -> Option<Self> {
+        let enabled = subgraph_config
+            .and_then(|c| c.enabled)
+            .or_else(|| global_config.and_then(|c| c.enabled))
+            .unwrap_or(false);
+
+        if !enabled {
+            return None;
+        }
+
+        let max_attempts = subgraph_config
+            .and_then(|c| c.max_attempts)
+            .or_else(|| global_config.and_then(|c| c.max_attempts))
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+
+        // A single attempt means there is nothing to retry
+        if max_attempts <= 1 {
+            return None;
+        }
+
+        let initial_backoff = subgraph_config
+            .and_then(|c| c.initial_backoff)
+            .or_else(|| global_config.and_then(|c| c.initial_backoff))
+            .unwrap_or(DEFAULT_INITIAL_BACKOFF);
+
+        let max_backoff = subgraph_config
+            .and_then(|c| c.max_backoff)
+            .or_else(|| global_config.and_then(|c| c.max_backoff))
+            .unwrap_or(DEFAULT_MAX_BACKOFF);
+
+        let status_codes = subgraph_config
+            .and_then(|c| c.status_codes.as_ref())
+            .or_else(|| global_config.and_then(|c| c.status_codes.as_ref()))
+            .cloned()
+            .unwrap_or_else(|| vec![StatusCodeMatcher::Hundreds(5)]);
+
+        Some(Self {
+            max_attempts,
+            initial_backoff,
+            max_backoff,
+            status_codes,
+        })
+    }
+
+    /// Whether a response with the given status code should be retried.
+    #[inline]
+    pub fn should_retry_status(&self, status: StatusCode) -> bool {
+        self.status_codes.iter().any(|m| m.matches(status))
+    }
+
+    /// Whether a failed request should be retried.
+    /// Only the requests that failed to connect to the subgraph are retried,
+    /// as they surely did not reach it.
+    #[inline]
+    pub fn should_retry_error(&self, error: &SubgraphExecutorError) -> bool {
+        matches!(error, SubgraphExecutorError::RequestFailure(err) if err.is_connect())
+    }
+
+    /// Delay before sending the request again, for the given retry (starting at 1).
+    ///
+    /// The delay grows exponentially with every retry, capped at `max_backoff`,
+    /// and a random "full jitter" is applied to it.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)));
+        let capped = exponential.min(self.max_backoff);
+
+        if capped.is_zero() {
+            return capped;
+        }
+
+        Duration::from_nanos(rand::random_range(0..=capped.as_nanos() as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use hive_router_config::traffic_shaping::{
+        StatusCodeMatcher, TrafficShapingSubgraphRetryConfig,
+    };
+    use http::StatusCode;
+
+    use super::RetryPolicy;
+
+    fn retry_config(enabled: Option<bool>) -> TrafficShapingSubgraphRetryConfig {
+        TrafficShapingSubgraphRetryConfig {
+            enabled,
+            max_attempts: None,
+            initial_backoff: None,
+            max_backoff: None,
+            status_codes: None,
+        }
+    }
+
+    #[test]
+    fn should_be_disabled_by_default() {
+        assert!(RetryPolicy::from_config(None, None).is_none());
+        assert!(RetryPolicy::from_config(Some(&retry_config(None)), None).is_none());
+    }
+
+    #[test]
+    fn should_fall_back_to_global_config() {
+        let global = TrafficShapingSubgraphRetryConfig {
+            max_attempts: Some(5),
+            ..retry_config(Some(true))
+        };
+        let subgraph = TrafficShapingSubgraphRetryConfig {
+            status_codes: Some(vec![StatusCodeMatcher::Exact(StatusCode::BAD_GATEWAY)]),
+            ..retry_config(None)
+        };
+
+        let policy = RetryPolicy::from_config(Some(&global), Some(&subgraph))
+            .expect("retries should be enabled");
+        assert_eq!(policy.max_attempts, 5);
+        assert!(policy.should_retry_status(StatusCode::BAD_GATEWAY));
+        assert!(!policy.should_retry_status(StatusCode::SERVICE_UNAVAILABLE));
+
+        let disabled = retry_config(Some(false));
+        assert!(RetryPolicy::from_config(Some(&global), Some(&disabled)).is_none());
+    }
+
+    #[test]
+    fn should_retry_5xx_by_default() {
+        let policy = RetryPolicy::from_config(Some(&retry_config(Some(true))), None)
+            .expect("retries should be enabled");
+        assert!(policy.should_retry_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(policy.should_retry_status(StatusCode::GATEWAY_TIMEOUT));
+        assert!(!policy.should_retry_status(StatusCode::OK));
+        assert!(!policy.should_retry_status(StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    #[test]
+    fn should_cap_the_exponential_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+            status_codes: vec![],
+        };
+
+        for _ in 0..100 {
+            assert!(policy.backoff(1) <= Duration::from_millis(100));
+            assert!(policy.backoff(2) <= Duration::from_millis(200));
+            assert!(policy.backoff(3) <= Duration::from_millis(300));
+            assert!(policy.backoff(30) <= Duration::from_millis(300));
+        }
+    }
+}
@@ -5,14 +5,16 @@ use hive_router_config::{
     traffic_shaping::ClientTLSConfig,
 };
 use hyper_rustls::{ConfigBuilderExt, HttpsConnector, HttpsConnectorBuilder};
-use hyper_util::client::legacy::connect::HttpConnector;
 use rustls::{
     client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
     pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer, ServerName, UnixTime},
     ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
 };
 
-use crate::executors::error::{SubgraphExecutorError, TlsCertificatesError};
+use crate::executors::{
+    error::{SubgraphExecutorError, TlsCertificatesError},
+    proxy::{ProxyConnector, ProxyOptions},
+};
 
 pub fn from_cert_file_config_to_certificate_der<'a>(
     cert_file_path: &SingleOrMultiple<FilePath>,
@@ -151,12 +153,16 @@ pub fn build_https_client_config(
 
 pub fn build_https_connector(
     tls_config: Option<&ClientTLSConfig>,
-) -> Result<HttpsConnector<HttpConnector>, SubgraphExecutorError> {
+    proxy_options: Option<&ProxyOptions>,
+) -> Result<HttpsConnector<ProxyConnector>, SubgraphExecutorError> {
+    let client_config = build_https_client_config(tls_config)?;
+    let proxy_connector = ProxyConnector::new(proxy_options, client_config.clone());
+
     Ok(HttpsConnectorBuilder::new()
-        .with_tls_config(build_https_client_config(tls_config)?)
+        .with_tls_config(client_config)
         .https_or_http()
         .enable_all_versions()
-        .build())
+        .wrap_connector(proxy_connector))
 }
 
 pub fn get_merged_tls_config(
@@ -0,0 +1,89 @@
+use http::Uri;
+
+use crate::executors::error::SubgraphExecutorError;
+
+const UNIX_SOCKET_SCHEME_PREFIX: &str = "unix://";
+
+/// Parses a subgraph endpoint in the `unix:///path/to/socket.sock[?path=/request/path]` form,
+/// into the URI understood by the Unix domain socket connector.
+///
+/// Returns `None` for the endpoints not using the `unix` scheme.
+pub fn parse_unix_socket_endpoint(endpoint: &str) -> Result<Option<Uri>, SubgraphExecutorError> {
+    let Some(rest) = endpoint.strip_prefix(UNIX_SOCKET_SCHEME_PREFIX) else {
+        return Ok(None);
+    };
+
+    let parse_failure = |reason: &str| {
+        SubgraphExecutorError::UnixSocketEndpointParseFailure(
+            endpoint.to_string(),
+            reason.to_string(),
+        )
+    };
+
+    if !rest.starts_with('/') {
+        return Err(parse_failure("the socket path must be absolute"));
+    }
+
+    let (socket_path, query) = match rest.split_once('?') {
+        Some((socket_path, query)) => (socket_path, Some(query)),
+        None => (rest, None),
+    };
+
+    if socket_path.len() <= 1 {
+        return Err(parse_failure("the socket path cannot be empty"));
+    }
+
+    let request_path = match query {
+        None => "/",
+        Some(query) => {
+            let Some(request_path) = query.strip_prefix("path=") else {
+                return Err(parse_failure(
+                    "only the 'path' query parameter is supported",
+                ));
+            };
+            if !request_path.starts_with('/') {
+                return Err(parse_failure(
+                    "the 'path' query parameter must start with '/'",
+                ));
+            }
+            request_path
+        }
+    };
+
+    Ok(Some(hyperlocal::Uri::new(socket_path, request_path).into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_unix_socket_endpoint;
+
+    #[test]
+    fn should_ignore_non_unix_endpoints() {
+        assert!(parse_unix_socket_endpoint("http://localhost:4000/graphql")
+            .expect("endpoint should be valid")
+            .is_none());
+    }
+
+    #[test]
+    fn should_parse_socket_and_request_paths() {
+        let expected: http::Uri = hyperlocal::Uri::new("/tmp/products.sock", "/graphql").into();
+        let uri = parse_unix_socket_endpoint("unix:///tmp/products.sock?path=/graphql")
+            .expect("endpoint should be valid")
+            .expect("endpoint should use the unix scheme");
+        assert_eq!(uri, expected);
+
+        let expected: http::Uri = hyperlocal::Uri::new("/tmp/products.sock", "/").into();
+        let uri = parse_unix_socket_endpoint("unix:///tmp/products.sock")
+            .expect("endpoint should be valid")
+            .expect("endpoint should use the unix scheme");
+        assert_eq!(uri, expected);
+    }
+
+    #[test]
+    fn should_reject_invalid_endpoints() {
+        assert!(parse_unix_socket_endpoint("unix://relative.sock").is_err());
+        assert!(parse_unix_socket_endpoint("unix:///").is_err());
+        assert!(parse_unix_socket_endpoint("unix:///tmp/products.sock?query=1").is_err());
+        assert!(parse_unix_socket_endpoint("unix:///tmp/products.sock?path=graphql").is_err());
+    }
+}
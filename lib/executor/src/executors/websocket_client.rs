@@ -22,6 +22,7 @@ use crate::{
         graphql_transport_ws::{ClientMessage, CloseCode, ConnectionInitPayload, ServerMessage},
         websocket_common::{
             handshake_timeout, heartbeat, parse_frame_to_text, FrameNotParsedToText, WsState,
+            HEARTBEAT_INTERVAL, HEARTBEAT_TIMEOUT,
         },
     },
     response::graphql_error::GraphQLError,
@@ -177,7 +178,13 @@ impl WsClient {
 
         // heartbeats
         let (heartbeat_stop_tx, heartbeat_stop_rx) = oneshot::channel();
-        rt::spawn(heartbeat(state.clone(), sink.clone(), heartbeat_stop_rx));
+        rt::spawn(heartbeat(
+            state.clone(),
+            sink.clone(),
+            heartbeat_stop_rx,
+            HEARTBEAT_INTERVAL,
+            HEARTBEAT_TIMEOUT,
+        ));
 
         // handshake timeout monitor will close connection if no ack received in time
         rt::spawn(handshake_timeout(
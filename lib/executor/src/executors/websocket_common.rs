@@ -76,16 +76,19 @@ pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
 /// Handshake message received timeout.
 pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
-/// Ping peer every heartbeat interval.
+/// Ping peer every heartbeat interval, closing the connection when the peer did not
+/// respond within the heartbeat timeout.
 pub async fn heartbeat<T>(
     state: Rc<RefCell<WsState<T>>>,
     sink: WsSink,
     mut stop_rx: oneshot::Receiver<()>,
+    interval: Duration,
+    timeout: Duration,
 ) {
     loop {
         tokio::select! {
-            _ = tokio::time::sleep(HEARTBEAT_INTERVAL) => {
-                if Instant::now().duration_since(state.borrow().last_heartbeat) > HEARTBEAT_TIMEOUT
+            _ = tokio::time::sleep(interval) => {
+                if Instant::now().duration_since(state.borrow().last_heartbeat) > timeout
                 {
                     debug!("WebSocket heartbeat timeout, closing connection");
                     let _ = sink
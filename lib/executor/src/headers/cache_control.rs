@@ -218,10 +218,31 @@ pub fn finalize(
     }
 }
 
+/// Read the `max-age` allowed by a `Cache-Control` value, typically the one produced by [`finalize`].
+///
+/// Returns `None` when the value forbids shared caching (`no-store`, `no-cache` or `private`)
+/// or cannot be parsed, and `Some(None)` when it allows caching without setting a `max-age`.
+pub fn cacheable_max_age(header: &str) -> Option<Option<u32>> {
+    let parsed = parse(header)?;
+    if parsed.no_store || parsed.no_cache || parsed.is_private {
+        return None;
+    }
+    Some(parsed.max_age)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn cacheable_max_age_reads_max_age() {
+        assert_eq!(cacheable_max_age("public, max-age=30"), Some(Some(30)));
+        assert_eq!(cacheable_max_age("must-revalidate"), Some(None));
+        assert_eq!(cacheable_max_age("no-store, no-cache"), None);
+        assert_eq!(cacheable_max_age("private, max-age=30"), None);
+        assert_eq!(cacheable_max_age("max-age=soon"), None);
+    }
+
     fn merge(a: Option<CacheControl>, b: CacheControl) -> CacheControl {
         let mut acc = a;
         merge_into(&mut acc, b);
@@ -1,16 +1,20 @@
 use crate::headers::{
     errors::HeaderRuleCompileError,
     plan::{
-        HeaderAggregationStrategy, HeaderRulesPlan, RequestHeaderRule, RequestHeaderRules,
-        RequestInsertExpression, RequestInsertStatic, RequestPropagateNamed, RequestPropagateRegex,
-        RequestRemoveNamed, RequestRemoveRegex, ResponseHeaderRule, ResponseHeaderRules,
-        ResponseInsertExpression, ResponseInsertStatic, ResponsePropagateNamed,
-        ResponsePropagateRegex, ResponseRemoveNamed, ResponseRemoveRegex,
+        HeaderAggregationStrategy, HeaderRuleCondition, HeaderRulesPlan, JwtClaimHeaderSource,
+        JwtClaimsHeaderEncoding, RequestConditional, RequestHeaderRule, RequestHeaderRules,
+        RequestInsertExpression, RequestInsertStatic, RequestJwtClaimHeader,
+        RequestPropagateCookies, RequestPropagateNamed, RequestPropagateRegex, RequestRemoveNamed,
+        RequestRemoveRegex, RequestRenameNamed, RequestRenameRegex, ResponseConditional,
+        ResponseHeaderRule, ResponseHeaderRules, ResponseInsertExpression, ResponseInsertStatic,
+        ResponsePropagateNamed, ResponsePropagateRegex, ResponseRemoveNamed, ResponseRemoveRegex,
+        ResponseRenameNamed, ResponseRenameRegex,
     },
 };
 use hive_router_internal::expressions::CompileExpression;
 
 use hive_router_config::headers as config;
+use hive_router_config::jwt_auth::{self as jwt_config, JwtClaimHeaderConfig};
 use http::HeaderName;
 use regex_automata::{meta, util::syntax::Config as SyntaxConfig};
 
@@ -74,6 +78,50 @@ impl HeaderRuleCompiler<Vec<RequestHeaderRule>> for config::RequestHeaderRule {
                     }));
                 }
             }
+            config::RequestHeaderRule::Rename(rule) => {
+                let spec = materialize_match_spec(&rule.spec, None, None)?;
+                let to = build_header_name(&rule.to)?;
+                if !spec.header_names.is_empty() {
+                    actions.push(RequestHeaderRule::RenameNamed(RequestRenameNamed {
+                        names: spec.header_names,
+                        to: to.clone(),
+                    }));
+                }
+                if let Some(include) = spec.include_regex {
+                    actions.push(RequestHeaderRule::RenameRegex(RequestRenameRegex {
+                        include,
+                        exclude: spec.exclude_regex,
+                        to,
+                    }));
+                }
+            }
+            config::RequestHeaderRule::When(rule) => {
+                let mut rules = Vec::new();
+                for nested_rule in &rule.rules {
+                    nested_rule.compile(&mut rules)?;
+                }
+                actions.push(RequestHeaderRule::Conditional(RequestConditional {
+                    condition: compile_condition(&rule.condition)?,
+                    rules,
+                }));
+            }
+            config::RequestHeaderRule::PropagateCookies(rule) => {
+                let names = match &rule.named {
+                    config::OneOrMany::One(name) => vec![name.clone()],
+                    config::OneOrMany::Many(names) => names.clone(),
+                };
+                let header = rule
+                    .header
+                    .as_ref()
+                    .map(|name| match names.len() == 1 {
+                        true => build_header_name(name),
+                        false => Err(HeaderRuleCompileError::InvalidCookieHeader),
+                    })
+                    .transpose()?;
+                actions.push(RequestHeaderRule::PropagateCookies(
+                    RequestPropagateCookies { names, header },
+                ));
+            }
         }
 
         Ok(())
@@ -156,6 +204,33 @@ impl HeaderRuleCompiler<Vec<ResponseHeaderRule>> for config::ResponseHeaderRule
                     }));
                 }
             }
+            config::ResponseHeaderRule::Rename(rule) => {
+                let spec = materialize_match_spec(&rule.spec, None, None)?;
+                let to = build_header_name(&rule.to)?;
+                if !spec.header_names.is_empty() {
+                    actions.push(ResponseHeaderRule::RenameNamed(ResponseRenameNamed {
+                        names: spec.header_names,
+                        to: to.clone(),
+                    }));
+                }
+                if let Some(include) = spec.include_regex {
+                    actions.push(ResponseHeaderRule::RenameRegex(ResponseRenameRegex {
+                        include,
+                        exclude: spec.exclude_regex,
+                        to,
+                    }));
+                }
+            }
+            config::ResponseHeaderRule::When(rule) => {
+                let mut rules = Vec::new();
+                for nested_rule in &rule.rules {
+                    nested_rule.compile(&mut rules)?;
+                }
+                actions.push(ResponseHeaderRule::Conditional(ResponseConditional {
+                    condition: compile_condition(&rule.condition)?,
+                    rules,
+                }));
+            }
         }
 
         Ok(())
@@ -192,6 +267,42 @@ pub fn compile_headers_plan(
     })
 }
 
+/// Compiles the headers carrying the claims of the JWT, configured with `jwt.forward_claims_to_upstream_headers`.
+pub fn compile_jwt_claim_headers(
+    cfg: &[JwtClaimHeaderConfig],
+) -> Result<Vec<RequestJwtClaimHeader>, HeaderRuleCompileError> {
+    cfg.iter()
+        .map(|header| {
+            let source = match &header.source {
+                jwt_config::JwtClaimHeaderSource::Claim { claim } => {
+                    JwtClaimHeaderSource::Claim(claim.clone())
+                }
+                jwt_config::JwtClaimHeaderSource::Claims { claims } => {
+                    JwtClaimHeaderSource::AllClaims(match claims {
+                        jwt_config::JwtClaimsHeaderEncoding::Base64 => {
+                            JwtClaimsHeaderEncoding::Base64
+                        }
+                        jwt_config::JwtClaimsHeaderEncoding::Json => JwtClaimsHeaderEncoding::Json,
+                    })
+                }
+                jwt_config::JwtClaimHeaderSource::Expression { expression } => {
+                    let program = expression.compile_expression(None).map_err(|err| {
+                        HeaderRuleCompileError::ExpressionBuild(
+                            header.name.clone(),
+                            err.diagnostics,
+                        )
+                    })?;
+                    JwtClaimHeaderSource::Expression(Box::new(program))
+                }
+            };
+            Ok(RequestJwtClaimHeader {
+                name: build_header_name(&header.name)?,
+                source,
+            })
+        })
+        .collect()
+}
+
 fn compile_request_header_rules(
     header_rules: &config::HeaderRules,
 ) -> Result<Vec<RequestHeaderRule>, HeaderRuleCompileError> {
@@ -297,6 +408,29 @@ fn materialize_match_spec(
     })
 }
 
+fn compile_condition(
+    condition: &config::HeaderRuleCondition,
+) -> Result<HeaderRuleCondition, HeaderRuleCompileError> {
+    let operation_name = match condition.operation_name.as_ref() {
+        None => None,
+        Some(pattern) => build_regex_many(std::slice::from_ref(pattern))?,
+    };
+    let client_name = match condition.client_name.as_ref() {
+        None => None,
+        Some(pattern) => build_regex_many(std::slice::from_ref(pattern))?,
+    };
+
+    Ok(HeaderRuleCondition {
+        subgraphs: condition.subgraphs.clone(),
+        operation_types: condition
+            .operation_types
+            .as_ref()
+            .map(|types| types.iter().map(|kind| kind.as_str()).collect()),
+        operation_name,
+        client_name,
+    })
+}
+
 fn build_header_name(header_name_str: &str) -> Result<http::HeaderName, HeaderRuleCompileError> {
     http::HeaderName::from_bytes(header_name_str.as_bytes())
         .map_err(|err| HeaderRuleCompileError::BadHeaderName(header_name_str.into(), err))
@@ -328,6 +462,7 @@ impl From<config::AggregationAlgo> for HeaderAggregationStrategy {
             config::AggregationAlgo::First => HeaderAggregationStrategy::First,
             config::AggregationAlgo::Last => HeaderAggregationStrategy::Last,
             config::AggregationAlgo::Append => HeaderAggregationStrategy::Append,
+            config::AggregationAlgo::Multiple => HeaderAggregationStrategy::Multiple,
         }
     }
 }
@@ -338,6 +473,7 @@ impl From<Option<config::AggregationAlgo>> for HeaderAggregationStrategy {
             Some(config::AggregationAlgo::First) => HeaderAggregationStrategy::First,
             Some(config::AggregationAlgo::Last) => HeaderAggregationStrategy::Last,
             Some(config::AggregationAlgo::Append) => HeaderAggregationStrategy::Append,
+            Some(config::AggregationAlgo::Multiple) => HeaderAggregationStrategy::Multiple,
             None => HeaderAggregationStrategy::Last,
         }
     }
@@ -473,6 +609,7 @@ mod tests {
         for algo in [
             config::AggregationAlgo::First,
             config::AggregationAlgo::Last,
+            config::AggregationAlgo::Multiple,
         ] {
             let rule = config::ResponseHeaderRule::Propagate(config::ResponsePropagateRule {
                 spec: config::MatchSpec {
@@ -0,0 +1,50 @@
+use http::header::COOKIE;
+use ntex::http::HeaderMap as NtexHeaderMap;
+
+/// Iterates over the `name=value` pairs of the `cookie` headers of the client request,
+/// in the order they were sent. Malformed pairs are skipped.
+pub fn request_cookies(headers: &NtexHeaderMap) -> impl Iterator<Item = (&str, &str)> {
+    headers
+        .get_all(COOKIE)
+        .filter_map(|header_value| header_value.to_str().ok())
+        .flat_map(|header_value| header_value.split(';'))
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let value = value.trim();
+            // a cookie value can be wrapped in double quotes, that are not part of the value
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((name, value))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{header::COOKIE, HeaderValue};
+    use ntex::http::HeaderMap as NtexHeaderMap;
+
+    use super::request_cookies;
+
+    #[test]
+    fn parses_the_cookies_of_all_cookie_headers() {
+        let mut headers = NtexHeaderMap::new();
+        headers.append(
+            COOKIE,
+            HeaderValue::from_static("session=abc; locale=\"en-US\"; malformed").into(),
+        );
+        headers.append(COOKIE, HeaderValue::from_static("theme=dark").into());
+
+        let cookies: Vec<_> = request_cookies(&headers).collect();
+
+        assert_eq!(
+            cookies,
+            vec![("session", "abc"), ("locale", "en-US"), ("theme", "dark")]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 pub mod cache_control;
 pub mod compile;
+pub mod cookies;
 pub mod errors;
 pub mod expression;
 pub mod plan;
@@ -11,10 +12,10 @@ pub mod sanitizer;
 mod tests {
     use crate::{
         execution::client_request_details::{
-            ClientRequestDetails, JwtRequestDetails, OperationDetails,
+            ClientIdentityDetails, ClientRequestDetails, JwtRequestDetails, OperationDetails,
         },
         headers::{
-            compile::compile_headers_plan,
+            compile::{compile_headers_plan, compile_jwt_claim_headers},
             request::modify_subgraph_request_headers,
             response::{apply_subgraph_response_headers, ResponseHeaderAggregator},
         },
@@ -99,7 +100,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
 
         let mut out = HeaderMap::new();
@@ -134,7 +137,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
         let mut out = HeaderMap::new();
         modify_subgraph_request_headers(&plan, "any", &client_details, &mut out).unwrap();
@@ -182,7 +187,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
 
         let mut out = HeaderMap::new();
@@ -197,6 +204,236 @@ mod tests {
         "#);
     }
 
+    // Tests renaming propagated headers, by name and by regex.
+    #[test]
+    fn test_rename_request_headers() {
+        let yaml_str = r#"
+          headers:
+            all:
+              request:
+                - propagate:
+                    named: authorization
+                - propagate:
+                    matching: "^x-acme-.*"
+                - rename:
+                    named: authorization
+                    to: x-forwarded-auth
+                - rename:
+                    matching: "^x-acme-tenant-.*"
+                    to: x-tenant
+        "#;
+        let config = parse_yaml_config(String::from(yaml_str)).unwrap();
+        let plan = compile_headers_plan(&config.headers).unwrap();
+
+        let mut client_headers = NtexHeaderMap::new();
+        client_headers.insert(
+            header_name_owned("authorization"),
+            header_value_owned("Bearer abc").into(),
+        );
+        client_headers.insert(
+            header_name_owned("x-acme-tenant-id"),
+            header_value_owned("acme").into(),
+        );
+        client_headers.insert(
+            header_name_owned("x-acme-region"),
+            header_value_owned("eu").into(),
+        );
+
+        let client_details = ClientRequestDetails {
+            method: &http::Method::POST,
+            url: &"http://example.com".parse().unwrap(),
+            headers: client_headers.into(),
+            operation: OperationDetails {
+                name: None,
+                query: "{ __typename }",
+                kind: "query",
+            },
+            jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
+            path_params: Default::default(),
+            plugin_context: None,
+        };
+
+        let mut out = HeaderMap::new();
+        modify_subgraph_request_headers(&plan, "any", &client_details, &mut out).unwrap();
+
+        assert!(out.get("authorization").is_none());
+        assert!(out.get("x-acme-tenant-id").is_none());
+        assert_eq!(out.get("x-forwarded-auth").unwrap(), "Bearer abc");
+        assert_eq!(out.get("x-tenant").unwrap(), "acme");
+        assert_eq!(out.get("x-acme-region").unwrap(), "eu");
+    }
+
+    // Tests rules applied only to some subgraphs, operation types and operation names.
+    #[test]
+    fn test_conditional_request_rules() {
+        let yaml_str = r#"
+          headers:
+            all:
+              request:
+                - when:
+                    subgraphs: [accounts]
+                    operation_types: [mutation]
+                    rules:
+                      - propagate:
+                          named: x-csrf-token
+                - when:
+                    operation_name: "^Admin.*"
+                    rules:
+                      - insert:
+                          name: x-admin
+                          value: "true"
+        "#;
+        let config = parse_yaml_config(String::from(yaml_str)).unwrap();
+        let plan = compile_headers_plan(&config.headers).unwrap();
+
+        let mut client_headers = NtexHeaderMap::new();
+        client_headers.insert(
+            header_name_owned("x-csrf-token"),
+            header_value_owned("token").into(),
+        );
+        let url: http::Uri = "http://example.com".parse().unwrap();
+        let client_details =
+            |name: Option<&'static str>, kind: &'static str| ClientRequestDetails {
+                method: &http::Method::POST,
+                url: &url,
+                headers: client_headers.clone().into(),
+                operation: OperationDetails {
+                    name,
+                    query: "{ __typename }",
+                    kind,
+                },
+                jwt: JwtRequestDetails::Unauthenticated.into(),
+                client: Default::default(),
+                path_params: Default::default(),
+                plugin_context: None,
+            };
+
+        let mut out = HeaderMap::new();
+        let mutation = client_details(Some("AdminUpdate"), "mutation");
+        modify_subgraph_request_headers(&plan, "accounts", &mutation, &mut out).unwrap();
+        insta::assert_snapshot!(out.to_string(), @r#"
+          x-csrf-token: token
+          x-admin: true
+        "#);
+
+        let mut out = HeaderMap::new();
+        modify_subgraph_request_headers(&plan, "products", &mutation, &mut out).unwrap();
+        insta::assert_snapshot!(out.to_string(), @r#"
+          x-admin: true
+        "#);
+
+        let mut out = HeaderMap::new();
+        let query = client_details(None, "query");
+        modify_subgraph_request_headers(&plan, "accounts", &query, &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    // Tests the rules depending on the client, as identified with `telemetry.client_identification`.
+    #[test]
+    fn test_client_request_rules() {
+        let yaml_str = r#"
+          headers:
+            all:
+              request:
+                - when:
+                    client_name: "^web$"
+                    rules:
+                      - insert:
+                          name: x-web
+                          value: "true"
+                - insert:
+                    name: x-client
+                    expression: '(string(.request.client.name) ?? "anonymous") + "@" + (string(.request.client.version) ?? "unknown")'
+        "#;
+        let config = parse_yaml_config(String::from(yaml_str)).unwrap();
+        let plan = compile_headers_plan(&config.headers).unwrap();
+
+        let url: http::Uri = "http://example.com".parse().unwrap();
+        let client_details = |client: ClientIdentityDetails| ClientRequestDetails {
+            method: &http::Method::POST,
+            url: &url,
+            headers: NtexHeaderMap::new().into(),
+            operation: OperationDetails {
+                name: None,
+                query: "{ __typename }",
+                kind: "query",
+            },
+            jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: client.into(),
+            path_params: Default::default(),
+            plugin_context: None,
+        };
+
+        let mut out = HeaderMap::new();
+        let web = client_details(ClientIdentityDetails {
+            name: Some("web".to_string()),
+            version: Some("1.2.0".to_string()),
+        });
+        modify_subgraph_request_headers(&plan, "accounts", &web, &mut out).unwrap();
+        insta::assert_snapshot!(out.to_string(), @r#"
+          x-web: true
+          x-client: web@1.2.0
+        "#);
+
+        let mut out = HeaderMap::new();
+        let anonymous = client_details(ClientIdentityDetails::default());
+        modify_subgraph_request_headers(&plan, "accounts", &anonymous, &mut out).unwrap();
+        insta::assert_snapshot!(out.to_string(), @r#"
+          x-client: anonymous@unknown
+        "#);
+    }
+
+    // Tests forwarding selected cookies, in the cookie header and as a header of their own.
+    #[test]
+    fn test_propagate_cookies() {
+        let yaml_str = r#"
+          headers:
+            all:
+              request:
+                - propagate_cookies:
+                    named: [session, locale]
+                - propagate_cookies:
+                    named: session
+                    header: x-session-id
+                - insert:
+                    name: x-theme
+                    expression: '.request.cookies.theme'
+        "#;
+        let config = parse_yaml_config(String::from(yaml_str)).unwrap();
+        let plan = compile_headers_plan(&config.headers).unwrap();
+
+        let mut client_headers = NtexHeaderMap::new();
+        client_headers.insert(
+            header_name_owned("cookie"),
+            header_value_owned("tracking=1; session=abc; theme=dark; locale=en").into(),
+        );
+
+        let client_details = ClientRequestDetails {
+            method: &http::Method::POST,
+            url: &"http://example.com".parse().unwrap(),
+            headers: client_headers.into(),
+            operation: OperationDetails {
+                name: None,
+                query: "{ __typename }",
+                kind: "query",
+            },
+            jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
+            path_params: Default::default(),
+            plugin_context: None,
+        };
+
+        let mut out = HeaderMap::new();
+        modify_subgraph_request_headers(&plan, "any", &client_details, &mut out).unwrap();
+
+        insta::assert_snapshot!(out.to_string(), @r#"
+          cookie: session=abc; locale=en
+          x-session-id: abc
+          x-theme: dark
+        "#);
+    }
+
     // Tests inserting a header with a value from a VRL expression.
     #[test]
     fn test_insert_request_header_with_expression() {
@@ -221,7 +458,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
 
         let mut out = HeaderMap::new();
@@ -232,6 +471,123 @@ mod tests {
         "#);
     }
 
+    // Tests inserting a header with a value from the claims of the JWT of the client.
+    #[test]
+    fn test_insert_request_header_with_jwt_claims() {
+        let yaml_str = r#"
+          headers:
+            subgraphs:
+              accounts:
+                request:
+                  - insert:
+                      name: x-user-id
+                      expression: '.request.jwt.claims.sub'
+        "#;
+        let config = parse_yaml_config(String::from(yaml_str)).unwrap();
+        let plan = compile_headers_plan(&config.headers).unwrap();
+        let client_headers = NtexHeaderMap::new();
+        let client_details = ClientRequestDetails {
+            method: &http::Method::POST,
+            url: &"http://example.com".parse().unwrap(),
+            headers: client_headers.into(),
+            operation: OperationDetails {
+                name: Some("Me"),
+                query: "query Me { me { id } }",
+                kind: "query",
+            },
+            jwt: JwtRequestDetails::Authenticated {
+                token: "token".to_string(),
+                prefix: Some("Bearer".to_string()),
+                claims: sonic_rs::json!({ "sub": "user-1" }),
+                scopes: None,
+            }
+            .into(),
+            client: Default::default(),
+            path_params: Default::default(),
+            plugin_context: None,
+        };
+
+        let mut out = HeaderMap::new();
+        modify_subgraph_request_headers(&plan, "accounts", &client_details, &mut out).unwrap();
+        assert_eq!(out.get("x-user-id").unwrap(), "user-1");
+
+        let mut out = HeaderMap::new();
+        modify_subgraph_request_headers(&plan, "products", &client_details, &mut out).unwrap();
+        assert!(out.get("x-user-id").is_none());
+    }
+
+    #[test]
+    fn test_forward_jwt_claims_as_headers() {
+        let yaml_str = r#"
+          headers:
+            all:
+              request:
+                - propagate:
+                    named: x-tenant
+          jwt:
+            enabled: true
+            jwks_providers: []
+            forward_claims_to_upstream_headers:
+              - name: x-user-id
+                claim: sub
+              - name: x-tenant
+                claim: tenant
+              - name: x-roles
+                claim: roles
+              - name: x-jwt-claims
+                claims: base64
+              - name: x-user-role
+                expression: '.request.jwt.claims.roles[0] ?? "viewer"'
+        "#;
+        let config = parse_yaml_config(String::from(yaml_str)).unwrap();
+        let mut plan = compile_headers_plan(&config.headers).unwrap();
+        plan.request.jwt_claims =
+            compile_jwt_claim_headers(&config.jwt.forward_claims_to_upstream_headers).unwrap();
+
+        // A client can not spoof the headers of the claims, even when they are propagated
+        let mut client_headers = NtexHeaderMap::new();
+        client_headers.insert(
+            header_name_owned("x-tenant"),
+            header_value_owned("spoofed").into(),
+        );
+        let client_headers = std::sync::Arc::new(client_headers);
+        let url = "http://example.com".parse().unwrap();
+        let client_details = |jwt: JwtRequestDetails| ClientRequestDetails {
+            method: &http::Method::POST,
+            url: &url,
+            headers: client_headers.clone(),
+            operation: OperationDetails {
+                name: None,
+                query: "{ __typename }",
+                kind: "query",
+            },
+            jwt: jwt.into(),
+            client: Default::default(),
+            path_params: Default::default(),
+            plugin_context: None,
+        };
+
+        let mut out = HeaderMap::new();
+        let authenticated = client_details(JwtRequestDetails::Authenticated {
+            token: "token".to_string(),
+            prefix: Some("Bearer".to_string()),
+            claims: sonic_rs::json!({ "sub": "user-1", "roles": ["admin"] }),
+            scopes: None,
+        });
+        modify_subgraph_request_headers(&plan, "any", &authenticated, &mut out).unwrap();
+        insta::assert_snapshot!(out.to_string(), @r#"
+          x-user-id: user-1
+          x-roles: ["admin"]
+          x-jwt-claims: eyJzdWIiOiJ1c2VyLTEiLCJyb2xlcyI6WyJhZG1pbiJdfQ==
+          x-user-role: admin
+        "#);
+
+        let mut out = HeaderMap::new();
+        let anonymous = client_details(JwtRequestDetails::Unauthenticated);
+        modify_subgraph_request_headers(&plan, "any", &anonymous, &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
     // Tests VRL expression fallback to a default value when a field is null.
     #[test]
     fn test_insert_request_header_with_expression_fallback() {
@@ -256,7 +612,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
 
         let mut out = HeaderMap::new();
@@ -297,7 +655,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
 
         // For "accounts" subgraph, the specific rule should apply.
@@ -342,7 +702,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
 
         let mut accumulator = ResponseHeaderAggregator::default();
@@ -411,7 +773,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
 
         let mut accumulator = ResponseHeaderAggregator::default();
@@ -479,7 +843,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
         let mut accumulator = ResponseHeaderAggregator::default();
 
@@ -540,7 +906,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
         let mut accumulator = ResponseHeaderAggregator::default();
 
@@ -578,6 +946,187 @@ mod tests {
         "#);
     }
 
+    // Tests that all the set-cookie fields of a single subgraph response are kept.
+    #[test]
+    fn test_response_propagate_set_cookie_fields_of_a_subgraph() {
+        let yaml_str = r#"
+          headers:
+            all:
+              response:
+                - propagate:
+                    named: set-cookie
+                    algorithm: append
+        "#;
+        let config = parse_yaml_config(String::from(yaml_str)).unwrap();
+        let plan = compile_headers_plan(&config.headers).unwrap();
+        let client_headers = NtexHeaderMap::new();
+        let client_details = ClientRequestDetails {
+            method: &http::Method::POST,
+            url: &"http://example.com".parse().unwrap(),
+            headers: client_headers.into(),
+            operation: OperationDetails {
+                name: None,
+                query: "{ __typename }",
+                kind: "query",
+            },
+            jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
+            path_params: Default::default(),
+            plugin_context: None,
+        };
+        let mut accumulator = ResponseHeaderAggregator::default();
+
+        let mut subgraph_headers = HeaderMap::new();
+        subgraph_headers.append(header_name_owned("set-cookie"), header_value_owned("a=1"));
+        subgraph_headers.append(header_name_owned("set-cookie"), header_value_owned("b=2"));
+        apply_subgraph_response_headers(
+            &plan,
+            "subgraph1",
+            &subgraph_headers,
+            &client_details,
+            &mut accumulator,
+        )
+        .unwrap();
+
+        let mut response = ntex::http::Response::Ok().finish();
+        accumulator
+            .modify_client_response_headers(response.headers_mut())
+            .unwrap();
+        let final_headers = response.headers();
+
+        insta::assert_snapshot!(final_headers.to_string(), @r#"
+          set-cookie: a=1
+          set-cookie: b=2
+        "#);
+    }
+
+    // Tests the `multiple` algorithm, emitting the value of every subgraph as a separate field.
+    #[test]
+    fn test_response_propagate_multiple() {
+        let yaml_str = r#"
+          headers:
+            all:
+              response:
+                - propagate:
+                    named: x-served-at
+                    algorithm: multiple
+        "#;
+        let config = parse_yaml_config(String::from(yaml_str)).unwrap();
+        let plan = compile_headers_plan(&config.headers).unwrap();
+        let client_headers = NtexHeaderMap::new();
+        let client_details = ClientRequestDetails {
+            method: &http::Method::POST,
+            url: &"http://example.com".parse().unwrap(),
+            headers: client_headers.into(),
+            operation: OperationDetails {
+                name: None,
+                query: "{ __typename }",
+                kind: "query",
+            },
+            jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
+            path_params: Default::default(),
+            plugin_context: None,
+        };
+        let mut accumulator = ResponseHeaderAggregator::default();
+
+        let mut subgraph1_headers = HeaderMap::new();
+        subgraph1_headers.insert(
+            header_name_owned("x-served-at"),
+            header_value_owned("Mon, 01 Jan 2024"),
+        );
+        apply_subgraph_response_headers(
+            &plan,
+            "subgraph1",
+            &subgraph1_headers,
+            &client_details,
+            &mut accumulator,
+        )
+        .unwrap();
+
+        let mut subgraph2_headers = HeaderMap::new();
+        subgraph2_headers.insert(
+            header_name_owned("x-served-at"),
+            header_value_owned("Tue, 02 Jan 2024"),
+        );
+        apply_subgraph_response_headers(
+            &plan,
+            "subgraph2",
+            &subgraph2_headers,
+            &client_details,
+            &mut accumulator,
+        )
+        .unwrap();
+
+        let mut response = ntex::http::Response::Ok().finish();
+        accumulator
+            .modify_client_response_headers(response.headers_mut())
+            .unwrap();
+        let final_headers = response.headers();
+
+        insta::assert_snapshot!(final_headers.to_string(), @r#"
+          x-served-at: Mon, 01 Jan 2024
+          x-served-at: Tue, 02 Jan 2024
+        "#);
+    }
+
+    // Tests renaming a response header propagated from several subgraphs.
+    #[test]
+    fn test_rename_response_headers() {
+        let yaml_str = r#"
+          headers:
+            all:
+              response:
+                - propagate:
+                    named: x-cost
+                    algorithm: append
+                - rename:
+                    named: x-cost
+                    to: x-subgraph-cost
+        "#;
+        let config = parse_yaml_config(String::from(yaml_str)).unwrap();
+        let plan = compile_headers_plan(&config.headers).unwrap();
+        let client_headers = NtexHeaderMap::new();
+        let client_details = ClientRequestDetails {
+            method: &http::Method::POST,
+            url: &"http://example.com".parse().unwrap(),
+            headers: client_headers.into(),
+            operation: OperationDetails {
+                name: None,
+                query: "{ __typename }",
+                kind: "query",
+            },
+            jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
+            path_params: Default::default(),
+            plugin_context: None,
+        };
+        let mut accumulator = ResponseHeaderAggregator::default();
+
+        for (subgraph_name, cost) in [("subgraph1", "1"), ("subgraph2", "2")] {
+            let mut subgraph_headers = HeaderMap::new();
+            subgraph_headers.insert(header_name_owned("x-cost"), header_value_owned(cost));
+            apply_subgraph_response_headers(
+                &plan,
+                subgraph_name,
+                &subgraph_headers,
+                &client_details,
+                &mut accumulator,
+            )
+            .unwrap();
+        }
+
+        let mut response = ntex::http::Response::Ok().finish();
+        accumulator
+            .modify_client_response_headers(response.headers_mut())
+            .unwrap();
+        let final_headers = response.headers();
+
+        insta::assert_snapshot!(final_headers.to_string(), @r#"
+          x-subgraph-cost: 1, 2
+        "#);
+    }
+
     // Tests inserting a response header with a value from a VRL expression.
     #[test]
     fn test_insert_response_header_with_expression() {
@@ -602,7 +1151,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
 
         let mut accumulator = ResponseHeaderAggregator::default();
@@ -665,7 +1216,9 @@ mod tests {
                 kind: "query",
             },
             jwt: JwtRequestDetails::Unauthenticated.into(),
+            client: Default::default(),
             path_params: Default::default(),
+            plugin_context: None,
         };
 
         let mut out = HeaderMap::new();
@@ -3,6 +3,8 @@ use hive_router_internal::expressions::vrl::compiler::Program as VrlProgram;
 use http::{HeaderName, HeaderValue};
 use regex_automata::meta::Regex;
 
+use crate::execution::client_request_details::ClientRequestDetails;
+
 #[derive(Default, Clone)]
 pub struct HeaderRulesPlan {
     pub request: RequestHeaderRules,
@@ -15,6 +17,10 @@ type SubgraphName = String;
 pub struct RequestHeaderRules {
     pub global: Vec<RequestHeaderRule>,
     pub by_subgraph: HashMap<SubgraphName, Vec<RequestHeaderRule>>,
+    /// The headers carrying the JWT claims, set after the other rules (`jwt.forward_claims_to_upstream_headers`).
+    pub jwt_claims: Vec<RequestJwtClaimHeader>,
+    /// The header carrying the ID of the request, set after the other rules (`request_id.subgraph_header`).
+    pub request_id: Option<RequestIdHeader>,
 }
 
 #[derive(Clone, Default)]
@@ -31,6 +37,10 @@ pub enum RequestHeaderRule {
     InsertExpression(RequestInsertExpression),
     RemoveNamed(RequestRemoveNamed),
     RemoveRegex(RequestRemoveRegex),
+    RenameNamed(RequestRenameNamed),
+    RenameRegex(RequestRenameRegex),
+    Conditional(RequestConditional),
+    PropagateCookies(RequestPropagateCookies),
 }
 
 #[derive(Clone)]
@@ -40,6 +50,12 @@ pub struct RequestPropagateNamed {
     pub rename: Option<HeaderName>,
 }
 
+#[derive(Clone)]
+pub struct RequestPropagateCookies {
+    pub names: Vec<String>,
+    pub header: Option<HeaderName>,
+}
+
 #[derive(Clone)]
 pub struct RequestPropagateRegex {
     pub include: Option<Regex>,
@@ -92,6 +108,115 @@ pub struct ResponseRemoveRegex {
     pub regex: Regex,
 }
 
+#[derive(Clone)]
+pub struct RequestRenameNamed {
+    pub names: Vec<HeaderName>,
+    pub to: HeaderName,
+}
+
+#[derive(Clone)]
+pub struct ResponseRenameNamed {
+    pub names: Vec<HeaderName>,
+    pub to: HeaderName,
+}
+
+#[derive(Clone)]
+pub struct RequestRenameRegex {
+    pub include: Regex,
+    pub exclude: Option<Regex>,
+    pub to: HeaderName,
+}
+
+#[derive(Clone)]
+pub struct ResponseRenameRegex {
+    pub include: Regex,
+    pub exclude: Option<Regex>,
+    pub to: HeaderName,
+}
+
+#[derive(Clone)]
+pub struct RequestConditional {
+    pub condition: HeaderRuleCondition,
+    pub rules: Vec<RequestHeaderRule>,
+}
+
+#[derive(Clone)]
+pub struct RequestJwtClaimHeader {
+    pub name: HeaderName,
+    pub source: JwtClaimHeaderSource,
+}
+
+#[derive(Clone)]
+pub struct RequestIdHeader {
+    /// The header of the client request holding the ID, set by the router.
+    pub source: HeaderName,
+    pub name: HeaderName,
+}
+
+#[derive(Clone)]
+pub enum JwtClaimHeaderSource {
+    Claim(String),
+    AllClaims(JwtClaimsHeaderEncoding),
+    Expression(Box<VrlProgram>),
+}
+
+#[derive(Clone, Copy)]
+pub enum JwtClaimsHeaderEncoding {
+    Base64,
+    Json,
+}
+
+#[derive(Clone)]
+pub struct ResponseConditional {
+    pub condition: HeaderRuleCondition,
+    pub rules: Vec<ResponseHeaderRule>,
+}
+
+#[derive(Clone, Default)]
+pub struct HeaderRuleCondition {
+    pub subgraphs: Option<Vec<String>>,
+    pub operation_types: Option<Vec<&'static str>>,
+    pub operation_name: Option<Regex>,
+    pub client_name: Option<Regex>,
+}
+
+impl HeaderRuleCondition {
+    pub fn matches(&self, subgraph_name: &str, client_request: &ClientRequestDetails) -> bool {
+        let operation = &client_request.operation;
+
+        if self
+            .subgraphs
+            .as_ref()
+            .is_some_and(|subgraphs| !subgraphs.iter().any(|name| name == subgraph_name))
+        {
+            return false;
+        }
+
+        if self
+            .operation_types
+            .as_ref()
+            .is_some_and(|types| !types.contains(&operation.kind))
+        {
+            return false;
+        }
+
+        if !matches_name(&self.operation_name, operation.name) {
+            return false;
+        }
+
+        matches_name(&self.client_name, client_request.client.name.as_deref())
+    }
+}
+
+/// An omitted regex matches every name, an anonymous one only matches an omitted regex.
+fn matches_name(regex: &Option<Regex>, name: Option<&str>) -> bool {
+    match (regex, name) {
+        (None, _) => true,
+        (Some(regex), Some(name)) => regex.is_match(name.as_bytes()),
+        (Some(_), None) => false,
+    }
+}
+
 #[derive(Clone)]
 pub enum ResponseHeaderRule {
     PropagateNamed(ResponsePropagateNamed),
@@ -100,6 +225,9 @@ pub enum ResponseHeaderRule {
     InsertExpression(ResponseInsertExpression),
     RemoveNamed(ResponseRemoveNamed),
     RemoveRegex(ResponseRemoveRegex),
+    RenameNamed(ResponseRenameNamed),
+    RenameRegex(ResponseRenameRegex),
+    Conditional(ResponseConditional),
 }
 
 #[derive(Clone)]
@@ -122,4 +250,5 @@ pub enum HeaderAggregationStrategy {
     First,
     Last,
     Append,
+    Multiple,
 }
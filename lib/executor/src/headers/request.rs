@@ -1,14 +1,20 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
 use hive_router_internal::expressions::ExecutableProgram;
-use http::HeaderMap;
+use http::{header::COOKIE, HeaderMap, HeaderName, HeaderValue};
+use sonic_rs::JsonValueTrait;
 
 use crate::{
-    execution::client_request_details::ClientRequestDetails,
+    execution::client_request_details::{ClientRequestDetails, JwtRequestDetails},
     headers::{
+        cookies::request_cookies,
         errors::HeaderRuleRuntimeError,
         expression::vrl_value_to_header_value,
         plan::{
-            HeaderRulesPlan, RequestHeaderRule, RequestInsertExpression, RequestInsertStatic,
-            RequestPropagateNamed, RequestPropagateRegex, RequestRemoveNamed, RequestRemoveRegex,
+            HeaderRulesPlan, JwtClaimHeaderSource, JwtClaimsHeaderEncoding, RequestConditional,
+            RequestHeaderRule, RequestIdHeader, RequestInsertExpression, RequestInsertStatic,
+            RequestJwtClaimHeader, RequestPropagateCookies, RequestPropagateNamed,
+            RequestPropagateRegex, RequestRemoveNamed, RequestRemoveRegex, RequestRenameNamed,
+            RequestRenameRegex,
         },
         sanitizer::{is_denied_header, is_never_join_header},
     },
@@ -35,6 +41,14 @@ pub fn modify_subgraph_request_headers(
         action.apply_request_headers(&ctx, output_headers)?;
     }
 
+    for claim_header in &header_rule_plan.request.jwt_claims {
+        claim_header.apply_request_headers(&ctx, output_headers)?;
+    }
+
+    if let Some(request_id_header) = &header_rule_plan.request.request_id {
+        request_id_header.apply_request_headers(&ctx, output_headers)?;
+    }
+
     Ok(())
 }
 
@@ -64,6 +78,10 @@ impl ApplyRequestHeader for RequestHeaderRule {
             Self::InsertExpression(data) => data.apply_request_headers(ctx, output_headers),
             Self::RemoveNamed(data) => data.apply_request_headers(ctx, output_headers),
             Self::RemoveRegex(data) => data.apply_request_headers(ctx, output_headers),
+            Self::RenameNamed(data) => data.apply_request_headers(ctx, output_headers),
+            Self::RenameRegex(data) => data.apply_request_headers(ctx, output_headers),
+            Self::Conditional(data) => data.apply_request_headers(ctx, output_headers),
+            Self::PropagateCookies(data) => data.apply_request_headers(ctx, output_headers),
         }
     }
 }
@@ -183,6 +201,78 @@ impl ApplyRequestHeader for RequestInsertExpression {
     }
 }
 
+impl ApplyRequestHeader for RequestJwtClaimHeader {
+    fn apply_request_headers(
+        &self,
+        ctx: &RequestExpressionContext,
+        output_headers: &mut HeaderMap,
+    ) -> Result<(), HeaderRuleRuntimeError> {
+        // The header is owned by the router, a value propagated from the client
+        // must never reach the subgraph, even when the request is not authenticated.
+        output_headers.remove(&self.name);
+
+        let JwtRequestDetails::Authenticated { claims, .. } = ctx.client_request.jwt.as_ref()
+        else {
+            return Ok(());
+        };
+
+        let header_value = match &self.source {
+            JwtClaimHeaderSource::Claim(claim) => {
+                let Some(value) = claims.get(claim.as_str()) else {
+                    return Ok(());
+                };
+                match value.as_str() {
+                    Some(value) => HeaderValue::from_str(value).ok(),
+                    None => HeaderValue::from_str(&value.to_string()).ok(),
+                }
+            }
+            JwtClaimHeaderSource::AllClaims(encoding) => {
+                let json = claims.to_string();
+                match encoding {
+                    JwtClaimsHeaderEncoding::Base64 => {
+                        HeaderValue::from_str(&STANDARD.encode(json)).ok()
+                    }
+                    JwtClaimsHeaderEncoding::Json => HeaderValue::from_str(&json).ok(),
+                }
+            }
+            JwtClaimHeaderSource::Expression(expression) => {
+                let value = expression.execute(ctx.into()).map_err(|err| {
+                    HeaderRuleRuntimeError::ExpressionEvaluation(
+                        self.name.to_string(),
+                        Box::new(err.0),
+                    )
+                })?;
+                match vrl_value_to_header_value(value) {
+                    Some(header_value) => Some(header_value),
+                    None => return Ok(()),
+                }
+            }
+        };
+
+        let header_value = header_value
+            .ok_or_else(|| HeaderRuleRuntimeError::BadHeaderValue(self.name.to_string()))?;
+        output_headers.insert(self.name.clone(), header_value);
+
+        Ok(())
+    }
+}
+
+impl ApplyRequestHeader for RequestIdHeader {
+    fn apply_request_headers(
+        &self,
+        ctx: &RequestExpressionContext,
+        output_headers: &mut HeaderMap,
+    ) -> Result<(), HeaderRuleRuntimeError> {
+        // Replaces whatever the other rules set, the subgraphs must see the ID the client gets
+        output_headers.remove(&self.name);
+        if let Some(header_value) = ctx.client_request.headers.get(&self.source) {
+            output_headers.insert(self.name.clone(), header_value.into());
+        }
+
+        Ok(())
+    }
+}
+
 impl ApplyRequestHeader for RequestRemoveNamed {
     fn apply_request_headers(
         &self,
@@ -224,3 +314,125 @@ impl ApplyRequestHeader for RequestRemoveRegex {
         Ok(())
     }
 }
+
+impl ApplyRequestHeader for RequestRenameNamed {
+    fn apply_request_headers(
+        &self,
+        _ctx: &RequestExpressionContext,
+        output_headers: &mut HeaderMap,
+    ) -> Result<(), HeaderRuleRuntimeError> {
+        for header_name in &self.names {
+            rename_header(output_headers, header_name, &self.to);
+        }
+
+        Ok(())
+    }
+}
+
+impl ApplyRequestHeader for RequestRenameRegex {
+    fn apply_request_headers(
+        &self,
+        _ctx: &RequestExpressionContext,
+        output_headers: &mut HeaderMap,
+    ) -> Result<(), HeaderRuleRuntimeError> {
+        let headers_to_rename: Vec<HeaderName> = output_headers
+            .keys()
+            .filter(|header_name| {
+                let header_bytes = header_name.as_str().as_bytes();
+                self.include.is_match(header_bytes)
+                    && !self
+                        .exclude
+                        .as_ref()
+                        .is_some_and(|regex| regex.is_match(header_bytes))
+            })
+            .cloned()
+            .collect();
+
+        for header_name in headers_to_rename.iter() {
+            rename_header(output_headers, header_name, &self.to);
+        }
+
+        Ok(())
+    }
+}
+
+impl ApplyRequestHeader for RequestConditional {
+    fn apply_request_headers(
+        &self,
+        ctx: &RequestExpressionContext,
+        output_headers: &mut HeaderMap,
+    ) -> Result<(), HeaderRuleRuntimeError> {
+        if !self
+            .condition
+            .matches(ctx.subgraph_name, ctx.client_request)
+        {
+            return Ok(());
+        }
+
+        for rule in &self.rules {
+            rule.apply_request_headers(ctx, output_headers)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ApplyRequestHeader for RequestPropagateCookies {
+    fn apply_request_headers(
+        &self,
+        ctx: &RequestExpressionContext,
+        output_headers: &mut HeaderMap,
+    ) -> Result<(), HeaderRuleRuntimeError> {
+        let mut cookies = request_cookies(&ctx.client_request.headers)
+            .filter(|(name, _)| self.names.iter().any(|named| named == *name));
+
+        if let Some(header_name) = &self.header {
+            if is_denied_header(header_name) {
+                return Ok(());
+            }
+            if let Some((_, value)) = cookies.next() {
+                let header_value = HeaderValue::from_str(value)
+                    .map_err(|_| HeaderRuleRuntimeError::BadHeaderValue(header_name.to_string()))?;
+                output_headers.insert(header_name.clone(), header_value);
+            }
+            return Ok(());
+        }
+
+        let mut cookie_header = output_headers
+            .get(COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let mut matched = false;
+        for (name, value) in cookies {
+            if !cookie_header.is_empty() {
+                cookie_header.push_str("; ");
+            }
+            cookie_header.push_str(name);
+            cookie_header.push('=');
+            cookie_header.push_str(value);
+            matched = true;
+        }
+
+        if matched {
+            let header_value = HeaderValue::from_str(&cookie_header)
+                .map_err(|_| HeaderRuleRuntimeError::BadHeaderValue(COOKIE.to_string()))?;
+            output_headers.insert(COOKIE, header_value);
+        }
+
+        Ok(())
+    }
+}
+
+/// Moves all the values of the header under the new name, after the values it may already have.
+fn rename_header(output_headers: &mut HeaderMap, from: &HeaderName, to: &HeaderName) {
+    if from == to || is_denied_header(from) || is_denied_header(to) {
+        return;
+    }
+
+    let values: Vec<_> = output_headers.get_all(from).iter().cloned().collect();
+    output_headers.remove(from);
+    for value in values {
+        output_headers.append(to, value);
+    }
+}
@@ -4,9 +4,10 @@ use crate::{
         errors::HeaderRuleRuntimeError,
         expression::vrl_value_to_header_value,
         plan::{
-            HeaderAggregationStrategy, HeaderRulesPlan, ResponseHeaderRule,
+            HeaderAggregationStrategy, HeaderRulesPlan, ResponseConditional, ResponseHeaderRule,
             ResponseInsertExpression, ResponseInsertStatic, ResponsePropagateNamed,
-            ResponsePropagateRegex, ResponseRemoveNamed, ResponseRemoveRegex,
+            ResponsePropagateRegex, ResponseRemoveNamed, ResponseRemoveRegex, ResponseRenameNamed,
+            ResponseRenameRegex,
         },
     },
 };
@@ -78,6 +79,9 @@ impl ApplyResponseHeader for ResponseHeaderRule {
             }
             ResponseHeaderRule::RemoveNamed(data) => data.apply_response_headers(ctx, accumulator),
             ResponseHeaderRule::RemoveRegex(data) => data.apply_response_headers(ctx, accumulator),
+            ResponseHeaderRule::RenameNamed(data) => data.apply_response_headers(ctx, accumulator),
+            ResponseHeaderRule::RenameRegex(data) => data.apply_response_headers(ctx, accumulator),
+            ResponseHeaderRule::Conditional(data) => data.apply_response_headers(ctx, accumulator),
         }
     }
 }
@@ -95,7 +99,8 @@ impl ApplyResponseHeader for ResponsePropagateNamed {
                 continue;
             }
 
-            if let Some(header_value) = ctx.subgraph_headers.get(header_name) {
+            // a subgraph can send several `set-cookie` fields, none of them must be dropped
+            for header_value in ctx.subgraph_headers.get_all(header_name) {
                 matched = true;
                 accumulator.write(
                     self.rename.as_ref().unwrap_or(header_name),
@@ -242,6 +247,69 @@ impl ApplyResponseHeader for ResponseRemoveRegex {
     }
 }
 
+impl ApplyResponseHeader for ResponseRenameNamed {
+    fn apply_response_headers(
+        &self,
+        _ctx: &ResponseExpressionContext,
+        accumulator: &mut ResponseHeaderAggregator,
+    ) -> Result<(), HeaderRuleRuntimeError> {
+        for header_name in &self.names {
+            accumulator.rename(header_name, &self.to);
+        }
+
+        Ok(())
+    }
+}
+
+impl ApplyResponseHeader for ResponseRenameRegex {
+    fn apply_response_headers(
+        &self,
+        _ctx: &ResponseExpressionContext,
+        accumulator: &mut ResponseHeaderAggregator,
+    ) -> Result<(), HeaderRuleRuntimeError> {
+        let headers_to_rename: Vec<HeaderName> = accumulator
+            .entries
+            .keys()
+            .filter(|header_name| {
+                let header_bytes = header_name.as_str().as_bytes();
+                self.include.is_match(header_bytes)
+                    && !self
+                        .exclude
+                        .as_ref()
+                        .is_some_and(|regex| regex.is_match(header_bytes))
+            })
+            .cloned()
+            .collect();
+
+        for header_name in headers_to_rename.iter() {
+            accumulator.rename(header_name, &self.to);
+        }
+
+        Ok(())
+    }
+}
+
+impl ApplyResponseHeader for ResponseConditional {
+    fn apply_response_headers(
+        &self,
+        ctx: &ResponseExpressionContext,
+        accumulator: &mut ResponseHeaderAggregator,
+    ) -> Result<(), HeaderRuleRuntimeError> {
+        if !self
+            .condition
+            .matches(ctx.subgraph_name, ctx.client_request)
+        {
+            return Ok(());
+        }
+
+        for rule in &self.rules {
+            rule.apply_response_headers(ctx, accumulator)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl ResponseHeaderAggregator {
     /// Modify the outgoing client response headers based on the aggregated headers from subgraphs.
     #[inline]
@@ -254,7 +322,9 @@ impl ResponseHeaderAggregator {
                 continue;
             }
 
-            if is_never_join_header(&name) {
+            if is_never_join_header(&name)
+                || matches!(agg_strategy, HeaderAggregationStrategy::Multiple)
+            {
                 // never-join headers must be emitted as multiple header fields
                 for value in values {
                     headers.append(name.clone(), value.into());
@@ -365,13 +435,27 @@ impl ResponseHeaderAggregator {
                 values.clear();
                 values.push(value.clone());
             }
-            (HeaderAggregationStrategy::Append, _) => {
+            (HeaderAggregationStrategy::Append | HeaderAggregationStrategy::Multiple, _) => {
                 values.push(value.clone());
             }
             (_, _) => {}
         }
     }
 
+    /// Moves the values of a header under the new name,
+    /// merged with the values it may already have according to their strategy.
+    pub fn rename(&mut self, from: &HeaderName, to: &HeaderName) {
+        if from == to || is_denied_response_header(from) || is_denied_response_header(to) {
+            return;
+        }
+
+        if let Some((strategy, values)) = self.entries.remove(from) {
+            for value in values.iter() {
+                self.write(to, value, strategy);
+            }
+        }
+    }
+
     // I deliberately chose to have a dedicated funtion over From<T>
     // to convert headers from "early return responses" (from coprocessor and plugins),
     // to prevent us from accidentally using First, Last or Append strategies,
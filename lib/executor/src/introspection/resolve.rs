@@ -48,6 +48,22 @@ fn resolve_str_variable<'a>(
         .and_then(|value| value.as_str())
 }
 
+/// Root introspection fields excluded by their `@skip` or `@include` conditions are not resolved,
+/// they would be left out of the response anyway.
+fn is_selected(
+    skip_if: &Option<String>,
+    include_if: &Option<String>,
+    ctx: &IntrospectionContext,
+) -> bool {
+    let skipped = skip_if
+        .as_deref()
+        .is_some_and(|var_name| resolve_boolean_variable(var_name, &ctx.variables) == Some(true));
+    let included = include_if
+        .as_deref()
+        .is_none_or(|var_name| resolve_boolean_variable(var_name, &ctx.variables) == Some(true));
+    !skipped && included
+}
+
 fn get_deprecation_reason(directives: &[Directive]) -> Option<&str> {
     directives
         .iter()
@@ -88,6 +104,36 @@ fn get_specified_by_url(directives: &[Directive]) -> Option<&str> {
         })
 }
 
+/// The `includeDeprecated` argument of the `args`, `fields`, `enumValues` and `inputFields` fields.
+fn resolve_include_deprecated(field: &FieldSelection, ctx: &IntrospectionContext) -> bool {
+    field
+        .arguments
+        .as_ref()
+        .and_then(|a| a.get_argument("includeDeprecated"))
+        .and_then(|v| match v {
+            AstValue::Boolean(b) => Some(*b),
+            AstValue::Variable(var_name) => {
+                resolve_boolean_variable(var_name.as_str(), &ctx.variables)
+            }
+            _ => None,
+        })
+        .unwrap_or(false)
+}
+
+fn resolve_input_values<'exec>(
+    input_values: &'exec [InputValue],
+    field: &'exec FieldSelection,
+    ctx: &'exec IntrospectionContext,
+) -> Value<'exec> {
+    let include_deprecated = resolve_include_deprecated(field, ctx);
+    let input_values = input_values
+        .iter()
+        .filter(|iv| include_deprecated || !is_deprecated(&iv.directives))
+        .map(|iv| resolve_input_value(iv, &field.selections, ctx))
+        .collect();
+    Value::Array(input_values)
+}
+
 fn is_one_of(directives: &[Directive]) -> bool {
     directives.iter().any(|d| d.name == "oneOf")
 }
@@ -175,14 +221,7 @@ fn resolve_field_selections<'exec>(
                     .description
                     .as_ref()
                     .map_or(Value::Null, |s| Value::String(s.into())),
-                "args" => {
-                    let args: Vec<_> = f
-                        .arguments
-                        .iter()
-                        .map(|arg| resolve_input_value(arg, &field.selections, ctx))
-                        .collect();
-                    Value::Array(args)
-                }
+                "args" => resolve_input_values(&f.arguments, field, ctx),
                 "type" => resolve_type(&f.field_type, &field.selections, ctx),
                 "isDeprecated" => Value::Bool(is_deprecated(&f.directives)),
                 "deprecationReason" => get_deprecation_reason(&f.directives)
@@ -304,18 +343,7 @@ fn resolve_type_definition_selections<'exec>(
                         _ => None,
                     };
                     if let Some(fields) = fields {
-                        let include_deprecated = field
-                            .arguments
-                            .as_ref()
-                            .and_then(|a| a.get_argument("includeDeprecated"))
-                            .and_then(|v| match v {
-                                AstValue::Boolean(b) => Some(*b),
-                                AstValue::Variable(var_name) => {
-                                    resolve_boolean_variable(var_name.as_str(), &ctx.variables)
-                                }
-                                _ => None,
-                            })
-                            .unwrap_or(false);
+                        let include_deprecated = resolve_include_deprecated(field, ctx);
 
                         let fields_values: Vec<Value<'exec>> = fields
                             .iter()
@@ -331,9 +359,13 @@ fn resolve_type_definition_selections<'exec>(
                     }
                 }
                 "interfaces" => {
-                    if let TypeDefinition::Object(obj) = type_def {
-                        let interface_values: Vec<_> = obj
-                            .implements_interfaces
+                    let implements_interfaces = match type_def {
+                        TypeDefinition::Object(o) => Some(&o.implements_interfaces),
+                        TypeDefinition::Interface(i) => Some(&i.implements_interfaces),
+                        _ => None,
+                    };
+                    if let Some(implements_interfaces) = implements_interfaces {
+                        let interface_values: Vec<_> = implements_interfaces
                             .iter()
                             .filter_map(|iface_name| ctx.schema.type_by_name(iface_name))
                             .map(|t| resolve_type_definition(t, &field.selections, ctx))
@@ -361,18 +393,7 @@ fn resolve_type_definition_selections<'exec>(
                 }
                 "enumValues" => {
                     if let TypeDefinition::Enum(enum_type) = type_def {
-                        let include_deprecated = field
-                            .arguments
-                            .as_ref()
-                            .and_then(|a| a.get_argument("includeDeprecated"))
-                            .and_then(|v| match v {
-                                AstValue::Boolean(b) => Some(*b),
-                                AstValue::Variable(var_name) => {
-                                    resolve_boolean_variable(var_name.as_str(), &ctx.variables)
-                                }
-                                _ => None,
-                            })
-                            .unwrap_or(false);
+                        let include_deprecated = resolve_include_deprecated(field, ctx);
 
                         let enum_values: Vec<_> = enum_type
                             .values
@@ -386,14 +407,7 @@ fn resolve_type_definition_selections<'exec>(
                     }
                 }
                 "inputFields" => match type_def {
-                    TypeDefinition::InputObject(io) => {
-                        let fields_values: Vec<_> = io
-                            .fields
-                            .iter()
-                            .map(|f| resolve_input_value(f, &field.selections, ctx))
-                            .collect();
-                        Value::Array(fields_values)
-                    }
+                    TypeDefinition::InputObject(io) => resolve_input_values(&io.fields, field, ctx),
                     _ => Value::Null,
                 },
                 "ofType" => Value::Null,
@@ -503,14 +517,7 @@ fn resolve_directive_selections<'exec>(
                         .collect();
                     Value::Array(locs)
                 }
-                "args" => {
-                    let args: Vec<_> = d
-                        .arguments
-                        .iter()
-                        .map(|arg| resolve_input_value(arg, &field.selections, ctx))
-                        .collect();
-                    Value::Array(args)
-                }
+                "args" => resolve_input_values(&d.arguments, field, ctx),
                 "isRepeatable" => Value::Bool(d.repeatable),
                 "__typename" => Value::String("__Directive".into()),
                 _ => Value::Null,
@@ -598,7 +605,7 @@ fn resolve_schema_selections<'exec>(
                 _ => Value::Null,
             };
             schema_data.push((inner_field.selection_identifier(), value));
-        } else if let SelectionItem::FragmentSpread(_) = item {
+        } else if let SelectionItem::InlineFragment(_) = item {
             let selection_items = item.selections();
             if let Some(selection_items) = selection_items {
                 let new_data = resolve_schema_selections(selection_items, ctx);
@@ -633,6 +640,9 @@ fn resolve_root_introspection_selections<'exec>(
     let mut data = Vec::with_capacity(items.len());
     for item in items {
         if let SelectionItem::Field(field) = item {
+            if !is_selected(&field.skip_if, &field.include_if, ctx) {
+                continue;
+            }
             let value = match field.name.as_str() {
                 "__schema" => resolve_schema_field(field, ctx),
                 "__type" => {
@@ -667,13 +677,16 @@ fn resolve_root_introspection_selections<'exec>(
                 _ => Value::Null,
             };
             data.push((field.selection_identifier(), value));
-        } else if let SelectionItem::InlineFragment(_) = item {
-            let selection_items = item.selections();
-            if let Some(selection_items) = selection_items {
-                let new_data =
-                    resolve_root_introspection_selections(root_type_name, selection_items, ctx);
-                data.extend(new_data);
+        } else if let SelectionItem::InlineFragment(fragment) = item {
+            if !is_selected(&fragment.skip_if, &fragment.include_if, ctx) {
+                continue;
             }
+            let new_data = resolve_root_introspection_selections(
+                root_type_name,
+                &fragment.selections.items,
+                ctx,
+            );
+            data.extend(new_data);
         }
     }
     data
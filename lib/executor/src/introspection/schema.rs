@@ -5,8 +5,11 @@ use graphql_tools::parser::{
     schema::{Definition, TypeDefinition},
 };
 use hive_router_query_planner::{
-    consumer_schema::ConsumerSchema, state::supergraph_state::OperationKind,
+    ast::value::Value as AstValue,
+    consumer_schema::ConsumerSchema,
+    state::supergraph_state::{OperationKind, TypeNode},
 };
+use sonic_rs::Value;
 
 #[derive(Debug)]
 pub struct FieldTypeInfo {
@@ -64,6 +67,14 @@ impl FieldNullability {
     }
 }
 
+/// A field of an input object type, as needed to coerce the input values of variables.
+#[derive(Debug)]
+pub struct InputFieldInfo {
+    pub name: String,
+    pub field_type: TypeNode,
+    pub default_value: Option<Value>,
+}
+
 #[derive(Debug, Default)]
 pub struct SchemaMetadata {
     pub possible_types: PossibleTypes,
@@ -73,6 +84,10 @@ pub struct SchemaMetadata {
     pub scalar_types: HashSet<String>,
     pub union_types: HashSet<String>,
     pub interface_types: HashSet<String>,
+    /// Fields of input object types, in their definition order.
+    pub input_object_fields: HashMap<String, Vec<InputFieldInfo>>,
+    /// Input object types marked with `@oneOf`, their values must set exactly one non-null field.
+    pub one_of_types: HashSet<String>,
     pub query_type_name: Option<String>,
     pub mutation_type_name: Option<String>,
     pub subscription_type_name: Option<String>,
@@ -95,10 +110,20 @@ impl SchemaMetadata {
         self.interface_types.contains(name)
     }
 
+    pub fn is_one_of_type(&self, name: &str) -> bool {
+        self.one_of_types.contains(name)
+    }
+
     pub fn get_type_fields(&self, type_name: &str) -> Option<&HashMap<String, FieldTypeInfo>> {
         self.type_fields.get(type_name)
     }
 
+    pub fn get_input_object_fields(&self, type_name: &str) -> Option<&[InputFieldInfo]> {
+        self.input_object_fields
+            .get(type_name)
+            .map(|fields| fields.as_slice())
+    }
+
     /// Gets the list of types that implement an interface or are members of a union.
     /// Returns None if the type is not an interface or has no implementors.
     pub fn get_possible_types(&self, interface_name: &str) -> Option<&HashSet<String>> {
@@ -170,6 +195,8 @@ impl SchemaWithMetadata for ConsumerSchema {
         let mut object_types: HashSet<String> = HashSet::default();
         let mut union_types: HashSet<String> = HashSet::default();
         let mut interface_types: HashSet<String> = HashSet::default();
+        let mut input_object_fields: HashMap<String, Vec<InputFieldInfo>> = HashMap::default();
+        let mut one_of_types: HashSet<String> = HashSet::default();
 
         for definition in &self.document.definitions {
             match definition {
@@ -238,6 +265,29 @@ impl SchemaWithMetadata for ConsumerSchema {
                 Definition::TypeDefinition(TypeDefinition::Scalar(scalar_type)) => {
                     scalar_types.insert(scalar_type.name.to_string());
                 }
+                Definition::TypeDefinition(TypeDefinition::InputObject(input_object_type)) => {
+                    let fields = input_object_type
+                        .fields
+                        .iter()
+                        .map(|field| InputFieldInfo {
+                            name: field.name.to_string(),
+                            field_type: TypeNode::from(&field.value_type),
+                            default_value: field
+                                .default_value
+                                .as_ref()
+                                .map(|default_value| (&AstValue::from(default_value)).into()),
+                        })
+                        .collect();
+                    input_object_fields.insert(input_object_type.name.to_string(), fields);
+
+                    if input_object_type
+                        .directives
+                        .iter()
+                        .any(|directive| directive.name == "oneOf")
+                    {
+                        one_of_types.insert(input_object_type.name.to_string());
+                    }
+                }
                 _ => {}
             }
         }
@@ -272,6 +322,8 @@ impl SchemaWithMetadata for ConsumerSchema {
             scalar_types,
             union_types,
             interface_types,
+            input_object_fields,
+            one_of_types,
             query_type_name,
             mutation_type_name,
             subscription_type_name,
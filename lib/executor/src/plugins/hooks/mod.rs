@@ -1,3 +1,4 @@
+pub mod on_config_reload;
 pub mod on_execute;
 pub mod on_graphql_analysis;
 pub mod on_graphql_error;
@@ -5,10 +6,12 @@ pub mod on_graphql_params;
 pub mod on_graphql_parse;
 pub mod on_graphql_validation;
 pub mod on_http_request;
+pub mod on_http_response;
 pub mod on_plugin_init;
 pub mod on_query_plan;
 pub mod on_subgraph_execute;
 pub mod on_subgraph_http_request;
+pub mod on_subscription;
 pub mod on_supergraph_load;
 
 mod sealed {
@@ -27,6 +30,8 @@ pub struct OnExecute;
 pub struct OnSubgraphExecute;
 pub struct OnSubgraphHttp;
 pub struct OnGraphqlError;
+pub struct OnSubscription;
+pub struct OnHttpResponse;
 
 impl sealed::Sealed for OnGraphqlAnalysis {}
 impl sealed::Sealed for OnHttpRequest {}
@@ -38,6 +43,8 @@ impl sealed::Sealed for OnExecute {}
 impl sealed::Sealed for OnSubgraphExecute {}
 impl sealed::Sealed for OnSubgraphHttp {}
 impl sealed::Sealed for OnGraphqlError {}
+impl sealed::Sealed for OnSubscription {}
+impl sealed::Sealed for OnHttpResponse {}
 
 impl HookMarker for OnGraphqlAnalysis {}
 impl HookMarker for OnHttpRequest {}
@@ -49,3 +56,5 @@ impl HookMarker for OnExecute {}
 impl HookMarker for OnSubgraphExecute {}
 impl HookMarker for OnSubgraphHttp {}
 impl HookMarker for OnGraphqlError {}
+impl HookMarker for OnSubscription {}
+impl HookMarker for OnHttpResponse {}
@@ -0,0 +1,8 @@
+use hive_router_config::HiveRouterConfig;
+
+pub struct OnConfigReloadHookPayload<'a> {
+    /// The config applied by the reload.
+    pub router_config: &'a HiveRouterConfig,
+    /// The dotted paths of the fields changed by the reload, as in `cors.max_age`.
+    pub changed_fields: &'a [String],
+}
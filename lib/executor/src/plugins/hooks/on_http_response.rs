@@ -0,0 +1,89 @@
+use http::StatusCode;
+use ntex::http::{
+    body::{Body, ResponseBody},
+    HeaderMap,
+};
+use ntex::util::Bytes as NtexBytes;
+use ntex::web;
+
+use crate::{
+    plugin_context::PluginContext, plugin_trait::RouterPluginBoxed,
+    request_context::RequestContextPluginApi, request_context::SharedRequestContext,
+};
+
+type RequestContextApi = RequestContextPluginApi<super::OnHttpResponse>;
+
+pub type OnHttpResponseHookResult<'req> = OnHttpResponseBodyHookPayload<'req>;
+
+/// The final GraphQL response, once serialized, and after the response headers rules,
+/// CORS and the coprocessor ran.
+/// Streamed responses (subscriptions, incremental delivery) have no final body and skip the hook.
+pub struct OnHttpResponseBodyHookPayload<'req> {
+    /// The HTTP status code of the response.
+    /// The plugin can modify it before proceeding.
+    pub status_code: StatusCode,
+    /// The headers of the response.
+    /// The plugin can modify them before proceeding, e.g. to add a checksum of the body.
+    ///
+    /// Example:
+    /// ```
+    /// async fn on_http_response<'req>(
+    ///     &'req self,
+    ///     mut payload: OnHttpResponseBodyHookPayload<'req>,
+    /// ) -> OnHttpResponseHookResult<'req> {
+    ///     let signature = self.sign(&payload.body);
+    ///     payload.headers.insert(
+    ///         HeaderName::from_static("x-signature"),
+    ///         HeaderValue::from_str(&signature).unwrap(),
+    ///     );
+    ///     payload.proceed()
+    /// }
+    /// ```
+    pub headers: HeaderMap,
+    /// The serialized body of the response, as sent to the client.
+    /// The plugin can replace it before proceeding.
+    pub body: NtexBytes,
+    /// The context object that can be used to share data across different plugin hooks for the same request.
+    /// It is unique per request and is dropped after the response is sent.
+    ///
+    /// [Learn more about the context data sharing in the docs](https://the-guild.dev/graphql/hive/docs/router/extensibility/plugin_system#context-data-sharing)
+    pub context: &'req PluginContext,
+    pub request_context: RequestContextApi,
+}
+
+impl<'req> OnHttpResponseBodyHookPayload<'req> {
+    /// Returning this will proceed the hook with `payload.status_code`, `payload.headers` and `payload.body`
+    pub fn proceed(self) -> OnHttpResponseHookResult<'req> {
+        self
+    }
+}
+
+pub async fn handle_http_response_with_plugins(
+    plugins: &[RouterPluginBoxed],
+    context: &PluginContext,
+    request_context: &SharedRequestContext,
+    mut response: web::HttpResponse,
+) -> web::HttpResponse {
+    let body = match response.body() {
+        ResponseBody::Body(Body::Bytes(bytes)) => bytes.clone(),
+        // streamed responses have no final body
+        _ => return response,
+    };
+
+    let mut payload = OnHttpResponseBodyHookPayload {
+        status_code: response.status(),
+        headers: std::mem::replace(response.headers_mut(), HeaderMap::new()),
+        body,
+        context,
+        request_context: request_context.for_plugin::<super::OnHttpResponse>(),
+    };
+
+    for plugin in plugins {
+        payload = plugin.on_http_response(payload).await;
+    }
+
+    let mut response = response.set_body(Body::Bytes(payload.body));
+    *response.status_mut() = payload.status_code;
+    *response.headers_mut() = payload.headers;
+    response
+}
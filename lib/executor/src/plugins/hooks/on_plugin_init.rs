@@ -2,14 +2,23 @@ use std::error::Error;
 
 use hive_router_internal::{
     background_tasks::{BackgroundTask, BackgroundTasksManager},
+    telemetry::metrics::plugin_metrics::PluginMetrics,
     BoxError,
 };
+use http::StatusCode;
 
-use crate::plugin_trait::RouterPlugin;
+use crate::{
+    plugin_routes::PluginRoute, plugin_storage::PluginStorage, plugin_trait::RouterPlugin,
+    response::error_status_codes::ErrorStatusCodes,
+};
 
 pub struct OnPluginInitPayload<'a, TRouterPlugin: RouterPlugin> {
     config: &'a serde_json::Value,
     bg_tasks_manager: &'a mut BackgroundTasksManager,
+    error_status_codes: &'a mut ErrorStatusCodes,
+    routes: &'a mut Vec<PluginRoute>,
+    metrics: &'a PluginMetrics,
+    storage: &'a PluginStorage,
     phantom: std::marker::PhantomData<TRouterPlugin>,
 }
 
@@ -22,10 +31,18 @@ where
     pub fn new(
         config: &'a serde_json::Value,
         bg_tasks_manager: &'a mut BackgroundTasksManager,
+        error_status_codes: &'a mut ErrorStatusCodes,
+        routes: &'a mut Vec<PluginRoute>,
+        metrics: &'a PluginMetrics,
+        storage: &'a PluginStorage,
     ) -> Self {
         Self {
             config,
             bg_tasks_manager,
+            error_status_codes,
+            routes,
+            metrics,
+            storage,
             phantom: std::marker::PhantomData,
         }
     }
@@ -90,6 +107,85 @@ where
     {
         self.bg_tasks_manager.register_task(task)
     }
+    /// Register the HTTP status code of the responses having an error with the given code (`extensions.code`),
+    /// e.g. to respond with `429` when the plugin rejects a request with `RATE_LIMITED`.
+    /// It applies to all the errors of the router, including the ones of subgraphs and other plugins.
+    ///
+    /// The codes of the `errors.status_codes` configuration take precedence,
+    /// and the first plugin registering a code wins.
+    ///
+    /// Example:
+    /// ```
+    /// fn on_plugin_init(mut payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+    ///     payload.register_error_status_code("RATE_LIMITED", StatusCode::TOO_MANY_REQUESTS);
+    ///     payload.initialize_plugin_with_defaults()
+    /// }
+    /// ```
+    pub fn register_error_status_code(&mut self, code: impl Into<String>, status_code: StatusCode) {
+        self.error_status_codes.register(code, status_code)
+    }
+    /// Register an HTTP route served by the router, next to the GraphQL endpoint,
+    /// e.g. a webhook or an administration endpoint of the plugin.
+    /// The state shared with the plugin can be moved into the handler, in an `Arc`.
+    ///
+    /// The router fails to start if the path of the route is already used by the router.
+    ///
+    /// Example:
+    /// ```
+    /// fn on_plugin_init(mut payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+    ///     let cache = Arc::new(Cache::default());
+    ///     let purged_cache = cache.clone();
+    ///     payload.register_route(PluginRoute::post("/admin/cache/purge", move |_request| {
+    ///         let cache = purged_cache.clone();
+    ///         async move {
+    ///             cache.clear();
+    ///             web::HttpResponse::NoContent().finish()
+    ///         }
+    ///     }));
+    ///     payload.initialize_plugin(Self { cache })
+    /// }
+    /// ```
+    pub fn register_route(&mut self, route: PluginRoute) {
+        self.routes.push(route)
+    }
+    /// Create the metrics of the plugin, under the meter of the router,
+    /// so they are exported with the metrics of the router.
+    /// The instruments do nothing when the metrics of the router are disabled.
+    ///
+    /// Example:
+    /// ```
+    /// fn on_plugin_init(payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+    ///     let rejected = payload
+    ///         .metrics()
+    ///         .counter("my_plugin.rejected_requests", "Requests rejected by my plugin");
+    ///     payload.initialize_plugin(Self { rejected })
+    /// }
+    ///
+    /// // later, in a hook
+    /// self.rejected.add(1, &[KeyValue::new("reason", "missing_token")]);
+    /// ```
+    pub fn metrics(&self) -> &PluginMetrics {
+        self.metrics
+    }
+    /// The key-value storage of the plugin, owned by the router and configured with `plugin_storage`,
+    /// e.g. to share rate limits or sessions between router instances when backed by Redis.
+    /// The keys are namespaced by the name of the plugin.
+    ///
+    /// Example:
+    /// ```
+    /// fn on_plugin_init(payload: OnPluginInitPayload<Self>) -> OnPluginInitResult<Self> {
+    ///     payload.initialize_plugin(Self { storage: payload.storage() })
+    /// }
+    ///
+    /// // later, in a hook
+    /// let attempts = self.storage.get(&user_id).await?;
+    /// self.storage
+    ///     .set(&user_id, next_attempts, Some(Duration::from_secs(60)))
+    ///     .await?;
+    /// ```
+    pub fn storage(&self) -> PluginStorage {
+        self.storage.for_plugin(TRouterPlugin::plugin_name())
+    }
     /// Returning this will disable the plugin and it won't be initialized.
     /// This can be used if the plugin determines during initialization that it shouldn't run
     /// (e.g. due to missing configuration or environment variables).
@@ -54,6 +54,8 @@ pub type OnQueryPlanStartHookResult<'exec> = StartHookResult<
 
 pub struct OnQueryPlanEndHookPayload {
     /// The generated query plan for the incoming GraphQL request.
+    /// It is shared with the query plan cache, use [`OnQueryPlanEndHookPayload::query_plan_mut`]
+    /// or [`OnQueryPlanEndHookPayload::replace_query_plan`] to change it for this request only.
     pub query_plan: Arc<QueryPlan>,
     /// The cache hint for the generated query plan.
     /// - If this is `CacheHint::Hit`, it means the query planning process didn't happen because the result was retrieved from the cache.
@@ -62,6 +64,71 @@ pub struct OnQueryPlanEndHookPayload {
     pub request_context: RequestContextApi,
 }
 
+impl OnQueryPlanEndHookPayload {
+    /// Mutable access to the query plan executed for this request,
+    /// to reorder or merge fetch nodes, inject synthetic fetches, or remove nodes.
+    /// The plan is copied on the first change, so the cached plan, used by other requests, stays untouched.
+    ///
+    /// Example:
+    /// ```
+    /// fn on_query_plan<'exec>(
+    ///     &'exec self,
+    ///     payload: OnQueryPlanStartHookPayload<'exec>,
+    /// ) -> OnQueryPlanStartHookResult<'exec> {
+    ///     payload.on_end(|mut payload| {
+    ///         if let Some(PlanNode::Parallel(parallel)) = &mut payload.query_plan_mut().node {
+    ///             parallel.nodes.retain(|node| {
+    ///                 node.as_fetch()
+    ///                     .is_none_or(|fetch| fetch.service_name != "quarantined")
+    ///             });
+    ///         }
+    ///         payload.proceed()
+    ///     })
+    /// }
+    /// ```
+    pub fn query_plan_mut(&mut self) -> &mut QueryPlan {
+        Arc::make_mut(&mut self.query_plan)
+    }
+
+    /// Replaces the query plan executed for this request. The cached plan stays untouched.
+    pub fn replace_query_plan(&mut self, query_plan: QueryPlan) {
+        self.query_plan = Arc::new(query_plan);
+    }
+}
+
 impl EndHookPayload<PlanExecutionOutput> for OnQueryPlanEndHookPayload {}
 
 pub type OnQueryPlanEndHookResult = EndHookResult<OnQueryPlanEndHookPayload, PlanExecutionOutput>;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use hive_router_query_planner::planner::{
+        plan_nodes::{PlanNode, QueryPlan, SequenceNode},
+        query_plan::QUERY_PLAN_KIND,
+    };
+
+    use super::OnQueryPlanEndHookPayload;
+    use crate::{
+        hooks::OnQueryPlan, plugin_trait::CacheHint, request_context::SharedRequestContext,
+    };
+
+    #[test]
+    fn mutating_the_plan_keeps_the_cached_one() {
+        let cached_plan = Arc::new(QueryPlan {
+            kind: QUERY_PLAN_KIND,
+            node: Some(PlanNode::Sequence(SequenceNode { nodes: vec![] })),
+        });
+
+        let mut payload = OnQueryPlanEndHookPayload {
+            query_plan: cached_plan.clone(),
+            cache_hint: CacheHint::Hit,
+            request_context: SharedRequestContext::default().for_plugin::<OnQueryPlan>(),
+        };
+        payload.query_plan_mut().node = None;
+
+        assert!(payload.query_plan.node.is_none());
+        assert!(cached_plan.node.is_some());
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hive_router_query_planner::ast::operation::OperationDefinition;
+
+use crate::execution::plan::PlanExecutionOutput;
+use crate::plugin_context::{PluginContext, PluginRequestState, RouterHttpRequest};
+use crate::plugin_trait::RouterPluginBoxed;
+use crate::request_context::{RequestContextPluginApi, SharedRequestContext};
+
+type RequestContextApi = RequestContextPluginApi<super::OnSubscription>;
+
+pub struct OnSubscriptionStartHookPayload<'exec> {
+    /// The incoming HTTP request to the router that started the subscription.
+    pub router_http_request: &'exec RouterHttpRequest<'exec>,
+    /// The context object that can be used to share data across different plugin hooks for the same request.
+    /// It lives as long as the subscription, so the values inserted here are available
+    /// in `on_subscription_event` and `on_subscription_end`.
+    ///
+    /// [Learn more about the context data sharing in the docs](https://the-guild.dev/graphql/hive/docs/router/extensibility/plugin_system#context-data-sharing)
+    pub context: &'exec PluginContext,
+    pub request_context: RequestContextApi,
+    /// The name of the subgraph the subscription is sent to.
+    pub subgraph_name: &'exec str,
+    /// The subscription operation from the GraphQL document.
+    pub operation_for_plan: &'exec OperationDefinition,
+    /// Coerced variable values of the subscription.
+    pub variable_values: &'exec Option<HashMap<String, sonic_rs::Value>>,
+}
+
+impl<'exec> OnSubscriptionStartHookPayload<'exec> {
+    /// Start the subscription
+    pub fn proceed(self) -> OnSubscriptionStartHookResult {
+        OnSubscriptionStartHookResult::Proceed
+    }
+}
+
+/// Returned by `on_subscription_start`, to authorize the subscription or reject it.
+///
+/// Example:
+/// ```
+/// async fn on_subscription_start<'exec>(
+///     &'exec self,
+///     payload: OnSubscriptionStartHookPayload<'exec>,
+/// ) -> OnSubscriptionStartHookResult {
+///     if payload.router_http_request.headers.get("authorization").is_none() {
+///         return OnSubscriptionStartHookResult::EndWithResponse(
+///             PlanExecutionOutput::from_graphql_error_to_response(
+///                 GraphQLError::from_message_and_code("Unauthorized", "UNAUTHORIZED"),
+///                 StatusCode::UNAUTHORIZED,
+///             ),
+///         );
+///     }
+///     payload.proceed()
+/// }
+/// ```
+pub enum OnSubscriptionStartHookResult {
+    Proceed,
+    /// Ends the request with this response, the subscription is not sent to the subgraph.
+    EndWithResponse(PlanExecutionOutput),
+}
+
+pub type OnSubscriptionEventHookResult<'a> = OnSubscriptionEventHookPayload<'a>;
+
+pub struct OnSubscriptionEventHookPayload<'a> {
+    /// The context object of the request that started the subscription.
+    pub context: &'a PluginContext,
+    pub request_context: RequestContextApi,
+    /// The name of the subgraph the subscription is sent to.
+    pub subgraph_name: &'a str,
+    /// The position of the event in the subscription, starting at 0.
+    pub event_index: usize,
+    /// The serialized GraphQL response of the event, as sent to the client.
+    /// The plugin can replace it before proceeding.
+    ///
+    /// Example:
+    /// ```
+    /// fn on_subscription_event<'a>(
+    ///     &'a self,
+    ///     mut payload: OnSubscriptionEventHookPayload<'a>,
+    /// ) -> OnSubscriptionEventHookResult<'a> {
+    ///     payload.body = redact(&payload.body);
+    ///     payload.proceed()
+    /// }
+    /// ```
+    pub body: Vec<u8>,
+}
+
+impl<'a> OnSubscriptionEventHookPayload<'a> {
+    /// Returning this will proceed the hook with `payload.body`
+    pub fn proceed(self) -> OnSubscriptionEventHookResult<'a> {
+        self
+    }
+}
+
+/// Why a subscription ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionEndReason {
+    /// The subgraph completed the subscription.
+    Completed,
+    /// The subscription was stopped after an error, sent to the client as the last event.
+    Error,
+    /// The client went away, or the router stopped the subscription (e.g. on shutdown or schema reload).
+    Cancelled,
+}
+
+pub struct OnSubscriptionEndHookPayload<'a> {
+    /// The context object of the request that started the subscription.
+    pub context: &'a PluginContext,
+    pub request_context: RequestContextApi,
+    /// The name of the subgraph the subscription was sent to.
+    pub subgraph_name: &'a str,
+    /// The time elapsed since the subscription started.
+    pub duration: Duration,
+    /// The number of events sent to the client, including the error ending the subscription.
+    pub event_count: usize,
+    pub reason: SubscriptionEndReason,
+}
+
+/// Runs the event and end hooks of a subscription, for as long as its stream lives.
+/// The end hooks run when it is dropped, so a subscription cancelled by the client is reported too.
+pub(crate) struct SubscriptionHooks {
+    plugins: Arc<Vec<RouterPluginBoxed>>,
+    context: Arc<PluginContext>,
+    request_context: SharedRequestContext,
+    subgraph_name: String,
+    started_at: Instant,
+    event_count: usize,
+    reason: SubscriptionEndReason,
+}
+
+impl SubscriptionHooks {
+    pub(crate) fn new(plugin_req_state: &PluginRequestState<'_>, subgraph_name: &str) -> Self {
+        Self {
+            plugins: plugin_req_state.plugins.clone(),
+            context: plugin_req_state.context.clone(),
+            request_context: plugin_req_state.request_context.clone(),
+            subgraph_name: subgraph_name.to_string(),
+            started_at: Instant::now(),
+            event_count: 0,
+            reason: SubscriptionEndReason::Cancelled,
+        }
+    }
+
+    pub(crate) fn on_event(&mut self, body: Vec<u8>) -> Vec<u8> {
+        let mut payload = OnSubscriptionEventHookPayload {
+            context: &self.context,
+            request_context: self.request_context.for_plugin::<super::OnSubscription>(),
+            subgraph_name: &self.subgraph_name,
+            event_index: self.event_count,
+            body,
+        };
+        for plugin in self.plugins.iter() {
+            payload = plugin.on_subscription_event(payload);
+        }
+        self.event_count += 1;
+        payload.body
+    }
+
+    /// Records the error ending the subscription, sent to the client as its last event.
+    pub(crate) fn on_error(&mut self) {
+        self.event_count += 1;
+        self.reason = SubscriptionEndReason::Error;
+    }
+
+    pub(crate) fn on_complete(&mut self) {
+        self.reason = SubscriptionEndReason::Completed;
+    }
+}
+
+impl Drop for SubscriptionHooks {
+    fn drop(&mut self) {
+        let payload = OnSubscriptionEndHookPayload {
+            context: &self.context,
+            request_context: self.request_context.for_plugin::<super::OnSubscription>(),
+            subgraph_name: &self.subgraph_name,
+            duration: self.started_at.elapsed(),
+            event_count: self.event_count,
+            reason: self.reason,
+        };
+        for plugin in self.plugins.iter() {
+            plugin.on_subscription_end(&payload);
+        }
+    }
+}
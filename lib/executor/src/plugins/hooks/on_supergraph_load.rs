@@ -9,6 +9,7 @@ use tokio_util::sync::CancellationToken;
 
 use crate::{
     introspection::schema::{SchemaMetadata, SchemaWithMetadata},
+    plugin_context::PluginContext,
     plugin_trait::{EndHookPayload, FromGraphQLErrorToResponse, StartHookPayload},
     response::graphql_error::GraphQLError,
 };
@@ -43,6 +44,10 @@ pub struct SupergraphData {
     pub planner: Planner,
     pub supergraph_schema: Arc<Document>,
     pub public_schema: PublicSchema,
+    /// The context of the plugins scoped to this supergraph, dropped with it.
+    /// Plugins can fill it in the `on_supergraph_load` hook with data computed from the schema,
+    /// and read it during requests with `payload.context.supergraph_context()`.
+    pub context: PluginContext,
 }
 
 /// A cheap, read-only snapshot of a [`Supergraph`] owner: the schema-derived data plus a clone
@@ -147,6 +152,7 @@ impl Supergraph {
             planner,
             supergraph_schema: Arc::new(document),
             public_schema,
+            context: PluginContext::default(),
         };
 
         Ok(Self {
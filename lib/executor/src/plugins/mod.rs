@@ -1,3 +1,5 @@
 pub mod hooks;
 pub mod plugin_context;
+pub mod plugin_routes;
+pub mod plugin_storage;
 pub mod plugin_trait;
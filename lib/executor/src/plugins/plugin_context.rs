@@ -1,17 +1,21 @@
 use std::{
     any::{Any, TypeId},
+    collections::BTreeMap,
     ops::{Deref, DerefMut},
-    sync::Arc,
+    sync::{Arc, Mutex, OnceLock},
 };
 
 use dashmap::{
     mapref::one::{Ref, RefMut},
     DashMap,
 };
+use hive_router_internal::expressions::vrl::core::Value as VrlValue;
+use hive_router_internal::telemetry::otel::opentelemetry::{Key, KeyValue, Value};
 use http::Uri;
 use ntex::router::Path;
 use ntex::{http::HeaderMap, web::HttpRequest};
 
+use crate::hooks::on_supergraph_load::SupergraphSnapshot;
 use crate::plugin_trait::RouterPluginBoxed;
 use crate::request_context::SharedRequestContext;
 
@@ -39,9 +43,18 @@ impl<'a> From<&'a HttpRequest> for RouterHttpRequest<'a> {
     }
 }
 
+/// The context shared by the plugins.
+///
+/// The `context` of the hooks is scoped to the request, and dropped after the response is sent.
+/// The supergraph has its own context too, living as long as the supergraph,
+/// e.g. to keep data computed from the schema in the `on_supergraph_load` hook,
+/// and reached from the context of a request with [`PluginContext::supergraph_context`].
 #[derive(Default)]
 pub struct PluginContext {
     inner: DashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    span_attributes: Mutex<Vec<KeyValue>>,
+    exposed: Mutex<BTreeMap<String, VrlValue>>,
+    supergraph: OnceLock<SupergraphSnapshot>,
 }
 
 pub struct PluginContextRefEntry<'a, T> {
@@ -197,6 +210,95 @@ impl PluginContext {
                 phantom: std::marker::PhantomData,
             })
     }
+    /// Remove the entry of type T from the context, returning it if it existed.
+    ///
+    /// Example:
+    /// ```
+    /// if let Some(context_data) = payload.context.remove::<ContextData>() {
+    ///     println!("{}", context_data.greetings);
+    /// }
+    /// ```
+    pub fn remove<T: Any + Send + Sync>(&self) -> Option<Box<T>> {
+        let type_id = TypeId::of::<T>();
+        self.inner
+            .remove(&type_id)
+            .and_then(|(_, boxed_any)| boxed_any.downcast::<T>().ok())
+    }
+    /// Expose a value to the VRL expressions of the router evaluated for the request,
+    /// e.g. in the header rules, as `.request.context.<key>`.
+    /// Exposing the same key twice keeps the last value.
+    ///
+    /// Example:
+    /// ```
+    /// payload.context.expose("tenant", "acme");
+    /// ```
+    ///
+    /// ```yaml
+    /// headers:
+    ///   all:
+    ///     request:
+    ///       - insert:
+    ///           name: x-tenant
+    ///           expression: .request.context.tenant
+    /// ```
+    pub fn expose(&self, key: impl Into<String>, value: impl Into<VrlValue>) {
+        self.exposed_values_lock().insert(key.into(), value.into());
+    }
+    /// Remove a value exposed to the VRL expressions, returning it if it existed.
+    pub fn remove_exposed(&self, key: &str) -> Option<VrlValue> {
+        self.exposed_values_lock().remove(key)
+    }
+    /// The values exposed to the VRL expressions, by key.
+    pub fn exposed_values(&self) -> BTreeMap<String, VrlValue> {
+        self.exposed_values_lock().clone()
+    }
+    fn exposed_values_lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, VrlValue>> {
+        self.exposed
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    /// The context of the supergraph selected for the request, living as long as the supergraph.
+    /// It is `None` in the `on_http_request` hook, before the supergraph is selected.
+    ///
+    /// Example:
+    /// ```
+    /// // when the supergraph is loaded
+    /// payload.new_supergraph.context.insert(FieldIndex::new(&payload.new_supergraph.supergraph_schema));
+    ///
+    /// // later, in a hook of a request
+    /// let index = payload.context.supergraph_context().and_then(|context| context.get_ref::<FieldIndex>());
+    /// ```
+    pub fn supergraph_context(&self) -> Option<&PluginContext> {
+        self.supergraph.get().map(|supergraph| &supergraph.context)
+    }
+    /// Sets the supergraph selected for the request, for the router to link the contexts.
+    /// The first selected supergraph is kept.
+    pub fn set_supergraph(&self, supergraph: SupergraphSnapshot) {
+        let _ = self.supergraph.set(supergraph);
+    }
+    /// Set an attribute on the span of the HTTP request, so the behavior of the plugin
+    /// shows up in the traces of the router.
+    /// The attributes are recorded once the response is ready, setting the same key twice keeps the last value.
+    ///
+    /// Example:
+    /// ```
+    /// payload.context.set_span_attribute("my_plugin.cache_hit", true);
+    /// ```
+    pub fn set_span_attribute(&self, key: impl Into<Key>, value: impl Into<Value>) {
+        let mut span_attributes = self
+            .span_attributes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        span_attributes.push(KeyValue::new(key, value));
+    }
+    /// Takes the span attributes set by the plugins, for the router to record them.
+    pub fn take_span_attributes(&self) -> Vec<KeyValue> {
+        let mut span_attributes = self
+            .span_attributes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::mem::take(&mut span_attributes)
+    }
 }
 
 pub struct PluginRequestState<'req> {
@@ -208,6 +310,52 @@ pub struct PluginRequestState<'req> {
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn takes_the_span_attributes() {
+        use super::PluginContext;
+
+        let ctx = PluginContext::default();
+        ctx.set_span_attribute("plugin.cache_hit", true);
+        ctx.set_span_attribute("plugin.tenant", "acme");
+
+        let attributes = ctx.take_span_attributes();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes[1].key.as_str(), "plugin.tenant");
+        assert_eq!(attributes[1].value.as_str(), "acme");
+        assert!(ctx.take_span_attributes().is_empty());
+    }
+    #[test]
+    fn removes_entries() {
+        use super::PluginContext;
+
+        struct TestCtx {
+            pub value: u32,
+        }
+
+        let ctx = PluginContext::default();
+        ctx.insert(TestCtx { value: 42 });
+
+        assert_eq!(
+            ctx.remove::<TestCtx>().map(|removed| removed.value),
+            Some(42)
+        );
+        assert!(!ctx.contains::<TestCtx>());
+        assert!(ctx.remove::<TestCtx>().is_none());
+    }
+    #[test]
+    fn exposes_values_to_expressions() {
+        use super::PluginContext;
+
+        let ctx = PluginContext::default();
+        ctx.expose("tenant", "acme");
+        ctx.expose("plan", "free");
+        ctx.expose("plan", "pro");
+        ctx.remove_exposed("tenant");
+
+        let exposed = ctx.exposed_values();
+        assert_eq!(exposed.len(), 1);
+        assert_eq!(exposed["plan"], "pro".into());
+    }
     #[test]
     fn inserts_and_gets_immut_ref() {
         use super::PluginContext;
@@ -0,0 +1,89 @@
+use std::{future::Future, sync::Arc};
+
+use futures::future::LocalBoxFuture;
+use ntex::{util::Bytes as NtexBytes, web};
+
+use crate::plugin_context::PluginContext;
+
+type PluginRouteHandler =
+    dyn Fn(PluginRouteRequest) -> LocalBoxFuture<'static, web::HttpResponse> + Send + Sync;
+
+/// The request received by the handler of a plugin route.
+pub struct PluginRouteRequest {
+    /// The incoming HTTP request, with its headers, query string and path parameters,
+    /// e.g. `request.match_info().get("id")` for a route registered as `/admin/cache/{id}`.
+    pub request: web::HttpRequest,
+    /// The body of the request.
+    pub body: NtexBytes,
+    /// The context object of the request, shared with the `on_http_request` hooks of the plugins.
+    ///
+    /// [Learn more about the context data sharing in the docs](https://the-guild.dev/graphql/hive/docs/router/extensibility/plugin_system#context-data-sharing)
+    pub context: Arc<PluginContext>,
+}
+
+/// An HTTP route served by the router next to the GraphQL endpoint,
+/// registered by a plugin during its initialization.
+///
+/// The `on_http_request` hooks of the plugins and the coprocessor run for these routes too,
+/// so they are authenticated the same way as the GraphQL endpoint.
+#[derive(Clone)]
+pub struct PluginRoute {
+    method: http::Method,
+    path: String,
+    handler: Arc<PluginRouteHandler>,
+}
+
+impl PluginRoute {
+    /// A route answering the requests with the given method and path.
+    /// The path can have parameters, e.g. `/admin/cache/{id}`.
+    pub fn new<F, Fut>(method: http::Method, path: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(PluginRouteRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = web::HttpResponse> + 'static,
+    {
+        Self {
+            method,
+            path: path.into(),
+            handler: Arc::new(move |request| Box::pin(handler(request))),
+        }
+    }
+
+    pub fn get<F, Fut>(path: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(PluginRouteRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = web::HttpResponse> + 'static,
+    {
+        Self::new(http::Method::GET, path, handler)
+    }
+
+    pub fn post<F, Fut>(path: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(PluginRouteRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = web::HttpResponse> + 'static,
+    {
+        Self::new(http::Method::POST, path, handler)
+    }
+
+    pub fn method(&self) -> &http::Method {
+        &self.method
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub async fn handle(&self, request: web::HttpRequest, body: NtexBytes) -> web::HttpResponse {
+        let context = request
+            .extensions()
+            .get::<Arc<PluginContext>>()
+            .cloned()
+            .unwrap_or_default();
+
+        (self.handler)(PluginRouteRequest {
+            request,
+            body,
+            context,
+        })
+        .await
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use hive_router_internal::telemetry::metrics::{
+    catalog::values::{PluginStorageOperation, PluginStorageResult},
+    plugin_storage_metrics::PluginStorageMetrics,
+};
+use moka::{sync::Cache, Expiry};
+
+#[derive(Debug, thiserror::Error)]
+#[error("plugin storage error: {0}")]
+pub struct PluginStorageError(pub Box<dyn std::error::Error + Send + Sync>);
+
+/// The backend of the plugin storage, configured with `plugin_storage.backend`.
+/// The keys are already namespaced by the name of the plugin.
+#[async_trait]
+pub trait PluginStorageBackend: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, PluginStorageError>;
+    async fn set(
+        &self,
+        key: &str,
+        value: Bytes,
+        ttl: Option<Duration>,
+    ) -> Result<(), PluginStorageError>;
+    async fn delete(&self, key: &str) -> Result<(), PluginStorageError>;
+}
+
+/// A key-value storage owned by the router and offered to the plugins,
+/// backed by the memory of the router instance or by Redis, as configured with `plugin_storage`.
+///
+/// Every plugin gets its own namespace, and the operations are recorded
+/// in the `hive.router.plugin_storage.*` metrics, labeled with the name of the plugin.
+#[derive(Clone)]
+pub struct PluginStorage {
+    backend: Arc<dyn PluginStorageBackend>,
+    metrics: Arc<PluginStorageMetrics>,
+    plugin_name: &'static str,
+}
+
+impl Default for PluginStorage {
+    fn default() -> Self {
+        Self::new(
+            Arc::new(MemoryPluginStorageBackend::new(10_000)),
+            PluginStorageMetrics::default(),
+        )
+    }
+}
+
+impl PluginStorage {
+    pub fn new(backend: Arc<dyn PluginStorageBackend>, metrics: PluginStorageMetrics) -> Self {
+        Self {
+            backend,
+            metrics: Arc::new(metrics),
+            plugin_name: "",
+        }
+    }
+
+    /// The storage of the given plugin, sharing the backend and the metrics.
+    pub fn for_plugin(&self, plugin_name: &'static str) -> Self {
+        Self {
+            backend: self.backend.clone(),
+            metrics: self.metrics.clone(),
+            plugin_name,
+        }
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}:{}", self.plugin_name, key)
+    }
+
+    /// The value of the key, or `None` if the key is unknown or expired.
+    pub async fn get(&self, key: &str) -> Result<Option<Bytes>, PluginStorageError> {
+        let started_at = Instant::now();
+        let result = self.backend.get(&self.key(key)).await;
+        let outcome = match &result {
+            Ok(Some(_)) => PluginStorageResult::Hit,
+            Ok(None) => PluginStorageResult::Miss,
+            Err(_) => PluginStorageResult::Error,
+        };
+        self.record(PluginStorageOperation::Get, outcome, started_at);
+        result
+    }
+
+    /// Stores the value of the key, expiring after the given TTL, or never if `None`.
+    pub async fn set(
+        &self,
+        key: &str,
+        value: impl Into<Bytes>,
+        ttl: Option<Duration>,
+    ) -> Result<(), PluginStorageError> {
+        let started_at = Instant::now();
+        let result = self.backend.set(&self.key(key), value.into(), ttl).await;
+        self.record(PluginStorageOperation::Set, outcome(&result), started_at);
+        result
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<(), PluginStorageError> {
+        let started_at = Instant::now();
+        let result = self.backend.delete(&self.key(key)).await;
+        self.record(PluginStorageOperation::Delete, outcome(&result), started_at);
+        result
+    }
+
+    fn record(
+        &self,
+        operation: PluginStorageOperation,
+        result: PluginStorageResult,
+        started_at: Instant,
+    ) {
+        self.metrics.record_operation(
+            self.plugin_name,
+            operation,
+            result,
+            started_at.elapsed().as_secs_f64(),
+        );
+    }
+}
+
+fn outcome(result: &Result<(), PluginStorageError>) -> PluginStorageResult {
+    match result {
+        Ok(()) => PluginStorageResult::Ok,
+        Err(_) => PluginStorageResult::Error,
+    }
+}
+
+/// Keeps the values in the memory of the router instance, evicting the least recently used ones.
+pub struct MemoryPluginStorageBackend {
+    cache: Cache<String, StoredValue>,
+}
+
+#[derive(Clone)]
+struct StoredValue {
+    value: Bytes,
+    ttl: Option<Duration>,
+}
+
+struct StoredValueExpiry;
+
+impl Expiry<String, StoredValue> for StoredValueExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        value: &StoredValue,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        value.ttl
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        value: &StoredValue,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        value.ttl
+    }
+}
+
+impl MemoryPluginStorageBackend {
+    pub fn new(max_entries: u64) -> Self {
+        Self {
+            cache: Cache::builder()
+                .max_capacity(max_entries)
+                .expire_after(StoredValueExpiry)
+                .build(),
+        }
+    }
+}
+
+#[async_trait]
+impl PluginStorageBackend for MemoryPluginStorageBackend {
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, PluginStorageError> {
+        Ok(self.cache.get(key).map(|stored| stored.value))
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        value: Bytes,
+        ttl: Option<Duration>,
+    ) -> Result<(), PluginStorageError> {
+        self.cache
+            .insert(key.to_string(), StoredValue { value, ttl });
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), PluginStorageError> {
+        self.cache.invalidate(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use hive_router_internal::telemetry::metrics::plugin_storage_metrics::PluginStorageMetrics;
+
+    use super::{MemoryPluginStorageBackend, PluginStorage};
+
+    #[tokio::test]
+    async fn namespaces_the_keys_of_plugins() {
+        let storage = PluginStorage::new(
+            Arc::new(MemoryPluginStorageBackend::new(100)),
+            PluginStorageMetrics::default(),
+        );
+        let rate_limit = storage.for_plugin("rate_limit");
+        let auth = storage.for_plugin("auth");
+
+        rate_limit.set("user:1", "5", None).await.unwrap();
+        assert_eq!(
+            rate_limit.get("user:1").await.unwrap().as_deref(),
+            Some(&b"5"[..])
+        );
+        assert_eq!(auth.get("user:1").await.unwrap(), None);
+
+        rate_limit.delete("user:1").await.unwrap();
+        assert_eq!(rate_limit.get("user:1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn expires_values_after_their_ttl() {
+        let storage = PluginStorage::default().for_plugin("session");
+
+        storage
+            .set("short", "a", Some(Duration::from_millis(10)))
+            .await
+            .unwrap();
+        storage.set("forever", "b", None).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(storage.get("short").await.unwrap(), None);
+        assert!(storage.get("forever").await.unwrap().is_some());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     hooks::{
+        on_config_reload::OnConfigReloadHookPayload,
         on_execute::{OnExecuteStartHookPayload, OnExecuteStartHookResult},
         on_graphql_analysis::{OnGraphqlAnalysisHookPayload, OnGraphqlAnalysisHookResult},
         on_graphql_error::{OnGraphQLErrorHookPayload, OnGraphQLErrorHookResult},
@@ -9,6 +10,7 @@ use crate::{
             OnGraphQLValidationStartHookPayload, OnGraphQLValidationStartHookResult,
         },
         on_http_request::{OnHttpRequestHookPayload, OnHttpRequestHookResult},
+        on_http_response::{OnHttpResponseBodyHookPayload, OnHttpResponseHookResult},
         on_plugin_init::{OnPluginInitPayload, OnPluginInitResult},
         on_query_plan::{OnQueryPlanStartHookPayload, OnQueryPlanStartHookResult},
         on_subgraph_execute::{
@@ -17,6 +19,11 @@ use crate::{
         on_subgraph_http_request::{
             OnSubgraphHttpRequestHookPayload, OnSubgraphHttpRequestHookResult,
         },
+        on_subscription::{
+            OnSubscriptionEndHookPayload, OnSubscriptionEventHookPayload,
+            OnSubscriptionEventHookResult, OnSubscriptionStartHookPayload,
+            OnSubscriptionStartHookResult,
+        },
         on_supergraph_load::{OnSupergraphLoadStartHookPayload, OnSupergraphLoadStartHookResult},
     },
     response::graphql_error::GraphQLError,
@@ -446,6 +453,22 @@ pub trait RouterPlugin: Send + Sync + 'static {
         start_payload.proceed()
     }
     #[inline]
+    async fn on_subscription_start<'exec>(
+        &'exec self,
+        payload: OnSubscriptionStartHookPayload<'exec>,
+    ) -> OnSubscriptionStartHookResult {
+        payload.proceed()
+    }
+    #[inline]
+    fn on_subscription_event<'a>(
+        &'a self,
+        payload: OnSubscriptionEventHookPayload<'a>,
+    ) -> OnSubscriptionEventHookResult<'a> {
+        payload.proceed()
+    }
+    #[inline]
+    fn on_subscription_end<'a>(&'a self, _payload: &OnSubscriptionEndHookPayload<'a>) {}
+    #[inline]
     fn on_supergraph_reload<'exec>(
         &'exec self,
         start_payload: OnSupergraphLoadStartHookPayload,
@@ -460,11 +483,22 @@ pub trait RouterPlugin: Send + Sync + 'static {
         payload.proceed()
     }
     #[inline]
+    async fn on_http_response<'req>(
+        &'req self,
+        payload: OnHttpResponseBodyHookPayload<'req>,
+    ) -> OnHttpResponseHookResult<'req> {
+        payload.proceed()
+    }
+    #[inline]
+    fn on_config_reload<'a>(&'a self, _payload: &OnConfigReloadHookPayload<'a>) {}
+    #[inline]
     async fn on_shutdown<'exec>(&'exec self) {}
 }
 
 #[async_trait::async_trait]
 pub trait DynRouterPlugin: Send + Sync + 'static {
+    /// The name of the plugin, as returned by [`RouterPlugin::plugin_name`].
+    fn name(&self) -> &'static str;
     fn on_http_request<'req>(
         &'req self,
         start_payload: OnHttpRequestHookPayload<'req>,
@@ -501,6 +535,15 @@ pub trait DynRouterPlugin: Send + Sync + 'static {
         &'exec self,
         start_payload: OnSubgraphHttpRequestHookPayload<'exec>,
     ) -> OnSubgraphHttpRequestHookResult<'exec>;
+    async fn on_subscription_start<'exec>(
+        &'exec self,
+        payload: OnSubscriptionStartHookPayload<'exec>,
+    ) -> OnSubscriptionStartHookResult;
+    fn on_subscription_event<'a>(
+        &'a self,
+        payload: OnSubscriptionEventHookPayload<'a>,
+    ) -> OnSubscriptionEventHookResult<'a>;
+    fn on_subscription_end<'a>(&'a self, payload: &OnSubscriptionEndHookPayload<'a>);
     fn on_supergraph_reload<'exec>(
         &'exec self,
         start_payload: OnSupergraphLoadStartHookPayload,
@@ -509,6 +552,11 @@ pub trait DynRouterPlugin: Send + Sync + 'static {
         &'req self,
         payload: OnGraphQLErrorHookPayload<'req>,
     ) -> OnGraphQLErrorHookResult<'req>;
+    async fn on_http_response<'req>(
+        &'req self,
+        payload: OnHttpResponseBodyHookPayload<'req>,
+    ) -> OnHttpResponseHookResult<'req>;
+    fn on_config_reload<'a>(&'a self, payload: &OnConfigReloadHookPayload<'a>);
     async fn on_shutdown<'exec>(&'exec self);
 }
 
@@ -517,6 +565,10 @@ impl<P> DynRouterPlugin for P
 where
     P: RouterPlugin,
 {
+    #[inline]
+    fn name(&self) -> &'static str {
+        P::plugin_name()
+    }
     #[inline]
     fn on_http_request<'req>(
         &'req self,
@@ -580,6 +632,24 @@ where
         RouterPlugin::on_subgraph_http_request(self, start_payload).await
     }
     #[inline]
+    async fn on_subscription_start<'exec>(
+        &'exec self,
+        payload: OnSubscriptionStartHookPayload<'exec>,
+    ) -> OnSubscriptionStartHookResult {
+        RouterPlugin::on_subscription_start(self, payload).await
+    }
+    #[inline]
+    fn on_subscription_event<'a>(
+        &'a self,
+        payload: OnSubscriptionEventHookPayload<'a>,
+    ) -> OnSubscriptionEventHookResult<'a> {
+        RouterPlugin::on_subscription_event(self, payload)
+    }
+    #[inline]
+    fn on_subscription_end<'a>(&'a self, payload: &OnSubscriptionEndHookPayload<'a>) {
+        RouterPlugin::on_subscription_end(self, payload)
+    }
+    #[inline]
     fn on_supergraph_reload<'exec>(
         &'exec self,
         start_payload: OnSupergraphLoadStartHookPayload,
@@ -594,6 +664,17 @@ where
         RouterPlugin::on_graphql_error(self, payload)
     }
     #[inline]
+    async fn on_http_response<'req>(
+        &'req self,
+        payload: OnHttpResponseBodyHookPayload<'req>,
+    ) -> OnHttpResponseHookResult<'req> {
+        RouterPlugin::on_http_response(self, payload).await
+    }
+    #[inline]
+    fn on_config_reload<'a>(&'a self, payload: &OnConfigReloadHookPayload<'a>) {
+        RouterPlugin::on_config_reload(self, payload)
+    }
+    #[inline]
     async fn on_shutdown<'exec>(&'exec self) {
         RouterPlugin::on_shutdown(self).await;
     }
@@ -4,7 +4,7 @@ use crate::projection::plan::{
     FieldProjectionCondition, FieldProjectionConditionError, FieldProjectionPlan,
     ProjectionValueSource,
 };
-use crate::response::graphql_error::GraphQLError;
+use crate::response::graphql_error::{GraphQLError, GraphQLErrorPath, GraphQLErrorPathSegment};
 use crate::response::value::Value;
 use bytes::BufMut;
 use sonic_rs::JsonValueTrait;
@@ -166,6 +166,227 @@ pub fn project_by_operation(
     Ok(buffer)
 }
 
+/// Projects the items of a root list field, delivered in a subsequent payload of `@stream`.
+///
+/// Writes a JSON array of the projected items, or `null` when a `null` item
+/// propagates to the list itself.
+pub fn project_stream_items(
+    items: &Value,
+    errors: &mut Vec<GraphQLError>,
+    plan: &FieldProjectionPlan,
+    operation_type_name: &str,
+    variable_values: &Option<HashMap<String, sonic_rs::Value>>,
+    schema_metadata: &SchemaMetadata,
+) -> Result<Vec<u8>, ProjectionError> {
+    let mut buffer = Vec::new();
+    project_selection_set(
+        items,
+        errors,
+        plan,
+        variable_values,
+        &mut buffer,
+        TypeName::resolved(operation_type_name),
+        schema_metadata,
+        &plan.nullability,
+    )?;
+    Ok(buffer)
+}
+
+/// The projected fields of a deferred fragment, with the path of the object they belong to.
+pub struct DeferredPatch {
+    pub path: GraphQLErrorPath,
+    pub data: Vec<u8>,
+}
+
+/// Projects a deferred fragment, delivered in a subsequent payload of `@defer`.
+///
+/// The fields of `plans` leading to the fragment are followed along `path`, through every item of a list,
+/// and the fields of the fragment are projected on every object found at its end.
+/// A `null`, or skipped, object on the way produces no patch.
+pub fn project_deferred_fragment(
+    data: &Value,
+    errors: &mut Vec<GraphQLError>,
+    plans: &[FieldProjectionPlan],
+    path: &[String],
+    operation_type_name: &str,
+    variable_values: &Option<HashMap<String, sonic_rs::Value>>,
+    schema_metadata: &SchemaMetadata,
+) -> Result<Vec<DeferredPatch>, ProjectionError> {
+    let mut patches = Vec::new();
+    if let Some(data_map) = data.as_object() {
+        collect_deferred_patches(
+            data_map,
+            errors,
+            plans,
+            path,
+            &mut GraphQLErrorPath::with_capacity(path.len()),
+            variable_values,
+            TypeName::resolved(operation_type_name),
+            schema_metadata,
+            &mut patches,
+        )?;
+    }
+    Ok(patches)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn collect_deferred_patches<'a>(
+    obj: &'a [(&str, Value)],
+    errors: &mut Vec<GraphQLError>,
+    plans: &'a [FieldProjectionPlan],
+    path: &[String],
+    response_path: &mut GraphQLErrorPath,
+    variable_values: &Option<HashMap<String, sonic_rs::Value>>,
+    parent_type_name: TypeName<'a>,
+    schema_metadata: &'a SchemaMetadata,
+    patches: &mut Vec<DeferredPatch>,
+) -> Result<(), ProjectionError> {
+    let Some((response_key, rest)) = path.split_first() else {
+        let mut buffer = Vec::new();
+        let mut first = true;
+        let null_propagation_decision = project_selection_set_with_map(
+            obj,
+            errors,
+            plans,
+            variable_values,
+            parent_type_name,
+            &mut buffer,
+            &mut first,
+            schema_metadata,
+        )?;
+
+        if null_propagation_decision.should_propagate() {
+            buffer.clear();
+            buffer.put(NULL);
+        } else if !first {
+            buffer.put(CLOSE_BRACE);
+        } else {
+            buffer.put(EMPTY_OBJECT);
+        }
+
+        patches.push(DeferredPatch {
+            path: response_path.clone(),
+            data: buffer,
+        });
+        return Ok(());
+    };
+
+    let Some(field_val) = obj
+        .binary_search_by_key(&response_key.as_str(), |(k, _)| *k)
+        .ok()
+        .map(|idx| &obj[idx].1)
+    else {
+        return Ok(());
+    };
+
+    for plan in plans
+        .iter()
+        .filter(|plan| plan.response_key == *response_key)
+    {
+        if let Some(guard) = &plan.parent_type_guard {
+            if !guard.matches(parent_type_name.get()?) {
+                continue;
+            }
+        }
+
+        if let Some(conditions) = &plan.conditions {
+            let field_type_name_fn =
+                || resolve_type_name(plan, Some(field_val), &parent_type_name, schema_metadata);
+            let parent_type_name_fn = || parent_type_name.get();
+            match check(
+                conditions,
+                &parent_type_name_fn,
+                &field_type_name_fn,
+                Some(field_val),
+                variable_values,
+            ) {
+                Ok(_) => {}
+                Err(FieldProjectionConditionError::Fatal(err)) => return Err(err),
+                Err(_) => continue,
+            }
+        }
+
+        let ProjectionValueSource::ResponseData {
+            selections: Some(selections),
+        } = &plan.value
+        else {
+            continue;
+        };
+
+        response_path
+            .segments
+            .push(GraphQLErrorPathSegment::String(response_key.clone()));
+        collect_deferred_patches_of_value(
+            field_val,
+            errors,
+            plan,
+            selections,
+            rest,
+            response_path,
+            variable_values,
+            parent_type_name.clone(),
+            schema_metadata,
+            patches,
+        )?;
+        response_path.segments.pop();
+
+        // The first plan matching the parent type owns the field
+        break;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn collect_deferred_patches_of_value<'a>(
+    data: &'a Value,
+    errors: &mut Vec<GraphQLError>,
+    plan: &'a FieldProjectionPlan,
+    selections: &'a [FieldProjectionPlan],
+    path: &[String],
+    response_path: &mut GraphQLErrorPath,
+    variable_values: &Option<HashMap<String, sonic_rs::Value>>,
+    parent_type_name: TypeName<'a>,
+    schema_metadata: &'a SchemaMetadata,
+    patches: &mut Vec<DeferredPatch>,
+) -> Result<(), ProjectionError> {
+    match data {
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                response_path
+                    .segments
+                    .push(GraphQLErrorPathSegment::Index(index));
+                collect_deferred_patches_of_value(
+                    item,
+                    errors,
+                    plan,
+                    selections,
+                    path,
+                    response_path,
+                    variable_values,
+                    parent_type_name.clone(),
+                    schema_metadata,
+                    patches,
+                )?;
+                response_path.segments.pop();
+            }
+            Ok(())
+        }
+        Value::Object(obj) => collect_deferred_patches(
+            obj,
+            errors,
+            selections,
+            path,
+            response_path,
+            variable_values,
+            TypeName::deferred(plan, Some(data), parent_type_name, schema_metadata),
+            schema_metadata,
+            patches,
+        ),
+        _ => Ok(()),
+    }
+}
+
 pub fn serialize_value_to_buffer(data: &Value, buffer: &mut Vec<u8>) {
     match data {
         Value::Null => buffer.put(NULL),
@@ -607,7 +828,10 @@ fn resolve_type_name<'a>(
 mod tests {
     use graphql_tools::parser::query::Definition;
     use hive_router_query_planner::{
-        ast::{document::NormalizedDocument, normalization::create_normalized_document},
+        ast::{
+            document::NormalizedDocument,
+            normalization::{create_normalized_document, defer::normalize_deferred_operation},
+        },
         consumer_schema::ConsumerSchema,
         state::supergraph_state::SupergraphState,
         utils::parsing::parse_operation,
@@ -615,8 +839,12 @@ mod tests {
     use sonic_rs::json;
 
     use crate::{
+        execution::incremental::DeferredProjectionPlan,
         introspection::schema::SchemaWithMetadata,
-        projection::{plan::FieldProjectionPlan, response::project_by_operation},
+        projection::{
+            plan::FieldProjectionPlan,
+            response::{project_by_operation, project_deferred_fragment},
+        },
         response::value::Value,
     };
 
@@ -833,4 +1061,79 @@ mod tests {
         }
         "#);
     }
+
+    #[test]
+    fn project_deferred_fragment_at_every_list_item() {
+        let supergraph = hive_router_query_planner::utils::parsing::parse_schema(
+            r#"
+            type Query {
+                users: [User]
+            }
+
+            type User {
+                id: ID!
+                name: String
+            }
+        "#,
+        );
+        let consumer_schema = ConsumerSchema::new_from_supergraph(&supergraph);
+        let schema_metadata = consumer_schema.schema_metadata();
+        let operation = parse_operation(
+            r#"
+            query {
+                users {
+                    id
+                    ... @defer(label: "name") {
+                        name
+                    }
+                }
+            }
+            "#,
+        );
+        let supergraph_state = SupergraphState::new(&supergraph);
+        let deferred_operation = normalize_deferred_operation(&supergraph_state, &operation, None)
+            .unwrap()
+            .unwrap();
+        let deferred_projection_plan =
+            DeferredProjectionPlan::from_operation(&deferred_operation, &schema_metadata);
+        let fragment = &deferred_projection_plan.fragments[0];
+
+        let data_json = json!({
+            "users": [
+                { "__typename": "User", "id": "1", "name": "a" },
+                null,
+                { "__typename": "User", "id": "3", "name": null }
+            ]
+        });
+        let data = Value::from(data_json.as_ref());
+        let mut errors = vec![];
+        let patches = project_deferred_fragment(
+            &data,
+            &mut errors,
+            &fragment.plans,
+            &fragment.path,
+            "Query",
+            &None,
+            &schema_metadata,
+        )
+        .unwrap();
+
+        let patches: Vec<(String, String)> = patches
+            .into_iter()
+            .map(|patch| {
+                (
+                    sonic_rs::to_string(&patch.path).unwrap(),
+                    String::from_utf8(patch.data).unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            patches,
+            vec![
+                (r#"["users",0]"#.to_string(), r#"{"name":"a"}"#.to_string()),
+                (r#"["users",2]"#.to_string(), r#"{"name":null}"#.to_string()),
+            ]
+        );
+        assert!(errors.is_empty());
+    }
 }
@@ -0,0 +1,215 @@
+use ahash::HashSet;
+use hive_router_config::errors::ErrorsConfig;
+use sonic_rs::Value;
+
+use crate::response::graphql_error::{GraphQLError, GraphQLErrorExtensions};
+
+/// Controls what the errors sent to the clients expose,
+/// applied to the final response once the plugins processed the errors.
+#[derive(Debug, Default)]
+pub struct ErrorMaskingPlan {
+    masking: Option<SubgraphErrorMasking>,
+    strip_extensions: HashSet<String>,
+    strip_subgraph_attribution: bool,
+}
+
+#[derive(Debug)]
+struct SubgraphErrorMasking {
+    message: String,
+    allowed_codes: HashSet<String>,
+    correlation_id: bool,
+}
+
+impl ErrorMaskingPlan {
+    pub fn from_config(config: &ErrorsConfig) -> Self {
+        ErrorMaskingPlan {
+            masking: config.masking.enabled.then(|| SubgraphErrorMasking {
+                message: config.masking.message.clone(),
+                allowed_codes: config.masking.allowed_codes.iter().cloned().collect(),
+                correlation_id: config.masking.correlation_id,
+            }),
+            strip_extensions: config.strip_extensions.iter().cloned().collect(),
+            strip_subgraph_attribution: !config.subgraph_attribution,
+        }
+    }
+
+    #[inline]
+    pub fn is_noop(&self) -> bool {
+        self.masking.is_none()
+            && self.strip_extensions.is_empty()
+            && !self.strip_subgraph_attribution
+    }
+
+    /// Masks the errors of subgraphs, unless their code is allowed,
+    /// and removes the stripped extensions from the other errors.
+    pub fn apply(&self, errors: &mut [GraphQLError]) {
+        if self.is_noop() {
+            return;
+        }
+
+        for error in errors.iter_mut() {
+            match &self.masking {
+                Some(masking) if masking.should_mask(error) => masking.mask(error),
+                _ => self.strip(&mut error.extensions),
+            }
+        }
+    }
+
+    /// Same as [`ErrorMaskingPlan::apply`], taking and returning the errors.
+    pub fn masked(&self, mut errors: Vec<GraphQLError>) -> Vec<GraphQLError> {
+        self.apply(&mut errors);
+        errors
+    }
+
+    fn strip(&self, extensions: &mut GraphQLErrorExtensions) {
+        if self.strip_subgraph_attribution || self.strip_extensions.contains("service") {
+            extensions.service_name = None;
+        }
+        if self.strip_subgraph_attribution || self.strip_extensions.contains("affectedPath") {
+            extensions.affected_path = None;
+        }
+        if self.strip_extensions.is_empty() {
+            return;
+        }
+        extensions
+            .extensions
+            .retain(|key, _| !self.strip_extensions.contains(key));
+    }
+}
+
+impl SubgraphErrorMasking {
+    fn should_mask(&self, error: &GraphQLError) -> bool {
+        error.extensions.service_name.is_some()
+            && !error
+                .extensions
+                .code
+                .as_ref()
+                .is_some_and(|code| self.allowed_codes.contains(code))
+    }
+
+    fn mask(&self, error: &mut GraphQLError) {
+        let original_message = std::mem::replace(&mut error.message, self.message.clone());
+        let original_extensions = std::mem::take(&mut error.extensions);
+
+        if self.correlation_id {
+            let correlation_id = uuid::Uuid::new_v4().to_string();
+            tracing::warn!(
+                correlation_id = %correlation_id,
+                subgraph_name = original_extensions.service_name.as_deref(),
+                code = original_extensions.code.as_deref(),
+                "masked subgraph error: {}",
+                original_message
+            );
+            error.extensions.set(
+                "correlationId".to_string(),
+                Value::from(correlation_id.as_str()),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hive_router_config::errors::{ErrorMaskingConfig, ErrorsConfig};
+    use sonic_rs::{json, JsonValueTrait};
+
+    use super::ErrorMaskingPlan;
+    use crate::response::graphql_error::GraphQLError;
+
+    fn subgraph_error(message: &str, code: &str) -> GraphQLError {
+        let mut error =
+            GraphQLError::from_message_and_code(message, code).add_subgraph_name("users");
+        error
+            .extensions
+            .set("stacktrace".to_string(), json!(["at resolver"]));
+        error
+    }
+
+    #[test]
+    fn masks_subgraph_errors_unless_their_code_is_allowed() {
+        let plan = ErrorMaskingPlan::from_config(&ErrorsConfig {
+            masking: ErrorMaskingConfig {
+                enabled: true,
+                allowed_codes: vec!["BAD_USER_INPUT".to_string()],
+                ..Default::default()
+            },
+            strip_extensions: vec!["stacktrace".to_string()],
+            ..Default::default()
+        });
+        let mut errors = vec![
+            subgraph_error("connection to db.internal refused", "INTERNAL_SERVER_ERROR"),
+            subgraph_error("id must be positive", "BAD_USER_INPUT"),
+            GraphQLError::from_message_and_code("Failed to execute", "PLAN_EXECUTION_FAILED"),
+        ];
+
+        plan.apply(&mut errors);
+
+        assert_eq!(
+            json!(errors),
+            json!([
+                { "message": "Unexpected error" },
+                {
+                    "message": "id must be positive",
+                    "extensions": { "code": "BAD_USER_INPUT", "service": "users" }
+                },
+                {
+                    "message": "Failed to execute",
+                    "extensions": { "code": "PLAN_EXECUTION_FAILED" }
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn adds_correlation_id_to_masked_errors() {
+        let plan = ErrorMaskingPlan::from_config(&ErrorsConfig {
+            masking: ErrorMaskingConfig {
+                enabled: true,
+                message: "Something went wrong".to_string(),
+                correlation_id: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let mut errors = vec![subgraph_error("oops", "INTERNAL_SERVER_ERROR")];
+
+        plan.apply(&mut errors);
+
+        assert_eq!(errors[0].message, "Something went wrong");
+        assert_eq!(errors[0].extensions.code, None);
+        let correlation_id = errors[0].extensions.get("correlationId").unwrap();
+        assert_eq!(correlation_id.as_str().unwrap().len(), 36);
+    }
+
+    #[test]
+    fn removes_subgraph_attribution_when_disabled() {
+        let plan = ErrorMaskingPlan::from_config(&ErrorsConfig {
+            subgraph_attribution: false,
+            ..Default::default()
+        });
+        let mut errors = vec![subgraph_error("oops", "INTERNAL_SERVER_ERROR")
+            .add_affected_path("me.reviews.@.product")];
+
+        plan.apply(&mut errors);
+
+        assert_eq!(
+            json!(errors),
+            json!([{
+                "message": "oops",
+                "extensions": { "code": "INTERNAL_SERVER_ERROR", "stacktrace": ["at resolver"] }
+            }])
+        );
+    }
+
+    #[test]
+    fn keeps_errors_untouched_by_default() {
+        let plan = ErrorMaskingPlan::from_config(&ErrorsConfig::default());
+        let mut errors = vec![subgraph_error("oops", "INTERNAL_SERVER_ERROR")];
+
+        plan.apply(&mut errors);
+
+        assert!(plan.is_noop());
+        assert_eq!(errors[0].message, "oops");
+        assert!(errors[0].extensions.get("stacktrace").is_some());
+    }
+}
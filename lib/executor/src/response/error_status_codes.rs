@@ -0,0 +1,117 @@
+use ahash::HashMap;
+use hive_router_config::errors::ErrorsConfig;
+use http::StatusCode;
+
+use crate::response::graphql_error::GraphQLError;
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "invalid HTTP status code {status_code} for the error code '{code}' in errors.status_codes"
+)]
+pub struct InvalidErrorStatusCode {
+    pub code: String,
+    pub status_code: u16,
+}
+
+/// Maps the codes of GraphQL errors (`extensions.code`) to the HTTP status code of the response,
+/// from the `errors.status_codes` configuration and the codes registered by the plugins.
+#[derive(Debug, Default, Clone)]
+pub struct ErrorStatusCodes {
+    by_code: HashMap<String, StatusCode>,
+}
+
+impl ErrorStatusCodes {
+    pub fn from_config(config: &ErrorsConfig) -> Result<Self, InvalidErrorStatusCode> {
+        let by_code = config
+            .status_codes
+            .iter()
+            .map(|(code, status_code)| {
+                StatusCode::from_u16(*status_code)
+                    .map(|status| (code.clone(), status))
+                    .map_err(|_| InvalidErrorStatusCode {
+                        code: code.clone(),
+                        status_code: *status_code,
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { by_code })
+    }
+
+    /// Registers the status code of an error code.
+    /// The status codes of the configuration take precedence over the registered ones,
+    /// and the first registration of a code wins.
+    pub fn register(&mut self, code: impl Into<String>, status_code: StatusCode) {
+        self.by_code.entry(code.into()).or_insert(status_code);
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.by_code.is_empty()
+    }
+
+    pub fn get(&self, code: &str) -> Option<StatusCode> {
+        self.by_code.get(code).copied()
+    }
+
+    /// The status code of the first error having a mapped code.
+    pub fn status_code_for(&self, errors: &[GraphQLError]) -> Option<StatusCode> {
+        if self.is_empty() {
+            return None;
+        }
+
+        errors.iter().find_map(|error| {
+            error
+                .extensions
+                .code
+                .as_deref()
+                .and_then(|code| self.get(code))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hive_router_config::errors::ErrorsConfig;
+    use http::StatusCode;
+
+    use super::ErrorStatusCodes;
+    use crate::response::graphql_error::GraphQLError;
+
+    #[test]
+    fn config_takes_precedence_over_registered_codes() {
+        let mut status_codes = ErrorStatusCodes::from_config(&ErrorsConfig {
+            status_codes: [("UNAUTHENTICATED".to_string(), 401)].into_iter().collect(),
+            ..Default::default()
+        })
+        .expect("status codes should be valid");
+        status_codes.register("UNAUTHENTICATED", StatusCode::FORBIDDEN);
+        status_codes.register("RATE_LIMITED", StatusCode::TOO_MANY_REQUESTS);
+
+        let errors = vec![
+            GraphQLError::from_message_and_code("Boom", "INTERNAL_SERVER_ERROR"),
+            GraphQLError::from_message_and_code("Slow down", "RATE_LIMITED"),
+            GraphQLError::from_message_and_code("Who are you?", "UNAUTHENTICATED"),
+        ];
+        assert_eq!(
+            status_codes.status_code_for(&errors),
+            Some(StatusCode::TOO_MANY_REQUESTS)
+        );
+        assert_eq!(
+            status_codes.get("UNAUTHENTICATED"),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(status_codes.status_code_for(&errors[..1]), None);
+    }
+
+    #[test]
+    fn rejects_invalid_status_codes() {
+        let result = ErrorStatusCodes::from_config(&ErrorsConfig {
+            status_codes: [("UNAUTHENTICATED".to_string(), 1000)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+    }
+}
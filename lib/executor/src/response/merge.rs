@@ -1,11 +1,60 @@
-use std::cmp::Ordering;
+use std::{
+    cmp::Ordering,
+    sync::atomic::{AtomicBool, Ordering as AtomicOrdering},
+};
+
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 
 use crate::response::value::Value;
 
+/// Batches with fewer merges than this are merged on the current thread,
+/// spreading them across threads costs more than it saves.
+pub const PARALLEL_MERGE_THRESHOLD: usize = 1024;
+
+/// The least number of merges handled by a thread, when a batch is merged in parallel.
+const PARALLEL_MERGE_MIN_CHUNK_SIZE: usize = 256;
+
+/// Set while a batch is merged in parallel.
+static PARALLEL_MERGE_RUNNING: AtomicBool = AtomicBool::new(false);
+
 pub fn deep_merge<'a>(target: &mut Value<'a>, source: Value<'a>) {
     deep_merge_internal(target, source)
 }
 
+/// Merges each source into its target, for example the entities of a `FlattenNode` fetch.
+/// Large batches are merged in parallel, the targets being distinct values of the response.
+///
+/// The calling async worker is blocked until the batch is merged, as it is when merging on its own thread:
+/// the values borrow the response, so they can't move to `spawn_blocking`,
+/// and `block_in_place` is not available on the current-thread runtimes of the workers.
+/// To bound that wait to the batch itself, a single batch is merged in parallel at a time,
+/// the others are merged on the current thread rather than queued behind it in the thread pool.
+pub fn deep_merge_batch<'a>(merges: Vec<(&mut Value<'a>, Value<'a>)>) {
+    if merges.len() < PARALLEL_MERGE_THRESHOLD
+        || PARALLEL_MERGE_RUNNING.swap(true, AtomicOrdering::Acquire)
+    {
+        for (target, source) in merges {
+            deep_merge_internal(target, source);
+        }
+        return;
+    }
+
+    let _running = ParallelMergeRunning;
+    merges
+        .into_par_iter()
+        .with_min_len(PARALLEL_MERGE_MIN_CHUNK_SIZE)
+        .for_each(|(target, source)| deep_merge_internal(target, source));
+}
+
+/// Clears [`PARALLEL_MERGE_RUNNING`] once the parallel merge is over, even when it panics.
+struct ParallelMergeRunning;
+
+impl Drop for ParallelMergeRunning {
+    fn drop(&mut self) {
+        PARALLEL_MERGE_RUNNING.store(false, AtomicOrdering::Release);
+    }
+}
+
 fn deep_merge_internal<'a>(target: &mut Value<'a>, source: Value<'a>) {
     match (target, source) {
         // If the source value is null, we do nothing.
@@ -84,3 +133,46 @@ fn deep_merge_objects<'a>(
     // Replace the original vector with the newly merged one.
     *target_vec = merged;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{deep_merge_batch, PARALLEL_MERGE_THRESHOLD};
+    use crate::response::value::Value;
+
+    fn entity<'a>(fields: Vec<(&'a str, Value<'a>)>) -> Value<'a> {
+        Value::Object(fields)
+    }
+
+    #[test]
+    fn merges_batches_above_the_threshold_in_parallel() {
+        let count = PARALLEL_MERGE_THRESHOLD * 2;
+        let mut targets: Vec<Value> = (0..count)
+            .map(|index| {
+                entity(vec![
+                    ("__typename", Value::String("Product".into())),
+                    ("upc", Value::U64(index as u64)),
+                ])
+            })
+            .collect();
+        let merges = targets
+            .iter_mut()
+            .enumerate()
+            .map(|(index, target)| {
+                let source = entity(vec![
+                    ("name", Value::String("Table".into())),
+                    ("upc", Value::U64(index as u64)),
+                ]);
+                (target, source)
+            })
+            .collect();
+
+        deep_merge_batch(merges);
+
+        for (index, target) in targets.iter().enumerate() {
+            assert_eq!(
+                target.to_string(),
+                format!(r#"{{"__typename": "Product", "name": "Table", "upc": {index}}}"#)
+            );
+        }
+    }
+}
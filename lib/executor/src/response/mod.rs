@@ -1,3 +1,5 @@
+pub mod error_masking;
+pub mod error_status_codes;
 pub mod graphql_error;
 pub mod merge;
 pub mod storage;
@@ -2,6 +2,8 @@ use bytes::Bytes;
 
 pub struct ResponsesStorage {
     responses: Vec<Bytes>,
+    // Bytes not coming from a subgraph response, such as cached entities
+    cached: Vec<Bytes>,
 }
 
 impl Default for ResponsesStorage {
@@ -14,6 +16,7 @@ impl ResponsesStorage {
     pub fn new() -> Self {
         Self {
             responses: Vec::new(),
+            cached: Vec::new(),
         }
     }
 
@@ -29,8 +32,18 @@ impl ResponsesStorage {
         self.responses.push(response);
     }
 
+    /// Keeps alive the bytes of cached data, without counting them as a subgraph response.
+    pub fn add_cached(&mut self, bytes: Bytes) {
+        self.cached.push(bytes);
+    }
+
     pub fn estimate_final_response_size(&self) -> usize {
-        let total_size: usize = self.responses.iter().map(|r| r.len()).sum();
+        let total_size: usize = self
+            .responses
+            .iter()
+            .chain(self.cached.iter())
+            .map(|r| r.len())
+            .sum();
         // Add a 20% buffer to account for JSON syntax, escaping, and other overhead.
         // I tested a bunch of numbers and it was the best from the bunch.
         (total_size as f64 * 1.2) as usize
@@ -18,14 +18,14 @@ fn entity_satisfies_any_type_condition(
         .any(|condition| possible_types.entity_satisfies_type_condition(type_name, condition))
 }
 
-pub fn traverse_and_callback_mut<'a, Callback>(
-    current_data: &mut Value<'a>,
+pub fn traverse_and_callback_mut<'t, 'a, Callback>(
+    current_data: &'t mut Value<'a>,
     remaining_path: &[FlattenNodePathSegment],
     schema_metadata: &SchemaMetadata,
     current_error_path: Option<GraphQLErrorPath>,
     callback: &mut Callback,
 ) where
-    Callback: FnMut(&mut Value<'a>, Option<GraphQLErrorPath>),
+    Callback: FnMut(&'t mut Value<'a>, Option<GraphQLErrorPath>),
 {
     if remaining_path.is_empty() {
         if let Value::Array(arr) = current_data {
@@ -185,6 +185,94 @@ pub fn traverse_and_callback<'a, Callback>(
     }
 }
 
+/// Same as `traverse_and_callback`, but also passes the objects holding the fields
+/// along the path, with the closest one last.
+pub fn traverse_and_callback_with_ancestors<'a, Callback>(
+    current_data: &'a Value<'a>,
+    remaining_path: &'a [FlattenNodePathSegment],
+    possible_types: &'a PossibleTypes,
+    ancestors: &mut Vec<&'a Value<'a>>,
+    callback: &mut Callback,
+) where
+    Callback: FnMut(&'a Value<'a>, &[&'a Value<'a>]),
+{
+    if remaining_path.is_empty() {
+        if let Value::Array(arr) = current_data {
+            for item in arr.iter() {
+                callback(item, ancestors);
+            }
+        } else {
+            callback(current_data, ancestors);
+        }
+        return;
+    }
+
+    match &remaining_path[0] {
+        FlattenNodePathSegment::List => {
+            if let Value::Array(arr) = current_data {
+                let rest_of_path = &remaining_path[1..];
+                for item in arr.iter() {
+                    traverse_and_callback_with_ancestors(
+                        item,
+                        rest_of_path,
+                        possible_types,
+                        ancestors,
+                        callback,
+                    );
+                }
+            }
+        }
+        FlattenNodePathSegment::Field(field_name) => {
+            if let Value::Object(map) = current_data {
+                if let Ok(idx) = map.binary_search_by_key(&field_name.as_str(), |(k, _)| k) {
+                    let (_, next_data) = &map[idx];
+                    let rest_of_path = &remaining_path[1..];
+                    ancestors.push(current_data);
+                    traverse_and_callback_with_ancestors(
+                        next_data,
+                        rest_of_path,
+                        possible_types,
+                        ancestors,
+                        callback,
+                    );
+                    ancestors.pop();
+                }
+            }
+        }
+        FlattenNodePathSegment::TypeCondition(type_condition) => {
+            if let Value::Object(obj) = current_data {
+                let maybe_type_name = obj
+                    .binary_search_by_key(&TYPENAME_FIELD_NAME, |(k, _)| k)
+                    .ok()
+                    .and_then(|idx| obj[idx].1.as_str());
+
+                if maybe_type_name.is_none_or(|type_name| {
+                    entity_satisfies_any_type_condition(possible_types, type_name, type_condition)
+                }) {
+                    let rest_of_path = &remaining_path[1..];
+                    traverse_and_callback_with_ancestors(
+                        current_data,
+                        rest_of_path,
+                        possible_types,
+                        ancestors,
+                        callback,
+                    );
+                }
+            } else if let Value::Array(arr) = current_data {
+                for item in arr.iter() {
+                    traverse_and_callback_with_ancestors(
+                        item,
+                        remaining_path,
+                        possible_types,
+                        ancestors,
+                        callback,
+                    );
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use hive_router_query_planner::planner::plan_nodes::FlattenNodePathSegment;
@@ -1,14 +1,22 @@
-use std::collections::HashMap;
+pub mod serialized;
+
+use std::{collections::HashMap, fmt::Write};
 
 use hive_router_query_planner::state::supergraph_state::TypeNode;
-use sonic_rs::{JsonNumberTrait, Value, ValueRef};
+use sonic_rs::{JsonContainerTrait, JsonValueMutTrait, JsonValueTrait, Value};
 
-use crate::introspection::schema::SchemaMetadata;
+use crate::{
+    introspection::schema::{InputFieldInfo, SchemaMetadata},
+    variables::serialized::RawVariables,
+};
 
+/// Collects and coerces the variables of the operation.
+/// The JSON sent by the client is removed from `raw_variables` for the variables changed by the coercion.
 #[inline]
 pub fn collect_variables(
     operation: &hive_router_query_planner::ast::operation::OperationDefinition,
     variables_map: &mut HashMap<String, Value>,
+    raw_variables: &mut RawVariables,
     schema_metadata: &SchemaMetadata,
 ) -> Result<Option<HashMap<String, Value>>, String> {
     if operation.variable_definitions.is_none() {
@@ -20,33 +28,41 @@ pub fn collect_variables(
         .iter()
         .map(|variable_definition| {
             let variable_name = variable_definition.name.as_str();
-            if let Some(variable_value) = variables_map.remove(variable_name) {
-                validate_runtime_value(
-                    variable_value.as_ref(),
-                    &variable_definition.variable_type,
-                    schema_metadata,
-                )?;
-                return Ok(Some((variable_name.to_string(), variable_value)));
-            }
-            if let Some(default_value) = &variable_definition.default_value {
-                // Assuming value_from_ast now returns Result<Value, String> or similar
-                // and needs to be adapted if it returns Option or panics.
-                // For now, let's assume it can return an Err that needs to be propagated.
-                let default_value_coerced: Value = default_value.into();
-                validate_runtime_value(
-                    default_value_coerced.as_ref(),
-                    &variable_definition.variable_type,
-                    schema_metadata,
-                )?;
-                return Ok(Some((variable_name.to_string(), default_value_coerced)));
-            }
-            if variable_definition.variable_type.is_non_null() {
+            let variable_type = &variable_definition.variable_type;
+            let mut variable_value = match variables_map.remove(variable_name) {
+                Some(variable_value) => variable_value,
+                None => match &variable_definition.default_value {
+                    Some(default_value) => default_value.into(),
+                    None if variable_type.is_non_null() => {
+                        return Err(format!(
+                            "Variable \"${}\" of required type \"{}\" was not provided.",
+                            variable_name, variable_type
+                        ));
+                    }
+                    None => return Ok(None),
+                },
+            };
+
+            if variable_value.is_null() && variable_type.is_non_null() {
                 return Err(format!(
-                    "Variable '{}' is non-nullable but no value was provided",
-                    variable_name
+                    "Variable \"${}\" of non-null type \"{}\" must not be null.",
+                    variable_name, variable_type
                 ));
             }
-            Ok(None)
+
+            let mut coerced = false;
+            coerce_runtime_value(
+                &mut variable_value,
+                variable_type,
+                schema_metadata,
+                &mut coerced,
+            )
+            .map_err(|err| err.into_message(variable_name))?;
+            if coerced {
+                raw_variables.remove(variable_name);
+            }
+
+            Ok(Some((variable_name.to_string(), variable_value)))
         })
         .collect();
 
@@ -60,185 +76,466 @@ pub fn collect_variables(
     }
 }
 
-#[inline]
-fn validate_runtime_value(
-    value: ValueRef,
+#[derive(Debug, PartialEq)]
+enum PathSegment {
+    Field(String),
+    Index(usize),
+}
+
+/// A value of a variable that cannot be coerced to its input type.
+#[derive(Debug, PartialEq)]
+struct InvalidValueError {
+    /// The location of the invalid value within the variable, from the innermost segment.
+    path: Vec<PathSegment>,
+    value: String,
+    reason: String,
+}
+
+impl InvalidValueError {
+    fn new(value: &Value, reason: String) -> Self {
+        InvalidValueError {
+            path: vec![],
+            value: sonic_rs::to_string(value).unwrap_or_default(),
+            reason,
+        }
+    }
+
+    fn at(mut self, segment: PathSegment) -> Self {
+        self.path.push(segment);
+        self
+    }
+
+    fn into_message(self, variable_name: &str) -> String {
+        let mut message = format!(
+            "Variable \"${}\" got invalid value {}",
+            variable_name, self.value
+        );
+        if !self.path.is_empty() {
+            let _ = write!(message, " at \"{}", variable_name);
+            for segment in self.path.iter().rev() {
+                let _ = match segment {
+                    PathSegment::Field(field_name) => write!(message, ".{}", field_name),
+                    PathSegment::Index(index) => write!(message, "[{}]", index),
+                };
+            }
+            message.push('"');
+        }
+        let _ = write!(message, "; {}", self.reason);
+        message
+    }
+}
+
+/// Coerces the value of a variable to its input type, following
+/// https://spec.graphql.org/September2025/#sec-Input-Values.
+/// Default values of omitted input object fields are filled in,
+/// and a single value provided for a list type is wrapped in a list, setting `coerced`.
+fn coerce_runtime_value(
+    value: &mut Value,
     type_node: &TypeNode,
     schema_metadata: &SchemaMetadata,
-) -> Result<(), String> {
-    if let ValueRef::Null = value {
-        return if type_node.is_non_null() {
-            Err("Value cannot be null for non-nullable type".to_string())
-        } else {
-            Ok(())
-        };
-    }
+    coerced: &mut bool,
+) -> Result<(), InvalidValueError> {
     match type_node {
-        TypeNode::Named(name) => {
-            if let Some(enum_values) = schema_metadata.enum_values.get(name) {
-                if let ValueRef::String(ref s) = value {
-                    if !enum_values.contains(&s.to_string()) {
-                        return Err(format!(
-                            "Value '{}' is not a valid enum value for type '{}'",
-                            s, name
-                        ));
-                    }
-                } else {
-                    return Err(format!(
-                        "Expected a string for enum type '{}', got {:?}",
-                        name, value
-                    ));
-                }
-            } else if let Some(fields) = schema_metadata.type_fields.get(name) {
-                if let ValueRef::Object(obj) = value {
-                    for (field_name, field_info) in fields {
-                        if let Some(field_value) = obj.get(field_name) {
-                            validate_runtime_value(
-                                field_value.as_ref(),
-                                &TypeNode::Named(field_info.output_type_name.to_string()),
-                                schema_metadata,
-                            )?;
-                        } else {
-                            return Err(format!(
-                                "Missing field '{}' for type '{}'",
-                                field_name, name
-                            ));
-                        }
-                    }
-                } else {
-                    return Err(format!(
-                        "Expected an object for type '{}', got {:?}",
-                        name, value
-                    ));
+        TypeNode::NonNull(inner_type) => {
+            if value.is_null() {
+                return Err(InvalidValueError::new(
+                    value,
+                    format!(
+                        "Expected non-nullable type \"{}\" not to be null.",
+                        type_node
+                    ),
+                ));
+            }
+            coerce_runtime_value(value, inner_type, schema_metadata, coerced)
+        }
+        _ if value.is_null() => Ok(()),
+        TypeNode::List(inner_type) => {
+            if let Some(items) = value.as_array_mut() {
+                for (index, item) in items.iter_mut().enumerate() {
+                    coerce_runtime_value(item, inner_type, schema_metadata, coerced)
+                        .map_err(|err| err.at(PathSegment::Index(index)))?;
                 }
             } else {
-                return match name.as_str() {
-                    "String" => {
-                        if let ValueRef::String(_) = value {
-                            Ok(())
-                        } else {
-                            Err(format!(
-                                "Expected a string for type '{}', got {:?}",
-                                name, value
-                            ))
-                        }
-                    }
-                    "Int" => {
-                        if let ValueRef::Number(ref num) = value {
-                            if num.is_i64() {
-                                Ok(())
-                            } else {
-                                Err(format!(
-                                    "Expected an integer for type '{}', got {:?}",
-                                    name, value
-                                ))
-                            }
-                        } else {
-                            Err(format!(
-                                "Expected a number for type '{}', got {:?}",
-                                name, value
-                            ))
-                        }
-                    }
-                    "Float" => {
-                        if let ValueRef::Number(ref num) = value {
-                            if num.is_f64() || num.is_i64() {
-                                Ok(())
-                            } else {
-                                Err(format!(
-                                    "Expected a float for type '{}', got {:?}",
-                                    name, value
-                                ))
-                            }
-                        } else {
-                            Err(format!(
-                                "Expected a number for type '{}', got {:?}",
-                                name, value
-                            ))
-                        }
-                    }
-                    "Boolean" => {
-                        if let ValueRef::Bool(_) = value {
-                            Ok(())
-                        } else {
-                            Err(format!(
-                                "Expected a boolean for type '{}', got {:?}",
-                                name, value
-                            ))
-                        }
-                    }
-                    "ID" => {
-                        if let ValueRef::String(_) = value {
-                            Ok(())
-                        } else {
-                            Err(format!("Expected a string for type 'ID', got {:?}", value))
-                        }
-                    }
-                    _ => Ok(()),
-                };
+                coerce_runtime_value(value, inner_type, schema_metadata, coerced)?;
+                let item = std::mem::replace(value, Value::new_null());
+                let mut items = Value::new_array_with(1);
+                items.append_value(item);
+                *value = items;
+                *coerced = true;
             }
+            Ok(())
         }
-        TypeNode::NonNull(inner_type) => {
-            // The null check is now handled above, so we can just recurse.
-            validate_runtime_value(value, inner_type, schema_metadata)?;
+        TypeNode::Named(name) => {
+            if let Some(fields) = schema_metadata.get_input_object_fields(name) {
+                coerce_input_object_value(value, name, fields, schema_metadata, coerced)
+            } else if let Some(enum_values) = schema_metadata.enum_values.get(name) {
+                match value.as_str() {
+                    Some(enum_value) if enum_values.contains(enum_value) => Ok(()),
+                    Some(enum_value) => Err(InvalidValueError::new(
+                        value,
+                        format!(
+                            "Value \"{}\" does not exist in \"{}\" enum.",
+                            enum_value, name
+                        ),
+                    )),
+                    None => Err(InvalidValueError::new(
+                        value,
+                        format!(
+                            "Enum \"{}\" cannot represent non-string value: {}.",
+                            name,
+                            sonic_rs::to_string(value).unwrap_or_default()
+                        ),
+                    )),
+                }
+            } else {
+                coerce_scalar_value(value, name)
+            }
         }
-        TypeNode::List(inner_type) => {
-            if let ValueRef::Array(arr) = value {
-                for item in arr.iter() {
-                    validate_runtime_value(item.as_ref(), inner_type, schema_metadata)?;
+    }
+}
+
+fn coerce_input_object_value(
+    value: &mut Value,
+    type_name: &str,
+    fields: &[InputFieldInfo],
+    schema_metadata: &SchemaMetadata,
+    coerced: &mut bool,
+) -> Result<(), InvalidValueError> {
+    if !value.is_object() {
+        return Err(InvalidValueError::new(
+            value,
+            format!("Expected type \"{}\" to be an object.", type_name),
+        ));
+    }
+
+    let unknown_field = value
+        .as_object()
+        .and_then(|object| {
+            object
+                .iter()
+                .find(|(field_name, _)| !fields.iter().any(|field| field.name == *field_name))
+        })
+        .map(|(field_name, _)| field_name.to_string());
+    if let Some(field_name) = unknown_field {
+        return Err(InvalidValueError::new(
+            value,
+            format!(
+                "Field \"{}\" is not defined by type \"{}\".",
+                field_name, type_name
+            ),
+        ));
+    }
+
+    if schema_metadata.is_one_of_type(type_name) {
+        validate_one_of_value(value, type_name)?;
+    }
+
+    let mut missing_field = None;
+    if let Some(object) = value.as_object_mut() {
+        for field in fields {
+            match object.get_mut(&field.name) {
+                Some(field_value) => {
+                    coerce_runtime_value(field_value, &field.field_type, schema_metadata, coerced)
+                        .map_err(|err| err.at(PathSegment::Field(field.name.clone())))?;
                 }
+                None => {
+                    if let Some(default_value) = &field.default_value {
+                        object.insert(&field.name, default_value.clone());
+                        *coerced = true;
+                    } else if field.field_type.is_non_null() {
+                        missing_field = Some(field);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    match missing_field {
+        Some(field) => Err(InvalidValueError::new(
+            value,
+            format!(
+                "Field \"{}.{}\" of required type \"{}\" was not provided.",
+                type_name, field.name, field.field_type
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Values of `@oneOf` input objects must set exactly one field, to a non-null value.
+fn validate_one_of_value(value: &Value, type_name: &str) -> Result<(), InvalidValueError> {
+    let Some(object) = value.as_object() else {
+        return Ok(());
+    };
+    let mut fields = object.iter();
+    match (fields.next(), fields.next()) {
+        (Some((field_name, field_value)), None) => {
+            if field_value.is_null() {
+                Err(InvalidValueError::new(
+                    value,
+                    format!("Field \"{}.{}\" must be non-null.", type_name, field_name),
+                ))
             } else {
-                validate_runtime_value(value, inner_type, schema_metadata)?;
+                Ok(())
             }
         }
+        _ => Err(InvalidValueError::new(
+            value,
+            format!(
+                "Exactly one key must be specified for OneOf type \"{}\".",
+                type_name
+            ),
+        )),
     }
-    Ok(())
+}
+
+/// Built-in scalars accept only their own kind of values, custom scalars accept any value.
+fn coerce_scalar_value(value: &Value, type_name: &str) -> Result<(), InvalidValueError> {
+    let reason = match type_name {
+        "String" if !value.is_str() => "String cannot represent a non string value",
+        "Boolean" if !value.is_boolean() => "Boolean cannot represent a non boolean value",
+        "Float" if !value.is_number() => "Float cannot represent non numeric value",
+        "Int" => match value.as_i64() {
+            Some(int) if i32::try_from(int).is_ok() => return Ok(()),
+            Some(_) => "Int cannot represent non 32-bit signed integer value",
+            None if value.as_u64().is_some() => {
+                "Int cannot represent non 32-bit signed integer value"
+            }
+            None => "Int cannot represent non-integer value",
+        },
+        "ID" if !value.is_str() && value.as_i64().is_none() => "ID cannot represent value",
+        _ => return Ok(()),
+    };
+
+    Err(InvalidValueError::new(
+        value,
+        format!(
+            "{}: {}",
+            reason,
+            sonic_rs::to_string(value).unwrap_or_default()
+        ),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
+    use hive_router_query_planner::state::supergraph_state::TypeNode;
+
+    use crate::introspection::schema::{InputFieldInfo, SchemaMetadata};
+
+    fn coerce(
+        value: &mut sonic_rs::Value,
+        type_name: &str,
+        schema_metadata: &SchemaMetadata,
+    ) -> Result<(), String> {
+        let type_node = TypeNode::try_from(type_name).unwrap();
+        super::coerce_runtime_value(value, &type_node, schema_metadata, &mut false)
+            .map_err(|err| err.into_message("input"))
+    }
+
+    fn input_field(
+        name: &str,
+        field_type: &str,
+        default_value: Option<sonic_rs::Value>,
+    ) -> InputFieldInfo {
+        InputFieldInfo {
+            name: name.to_string(),
+            field_type: TypeNode::try_from(field_type).unwrap(),
+            default_value,
+        }
+    }
+
     #[test]
     fn allow_null_values_for_nullable_scalar_types() {
-        let schema_metadata = crate::introspection::schema::SchemaMetadata::default();
+        let schema_metadata = SchemaMetadata::default();
 
         let scalars = vec!["String", "Int", "Float", "Boolean", "ID"];
         for scalar in scalars {
-            let type_node = crate::variables::TypeNode::Named(scalar.to_string());
-
-            let value = sonic_rs::ValueRef::Null;
-
-            let result = super::validate_runtime_value(value, &type_node, &schema_metadata);
+            let mut value = sonic_rs::Value::new_null();
+            let result = coerce(&mut value, scalar, &schema_metadata);
             assert_eq!(result, Ok(()));
         }
     }
     #[test]
     fn allow_null_values_for_nullable_list_types() {
-        let schema_metadata = crate::introspection::schema::SchemaMetadata::default();
-        let type_node = crate::variables::TypeNode::List(Box::new(
-            crate::variables::TypeNode::Named("String".to_string()),
-        ));
-        let value = sonic_rs::ValueRef::Null;
-        let result = super::validate_runtime_value(value, &type_node, &schema_metadata);
+        let schema_metadata = SchemaMetadata::default();
+        let mut value = sonic_rs::Value::new_null();
+        let result = coerce(&mut value, "[String]", &schema_metadata);
         assert_eq!(result, Ok(()));
+        assert!(value.is_null());
     }
     #[test]
     fn allow_matching_non_list_values_for_list_types() {
-        let schema_metadata = crate::introspection::schema::SchemaMetadata::default();
-        let type_node = crate::variables::TypeNode::List(Box::new(
-            crate::variables::TypeNode::Named("String".to_string()),
-        ));
-        let value = sonic_rs::ValueRef::String("not a list");
-        let result = super::validate_runtime_value(value, &type_node, &schema_metadata);
+        let schema_metadata = SchemaMetadata::default();
+        let mut value = sonic_rs::json!("not a list");
+        let result = coerce(&mut value, "[String]", &schema_metadata);
         assert_eq!(result, Ok(()));
+        assert_eq!(value, sonic_rs::json!(["not a list"]));
+
+        let mut value = sonic_rs::json!(1);
+        let result = coerce(&mut value, "[[Int!]!]", &schema_metadata);
+        assert_eq!(result, Ok(()));
+        assert_eq!(value, sonic_rs::json!([[1]]));
     }
     #[test]
     fn disallow_non_matching_non_list_values_for_list_types() {
-        let schema_metadata = crate::introspection::schema::SchemaMetadata::default();
-        let type_node = crate::variables::TypeNode::List(Box::new(
-            crate::variables::TypeNode::Named("String".to_string()),
-        ));
-        let value = sonic_rs::ValueRef::Number(sonic_rs::Number::from(123));
-        let result = super::validate_runtime_value(value, &type_node, &schema_metadata);
+        let schema_metadata = SchemaMetadata::default();
+        let mut value = sonic_rs::json!(123);
+        let result = coerce(&mut value, "[String]", &schema_metadata);
+        assert_eq!(
+            result,
+            Err("Variable \"$input\" got invalid value 123; String cannot represent a non string value: 123".to_string())
+        );
+    }
+    #[test]
+    fn disallow_null_items_for_non_null_list_items() {
+        let schema_metadata = SchemaMetadata::default();
+        let mut value = sonic_rs::json!([1, null]);
+        let result = coerce(&mut value, "[Int!]", &schema_metadata);
+        assert_eq!(
+            result,
+            Err("Variable \"$input\" got invalid value null at \"input[1]\"; Expected non-nullable type \"Int!\" not to be null.".to_string())
+        );
+    }
+    #[test]
+    fn disallow_non_32_bit_integers_for_int_types() {
+        let schema_metadata = SchemaMetadata::default();
+
+        let mut value = sonic_rs::json!(2147483647);
+        assert_eq!(coerce(&mut value, "Int", &schema_metadata), Ok(()));
+
+        let mut value = sonic_rs::json!(2147483648_i64);
+        assert_eq!(
+            coerce(&mut value, "Int", &schema_metadata),
+            Err("Variable \"$input\" got invalid value 2147483648; Int cannot represent non 32-bit signed integer value: 2147483648".to_string())
+        );
+
+        let mut value = sonic_rs::json!(1.5);
+        assert_eq!(
+            coerce(&mut value, "Int", &schema_metadata),
+            Err("Variable \"$input\" got invalid value 1.5; Int cannot represent non-integer value: 1.5".to_string())
+        );
+    }
+    #[test]
+    fn coerce_input_object_fields() {
+        let mut schema_metadata = SchemaMetadata::default();
+        schema_metadata.input_object_fields.insert(
+            "ReviewInput".to_string(),
+            vec![
+                input_field("body", "String!", None),
+                input_field("stars", "Int", Some(sonic_rs::json!(5))),
+                input_field("tags", "[String!]", None),
+            ],
+        );
+
+        let mut value = sonic_rs::json!({ "body": "Great", "tags": "new" });
+        let result = coerce(&mut value, "ReviewInput!", &schema_metadata);
+        assert_eq!(result, Ok(()));
+        assert_eq!(value["stars"], sonic_rs::json!(5));
+        assert_eq!(value["tags"], sonic_rs::json!(["new"]));
+
+        let mut value = sonic_rs::json!({ "stars": 1 });
+        let result = coerce(&mut value, "ReviewInput!", &schema_metadata);
+        assert_eq!(
+            result,
+            Err("Variable \"$input\" got invalid value {\"stars\":1}; Field \"ReviewInput.body\" of required type \"String!\" was not provided.".to_string())
+        );
+
+        let mut value = sonic_rs::json!({ "body": "Great", "title": "Review" });
+        let result = coerce(&mut value, "ReviewInput!", &schema_metadata);
+        assert_eq!(
+            result,
+            Err("Variable \"$input\" got invalid value {\"body\":\"Great\",\"title\":\"Review\"}; Field \"title\" is not defined by type \"ReviewInput\".".to_string())
+        );
+
+        let mut value = sonic_rs::json!([{ "body": "Great", "tags": ["new", 1] }]);
+        let result = coerce(&mut value, "[ReviewInput]", &schema_metadata);
+        assert_eq!(
+            result,
+            Err("Variable \"$input\" got invalid value 1 at \"input[0].tags[1]\"; String cannot represent a non string value: 1".to_string())
+        );
+
+        let mut value = sonic_rs::json!("Great");
+        let result = coerce(&mut value, "ReviewInput", &schema_metadata);
+        assert_eq!(
+            result,
+            Err("Variable \"$input\" got invalid value \"Great\"; Expected type \"ReviewInput\" to be an object.".to_string())
+        );
+    }
+    #[test]
+    fn flag_the_values_changed_by_the_coercion() {
+        let mut schema_metadata = SchemaMetadata::default();
+        schema_metadata.input_object_fields.insert(
+            "ReviewInput".to_string(),
+            vec![
+                input_field("body", "String!", None),
+                input_field("stars", "Int", Some(sonic_rs::json!(5))),
+            ],
+        );
+        let type_node = TypeNode::try_from("[ReviewInput!]").unwrap();
+        let coerced = |mut value: sonic_rs::Value| {
+            let mut coerced = false;
+            super::coerce_runtime_value(&mut value, &type_node, &schema_metadata, &mut coerced)
+                .unwrap();
+            coerced
+        };
+
+        assert!(!coerced(sonic_rs::json!([{ "body": "Great", "stars": 4 }])));
+        assert!(coerced(sonic_rs::json!([{ "body": "Great" }])));
+        assert!(coerced(sonic_rs::json!({ "body": "Great", "stars": 4 })));
+    }
+    #[test]
+    fn disallow_unknown_enum_values() {
+        let mut schema_metadata = SchemaMetadata::default();
+        schema_metadata.enum_values.insert(
+            "Color".to_string(),
+            ["RED".to_string()].into_iter().collect(),
+        );
+
+        let mut value = sonic_rs::json!("RED");
+        assert_eq!(coerce(&mut value, "Color", &schema_metadata), Ok(()));
+
+        let mut value = sonic_rs::json!("BLUE");
+        assert_eq!(
+            coerce(&mut value, "Color", &schema_metadata),
+            Err("Variable \"$input\" got invalid value \"BLUE\"; Value \"BLUE\" does not exist in \"Color\" enum.".to_string())
+        );
+    }
+    #[test]
+    fn require_exactly_one_non_null_field_for_one_of_types() {
+        let mut schema_metadata = SchemaMetadata::default();
+        schema_metadata
+            .one_of_types
+            .insert("PaymentMethod".to_string());
+        schema_metadata.input_object_fields.insert(
+            "PaymentMethod".to_string(),
+            vec![
+                input_field("card", "String", None),
+                input_field("paypal", "String", None),
+            ],
+        );
+
+        let mut value = sonic_rs::json!({ "card": "1234" });
+        let result = coerce(&mut value, "PaymentMethod", &schema_metadata);
+        assert_eq!(result, Ok(()));
+
+        let mut value = sonic_rs::json!({ "card": "1234", "paypal": "john@doe.com" });
+        let result = coerce(&mut value, "PaymentMethod", &schema_metadata);
+        assert_eq!(
+            result,
+            Err("Variable \"$input\" got invalid value {\"card\":\"1234\",\"paypal\":\"john@doe.com\"}; Exactly one key must be specified for OneOf type \"PaymentMethod\".".to_string())
+        );
+
+        let mut value = sonic_rs::json!({});
+        let result = coerce(&mut value, "PaymentMethod", &schema_metadata);
         assert!(result.is_err());
+
+        let mut value = sonic_rs::json!({ "card": null });
+        let result = coerce(&mut value, "PaymentMethod", &schema_metadata);
+        assert_eq!(
+            result,
+            Err("Variable \"$input\" got invalid value {\"card\":null}; Field \"PaymentMethod.card\" must be non-null.".to_string())
+        );
     }
 }
@@ -0,0 +1,98 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use ntex::util::Bytes;
+use sonic_rs::Value;
+
+use crate::execution::plan::VariablesMap;
+
+/// The JSON of the variables, as sent by the client in the body of the request, by name.
+pub type RawVariables = HashMap<String, Bytes>;
+
+/// The JSON of the variables of a request, written as-is into the body of every subgraph request using them.
+///
+/// The JSON sent by the client is kept for the variables the router left as they were sent.
+/// The other variables are serialized the first time a subgraph request uses them,
+/// once for the whole request, including every event of a subscription.
+#[derive(Clone, Debug, Default)]
+pub struct SerializedVariables {
+    raw: RawVariables,
+    serialized: HashMap<String, OnceLock<Option<Vec<u8>>>>,
+}
+
+impl SerializedVariables {
+    pub fn new(variables: &Option<VariablesMap>, mut raw: RawVariables) -> Self {
+        let Some(variables) = variables else {
+            return Self::default();
+        };
+
+        // the client may send variables the operation doesn't define
+        raw.retain(|name, _| variables.contains_key(name));
+        let serialized = variables
+            .keys()
+            .filter(|name| !raw.contains_key(*name))
+            .map(|name| (name.clone(), OnceLock::new()))
+            .collect();
+
+        Self { raw, serialized }
+    }
+
+    /// The JSON of the variable, as sent by the client, or serialized from its value on first use.
+    /// The value must be the one of the variables of the request, not a value replaced since.
+    pub fn get(&self, name: &str, value: &Value) -> Option<&[u8]> {
+        if let Some(raw) = self.raw.get(name) {
+            return Some(raw);
+        }
+
+        self.serialized
+            .get(name)?
+            // Variables failing to serialize are serialized again, and reported, by the request
+            .get_or_init(|| sonic_rs::to_vec(value).ok())
+            .as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex::util::Bytes;
+    use sonic_rs::json;
+
+    use super::{RawVariables, SerializedVariables};
+    use crate::execution::plan::VariablesMap;
+
+    #[test]
+    fn serializes_the_variables_on_first_use() {
+        let variables = Some(VariablesMap::from([
+            ("id".to_string(), json!("1")),
+            ("filter".to_string(), json!({ "first": 2 })),
+        ]));
+        let serialized = SerializedVariables::new(&variables, RawVariables::new());
+        let values = variables.as_ref().unwrap();
+
+        assert_eq!(serialized.get("id", &values["id"]), Some(&b"\"1\""[..]));
+        assert_eq!(
+            serialized.get("filter", &values["filter"]),
+            Some(&b"{\"first\":2}"[..])
+        );
+        // serialized once, the value of the first use is kept
+        assert_eq!(serialized.get("id", &json!("2")), Some(&b"\"1\""[..]));
+        assert_eq!(serialized.get("missing", &json!("1")), None);
+    }
+
+    #[test]
+    fn forwards_the_json_sent_by_the_client() {
+        let variables = Some(VariablesMap::from([
+            ("id".to_string(), json!("1")),
+            ("first".to_string(), json!(2)),
+        ]));
+        let raw = RawVariables::from([
+            ("id".to_string(), Bytes::from_static(b"\"1\"")),
+            ("unused".to_string(), Bytes::from_static(b"true")),
+        ]);
+        let serialized = SerializedVariables::new(&variables, raw);
+        let values = variables.as_ref().unwrap();
+
+        assert_eq!(serialized.get("id", &values["id"]), Some(&b"\"1\""[..]));
+        assert_eq!(serialized.get("first", &values["first"]), Some(&b"2"[..]));
+        assert_eq!(serialized.get("unused", &json!(true)), None);
+    }
+}
@@ -7,8 +7,8 @@ use crate::static_graphql::query::{
     VariableDefinition,
 };
 use crate::static_graphql::schema::{
-    self, DirectiveDefinition, EnumValue, Field, InputValue, InterfaceType, ObjectType,
-    TypeDefinition, TypeExtension, UnionType,
+    self, DirectiveDefinition, EnumValue, Field, InputObjectType, InputValue, InterfaceType,
+    ObjectType, TypeDefinition, TypeExtension, UnionType,
 };
 
 lazy_static! {
@@ -393,6 +393,14 @@ impl UnionType {
     }
 }
 
+impl InputObjectType {
+    pub fn is_one_of(&self) -> bool {
+        self.directives
+            .iter()
+            .any(|directive| directive.name == "oneOf")
+    }
+}
+
 impl InterfaceType {
     pub fn is_implemented_by(&self, other_type: &TypeDefinition) -> bool {
         other_type.interfaces().iter().any(|v| self.name.eq(v))
@@ -92,6 +92,8 @@ pub mod schema;
 pub use format::Style;
 pub use position::Pos;
 pub use query::parse_query;
+pub use query::parse_query_with_limits;
 pub use query::parse_query_with_token_limit;
 pub use query::{minify_query, minify_query_document};
 pub use schema::parse_schema;
+pub use tokenizer::DEFAULT_RECURSION_LIMIT;
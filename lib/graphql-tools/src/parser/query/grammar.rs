@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use combine::{eof, many1, optional, position, StdParseResult};
 use combine::{parser, Parser};
 
@@ -269,6 +271,22 @@ where
     handle_token_stream(tokens)
 }
 
+/// Parses a piece of query language with limits on the number of tokens,
+/// on the nesting of brackets and on the time spent parsing, and returns an AST
+pub fn parse_query_with_limits<'a, S>(
+    s: &'a str,
+    token_limit: Option<usize>,
+    recursion_limit: usize,
+    time_limit: Option<Duration>,
+) -> Result<Document<'a, S>, ParseError>
+where
+    S: Text<'a>,
+{
+    let tokens = TokenStream::with_recursion_limit(s, recursion_limit, token_limit)
+        .with_deadline(time_limit.map(|time_limit| Instant::now() + time_limit));
+    handle_token_stream(tokens)
+}
+
 fn handle_token_stream<'a, S>(mut tokens: TokenStream<'a>) -> Result<Document<'a, S>, ParseError>
 where
     S: Text<'a>,
@@ -422,4 +440,35 @@ mod test {
             "Parse error at 1:114\nExpected ]\nRecursion limit exceeded\n"
         )
     }
+
+    #[test]
+    fn recursion_limit() {
+        let query = format!("{}{}", "{ a ".repeat(5), "}".repeat(5));
+        assert!(parse_query_with_limits::<&str>(&query, None, 5, None).is_ok());
+
+        let err = parse_query_with_limits::<&str>(&query, None, 4, None).unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "Parse error at 1:17\nExpected }\nRecursion limit exceeded\n"
+        );
+    }
+
+    #[test]
+    fn token_and_recursion_limits() {
+        let err = parse_query_with_limits::<&str>("{ a { b } }", Some(3), 50, None).unwrap_err();
+        assert!(format!("{}", err).contains("Token limit exceeded"));
+    }
+
+    #[test]
+    fn time_limit() {
+        let query = format!("{{ {} }}", "a ".repeat(1000));
+        assert!(
+            parse_query_with_limits::<&str>(&query, None, 50, Some(Duration::from_secs(60)))
+                .is_ok()
+        );
+
+        let err =
+            parse_query_with_limits::<&str>(&query, None, 50, Some(Duration::ZERO)).unwrap_err();
+        assert!(format!("{}", err).contains("Parse timeout exceeded"));
+    }
 }
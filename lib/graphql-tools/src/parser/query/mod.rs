@@ -8,5 +8,7 @@ mod minify;
 
 pub use self::ast::*;
 pub use self::error::ParseError;
-pub use self::grammar::{consume_definition, parse_query, parse_query_with_token_limit};
+pub use self::grammar::{
+    consume_definition, parse_query, parse_query_with_limits, parse_query_with_token_limit,
+};
 pub use self::minify::{minify_query, minify_query_document};
@@ -1,7 +1,7 @@
 use combine::easy::{Error, Errors};
 use combine::error::StreamError;
 use combine::sep_by1;
-use combine::{choice, eof, many, many1, optional, position};
+use combine::{attempt, choice, eof, many, many1, optional, position};
 use combine::{parser, Parser, StdParseResult};
 
 use super::ast::*;
@@ -19,9 +19,14 @@ where
     (
         position().skip(ident("schema")),
         parser(directives),
-        punct("{")
-            .with(many((kind(T::Name).skip(punct(":")), name::<'a, S>())))
-            .skip(punct("}")),
+        // the root operation types are optional, as in the schemas only applying directives
+        // like `schema @link(...)`
+        optional(
+            punct("{")
+                .with(many((kind(T::Name).skip(punct(":")), name::<'a, S>())))
+                .skip(punct("}")),
+        )
+        .map(Option::unwrap_or_default),
     )
         .flat_map(
             |(position, directives, operations): (_, _, Vec<(Token, _)>)| {
@@ -671,6 +676,19 @@ where
         .into_result()
 }
 
+/// Parses `extend schema` as a schema definition, as it is commonly used by subgraphs
+/// to apply `@link` to their schema.
+pub fn schema_extension<'a, S>(
+    input: &mut TokenStream<'a>,
+) -> StdParseResult<SchemaDefinition<'a, S>, TokenStream<'a>>
+where
+    S: Text<'a>,
+{
+    attempt(ident("extend").with(parser(schema)))
+        .parse_stream(input)
+        .into_result()
+}
+
 pub fn definition<'a, T>(
     input: &mut TokenStream<'a>,
 ) -> StdParseResult<Definition<'a, T>, TokenStream<'a>>
@@ -679,6 +697,7 @@ where
 {
     choice((
         parser(schema).map(Definition::SchemaDefinition),
+        parser(schema_extension).map(Definition::SchemaDefinition),
         parser(type_extension).map(Definition::TypeExtension),
         parser(described_definition),
     ))
@@ -705,7 +724,7 @@ where
 #[cfg(test)]
 mod test {
     use super::parse_schema;
-    use super::{Definition, Document, SchemaDefinition};
+    use super::{Definition, Directive, Document, SchemaDefinition, Value};
     use crate::parser::position::Pos;
 
     fn ast<'a>(s: &'a str) -> Document<'a, String> {
@@ -725,4 +744,37 @@ mod test {
             })])
         );
     }
+    #[test]
+    fn schema_without_operations() {
+        assert_eq!(
+            ast("extend schema @link(url: \"https://specs.apollo.dev/federation/v2.5\")"),
+            Document::new(vec![Definition::SchemaDefinition(SchemaDefinition {
+                position: Pos { line: 1, column: 8 },
+                directives: vec![Directive {
+                    position: Pos {
+                        line: 1,
+                        column: 15
+                    },
+                    name: "link".into(),
+                    arguments: vec![(
+                        "url".into(),
+                        Value::String("https://specs.apollo.dev/federation/v2.5".into())
+                    )],
+                }],
+                query: None,
+                mutation: None,
+                subscription: None
+            })])
+        );
+    }
+
+    #[test]
+    fn type_extension_after_schema_extension() {
+        let document = ast("extend schema @link(url: \"a\")\nextend type Query { a: String }");
+        assert_eq!(document.definitions.len(), 2);
+        assert!(matches!(
+            document.definitions[1],
+            Definition::TypeExtension(_)
+        ));
+    }
 }
@@ -1,4 +1,5 @@
 use std::fmt::{self};
+use std::time::Instant;
 
 use combine::easy::{Error, Errors, Info};
 use combine::error::StreamError;
@@ -32,6 +33,7 @@ pub struct TokenStream<'a> {
     recursion_limit: usize,
     token_limit: Option<usize>,
     token_count: usize,
+    deadline: Option<Instant>,
 }
 
 impl TokenStream<'_> {
@@ -130,13 +132,19 @@ fn check_float(value: &str, exponent: Option<usize>, real: Option<usize>) -> boo
     }
 }
 
+/// The default limit of nested brackets (selection sets, arguments, lists and objects).
+pub const DEFAULT_RECURSION_LIMIT: usize = 50;
+
+// Number of tokens read between two checks of the parse deadline
+const DEADLINE_CHECK_INTERVAL: usize = 64;
+
 impl<'a> TokenStream<'a> {
     pub fn new(s: &'a str) -> TokenStream<'a> {
-        Self::with_recursion_limit(s, 50, None)
+        Self::with_recursion_limit(s, DEFAULT_RECURSION_LIMIT, None)
     }
 
     pub fn new_with_token_limit(s: &'a str, token_limit: usize) -> TokenStream<'a> {
-        Self::with_recursion_limit(s, 50, Some(token_limit))
+        Self::with_recursion_limit(s, DEFAULT_RECURSION_LIMIT, Some(token_limit))
     }
 
     /// Specify a limit to recursive parsing. Note that increasing the limit
@@ -155,11 +163,19 @@ impl<'a> TokenStream<'a> {
             recursion_limit,
             token_limit,
             token_count: 0,
+            deadline: None,
         };
         me.skip_whitespace();
         me
     }
 
+    /// Specify a point in time after which the parsing fails,
+    /// so the time spent on a single document is bounded.
+    pub(crate) fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
     /// Convenience for the common case where a token does
     /// not span multiple lines. Infallible.
     #[inline]
@@ -167,7 +183,7 @@ impl<'a> TokenStream<'a> {
         self.position.column += size;
         self.off += size;
         // Should be counted?
-        if self.token_limit.is_some() {
+        if self.token_limit.is_some() || self.deadline.is_some() {
             self.token_count += 1;
         }
         Ok((kind, size))
@@ -179,6 +195,14 @@ impl<'a> TokenStream<'a> {
                 return Err(Error::message_static_message("Token limit exceeded"));
             }
         }
+        if let Some(deadline) = self.deadline {
+            // Reading the clock for every token would slow down the parsing
+            if self.token_count.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+                && Instant::now() >= deadline
+            {
+                return Err(Error::message_static_message("Parse timeout exceeded"));
+            }
+        }
         use self::Kind::*;
         let mut iter = self.buf[self.off..].char_indices();
         let cur_char = match iter.next() {
@@ -1,6 +1,7 @@
 use crate::parser::schema::TypeDefinition;
 
-use crate::static_graphql::query::Value;
+use crate::static_graphql::query::{Definition, Value};
+use crate::static_graphql::schema::InputObjectType;
 use crate::validation::utils::ValidationError;
 use crate::{
     ast::{OperationVisitor, OperationVisitorContext},
@@ -26,6 +27,66 @@ impl ValuesOfCorrectType {
         !matches!(type_name, "String" | "Int" | "Float" | "Boolean" | "ID")
     }
 
+    /// Values of `@oneOf` input objects must set exactly one field, to a non-null value.
+    /// A variable used as that field must be of a non-null type.
+    pub fn validate_one_of_value(
+        &self,
+        visitor_context: &OperationVisitorContext,
+        user_context: &mut ValidationErrorContext,
+        input_object_def: &InputObjectType,
+        object_value: &[(String, Value)],
+    ) {
+        let [(field_name, field_value)] = object_value else {
+            user_context.report_error(ValidationError {
+                error_code: self.error_code(),
+                message: format!(
+                    "OneOf Input Object \"{}\" must specify exactly one key.",
+                    input_object_def.name
+                ),
+                locations: vec![],
+            });
+            return;
+        };
+
+        match field_value {
+            Value::Null => user_context.report_error(ValidationError {
+                error_code: self.error_code(),
+                message: format!(
+                    "Field \"{}.{}\" must be non-null.",
+                    input_object_def.name, field_name
+                ),
+                locations: vec![],
+            }),
+            Value::Variable(variable_name) => {
+                let is_nullable = visitor_context
+                    .operation
+                    .definitions
+                    .iter()
+                    .filter_map(|definition| match definition {
+                        Definition::Operation(operation) => Some(operation),
+                        _ => None,
+                    })
+                    .flat_map(|operation| operation.variable_definitions())
+                    .any(|variable_definition| {
+                        variable_definition.name.eq(variable_name)
+                            && !variable_definition.var_type.is_non_null()
+                    });
+
+                if is_nullable {
+                    user_context.report_error(ValidationError {
+                        error_code: self.error_code(),
+                        message: format!(
+                            "Variable \"${}\" must be non-nullable to be used for OneOf Input Object \"{}\".",
+                            variable_name, input_object_def.name
+                        ),
+                        locations: vec![],
+                    })
+                }
+            }
+            _ => {}
+        }
+    }
+
     pub fn validate_value(
         &mut self,
         visitor_context: &mut OperationVisitorContext,
@@ -158,6 +219,15 @@ impl<'doc> OperationVisitor<'doc, ValidationErrorContext> for ValuesOfCorrectTyp
                     })
                 }
             });
+
+            if input_object_def.is_one_of() {
+                self.validate_one_of_value(
+                    visitor_context,
+                    user_context,
+                    input_object_def,
+                    object_value,
+                );
+            }
         }
     }
 
@@ -1966,3 +2036,89 @@ fn reports_original_error_for_custom_scalar_which_throws() {
     let messages = get_messages(&errors);
     assert_eq!(messages.len(), 0);
 }
+
+#[cfg(test)]
+static ONE_OF_SCHEMA: &str = "
+  input PaymentMethod @oneOf {
+    card: String
+    paypal: String
+  }
+  type Query { pay(method: PaymentMethod): Boolean }";
+
+#[test]
+fn valid_one_of_value() {
+    use crate::validation::test_utils::*;
+
+    let mut plan = create_plan_from_rule(Box::new(ValuesOfCorrectType::new()));
+    let errors = test_operation_with_schema(
+        "query Pay($card: String!) {
+          literal: pay(method: { card: \"1234\" })
+          variable: pay(method: { card: $card })
+        }",
+        ONE_OF_SCHEMA,
+        &mut plan,
+    );
+
+    let messages = get_messages(&errors);
+    assert_eq!(messages.len(), 0);
+}
+
+#[test]
+fn invalid_one_of_value_with_multiple_fields() {
+    use crate::validation::test_utils::*;
+
+    let mut plan = create_plan_from_rule(Box::new(ValuesOfCorrectType::new()));
+    let errors = test_operation_with_schema(
+        "{
+          pay(method: { card: \"1234\", paypal: \"john@doe.com\" })
+        }",
+        ONE_OF_SCHEMA,
+        &mut plan,
+    );
+
+    let messages = get_messages(&errors);
+    assert_eq!(
+        messages,
+        vec!["OneOf Input Object \"PaymentMethod\" must specify exactly one key."]
+    );
+}
+
+#[test]
+fn invalid_one_of_value_with_null_field() {
+    use crate::validation::test_utils::*;
+
+    let mut plan = create_plan_from_rule(Box::new(ValuesOfCorrectType::new()));
+    let errors = test_operation_with_schema(
+        "{
+          pay(method: { card: null })
+        }",
+        ONE_OF_SCHEMA,
+        &mut plan,
+    );
+
+    let messages = get_messages(&errors);
+    assert_eq!(
+        messages,
+        vec!["Field \"PaymentMethod.card\" must be non-null."]
+    );
+}
+
+#[test]
+fn invalid_one_of_value_with_nullable_variable() {
+    use crate::validation::test_utils::*;
+
+    let mut plan = create_plan_from_rule(Box::new(ValuesOfCorrectType::new()));
+    let errors = test_operation_with_schema(
+        "query Pay($card: String) {
+          pay(method: { card: $card })
+        }",
+        ONE_OF_SCHEMA,
+        &mut plan,
+    );
+
+    let messages = get_messages(&errors);
+    assert_eq!(
+        messages,
+        vec!["Variable \"$card\" must be non-nullable to be used for OneOf Input Object \"PaymentMethod\"."]
+    );
+}
@@ -85,6 +85,10 @@ impl CompileExpression for str {
 /// target values, and error handling.
 pub trait ExecutableProgram {
     fn execute(&self, value: VrlValue) -> Result<VrlValue, ExpressionExecutionError>;
+
+    /// Same as `execute`, but the changes made by the program to the target (`.`)
+    /// are kept in `value`, for programs assigning fields instead of returning a value.
+    fn execute_in_place(&self, value: &mut VrlValue) -> Result<VrlValue, ExpressionExecutionError>;
 }
 
 impl ExecutableProgram for VrlProgram {
@@ -101,6 +105,21 @@ impl ExecutableProgram for VrlProgram {
 
         Ok(self.resolve(&mut ctx)?)
     }
+
+    fn execute_in_place(&self, value: &mut VrlValue) -> Result<VrlValue, ExpressionExecutionError> {
+        let mut target = VrlTargetValue {
+            value: std::mem::replace(value, VrlValue::Null),
+            metadata: VrlValue::Object(BTreeMap::new()),
+            secrets: VrlSecrets::default(),
+        };
+
+        let mut state = VrlState::default();
+        let mut ctx = VrlContext::new(&mut target, &mut state, &VRL_TIMEZONE);
+        let result = self.resolve(&mut ctx);
+
+        *value = target.value;
+        Ok(result?)
+    }
 }
 
 #[derive(Debug, Default, Clone)]
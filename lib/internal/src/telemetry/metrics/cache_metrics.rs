@@ -16,6 +16,7 @@ pub struct CacheMetrics {
     pub validate: CacheMetricSet,
     pub normalize: CacheMetricSet,
     pub plan: CacheMetricSet,
+    pub entity: CacheMetricSet,
 }
 
 impl CacheMetrics {
@@ -49,6 +50,13 @@ impl CacheMetrics {
                 names::PLAN_CACHE_SIZE,
                 "Plan",
             ),
+            entity: CacheMetricSet::new(
+                meter,
+                names::ENTITY_CACHE_REQUESTS_TOTAL,
+                names::ENTITY_CACHE_DURATION,
+                names::ENTITY_CACHE_SIZE,
+                "Entity",
+            ),
         }
     }
 }
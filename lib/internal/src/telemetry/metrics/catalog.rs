@@ -48,6 +48,22 @@ pub mod values {
         }
     }
 
+    #[derive(Clone, Copy, Debug, strum::IntoStaticStr)]
+    pub enum PlanningStatus {
+        #[strum(serialize = "ok")]
+        Ok,
+        #[strum(serialize = "error")]
+        Error,
+        #[strum(serialize = "timeout")]
+        Timeout,
+    }
+
+    impl PlanningStatus {
+        pub fn as_str(self) -> &'static str {
+            self.into()
+        }
+    }
+
     #[derive(Clone, Copy, Debug, strum::IntoStaticStr)]
     pub enum CacheResult {
         #[strum(serialize = "hit")]
@@ -62,6 +78,71 @@ pub mod values {
         }
     }
 
+    /// The operation of a plugin on the plugin storage.
+    #[derive(Clone, Copy, Debug, strum::IntoStaticStr)]
+    pub enum PluginStorageOperation {
+        #[strum(serialize = "get")]
+        Get,
+        #[strum(serialize = "set")]
+        Set,
+        #[strum(serialize = "delete")]
+        Delete,
+    }
+
+    impl PluginStorageOperation {
+        pub fn as_str(self) -> &'static str {
+            self.into()
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, strum::IntoStaticStr)]
+    pub enum PluginStorageResult {
+        #[strum(serialize = "hit")]
+        Hit,
+        #[strum(serialize = "miss")]
+        Miss,
+        #[strum(serialize = "ok")]
+        Ok,
+        #[strum(serialize = "error")]
+        Error,
+    }
+
+    impl PluginStorageResult {
+        pub fn as_str(self) -> &'static str {
+            self.into()
+        }
+    }
+
+    /// Whether a request of a client was allowed or rejected by the rate limiting.
+    #[derive(Clone, Copy, Debug, strum::IntoStaticStr)]
+    pub enum RateLimitResult {
+        #[strum(serialize = "allowed")]
+        Allowed,
+        #[strum(serialize = "limited")]
+        Limited,
+    }
+
+    impl RateLimitResult {
+        pub fn as_str(self) -> &'static str {
+            self.into()
+        }
+    }
+
+    /// Whether the compressed body was sent to, or received from a subgraph.
+    #[derive(Clone, Copy, Debug, strum::IntoStaticStr)]
+    pub enum CompressionDirection {
+        #[strum(serialize = "request")]
+        Request,
+        #[strum(serialize = "response")]
+        Response,
+    }
+
+    impl CompressionDirection {
+        pub fn as_str(self) -> &'static str {
+            self.into()
+        }
+    }
+
     /// Why a client subscription ended, recorded on the `ended_total` counter.
     ///
     /// Defaults to `ClientDisconnected` when a guard drops without an explicit
@@ -141,6 +222,10 @@ pub mod labels {
     pub const COPROCESSOR_STAGE: &str = "coprocessor.stage";
     pub const CIRCUIT_BREAKER_FROM_STATE: &str = "circuit_breaker.from_state";
     pub const CIRCUIT_BREAKER_TO_STATE: &str = "circuit_breaker.to_state";
+    pub const COMPRESSION_DIRECTION: &str = "compression.direction";
+    pub const COMPRESSION_ENCODING: &str = "compression.encoding";
+    pub const PLUGIN_NAME: &str = "plugin.name";
+    pub const PLUGIN_STORAGE_OPERATION: &str = "plugin_storage.operation";
 }
 
 pub mod units {
@@ -154,6 +239,8 @@ pub mod units {
 
 pub mod names {
     pub const GRAPHQL_ERRORS_TOTAL: &str = "hive.router.graphql.errors_total";
+    pub const GRAPHQL_PLANNING_DURATION: &str = "hive.router.graphql.planning.duration";
+    pub const GRAPHQL_EXECUTION_DURATION: &str = "hive.router.graphql.execution.duration";
     pub const COST_ESTIMATED: &str = "cost.estimated";
     pub const COST_ACTUAL: &str = "cost.actual";
     pub const COST_DELTA: &str = "cost.delta";
@@ -180,12 +267,18 @@ pub mod names {
     pub const PLAN_CACHE_REQUESTS_TOTAL: &str = "hive.router.plan_cache.requests_total";
     pub const PLAN_CACHE_DURATION: &str = "hive.router.plan_cache.duration";
     pub const PLAN_CACHE_SIZE: &str = "hive.router.plan_cache.size";
+    pub const ENTITY_CACHE_REQUESTS_TOTAL: &str = "hive.router.entity_cache.requests_total";
+    pub const ENTITY_CACHE_DURATION: &str = "hive.router.entity_cache.duration";
+    pub const ENTITY_CACHE_SIZE: &str = "hive.router.entity_cache.size";
     pub const CIRCUIT_BREAKER_SHORT_CIRCUITS_TOTAL: &str =
         "hive.router.circuit_breaker.short_circuits_total";
     pub const CIRCUIT_BREAKER_STATE: &str = "hive.router.circuit_breaker.state";
     pub const CIRCUIT_BREAKER_STATE_TRANSITIONS_TOTAL: &str =
         "hive.router.circuit_breaker.state_transitions_total";
     pub const CIRCUIT_BREAKER_FAILURES_TOTAL: &str = "hive.router.circuit_breaker.failures_total";
+    pub const SUBGRAPH_RETRIES_TOTAL: &str = "hive.router.subgraph.retries_total";
+    pub const SUBGRAPH_COMPRESSION_SAVED_BYTES_TOTAL: &str =
+        "hive.router.subgraph.compression.saved_bytes_total";
     pub const PERSISTED_DOCUMENTS_STORAGE_FAILURES_TOTAL: &str =
         "hive.router.persisted_documents.storage.failures_total";
     pub const PERSISTED_DOCUMENTS_EXTRACT_MISSING_ID_TOTAL: &str =
@@ -213,6 +306,9 @@ pub mod names {
         "hive.router.subscriptions.clients.lagged_messages_total";
     pub const SUBSCRIPTIONS_CLIENTS_SENT_MESSAGES_TOTAL: &str =
         "hive.router.subscriptions.clients.sent_messages_total";
+    pub const PLUGIN_STORAGE_OPERATIONS_TOTAL: &str = "hive.router.plugin_storage.operations_total";
+    pub const PLUGIN_STORAGE_DURATION: &str = "hive.router.plugin_storage.duration";
+    pub const RATE_LIMIT_REQUESTS_TOTAL: &str = "hive.router.rate_limit.requests_total";
 }
 
 pub(crate) const METRIC_SPECS: &[(&str, &[&str])] = &[
@@ -264,6 +360,14 @@ pub(crate) const METRIC_SPECS: &[(&str, &[&str])] = &[
         &[labels::SUBSCRIPTION_TRANSPORT],
     ),
     (names::GRAPHQL_ERRORS_TOTAL, &[labels::CODE]),
+    (names::GRAPHQL_PLANNING_DURATION, &[labels::STATUS]),
+    (
+        names::GRAPHQL_EXECUTION_DURATION,
+        &[
+            labels::GRAPHQL_OPERATION_TYPE,
+            labels::GRAPHQL_RESPONSE_STATUS,
+        ],
+    ),
     (
         names::COST_ESTIMATED,
         &[labels::COST_RESULT, labels::GRAPHQL_OPERATION_NAME],
@@ -399,6 +503,9 @@ pub(crate) const METRIC_SPECS: &[(&str, &[&str])] = &[
     (names::PLAN_CACHE_REQUESTS_TOTAL, &[labels::RESULT]),
     (names::PLAN_CACHE_DURATION, &[labels::RESULT]),
     (names::PLAN_CACHE_SIZE, &[]),
+    (names::ENTITY_CACHE_REQUESTS_TOTAL, &[labels::RESULT]),
+    (names::ENTITY_CACHE_DURATION, &[labels::RESULT]),
+    (names::ENTITY_CACHE_SIZE, &[]),
     (
         names::CIRCUIT_BREAKER_SHORT_CIRCUITS_TOTAL,
         &[labels::SUBGRAPH_NAME],
@@ -416,6 +523,15 @@ pub(crate) const METRIC_SPECS: &[(&str, &[&str])] = &[
         names::CIRCUIT_BREAKER_FAILURES_TOTAL,
         &[labels::SUBGRAPH_NAME],
     ),
+    (names::SUBGRAPH_RETRIES_TOTAL, &[labels::SUBGRAPH_NAME]),
+    (
+        names::SUBGRAPH_COMPRESSION_SAVED_BYTES_TOTAL,
+        &[
+            labels::SUBGRAPH_NAME,
+            labels::COMPRESSION_DIRECTION,
+            labels::COMPRESSION_ENCODING,
+        ],
+    ),
     (names::PERSISTED_DOCUMENTS_STORAGE_FAILURES_TOTAL, &[]),
     (names::PERSISTED_DOCUMENTS_EXTRACT_MISSING_ID_TOTAL, &[]),
     (
@@ -427,6 +543,23 @@ pub(crate) const METRIC_SPECS: &[(&str, &[&str])] = &[
         names::COPROCESSOR_ERRORS_TOTAL,
         &[labels::COPROCESSOR_STAGE],
     ),
+    (
+        names::PLUGIN_STORAGE_OPERATIONS_TOTAL,
+        &[
+            labels::PLUGIN_NAME,
+            labels::PLUGIN_STORAGE_OPERATION,
+            labels::RESULT,
+        ],
+    ),
+    (
+        names::PLUGIN_STORAGE_DURATION,
+        &[
+            labels::PLUGIN_NAME,
+            labels::PLUGIN_STORAGE_OPERATION,
+            labels::RESULT,
+        ],
+    ),
+    (names::RATE_LIMIT_REQUESTS_TOTAL, &[labels::RESULT]),
 ];
 
 pub fn labels_for(metric_name: &str) -> Option<&'static [&'static str]> {
@@ -0,0 +1,59 @@
+use opentelemetry::{
+    metrics::{Counter, Meter},
+    KeyValue,
+};
+
+use crate::telemetry::metrics::catalog::{labels, names, units, values::CompressionDirection};
+
+#[cfg(debug_assertions)]
+use crate::telemetry::metrics::catalog::debug_assert_attrs;
+
+/// Tracks the bytes saved by compressing the requests sent to the subgraphs, and their responses.
+pub struct CompressionMetrics {
+    saved_bytes: Option<Counter<u64>>,
+}
+
+impl CompressionMetrics {
+    pub fn new(meter: Option<&Meter>) -> Self {
+        let saved_bytes = meter.map(|meter| {
+            meter
+                .u64_counter(names::SUBGRAPH_COMPRESSION_SAVED_BYTES_TOTAL)
+                .with_unit(units::BYTES)
+                .with_description(
+                    "Number of bytes not transferred thanks to the compression \
+                     of the subgraph requests and responses.",
+                )
+                .build()
+        });
+
+        Self { saved_bytes }
+    }
+
+    /// Records the difference between the size of a body and the size of its compressed form.
+    pub fn record_compressed_body(
+        &self,
+        subgraph_name: &str,
+        direction: CompressionDirection,
+        encoding: &'static str,
+        original_size: usize,
+        compressed_size: usize,
+    ) {
+        let Some(counter) = &self.saved_bytes else {
+            return;
+        };
+
+        let attributes = [
+            KeyValue::new(labels::SUBGRAPH_NAME, subgraph_name.to_string()),
+            KeyValue::new(labels::COMPRESSION_DIRECTION, direction.as_str()),
+            KeyValue::new(labels::COMPRESSION_ENCODING, encoding),
+        ];
+
+        #[cfg(debug_assertions)]
+        debug_assert_attrs(names::SUBGRAPH_COMPRESSION_SAVED_BYTES_TOTAL, &attributes);
+
+        counter.add(
+            original_size.saturating_sub(compressed_size) as u64,
+            &attributes,
+        );
+    }
+}
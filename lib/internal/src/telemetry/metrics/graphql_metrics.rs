@@ -1,14 +1,29 @@
+use std::time::Instant;
+
 use opentelemetry::{
-    metrics::{Counter, Meter},
+    metrics::{Counter, Histogram, Meter},
     KeyValue,
 };
 
+use crate::telemetry::metrics::capture::Capture;
 #[cfg(debug_assertions)]
 use crate::telemetry::metrics::catalog::debug_assert_attrs;
 use crate::telemetry::metrics::catalog::{labels, names, values};
 
 struct GraphQLInstruments {
     errors_total: Option<Counter<u64>>,
+    planning_duration: Option<Histogram<f64>>,
+    execution_duration: Option<Histogram<f64>>,
+}
+
+pub struct GraphQLPlanningState<'a> {
+    histogram: &'a Histogram<f64>,
+    started_at: Instant,
+}
+
+pub struct GraphQLExecutionState<'a> {
+    histogram: &'a Histogram<f64>,
+    started_at: Instant,
 }
 
 pub struct GraphQLMetrics {
@@ -30,8 +45,47 @@ impl GraphQLMetrics {
                 .build()
         });
 
+        let planning_duration = meter.map(|meter| {
+            meter
+                .f64_histogram(names::GRAPHQL_PLANNING_DURATION)
+                .with_unit("s")
+                .with_description("Duration of query planning, on plan cache misses")
+                .build()
+        });
+        let execution_duration = meter.map(|meter| {
+            meter
+                .f64_histogram(names::GRAPHQL_EXECUTION_DURATION)
+                .with_unit("s")
+                .with_description("Duration of query plan execution, including subgraph requests")
+                .build()
+        });
+
         Self {
-            instruments: GraphQLInstruments { errors_total },
+            instruments: GraphQLInstruments {
+                errors_total,
+                planning_duration,
+                execution_duration,
+            },
+        }
+    }
+
+    pub fn capture_planning<'a>(&'a self) -> Capture<GraphQLPlanningState<'a>> {
+        match &self.instruments.planning_duration {
+            Some(histogram) => Capture::enabled(GraphQLPlanningState {
+                histogram,
+                started_at: Instant::now(),
+            }),
+            None => Capture::disabled(),
+        }
+    }
+
+    pub fn capture_execution<'a>(&'a self) -> Capture<GraphQLExecutionState<'a>> {
+        match &self.instruments.execution_duration {
+            Some(histogram) => Capture::enabled(GraphQLExecutionState {
+                histogram,
+                started_at: Instant::now(),
+            }),
+            None => Capture::disabled(),
         }
     }
 
@@ -72,3 +126,46 @@ impl GraphQLErrorMetricsRecorder {
         }
     }
 }
+
+impl Capture<GraphQLPlanningState<'_>> {
+    pub fn finish(self, status: values::PlanningStatus) {
+        let Some(state) = self.take() else {
+            return;
+        };
+
+        let attributes = [KeyValue::new(labels::STATUS, status.as_str())];
+
+        #[cfg(debug_assertions)]
+        debug_assert_attrs(names::GRAPHQL_PLANNING_DURATION, &attributes);
+        state
+            .histogram
+            .record(state.started_at.elapsed().as_secs_f64(), &attributes);
+    }
+}
+
+impl Capture<GraphQLExecutionState<'_>> {
+    /// Records the duration of the execution,
+    /// with the attributes of `telemetry.instrumentation` after the labels of the catalog.
+    pub fn finish(
+        self,
+        operation_type: &str,
+        response_status: values::GraphQLResponseStatus,
+        custom_attributes: &[KeyValue],
+    ) {
+        let Some(state) = self.take() else {
+            return;
+        };
+
+        let mut attributes = vec![
+            KeyValue::new(labels::GRAPHQL_OPERATION_TYPE, operation_type.to_string()),
+            KeyValue::new(labels::GRAPHQL_RESPONSE_STATUS, response_status.as_str()),
+        ];
+
+        #[cfg(debug_assertions)]
+        debug_assert_attrs(names::GRAPHQL_EXECUTION_DURATION, &attributes);
+        attributes.extend_from_slice(custom_attributes);
+        state
+            .histogram
+            .record(state.started_at.elapsed().as_secs_f64(), &attributes);
+    }
+}
@@ -2,12 +2,17 @@ pub mod cache_metrics;
 mod capture;
 pub mod catalog;
 pub mod circuit_breaker_metrics;
+pub mod compression_metrics;
 pub mod coprocessor_metrics;
 pub mod demand_control_metrics;
 pub mod graphql_metrics;
 pub mod http_client_metrics;
 pub mod http_server_metrics;
 pub mod persisted_documents_metrics;
+pub mod plugin_metrics;
+pub mod plugin_storage_metrics;
+pub mod rate_limit_metrics;
+pub mod retry_metrics;
 pub mod setup;
 pub mod subscription_metrics;
 pub mod supergraph_metrics;
@@ -18,12 +23,15 @@ use opentelemetry::metrics::Meter;
 
 use crate::telemetry::metrics::cache_metrics::CacheMetrics;
 use crate::telemetry::metrics::circuit_breaker_metrics::CircuitBreakerMetrics;
+use crate::telemetry::metrics::compression_metrics::CompressionMetrics;
 use crate::telemetry::metrics::coprocessor_metrics::CoprocessorMetrics;
 use crate::telemetry::metrics::demand_control_metrics::DemandControlMetrics;
 use crate::telemetry::metrics::graphql_metrics::GraphQLMetrics;
 use crate::telemetry::metrics::http_client_metrics::HttpClientMetrics;
 use crate::telemetry::metrics::http_server_metrics::HttpServerMetrics;
 use crate::telemetry::metrics::persisted_documents_metrics::PersistedDocumentsMetrics;
+use crate::telemetry::metrics::rate_limit_metrics::RateLimitMetrics;
+use crate::telemetry::metrics::retry_metrics::RetryMetrics;
 use crate::telemetry::metrics::subscription_metrics::SubscriptionMetrics;
 use crate::telemetry::metrics::supergraph_metrics::SupergraphMetrics;
 
@@ -35,9 +43,12 @@ pub struct Metrics {
     pub supergraph: SupergraphMetrics,
     pub cache: CacheMetrics,
     pub circuit_breaker: CircuitBreakerMetrics,
+    pub retry: RetryMetrics,
+    pub compression: CompressionMetrics,
     pub persisted_documents: PersistedDocumentsMetrics,
     pub coprocessor: CoprocessorMetrics,
     pub subscriptions: SubscriptionMetrics,
+    pub rate_limit: RateLimitMetrics,
 }
 
 impl Metrics {
@@ -50,9 +61,12 @@ impl Metrics {
             supergraph: SupergraphMetrics::new(meter),
             cache: CacheMetrics::new(meter),
             circuit_breaker: CircuitBreakerMetrics::new(meter),
+            retry: RetryMetrics::new(meter),
+            compression: CompressionMetrics::new(meter),
             persisted_documents: PersistedDocumentsMetrics::new(meter),
             coprocessor: CoprocessorMetrics::new(meter),
             subscriptions: SubscriptionMetrics::new(meter),
+            rate_limit: RateLimitMetrics::new(meter),
         }
     }
 }
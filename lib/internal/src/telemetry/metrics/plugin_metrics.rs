@@ -0,0 +1,97 @@
+use std::borrow::Cow;
+
+use opentelemetry::metrics::{Counter, Gauge, Histogram, Meter};
+pub use opentelemetry::KeyValue;
+
+/// Creates the metrics of plugins under the meter of the router,
+/// so they are exported next to the metrics of the router, with the same exporters.
+///
+/// The instruments do nothing when the metrics are disabled.
+#[derive(Clone, Default)]
+pub struct PluginMetrics {
+    meter: Option<Meter>,
+}
+
+impl PluginMetrics {
+    pub fn new(meter: Option<&Meter>) -> Self {
+        Self {
+            meter: meter.cloned(),
+        }
+    }
+
+    /// A monotonic counter, e.g. the number of requests rejected by the plugin.
+    pub fn counter(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        description: impl Into<Cow<'static, str>>,
+    ) -> PluginCounter {
+        PluginCounter(self.meter.as_ref().map(|meter| {
+            meter
+                .u64_counter(name)
+                .with_description(description)
+                .build()
+        }))
+    }
+
+    /// A histogram of values in the given unit, e.g. `s` for durations.
+    pub fn histogram(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        description: impl Into<Cow<'static, str>>,
+        unit: impl Into<Cow<'static, str>>,
+    ) -> PluginHistogram {
+        PluginHistogram(self.meter.as_ref().map(|meter| {
+            meter
+                .f64_histogram(name)
+                .with_description(description)
+                .with_unit(unit)
+                .build()
+        }))
+    }
+
+    /// A gauge recording the last value, e.g. the size of a cache of the plugin.
+    pub fn gauge(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        description: impl Into<Cow<'static, str>>,
+    ) -> PluginGauge {
+        PluginGauge(
+            self.meter
+                .as_ref()
+                .map(|meter| meter.f64_gauge(name).with_description(description).build()),
+        )
+    }
+}
+
+#[derive(Clone)]
+pub struct PluginCounter(Option<Counter<u64>>);
+
+impl PluginCounter {
+    pub fn add(&self, value: u64, attributes: &[KeyValue]) {
+        if let Some(counter) = &self.0 {
+            counter.add(value, attributes);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PluginHistogram(Option<Histogram<f64>>);
+
+impl PluginHistogram {
+    pub fn record(&self, value: f64, attributes: &[KeyValue]) {
+        if let Some(histogram) = &self.0 {
+            histogram.record(value, attributes);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PluginGauge(Option<Gauge<f64>>);
+
+impl PluginGauge {
+    pub fn record(&self, value: f64, attributes: &[KeyValue]) {
+        if let Some(gauge) = &self.0 {
+            gauge.record(value, attributes);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::KeyValue;
+
+use crate::telemetry::metrics::catalog;
+#[cfg(debug_assertions)]
+use crate::telemetry::metrics::catalog::debug_assert_attrs;
+use crate::telemetry::metrics::catalog::values::{PluginStorageOperation, PluginStorageResult};
+
+#[derive(Clone, Default)]
+pub struct PluginStorageMetrics {
+    pub operations_total: Option<Counter<u64>>,
+    pub duration: Option<Histogram<f64>>,
+}
+
+impl PluginStorageMetrics {
+    pub fn new(meter: Option<&Meter>) -> Self {
+        let Some(meter) = meter else {
+            return Self::default();
+        };
+
+        Self {
+            operations_total: Some(
+                meter
+                    .u64_counter(catalog::names::PLUGIN_STORAGE_OPERATIONS_TOTAL)
+                    .with_description("Total number of operations of plugins on the plugin storage")
+                    .build(),
+            ),
+            duration: Some(
+                meter
+                    .f64_histogram(catalog::names::PLUGIN_STORAGE_DURATION)
+                    .with_description("Duration of operations of plugins on the plugin storage")
+                    .with_unit(catalog::units::SECONDS)
+                    .build(),
+            ),
+        }
+    }
+
+    pub fn record_operation(
+        &self,
+        plugin_name: &'static str,
+        operation: PluginStorageOperation,
+        result: PluginStorageResult,
+        duration: f64,
+    ) {
+        let attrs = [
+            KeyValue::new(catalog::labels::PLUGIN_NAME, plugin_name),
+            KeyValue::new(
+                catalog::labels::PLUGIN_STORAGE_OPERATION,
+                operation.as_str(),
+            ),
+            KeyValue::new(catalog::labels::RESULT, result.as_str()),
+        ];
+
+        if let Some(metric) = &self.operations_total {
+            #[cfg(debug_assertions)]
+            debug_assert_attrs(catalog::names::PLUGIN_STORAGE_OPERATIONS_TOTAL, &attrs);
+            metric.add(1, &attrs);
+        }
+
+        if let Some(metric) = &self.duration {
+            #[cfg(debug_assertions)]
+            debug_assert_attrs(catalog::names::PLUGIN_STORAGE_DURATION, &attrs);
+            metric.record(duration, &attrs);
+        }
+    }
+}
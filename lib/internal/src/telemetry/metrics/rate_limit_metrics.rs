@@ -0,0 +1,37 @@
+use opentelemetry::metrics::{Counter, Meter};
+use opentelemetry::KeyValue;
+
+#[cfg(debug_assertions)]
+use crate::telemetry::metrics::catalog::debug_assert_attrs;
+use crate::telemetry::metrics::catalog::{labels, names, values::RateLimitResult};
+
+pub struct RateLimitMetrics {
+    requests_total: Option<Counter<u64>>,
+}
+
+impl RateLimitMetrics {
+    pub fn new(meter: Option<&Meter>) -> Self {
+        let requests_total = meter.map(|meter| {
+            meter
+                .u64_counter(names::RATE_LIMIT_REQUESTS_TOTAL)
+                .with_unit("{request}")
+                .with_description(
+                    "Total number of requests checked against the rate limit of their client",
+                )
+                .build()
+        });
+
+        Self { requests_total }
+    }
+
+    pub fn record_request(&self, result: RateLimitResult) {
+        let Some(counter) = &self.requests_total else {
+            return;
+        };
+
+        let attrs = [KeyValue::new(labels::RESULT, result.as_str())];
+        #[cfg(debug_assertions)]
+        debug_assert_attrs(names::RATE_LIMIT_REQUESTS_TOTAL, &attrs);
+        counter.add(1, &attrs);
+    }
+}
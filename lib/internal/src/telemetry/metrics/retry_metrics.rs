@@ -0,0 +1,48 @@
+use opentelemetry::{
+    metrics::{Counter, Meter},
+    KeyValue,
+};
+
+use crate::telemetry::metrics::catalog::{labels, names};
+
+#[cfg(debug_assertions)]
+use crate::telemetry::metrics::catalog::debug_assert_attrs;
+
+/// Tracks the retries of the requests sent to the subgraphs.
+pub struct RetryMetrics {
+    retries: Option<Counter<u64>>,
+}
+
+impl RetryMetrics {
+    pub fn new(meter: Option<&Meter>) -> Self {
+        let retries = meter.map(|meter| {
+            meter
+                .u64_counter(names::SUBGRAPH_RETRIES_TOTAL)
+                .with_unit("{request}")
+                .with_description(
+                    "Number of subgraph requests sent again after a connection \
+                     error or a retryable status code.",
+                )
+                .build()
+        });
+
+        Self { retries }
+    }
+
+    /// Records that a request to the subgraph is about to be sent again.
+    pub fn record_retry(&self, subgraph_name: &str) {
+        let Some(counter) = &self.retries else {
+            return;
+        };
+
+        let attributes = [KeyValue::new(
+            labels::SUBGRAPH_NAME,
+            subgraph_name.to_string(),
+        )];
+
+        #[cfg(debug_assertions)]
+        debug_assert_attrs(names::SUBGRAPH_RETRIES_TOTAL, &attributes);
+
+        counter.add(1, &attributes);
+    }
+}
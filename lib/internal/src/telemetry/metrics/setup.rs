@@ -34,7 +34,7 @@ use crate::{
     telemetry::{
         error::TelemetryError,
         metrics::catalog::{
-            all_metric_names, labels_for,
+            all_metric_names, labels_for, names,
             units::{BYTES, DEMAND_CONTROL_COST_UNIT, SECONDS},
         },
         resolve_string_map,
@@ -56,7 +56,7 @@ fn build_instrument_rules(
 
     for (metric_name, toggle) in &config.metrics.instrumentation.instruments {
         // Metric names are validated strictly: unknown names fail startup.
-        let Some(default_labels) = labels_for(metric_name.as_str()) else {
+        let Some(catalog_labels) = labels_for(metric_name.as_str()) else {
             let mut valid_metrics = all_metric_names();
             valid_metrics.sort_unstable();
             return Err(TelemetryError::MetricsExporterSetup(format!(
@@ -65,6 +65,12 @@ fn build_instrument_rules(
             )));
         };
 
+        // The attributes of `telemetry.instrumentation` are recorded along the labels of the catalog.
+        let mut default_labels: Vec<&str> = catalog_labels.to_vec();
+        if metric_name == names::GRAPHQL_EXECUTION_DURATION {
+            default_labels.extend(config.instrumentation.metric_attribute_names());
+        }
+
         match toggle {
             ToggleWith::Disabled => {
                 rules.insert(metric_name.clone(), InstrumentRule::Disabled);
@@ -102,7 +108,7 @@ fn build_instrument_rules(
                 let filtered_labels = default_labels
                     .iter()
                     .filter(|label| allowed_labels.contains(**label))
-                    .map(|label| Key::new(*label))
+                    .map(|label| Key::new(label.to_string()))
                     .collect();
 
                 rules.insert(
@@ -124,6 +130,7 @@ pub struct MetricsSetup {
 pub struct PrometheusRuntimeConfig {
     pub registry: Registry,
     pub port: Option<u16>,
+    pub host: Option<String>,
     pub path: String,
 }
 
@@ -447,6 +454,7 @@ fn setup_prometheus_reader(
         Some(PrometheusRuntimeConfig {
             registry,
             port: prometheus_config.port,
+            host: prometheus_config.host.clone(),
             path,
         }),
     ))
@@ -509,7 +517,11 @@ mod tests {
 
     use hive_router_config::{
         primitives::toggle::ToggleWith,
-        telemetry::{metrics::InstrumentConfig, TelemetryConfig},
+        telemetry::{
+            instrumentation::{CustomAttributeConfig, CustomAttributeMetricsConfig},
+            metrics::InstrumentConfig,
+            TelemetryConfig,
+        },
     };
 
     use crate::telemetry::metrics::catalog::{labels, names};
@@ -576,4 +588,41 @@ mod tests {
         // If a rule does not exist, it means that the metric gets all labels
         assert!(rule.is_none(), "rule should not exist");
     }
+
+    #[test]
+    fn keeps_custom_attributes_of_filtered_metric() {
+        let mut config = TelemetryConfig::default();
+        for (name, metrics) in [("tenant.id", true), ("cart.size", false)] {
+            config.instrumentation.attributes.insert(
+                name.to_string(),
+                CustomAttributeConfig {
+                    expression: ".request.headers.\"x-tenant-id\"".to_string(),
+                    spans: true,
+                    metrics: CustomAttributeMetricsConfig {
+                        enabled: metrics,
+                        ..Default::default()
+                    },
+                },
+            );
+        }
+        config.metrics.instrumentation.instruments.insert(
+            names::GRAPHQL_EXECUTION_DURATION.to_string(),
+            ToggleWith::Enabled(InstrumentConfig {
+                attributes: HashMap::from([(labels::GRAPHQL_RESPONSE_STATUS.to_string(), false)]),
+            }),
+        );
+
+        let rules = build_instrument_rules(&config).expect("config should be valid");
+        let Some(InstrumentRule::Filtered(allowed)) = rules.get(names::GRAPHQL_EXECUTION_DURATION)
+        else {
+            panic!("expected filtered rule")
+        };
+
+        let allowed: Vec<&str> = allowed.iter().map(|key| key.as_str()).collect();
+
+        assert!(allowed.contains(&labels::GRAPHQL_OPERATION_TYPE));
+        assert!(allowed.contains(&"tenant.id"));
+        assert!(!allowed.contains(&"cart.size"));
+        assert!(!allowed.contains(&labels::GRAPHQL_RESPONSE_STATUS));
+    }
 }
@@ -16,8 +16,9 @@ use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::Layer;
 
 use crate::telemetry::metrics::Metrics;
-use crate::telemetry::propagation::HeaderMapInjector;
+use crate::telemetry::propagation::{DatadogPropagator, HeaderMapInjector};
 use crate::telemetry::traces::build_trace_provider;
+use crate::telemetry::traces::spans::LOG_CONTEXT_TARGET_NAME;
 
 pub mod error;
 pub mod metrics;
@@ -40,6 +41,8 @@ use utils::resolve_string_map;
 #[derive(Clone)]
 pub struct TelemetryContext {
     propagator: Option<Arc<TextMapCompositePropagator>>,
+    /// Whether the trace context of incoming requests is extracted.
+    trust_incoming: bool,
     pub metrics: Arc<Metrics>,
     meter: Option<Meter>,
 }
@@ -78,11 +81,16 @@ impl TelemetryContext {
             propagators.push(Box::new(JaegerPropagator::new()));
         }
 
+        if config.datadog {
+            propagators.push(Box::new(DatadogPropagator::new()));
+        }
+
         let metrics = Arc::new(Metrics::new(meter.as_ref()));
 
         if propagators.is_empty() {
             return Self {
                 propagator: None,
+                trust_incoming: config.trust_incoming,
                 metrics,
                 meter,
             };
@@ -90,6 +98,7 @@ impl TelemetryContext {
 
         Self {
             propagator: Some(Arc::new(TextMapCompositePropagator::new(propagators))),
+            trust_incoming: config.trust_incoming,
             metrics,
             meter,
         }
@@ -115,10 +124,9 @@ impl TelemetryContext {
     where
         E: otel::opentelemetry::propagation::Extractor,
     {
-        if let Some(propagator) = &self.propagator {
-            propagator.extract(extractor)
-        } else {
-            otel::opentelemetry::Context::new()
+        match &self.propagator {
+            Some(propagator) if self.trust_incoming => propagator.extract(extractor),
+            _ => otel::opentelemetry::Context::new(),
         }
     }
 
@@ -155,8 +163,11 @@ where
         .with_location(false)
         .with_threads(false)
         // Drop events from tracing macros (info!, error!, etc.),
-        // but accept those from span.add_event()
-        .with_filter(filter_fn(|metadata| metadata.is_span()));
+        // but accept those from span.add_event(),
+        // and the spans meant for the log lines only
+        .with_filter(filter_fn(|metadata| {
+            metadata.is_span() && metadata.target() != LOG_CONTEXT_TARGET_NAME
+        }));
 
     Ok(Some((traces_layer, traces_provider)))
 }
@@ -1,6 +1,13 @@
+use std::sync::LazyLock;
+
 use http::{HeaderMap, HeaderName, HeaderValue};
+use opentelemetry::{
+    propagation::{text_map_propagator::FieldIter, Extractor},
+    trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+    Context,
+};
 
-use crate::telemetry::Injector;
+use crate::telemetry::{Injector, TextMapPropagator};
 
 pub struct HeaderMapInjector<'a>(&'a mut HeaderMap);
 
@@ -23,3 +30,271 @@ impl Injector for HeaderMapInjector<'_> {
         self.0.insert(name, val);
     }
 }
+
+const DATADOG_TRACE_ID_HEADER: &str = "x-datadog-trace-id";
+const DATADOG_PARENT_ID_HEADER: &str = "x-datadog-parent-id";
+const DATADOG_SAMPLING_PRIORITY_HEADER: &str = "x-datadog-sampling-priority";
+const DATADOG_ORIGIN_HEADER: &str = "x-datadog-origin";
+const DATADOG_TAGS_HEADER: &str = "x-datadog-tags";
+/// The tag carrying the upper 64 bits of a 128-bit trace ID, in hexadecimal.
+const DATADOG_TRACE_ID_HIGH_TAG: &str = "_dd.p.tid";
+/// The key of the trace state keeping the sampling priority and the origin of a Datadog trace,
+/// as written by the Datadog tracers in the W3C `tracestate` header.
+const DATADOG_TRACE_STATE_KEY: &str = "dd";
+
+static DATADOG_HEADER_FIELDS: LazyLock<[String; 5]> = LazyLock::new(|| {
+    [
+        DATADOG_TRACE_ID_HEADER.to_string(),
+        DATADOG_PARENT_ID_HEADER.to_string(),
+        DATADOG_SAMPLING_PRIORITY_HEADER.to_string(),
+        DATADOG_ORIGIN_HEADER.to_string(),
+        DATADOG_TAGS_HEADER.to_string(),
+    ]
+});
+
+/// The sampling priority and the origin of a Datadog trace, kept in the `dd` entry of the trace state.
+#[derive(Debug, Default, PartialEq)]
+pub struct DatadogTraceState {
+    /// `-1` (user reject), `0` (auto reject), `1` (auto keep) or `2` (user keep)
+    pub sampling_priority: Option<i32>,
+    pub origin: Option<String>,
+}
+
+impl DatadogTraceState {
+    pub fn from_trace_state(trace_state: &TraceState) -> Self {
+        let mut state = Self::default();
+        let Some(value) = trace_state.get(DATADOG_TRACE_STATE_KEY) else {
+            return state;
+        };
+
+        for member in value.split(';') {
+            match member.split_once(':') {
+                Some(("s", priority)) => state.sampling_priority = priority.parse().ok(),
+                Some(("o", origin)) => state.origin = Some(origin.to_string()),
+                _ => {}
+            }
+        }
+
+        state
+    }
+
+    fn to_trace_state(&self) -> TraceState {
+        let members: Vec<String> = self
+            .sampling_priority
+            .map(|priority| format!("s:{priority}"))
+            .into_iter()
+            .chain(self.origin.as_ref().map(|origin| format!("o:{origin}")))
+            .collect();
+
+        if members.is_empty() {
+            return TraceState::NONE;
+        }
+
+        TraceState::from_key_value([(DATADOG_TRACE_STATE_KEY, members.join(";"))])
+            .unwrap_or(TraceState::NONE)
+    }
+}
+
+/// Propagates the trace context in the headers of the Datadog tracers.
+///
+/// Datadog trace IDs are 64-bit decimal numbers, the upper 64 bits of a 128-bit trace ID
+/// being sent in the `_dd.p.tid` tag of `x-datadog-tags`.
+/// The sampling priority and the origin are kept in the trace state,
+/// to be sent to the subgraphs and to the Datadog agent.
+#[derive(Debug, Default)]
+pub struct DatadogPropagator;
+
+impl DatadogPropagator {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn extract_span_context(&self, extractor: &dyn Extractor) -> Option<SpanContext> {
+        let trace_id_low: u64 = extractor
+            .get(DATADOG_TRACE_ID_HEADER)?
+            .trim()
+            .parse()
+            .ok()?;
+        let parent_id: u64 = extractor
+            .get(DATADOG_PARENT_ID_HEADER)?
+            .trim()
+            .parse()
+            .ok()?;
+        if trace_id_low == 0 || parent_id == 0 {
+            return None;
+        }
+
+        let trace_id_high = extractor
+            .get(DATADOG_TAGS_HEADER)
+            .and_then(|tags| {
+                tags.split(',')
+                    .filter_map(|tag| tag.split_once('='))
+                    .find(|(name, _)| name.trim() == DATADOG_TRACE_ID_HIGH_TAG)
+            })
+            .and_then(|(_, value)| u64::from_str_radix(value.trim(), 16).ok())
+            .unwrap_or(0);
+        let trace_id = (u128::from(trace_id_high) << 64) | u128::from(trace_id_low);
+
+        let state = DatadogTraceState {
+            sampling_priority: extractor
+                .get(DATADOG_SAMPLING_PRIORITY_HEADER)
+                .and_then(|priority| priority.trim().parse().ok()),
+            origin: extractor
+                .get(DATADOG_ORIGIN_HEADER)
+                .map(|origin| origin.trim().to_string())
+                .filter(|origin| !origin.is_empty()),
+        };
+        // Without a priority, the sampling decision is left to the router
+        let trace_flags = match state.sampling_priority {
+            Some(priority) if priority <= 0 => TraceFlags::default(),
+            _ => TraceFlags::SAMPLED,
+        };
+
+        Some(SpanContext::new(
+            TraceId::from_bytes(trace_id.to_be_bytes()),
+            SpanId::from_bytes(parent_id.to_be_bytes()),
+            trace_flags,
+            true,
+            state.to_trace_state(),
+        ))
+    }
+}
+
+impl TextMapPropagator for DatadogPropagator {
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        let span = cx.span();
+        let span_context = span.span_context();
+        if !span_context.is_valid() {
+            return;
+        }
+
+        let trace_id = u128::from_be_bytes(span_context.trace_id().to_bytes());
+        let span_id = u64::from_be_bytes(span_context.span_id().to_bytes());
+        injector.set(DATADOG_TRACE_ID_HEADER, (trace_id as u64).to_string());
+        injector.set(DATADOG_PARENT_ID_HEADER, span_id.to_string());
+
+        let trace_id_high = (trace_id >> 64) as u64;
+        if trace_id_high != 0 {
+            injector.set(
+                DATADOG_TAGS_HEADER,
+                format!("{DATADOG_TRACE_ID_HIGH_TAG}={trace_id_high:016x}"),
+            );
+        }
+
+        let state = DatadogTraceState::from_trace_state(span_context.trace_state());
+        // The priority received is kept, unless the router sampled the trace otherwise
+        let sampling_priority = match (span_context.is_sampled(), state.sampling_priority) {
+            (true, Some(priority)) if priority > 0 => priority,
+            (true, _) => 1,
+            (false, Some(priority)) if priority <= 0 => priority,
+            (false, _) => 0,
+        };
+        injector.set(
+            DATADOG_SAMPLING_PRIORITY_HEADER,
+            sampling_priority.to_string(),
+        );
+
+        if let Some(origin) = state.origin {
+            injector.set(DATADOG_ORIGIN_HEADER, origin);
+        }
+    }
+
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        match self.extract_span_context(extractor) {
+            Some(span_context) => cx.with_remote_span_context(span_context),
+            None => cx.clone(),
+        }
+    }
+
+    fn fields(&self) -> FieldIter<'_> {
+        FieldIter::new(DATADOG_HEADER_FIELDS.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use opentelemetry::{
+        propagation::TextMapPropagator,
+        trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+        Context,
+    };
+
+    use super::{DatadogPropagator, DatadogTraceState};
+
+    #[test]
+    fn extracts_and_injects_datadog_headers() {
+        let incoming = HashMap::from([
+            ("x-datadog-trace-id".to_string(), "1234".to_string()),
+            ("x-datadog-parent-id".to_string(), "5678".to_string()),
+            ("x-datadog-sampling-priority".to_string(), "2".to_string()),
+            ("x-datadog-origin".to_string(), "rum".to_string()),
+            (
+                "x-datadog-tags".to_string(),
+                "_dd.p.dm=-4,_dd.p.tid=640cfd8d00000000".to_string(),
+            ),
+        ]);
+
+        let propagator = DatadogPropagator::new();
+        let cx = propagator.extract(&incoming);
+        let span = cx.span();
+        let span_context = span.span_context();
+
+        assert!(span_context.is_remote());
+        assert!(span_context.is_sampled());
+        assert_eq!(
+            span_context.trace_id(),
+            TraceId::from_hex("640cfd8d0000000000000000000004d2").unwrap()
+        );
+        assert_eq!(
+            span_context.span_id(),
+            SpanId::from_bytes(5678u64.to_be_bytes())
+        );
+        assert_eq!(
+            DatadogTraceState::from_trace_state(span_context.trace_state()),
+            DatadogTraceState {
+                sampling_priority: Some(2),
+                origin: Some("rum".to_string()),
+            }
+        );
+
+        let mut outgoing = HashMap::new();
+        propagator.inject_context(&cx, &mut outgoing);
+        assert_eq!(outgoing["x-datadog-trace-id"], "1234");
+        assert_eq!(outgoing["x-datadog-parent-id"], "5678");
+        assert_eq!(outgoing["x-datadog-sampling-priority"], "2");
+        assert_eq!(outgoing["x-datadog-origin"], "rum");
+        assert_eq!(outgoing["x-datadog-tags"], "_dd.p.tid=640cfd8d00000000");
+    }
+
+    #[test]
+    fn rejected_priority_is_not_sampled() {
+        let incoming = HashMap::from([
+            ("x-datadog-trace-id".to_string(), "1234".to_string()),
+            ("x-datadog-parent-id".to_string(), "5678".to_string()),
+            ("x-datadog-sampling-priority".to_string(), "-1".to_string()),
+        ]);
+
+        let cx = DatadogPropagator::new().extract(&incoming);
+        assert!(!cx.span().span_context().is_sampled());
+    }
+
+    #[test]
+    fn injects_the_decision_of_the_router() {
+        let cx = Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from_bytes(1u128.to_be_bytes()),
+            SpanId::from_bytes(2u64.to_be_bytes()),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::NONE,
+        ));
+
+        let mut outgoing = HashMap::new();
+        DatadogPropagator::new().inject_context(&cx, &mut outgoing);
+        assert_eq!(outgoing["x-datadog-trace-id"], "1");
+        assert_eq!(outgoing["x-datadog-parent-id"], "2");
+        assert_eq!(outgoing["x-datadog-sampling-priority"], "1");
+        assert!(!outgoing.contains_key("x-datadog-tags"));
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, SystemTime};
+
+use opentelemetry::{
+    trace::{SpanId, SpanKind, Status},
+    Value,
+};
+use opentelemetry_sdk::{
+    error::{OTelSdkError, OTelSdkResult},
+    trace::{SpanData, SpanExporter},
+    Resource,
+};
+use serde::Serialize;
+
+use crate::telemetry::{
+    error::TelemetryError,
+    propagation::DatadogTraceState,
+    traces::spans::attributes::{ERROR_MESSAGE, GRAPHQL_OPERATION_NAME},
+};
+
+const TRACES_PATH: &str = "/v0.4/traces";
+/// The metric of the root span of a trace chunk, telling the agent whether to keep the trace.
+const SAMPLING_PRIORITY_METRIC: &str = "_sampling_priority_v1";
+
+/// Exports the spans to a Datadog agent, in the JSON encoding of its `/v0.4/traces` API.
+///
+/// The spans of a batch are grouped by trace,
+/// and the sampling priority is set on the local root span of every trace,
+/// from the `dd` entry of the trace state or to `1`, as only the sampled spans are exported.
+#[derive(Debug)]
+pub struct DatadogExporter {
+    client: reqwest::Client,
+    traces_url: String,
+    service: String,
+    env: Option<String>,
+    version: Option<String>,
+}
+
+/// A span, in the format of the Datadog agent.
+#[derive(Debug, Serialize)]
+struct DatadogSpan<'a> {
+    service: &'a str,
+    name: &'a str,
+    resource: &'a str,
+    r#type: &'static str,
+    trace_id: u64,
+    span_id: u64,
+    parent_id: u64,
+    /// Nanoseconds since the UNIX epoch
+    start: i64,
+    /// In nanoseconds
+    duration: i64,
+    error: i32,
+    meta: BTreeMap<&'a str, String>,
+    metrics: BTreeMap<&'a str, f64>,
+}
+
+impl DatadogExporter {
+    pub fn new(endpoint: &str, timeout: Duration) -> Result<Self, TelemetryError> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|err| TelemetryError::TracesExporterSetup(err.to_string()))?;
+
+        Ok(Self {
+            client,
+            traces_url: format!("{}{TRACES_PATH}", endpoint.trim_end_matches('/')),
+            service: "hive-router".to_string(),
+            env: None,
+            version: None,
+        })
+    }
+
+    fn to_datadog_traces<'a>(&'a self, batch: &'a [SpanData]) -> Vec<Vec<DatadogSpan<'a>>> {
+        let mut traces: HashMap<_, Vec<&SpanData>> = HashMap::new();
+        for span in batch {
+            traces
+                .entry(span.span_context.trace_id())
+                .or_default()
+                .push(span);
+        }
+
+        traces
+            .into_values()
+            .map(|spans| {
+                let span_ids: HashSet<SpanId> = spans
+                    .iter()
+                    .map(|span| span.span_context.span_id())
+                    .collect();
+
+                spans
+                    .into_iter()
+                    .map(|span| {
+                        let is_chunk_root = !span_ids.contains(&span.parent_span_id);
+                        self.to_datadog_span(span, is_chunk_root)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn to_datadog_span<'a>(&'a self, span: &'a SpanData, is_chunk_root: bool) -> DatadogSpan<'a> {
+        let trace_id = u128::from_be_bytes(span.span_context.trace_id().to_bytes());
+        let mut meta = BTreeMap::new();
+        let mut metrics = BTreeMap::new();
+
+        for attribute in &span.attributes {
+            match &attribute.value {
+                Value::I64(value) => {
+                    metrics.insert(attribute.key.as_str(), *value as f64);
+                }
+                Value::F64(value) => {
+                    metrics.insert(attribute.key.as_str(), *value);
+                }
+                value => {
+                    meta.insert(attribute.key.as_str(), value.as_str().into_owned());
+                }
+            }
+        }
+
+        meta.insert(
+            "span.kind",
+            match span.span_kind {
+                SpanKind::Server => "server",
+                SpanKind::Client => "client",
+                SpanKind::Producer => "producer",
+                SpanKind::Consumer => "consumer",
+                SpanKind::Internal => "internal",
+            }
+            .to_string(),
+        );
+        if let Some(env) = &self.env {
+            meta.insert("env", env.clone());
+        }
+        if let Some(version) = &self.version {
+            meta.insert("version", version.clone());
+        }
+
+        let error = match &span.status {
+            Status::Error { description } => {
+                if !description.is_empty() {
+                    meta.insert(ERROR_MESSAGE, description.to_string());
+                }
+                1
+            }
+            _ => 0,
+        };
+
+        if is_chunk_root {
+            let state = DatadogTraceState::from_trace_state(span.span_context.trace_state());
+            metrics.insert(
+                SAMPLING_PRIORITY_METRIC,
+                state
+                    .sampling_priority
+                    .filter(|priority| *priority > 0)
+                    .unwrap_or(1) as f64,
+            );
+            if let Some(origin) = state.origin {
+                meta.insert("_dd.origin", origin);
+            }
+            let trace_id_high = (trace_id >> 64) as u64;
+            if trace_id_high != 0 {
+                meta.insert("_dd.p.tid", format!("{trace_id_high:016x}"));
+            }
+        }
+
+        // The operations are grouped by their GraphQL operation name, when the span has one
+        let resource = span
+            .attributes
+            .iter()
+            .find(|attribute| attribute.key.as_str() == GRAPHQL_OPERATION_NAME)
+            .and_then(|attribute| match &attribute.value {
+                Value::String(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .unwrap_or(&span.name);
+
+        DatadogSpan {
+            service: &self.service,
+            name: &span.name,
+            resource,
+            r#type: match span.span_kind {
+                SpanKind::Server => "web",
+                SpanKind::Client => "http",
+                _ => "custom",
+            },
+            trace_id: trace_id as u64,
+            span_id: u64::from_be_bytes(span.span_context.span_id().to_bytes()),
+            parent_id: u64::from_be_bytes(span.parent_span_id.to_bytes()),
+            start: unix_nanos(span.start_time),
+            duration: span
+                .end_time
+                .duration_since(span.start_time)
+                .unwrap_or_default()
+                .as_nanos() as i64,
+            error,
+            meta,
+            metrics,
+        }
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> i64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as i64
+}
+
+impl SpanExporter for DatadogExporter {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let traces = self.to_datadog_traces(&batch);
+        let trace_count = traces.len();
+        let body = sonic_rs::to_vec(&traces).map_err(|err| {
+            OTelSdkError::InternalFailure(format!("Failed to serialize the spans: {err}"))
+        })?;
+
+        let response = self
+            .client
+            .put(&self.traces_url)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header("Datadog-Meta-Lang", "rust")
+            .header("Datadog-Meta-Tracer-Version", env!("CARGO_PKG_VERSION"))
+            .header("X-Datadog-Trace-Count", trace_count.to_string())
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| {
+                OTelSdkError::InternalFailure(format!(
+                    "Failed to send the spans to the Datadog agent: {err}"
+                ))
+            })?;
+
+        match response.status().is_success() {
+            true => Ok(()),
+            false => Err(OTelSdkError::InternalFailure(format!(
+                "The Datadog agent responded with a {} status",
+                response.status()
+            ))),
+        }
+    }
+
+    fn shutdown(&mut self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        let get = |key: &'static str| {
+            resource
+                .get(&opentelemetry::Key::from_static_str(key))
+                .map(|value| value.as_str().into_owned())
+        };
+
+        if let Some(service) = get("service.name") {
+            self.service = service;
+        }
+        self.env = get("deployment.environment.name").or_else(|| get("deployment.environment"));
+        self.version = get("service.version");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use opentelemetry::{
+        trace::{Span, Status, TraceContextExt, Tracer, TracerProvider},
+        Context, KeyValue,
+    };
+    use opentelemetry_sdk::{
+        trace::{
+            InMemorySpanExporterBuilder, SdkTracerProvider, SimpleSpanProcessor, SpanExporter,
+        },
+        Resource,
+    };
+
+    use super::DatadogExporter;
+
+    #[test]
+    fn maps_spans_to_datadog_traces() {
+        let in_memory = InMemorySpanExporterBuilder::new().build();
+        let provider = SdkTracerProvider::builder()
+            .with_span_processor(SimpleSpanProcessor::new(in_memory.clone()))
+            .build();
+        let tracer = provider.tracer("test");
+
+        let root = tracer
+            .span_builder("graphql.operation")
+            .with_kind(opentelemetry::trace::SpanKind::Server)
+            .with_attributes([
+                KeyValue::new("graphql.operation.name", "GetUser"),
+                KeyValue::new("hive.graphql.error.count", 1),
+            ])
+            .start(&tracer);
+        let cx = Context::new().with_span(root);
+        let mut child = tracer.start_with_context("subgraph", &cx);
+        child.set_status(Status::error("boom"));
+        child.end();
+        cx.span().end();
+
+        let spans = in_memory.get_finished_spans().unwrap();
+        let mut exporter = DatadogExporter::new("http://localhost:8126/", Duration::from_secs(1))
+            .expect("exporter should build");
+        exporter.set_resource(
+            &Resource::builder()
+                .with_attributes([
+                    KeyValue::new("service.name", "router"),
+                    KeyValue::new("deployment.environment.name", "prod"),
+                ])
+                .build(),
+        );
+
+        assert_eq!(exporter.traces_url, "http://localhost:8126/v0.4/traces");
+
+        let traces = exporter.to_datadog_traces(&spans);
+        assert_eq!(traces.len(), 1);
+
+        let root = traces[0]
+            .iter()
+            .find(|span| span.name == "graphql.operation")
+            .unwrap();
+        assert_eq!(root.service, "router");
+        assert_eq!(root.resource, "GetUser");
+        assert_eq!(root.r#type, "web");
+        assert_eq!(root.parent_id, 0);
+        assert_eq!(root.meta["env"], "prod");
+        assert_eq!(root.metrics["_sampling_priority_v1"], 1.0);
+        assert_eq!(root.metrics["hive.graphql.error.count"], 1.0);
+
+        let child = traces[0]
+            .iter()
+            .find(|span| span.name == "subgraph")
+            .unwrap();
+        assert_eq!(child.trace_id, root.trace_id);
+        assert_eq!(child.parent_id, root.span_id);
+        assert_eq!(child.error, 1);
+        assert_eq!(child.meta["error.message"], "boom");
+        assert!(!child.metrics.contains_key("_sampling_priority_v1"));
+    }
+}
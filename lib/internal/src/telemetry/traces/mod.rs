@@ -1,7 +1,7 @@
 //! This module builds the `SdkTracerProvider` from config and attaches the appropriate
 //! span processors/exporters.
 //!
-//! Standard OTLP and stdout exporters use the SDK's `BatchSpanProcessor`,
+//! Standard OTLP, stdout, Zipkin and Datadog exporters use the SDK's `BatchSpanProcessor`,
 //! while Hive tracing routes through a custom pipeline:
 //! -> `TraceBatchSpanProcessor` buffers spans per trace
 //! -> `HiveConsoleExporter` normalizes
@@ -29,9 +29,11 @@ use opentelemetry_sdk::{
     },
     Resource,
 };
+use opentelemetry_zipkin::ZipkinExporter;
 use std::collections::HashMap;
 use tracing_opentelemetry::OpenTelemetryLayer;
 
+use self::datadog_exporter::DatadogExporter;
 #[cfg(feature = "noop_otlp_exporter")]
 use self::noop_exporter::NoopExporter;
 use self::standard_pipeline_exporter::StandardPipelineExporter;
@@ -45,6 +47,7 @@ pub use control::{disabled_span, is_level_enabled, is_tracing_enabled, set_traci
 
 pub mod compatibility;
 pub mod control;
+pub mod datadog_exporter;
 pub mod hive_console_exporter;
 mod noop_exporter;
 pub mod spans;
@@ -179,6 +182,44 @@ fn setup_exporters(
                         ),
                     ));
             }
+            TracingExporterConfig::Zipkin(zipkin_config) => {
+                if !zipkin_config.enabled {
+                    continue;
+                }
+
+                let endpoint =
+                    resolve_value_or_expression(&zipkin_config.endpoint, "Zipkin endpoint")?;
+                let exporter = ZipkinExporter::builder()
+                    .with_collector_endpoint(endpoint)
+                    .build()
+                    .map_err(|e| TelemetryError::TracesExporterSetup(e.to_string()))?;
+
+                tracer_provider_builder =
+                    tracer_provider_builder.with_span_processor(build_batched_span_processor(
+                        &zipkin_config.batch_processor,
+                        &resource,
+                        StandardPipelineExporter::new(exporter, sem_conv_mode),
+                    ));
+            }
+            TracingExporterConfig::Datadog(datadog_config) => {
+                if !datadog_config.enabled {
+                    continue;
+                }
+
+                let endpoint =
+                    resolve_value_or_expression(&datadog_config.endpoint, "Datadog endpoint")?;
+                let exporter = DatadogExporter::new(
+                    &endpoint,
+                    datadog_config.batch_processor.max_export_timeout,
+                )?;
+
+                tracer_provider_builder =
+                    tracer_provider_builder.with_span_processor(build_batched_span_processor(
+                        &datadog_config.batch_processor,
+                        &resource,
+                        StandardPipelineExporter::new(exporter, sem_conv_mode),
+                    ));
+            }
         }
     }
 
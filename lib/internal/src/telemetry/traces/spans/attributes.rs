@@ -14,6 +14,7 @@ pub const URL_PATH: &str = "url.path";
 pub const URL_SCHEME: &str = "url.scheme";
 pub const HTTP_REQUEST_BODY_SIZE: &str = "http.request.body.size";
 pub const HTTP_REQUEST_METHOD: &str = "http.request.method";
+pub const HTTP_REQUEST_RESEND_COUNT: &str = "http.request.resend_count";
 pub const NETWORK_PROTOCOL_VERSION: &str = "network.protocol.version";
 pub const USER_AGENT_ORIGINAL: &str = "user_agent.original";
 pub const HTTP_RESPONSE_STATUS_CODE: &str = "http.response.status_code";
@@ -42,6 +43,7 @@ pub const HIVE_GRAPHQL_ERROR_COUNT: &str = "hive.graphql.error.count";
 pub const HIVE_GRAPHQL_ERROR_CODES: &str = "hive.graphql.error.codes";
 pub const HIVE_CLIENT_NAME: &str = "hive.client.name";
 pub const HIVE_CLIENT_VERSION: &str = "hive.client.version";
+pub const HIVE_REQUEST_ID: &str = "hive.request.id";
 pub const HIVE_GRAPHQL_OPERATION_HASH: &str = "hive.graphql.operation.hash";
 pub const HIVE_GRAPHQL_SUBGRAPH_NAME: &str = "hive.graphql.subgraph.name";
 /// Hive-specific attributes for errors
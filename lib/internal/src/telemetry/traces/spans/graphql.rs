@@ -360,6 +360,18 @@ impl GraphQLOperationSpan {
         GraphQLOperationSpan { span }
     }
 
+    /// Records the attributes of `telemetry.instrumentation` computed from the operation.
+    pub fn record_custom_attributes(&self, attributes: &[KeyValue]) {
+        if self.span.is_disabled() {
+            return;
+        }
+
+        for attribute in attributes {
+            self.span
+                .set_attribute(attribute.key.clone(), attribute.value.clone());
+        }
+    }
+
     pub fn record_error_count(&self, count: usize) {
         self.span
             .record(attributes::HIVE_GRAPHQL_ERROR_COUNT, count);
@@ -7,9 +7,11 @@ use http::{
 use http_body_util::Full;
 use hyper::body::Body;
 use ntex::http::{body::MessageBody, HeaderMap as NtexHeaderMap};
+use opentelemetry::KeyValue;
 use std::borrow::{Borrow, Cow};
 use std::net::{IpAddr, SocketAddr};
 use tracing::{field::Empty, info_span, record_all, Level, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 /// Minimal request interface required to build an HTTP server span.
 ///
@@ -139,6 +141,8 @@ impl HttpServerRequestSpan {
             "client.port" = client_port,
             "network.peer.address" = peer_address,
             "network.peer.port" = peer_port,
+            // Hive
+            "hive.request.id" = Empty,
         );
 
         Self { span }
@@ -149,6 +153,21 @@ impl HttpServerRequestSpan {
             .record(attributes::HTTP_REQUEST_BODY_SIZE, body_size);
     }
 
+    pub fn record_request_id(&self, request_id: &str) {
+        self.span.record(attributes::HIVE_REQUEST_ID, request_id);
+    }
+
+    /// Records the attributes set by the plugins while handling the request.
+    pub fn record_plugin_attributes(&self, attributes: Vec<KeyValue>) {
+        if self.span.is_disabled() {
+            return;
+        }
+
+        for attribute in attributes {
+            self.span.set_attribute(attribute.key, attribute.value);
+        }
+    }
+
     pub fn record_response(&self, response: &ntex::web::HttpResponse) {
         if self.span.is_disabled() {
             return;
@@ -243,11 +262,22 @@ impl HttpClientRequestSpan {
             "user_agent.original" = header_user_agent.as_ref().and_then(|v| v.to_str().ok()),
             "http.response.status_code" = Empty,
             "http.response.body.size" = Empty,
+            "http.request.resend_count" = Empty,
         );
 
         Self { span }
     }
 
+    /// Records the ordinal number of the request resending attempt,
+    /// for requests that are retried.
+    pub fn record_resend_count(&self, resend_count: u32) {
+        if self.span.is_disabled() {
+            return;
+        }
+
+        self.span.record("http.request.resend_count", resend_count);
+    }
+
     pub fn record_response<B>(&self, response: &Response<B>)
     where
         B: Body<Data = Bytes>,
@@ -413,6 +443,18 @@ impl HttpInflightRequestSpan {
     }
 }
 
+/// The IP address of the client, resolved like the `client.address` attribute of the span:
+/// from the configured IP header, or the socket peer address.
+pub fn resolve_client_ip<Req: HttpServerSpanRequest>(
+    request: &Req,
+    client_ip_header_config: &Option<ClientIpHeaderConfig>,
+) -> Option<IpAddr> {
+    match resolve_client_address(request, client_ip_header_config) {
+        Some(client) => Some(client.parsed_ip),
+        None => request.peer_addr().map(|peer| peer.ip()),
+    }
+}
+
 fn resolve_client_address<'a, Req: HttpServerSpanRequest>(
     request: &'a Req,
     config: &Option<ClientIpHeaderConfig>,
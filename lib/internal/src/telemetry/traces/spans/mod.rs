@@ -12,6 +12,8 @@
 //! `HiveSpanKind` enumerates supported span kinds (e.g. `graphql.operation`, `http.server`),
 //! while `HiveEventKind` enumerates event kinds (e.g. GraphQL error events).
 pub const TARGET_NAME: &str = "hive-router";
+/// The target of the spans carrying fields for the log lines only, never exported as traces.
+pub const LOG_CONTEXT_TARGET_NAME: &str = "hive-router::log-context";
 
 pub mod attributes;
 pub mod coprocessor;
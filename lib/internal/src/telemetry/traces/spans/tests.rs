@@ -240,9 +240,18 @@ fn test_http_server_request_span() {
                 attributes::CLIENT_PORT,
                 attributes::NETWORK_PEER_ADDRESS,
                 attributes::NETWORK_PEER_PORT,
+                attributes::HIVE_REQUEST_ID,
             ],
         );
 
+        layer.assert_not_recorded(&span, attributes::HIVE_REQUEST_ID);
+        span.record_request_id("01JAR7M1X4Q7ZC6C6QK6B6W2D0");
+        layer.assert_recorded_value(
+            &span,
+            attributes::HIVE_REQUEST_ID,
+            "01JAR7M1X4Q7ZC6C6QK6B6W2D0",
+        );
+
         let response = ntex::web::HttpResponse::Ok().body(response_body);
         span.record_response(&response);
 
@@ -559,6 +568,7 @@ fn test_http_client_request_span() {
                 attributes::USER_AGENT_ORIGINAL,
                 attributes::HTTP_RESPONSE_STATUS_CODE,
                 attributes::HTTP_RESPONSE_BODY_SIZE,
+                attributes::HTTP_REQUEST_RESEND_COUNT,
             ],
         );
 
@@ -567,6 +577,9 @@ fn test_http_client_request_span() {
             .body(Full::new(response_body.clone()))
             .unwrap();
         span.record_response(&response);
+        span.record_resend_count(2);
+
+        layer.assert_recorded_value(&span, attributes::HTTP_REQUEST_RESEND_COUNT, "2");
 
         layer.assert_recorded_value(&span, attributes::HTTP_RESPONSE_STATUS_CODE, "200");
         layer.assert_recorded_value(
@@ -94,6 +94,7 @@ pub fn resolve_value_or_expression(
 ) -> Result<String, TelemetryError> {
     match value_or_expr {
         ValueOrExpression::Value(v) => Ok(v.clone()),
+        ValueOrExpression::Secret(secret) => Ok(secret.value().clone()),
         ValueOrExpression::Expression { expression } => {
             evaluate_expression_as_string(expression, context)
         }
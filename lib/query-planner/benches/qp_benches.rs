@@ -69,6 +69,7 @@ fn query_plan_pipeline(c: &mut Criterion) {
                 bb_override_context,
                 query_tree,
                 bb_kind,
+                &[],
                 &QueryPlannerOptions::default(),
                 &cancellation_token,
             )
@@ -120,6 +121,7 @@ fn query_plan_pipeline(c: &mut Criterion) {
                 bb_override_context,
                 query_tree,
                 bb_kind,
+                &[],
                 &QueryPlannerOptions::default(),
                 &cancellation_token,
             )
@@ -0,0 +1,450 @@
+use std::hash::{Hash, Hasher};
+
+use graphql_tools::parser::query::{
+    self as query_ast, Definition, Directive, Field, InlineFragment, OperationDefinition, Query,
+    Selection, SelectionSet, Value,
+};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::ast::normalization::context::NormalizationContext;
+use crate::ast::normalization::error::NormalizationError;
+use crate::ast::normalization::normalize_operation;
+use crate::ast::normalization::pipeline::{
+    drop_fragment_definitions, drop_unused_operations, inline_fragment_spreads,
+};
+use crate::ast::operation;
+use crate::state::supergraph_state::SupergraphState;
+
+const DEFER_DIRECTIVE_NAME: &str = "defer";
+
+/// A query with `@defer` fragments, split into the operations resolving each part of the response.
+#[derive(Debug, Clone)]
+pub struct DeferredOperation {
+    /// The operation without the deferred fragments, resolving the initial payload.
+    pub primary: operation::OperationDefinition,
+    /// The deferred fragments, in the order of the document.
+    pub fragments: Vec<DeferredFragment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeferredFragment {
+    pub label: Option<String>,
+    /// Response keys of the fields leading to the fragment.
+    pub path: Vec<String>,
+    /// The variable of the `if` argument, the fragment is deferred only when it's true.
+    pub if_variable: Option<String>,
+    /// The fields leading to the fragment, and the fields of the fragment.
+    pub operation: operation::OperationDefinition,
+}
+
+impl DeferredOperation {
+    pub fn hash(&self) -> u64 {
+        let mut hasher = Xxh3::new();
+        self.primary.hash().hash(&mut hasher);
+        for fragment in &self.fragments {
+            fragment.label.hash(&mut hasher);
+            fragment.path.hash(&mut hasher);
+            fragment.if_variable.hash(&mut hasher);
+            fragment.operation.hash().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+/// Splits a query with `@defer` fragments into its primary operation and deferred fragments,
+/// each of them normalized on its own.
+///
+/// Returns `None` for mutations, subscriptions and queries without deferred fragments.
+/// A fragment nested in a deferred fragment is delivered together with its parent.
+pub fn normalize_deferred_operation(
+    supergraph: &SupergraphState,
+    query: &query_ast::Document<'static, String>,
+    operation_name: Option<&str>,
+) -> Result<Option<DeferredOperation>, NormalizationError> {
+    if !query.definitions.iter().any(|definition| match definition {
+        Definition::Operation(operation) => {
+            contains_defer_directive(operation_selection_set(operation))
+        }
+        Definition::Fragment(fragment) => contains_defer_directive(&fragment.selection_set),
+    }) {
+        return Ok(None);
+    }
+
+    let mut document = query.clone();
+    let mut ctx = NormalizationContext {
+        operation_name,
+        document: &mut document,
+        supergraph,
+        root_types: supergraph.into(),
+        subgraph_name: None,
+    };
+    drop_unused_operations(&mut ctx)?;
+    inline_fragment_spreads(&mut ctx)?;
+    drop_fragment_definitions(&mut ctx)?;
+
+    let operation = document
+        .definitions
+        .into_iter()
+        .find_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            Definition::Fragment(_) => None,
+        })
+        .ok_or(NormalizationError::OperationNotFound)?;
+
+    let selection_set = match &operation {
+        OperationDefinition::SelectionSet(selection_set) => selection_set,
+        OperationDefinition::Query(query) => &query.selection_set,
+        OperationDefinition::Mutation(_) | OperationDefinition::Subscription(_) => {
+            return Ok(None);
+        }
+    };
+
+    let mut fragments = Vec::new();
+    let primary_selection_set = split_selection_set(
+        selection_set,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut fragments,
+    );
+
+    if fragments.is_empty() {
+        return Ok(None);
+    }
+
+    let normalize = |selection_set: SelectionSet<'static, String>| {
+        let document = query_ast::Document {
+            definitions: vec![Definition::Operation(with_selection_set(
+                &operation,
+                selection_set,
+            ))],
+        };
+        normalize_operation(supergraph, &document, operation_name).map(|doc| doc.operation)
+    };
+
+    Ok(Some(DeferredOperation {
+        primary: normalize(primary_selection_set)?,
+        fragments: fragments
+            .into_iter()
+            .map(|fragment| {
+                Ok(DeferredFragment {
+                    label: fragment.label,
+                    path: fragment.path,
+                    if_variable: fragment.if_variable,
+                    operation: normalize(SelectionSet {
+                        span: selection_set.span,
+                        items: vec![fragment.selection],
+                    })?,
+                })
+            })
+            .collect::<Result<_, NormalizationError>>()?,
+    }))
+}
+
+/// A deferred fragment, wrapped in the fields and fragments leading to it.
+struct SplitFragment {
+    label: Option<String>,
+    path: Vec<String>,
+    if_variable: Option<String>,
+    selection: Selection<'static, String>,
+}
+
+enum DeferDirective {
+    /// `@defer(if: false)`, the fragment is part of the primary operation.
+    Disabled,
+    Enabled {
+        label: Option<String>,
+        if_variable: Option<String>,
+    },
+}
+
+fn defer_directive(directives: &[Directive<'static, String>]) -> Option<DeferDirective> {
+    let directive = directives
+        .iter()
+        .find(|directive| directive.name == DEFER_DIRECTIVE_NAME)?;
+    let argument = |name: &str| {
+        directive
+            .arguments
+            .iter()
+            .find(|(argument_name, _)| argument_name == name)
+            .map(|(_, value)| value)
+    };
+
+    let if_variable = match argument("if") {
+        Some(Value::Boolean(false)) => return Some(DeferDirective::Disabled),
+        Some(Value::Variable(name)) => Some(name.clone()),
+        _ => None,
+    };
+    let label = match argument("label") {
+        Some(Value::String(label)) => Some(label.clone()),
+        _ => None,
+    };
+
+    Some(DeferDirective::Enabled { label, if_variable })
+}
+
+fn without_defer_directive(
+    directives: &[Directive<'static, String>],
+) -> Vec<Directive<'static, String>> {
+    directives
+        .iter()
+        .filter(|directive| directive.name != DEFER_DIRECTIVE_NAME)
+        .cloned()
+        .collect()
+}
+
+/// Removes the deferred fragments from the selection set, and collects them in `fragments`.
+///
+/// `ancestors` holds the fields and fragments leading to the selection set, without their selections.
+fn split_selection_set(
+    selection_set: &SelectionSet<'static, String>,
+    ancestors: &mut Vec<Selection<'static, String>>,
+    path: &mut Vec<String>,
+    fragments: &mut Vec<SplitFragment>,
+) -> SelectionSet<'static, String> {
+    let mut items = Vec::with_capacity(selection_set.items.len());
+
+    for item in &selection_set.items {
+        match item {
+            Selection::Field(field) => {
+                path.push(field.alias.as_ref().unwrap_or(&field.name).clone());
+                ancestors.push(Selection::Field(field_without_selections(field)));
+                let selection_set =
+                    split_selection_set(&field.selection_set, ancestors, path, fragments);
+                ancestors.pop();
+                path.pop();
+
+                items.push(Selection::Field(Field {
+                    selection_set,
+                    ..field_without_selections(field)
+                }));
+            }
+            Selection::InlineFragment(fragment) => match defer_directive(&fragment.directives) {
+                Some(DeferDirective::Enabled { label, if_variable }) => {
+                    let selection = Selection::InlineFragment(InlineFragment {
+                        position: fragment.position,
+                        type_condition: fragment.type_condition.clone(),
+                        directives: without_defer_directive(&fragment.directives),
+                        selection_set: strip_defer_directives(&fragment.selection_set),
+                    });
+                    let selection = ancestors.iter().rev().fold(selection, |inner, ancestor| {
+                        let mut ancestor = ancestor.clone();
+                        match &mut ancestor {
+                            Selection::Field(field) => field.selection_set.items.push(inner),
+                            Selection::InlineFragment(fragment) => {
+                                fragment.selection_set.items.push(inner)
+                            }
+                            Selection::FragmentSpread(_) => {}
+                        }
+                        ancestor
+                    });
+
+                    fragments.push(SplitFragment {
+                        label,
+                        path: path.clone(),
+                        if_variable,
+                        selection,
+                    });
+                }
+                Some(DeferDirective::Disabled) | None => {
+                    let directives = without_defer_directive(&fragment.directives);
+                    ancestors.push(Selection::InlineFragment(InlineFragment {
+                        position: fragment.position,
+                        type_condition: fragment.type_condition.clone(),
+                        directives: directives.clone(),
+                        selection_set: empty_selection_set(&fragment.selection_set),
+                    }));
+                    let selection_set =
+                        split_selection_set(&fragment.selection_set, ancestors, path, fragments);
+                    ancestors.pop();
+
+                    items.push(Selection::InlineFragment(InlineFragment {
+                        position: fragment.position,
+                        type_condition: fragment.type_condition.clone(),
+                        directives,
+                        selection_set,
+                    }));
+                }
+            },
+            Selection::FragmentSpread(_) => {
+                // Fragment spreads are inlined before the split.
+                items.push(item.clone());
+            }
+        }
+    }
+
+    SelectionSet {
+        span: selection_set.span,
+        items,
+    }
+}
+
+/// Removes the `@defer` directives of the fragments nested in a deferred fragment.
+fn strip_defer_directives(
+    selection_set: &SelectionSet<'static, String>,
+) -> SelectionSet<'static, String> {
+    SelectionSet {
+        span: selection_set.span,
+        items: selection_set
+            .items
+            .iter()
+            .map(|item| match item {
+                Selection::Field(field) => Selection::Field(Field {
+                    selection_set: strip_defer_directives(&field.selection_set),
+                    ..field_without_selections(field)
+                }),
+                Selection::InlineFragment(fragment) => Selection::InlineFragment(InlineFragment {
+                    position: fragment.position,
+                    type_condition: fragment.type_condition.clone(),
+                    directives: without_defer_directive(&fragment.directives),
+                    selection_set: strip_defer_directives(&fragment.selection_set),
+                }),
+                Selection::FragmentSpread(_) => item.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn field_without_selections(field: &Field<'static, String>) -> Field<'static, String> {
+    Field {
+        position: field.position,
+        alias: field.alias.clone(),
+        name: field.name.clone(),
+        arguments: field.arguments.clone(),
+        directives: field.directives.clone(),
+        selection_set: empty_selection_set(&field.selection_set),
+    }
+}
+
+fn empty_selection_set(
+    selection_set: &SelectionSet<'static, String>,
+) -> SelectionSet<'static, String> {
+    SelectionSet {
+        span: selection_set.span,
+        items: Vec::new(),
+    }
+}
+
+fn with_selection_set(
+    operation: &OperationDefinition<'static, String>,
+    selection_set: SelectionSet<'static, String>,
+) -> OperationDefinition<'static, String> {
+    match operation {
+        OperationDefinition::Query(query) => OperationDefinition::Query(Query {
+            position: query.position,
+            name: query.name.clone(),
+            variable_definitions: query.variable_definitions.clone(),
+            directives: query.directives.clone(),
+            selection_set,
+        }),
+        _ => OperationDefinition::SelectionSet(selection_set),
+    }
+}
+
+fn operation_selection_set<'a>(
+    operation: &'a OperationDefinition<'static, String>,
+) -> &'a SelectionSet<'static, String> {
+    match operation {
+        OperationDefinition::SelectionSet(selection_set) => selection_set,
+        OperationDefinition::Query(query) => &query.selection_set,
+        OperationDefinition::Mutation(mutation) => &mutation.selection_set,
+        OperationDefinition::Subscription(subscription) => &subscription.selection_set,
+    }
+}
+
+fn contains_defer_directive(selection_set: &SelectionSet<'static, String>) -> bool {
+    selection_set.items.iter().any(|item| match item {
+        Selection::Field(field) => contains_defer_directive(&field.selection_set),
+        Selection::InlineFragment(fragment) => {
+            fragment
+                .directives
+                .iter()
+                .any(|directive| directive.name == DEFER_DIRECTIVE_NAME)
+                || contains_defer_directive(&fragment.selection_set)
+        }
+        Selection::FragmentSpread(spread) => spread
+            .directives
+            .iter()
+            .any(|directive| directive.name == DEFER_DIRECTIVE_NAME),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::normalization::defer::normalize_deferred_operation;
+    use crate::state::supergraph_state::SupergraphState;
+    use crate::utils::parsing::{parse_operation, parse_schema};
+
+    fn supergraph() -> SupergraphState {
+        let schema = parse_schema(
+            r#"
+            type Query {
+              me: User
+              users: [User]
+            }
+
+            type User {
+              id: ID!
+              name: String
+              friends: [User]
+            }
+            "#,
+        );
+        SupergraphState::new(&schema)
+    }
+
+    #[test]
+    fn splits_deferred_fragments() {
+        let supergraph = supergraph();
+        let document = parse_operation(
+            r#"
+            query ($slow: Boolean!) {
+              me {
+                id
+                ... @defer(label: "name") { name }
+              }
+              users {
+                ...Friends @defer(if: $slow)
+              }
+            }
+
+            fragment Friends on User {
+              friends { id ... @defer { name } }
+            }
+            "#,
+        );
+
+        let deferred = normalize_deferred_operation(&supergraph, &document, None)
+            .unwrap()
+            .expect("operation should have deferred fragments");
+
+        insta::assert_snapshot!(deferred.primary, @"query($slow:Boolean!){me{id} users{__typename}}");
+        assert_eq!(deferred.fragments.len(), 2);
+
+        let name = &deferred.fragments[0];
+        assert_eq!(name.label.as_deref(), Some("name"));
+        assert_eq!(name.path, vec!["me".to_string()]);
+        assert_eq!(name.if_variable, None);
+        insta::assert_snapshot!(name.operation, @"query($slow:Boolean!){me{name}}");
+
+        let friends = &deferred.fragments[1];
+        assert_eq!(friends.label, None);
+        assert_eq!(friends.path, vec!["users".to_string()]);
+        assert_eq!(friends.if_variable.as_deref(), Some("slow"));
+        insta::assert_snapshot!(friends.operation, @"query($slow:Boolean!){users{friends{id name}}}");
+    }
+
+    #[test]
+    fn ignores_disabled_and_non_query_defer() {
+        let supergraph = supergraph();
+
+        let document = parse_operation("{ me { id ... @defer(if: false) { name } } }");
+        assert!(normalize_deferred_operation(&supergraph, &document, None)
+            .unwrap()
+            .is_none());
+
+        let document = parse_operation("{ me { id name } }");
+        assert!(normalize_deferred_operation(&supergraph, &document, None)
+            .unwrap()
+            .is_none());
+    }
+}
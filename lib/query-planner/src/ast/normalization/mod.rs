@@ -1,6 +1,7 @@
 use graphql_tools::parser::query::{self as query_ast, Definition, OperationDefinition};
 
 pub mod context;
+pub mod defer;
 pub mod error;
 pub mod pipeline;
 pub mod utils;
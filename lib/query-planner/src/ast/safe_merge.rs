@@ -32,10 +32,13 @@ pub enum ConflictResolutionLocation {
 
 pub type AliasesRecords = Vec<(MergePath, String)>;
 
+/// Prefix of the aliases given to conflicting fields merged into the same selection set.
+pub const INTERNAL_ALIAS_PREFIX: &str = "_internal_qp_alias_";
+
 impl SafeSelectionSetMerger {
     pub fn safe_next_alias_name(&mut self, target_existing: &[SelectionItem]) -> String {
         loop {
-            let alias = format!("_internal_qp_alias_{}", self.aliases_counter);
+            let alias = format!("{INTERNAL_ALIAS_PREFIX}{}", self.aliases_counter);
             self.aliases_counter += 1;
 
             let exists = target_existing
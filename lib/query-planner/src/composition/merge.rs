@@ -0,0 +1,1016 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use graphql_tools::parser::schema::{
+    Definition, Directive, DirectiveDefinition, Document, EnumType, EnumValue, Field,
+    InputObjectType, InputValue, InterfaceType, ObjectType, ScalarType, SchemaDefinition, Type,
+    TypeDefinition, UnionType, Value,
+};
+
+use super::{
+    subgraph::{Key, Subgraph, SubgraphField, SubgraphType, TypeKind},
+    CompositionError,
+};
+
+type StaticDirective = Directive<'static, String>;
+type StaticType = Type<'static, String>;
+type StaticInputValue = InputValue<'static, String>;
+type StaticTypeDefinition = TypeDefinition<'static, String>;
+
+const JOIN_DIRECTIVES: &str = r#"directive @join__directive(
+  graphs: [join__Graph!]
+  name: String!
+  args: join__DirectiveArguments
+) repeatable on SCHEMA | OBJECT | INTERFACE | FIELD_DEFINITION
+
+directive @join__enumValue(graph: join__Graph!) repeatable on ENUM_VALUE
+
+directive @join__field(
+  graph: join__Graph
+  requires: join__FieldSet
+  provides: join__FieldSet
+  type: String
+  external: Boolean
+  override: String
+  usedOverridden: Boolean
+  overrideLabel: String
+  contextArguments: [join__ContextArgument!]
+) repeatable on FIELD_DEFINITION | INPUT_FIELD_DEFINITION
+
+directive @join__graph(name: String!, url: String!) on ENUM_VALUE
+
+directive @join__implements(
+  graph: join__Graph!
+  interface: String!
+) repeatable on OBJECT | INTERFACE
+
+directive @join__type(
+  graph: join__Graph!
+  key: join__FieldSet
+  extension: Boolean! = false
+  resolvable: Boolean! = true
+  isInterfaceObject: Boolean! = false
+) repeatable on OBJECT | INTERFACE | UNION | ENUM | INPUT_OBJECT | SCALAR
+
+directive @join__unionMember(
+  graph: join__Graph!
+  member: String!
+) repeatable on UNION
+
+directive @link(
+  url: String
+  as: String
+  for: link__Purpose
+  import: [link__Import]
+) repeatable on SCHEMA
+"#;
+
+const JOIN_TYPES: &str = r#"input join__ContextArgument {
+  name: String!
+  type: String!
+  context: String!
+  selection: join__FieldValue!
+}
+
+scalar join__DirectiveArguments
+
+scalar join__FieldSet
+
+scalar join__FieldValue
+
+scalar link__Import
+
+enum link__Purpose {
+  """
+  `SECURITY` features provide metadata necessary to securely resolve fields.
+  """
+  SECURITY
+
+  """
+  `EXECUTION` features provide metadata necessary for operation execution.
+  """
+  EXECUTION
+}
+"#;
+
+/// A spec linked by the supergraph when one of its directives is used.
+struct Spec {
+    directives: &'static [&'static str],
+    url: &'static str,
+    purpose: Option<&'static str>,
+    /// Specs with several directives import them, the others are referenced by the spec name.
+    imports: bool,
+    definitions: &'static str,
+}
+
+const SPECS: [Spec; 5] = [
+    Spec {
+        directives: &["authenticated"],
+        url: "https://specs.apollo.dev/authenticated/v0.1",
+        purpose: Some("SECURITY"),
+        imports: false,
+        definitions: "directive @authenticated on FIELD_DEFINITION | OBJECT | INTERFACE | SCALAR | ENUM\n",
+    },
+    Spec {
+        directives: &["cost", "listSize"],
+        url: "https://specs.apollo.dev/cost/v0.1",
+        purpose: None,
+        imports: true,
+        definitions: r#"directive @cost(
+  weight: Int!
+) on ARGUMENT_DEFINITION | ENUM | FIELD_DEFINITION | INPUT_FIELD_DEFINITION | OBJECT | SCALAR
+
+directive @listSize(
+  assumedSize: Int
+  slicingArguments: [String!]
+  sizedFields: [String!]
+  requireOneSlicingArgument: Boolean = true
+) on FIELD_DEFINITION
+"#,
+    },
+    Spec {
+        directives: &["inaccessible"],
+        url: "https://specs.apollo.dev/inaccessible/v0.2",
+        purpose: Some("SECURITY"),
+        imports: false,
+        definitions: "directive @inaccessible on FIELD_DEFINITION | OBJECT | INTERFACE | UNION | ARGUMENT_DEFINITION | SCALAR | ENUM | ENUM_VALUE | INPUT_OBJECT | INPUT_FIELD_DEFINITION\n",
+    },
+    Spec {
+        directives: &["requiresScopes"],
+        url: "https://specs.apollo.dev/requiresScopes/v0.1",
+        purpose: Some("SECURITY"),
+        imports: false,
+        definitions: r#"directive @requiresScopes(
+  scopes: [[requiresScopes__Scope!]!]!
+) on FIELD_DEFINITION | OBJECT | INTERFACE | SCALAR | ENUM
+
+scalar requiresScopes__Scope
+"#,
+    },
+    Spec {
+        directives: &["tag"],
+        url: "https://specs.apollo.dev/tag/v0.3",
+        purpose: None,
+        imports: false,
+        definitions: "directive @tag(\n  name: String!\n) repeatable on FIELD_DEFINITION | OBJECT | INTERFACE | UNION | ARGUMENT_DEFINITION | SCALAR | ENUM | ENUM_VALUE | INPUT_OBJECT | INPUT_FIELD_DEFINITION | SCHEMA\n",
+    },
+];
+
+/// A subgraph, with the name of its value in the `join__Graph` enum.
+struct Graph<'s> {
+    enum_value: String,
+    subgraph: &'s Subgraph,
+}
+
+/// The definition of a type in one of the subgraphs.
+type Entry<'s> = (usize, &'s SubgraphType);
+
+/// The definition of a field in one of the subgraphs.
+type FieldEntry<'s> = (usize, &'s SubgraphType, &'s SubgraphField);
+
+/// How a subgraph resolves a field, printed as `@join__field`.
+struct JoinField<'s> {
+    graph: usize,
+    requires: Option<&'s str>,
+    provides: Option<&'s str>,
+    external: bool,
+    override_from: Option<&'s str>,
+    override_label: Option<&'s str>,
+    used_overridden: bool,
+}
+
+impl JoinField<'_> {
+    fn is_plain(&self) -> bool {
+        self.requires.is_none()
+            && self.provides.is_none()
+            && !self.external
+            && self.override_from.is_none()
+            && self.override_label.is_none()
+            && !self.used_overridden
+    }
+
+    /// Returns true if the subgraph resolves the field for every request,
+    /// without it being overridden or progressively migrated to another subgraph.
+    fn always_resolves(&self) -> bool {
+        !self.external && !self.used_overridden && self.override_label.is_none()
+    }
+}
+
+struct Merger<'s> {
+    graphs: Vec<Graph<'s>>,
+    input_types: HashSet<&'s str>,
+    output_types: HashSet<&'s str>,
+    used_directives: BTreeSet<String>,
+}
+
+pub(super) fn merge(subgraphs: &[Subgraph]) -> Result<String, CompositionError> {
+    let mut merger = Merger::new(subgraphs);
+
+    let type_names: BTreeSet<&str> = subgraphs
+        .iter()
+        .flat_map(|subgraph| subgraph.types.iter().map(|t| t.name.as_str()))
+        .collect();
+
+    let mut definitions = Vec::with_capacity(type_names.len());
+    for type_name in type_names {
+        definitions.push(merger.merge_type(type_name)?);
+    }
+    merger.add_interface_object_fields(&mut definitions);
+
+    Ok(merger.print(definitions))
+}
+
+impl<'s> Merger<'s> {
+    fn new(subgraphs: &'s [Subgraph]) -> Self {
+        let mut graphs: Vec<Graph<'s>> = Vec::with_capacity(subgraphs.len());
+        for subgraph in subgraphs {
+            let base: String = subgraph
+                .name
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() {
+                        c.to_ascii_uppercase()
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            let base = match base.chars().next() {
+                Some(c) if c.is_ascii_alphabetic() || c == '_' => base,
+                _ => format!("_{base}"),
+            };
+            let mut enum_value = base.clone();
+            let mut suffix = 1;
+            while graphs.iter().any(|graph| graph.enum_value == enum_value) {
+                suffix += 1;
+                enum_value = format!("{base}_{suffix}");
+            }
+            graphs.push(Graph {
+                enum_value,
+                subgraph,
+            });
+        }
+
+        let mut input_types = HashSet::new();
+        let mut output_types = HashSet::new();
+        for subgraph_type in subgraphs.iter().flat_map(|subgraph| subgraph.types.iter()) {
+            for field in &subgraph_type.fields {
+                output_types.insert(named_type(&field.field_type));
+                for argument in &field.arguments {
+                    input_types.insert(named_type(&argument.value_type));
+                }
+            }
+            for field in &subgraph_type.input_fields {
+                input_types.insert(named_type(&field.value_type));
+            }
+        }
+
+        Self {
+            graphs,
+            input_types,
+            output_types,
+            used_directives: BTreeSet::new(),
+        }
+    }
+
+    fn entries(&self, type_name: &str) -> Vec<Entry<'s>> {
+        self.graphs
+            .iter()
+            .enumerate()
+            .filter_map(|(graph, g)| g.subgraph.get_type(type_name).map(|t| (graph, t)))
+            .collect()
+    }
+
+    fn graph_value(&self, graph: usize) -> Value<'static, String> {
+        Value::Enum(self.graphs[graph].enum_value.clone())
+    }
+
+    fn merge_type(&mut self, type_name: &str) -> Result<StaticTypeDefinition, CompositionError> {
+        let entries = self.entries(type_name);
+        let is_interface = entries.iter().any(|(_, t)| t.kind == TypeKind::Interface);
+        let kind = if is_interface {
+            TypeKind::Interface
+        } else {
+            entries[0].1.kind
+        };
+        for (graph, subgraph_type) in &entries {
+            let is_interface_object = is_interface
+                && subgraph_type.kind == TypeKind::Object
+                && subgraph_type.interface_object;
+            if subgraph_type.kind != kind && !is_interface_object {
+                return Err(CompositionError::TypeMerge(
+                    type_name.to_string(),
+                    format!(
+                        "defined as {} in subgraph '{}' and as {} in subgraph '{}'",
+                        kind.as_str(),
+                        self.graphs[entries[0].0].subgraph.name,
+                        subgraph_type.kind.as_str(),
+                        self.graphs[*graph].subgraph.name,
+                    ),
+                ));
+            }
+        }
+
+        let description = entries.iter().find_map(|(_, t)| t.description.clone());
+        let mut directives = self.join_types(type_name, &entries);
+        for (_, subgraph_type) in &entries {
+            for directive in &subgraph_type.directives {
+                self.add_directive(&mut directives, directive);
+            }
+        }
+
+        Ok(match kind {
+            TypeKind::Scalar => TypeDefinition::Scalar(ScalarType {
+                description,
+                directives,
+                ..ScalarType::new(type_name.to_string())
+            }),
+            TypeKind::Object => {
+                let (implements_interfaces, join_implements) = self.merge_implements(&entries);
+                directives.extend(join_implements);
+                TypeDefinition::Object(ObjectType {
+                    description,
+                    implements_interfaces,
+                    directives,
+                    fields: self.merge_fields(type_name, &entries, true)?,
+                    ..ObjectType::new(type_name.to_string())
+                })
+            }
+            TypeKind::Interface => {
+                let interfaces: Vec<Entry<'s>> = entries
+                    .iter()
+                    .copied()
+                    .filter(|(_, t)| !t.interface_object)
+                    .collect();
+                let (implements_interfaces, join_implements) = self.merge_implements(&interfaces);
+                directives.extend(join_implements);
+                TypeDefinition::Interface(InterfaceType {
+                    description,
+                    implements_interfaces,
+                    directives,
+                    fields: self.merge_fields(type_name, &entries, false)?,
+                    ..InterfaceType::new(type_name.to_string())
+                })
+            }
+            TypeKind::Union => {
+                let mut types: Vec<String> = vec![];
+                for (graph, subgraph_type) in &entries {
+                    for member in &subgraph_type.members {
+                        directives.push(directive(
+                            "join__unionMember",
+                            vec![
+                                ("graph", self.graph_value(*graph)),
+                                ("member", Value::String(member.clone())),
+                            ],
+                        ));
+                        if !types.contains(member) {
+                            types.push(member.clone());
+                        }
+                    }
+                }
+                TypeDefinition::Union(UnionType {
+                    description,
+                    directives,
+                    types,
+                    ..UnionType::new(type_name.to_string())
+                })
+            }
+            TypeKind::Enum => TypeDefinition::Enum(EnumType {
+                description,
+                directives,
+                values: self.merge_enum_values(type_name, &entries)?,
+                ..EnumType::new(type_name.to_string())
+            }),
+            TypeKind::InputObject => {
+                let fields: Vec<&[StaticInputValue]> = entries
+                    .iter()
+                    .map(|(_, t)| t.input_fields.as_slice())
+                    .collect();
+                TypeDefinition::InputObject(InputObjectType {
+                    description,
+                    directives,
+                    fields: self.merge_input_values(type_name, "field", &fields)?,
+                    ..InputObjectType::new(type_name.to_string())
+                })
+            }
+        })
+    }
+
+    /// Builds the `@join__type` directives, one per key of the type in each subgraph.
+    fn join_types(&self, type_name: &str, entries: &[Entry<'s>]) -> Vec<StaticDirective> {
+        // every subgraph can resolve the query root type, even without defining it
+        if type_name == "Query" {
+            return (0..self.graphs.len())
+                .map(|graph| directive("join__type", vec![("graph", self.graph_value(graph))]))
+                .collect();
+        }
+
+        let mut directives = vec![];
+        for (graph, subgraph_type) in entries {
+            let mut arguments = vec![("graph", self.graph_value(*graph))];
+            if subgraph_type.keys.is_empty() {
+                if subgraph_type.interface_object {
+                    arguments.push(("isInterfaceObject", Value::Boolean(true)));
+                }
+                directives.push(directive("join__type", arguments));
+                continue;
+            }
+            for Key { fields, resolvable } in &subgraph_type.keys {
+                let mut arguments = arguments.clone();
+                arguments.push(("key", Value::String(fields.clone())));
+                if subgraph_type.extension && !self.graphs[*graph].subgraph.federation_v2 {
+                    arguments.push(("extension", Value::Boolean(true)));
+                }
+                if !resolvable {
+                    arguments.push(("resolvable", Value::Boolean(false)));
+                }
+                if subgraph_type.interface_object {
+                    arguments.push(("isInterfaceObject", Value::Boolean(true)));
+                }
+                directives.push(directive("join__type", arguments));
+            }
+        }
+        directives
+    }
+
+    fn merge_implements(&self, entries: &[Entry<'s>]) -> (Vec<String>, Vec<StaticDirective>) {
+        let mut interfaces: Vec<String> = vec![];
+        let mut directives = vec![];
+        for (graph, subgraph_type) in entries {
+            for interface in &subgraph_type.implements {
+                directives.push(directive(
+                    "join__implements",
+                    vec![
+                        ("graph", self.graph_value(*graph)),
+                        ("interface", Value::String(interface.clone())),
+                    ],
+                ));
+                if !interfaces.contains(interface) {
+                    interfaces.push(interface.clone());
+                }
+            }
+        }
+        (interfaces, directives)
+    }
+
+    fn merge_fields(
+        &mut self,
+        type_name: &str,
+        entries: &[Entry<'s>],
+        is_object: bool,
+    ) -> Result<Vec<Field<'static, String>>, CompositionError> {
+        let graph_count = if type_name == "Query" {
+            self.graphs.len()
+        } else {
+            entries.len()
+        };
+
+        let mut field_names: Vec<&str> = vec![];
+        for (_, subgraph_type) in entries {
+            for field in &subgraph_type.fields {
+                if !field_names.contains(&field.name.as_str()) {
+                    field_names.push(&field.name);
+                }
+            }
+        }
+
+        let mut fields = Vec::with_capacity(field_names.len());
+        for field_name in field_names {
+            let field_entries: Vec<FieldEntry<'s>> = entries
+                .iter()
+                .filter_map(|(graph, t)| t.field(field_name).map(|field| (*graph, *t, field)))
+                .collect();
+            fields.push(self.merge_field(type_name, graph_count, &field_entries, is_object)?);
+        }
+        Ok(fields)
+    }
+
+    fn merge_field(
+        &mut self,
+        type_name: &str,
+        graph_count: usize,
+        entries: &[FieldEntry<'s>],
+        is_object: bool,
+    ) -> Result<Field<'static, String>, CompositionError> {
+        let field_name = entries[0].2.name.as_str();
+        let error = |message: String| {
+            CompositionError::TypeMerge(
+                type_name.to_string(),
+                format!("field '{field_name}' {message}"),
+            )
+        };
+
+        let mut field_type = entries[0].2.field_type.clone();
+        for (_, _, field) in entries.iter().skip(1) {
+            field_type = merge_output_type(&field_type, &field.field_type).ok_or_else(|| {
+                error(format!(
+                    "has incompatible types {} and {}",
+                    field_type, field.field_type
+                ))
+            })?;
+        }
+        let types_differ = entries
+            .iter()
+            .any(|(_, _, field)| field.field_type != field_type);
+
+        // the key fields of the extended entities are external in federation v1,
+        // even though the subgraph resolves them
+        let external: Vec<bool> = entries
+            .iter()
+            .map(|(graph, subgraph_type, field)| {
+                field.external
+                    && !(subgraph_type.is_key_field(field_name)
+                        && (subgraph_type.extension || !self.graphs[*graph].subgraph.federation_v2))
+            })
+            .collect();
+        if external.iter().all(|external| *external) {
+            return Err(error("is marked @external in every subgraph".to_string()));
+        }
+
+        let mut overridden: HashMap<&str, Option<&str>> = HashMap::new();
+        for (_, _, field) in entries {
+            if let Some(from) = field.override_from.as_deref() {
+                overridden.insert(from, field.override_label.as_deref());
+            }
+        }
+
+        let mut join_fields: Vec<JoinField<'s>> = Vec::with_capacity(entries.len());
+        for ((graph, subgraph_type, field), external) in entries.iter().zip(&external) {
+            let mut join_field = JoinField {
+                graph: *graph,
+                requires: field.requires.as_deref(),
+                provides: field.provides.as_deref(),
+                external: *external,
+                override_from: field.override_from.as_deref(),
+                override_label: field.override_label.as_deref(),
+                used_overridden: false,
+            };
+            if let Some(label) = overridden.get(self.graphs[*graph].subgraph.name.as_str()) {
+                match label {
+                    Some(label) => join_field.override_label = Some(label),
+                    // the overridden field still takes part in the keys of the entity
+                    None if subgraph_type.is_key_field(field_name) => {
+                        join_field.used_overridden = true
+                    }
+                    None => continue,
+                }
+            }
+            join_fields.push(join_field);
+        }
+
+        if is_object {
+            let resolving: Vec<&FieldEntry<'s>> = join_fields
+                .iter()
+                .filter(|join_field| join_field.always_resolves())
+                .filter_map(|join_field| {
+                    entries
+                        .iter()
+                        .find(|(graph, _, _)| *graph == join_field.graph)
+                })
+                .collect();
+            if resolving.len() > 1 {
+                for (graph, subgraph_type, field) in &resolving {
+                    let subgraph = self.graphs[*graph].subgraph;
+                    let shareable = !subgraph.federation_v2
+                        || field.shareable
+                        || subgraph_type.shareable
+                        || subgraph_type.is_key_field(field_name);
+                    if !shareable {
+                        let subgraphs: Vec<&str> = resolving
+                            .iter()
+                            .map(|(graph, _, _)| self.graphs[*graph].subgraph.name.as_str())
+                            .collect();
+                        return Err(error(format!(
+                            "is resolved by subgraphs '{}' but is not marked @shareable in subgraph '{}'",
+                            subgraphs.join("', '"),
+                            subgraph.name
+                        )));
+                    }
+                }
+            }
+        }
+
+        let mut directives = vec![];
+        let needs_join_field = join_fields.len() != graph_count
+            || types_differ
+            || join_fields.iter().any(|join_field| !join_field.is_plain());
+        if needs_join_field {
+            for join_field in &join_fields {
+                let mut arguments = vec![("graph", self.graph_value(join_field.graph))];
+                if let Some(requires) = join_field.requires {
+                    arguments.push(("requires", Value::String(requires.to_string())));
+                }
+                if let Some(provides) = join_field.provides {
+                    arguments.push(("provides", Value::String(provides.to_string())));
+                }
+                if types_differ {
+                    let (_, _, field) = entries
+                        .iter()
+                        .find(|(graph, _, _)| *graph == join_field.graph)
+                        .expect("join field without entry");
+                    arguments.push(("type", Value::String(field.field_type.to_string())));
+                }
+                if join_field.external {
+                    arguments.push(("external", Value::Boolean(true)));
+                }
+                if let Some(from) = join_field.override_from {
+                    arguments.push(("override", Value::String(from.to_string())));
+                }
+                if join_field.used_overridden {
+                    arguments.push(("usedOverridden", Value::Boolean(true)));
+                }
+                if let Some(label) = join_field.override_label {
+                    arguments.push(("overrideLabel", Value::String(label.to_string())));
+                }
+                directives.push(directive("join__field", arguments));
+            }
+        }
+        for (_, _, field) in entries {
+            for field_directive in &field.directives {
+                self.add_directive(&mut directives, field_directive);
+            }
+        }
+
+        // the arguments of the external fields are only declared to match the resolving subgraph
+        let resolving_arguments: Vec<&[StaticInputValue]> = entries
+            .iter()
+            .zip(&external)
+            .filter(|(_, external)| !**external)
+            .map(|((_, _, field), _)| field.arguments.as_slice())
+            .collect();
+
+        Ok(Field {
+            position: Default::default(),
+            description: entries
+                .iter()
+                .find_map(|(_, _, field)| field.description.clone()),
+            name: field_name.to_string(),
+            arguments: self.merge_input_values(
+                type_name,
+                &format!("argument of field '{field_name}'"),
+                &resolving_arguments,
+            )?,
+            field_type,
+            directives,
+        })
+    }
+
+    /// Merges the arguments of a field, or the fields of an input object,
+    /// keeping the ones defined in every subgraph with their strictest type.
+    fn merge_input_values(
+        &mut self,
+        type_name: &str,
+        what: &str,
+        entries: &[&[StaticInputValue]],
+    ) -> Result<Vec<StaticInputValue>, CompositionError> {
+        let mut names: Vec<&str> = vec![];
+        for values in entries {
+            for value in values.iter() {
+                if !names.contains(&value.name.as_str()) {
+                    names.push(&value.name);
+                }
+            }
+        }
+
+        let mut merged = vec![];
+        for name in names {
+            let values: Vec<&StaticInputValue> = entries
+                .iter()
+                .filter_map(|values| values.iter().find(|value| value.name == name))
+                .collect();
+            if values.len() != entries.len() {
+                let required = values.iter().any(|value| {
+                    matches!(value.value_type, Type::NonNullType(_))
+                        && value.default_value.is_none()
+                });
+                if required {
+                    return Err(CompositionError::TypeMerge(
+                        type_name.to_string(),
+                        format!("required {what} '{name}' is not defined in every subgraph"),
+                    ));
+                }
+                continue;
+            }
+
+            let mut value = values[0].clone();
+            value.directives = vec![];
+            for other in &values {
+                value.value_type = merge_input_type(&value.value_type, &other.value_type)
+                    .ok_or_else(|| {
+                        CompositionError::TypeMerge(
+                            type_name.to_string(),
+                            format!(
+                                "{what} '{name}' has incompatible types {} and {}",
+                                value.value_type, other.value_type
+                            ),
+                        )
+                    })?;
+                if value.description.is_none() {
+                    value.description = other.description.clone();
+                }
+                if value.default_value.is_none() {
+                    value.default_value = other.default_value.clone();
+                }
+                for value_directive in &other.directives {
+                    self.add_directive(&mut value.directives, value_directive);
+                }
+            }
+            merged.push(value);
+        }
+        Ok(merged)
+    }
+
+    fn merge_enum_values(
+        &mut self,
+        type_name: &str,
+        entries: &[Entry<'s>],
+    ) -> Result<Vec<EnumValue<'static, String>>, CompositionError> {
+        let mut names: Vec<&str> = vec![];
+        for (_, subgraph_type) in entries {
+            for value in &subgraph_type.values {
+                if !names.contains(&value.name.as_str()) {
+                    names.push(&value.name);
+                }
+            }
+        }
+
+        let is_input = self.input_types.contains(type_name);
+        let is_output = self.output_types.contains(type_name);
+        let mut values = vec![];
+        for name in names {
+            let defined_in: Vec<(usize, &EnumValue<'static, String>)> = entries
+                .iter()
+                .filter_map(|(graph, t)| {
+                    t.values
+                        .iter()
+                        .find(|value| value.name == name)
+                        .map(|value| (*graph, value))
+                })
+                .collect();
+            if defined_in.len() != entries.len() {
+                // a value unknown to a subgraph can't be sent to it,
+                // and a value it doesn't know can't be returned by it
+                if is_input && is_output {
+                    return Err(CompositionError::TypeMerge(
+                        type_name.to_string(),
+                        format!(
+                            "value '{name}' is not defined in every subgraph, \
+                             but the enum is used both as an input and an output type"
+                        ),
+                    ));
+                }
+                if is_input {
+                    continue;
+                }
+            }
+
+            let mut value = EnumValue::new(name.to_string());
+            for (graph, subgraph_value) in defined_in {
+                if value.description.is_none() {
+                    value.description = subgraph_value.description.clone();
+                }
+                value.directives.push(directive(
+                    "join__enumValue",
+                    vec![("graph", self.graph_value(graph))],
+                ));
+                for value_directive in &subgraph_value.directives {
+                    self.add_directive(&mut value.directives, value_directive);
+                }
+            }
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    /// Adds the fields an `@interfaceObject` contributes to an interface
+    /// to the object types implementing it, as they are resolved through the interface.
+    fn add_interface_object_fields(&self, definitions: &mut [StaticTypeDefinition]) {
+        let mut contributed: HashMap<String, Vec<Field<'static, String>>> = HashMap::new();
+        for definition in definitions.iter() {
+            let TypeDefinition::Interface(interface) = definition else {
+                continue;
+            };
+            let field_names: HashSet<&str> = self
+                .entries(&interface.name)
+                .into_iter()
+                .filter(|(_, t)| t.interface_object)
+                .flat_map(|(_, t)| t.fields.iter().map(|field| field.name.as_str()))
+                .collect();
+            if field_names.is_empty() {
+                continue;
+            }
+            let fields = interface
+                .fields
+                .iter()
+                .filter(|field| field_names.contains(field.name.as_str()))
+                .map(|field| Field {
+                    directives: vec![directive("join__field", vec![])],
+                    ..field.clone()
+                })
+                .collect();
+            contributed.insert(interface.name.clone(), fields);
+        }
+        if contributed.is_empty() {
+            return;
+        }
+
+        for definition in definitions.iter_mut() {
+            let TypeDefinition::Object(object) = definition else {
+                continue;
+            };
+            for interface in &object.implements_interfaces {
+                let Some(fields) = contributed.get(interface) else {
+                    continue;
+                };
+                for field in fields {
+                    if !object.fields.iter().any(|other| other.name == field.name) {
+                        object.fields.push(field.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds a directive copied from a subgraph, unless another subgraph already applied it.
+    fn add_directive(&mut self, directives: &mut Vec<StaticDirective>, added: &StaticDirective) {
+        let exists = directives
+            .iter()
+            .any(|other| other.name == added.name && other.arguments == added.arguments);
+        if !exists {
+            self.used_directives.insert(added.name.clone());
+            directives.push(added.clone());
+        }
+    }
+
+    /// The executable directives defined in every subgraph, which clients may send.
+    fn executable_directives(&self) -> Vec<DirectiveDefinition<'static, String>> {
+        let mut graphs = self.graphs.iter().map(|graph| graph.subgraph);
+        let Some(first) = graphs.next() else {
+            return vec![];
+        };
+        let others: Vec<&Subgraph> = graphs.collect();
+        first
+            .executable_directives
+            .iter()
+            .filter(|definition| {
+                others.iter().all(|other| {
+                    other
+                        .executable_directives
+                        .iter()
+                        .any(|d| d.name == definition.name)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn print(&self, definitions: Vec<StaticTypeDefinition>) -> String {
+        let specs: Vec<&Spec> = SPECS
+            .iter()
+            .filter(|spec| {
+                spec.directives
+                    .iter()
+                    .any(|name| self.used_directives.contains(*name))
+            })
+            .collect();
+
+        let mut links = vec![
+            link("https://specs.apollo.dev/link/v1.0", None, None),
+            link(
+                "https://specs.apollo.dev/join/v0.5",
+                Some("EXECUTION"),
+                None,
+            ),
+        ];
+        for spec in &specs {
+            let imports = spec.imports.then_some(spec.directives);
+            links.push(link(spec.url, spec.purpose, imports));
+        }
+        let has_type = |name: &str| {
+            definitions.iter().any(|definition| match definition {
+                TypeDefinition::Object(object) => object.name == name,
+                _ => false,
+            })
+        };
+        let schema = SchemaDefinition {
+            position: Default::default(),
+            directives: links,
+            query: Some("Query".to_string()),
+            mutation: has_type("Mutation").then(|| "Mutation".to_string()),
+            subscription: has_type("Subscription").then(|| "Subscription".to_string()),
+        };
+
+        let mut output = Document::new(vec![Definition::SchemaDefinition(schema)]).to_string();
+        output.push('\n');
+        output.push_str(JOIN_DIRECTIVES);
+        for spec in &specs {
+            output.push('\n');
+            output.push_str(spec.definitions);
+        }
+        if self.used_directives.contains("oneOf") {
+            output.push_str("\ndirective @oneOf on INPUT_OBJECT\n");
+        }
+        for definition in self.executable_directives() {
+            output.push('\n');
+            output.push_str(&definition.to_string());
+        }
+        output.push('\n');
+        output.push_str(JOIN_TYPES);
+
+        let mut join_graph = EnumType::new("join__Graph".to_string());
+        join_graph.values = self
+            .graphs
+            .iter()
+            .map(|graph| EnumValue {
+                directives: vec![directive(
+                    "join__graph",
+                    vec![
+                        ("name", Value::String(graph.subgraph.name.clone())),
+                        ("url", Value::String(graph.subgraph.url.clone())),
+                    ],
+                )],
+                ..EnumValue::new(graph.enum_value.clone())
+            })
+            .collect();
+
+        let types = std::iter::once(TypeDefinition::Enum(join_graph))
+            .chain(definitions)
+            .map(Definition::TypeDefinition)
+            .collect();
+        output.push('\n');
+        output.push_str(&Document::new(types).to_string());
+        output
+    }
+}
+
+fn directive(name: &str, arguments: Vec<(&str, Value<'static, String>)>) -> StaticDirective {
+    Directive {
+        position: Default::default(),
+        name: name.to_string(),
+        arguments: arguments
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    }
+}
+
+fn link(url: &str, purpose: Option<&str>, imports: Option<&[&str]>) -> StaticDirective {
+    let mut arguments = vec![("url", Value::String(url.to_string()))];
+    if let Some(purpose) = purpose {
+        arguments.push(("for", Value::Enum(purpose.to_string())));
+    }
+    if let Some(imports) = imports {
+        let imports = imports
+            .iter()
+            .map(|name| Value::String(format!("@{name}")))
+            .collect();
+        arguments.push(("import", Value::List(imports)));
+    }
+    directive("link", arguments)
+}
+
+fn named_type(field_type: &StaticType) -> &str {
+    match field_type {
+        Type::NamedType(name) => name,
+        Type::ListType(inner) | Type::NonNullType(inner) => named_type(inner),
+    }
+}
+
+/// Merges the types a field has in two subgraphs into the least strict one,
+/// as the supergraph can't guarantee a value every subgraph doesn't.
+fn merge_output_type(left: &StaticType, right: &StaticType) -> Option<StaticType> {
+    match (left, right) {
+        (Type::NonNullType(left), Type::NonNullType(right)) => {
+            Some(Type::NonNullType(Box::new(merge_output_type(left, right)?)))
+        }
+        (Type::NonNullType(left), right) | (right, Type::NonNullType(left)) => {
+            merge_output_type(left, right)
+        }
+        (Type::ListType(left), Type::ListType(right)) => {
+            Some(Type::ListType(Box::new(merge_output_type(left, right)?)))
+        }
+        (Type::NamedType(left), Type::NamedType(right)) if left == right => {
+            Some(Type::NamedType(left.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Merges the types an input value has in two subgraphs into the strictest one,
+/// as the supergraph must only accept values every subgraph accepts.
+fn merge_input_type(left: &StaticType, right: &StaticType) -> Option<StaticType> {
+    match (left, right) {
+        (Type::NonNullType(left), Type::NonNullType(right)) => {
+            Some(Type::NonNullType(Box::new(merge_input_type(left, right)?)))
+        }
+        (Type::NonNullType(left), right) | (right, Type::NonNullType(left)) => {
+            Some(Type::NonNullType(Box::new(merge_input_type(left, right)?)))
+        }
+        (Type::ListType(left), Type::ListType(right)) => {
+            Some(Type::ListType(Box::new(merge_input_type(left, right)?)))
+        }
+        (Type::NamedType(left), Type::NamedType(right)) if left == right => {
+            Some(Type::NamedType(left.clone()))
+        }
+        _ => None,
+    }
+}
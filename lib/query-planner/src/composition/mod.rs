@@ -0,0 +1,497 @@
+//! Composes a supergraph from the schemas of its subgraphs.
+//!
+//! The composition covers the federation directives the query planner relies on
+//! (`@key`, `@external`, `@requires`, `@provides`, `@shareable`, `@override`,
+//! `@interfaceObject`) and copies the security and cost directives to the supergraph.
+//! It is meant for local development, where running an external composition step
+//! for every change of a subgraph is tedious.
+
+mod merge;
+mod subgraph;
+
+use self::subgraph::Subgraph;
+
+/// The schema of a subgraph, as returned by its `_service { sdl }` field.
+#[derive(Debug, Clone)]
+pub struct SubgraphSchema {
+    pub name: String,
+    /// The URL the router uses to reach the subgraph.
+    pub url: String,
+    pub sdl: String,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum CompositionError {
+    #[error("no subgraphs to compose")]
+    NoSubgraphs,
+    #[error("subgraph '{0}' is defined more than once")]
+    DuplicateSubgraph(String),
+    #[error("failed to parse the schema of subgraph '{0}': {1}")]
+    SubgraphParse(String, String),
+    #[error("invalid schema of subgraph '{0}': {1}")]
+    InvalidSubgraph(String, String),
+    #[error("failed to merge type '{0}': {1}")]
+    TypeMerge(String, String),
+}
+
+/// Composes the schemas of the subgraphs into the SDL of a supergraph.
+pub fn compose(subgraphs: &[SubgraphSchema]) -> Result<String, CompositionError> {
+    if subgraphs.is_empty() {
+        return Err(CompositionError::NoSubgraphs);
+    }
+
+    let mut parsed: Vec<Subgraph> = Vec::with_capacity(subgraphs.len());
+    for subgraph in subgraphs {
+        if parsed.iter().any(|other| other.name == subgraph.name) {
+            return Err(CompositionError::DuplicateSubgraph(subgraph.name.clone()));
+        }
+        parsed.push(Subgraph::parse(
+            &subgraph.name,
+            &subgraph.url,
+            &subgraph.sdl,
+        )?);
+    }
+
+    merge::merge(&parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{
+        ast::normalization::normalize_operation,
+        graph::PlannerOverrideContext,
+        planner::Planner,
+        utils::{
+            cancellation::CancellationToken,
+            parsing::{parse_operation, parse_schema},
+        },
+    };
+
+    use super::{compose, SubgraphSchema};
+
+    fn fixture(name: &str) -> SubgraphSchema {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("fixture/composition")
+            .join(format!("{name}.graphql"));
+        subgraph(
+            name,
+            &std::fs::read_to_string(path).expect("Unable to read subgraph schema"),
+        )
+    }
+
+    fn subgraph(name: &str, sdl: &str) -> SubgraphSchema {
+        SubgraphSchema {
+            name: name.to_string(),
+            url: format!("http://0.0.0.0:4200/{name}"),
+            sdl: sdl.to_string(),
+        }
+    }
+
+    /// Composes the subgraphs and returns the types of the supergraph, without the spec definitions.
+    fn compose_types(subgraphs: &[SubgraphSchema]) -> String {
+        let supergraph = compose(subgraphs).expect("failed to compose");
+        let start = supergraph
+            .find("enum join__Graph")
+            .expect("missing join__Graph enum");
+        supergraph[start..].to_string()
+    }
+
+    const FED2: &str = r#"extend schema @link(url: "https://specs.apollo.dev/federation/v2.5", import: ["@key", "@shareable", "@override", "@interfaceObject", "@tag"])"#;
+
+    #[test]
+    fn composes_subgraphs_into_supergraph() {
+        let subgraphs = ["accounts", "inventory", "products", "reviews"].map(fixture);
+
+        insta::assert_snapshot!(compose_types(&subgraphs), @r#"
+        enum join__Graph {
+          ACCOUNTS @join__graph(name: "accounts", url: "http://0.0.0.0:4200/accounts")
+          INVENTORY @join__graph(name: "inventory", url: "http://0.0.0.0:4200/inventory")
+          PRODUCTS @join__graph(name: "products", url: "http://0.0.0.0:4200/products")
+          REVIEWS @join__graph(name: "reviews", url: "http://0.0.0.0:4200/reviews")
+        }
+
+        type GitHubAccount implements SocialAccount @join__type(graph: ACCOUNTS) @join__implements(graph: ACCOUNTS, interface: "SocialAccount") {
+          url: String!
+          handle: String!
+          repoCount: Int!
+        }
+
+        type Mutation @join__type(graph: PRODUCTS) {
+          upload(file: Upload): String!
+          reentryTest: ReentryTestPayload!
+          oneofTest(input: OneOfTestInput!): OneOfTestResult!
+        }
+
+        type NonNullNested @join__type(graph: ACCOUNTS) {
+          fieldThatErrors: String!
+        }
+
+        type NullableNested @join__type(graph: ACCOUNTS) {
+          fieldThatErrors: String
+        }
+
+        input OneOfTestInput @join__type(graph: PRODUCTS) {
+          string: String
+          int: Int
+          float: Float
+          boolean: Boolean
+          id: ID
+        }
+
+        type OneOfTestResult @join__type(graph: PRODUCTS) {
+          string: String
+          int: Int
+          float: Float
+          boolean: Boolean
+          id: ID
+        }
+
+        type Product @join__type(graph: INVENTORY, key: "upc") @join__type(graph: PRODUCTS, key: "upc") @join__type(graph: REVIEWS, key: "upc") {
+          upc: String!
+          weight: Int @join__field(graph: INVENTORY, external: true) @join__field(graph: PRODUCTS)
+          price: Int @join__field(graph: INVENTORY, external: true) @join__field(graph: PRODUCTS)
+          inStock: Boolean @join__field(graph: INVENTORY)
+          shippingEstimate: Int @join__field(graph: INVENTORY, requires: "price weight")
+          name: String @join__field(graph: PRODUCTS)
+          notes: String @join__field(graph: PRODUCTS)
+          internal: String @join__field(graph: PRODUCTS)
+          reviews: [Review] @join__field(graph: REVIEWS)
+        }
+
+        type Query @join__type(graph: ACCOUNTS) @join__type(graph: INVENTORY) @join__type(graph: PRODUCTS) @join__type(graph: REVIEWS) {
+          me: User @join__field(graph: ACCOUNTS)
+          user(id: ID!): User @join__field(graph: ACCOUNTS)
+          users: [User] @join__field(graph: ACCOUNTS)
+          """
+            A nullable root field whose resolver always errors. The subgraph reports
+            the error and resolves the field to `null`.
+          """
+          nullableFieldThatErrors: String @join__field(graph: ACCOUNTS)
+          """
+            A non-null root field whose resolver always errors. Used to verify
+            null-propagation for non-null root fields.
+          """
+          nonNullFieldThatErrors: String! @join__field(graph: ACCOUNTS)
+          """
+            A non-null nested object; its inner field errors, so the `null` bubbles
+            through `NonNullNested!` up to `data`.
+          """
+          nonNullNested: NonNullNested! @join__field(graph: ACCOUNTS)
+          """
+            A nullable nested object; its inner (nullable) field errors, so the `null`
+            stays on that field.
+          """
+          nullableNested: NullableNested @join__field(graph: ACCOUNTS)
+          topProducts(first: Int = 5): [Product] @join__field(graph: PRODUCTS)
+        }
+
+        type ReentryTestPayload @join__type(graph: PRODUCTS) {
+          ok: Boolean!
+          query: Query!
+        }
+
+        type Review @join__type(graph: REVIEWS, key: "id") {
+          id: ID!
+          body: String
+          product: Product
+          author: User @join__field(graph: REVIEWS, provides: "username")
+        }
+
+        interface SocialAccount @join__type(graph: ACCOUNTS) {
+          url: String!
+          handle: String!
+        }
+
+        type TwitterAccount implements SocialAccount @join__type(graph: ACCOUNTS) @join__implements(graph: ACCOUNTS, interface: "SocialAccount") {
+          url: String!
+          handle: String!
+          followers: Int!
+        }
+
+        "A multipart file upload"
+        scalar Upload @join__type(graph: PRODUCTS)
+
+        type User @join__type(graph: ACCOUNTS, key: "id") @join__type(graph: REVIEWS, key: "id") {
+          id: ID!
+          name: String @join__field(graph: ACCOUNTS)
+          username: String @join__field(graph: ACCOUNTS) @join__field(graph: REVIEWS, external: true)
+          birthday: Int @join__field(graph: ACCOUNTS)
+          socialAccounts: [SocialAccount!]! @join__field(graph: ACCOUNTS)
+          reviews: [Review] @join__field(graph: REVIEWS)
+        }
+        "#);
+    }
+
+    #[test]
+    fn plans_queries_on_composed_supergraph() -> Result<(), Box<dyn std::error::Error>> {
+        let subgraphs = ["accounts", "inventory", "products", "reviews"].map(fixture);
+        let supergraph = parse_schema(&compose(&subgraphs)?);
+        let planner = Planner::new_from_supergraph(&supergraph, Default::default())?;
+
+        let operation = parse_operation(
+            r#"
+            query {
+              topProducts {
+                name
+                shippingEstimate
+                reviews {
+                  author {
+                    username
+                  }
+                }
+              }
+            }"#,
+        );
+        let document = normalize_operation(&planner.supergraph, &operation, None)?;
+        let query_plan = planner.plan_from_normalized_operation(
+            document.executable_operation(),
+            PlannerOverrideContext::default(),
+            &CancellationToken::new(),
+        )?;
+
+        insta::assert_snapshot!(format!("{}", query_plan), @r#"
+        QueryPlan {
+          Sequence {
+            Fetch(service: "products") {
+              {
+                topProducts {
+                  __typename
+                  name
+                  upc
+                  price
+                  weight
+                }
+              }
+            },
+            Parallel {
+              Flatten(path: "topProducts.@") {
+                Fetch(service: "inventory") {
+                  {
+                    ... on Product {
+                      __typename
+                      price
+                      weight
+                      upc
+                    }
+                  } =>
+                  {
+                    ... on Product {
+                      shippingEstimate
+                    }
+                  }
+                },
+              },
+              Flatten(path: "topProducts.@") {
+                Fetch(service: "reviews") {
+                  {
+                    ... on Product {
+                      __typename
+                      upc
+                    }
+                  } =>
+                  {
+                    ... on Product {
+                      reviews {
+                        author {
+                          username
+                        }
+                      }
+                    }
+                  }
+                },
+              },
+            },
+          },
+        },
+        "#);
+        Ok(())
+    }
+
+    #[test]
+    fn merges_overridden_fields() {
+        let subgraphs = [
+            subgraph(
+                "a",
+                &format!(
+                    r#"{FED2}
+                    type Query {{ posts: [Post] }}
+                    type Post @key(fields: "id") {{ id: ID! title: String @tag(name: "public") }}"#
+                ),
+            ),
+            subgraph(
+                "b",
+                &format!(
+                    r#"{FED2}
+                    type Post @key(fields: "id") {{ id: ID! title: String @override(from: "a") author: String }}"#
+                ),
+            ),
+        ];
+
+        insta::assert_snapshot!(compose_types(&subgraphs), @r#"
+        enum join__Graph {
+          A @join__graph(name: "a", url: "http://0.0.0.0:4200/a")
+          B @join__graph(name: "b", url: "http://0.0.0.0:4200/b")
+        }
+
+        type Post @join__type(graph: A, key: "id") @join__type(graph: B, key: "id") {
+          id: ID!
+          title: String @join__field(graph: B, override: "a") @tag(name: "public")
+          author: String @join__field(graph: B)
+        }
+
+        type Query @join__type(graph: A) @join__type(graph: B) {
+          posts: [Post] @join__field(graph: A)
+        }
+        "#);
+    }
+
+    #[test]
+    fn merges_interface_objects_into_interfaces() {
+        let subgraphs = [
+            subgraph(
+                "a",
+                &format!(
+                    r#"{FED2}
+                    type Query {{ media: [Media] }}
+                    interface Media @key(fields: "id") {{ id: ID! }}
+                    type Book implements Media @key(fields: "id") {{ id: ID! pages: Int }}
+                    type Movie implements Media @key(fields: "id") {{ id: ID! }}"#
+                ),
+            ),
+            subgraph(
+                "b",
+                &format!(
+                    r#"{FED2}
+                    type Media @key(fields: "id") @interfaceObject {{ id: ID! rating: Int }}"#
+                ),
+            ),
+        ];
+
+        insta::assert_snapshot!(compose_types(&subgraphs), @r#"
+        enum join__Graph {
+          A @join__graph(name: "a", url: "http://0.0.0.0:4200/a")
+          B @join__graph(name: "b", url: "http://0.0.0.0:4200/b")
+        }
+
+        type Book implements Media @join__type(graph: A, key: "id") @join__implements(graph: A, interface: "Media") {
+          id: ID!
+          pages: Int
+          rating: Int @join__field
+        }
+
+        interface Media @join__type(graph: A, key: "id") @join__type(graph: B, key: "id", isInterfaceObject: true) {
+          id: ID!
+          rating: Int @join__field(graph: B)
+        }
+
+        type Movie implements Media @join__type(graph: A, key: "id") @join__implements(graph: A, interface: "Media") {
+          id: ID!
+          rating: Int @join__field
+        }
+
+        type Query @join__type(graph: A) @join__type(graph: B) {
+          media: [Media] @join__field(graph: A)
+        }
+        "#);
+    }
+
+    #[test]
+    fn merges_federation_v1_extensions() {
+        let subgraphs = [
+            subgraph(
+                "a",
+                r#"type Query { post: Post } type Post @key(fields: "id") { id: ID! title: String }"#,
+            ),
+            subgraph(
+                "b",
+                r#"extend type Post @key(fields: "id") { id: ID! @external title: String }"#,
+            ),
+        ];
+
+        insta::assert_snapshot!(compose_types(&subgraphs), @r#"
+        enum join__Graph {
+          A @join__graph(name: "a", url: "http://0.0.0.0:4200/a")
+          B @join__graph(name: "b", url: "http://0.0.0.0:4200/b")
+        }
+
+        type Post @join__type(graph: A, key: "id") @join__type(graph: B, key: "id", extension: true) {
+          id: ID!
+          title: String
+        }
+
+        type Query @join__type(graph: A) @join__type(graph: B) {
+          post: Post @join__field(graph: A)
+        }
+        "#);
+    }
+
+    #[test]
+    fn keeps_common_values_of_input_enums() {
+        let subgraphs = [
+            subgraph(
+                "a",
+                &format!(
+                    "{FED2}\ntype Query {{ posts(sort: Sort): [ID] }} enum Sort {{ ASC DESC }}"
+                ),
+            ),
+            subgraph(
+                "b",
+                &format!("{FED2}\ntype Query {{ comments(sort: Sort): [ID] }} enum Sort {{ ASC }}"),
+            ),
+        ];
+
+        insta::assert_snapshot!(compose_types(&subgraphs), @r#"
+        enum join__Graph {
+          A @join__graph(name: "a", url: "http://0.0.0.0:4200/a")
+          B @join__graph(name: "b", url: "http://0.0.0.0:4200/b")
+        }
+
+        type Query @join__type(graph: A) @join__type(graph: B) {
+          posts(sort: Sort): [ID] @join__field(graph: A)
+          comments(sort: Sort): [ID] @join__field(graph: B)
+        }
+
+        enum Sort @join__type(graph: A) @join__type(graph: B) {
+          ASC @join__enumValue(graph: A) @join__enumValue(graph: B)
+        }
+        "#);
+    }
+
+    #[test]
+    fn rejects_enums_with_different_values_used_as_input_and_output() {
+        let subgraphs = [
+            subgraph(
+                "a",
+                &format!("{FED2}\ntype Query {{ posts: [Status] }} enum Status {{ DRAFT }}"),
+            ),
+            subgraph(
+                "b",
+                &format!(
+                    "{FED2}\ntype Query {{ drafts(status: Status): [ID] }} enum Status {{ DRAFT PUBLISHED }}"
+                ),
+            ),
+        ];
+
+        let error = compose(&subgraphs).expect_err("expected composition to fail");
+        insta::assert_snapshot!(error, @"failed to merge type 'Status': value 'PUBLISHED' is not defined in every subgraph, but the enum is used both as an input and an output type");
+    }
+
+    #[test]
+    fn rejects_fields_resolved_by_several_subgraphs_without_shareable() {
+        let subgraphs = [
+            subgraph(
+                "a",
+                &format!("{FED2}\ntype Query {{ post: Post }} type Post {{ id: ID! }}"),
+            ),
+            subgraph(
+                "b",
+                &format!("{FED2}\ntype Query {{ latest: Post }} type Post {{ id: ID! }}"),
+            ),
+        ];
+
+        let error = compose(&subgraphs).expect_err("expected composition to fail");
+        insta::assert_snapshot!(error, @"failed to merge type 'Post': field 'id' is resolved by subgraphs 'a', 'b' but is not marked @shareable in subgraph 'a'");
+    }
+}
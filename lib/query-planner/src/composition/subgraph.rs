@@ -0,0 +1,714 @@
+use std::collections::HashMap;
+
+use graphql_tools::parser::schema::{
+    Definition, Directive, DirectiveDefinition, DirectiveLocation, Document, EnumValue, Field,
+    InputValue, Type, TypeDefinition, TypeExtension, Value,
+};
+
+use super::CompositionError;
+
+/// The directives of the federation specs the composition understands,
+/// under their canonical names.
+const FEDERATION_DIRECTIVES: [&str; 15] = [
+    "key",
+    "requires",
+    "provides",
+    "external",
+    "shareable",
+    "override",
+    "extends",
+    "interfaceObject",
+    "inaccessible",
+    "tag",
+    "authenticated",
+    "requiresScopes",
+    "cost",
+    "listSize",
+    "composeDirective",
+];
+
+/// The directives applied in the subgraphs that are copied to the supergraph.
+const PROPAGATED_DIRECTIVES: [&str; 6] = [
+    "inaccessible",
+    "tag",
+    "authenticated",
+    "requiresScopes",
+    "cost",
+    "listSize",
+];
+
+/// The directives of the GraphQL specification, kept as they are.
+const BUILT_IN_DIRECTIVES: [&str; 7] = [
+    "skip",
+    "include",
+    "deprecated",
+    "specifiedBy",
+    "oneOf",
+    "defer",
+    "stream",
+];
+
+const BUILT_IN_SCALARS: [&str; 5] = ["String", "Int", "Float", "Boolean", "ID"];
+
+/// The types added to the subgraphs by the federation specs, never part of the supergraph.
+const FEDERATION_TYPES: [&str; 5] = ["_Service", "_Entity", "_Any", "_FieldSet", "FieldSet"];
+
+type StaticDirective = Directive<'static, String>;
+type StaticType = Type<'static, String>;
+type StaticInputValue = InputValue<'static, String>;
+type StaticEnumValue = EnumValue<'static, String>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+}
+
+impl TypeKind {
+    pub(super) fn as_str(&self) -> &'static str {
+        match self {
+            TypeKind::Scalar => "a scalar",
+            TypeKind::Object => "an object type",
+            TypeKind::Interface => "an interface",
+            TypeKind::Union => "a union",
+            TypeKind::Enum => "an enum",
+            TypeKind::InputObject => "an input object",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Key {
+    pub fields: String,
+    pub resolvable: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct SubgraphField {
+    pub name: String,
+    pub description: Option<String>,
+    pub field_type: StaticType,
+    pub arguments: Vec<StaticInputValue>,
+    pub external: bool,
+    pub shareable: bool,
+    pub requires: Option<String>,
+    pub provides: Option<String>,
+    pub override_from: Option<String>,
+    pub override_label: Option<String>,
+    pub directives: Vec<StaticDirective>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct SubgraphType {
+    pub name: String,
+    pub kind: TypeKind,
+    pub description: Option<String>,
+    pub keys: Vec<Key>,
+    /// Defined only by `extend type` or with `@extends`.
+    pub extension: bool,
+    pub shareable: bool,
+    pub interface_object: bool,
+    pub directives: Vec<StaticDirective>,
+    pub implements: Vec<String>,
+    pub fields: Vec<SubgraphField>,
+    pub input_fields: Vec<StaticInputValue>,
+    pub members: Vec<String>,
+    pub values: Vec<StaticEnumValue>,
+}
+
+impl SubgraphType {
+    fn new(name: String, kind: TypeKind) -> Self {
+        Self {
+            name,
+            kind,
+            description: None,
+            keys: vec![],
+            extension: true,
+            shareable: false,
+            interface_object: false,
+            directives: vec![],
+            implements: vec![],
+            fields: vec![],
+            input_fields: vec![],
+            members: vec![],
+            values: vec![],
+        }
+    }
+
+    /// Returns true if the field is selected at the top level of one of the keys of the type.
+    pub(super) fn is_key_field(&self, field_name: &str) -> bool {
+        self.keys
+            .iter()
+            .any(|key| top_level_fields(&key.fields).any(|name| name == field_name))
+    }
+
+    pub(super) fn field(&self, field_name: &str) -> Option<&SubgraphField> {
+        self.fields.iter().find(|field| field.name == field_name)
+    }
+}
+
+/// A subgraph schema, with its federation directives resolved and its root types renamed
+/// to `Query`, `Mutation` and `Subscription`.
+pub(super) struct Subgraph {
+    pub name: String,
+    pub url: String,
+    /// Subgraphs not linking the federation v2 spec follow the federation v1 semantics,
+    /// where every field can be resolved by several subgraphs.
+    pub federation_v2: bool,
+    pub types: Vec<SubgraphType>,
+    /// The definitions of the executable directives, like `directive @lowercase on FIELD`.
+    pub executable_directives: Vec<DirectiveDefinition<'static, String>>,
+}
+
+impl Subgraph {
+    pub(super) fn parse(name: &str, url: &str, sdl: &str) -> Result<Self, CompositionError> {
+        let document: Document<'static, String> = graphql_tools::parser::parse_schema(sdl)
+            .map_err(|err| CompositionError::SubgraphParse(name.to_string(), err.to_string()))?
+            .into_static();
+
+        let mut names = DirectiveNames::default();
+        let mut federation_v2 = false;
+        let mut root_types = HashMap::new();
+        for definition in &document.definitions {
+            let Definition::SchemaDefinition(schema) = definition else {
+                continue;
+            };
+            for (root_type, canonical) in [
+                (&schema.query, "Query"),
+                (&schema.mutation, "Mutation"),
+                (&schema.subscription, "Subscription"),
+            ] {
+                if let Some(root_type) = root_type {
+                    root_types.insert(root_type.clone(), canonical.to_string());
+                }
+            }
+            for directive in schema.directives.iter().filter(|d| d.name == "link") {
+                if names.add_link(directive) {
+                    federation_v2 = true;
+                }
+            }
+        }
+        if !federation_v2 {
+            names = DirectiveNames::federation_v1();
+        }
+
+        let mut types: Vec<SubgraphType> = vec![];
+        let mut executable_directives = vec![];
+        for definition in document.definitions {
+            let (type_name, kind, description, directives, extension) = match &definition {
+                Definition::TypeDefinition(definition) => {
+                    let (name, kind, description, directives) = type_definition_header(definition);
+                    (name, kind, description, directives, false)
+                }
+                Definition::TypeExtension(extension) => {
+                    let (name, kind, directives) = type_extension_header(extension);
+                    (name, kind, None, directives, true)
+                }
+                Definition::DirectiveDefinition(directive) => {
+                    if is_executable(directive) && !is_built_in_directive(&directive.name) {
+                        executable_directives.push(directive.clone());
+                    }
+                    continue;
+                }
+                Definition::SchemaDefinition(_) => continue,
+            };
+            if is_federation_type(&type_name) || BUILT_IN_SCALARS.contains(&type_name.as_str()) {
+                continue;
+            }
+
+            let type_name = root_types.get(&type_name).cloned().unwrap_or(type_name);
+            let index = match types.iter().position(|t| t.name == type_name) {
+                Some(index) => index,
+                None => {
+                    types.push(SubgraphType::new(type_name, kind));
+                    types.len() - 1
+                }
+            };
+            let subgraph_type = &mut types[index];
+            if subgraph_type.kind != kind {
+                return Err(CompositionError::InvalidSubgraph(
+                    name.to_string(),
+                    format!(
+                        "type '{}' is defined both as {} and as {}",
+                        subgraph_type.name,
+                        subgraph_type.kind.as_str(),
+                        kind.as_str()
+                    ),
+                ));
+            }
+            subgraph_type.extension &= extension;
+            if subgraph_type.description.is_none() {
+                subgraph_type.description = description;
+            }
+            apply_type_directives(subgraph_type, &directives, &names);
+
+            match definition {
+                Definition::TypeDefinition(TypeDefinition::Object(object)) => {
+                    subgraph_type
+                        .implements
+                        .extend(object.implements_interfaces);
+                    add_fields(subgraph_type, object.fields, &names);
+                }
+                Definition::TypeExtension(TypeExtension::Object(object)) => {
+                    subgraph_type
+                        .implements
+                        .extend(object.implements_interfaces);
+                    add_fields(subgraph_type, object.fields, &names);
+                }
+                Definition::TypeDefinition(TypeDefinition::Interface(interface)) => {
+                    subgraph_type
+                        .implements
+                        .extend(interface.implements_interfaces);
+                    add_fields(subgraph_type, interface.fields, &names);
+                }
+                Definition::TypeExtension(TypeExtension::Interface(interface)) => {
+                    subgraph_type
+                        .implements
+                        .extend(interface.implements_interfaces);
+                    add_fields(subgraph_type, interface.fields, &names);
+                }
+                Definition::TypeDefinition(TypeDefinition::Union(union)) => {
+                    subgraph_type.members.extend(union.types);
+                }
+                Definition::TypeExtension(TypeExtension::Union(union)) => {
+                    subgraph_type.members.extend(union.types);
+                }
+                Definition::TypeDefinition(TypeDefinition::Enum(enum_type)) => {
+                    add_enum_values(subgraph_type, enum_type.values, &names);
+                }
+                Definition::TypeExtension(TypeExtension::Enum(enum_type)) => {
+                    add_enum_values(subgraph_type, enum_type.values, &names);
+                }
+                Definition::TypeDefinition(TypeDefinition::InputObject(input)) => {
+                    add_input_fields(subgraph_type, input.fields, &names);
+                }
+                Definition::TypeExtension(TypeExtension::InputObject(input)) => {
+                    add_input_fields(subgraph_type, input.fields, &names);
+                }
+                _ => {}
+            }
+        }
+
+        for subgraph_type in types.iter_mut() {
+            if subgraph_type.name == "Query" {
+                subgraph_type
+                    .fields
+                    .retain(|field| field.name != "_service" && field.name != "_entities");
+            }
+            if !root_types.is_empty() {
+                rename_type_references(subgraph_type, &root_types);
+            }
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            federation_v2,
+            types,
+            executable_directives,
+        })
+    }
+
+    pub(super) fn get_type(&self, type_name: &str) -> Option<&SubgraphType> {
+        self.types.iter().find(|t| t.name == type_name)
+    }
+}
+
+/// Maps the names of the directives, as used in a subgraph, to their canonical name.
+#[derive(Default)]
+struct DirectiveNames {
+    canonical_by_name: HashMap<String, &'static str>,
+}
+
+impl DirectiveNames {
+    fn federation_v1() -> Self {
+        Self {
+            canonical_by_name: FEDERATION_DIRECTIVES
+                .iter()
+                .map(|name| (name.to_string(), *name))
+                .collect(),
+        }
+    }
+
+    /// Registers the names imported by a `@link` directive,
+    /// and returns true if it links the federation v2 spec.
+    fn add_link(&mut self, directive: &StaticDirective) -> bool {
+        let Some(url) = string_argument(directive, "url") else {
+            return false;
+        };
+        let Some(spec) = url
+            .strip_prefix("https://specs.apollo.dev/")
+            .and_then(|path| path.split('/').next())
+        else {
+            return false;
+        };
+
+        let is_federation = spec == "federation";
+        let prefix = string_argument(directive, "as").unwrap_or(spec);
+        if is_federation {
+            for canonical in FEDERATION_DIRECTIVES {
+                self.canonical_by_name
+                    .insert(format!("{prefix}__{canonical}"), canonical);
+            }
+        } else if let Some(canonical) = FEDERATION_DIRECTIVES.iter().find(|name| **name == spec) {
+            // specs like `inaccessible` or `tag` are named after their only directive
+            self.canonical_by_name.insert(prefix.to_string(), canonical);
+        }
+
+        let imports = directive
+            .arguments
+            .iter()
+            .find(|(name, _)| name == "import")
+            .map(|(_, value)| value);
+        if let Some(Value::List(imports)) = imports {
+            for import in imports {
+                let (imported, local) = match import {
+                    Value::String(name) => (name.as_str(), name.as_str()),
+                    Value::Object(fields) => {
+                        let field = |key: &str| {
+                            fields.iter().find_map(|(name, value)| match value {
+                                Value::String(value) if name == key => Some(value.as_str()),
+                                _ => None,
+                            })
+                        };
+                        let Some(imported) = field("name") else {
+                            continue;
+                        };
+                        (imported, field("as").unwrap_or(imported))
+                    }
+                    _ => continue,
+                };
+                let (Some(imported), Some(local)) =
+                    (imported.strip_prefix('@'), local.strip_prefix('@'))
+                else {
+                    continue;
+                };
+                if let Some(canonical) = FEDERATION_DIRECTIVES.iter().find(|n| **n == imported) {
+                    self.canonical_by_name.insert(local.to_string(), canonical);
+                }
+            }
+        }
+
+        is_federation && url.contains("/federation/v2")
+    }
+
+    fn canonical(&self, name: &str) -> Option<&'static str> {
+        self.canonical_by_name.get(name).copied()
+    }
+}
+
+fn string_argument<'d>(directive: &'d StaticDirective, argument: &str) -> Option<&'d str> {
+    directive
+        .arguments
+        .iter()
+        .find_map(|(name, value)| match value {
+            Value::String(value) if name == argument => Some(value.as_str()),
+            _ => None,
+        })
+}
+
+fn bool_argument(directive: &StaticDirective, argument: &str) -> Option<bool> {
+    directive
+        .arguments
+        .iter()
+        .find_map(|(name, value)| match value {
+            Value::Boolean(value) if name == argument => Some(*value),
+            _ => None,
+        })
+}
+
+fn is_federation_type(name: &str) -> bool {
+    FEDERATION_TYPES.contains(&name)
+        || name.starts_with("link__")
+        || name.starts_with("federation__")
+        || name.starts_with("join__")
+}
+
+fn is_built_in_directive(name: &str) -> bool {
+    BUILT_IN_DIRECTIVES.contains(&name)
+}
+
+fn is_executable(directive: &DirectiveDefinition<'static, String>) -> bool {
+    directive.locations.iter().all(DirectiveLocation::is_query)
+}
+
+/// Returns the directive renamed to its canonical name,
+/// if it is a built-in directive or one copied to the supergraph.
+fn propagated(directive: &StaticDirective, names: &DirectiveNames) -> Option<StaticDirective> {
+    if is_built_in_directive(&directive.name) {
+        return Some(directive.clone());
+    }
+    let canonical = names.canonical(&directive.name)?;
+    PROPAGATED_DIRECTIVES
+        .contains(&canonical)
+        .then(|| Directive {
+            name: canonical.to_string(),
+            ..directive.clone()
+        })
+}
+
+fn propagated_directives(
+    directives: &[StaticDirective],
+    names: &DirectiveNames,
+) -> Vec<StaticDirective> {
+    directives
+        .iter()
+        .filter_map(|directive| propagated(directive, names))
+        .collect()
+}
+
+fn type_definition_header(
+    definition: &TypeDefinition<'static, String>,
+) -> (String, TypeKind, Option<String>, Vec<StaticDirective>) {
+    match definition {
+        TypeDefinition::Scalar(t) => (
+            t.name.clone(),
+            TypeKind::Scalar,
+            t.description.clone(),
+            t.directives.clone(),
+        ),
+        TypeDefinition::Object(t) => (
+            t.name.clone(),
+            TypeKind::Object,
+            t.description.clone(),
+            t.directives.clone(),
+        ),
+        TypeDefinition::Interface(t) => (
+            t.name.clone(),
+            TypeKind::Interface,
+            t.description.clone(),
+            t.directives.clone(),
+        ),
+        TypeDefinition::Union(t) => (
+            t.name.clone(),
+            TypeKind::Union,
+            t.description.clone(),
+            t.directives.clone(),
+        ),
+        TypeDefinition::Enum(t) => (
+            t.name.clone(),
+            TypeKind::Enum,
+            t.description.clone(),
+            t.directives.clone(),
+        ),
+        TypeDefinition::InputObject(t) => (
+            t.name.clone(),
+            TypeKind::InputObject,
+            t.description.clone(),
+            t.directives.clone(),
+        ),
+    }
+}
+
+fn type_extension_header(
+    extension: &TypeExtension<'static, String>,
+) -> (String, TypeKind, Vec<StaticDirective>) {
+    match extension {
+        TypeExtension::Scalar(t) => (t.name.clone(), TypeKind::Scalar, t.directives.clone()),
+        TypeExtension::Object(t) => (t.name.clone(), TypeKind::Object, t.directives.clone()),
+        TypeExtension::Interface(t) => (t.name.clone(), TypeKind::Interface, t.directives.clone()),
+        TypeExtension::Union(t) => (t.name.clone(), TypeKind::Union, t.directives.clone()),
+        TypeExtension::Enum(t) => (t.name.clone(), TypeKind::Enum, t.directives.clone()),
+        TypeExtension::InputObject(t) => {
+            (t.name.clone(), TypeKind::InputObject, t.directives.clone())
+        }
+    }
+}
+
+fn apply_type_directives(
+    subgraph_type: &mut SubgraphType,
+    directives: &[StaticDirective],
+    names: &DirectiveNames,
+) {
+    for directive in directives {
+        match names.canonical(&directive.name) {
+            Some("key") => {
+                let Some(fields) = string_argument(directive, "fields") else {
+                    continue;
+                };
+                let key = Key {
+                    fields: fields.to_string(),
+                    resolvable: bool_argument(directive, "resolvable").unwrap_or(true),
+                };
+                if !subgraph_type.keys.contains(&key) {
+                    subgraph_type.keys.push(key);
+                }
+            }
+            Some("extends") => subgraph_type.extension = true,
+            Some("shareable") => subgraph_type.shareable = true,
+            Some("interfaceObject") => subgraph_type.interface_object = true,
+            _ => {
+                if let Some(directive) = propagated(directive, names) {
+                    if !subgraph_type.directives.contains(&directive) {
+                        subgraph_type.directives.push(directive);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn add_fields(
+    subgraph_type: &mut SubgraphType,
+    fields: Vec<Field<'static, String>>,
+    names: &DirectiveNames,
+) {
+    for field in fields {
+        let mut subgraph_field = SubgraphField {
+            name: field.name,
+            description: field.description,
+            field_type: field.field_type,
+            arguments: field
+                .arguments
+                .into_iter()
+                .map(|argument| input_value(argument, names))
+                .collect(),
+            external: false,
+            shareable: false,
+            requires: None,
+            provides: None,
+            override_from: None,
+            override_label: None,
+            directives: vec![],
+        };
+
+        for directive in &field.directives {
+            match names.canonical(&directive.name) {
+                Some("external") => subgraph_field.external = true,
+                Some("shareable") => subgraph_field.shareable = true,
+                Some("requires") => {
+                    subgraph_field.requires =
+                        string_argument(directive, "fields").map(str::to_string)
+                }
+                Some("provides") => {
+                    subgraph_field.provides =
+                        string_argument(directive, "fields").map(str::to_string)
+                }
+                Some("override") => {
+                    subgraph_field.override_from =
+                        string_argument(directive, "from").map(str::to_string);
+                    subgraph_field.override_label =
+                        string_argument(directive, "label").map(str::to_string);
+                }
+                _ => {
+                    if let Some(directive) = propagated(directive, names) {
+                        subgraph_field.directives.push(directive);
+                    }
+                }
+            }
+        }
+
+        subgraph_type.fields.push(subgraph_field);
+    }
+}
+
+fn input_value(mut value: StaticInputValue, names: &DirectiveNames) -> StaticInputValue {
+    value.directives = propagated_directives(&value.directives, names);
+    value
+}
+
+fn add_input_fields(
+    subgraph_type: &mut SubgraphType,
+    fields: Vec<StaticInputValue>,
+    names: &DirectiveNames,
+) {
+    subgraph_type
+        .input_fields
+        .extend(fields.into_iter().map(|field| input_value(field, names)));
+}
+
+fn add_enum_values(
+    subgraph_type: &mut SubgraphType,
+    values: Vec<StaticEnumValue>,
+    names: &DirectiveNames,
+) {
+    subgraph_type
+        .values
+        .extend(values.into_iter().map(|mut value| {
+            value.directives = propagated_directives(&value.directives, names);
+            value
+        }));
+}
+
+fn rename_type_references(subgraph_type: &mut SubgraphType, renames: &HashMap<String, String>) {
+    let rename = |name: &mut String| {
+        if let Some(renamed) = renames.get(name) {
+            *name = renamed.clone();
+        }
+    };
+    for field in subgraph_type.fields.iter_mut() {
+        rename_in_type(&mut field.field_type, &rename);
+        for argument in field.arguments.iter_mut() {
+            rename_in_type(&mut argument.value_type, &rename);
+        }
+    }
+    for field in subgraph_type.input_fields.iter_mut() {
+        rename_in_type(&mut field.value_type, &rename);
+    }
+    subgraph_type.implements.iter_mut().for_each(rename);
+    subgraph_type.members.iter_mut().for_each(rename);
+}
+
+fn rename_in_type(field_type: &mut StaticType, rename: &impl Fn(&mut String)) {
+    match field_type {
+        Type::NamedType(name) => rename(name),
+        Type::ListType(inner) | Type::NonNullType(inner) => rename_in_type(inner, rename),
+    }
+}
+
+/// Iterates over the names of the fields selected at the top level of a field set,
+/// like `id` and `organization` in `id organization { id }`.
+pub(super) fn top_level_fields(field_set: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0usize;
+    let mut previous_was_spread = false;
+    field_set
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .flat_map(|token| {
+            // braces may be glued to the names, like `organization{id}`
+            let mut parts = vec![];
+            let mut start = 0;
+            for (index, c) in token.char_indices() {
+                if c == '{' || c == '}' || c == '(' || c == ')' || c == ':' {
+                    if start < index {
+                        parts.push(&token[start..index]);
+                    }
+                    parts.push(&token[index..index + c.len_utf8()]);
+                    start = index + c.len_utf8();
+                }
+            }
+            if start < token.len() {
+                parts.push(&token[start..]);
+            }
+            parts
+        })
+        .filter(move |part| {
+            match *part {
+                "{" | "(" => {
+                    depth += 1;
+                    return false;
+                }
+                "}" | ")" => {
+                    depth = depth.saturating_sub(1);
+                    return false;
+                }
+                _ => {}
+            }
+            if depth > 0 || *part == ":" {
+                return false;
+            }
+            if *part == "..." || *part == "on" {
+                previous_was_spread = true;
+                return false;
+            }
+            if previous_was_spread {
+                // the type condition of an inline fragment
+                previous_was_spread = false;
+                return false;
+            }
+            true
+        })
+}
@@ -0,0 +1,533 @@
+use std::collections::HashSet;
+
+use graphql_tools::static_graphql::schema::{
+    Definition, Directive, Document, Field, InputValue, Type, TypeDefinition, Value,
+};
+
+use crate::federation_spec::directives::TagDirective;
+
+/// Filters the consumer schema by the `@tag` directives of the supergraph,
+/// to serve a variant of it to the clients.
+#[derive(Debug, Clone, Default)]
+pub struct Contract {
+    /// When not empty, only the types and fields tagged with one of these tags are kept,
+    /// along with the fields of the tagged types.
+    pub include_tags: Vec<String>,
+    /// The elements tagged with one of these tags are removed, even if included.
+    pub exclude_tags: Vec<String>,
+}
+
+impl Contract {
+    fn tags(directives: &[Directive]) -> impl Iterator<Item = &str> {
+        directives
+            .iter()
+            .filter(|directive| directive.name == TagDirective::NAME)
+            .filter_map(|directive| {
+                directive
+                    .arguments
+                    .iter()
+                    .find_map(|(name, value)| match value {
+                        Value::String(tag) if name == "name" => Some(tag.as_str()),
+                        _ => None,
+                    })
+            })
+    }
+
+    fn is_excluded(&self, directives: &[Directive]) -> bool {
+        Self::tags(directives).any(|tag| self.exclude_tags.iter().any(|t| t == tag))
+    }
+
+    fn is_tagged_included(&self, directives: &[Directive]) -> bool {
+        Self::tags(directives).any(|tag| self.include_tags.iter().any(|t| t == tag))
+    }
+
+    /// Returns the supergraph without the types, fields, arguments and values hidden by the contract.
+    ///
+    /// The elements referencing a removed type are removed too, along with the types
+    /// no longer reachable from the root types, so the result stays a valid schema.
+    pub(crate) fn apply(&self, supergraph: &Document) -> Document {
+        let mut document = supergraph.clone();
+        // SAFETY: Supergraph is guaranteed to have a query type, it's one of the validation rules
+        let query_type = document
+            .query_type_name()
+            .cloned()
+            .expect("Query type not found in schema");
+        let root_types: HashSet<String> = [
+            document.query_type_name(),
+            document.mutation_type_name(),
+            document.subscription_type_name(),
+        ]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+
+        let mut removed: HashSet<String> = HashSet::new();
+        for definition in document.definitions.iter_mut() {
+            let Definition::TypeDefinition(type_definition) = definition else {
+                continue;
+            };
+            let name = type_name(type_definition).to_string();
+            if is_internal_type(&name) {
+                continue;
+            }
+            if self.is_excluded(type_directives(type_definition)) && name != query_type {
+                removed.insert(name);
+                continue;
+            }
+
+            let type_included = self.include_tags.is_empty()
+                || self.is_tagged_included(type_directives(type_definition));
+            match type_definition {
+                TypeDefinition::Object(object) => {
+                    self.filter_fields(&mut object.fields, type_included)
+                }
+                TypeDefinition::Interface(interface) => {
+                    self.filter_fields(&mut interface.fields, type_included)
+                }
+                TypeDefinition::InputObject(input) => input.fields.retain(|field| {
+                    !self.is_excluded(&field.directives)
+                        && (type_included || self.is_tagged_included(&field.directives))
+                }),
+                TypeDefinition::Enum(enum_type) => enum_type
+                    .values
+                    .retain(|value| !self.is_excluded(&value.directives)),
+                TypeDefinition::Scalar(_) | TypeDefinition::Union(_) => {}
+            }
+        }
+
+        remove_dangling_references(&mut document, &mut removed, &query_type);
+        let root_types: HashSet<String> = root_types.difference(&removed).cloned().collect();
+        removed.extend(unreachable_types(&document, &root_types));
+
+        document
+            .definitions
+            .retain_mut(|definition| match definition {
+                Definition::TypeDefinition(type_definition) => {
+                    !removed.contains(type_name(type_definition))
+                }
+                Definition::SchemaDefinition(schema_definition) => {
+                    // a mutation or subscription type left without fields is removed
+                    for root_type in [
+                        &mut schema_definition.mutation,
+                        &mut schema_definition.subscription,
+                    ] {
+                        if root_type
+                            .as_ref()
+                            .is_some_and(|name| removed.contains(name))
+                        {
+                            *root_type = None;
+                        }
+                    }
+                    true
+                }
+                _ => true,
+            });
+        document
+    }
+
+    fn filter_fields(&self, fields: &mut Vec<Field>, type_included: bool) {
+        fields.retain(|field| {
+            !self.is_excluded(&field.directives)
+                && (type_included || self.is_tagged_included(&field.directives))
+        });
+        for field in fields.iter_mut() {
+            field
+                .arguments
+                .retain(|argument| !self.is_excluded(&argument.directives));
+        }
+    }
+}
+
+/// The types of the federation specs, like `join__Graph`, are stripped with the other schema internals.
+fn is_internal_type(name: &str) -> bool {
+    name.contains("__")
+}
+
+fn type_name(type_definition: &TypeDefinition) -> &str {
+    match type_definition {
+        TypeDefinition::Scalar(t) => &t.name,
+        TypeDefinition::Object(t) => &t.name,
+        TypeDefinition::Interface(t) => &t.name,
+        TypeDefinition::Union(t) => &t.name,
+        TypeDefinition::Enum(t) => &t.name,
+        TypeDefinition::InputObject(t) => &t.name,
+    }
+}
+
+fn type_directives(type_definition: &TypeDefinition) -> &[Directive] {
+    match type_definition {
+        TypeDefinition::Scalar(t) => &t.directives,
+        TypeDefinition::Object(t) => &t.directives,
+        TypeDefinition::Interface(t) => &t.directives,
+        TypeDefinition::Union(t) => &t.directives,
+        TypeDefinition::Enum(t) => &t.directives,
+        TypeDefinition::InputObject(t) => &t.directives,
+    }
+}
+
+fn is_required(input_value: &InputValue) -> bool {
+    matches!(input_value.value_type, Type::NonNullType(_)) && input_value.default_value.is_none()
+}
+
+/// Removes the fields and arguments of a removed type, until no removal leads to another.
+fn remove_dangling_references(
+    document: &mut Document,
+    removed: &mut HashSet<String>,
+    query_type: &str,
+) {
+    loop {
+        let mut newly_removed: Vec<String> = vec![];
+        for definition in document.definitions.iter_mut() {
+            let Definition::TypeDefinition(type_definition) = definition else {
+                continue;
+            };
+            let name = type_name(type_definition).to_string();
+            if removed.contains(&name) || is_internal_type(&name) {
+                continue;
+            }
+
+            let is_empty = match type_definition {
+                TypeDefinition::Object(object) => {
+                    object
+                        .implements_interfaces
+                        .retain(|interface| !removed.contains(interface));
+                    remove_dangling_fields(&mut object.fields, removed);
+                    object.fields.is_empty()
+                }
+                TypeDefinition::Interface(interface) => {
+                    interface
+                        .implements_interfaces
+                        .retain(|other| !removed.contains(other));
+                    remove_dangling_fields(&mut interface.fields, removed);
+                    interface.fields.is_empty()
+                }
+                TypeDefinition::Union(union) => {
+                    union.types.retain(|member| !removed.contains(member));
+                    union.types.is_empty()
+                }
+                TypeDefinition::InputObject(input) => {
+                    let has_required_removed = input.fields.iter().any(|field| {
+                        removed.contains(field.value_type.inner_type()) && is_required(field)
+                    });
+                    input
+                        .fields
+                        .retain(|field| !removed.contains(field.value_type.inner_type()));
+                    has_required_removed || input.fields.is_empty()
+                }
+                TypeDefinition::Enum(enum_type) => enum_type.values.is_empty(),
+                TypeDefinition::Scalar(_) => false,
+            };
+            // the query type is kept, even empty, as the schema can't do without it
+            if is_empty && name != query_type {
+                newly_removed.push(name);
+            }
+        }
+
+        if newly_removed.is_empty() {
+            break;
+        }
+        removed.extend(newly_removed);
+    }
+}
+
+fn remove_dangling_fields(fields: &mut Vec<Field>, removed: &HashSet<String>) {
+    fields.retain(|field| {
+        !removed.contains(field.field_type.inner_type())
+            && !field.arguments.iter().any(|argument| {
+                removed.contains(argument.value_type.inner_type()) && is_required(argument)
+            })
+    });
+    for field in fields.iter_mut() {
+        field
+            .arguments
+            .retain(|argument| !removed.contains(argument.value_type.inner_type()));
+    }
+}
+
+/// Returns the types that can't be reached from the root types, once filtered.
+fn unreachable_types(document: &Document, root_types: &HashSet<String>) -> HashSet<String> {
+    let definitions: Vec<&TypeDefinition> = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::TypeDefinition(type_definition) => Some(type_definition),
+            _ => None,
+        })
+        .collect();
+
+    let mut reachable: HashSet<&str> = HashSet::new();
+    let mut queue: Vec<&str> = root_types.iter().map(String::as_str).collect();
+    while let Some(name) = queue.pop() {
+        if !reachable.insert(name) {
+            continue;
+        }
+        let Some(type_definition) = definitions.iter().find(|t| type_name(t) == name) else {
+            continue;
+        };
+        let fields: &[Field] = match type_definition {
+            TypeDefinition::Object(object) => {
+                queue.extend(object.implements_interfaces.iter().map(String::as_str));
+                &object.fields
+            }
+            TypeDefinition::Interface(interface) => {
+                queue.extend(interface.implements_interfaces.iter().map(String::as_str));
+                // the implementations of an interface can be selected with fragments
+                queue.extend(definitions.iter().filter_map(|t| match t {
+                    TypeDefinition::Object(object)
+                        if object.implements_interfaces.iter().any(|i| i == name) =>
+                    {
+                        Some(object.name.as_str())
+                    }
+                    _ => None,
+                }));
+                &interface.fields
+            }
+            TypeDefinition::Union(union) => {
+                queue.extend(union.types.iter().map(String::as_str));
+                &[]
+            }
+            TypeDefinition::InputObject(input) => {
+                queue.extend(input.fields.iter().map(|f| f.value_type.inner_type()));
+                &[]
+            }
+            TypeDefinition::Scalar(_) | TypeDefinition::Enum(_) => &[],
+        };
+        for field in fields {
+            queue.push(field.field_type.inner_type());
+            queue.extend(
+                field
+                    .arguments
+                    .iter()
+                    .map(|argument| argument.value_type.inner_type()),
+            );
+        }
+    }
+
+    definitions
+        .iter()
+        .map(|type_definition| type_name(type_definition))
+        .filter(|name| !reachable.contains(name) && !is_internal_type(name))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::consumer_schema::{
+        contract::Contract, strip_schema_internals::StripSchemaInternals,
+    };
+    use crate::utils::parsing::parse_schema;
+
+    const SUPERGRAPH: &str = r#"
+        schema {
+          query: Query
+          mutation: Mutation
+        }
+
+        directive @tag(name: String!) repeatable on FIELD_DEFINITION | OBJECT | INTERFACE | UNION | ARGUMENT_DEFINITION | SCALAR | ENUM | ENUM_VALUE | INPUT_OBJECT | INPUT_FIELD_DEFINITION | SCHEMA
+
+        type Query {
+          products(filter: ProductFilter): [Product!]! @tag(name: "public")
+          product(id: ID!): Product @tag(name: "public")
+          me: User @tag(name: "public")
+          stats: Stats
+        }
+
+        type Mutation {
+          updatePrice(id: ID!, price: PriceInput!): Product @tag(name: "internal")
+        }
+
+        type Product implements Node @tag(name: "public") {
+          id: ID!
+          name: String
+          cost: Float @tag(name: "internal")
+          category: Category
+          owner: User @tag(name: "internal")
+          search(kind: SearchKind @tag(name: "internal")): String
+        }
+
+        interface Node {
+          id: ID!
+        }
+
+        type User {
+          id: ID! @tag(name: "public")
+          email: String @tag(name: "internal")
+          secret: Secret
+        }
+
+        type Secret @tag(name: "internal") {
+          value: String
+        }
+
+        type Stats {
+          count: Int
+        }
+
+        enum Category {
+          BOOKS
+          TOYS
+          PROTOTYPES @tag(name: "internal")
+        }
+
+        enum SearchKind {
+          FULL
+        }
+
+        input ProductFilter {
+          name: String @tag(name: "public")
+          category: Category @tag(name: "public")
+          minCost: Float @tag(name: "internal")
+        }
+
+        input PriceInput @tag(name: "internal") {
+          value: Float!
+        }
+    "#;
+
+    fn filtered(contract: Contract) -> String {
+        let supergraph = parse_schema(SUPERGRAPH);
+        let filtered = contract.apply(&supergraph);
+        let schema_str = format!(
+            "{}",
+            StripSchemaInternals::strip_schema_internals(&filtered)
+        );
+        // the filtered schema must remain valid
+        parse_schema(&schema_str);
+        schema_str
+    }
+
+    #[test]
+    fn exclude_tags() {
+        insta::assert_snapshot!(filtered(Contract {
+            include_tags: vec![],
+            exclude_tags: vec!["internal".to_string()],
+        }), @r#"
+        schema {
+          query: Query
+        }
+
+        type Query {
+          products(filter: ProductFilter): [Product!]!
+          product(id: ID!): Product
+          me: User
+          stats: Stats
+        }
+
+        type Product implements Node {
+          id: ID!
+          name: String
+          category: Category
+          search: String
+        }
+
+        interface Node {
+          id: ID!
+        }
+
+        type User {
+          id: ID!
+        }
+
+        type Stats {
+          count: Int
+        }
+
+        enum Category {
+          BOOKS
+          TOYS
+        }
+
+        input ProductFilter {
+          name: String
+          category: Category
+        }
+        "#);
+    }
+
+    #[test]
+    fn include_tags() {
+        insta::assert_snapshot!(filtered(Contract {
+            include_tags: vec!["public".to_string()],
+            exclude_tags: vec![],
+        }), @r#"
+        schema {
+          query: Query
+        }
+
+        type Query {
+          products(filter: ProductFilter): [Product!]!
+          product(id: ID!): Product
+          me: User
+        }
+
+        type Product {
+          id: ID!
+          name: String
+          cost: Float
+          category: Category
+          owner: User
+          search(kind: SearchKind): String
+        }
+
+        type User {
+          id: ID!
+        }
+
+        enum Category {
+          BOOKS
+          TOYS
+          PROTOTYPES
+        }
+
+        enum SearchKind {
+          FULL
+        }
+
+        input ProductFilter {
+          name: String
+          category: Category
+        }
+        "#);
+    }
+
+    #[test]
+    fn exclude_takes_precedence_over_include() {
+        insta::assert_snapshot!(filtered(Contract {
+            include_tags: vec!["public".to_string(), "internal".to_string()],
+            exclude_tags: vec!["internal".to_string()],
+        }), @r#"
+        schema {
+          query: Query
+        }
+
+        type Query {
+          products(filter: ProductFilter): [Product!]!
+          product(id: ID!): Product
+          me: User
+        }
+
+        type Product {
+          id: ID!
+          name: String
+          category: Category
+          search: String
+        }
+
+        type User {
+          id: ID!
+        }
+
+        enum Category {
+          BOOKS
+          TOYS
+        }
+
+        input ProductFilter {
+          name: String
+          category: Category
+        }
+        "#);
+    }
+}
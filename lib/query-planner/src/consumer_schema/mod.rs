@@ -1,3 +1,4 @@
+pub mod contract;
 pub(crate) mod prune_inacessible;
 pub(crate) mod strip_schema_internals;
 
@@ -6,6 +7,7 @@ use std::{
     sync::Arc,
 };
 
+use contract::Contract;
 use graphql_tools::static_graphql::schema::{Definition, Document, TypeDefinition};
 use prune_inacessible::PruneInaccessible;
 use strip_schema_internals::StripSchemaInternals;
@@ -29,12 +31,23 @@ impl From<Arc<Document>> for ConsumerSchema {
 
 impl ConsumerSchema {
     pub fn new_from_supergraph(supergraph: &Document) -> Self {
-        let document: Arc<Document> = Self::create_consumer_schema(supergraph).into();
+        Self::new_from_supergraph_with_contract(supergraph, None)
+    }
+
+    /// Creates the consumer schema of a contract, exposing only the elements it keeps.
+    pub fn new_from_supergraph_with_contract(
+        supergraph: &Document,
+        contract: Option<&Contract>,
+    ) -> Self {
+        let document: Arc<Document> = Self::create_consumer_schema(supergraph, contract).into();
         document.into()
     }
 
-    fn create_consumer_schema(supergraph: &Document) -> Document {
-        let mut result = PruneInaccessible::prune(supergraph);
+    fn create_consumer_schema(supergraph: &Document, contract: Option<&Contract>) -> Document {
+        let mut result = match contract {
+            Some(contract) => PruneInaccessible::prune(&contract.apply(supergraph)),
+            None => PruneInaccessible::prune(supergraph),
+        };
         result = StripSchemaInternals::strip_schema_internals(&result);
         // Add introspection schema to the consumer schema
         let introspection_schema = include_str!("introspection_schema.graphql");
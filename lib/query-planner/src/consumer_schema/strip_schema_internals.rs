@@ -4,14 +4,16 @@ use graphql_tools::parser::schema::*;
 use crate::{
     federation_spec::{
         definitions::{
-            CorePurposesEnum, JoinDirectiveArgumentsScalar, JoinFieldSetScalar, JoinGraphEnum,
+            ContextFieldValueScalar, CorePurposesEnum, JoinContextArgumentInput,
+            JoinDirectiveArgumentsScalar, JoinFieldSetScalar, JoinFieldValueScalar, JoinGraphEnum,
             LinkImportScalar, LinkPurposeEnum, RequiresScopesScopeScalar,
         },
         demand_control::{CostDirective, ListSizeDirective},
         directives::{
-            AuthenticatedDirective, CoreDirective, InaccessibleDirective, JoinEnumValueDirective,
-            JoinFieldDirective, JoinGraphDirective, JoinImplementsDirective, JoinTypeDirective,
-            JoinUnionMemberDirective, LinkDirective, RequiresScopesDirective, TagDirective,
+            AuthenticatedDirective, ContextDirective, CoreDirective, InaccessibleDirective,
+            JoinEnumValueDirective, JoinFieldDirective, JoinGraphDirective,
+            JoinImplementsDirective, JoinTypeDirective, JoinUnionMemberDirective, LinkDirective,
+            RequiresScopesDirective, TagDirective,
         },
         join_directive::JoinDirectiveDirective,
         join_owner::JoinOwnerDirective,
@@ -22,7 +24,7 @@ use crate::{
 // directive @inaccessible on FIELD_DEFINITION | OBJECT | INTERFACE | UNION | ENUM | ENUM_VALUE | SCALAR | INPUT_OBJECT | INPUT_FIELD_DEFINITION | ARGUMENT_DEFINITION
 pub(crate) struct StripSchemaInternals;
 
-static DIRECTIVES_TO_STRIP: [&str; 16] = [
+static DIRECTIVES_TO_STRIP: [&str; 17] = [
     JoinTypeDirective::NAME,
     JoinEnumValueDirective::NAME,
     JoinFieldDirective::NAME,
@@ -39,9 +41,10 @@ static DIRECTIVES_TO_STRIP: [&str; 16] = [
     RequiresScopesDirective::NAME,
     CostDirective::NAME,
     ListSizeDirective::NAME,
+    ContextDirective::NAME,
 ];
 
-static DEFINITIONS_TO_STRIP: [&str; 7] = [
+static DEFINITIONS_TO_STRIP: [&str; 10] = [
     LinkPurposeEnum::NAME,
     LinkImportScalar::NAME,
     JoinGraphEnum::NAME,
@@ -49,6 +52,9 @@ static DEFINITIONS_TO_STRIP: [&str; 7] = [
     JoinDirectiveArgumentsScalar::NAME,
     CorePurposesEnum::NAME,
     RequiresScopesScopeScalar::NAME,
+    JoinContextArgumentInput::NAME,
+    JoinFieldValueScalar::NAME,
+    ContextFieldValueScalar::NAME,
 ];
 
 impl StripSchemaInternals {
@@ -0,0 +1,35 @@
+use graphql_tools::parser::schema::{Directive, Value};
+
+use super::directives::FederationDirective;
+
+/// `@context(name:)` marks a type as a source of values for `@fromContext` arguments.
+/// The name is prefixed with the subgraph name during composition.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ContextDirective {
+    pub name: String,
+}
+
+impl ContextDirective {
+    pub const NAME: &str = "context";
+}
+
+impl FederationDirective for ContextDirective {
+    fn directive_name() -> &'static str {
+        Self::NAME
+    }
+
+    fn parse(directive: &Directive<'_, String>) -> Self {
+        let name = directive
+            .arguments
+            .iter()
+            .find_map(
+                |(arg_name, arg_value)| match (arg_name.as_str(), arg_value) {
+                    ("name", Value::String(value)) => Some(value.clone()),
+                    _ => None,
+                },
+            )
+            .unwrap_or_default();
+
+        Self { name }
+    }
+}
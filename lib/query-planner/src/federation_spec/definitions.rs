@@ -39,3 +39,21 @@ pub struct JoinDirectiveArgumentsScalar {}
 impl JoinDirectiveArgumentsScalar {
     pub const NAME: &str = "join__DirectiveArguments";
 }
+
+pub struct JoinContextArgumentInput {}
+
+impl JoinContextArgumentInput {
+    pub const NAME: &str = "join__ContextArgument";
+}
+
+pub struct JoinFieldValueScalar {}
+
+impl JoinFieldValueScalar {
+    pub const NAME: &str = "join__FieldValue";
+}
+
+pub struct ContextFieldValueScalar {}
+
+impl ContextFieldValueScalar {
+    pub const NAME: &str = "context__ContextFieldValue";
+}
@@ -1,5 +1,6 @@
 pub use crate::federation_spec::authorization::AuthenticatedDirective;
 pub use crate::federation_spec::authorization::RequiresScopesDirective;
+pub use crate::federation_spec::context::ContextDirective;
 pub use crate::federation_spec::directive_trait::FederationDirective;
 pub use crate::federation_spec::inacessible::InaccessibleDirective;
 pub use crate::federation_spec::join_enum_value::JoinEnumValueDirective;
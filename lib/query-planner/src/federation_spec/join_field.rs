@@ -17,6 +17,37 @@ pub struct JoinFieldDirective {
     pub override_value: Option<String>,
     pub override_label: Option<OverrideLabel>,
     pub used_overridden: bool,
+    pub context_arguments: Vec<JoinContextArgument>,
+}
+
+/// An argument of a field, whose value is selected from an ancestor annotated with `@context`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JoinContextArgument {
+    pub name: String,
+    pub type_in_graph: TypeNode,
+    pub context: String,
+    pub selection: String,
+}
+
+impl JoinContextArgument {
+    fn parse(value: &Value<'_, String>) -> Option<Self> {
+        let Value::Object(fields) = value else {
+            return None;
+        };
+        let string_field = |name: &str| {
+            fields.iter().find_map(|(key, value)| match value {
+                Value::String(value) if key == name => Some(value.clone()),
+                _ => None,
+            })
+        };
+
+        Some(Self {
+            name: string_field("name")?,
+            type_in_graph: string_field("type")?.as_str().try_into().ok()?,
+            context: string_field("context")?,
+            selection: string_field("selection")?,
+        })
+    }
 }
 
 // Kamil: I added allow(clippy), because I prefer to define the defaults explicitly,
@@ -33,6 +64,7 @@ impl Default for JoinFieldDirective {
             override_value: None,
             override_label: None,
             used_overridden: false,
+            context_arguments: Vec::new(),
         }
     }
 }
@@ -131,6 +163,13 @@ impl FederationDirective for JoinFieldDirective {
                 if let Value::String(value) = arg_value {
                     result.override_label = Some(Self::parse_override_label(value));
                 }
+            } else if arg_name.eq("contextArguments") {
+                if let Value::List(values) = arg_value {
+                    result.context_arguments = values
+                        .iter()
+                        .filter_map(JoinContextArgument::parse)
+                        .collect();
+                }
             }
         }
 
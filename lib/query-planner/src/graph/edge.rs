@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt::{Debug, Display},
     hash::Hash,
 };
@@ -96,6 +96,9 @@ type ActiveFlags = HashSet<String>;
 pub struct PlannerOverrideContext {
     active_flags: ActiveFlags,
     request_percentage_value: Percentage,
+    /// Outcomes of percentage labels forced on or off,
+    /// regardless of the percentage value of the request.
+    forced_percentages: HashMap<Percentage, bool>,
 }
 
 impl PlannerOverrideContext {
@@ -103,13 +106,30 @@ impl PlannerOverrideContext {
         Self {
             active_flags,
             request_percentage_value,
+            forced_percentages: Default::default(),
         }
     }
 
+    /// Maps a key identifying the request (e.g. a user id) to a percentage value.
+    /// The same key always lands on the same side of a `percent(x)` label,
+    /// so the rollout is sticky across requests and router instances.
+    pub fn percentage_from_request_key(key: &[u8]) -> Percentage {
+        xxhash_rust::xxh3::xxh3_64(key) % (100 * PERCENTAGE_SCALE_FACTOR)
+    }
+
+    pub fn with_forced_percentages(
+        mut self,
+        forced_percentages: HashMap<Percentage, bool>,
+    ) -> Self {
+        self.forced_percentages = forced_percentages;
+        self
+    }
+
     pub fn from_percentage(value: f64) -> Self {
         Self {
             active_flags: Default::default(),
             request_percentage_value: (value * (PERCENTAGE_SCALE_FACTOR as f64)) as u64,
+            forced_percentages: Default::default(),
         }
     }
 
@@ -117,6 +137,7 @@ impl PlannerOverrideContext {
         Self {
             active_flags: HashSet::from([value]),
             request_percentage_value: 0,
+            forced_percentages: Default::default(),
         }
     }
 
@@ -125,7 +146,10 @@ impl PlannerOverrideContext {
     }
 
     pub fn is_in_range(&self, percentage: &Percentage) -> bool {
-        &self.request_percentage_value < percentage
+        match self.forced_percentages.get(percentage) {
+            Some(forced) => *forced,
+            None => &self.request_percentage_value < percentage,
+        }
     }
 }
 
@@ -146,6 +170,22 @@ pub enum OverrideLabel {
     Percentage(Percentage),
 }
 
+impl OverrideLabel {
+    /// Parses the percentage of a `percent(x)` label,
+    /// the same way it's stored when read from `@join__field(overrideLabel:)`.
+    pub fn percentage_from_label(label: &str) -> Option<Percentage> {
+        let value = label
+            .strip_prefix("percent(")?
+            .strip_suffix(')')?
+            .parse::<f64>()
+            .ok()?;
+
+        (0.0..=100.0)
+            .contains(&value)
+            .then_some((value * (PERCENTAGE_SCALE_FACTOR as f64)) as u64)
+    }
+}
+
 impl Display for OverrideLabel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -2,6 +2,7 @@ pub(crate) mod edge;
 pub(crate) mod error;
 pub(crate) mod node;
 
+pub use self::edge::OverrideLabel;
 pub use self::edge::PlannerOverrideContext;
 pub use self::edge::PERCENTAGE_SCALE_FACTOR;
 
@@ -697,6 +698,23 @@ impl Graph {
                                 requires_str,
                               )
                               .into(),
+                          })
+                          // A field with arguments taken from a @context is resolved with an entity call,
+                          // the same way as a field with @requires,
+                          // so the values can be collected from its ancestors before the call.
+                          .or_else(|| {
+                              maybe_join_field
+                                  .filter(|join_field| !join_field.context_arguments.is_empty())
+                                  .map(|_| TypeAwareSelection {
+                                      type_name: def_name.to_string(),
+                                      selection_set: FederationRules::parse_requires(
+                                          state,
+                                          &graph_id.to_string(),
+                                          def_name,
+                                          &"__typename".to_string(),
+                                      )
+                                      .into(),
+                                  })
                           });
 
                     // If a field points to a union type:
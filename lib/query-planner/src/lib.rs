@@ -2,6 +2,7 @@
 // #![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
 
 pub mod ast;
+pub mod composition;
 pub mod consumer_schema;
 pub mod federation_spec;
 pub mod graph;
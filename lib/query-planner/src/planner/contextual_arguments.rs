@@ -0,0 +1,429 @@
+use std::collections::BTreeSet;
+
+use graphql_tools::parser::query as query_ast;
+use petgraph::graph::NodeIndex;
+use tracing::trace;
+
+use crate::{
+    ast::{
+        arguments::ArgumentsMap,
+        operation::{OperationDefinition, VariableDefinition},
+        selection_item::SelectionItem,
+        selection_set::SelectionSet,
+        value::Value,
+    },
+    federation_spec::join_field::JoinContextArgument,
+    planner::{
+        fetch::{
+            error::FetchGraphError, fetch_graph::FetchGraph, fetch_step_data::FetchStepFlags,
+            state::SingleTypeFetchStep,
+        },
+        plan_nodes::ContextArgument,
+    },
+    state::supergraph_state::{SupergraphDefinition, SupergraphField, SupergraphState},
+};
+
+const CONTEXTUAL_ARGUMENT_VARIABLE_PREFIX: &str = "contextualArgument_";
+
+/// Prepares an operation for fields with arguments annotated with `@fromContext`.
+///
+/// Given `t { u { field } }`, where `T @context(name: "ctx")` and
+/// `field(a: String @fromContext(field: "$ctx { prop }"))`,
+/// the operation becomes `t { contextualArgument_0: prop u { field(a: $contextualArgument_0) } }`.
+///
+/// The closest ancestor setting the context selects the value under a response key
+/// equal to the name of the variable, so the executor can find it for each entity.
+/// Returns `None` when the operation does not use any contextual argument.
+pub fn apply_contextual_arguments(
+    supergraph: &SupergraphState,
+    operation: &OperationDefinition,
+) -> Option<(OperationDefinition, Vec<ContextArgument>)> {
+    if supergraph.contexts.is_empty() {
+        return None;
+    }
+
+    let root_type_name = supergraph.expect_root_type_name(operation.operation_kind.as_ref());
+    let mut collector = Collector {
+        supergraph,
+        ancestors: Vec::new(),
+        argument_edits: Vec::new(),
+        selection_inserts: Vec::new(),
+        variable_definitions: Vec::new(),
+        context_arguments: Vec::new(),
+    };
+    collector.visit(&operation.selection_set, root_type_name, &mut Vec::new(), 0);
+
+    if collector.context_arguments.is_empty() {
+        return None;
+    }
+
+    let mut operation = operation.clone();
+
+    // Arguments are added first, as appending selections does not shift the indexes of existing items
+    for edit in collector.argument_edits {
+        let Some((field_index, set_path)) = edit.field_path.split_last() else {
+            continue;
+        };
+        let selection_set = selection_set_at_mut(&mut operation.selection_set, set_path);
+        if let Some(SelectionItem::Field(field)) = selection_set.items.get_mut(*field_index) {
+            field
+                .arguments
+                .get_or_insert_with(ArgumentsMap::new)
+                .add_argument(edit.argument_name, Value::Variable(edit.variable_name));
+        }
+    }
+
+    for insert in collector.selection_inserts {
+        selection_set_at_mut(&mut operation.selection_set, &insert.set_path)
+            .items
+            .extend(insert.items);
+    }
+
+    operation
+        .variable_definitions
+        .get_or_insert_default()
+        .extend(collector.variable_definitions);
+
+    Some((operation, collector.context_arguments))
+}
+
+/// Makes every fetch step using a contextual argument wait for the steps
+/// fetching its value in one of the ancestors.
+pub fn link_contextual_fetch_steps(
+    fetch_graph: &mut FetchGraph<SingleTypeFetchStep>,
+    context_arguments: &[ContextArgument],
+) -> Result<(), FetchGraphError> {
+    for context_argument in context_arguments {
+        let mut providers: Vec<NodeIndex> = Vec::new();
+        let mut consumers: Vec<NodeIndex> = Vec::new();
+
+        for (index, step) in fetch_graph.all_nodes() {
+            if step
+                .output
+                .variable_usages()
+                .contains(&context_argument.variable_name)
+            {
+                consumers.push(index);
+            }
+
+            if step.output.iter().any(|(_, selection_set)| {
+                selects_response_key(selection_set, &context_argument.variable_name)
+            }) {
+                providers.push(index);
+            }
+        }
+
+        for consumer_index in consumers {
+            let consumer = fetch_graph.get_step_data_mut(consumer_index)?;
+
+            if !consumer.is_entity_call() {
+                return Err(FetchGraphError::Internal(format!(
+                    "expected an entity call to pass ${}",
+                    context_argument.variable_name
+                )));
+            }
+
+            consumer.flags.insert(FetchStepFlags::USED_FOR_CONTEXT);
+            consumer.add_context_argument(context_argument.clone());
+
+            for provider_index in providers.iter() {
+                if *provider_index == consumer_index
+                    || fetch_graph.is_descendant_of(*provider_index, consumer_index)
+                {
+                    return Err(FetchGraphError::Internal(format!(
+                        "fetch step [{}] can't wait for [{}] to receive ${}",
+                        consumer_index.index(),
+                        provider_index.index(),
+                        context_argument.variable_name
+                    )));
+                }
+
+                if !fetch_graph.is_descendant_of(consumer_index, *provider_index) {
+                    trace!(
+                        "connecting [{}] -> [{}] to pass ${}",
+                        provider_index.index(),
+                        consumer_index.index(),
+                        context_argument.variable_name
+                    );
+                    fetch_graph.connect(*provider_index, consumer_index);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn selects_response_key(selection_set: &SelectionSet, response_key: &str) -> bool {
+    selection_set.items.iter().any(|item| match item {
+        SelectionItem::Field(field) => {
+            field.selection_identifier() == response_key
+                || selects_response_key(&field.selections, response_key)
+        }
+        SelectionItem::InlineFragment(fragment) => {
+            selects_response_key(&fragment.selections, response_key)
+        }
+        SelectionItem::FragmentSpread(_) => false,
+    })
+}
+
+fn selection_set_at_mut<'a>(
+    mut selection_set: &'a mut SelectionSet,
+    path: &[usize],
+) -> &'a mut SelectionSet {
+    for index in path {
+        selection_set = match &mut selection_set.items[*index] {
+            SelectionItem::Field(field) => &mut field.selections,
+            SelectionItem::InlineFragment(fragment) => &mut fragment.selections,
+            SelectionItem::FragmentSpread(_) => unreachable!("fragment spreads are inlined"),
+        };
+    }
+
+    selection_set
+}
+
+/// A selection set of an ancestor, that may set a context.
+struct Ancestor<'a> {
+    type_name: &'a str,
+    /// Indexes of items leading to the selection set
+    set_path: Vec<usize>,
+    /// Fields and fragments of the same object share the depth
+    depth: usize,
+}
+
+struct ArgumentEdit {
+    field_path: Vec<usize>,
+    argument_name: String,
+    variable_name: String,
+}
+
+struct SelectionInsert {
+    set_path: Vec<usize>,
+    items: Vec<SelectionItem>,
+}
+
+struct Collector<'a> {
+    supergraph: &'a SupergraphState,
+    ancestors: Vec<Ancestor<'a>>,
+    argument_edits: Vec<ArgumentEdit>,
+    selection_inserts: Vec<SelectionInsert>,
+    variable_definitions: Vec<VariableDefinition>,
+    context_arguments: Vec<ContextArgument>,
+}
+
+impl<'a> Collector<'a> {
+    fn visit(
+        &mut self,
+        selection_set: &SelectionSet,
+        type_name: &'a str,
+        set_path: &mut Vec<usize>,
+        depth: usize,
+    ) {
+        self.ancestors.push(Ancestor {
+            type_name,
+            set_path: set_path.clone(),
+            depth,
+        });
+
+        for (index, item) in selection_set.items.iter().enumerate() {
+            set_path.push(index);
+            match item {
+                SelectionItem::Field(field) => {
+                    if let Some(field_definition) = self
+                        .supergraph
+                        .definitions
+                        .get(type_name)
+                        .and_then(|definition| definition.fields().get(&field.name))
+                    {
+                        for context_argument in contextual_arguments_of(field_definition) {
+                            self.collect(context_argument, set_path, depth);
+                        }
+
+                        if !field.selections.is_empty() {
+                            self.visit(
+                                &field.selections,
+                                field_definition.field_type.inner_type(),
+                                set_path,
+                                depth + 1,
+                            );
+                        }
+                    }
+                }
+                SelectionItem::InlineFragment(fragment) => {
+                    if let Some(definition) =
+                        self.supergraph.definitions.get(&fragment.type_condition)
+                    {
+                        self.visit(&fragment.selections, definition.name(), set_path, depth);
+                    }
+                }
+                SelectionItem::FragmentSpread(_) => {}
+            }
+            set_path.pop();
+        }
+
+        self.ancestors.pop();
+    }
+
+    fn collect(&mut self, argument: &JoinContextArgument, field_path: &[usize], depth: usize) {
+        let Some(selection_set) = parse_context_selection(&argument.selection) else {
+            trace!(
+                "invalid selection '{}' of contextual argument '{}'",
+                argument.selection,
+                argument.name
+            );
+            return;
+        };
+
+        let variable_name = format!(
+            "{}{}",
+            CONTEXTUAL_ARGUMENT_VARIABLE_PREFIX,
+            self.context_arguments.len()
+        );
+
+        // The closest ancestor wins, the object holding the field itself is not an ancestor
+        let Some((set_path, items)) = self
+            .ancestors
+            .iter()
+            .rev()
+            .filter(|ancestor| ancestor.depth < depth)
+            .filter(|ancestor| {
+                self.supergraph
+                    .is_context_type(&argument.context, ancestor.type_name)
+            })
+            .find_map(|ancestor| {
+                let items = self.aliased_items(&selection_set, ancestor.type_name, &variable_name);
+                (!items.is_empty()).then(|| (ancestor.set_path.clone(), items))
+            })
+        else {
+            trace!(
+                "no ancestor sets context '{}' for argument '{}'",
+                argument.context,
+                argument.name
+            );
+            return;
+        };
+
+        self.selection_inserts
+            .push(SelectionInsert { set_path, items });
+        self.argument_edits.push(ArgumentEdit {
+            field_path: field_path.to_vec(),
+            argument_name: argument.name.clone(),
+            variable_name: variable_name.clone(),
+        });
+        self.variable_definitions.push(VariableDefinition {
+            name: variable_name.clone(),
+            variable_type: argument.type_in_graph.clone(),
+            default_value: None,
+        });
+        self.context_arguments.push(ContextArgument {
+            variable_name,
+            path: value_path(&selection_set),
+        });
+    }
+
+    /// Items of the context selection applicable to the ancestor type,
+    /// with top-level fields aliased with the name of the variable.
+    fn aliased_items(
+        &self,
+        selection_set: &SelectionSet,
+        type_name: &str,
+        alias: &str,
+    ) -> Vec<SelectionItem> {
+        let is_abstract = matches!(
+            self.supergraph.definitions.get(type_name),
+            Some(SupergraphDefinition::Interface(_) | SupergraphDefinition::Union(_))
+        );
+        let mut items = Vec::new();
+
+        for item in &selection_set.items {
+            match item {
+                SelectionItem::Field(field) => {
+                    let mut field = field.clone();
+                    field.alias = Some(alias.to_string());
+                    items.push(SelectionItem::Field(field));
+                }
+                SelectionItem::InlineFragment(fragment) if fragment.type_condition == type_name => {
+                    items.extend(self.aliased_items(&fragment.selections, type_name, alias));
+                }
+                SelectionItem::InlineFragment(fragment) if is_abstract => {
+                    let selections = SelectionSet {
+                        items: self.aliased_items(
+                            &fragment.selections,
+                            &fragment.type_condition,
+                            alias,
+                        ),
+                    };
+                    items.push(SelectionItem::InlineFragment(
+                        fragment.with_new_selections(selections),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        items
+    }
+}
+
+fn contextual_arguments_of(field_definition: &SupergraphField) -> Vec<&JoinContextArgument> {
+    let mut seen = BTreeSet::new();
+
+    field_definition
+        .join_field
+        .iter()
+        .flat_map(|join_field| join_field.context_arguments.iter())
+        .filter(|argument| seen.insert(argument.name.as_str()))
+        .collect()
+}
+
+fn parse_context_selection(selection: &str) -> Option<SelectionSet> {
+    let selection = selection.trim();
+    let selection = if selection.starts_with('{') {
+        selection.to_string()
+    } else {
+        format!("{{ {selection} }}")
+    };
+
+    match query_ast::parse_query::<String>(&selection)
+        .ok()?
+        .definitions
+        .into_iter()
+        .next()?
+    {
+        query_ast::Definition::Operation(query_ast::OperationDefinition::SelectionSet(
+            selection_set,
+        )) => Some(selection_set.into()),
+        _ => None,
+    }
+}
+
+/// Path to the value, relative to the aliased top-level field.
+/// A selection of a single field is unwrapped, so `{ user { id } }` points to `user.id`.
+fn value_path(selection_set: &SelectionSet) -> Vec<String> {
+    let first_field = selection_set.items.iter().find_map(|item| match item {
+        SelectionItem::Field(field) => Some(field.clone()),
+        SelectionItem::InlineFragment(fragment) => {
+            fragment
+                .selections
+                .items
+                .iter()
+                .find_map(|item| match item {
+                    SelectionItem::Field(field) => Some(field.clone()),
+                    _ => None,
+                })
+        }
+        SelectionItem::FragmentSpread(_) => None,
+    });
+
+    let mut path = Vec::new();
+    let mut current = first_field.map(|field| field.selections);
+
+    while let Some(selections) = current.take() {
+        if let [SelectionItem::Field(field)] = selections.items.as_slice() {
+            path.push(field.selection_identifier().to_string());
+            current = Some(field.selections.clone());
+        }
+    }
+
+    path
+}
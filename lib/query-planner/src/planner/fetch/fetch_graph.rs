@@ -7,10 +7,14 @@ use crate::graph::edge::{
 };
 use crate::graph::node::Node;
 use crate::graph::Graph;
+use crate::planner::contextual_arguments::link_contextual_fetch_steps;
 use crate::planner::fetch::fetch_step_data::{FetchStepData, FetchStepFlags, FetchStepKind};
 use crate::planner::fetch::selections::FetchStepSelections;
 use crate::planner::fetch::state::{MultiTypeFetchStep, SingleTypeFetchStep};
-use crate::planner::plan_nodes::{FetchNodePathSegment, FetchRewrite, ValueSetter};
+use crate::planner::plan_format::{Diagram, DiagramFormat};
+use crate::planner::plan_nodes::{
+    ContextArgument, FetchNodePathSegment, FetchRewrite, ValueSetter,
+};
 use crate::planner::tree::query_tree::QueryTree;
 use crate::planner::tree::query_tree_node::{MutationFieldPosition, QueryTreeNode};
 use crate::planner::walker::path::OperationPath;
@@ -22,10 +26,10 @@ use petgraph::algo::has_path_connecting;
 use petgraph::graph::EdgeReference;
 use petgraph::stable_graph::{EdgeIndex, NodeIndex, NodeIndices, NodeReferences, StableDiGraph};
 use petgraph::visit::EdgeRef;
-use petgraph::visit::{Bfs, IntoNodeReferences};
+use petgraph::visit::{Bfs, IntoEdgeReferences, IntoNodeReferences};
 use petgraph::Directed;
 use petgraph::Direction;
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::fmt::{Debug, Display};
 use tracing::{instrument, trace};
 
@@ -287,6 +291,67 @@ impl<State> Display for FetchGraph<State> {
     }
 }
 
+impl<State> FetchGraph<State> {
+    /// Returns the fetch graph as a diagram, with a node per fetch step,
+    /// labeled with its subgraph and response path, and an edge from every step to its dependents.
+    /// Edges to steps resolving `@requires` or `@fromContext` are labeled.
+    pub fn to_diagram(&self, format: DiagramFormat) -> String {
+        let mut diagram = Diagram::new(format);
+        let mut diagram_ids = HashMap::new();
+
+        for (node_index, step) in self.graph.node_references() {
+            let id = if step.service_name == SubgraphName::any() {
+                diagram.add_node("Root")
+            } else {
+                diagram.add_node(FetchStepLabel(node_index, step))
+            };
+            diagram_ids.insert(node_index, id);
+        }
+
+        for edge in self.graph.edge_references() {
+            let child = &self.graph[edge.target()];
+            let label = if child.flags.contains(FetchStepFlags::USED_FOR_REQUIRES) {
+                Some("requires")
+            } else if child.flags.contains(FetchStepFlags::USED_FOR_CONTEXT) {
+                Some("context")
+            } else {
+                None
+            };
+            diagram.add_edge(
+                diagram_ids[&edge.source()],
+                diagram_ids[&edge.target()],
+                label,
+            );
+        }
+
+        diagram.finish()
+    }
+}
+
+struct FetchStepLabel<'a, State>(NodeIndex, &'a FetchStepData<State>);
+
+impl<State> Display for FetchStepLabel<'_, State> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let FetchStepLabel(node_index, step) = self;
+        write!(
+            f,
+            "[{}] {} at $.{}",
+            node_index.index(),
+            step.service_name,
+            step.response_path.join(".")
+        )?;
+
+        match &step.condition {
+            Some(Condition::Include(var_name)) => write!(f, " @include(if: ${})", var_name),
+            Some(Condition::Skip(var_name)) => write!(f, " @skip(if: ${})", var_name),
+            Some(Condition::SkipAndInclude { skip, include }) => {
+                write!(f, " @skip(if: ${}) @include(if: ${})", skip, include)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
 fn create_noop_fetch_step(
     fetch_graph: &mut FetchGraph<SingleTypeFetchStep>,
     created_from_requires: bool,
@@ -309,6 +374,7 @@ fn create_noop_fetch_step(
         operation_kind: OperationKind::Query,
         input_rewrites: None,
         output_rewrites: None,
+        context_arguments: None,
         variable_usages: None,
         variable_definitions: None,
         mutation_field_position: None,
@@ -349,6 +415,7 @@ fn create_fetch_step_for_entity_call(
         operation_kind: OperationKind::Query,
         input_rewrites: None,
         output_rewrites: None,
+        context_arguments: None,
         variable_usages: None,
         variable_definitions: None,
         mutation_field_position: None,
@@ -382,6 +449,7 @@ fn create_fetch_step_for_root_move(
         variable_definitions: None,
         input_rewrites: None,
         output_rewrites: None,
+        context_arguments: None,
         mutation_field_position,
         internal_aliases_locations: Vec::new(),
     });
@@ -1996,12 +2064,14 @@ pub fn find_graph_roots<State>(graph: &FetchGraph<State>) -> Vec<NodeIndex> {
     requirements_count = query_tree.root.requirements.len(),
     children_count = query_tree.root.children.len(),
 ))]
+#[allow(clippy::too_many_arguments)]
 pub fn build_fetch_graph_from_query_tree(
     graph: &Graph,
     supergraph: &SupergraphState,
     override_context: &PlannerOverrideContext,
     query_tree: QueryTree,
     operation_kind: OperationKind,
+    context_arguments: &[ContextArgument],
     options: &QueryPlannerOptions,
     cancellation_token: &CancellationToken,
 ) -> Result<FetchGraph<MultiTypeFetchStep>, FetchGraphError> {
@@ -2040,6 +2110,7 @@ pub fn build_fetch_graph_from_query_tree(
 
     // fine to unwrap as we have already checked the length
     fetch_graph.root_index = Some(*root_indexes.first().unwrap());
+    link_contextual_fetch_steps(&mut fetch_graph, context_arguments)?;
     let mut fetch_graph = fetch_graph.to_multi_type();
     fetch_graph.optimize(supergraph, options, cancellation_token)?;
     fetch_graph.collect_variable_usages()?;
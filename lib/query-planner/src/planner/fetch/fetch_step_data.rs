@@ -14,7 +14,7 @@ use crate::{
             selections::FetchStepSelections,
             state::{MultiTypeFetchStep, SingleTypeFetchStep},
         },
-        plan_nodes::FetchRewrite,
+        plan_nodes::{ContextArgument, FetchRewrite},
         tree::query_tree_node::MutationFieldPosition,
     },
     state::supergraph_state::{OperationKind, SubgraphName},
@@ -27,6 +27,8 @@ bitflags! {
         const USED_FOR_REQUIRES = 1 << 0;
         /// This fetch is for resolving a type condition on an interface.
         const USED_FOR_TYPE_CONDITION = 1 << 1;
+        /// This fetch passes values selected from ancestors of the entities (@fromContext).
+        const USED_FOR_CONTEXT = 1 << 2;
     }
 }
 
@@ -46,6 +48,7 @@ pub struct FetchStepData<State> {
     pub mutation_field_position: MutationFieldPosition,
     pub input_rewrites: Option<Vec<FetchRewrite>>,
     pub output_rewrites: Option<Vec<FetchRewrite>>,
+    pub context_arguments: Option<Vec<ContextArgument>>,
     pub internal_aliases_locations: Vec<(String, AliasesRecords)>,
 }
 
@@ -79,6 +82,10 @@ impl<State> Display for FetchStepData<State> {
             write!(f, " [no_pass_through]")?;
         }
 
+        if self.flags.contains(FetchStepFlags::USED_FOR_CONTEXT) {
+            write!(f, " [@fromContext]")?;
+        }
+
         if let Some(condition) = &self.condition {
             match condition {
                 Condition::Include(var_name) => write!(f, " [@include(if: ${})]", var_name)?,
@@ -114,6 +121,14 @@ impl<State> FetchStepData<State> {
         }
     }
 
+    pub fn add_context_argument(&mut self, context_argument: ContextArgument) {
+        let context_arguments = self.context_arguments.get_or_insert_default();
+
+        if !context_arguments.contains(&context_argument) {
+            context_arguments.push(context_argument);
+        }
+    }
+
     pub fn add_output_rewrite(&mut self, rewrite: FetchRewrite) {
         let rewrites = self.output_rewrites.get_or_insert_default();
 
@@ -231,6 +246,7 @@ impl FetchStepData<SingleTypeFetchStep> {
             mutation_field_position: self.mutation_field_position,
             input_rewrites: self.input_rewrites,
             output_rewrites: self.output_rewrites,
+            context_arguments: self.context_arguments,
             internal_aliases_locations: self.internal_aliases_locations,
         }
     }
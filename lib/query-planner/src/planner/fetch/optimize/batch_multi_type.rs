@@ -433,6 +433,13 @@ impl FetchStepData<MultiTypeFetchStep> {
             return false;
         }
 
+        // Context values are collected from the ancestors of entities at a single path.
+        if self.flags.contains(FetchStepFlags::USED_FOR_CONTEXT)
+            || other.flags.contains(FetchStepFlags::USED_FOR_CONTEXT)
+        {
+            return false;
+        }
+
         // Paths must match after removing type conditions.
         if self.response_path.without_type_castings() != other.response_path.without_type_castings()
         {
@@ -102,7 +102,7 @@ impl FetchStepData<MultiTypeFetchStep> {
 
         if other
             .flags
-            .contains(FetchStepFlags::USED_FOR_TYPE_CONDITION)
+            .intersects(FetchStepFlags::USED_FOR_TYPE_CONDITION | FetchStepFlags::USED_FOR_CONTEXT)
         {
             return false;
         }
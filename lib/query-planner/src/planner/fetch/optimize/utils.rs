@@ -104,6 +104,13 @@ pub(crate) fn perform_fetch_step_merge(
         target.internal_aliases_locations.extend(scoped_aliases);
     }
 
+    if let Some(context_arguments) = source.context_arguments.take() {
+        for context_argument in context_arguments {
+            target.add_context_argument(context_argument);
+        }
+        target.flags.insert(FetchStepFlags::USED_FOR_CONTEXT);
+    }
+
     if let Some(input_rewrites) = source.input_rewrites.take() {
         if !input_rewrites.is_empty() {
             for input_rewrite in input_rewrites {
@@ -234,6 +241,17 @@ impl FetchStepData<MultiTypeFetchStep> {
             return false;
         }
 
+        // Context values are collected per entity, before the call is made,
+        // so such call can only be merged with another call for the same entities.
+        if (self.flags.contains(FetchStepFlags::USED_FOR_CONTEXT)
+            || other.flags.contains(FetchStepFlags::USED_FOR_CONTEXT))
+            && !(self.is_entity_call()
+                && other.is_entity_call()
+                && self.response_path == other.response_path)
+        {
+            return false;
+        }
+
         // If both are entities, their response_paths should match,
         // as we can't merge entity calls resolving different entities
         if matches!(self.kind, FetchStepKind::Entity) && self.kind == other.kind {
@@ -5,15 +5,19 @@ use fetch::{error::FetchGraphError, fetch_graph::build_fetch_graph_from_query_tr
 use graphql_tools::parser::schema;
 use petgraph::graph::NodeIndex;
 use plan_nodes::QueryPlan;
-use query_plan::build_query_plan_from_fetch_graph;
+use query_plan::{build_deferred_query_plan_from_fetch_graph, build_query_plan_from_fetch_graph};
 use walker::{error::WalkOperationError, walk_operation};
 
 use crate::{
-    ast::operation::{OperationDefinition, VariableDefinition},
-    consumer_schema::ConsumerSchema,
+    ast::{
+        normalization::defer::DeferredOperation,
+        operation::{OperationDefinition, VariableDefinition},
+    },
+    consumer_schema::{contract::Contract, ConsumerSchema},
     graph::{edge::PlannerOverrideContext, error::GraphError, Graph},
     planner::{
         best::find_best_combination,
+        contextual_arguments::apply_contextual_arguments,
         fetch::{fetch_graph::FetchGraph, state::MultiTypeFetchStep},
     },
     state::supergraph_state::{OperationKind, SupergraphState},
@@ -21,9 +25,12 @@ use crate::{
 };
 
 pub mod best;
+pub mod contextual_arguments;
 mod error;
 pub mod fetch;
+pub mod plan_format;
 pub mod plan_nodes;
+pub mod plan_stats;
 pub mod query_plan;
 pub mod tree;
 pub mod walker;
@@ -31,6 +38,8 @@ pub mod walker;
 #[derive(Debug, Clone, Default)]
 pub struct QueryPlannerOptions {
     pub experimental_abstract_type_folding: bool,
+    /// Filters the consumer schema, while the plans still target the full supergraph.
+    pub contract: Option<Contract>,
 }
 
 pub struct Planner {
@@ -71,7 +80,10 @@ impl Planner {
         options: QueryPlannerOptions,
     ) -> Result<Self, PlannerError> {
         let graph = Graph::graph_from_supergraph_state(&supergraph_state)?;
-        let consumer_schema = ConsumerSchema::new_from_supergraph(parsed_supergraph);
+        let consumer_schema = ConsumerSchema::new_from_supergraph_with_contract(
+            parsed_supergraph,
+            options.contract.as_ref(),
+        );
 
         Ok(Planner {
             graph,
@@ -88,11 +100,54 @@ impl Planner {
         override_context: PlannerOverrideContext,
         cancellation_token: &CancellationToken,
     ) -> Result<QueryPlan, PlannerError> {
+        let fetch_graph =
+            self.build_fetch_graph(normalized_operation, &override_context, cancellation_token)?;
+        let query_plan =
+            build_query_plan_from_fetch_graph(fetch_graph, &self.supergraph, cancellation_token)?;
+
+        Ok(query_plan)
+    }
+
+    /// Plans an operation with `@defer` fragments, the plan has a `DeferNode` at its root.
+    ///
+    /// `normalized_operation` is the whole operation, the fetches are planned as for any other
+    /// operation and then split between the primary and the deferred parts of the response.
+    pub fn plan_deferred_operation(
+        &self,
+        normalized_operation: &OperationDefinition,
+        deferred_operation: &DeferredOperation,
+        override_context: PlannerOverrideContext,
+        cancellation_token: &CancellationToken,
+    ) -> Result<QueryPlan, PlannerError> {
+        let fetch_graph =
+            self.build_fetch_graph(normalized_operation, &override_context, cancellation_token)?;
+        let query_plan = build_deferred_query_plan_from_fetch_graph(
+            fetch_graph,
+            deferred_operation,
+            &self.supergraph,
+            cancellation_token,
+        )?;
+
+        Ok(query_plan)
+    }
+
+    fn build_fetch_graph(
+        &self,
+        normalized_operation: &OperationDefinition,
+        override_context: &PlannerOverrideContext,
+        cancellation_token: &CancellationToken,
+    ) -> Result<FetchGraph<MultiTypeFetchStep>, PlannerError> {
+        let contextual_operation =
+            apply_contextual_arguments(&self.supergraph, normalized_operation);
+        let (operation, context_arguments) = match &contextual_operation {
+            Some((operation, context_arguments)) => (operation, context_arguments.as_slice()),
+            None => (normalized_operation, [].as_slice()),
+        };
         let best_paths_per_leaf = walk_operation(
             &self.graph,
             &self.supergraph,
-            &override_context,
-            normalized_operation,
+            override_context,
+            operation,
             cancellation_token,
         )?;
         let query_tree =
@@ -100,20 +155,19 @@ impl Planner {
         let mut fetch_graph = build_fetch_graph_from_query_tree(
             &self.graph,
             &self.supergraph,
-            &override_context,
+            override_context,
             query_tree,
-            normalized_operation
+            operation
                 .operation_kind
                 .clone()
                 .unwrap_or(OperationKind::Query),
+            context_arguments,
             &self.options,
             cancellation_token,
         )?;
-        add_variables_to_fetch_steps(&mut fetch_graph, &normalized_operation.variable_definitions)?;
-        let query_plan =
-            build_query_plan_from_fetch_graph(fetch_graph, &self.supergraph, cancellation_token)?;
+        add_variables_to_fetch_steps(&mut fetch_graph, &operation.variable_definitions)?;
 
-        Ok(query_plan)
+        Ok(fetch_graph)
     }
 }
 
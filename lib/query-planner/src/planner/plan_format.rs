@@ -0,0 +1,504 @@
+use std::{
+    fmt::{Display, Formatter as FmtFormatter, Result as FmtResult, Write},
+    str::FromStr,
+};
+
+use serde::Serialize;
+
+use crate::{
+    ast::{operation::SubgraphFetchOperation, selection_item::SelectionItem},
+    planner::plan_nodes::{
+        BatchFetchNode, ConditionNode, DeferNode, FetchNode, FetchNodePathSegment, FetchRewrite,
+        FlattenNodePath, FlattenNodePathSegment, PlanNode, QueryPlan,
+    },
+    state::supergraph_state::OperationKind,
+};
+
+/// The formats a query plan can be printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryPlanFormat {
+    /// The JSON serialization of [`QueryPlan`]
+    Json,
+    /// The JSON format of Apollo Router's query plans
+    Apollo,
+    /// The human-readable format, shared with Apollo Router
+    Text,
+    /// A Mermaid flowchart of the plan nodes
+    Mermaid,
+}
+
+impl FromStr for QueryPlanFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(QueryPlanFormat::Json),
+            "apollo" => Ok(QueryPlanFormat::Apollo),
+            "text" => Ok(QueryPlanFormat::Text),
+            "mermaid" => Ok(QueryPlanFormat::Mermaid),
+            other => Err(format!(
+                "unknown query plan format '{other}', expected one of: json, apollo, text, mermaid"
+            )),
+        }
+    }
+}
+
+impl QueryPlan {
+    /// Returns the plan in the JSON format of Apollo Router,
+    /// so existing tooling diffing Apollo query plans keeps working.
+    ///
+    /// `BatchFetch` nodes have no Apollo equivalent and keep their own shape.
+    pub fn to_apollo(&self) -> ApolloQueryPlan<'_> {
+        ApolloQueryPlan {
+            kind: "QueryPlan",
+            node: self.node.as_ref().map(ApolloPlanNode::from),
+        }
+    }
+
+    /// Returns the plan as a Mermaid flowchart.
+    pub fn to_mermaid(&self) -> String {
+        self.to_diagram(DiagramFormat::Mermaid)
+    }
+
+    /// Returns the plan nodes as a diagram, with an edge from every node to its children.
+    pub fn to_diagram(&self, format: DiagramFormat) -> String {
+        let mut diagram = Diagram::new(format);
+        let root = diagram.add_node("QueryPlan");
+        if let Some(node) = &self.node {
+            diagram.add_plan_node(node, root);
+        }
+        diagram.finish()
+    }
+}
+
+#[derive(Serialize)]
+pub struct ApolloQueryPlan<'a> {
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node: Option<ApolloPlanNode<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind")]
+enum ApolloPlanNode<'a> {
+    Fetch(ApolloFetchNode<'a>),
+    BatchFetch(&'a BatchFetchNode),
+    Sequence(ApolloNodes<'a>),
+    Parallel(ApolloNodes<'a>),
+    Flatten(ApolloFlattenNode<'a>),
+    Condition(ApolloConditionNode<'a>),
+    Subscription(ApolloSubscriptionNode<'a>),
+    Defer(ApolloDeferNode<'a>),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApolloFetchNode<'a> {
+    service_name: &'a str,
+    requires: &'a [SelectionItem],
+    variable_usages: Vec<&'a str>,
+    operation: &'a SubgraphFetchOperation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operation_kind: Option<&'a OperationKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    input_rewrites: Option<Vec<ApolloRewrite>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_rewrites: Option<Vec<ApolloRewrite>>,
+}
+
+#[derive(Serialize)]
+struct ApolloNodes<'a> {
+    nodes: Vec<ApolloPlanNode<'a>>,
+}
+
+#[derive(Serialize)]
+struct ApolloFlattenNode<'a> {
+    path: Vec<String>,
+    node: Box<ApolloPlanNode<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApolloConditionNode<'a> {
+    condition: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    if_clause: Option<Box<ApolloPlanNode<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    else_clause: Option<Box<ApolloPlanNode<'a>>>,
+}
+
+#[derive(Serialize)]
+struct ApolloSubscriptionNode<'a> {
+    primary: ApolloFetchNode<'a>,
+}
+
+#[derive(Serialize)]
+struct ApolloDeferNode<'a> {
+    primary: ApolloDeferPrimary<'a>,
+    deferred: Vec<ApolloDeferredNode<'a>>,
+}
+
+#[derive(Serialize)]
+struct ApolloDeferPrimary<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subselection: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node: Option<Box<ApolloPlanNode<'a>>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApolloDeferredNode<'a> {
+    depends: Vec<ApolloDeferDependency<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    query_path: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subselection: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node: Option<Box<ApolloPlanNode<'a>>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApolloDeferDependency<'a> {
+    id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    defer_label: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind")]
+enum ApolloRewrite {
+    ValueSetter(ApolloValueSetter),
+    KeyRenamer(ApolloKeyRenamer),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApolloValueSetter {
+    path: Vec<String>,
+    set_value_to: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApolloKeyRenamer {
+    path: Vec<String>,
+    rename_key_to: String,
+}
+
+impl<'a> From<&'a PlanNode> for ApolloPlanNode<'a> {
+    fn from(node: &'a PlanNode) -> Self {
+        match node {
+            PlanNode::Fetch(fetch) => ApolloPlanNode::Fetch(fetch.into()),
+            PlanNode::BatchFetch(batch_fetch) => ApolloPlanNode::BatchFetch(batch_fetch),
+            PlanNode::Sequence(sequence) => ApolloPlanNode::Sequence(ApolloNodes {
+                nodes: sequence.nodes.iter().map(ApolloPlanNode::from).collect(),
+            }),
+            PlanNode::Parallel(parallel) => ApolloPlanNode::Parallel(ApolloNodes {
+                nodes: parallel.nodes.iter().map(ApolloPlanNode::from).collect(),
+            }),
+            PlanNode::Flatten(flatten) => ApolloPlanNode::Flatten(ApolloFlattenNode {
+                path: apollo_flatten_path(&flatten.path),
+                node: Box::new(flatten.node.as_ref().into()),
+            }),
+            PlanNode::Condition(condition) => ApolloPlanNode::Condition(condition.into()),
+            PlanNode::Subscription(subscription) => {
+                ApolloPlanNode::Subscription(ApolloSubscriptionNode {
+                    primary: (&subscription.primary).into(),
+                })
+            }
+            PlanNode::Defer(defer) => ApolloPlanNode::Defer(defer.into()),
+        }
+    }
+}
+
+impl<'a> From<&'a FetchNode> for ApolloFetchNode<'a> {
+    fn from(fetch: &'a FetchNode) -> Self {
+        ApolloFetchNode {
+            service_name: &fetch.service_name,
+            requires: fetch
+                .requires
+                .as_ref()
+                .map(|requires| requires.items.as_slice())
+                .unwrap_or_default(),
+            variable_usages: fetch
+                .variable_usages
+                .iter()
+                .flatten()
+                .map(String::as_str)
+                .collect(),
+            operation: &fetch.operation,
+            operation_kind: fetch.operation_kind.as_ref(),
+            input_rewrites: fetch.input_rewrites.as_deref().map(apollo_rewrites),
+            output_rewrites: fetch.output_rewrites.as_deref().map(apollo_rewrites),
+        }
+    }
+}
+
+impl<'a> From<&'a ConditionNode> for ApolloConditionNode<'a> {
+    fn from(condition: &'a ConditionNode) -> Self {
+        ApolloConditionNode {
+            condition: &condition.condition,
+            if_clause: condition
+                .if_clause
+                .as_deref()
+                .map(|node| Box::new(node.into())),
+            else_clause: condition
+                .else_clause
+                .as_deref()
+                .map(|node| Box::new(node.into())),
+        }
+    }
+}
+
+impl<'a> From<&'a DeferNode> for ApolloDeferNode<'a> {
+    fn from(defer: &'a DeferNode) -> Self {
+        ApolloDeferNode {
+            primary: ApolloDeferPrimary {
+                subselection: defer.primary.subselection.as_deref(),
+                node: defer
+                    .primary
+                    .node
+                    .as_deref()
+                    .map(|node| Box::new(node.into())),
+            },
+            deferred: defer
+                .deferred
+                .iter()
+                .map(|deferred| ApolloDeferredNode {
+                    depends: deferred
+                        .depends
+                        .iter()
+                        .map(|dependency| ApolloDeferDependency {
+                            id: &dependency.id,
+                            defer_label: dependency.defer_label.as_deref(),
+                        })
+                        .collect(),
+                    label: deferred.label.as_deref(),
+                    query_path: &deferred.query_path,
+                    subselection: deferred.subselection.as_deref(),
+                    node: deferred.node.as_deref().map(|node| Box::new(node.into())),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Apollo attaches type conditions to the previous path element, as in `products.@|[Book,Movie]`.
+fn apollo_flatten_path(path: &FlattenNodePath) -> Vec<String> {
+    let mut elements: Vec<String> = Vec::with_capacity(path.as_slice().len());
+
+    for segment in path.as_slice() {
+        match segment {
+            FlattenNodePathSegment::Field(field_name) => elements.push(field_name.clone()),
+            FlattenNodePathSegment::List => elements.push("@".to_string()),
+            FlattenNodePathSegment::TypeCondition(type_names) => {
+                let condition = format!(
+                    "|[{}]",
+                    type_names
+                        .iter()
+                        .map(String::as_str)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                );
+                match elements.last_mut() {
+                    Some(last) => last.push_str(&condition),
+                    None => elements.push(condition),
+                }
+            }
+        }
+    }
+
+    elements
+}
+
+fn apollo_rewrites(rewrites: &[FetchRewrite]) -> Vec<ApolloRewrite> {
+    rewrites
+        .iter()
+        .map(|rewrite| match rewrite {
+            FetchRewrite::ValueSetter(setter) => ApolloRewrite::ValueSetter(ApolloValueSetter {
+                path: apollo_rewrite_path(&setter.path),
+                set_value_to: setter.set_value_to.clone(),
+            }),
+            FetchRewrite::KeyRenamer(renamer) => ApolloRewrite::KeyRenamer(ApolloKeyRenamer {
+                path: apollo_rewrite_path(&renamer.path),
+                rename_key_to: renamer.rename_key_to.clone(),
+            }),
+        })
+        .collect()
+}
+
+fn apollo_rewrite_path(path: &[FetchNodePathSegment]) -> Vec<String> {
+    path.iter()
+        .map(|segment| match segment {
+            FetchNodePathSegment::Key(key) => key.clone(),
+            FetchNodePathSegment::TypenameEquals(type_names) => format!(
+                "... on {}",
+                type_names
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join("|")
+            ),
+        })
+        .collect()
+}
+
+/// The formats the plan nodes and the fetch graph can be drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagramFormat {
+    /// A Mermaid flowchart
+    Mermaid,
+    /// A Graphviz DOT digraph
+    Dot,
+}
+
+impl FromStr for DiagramFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "mermaid" => Ok(DiagramFormat::Mermaid),
+            "dot" => Ok(DiagramFormat::Dot),
+            other => Err(format!(
+                "unknown diagram format '{other}', expected one of: mermaid, dot"
+            )),
+        }
+    }
+}
+
+/// Writes the nodes and edges of a directed diagram, in the syntax of a [`DiagramFormat`].
+pub(crate) struct Diagram {
+    format: DiagramFormat,
+    output: String,
+    next_id: usize,
+}
+
+impl Diagram {
+    pub(crate) fn new(format: DiagramFormat) -> Self {
+        let output = match format {
+            DiagramFormat::Mermaid => "flowchart TD\n",
+            DiagramFormat::Dot => "digraph {\n",
+        };
+
+        Self {
+            format,
+            output: output.to_string(),
+            next_id: 0,
+        }
+    }
+
+    pub(crate) fn add_node(&mut self, label: impl Display) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        // quotes would end the label
+        let _ = match self.format {
+            DiagramFormat::Mermaid => writeln!(
+                self.output,
+                "  node{id}[\"{}\"]",
+                label.to_string().replace('"', "#quot;")
+            ),
+            DiagramFormat::Dot => writeln!(
+                self.output,
+                "  node{id} [label=\"{}\"]",
+                label.to_string().replace('"', "\\\"")
+            ),
+        };
+        id
+    }
+
+    pub(crate) fn add_edge(&mut self, from: usize, to: usize, label: Option<&str>) {
+        let _ = match (self.format, label) {
+            (DiagramFormat::Mermaid, Some(label)) => {
+                writeln!(self.output, "  node{from} -->|{label}| node{to}")
+            }
+            (DiagramFormat::Mermaid, None) => writeln!(self.output, "  node{from} --> node{to}"),
+            (DiagramFormat::Dot, Some(label)) => {
+                writeln!(self.output, "  node{from} -> node{to} [label=\"{label}\"]")
+            }
+            (DiagramFormat::Dot, None) => writeln!(self.output, "  node{from} -> node{to}"),
+        };
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        if self.format == DiagramFormat::Dot {
+            self.output.push_str("}\n");
+        }
+        self.output
+    }
+
+    fn add_plan_node(&mut self, node: &PlanNode, parent: usize) {
+        self.add_labeled_plan_node(node, parent, None);
+    }
+
+    fn add_labeled_plan_node(&mut self, node: &PlanNode, parent: usize, edge_label: Option<&str>) {
+        let id = match node {
+            PlanNode::Fetch(fetch) => self.add_node(FetchLabel(fetch)),
+            PlanNode::BatchFetch(batch_fetch) => {
+                self.add_node(format!("BatchFetch({})", batch_fetch.service_name))
+            }
+            PlanNode::Sequence(_) => self.add_node("Sequence"),
+            PlanNode::Parallel(_) => self.add_node("Parallel"),
+            PlanNode::Flatten(flatten) => self.add_node(format!("Flatten({})", flatten.path)),
+            PlanNode::Condition(condition) => {
+                self.add_node(format!("Condition(${})", condition.condition))
+            }
+            PlanNode::Subscription(subscription) => self.add_node(format!(
+                "Subscription({})",
+                subscription.primary.service_name
+            )),
+            PlanNode::Defer(_) => self.add_node("Defer"),
+        };
+        self.add_edge(parent, id, edge_label);
+
+        match node {
+            PlanNode::Sequence(sequence) => {
+                for child in &sequence.nodes {
+                    self.add_plan_node(child, id);
+                }
+            }
+            PlanNode::Parallel(parallel) => {
+                for child in &parallel.nodes {
+                    self.add_plan_node(child, id);
+                }
+            }
+            PlanNode::Flatten(flatten) => self.add_plan_node(&flatten.node, id),
+            PlanNode::Condition(condition) => {
+                if let Some(if_clause) = &condition.if_clause {
+                    self.add_labeled_plan_node(if_clause, id, Some("if"));
+                }
+                if let Some(else_clause) = &condition.else_clause {
+                    self.add_labeled_plan_node(else_clause, id, Some("else"));
+                }
+            }
+            PlanNode::Defer(defer) => {
+                if let Some(primary) = &defer.primary.node {
+                    self.add_labeled_plan_node(primary, id, Some("primary"));
+                }
+                for deferred in &defer.deferred {
+                    if let Some(deferred_node) = &deferred.node {
+                        self.add_labeled_plan_node(
+                            deferred_node,
+                            id,
+                            Some(deferred.label.as_deref().unwrap_or("deferred")),
+                        );
+                    }
+                }
+            }
+            PlanNode::Fetch(_) | PlanNode::BatchFetch(_) | PlanNode::Subscription(_) => {}
+        }
+    }
+}
+
+struct FetchLabel<'a>(&'a FetchNode);
+
+impl Display for FetchLabel<'_> {
+    fn fmt(&self, f: &mut FmtFormatter<'_>) -> FmtResult {
+        match &self.0.requires {
+            Some(_) => write!(f, "Fetch({}, entities)", self.0.service_name),
+            None => write!(f, "Fetch({})", self.0.service_name),
+        }
+    }
+}
@@ -120,6 +120,29 @@ pub struct FetchNode {
     pub input_rewrites: Option<Vec<FetchRewrite>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_rewrites: Option<Vec<FetchRewrite>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_arguments: Option<Vec<ContextArgument>>,
+}
+
+/// A variable of an entity fetch, holding a value selected from an ancestor of each entity.
+/// The ancestor exposes the value under a response key equal to the variable name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextArgument {
+    pub variable_name: String,
+    /// Path to the value, starting from the response key in the ancestor.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<String>,
+}
+
+impl Display for ContextArgument {
+    fn fmt(&self, f: &mut FmtFormatter<'_>) -> FmtResult {
+        write!(f, "${}", self.variable_name)?;
+        for key in &self.path {
+            write!(f, ".{}", key)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -722,6 +745,7 @@ impl FetchNode {
                 requires: Some(create_input_selection_set(&step.input)),
                 input_rewrites: step.input_rewrites.clone(),
                 output_rewrites: step.output_rewrites.clone(),
+                context_arguments: step.context_arguments.clone(),
             },
             false => {
                 let root_type_name = supergraph.expect_root_type_name(Some(&step.operation_kind));
@@ -748,6 +772,7 @@ impl FetchNode {
                     requires: None,
                     input_rewrites: step.input_rewrites.clone(),
                     output_rewrites: step.output_rewrites.clone(),
+                    context_arguments: None,
                 }
             }
         }
@@ -861,6 +886,13 @@ impl PrettyDisplay for FetchNode {
             requires.pretty_fmt(f, depth + 2)?;
             writeln!(f, "{indent}  }} =>")?;
         }
+        if let Some(context_arguments) = &self.context_arguments {
+            writeln!(f, "{indent}  context: [")?;
+            for context_argument in context_arguments {
+                writeln!(f, "{indent}    {}", context_argument)?;
+            }
+            writeln!(f, "{indent}  ]")?;
+        }
         self.operation.pretty_fmt(f, depth)?;
         writeln!(f, "{indent}}},")?;
 
@@ -957,6 +989,43 @@ impl PrettyDisplay for ConditionNode {
     }
 }
 
+impl PrettyDisplay for DeferNode {
+    fn pretty_fmt(&self, f: &mut FmtFormatter<'_>, depth: usize) -> FmtResult {
+        let indent = get_indent(depth);
+        writeln!(f, "{indent}Defer {{")?;
+        writeln!(f, "{indent}  Primary {{")?;
+        if let Some(node) = &self.primary.node {
+            node.pretty_fmt(f, depth + 2)?;
+        }
+        writeln!(f, "{indent}  }},")?;
+        for deferred in &self.deferred {
+            write!(
+                f,
+                "{indent}  Deferred(path: \"{}\"",
+                deferred.query_path.join(".")
+            )?;
+            if let Some(label) = &deferred.label {
+                write!(f, ", label: \"{label}\"")?;
+            }
+            if !deferred.depends.is_empty() {
+                let depends: Vec<&str> = deferred
+                    .depends
+                    .iter()
+                    .map(|dependency| dependency.id.as_str())
+                    .collect();
+                write!(f, ", depends: [{}]", depends.join(", "))?;
+            }
+            writeln!(f, ") {{")?;
+            if let Some(node) = &deferred.node {
+                node.pretty_fmt(f, depth + 2)?;
+            }
+            writeln!(f, "{indent}  }},")?;
+        }
+        writeln!(f, "{indent}}},")?;
+        Ok(())
+    }
+}
+
 impl PrettyDisplay for SubscriptionNode {
     fn pretty_fmt(&self, f: &mut FmtFormatter<'_>, depth: usize) -> FmtResult {
         let indent = get_indent(depth);
@@ -977,7 +1046,7 @@ impl PrettyDisplay for PlanNode {
             PlanNode::Parallel(node) => node.pretty_fmt(f, depth),
             PlanNode::Condition(node) => node.pretty_fmt(f, depth),
             PlanNode::Subscription(node) => node.pretty_fmt(f, depth),
-            _ => Ok(()),
+            PlanNode::Defer(node) => node.pretty_fmt(f, depth),
         }
     }
 }
@@ -0,0 +1,104 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter as FmtFormatter, Result as FmtResult},
+};
+
+use crate::planner::plan_nodes::{PlanNode, QueryPlan};
+
+/// The shape of a query plan, to compare plans of an operation across supergraphs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryPlanStats {
+    /// Number of requests sent to subgraphs, counting both branches of conditions
+    pub fetch_count: usize,
+    /// Subgraphs the plan fetches from
+    pub subgraphs: BTreeSet<String>,
+    /// Longest chain of fetches waiting for each other
+    pub sequence_depth: usize,
+}
+
+impl QueryPlan {
+    pub fn stats(&self) -> QueryPlanStats {
+        let mut stats = QueryPlanStats::default();
+        if let Some(node) = &self.node {
+            stats.sequence_depth = collect_stats(node, &mut stats);
+        }
+        stats
+    }
+}
+
+/// Counts the fetches and subgraphs of the node, and returns its sequence depth.
+fn collect_stats(node: &PlanNode, stats: &mut QueryPlanStats) -> usize {
+    match node {
+        PlanNode::Fetch(fetch) => {
+            stats.fetch_count += 1;
+            stats.subgraphs.insert(fetch.service_name.clone());
+            1
+        }
+        PlanNode::BatchFetch(batch_fetch) => {
+            stats.fetch_count += 1;
+            stats.subgraphs.insert(batch_fetch.service_name.clone());
+            1
+        }
+        PlanNode::Subscription(subscription) => {
+            stats.fetch_count += 1;
+            stats
+                .subgraphs
+                .insert(subscription.primary.service_name.clone());
+            1
+        }
+        PlanNode::Sequence(sequence) => sequence
+            .nodes
+            .iter()
+            .map(|child| collect_stats(child, stats))
+            .sum(),
+        PlanNode::Parallel(parallel) => parallel
+            .nodes
+            .iter()
+            .map(|child| collect_stats(child, stats))
+            .max()
+            .unwrap_or(0),
+        PlanNode::Flatten(flatten) => collect_stats(&flatten.node, stats),
+        PlanNode::Condition(condition) => {
+            let if_depth = condition
+                .if_clause
+                .as_ref()
+                .map_or(0, |node| collect_stats(node, stats));
+            let else_depth = condition
+                .else_clause
+                .as_ref()
+                .map_or(0, |node| collect_stats(node, stats));
+            if_depth.max(else_depth)
+        }
+        PlanNode::Defer(defer) => {
+            let primary_depth = defer
+                .primary
+                .node
+                .as_ref()
+                .map_or(0, |node| collect_stats(node, stats));
+            let deferred_depth = defer
+                .deferred
+                .iter()
+                .filter_map(|deferred| deferred.node.as_ref())
+                .map(|node| collect_stats(node, stats))
+                .max()
+                .unwrap_or(0);
+            primary_depth + deferred_depth
+        }
+    }
+}
+
+impl Display for QueryPlanStats {
+    fn fmt(&self, f: &mut FmtFormatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} fetch(es), sequence depth {}, subgraphs [{}]",
+            self.fetch_count,
+            self.sequence_depth,
+            self.subgraphs
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
@@ -18,8 +18,11 @@ use super::{
     plan_nodes::{ParallelNode, QueryPlan, SequenceNode},
 };
 
+mod defer;
 mod optimize;
 
+pub use defer::build_deferred_query_plan_from_fetch_graph;
+
 /// Tracks the in-degree of FetchGraph (DAG) in a dependency graph.
 /// The in-degree of a step is the number of its prerequisite parent steps
 /// that have not yet been processed. A step is "fulfilled" (ready to be processed)
@@ -174,6 +177,19 @@ pub fn build_query_plan_from_fetch_graph(
         }
     }
 
+    let root_node = optimize_plan_sequence(overall_plan_sequence, supergraph)?;
+
+    Ok(QueryPlan {
+        kind: QUERY_PLAN_KIND,
+        node: Some(root_node),
+    })
+}
+
+/// Turns the waves of plan nodes into a single, optimized, plan node.
+fn optimize_plan_sequence(
+    overall_plan_sequence: Vec<PlanNode>,
+    supergraph: &SupergraphState,
+) -> Result<PlanNode, QueryPlanError> {
     // First do light top-level normalization (e.g. flatten nested Sequence wrappers).
     let overall_plan_sequence = optimize_top_level_sequence(overall_plan_sequence);
 
@@ -185,10 +201,5 @@ pub fn build_query_plan_from_fetch_graph(
     };
 
     // Then run full recursive optimization + batching rewrites.
-    let root_node = optimize_root_node(root_node, supergraph)?;
-
-    Ok(QueryPlan {
-        kind: QUERY_PLAN_KIND,
-        node: Some(root_node),
-    })
+    optimize_root_node(root_node, supergraph)
 }
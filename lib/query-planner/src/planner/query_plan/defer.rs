@@ -0,0 +1,347 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef};
+
+use crate::{
+    ast::{
+        merge_path::Segment, normalization::defer::DeferredOperation,
+        safe_merge::INTERNAL_ALIAS_PREFIX, selection_item::SelectionItem,
+        selection_set::SelectionSet,
+    },
+    planner::{
+        error::QueryPlanError,
+        fetch::{
+            fetch_graph::FetchGraph, fetch_step_data::FetchStepData, state::MultiTypeFetchStep,
+        },
+        plan_nodes::{
+            DeferDependency, DeferNode, DeferPrimary, DeferredNode, ParallelNode, PlanNode,
+            QueryPlan,
+        },
+        query_plan::{optimize_plan_sequence, QUERY_PLAN_KIND},
+    },
+    state::supergraph_state::SupergraphState,
+    utils::cancellation::CancellationToken,
+};
+
+/// The group of a fetch step, `0` resolves the primary operation,
+/// and `i + 1` resolves the `i`-th deferred fragment.
+type DeferGroup = usize;
+
+/// Response keys of a deferred operation, with the earliest group selecting them.
+#[derive(Default)]
+struct ResponseKeyTree {
+    group: Option<DeferGroup>,
+    children: HashMap<String, ResponseKeyTree>,
+}
+
+impl ResponseKeyTree {
+    fn new(deferred_operation: &DeferredOperation) -> Self {
+        let mut tree = Self::default();
+        tree.insert(&deferred_operation.primary.selection_set, 0, 0);
+        for (index, fragment) in deferred_operation.fragments.iter().enumerate() {
+            // the fields leading to the fragment are not part of it
+            tree.insert(
+                &fragment.operation.selection_set,
+                fragment.path.len(),
+                index + 1,
+            );
+        }
+        tree
+    }
+
+    /// Records the group of the fields selected at `depth` or deeper.
+    fn insert(&mut self, selection_set: &SelectionSet, depth: usize, group: DeferGroup) {
+        for item in &selection_set.items {
+            match item {
+                SelectionItem::Field(field) => {
+                    let child = self
+                        .children
+                        .entry(field.selection_identifier().to_string())
+                        .or_default();
+                    if depth == 0 {
+                        child.group = Some(child.group.map_or(group, |g| g.min(group)));
+                    }
+                    child.insert(&field.selections, depth.saturating_sub(1), group);
+                }
+                SelectionItem::InlineFragment(fragment) => {
+                    self.insert(&fragment.selections, depth, group)
+                }
+                SelectionItem::FragmentSpread(_) => {}
+            }
+        }
+    }
+
+    /// Finds the earliest group of the leaf fields of a selection set.
+    /// The fields unknown to the operation, like the keys of entities, are not considered.
+    fn leaf_group(&self, selection_set: &SelectionSet) -> Option<DeferGroup> {
+        selection_set
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                SelectionItem::Field(field) => {
+                    let response_key = field.selection_identifier();
+                    if response_key.starts_with(INTERNAL_ALIAS_PREFIX) {
+                        // the response key of a conflicting field is only known after the fetch
+                        return Some(0);
+                    }
+                    let child = self.children.get(response_key)?;
+                    if field.is_leaf() {
+                        child.group
+                    } else {
+                        child.leaf_group(&field.selections)
+                    }
+                }
+                SelectionItem::InlineFragment(fragment) => self.leaf_group(&fragment.selections),
+                SelectionItem::FragmentSpread(_) => None,
+            })
+            .min()
+    }
+
+    fn step_group(&self, step: &FetchStepData<MultiTypeFetchStep>) -> Option<DeferGroup> {
+        let mut node = self;
+        for segment in step.response_path.inner.iter() {
+            if let Segment::Field(field, _, _) = segment {
+                node = node.children.get(field.response_key())?;
+            }
+        }
+
+        step.output
+            .iter()
+            .filter_map(|(_, selection_set)| node.leaf_group(selection_set))
+            .min()
+    }
+}
+
+/// Builds a query plan with a `DeferNode` at its root, for an operation with deferred fragments.
+///
+/// Every fetch step is assigned to the earliest group selecting one of its fields,
+/// or to the earliest group of its children, as a step runs before the steps depending on it.
+/// The steps of the primary group form the primary node, and the steps of every deferred fragment
+/// its deferred node, depending on the steps of the earlier groups.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn build_deferred_query_plan_from_fetch_graph(
+    fetch_graph: FetchGraph<MultiTypeFetchStep>,
+    deferred_operation: &DeferredOperation,
+    supergraph: &SupergraphState,
+    cancellation_token: &CancellationToken,
+) -> Result<QueryPlan, QueryPlanError> {
+    let root_index = fetch_graph.root_index.ok_or(QueryPlanError::NoRoot)?;
+    let tree = ResponseKeyTree::new(deferred_operation);
+
+    let mut steps = Vec::new();
+    let mut own_groups = HashMap::new();
+    fetch_graph.bfs(root_index, |step_index, step_data| {
+        if *step_index != root_index {
+            steps.push(*step_index);
+            own_groups.insert(*step_index, tree.step_group(step_data));
+        }
+        false // never stop traversing
+    });
+    if steps.is_empty() {
+        return Err(QueryPlanError::EmptyPlan);
+    }
+
+    let groups = assign_groups(&fetch_graph, &steps, own_groups, cancellation_token)?;
+
+    let defer_label = |group: DeferGroup| match group {
+        0 => None,
+        group => deferred_operation.fragments[group - 1].label.clone(),
+    };
+
+    let primary = DeferPrimary {
+        subselection: Some(deferred_operation.primary.selection_set.to_string()),
+        node: build_group_node(
+            &fetch_graph,
+            &steps,
+            &groups,
+            0,
+            supergraph,
+            cancellation_token,
+        )?
+        .map(Box::new),
+    };
+
+    let mut deferred = Vec::with_capacity(deferred_operation.fragments.len());
+    for (index, fragment) in deferred_operation.fragments.iter().enumerate() {
+        let group = index + 1;
+
+        let mut depends = BTreeMap::new();
+        for step_index in steps.iter().filter(|step| groups[*step] == group) {
+            for parent_edge in fetch_graph.parents_of(*step_index) {
+                let parent_index = parent_edge.source();
+                if parent_index == root_index || groups[&parent_index] >= group {
+                    continue;
+                }
+                let parent = fetch_graph.get_step_data(parent_index)?;
+                depends.insert(parent.id, groups[&parent_index]);
+            }
+        }
+
+        deferred.push(DeferredNode {
+            depends: depends
+                .into_iter()
+                .map(|(id, group)| DeferDependency {
+                    id: id.to_string(),
+                    defer_label: defer_label(group),
+                })
+                .collect(),
+            label: fragment.label.clone(),
+            query_path: fragment.path.clone(),
+            subselection: selection_set_at_path(&fragment.operation.selection_set, &fragment.path)
+                .map(|selection_set| selection_set.to_string()),
+            node: build_group_node(
+                &fetch_graph,
+                &steps,
+                &groups,
+                group,
+                supergraph,
+                cancellation_token,
+            )?
+            .map(Box::new),
+        });
+    }
+
+    Ok(QueryPlan {
+        kind: QUERY_PLAN_KIND,
+        node: Some(PlanNode::Defer(DeferNode { primary, deferred })),
+    })
+}
+
+/// Assigns every step to the earliest of its own group and the groups of its children,
+/// the steps without fields of their own, nor children, are part of the primary group.
+fn assign_groups(
+    fetch_graph: &FetchGraph<MultiTypeFetchStep>,
+    steps: &[NodeIndex],
+    own_groups: HashMap<NodeIndex, Option<DeferGroup>>,
+    cancellation_token: &CancellationToken,
+) -> Result<HashMap<NodeIndex, DeferGroup>, QueryPlanError> {
+    let mut groups = own_groups;
+
+    loop {
+        cancellation_token.bail_if_cancelled()?;
+        let mut changed = false;
+
+        for step_index in steps {
+            let children_group = fetch_graph
+                .children_of(*step_index)
+                .filter_map(|child_edge| groups.get(&child_edge.target()).copied().flatten())
+                .min();
+            let current = groups.get(step_index).copied().flatten();
+            let next = match (current, children_group) {
+                (Some(current), Some(children_group)) => Some(current.min(children_group)),
+                (current, children_group) => current.or(children_group),
+            };
+
+            if next != current {
+                groups.insert(*step_index, next);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|(step_index, group)| (step_index, group.unwrap_or(0)))
+        .collect())
+}
+
+/// Plans the steps of a group in waves, like `build_query_plan_from_fetch_graph`,
+/// only the parents from the same group are awaited.
+fn build_group_node(
+    fetch_graph: &FetchGraph<MultiTypeFetchStep>,
+    steps: &[NodeIndex],
+    groups: &HashMap<NodeIndex, DeferGroup>,
+    group: DeferGroup,
+    supergraph: &SupergraphState,
+    cancellation_token: &CancellationToken,
+) -> Result<Option<PlanNode>, QueryPlanError> {
+    let is_in_group = |step_index: &NodeIndex| groups.get(step_index) == Some(&group);
+
+    let mut in_degrees: HashMap<NodeIndex, usize> = steps
+        .iter()
+        .filter(|step_index| is_in_group(step_index))
+        .map(|step_index| {
+            let in_degree = fetch_graph
+                .parents_of(*step_index)
+                .filter(|edge| is_in_group(&edge.source()))
+                .count();
+            (*step_index, in_degree)
+        })
+        .collect();
+    let group_size = in_degrees.len();
+
+    let mut queue: VecDeque<NodeIndex> = steps
+        .iter()
+        .filter(|step_index| in_degrees.get(step_index) == Some(&0))
+        .copied()
+        .collect();
+
+    let mut planned_nodes_count = 0;
+    let mut overall_plan_sequence: Vec<PlanNode> = Vec::new();
+
+    while !queue.is_empty() {
+        let mut current_wave_nodes: Vec<PlanNode> = Vec::new();
+
+        for _ in 0..queue.len() {
+            let Some(step_index) = queue.pop_front() else {
+                break;
+            };
+
+            let step_data = fetch_graph.get_step_data(step_index)?;
+            current_wave_nodes.push(PlanNode::from_fetch_step(step_data, supergraph));
+            planned_nodes_count += 1;
+
+            for child_edge in fetch_graph.children_of(step_index) {
+                cancellation_token.bail_if_cancelled()?;
+                if let Some(in_degree) = in_degrees.get_mut(&child_edge.target()) {
+                    *in_degree -= 1;
+                    if *in_degree == 0 {
+                        queue.push_back(child_edge.target());
+                    }
+                }
+            }
+        }
+
+        if current_wave_nodes.len() == 1 {
+            overall_plan_sequence.extend(current_wave_nodes);
+        } else {
+            overall_plan_sequence.push(PlanNode::Parallel(ParallelNode {
+                nodes: current_wave_nodes,
+            }));
+        }
+    }
+
+    if planned_nodes_count != group_size {
+        return Err(QueryPlanError::Internal("Cycle detected".to_string()));
+    }
+
+    if overall_plan_sequence.is_empty() {
+        return Ok(None);
+    }
+
+    optimize_plan_sequence(overall_plan_sequence, supergraph).map(Some)
+}
+
+/// Finds the selection set of the fields at the end of a path of response keys.
+fn selection_set_at_path<'a>(
+    selection_set: &'a SelectionSet,
+    path: &[String],
+) -> Option<&'a SelectionSet> {
+    let Some((response_key, rest)) = path.split_first() else {
+        return Some(selection_set);
+    };
+
+    selection_set.items.iter().find_map(|item| match item {
+        SelectionItem::Field(field) if field.selection_identifier() == response_key => {
+            selection_set_at_path(&field.selections, rest)
+        }
+        SelectionItem::InlineFragment(fragment) => {
+            selection_set_at_path(&fragment.selections, path)
+        }
+        _ => None,
+    })
+}
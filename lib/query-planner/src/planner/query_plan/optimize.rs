@@ -342,6 +342,11 @@ impl EntityFetch {
             return Ok(None);
         };
 
+        // Context values are collected per entity, and may split the call into multiple requests
+        if fetch_node.context_arguments.is_some() {
+            return Ok(None);
+        }
+
         let Some(entities_field) = fetch_node
             .operation
             .document
@@ -2270,6 +2275,7 @@ mod tests {
             requires: Some(requires),
             input_rewrites: None,
             output_rewrites: None,
+            context_arguments: None,
         };
 
         let path = FlattenNodePath::from(&MergePath::new(vec![
@@ -2337,6 +2343,7 @@ mod tests {
             requires: None,
             input_rewrites: None,
             output_rewrites: None,
+            context_arguments: None,
         }
     }
 
@@ -335,7 +335,7 @@ fn process_inline_fragment<'graph, 'op: 'graph>(
         }
 
         if fields_to_resolve_locally.is_empty() {
-            let mut indirect_paths = find_indirect_paths(
+            let indirect_paths = find_indirect_paths(
                 graph,
                 supergraph,
                 override_context,
@@ -345,12 +345,15 @@ fn process_inline_fragment<'graph, 'op: 'graph>(
                 cancellation_token,
             )?;
 
-            if !indirect_paths.is_empty() {
+            // The paths are ordered by subgraph name, so the cheapest one is picked,
+            // instead of a detour through another @interfaceObject subgraph.
+            let has_indirect_paths = !indirect_paths.is_empty();
+            if let Some(indirect_path) = find_best_paths(indirect_paths).into_iter().next() {
                 trace!("advanced: {}", path.pretty_print(graph));
-                next_paths.push(indirect_paths.remove(0));
+                next_paths.push(indirect_path);
             }
 
-            if indirect_paths.is_empty() && direct_paths.is_empty() {
+            if !has_indirect_paths && direct_paths.is_empty() {
                 // Looks like a union member or an interface implementation is not resolvable.
                 // The fact the fragment for that object type passed GraphQL validations,
                 // means that it's a child of the abstract type,
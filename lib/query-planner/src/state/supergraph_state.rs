@@ -12,7 +12,7 @@ use crate::{
     federation_spec::{
         demand_control::{CostDirective, ListSizeDirective},
         directives::{
-            AuthenticatedDirective, FederationDirective, InaccessibleDirective,
+            AuthenticatedDirective, ContextDirective, FederationDirective, InaccessibleDirective,
             JoinEnumValueDirective, JoinFieldDirective, JoinGraphDirective,
             JoinImplementsDirective, JoinTypeDirective, JoinUnionMemberDirective,
             RequiresScopesDirective,
@@ -188,6 +188,8 @@ pub struct SupergraphState {
     pub interface_to_object_types: InterfaceToObjectTypesMap,
     /// A pre-computed set of all progressive override labels in the supergraph
     pub progressive_overrides: ProgressiveOverrides,
+    /// A map of context names (set with @context) to the types setting them
+    pub contexts: HashMap<String, BTreeSet<String>>,
 }
 
 impl SupergraphState {
@@ -201,12 +203,14 @@ impl SupergraphState {
             Self::create_interface_object_in_subgraph(&definitions);
         let interface_to_object_types = Self::create_interface_to_object_types(&definitions);
         let progressive_overrides = Self::extract_progressive_overrides(&definitions);
+        let contexts = Self::extract_contexts(schema);
 
         let mut instance = Self {
             definitions,
             interface_object_types_in_subgraphs,
             interface_to_object_types,
             progressive_overrides,
+            contexts,
             known_subgraphs,
             subgraph_endpoint_map,
             known_scalars: Self::extract_known_scalars(schema),
@@ -289,6 +293,50 @@ impl SupergraphState {
         Some(field_definition.field_type.inner_type())
     }
 
+    /// Types that can be an ancestor providing the value of the given context.
+    pub fn is_context_type(&self, context_name: &str, type_name: &str) -> bool {
+        self.contexts.get(context_name).is_some_and(|types| {
+            types.iter().any(|context_type| {
+                context_type == type_name
+                    || self
+                        .interface_members(context_type)
+                        .is_some_and(|members| members.contains(type_name))
+                    || matches!(
+                        self.definitions.get(context_type),
+                        Some(SupergraphDefinition::Union(union_type)) if union_type.types.iter().any(|member| member == type_name)
+                    )
+            })
+        })
+    }
+
+    fn extract_contexts(schema: &SchemaDocument) -> HashMap<String, BTreeSet<String>> {
+        let mut contexts: HashMap<String, BTreeSet<String>> = HashMap::new();
+
+        for definition in &schema.definitions {
+            let (type_name, directives) = match definition {
+                input::Definition::TypeDefinition(input::TypeDefinition::Object(t)) => {
+                    (&t.name, &t.directives)
+                }
+                input::Definition::TypeDefinition(input::TypeDefinition::Interface(t)) => {
+                    (&t.name, &t.directives)
+                }
+                input::Definition::TypeDefinition(input::TypeDefinition::Union(t)) => {
+                    (&t.name, &t.directives)
+                }
+                _ => continue,
+            };
+
+            for context in Self::extract_directives::<ContextDirective>(directives) {
+                contexts
+                    .entry(context.name)
+                    .or_default()
+                    .insert(type_name.to_string());
+            }
+        }
+
+        contexts
+    }
+
     fn create_interface_object_in_subgraph(
         definitions: &DefinitionMap,
     ) -> InterfaceObjectToSubgraphsMap {
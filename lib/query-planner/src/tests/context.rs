@@ -0,0 +1,76 @@
+use crate::{
+    tests::testkit::{build_query_plan_with_defaults, init_logger},
+    utils::parsing::parse_operation,
+};
+use std::error::Error;
+
+// `User` sets the `userContext` in the `payments` subgraph,
+// and `Transaction.amount` receives the `currency` of the user with @fromContext.
+const SUPERGRAPH: &str = "fixture/tests/context.supergraph.graphql";
+
+/// The value is selected from the closest ancestor setting the context,
+/// and passed to the entity call resolving the field as a variable.
+#[test]
+fn contextual_argument_from_ancestor() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          me {
+            name
+            transactions {
+              id
+              amount
+            }
+          }
+        }
+        "#,
+    );
+    let query_plan = build_query_plan_with_defaults(SUPERGRAPH, document)?;
+    let plan_str = format!("{}", query_plan);
+
+    assert!(
+        plan_str.contains("contextualArgument_0: currency"),
+        "the ancestor must select the context value: {plan_str}"
+    );
+    assert!(
+        plan_str.contains("amount(currency: $contextualArgument_0)"),
+        "the field must receive the context value: {plan_str}"
+    );
+    assert!(
+        plan_str.contains("$contextualArgument_0:String!"),
+        "the entity call must define the variable: {plan_str}"
+    );
+    assert!(
+        plan_str.contains("context: [\n"),
+        "the entity call must list the contextual arguments: {plan_str}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn no_contextual_arguments_without_fields_using_them() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          me {
+            name
+            transactions {
+              id
+            }
+          }
+        }
+        "#,
+    );
+    let query_plan = build_query_plan_with_defaults(SUPERGRAPH, document)?;
+    let plan_str = format!("{}", query_plan);
+
+    assert!(
+        !plan_str.contains("contextualArgument"),
+        "no context value should be selected: {plan_str}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,268 @@
+use crate::{
+    tests::testkit::{build_deferred_query_plan, init_logger},
+    utils::parsing::parse_operation,
+};
+use std::error::Error;
+
+#[test]
+fn defer_entity_fields_of_another_subgraph() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          topProducts {
+            name
+            ... @defer(label: "reviews") {
+              reviews {
+                body
+              }
+            }
+          }
+        }
+      "#,
+    );
+    let query_plan = build_deferred_query_plan("../../bench/supergraph.graphql", document)?;
+
+    insta::assert_snapshot!(format!("{}", query_plan), @r#"
+    QueryPlan {
+      Defer {
+        Primary {
+          Fetch(service: "products") {
+            {
+              topProducts {
+                __typename
+                name
+                upc
+              }
+            }
+          },
+        },
+        Deferred(path: "topProducts", label: "reviews", depends: [2]) {
+          Flatten(path: "topProducts.@") {
+            Fetch(service: "reviews") {
+              {
+                ... on Product {
+                  __typename
+                  upc
+                }
+              } =>
+              {
+                ... on Product {
+                  reviews {
+                    body
+                  }
+                }
+              }
+            },
+          },
+        },
+      },
+    },
+    "#);
+
+    Ok(())
+}
+
+#[test]
+fn defer_root_field() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          me {
+            name
+          }
+          ... @defer {
+            topProducts {
+              name
+            }
+          }
+        }
+      "#,
+    );
+    let query_plan = build_deferred_query_plan("../../bench/supergraph.graphql", document)?;
+
+    insta::assert_snapshot!(format!("{}", query_plan), @r#"
+    QueryPlan {
+      Defer {
+        Primary {
+          Fetch(service: "accounts") {
+            {
+              me {
+                name
+              }
+            }
+          },
+        },
+        Deferred(path: "") {
+          Fetch(service: "products") {
+            {
+              topProducts {
+                name
+              }
+            }
+          },
+        },
+      },
+    },
+    "#);
+
+    Ok(())
+}
+
+#[test]
+fn defer_fields_resolved_by_the_primary_fetch() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          topProducts {
+            name
+            ... @defer(label: "price") {
+              price
+            }
+          }
+        }
+      "#,
+    );
+    let query_plan = build_deferred_query_plan("../../bench/supergraph.graphql", document)?;
+
+    insta::assert_snapshot!(format!("{}", query_plan), @r#"
+    QueryPlan {
+      Defer {
+        Primary {
+          Fetch(service: "products") {
+            {
+              topProducts {
+                name
+                price
+              }
+            }
+          },
+        },
+        Deferred(path: "topProducts", label: "price") {
+        },
+      },
+    },
+    "#);
+
+    Ok(())
+}
+
+#[test]
+fn defer_nested_fragments() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          me {
+            name
+            ... @defer(label: "reviews") {
+              reviews {
+                body
+                product {
+                  upc
+                  ... @defer(label: "stock") {
+                    inStock
+                  }
+                }
+              }
+            }
+          }
+          ... @defer(label: "top") {
+            topProducts {
+              inStock
+            }
+          }
+        }
+      "#,
+    );
+    let query_plan = build_deferred_query_plan("../../bench/supergraph.graphql", document)?;
+
+    insta::assert_snapshot!(format!("{}", query_plan), @r#"
+    QueryPlan {
+      Defer {
+        Primary {
+          Fetch(service: "accounts") {
+            {
+              me {
+                __typename
+                name
+                id
+              }
+            }
+          },
+        },
+        Deferred(path: "me", label: "reviews", depends: [2]) {
+          Sequence {
+            Flatten(path: "me") {
+              Fetch(service: "reviews") {
+                {
+                  ... on User {
+                    __typename
+                    id
+                  }
+                } =>
+                {
+                  ... on User {
+                    reviews {
+                      body
+                      product {
+                        __typename
+                        upc
+                      }
+                    }
+                  }
+                }
+              },
+            },
+            Flatten(path: "me.reviews.@.product") {
+              Fetch(service: "inventory") {
+                {
+                  ... on Product {
+                    __typename
+                    upc
+                  }
+                } =>
+                {
+                  ... on Product {
+                    inStock
+                  }
+                }
+              },
+            },
+          },
+        },
+        Deferred(path: "", label: "top") {
+          Sequence {
+            Fetch(service: "products") {
+              {
+                topProducts {
+                  __typename
+                  upc
+                }
+              }
+            },
+            Flatten(path: "topProducts.@") {
+              Fetch(service: "inventory") {
+                {
+                  ... on Product {
+                    __typename
+                    upc
+                  }
+                } =>
+                {
+                  ... on Product {
+                    inStock
+                  }
+                }
+              },
+            },
+          },
+        },
+      },
+    },
+    "#);
+
+    Ok(())
+}
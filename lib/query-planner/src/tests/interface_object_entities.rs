@@ -0,0 +1,412 @@
+use crate::{
+    tests::testkit::{build_query_plan_with_defaults, init_logger},
+    utils::parsing::parse_operation,
+};
+use std::error::Error;
+
+// `Media` is an entity interface of the `media` subgraph,
+// that `reviews` and `inventory` subgraphs model as an object with @interfaceObject.
+const SUPERGRAPH: &str = "fixture/tests/interface-object-entities.supergraph.graphql";
+
+/// Fields of the interface objects are resolved with entity calls on the interface,
+/// for all the implementations at once.
+#[test]
+fn entity_interface_fields_from_interface_objects() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          media {
+            id
+            title
+            inStock
+            reviews {
+              score
+            }
+          }
+        }
+        "#,
+    );
+    let query_plan = build_query_plan_with_defaults(SUPERGRAPH, document)?;
+
+    insta::assert_snapshot!(format!("{}", query_plan), @r#"
+    QueryPlan {
+      Sequence {
+        Fetch(service: "media") {
+          {
+            media {
+              __typename
+              id
+              title
+            }
+          }
+        },
+        Parallel {
+          Flatten(path: "media.@") {
+            Fetch(service: "reviews") {
+              {
+                ... on Media {
+                  __typename
+                  id
+                }
+              } =>
+              {
+                ... on Media {
+                  reviews {
+                    score
+                  }
+                }
+              }
+            },
+          },
+          Flatten(path: "media.@") {
+            Fetch(service: "inventory") {
+              {
+                ... on Media {
+                  __typename
+                  id
+                }
+              } =>
+              {
+                ... on Media {
+                  inStock
+                }
+              }
+            },
+          },
+        },
+      },
+    },
+    "#);
+
+    Ok(())
+}
+
+/// The root field of an interface object subgraph doesn't know the concrete types,
+/// so their `__typename` is resolved by the subgraph defining the interface.
+#[test]
+fn typename_from_interface_object_root_field() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          topRatedMedia {
+            __typename
+            id
+            reviews {
+              score
+            }
+          }
+        }
+        "#,
+    );
+    let query_plan = build_query_plan_with_defaults(SUPERGRAPH, document)?;
+
+    insta::assert_snapshot!(format!("{}", query_plan), @r#"
+    QueryPlan {
+      Sequence {
+        Fetch(service: "reviews") {
+          {
+            topRatedMedia {
+              __typename
+              id
+              reviews {
+                score
+              }
+            }
+          }
+        },
+        Flatten(path: "topRatedMedia.@") {
+          Fetch(service: "media") {
+            {
+              ... on Media {
+                __typename
+                id
+              }
+            } =>
+            {
+              ... on Media {
+                __typename
+              }
+            }
+          },
+        },
+      },
+    },
+    "#);
+
+    Ok(())
+}
+
+/// Fragments on the implementations are resolved by the subgraph defining the interface,
+/// after the `__typename` of the entities is known.
+#[test]
+fn inline_fragments_from_interface_object_root_field() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          topRatedMedia {
+            title
+            inStock
+            ... on Book {
+              author
+            }
+            ... on Movie {
+              director
+            }
+          }
+        }
+        "#,
+    );
+    let query_plan = build_query_plan_with_defaults(SUPERGRAPH, document)?;
+
+    insta::assert_snapshot!(format!("{}", query_plan), @r#"
+    QueryPlan {
+      Sequence {
+        Fetch(service: "reviews") {
+          {
+            topRatedMedia {
+              __typename
+              id
+            }
+          }
+        },
+        Parallel {
+          Flatten(path: "topRatedMedia.@") {
+            Fetch(service: "inventory") {
+              {
+                ... on Media {
+                  __typename
+                  id
+                }
+              } =>
+              {
+                ... on Media {
+                  inStock
+                }
+              }
+            },
+          },
+          Flatten(path: "topRatedMedia.@") {
+            Fetch(service: "media") {
+              {
+                ... on Media {
+                  __typename
+                  id
+                }
+              } =>
+              {
+                ... on Media {
+                  title
+                  __typename
+                  ... on Book {
+                    author
+                  }
+                  ... on Movie {
+                    director
+                  }
+                }
+              }
+            },
+          },
+        },
+      },
+    },
+    "#);
+
+    Ok(())
+}
+
+/// Interface object fields selected on an implementation are resolved with an entity call
+/// on the interface, with the `__typename` of the representations rewritten to the interface.
+#[test]
+fn interface_object_fields_on_implementation() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          book(id: "1") {
+            title
+            author
+            inStock
+            reviews {
+              score
+              body
+            }
+          }
+        }
+        "#,
+    );
+    let query_plan = build_query_plan_with_defaults(SUPERGRAPH, document)?;
+
+    insta::assert_snapshot!(format!("{}", query_plan), @r#"
+    QueryPlan {
+      Sequence {
+        Fetch(service: "media") {
+          {
+            book(id: "1") {
+              __typename
+              title
+              author
+              id
+            }
+          }
+        },
+        Parallel {
+          Flatten(path: "book") {
+            Fetch(service: "reviews") {
+              {
+                ... on Book {
+                  __typename
+                  id
+                }
+              } =>
+              {
+                ... on Media {
+                  reviews {
+                    score
+                    body
+                  }
+                }
+              }
+            },
+          },
+          Flatten(path: "book") {
+            Fetch(service: "inventory") {
+              {
+                ... on Book {
+                  __typename
+                  id
+                }
+              } =>
+              {
+                ... on Media {
+                  inStock
+                }
+              }
+            },
+          },
+        },
+      },
+    },
+    "#);
+
+    insta::assert_snapshot!(format!("{}", sonic_rs::to_string_pretty(&query_plan).unwrap_or_default()), @r#"
+    {
+      "kind": "QueryPlan",
+      "node": {
+        "kind": "Sequence",
+        "nodes": [
+          {
+            "kind": "Fetch",
+            "serviceName": "media",
+            "operationKind": "query",
+            "operation": "{book(id: \"1\"){__typename title author id}}"
+          },
+          {
+            "kind": "Parallel",
+            "nodes": [
+              {
+                "kind": "Flatten",
+                "path": [
+                  {
+                    "Field": "book"
+                  }
+                ],
+                "node": {
+                  "kind": "Fetch",
+                  "serviceName": "reviews",
+                  "operationKind": "query",
+                  "operation": "query($representations:[_Any!]!){_entities(representations: $representations){...on Media{reviews{score body}}}}",
+                  "requires": [
+                    {
+                      "kind": "InlineFragment",
+                      "typeCondition": "Book",
+                      "selections": [
+                        {
+                          "kind": "Field",
+                          "name": "__typename"
+                        },
+                        {
+                          "kind": "Field",
+                          "name": "id"
+                        }
+                      ]
+                    }
+                  ],
+                  "inputRewrites": [
+                    {
+                      "ValueSetter": {
+                        "path": [
+                          {
+                            "TypenameEquals": [
+                              "Media"
+                            ]
+                          },
+                          {
+                            "Key": "__typename"
+                          }
+                        ],
+                        "setValueTo": "Media"
+                      }
+                    }
+                  ]
+                }
+              },
+              {
+                "kind": "Flatten",
+                "path": [
+                  {
+                    "Field": "book"
+                  }
+                ],
+                "node": {
+                  "kind": "Fetch",
+                  "serviceName": "inventory",
+                  "operationKind": "query",
+                  "operation": "query($representations:[_Any!]!){_entities(representations: $representations){...on Media{inStock}}}",
+                  "requires": [
+                    {
+                      "kind": "InlineFragment",
+                      "typeCondition": "Book",
+                      "selections": [
+                        {
+                          "kind": "Field",
+                          "name": "__typename"
+                        },
+                        {
+                          "kind": "Field",
+                          "name": "id"
+                        }
+                      ]
+                    }
+                  ],
+                  "inputRewrites": [
+                    {
+                      "ValueSetter": {
+                        "path": [
+                          {
+                            "TypenameEquals": [
+                              "Media"
+                            ]
+                          },
+                          {
+                            "Key": "__typename"
+                          }
+                        ],
+                        "setValueTo": "Media"
+                      }
+                    }
+                  ]
+                }
+              }
+            ]
+          }
+        ]
+      }
+    }
+    "#);
+
+    Ok(())
+}
@@ -1,15 +1,20 @@
 mod alias;
 mod arguments;
+mod context;
+mod defer;
 mod fragments;
 mod include_skip;
 mod interface;
 mod interface_object;
+mod interface_object_entities;
 mod interface_object_with_requires;
 mod issues;
 mod mutations;
 mod object_entities;
 mod override_requires;
 mod overrides;
+mod plan_format;
+mod plan_stats;
 mod provides;
 mod renamed_root_types;
 mod requires;
@@ -1,9 +1,9 @@
 use crate::{
-    graph::PlannerOverrideContext,
+    graph::{OverrideLabel, PlannerOverrideContext, PERCENTAGE_SCALE_FACTOR},
     tests::testkit::{build_query_plan, build_query_plan_with_defaults, init_logger},
     utils::parsing::parse_operation,
 };
-use std::error::Error;
+use std::{collections::HashMap, error::Error};
 
 #[test]
 fn single_simple_overrides() -> Result<(), Box<dyn Error>> {
@@ -382,3 +382,72 @@ fn progressive_override_flag_test() -> Result<(), Box<dyn Error>> {
     "#);
     Ok(())
 }
+
+#[test]
+fn progressive_override_forced_percentage_test() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+          aFeed {
+            createdAt
+          }
+          bFeed {
+            createdAt
+          }
+        }
+        "#,
+    );
+    let percentage = OverrideLabel::percentage_from_label("percent(75)").unwrap();
+    assert_eq!(percentage, 75 * PERCENTAGE_SCALE_FACTOR);
+
+    let in_range_plan = build_query_plan(
+        "fixture/tests/simple-progressive-overrides.supergraph.graphql",
+        document.clone(),
+        PlannerOverrideContext::from_percentage(50.0),
+        Default::default(),
+    )?;
+    let out_of_range_plan = build_query_plan(
+        "fixture/tests/simple-progressive-overrides.supergraph.graphql",
+        document.clone(),
+        PlannerOverrideContext::from_percentage(90.0),
+        Default::default(),
+    )?;
+
+    // forced on, even though 90 is out of the range of the label
+    let query_plan = build_query_plan(
+        "fixture/tests/simple-progressive-overrides.supergraph.graphql",
+        document.clone(),
+        PlannerOverrideContext::from_percentage(90.0)
+            .with_forced_percentages(HashMap::from([(percentage, true)])),
+        Default::default(),
+    )?;
+    assert_eq!(format!("{}", query_plan), format!("{}", in_range_plan));
+
+    // forced off, even though 50 is in the range of the label
+    let query_plan = build_query_plan(
+        "fixture/tests/simple-progressive-overrides.supergraph.graphql",
+        document,
+        PlannerOverrideContext::from_percentage(50.0)
+            .with_forced_percentages(HashMap::from([(percentage, false)])),
+        Default::default(),
+    )?;
+    assert_eq!(format!("{}", query_plan), format!("{}", out_of_range_plan));
+
+    Ok(())
+}
+
+#[test]
+fn progressive_override_request_key_test() {
+    let percentage = PlannerOverrideContext::percentage_from_request_key(b"user-1");
+
+    assert!(percentage < 100 * PERCENTAGE_SCALE_FACTOR);
+    assert_eq!(
+        percentage,
+        PlannerOverrideContext::percentage_from_request_key(b"user-1")
+    );
+    assert_ne!(
+        percentage,
+        PlannerOverrideContext::percentage_from_request_key(b"user-2")
+    );
+}
@@ -0,0 +1,187 @@
+use crate::{
+    planner::plan_format::DiagramFormat,
+    tests::testkit::{build_query_plan_with_defaults, init_logger},
+    utils::parsing::parse_operation,
+};
+use std::error::Error;
+
+#[test]
+fn apollo_mermaid_and_dot_formats() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+            query {
+              products {
+                price {
+                  amount
+                  currency
+                }
+                isAvailable
+              }
+            }"#,
+    );
+    let query_plan =
+        build_query_plan_with_defaults("fixture/tests/testing.supergraph.graphql", document)?;
+
+    insta::assert_snapshot!(format!("{}", sonic_rs::to_string_pretty(&query_plan.to_apollo()).unwrap_or_default()), @r#"
+    {
+      "kind": "QueryPlan",
+      "node": {
+        "kind": "Sequence",
+        "nodes": [
+          {
+            "kind": "Fetch",
+            "serviceName": "store",
+            "requires": [],
+            "variableUsages": [],
+            "operation": "{products{__typename id}}",
+            "operationKind": "query"
+          },
+          {
+            "kind": "Flatten",
+            "path": [
+              "products"
+            ],
+            "node": {
+              "kind": "Fetch",
+              "serviceName": "info",
+              "requires": [
+                {
+                  "kind": "InlineFragment",
+                  "typeCondition": "Product",
+                  "selections": [
+                    {
+                      "kind": "Field",
+                      "name": "__typename"
+                    },
+                    {
+                      "kind": "Field",
+                      "name": "id"
+                    }
+                  ]
+                }
+              ],
+              "variableUsages": [],
+              "operation": "query($representations:[_Any!]!){_entities(representations: $representations){...on Product{isAvailable uuid}}}",
+              "operationKind": "query"
+            }
+          },
+          {
+            "kind": "Flatten",
+            "path": [
+              "products"
+            ],
+            "node": {
+              "kind": "Fetch",
+              "serviceName": "cost",
+              "requires": [
+                {
+                  "kind": "InlineFragment",
+                  "typeCondition": "Product",
+                  "selections": [
+                    {
+                      "kind": "Field",
+                      "name": "__typename"
+                    },
+                    {
+                      "kind": "Field",
+                      "name": "uuid"
+                    }
+                  ]
+                }
+              ],
+              "variableUsages": [],
+              "operation": "query($representations:[_Any!]!){_entities(representations: $representations){...on Product{price{amount currency}}}}",
+              "operationKind": "query"
+            }
+          }
+        ]
+      }
+    }
+    "#);
+
+    insta::assert_snapshot!(query_plan.to_mermaid(), @r#"
+    flowchart TD
+      node0["QueryPlan"]
+      node1["Sequence"]
+      node0 --> node1
+      node2["Fetch(store)"]
+      node1 --> node2
+      node3["Flatten(products)"]
+      node1 --> node3
+      node4["Fetch(info, entities)"]
+      node3 --> node4
+      node5["Flatten(products)"]
+      node1 --> node5
+      node6["Fetch(cost, entities)"]
+      node5 --> node6
+    "#);
+
+    insta::assert_snapshot!(query_plan.to_diagram(DiagramFormat::Dot), @r#"
+    digraph {
+      node0 [label="QueryPlan"]
+      node1 [label="Sequence"]
+      node0 -> node1
+      node2 [label="Fetch(store)"]
+      node1 -> node2
+      node3 [label="Flatten(products)"]
+      node1 -> node3
+      node4 [label="Fetch(info, entities)"]
+      node3 -> node4
+      node5 [label="Flatten(products)"]
+      node1 -> node5
+      node6 [label="Fetch(cost, entities)"]
+      node5 -> node6
+    }
+    "#);
+
+    Ok(())
+}
+
+#[test]
+fn apollo_format_of_rewrites() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+        query {
+            i {
+                ... on TypeA {
+                    strField # String
+                }
+                ... on TypeB {
+                    strField # [String]
+                }
+            }
+        }
+"#,
+    );
+    let query_plan =
+        build_query_plan_with_defaults("fixture/tests/mismatch-mix.supergraph.graphql", document)?;
+
+    insta::assert_snapshot!(format!("{}", sonic_rs::to_string_pretty(&query_plan.to_apollo()).unwrap_or_default()), @r#"
+    {
+      "kind": "QueryPlan",
+      "node": {
+        "kind": "Fetch",
+        "serviceName": "a",
+        "requires": [],
+        "variableUsages": [],
+        "operation": "{i{__typename ...on TypeA{strField} ...on TypeB{_internal_qp_alias_0: strField}}}",
+        "operationKind": "query",
+        "outputRewrites": [
+          {
+            "kind": "KeyRenamer",
+            "path": [
+              "i",
+              "... on TypeB",
+              "_internal_qp_alias_0"
+            ],
+            "renameKeyTo": "strField"
+          }
+        ]
+      }
+    }
+    "#);
+
+    Ok(())
+}
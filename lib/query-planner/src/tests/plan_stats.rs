@@ -0,0 +1,45 @@
+use crate::{
+    tests::testkit::{build_query_plan_with_defaults, init_logger},
+    utils::parsing::parse_operation,
+};
+use std::error::Error;
+
+#[test]
+fn stats_of_a_sequence() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        r#"
+            query {
+              products {
+                price {
+                  amount
+                  currency
+                }
+                isAvailable
+              }
+            }"#,
+    );
+    let query_plan =
+        build_query_plan_with_defaults("fixture/tests/testing.supergraph.graphql", document)?;
+    let stats = query_plan.stats();
+
+    assert_eq!(stats.fetch_count, 3);
+    assert_eq!(stats.sequence_depth, 3);
+    insta::assert_snapshot!(stats, @"3 fetch(es), sequence depth 3, subgraphs [cost, info, store]");
+
+    Ok(())
+}
+
+#[test]
+fn stats_of_parallel_fetches() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    let document = parse_operation(
+        &std::fs::read_to_string("../../bench/operation.graphql")
+            .expect("Unable to read input file"),
+    );
+    let query_plan = build_query_plan_with_defaults("../../bench/supergraph.graphql", document)?;
+
+    insta::assert_snapshot!(query_plan.stats(), @"7 fetch(es), sequence depth 4, subgraphs [accounts, inventory, products, reviews]");
+
+    Ok(())
+}
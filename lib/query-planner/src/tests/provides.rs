@@ -437,3 +437,100 @@ fn provides_on_interface_2_test() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn provides_with_fragment_on_interface_implementation() -> Result<(), Box<dyn Error>> {
+    init_logger();
+    // `Query.book` provides `animals { ... on Dog { name } }` in subgraph `a`
+    let document = parse_operation(
+        r#"
+          query {
+            book {
+              animals {
+                ... on Dog {
+                  name
+                }
+              }
+            }
+          }
+        "#,
+    );
+    let query_plan = build_query_plan_with_defaults(
+        "fixture/tests/provides-on-interface.supergraph.graphql",
+        document,
+    )?;
+
+    // `Dog.name` is external in `a`, but provided, so no entity call is needed
+    insta::assert_snapshot!(format!("{}", query_plan), @r#"
+    QueryPlan {
+      Fetch(service: "a") {
+        {
+          book {
+            animals {
+              __typename
+              ... on Dog {
+                name
+              }
+            }
+          }
+        }
+      },
+    },
+    "#);
+
+    let document = parse_operation(
+        r#"
+          query {
+            book {
+              animals {
+                ... on Cat {
+                  name
+                }
+              }
+            }
+          }
+        "#,
+    );
+    let query_plan = build_query_plan_with_defaults(
+        "fixture/tests/provides-on-interface.supergraph.graphql",
+        document,
+    )?;
+
+    // `Cat.name` is not provided, so it's resolved by `c`
+    insta::assert_snapshot!(format!("{}", query_plan), @r#"
+    QueryPlan {
+      Sequence {
+        Fetch(service: "a") {
+          {
+            book {
+              __typename
+              id
+            }
+          }
+        },
+        Flatten(path: "book") {
+          Fetch(service: "c") {
+            {
+              ... on Book {
+                __typename
+                id
+              }
+            } =>
+            {
+              ... on Book {
+                animals {
+                  __typename
+                  ... on Cat {
+                    name
+                  }
+                }
+              }
+            }
+          },
+        },
+      },
+    },
+    "#);
+
+    Ok(())
+}
@@ -8,15 +8,17 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilte
 
 use graphql_tools::parser::query as query_ast;
 
+use crate::ast::normalization::defer::normalize_deferred_operation;
 use crate::ast::normalization::normalize_operation;
 use crate::graph::edge::PlannerOverrideContext;
 use crate::graph::Graph;
 use crate::planner::best::find_best_combination;
+use crate::planner::contextual_arguments::apply_contextual_arguments;
 use crate::planner::fetch::fetch_graph::build_fetch_graph_from_query_tree;
 use crate::planner::plan_nodes::QueryPlan;
 use crate::planner::query_plan::build_query_plan_from_fetch_graph;
 use crate::planner::walker::walk_operation;
-use crate::planner::{add_variables_to_fetch_steps, QueryPlannerOptions};
+use crate::planner::{add_variables_to_fetch_steps, Planner, QueryPlannerOptions};
 use crate::state::supergraph_state::{OperationKind, SupergraphState};
 use crate::utils::cancellation::CancellationToken;
 use crate::utils::parsing::parse_schema;
@@ -65,7 +67,12 @@ pub fn build_query_plan(
     let supergraph_state = SupergraphState::new(&schema);
     let graph = Graph::graph_from_supergraph_state(&supergraph_state)?;
     let document = normalize_operation(&supergraph_state, &query, None)?;
-    let operation = document.executable_operation();
+    let contextual_operation =
+        apply_contextual_arguments(&supergraph_state, document.executable_operation());
+    let (operation, context_arguments) = match &contextual_operation {
+        Some((operation, context_arguments)) => (operation, context_arguments.as_slice()),
+        None => (document.executable_operation(), [].as_slice()),
+    };
     let best_paths_per_leaf = walk_operation(
         &graph,
         &supergraph_state,
@@ -83,6 +90,7 @@ pub fn build_query_plan(
             .operation_kind
             .clone()
             .unwrap_or(OperationKind::Query),
+        context_arguments,
         &options,
         &cancellation_token,
     )?;
@@ -105,3 +113,24 @@ pub fn build_query_plan_with_defaults(
         Default::default(),
     )
 }
+
+pub fn build_deferred_query_plan(
+    fixture_path: &str,
+    query: query_ast::Document<'static, String>,
+) -> Result<QueryPlan, Box<dyn Error>> {
+    let cancellation_token = CancellationToken::new();
+    let schema = parse_schema(&read_supergraph(fixture_path));
+    let planner = Planner::new_from_supergraph(&schema, Default::default())?;
+    let document = normalize_operation(&planner.supergraph, &query, None)?;
+    let deferred_operation = normalize_deferred_operation(&planner.supergraph, &query, None)?
+        .ok_or("operation has no deferred fragments")?;
+
+    let plan = planner.plan_deferred_operation(
+        document.executable_operation(),
+        &deferred_operation,
+        PlannerOverrideContext::default(),
+        &cancellation_token,
+    )?;
+
+    Ok(plan)
+}
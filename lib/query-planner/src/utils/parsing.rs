@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 #[inline]
 pub fn parse_schema(sdl: &str) -> graphql_tools::parser::schema::Document<'static, String> {
     graphql_tools::parser::parse_schema(sdl)
@@ -43,3 +45,22 @@ pub fn safe_parse_operation_with_token_limit(
     graphql_tools::parser::parse_query_with_token_limit(operation, token_limit)
         .map(|op| op.into_static())
 }
+
+#[inline]
+pub fn safe_parse_operation_with_limits(
+    operation: &str,
+    token_limit: Option<usize>,
+    recursion_limit: usize,
+    time_limit: Option<Duration>,
+) -> Result<
+    graphql_tools::parser::query::Document<'static, String>,
+    graphql_tools::parser::query::ParseError,
+> {
+    graphql_tools::parser::parse_query_with_limits(
+        operation,
+        token_limit,
+        recursion_limit,
+        time_limit,
+    )
+    .map(|op| op.into_static())
+}
@@ -0,0 +1,158 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::{file_path::FilePath, percentage::Percentage};
+
+/// Configuration of the access log of the router.
+///
+/// ### Example
+/// ```yaml
+/// access_log:
+///   enabled: true
+///   format: apache
+///   fields: [timestamp, status, duration, operation_name, client_name]
+///   sampling: 10%
+///   output:
+///     file: ./access.log
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AccessLogConfig {
+    /// Writes one line per request to the GraphQL endpoint, apart from the logs of the router,
+    /// whatever the `log` level and filter are.
+    ///
+    /// The line of a subscription or of an incrementally delivered response
+    /// is written once its response starts, not once it completes.
+    ///
+    /// Disabled by default.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The format of the lines. By default, `json` is used.
+    #[serde(default)]
+    pub format: AccessLogFormat,
+
+    /// The fields of the lines, in order. By default, every field is written.
+    #[serde(default = "default_fields")]
+    pub fields: Vec<AccessLogField>,
+
+    /// The share of the requests written to the access log.
+    /// Default: 100%
+    #[serde(default = "default_sampling")]
+    #[schemars(with = "String")]
+    pub sampling: Percentage,
+
+    /// Where the lines are written. By default, `stdout` is used.
+    #[serde(default)]
+    pub output: AccessLogOutput,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: AccessLogFormat::default(),
+            fields: default_fields(),
+            sampling: default_sampling(),
+            output: AccessLogOutput::default(),
+        }
+    }
+}
+
+fn default_fields() -> Vec<AccessLogField> {
+    vec![
+        AccessLogField::Timestamp,
+        AccessLogField::Method,
+        AccessLogField::Path,
+        AccessLogField::Status,
+        AccessLogField::Duration,
+        AccessLogField::OperationName,
+        AccessLogField::OperationType,
+        AccessLogField::ClientName,
+        AccessLogField::ClientVersion,
+        AccessLogField::Subgraphs,
+        AccessLogField::CacheStatus,
+        AccessLogField::RequestId,
+    ]
+}
+
+fn default_sampling() -> Percentage {
+    Percentage::from_f64(1.0).unwrap()
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessLogFormat {
+    /// A JSON object per line, keyed by the names of the fields.
+    #[default]
+    Json,
+    /// The values of the fields separated by spaces, as in the access logs of Apache,
+    /// with `-` for a missing value:
+    ///
+    /// `[2026-01-01T10:00:00.000Z] POST /graphql 200 12.345 "GetUser" query "web" "1.2.0" accounts,products miss`
+    Apache,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessLogField {
+    /// The time the request was received, in the RFC 3339 format.
+    Timestamp,
+    /// The HTTP method of the request.
+    Method,
+    /// The path of the request.
+    Path,
+    /// The HTTP status of the response.
+    Status,
+    /// The time it took to respond, in milliseconds.
+    Duration,
+    /// The name of the GraphQL operation.
+    OperationName,
+    /// The type of the GraphQL operation: `query`, `mutation` or `subscription`.
+    OperationType,
+    /// The name of the client, read from `telemetry.client_identification.name_header`.
+    ClientName,
+    /// The version of the client, read from `telemetry.client_identification.version_header`.
+    ClientVersion,
+    /// The subgraphs fetched by the query plan of the operation.
+    /// None when the response comes from the response cache,
+    /// or is shared with an identical in-flight request.
+    Subgraphs,
+    /// `hit` or `miss` when the response cache applies to the operation.
+    CacheStatus,
+    /// The ID of the request, when `request_id` is enabled.
+    RequestId,
+}
+
+impl AccessLogField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccessLogField::Timestamp => "timestamp",
+            AccessLogField::Method => "method",
+            AccessLogField::Path => "path",
+            AccessLogField::Status => "status",
+            AccessLogField::Duration => "duration",
+            AccessLogField::OperationName => "operation_name",
+            AccessLogField::OperationType => "operation_type",
+            AccessLogField::ClientName => "client_name",
+            AccessLogField::ClientVersion => "client_version",
+            AccessLogField::Subgraphs => "subgraphs",
+            AccessLogField::CacheStatus => "cache_status",
+            AccessLogField::RequestId => "request_id",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum AccessLogOutput {
+    /// Writes the lines to the standard output, along with the logs of the router.
+    #[default]
+    Stdout,
+    /// Writes the lines to the standard error.
+    Stderr,
+    /// Appends the lines to a file, created when missing.
+    ///
+    /// A relative path is relative to the directory of the config file.
+    File(FilePath),
+}
@@ -0,0 +1,164 @@
+use std::net::SocketAddr;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::value_or_expression::ValueOrExpression;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AdminConfig {
+    /// Endpoint pushing a new supergraph to the router, as an alternative to polling.
+    #[serde(default)]
+    pub supergraph: AdminSupergraphConfig,
+
+    /// Endpoint exposing the runtime state of the router as JSON, for dashboards and ops tooling.
+    #[serde(default)]
+    pub diagnostics: AdminDiagnosticsConfig,
+
+    /// Endpoints purging the caches of the router, without waiting for the entries to expire.
+    #[serde(default)]
+    pub cache: AdminCacheConfig,
+
+    /// The IP address and port of a dedicated HTTP server for the administration endpoints,
+    /// to keep them off the public listener, e.g. on `127.0.0.1` only.
+    /// When not set, the endpoints are registered on the main server.
+    ///
+    /// Example: `127.0.0.1:4010`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen: Option<SocketAddr>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AdminSupergraphConfig {
+    /// Enables/disables the supergraph endpoint. By default, the endpoint is disabled.
+    ///
+    /// When enabled, a `POST` request to the endpoint, carrying the supergraph SDL as its body,
+    /// replaces the supergraph served by the router once it is successfully built.
+    /// A `POST` request to `<path>/rollback` restores the previously served supergraph.
+    ///
+    /// The supergraph source (`supergraph.source`) is still polled when a `poll_interval` is set,
+    /// and replaces the pushed supergraph when it changes.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The path of the endpoint. By default, `/admin/supergraph` is used.
+    #[serde(default = "default_supergraph_path")]
+    pub path: String,
+
+    /// The token expected in the `Authorization: Bearer <token>` header of the requests.
+    /// Required when the endpoint is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// token:
+    ///   expression: env("ROUTER_ADMIN_TOKEN")
+    /// ```
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<ValueOrExpression<String>>,
+}
+
+impl Default for AdminSupergraphConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_supergraph_path(),
+            token: None,
+        }
+    }
+}
+
+fn default_supergraph_path() -> String {
+    "/admin/supergraph".to_string()
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AdminDiagnosticsConfig {
+    /// Enables/disables the diagnostics endpoint. By default, the endpoint is disabled.
+    ///
+    /// When enabled, a `GET` request to the endpoint responds with the runtime state of the router:
+    /// the served supergraph, the recent supergraph reload errors, the size of the caches
+    /// and the enabled plugins.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The path of the endpoint. By default, `/admin/diagnostics` is used.
+    #[serde(default = "default_diagnostics_path")]
+    pub path: String,
+
+    /// The token expected in the `Authorization: Bearer <token>` header of the requests.
+    /// Required when the endpoint is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// token:
+    ///   expression: env("ROUTER_ADMIN_TOKEN")
+    /// ```
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<ValueOrExpression<String>>,
+}
+
+impl Default for AdminDiagnosticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_diagnostics_path(),
+            token: None,
+        }
+    }
+}
+
+fn default_diagnostics_path() -> String {
+    "/admin/diagnostics".to_string()
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AdminCacheConfig {
+    /// Enables/disables the cache endpoints. By default, the endpoints are disabled.
+    ///
+    /// When enabled, the following `POST` requests purge the caches of the router:
+    /// - `<path>/response` purges the response cache. The JSON body `{"types": ["Product"]}`
+    ///   only purges the responses of the operations selecting the given types,
+    ///   and `{"surrogate_keys": ["Product:upc=1"]}` the responses containing the given entities
+    ///   (see `response_cache.surrogate_keys`). An empty body purges all the responses.
+    /// - `<path>/entity` drops the cached entities of a type, as in `{"typename": "Product"}`,
+    ///   or a single entity when its key fields are given, as in `{"typename": "Product", "key": {"upc": "1"}}`.
+    /// - `<path>/plan` clears the cached validation, normalization and query plans of the operations.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The path of the endpoints. By default, `/admin/cache` is used.
+    #[serde(default = "default_cache_path")]
+    pub path: String,
+
+    /// The token expected in the `Authorization: Bearer <token>` header of the requests.
+    /// Required when the endpoints are enabled.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// token:
+    ///   expression: env("ROUTER_ADMIN_TOKEN")
+    /// ```
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<ValueOrExpression<String>>,
+}
+
+impl Default for AdminCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_cache_path(),
+            token: None,
+        }
+    }
+}
+
+fn default_cache_path() -> String {
+    "/admin/cache".to_string()
+}
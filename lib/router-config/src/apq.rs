@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::value_or_expression::ValueOrExpression;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ApqConfig {
+    /// Enables/disables Automatic Persisted Queries (APQ). By default, APQ is disabled.
+    ///
+    /// When enabled, clients can send the SHA-256 hash of an operation in
+    /// `extensions.persistedQuery.sha256Hash` instead of the operation itself.
+    /// Unknown hashes are answered with a `PERSISTED_QUERY_NOT_FOUND` error,
+    /// and the operation is registered once the client sends it along with its hash.
+    ///
+    /// Registered operations are not safelisted, any client can register an operation.
+    /// To only accept known operations, use `persisted_documents` with `require_id` instead.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The storage backend of the registered operations. Defaults to an in-memory cache.
+    #[serde(default)]
+    pub backend: ApqBackendConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, tag = "type")]
+pub enum ApqBackendConfig {
+    /// Keeps the registered operations in the memory of the router instance,
+    /// evicting the least recently used ones.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: memory
+    /// max_entries: 1000
+    /// ```
+    #[serde(rename = "memory")]
+    Memory {
+        /// The maximum number of operations kept in the cache.
+        ///
+        /// Defaults to 1000.
+        #[serde(default = "default_max_entries")]
+        max_entries: u64,
+    },
+    /// Keeps the registered operations in Redis, shared between router instances,
+    /// every operation expiring `ttl` after it was registered.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: redis
+    /// url: redis://localhost:6379
+    /// key_prefix: "hive-router:apq:"
+    /// ttl: 24h
+    /// ```
+    #[serde(rename = "redis")]
+    Redis {
+        /// The connection URL of the Redis server, e.g. `redis://localhost:6379`.
+        url: ValueOrExpression<String>,
+        /// The prefix of every key written by the router.
+        ///
+        /// Defaults to `hive-router:apq:`.
+        #[serde(default = "default_key_prefix")]
+        key_prefix: String,
+        #[serde(
+            default = "default_ttl",
+            deserialize_with = "humantime_serde::deserialize",
+            serialize_with = "humantime_serde::serialize"
+        )]
+        #[schemars(with = "String")]
+        /// How long a registered operation is kept, as any client can register operations.
+        /// An expired operation is registered again by the next client sending it along with its hash.
+        ///
+        /// Defaults to `24h`.
+        ttl: Duration,
+    },
+}
+
+impl Default for ApqBackendConfig {
+    fn default() -> Self {
+        Self::Memory {
+            max_entries: default_max_entries(),
+        }
+    }
+}
+
+fn default_max_entries() -> u64 {
+    1_000
+}
+
+fn default_key_prefix() -> String {
+    "hive-router:apq:".to_string()
+}
+
+fn default_ttl() -> Duration {
+    Duration::from_secs(24 * 60 * 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_memory_backend() {
+        let config = serde_json::from_str::<ApqConfig>(r#"{"enabled": true}"#).unwrap();
+        assert!(config.enabled);
+        match config.backend {
+            ApqBackendConfig::Memory { max_entries } => assert_eq!(max_entries, 1_000),
+            _ => panic!("expected the memory backend"),
+        }
+    }
+
+    #[test]
+    fn redis_backend() {
+        let config = serde_json::from_str::<ApqConfig>(
+            r#"{
+                "enabled": true,
+                "backend": { "type": "redis", "url": "redis://localhost:6379" }
+            }"#,
+        )
+        .unwrap();
+        match config.backend {
+            ApqBackendConfig::Redis {
+                key_prefix, ttl, ..
+            } => {
+                assert_eq!(key_prefix, "hive-router:apq:");
+                assert_eq!(ttl, Duration::from_secs(24 * 60 * 60));
+            }
+            _ => panic!("expected the redis backend"),
+        }
+
+        let config = serde_json::from_str::<ApqConfig>(
+            r#"{
+                "enabled": true,
+                "backend": { "type": "redis", "url": "redis://localhost:6379", "ttl": "30m" }
+            }"#,
+        )
+        .unwrap();
+        match config.backend {
+            ApqBackendConfig::Redis { ttl, .. } => assert_eq!(ttl, Duration::from_secs(30 * 60)),
+            _ => panic!("expected the redis backend"),
+        }
+    }
+}
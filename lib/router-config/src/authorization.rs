@@ -1,10 +1,19 @@
+use std::time::Duration;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::primitives::file_path::FilePath;
+
 #[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct AuthorizationConfig {
+    #[serde(default)]
     pub directives: AuthorizationDirectivesConfig,
+    /// Authorizes the operations with an [Open Policy Agent](https://www.openpolicyagent.org) policy,
+    /// after the authorization directives. Disabled when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opa: Option<OpaAuthorizationConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -43,3 +52,167 @@ impl Default for AuthorizationDirectivesConfig {
         }
     }
 }
+
+/// The policy is evaluated with an input document describing the request:
+///
+/// ```json
+/// {
+///   "operation": { "name": "Me", "type": "query", "query": "query Me { me { id } }" },
+///   "variables": {},
+///   "jwt": { "authenticated": true, "claims": { "sub": "user-1" }, "scopes": ["read"] },
+///   "headers": { "x-tenant": "acme" }
+/// }
+/// ```
+///
+/// The decision of the policy is either a boolean allowing or denying the operation,
+/// or an object like `{ "allow": true, "denied_fields": ["User.email", "Payment"] }`,
+/// whose `denied_fields` (field or type coordinates) are removed from the operation,
+/// like the fields of the authorization directives in `filter` mode.
+/// An undefined decision, or an object whose `allow` is undefined, denies the operation.
+///
+/// ### Example
+/// ```yaml
+/// authorization:
+///   opa:
+///     policy:
+///       type: http
+///       url: http://localhost:8181/v1/data/graphql/authz
+///     operation_types: [mutation]
+///     cache:
+///       ttl: 30s
+///       headers: [x-tenant]
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OpaAuthorizationConfig {
+    /// Where the policy is evaluated.
+    pub policy: OpaPolicyConfig,
+    /// Only the operations of these types are authorized by the policy.
+    /// All the operations are authorized when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_types: Option<Vec<OpaOperationType>>,
+    /// What to do with the operation when the policy can not be evaluated,
+    /// e.g. when the OPA server is unreachable.
+    ///
+    /// Defaults to `deny`.
+    #[serde(default)]
+    pub on_error: OpaErrorMode,
+    /// Caches the decisions by input document, so identical requests don't evaluate the policy again.
+    /// When enabled, the input document only contains the headers listed in `cache.headers`.
+    /// Disabled when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<OpaDecisionCacheConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, tag = "type")]
+pub enum OpaPolicyConfig {
+    /// Evaluates the policy with the Data API of an OPA server, usually running as a sidecar.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: http
+    /// url: http://localhost:8181/v1/data/graphql/authz
+    /// timeout: 500ms
+    /// ```
+    #[serde(rename = "http")]
+    Http {
+        /// The URL of the decision, `/v1/data/` followed by the path of the rule.
+        url: String,
+        #[serde(
+            default = "default_opa_timeout",
+            deserialize_with = "humantime_serde::deserialize",
+            serialize_with = "humantime_serde::serialize"
+        )]
+        #[schemars(with = "String")]
+        /// The maximum duration of an evaluation.
+        ///
+        /// Defaults to `1s`.
+        timeout: Duration,
+    },
+    /// Evaluates the policy within the router, with a policy compiled to WebAssembly
+    /// (`opa build -t wasm -e graphql/authz policy.rego`).
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: wasm
+    /// path: ./policy.wasm
+    /// entrypoint: graphql/authz
+    /// ```
+    #[serde(rename = "wasm")]
+    Wasm {
+        /// The path to the `.wasm` module of the policy, relative to the config file.
+        path: FilePath,
+        /// The entrypoint of the policy, as passed to `opa build -e`.
+        entrypoint: String,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OpaOperationType {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl OpaOperationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Mutation => "mutation",
+            Self::Subscription => "subscription",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OpaErrorMode {
+    /// Rejects the operation.
+    #[default]
+    Deny,
+    /// Executes the operation as if the policy allowed it.
+    Allow,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OpaDecisionCacheConfig {
+    #[serde(
+        default = "default_opa_cache_ttl",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    /// How long a decision is kept.
+    ///
+    /// Defaults to `30s`.
+    pub ttl: Duration,
+    /// The maximum number of decisions kept in the cache.
+    ///
+    /// Defaults to 10000.
+    #[serde(default = "default_opa_cache_max_entries")]
+    pub max_entries: u64,
+    /// The request headers passed to the policy, and part of the cache key.
+    /// The other headers are left out of the input document,
+    /// so a decision never depends on a header missing from its key.
+    ///
+    /// Defaults to no headers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<String>,
+}
+
+fn default_opa_timeout() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_opa_cache_ttl() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn default_opa_cache_max_entries() -> u64 {
+    10_000
+}
@@ -0,0 +1,30 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Configuration of a contract, a filtered variant of the supergraph served to the clients.
+///
+/// The elements of the supergraph are filtered by their `@tag` directives.
+/// The clients can only query and introspect the filtered schema,
+/// while the query planner still plans against the full supergraph.
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+#[schemars(example = contract_example())]
+pub struct ContractConfig {
+    /// Only the types and fields tagged with one of these tags are exposed.
+    /// The fields of a tagged type are exposed, unless excluded.
+    ///
+    /// When empty, every element not excluded is exposed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_tags: Vec<String>,
+    /// The types, fields, arguments and enum values tagged with one of these tags are hidden.
+    /// Excluding takes precedence over including.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tags: Vec<String>,
+}
+
+fn contract_example() -> ContractConfig {
+    ContractConfig {
+        include_tags: vec!["public".into()],
+        exclude_tags: vec!["internal".into()],
+    }
+}
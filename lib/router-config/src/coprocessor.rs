@@ -62,6 +62,9 @@ pub struct CoprocessorStagesConfig {
     #[serde(default)]
     /// Hooks around GraphQL processing
     pub graphql: CoprocessorGraphqlStageConfig,
+    #[serde(default)]
+    /// Hooks around the HTTP requests sent to subgraphs
+    pub subgraph: CoprocessorSubgraphStageConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
@@ -89,6 +92,23 @@ pub struct CoprocessorGraphqlStageConfig {
     pub response: Option<CoprocessorHookConfig<CoprocessorGraphqlResponseIncludeConfig>>,
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CoprocessorSubgraphStageConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Configuration for `subgraph.request` hook.
+    pub request: Option<CoprocessorHookConfig<CoprocessorSubgraphRequestIncludeConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Configuration for `subgraph.response` hook.
+    pub response: Option<CoprocessorHookConfig<CoprocessorSubgraphResponseIncludeConfig>>,
+}
+
+impl CoprocessorSubgraphStageConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.request.is_some() || self.response.is_some()
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
 #[serde(deny_unknown_fields)]
 pub struct CoprocessorHookConfig<I: Default> {
@@ -234,6 +254,53 @@ pub struct CoprocessorGraphqlAnalysisIncludeConfig {
     pub sdl: bool,
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CoprocessorSubgraphRequestIncludeConfig {
+    #[serde(default)]
+    /// Include the body of the subgraph HTTP request.
+    pub body: bool,
+    #[serde(default)]
+    /// Include request context.
+    ///
+    /// Values:
+    /// - `false`: no context
+    /// - `true`: full context
+    /// - list: selected context keys
+    pub context: ContextSelection,
+    #[serde(default)]
+    /// Include the headers of the subgraph HTTP request.
+    pub headers: bool,
+    #[serde(default)]
+    /// Include the method of the subgraph HTTP request.
+    pub method: bool,
+    #[serde(default)]
+    /// Include the URL of the subgraph HTTP request.
+    pub uri: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CoprocessorSubgraphResponseIncludeConfig {
+    #[serde(default)]
+    /// Include the body of the subgraph HTTP response.
+    pub body: bool,
+    #[serde(default)]
+    /// Include request context.
+    ///
+    /// Values:
+    /// - `false`: no context
+    /// - `true`: full context
+    /// - list: selected context keys
+    pub context: ContextSelection,
+    #[serde(default)]
+    /// Include the headers of the subgraph HTTP response.
+    pub headers: bool,
+    #[serde(default)]
+    /// Include the status code of the subgraph HTTP response.
+    pub status_code: bool,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum GraphqlBodyField {
@@ -1,9 +1,9 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::primitives::http_header::HttpHeaderName;
+use crate::primitives::{http_header::HttpHeaderName, value_or_expression::ValueOrExpression};
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(deny_unknown_fields)]
@@ -36,6 +36,13 @@ pub struct DemandControlConfig {
     /// Note: the "actual" value calculated in any mode is not used for enforcment.
     #[serde(default)]
     pub actual_cost_mode: DemandControlActualCostMode,
+
+    /// Cost budgets of the clients over time windows.
+    ///
+    /// Every client can spend at most the configured number of cost units per window,
+    /// charged with the estimated cost of its operations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_budgets: Option<ClientCostBudgetsConfig>,
 }
 
 /// Default header name used to expose the configured `max` cost limit when
@@ -189,3 +196,178 @@ pub struct DefaultListSizeConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub subgraphs: Option<HashMap<String, usize>>,
 }
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ClientCostBudgetsConfig {
+    /// Controls what happens when a client exceeds one of its budgets.
+    ///
+    /// - `enforce`: reject the operation with a `429 Too Many Requests` response,
+    ///   carrying a `Retry-After` header, until the exceeded window is over.
+    ///   The rejected operations are not charged.
+    /// - `measure`: never reject. The operation is charged, and the exceeded budget is logged.
+    pub mode: DemandControlMode,
+
+    /// How the clients are identified.
+    ///
+    /// The operations without the configured identity, e.g. without the header or without a valid JWT,
+    /// share the budgets of a single anonymous client.
+    pub key: ClientCostBudgetKeyConfig,
+
+    /// The budgets of every client. An operation is allowed when it fits in all of them.
+    ///
+    /// The windows are fixed, and aligned on the Unix epoch,
+    /// so a `1m` window starts at the beginning of every minute.
+    pub windows: Vec<ClientCostBudgetWindowConfig>,
+
+    /// The storage backend of the spent budgets. Defaults to the memory of the router instance.
+    ///
+    /// When several router instances serve the same clients, use the `redis` backend,
+    /// so the budgets are shared between the instances.
+    #[serde(default)]
+    pub backend: ClientCostBudgetsBackendConfig,
+
+    /// Reports the remaining budgets of the client in the `costBudget` extension of the response.
+    ///
+    /// Defaults to true.
+    #[serde(default = "default_expose_extension")]
+    pub expose_extension: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ClientCostBudgetWindowConfig {
+    /// The maximum cost a client can spend in the window.
+    pub max: u64,
+    #[serde(
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    /// The length of the window, e.g. `1m` or `1h`. Must be a whole number of seconds.
+    pub window: Duration,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, tag = "type")]
+pub enum ClientCostBudgetKeyConfig {
+    /// Identifies the clients by the value of a request header.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: header
+    /// name: x-tenant-id
+    /// ```
+    #[serde(rename = "header")]
+    Header {
+        /// The name of the header.
+        name: HttpHeaderName,
+    },
+    /// Identifies the clients by a claim of their validated JWT.
+    /// Requires `jwt` to be enabled.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: jwt_claim
+    /// claim: sub
+    /// ```
+    #[serde(rename = "jwt_claim")]
+    JwtClaim {
+        /// The name of the claim.
+        ///
+        /// Defaults to `sub`.
+        #[serde(default = "default_jwt_claim")]
+        claim: String,
+    },
+    /// Identifies the clients by the API key sent in a request header.
+    /// Only the hash of the key is kept by the router, never the key itself.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: api_key
+    /// header: x-api-key
+    /// ```
+    #[serde(rename = "api_key")]
+    ApiKey {
+        /// The name of the header carrying the API key.
+        ///
+        /// Defaults to `x-api-key`.
+        #[serde(default = "default_api_key_header")]
+        header: HttpHeaderName,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, tag = "type")]
+pub enum ClientCostBudgetsBackendConfig {
+    /// Keeps the spent budgets in the memory of the router instance,
+    /// so every router instance enforces the budgets on its own.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: memory
+    /// max_entries: 100000
+    /// ```
+    #[serde(rename = "memory")]
+    Memory {
+        /// The maximum number of clients tracked at once.
+        /// The clients idle for longer than the longest window are evicted first.
+        ///
+        /// Defaults to 100000.
+        #[serde(default = "default_max_entries")]
+        max_entries: u64,
+    },
+    /// Keeps the spent budgets in Redis, shared between router instances.
+    ///
+    /// The budgets are checked and charged atomically with a Lua script.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: redis
+    /// url: redis://localhost:6379
+    /// key_prefix: "hive-router:cost-budget:"
+    /// ```
+    #[serde(rename = "redis")]
+    Redis {
+        /// The connection URL of the Redis server, e.g. `redis://localhost:6379`.
+        url: ValueOrExpression<String>,
+        /// The prefix of every key written by the router.
+        ///
+        /// Defaults to `hive-router:cost-budget:`.
+        #[serde(default = "default_key_prefix")]
+        key_prefix: String,
+    },
+}
+
+impl Default for ClientCostBudgetsBackendConfig {
+    fn default() -> Self {
+        Self::Memory {
+            max_entries: default_max_entries(),
+        }
+    }
+}
+
+fn default_expose_extension() -> bool {
+    true
+}
+
+fn default_jwt_claim() -> String {
+    "sub".to_string()
+}
+
+fn default_api_key_header() -> HttpHeaderName {
+    "x-api-key".into()
+}
+
+fn default_max_entries() -> u64 {
+    100_000
+}
+
+fn default_key_prefix() -> String {
+    "hive-router:cost-budget:".to_string()
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct EntityCacheConfig {
+    /// Enables/disables the entity cache. By default, the entity cache is disabled.
+    ///
+    /// When enabled, the entities resolved by subgraphs through `_entities` queries are cached,
+    /// keyed by their representation (type name and key fields), and only the representations
+    /// that are not cached are sent to the subgraphs.
+    ///
+    /// Cached entities are shared between all clients, only types whose fields do not depend on
+    /// the client should be cached.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The time-to-live of the entities of types not listed in `types`.
+    ///
+    /// If set to 0 (the default), only the entities of the types listed in `types` are cached.
+    #[serde(
+        default = "default_ttl",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub default_ttl: Duration,
+
+    /// The caching configuration of entity types, by type name.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// types:
+    ///   Product:
+    ///     ttl: 30s
+    ///   User:
+    ///     ttl: 0s # never cached
+    /// ```
+    #[serde(default)]
+    pub types: HashMap<String, EntityCacheTypeConfig>,
+
+    /// The maximum number of entities kept in the cache.
+    ///
+    /// Defaults to 10000.
+    #[serde(default = "default_max_entries")]
+    pub max_entries: u64,
+
+    /// Drops the cached entities returned by successful mutations, identified by their `@key` fields,
+    /// so that the clients read their own writes.
+    ///
+    /// Only the entities whose key fields are selected by the mutation are dropped.
+    /// Disabled by default.
+    #[serde(default)]
+    pub invalidate_on_mutation: bool,
+}
+
+impl Default for EntityCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_ttl: default_ttl(),
+            types: HashMap::new(),
+            max_entries: default_max_entries(),
+            invalidate_on_mutation: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct EntityCacheTypeConfig {
+    /// The time-to-live of the cached entities of the type. Entities are not cached when set to 0.
+    #[serde(
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub ttl: Duration,
+}
+
+impl EntityCacheConfig {
+    /// The time-to-live of the entities of the given type, `None` when they must not be cached.
+    pub fn ttl_for_type(&self, type_name: &str) -> Option<Duration> {
+        let ttl = self
+            .types
+            .get(type_name)
+            .map_or(self.default_ttl, |type_config| type_config.ttl);
+        (!ttl.is_zero()).then_some(ttl)
+    }
+}
+
+fn default_ttl() -> Duration {
+    Duration::ZERO
+}
+
+fn default_max_entries() -> u64 {
+    10_000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_type_ttl() {
+        let config = serde_json::from_str::<EntityCacheConfig>(
+            r#"{
+                "enabled": true,
+                "types": {
+                    "Product": { "ttl": "30s" },
+                    "User": { "ttl": "0s" }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.max_entries, 10_000);
+        assert_eq!(
+            config.ttl_for_type("Product"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(config.ttl_for_type("User"), None);
+        assert_eq!(config.ttl_for_type("Review"), None);
+    }
+
+    #[test]
+    fn default_ttl_applies_to_unlisted_types() {
+        let config = serde_json::from_str::<EntityCacheConfig>(
+            r#"{
+                "enabled": true,
+                "default_ttl": "1m",
+                "types": { "User": { "ttl": "0s" } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.ttl_for_type("Review"), Some(Duration::from_secs(60)));
+        assert_eq!(config.ttl_for_type("User"), None);
+    }
+}
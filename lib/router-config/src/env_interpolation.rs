@@ -0,0 +1,314 @@
+use config::{Value, ValueKind};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum EnvVarInterpolationError {
+    #[error("'{path}' references the environment variable '{name}', which is not set. Set it, or give it a default with '${{{name}:-default}}'")]
+    MissingEnvVar { path: String, name: String },
+    #[error("'{path}' references an environment variable with an invalid name: '{name}'")]
+    InvalidEnvVarName { path: String, name: String },
+    #[error("'{path}' has a '${{' without a closing '}}'. Use '$${{' for a literal '${{'")]
+    UnterminatedPlaceholder { path: String },
+}
+
+/// Expands the `${ENV_VAR}` and `${ENV_VAR:-default}` placeholders of every string of the config,
+/// except the VRL expressions and programs, kept as written, as they read the environment with `env(...)`.
+///
+/// The default is used when the variable is not set or is empty,
+/// and a variable without a default must be set.
+/// `$${` is a literal `${`.
+pub fn interpolate_env_vars(
+    value: &mut Value,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<(), EnvVarInterpolationError> {
+    interpolate_value(value, "", lookup)
+}
+
+/// The key of the VRL expressions of the config, e.g. in `ValueOrExpression`.
+const EXPRESSION_KEY: &str = "expression";
+
+/// The paths of the VRL programs of the config not held by an `expression` key.
+const VRL_PROGRAM_PATHS: &[&str] = &["hooks.on_http_request", "hooks.on_subgraph_request"];
+
+fn interpolate_value(
+    value: &mut Value,
+    path: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<(), EnvVarInterpolationError> {
+    match &mut value.kind {
+        ValueKind::String(string) if string.contains("${") => {
+            *string = interpolate_str(string, path, lookup)?;
+        }
+        ValueKind::Table(table) => {
+            for (key, value) in table
+                .iter_mut()
+                .filter(|(key, _)| key.as_str() != EXPRESSION_KEY)
+            {
+                let path = match path {
+                    "" => key.clone(),
+                    _ => format!("{path}.{key}"),
+                };
+                if VRL_PROGRAM_PATHS.contains(&path.as_str()) {
+                    continue;
+                }
+                interpolate_value(value, &path, lookup)?;
+            }
+        }
+        ValueKind::Array(array) => {
+            for (index, value) in array.iter_mut().enumerate() {
+                interpolate_value(value, &format!("{path}[{index}]"), lookup)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn interpolate_str(
+    input: &str,
+    path: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<String, EnvVarInterpolationError> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("$${") {
+            output.push_str("${");
+            rest = after;
+            continue;
+        }
+
+        let Some(placeholder) = rest.strip_prefix("${") else {
+            output.push('$');
+            rest = &rest[1..];
+            continue;
+        };
+
+        let end = placeholder.find('}').ok_or_else(|| {
+            EnvVarInterpolationError::UnterminatedPlaceholder {
+                path: path.to_string(),
+            }
+        })?;
+        let (name, default) = match placeholder[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&placeholder[..end], None),
+        };
+
+        if !is_valid_env_var_name(name) {
+            return Err(EnvVarInterpolationError::InvalidEnvVarName {
+                path: path.to_string(),
+                name: name.to_string(),
+            });
+        }
+
+        match (lookup(name), default) {
+            (Some(value), Some(default)) if value.is_empty() => output.push_str(default),
+            (Some(value), _) => output.push_str(&value),
+            (None, Some(default)) => output.push_str(default),
+            (None, None) => {
+                return Err(EnvVarInterpolationError::MissingEnvVar {
+                    path: path.to_string(),
+                    name: name.to_string(),
+                })
+            }
+        }
+
+        rest = &placeholder[end + 1..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn is_valid_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "ACCOUNTS_URL" => Some("http://accounts:4001/graphql".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    fn interpolate(input: &str) -> Result<String, EnvVarInterpolationError> {
+        interpolate_str(input, "some.path", &lookup)
+    }
+
+    #[test]
+    fn expands_the_placeholders() {
+        assert_eq!(
+            interpolate("${ACCOUNTS_URL}").unwrap(),
+            "http://accounts:4001/graphql"
+        );
+        assert_eq!(
+            interpolate("url: ${ACCOUNTS_URL}, again: ${ACCOUNTS_URL}").unwrap(),
+            "url: http://accounts:4001/graphql, again: http://accounts:4001/graphql"
+        );
+        assert_eq!(
+            interpolate("no placeholder, $5").unwrap(),
+            "no placeholder, $5"
+        );
+    }
+
+    #[test]
+    fn uses_the_default_when_the_variable_is_not_set_or_empty() {
+        assert_eq!(
+            interpolate("${MISSING:-redis://localhost:6379}").unwrap(),
+            "redis://localhost:6379"
+        );
+        assert_eq!(interpolate("${EMPTY:-fallback}").unwrap(), "fallback");
+        assert_eq!(interpolate("${MISSING:-}").unwrap(), "");
+        assert_eq!(interpolate("${EMPTY}").unwrap(), "");
+        assert_eq!(
+            interpolate("${ACCOUNTS_URL:-fallback}").unwrap(),
+            "http://accounts:4001/graphql"
+        );
+    }
+
+    #[test]
+    fn keeps_escaped_placeholders() {
+        assert_eq!(interpolate("$${ACCOUNTS_URL}").unwrap(), "${ACCOUNTS_URL}");
+    }
+
+    #[test]
+    fn rejects_invalid_placeholders() {
+        assert_eq!(
+            interpolate("${MISSING}"),
+            Err(EnvVarInterpolationError::MissingEnvVar {
+                path: "some.path".to_string(),
+                name: "MISSING".to_string(),
+            })
+        );
+        assert_eq!(
+            interpolate("${1NVALID}"),
+            Err(EnvVarInterpolationError::InvalidEnvVarName {
+                path: "some.path".to_string(),
+                name: "1NVALID".to_string(),
+            })
+        );
+        assert_eq!(
+            interpolate("${ACCOUNTS_URL"),
+            Err(EnvVarInterpolationError::UnterminatedPlaceholder {
+                path: "some.path".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_the_expressions_as_written() {
+        let mut value = config::Config::builder()
+            .add_source(config::File::from_str(
+                r#"
+                override_subgraph_urls:
+                  accounts:
+                    url:
+                      expression: '"http://" + "${HOST}"'
+                  products:
+                    url: ${ACCOUNTS_URL}
+                "#,
+                config::FileFormat::Yaml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Value>()
+            .unwrap();
+
+        interpolate_env_vars(&mut value, &lookup).unwrap();
+
+        let string_at = |path: &[&str]| {
+            path.iter()
+                .fold(value.clone(), |value, key| {
+                    value.into_table().unwrap()[*key].clone()
+                })
+                .into_string()
+                .unwrap()
+        };
+        assert_eq!(
+            string_at(&["override_subgraph_urls", "accounts", "url", "expression"]),
+            r#""http://" + "${HOST}""#
+        );
+        assert_eq!(
+            string_at(&["override_subgraph_urls", "products", "url"]),
+            "http://accounts:4001/graphql"
+        );
+    }
+
+    #[test]
+    fn keeps_the_hook_programs_as_written() {
+        let mut value = config::Config::builder()
+            .add_source(config::File::from_str(
+                r#"
+                hooks:
+                  on_http_request: |
+                    .request.headers."x-host" = "${HOST}"
+                  on_subgraph_request: |
+                    .request.headers."x-subgraph-host" = "${HOST}"
+                "#,
+                config::FileFormat::Yaml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Value>()
+            .unwrap();
+
+        interpolate_env_vars(&mut value, &lookup).unwrap();
+
+        let hooks = value.into_table().unwrap()["hooks"]
+            .clone()
+            .into_table()
+            .unwrap();
+        assert_eq!(
+            hooks["on_http_request"].clone().into_string().unwrap(),
+            ".request.headers.\"x-host\" = \"${HOST}\"\n"
+        );
+        assert_eq!(
+            hooks["on_subgraph_request"].clone().into_string().unwrap(),
+            ".request.headers.\"x-subgraph-host\" = \"${HOST}\"\n"
+        );
+    }
+
+    #[test]
+    fn reports_the_path_of_the_value() {
+        let mut value = config::Config::builder()
+            .add_source(config::File::from_str(
+                r#"
+                override_subgraph_urls:
+                  accounts:
+                    url: ${ACCOUNTS_URL}
+                headers:
+                  all:
+                    request:
+                      - insert:
+                          name: x-token
+                          value: ${TOKEN}
+                "#,
+                config::FileFormat::Yaml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize::<Value>()
+            .unwrap();
+
+        assert_eq!(
+            interpolate_env_vars(&mut value, &lookup),
+            Err(EnvVarInterpolationError::MissingEnvVar {
+                path: "headers.all.request[0].insert.value".to_string(),
+                name: "TOKEN".to_string(),
+            })
+        );
+    }
+}
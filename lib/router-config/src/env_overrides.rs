@@ -51,6 +51,10 @@ pub struct EnvVarOverrides {
     pub hive_tracing_enabled: Option<bool>,
     #[envconfig(from = "HIVE_USAGE_REPORTING_ENABLED")]
     pub hive_usage_reporting_enabled: Option<bool>,
+    #[envconfig(from = "APOLLO_KEY")]
+    pub apollo_key: Option<String>,
+    #[envconfig(from = "APOLLO_GRAPH_REF")]
+    pub apollo_graph_ref: Option<String>,
 
     // Tracing overrides
     #[envconfig(from = "TELEMETRY_TRACING_SAMPLING_RATE")]
@@ -157,6 +161,14 @@ impl EnvVarOverrides {
             config = config.set_override("telemetry.hive.target", hive_target)?;
         }
 
+        if let Some(apollo_key) = self.apollo_key.take() {
+            config = config.set_override("telemetry.apollo.key", apollo_key)?;
+        }
+
+        if let Some(apollo_graph_ref) = self.apollo_graph_ref.take() {
+            config = config.set_override("telemetry.apollo.graph_ref", apollo_graph_ref)?;
+        }
+
         if let Some(tracing_sampling_rate) = self.tracing_sampling_rate.take() {
             debug!(
                 "[config-override] 'telemetry.tracing.collect.sampling' = {}",
@@ -200,7 +212,7 @@ impl EnvVarOverrides {
 mod tests {
     use config::{Config, File, FileFormat};
 
-    use crate::HiveRouterConfig;
+    use crate::{primitives::value_or_expression::ValueOrExpression, HiveRouterConfig};
 
     use super::*;
 
@@ -279,4 +291,22 @@ query_planner:
 
         assert!(config.query_planner.experimental_abstract_type_folding);
     }
+
+    #[test]
+    fn apollo_overrides_set_key_and_graph_ref() {
+        let config = config_from_overrides(EnvVarOverrides {
+            apollo_key: Some("service:my-graph:key".to_string()),
+            apollo_graph_ref: Some("my-graph@current".to_string()),
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            config.telemetry.apollo.key,
+            Some(ValueOrExpression::Value(ref key)) if key == "service:my-graph:key"
+        ));
+        assert!(matches!(
+            config.telemetry.apollo.graph_ref,
+            Some(ValueOrExpression::Value(ref graph_ref)) if graph_ref == "my-graph@current"
+        ));
+    }
 }
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Configuration of the errors exposed to the clients.
+///
+/// It is applied to the final response, after the plugins processed the errors,
+/// so plugins still see the original errors.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ErrorsConfig {
+    /// Masking of the errors returned by subgraphs.
+    #[serde(default)]
+    pub masking: ErrorMaskingConfig,
+
+    /// Keys of `extensions` removed from every error sent to the clients,
+    /// for example `stacktrace` or `exception`.
+    ///
+    /// The `code` extension is never removed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub strip_extensions: Vec<String>,
+
+    /// Attributes the errors produced while fetching from subgraphs to the subgraph,
+    /// with the `service` extension holding the name of the subgraph,
+    /// and the `affectedPath` extension holding the response path of the fetch.
+    ///
+    /// Enabled by default. Disable it to not reveal the names of the subgraphs to the clients.
+    #[serde(default = "default_subgraph_attribution")]
+    pub subgraph_attribution: bool,
+
+    /// How the errors occurring while executing the query plan affect the response.
+    ///
+    /// Defaults to `best_effort`.
+    #[serde(default)]
+    pub policy: ErrorPolicy,
+
+    /// HTTP status codes of the responses having errors, by error code (`extensions.code`),
+    /// for example `UNAUTHENTICATED: 401` or `RATE_LIMITED: 429`.
+    ///
+    /// When several errors have a mapped code, the first one decides the status code.
+    /// Streamed responses always use `200`. Plugins can register more codes,
+    /// the ones of the configuration taking precedence.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub status_codes: HashMap<String, u16>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorPolicy {
+    /// Executes the whole query plan, and responds with the partial data and the errors.
+    /// Fields that failed to resolve are `null`, propagated to their closest nullable parent
+    /// as the GraphQL specification requires.
+    #[default]
+    BestEffort,
+    /// Stops executing the remaining nodes of the query plan once an error occurs,
+    /// and responds with the data resolved so far and the errors.
+    FailFast,
+    /// Stops executing the remaining nodes of the query plan once an error occurs,
+    /// and responds with `null` data and the errors, as if the errors propagated to the root.
+    Propagate,
+}
+
+impl Default for ErrorsConfig {
+    fn default() -> Self {
+        Self {
+            masking: ErrorMaskingConfig::default(),
+            strip_extensions: Vec::new(),
+            subgraph_attribution: default_subgraph_attribution(),
+            policy: ErrorPolicy::default(),
+            status_codes: HashMap::new(),
+        }
+    }
+}
+
+fn default_subgraph_attribution() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ErrorMaskingConfig {
+    /// Replaces the message of the errors returned by subgraphs with a generic message,
+    /// and removes their extensions. By default, subgraph errors are not masked.
+    ///
+    /// The `path` and `locations` of masked errors are kept.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The message of masked errors.
+    ///
+    /// Defaults to `Unexpected error`.
+    #[serde(default = "default_masked_message")]
+    pub message: String,
+
+    /// Error codes (`extensions.code`) of subgraph errors that are sent to the clients unmasked,
+    /// for example `BAD_USER_INPUT` or `FORBIDDEN`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_codes: Vec<String>,
+
+    /// Adds a unique `correlationId` extension to masked errors,
+    /// and logs the original error along with it,
+    /// so a masked error reported by a client can be found in the logs of the router.
+    #[serde(default)]
+    pub correlation_id: bool,
+}
+
+impl Default for ErrorMaskingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            message: default_masked_message(),
+            allowed_codes: Vec::new(),
+            correlation_id: false,
+        }
+    }
+}
+
+fn default_masked_message() -> String {
+    "Unexpected error".to_string()
+}
@@ -140,6 +140,18 @@ pub enum RequestHeaderRule {
     /// - For **never-join** headers (e.g. `set-cookie`): **appends** another
     ///   occurrence (multiple lines), never comma-joins.
     Insert(RequestInsertRule),
+
+    /// Rename headers already set on the request to a subgraph,
+    /// by a previous `propagate` or `insert`.
+    Rename(RenameRule),
+
+    /// Apply the nested rules only to the requests matching the conditions,
+    /// so sensitive headers are not forwarded to every subgraph.
+    When(ConditionalHeaderRules<RequestHeaderRule>),
+
+    /// Forward selected cookies of the client request to subgraphs,
+    /// in the `cookie` header or as a header of their own.
+    PropagateCookies(PropagateCookiesRule),
 }
 
 /// Response-header rules (applied before sending back to the client).
@@ -164,6 +176,13 @@ pub enum ResponseHeaderRule {
     ///
     /// For never-join headers, appends another occurrence (multiple lines).
     Insert(ResponseInsertRule),
+
+    /// Rename headers already set on the response to the client,
+    /// by a previous `propagate` or `insert`.
+    Rename(RenameRule),
+
+    /// Apply the nested rules only to the responses of the requests matching the conditions.
+    When(ConditionalHeaderRules<ResponseHeaderRule>),
 }
 
 /// Remove headers matched by the specification.
@@ -173,6 +192,134 @@ pub struct RemoveRule {
     pub spec: MatchSpec,
 }
 
+/// Forward cookies from the client request to subgraph requests.
+///
+/// - Without `header`, the cookies are forwarded in the `cookie` header,
+///   after the cookies already set by previous rules.
+/// - With `header`, the value of the cookie is forwarded under that header.
+///   Only allowed when a single cookie is named.
+///
+/// ### Examples
+/// ```yaml
+/// # Forward only the session and locale cookies, not the other cookies of the client
+/// propagate_cookies:
+///   named: [session, locale]
+///
+/// # Forward the session cookie as a header
+/// propagate_cookies:
+///   named: session
+///   header: x-session-id
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct PropagateCookiesRule {
+    /// Names of the cookies to forward (case-sensitive, OR).
+    pub named: OneOrMany<String>,
+
+    /// Forward the value of the cookie under this header, instead of in the `cookie` header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<HeaderName>,
+}
+
+/// Rules applied only when all the conditions are met.
+///
+/// ### Examples
+/// ```yaml
+/// # Forward the CSRF token only with the mutations sent to the accounts subgraph
+/// - when:
+///     subgraphs: [accounts]
+///     operation_types: [mutation]
+///     rules:
+///       - propagate:
+///           named: x-csrf-token
+///
+/// # Forward the admin token only with the admin operations
+/// - when:
+///     operation_name: "^Admin.*"
+///     rules:
+///       - propagate:
+///           named: x-admin-token
+///
+/// # Forward the session only with the requests of the web client
+/// - when:
+///     client_name: "^web$"
+///     rules:
+///       - propagate_cookies:
+///           named: session
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct ConditionalHeaderRules<R> {
+    #[serde(flatten)]
+    pub condition: HeaderRuleCondition,
+    /// The rules applied when the conditions are met, in order.
+    pub rules: Vec<R>,
+}
+
+/// The conditions of [`ConditionalHeaderRules`]. Omitted conditions always match.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+pub struct HeaderRuleCondition {
+    /// Only for the requests sent to these subgraphs, and their responses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subgraphs: Option<Vec<String>>,
+
+    /// Only for these types of operations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_types: Option<Vec<HeaderRuleOperationType>>,
+
+    /// Only for the operations whose name matches this regex.
+    /// Anonymous operations never match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<RegExp>,
+
+    /// Only for the clients whose name, as identified with `telemetry.client_identification`,
+    /// matches this regex. Anonymous clients never match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<RegExp>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderRuleOperationType {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl HeaderRuleOperationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Mutation => "mutation",
+            Self::Subscription => "subscription",
+        }
+    }
+}
+
+/// Rename headers matched by the specification.
+///
+/// The values of all the matched headers are kept under the new name.
+///
+/// ### Examples
+/// ```yaml
+/// # Forward the authorization of the client as x-forwarded-auth
+/// - propagate:
+///     named: authorization
+/// - rename:
+///     named: authorization
+///     to: x-forwarded-auth
+///
+/// # Collect the deprecated tenant headers under a single one
+/// - rename:
+///     matching: "^x-acme-tenant-.*"
+///     to: x-tenant
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct RenameRule {
+    #[serde(flatten)]
+    pub spec: MatchSpec,
+    /// The new name of the matched headers.
+    pub to: HeaderName,
+}
+
 /// Insert a header with a static value.
 ///
 /// ### Examples
@@ -238,6 +385,11 @@ pub enum InsertSource {
     /// The expression has access to a context object with `.request`, `.subgraph`,
     /// and `.response.headers` fields.
     ///
+    /// The `.request` object holds the `headers`, `method`, `url` and `path_params` of the request,
+    /// its GraphQL `operation` (`name`, `type` and `query`), its `jwt` (`authenticated`, `claims`, `scopes`)
+    /// and the `context` values exposed by plugins.
+    /// The expressions are compiled when the configuration is loaded.
+    ///
     /// For more information on the available functions and syntax, see the
     /// [VRL documentation](https://vrl.dev/).
     ///
@@ -247,6 +399,16 @@ pub enum InsertSource {
     /// - insert:
     ///     name: x-auth-scheme
     ///     expression: 'split(.request.headers.authorization, " ")[0] ?? "none"'
+    ///
+    /// # Forward the subject of the JWT of the client.
+    /// - insert:
+    ///     name: x-user-id
+    ///     expression: .request.jwt.claims.sub
+    ///
+    /// # Forward the client, as identified with `telemetry.client_identification`.
+    /// - insert:
+    ///     name: x-client
+    ///     expression: '(string(.request.client.name) ?? "anonymous") + "@" + (string(.request.client.version) ?? "unknown")'
     /// ```
     Expression { expression: String },
 }
@@ -266,7 +428,7 @@ pub enum OneOrMany<T> {
     Many(Vec<T>),
 }
 
-/// Header matching specification used by `propagate` and `remove`.
+/// Header matching specification used by `propagate`, `remove` and `rename`.
 ///
 /// **Semantics**
 /// - `named`: match by exact name(s), case-insensitive (OR).
@@ -359,6 +521,8 @@ pub enum AggregationAlgo {
     /// field. For never-join headers (e.g. `Set-Cookie`) they are emitted as
     /// separate header fields.
     ///
+    /// Can also be written as `comma_join`.
+    ///
     /// **`cache-control` special case:** Instead of comma-joining, the router
     /// applies a restrictive merge across all subgraph values:
     /// - `no-store`, `no-cache`, or `private` from any subgraph poisons the result.
@@ -374,7 +538,14 @@ pub enum AggregationAlgo {
     ///
     /// If no subgraph sends `Cache-Control` and no `default` is configured,
     /// the router leaves the header absent from the client response.
+    #[serde(alias = "comma_join")]
     Append,
+    /// Collect all values and emit each of them as a separate header field,
+    /// as for never-join headers, instead of comma-joining them.
+    ///
+    /// Useful for headers whose values can not be safely comma-joined,
+    /// like custom headers carrying dates.
+    Multiple,
 }
 
 /// Propagate headers from subgraph responses to the final client response.
@@ -399,6 +570,11 @@ pub enum AggregationAlgo {
 ///   named: vary
 ///   algorithm: append
 ///
+/// # Keep the value of every subgraph in its own header field
+/// propagate:
+///   named: x-served-at
+///   algorithm: multiple
+///
 /// # Ensure a fallback header is always present
 /// propagate:
 ///   named: x-backend
@@ -0,0 +1,41 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Hooks written as VRL programs, for lightweight logic that doesn't deserve a plugin.
+///
+/// A program reads and modifies `.request.headers`, keeps values for the following hooks
+/// of the request in `.context`, and rejects the request by setting `.reject`.
+///
+/// Example:
+/// ```yaml
+/// hooks:
+///   on_http_request: |
+///     if .request.headers."x-api-key" == null {
+///       .reject = { "status": 401, "message": "Missing API key" }
+///     }
+///     .context.tenant = .request.headers."x-tenant"
+///   on_subgraph_request: |
+///     .request.headers."x-tenant" = .context.tenant
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Runs when the router receives an HTTP request, before it's processed.
+    ///
+    /// The program has access to `.request.method`, `.request.url`, `.request.headers` and `.context`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_http_request: Option<String>,
+
+    /// Runs before each HTTP request sent to a subgraph.
+    ///
+    /// The program has access to `.subgraph.name`, `.request.method`, `.request.url`,
+    /// `.request.headers` of the subgraph request, and `.context`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_subgraph_request: Option<String>,
+}
+
+impl HooksConfig {
+    pub fn is_empty(&self) -> bool {
+        self.on_http_request.is_none() && self.on_subgraph_request.is_none()
+    }
+}
@@ -0,0 +1,150 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::HiveRouterConfig;
+
+/// The sections and fields of the config re-applied at runtime when the config is reloaded.
+const RELOADABLE_FIELDS: [&str; 6] = [
+    "headers",
+    "traffic_shaping",
+    "cors",
+    "limits",
+    "log.level",
+    "log.filter",
+];
+
+/// The fields of the reloadable sections only applied when the router starts,
+/// as they configure the HTTP server itself.
+const NON_RELOADABLE_FIELDS: [&str; 3] = [
+    "traffic_shaping.router.tls",
+    "traffic_shaping.router.request_timeout",
+    "traffic_shaping.router.max_long_lived_clients",
+];
+
+/// Configuration of the reload of the router config at runtime.
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HotReloadConfig {
+    /// Watches the config file and re-applies it when it changes, without restarting the router.
+    ///
+    /// Only the `headers`, `traffic_shaping`, `cors` and `limits` sections,
+    /// and the `log.level` and `log.filter` fields are re-applied.
+    /// The TLS, request timeout and long-lived clients limit of `traffic_shaping.router`
+    /// configure the HTTP server and are only applied when the router starts.
+    ///
+    /// A changed config changing any other field is rejected as a whole,
+    /// and the router keeps running with its current config.
+    ///
+    /// Disabled by default.
+    #[serde(default)]
+    pub watch: bool,
+}
+
+/// The dotted paths of the fields whose values differ between two configs, as in `cors.max_age`.
+/// Lists are compared as a whole.
+pub fn changed_fields(
+    current: &HiveRouterConfig,
+    new: &HiveRouterConfig,
+) -> Result<Vec<String>, serde_json::Error> {
+    let mut changed = Vec::new();
+    diff_values(
+        "",
+        &serde_json::to_value(current)?,
+        &serde_json::to_value(new)?,
+        &mut changed,
+    );
+    Ok(changed)
+}
+
+/// Whether a changed field, as returned by [`changed_fields`], is re-applied at runtime.
+pub fn is_reloadable_field(path: &str) -> bool {
+    let within = |field: &&str| {
+        path == *field
+            || path
+                .strip_prefix(*field)
+                .is_some_and(|rest| rest.starts_with('.'))
+    };
+    RELOADABLE_FIELDS.iter().any(within) && !NON_RELOADABLE_FIELDS.iter().any(within)
+}
+
+fn diff_values(path: &str, current: &Value, new: &Value, changed: &mut Vec<String>) {
+    match (current, new) {
+        (Value::Object(current), Value::Object(new)) => {
+            let mut keys: Vec<&String> = current.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff_values(
+                    &field,
+                    current.get(key).unwrap_or(&Value::Null),
+                    new.get(key).unwrap_or(&Value::Null),
+                    changed,
+                );
+            }
+        }
+        (current, new) if current != new => changed.push(path.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_yaml_config;
+
+    use super::{changed_fields, is_reloadable_field};
+
+    #[test]
+    fn lists_the_changed_fields() {
+        let current = parse_yaml_config(
+            r#"
+            limits:
+                max_depth:
+                    n: 10
+            "#
+            .to_string(),
+        )
+        .unwrap();
+        let new = parse_yaml_config(
+            r#"
+            limits:
+                max_depth:
+                    n: 20
+                max_aliases:
+                    n: 5
+            http:
+                port: 5000
+            "#
+            .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            changed_fields(&current, &new).unwrap(),
+            vec!["http.port", "limits.max_aliases", "limits.max_depth.n"]
+        );
+        assert!(changed_fields(&current, &current).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_reloads_the_hot_safe_fields() {
+        assert!(is_reloadable_field("cors.origins"));
+        assert!(is_reloadable_field("headers"));
+        assert!(is_reloadable_field("log.level"));
+        assert!(is_reloadable_field("traffic_shaping.router.dedupe.enabled"));
+        assert!(is_reloadable_field("traffic_shaping.all.request_timeout"));
+
+        assert!(!is_reloadable_field("http.port"));
+        assert!(!is_reloadable_field("log.format"));
+        assert!(!is_reloadable_field("corsets"));
+        assert!(!is_reloadable_field("traffic_shaping.router.tls.cert_file"));
+        assert!(!is_reloadable_field(
+            "traffic_shaping.router.request_timeout"
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use std::num::NonZeroUsize;
+use std::{collections::BTreeMap, net::SocketAddr, num::NonZeroUsize, time::Duration};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -24,6 +24,29 @@ pub struct HttpServerConfig {
     #[serde(default = "http_server_port_default")]
     pub port: u16,
 
+    /// The IP addresses and ports the HTTP server listens on, in addition to `host` and `port`,
+    /// to serve the router on several interfaces.
+    ///
+    /// The TLS config of `traffic_shaping.router.tls` applies to every address.
+    ///
+    /// Example: `["127.0.0.1:4001", "[::1]:4001"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_listen: Vec<SocketAddr>,
+
+    /// The IP address and port of a dedicated HTTP server for the `/health` and `/readiness` probes,
+    /// to keep them apart from the GraphQL traffic.
+    /// When not set, the probes are served by the main server.
+    ///
+    /// Example: `0.0.0.0:8088`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probes_listen: Option<SocketAddr>,
+
+    /// Checks of the dependencies of the router, for `/readiness` to fail while one of them is unavailable.
+    ///
+    /// By default, `/readiness` only checks that a supergraph is loaded.
+    #[serde(default)]
+    pub readiness: ReadinessConfig,
+
     /// The number of worker threads to use for the HTTP server. Must be at least `1`.
     ///
     /// Defaults to the number of physical CPU cores available to the process.
@@ -36,6 +59,175 @@ pub struct HttpServerConfig {
     /// Can also be set via the `ROUTER_HTTP_WORKERS` environment variable.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workers: Option<NonZeroUsize>,
+
+    /// Configuration of GraphQL requests sent with the `GET` method,
+    /// passing `query`, `operationName`, `variables` and `extensions` as query parameters.
+    ///
+    /// Only queries can be executed over `GET`, mutations are rejected with a `405` status.
+    #[serde(default)]
+    pub get_requests: GetRequestsConfig,
+
+    /// Computes a strong `ETag`, a hash of the response body, for the responses of queries
+    /// sent over `GET` or through Automatic Persisted Queries, and answers the requests
+    /// whose `If-None-Match` header holds the current `ETag` with a `304 Not Modified`
+    /// status and no body.
+    ///
+    /// Saves bandwidth for clients polling queries whose results rarely change.
+    ///
+    /// Disabled by default.
+    #[serde(default)]
+    pub etag: bool,
+
+    /// How long the router drains its in-flight requests when it receives `SIGTERM` or `SIGINT`,
+    /// before it closes their connections and exits.
+    ///
+    /// Once the signal is received, the router stops accepting new connections,
+    /// and `/readiness` responds with a `503` status.
+    /// The subscriptions still active once the timeout expired are completed with an error,
+    /// for their clients to subscribe again to another instance.
+    /// The plugin `on_shutdown` hooks run and the telemetry is flushed once the requests are drained.
+    ///
+    /// A second signal stops the router right away.
+    ///
+    /// Default: 30s
+    #[serde(
+        default = "drain_timeout_default",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub drain_timeout: Duration,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GetRequestsConfig {
+    /// Enables/disables GraphQL requests over `GET`. Enabled by default.
+    #[serde(default = "get_requests_enabled_default")]
+    pub enabled: bool,
+
+    /// Only accepts `GET` requests identifying the operation by a hash or an id,
+    /// through Automatic Persisted Queries (`extensions.persistedQuery.sha256Hash`)
+    /// or persisted documents, and rejects the ones sending the operation in `query`.
+    ///
+    /// Useful when `GET` responses are cached by a CDN, to only cache known operations.
+    /// Operations can still be sent, or registered, over `POST`.
+    ///
+    /// Disabled by default.
+    #[serde(default)]
+    pub persisted_operations_only: bool,
+}
+
+/// The dependencies checked by `/readiness`, in addition to the supergraph.
+///
+/// The checks run in the background, every `interval`,
+/// and `/readiness` responds with their last outcome, without reaching the dependencies itself.
+/// The router is not ready until the checks ran once.
+///
+/// ```yaml
+/// http:
+///   readiness:
+///     jwks: true
+///     redis: true
+///     subgraphs:
+///       accounts: {}
+///       products:
+///         url: http://products:4002/health
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ReadinessConfig {
+    /// Requires the keys of every JWKS provider of `jwt` to be loaded.
+    ///
+    /// Disabled by default.
+    #[serde(default)]
+    pub jwks: bool,
+
+    /// Requires the Redis servers used by the router to respond to a `PING`:
+    /// the ones of `apq`, `response_cache`, `rate_limit`, `demand_control.client_budgets`
+    /// and `plugin_storage`, when they are configured with the `redis` backend.
+    ///
+    /// Disabled by default.
+    #[serde(default)]
+    pub redis: bool,
+
+    /// The subgraphs required to respond to a health ping, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub subgraphs: BTreeMap<String, SubgraphReadinessConfig>,
+
+    /// How often the checks run.
+    ///
+    /// Default: 10s
+    #[serde(
+        default = "readiness_interval_default",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub interval: Duration,
+
+    /// How long a check waits for a dependency to respond before it fails.
+    ///
+    /// Default: 2s
+    #[serde(
+        default = "readiness_timeout_default",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub timeout: Duration,
+}
+
+impl ReadinessConfig {
+    /// Returns true when a dependency is checked.
+    pub fn has_checks(&self) -> bool {
+        self.jwks || self.redis || !self.subgraphs.is_empty()
+    }
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self {
+            jwks: false,
+            redis: false,
+            subgraphs: BTreeMap::new(),
+            interval: readiness_interval_default(),
+            timeout: readiness_timeout_default(),
+        }
+    }
+}
+
+fn readiness_interval_default() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn readiness_timeout_default() -> Duration {
+    Duration::from_secs(2)
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SubgraphReadinessConfig {
+    /// The URL of the health endpoint of the subgraph, requested with `GET`.
+    /// The subgraph is healthy when it responds with a `2xx` status.
+    ///
+    /// When not set, a `{ __typename }` query is sent to the URL of the subgraph,
+    /// the one of `override_subgraph_urls` when it is a static URL, or the one of the supergraph.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl Default for GetRequestsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: get_requests_enabled_default(),
+            persisted_operations_only: false,
+        }
+    }
+}
+
+fn get_requests_enabled_default() -> bool {
+    true
 }
 
 impl Default for HttpServerConfig {
@@ -43,8 +235,14 @@ impl Default for HttpServerConfig {
         Self {
             host: http_server_host_default(),
             port: http_server_port_default(),
+            additional_listen: Vec::new(),
+            probes_listen: None,
+            readiness: ReadinessConfig::default(),
             graphql_endpoint: graphql_endpoint_default(),
             workers: None,
+            get_requests: GetRequestsConfig::default(),
+            etag: false,
+            drain_timeout: drain_timeout_default(),
         }
     }
 }
@@ -60,3 +258,7 @@ fn graphql_endpoint_default() -> String {
 fn http_server_port_default() -> u16 {
     4000
 }
+
+fn drain_timeout_default() -> Duration {
+    Duration::from_secs(30)
+}
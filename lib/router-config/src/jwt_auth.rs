@@ -12,8 +12,12 @@ pub struct JwtAuthConfig {
     #[serde(default = "default_enabled")]
     pub enabled: bool,
     /// A list of JWKS providers to use for verifying the JWT signature.
-    /// Can be either a path to a local JSON of the file-system, or a URL to a remote JWKS provider.
-    pub jwks_providers: Vec<JwksProviderSourceConfig>,
+    /// Can be either a path to a local JSON of the file-system, a URL to a remote JWKS provider,
+    /// or an OpenID Connect issuer whose JWKS is discovered automatically.
+    ///
+    /// Every provider can restrict the issuers, audiences and algorithms of the tokens verified by its keys,
+    /// to accept the tokens of several identity providers at once.
+    pub jwks_providers: Vec<JwksProviderConfig>,
     /// Specify the [principal](https://tools.ietf.org/html/rfc7519#section-4.1.1) that issued the JWT, usually a URL or an email address.
     /// If specified, it has to match the `iss` field in JWT, otherwise the token's `iss` field is not checked.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -43,6 +47,13 @@ pub struct JwtAuthConfig {
     #[serde(default = "default_forward_claims_to_upstream_extensions")]
     /// Forward the JWT claims to the upstream service using GraphQL's `.extensions`.
     pub forward_claims_to_upstream_extensions: JwtClaimsForwardingConfig,
+    /// Forward the claims of the validated JWT to the subgraphs as headers,
+    /// so the subgraphs don't need to validate the token again.
+    ///
+    /// The headers are set after the `headers` rules, and removed from the requests without a valid JWT,
+    /// so a client can not send them itself.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forward_claims_to_upstream_headers: Vec<JwtClaimHeaderConfig>,
 }
 
 impl JwtAuthConfig {
@@ -74,6 +85,7 @@ impl Default for JwtAuthConfig {
             audiences: None,
             issuers: None,
             allowed_algorithms: None,
+            forward_claims_to_upstream_headers: vec![],
         }
     }
 }
@@ -84,6 +96,52 @@ pub struct JwtClaimsForwardingConfig {
     pub field_name: String,
 }
 
+/// A header sent to the subgraphs with the claims of the JWT.
+///
+/// ### Examples
+/// ```yaml
+/// forward_claims_to_upstream_headers:
+///   # The subject of the token
+///   - name: x-user-id
+///     claim: sub
+///   # All the claims, as base64-encoded JSON
+///   - name: x-jwt-claims
+///     claims: base64
+///   # A value computed from the claims
+///   - name: x-user-role
+///     expression: '.request.jwt.claims.roles[0] ?? "viewer"'
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct JwtClaimHeaderConfig {
+    /// The name of the header sent to the subgraphs.
+    pub name: String,
+    #[serde(flatten)]
+    pub source: JwtClaimHeaderSource,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(untagged)]
+pub enum JwtClaimHeaderSource {
+    /// The value of a single claim, e.g. `sub`.
+    /// Strings are sent as they are, other values as JSON.
+    /// The header is not sent when the token doesn't have the claim.
+    Claim { claim: String },
+    /// All the claims of the token, encoded as JSON.
+    Claims { claims: JwtClaimsHeaderEncoding },
+    /// A value computed by a VRL expression, with the same context as the expressions of the `headers` rules,
+    /// so the claims are available as `.request.jwt.claims`.
+    Expression { expression: String },
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum JwtClaimsHeaderEncoding {
+    /// The JSON of the claims, encoded with the standard base64 alphabet.
+    Base64,
+    /// The JSON of the claims as it is. Claims with non-ASCII characters can not be sent as a header.
+    Json,
+}
+
 fn default_forward_claims_to_upstream_extensions() -> JwtClaimsForwardingConfig {
     JwtClaimsForwardingConfig {
         enabled: false,
@@ -91,6 +149,35 @@ fn default_forward_claims_to_upstream_extensions() -> JwtClaimsForwardingConfig
     }
 }
 
+/// ### Examples
+/// ```yaml
+/// jwks_providers:
+///   - source: oidc
+///     issuer: https://accounts.google.com
+///     audiences: [my-client-id]
+///   - source: remote
+///     url: https://auth.example.com/.well-known/jwks.json
+///     issuers: [https://auth.example.com]
+///     allowed_algorithms: [RS256]
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct JwksProviderConfig {
+    #[serde(flatten)]
+    pub source: JwksProviderSourceConfig,
+    /// The issuers of the tokens verified by the keys of this provider, replacing `jwt.issuers` for these tokens.
+    /// For `oidc` providers, defaults to the issuer of the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuers: Option<Vec<String>>,
+    /// The audiences of the tokens verified by the keys of this provider, replacing `jwt.audiences` for these tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audiences: Option<Vec<String>>,
+    /// The algorithms of the tokens verified by the keys of this provider, on top of `jwt.allowed_algorithms`.
+    /// For `oidc` providers, defaults to the `id_token_signing_alg_values_supported` of the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<Vec<String>>")]
+    pub allowed_algorithms: Option<Vec<Algorithm>>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
 #[serde(tag = "source")]
 pub enum JwksProviderSourceConfig {
@@ -120,6 +207,26 @@ pub enum JwksProviderSourceConfig {
         /// If set to `false`, the JWKS will be fetched on-demand, when the first request comes in.
         prefetch: Option<bool>,
     },
+    /// An OpenID Connect provider. The JWKS URL, the issuer and the supported algorithms are read from
+    /// the discovery document of the issuer (`/.well-known/openid-configuration`), and refreshed in the background.
+    #[serde(rename = "oidc")]
+    #[schemars(title = "oidc")]
+    Oidc {
+        /// The URL of the issuer, e.g. `https://accounts.google.com`.
+        /// It has to match the `issuer` of the discovery document.
+        issuer: String,
+        #[serde(
+            deserialize_with = "humantime_serde::deserialize",
+            serialize_with = "humantime_serde::serialize",
+            default = "default_polling_interval"
+        )]
+        #[schemars(with = "String")]
+        /// How often the discovery document and the JWKS should be polled for updates.
+        polling_interval: Option<Duration>,
+        /// If set to `true`, the discovery document and the JWKS will be fetched on startup.
+        /// If set to `false`, they will be fetched on-demand, when the first request comes in.
+        prefetch: Option<bool>,
+    },
 }
 
 fn default_polling_interval() -> Option<Duration> {
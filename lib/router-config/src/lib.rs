@@ -1,48 +1,68 @@
+pub mod access_log;
+pub mod admin;
+pub mod apq;
 pub mod authorization;
+pub mod contract;
 pub mod coprocessor;
 pub mod cors;
 pub mod csrf;
 pub mod demand_control;
+pub mod entity_cache;
+mod env_interpolation;
 mod env_overrides;
+pub mod errors;
 pub mod headers;
+pub mod hooks;
+pub mod hot_reload;
 pub mod http_server;
 pub mod introspection_policy;
 pub mod jwt_auth;
 pub mod laboratory;
 pub mod limits;
 pub mod log;
+pub mod mcp;
 pub mod override_labels;
 pub mod override_subgraph_urls;
 pub mod persisted_documents;
+pub mod plugin_storage;
 pub mod primitives;
+pub mod progressive_override;
 pub mod query_planner;
+pub mod rate_limit;
+mod relative_paths;
+pub mod request_id;
+pub mod response_cache;
 pub mod response_extensions;
+pub mod rhai;
 pub mod storage;
 pub mod subscriptions;
 pub mod supergraph;
 pub mod telemetry;
 pub mod traffic_shaping;
 pub mod usage_reporting;
+pub mod wasm_plugins;
 pub mod websocket;
 
-use config::{Config, File, FileFormat, FileSourceFile};
+use config::{builder::DefaultState, Config, ConfigBuilder, File, FileFormat, FileSourceFile};
 use envconfig::Envconfig;
 pub use humantime_serde;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{collections::HashMap, convert::Infallible};
 
 use crate::storage::StorageConfigMap;
 use crate::{
+    env_interpolation::{interpolate_env_vars, EnvVarInterpolationError},
     env_overrides::{EnvVarOverrides, EnvVarOverridesError},
     http_server::HttpServerConfig,
     introspection_policy::IntrospectionPermissionConfig,
     laboratory::LaboratoryConfig,
     log::LoggingConfig,
     override_labels::OverrideLabelsConfig,
-    primitives::file_path::with_start_path,
+    primitives::{file_path::with_start_path, single_or_multiple::SingleOrMultiple},
     query_planner::QueryPlannerConfig,
+    relative_paths::resolve_extended_file_paths,
     supergraph::SupergraphSource,
     traffic_shaping::TrafficShapingConfig,
 };
@@ -53,12 +73,36 @@ pub struct HiveRouterConfig {
     #[serde(skip)]
     root_directory: PathBuf,
 
+    /// The config files this config is overlaid on, as in a `router.prod.yaml` extending a base `router.config.yaml`.
+    ///
+    /// The paths are relative to the directory of this config file.
+    /// The files are merged in order, each one overriding the previous ones, and this config overrides them all.
+    /// The maps (objects) are merged key by key, while the lists and the other values are replaced as a whole.
+    /// A file can extend other files in turn, but not one of the files extending it.
+    ///
+    /// Example:
+    /// ```yaml
+    /// extends: router.config.yaml
+    /// log:
+    ///   level: warn
+    /// ```
+    #[serde(default, skip_serializing)]
+    pub extends: Option<SingleOrMultiple<String>>,
+
     /// The router logger configuration.
     ///
     /// The router is configured to be mostly silent (`info`) level, and will print only important messages, warnings, and errors.
     #[serde(default)]
     pub log: LoggingConfig,
 
+    /// The access log of the router, one line per request to the GraphQL endpoint.
+    #[serde(default)]
+    pub access_log: access_log::AccessLogConfig,
+
+    /// The ID given to every request to the GraphQL endpoint, for its logs, traces and subgraph requests to be correlated.
+    #[serde(default)]
+    pub request_id: request_id::RequestIdConfig,
+
     /// Configuration for the Hive Laboratory interface.
     #[serde(default)]
     pub laboratory: LaboratoryConfig,
@@ -89,6 +133,18 @@ pub struct HiveRouterConfig {
     #[serde(default)]
     pub response_extensions: response_extensions::ResponseExtensionsConfig,
 
+    /// Configuration of the errors exposed to the clients.
+    #[serde(default)]
+    pub errors: errors::ErrorsConfig,
+
+    /// Configuration for the response cache.
+    #[serde(default)]
+    pub response_cache: response_cache::ResponseCacheConfig,
+
+    /// Configuration for the entity cache.
+    #[serde(default)]
+    pub entity_cache: entity_cache::EntityCacheConfig,
+
     /// Configuration for CSRF prevention.
     #[serde(default)]
     pub csrf: csrf::CSRFPreventionConfig,
@@ -112,6 +168,10 @@ pub struct HiveRouterConfig {
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub override_labels: OverrideLabelsConfig,
 
+    /// Configuration for progressive overrides.
+    #[serde(default)]
+    pub progressive_override: progressive_override::ProgressiveOverrideConfig,
+
     #[serde(default)]
     pub authorization: authorization::AuthorizationConfig,
 
@@ -133,6 +193,24 @@ pub struct HiveRouterConfig {
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub plugins: HashMap<String, PluginConfig>,
 
+    /// The key-value storage offered to the plugins, e.g. to share state between router instances.
+    #[serde(default)]
+    pub plugin_storage: plugin_storage::PluginStorageConfig,
+
+    /// WebAssembly plugins, loaded from `.wasm` modules at startup.
+    /// They run after the plugins of the registry, in the order they are listed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wasm_plugins: Vec<wasm_plugins::WasmPluginConfig>,
+
+    /// Hooks written as VRL programs.
+    /// They run after the plugins of the registry, before the WebAssembly plugins.
+    #[serde(default, skip_serializing_if = "hooks::HooksConfig::is_empty")]
+    pub hooks: hooks::HooksConfig,
+
+    /// Rhai scripts, run after the VRL hooks, before the WebAssembly plugins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rhai: Option<rhai::RhaiConfig>,
+
     /// Configuration for subscriptions.
     #[serde(default)]
     pub subscriptions: subscriptions::SubscriptionsConfig,
@@ -145,10 +223,35 @@ pub struct HiveRouterConfig {
     #[serde(default)]
     pub persisted_documents: persisted_documents::PersistedDocumentsConfig,
 
+    /// Configuration for Automatic Persisted Queries (APQ).
+    #[serde(default)]
+    pub apq: apq::ApqConfig,
+
+    /// Configuration for the rate limiting of the clients, keyed by a configurable identity.
+    #[serde(default)]
+    pub rate_limit: rate_limit::RateLimitConfig,
+
     /// Configuration for coprocessor.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub coprocessor: Option<coprocessor::CoprocessorConfig>,
 
+    /// Configuration of a contract, exposing a variant of the supergraph filtered by `@tag`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<contract::ContractConfig>,
+
+    /// Configuration of the administration endpoints of the router.
+    #[serde(default)]
+    pub admin: admin::AdminConfig,
+
+    /// Configuration of the Model Context Protocol (MCP) server,
+    /// exposing GraphQL operations as tools to AI agents.
+    #[serde(default)]
+    pub mcp: mcp::McpConfig,
+
+    /// Configuration of the reload of this config at runtime.
+    #[serde(default)]
+    pub hot_reload: hot_reload::HotReloadConfig,
+
     /// Configuration for storage sources.
     ///
     /// Each key is a unique identifier for the storage source, that can later be references in other parts of the config file.
@@ -232,6 +335,32 @@ impl HiveRouterConfig {
     pub fn callback_conf(&self) -> Option<&subscriptions::CallbackConfig> {
         self.subscriptions.callback.as_ref()
     }
+
+    /// The path of the MCP endpoint, when it is served by the main server.
+    pub fn mcp_path(&self) -> Option<&str> {
+        (self.mcp.enabled && self.mcp.listen.is_none()).then_some(self.mcp.path.as_str())
+    }
+
+    pub fn admin_supergraph_path(&self) -> Option<&str> {
+        self.admin
+            .supergraph
+            .enabled
+            .then_some(self.admin.supergraph.path.as_str())
+    }
+
+    pub fn admin_diagnostics_path(&self) -> Option<&str> {
+        self.admin
+            .diagnostics
+            .enabled
+            .then_some(self.admin.diagnostics.path.as_str())
+    }
+
+    pub fn admin_cache_path(&self) -> Option<&str> {
+        self.admin
+            .cache
+            .enabled
+            .then_some(self.admin.cache.path.as_str())
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -242,10 +371,14 @@ pub enum RouterConfigError {
     EnvVarOverridesError(#[from] EnvVarOverridesError),
     #[error("Failed to load the environment variables: {0}")]
     EnvVarLoadError(#[from] envconfig::Error),
+    #[error("Failed to interpolate the environment variables: {0}")]
+    EnvVarInterpolationError(#[from] EnvVarInterpolationError),
     #[error("Failed to get the current directory: {0}")]
     CurrentDirError(std::io::Error),
     #[error("Failed to parse the configuration file path: {0}")]
     ConfigPathParseError(Infallible),
+    #[error("The configuration files extend each other in a cycle: {0}")]
+    ConfigExtendsCycle(String),
 }
 
 static DEFAULT_FILE_NAMES: &[&str] = &[
@@ -259,6 +392,20 @@ fn get_current_dir() -> Result<PathBuf, RouterConfigError> {
     std::env::current_dir().map_err(RouterConfigError::CurrentDirError)
 }
 
+/// The path of the config file loaded by [`load_config`], if any.
+pub fn config_file_path(override_config_path: Option<&str>) -> Option<PathBuf> {
+    match override_config_path {
+        Some(path) => Some(PathBuf::from(path)),
+        None => {
+            let current_dir = get_current_dir().ok()?;
+            DEFAULT_FILE_NAMES
+                .iter()
+                .map(|name| current_dir.join(name))
+                .find(|path| path.is_file())
+        }
+    }
+}
+
 pub fn load_config(
     overide_config_path: Option<String>,
 ) -> Result<HiveRouterConfig, RouterConfigError> {
@@ -270,22 +417,23 @@ pub fn load_config(
         let path_buf = path_str
             .parse::<std::path::PathBuf>()
             .map_err(RouterConfigError::ConfigPathParseError)?;
-        let path_dupe = path_buf.clone();
-        let parent_dir = path_dupe.parent().unwrap();
-        let as_file: File<FileSourceFile, _> = path_buf.into();
-
-        config = config.add_source(as_file.required(true));
+        let parent_dir = path_buf.parent().unwrap();
         config_root_path = config_root_path.join(parent_dir);
+
+        config = add_config_file(config, &path_buf, &mut Vec::new())?;
     } else {
         for name in DEFAULT_FILE_NAMES {
-            config = config.add_source(File::with_name(name).required(false));
+            let path_buf = config_root_path.join(name);
+            if path_buf.is_file() {
+                config = add_config_file(config, &path_buf, &mut Vec::new())?;
+            }
         }
     }
 
     config = env_overrides.apply_overrides(config)?;
 
     let mut base_cfg = with_start_path(&config_root_path, || {
-        config.build()?.try_deserialize::<HiveRouterConfig>()
+        deserialize_config(config.build()?, &config_root_path)
     })?;
 
     base_cfg.root_directory = config_root_path;
@@ -297,11 +445,95 @@ pub fn parse_yaml_config(config_raw: String) -> Result<HiveRouterConfig, RouterC
     let config_root_path = get_current_dir()?;
     let config = Config::builder();
 
-    with_start_path(&config_root_path, || {
-        config
+    let extends = read_extends(
+        Config::builder()
             .add_source(File::from_str(&config_raw, FileFormat::Yaml))
-            .build()?
-            .try_deserialize::<HiveRouterConfig>()
+            .build()?,
+    )?;
+    let config = add_extended_files(config, extends, &config_root_path, &mut Vec::new())?;
+
+    with_start_path(&config_root_path, || {
+        deserialize_config(
+            config
+                .add_source(File::from_str(&config_raw, FileFormat::Yaml))
+                .build()?,
+            &config_root_path,
+        )
     })
-    .map_err(RouterConfigError::ConfigLoadError)
+}
+
+/// Adds a config file as a source, after the files it extends.
+/// `chain` holds the files extending it, to detect the cycles.
+fn add_config_file(
+    mut config: ConfigBuilder<DefaultState>,
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<ConfigBuilder<DefaultState>, RouterConfigError> {
+    let canonical_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if chain.contains(&canonical_path) {
+        let cycle = chain
+            .iter()
+            .skip_while(|extending| **extending != canonical_path)
+            .chain(std::iter::once(&canonical_path))
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>();
+        return Err(RouterConfigError::ConfigExtendsCycle(cycle.join(" -> ")));
+    }
+
+    let file = || -> File<FileSourceFile, FileFormat> { path.to_path_buf().into() };
+    let extends = read_extends(
+        Config::builder()
+            .add_source(file().required(true))
+            .build()?,
+    )?;
+    if !extends.is_empty() {
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        chain.push(canonical_path);
+        config = add_extended_files(config, extends, base_dir, chain)?;
+        chain.pop();
+    }
+
+    Ok(config.add_source(file().required(true)))
+}
+
+fn add_extended_files(
+    mut config: ConfigBuilder<DefaultState>,
+    extends: Vec<String>,
+    base_dir: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<ConfigBuilder<DefaultState>, RouterConfigError> {
+    for extended in extends {
+        config = add_config_file(config, &base_dir.join(extended), chain)?;
+    }
+
+    Ok(config)
+}
+
+/// The `extends` field of a single config file, before it is merged with the others.
+fn read_extends(config: Config) -> Result<Vec<String>, config::ConfigError> {
+    #[derive(Deserialize)]
+    struct ExtendsOnly {
+        #[serde(default)]
+        extends: Option<SingleOrMultiple<String>>,
+    }
+
+    Ok(config
+        .try_deserialize::<ExtendsOnly>()?
+        .extends
+        .map(|extends| extends.values().to_vec())
+        .unwrap_or_default())
+}
+
+/// Deserializes the config once the `${ENV_VAR}` placeholders of its strings are expanded,
+/// and the relative file paths of the extended files are made relative to their own directory.
+fn deserialize_config(
+    config: Config,
+    root_dir: &Path,
+) -> Result<HiveRouterConfig, RouterConfigError> {
+    // the cache keeps the file each value comes from
+    let mut value = config.cache;
+    interpolate_env_vars(&mut value, &|name| std::env::var(name).ok())?;
+    resolve_extended_file_paths(&mut value, root_dir);
+
+    Ok(HiveRouterConfig::deserialize(value)?)
 }
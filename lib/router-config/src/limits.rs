@@ -1,3 +1,5 @@
+use std::{num::NonZeroUsize, time::Duration};
+
 use human_size::Size;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -32,9 +34,81 @@ pub struct LimitsConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_aliases: Option<MaxAliasesRuleConfig>,
 
+    /// Configuration of limiting the number of root fields in the incoming GraphQL operations.
+    /// If not specified, root field limiting is disabled.
+    ///
+    /// It is used to prevent operations batching too many root fields that could lead to overfetching or DOS attacks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_root_fields: Option<MaxRootFieldsRuleConfig>,
+
+    /// Configuration of limiting the nesting of the `fields`, `interfaces`, `possibleTypes` and `inputFields`
+    /// selections in the introspection queries.
+    /// If not specified, introspection depth limiting is disabled.
+    ///
+    /// The introspection query of GraphiQL and most other tools has a depth of 1,
+    /// deeper introspection queries are used to make the router resolve huge responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_introspection_depth: Option<MaxIntrospectionDepthRuleConfig>,
+
+    /// Configuration of limiting the nesting of brackets (selection sets, arguments, lists and objects)
+    /// while parsing the incoming GraphQL operations.
+    /// If not specified, the nesting is limited to 50 levels.
+    ///
+    /// It is used to reject deeply nested documents before they are parsed entirely.
+    /// Increasing it above the default is discouraged, as deeply nested documents may overflow the stack.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_recursion: Option<MaxRecursionConfig>,
+
+    /// Limits the size of the incoming GraphQL operation documents.
+    /// If not specified, document size limiting is disabled.
+    ///
+    /// Documents exceeding the limit are rejected before they are parsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub max_document_size: Option<Size>,
+
+    /// Limits the time spent parsing an incoming GraphQL operation document, for example `50ms`.
+    /// If not specified, parse time limiting is disabled.
+    ///
+    /// Documents taking longer to parse are rejected with a `PARSE_TIMEOUT_EXCEEDED` error.
+    #[serde(
+        default,
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<String>")]
+    pub max_parse_duration: Option<Duration>,
+
     #[serde(default = "default_max_request_body_size")]
     #[schemars(with = "String")]
     pub max_request_body_size: Size,
+
+    /// Limits the size of the serialized GraphQL responses.
+    /// If not specified, response size limiting is disabled.
+    ///
+    /// Responses exceeding the limit are replaced with a `RESPONSE_TOO_LARGE` error.
+    /// Incrementally delivered responses and subscription events are not limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub max_response_size: Option<Size>,
+
+    /// Limits the number of entity representations sent to a subgraph in a single request.
+    /// If not specified, all the entities of a fetch are sent in a single request.
+    ///
+    /// Entities exceeding the limit are split into multiple requests, sent one after another.
+    /// Batched entity fetches, resolving multiple fields in one request, are not split.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_entities_per_fetch: Option<NonZeroUsize>,
+
+    /// Limits the size of the serialized entity representations sent to a subgraph in a single request.
+    /// If not specified, representation size limiting is disabled.
+    ///
+    /// Representations exceeding the limit are split into multiple requests, sent one after another.
+    /// A single representation larger than the limit is still sent on its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub max_representations_size: Option<Size>,
 }
 
 impl Default for LimitsConfig {
@@ -44,7 +118,15 @@ impl Default for LimitsConfig {
             max_directives: None,
             max_tokens: None,
             max_aliases: None,
+            max_root_fields: None,
+            max_introspection_depth: None,
+            max_recursion: None,
+            max_document_size: None,
+            max_parse_duration: None,
             max_request_body_size: default_max_request_body_size(),
+            max_response_size: None,
+            max_entities_per_fetch: None,
+            max_representations_size: None,
         }
     }
 }
@@ -89,6 +171,24 @@ pub struct MaxAliasesRuleConfig {
     pub n: usize,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct MaxRootFieldsRuleConfig {
+    /// Root fields threshold
+    pub n: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct MaxIntrospectionDepthRuleConfig {
+    /// Introspection depth threshold
+    pub n: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct MaxRecursionConfig {
+    /// Nesting threshold
+    pub n: usize,
+}
+
 fn default_max_request_body_size() -> Size {
     "2MB".parse().expect(
         "Default value for 'limits.max_request_body_size' should be a valid human-readable size",
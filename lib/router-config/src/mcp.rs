@@ -0,0 +1,88 @@
+use std::net::SocketAddr;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::absolute_path::AbsolutePath;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct McpConfig {
+    /// Enables/disables the Model Context Protocol (MCP) server. By default, it is disabled.
+    ///
+    /// When enabled, the operations listed in `tools` are exposed as MCP tools,
+    /// over the Streamable HTTP transport, and executed like any other GraphQL request,
+    /// with the headers of the MCP request.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The path of the MCP endpoint. Defaults to `/mcp`.
+    #[serde(default = "default_mcp_path")]
+    pub path: AbsolutePath,
+
+    /// The IP address and port of a dedicated HTTP server for the MCP endpoint.
+    /// When not set, the endpoint is registered on the main server.
+    ///
+    /// Example: `0.0.0.0:4002`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen: Option<SocketAddr>,
+
+    /// The operations exposed as tools. Only these operations can be executed through MCP.
+    ///
+    /// The input schema of a tool is derived from the variable definitions of its operation.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// tools:
+    ///   - name: get_product
+    ///     description: Returns a product by its UPC.
+    ///     query: "query GetProduct($upc: String!) { product(upc: $upc) { upc name price } }"
+    ///   - name: top_products
+    ///     persisted_document_id: "my-app~1.0.0~a1b2c3"
+    /// ```
+    #[serde(default)]
+    pub tools: Vec<McpToolConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct McpToolConfig {
+    /// The name of the tool, unique among the tools.
+    pub name: String,
+    /// The description of the tool, helping the model decide when to call it.
+    /// Defaults to the GraphQL operation itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The operation executed by the tool.
+    #[serde(flatten)]
+    pub operation: McpToolOperation,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(untagged)]
+pub enum McpToolOperation {
+    /// A GraphQL document, and the name of the operation to execute,
+    /// when the document contains more than one operation.
+    Document {
+        query: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        operation_name: Option<String>,
+    },
+    /// The id of a persisted document, resolved from the `persisted_documents` storage.
+    PersistedDocument { persisted_document_id: String },
+}
+
+impl Default for McpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_mcp_path(),
+            listen: None,
+            tools: Vec::new(),
+        }
+    }
+}
+
+fn default_mcp_path() -> AbsolutePath {
+    AbsolutePath::try_from("/mcp").expect("default MCP path is valid")
+}
@@ -9,6 +9,10 @@ pub type OverrideLabelsConfig = HashMap<String, LabelOverrideValue>;
 ///
 /// It can be a simple boolean,
 /// or an object containing the expression that evaluates to a boolean.
+///
+/// A label set to `false` is never applied, even when enabled by a plugin or a coprocessor.
+/// Percentage labels, like `percent(25)`, can be forced on or off as well,
+/// regardless of the percentage of the request.
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(untagged)]
 pub enum LabelOverrideValue {
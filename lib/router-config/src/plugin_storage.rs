@@ -0,0 +1,99 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::value_or_expression::ValueOrExpression;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PluginStorageConfig {
+    /// The backend of the key-value storage offered to the plugins. Defaults to an in-memory cache.
+    ///
+    /// Every plugin has its own namespace, so plugins can not read or overwrite the keys of other plugins.
+    #[serde(default)]
+    pub backend: PluginStorageBackendConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, tag = "type")]
+pub enum PluginStorageBackendConfig {
+    /// Keeps the values in the memory of the router instance,
+    /// evicting the least recently used ones.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: memory
+    /// max_entries: 10000
+    /// ```
+    #[serde(rename = "memory")]
+    Memory {
+        /// The maximum number of values kept in the cache, across all plugins.
+        ///
+        /// Defaults to 10000.
+        #[serde(default = "default_max_entries")]
+        max_entries: u64,
+    },
+    /// Keeps the values in Redis, shared between router instances.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: redis
+    /// url: redis://localhost:6379
+    /// key_prefix: "hive-router:plugins:"
+    /// ```
+    #[serde(rename = "redis")]
+    Redis {
+        /// The connection URL of the Redis server, e.g. `redis://localhost:6379`.
+        url: ValueOrExpression<String>,
+        /// The prefix of every key written by the router, followed by the name of the plugin.
+        ///
+        /// Defaults to `hive-router:plugins:`.
+        #[serde(default = "default_key_prefix")]
+        key_prefix: String,
+    },
+}
+
+impl Default for PluginStorageBackendConfig {
+    fn default() -> Self {
+        Self::Memory {
+            max_entries: default_max_entries(),
+        }
+    }
+}
+
+fn default_max_entries() -> u64 {
+    10_000
+}
+
+fn default_key_prefix() -> String {
+    "hive-router:plugins:".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_memory_backend() {
+        let config = serde_json::from_str::<PluginStorageConfig>("{}").unwrap();
+        match config.backend {
+            PluginStorageBackendConfig::Memory { max_entries } => assert_eq!(max_entries, 10_000),
+            _ => panic!("expected the memory backend"),
+        }
+    }
+
+    #[test]
+    fn redis_backend() {
+        let config = serde_json::from_str::<PluginStorageConfig>(
+            r#"{ "backend": { "type": "redis", "url": "redis://localhost:6379" } }"#,
+        )
+        .unwrap();
+        match config.backend {
+            PluginStorageBackendConfig::Redis { key_prefix, .. } => {
+                assert_eq!(key_prefix, "hive-router:plugins:")
+            }
+            _ => panic!("expected the redis backend"),
+        }
+    }
+}
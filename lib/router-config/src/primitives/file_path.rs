@@ -11,8 +11,8 @@ use serde::{
 };
 
 #[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
 pub struct FilePath {
-    #[serde(flatten)]
     pub relative: String,
     #[serde(skip)]
     pub absolute: String,
@@ -4,6 +4,7 @@ pub mod http_header;
 pub mod ip_network;
 pub mod percentage;
 pub mod retry_policy;
+pub mod secret_ref;
 pub mod single_or_multiple;
 pub mod toggle;
 pub mod value_or_expression;
@@ -0,0 +1,169 @@
+use std::{fmt, fs};
+
+use schemars::JsonSchema;
+use serde::{ser::SerializeMap, Deserialize, Serialize, Serializer};
+
+use crate::primitives::file_path::FilePath;
+
+/// A reference to a sensitive value kept out of the config file, read when the config is loaded.
+///
+/// ### Examples
+/// ```yaml
+/// secret_ref:
+///   file: /var/run/secrets/hive/token
+/// ```
+/// ```yaml
+/// secret_ref:
+///   env: HIVE_TOKEN
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum SecretRef {
+    /// Reads the value from a file, like a Kubernetes or Docker secret mounted in the container,
+    /// or a secret rendered by a Vault agent.
+    ///
+    /// A relative path is relative to the directory of the config file.
+    /// The trailing line break of the file is not part of the value.
+    File(FilePath),
+    /// Reads the value from an environment variable.
+    Env(String),
+}
+
+impl SecretRef {
+    /// Reads the referenced value. The error never contains the value.
+    pub fn resolve(&self) -> Result<String, String> {
+        match self {
+            SecretRef::File(path) => {
+                let content = fs::read_to_string(&path.absolute).map_err(|err| {
+                    format!(
+                        "failed to read the secret file '{}': {}",
+                        path.relative, err
+                    )
+                })?;
+                let value = content
+                    .strip_suffix('\n')
+                    .map(|value| value.strip_suffix('\r').unwrap_or(value))
+                    .unwrap_or(&content);
+                Ok(value.to_string())
+            }
+            SecretRef::Env(name) => std::env::var(name).map_err(|err| {
+                format!("failed to read the secret environment variable '{name}': {err}")
+            }),
+        }
+    }
+}
+
+/// A value read from a [`SecretRef`].
+///
+/// The value is redacted from the debug output, and the reference is serialized instead of the value,
+/// so the secret does not end up in the logs, or in the fields listed as changed by a reload.
+#[derive(Clone)]
+pub struct Secret<T> {
+    reference: SecretRef,
+    value: T,
+}
+
+impl<T> Secret<T> {
+    pub fn new(reference: SecretRef, value: T) -> Self {
+        Self { reference, value }
+    }
+
+    pub fn reference(&self) -> &SecretRef {
+        &self.reference
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<secret>")
+    }
+}
+
+impl<T> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("secret_ref", &self.reference)?;
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tempfile::NamedTempFile;
+
+    use crate::primitives::value_or_expression::ValueOrExpression;
+
+    #[test]
+    fn reads_the_value_of_a_secret_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"redis://:s3cr3t@localhost:6379\n").unwrap();
+
+        let config = serde_json::from_value::<ValueOrExpression<String>>(serde_json::json!({
+            "secret_ref": { "file": file.path().to_string_lossy() }
+        }))
+        .unwrap();
+        assert!(matches!(
+            &config,
+            ValueOrExpression::Secret(secret) if secret.value() == "redis://:s3cr3t@localhost:6379"
+        ));
+    }
+
+    #[test]
+    fn redacts_the_value_of_a_secret() {
+        std::env::set_var("HIVE_ROUTER_SECRET_REF_REDACTED_TEST", "s3cr3t");
+
+        let config = serde_json::from_value::<ValueOrExpression<String>>(serde_json::json!({
+            "secret_ref": { "env": "HIVE_ROUTER_SECRET_REF_REDACTED_TEST" }
+        }))
+        .unwrap();
+
+        assert_eq!(format!("{config:?}"), "Secret(<secret>)");
+        assert_eq!(
+            serde_json::to_value(&config).unwrap(),
+            serde_json::json!({
+                "secret_ref": { "env": "HIVE_ROUTER_SECRET_REF_REDACTED_TEST" }
+            })
+        );
+    }
+
+    #[test]
+    fn reads_the_value_of_a_secret_env_var() {
+        std::env::set_var("HIVE_ROUTER_SECRET_REF_TEST", "s3cr3t");
+
+        let config = serde_json::from_value::<ValueOrExpression<String>>(serde_json::json!({
+            "secret_ref": { "env": "HIVE_ROUTER_SECRET_REF_TEST" }
+        }))
+        .unwrap();
+        assert!(matches!(&config, ValueOrExpression::Secret(secret) if secret.value() == "s3cr3t"));
+
+        let err = serde_json::from_value::<ValueOrExpression<String>>(serde_json::json!({
+            "secret_ref": { "env": "HIVE_ROUTER_SECRET_REF_TEST_MISSING" }
+        }))
+        .unwrap_err();
+        assert!(err.to_string().starts_with(
+            "failed to read the secret environment variable 'HIVE_ROUTER_SECRET_REF_TEST_MISSING'"
+        ));
+    }
+
+    #[test]
+    fn keeps_values_and_expressions() {
+        let config =
+            serde_json::from_value::<ValueOrExpression<String>>(serde_json::json!("inline"))
+                .unwrap();
+        assert!(matches!(config, ValueOrExpression::Value(value) if value == "inline"));
+
+        let config = serde_json::from_value::<ValueOrExpression<bool>>(serde_json::json!({
+            "expression": "true"
+        }))
+        .unwrap();
+        assert!(
+            matches!(config, ValueOrExpression::Expression { expression } if expression == "true")
+        );
+    }
+}
@@ -1,11 +1,20 @@
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{value::StringDeserializer, DeserializeOwned, Error},
+    Deserialize, Deserializer, Serialize,
+};
 
-#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+use crate::primitives::secret_ref::{Secret, SecretRef};
+
+#[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum ValueOrExpression<T: Default> {
     Value(T),
-    Expression { expression: String },
+    Expression {
+        expression: String,
+    },
+    /// A value read from a `secret_ref` when the config is loaded.
+    Secret(Secret<T>),
 }
 
 impl<T: Default> Default for ValueOrExpression<T> {
@@ -13,3 +22,55 @@ impl<T: Default> Default for ValueOrExpression<T> {
         ValueOrExpression::Value(T::default())
     }
 }
+
+/// The accepted forms of a [`ValueOrExpression`].
+/// A `secret_ref` is read when the config is loaded, and becomes a [`Secret`].
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+#[schemars(rename = "ValueOrExpression_for_{T}")]
+enum ValueOrExpressionSource<T> {
+    SecretRef {
+        // deserialized on its own, for its errors not to be swallowed by the untagged enum
+        #[schemars(with = "SecretRef")]
+        secret_ref: serde_json::Value,
+    },
+    Value(T),
+    Expression {
+        expression: String,
+    },
+}
+
+impl<'de, T: Default + DeserializeOwned> Deserialize<'de> for ValueOrExpression<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match ValueOrExpressionSource::<T>::deserialize(deserializer)? {
+            ValueOrExpressionSource::SecretRef { secret_ref } => {
+                let reference = serde_json::from_value::<SecretRef>(secret_ref)
+                    .map_err(|err| D::Error::custom(format!("invalid secret_ref: {err}")))?;
+                let secret = reference.resolve().map_err(D::Error::custom)?;
+                T::deserialize(StringDeserializer::<D::Error>::new(secret))
+                    .map(|value| ValueOrExpression::Secret(Secret::new(reference, value)))
+            }
+            ValueOrExpressionSource::Value(value) => Ok(ValueOrExpression::Value(value)),
+            ValueOrExpressionSource::Expression { expression } => {
+                Ok(ValueOrExpression::Expression { expression })
+            }
+        }
+    }
+}
+
+impl<T: Default + JsonSchema> JsonSchema for ValueOrExpression<T> {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        ValueOrExpressionSource::<T>::schema_name()
+    }
+
+    fn schema_id() -> std::borrow::Cow<'static, str> {
+        ValueOrExpressionSource::<T>::schema_id()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        ValueOrExpressionSource::<T>::json_schema(generator)
+    }
+}
@@ -0,0 +1,30 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Configuration of progressive overrides, `@override(label: "percent(x)")` in particular.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProgressiveOverrideConfig {
+    /// The key of a request, used to decide whether a `percent(x)` label applies to it.
+    ///
+    /// Requests sharing the same key are always on the same side of the rollout,
+    /// across requests and router instances.
+    /// When not set, or when the expression evaluates to `null`,
+    /// every request is assigned a random percentage instead.
+    ///
+    /// Example:
+    /// ```yaml
+    /// progressive_override:
+    ///   percentage_key:
+    ///     expression: '.request.headers."x-user-id"'
+    /// ```
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentage_key: Option<PercentageKeyConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PercentageKeyConfig {
+    /// An expression that must evaluate to a string or `null`.
+    pub expression: String,
+}
@@ -6,13 +6,27 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct QueryPlannerConfig {
-    /// A flag to allow exposing the query plan in the response.
-    /// When set to `true` and an incoming request has a `hive-expose-query-plan: true` header, the query plan will be exposed in the response, as part of `extensions`.
+    /// Allows exposing the query plan in the response.
+    /// When allowed and an incoming request has a `hive-expose-query-plan: true` header, the query plan will be exposed in the response, as part of `extensions`,
+    /// along with the time spent planning it. With `hive-expose-query-plan: dry-run`, the operation is planned but not executed.
+    /// The `hive-expose-query-plan-format` header selects the format of the exposed plan:
+    /// `json` (default), `apollo` (the JSON format of Apollo Router), `text` or `mermaid`.
+    ///
+    /// It can be a boolean, or an expression evaluated for every request asking for the query plan,
+    /// to allow it only for some clients.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// allow_expose:
+    ///   expression: '.request.headers."x-internal-token" == "my-secret"'
+    /// ```
     #[serde(default = "default_query_planning_allow_expose")]
-    pub allow_expose: bool,
+    pub allow_expose: AllowExposeQueryPlanConfig,
     /// The maximum time for the query planner to create an execution plan.
     /// This acts as a safeguard against overly complex or malicious queries that could degrade server performance.
-    /// When the timeout is reached, the planning process is cancelled.
+    /// When the timeout is reached, the planning process is cancelled,
+    /// and the request fails with a `PLANNING_TIMEOUT` error.
     ///
     /// Default: 10s.
     #[serde(
@@ -33,6 +47,101 @@ pub struct QueryPlannerConfig {
     /// Default: false.
     #[serde(default = "default_experimental_abstract_type_folding")]
     pub experimental_abstract_type_folding: bool,
+    /// Configuration of the cache of query plans.
+    #[serde(default)]
+    pub cache: QueryPlanCacheConfig,
+    /// Operations planned every time a new supergraph is loaded, before it serves requests,
+    /// so the first requests executing them don't wait for the query planner.
+    ///
+    /// Plans are prepared for requests without progressive override labels.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// warmup:
+    ///   - query: "query TopProducts { topProducts { upc name } }"
+    ///   - query: "query A { a } query B { b }"
+    ///     operation_name: B
+    ///   - persisted_document_id: "my-app~1.0.0~a1b2c3"
+    /// ```
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warmup: Vec<QueryPlanWarmupOperation>,
+}
+
+/// Defines whether the query plan can be exposed in the response.
+///
+/// It can be a simple boolean,
+/// or an object containing the expression that evaluates to a boolean.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(untagged)]
+pub enum AllowExposeQueryPlanConfig {
+    /// A static boolean value to allow or disallow exposing the query plan.
+    Boolean(bool),
+    /// A dynamic value computed by an expression.
+    Expression {
+        /// An expression that must evaluate to a boolean. If true, the query plan can be exposed.
+        expression: String,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct QueryPlanCacheConfig {
+    /// The maximum number of query plans kept in the cache.
+    ///
+    /// Default: 1000.
+    #[serde(default = "default_plan_cache_max_entries")]
+    pub max_entries: u64,
+    /// The time-to-live of a cached query plan.
+    /// Plans are kept until evicted by newer plans, or until a new supergraph is loaded, when not set.
+    #[serde(
+        default,
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "Option<String>")]
+    pub ttl: Option<Duration>,
+    /// The maximum memory used by the cached query plans, in bytes, based on the size of their serialized form.
+    /// When set, it bounds the cache instead of `max_entries`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<u64>,
+    /// Persists the operations of the cached query plans to a file when the router shuts down,
+    /// and plans them again when the router starts, before serving requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persistence: Option<QueryPlanCachePersistenceConfig>,
+}
+
+impl Default for QueryPlanCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: default_plan_cache_max_entries(),
+            ttl: None,
+            max_size: None,
+            persistence: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct QueryPlanCachePersistenceConfig {
+    /// The path of the file holding the persisted operations.
+    /// The file is created on shutdown, if it doesn't exist.
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(untagged)]
+pub enum QueryPlanWarmupOperation {
+    /// A GraphQL document, and the name of the operation to plan,
+    /// when the document contains more than one operation.
+    Document {
+        query: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        operation_name: Option<String>,
+    },
+    /// The id of a persisted document, resolved from the `persisted_documents` storage.
+    PersistedDocument { persisted_document_id: String },
 }
 
 impl Default for QueryPlannerConfig {
@@ -41,12 +150,14 @@ impl Default for QueryPlannerConfig {
             allow_expose: default_query_planning_allow_expose(),
             timeout: default_query_planning_timeout(),
             experimental_abstract_type_folding: default_experimental_abstract_type_folding(),
+            cache: QueryPlanCacheConfig::default(),
+            warmup: Vec::new(),
         }
     }
 }
 
-fn default_query_planning_allow_expose() -> bool {
-    false
+fn default_query_planning_allow_expose() -> AllowExposeQueryPlanConfig {
+    AllowExposeQueryPlanConfig::Boolean(false)
 }
 
 fn default_query_planning_timeout() -> Duration {
@@ -56,3 +167,7 @@ fn default_query_planning_timeout() -> Duration {
 fn default_experimental_abstract_type_folding() -> bool {
     false
 }
+
+fn default_plan_cache_max_entries() -> u64 {
+    1000
+}
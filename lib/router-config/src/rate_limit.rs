@@ -0,0 +1,230 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::{http_header::HttpHeaderName, value_or_expression::ValueOrExpression};
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Enables/disables the rate limiting of the clients. By default, the rate limiting is disabled.
+    ///
+    /// When enabled, every client gets its own token bucket,
+    /// and the requests exceeding it are rejected with a `429 Too Many Requests` response,
+    /// carrying a `Retry-After` header.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Number of requests per second a client is allowed to send, at the steady rate.
+    /// Required when the rate limiting is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<u32>,
+
+    /// Number of requests a client is allowed to send at once, above the steady rate.
+    /// It is the capacity of the token bucket of the client.
+    ///
+    /// Defaults to the value of `requests_per_second`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+
+    /// How the clients are identified. Defaults to the IP address of the client.
+    ///
+    /// The requests without the configured identity, e.g. without the header or without a valid JWT,
+    /// are identified by the IP address of the client.
+    #[serde(default)]
+    pub key: RateLimitKeyConfig,
+
+    /// The storage backend of the token buckets. Defaults to the memory of the router instance.
+    ///
+    /// When several router instances serve the same clients, use the `redis` backend,
+    /// so the limit is shared between the instances.
+    #[serde(default)]
+    pub backend: RateLimitBackendConfig,
+
+    /// Exposes the state of the bucket of the client in the
+    /// `x-ratelimit-limit`, `x-ratelimit-remaining` and `x-ratelimit-reset` response headers.
+    ///
+    /// Defaults to true.
+    #[serde(default = "default_expose_headers")]
+    pub expose_headers: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields, tag = "type")]
+pub enum RateLimitKeyConfig {
+    /// Identifies the clients by their IP address,
+    /// resolved with `telemetry.client_identification.ip_header`.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: ip
+    /// ```
+    #[default]
+    #[serde(rename = "ip")]
+    Ip,
+    /// Identifies the clients by the value of a request header.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: header
+    /// name: x-tenant-id
+    /// ```
+    #[serde(rename = "header")]
+    Header {
+        /// The name of the header.
+        name: HttpHeaderName,
+    },
+    /// Identifies the clients by a claim of their validated JWT.
+    /// Requires `jwt` to be enabled.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: jwt_claim
+    /// claim: sub
+    /// ```
+    #[serde(rename = "jwt_claim")]
+    JwtClaim {
+        /// The name of the claim.
+        ///
+        /// Defaults to `sub`.
+        #[serde(default = "default_jwt_claim")]
+        claim: String,
+    },
+    /// Identifies the clients by the API key sent in a request header.
+    /// Only the hash of the key is kept by the router, never the key itself.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: api_key
+    /// header: x-api-key
+    /// ```
+    #[serde(rename = "api_key")]
+    ApiKey {
+        /// The name of the header carrying the API key.
+        ///
+        /// Defaults to `x-api-key`.
+        #[serde(default = "default_api_key_header")]
+        header: HttpHeaderName,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, tag = "type")]
+pub enum RateLimitBackendConfig {
+    /// Keeps the token buckets in the memory of the router instance,
+    /// so every router instance enforces the limit on its own.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: memory
+    /// max_entries: 100000
+    /// ```
+    #[serde(rename = "memory")]
+    Memory {
+        /// The maximum number of clients tracked at once.
+        /// The buckets of idle clients are full, and are evicted first.
+        ///
+        /// Defaults to 100000.
+        #[serde(default = "default_max_entries")]
+        max_entries: u64,
+    },
+    /// Keeps the token buckets in Redis, shared between router instances.
+    ///
+    /// The buckets are updated atomically with a Lua script,
+    /// using the clock of the Redis server.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: redis
+    /// url: redis://localhost:6379
+    /// key_prefix: "hive-router:rate-limit:"
+    /// ```
+    #[serde(rename = "redis")]
+    Redis {
+        /// The connection URL of the Redis server, e.g. `redis://localhost:6379`.
+        url: ValueOrExpression<String>,
+        /// The prefix of every key written by the router.
+        ///
+        /// Defaults to `hive-router:rate-limit:`.
+        #[serde(default = "default_key_prefix")]
+        key_prefix: String,
+    },
+}
+
+impl Default for RateLimitBackendConfig {
+    fn default() -> Self {
+        Self::Memory {
+            max_entries: default_max_entries(),
+        }
+    }
+}
+
+fn default_expose_headers() -> bool {
+    true
+}
+
+fn default_jwt_claim() -> String {
+    "sub".to_string()
+}
+
+fn default_api_key_header() -> HttpHeaderName {
+    "x-api-key".into()
+}
+
+fn default_max_entries() -> u64 {
+    100_000
+}
+
+fn default_key_prefix() -> String {
+    "hive-router:rate-limit:".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_ip_key_and_memory_backend() {
+        let config = serde_json::from_str::<RateLimitConfig>(
+            r#"{"enabled": true, "requests_per_second": 10}"#,
+        )
+        .unwrap();
+        assert!(config.enabled);
+        assert!(config.expose_headers);
+        assert!(matches!(config.key, RateLimitKeyConfig::Ip));
+        match config.backend {
+            RateLimitBackendConfig::Memory { max_entries } => assert_eq!(max_entries, 100_000),
+            _ => panic!("expected the memory backend"),
+        }
+    }
+
+    #[test]
+    fn api_key_and_redis_backend() {
+        let config = serde_json::from_str::<RateLimitConfig>(
+            r#"{
+                "enabled": true,
+                "requests_per_second": 10,
+                "key": { "type": "api_key" },
+                "backend": { "type": "redis", "url": "redis://localhost:6379" }
+            }"#,
+        )
+        .unwrap();
+        match config.key {
+            RateLimitKeyConfig::ApiKey { header } => {
+                assert_eq!(header.get_header_ref().as_str(), "x-api-key")
+            }
+            _ => panic!("expected the api_key key"),
+        }
+        match config.backend {
+            RateLimitBackendConfig::Redis { key_prefix, .. } => {
+                assert_eq!(key_prefix, "hive-router:rate-limit:")
+            }
+            _ => panic!("expected the redis backend"),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use config::{Value, ValueKind};
+use serde_json::Value as JsonValue;
+
+use crate::HiveRouterConfig;
+
+/// Makes the relative file paths written in an extended file relative to the directory of that file,
+/// instead of the directory of the file the router is started with.
+///
+/// The file paths are the strings of the `path` format in the JSON schema of the config,
+/// and the file a string comes from is its origin, recorded by the `config` crate when the files are merged.
+pub(crate) fn resolve_extended_file_paths(value: &mut Value, root_dir: &Path) {
+    let schema = schemars::schema_for!(HiveRouterConfig);
+    let resolver = ExtendedFilePaths {
+        root_schema: schema.as_value(),
+        root_dir: root_dir
+            .canonicalize()
+            .unwrap_or_else(|_| root_dir.to_path_buf()),
+    };
+
+    resolver.visit(schema.as_value(), value);
+}
+
+struct ExtendedFilePaths<'a> {
+    root_schema: &'a JsonValue,
+    root_dir: PathBuf,
+}
+
+impl<'a> ExtendedFilePaths<'a> {
+    fn visit(&self, schema: &'a JsonValue, value: &mut Value) {
+        let mut schemas = Vec::new();
+        self.collect_schemas(schema, &mut schemas);
+
+        if let ValueKind::String(path) = &value.kind {
+            if let Some(resolved) = self.resolve(&schemas, path, value.origin()) {
+                value.kind = ValueKind::String(resolved);
+            }
+            return;
+        }
+
+        match &mut value.kind {
+            ValueKind::Table(table) => {
+                for (key, value) in table.iter_mut() {
+                    for schema in &schemas {
+                        let field_schema = schema
+                            .get("properties")
+                            .and_then(|properties| properties.get(key))
+                            .or_else(|| {
+                                schema
+                                    .get("additionalProperties")
+                                    .filter(|schema| schema.is_object())
+                            });
+                        if let Some(field_schema) = field_schema {
+                            self.visit(field_schema, value);
+                        }
+                    }
+                }
+            }
+            ValueKind::Array(values) => {
+                for schema in &schemas {
+                    if let Some(items_schema) = schema.get("items") {
+                        for value in values.iter_mut() {
+                            self.visit(items_schema, value);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The schema, the schema it references, and the variants it is composed of.
+    fn collect_schemas(&self, schema: &'a JsonValue, schemas: &mut Vec<&'a JsonValue>) {
+        schemas.push(schema);
+
+        if let Some(referenced) = schema
+            .get("$ref")
+            .and_then(JsonValue::as_str)
+            .and_then(|reference| reference.strip_prefix('#'))
+            .and_then(|pointer| self.root_schema.pointer(pointer))
+        {
+            self.collect_schemas(referenced, schemas);
+        }
+
+        for keyword in ["allOf", "anyOf", "oneOf"] {
+            if let Some(variants) = schema.get(keyword).and_then(JsonValue::as_array) {
+                for variant in variants {
+                    self.collect_schemas(variant, schemas);
+                }
+            }
+        }
+    }
+
+    /// The path relative to the directory of the extended file the string comes from,
+    /// when the string is a relative file path written in an extended file of another directory.
+    fn resolve(&self, schemas: &[&JsonValue], path: &str, origin: Option<&str>) -> Option<String> {
+        let is_file_path = schemas
+            .iter()
+            .any(|schema| schema.get("format").and_then(JsonValue::as_str) == Some("path"));
+        if !is_file_path || Path::new(path).is_absolute() {
+            return None;
+        }
+
+        let dir = match Path::new(origin?).parent()? {
+            dir if dir.as_os_str().is_empty() => Path::new("."),
+            dir => dir,
+        };
+        let dir = dir.canonicalize().ok()?;
+
+        (dir != self.root_dir).then(|| dir.join(path).to_string_lossy().into_owned())
+    }
+}
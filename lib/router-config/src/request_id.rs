@@ -0,0 +1,86 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::http_header::HttpHeaderName;
+
+/// Configuration of the ID given to every request to the GraphQL endpoint,
+/// to correlate the logs, traces and subgraph requests of a request with the response of its client.
+///
+/// ### Example
+/// ```yaml
+/// request_id:
+///   enabled: true
+///   header: x-request-id
+///   subgraph_header: x-correlation-id
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RequestIdConfig {
+    /// Gives an ID to every request, returned to the client in the `header` response header.
+    ///
+    /// The ID is attached to the log lines written while the request is handled,
+    /// as the `request_id` field of the `request` span, and to the `http.server` span,
+    /// as the `hive.request.id` attribute.
+    ///
+    /// Disabled by default.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The header of the response carrying the ID of the request,
+    /// and of the client request carrying an ID to reuse.
+    /// By default, `x-request-id` is used.
+    #[serde(default = "default_header")]
+    pub header: HttpHeaderName,
+
+    /// Reuses the ID sent by the client in `header`, e.g. by a load balancer in front of the router.
+    /// An ID longer than 128 characters, or holding anything other than visible ASCII characters,
+    /// is replaced by a generated one.
+    ///
+    /// When disabled, or when the client sends no ID, a ULID is generated.
+    /// Enabled by default.
+    #[serde(default = "default_accept_incoming")]
+    pub accept_incoming: bool,
+
+    /// Forwards the ID to the subgraphs, in the `subgraph_header` header.
+    /// Enabled by default.
+    #[serde(default = "default_forward_to_subgraphs")]
+    pub forward_to_subgraphs: bool,
+
+    /// The header of the subgraph requests carrying the ID. By default, `header` is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subgraph_header: Option<HttpHeaderName>,
+}
+
+impl Default for RequestIdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            header: default_header(),
+            accept_incoming: default_accept_incoming(),
+            forward_to_subgraphs: default_forward_to_subgraphs(),
+            subgraph_header: None,
+        }
+    }
+}
+
+impl RequestIdConfig {
+    /// The header of the subgraph requests carrying the ID, when it is forwarded.
+    pub fn subgraph_header(&self) -> Option<&HttpHeaderName> {
+        match self.enabled && self.forward_to_subgraphs {
+            true => Some(self.subgraph_header.as_ref().unwrap_or(&self.header)),
+            false => None,
+        }
+    }
+}
+
+fn default_header() -> HttpHeaderName {
+    "x-request-id".into()
+}
+
+fn default_accept_incoming() -> bool {
+    true
+}
+
+fn default_forward_to_subgraphs() -> bool {
+    true
+}
@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::http_header::HttpHeaderName;
+use crate::primitives::value_or_expression::ValueOrExpression;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseCacheConfig {
+    /// Enables/disables the response cache. By default, the response cache is disabled.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The max-age applied to root fields and fields returning composite types
+    /// that have no `@cacheControl` hint, either on the field or on the returned type.
+    ///
+    /// Scalar fields without a hint inherit the max-age of their parent field.
+    ///
+    /// If set to 0 (the default), only operations where every field is covered by a
+    /// `@cacheControl` hint are cached.
+    #[serde(
+        default = "default_max_age",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub default_max_age: Duration,
+
+    /// The request header identifying the client, used to scope the cache key of
+    /// operations with a `PRIVATE` cache scope.
+    ///
+    /// Responses of `PRIVATE` operations are only cached when the request carries this header,
+    /// and are only served back to requests carrying the same value.
+    ///
+    /// Defaults to `authorization`.
+    #[serde(default = "default_private_id_header")]
+    pub private_id_header: HttpHeaderName,
+
+    /// The storage backend of the cached responses. Defaults to an in-memory cache.
+    #[serde(default)]
+    pub backend: ResponseCacheBackendConfig,
+
+    /// Tags the cached responses with the surrogate keys of the entities they contain,
+    /// so they can be purged by entity through the cache admin endpoint.
+    ///
+    /// The surrogate key of an entity is its typename and the values of one of its `@key`s,
+    /// as in `Product:upc=1` or `Review:id=1,product.upc=1`, for every key whose fields
+    /// are selected by the operation.
+    ///
+    /// Disabled by default, as the cached responses are parsed to find their entities.
+    #[serde(default)]
+    pub surrogate_keys: bool,
+
+    /// Purges the cached responses containing the entities returned by successful mutations,
+    /// by their surrogate keys, so that the clients read their own writes.
+    ///
+    /// Requires `surrogate_keys`. Disabled by default.
+    #[serde(default)]
+    pub invalidate_on_mutation: bool,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_max_age: default_max_age(),
+            private_id_header: default_private_id_header(),
+            backend: ResponseCacheBackendConfig::default(),
+            surrogate_keys: false,
+            invalidate_on_mutation: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields, tag = "type")]
+pub enum ResponseCacheBackendConfig {
+    /// Keeps the cached responses in the memory of the router instance.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: memory
+    /// max_entries: 10000
+    /// ```
+    #[serde(rename = "memory")]
+    Memory {
+        /// The maximum number of responses kept in the cache.
+        ///
+        /// Defaults to 10000.
+        #[serde(default = "default_max_entries")]
+        max_entries: u64,
+    },
+    /// Keeps the cached responses in Redis, shared between router instances.
+    ///
+    /// # Example
+    ///
+    /// ```yaml
+    /// type: redis
+    /// url: redis://localhost:6379
+    /// key_prefix: "hive-router:response-cache:"
+    /// ```
+    #[serde(rename = "redis")]
+    Redis {
+        /// The connection URL of the Redis server, e.g. `redis://localhost:6379`.
+        url: ValueOrExpression<String>,
+        /// The prefix of every key written by the router.
+        ///
+        /// Defaults to `hive-router:response-cache:`.
+        #[serde(default = "default_key_prefix")]
+        key_prefix: String,
+    },
+}
+
+impl Default for ResponseCacheBackendConfig {
+    fn default() -> Self {
+        Self::Memory {
+            max_entries: default_max_entries(),
+        }
+    }
+}
+
+fn default_max_age() -> Duration {
+    Duration::ZERO
+}
+
+fn default_private_id_header() -> HttpHeaderName {
+    "authorization".into()
+}
+
+fn default_max_entries() -> u64 {
+    10_000
+}
+
+fn default_key_prefix() -> String {
+    "hive-router:response-cache:".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_memory_backend() {
+        let config = serde_json::from_str::<ResponseCacheConfig>(r#"{"enabled": true}"#).unwrap();
+        assert!(config.enabled);
+        assert!(config.default_max_age.is_zero());
+        assert_eq!(config.private_id_header.get_header_ref(), "authorization");
+        assert!(matches!(
+            config.backend,
+            ResponseCacheBackendConfig::Memory {
+                max_entries: 10_000
+            }
+        ));
+    }
+
+    #[test]
+    fn redis_backend() {
+        let config = serde_json::from_str::<ResponseCacheConfig>(
+            r#"{
+                "enabled": true,
+                "default_max_age": "30s",
+                "backend": { "type": "redis", "url": "redis://localhost:6379" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.default_max_age, Duration::from_secs(30));
+        match config.backend {
+            ResponseCacheBackendConfig::Redis { key_prefix, .. } => {
+                assert_eq!(key_prefix, "hive-router:response-cache:");
+            }
+            other => panic!("unexpected backend: {other:?}"),
+        }
+    }
+}
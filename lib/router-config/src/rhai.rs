@@ -0,0 +1,19 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::file_path::FilePath;
+
+/// Configuration of the Rhai scripts, run around requests.
+///
+/// Example:
+/// ```yaml
+/// rhai:
+///   main: ./rhai/main.rhai
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RhaiConfig {
+    /// The path to the main script, relative to the config file.
+    /// Modules imported by the script are resolved relative to its directory.
+    pub main: FilePath,
+}
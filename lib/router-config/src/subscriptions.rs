@@ -2,12 +2,13 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::num::NonZeroUsize;
 use std::time::Duration;
 
 use crate::primitives::absolute_path::AbsolutePath;
 use crate::primitives::value_or_expression::ValueOrExpression;
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Default)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct SubscriptionsConfig {
     /// Enables/disables subscriptions. By default, the subscriptions are disabled.
@@ -26,9 +27,9 @@ pub struct SubscriptionsConfig {
     /// use cases. Increase this value if you expect bursts of events or have slow consumers that
     /// need more headroom to catch up.
     ///
-    /// Defaults to 32.
+    /// Must be greater than 0. Defaults to 32.
     #[serde(default = "default_broadcast_capacity")]
-    pub broadcast_capacity: usize,
+    pub broadcast_capacity: NonZeroUsize,
     /// The capacity of the per-subscription buffer between a subgraph and the router's
     /// processing pipeline.
     ///
@@ -47,15 +48,79 @@ pub struct SubscriptionsConfig {
     /// keeps memory minimal and drops eagerly, which is appropriate when only the latest events
     /// matter.
     ///
-    /// Defaults to 1024.
+    /// Must be greater than 0. Defaults to 1024.
     #[serde(default = "default_subgraph_buffer_capacity")]
-    pub subgraph_buffer_capacity: usize,
+    pub subgraph_buffer_capacity: NonZeroUsize,
+    /// The interval at which the router sends heartbeats to clients subscribed over
+    /// Server-Sent Events or Apollo Multipart HTTP, keeping idle connections open.
+    ///
+    /// If set to 0, heartbeats are disabled. Defaults to 10 seconds.
+    #[serde(
+        default = "default_client_heartbeat_interval",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub client_heartbeat_interval: Duration,
+    /// The maximum number of subscriptions the router serves to its clients at the same time,
+    /// over WebSockets and HTTP streaming combined.
+    ///
+    /// Every client subscription counts toward the limit, including the ones deduplicated onto
+    /// a single subgraph subscription. When the limit is reached, new subscriptions are rejected
+    /// with the `TOO_MANY_SUBSCRIPTIONS` error code until the active ones complete.
+    ///
+    /// Must be greater than 0. By default, the number of subscriptions is not limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_active_subscriptions: Option<NonZeroUsize>,
+    /// Which active subscriptions are terminated when the supergraph they were planned for is
+    /// reloaded or replaced.
+    ///
+    /// Terminated subscriptions receive a final error with the `SUBSCRIPTION_SCHEMA_RELOAD` code,
+    /// after which the clients are expected to subscribe again.
+    ///
+    /// Defaults to `always`.
+    #[serde(default)]
+    pub terminate_on_schema_reload: SubscriptionSchemaReloadTermination,
     /// Configuration for subgraphs using the HTTP Callback protocol.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub callback: Option<CallbackConfig>,
     /// Configuration for subgraphs using WebSocket protocol.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub websocket: Option<WebSocketConfig>,
+    /// Configuration for subgraphs using the GraphQL over SSE protocol.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sse: Option<SseConfig>,
+}
+
+impl Default for SubscriptionsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            broadcast_capacity: default_broadcast_capacity(),
+            subgraph_buffer_capacity: default_subgraph_buffer_capacity(),
+            client_heartbeat_interval: default_client_heartbeat_interval(),
+            max_active_subscriptions: None,
+            terminate_on_schema_reload: SubscriptionSchemaReloadTermination::default(),
+            callback: None,
+            websocket: None,
+            sse: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionSchemaReloadTermination {
+    /// Terminate every subscription of the reloaded supergraph. This is the default.
+    #[default]
+    Always,
+    /// Terminate only the subscriptions whose operation is no longer valid against the new
+    /// supergraph. The other subscriptions keep receiving events through the query plan they
+    /// were started with.
+    ///
+    /// Subscriptions are always terminated when the router has no configured supergraph to
+    /// compare against, for example when the supergraph is provided by a plugin.
+    BreakingChanges,
 }
 
 /// Configuration for the HTTP Callback subscription mode.
@@ -103,12 +168,16 @@ pub struct CallbackConfig {
     pub subgraphs: HashSet<String>,
 }
 
-fn default_broadcast_capacity() -> usize {
-    32
+fn default_broadcast_capacity() -> NonZeroUsize {
+    NonZeroUsize::new(32).expect("default broadcast capacity is not zero")
+}
+
+fn default_subgraph_buffer_capacity() -> NonZeroUsize {
+    NonZeroUsize::new(1024).expect("default subgraph buffer capacity is not zero")
 }
 
-fn default_subgraph_buffer_capacity() -> usize {
-    1024
+fn default_client_heartbeat_interval() -> Duration {
+    Duration::from_secs(10)
 }
 
 fn default_callback_path() -> AbsolutePath {
@@ -134,6 +203,20 @@ pub struct WebSocketConfig {
     pub subgraphs: HashMap<String, WebSocketSubgraphConfig>,
 }
 
+/// Configuration for the GraphQL over SSE subscription mode.
+///
+/// By default, the router accepts both Multipart HTTP and SSE from subgraphs, preferring multipart.
+/// Subgraphs listed here are only offered SSE (`Accept: text/event-stream`).
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SseConfig {
+    /// The list of subgraph names that use the GraphQL over SSE protocol.
+    ///
+    /// Subgraphs claimed by `callback` or `websocket` take precedence.
+    #[serde(default)]
+    pub subgraphs: HashSet<String>,
+}
+
 /// WebSocket configuration for a specific subgraph or the default for all subgraphs.
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
 #[serde(deny_unknown_fields)]
@@ -166,6 +249,11 @@ impl SubscriptionsConfig {
                 return SubscriptionProtocol::WebSocket;
             }
         }
+        if let Some(ref sse) = self.sse {
+            if sse.subgraphs.contains(subgraph_name) {
+                return SubscriptionProtocol::SSE;
+            }
+        }
         SubscriptionProtocol::HTTP
     }
 
@@ -189,6 +277,91 @@ impl SubscriptionsConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn defaults_match_serde_defaults() {
+        let config = serde_json::from_str::<SubscriptionsConfig>("{}").unwrap();
+        let default = SubscriptionsConfig::default();
+        assert_eq!(config.broadcast_capacity, default.broadcast_capacity);
+        assert_eq!(
+            config.subgraph_buffer_capacity,
+            default.subgraph_buffer_capacity
+        );
+        assert_eq!(
+            config.client_heartbeat_interval,
+            default.client_heartbeat_interval
+        );
+    }
+
+    #[test]
+    fn client_heartbeat_interval_can_be_disabled() {
+        let config =
+            serde_json::from_str::<SubscriptionsConfig>(r#"{"client_heartbeat_interval": "0s"}"#)
+                .unwrap();
+        assert!(config.client_heartbeat_interval.is_zero());
+    }
+
+    #[test]
+    fn buffer_capacities_must_not_be_zero() {
+        for field in ["broadcast_capacity", "subgraph_buffer_capacity"] {
+            let result =
+                serde_json::from_str::<SubscriptionsConfig>(&format!(r#"{{"{field}": 0}}"#));
+            assert!(result.is_err(), "expected {field} of 0 to be rejected");
+        }
+    }
+
+    #[test]
+    fn max_active_subscriptions_must_not_be_zero() {
+        let result =
+            serde_json::from_str::<SubscriptionsConfig>(r#"{"max_active_subscriptions": 0}"#);
+        assert!(result.is_err());
+
+        let config =
+            serde_json::from_str::<SubscriptionsConfig>(r#"{"max_active_subscriptions": 10}"#)
+                .unwrap();
+        assert_eq!(config.max_active_subscriptions, NonZeroUsize::new(10));
+    }
+
+    #[test]
+    fn terminate_on_schema_reload_defaults_to_always() {
+        let config = serde_json::from_str::<SubscriptionsConfig>("{}").unwrap();
+        assert_eq!(
+            config.terminate_on_schema_reload,
+            SubscriptionSchemaReloadTermination::Always
+        );
+
+        let config = serde_json::from_str::<SubscriptionsConfig>(
+            r#"{"terminate_on_schema_reload": "breaking_changes"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.terminate_on_schema_reload,
+            SubscriptionSchemaReloadTermination::BreakingChanges
+        );
+    }
+
+    #[test]
+    fn sse_subgraphs_use_sse_protocol() {
+        let config = serde_json::from_str::<SubscriptionsConfig>(
+            r#"{
+                "sse": { "subgraphs": ["products", "reviews"] },
+                "websocket": { "subgraphs": { "reviews": {} } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.get_protocol_for_subgraph("products"),
+            SubscriptionProtocol::SSE
+        );
+        assert_eq!(
+            config.get_protocol_for_subgraph("reviews"),
+            SubscriptionProtocol::WebSocket
+        );
+        assert_eq!(
+            config.get_protocol_for_subgraph("accounts"),
+            SubscriptionProtocol::HTTP
+        );
+    }
+
     #[test]
     fn callback_path_must_be_absolute() {
         let err = serde_json::from_str::<CallbackConfig>(
@@ -230,6 +403,9 @@ pub enum SubscriptionProtocol {
     /// - GraphQL Incremental Delivery. Implements the official GraphQL Incremental Delivery specification. See: https://github.com/graphql/graphql-over-http/blob/main/rfcs/IncrementalDelivery.md.
     #[default]
     HTTP,
+    /// Uses only the "distinct connection mode" of the GraphQL over SSE specification.
+    /// See: https://github.com/graphql/graphql-over-http/blob/main/rfcs/GraphQLOverSSE.md#distinct-connections-mode.
+    SSE,
     /// Uses GraphQL over WebSocket (graphql-transport-ws subprotocol).
     WebSocket,
     /// Uses the HTTP Callback protocol for subscriptions.
@@ -91,6 +91,48 @@ pub enum SupergraphSource {
         #[schemars(with = "String")]
         poll_interval: Option<Duration>,
     },
+    /// Composes the supergraph from the schemas of the subgraphs, at startup and whenever
+    /// one of them changes. Meant for local development, as it removes the need for a
+    /// separate composition step.
+    #[serde(rename = "compose")]
+    Compose {
+        /// The subgraphs to compose the supergraph from.
+        subgraphs: Vec<ComposeSubgraphConfig>,
+        /// Optional interval at which the subgraph schemas should be checked for changes.
+        /// If not provided, the supergraph will only be composed once when the router starts.
+        #[serde(
+            default = "default_file_poll_interval",
+            deserialize_with = "humantime_serde::deserialize",
+            serialize_with = "humantime_serde::serialize"
+        )]
+        #[schemars(with = "String")]
+        poll_interval: Option<Duration>,
+    },
+    /// Serves a single GraphQL API that is not federated (a monograph).
+    ///
+    /// The schema of the API becomes a supergraph with a single subgraph, so every operation
+    /// is sent as is to the API, while the router still provides its caching, authorization,
+    /// limits, telemetry and plugins.
+    #[serde(rename = "schema")]
+    Schema {
+        /// The URL the router sends the requests to.
+        endpoint: String,
+        /// The path to the schema of the GraphQL API.
+        path: FilePath,
+        /// The name of the API, as it appears in the query plans, telemetry
+        /// and the per-subgraph configuration.
+        #[serde(default = "default_schema_subgraph_name")]
+        subgraph_name: String,
+        /// Optional interval at which the schema file should be polled for changes.
+        /// If not provided, the schema will only be loaded once when the router starts.
+        #[serde(
+            default = "default_file_poll_interval",
+            deserialize_with = "humantime_serde::deserialize",
+            serialize_with = "humantime_serde::serialize"
+        )]
+        #[schemars(with = "String")]
+        poll_interval: Option<Duration>,
+    },
     /// No configured supergraph source. A plugin must select a supergraph for every GraphQL
     /// request and WebSocket upgrade that needs one, via `set_supergraph` in
     /// `on_http_request`.
@@ -102,6 +144,19 @@ pub enum SupergraphSource {
     Plugin,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ComposeSubgraphConfig {
+    /// The name of the subgraph, as it appears in the query plans and telemetry.
+    pub name: String,
+    /// The URL the router sends the subgraph requests to.
+    pub routing_url: String,
+    /// The path to the schema of the subgraph.
+    ///
+    /// If not provided, the schema is fetched from `routing_url` with the `{ _service { sdl } }` query.
+    pub schema_file: Option<FilePath>,
+}
+
 fn default_accept_invalid_certs() -> bool {
     false
 }
@@ -124,11 +179,17 @@ impl SupergraphSource {
             SupergraphSource::File { .. } => "file",
             SupergraphSource::HiveConsole { .. } => "hive",
             SupergraphSource::Storage { storage_id, .. } => storage_id.as_str(),
+            SupergraphSource::Compose { .. } => "compose",
+            SupergraphSource::Schema { .. } => "schema",
             SupergraphSource::Plugin => "plugin",
         }
     }
 }
 
+fn default_schema_subgraph_name() -> String {
+    "api".to_string()
+}
+
 fn default_hive_poll_interval() -> Duration {
     Duration::from_secs(10)
 }
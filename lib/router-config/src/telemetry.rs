@@ -6,9 +6,14 @@ use serde::{Deserialize, Serialize};
 use crate::primitives::http_header::HttpHeaderName;
 use crate::primitives::ip_network::IpNetwork;
 use crate::primitives::value_or_expression::ValueOrExpression;
-use crate::telemetry::{hive::HiveTelemetryConfig, metrics::MetricsConfig, tracing::TracingConfig};
+use crate::telemetry::{
+    apollo::ApolloTelemetryConfig, hive::HiveTelemetryConfig,
+    instrumentation::InstrumentationConfig, metrics::MetricsConfig, tracing::TracingConfig,
+};
 
+pub mod apollo;
 pub mod hive;
+pub mod instrumentation;
 pub mod metrics;
 pub mod tracing;
 
@@ -19,6 +24,8 @@ pub struct TelemetryConfig {
     #[serde(default)]
     pub hive: Option<HiveTelemetryConfig>,
     #[serde(default)]
+    pub apollo: ApolloTelemetryConfig,
+    #[serde(default)]
     pub tracing: TracingConfig,
     #[serde(default)]
     pub metrics: MetricsConfig,
@@ -26,6 +33,9 @@ pub struct TelemetryConfig {
     pub resource: ResourceConfig,
     #[serde(default)]
     pub client_identification: ClientIdentificationConfig,
+    /// Attributes computed from every GraphQL operation, added to its span and metrics.
+    #[serde(default)]
+    pub instrumentation: InstrumentationConfig,
 }
 
 impl TelemetryConfig {
@@ -52,6 +62,17 @@ pub struct ClientIdentificationConfig {
     pub name_header: HttpHeaderName,
     #[serde(default = "default_client_version_header")]
     pub version_header: HttpHeaderName,
+    /// Rejects the requests without a client name, with a `400` status
+    /// and the `CLIENT_IDENTIFICATION_REQUIRED` error code.
+    ///
+    /// A name set by a plugin, in the `telemetry.client_name` field of the request context, is accepted.
+    /// Disabled by default.
+    #[serde(default)]
+    pub require_name: bool,
+    /// Rejects the requests without a client version, as `require_name` does for the name.
+    /// Disabled by default.
+    #[serde(default)]
+    pub require_version: bool,
     /// Defines how the client IP address is determined.
     ///
     /// Important: HTTP headers like `x-forwarded-for` can be spoofed by clients.
@@ -83,6 +104,8 @@ impl Default for ClientIdentificationConfig {
         Self {
             name_header: default_client_name_header(),
             version_header: default_client_version_header(),
+            require_name: false,
+            require_version: false,
             ip_header: None,
         }
     }
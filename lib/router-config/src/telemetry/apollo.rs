@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::{percentage::Percentage, value_or_expression::ValueOrExpression};
+
+/// Reports usage and federated traces (ftv1) to Apollo GraphOS,
+/// for teams migrating from Apollo Router whose GraphOS Studio dashboards should keep working.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ApolloTelemetryConfig {
+    /// Enables/disables the reporting to Apollo GraphOS. Disabled by default.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The GraphOS API key of the graph.
+    /// Can also be provided via the `APOLLO_KEY` environment variable.
+    #[serde(default)]
+    pub key: Option<ValueOrExpression<String>>,
+
+    /// The graph ref reported to, in the `graph-id@variant` format.
+    /// Can also be provided via the `APOLLO_GRAPH_REF` environment variable.
+    #[serde(default)]
+    pub graph_ref: Option<ValueOrExpression<String>>,
+
+    /// The usage reporting ingress of GraphOS.
+    /// Defaults to `https://usage-reporting.api.apollographql.com/api/ingress/traces`.
+    #[serde(default = "default_endpoint")]
+    pub endpoint: String,
+
+    /// The share of operations reported to GraphOS.
+    /// Default: 100%
+    #[serde(default = "default_sampling")]
+    #[schemars(with = "String")]
+    pub sampling: Percentage,
+
+    /// Asks the subgraphs of sampled operations for their federated traces (ftv1),
+    /// with the `apollo-federation-include-trace: ftv1` header,
+    /// and attaches them to the fetches of the reported query plan.
+    /// Default: true
+    #[serde(default = "default_federated_traces")]
+    pub federated_traces: bool,
+
+    /// A maximum number of operations to hold in a buffer before sending them to GraphOS.
+    /// Default: 1000
+    #[serde(default = "default_buffer_size")]
+    pub buffer_size: usize,
+
+    /// Frequency of flushing the buffer to GraphOS.
+    /// Default: 5 seconds
+    #[serde(
+        default = "default_flush_interval",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub flush_interval: Duration,
+
+    /// A timeout for the entire request to GraphOS.
+    /// Default: 15 seconds
+    #[serde(
+        default = "default_request_timeout",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub request_timeout: Duration,
+}
+
+impl Default for ApolloTelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key: None,
+            graph_ref: None,
+            endpoint: default_endpoint(),
+            sampling: default_sampling(),
+            federated_traces: default_federated_traces(),
+            buffer_size: default_buffer_size(),
+            flush_interval: default_flush_interval(),
+            request_timeout: default_request_timeout(),
+        }
+    }
+}
+
+fn default_endpoint() -> String {
+    "https://usage-reporting.api.apollographql.com/api/ingress/traces".to_string()
+}
+
+fn default_sampling() -> Percentage {
+    Percentage::from_f64(1.0).unwrap()
+}
+
+fn default_federated_traces() -> bool {
+    true
+}
+
+fn default_buffer_size() -> usize {
+    1000
+}
+
+fn default_flush_interval() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_request_timeout() -> Duration {
+    Duration::from_secs(15)
+}
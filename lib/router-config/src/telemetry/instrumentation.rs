@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Configures the attributes computed from every GraphQL operation,
+/// and added to its span and metrics.
+///
+/// ### Example
+/// ```yaml
+/// telemetry:
+///   instrumentation:
+///     attributes:
+///       tenant.id:
+///         expression: .request.headers."x-tenant-id"
+///         metrics:
+///           enabled: true
+///           allow: [acme, globex]
+///       cart.size:
+///         expression: length(array(.variables.items) ?? [])
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct InstrumentationConfig {
+    /// The attributes, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, CustomAttributeConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CustomAttributeConfig {
+    /// A VRL expression computing the value of the attribute, evaluated once the operation is planned.
+    ///
+    /// The expression has access to the request, in `.request`
+    /// (headers, operation, JWT claims, client, etc.),
+    /// and to the coerced variables of the operation, in `.variables`.
+    ///
+    /// A string, a number or a boolean is used as is, an array or an object is serialized to JSON.
+    /// The attribute is not set when the expression returns `null` or fails.
+    pub expression: String,
+
+    /// Adds the attribute to the `graphql.operation` span.
+    /// Enabled by default.
+    #[serde(default = "default_spans")]
+    pub spans: bool,
+
+    /// Adds the attribute to the `hive.router.graphql.execution.duration` metric.
+    #[serde(default)]
+    pub metrics: CustomAttributeMetricsConfig,
+}
+
+/// Every distinct value of a metric attribute creates a new time series,
+/// so the values can be limited to a known list, the others being replaced by `other_value`.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CustomAttributeMetricsConfig {
+    /// Disabled by default.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The values kept as is. By default, every value not in `deny` is kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+
+    /// The values replaced by `other_value`, even when they are in `allow`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+
+    /// The value recorded in place of the values not allowed.
+    /// By default, `other` is used.
+    #[serde(default = "default_other_value")]
+    pub other_value: String,
+}
+
+impl Default for CustomAttributeMetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allow: None,
+            deny: Vec::new(),
+            other_value: default_other_value(),
+        }
+    }
+}
+
+impl CustomAttributeMetricsConfig {
+    /// Returns true when `value` is recorded as is, and not replaced by `other_value`.
+    pub fn is_allowed(&self, value: &str) -> bool {
+        let allowed = self
+            .allow
+            .as_ref()
+            .is_none_or(|allow| allow.iter().any(|allowed| allowed == value));
+
+        allowed && !self.deny.iter().any(|denied| denied == value)
+    }
+}
+
+impl InstrumentationConfig {
+    /// The names of the attributes added to the metrics.
+    pub fn metric_attribute_names(&self) -> impl Iterator<Item = &str> {
+        self.attributes
+            .iter()
+            .filter(|(_, attribute)| attribute.metrics.enabled)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+fn default_spans() -> bool {
+    true
+}
+
+fn default_other_value() -> String {
+    "other".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_every_value_by_default() {
+        let config = serde_json::from_str::<CustomAttributeConfig>(
+            r#"{"expression": ".request.headers.\"x-tenant-id\""}"#,
+        )
+        .unwrap();
+        assert!(config.spans);
+        assert!(!config.metrics.enabled);
+        assert_eq!(config.metrics.other_value, "other");
+        assert!(config.metrics.is_allowed("acme"));
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let config = serde_json::from_str::<CustomAttributeMetricsConfig>(
+            r#"{"enabled": true, "allow": ["acme", "globex"], "deny": ["globex"]}"#,
+        )
+        .unwrap();
+        assert!(config.is_allowed("acme"));
+        assert!(!config.is_allowed("globex"));
+        assert!(!config.is_allowed("initech"));
+    }
+}
@@ -257,6 +257,12 @@ pub struct MetricsPrometheusConfig {
     pub enabled: bool,
     #[serde(default)]
     pub port: Option<u16>,
+    /// The host address of the dedicated metrics server, started when `port` differs from the port of the router,
+    /// e.g. `127.0.0.1` to only expose the metrics locally.
+    ///
+    /// Defaults to `http.host`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
     #[serde(default = "default_prometheus_path")]
     pub path: String,
 }
@@ -208,6 +208,12 @@ pub enum TracingExporterConfig {
     Otlp(Box<TracingOtlpConfig>),
     #[serde(rename = "stdout")]
     Stdout(Box<StdoutExporterConfig>),
+    /// Sends the spans to a Zipkin collector, with the Zipkin JSON v2 API.
+    #[serde(rename = "zipkin")]
+    Zipkin(Box<TracingZipkinConfig>),
+    /// Sends the spans to a Datadog agent, with its `/v0.4/traces` API.
+    #[serde(rename = "datadog")]
+    Datadog(Box<TracingDatadogConfig>),
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -223,11 +229,80 @@ fn default_stdout_config_enabled() -> bool {
     true
 }
 
+/// ### Example
+/// ```yaml
+/// telemetry:
+///   tracing:
+///     exporters:
+///       - kind: zipkin
+///         endpoint: http://zipkin:9411/api/v2/spans
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TracingZipkinConfig {
+    #[serde(default = "default_zipkin_config_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub batch_processor: BatchProcessorConfig,
+    /// The URL the spans are sent to.
+    /// By default, `http://localhost:9411/api/v2/spans` is used.
+    #[serde(default = "default_zipkin_endpoint")]
+    pub endpoint: ValueOrExpression<String>,
+}
+
+fn default_zipkin_config_enabled() -> bool {
+    true
+}
+
+fn default_zipkin_endpoint() -> ValueOrExpression<String> {
+    ValueOrExpression::Value("http://localhost:9411/api/v2/spans".to_string())
+}
+
+/// The service of the spans is the `service.name` resource attribute,
+/// and their environment and version the `deployment.environment.name` and `service.version` ones.
+///
+/// The Datadog agent keeps the traces according to their sampling priority,
+/// `1` for the traces sampled by the router,
+/// or the one received in the `x-datadog-sampling-priority` header when `propagation.datadog` is enabled.
+///
+/// ### Example
+/// ```yaml
+/// telemetry:
+///   tracing:
+///     exporters:
+///       - kind: datadog
+///         endpoint: http://datadog-agent:8126
+///     propagation:
+///       datadog: true
+/// ```
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TracingDatadogConfig {
+    #[serde(default = "default_datadog_config_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub batch_processor: BatchProcessorConfig,
+    /// The URL of the Datadog agent.
+    /// By default, `http://localhost:8126` is used.
+    #[serde(default = "default_datadog_endpoint")]
+    pub endpoint: ValueOrExpression<String>,
+}
+
+fn default_datadog_config_enabled() -> bool {
+    true
+}
+
+fn default_datadog_endpoint() -> ValueOrExpression<String> {
+    ValueOrExpression::Value("http://localhost:8126".to_string())
+}
+
 impl TracingExporterConfig {
     fn is_enabled(&self) -> bool {
         match self {
             TracingExporterConfig::Otlp(otlp_config) => otlp_config.enabled,
             TracingExporterConfig::Stdout(stdout_config) => stdout_config.enabled,
+            TracingExporterConfig::Zipkin(zipkin_config) => zipkin_config.enabled,
+            TracingExporterConfig::Datadog(datadog_config) => datadog_config.enabled,
         }
     }
 }
@@ -300,6 +375,25 @@ pub struct TracingPropagationConfig {
     pub b3: bool,
     #[serde(default = "default_propagation_jaeger")]
     pub jaeger: bool,
+    /// Reads and writes the trace context in the headers of the Datadog tracers:
+    /// `x-datadog-trace-id`, `x-datadog-parent-id`, `x-datadog-sampling-priority`,
+    /// `x-datadog-origin` and the `_dd.p.tid` tag of `x-datadog-tags`.
+    ///
+    /// A trace received with a sampling priority of `0` or less is not sampled,
+    /// when `collect.parent_based_sampler` is enabled.
+    ///
+    /// Disabled by default.
+    #[serde(default = "default_propagation_datadog")]
+    pub datadog: bool,
+    /// Whether to continue the trace context extracted from incoming requests.
+    ///
+    /// When disabled, every request starts a new trace, ignoring the trace headers sent by clients,
+    /// while the router's own trace context is still propagated to subgraphs.
+    /// Useful when the router is exposed to untrusted clients.
+    ///
+    /// Enabled by default.
+    #[serde(default = "default_propagation_trust_incoming")]
+    pub trust_incoming: bool,
 }
 
 impl Default for TracingPropagationConfig {
@@ -309,6 +403,8 @@ impl Default for TracingPropagationConfig {
             baggage: default_propagation_baggage(),
             b3: default_propagation_b3(),
             jaeger: default_propagation_jaeger(),
+            datadog: default_propagation_datadog(),
+            trust_incoming: default_propagation_trust_incoming(),
         }
     }
 }
@@ -325,6 +421,12 @@ fn default_propagation_b3() -> bool {
 fn default_propagation_jaeger() -> bool {
     false
 }
+fn default_propagation_datadog() -> bool {
+    false
+}
+fn default_propagation_trust_incoming() -> bool {
+    true
+}
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(deny_unknown_fields)]
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::file_path::FilePath;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WasmPluginConfig {
+    /// The name of the plugin, used in logs and errors.
+    pub name: String,
+
+    /// The path to the `.wasm` module, relative to the config file.
+    pub path: FilePath,
+
+    #[serde(default = "default_wasm_plugin_enabled")]
+    pub enabled: bool,
+
+    /// When enabled, a module failing to load or to initialize is skipped with a warning,
+    /// instead of preventing the router from starting.
+    #[serde(default)]
+    pub warn_on_error: bool,
+
+    /// The configuration passed to the `on_plugin_init` export of the module.
+    #[serde(default = "default_wasm_plugin_config")]
+    pub config: serde_json::Value,
+
+    /// The number of instances of the module.
+    /// An instance runs one hook at a time, so this is the number of hooks of the plugin
+    /// that can run concurrently.
+    ///
+    /// Defaults to `4`.
+    #[serde(default = "default_wasm_plugin_instances")]
+    pub instances: usize,
+
+    #[serde(
+        default = "default_wasm_plugin_timeout",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    /// The maximum duration of a hook call. A hook exceeding it fails the request.
+    ///
+    /// Defaults to `100ms`.
+    pub timeout: Duration,
+
+    /// The hosts the module is allowed to send HTTP requests to, through the Extism host functions.
+    /// No host is allowed by default.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_hosts: Vec<String>,
+}
+
+fn default_wasm_plugin_enabled() -> bool {
+    true
+}
+
+fn default_wasm_plugin_config() -> serde_json::Value {
+    serde_json::Value::Object(serde_json::Map::new())
+}
+
+fn default_wasm_plugin_instances() -> usize {
+    4
+}
+
+fn default_wasm_plugin_timeout() -> Duration {
+    Duration::from_millis(100)
+}
@@ -1,9 +1,12 @@
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::primitives::absolute_path::AbsolutePath;
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Default)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct WebSocketConfig {
     /// Enables/disables WebSocket connections.
@@ -26,6 +29,149 @@ pub struct WebSocketConfig {
     /// Configuration for handling headers for WebSocket connections.
     #[serde(default)]
     pub headers: WebSocketHeadersConfig,
+
+    /// The interval at which the router pings the connected clients, keeping the connections
+    /// alive and detecting the stale ones.
+    ///
+    /// If set to 0, the router does not ping the clients. Defaults to 5 seconds.
+    #[serde(
+        default = "default_keep_alive_interval",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub keep_alive_interval: Duration,
+
+    /// How long the router waits for a client to answer its pings before closing the connection.
+    ///
+    /// Defaults to 10 seconds.
+    #[serde(
+        default = "default_keep_alive_timeout",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub keep_alive_timeout: Duration,
+
+    /// Closes the connections that have had no active subscription for the given duration.
+    ///
+    /// By default, idle connections are kept open.
+    #[serde(
+        default,
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<String>")]
+    pub idle_timeout: Option<Duration>,
+
+    /// The maximum number of active subscriptions on a single connection.
+    ///
+    /// Subscribing over the limit is answered with an error using the `TOO_MANY_SUBSCRIPTIONS`
+    /// code, and the connection stays open.
+    ///
+    /// Must be greater than 0. By default, the number of subscriptions is not limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_subscriptions_per_connection: Option<NonZeroUsize>,
+
+    /// The GraphQL over WebSocket subprotocols accepted by the router.
+    ///
+    /// When a client offers several of the enabled subprotocols, the first one it offers is used.
+    #[serde(default)]
+    pub protocols: WebSocketProtocolsConfig,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            headers: WebSocketHeadersConfig::default(),
+            keep_alive_interval: default_keep_alive_interval(),
+            keep_alive_timeout: default_keep_alive_timeout(),
+            idle_timeout: None,
+            max_subscriptions_per_connection: None,
+            protocols: WebSocketProtocolsConfig::default(),
+        }
+    }
+}
+
+fn default_keep_alive_interval() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_keep_alive_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+#[derive(Default, Deserialize, Serialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct WebSocketProtocolsConfig {
+    /// The `graphql-transport-ws` subprotocol of the `graphql-ws` library.
+    /// See: https://github.com/enisdenjo/graphql-ws/blob/master/PROTOCOL.md
+    #[serde(default)]
+    pub graphql_transport_ws: GraphQLTransportWsProtocolConfig,
+    /// The legacy `graphql-ws` subprotocol of the deprecated `subscriptions-transport-ws` library,
+    /// still used by older clients.
+    /// See: https://github.com/apollographql/subscriptions-transport-ws/blob/master/PROTOCOL.md
+    ///
+    /// The connection init payload is handled the same way as with `graphql-transport-ws`,
+    /// following the `headers` configuration.
+    #[serde(default)]
+    pub graphql_ws: GraphQLWsProtocolConfig,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct GraphQLTransportWsProtocolConfig {
+    /// Enables/disables the subprotocol. Enabled by default.
+    #[serde(default = "default_graphql_transport_ws_enabled")]
+    pub enabled: bool,
+}
+
+impl Default for GraphQLTransportWsProtocolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_graphql_transport_ws_enabled(),
+        }
+    }
+}
+
+fn default_graphql_transport_ws_enabled() -> bool {
+    true
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct GraphQLWsProtocolConfig {
+    /// Enables/disables the subprotocol. Disabled by default.
+    #[serde(default)]
+    pub enabled: bool,
+    /// The interval at which the router sends `ka` (keep alive) messages to the clients,
+    /// starting with the acknowledgement of the connection. Some clients consider the connection
+    /// lost when they stop receiving them.
+    ///
+    /// If set to 0, no `ka` messages are sent. Defaults to 30 seconds.
+    #[serde(
+        default = "default_graphql_ws_keep_alive_interval",
+        deserialize_with = "humantime_serde::deserialize",
+        serialize_with = "humantime_serde::serialize"
+    )]
+    #[schemars(with = "String")]
+    pub keep_alive_interval: Duration,
+}
+
+impl Default for GraphQLWsProtocolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keep_alive_interval: default_graphql_ws_keep_alive_interval(),
+        }
+    }
+}
+
+fn default_graphql_ws_keep_alive_interval() -> Duration {
+    Duration::from_secs(30)
 }
 
 #[derive(Default, Deserialize, Serialize, JsonSchema, Debug)]
@@ -116,3 +262,40 @@ impl WebSocketHeadersConfig {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_match_serde_defaults() {
+        let config = serde_json::from_str::<WebSocketConfig>("{}").unwrap();
+        let default = WebSocketConfig::default();
+        assert_eq!(config.keep_alive_interval, default.keep_alive_interval);
+        assert_eq!(config.keep_alive_timeout, default.keep_alive_timeout);
+        assert_eq!(config.idle_timeout, None);
+        assert_eq!(config.max_subscriptions_per_connection, None);
+    }
+
+    #[test]
+    fn parses_connection_limits() {
+        let config = serde_json::from_str::<WebSocketConfig>(
+            r#"{
+                "keep_alive_interval": "0s",
+                "idle_timeout": "1m",
+                "max_subscriptions_per_connection": 5
+            }"#,
+        )
+        .unwrap();
+        assert!(config.keep_alive_interval.is_zero());
+        assert_eq!(config.idle_timeout, Some(Duration::from_secs(60)));
+        assert_eq!(
+            config.max_subscriptions_per_connection,
+            NonZeroUsize::new(5)
+        );
+
+        let result =
+            serde_json::from_str::<WebSocketConfig>(r#"{"max_subscriptions_per_connection": 0}"#);
+        assert!(result.is_err());
+    }
+}